@@ -0,0 +1,36 @@
+//! Indexing Benchmarks
+//! A full `scan_dir` pass over a generated tree, written to an in-memory
+//! database so disk sync doesn't dominate. Baselines are in
+//! benches/README.md.
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use etools_lib::bench::{datagen, open_files_db, FileIndexer, IndexerConfig};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+
+fn scan_tree(c: &mut Criterion) {
+    let root = tempfile::tempdir().unwrap();
+    let files = datagen::file_tree(root.path(), 20_000).unwrap();
+    let config = IndexerConfig::default();
+
+    let mut group = c.benchmark_group("scan_dir_20k");
+    group.sample_size(10);
+    group.bench_function("fresh_index", |b| {
+        b.iter_batched(
+            || {
+                let conn = open_files_db(Path::new(":memory:")).unwrap();
+                (conn, Mutex::new(HashSet::new()))
+            },
+            |(conn, indexed)| {
+                let count = FileIndexer::scan_dir(root.path(), &config, &indexed, &conn, &|_| {}, 0, 0).unwrap();
+                assert_eq!(count, files);
+            },
+            BatchSize::PerIteration,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, scan_tree);
+criterion_main!(benches);
@@ -0,0 +1,50 @@
+//! Plugin Listing Benchmarks
+//! `list_plugins` over a mock app with 40 installed plugins: a cold listing
+//! that rescans every plugin folder against the cached registry snapshot
+//! the plugins panel gets. Baselines are in benches/README.md.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use etools_lib::bench::list_plugins;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+
+const PLUGINS: usize = 40;
+
+fn seed_plugins(data_dir: &Path) {
+    for i in 0..PLUGINS {
+        let plugin_dir = data_dir.join("plugins").join(format!("plugin-{}", i));
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let manifest = json!({
+            "name": format!("Plugin {}", i),
+            "version": "1.0.0",
+            "description": "Seeded for the plugin listing benchmark",
+            "author": null,
+            "permissions": ["network"],
+            "entry": "index.js",
+            "triggers": [format!("p{}:", i)],
+            "api_version": "1.1.0",
+        });
+        fs::write(plugin_dir.join("plugin.json"), manifest.to_string()).unwrap();
+        fs::write(plugin_dir.join("index.js"), "export default {};").unwrap();
+    }
+}
+
+fn plugin_listing(c: &mut Criterion) {
+    // An absolute identifier puts every app dir inside the temp dir
+    let dir = tempfile::tempdir().unwrap();
+    seed_plugins(dir.path());
+    let mut context: tauri::Context<MockRuntime> = mock_context(noop_assets());
+    context.config_mut().identifier = dir.path().to_string_lossy().into_owned();
+    let app = mock_builder().build(context).unwrap();
+    assert_eq!(list_plugins(app.handle(), true).unwrap().len(), PLUGINS);
+
+    let mut group = c.benchmark_group("plugin_list_40");
+    group.bench_function("cold", |b| b.iter(|| list_plugins(app.handle(), true).unwrap()));
+    group.bench_function("cached", |b| b.iter(|| list_plugins(app.handle(), false).unwrap()));
+    group.finish();
+}
+
+criterion_group!(benches, plugin_listing);
+criterion_main!(benches);
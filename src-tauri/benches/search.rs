@@ -0,0 +1,55 @@
+//! Search Benchmarks
+//! App scoring, file index queries and browser ranking at realistic sizes.
+//! Baselines are in benches/README.md; compare against a saved run with
+//! `cargo bench --bench search -- --baseline main`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use etools_lib::bench::{datagen, open_browser_db, open_files_db, search_apps, search_browser_data, search_files, ParsedQuery};
+
+/// Queries from a full word down to a short prefix, as typed
+const APP_QUERIES: &[&str] = &["terminal", "vid", "ps", "zzz"];
+
+fn app_scoring(c: &mut Criterion) {
+    let apps = datagen::apps(5_000);
+    let mut group = c.benchmark_group("app_scoring_5k");
+    for query in APP_QUERIES {
+        let parsed = ParsedQuery::new(query);
+        group.bench_with_input(BenchmarkId::from_parameter(query), &parsed, |b, parsed| {
+            b.iter(|| search_apps(black_box(&apps), parsed, 50))
+        });
+    }
+    group.finish();
+}
+
+fn file_search(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let conn = open_files_db(&dir.path().join("files.db")).unwrap();
+    datagen::seed_files_db(&conn, 100_000).unwrap();
+
+    // Only the LIKE scan exists; add a full-text variant here once the
+    // index has one, so both are compared on the same rows
+    let mut group = c.benchmark_group("file_search_100k");
+    for query in ["report", "rep", "tag:budget notes"] {
+        group.bench_with_input(BenchmarkId::new("like", query), query, |b, query| {
+            b.iter(|| search_files(&conn, black_box(query), 50).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn browser_ranking(c: &mut Criterion) {
+    let dir = tempfile::tempdir().unwrap();
+    let conn = open_browser_db(&dir.path().join("browser.db")).unwrap();
+    datagen::seed_browser_db(&conn, 50_000).unwrap();
+
+    let mut group = c.benchmark_group("browser_ranking_50k");
+    for query in ["example", "music", "zzz"] {
+        group.bench_with_input(BenchmarkId::from_parameter(query), query, |b, query| {
+            b.iter(|| search_browser_data(&conn, black_box(query), 20, true).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, app_scoring, file_search, browser_ranking);
+criterion_main!(benches);
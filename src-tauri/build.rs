@@ -1,3 +1,47 @@
+use std::fmt::Write as _;
+use std::path::Path;
+use std::{env, fs};
+
+#[path = "build/event_types.rs"]
+mod event_types;
+
 fn main() {
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    generate_emoji_table(Path::new(&out_dir));
+    event_types::generate(Path::new(&out_dir));
     tauri_build::build()
 }
+
+/// Turn assets/emoji.tsv into the array literal the emoji search provider
+/// includes, so the table is static data rather than parsed at startup
+fn generate_emoji_table(out_dir: &Path) {
+    let source = "assets/emoji.tsv";
+    println!("cargo:rerun-if-changed={}", source);
+
+    let tsv = fs::read_to_string(source).unwrap_or_else(|e| panic!("Failed to read {}: {}", source, e));
+    let mut table = String::from("&[\n");
+    for (number, line) in tsv.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let columns: Vec<&str> = line.split('\t').collect();
+        let [glyph, name, group, keywords, skin_tones] = columns[..] else {
+            panic!("{}:{}: expected 5 tab-separated columns", source, number + 1);
+        };
+        let keywords: Vec<&str> = keywords.split('|').map(str::trim).filter(|k| !k.is_empty()).collect();
+        writeln!(
+            table,
+            "    EmojiRecord {{ glyph: {:?}, name: {:?}, group: {:?}, keywords: &{:?}, skin_tones: {} }},",
+            glyph,
+            name,
+            group,
+            keywords,
+            skin_tones == "1"
+        )
+        .unwrap();
+    }
+    table.push(']');
+
+    fs::write(out_dir.join("emoji_table.rs"), table).expect("Failed to write emoji table");
+}
@@ -0,0 +1,684 @@
+//! Writes the frontend's definitions for backend events, events.d.ts in OUT_DIR,
+//! from the catalog in src/types/events.rs. Payload types are looked up by name
+//! anywhere under src and translated through their serde attributes. A test in
+//! types/events.rs keeps the committed copy, src/types/generated/events.d.ts, in step.
+//!
+//! This reads source rather than compiled types, so it only knows the shapes
+//! payloads use: structs, enums, the common std containers, and the serde attributes
+//! that rename, skip, flatten or tag. A type it can't resolve becomes `unknown` in the
+//! output and a build warning.
+
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Expr, Fields, GenericArgument, Item, Lit, LitStr, Meta, PathArguments, Token, Type};
+
+const SOURCE_DIR: &str = "src";
+const CATALOG: &str = "src/types/events.rs";
+const OUTPUT: &str = "events.d.ts";
+
+pub fn generate(out_dir: &Path) {
+    println!("cargo:rerun-if-changed={}", CATALOG);
+
+    let mut definitions = HashMap::new();
+    collect_definitions(Path::new(SOURCE_DIR), &mut definitions);
+    let catalog = parse_catalog(&parse_source(Path::new(CATALOG)).unwrap_or_else(|e| panic!("{}: {}", CATALOG, e)));
+    if catalog.is_empty() {
+        panic!("{}: no events found in app_events!", CATALOG);
+    }
+
+    let mut writer = DtsWriter::new(&definitions);
+    let output = writer.render(&catalog);
+    for warning in &writer.warnings {
+        println!("cargo:warning={}", warning);
+    }
+    // Only the files payloads came from, so unrelated edits don't rerun the
+    // build script. A payload that moves changes the file it's used from too.
+    for source in writer.sources.iter().filter(|source| **source != Path::new(CATALOG)) {
+        println!("cargo:rerun-if-changed={}", source.display());
+    }
+
+    let output_path = out_dir.join(OUTPUT);
+    fs::write(&output_path, output).unwrap_or_else(|e| panic!("Failed to write {}: {}", output_path.display(), e));
+}
+
+fn parse_source(path: &Path) -> Result<syn::File, String> {
+    let source = fs::read_to_string(path).map_err(|e| format!("Failed to read: {}", e))?;
+    syn::parse_file(&source).map_err(|e| format!("Failed to parse: {}", e))
+}
+
+/// `#[serde(...)]` entries, e.g. ("rename", Some("type")) or ("flatten", None)
+#[derive(Debug, Default)]
+struct SerdeAttrs(Vec<(String, Option<String>)>);
+
+impl SerdeAttrs {
+    fn parse(attributes: &[Attribute]) -> Self {
+        let mut entries = Vec::new();
+        for attribute in attributes.iter().filter(|attribute| attribute.path().is_ident("serde")) {
+            // An entry this doesn't understand ends the attribute, not the build
+            let _ = attribute.parse_nested_meta(|meta| {
+                let key = meta.path.get_ident().map(ToString::to_string).unwrap_or_default();
+                let value = if meta.input.peek(Token![=]) {
+                    Some(meta.value()?.parse::<LitStr>()?.value())
+                } else if meta.input.peek(syn::token::Paren) {
+                    // rename(serialize = "..."), the only direction that matters here
+                    let mut serialize = None;
+                    meta.parse_nested_meta(|direction| {
+                        let value = direction.value()?.parse::<LitStr>()?.value();
+                        if direction.path.is_ident("serialize") {
+                            serialize = Some(value);
+                        }
+                        Ok(())
+                    })?;
+                    serialize
+                } else {
+                    None
+                };
+                entries.push((key, value));
+                Ok(())
+            });
+        }
+        Self(entries)
+    }
+
+    fn has(&self, key: &str) -> bool {
+        self.0.iter().any(|(k, _)| k == key)
+    }
+
+    fn value(&self, key: &str) -> Option<&str> {
+        self.0.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.as_deref())
+    }
+}
+
+/// `///` lines, without the space after the slashes
+fn docs(attributes: &[Attribute]) -> Vec<String> {
+    attributes
+        .iter()
+        .filter_map(|attribute| match &attribute.meta {
+            Meta::NameValue(doc) if doc.path.is_ident("doc") => match &doc.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(line) => Some(line.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .flat_map(|doc| {
+            doc.lines()
+                .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end().to_string())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn derives_serialize(attributes: &[Attribute]) -> bool {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("derive"))
+        .any(|attribute| {
+            attribute
+                .parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .is_ok_and(|paths| {
+                    paths
+                        .iter()
+                        .any(|path| path.segments.last().is_some_and(|s| s.ident == "Serialize"))
+                })
+        })
+}
+
+#[derive(Debug, Clone)]
+enum Ty {
+    /// Last path segment and its type arguments, e.g. Option<String>
+    Path(String, Vec<Ty>),
+    Tuple(Vec<Ty>),
+    Array(Box<Ty>),
+    Unknown,
+}
+
+impl From<&Type> for Ty {
+    fn from(ty: &Type) -> Self {
+        match ty {
+            Type::Reference(reference) => Self::from(&*reference.elem),
+            Type::Paren(inner) => Self::from(&*inner.elem),
+            Type::Group(inner) => Self::from(&*inner.elem),
+            Type::Tuple(tuple) => Ty::Tuple(tuple.elems.iter().map(Self::from).collect()),
+            Type::Array(array) => Ty::Array(Box::new(Self::from(&*array.elem))),
+            Type::Slice(slice) => Ty::Array(Box::new(Self::from(&*slice.elem))),
+            Type::Path(path) if path.qself.is_none() => {
+                let Some(segment) = path.path.segments.last() else {
+                    return Ty::Unknown;
+                };
+                let args = match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => args
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            GenericArgument::Type(ty) => Some(Self::from(ty)),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => Vec::new(),
+                };
+                Ty::Path(segment.ident.unraw().to_string(), args)
+            }
+            _ => Ty::Unknown,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Field {
+    /// Empty for tuple fields
+    name: String,
+    docs: Vec<String>,
+    serde: SerdeAttrs,
+    ty: Ty,
+}
+
+#[derive(Debug)]
+enum Shape {
+    Unit,
+    Tuple(Vec<Field>),
+    Struct(Vec<Field>),
+    Enum(Vec<Variant>),
+}
+
+impl From<&Fields> for Shape {
+    fn from(fields: &Fields) -> Self {
+        let convert = |fields: &Punctuated<syn::Field, Token![,]>| {
+            fields
+                .iter()
+                .map(|field| Field {
+                    name: field
+                        .ident
+                        .as_ref()
+                        .map(|ident| ident.unraw().to_string())
+                        .unwrap_or_default(),
+                    docs: docs(&field.attrs),
+                    serde: SerdeAttrs::parse(&field.attrs),
+                    ty: Ty::from(&field.ty),
+                })
+                .collect()
+        };
+        match fields {
+            Fields::Named(fields) => Shape::Struct(convert(&fields.named)),
+            Fields::Unnamed(fields) => Shape::Tuple(convert(&fields.unnamed)),
+            Fields::Unit => Shape::Unit,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Variant {
+    name: String,
+    docs: Vec<String>,
+    serde: SerdeAttrs,
+    shape: Shape,
+}
+
+#[derive(Debug)]
+struct Definition {
+    /// The file it's defined in, which the build reruns on if the type is used
+    file: PathBuf,
+    docs: Vec<String>,
+    serde: SerdeAttrs,
+    serializable: bool,
+    generic: bool,
+    shape: Shape,
+}
+
+fn collect_definitions(dir: &Path, definitions: &mut HashMap<String, Vec<Definition>>) {
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", dir.display(), e))
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_definitions(&path, definitions);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            // A file rustc can't parse fails the build on its own
+            if let Ok(file) = parse_source(&path) {
+                add_definitions(&path, &file.items, definitions);
+            }
+        }
+    }
+}
+
+fn add_definitions(file: &Path, items: &[Item], definitions: &mut HashMap<String, Vec<Definition>>) {
+    for item in items {
+        let (name, attributes, generics, shape) = match item {
+            Item::Struct(item) => (&item.ident, &item.attrs, &item.generics, Shape::from(&item.fields)),
+            Item::Enum(item) => {
+                let variants = item
+                    .variants
+                    .iter()
+                    .map(|variant| Variant {
+                        name: variant.ident.unraw().to_string(),
+                        docs: docs(&variant.attrs),
+                        serde: SerdeAttrs::parse(&variant.attrs),
+                        shape: Shape::from(&variant.fields),
+                    })
+                    .collect();
+                (&item.ident, &item.attrs, &item.generics, Shape::Enum(variants))
+            }
+            Item::Mod(module) => {
+                if let Some((_, items)) = &module.content {
+                    add_definitions(file, items, definitions);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+        definitions
+            .entry(name.unraw().to_string())
+            .or_default()
+            .push(Definition {
+                file: file.to_path_buf(),
+                docs: docs(attributes),
+                serde: SerdeAttrs::parse(attributes),
+                serializable: derives_serialize(attributes),
+                generic: !generics.params.is_empty(),
+                shape,
+            });
+    }
+}
+
+/// An `app_events!` entry: `CONSTANT = "name" => Payload;`
+struct CatalogEntry {
+    docs: Vec<String>,
+    name: String,
+    payload: Ty,
+}
+
+impl Parse for CatalogEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attributes = input.call(Attribute::parse_outer)?;
+        input.parse::<syn::Ident>()?;
+        input.parse::<Token![=]>()?;
+        let name = input.parse::<LitStr>()?.value();
+        input.parse::<Token![=>]>()?;
+        let payload = input.parse::<Type>()?;
+        input.parse::<Token![;]>()?;
+        Ok(Self {
+            docs: docs(&attributes),
+            name,
+            payload: Ty::from(&payload),
+        })
+    }
+}
+
+fn parse_catalog(file: &syn::File) -> Vec<CatalogEntry> {
+    // The invocation, not the macro_rules! definition
+    let Some(invocation) = file.items.iter().find_map(|item| match item {
+        Item::Macro(item) if item.mac.path.is_ident("app_events") => Some(&item.mac),
+        _ => None,
+    }) else {
+        return Vec::new();
+    };
+    invocation
+        .parse_body_with(|input: ParseStream| {
+            let mut entries = Vec::new();
+            while !input.is_empty() {
+                entries.push(input.parse::<CatalogEntry>()?);
+            }
+            Ok(entries)
+        })
+        .unwrap_or_else(|e| panic!("{}: malformed app_events! entry: {}", CATALOG, e))
+}
+
+/// Apply a serde `rename_all` rule to a snake_case field or PascalCase variant
+fn rename_all(name: &str, rule: Option<&str>, variant: bool) -> String {
+    let words: Vec<String> = if variant {
+        let mut words: Vec<String> = Vec::new();
+        for c in name.chars() {
+            match words.last_mut() {
+                Some(word) if !c.is_uppercase() => word.push(c),
+                _ => words.push(c.to_string()),
+            }
+        }
+        words.iter().map(|word| word.to_lowercase()).collect()
+    } else {
+        name.split('_').map(str::to_string).collect()
+    };
+    let pascal = || -> String {
+        words
+            .iter()
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map_or(String::new(), |first| first.to_uppercase().chain(chars).collect())
+            })
+            .collect()
+    };
+
+    match rule {
+        Some("lowercase") => name.to_lowercase(),
+        Some("UPPERCASE") => name.to_uppercase(),
+        Some("PascalCase") => pascal(),
+        Some("camelCase") => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            chars
+                .next()
+                .map_or(String::new(), |first| first.to_lowercase().chain(chars).collect())
+        }
+        Some("snake_case") => words.join("_"),
+        Some("SCREAMING_SNAKE_CASE") => words.join("_").to_uppercase(),
+        Some("kebab-case") => words.join("-"),
+        Some("SCREAMING-KEBAB-CASE") => words.join("-").to_uppercase(),
+        _ => name.to_string(),
+    }
+}
+
+/// A property name, quoted unless it's a plain identifier
+fn property(name: &str) -> String {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if plain {
+        name.to_string()
+    } else {
+        format!("'{}'", name)
+    }
+}
+
+fn write_docs(out: &mut String, docs: &[String], indent: &str) {
+    let docs: Vec<String> = docs.iter().map(|doc| doc.replace("*/", "*\\/")).collect();
+    match docs.as_slice() {
+        [] => {}
+        [line] => writeln!(out, "{}/** {} */", indent, line).unwrap(),
+        lines => {
+            writeln!(out, "{}/**", indent).unwrap();
+            for line in lines {
+                writeln!(out, "{} *{}{}", indent, if line.is_empty() { "" } else { " " }, line).unwrap();
+            }
+            writeln!(out, "{} */", indent).unwrap();
+        }
+    }
+}
+
+/// A serialized field: its key, whether it may be absent, its type and docs
+struct Property<'a> {
+    key: String,
+    optional: bool,
+    ty: String,
+    docs: &'a [String],
+}
+
+struct DtsWriter<'a> {
+    definitions: &'a HashMap<String, Vec<Definition>>,
+    queue: VecDeque<String>,
+    queued: HashSet<String>,
+    warnings: Vec<String>,
+    /// Files the rendered definitions came from
+    sources: BTreeSet<&'a Path>,
+}
+
+impl<'a> DtsWriter<'a> {
+    fn new(definitions: &'a HashMap<String, Vec<Definition>>) -> Self {
+        Self {
+            definitions,
+            queue: VecDeque::new(),
+            queued: HashSet::new(),
+            warnings: Vec::new(),
+            sources: BTreeSet::new(),
+        }
+    }
+
+    fn render(&mut self, catalog: &[CatalogEntry]) -> String {
+        let mut out = String::from(
+            "// Generated by src-tauri/build.rs from the event catalog in src-tauri/src/types/events.rs.\n\
+             // Don't edit; change the catalog or the payload types and rebuild the backend.\n\n",
+        );
+        out.push_str("/** Payload of each event the backend emits, by event name */\nexport interface AppEvents {\n");
+        for entry in catalog {
+            let payload = self.ts_type(&entry.payload);
+            write_docs(&mut out, &entry.docs, "  ");
+            writeln!(out, "  '{}': {};", entry.name, payload).unwrap();
+        }
+        out.push_str("}\n\nexport type AppEventName = keyof AppEvents;\n");
+
+        while let Some(name) = self.queue.pop_front() {
+            let definition = self.definition(&name).expect("only known types are queued");
+            self.sources.insert(&definition.file);
+            out.push('\n');
+            write_docs(&mut out, &definition.docs, "");
+            let rendered = self.render_definition(&name, definition);
+            out.push_str(&rendered);
+        }
+        out
+    }
+
+    /// The serializable definition of `name`, if there's exactly one
+    fn definition(&self, name: &str) -> Option<&'a Definition> {
+        let candidates: Vec<&Definition> = self
+            .definitions
+            .get(name)?
+            .iter()
+            .filter(|definition| definition.serializable && !definition.generic)
+            .collect();
+        match candidates.as_slice() {
+            [definition] => Some(definition),
+            _ => None,
+        }
+    }
+
+    fn named(&mut self, name: &str) -> String {
+        if self.definition(name).is_none() {
+            let reason = match self.definitions.get(name).map(Vec::len) {
+                None => "no definition found",
+                Some(1) => "not a non-generic type deriving Serialize",
+                Some(_) => "defined more than once",
+            };
+            self.warnings
+                .push(format!("events.d.ts: {} is typed as unknown ({})", name, reason));
+            return "unknown".to_string();
+        }
+        if self.queued.insert(name.to_string()) {
+            self.queue.push_back(name.to_string());
+        }
+        name.to_string()
+    }
+
+    fn ts_type(&mut self, ty: &Ty) -> String {
+        match ty {
+            Ty::Unknown => "unknown".to_string(),
+            Ty::Tuple(items) if items.is_empty() => "null".to_string(),
+            Ty::Tuple(items) => {
+                let items: Vec<String> = items.iter().map(|item| self.ts_type(item)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Ty::Array(item) => {
+                let item = self.ts_type(item);
+                array_of(&item)
+            }
+            Ty::Path(name, args) => {
+                let mut arg = |index: usize| args.get(index).map_or("unknown".to_string(), |arg| self.ts_type(arg));
+                match name.as_str() {
+                    "String" | "str" | "char" | "PathBuf" | "Path" | "OsString" | "Uuid" => "string".to_string(),
+                    "bool" => "boolean".to_string(),
+                    "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+                    | "isize" | "f32" | "f64" => "number".to_string(),
+                    "Option" => format!("{} | null", arg(0)),
+                    "Vec" | "VecDeque" | "HashSet" | "BTreeSet" => array_of(&arg(0)),
+                    "HashMap" | "BTreeMap" => format!("Record<string, {}>", arg(1)),
+                    "Box" | "Arc" | "Rc" | "Cow" => arg(args.len().saturating_sub(1)),
+                    "Value" => "unknown".to_string(),
+                    _ => self.named(name),
+                }
+            }
+        }
+    }
+
+    /// The serialized properties of named fields
+    fn properties<'f>(&mut self, fields: &'f [Field], rule: Option<&str>) -> (Vec<Property<'f>>, Vec<String>) {
+        let mut properties = Vec::new();
+        let mut flattened = Vec::new();
+        for field in fields {
+            if field.serde.has("skip") || field.serde.has("skip_serializing") {
+                continue;
+            }
+            if field.serde.has("flatten") {
+                flattened.push(self.ts_type(&field.ty));
+                continue;
+            }
+            let optional = field.serde.has("skip_serializing_if");
+            let ty = match &field.ty {
+                Ty::Path(name, args) if optional && name == "Option" && args.len() == 1 => self.ts_type(&args[0]),
+                ty => self.ts_type(ty),
+            };
+            properties.push(Property {
+                key: field
+                    .serde
+                    .value("rename")
+                    .map_or_else(|| rename_all(&field.name, rule, false), str::to_string),
+                optional,
+                ty,
+                docs: &field.docs,
+            });
+        }
+        (properties, flattened)
+    }
+
+    /// `{ a: T; b?: U }` on one line, plus any flattened types
+    fn inline_object(&mut self, fields: &[Field], rule: Option<&str>, tag: Option<String>) -> String {
+        let (properties, flattened) = self.properties(fields, rule);
+        let members: Vec<String> = tag
+            .into_iter()
+            .chain(
+                properties
+                    .iter()
+                    .map(|p| format!("{}{}: {}", property(&p.key), if p.optional { "?" } else { "" }, p.ty)),
+            )
+            .collect();
+        let object = if members.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{ {} }}", members.join("; "))
+        };
+        std::iter::once(object).chain(flattened).collect::<Vec<_>>().join(" & ")
+    }
+
+    fn render_definition(&mut self, name: &str, definition: &Definition) -> String {
+        let rule = definition.serde.value("rename_all");
+        match &definition.shape {
+            Shape::Unit => format!("export type {} = null;\n", name),
+            Shape::Tuple(fields) if fields.len() == 1 => {
+                format!("export type {} = {};\n", name, self.ts_type(&fields[0].ty))
+            }
+            Shape::Tuple(fields) => {
+                let items: Vec<String> = fields.iter().map(|field| self.ts_type(&field.ty)).collect();
+                format!("export type {} = [{}];\n", name, items.join(", "))
+            }
+            Shape::Struct(fields) if definition.serde.has("transparent") => {
+                let field = fields.iter().find(|field| !field.serde.has("skip"));
+                let ty = field.map_or("null".to_string(), |field| self.ts_type(&field.ty));
+                format!("export type {} = {};\n", name, ty)
+            }
+            Shape::Struct(fields) => {
+                let (properties, flattened) = self.properties(fields, rule);
+                let mut out = String::new();
+                if flattened.is_empty() {
+                    writeln!(out, "export interface {} {{", name).unwrap();
+                } else {
+                    writeln!(out, "export type {} = {} & {{", name, flattened.join(" & ")).unwrap();
+                }
+                for p in &properties {
+                    write_docs(&mut out, p.docs, "  ");
+                    writeln!(
+                        out,
+                        "  {}{}: {};",
+                        property(&p.key),
+                        if p.optional { "?" } else { "" },
+                        p.ty
+                    )
+                    .unwrap();
+                }
+                out.push_str(if flattened.is_empty() { "}\n" } else { "};\n" });
+                out
+            }
+            Shape::Enum(variants) => {
+                let mut out = format!("export type {} =\n", name);
+                let variants: Vec<&Variant> = variants
+                    .iter()
+                    .filter(|variant| !variant.serde.has("skip") && !variant.serde.has("skip_serializing"))
+                    .collect();
+                for (index, variant) in variants.iter().enumerate() {
+                    let rendered = self.render_variant(variant, &definition.serde, rule);
+                    write_docs(&mut out, &variant.docs, "  ");
+                    let end = if index + 1 == variants.len() { ";" } else { "" };
+                    writeln!(out, "  | {}{}", rendered, end).unwrap();
+                }
+                if variants.is_empty() {
+                    out = format!("export type {} = never;\n", name);
+                }
+                out
+            }
+        }
+    }
+
+    /// One member of an enum's union, following the enum's tagging
+    fn render_variant(&mut self, variant: &Variant, container: &SerdeAttrs, rule: Option<&str>) -> String {
+        let tag_value = variant
+            .serde
+            .value("rename")
+            .map_or_else(|| rename_all(&variant.name, rule, true), str::to_string);
+        let variant_rule = variant.serde.value("rename_all");
+        let content = |writer: &mut Self| match &variant.shape {
+            Shape::Unit => "null".to_string(),
+            Shape::Tuple(fields) if fields.len() == 1 => writer.ts_type(&fields[0].ty),
+            Shape::Tuple(fields) => {
+                let items: Vec<String> = fields.iter().map(|field| writer.ts_type(&field.ty)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Shape::Struct(fields) => writer.inline_object(fields, variant_rule, None),
+            Shape::Enum(_) => "unknown".to_string(),
+        };
+
+        if container.has("untagged") {
+            return content(self);
+        }
+        match (container.value("tag"), container.value("content")) {
+            (Some(tag), Some(content_key)) => match variant.shape {
+                Shape::Unit => format!("{{ {}: '{}' }}", property(tag), tag_value),
+                _ => format!(
+                    "{{ {}: '{}'; {}: {} }}",
+                    property(tag),
+                    tag_value,
+                    property(content_key),
+                    content(self)
+                ),
+            },
+            (Some(tag), None) => {
+                let tag_member = format!("{}: '{}'", property(tag), tag_value);
+                match &variant.shape {
+                    Shape::Struct(fields) => self.inline_object(fields, variant_rule, Some(tag_member)),
+                    Shape::Unit => format!("{{ {} }}", tag_member),
+                    _ => format!("{{ {} }} & {}", tag_member, content(self)),
+                }
+            }
+            _ => match variant.shape {
+                Shape::Unit => format!("'{}'", tag_value),
+                _ => format!("{{ {}: {} }}", property(&tag_value), content(self)),
+            },
+        }
+    }
+}
+
+fn array_of(item: &str) -> String {
+    if item.contains(" | ") || item.contains(" & ") {
+        format!("({})[]", item)
+    } else {
+        format!("{}[]", item)
+    }
+}
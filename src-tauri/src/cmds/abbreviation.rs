@@ -1,6 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Abbreviation {
@@ -12,6 +16,12 @@ pub struct Abbreviation {
     pub enabled: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// Times the abbreviation was expanded or opened
+    #[serde(default)]
+    pub use_count: u32,
+    /// RFC 3339 time of the last use
+    #[serde(default)]
+    pub last_used_at: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +31,130 @@ pub struct AbbreviationCategory {
     pub description: Option<String>,
     pub icon: Option<String>,
     pub color: Option<String>,
+    /// Disabled categories hide all of their abbreviations, regardless of
+    /// the per-abbreviation `enabled` flag
+    #[serde(default = "default_category_enabled")]
+    pub enabled: bool,
+}
+
+fn default_category_enabled() -> bool {
+    true
+}
+
+/// How an imported configuration is combined with the existing one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Keep existing categories and abbreviations and add the new ones
+    #[default]
+    Merge,
+    /// Replace the whole configuration with the imported one
+    Replace,
+}
+
+/// Options of `import_abbreviation_config`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ImportOptions {
+    pub mode: ImportMode,
+    /// Only report what the import would do
+    pub dry_run: bool,
+    /// How to settle conflicts, by imported abbr. Conflicts without one are
+    /// reported and left alone.
+    pub resolutions: HashMap<String, ConflictResolution>,
+}
+
+/// How to settle an imported abbreviation whose abbr is already used with
+/// a different expansion
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    KeepExisting,
+    /// Take the imported expansion, description and category, keeping the
+    /// existing id and usage
+    UseImported,
+}
+
+/// An imported abbreviation whose abbr is already used
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportConflict {
+    pub existing: Abbreviation,
+    pub imported: Abbreviation,
+    /// None until the user picks one
+    pub resolution: Option<ConflictResolution>,
+}
+
+/// An entry of the imported file that couldn't be used
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvalidImport {
+    /// "abbreviations" or "categories"
+    pub section: String,
+    /// Position in that list
+    pub index: usize,
+    pub reason: String,
+}
+
+/// What an import did, or would do on a dry run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ImportReport {
+    pub mode: ImportMode,
+    pub dry_run: bool,
+    pub added: Vec<Abbreviation>,
+    pub categories_added: Vec<AbbreviationCategory>,
+    /// Already present with the same expansion
+    pub skipped: Vec<Abbreviation>,
+    pub conflicts: Vec<ImportConflict>,
+    pub invalid: Vec<InvalidImport>,
+}
+
+impl ImportReport {
+    fn invalid(&mut self, section: &str, index: usize, reason: impl fmt::Display) {
+        self.invalid.push(InvalidImport {
+            section: section.to_string(),
+            index,
+            reason: reason.to_string(),
+        });
+    }
+}
+
+/// Order in which abbreviations are listed and searched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbbreviationSortMode {
+    /// By abbr, ignoring case
+    Alphabetical,
+    /// Most used first, then most recently used
+    #[default]
+    MostUsed,
+    /// Most recently used first; never-used ones last
+    Recent,
+}
+
+/// Failure of an abbreviation command
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AbbreviationError {
+    /// Another abbreviation already uses this abbr; the UI can offer to
+    /// edit `conflicting_id` instead
+    Conflict { abbr: String, conflicting_id: String },
+    Failed { message: String },
+}
+
+impl fmt::Display for AbbreviationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AbbreviationError::Conflict { abbr, conflicting_id } => {
+                write!(f, "Abbreviation '{}' already exists (id {})", abbr, conflicting_id)
+            }
+            AbbreviationError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for AbbreviationError {
+    fn from(message: String) -> Self {
+        AbbreviationError::Failed { message }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -31,6 +165,8 @@ pub struct AbbreviationConfig {
     pub auto_open_single: bool,
     pub show_in_search: bool,
     pub case_sensitive: bool,
+    #[serde(default)]
+    pub sort_mode: AbbreviationSortMode,
 }
 
 impl Default for AbbreviationConfig {
@@ -46,6 +182,8 @@ impl Default for AbbreviationConfig {
                     enabled: true,
                     created_at: chrono::Utc::now().to_rfc3339(),
                     updated_at: chrono::Utc::now().to_rfc3339(),
+                    use_count: 0,
+                    last_used_at: None,
                 },
                 Abbreviation {
                     id: "2".to_string(),
@@ -56,6 +194,8 @@ impl Default for AbbreviationConfig {
                     enabled: true,
                     created_at: chrono::Utc::now().to_rfc3339(),
                     updated_at: chrono::Utc::now().to_rfc3339(),
+                    use_count: 0,
+                    last_used_at: None,
                 },
                 Abbreviation {
                     id: "3".to_string(),
@@ -66,6 +206,8 @@ impl Default for AbbreviationConfig {
                     enabled: true,
                     created_at: chrono::Utc::now().to_rfc3339(),
                     updated_at: chrono::Utc::now().to_rfc3339(),
+                    use_count: 0,
+                    last_used_at: None,
                 },
             ],
             categories: vec![
@@ -75,6 +217,7 @@ impl Default for AbbreviationConfig {
                     description: Some("编程和开发相关".to_string()),
                     icon: Some("💻".to_string()),
                     color: Some("#007acc".to_string()),
+                    enabled: true,
                 },
                 AbbreviationCategory {
                     id: "search".to_string(),
@@ -82,26 +225,426 @@ impl Default for AbbreviationConfig {
                     description: Some("搜索和查询".to_string()),
                     icon: Some("🔍".to_string()),
                     color: Some("#4285f4".to_string()),
+                    enabled: true,
                 },
             ],
             global_enabled: true,
             auto_open_single: false,
             show_in_search: true,
             case_sensitive: false,
+            sort_mode: AbbreviationSortMode::default(),
         }
     }
 }
 
+impl AbbreviationConfig {
+    /// Whether a category is enabled. Unknown or missing categories count as enabled.
+    pub fn is_category_enabled(&self, category_id: Option<&str>) -> bool {
+        category_id
+            .and_then(|id| self.categories.iter().find(|c| c.id == id))
+            .is_none_or(|c| c.enabled)
+    }
+
+    /// Whether an abbreviation is active, taking its category into account
+    pub fn is_abbreviation_active(&self, abbreviation: &Abbreviation) -> bool {
+        abbreviation.enabled && self.is_category_enabled(abbreviation.category.as_deref())
+    }
+
+    /// Whether any abbreviation was added or imported by the user, rather
+    /// than seeded by the default config
+    pub fn has_user_entries(&self) -> bool {
+        let seeded = AbbreviationConfig::default().abbreviations;
+        self.abbreviations.iter().any(|abbreviation| {
+            !seeded
+                .iter()
+                .any(|seed| seed.abbr == abbreviation.abbr && seed.expansion == abbreviation.expansion)
+        })
+    }
+
+    /// Whether two abbr strings are the same under the case setting
+    fn same_abbr(&self, a: &str, b: &str) -> bool {
+        if self.case_sensitive {
+            a == b
+        } else {
+            a.to_lowercase() == b.to_lowercase()
+        }
+    }
+
+    /// Another abbreviation (not `exclude_id`) that already uses `abbr`
+    pub fn find_conflict(&self, abbr: &str, exclude_id: Option<&str>) -> Option<&Abbreviation> {
+        self.abbreviations
+            .iter()
+            .find(|item| Some(item.id.as_str()) != exclude_id && self.same_abbr(&item.abbr, abbr))
+    }
+
+    fn check_conflict(&self, abbr: &str, exclude_id: Option<&str>) -> Result<(), AbbreviationError> {
+        match self.find_conflict(abbr, exclude_id) {
+            Some(existing) => Err(AbbreviationError::Conflict {
+                abbr: abbr.to_string(),
+                conflicting_id: existing.id.clone(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Reject a whole config, as saved from the UI, whose abbreviations
+    /// share an abbr. Duplicates `stored` already had, both unchanged, are
+    /// left alone so configs saved before conflicts were checked still save.
+    fn check_duplicates(&self, stored: &AbbreviationConfig) -> Result<(), AbbreviationError> {
+        let unchanged = |item: &Abbreviation| {
+            stored.abbreviations.iter().any(|old| old.id == item.id && old.abbr == item.abbr)
+        };
+        for (index, item) in self.abbreviations.iter().enumerate() {
+            let earlier = self.abbreviations[..index]
+                .iter()
+                .find(|other| self.same_abbr(&other.abbr, &item.abbr) && !(unchanged(other) && unchanged(item)));
+            if let Some(existing) = earlier {
+                return Err(AbbreviationError::Conflict {
+                    abbr: item.abbr.clone(),
+                    conflicting_id: existing.id.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Sort abbreviations by `sort_mode`. The sort is stable, so ties keep
+    /// insertion order.
+    pub fn sort<T: AsRef<Abbreviation>>(&self, items: &mut [T]) {
+        match self.sort_mode {
+            AbbreviationSortMode::Alphabetical => items.sort_by_cached_key(|a| a.as_ref().abbr.to_lowercase()),
+            AbbreviationSortMode::MostUsed => {
+                items.sort_by_cached_key(|a| (Reverse(a.as_ref().use_count), Reverse(last_used(a.as_ref()))))
+            }
+            AbbreviationSortMode::Recent => {
+                items.sort_by_cached_key(|a| (Reverse(last_used(a.as_ref())), Reverse(a.as_ref().use_count)))
+            }
+        }
+    }
+
+    /// Count a use of an abbreviation and stamp the time
+    pub fn record_use(&mut self, id: &str) -> Result<&Abbreviation, String> {
+        let abbr = self
+            .abbreviations
+            .iter_mut()
+            .find(|a| a.id == id)
+            .ok_or_else(|| format!("Abbreviation not found: {}", id))?;
+        abbr.use_count = abbr.use_count.saturating_add(1);
+        abbr.last_used_at = Some(chrono::Utc::now().to_rfc3339());
+        Ok(abbr)
+    }
+
+    /// Find a category by id or (case-insensitive) name
+    fn find_category(&self, key: &str) -> Option<&AbbreviationCategory> {
+        let key_lower = key.to_lowercase();
+        self.categories
+            .iter()
+            .find(|c| c.id == key)
+            .or_else(|| self.categories.iter().find(|c| c.name.to_lowercase() == key_lower))
+    }
+
+    /// Expand an abbreviation to its target, honoring global and category switches
+    pub fn expand(&self, abbr: &str) -> Option<&Abbreviation> {
+        if !self.global_enabled {
+            return None;
+        }
+
+        self.abbreviations
+            .iter()
+            .find(|item| self.same_abbr(&item.abbr, abbr) && self.is_abbreviation_active(item))
+    }
+
+    /// Search abbreviations by abbr, expansion or description, in
+    /// `sort_mode` order.
+    ///
+    /// A leading `@category` token (e.g. "@dev gh") restricts matching to a
+    /// single category, looked up by id or name.
+    pub fn search(&self, query: &str) -> Vec<&Abbreviation> {
+        if !self.global_enabled || !self.show_in_search {
+            return vec![];
+        }
+
+        let (scope, term) = parse_category_scope(query);
+
+        let category_id = match scope {
+            Some(key) => match self.find_category(key) {
+                Some(category) => Some(category.id.as_str()),
+                None => return vec![],
+            },
+            None => None,
+        };
+
+        let normalize = |s: &str| {
+            if self.case_sensitive {
+                s.to_string()
+            } else {
+                s.to_lowercase()
+            }
+        };
+        let term = normalize(term.trim());
+
+        let mut matches: Vec<&Abbreviation> = self
+            .abbreviations
+            .iter()
+            .filter(|item| self.is_abbreviation_active(item))
+            .filter(|item| category_id.is_none_or(|id| item.category.as_deref() == Some(id)))
+            .filter(|item| {
+                term.is_empty()
+                    || normalize(&item.abbr).contains(&term)
+                    || normalize(&item.expansion).contains(&term)
+                    || item
+                        .description
+                        .as_deref()
+                        .is_some_and(|d| normalize(d).contains(&term))
+            })
+            .collect();
+        self.sort(&mut matches);
+        matches
+    }
+
+    /// Enable or disable a category
+    pub fn set_category_enabled(&mut self, category_id: &str, enabled: bool) -> Result<(), String> {
+        let category = self
+            .categories
+            .iter_mut()
+            .find(|c| c.id == category_id)
+            .ok_or_else(|| format!("Category not found: {}", category_id))?;
+        category.enabled = enabled;
+        Ok(())
+    }
+
+    /// Delete a category, moving its abbreviations to `reassign_to`
+    /// (or leaving them uncategorized). Returns the number of reassigned items.
+    pub fn delete_category(
+        &mut self,
+        category_id: &str,
+        reassign_to: Option<&str>,
+    ) -> Result<usize, String> {
+        let index = self
+            .categories
+            .iter()
+            .position(|c| c.id == category_id)
+            .ok_or_else(|| format!("Category not found: {}", category_id))?;
+
+        if let Some(target) = reassign_to {
+            if target == category_id {
+                return Err("Cannot reassign abbreviations to the deleted category".to_string());
+            }
+            if !self.categories.iter().any(|c| c.id == target) {
+                return Err(format!("Category not found: {}", target));
+            }
+        }
+
+        self.categories.remove(index);
+
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut reassigned = 0;
+        for abbr in self
+            .abbreviations
+            .iter_mut()
+            .filter(|a| a.category.as_deref() == Some(category_id))
+        {
+            abbr.category = reassign_to.map(|s| s.to_string());
+            abbr.updated_at = now.clone();
+            reassigned += 1;
+        }
+
+        Ok(reassigned)
+    }
+
+    /// Work out the config that importing `imported`, a config in export
+    /// format, results in. Categories are matched by id and abbreviations by
+    /// abbr; existing ones are kept unless a conflict resolution says
+    /// otherwise. Entries that don't parse, or name an unknown category, are
+    /// listed in the report instead of failing the import.
+    pub fn plan_import(
+        &self,
+        imported: &Value,
+        options: &ImportOptions,
+    ) -> Result<(AbbreviationConfig, ImportReport), String> {
+        let object = imported
+            .as_object()
+            .ok_or("Invalid configuration format: expected an object")?;
+        let mut report = ImportReport {
+            mode: options.mode,
+            dry_run: options.dry_run,
+            ..Default::default()
+        };
+
+        let mut config = match options.mode {
+            ImportMode::Merge => self.clone(),
+            ImportMode::Replace => {
+                let flag = |key: &str, current: bool| object.get(key).and_then(Value::as_bool).unwrap_or(current);
+                AbbreviationConfig {
+                    abbreviations: Vec::new(),
+                    categories: Vec::new(),
+                    global_enabled: flag("global_enabled", self.global_enabled),
+                    auto_open_single: flag("auto_open_single", self.auto_open_single),
+                    show_in_search: flag("show_in_search", self.show_in_search),
+                    case_sensitive: flag("case_sensitive", self.case_sensitive),
+                    sort_mode: object
+                        .get("sort_mode")
+                        .and_then(|v| serde_json::from_value(v.clone()).ok())
+                        .unwrap_or(self.sort_mode),
+                }
+            }
+        };
+
+        for (index, value) in import_section(object, "categories")?.iter().enumerate() {
+            let category: AbbreviationCategory = match serde_json::from_value(value.clone()) {
+                Ok(category) => category,
+                Err(e) => {
+                    report.invalid("categories", index, e);
+                    continue;
+                }
+            };
+            if category.id.trim().is_empty() {
+                report.invalid("categories", index, "Category id is empty");
+            } else if !config.categories.iter().any(|c| c.id == category.id) {
+                config.categories.push(category.clone());
+                report.categories_added.push(category);
+            }
+        }
+
+        let now = chrono::Utc::now().to_rfc3339();
+        for (index, value) in import_section(object, "abbreviations")?.iter().enumerate() {
+            let mut abbr: Abbreviation = match serde_json::from_value(value.clone()) {
+                Ok(abbr) => abbr,
+                Err(e) => {
+                    report.invalid("abbreviations", index, e);
+                    continue;
+                }
+            };
+            if abbr.abbr.trim().is_empty() || abbr.expansion.trim().is_empty() {
+                report.invalid("abbreviations", index, "Abbreviation and expansion must not be empty");
+                continue;
+            }
+            if let Some(category) = abbr.category.as_deref() {
+                if !config.categories.iter().any(|c| c.id == category) {
+                    report.invalid("abbreviations", index, format!("Unknown category: {}", category));
+                    continue;
+                }
+            }
+
+            let Some(existing) = config.find_conflict(&abbr.abbr, None).cloned() else {
+                if config.abbreviations.iter().any(|a| a.id == abbr.id) {
+                    abbr.id = uuid::Uuid::new_v4().to_string();
+                }
+                config.abbreviations.push(abbr.clone());
+                report.added.push(abbr);
+                continue;
+            };
+
+            if report.added.iter().any(|a| a.id == existing.id) {
+                report.invalid("abbreviations", index, format!("Duplicate abbreviation: {}", abbr.abbr));
+            } else if existing.expansion == abbr.expansion {
+                report.skipped.push(abbr);
+            } else {
+                let resolution = options.resolutions.get(&abbr.abbr).copied();
+                if resolution == Some(ConflictResolution::UseImported) {
+                    if let Some(target) = config.abbreviations.iter_mut().find(|a| a.id == existing.id) {
+                        target.expansion = abbr.expansion.clone();
+                        target.description = abbr.description.clone();
+                        target.category = abbr.category.clone();
+                        target.updated_at = now.clone();
+                    }
+                }
+                report.conflicts.push(ImportConflict {
+                    existing,
+                    imported: abbr,
+                    resolution,
+                });
+            }
+        }
+
+        Ok((config, report))
+    }
+}
+
+/// A list of an imported config; a missing one counts as empty
+fn import_section<'a>(object: &'a serde_json::Map<String, Value>, key: &str) -> Result<&'a [Value], String> {
+    match object.get(key) {
+        None | Some(Value::Null) => Ok(&[]),
+        Some(Value::Array(items)) => Ok(items),
+        Some(_) => Err(format!("Invalid configuration format: {} must be a list", key)),
+    }
+}
+
+impl AsRef<Abbreviation> for Abbreviation {
+    fn as_ref(&self) -> &Abbreviation {
+        self
+    }
+}
+
+/// Last use time; unparsable or missing times sort as never used
+fn last_used(abbr: &Abbreviation) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+    abbr.last_used_at
+        .as_deref()
+        .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+}
+
+/// Split a leading "@category" token from a query.
+/// "@dev gh" -> (Some("dev"), "gh"); "gh" -> (None, "gh")
+pub fn parse_category_scope(query: &str) -> (Option<&str>, &str) {
+    let trimmed = query.trim_start();
+    match trimmed.strip_prefix('@') {
+        Some(rest) if !rest.is_empty() => {
+            let (category, term) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            (Some(category), term)
+        }
+        _ => (None, query),
+    }
+}
+
 async fn get_config_path(handle: &AppHandle) -> Result<PathBuf, String> {
-    let config_dir = handle
-        .path()
-        .app_config_dir()
-        .map_err(|e| format!("Failed to get config dir: {}", e))?;
+    crate::services::config_service::abbreviation_config_path(handle)
+}
+
+/// Load the abbreviation config, falling back to defaults if it doesn't exist yet
+async fn load_config(handle: &AppHandle) -> Result<AbbreviationConfig, String> {
+    read_config_file(&get_config_path(handle).await?)
+}
+
+/// Persist the abbreviation config
+async fn write_config(handle: &AppHandle, config: &AbbreviationConfig) -> Result<(), String> {
+    write_config_file(&get_config_path(handle).await?, config)
+}
+
+fn read_config_file(config_path: &Path) -> Result<AbbreviationConfig, String> {
+    if !config_path.exists() {
+        return Ok(AbbreviationConfig::default());
+    }
+
+    let content = std::fs::read_to_string(config_path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config file: {}", e))
+}
 
-    std::fs::create_dir_all(&config_dir)
-        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+fn write_config_file(config_path: &Path, config: &AbbreviationConfig) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-    Ok(config_dir.join("abbreviations.json"))
+    std::fs::write(config_path, content)
+        .map_err(|e| format!("Failed to write config file: {}", e))
+}
+
+/// Whether the config at `config_path` has abbreviations beyond the seeded
+/// defaults that get written on first read
+pub fn has_user_abbreviations(config_path: &Path) -> bool {
+    read_config_file(config_path).is_ok_and(|config| config.has_user_entries())
+}
+
+/// Import `config_json` into the config at `config_path`, writing it
+/// unless this is a dry run
+fn import_config_file(config_path: &Path, config_json: &str, options: &ImportOptions) -> Result<ImportReport, String> {
+    let imported: Value = serde_json::from_str(config_json)
+        .map_err(|e| format!("Invalid configuration format: {}", e))?;
+    let (config, report) = read_config_file(config_path)?.plan_import(&imported, options)?;
+    if !options.dry_run {
+        write_config_file(config_path, &config)?;
+    }
+    Ok(report)
 }
 
 #[tauri::command]
@@ -122,16 +665,22 @@ pub async fn get_abbreviation_config(
     let content = std::fs::read_to_string(&config_path)
         .map_err(|e| format!("Failed to read config file: {}", e))?;
     
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config file: {}", e))
+    let mut config: AbbreviationConfig = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config file: {}", e))?;
+
+    let mut abbreviations = std::mem::take(&mut config.abbreviations);
+    config.sort(&mut abbreviations);
+    config.abbreviations = abbreviations;
+    Ok(config)
 }
 
 #[tauri::command]
 pub async fn save_abbreviation_config(
     config: AbbreviationConfig,
     handle: AppHandle,
-) -> Result<(), String> {
+) -> Result<(), AbbreviationError> {
     let config_path = get_config_path(&handle).await?;
+    config.check_duplicates(&read_config_file(&config_path)?)?;
     
     let content = serde_json::to_string_pretty(&config)
         .map_err(|e| format!("Failed to serialize config: {}", e))?;
@@ -146,7 +695,7 @@ pub async fn save_abbreviation_config(
 pub async fn add_abbreviation(
     abbreviation: Abbreviation,
     handle: AppHandle,
-) -> Result<Abbreviation, String> {
+) -> Result<Abbreviation, AbbreviationError> {
     let config_path = get_config_path(&handle).await?;
     
     let mut config = if config_path.exists() {
@@ -157,11 +706,15 @@ pub async fn add_abbreviation(
     } else {
         AbbreviationConfig::default()
     };
+
+    config.check_conflict(&abbreviation.abbr, None)?;
     
     let new_abbr = Abbreviation {
         id: chrono::Utc::now().timestamp_millis().to_string(),
         created_at: chrono::Utc::now().to_rfc3339(),
         updated_at: chrono::Utc::now().to_rfc3339(),
+        use_count: 0,
+        last_used_at: None,
         ..abbreviation
     };
     
@@ -181,7 +734,7 @@ pub async fn update_abbreviation(
     id: String,
     updates: Abbreviation,
     handle: AppHandle,
-) -> Result<Abbreviation, String> {
+) -> Result<Abbreviation, AbbreviationError> {
     let config_path = get_config_path(&handle).await?;
     
     let mut config = if config_path.exists() {
@@ -196,10 +749,15 @@ pub async fn update_abbreviation(
     let index = config.abbreviations.iter()
         .position(|abbr| abbr.id == id)
         .ok_or("Abbreviation not found".to_string())?;
+
+    config.check_conflict(&updates.abbr, Some(&id))?;
     
     let mut updated_abbr = updates;
     updated_abbr.id = id.clone();
     updated_abbr.updated_at = chrono::Utc::now().to_rfc3339();
+    // Usage is tracked by record_abbreviation_use, not edited
+    updated_abbr.use_count = config.abbreviations[index].use_count;
+    updated_abbr.last_used_at = config.abbreviations[index].last_used_at.clone();
     
     config.abbreviations[index] = updated_abbr.clone();
     
@@ -262,20 +820,432 @@ pub async fn export_abbreviation_config(
         .map_err(|e| format!("Failed to serialize config: {}", e))
 }
 
+/// Import an exported config. Returns what was added, skipped, left in
+/// conflict or rejected, for the UI to confirm; with `dry_run` nothing is
+/// written.
 #[tauri::command]
 pub async fn import_abbreviation_config(
     config_json: String,
+    options: Option<ImportOptions>,
     handle: AppHandle,
-) -> Result<(), String> {
-    let imported: AbbreviationConfig = serde_json::from_str(&config_json)
-        .map_err(|e| format!("Invalid configuration format: {}", e))?;
-    
+) -> Result<ImportReport, String> {
     let config_path = get_config_path(&handle).await?;
-    let content = serde_json::to_string_pretty(&imported)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    
-    std::fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
-    
-    Ok(())
-}
\ No newline at end of file
+    import_config_file(&config_path, &config_json, &options.unwrap_or_default())
+}
+
+/// Enable or disable a whole category
+#[tauri::command]
+pub async fn set_category_enabled(
+    id: String,
+    enabled: bool,
+    handle: AppHandle,
+) -> Result<(), String> {
+    let mut config = load_config(&handle).await?;
+    config.set_category_enabled(&id, enabled)?;
+    write_config(&handle, &config).await
+}
+
+/// Delete a category, reassigning its abbreviations to another category
+/// (or leaving them uncategorized when `reassign_to` is None)
+#[tauri::command]
+pub async fn delete_category(
+    id: String,
+    reassign_to: Option<String>,
+    handle: AppHandle,
+) -> Result<usize, String> {
+    let mut config = load_config(&handle).await?;
+    let reassigned = config.delete_category(&id, reassign_to.as_deref())?;
+    write_config(&handle, &config).await?;
+    Ok(reassigned)
+}
+
+/// Count a use of an abbreviation; call when one is expanded or opened
+#[tauri::command]
+pub async fn record_abbreviation_use(
+    id: String,
+    handle: AppHandle,
+) -> Result<Abbreviation, String> {
+    let mut config = load_config(&handle).await?;
+    let abbr = config.record_use(&id)?.clone();
+    write_config(&handle, &config).await?;
+    Ok(abbr)
+}
+
+/// Search abbreviations, supporting "@category term" scoping
+#[tauri::command]
+pub async fn search_abbreviations(
+    query: String,
+    handle: AppHandle,
+) -> Result<Vec<Abbreviation>, String> {
+    let config = load_config(&handle).await?;
+    Ok(config.search(&query).into_iter().cloned().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn abbr(id: &str, abbr: &str, category: Option<&str>, enabled: bool) -> Abbreviation {
+        Abbreviation {
+            id: id.to_string(),
+            abbr: abbr.to_string(),
+            expansion: format!("https://{}.example.com", abbr),
+            description: None,
+            category: category.map(|c| c.to_string()),
+            enabled,
+            created_at: String::new(),
+            updated_at: String::new(),
+            use_count: 0,
+            last_used_at: None,
+        }
+    }
+
+    fn category(id: &str, enabled: bool) -> AbbreviationCategory {
+        AbbreviationCategory {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            description: None,
+            icon: None,
+            color: None,
+            enabled,
+        }
+    }
+
+    fn test_config() -> AbbreviationConfig {
+        AbbreviationConfig {
+            abbreviations: vec![
+                abbr("1", "gh", Some("dev"), true),
+                abbr("2", "ggl", Some("search"), true),
+                abbr("3", "gl", Some("dev"), false),
+                abbr("4", "gmail", None, true),
+            ],
+            categories: vec![category("dev", true), category("search", true)],
+            global_enabled: true,
+            auto_open_single: false,
+            show_in_search: true,
+            case_sensitive: false,
+            sort_mode: AbbreviationSortMode::MostUsed,
+        }
+    }
+
+    fn ids(items: Vec<&Abbreviation>) -> Vec<String> {
+        items.into_iter().map(|a| a.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_disabled_category_hides_abbreviations() {
+        let mut config = test_config();
+        config.set_category_enabled("dev", false).unwrap();
+
+        assert_eq!(ids(config.search("g")), vec!["2", "4"]);
+        assert!(config.expand("gh").is_none());
+        assert!(config.expand("ggl").is_some());
+    }
+
+    #[test]
+    fn test_enabled_category_keeps_item_flag() {
+        let config = test_config();
+        // "gl" is disabled individually even though its category is enabled
+        assert!(config.expand("gl").is_none());
+        assert!(!ids(config.search("gl")).contains(&"3".to_string()));
+    }
+
+    #[test]
+    fn test_category_scoped_search() {
+        let config = test_config();
+        assert_eq!(ids(config.search("@dev g")), vec!["1"]);
+        assert_eq!(ids(config.search("@search")), vec!["2"]);
+        // Category names work too
+        assert_eq!(ids(config.search("@DEV gh")), vec!["1"]);
+        assert!(config.search("@unknown gh").is_empty());
+    }
+
+    #[test]
+    fn test_parse_category_scope() {
+        assert_eq!(parse_category_scope("@dev gh"), (Some("dev"), "gh"));
+        assert_eq!(parse_category_scope("@dev"), (Some("dev"), ""));
+        assert_eq!(parse_category_scope("gh"), (None, "gh"));
+        assert_eq!(parse_category_scope("@"), (None, "@"));
+    }
+
+    #[test]
+    fn test_delete_category_reassigns_orphans() {
+        let mut config = test_config();
+        let moved = config.delete_category("dev", Some("search")).unwrap();
+
+        assert_eq!(moved, 2);
+        assert!(config.categories.iter().all(|c| c.id != "dev"));
+        assert!(config
+            .abbreviations
+            .iter()
+            .filter(|a| a.id == "1" || a.id == "3")
+            .all(|a| a.category.as_deref() == Some("search")));
+    }
+
+    #[test]
+    fn test_delete_category_without_target_uncategorizes() {
+        let mut config = test_config();
+        config.delete_category("dev", None).unwrap();
+
+        let gh = config.abbreviations.iter().find(|a| a.id == "1").unwrap();
+        assert_eq!(gh.category, None);
+        assert!(config.expand("gh").is_some());
+    }
+
+    #[test]
+    fn test_delete_category_rejects_invalid_target() {
+        let mut config = test_config();
+        assert!(config.delete_category("dev", Some("missing")).is_err());
+        assert!(config.delete_category("dev", Some("dev")).is_err());
+        assert!(config.delete_category("missing", None).is_err());
+        // Nothing was removed on failure
+        assert_eq!(config.categories.len(), 2);
+    }
+
+    fn imported_config() -> Value {
+        let mut changed = abbr("1", "gh", Some("dev"), true);
+        changed.expansion = "https://github.example.org".to_string();
+        serde_json::json!({
+            "abbreviations": [
+                changed,
+                abbr("2", "ggl", Some("search"), true),
+                abbr("9", "so", Some("qa"), true),
+            ],
+            "categories": [category("dev", false), category("qa", true)],
+            "global_enabled": false,
+            "show_in_search": false,
+        })
+    }
+
+    fn import_ids(items: &[Abbreviation]) -> Vec<&str> {
+        items.iter().map(|a| a.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_merge_import_reports_conflicts() {
+        let config = test_config();
+        let (merged, report) = config.plan_import(&imported_config(), &ImportOptions::default()).unwrap();
+
+        assert_eq!(import_ids(&report.added), vec!["9"]);
+        assert_eq!(import_ids(&report.skipped), vec!["2"]);
+        assert_eq!(report.conflicts.len(), 1);
+        assert_eq!(report.conflicts[0].existing.id, "1");
+        assert_eq!(report.conflicts[0].resolution, None);
+        assert_eq!(report.categories_added.len(), 1);
+        assert!(report.invalid.is_empty());
+
+        // Existing entries and switches are kept
+        assert_eq!(merged.expand("gh").unwrap().expansion, "https://gh.example.com");
+        assert!(merged.is_category_enabled(Some("dev")));
+        assert!(merged.global_enabled);
+        assert!(merged.show_in_search);
+        assert_eq!(merged.abbreviations.len(), 5);
+
+        let options = ImportOptions {
+            resolutions: HashMap::from([("gh".to_string(), ConflictResolution::UseImported)]),
+            ..Default::default()
+        };
+        let (merged, report) = config.plan_import(&imported_config(), &options).unwrap();
+        assert_eq!(report.conflicts[0].resolution, Some(ConflictResolution::UseImported));
+        let gh = merged.expand("gh").unwrap();
+        assert_eq!((gh.id.as_str(), gh.expansion.as_str()), ("1", "https://github.example.org"));
+    }
+
+    #[test]
+    fn test_replace_import_takes_imported_config() {
+        let options = ImportOptions {
+            mode: ImportMode::Replace,
+            ..Default::default()
+        };
+        let (replaced, report) = test_config().plan_import(&imported_config(), &options).unwrap();
+
+        assert_eq!(import_ids(&replaced.abbreviations), vec!["1", "9"]);
+        assert_eq!(replaced.categories.len(), 2);
+        assert!(!replaced.global_enabled);
+        assert!(report.conflicts.is_empty());
+        // "search" was replaced away along with the old categories
+        assert_eq!(report.invalid.len(), 1);
+        assert!(report.invalid[0].reason.contains("search"));
+    }
+
+    #[test]
+    fn test_dry_run_import_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abbreviations.json");
+        write_config_file(&path, &test_config()).unwrap();
+        let before = std::fs::read(&path).unwrap();
+        let json = imported_config().to_string();
+
+        let options = ImportOptions {
+            dry_run: true,
+            ..Default::default()
+        };
+        let report = import_config_file(&path, &json, &options).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(import_ids(&report.added), vec!["9"]);
+        assert_eq!(std::fs::read(&path).unwrap(), before);
+
+        import_config_file(&path, &json, &ImportOptions::default()).unwrap();
+        assert_eq!(read_config_file(&path).unwrap().abbreviations.len(), 5);
+    }
+
+    #[test]
+    fn test_invalid_import_entries_are_itemized() {
+        let imported = serde_json::json!({
+            "abbreviations": [
+                {"id": "7", "abbr": "nope"},
+                abbr("8", "wiki", Some("missing"), true),
+                abbr("9", "so", None, true),
+                abbr("10", "SO", None, true),
+                "not an abbreviation",
+            ],
+            "categories": [{"name": "no id"}],
+        });
+        let (merged, report) = test_config().plan_import(&imported, &ImportOptions::default()).unwrap();
+
+        assert_eq!(import_ids(&report.added), vec!["9"]);
+        let invalid: Vec<(&str, usize)> = report.invalid.iter().map(|i| (i.section.as_str(), i.index)).collect();
+        assert_eq!(
+            invalid,
+            vec![
+                ("categories", 0),
+                ("abbreviations", 0),
+                ("abbreviations", 1),
+                ("abbreviations", 3),
+                ("abbreviations", 4)
+            ]
+        );
+        assert!(report.invalid[2].reason.contains("missing"));
+        assert_eq!(merged.abbreviations.len(), 5);
+
+        assert!(test_config().plan_import(&serde_json::json!([]), &ImportOptions::default()).is_err());
+    }
+
+    #[test]
+    fn test_category_enabled_defaults_to_true() {
+        let json = r#"{"id":"dev","name":"Dev","description":null,"icon":null,"color":null}"#;
+        let category: AbbreviationCategory = serde_json::from_str(json).unwrap();
+        assert!(category.enabled);
+    }
+
+    #[test]
+    fn test_sort_modes() {
+        let mut config = test_config();
+        let used = |config: &mut AbbreviationConfig, id: &str, count: u32, at: &str| {
+            let item = config.abbreviations.iter_mut().find(|a| a.id == id).unwrap();
+            item.use_count = count;
+            item.last_used_at = Some(at.to_string());
+        };
+        used(&mut config, "2", 3, "2024-05-01T10:00:00+00:00");
+        used(&mut config, "4", 7, "2024-04-01T10:00:00+00:00");
+        // Same time in another offset, earlier than "2" once normalized
+        used(&mut config, "1", 3, "2024-05-01T11:00:00+02:00");
+
+        assert_eq!(ids(config.search("g")), vec!["4", "2", "1"]);
+
+        config.sort_mode = AbbreviationSortMode::Recent;
+        assert_eq!(ids(config.search("g")), vec!["2", "1", "4"]);
+
+        config.sort_mode = AbbreviationSortMode::Alphabetical;
+        config.abbreviations.push(abbr("5", "Gap", None, true));
+        assert_eq!(ids(config.search("g")), vec!["5", "2", "1", "4"]);
+    }
+
+    #[test]
+    fn test_never_used_sort_last_by_recency() {
+        let mut config = test_config();
+        config.sort_mode = AbbreviationSortMode::Recent;
+        config.record_use("4").unwrap();
+        config.record_use("4").unwrap();
+
+        let gmail = config.abbreviations.iter().find(|a| a.id == "4").unwrap();
+        assert_eq!(gmail.use_count, 2);
+        assert!(gmail.last_used_at.is_some());
+        // Unused ones keep insertion order behind it
+        assert_eq!(ids(config.search("g")), vec!["4", "1", "2"]);
+        assert!(config.record_use("missing").is_err());
+    }
+
+    #[test]
+    fn test_conflicts_ignore_case_by_default() {
+        let config = test_config();
+        assert_eq!(
+            config.check_conflict("GH", None),
+            Err(AbbreviationError::Conflict { abbr: "GH".to_string(), conflicting_id: "1".to_string() })
+        );
+        // An abbreviation doesn't conflict with itself on update
+        assert_eq!(config.check_conflict("GH", Some("1")), Ok(()));
+        assert_eq!(config.find_conflict("gh", Some("2")).map(|a| a.id.as_str()), Some("1"));
+        assert_eq!(config.check_conflict("new", None), Ok(()));
+    }
+
+    #[test]
+    fn test_conflicts_respect_case_sensitive() {
+        let mut config = test_config();
+        config.case_sensitive = true;
+        assert_eq!(config.check_conflict("GH", None), Ok(()));
+        assert!(matches!(
+            config.check_conflict("gh", Some("2")),
+            Err(AbbreviationError::Conflict { conflicting_id, .. }) if conflicting_id == "1"
+        ));
+    }
+
+    #[test]
+    fn test_saved_config_rejects_new_duplicates() {
+        let stored = test_config();
+        let mut config = test_config();
+        config.abbreviations.push(abbr("9", "GH", None, true));
+        assert_eq!(
+            config.check_duplicates(&stored),
+            Err(AbbreviationError::Conflict { abbr: "GH".to_string(), conflicting_id: "1".to_string() })
+        );
+
+        // Renaming one onto another is caught too
+        let mut config = test_config();
+        config.abbreviations[1].abbr = "gh".to_string();
+        assert!(matches!(
+            config.check_duplicates(&stored),
+            Err(AbbreviationError::Conflict { conflicting_id, .. }) if conflicting_id == "1"
+        ));
+
+        config.case_sensitive = true;
+        config.abbreviations[1].abbr = "GH".to_string();
+        assert_eq!(config.check_duplicates(&stored), Ok(()));
+    }
+
+    #[test]
+    fn test_saved_config_keeps_existing_duplicates() {
+        let mut stored = test_config();
+        stored.abbreviations.push(abbr("9", "gh", None, true));
+        let mut config = stored.clone();
+        config.sort_mode = AbbreviationSortMode::Alphabetical;
+        assert_eq!(config.check_duplicates(&stored), Ok(()));
+    }
+
+    #[test]
+    fn test_seeded_default_config_has_no_user_abbreviations() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("abbreviations.json");
+        assert!(!has_user_abbreviations(&path));
+
+        // As written by get_abbreviation_config on first read
+        write_config_file(&path, &AbbreviationConfig::default()).unwrap();
+        assert!(!has_user_abbreviations(&path));
+
+        let json = serde_json::json!({ "abbreviations": [abbr("9", "jira", None, true)] }).to_string();
+        import_config_file(&path, &json, &ImportOptions::default()).unwrap();
+        assert!(has_user_abbreviations(&path));
+    }
+
+    #[test]
+    fn test_usage_fields_default_for_existing_configs() {
+        let json = r#"{"abbreviations":[{"id":"1","abbr":"gh","expansion":"https://github.com",
+            "description":null,"category":null,"enabled":true,"created_at":"","updated_at":""}],
+            "categories":[],"global_enabled":true,"auto_open_single":false,"show_in_search":true,
+            "case_sensitive":false}"#;
+        let config: AbbreviationConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config.sort_mode, AbbreviationSortMode::MostUsed);
+        assert_eq!(config.abbreviations[0].use_count, 0);
+        assert_eq!(config.abbreviations[0].last_used_at, None);
+    }
+}
@@ -0,0 +1,58 @@
+/**
+ * Usage Analytics Commands
+ * Local usage summary, purge, and event recording for frontend-run plugins
+ */
+
+use crate::db::analytics::{init_analytics_db, purge_events_before, summarize_events, UsageSummary};
+use crate::services::analytics::{record_usage, UsageKind};
+use serde::Deserialize;
+use tauri::AppHandle;
+
+/// Summary period, counted back from now
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsagePeriod {
+    Day,
+    Week,
+    Month,
+    All,
+}
+
+impl UsagePeriod {
+    /// Earliest timestamp (unix seconds) included in the period
+    fn since(&self, now: i64) -> i64 {
+        const DAY: i64 = 24 * 3600;
+        match self {
+            UsagePeriod::Day => now - DAY,
+            UsagePeriod::Week => now - 7 * DAY,
+            UsagePeriod::Month => now - 30 * DAY,
+            UsagePeriod::All => 0,
+        }
+    }
+}
+
+/// Get aggregated usage for a period
+#[tauri::command]
+pub fn get_usage_summary(handle: AppHandle, period: UsagePeriod) -> Result<UsageSummary, String> {
+    let conn = init_analytics_db(&handle).map_err(|e| format!("Failed to open analytics db: {}", e))?;
+    summarize_events(&conn, period.since(chrono::Utc::now().timestamp()))
+        .map_err(|e| format!("Failed to summarize usage: {}", e))
+}
+
+/// Delete usage events older than `before` (unix seconds), or all events if
+/// omitted. Returns the number of deleted events.
+#[tauri::command]
+pub fn purge_usage_data(handle: AppHandle, before: Option<i64>) -> Result<usize, String> {
+    let conn = init_analytics_db(&handle).map_err(|e| format!("Failed to open analytics db: {}", e))?;
+    purge_events_before(&conn, before.unwrap_or(i64::MAX))
+        .map_err(|e| format!("Failed to purge usage data: {}", e))
+}
+
+/// Record a usage event that happens in the frontend: a plugin run, or a
+/// search once one of its results is acted on
+#[tauri::command]
+pub fn record_usage_event(handle: AppHandle, kind: String, subject: String) -> Result<(), String> {
+    let kind = UsageKind::parse(&kind).ok_or_else(|| format!("Unknown usage kind: {}", kind))?;
+    record_usage(&handle, kind, &subject);
+    Ok(())
+}
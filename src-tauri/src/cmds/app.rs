@@ -4,90 +4,61 @@
  */
 
 use crate::models::app::*;
+use crate::services::analytics::{record_usage, UsageKind};
 use crate::services::app_monitor::AppMonitor;
+use crate::utils::run_blocking;
 use std::sync::Mutex;
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 
 /// Global app monitor state
 pub struct AppState {
     pub app_monitor: Mutex<AppMonitor>,
 }
 
-/// Get installed applications
+/// Get installed applications. `refresh` rescans the application folders,
+/// so the listing runs on the blocking pool.
 #[tauri::command]
-pub fn get_installed_apps(
-    refresh: bool,
-    state: State<AppState>,
-) -> Result<GetInstalledAppsResponse, String> {
-    let start = std::time::Instant::now();
-
-    let mut monitor = state.app_monitor.lock().map_err(|e| e.to_string())?;
+pub async fn get_installed_apps(handle: AppHandle, refresh: bool) -> Result<GetInstalledAppsResponse, String> {
+    run_blocking(move || {
+        let state = handle.state::<AppState>();
+        let start = std::time::Instant::now();
 
-    let apps = if refresh {
-        monitor.scan_apps()
-    } else {
-        // Return cached apps or scan if empty
-        if monitor.scan_apps().is_empty() {
-            monitor.scan_apps()
+        let apps = if refresh {
+            AppMonitor::rescan_shared(&state.app_monitor)?
         } else {
-            vec![] // Will be populated from cache in production
-        }
-    };
+            state.app_monitor.lock().map_err(|e| e.to_string())?.scan_apps()
+        };
 
-    let scan_time = start.elapsed().as_millis() as u64;
+        let scan_time = start.elapsed().as_millis() as u64;
 
-    Ok(GetInstalledAppsResponse { apps, scan_time })
+        Ok(GetInstalledAppsResponse { apps, scan_time })
+    })
+    .await
 }
 
-/// Launch an application
+/// Rescan installed applications for search, e.g. when the app watcher
+/// can't see the directory an app was installed to. Emits `apps:changed`.
 #[tauri::command]
-pub fn launch_app(path: String) -> Result<LaunchAppResponse, String> {
-    #[cfg(target_os = "macos")]
-    {
-        use std::process::Command;
-        Command::new("open")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to launch app: {}", e))?;
-
-        return Ok(LaunchAppResponse {
-            success: true,
-            pid: None,
-        });
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        use std::process::Command;
-        Command::new("cmd")
-            .args(&["/C", "start", "", &path])
-            .spawn()
-            .map_err(|e| format!("Failed to launch app: {}", e))?;
-
-        return Ok(LaunchAppResponse {
-            success: true,
-            pid: None,
-        });
-    }
-
-    #[cfg(target_os = "linux")]
-    {
-        use std::process::Command;
-        Command::new("xdg-open")
-            .arg(&path)
-            .spawn()
-            .map_err(|e| format!("Failed to launch app: {}", e))?;
-
-        return Ok(LaunchAppResponse {
-            success: true,
-            pid: None,
-        });
-    }
+pub async fn rescan_apps(handle: AppHandle) -> Result<usize, String> {
+    run_blocking(move || crate::services::app_watcher::refresh_and_notify(&handle)).await
+}
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
-    {
-        return Err("Unsupported platform".to_string());
-    }
+/// Launch an application. `source` is the entry's source and picks the
+/// launch mechanism; without it the source is inferred from the path.
+#[tauri::command]
+pub fn launch_app(handle: AppHandle, path: String, source: Option<AppSource>) -> Result<LaunchAppResponse, String> {
+    record_usage(&handle, UsageKind::Launch, &path);
+
+    let command = source.unwrap_or_else(|| AppSource::infer(&path)).launch_command(&path);
+    std::process::Command::new(&command.program)
+        .args(&command.args)
+        .spawn()
+        .map_err(|e| format!("Failed to launch app: {}", e))?;
+
+    Ok(LaunchAppResponse {
+        success: true,
+        pid: None,
+    })
 }
 
 /// Track application usage
@@ -0,0 +1,68 @@
+/**
+ * Backup Commands
+ * Create and restore backups of settings, abbreviations, plugin state,
+ * clipboard history and usage data
+ */
+
+use crate::cmds::settings::{load_settings, modify_settings};
+use crate::services::backup::{self, BackupManifest, BackupOptions, BackupPaths, RestoreReport};
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::search::cache::invalidate_search_cache;
+use crate::utils::run_blocking;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+fn backup_paths(handle: &AppHandle) -> Result<BackupPaths, String> {
+    Ok(BackupPaths {
+        config_dir: handle
+            .path()
+            .app_config_dir()
+            .map_err(|e| format!("Failed to get config dir: {}", e))?,
+        data_dir: crate::db::ensure_data_dir(handle)?,
+    })
+}
+
+/// Create a backup zip at `target_path`
+#[tauri::command]
+pub async fn create_backup(
+    handle: AppHandle,
+    target_path: String,
+    options: Option<BackupOptions>,
+) -> Result<BackupManifest, String> {
+    let paths = backup_paths(&handle)?;
+    run_blocking(move || {
+        backup::create_backup(&paths, &PathBuf::from(target_path), &options.unwrap_or_default())
+    })
+    .await
+}
+
+/// Restore a backup. The indexer and browser reader are paused while files
+/// are swapped in, and restored settings take effect immediately.
+#[tauri::command]
+pub async fn restore_backup(
+    handle: AppHandle,
+    path: String,
+    options: Option<BackupOptions>,
+) -> Result<RestoreReport, String> {
+    let paths = backup_paths(&handle)?;
+    // The data stays where it is, whatever data dir the backup's settings name
+    let data_dir = load_settings(&handle)?.data_dir;
+
+    // Waiting for the guard and copying files both block
+    let guard = handle.state::<MaintenanceGuard>().inner().clone();
+    let report = run_blocking(move || {
+        let _exclusive = guard.exclusive()?;
+        backup::restore_backup(&paths, &PathBuf::from(path), &options.unwrap_or_default())
+    })
+    .await?;
+
+    if report.restored.iter().any(|c| c == "settings") {
+        modify_settings(&handle, |settings| {
+            settings.data_dir = data_dir;
+            Ok::<_, String>(())
+        })?;
+    }
+    invalidate_search_cache(&handle);
+
+    Ok(report)
+}
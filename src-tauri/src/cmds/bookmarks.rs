@@ -0,0 +1,63 @@
+/**
+ * Bookmark Commands
+ * Browsing cached bookmarks by folder and opening a folder's bookmarks
+ */
+
+use crate::db::browser::{bookmark_folder_counts, bookmarks_in_folder, init_browser_db, BrowserEntry};
+use crate::models::plugin::is_web_url;
+use crate::services::bookmark_folders::{build_folder_tree, check_tab_count, BookmarkFolder};
+use std::collections::HashSet;
+use tauri::AppHandle;
+
+/// The folder tree of cached bookmarks across browsers, with counts
+#[tauri::command]
+pub fn get_bookmark_folders(handle: AppHandle) -> Result<Vec<BookmarkFolder>, String> {
+    let conn = init_browser_db(&handle).map_err(|e| format!("DB error: {}", e))?;
+    let counts = bookmark_folder_counts(&conn).map_err(|e| format!("Failed to read bookmark folders: {}", e))?;
+    Ok(build_folder_tree(&counts))
+}
+
+/// Bookmarks in a folder path from `get_bookmark_folders`, including its
+/// subfolders when `recursive`
+#[tauri::command]
+pub fn get_bookmarks_in_folder(
+    handle: AppHandle,
+    folder_path: String,
+    recursive: bool,
+) -> Result<Vec<BrowserEntry>, String> {
+    let conn = init_browser_db(&handle).map_err(|e| format!("DB error: {}", e))?;
+    bookmarks_in_folder(&conn, &folder_path, recursive).map_err(|e| format!("Failed to read bookmarks: {}", e))
+}
+
+/// Open every bookmark directly in a folder in the default browser, each
+/// page once. More than ten tabs need `confirmed`. Returns how many were
+/// opened.
+#[tauri::command]
+pub fn open_bookmark_folder(handle: AppHandle, folder_path: String, confirmed: bool) -> Result<usize, String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let conn = init_browser_db(&handle).map_err(|e| format!("DB error: {}", e))?;
+    let bookmarks =
+        bookmarks_in_folder(&conn, &folder_path, false).map_err(|e| format!("Failed to read bookmarks: {}", e))?;
+
+    // The same page can be bookmarked in several browsers. Bookmarklets and
+    // file URLs aren't opened as tabs.
+    let mut seen = HashSet::new();
+    let urls: Vec<String> = bookmarks
+        .into_iter()
+        .map(|bookmark| bookmark.url)
+        .filter(|url| is_web_url(url) && seen.insert(url.clone()))
+        .collect();
+    if urls.is_empty() {
+        return Err(format!("No bookmarks in {}", folder_path));
+    }
+    check_tab_count(urls.len(), confirmed)?;
+
+    for url in &urls {
+        handle
+            .opener()
+            .open_url(url, None::<&str>)
+            .map_err(|e| format!("Failed to open {}: {}", url, e))?;
+    }
+    Ok(urls.len())
+}
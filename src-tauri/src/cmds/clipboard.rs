@@ -3,18 +3,24 @@
  * Tauri commands for clipboard history management
  */
 
+use crate::db::clipboard::{clipboard_cursor, open_clipboard_db, set_clipboard_pinned, sync_clipboard_items};
 use crate::models::clipboard::*;
+use crate::models::pagination::{Page, PageCursor};
+use crate::services::app_paths;
+use crate::services::analytics::{record_usage, UsageKind};
+use crate::services::clipboard_images::remove_item_images;
+use crate::services::clipboard_search::{self, ClipboardMatch, ClipboardSearchError, ClipboardSearchOptions};
+use crate::services::clipboard_watcher::ClipboardWatcher;
+use crate::services::privacy::PrivacyMode;
 use arboard::Clipboard;
 use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use rusqlite::Connection;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
 
 /// Get clipboard history directory
 fn get_clipboard_dir(handle: &AppHandle) -> Result<PathBuf, String> {
-    handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))
+    app_paths::data_dir(handle)
         .map(|dir| dir.join("clipboard"))
 }
 
@@ -26,38 +32,97 @@ fn ensure_clipboard_dir(handle: &AppHandle) -> Result<PathBuf, String> {
     Ok(dir)
 }
 
-/// Get clipboard history
+/// Newest clipboard items from the rotating daily files, reading only as
+/// many days as needed. Deleted items (whose item file is gone) are skipped.
+pub(crate) fn recent_clipboard_items(handle: &AppHandle, limit: usize) -> Result<Vec<ClipboardItem>, String> {
+    let clipboard_dir = get_clipboard_dir(handle)?;
+    let Ok(entries) = fs::read_dir(&clipboard_dir) else {
+        return Ok(Vec::new());
+    };
+
+    // clipboard_YYYY-MM-DD.json sorts by date
+    let mut daily_files: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("clipboard_") && name.ends_with(".json"))
+        })
+        .collect();
+    daily_files.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut items: Vec<ClipboardItem> = Vec::new();
+    for path in daily_files {
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let daily: Vec<ClipboardItem> = serde_json::from_str(&content).unwrap_or_default();
+        // Appended in order, so the newest is last
+        for item in daily.into_iter().rev() {
+            if items.len() >= limit {
+                return Ok(items);
+            }
+            if clipboard_dir.join(&item.id).exists() && !items.iter().any(|i| i.hash == item.hash) {
+                items.push(item);
+            }
+        }
+    }
+    Ok(items)
+}
+
+/// Get clipboard history, newest first. Pass a page's `next_cursor` back
+/// as `cursor` for the page after it; items copied in between don't shift
+/// it. Without a limit the page runs to the end of history. Image items
+/// carry only their thumbnail; `get_clipboard_item` has the full image.
 #[tauri::command]
 pub fn get_clipboard_history(
     handle: AppHandle,
     limit: Option<usize>,
-) -> Result<Vec<ClipboardItem>, String> {
-    let clipboard_dir = ensure_clipboard_dir(&handle)?;
-    let mut items = Vec::new();
-
-    let entries = fs::read_dir(&clipboard_dir)
-        .map_err(|e| format!("Failed to read clipboard directory: {}", e))?;
+    cursor: Option<String>,
+    privacy: State<PrivacyMode>,
+) -> Result<Page<ClipboardItem>, String> {
+    let after = PageCursor::parse(cursor.as_deref())?;
+    let mut items = stored_clipboard_items(&handle)?;
+    for item in &mut items {
+        item.image_path = None;
+    }
 
-    for entry in entries.flatten() {
-        if let Ok(content) = fs::read_to_string(entry.path()) {
-            if let Ok(item) = serde_json::from_str::<ClipboardItem>(&content) {
-                items.push(item);
-            }
-        }
+    // Sensitive items stay hidden while privacy mode is on
+    if privacy.is_active() {
+        items.retain(|item| !item.is_sensitive);
     }
 
-    // Sort by timestamp descending
-    items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    // Newest first, in cursor order
+    items.sort_by_cached_key(|item| std::cmp::Reverse(clipboard_cursor(item)));
 
-    // Apply limit
-    if let Some(limit) = limit {
-        items.truncate(limit);
-    }
+    Ok(Page::from_sorted(items, after.as_ref(), limit.unwrap_or(usize::MAX), clipboard_cursor))
+}
 
-    Ok(items)
+/// Every item in history, read from the item files, in no particular order
+fn stored_clipboard_items(handle: &AppHandle) -> Result<Vec<ClipboardItem>, String> {
+    let clipboard_dir = ensure_clipboard_dir(handle)?;
+    let entries = fs::read_dir(&clipboard_dir)
+        .map_err(|e| format!("Failed to read clipboard directory: {}", e))?;
+
+    Ok(entries
+        .flatten()
+        .filter_map(|entry| fs::read_to_string(entry.path()).ok())
+        .filter_map(|content| serde_json::from_str::<ClipboardItem>(&content).ok())
+        .collect())
+}
+
+/// The search database, brought in line with the item files, which are
+/// what history is kept in
+fn synced_clipboard_db(handle: &AppHandle) -> Result<Connection, String> {
+    let items = stored_clipboard_items(handle)?;
+    let mut conn = open_clipboard_db(Path::new(&get_clipboard_db_path(handle)?))
+        .map_err(|e| format!("Failed to open clipboard database: {}", e))?;
+    sync_clipboard_items(&mut conn, &items).map_err(|e| format!("Failed to index clipboard history: {}", e))?;
+    Ok(conn)
 }
 
-/// Get a specific clipboard item
+/// Get a specific clipboard item, with the full image path for images
 #[tauri::command]
 pub fn get_clipboard_item(
     handle: AppHandle,
@@ -79,7 +144,9 @@ pub fn paste_clipboard_item(
     handle: AppHandle,
     id: String,
 ) -> Result<(), String> {
-    let item = get_clipboard_item(handle, id)?;
+    let item = get_clipboard_item(handle.clone(), id)?;
+    // Only the content type is recorded, never the clipboard content itself
+    let usage_subject = format!("{:?}", item.content_type).to_lowercase();
 
     // Use arboard to write content to system clipboard
     let mut clipboard = Clipboard::new()
@@ -110,6 +177,8 @@ pub fn paste_clipboard_item(
         }
     }
 
+    record_usage(&handle, UsageKind::Clipboard, &usage_subject);
+
     Ok(())
 }
 
@@ -122,11 +191,21 @@ pub fn delete_clipboard_item(
     let clipboard_dir = get_clipboard_dir(&handle)?;
     let item_path = clipboard_dir.join(&id);
 
+    // Images go with the item
+    if let Ok(item) = get_clipboard_item(handle.clone(), id.clone()) {
+        remove_item_images(&item);
+    }
+
     if item_path.exists() {
         fs::remove_file(&item_path)
             .map_err(|e| format!("Failed to delete clipboard item: {}", e))?;
     }
 
+    // Otherwise the watcher would take copying it again for a duplicate
+    if let Some(watcher) = handle.try_state::<ClipboardWatcher>() {
+        watcher.delete_item(&id)?;
+    }
+
     Ok(())
 }
 
@@ -135,8 +214,11 @@ pub fn delete_clipboard_item(
 pub fn clear_clipboard_history(
     handle: AppHandle,
 ) -> Result<(), String> {
-    let clipboard_dir = get_clipboard_dir(&handle)?;
+    if let Some(watcher) = handle.try_state::<ClipboardWatcher>() {
+        return watcher.clear();
+    }
 
+    let clipboard_dir = get_clipboard_dir(&handle)?;
     if clipboard_dir.exists() {
         fs::remove_dir_all(&clipboard_dir)
             .map_err(|e| format!("Failed to clear clipboard history: {}", e))?;
@@ -164,72 +246,46 @@ pub fn set_clipboard_settings(
     Ok(())
 }
 
-/// Search clipboard history
+/// Search clipboard history, newest first. The query can hold filters
+/// such as `type:image`, `app:<name>` or `from:yesterday`, and `options`
+/// more; see `services::clipboard_search`. Pass a page's `next_cursor`
+/// back as `cursor` for the page after it.
 #[tauri::command]
 pub fn search_clipboard(
     handle: AppHandle,
     query: String,
     limit: usize,
-) -> Result<Vec<ClipboardItem>, String> {
-    let clipboard_db = get_clipboard_db_path(&handle)?;
-
-    // Open database
-    let conn = rusqlite::Connection::open(&clipboard_db)
-        .map_err(|e| format!("Failed to open clipboard database: {}", e))?;
-
-    // Build search query
-    let search_pattern = format!("%{}%", query);
-
-    let mut stmt = conn
-        .prepare(
-            "
-            SELECT id, content_type, text, timestamp, is_sensitive
-            FROM clipboard_history
-            WHERE text LIKE ?1
-            ORDER BY timestamp DESC
-            LIMIT ?2
-            ",
-        )
-        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
-
-    let items: Vec<ClipboardItem> = stmt
-        .query_map(
-            rusqlite::params![search_pattern, limit as i64],
-            |row| {
-                let content_type_str: String = row.get(1)?;
-                let content_type = match content_type_str.as_str() {
-                    "Text" => ClipboardContentType::Text,
-                    "Image" => ClipboardContentType::Image,
-                    "Html" => ClipboardContentType::Html,
-                    "File" => ClipboardContentType::File,
-                    _ => ClipboardContentType::Text,
-                };
-
-                Ok(ClipboardItem {
-                    id: row.get(0)?,
-                    content_type,
-                    text: row.get(2)?,
-                    image_path: None,
-                    hash: String::new(),
-                    timestamp: row.get(3)?,
-                    is_sensitive: row.get(4).unwrap_or(false),
-                    app_source: None,
-                })
-            },
-        )
-        .map_err(|e| format!("Failed to query clipboard: {}", e))?
-        .filter_map(|row| row.ok())
-        .collect();
+    cursor: Option<String>,
+    options: Option<ClipboardSearchOptions>,
+    privacy: State<PrivacyMode>,
+) -> Result<Page<ClipboardMatch>, ClipboardSearchError> {
+    let after = PageCursor::parse(cursor.as_deref())?;
+    let conn = synced_clipboard_db(&handle)?;
+
+    clipboard_search::search_clipboard(
+        &conn,
+        &query,
+        &options.unwrap_or_default(),
+        after.as_ref(),
+        limit,
+        privacy.is_active(),
+    )
+}
 
-    Ok(items)
+/// Pin or unpin a clipboard item, for `is:pinned` searches
+#[tauri::command]
+pub fn set_clipboard_item_pinned(handle: AppHandle, id: String, pinned: bool) -> Result<(), String> {
+    let conn = synced_clipboard_db(&handle)?;
+    match set_clipboard_pinned(&conn, &id, pinned) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(format!("Clipboard item not found: {}", id)),
+        Err(e) => Err(format!("Failed to pin clipboard item: {}", e)),
+    }
 }
 
 /// Get clipboard database path
 fn get_clipboard_db_path(handle: &AppHandle) -> Result<String, String> {
-    let data_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))?;
+    let data_dir = app_paths::data_dir(handle)?;
 
     let db_dir = data_dir.join("clipboard");
     std::fs::create_dir_all(&db_dir)
@@ -0,0 +1,31 @@
+/**
+ * Connectivity Commands
+ * Report whether network features are available and let users force
+ * offline mode. Changes are pushed as `connectivity:changed` events.
+ */
+
+use crate::cmds::settings::modify_settings;
+use crate::services::connectivity::{self, ConnectivityStatus, PROBE_URL};
+use tauri::AppHandle;
+
+/// Current online/offline state
+#[tauri::command]
+pub fn get_connectivity_status() -> ConnectivityStatus {
+    connectivity::monitor().status()
+}
+
+/// Turn offline mode on or off. The choice is saved; turning it off checks
+/// the connection right away instead of waiting for the next probe.
+#[tauri::command]
+pub fn set_offline_mode(handle: AppHandle, forced: bool) -> Result<ConnectivityStatus, String> {
+    // Applies the setting to the connectivity monitor
+    modify_settings(&handle, |settings| {
+        settings.offline_mode = forced;
+        Ok::<_, String>(())
+    })?;
+
+    if !forced {
+        std::thread::spawn(|| connectivity::probe(connectivity::monitor(), PROBE_URL));
+    }
+    Ok(connectivity::monitor().status())
+}
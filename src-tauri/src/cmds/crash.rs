@@ -0,0 +1,33 @@
+/**
+ * Crash Report Commands
+ * List, read and delete locally saved crash reports
+ */
+
+use crate::services::app_paths;
+use crate::services::crash_reporter::{self, CrashReport, CrashReportSummary};
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+/// Get crash report directory
+fn get_crash_dir(handle: &AppHandle) -> Result<PathBuf, String> {
+    app_paths::data_dir(handle)
+        .map(|dir| crash_reporter::crash_dir(&dir))
+}
+
+/// List saved crash reports, newest first
+#[tauri::command]
+pub fn list_crash_reports(handle: AppHandle) -> Result<Vec<CrashReportSummary>, String> {
+    Ok(crash_reporter::list_reports(&get_crash_dir(&handle)?))
+}
+
+/// Get a full crash report
+#[tauri::command]
+pub fn get_crash_report(handle: AppHandle, id: String) -> Result<CrashReport, String> {
+    crash_reporter::read_report(&get_crash_dir(&handle)?, &id)
+}
+
+/// Delete a crash report
+#[tauri::command]
+pub fn delete_crash_report(handle: AppHandle, id: String) -> Result<(), String> {
+    crash_reporter::delete_report(&get_crash_dir(&handle)?, &id)
+}
@@ -0,0 +1,50 @@
+/**
+ * Data Access Commands
+ * Which browser profiles and indexed folders the app can read, and links
+ * to the System Settings panes that grant access
+ */
+
+use crate::cmds::search::SearchState;
+use crate::services::browser_reader::{BrowserReader, BrowserReaderConfig};
+use crate::services::data_access::{DataSourceStatus, PrivacyPane};
+use std::path::Path;
+use tauri::{AppHandle, State};
+
+/// Access to each enabled browser's data and each indexed folder
+#[tauri::command]
+pub fn get_data_access_status(state: State<SearchState>) -> Result<Vec<DataSourceStatus>, String> {
+    let settings = state.settings()?;
+    let reader = BrowserReader::new(BrowserReaderConfig::from_settings(&settings));
+
+    let mut statuses = Vec::new();
+    for browser in &BrowserReaderConfig::default().enabled_browsers {
+        // Browsers this platform doesn't have aren't sources
+        let Ok(path) = reader.data_location(browser) else {
+            continue;
+        };
+        let source = format!("{:?}", browser).to_lowercase();
+        statuses.push(DataSourceStatus::probe(&source, &path, PrivacyPane::FullDiskAccess));
+    }
+    for root in &settings.file_index_paths {
+        statuses.push(DataSourceStatus::probe(
+            "index_root",
+            Path::new(root),
+            PrivacyPane::FilesAndFolders,
+        ));
+    }
+    Ok(statuses)
+}
+
+/// Open a privacy pane of System Settings (macOS only)
+#[tauri::command]
+pub fn open_privacy_settings(handle: AppHandle, pane: PrivacyPane) -> Result<(), String> {
+    if !cfg!(target_os = "macos") {
+        return Err("Privacy settings panes are only available on macOS".to_string());
+    }
+
+    use tauri_plugin_opener::OpenerExt;
+    handle
+        .opener()
+        .open_url(pane.settings_url(), None::<&str>)
+        .map_err(|e| format!("Failed to open System Settings: {}", e))
+}
@@ -0,0 +1,85 @@
+/**
+ * Data Location Commands
+ * Move the app's databases, plugins and state files to another folder
+ */
+
+use crate::cmds::search::{start_file_indexer, stop_file_indexer, SearchState};
+use crate::cmds::settings::modify_settings;
+use crate::services::app_paths::{self, DataLocations, MigrationReport};
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::search::cache::invalidate_search_cache;
+use crate::services::task_supervisor::TaskSupervisor;
+use crate::types::events;
+use crate::utils::run_blocking;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+
+/// Move the data dir to `new_path`, an existing, empty and writable folder
+/// outside it. Config files such as settings.json stay where they are. The
+/// file indexer and database writers are paused throughout and
+/// `data-location:progress` reports each step. When a step fails the data
+/// stays where it was. Services that opened their files at startup keep
+/// them until the app restarts, so the UI should follow up with
+/// `restart_app`.
+#[tauri::command]
+pub async fn migrate_data_location(handle: AppHandle, new_path: String) -> Result<MigrationReport, String> {
+    let from = app_paths::data_dir(&handle)?;
+    let to = PathBuf::from(new_path);
+    app_paths::validate_data_location(&to, &from)?;
+    let config_dir = handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?;
+
+    let indexing = handle
+        .state::<SearchState>()
+        .file_indexer
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .is_some();
+    stop_file_indexer(handle.state::<SearchState>(), handle.state::<TaskSupervisor>())?;
+
+    let guard = handle.state::<MaintenanceGuard>().inner().clone();
+    let app = handle.clone();
+    let report = run_blocking(move || {
+        // Pauses the browser reader and any other database writers
+        let _exclusive = guard.exclusive()?;
+        app_paths::migrate_data_dir(
+            &from,
+            &to,
+            &app_paths::config_files(&config_dir),
+            |progress| {
+                let _ = events::emit(&app, progress);
+            },
+            || {
+                let new_root = to.to_string_lossy().into_owned();
+                modify_settings(&app, |settings| {
+                    settings.data_dir = Some(new_root);
+                    Ok::<_, String>(())
+                })?;
+                if let Some(locations) = app.try_state::<DataLocations>() {
+                    locations.set_data_dir(Some(to.clone()));
+                }
+                Ok(())
+            },
+        )
+    })
+    .await;
+
+    // Whether the data moved or stayed, indexing carries on where it is now
+    if indexing {
+        if let Err(e) = start_file_indexer(
+            handle.clone(),
+            handle.state::<SearchState>(),
+            handle.state::<TaskSupervisor>(),
+            None,
+        ) {
+            eprintln!("[DataLocation] Failed to restart the file indexer: {}", e);
+        }
+    }
+    let report = report?;
+
+    invalidate_search_cache(&handle);
+    println!("[DataLocation] Moved {} files ({} bytes) to {}", report.files, report.bytes, report.to);
+    Ok(report)
+}
@@ -0,0 +1,108 @@
+/**
+ * Data Reset Commands
+ * Selectively reset search indexes, clipboard history, plugins, settings
+ * and caches back to a fresh-install state
+ */
+
+use crate::cmds::search::{start_file_indexer, stop_file_indexer, SearchState};
+use crate::cmds::settings::reset_settings;
+use crate::services::backup::BackupPaths;
+use crate::services::data_reset::{self, ResetComponent, ResetReport};
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::plugin_sandbox::PluginSandbox;
+use crate::services::search::cache::invalidate_search_cache;
+use crate::services::task_supervisor::TaskSupervisor;
+use crate::types::events;
+use crate::utils::run_blocking;
+use tauri::{AppHandle, Manager};
+
+fn reset_paths(handle: &AppHandle) -> Result<BackupPaths, String> {
+    Ok(BackupPaths {
+        config_dir: handle
+            .path()
+            .app_config_dir()
+            .map_err(|e| format!("Failed to get config dir: {}", e))?,
+        data_dir: crate::db::ensure_data_dir(handle)?,
+    })
+}
+
+/// Stop the file indexer so it doesn't write into a database being
+/// deleted. Returns whether it was running.
+fn stop_indexer(handle: &AppHandle) -> Result<bool, String> {
+    let running = handle
+        .state::<SearchState>()
+        .file_indexer
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .is_some();
+    stop_file_indexer(handle.state::<SearchState>(), handle.state::<TaskSupervisor>())?;
+    Ok(running)
+}
+
+/// Start the file indexer stopped for the reset again, on the saved index
+/// paths. Failures are only logged; the reset itself is done.
+fn restart_indexer(handle: &AppHandle) {
+    if let Err(e) = start_file_indexer(
+        handle.clone(),
+        handle.state::<SearchState>(),
+        handle.state::<TaskSupervisor>(),
+        None,
+    ) {
+        eprintln!("[DataReset] Failed to restart the file indexer: {}", e);
+    }
+}
+
+/// Reset the selected components. `confirm_token` is the text the user
+/// typed to confirm and must match `RESET`. Emits `app-data:reset` with
+/// what was removed.
+#[tauri::command]
+pub async fn reset_app_data(
+    handle: AppHandle,
+    components: Vec<ResetComponent>,
+    confirm_token: String,
+) -> Result<ResetReport, String> {
+    data_reset::check_confirm_token(&confirm_token)?;
+    let paths = reset_paths(&handle)?;
+
+    let indexing = components.contains(&ResetComponent::SearchIndexes) && stop_indexer(&handle)?;
+
+    let guard = handle.state::<MaintenanceGuard>().inner().clone();
+    let to_reset = components.clone();
+    let report = run_blocking(move || {
+        // Pauses the browser reader and any other database writers
+        let _exclusive = guard.exclusive()?;
+        data_reset::reset_components(&paths, &to_reset)
+    })
+    .await;
+    let report = match report {
+        Ok(report) => report,
+        Err(e) => {
+            if indexing {
+                restart_indexer(&handle);
+            }
+            return Err(e);
+        }
+    };
+
+    if components.contains(&ResetComponent::Plugins) {
+        let sandbox = handle.state::<PluginSandbox>();
+        for plugin_id in sandbox.get_registered_plugins() {
+            let _ = sandbox.unregister_plugin(&plugin_id);
+        }
+    }
+    let settings = if components.contains(&ResetComponent::Settings) {
+        reset_settings(handle.clone()).map(drop)
+    } else {
+        Ok(())
+    };
+
+    // After the settings, so a reset of both indexes the default paths
+    if indexing {
+        restart_indexer(&handle);
+    }
+    settings?;
+    invalidate_search_cache(&handle);
+
+    let _ = events::emit(&handle, &report);
+    Ok(report)
+}
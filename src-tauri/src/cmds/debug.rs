@@ -3,10 +3,11 @@
  * Commands for debugging and logging
  */
 
+use crate::services::app_paths;
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 
 // ============================================================================
 // Constants
@@ -21,10 +22,7 @@ const DEBUG_LOG_FILE: &str = "debug.log";
 
 /// Get debug log file path in app data directory
 fn get_debug_log_path(handle: &AppHandle) -> Result<PathBuf, String> {
-    handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))
+    app_paths::data_dir(handle)
         .map(|dir| dir.join(DEBUG_LOG_FILE))
 }
 
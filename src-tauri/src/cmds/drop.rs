@@ -0,0 +1,151 @@
+/**
+ * Drop Commands
+ * Files dropped on the main window: find the actions that can take them and
+ * run the one the user picks
+ */
+
+use crate::cmds::plugins::{get_plugin_enabled_state, installed_plugin_manifest, list_plugins};
+use crate::cmds::startup::ensure_stage;
+use crate::services::drop_actions::{self, DropAction, DropActionKind, DroppedItem, DroppedPaths};
+use crate::services::plugin_sandbox::PluginSandbox;
+use crate::services::startup::StartupStage;
+use crate::types::events::{self, DropActionsAvailable};
+use arboard::Clipboard;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// What running a drop action did
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DropOutcome {
+    /// The paths are on the clipboard
+    Copied,
+    /// The paths were moved; these are their new locations
+    Moved { paths: Vec<String> },
+    /// Run this query to hand the paths to the plugin's trigger
+    Dispatch { query: String },
+}
+
+/// Drop actions of the enabled plugins that take `items` and may read them
+fn plugin_actions<R: Runtime>(handle: &AppHandle<R>, items: &[DroppedItem]) -> Result<Vec<DropAction>, String> {
+    // Read grants aren't known until the sandbox is restored; the built-in
+    // actions are offered meanwhile
+    if ensure_stage(handle, StartupStage::Sandbox).is_err() {
+        return Ok(Vec::new());
+    }
+    let sandbox = handle.state::<PluginSandbox>();
+
+    let mut actions: Vec<DropAction> = list_plugins(handle, false)?
+        .into_iter()
+        .filter(|plugin| plugin.enabled)
+        .filter_map(|plugin| {
+            let manifest = installed_plugin_manifest(handle, &plugin.id).ok()?;
+            drop_actions::plugin_action(&plugin.id, &plugin.name, &manifest, items)
+        })
+        .filter(|action| match &action.kind {
+            DropActionKind::Plugin { plugin_id, .. } => {
+                drop_actions::check_read_permission(&sandbox, plugin_id).is_ok()
+            }
+            _ => true,
+        })
+        .collect();
+    actions.sort_by_key(|action| action.title.to_lowercase());
+    Ok(actions)
+}
+
+/// Files dropped on the main window. Finding plugin actions can wait on
+/// startup, so it happens off the event loop.
+pub fn files_dropped<R: Runtime>(handle: &AppHandle<R>, paths: Vec<PathBuf>) {
+    let handle = handle.clone();
+    let paths = paths.iter().map(|path| path.to_string_lossy().to_string()).collect();
+    std::thread::spawn(move || {
+        if let Err(e) = handle_dropped_files(handle, paths) {
+            eprintln!("[Drop] {}", e);
+        }
+    });
+}
+
+/// Classify dropped paths and announce the actions that can take them on
+/// `drop:actions-available`. Paths that no longer exist are left out.
+#[tauri::command]
+pub fn handle_dropped_files<R: Runtime>(
+    handle: AppHandle<R>,
+    paths: Vec<String>,
+) -> Result<DropActionsAvailable, String> {
+    let items: Vec<DroppedItem> = paths
+        .iter()
+        .map(Path::new)
+        .filter(|path| path.exists())
+        .map(drop_actions::describe)
+        .collect();
+    if items.is_empty() {
+        return Err("None of the dropped files exist".to_string());
+    }
+
+    if let Some(dropped) = handle.try_state::<DroppedPaths>() {
+        dropped.replace(&items);
+    }
+
+    let mut actions = drop_actions::builtin_actions();
+    actions.extend(plugin_actions(&handle, &items)?);
+
+    let available = DropActionsAvailable { items, actions };
+    events::emit(&handle, available.clone()).map_err(|e| e.to_string())?;
+    Ok(available)
+}
+
+/// Run the drop action `action_id` on `paths`, which must all come from the
+/// latest drop. Moving needs `destination`; a plugin action checks again
+/// that the plugin may read files, then returns the query that dispatches
+/// them to its trigger as one batch.
+#[tauri::command]
+pub fn run_drop_action<R: Runtime>(
+    handle: AppHandle<R>,
+    action_id: String,
+    paths: Vec<String>,
+    destination: Option<String>,
+) -> Result<DropOutcome, String> {
+    if paths.is_empty() {
+        return Err("No files to act on".to_string());
+    }
+    handle
+        .try_state::<DroppedPaths>()
+        .ok_or("Nothing was dropped")?
+        .check(&paths)?;
+
+    match action_id.as_str() {
+        "copy_path" => {
+            let mut clipboard = Clipboard::new()
+                .map_err(|e| format!("Failed to access system clipboard: {}", e))?;
+            clipboard.set_text(paths.join("\n"))
+                .map_err(|e| format!("Failed to set clipboard text: {}", e))?;
+            Ok(DropOutcome::Copied)
+        }
+        "move_to_folder" => {
+            let destination = destination.ok_or("Pick a folder to move the files to")?;
+            let paths = drop_actions::move_to_folder(&paths, Path::new(&destination))?;
+            Ok(DropOutcome::Moved { paths })
+        }
+        id => {
+            let plugin_id = id
+                .strip_prefix("plugin:")
+                .ok_or_else(|| format!("Unknown drop action: {}", id))?;
+            if !get_plugin_enabled_state(&handle, plugin_id)? {
+                return Err(format!("Plugin {} is disabled", plugin_id));
+            }
+            let items: Vec<DroppedItem> = paths.iter().map(|path| drop_actions::describe(Path::new(path))).collect();
+            let manifest = installed_plugin_manifest(&handle, plugin_id)?;
+            let action = drop_actions::plugin_action(plugin_id, &manifest.name, &manifest, &items)
+                .ok_or_else(|| format!("Plugin {} doesn't take these files", plugin_id))?;
+
+            ensure_stage(&handle, StartupStage::Sandbox)?;
+            drop_actions::check_read_permission(&handle.state::<PluginSandbox>(), plugin_id)?;
+
+            let DropActionKind::Plugin { trigger, .. } = action.kind else {
+                return Err(format!("Unknown drop action: {}", id));
+            };
+            Ok(DropOutcome::Dispatch { query: drop_actions::dispatch_query(&trigger, &paths) })
+        }
+    }
+}
@@ -0,0 +1,168 @@
+/**
+ * File Index Path Commands
+ * Manage the folders covered by the file index from the settings screen.
+ * Each change updates the saved `file_index_paths` setting and the running
+ * indexer under the indexer lock, so the two never drift.
+ */
+
+use crate::cmds::search::SearchState;
+use crate::cmds::settings::{load_settings, modify_settings};
+use crate::db::files::{delete_files_under, init_files_db, path_index_stats, PathIndexStats};
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::file_indexer::{check_new_index_path, FileIndexer, IndexerConfig};
+use crate::services::path_display::{breadcrumbs, Breadcrumb};
+use crate::services::privacy::PrivacyMode;
+use crate::services::search::cache::invalidate_search_cache;
+use crate::utils::run_blocking;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+/// An indexed folder and what it contributes to the index
+#[derive(Debug, Serialize)]
+pub struct IndexPathInfo {
+    pub path: String,
+    /// False when the folder was moved or deleted after being added
+    pub exists: bool,
+    #[serde(flatten)]
+    pub stats: PathIndexStats,
+}
+
+/// Index `path` with the current settings on the blocking pool. Returns the
+/// number of files added.
+async fn index_subtree(handle: &AppHandle, path: &str) -> Result<usize, String> {
+    let settings = handle.state::<SearchState>().settings()?;
+    let indexer = FileIndexer::new(IndexerConfig {
+        read_tags: settings.index_file_tags,
+        ..Default::default()
+    })
+    .with_privacy(handle.state::<PrivacyMode>().inner().clone())
+    .with_maintenance(handle.state::<MaintenanceGuard>().inner().clone());
+
+    let handle = handle.clone();
+    let paths = vec![path.to_string()];
+    run_blocking(move || indexer.index_paths(&handle, &paths)).await
+}
+
+/// Delete the rows of files under `path`. Returns the number removed.
+fn delete_rows(handle: &AppHandle, path: &str) -> Result<usize, String> {
+    let guard = handle.state::<MaintenanceGuard>();
+    let _shared = guard
+        .try_shared()
+        .ok_or("The file index is busy with database maintenance")?;
+
+    let conn = init_files_db(handle).map_err(|e| format!("DB error: {}", e))?;
+    let deleted = delete_files_under(&conn, path).map_err(|e| format!("DB error: {}", e))?;
+    invalidate_search_cache(handle);
+    Ok(deleted)
+}
+
+/// Indexed folders with their file count, size and last scan time
+#[tauri::command]
+pub fn list_index_paths(handle: AppHandle) -> Result<Vec<IndexPathInfo>, String> {
+    let settings = load_settings(&handle)?;
+    let conn = init_files_db(&handle).map_err(|e| format!("DB error: {}", e))?;
+
+    settings
+        .file_index_paths
+        .into_iter()
+        .map(|path| -> Result<IndexPathInfo, String> {
+            let stats = path_index_stats(&conn, &path).map_err(|e| format!("DB error: {}", e))?;
+            Ok(IndexPathInfo {
+                exists: Path::new(&path).is_dir(),
+                path,
+                stats,
+            })
+        })
+        .collect()
+}
+
+/// Add a folder to the index and index it right away. Folders inside an
+/// indexed folder are rejected; indexed folders inside the new one are
+/// merged into it. Returns the number of files added.
+#[tauri::command]
+pub async fn add_index_path(handle: AppHandle, path: String) -> Result<usize, String> {
+    let canonical = std::fs::canonicalize(&path).map_err(|e| format!("Cannot index {}: {}", path, e))?;
+    if !canonical.is_dir() {
+        return Err(format!("{} is not a folder", path));
+    }
+    let path = canonical.to_string_lossy().to_string();
+
+    {
+        let state = handle.state::<SearchState>();
+        let indexer = state.file_indexer.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+        let absorbed = modify_settings(&handle, |settings| {
+            let absorbed = check_new_index_path(&settings.file_index_paths, &canonical)?;
+            settings.file_index_paths.retain(|p| !absorbed.contains(p));
+            settings.file_index_paths.push(path.clone());
+            Ok::<_, String>(absorbed)
+        })?;
+
+        if let Some(indexer) = indexer.as_ref() {
+            for child in &absorbed {
+                indexer.remove_path(Path::new(child))?;
+            }
+            indexer.add_path(&canonical)?;
+        }
+    }
+
+    index_subtree(&handle, &path).await
+}
+
+/// Remove a folder from the index and delete its files from it. Returns
+/// the number of files removed.
+#[tauri::command]
+pub fn remove_index_path(handle: AppHandle, path: String) -> Result<usize, String> {
+    {
+        let state = handle.state::<SearchState>();
+        let indexer = state.file_indexer.lock().map_err(|e| format!("Lock error: {}", e))?;
+
+        modify_settings(&handle, |settings| {
+            let before = settings.file_index_paths.len();
+            settings.file_index_paths.retain(|p| Path::new(p) != Path::new(&path));
+            if settings.file_index_paths.len() == before {
+                return Err(format!("{} is not an indexed folder", path));
+            }
+            Ok(())
+        })?;
+
+        if let Some(indexer) = indexer.as_ref() {
+            indexer.remove_path(Path::new(&path))?;
+        }
+    }
+
+    delete_rows(&handle, &path)
+}
+
+/// Drop and re-index everything under `path`, which must be an indexed
+/// folder or inside one. Returns the number of files indexed.
+#[tauri::command]
+pub async fn reindex_path(handle: AppHandle, path: String) -> Result<usize, String> {
+    let settings = load_settings(&handle)?;
+    if !settings.file_index_paths.iter().any(|root| Path::new(&path).starts_with(root)) {
+        return Err(format!("{} is not in an indexed folder", path));
+    }
+
+    delete_rows(&handle, &path)?;
+    {
+        let state = handle.state::<SearchState>();
+        let indexer = state.file_indexer.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if let Some(indexer) = indexer.as_ref() {
+            indexer.forget_path(Path::new(&path))?;
+        }
+    }
+
+    index_subtree(&handle, &path).await
+}
+
+/// Breadcrumbs from the home folder or root down to `path`, each marked
+/// browsable when it's inside an indexed folder and so can be searched
+/// within
+#[tauri::command]
+pub fn get_path_breadcrumbs(handle: AppHandle, path: String) -> Result<Vec<Breadcrumb>, String> {
+    let settings = load_settings(&handle)?;
+    let roots: Vec<PathBuf> = settings.file_index_paths.iter().map(PathBuf::from).collect();
+    let home = handle.path().home_dir().ok();
+    Ok(breadcrumbs(Path::new(&path), home.as_deref(), &roots))
+}
@@ -1,8 +1,14 @@
 //! File System Commands
 //! Tauri commands for file system operations
 
+use crate::db::files::{init_files_db, set_placeholder};
+use crate::services::cloud_files::{ensure_downloaded, SystemPlaceholders};
+use crate::services::file_preview::{self, FilePreview, PreviewError};
+use crate::services::search::cache::invalidate_search_cache;
+use crate::utils::run_blocking;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
 
 /// Read a text file
 #[tauri::command]
@@ -43,3 +49,35 @@ pub fn write_file(path: String, content: String) -> Result<(), String> {
     fs::write(&path, content)
         .map_err(|e| format!("Failed to write file: {}", e))
 }
+
+/// Metadata and the start of a file's contents for the preview pane. Runs
+/// off the main thread; repeated calls for an unchanged file are cached.
+#[tauri::command]
+pub async fn get_file_preview(path: String) -> Result<FilePreview, PreviewError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        file_preview::preview_file(&PathBuf::from(path), file_preview::cache(), &SystemPlaceholders)
+    })
+    .await
+    .map_err(|e| PreviewError::Failed { message: e.to_string() })?
+}
+
+/// Open a file with its default app, first downloading it if it's a cloud
+/// placeholder. Waits for the download, which can take a while.
+#[tauri::command]
+pub async fn download_and_open_file(handle: AppHandle, path: String) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let download_path = PathBuf::from(&path);
+    let downloaded = run_blocking(move || ensure_downloaded(&SystemPlaceholders, &download_path)).await?;
+    if downloaded {
+        if let Ok(conn) = init_files_db(&handle) {
+            let _ = set_placeholder(&conn, &path, false);
+            invalidate_search_cache(&handle);
+        }
+    }
+
+    handle
+        .opener()
+        .open_path(&path, None::<&str>)
+        .map_err(|e| format!("Failed to open {}: {}", path, e))
+}
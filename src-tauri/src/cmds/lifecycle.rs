@@ -0,0 +1,116 @@
+/**
+ * App Lifecycle Commands
+ * Graceful quit and restart with an ordered shutdown, and checking for a
+ * newer release
+ */
+
+use crate::cmds::search::{stop_file_indexer, SearchState};
+use crate::services::app_update::{check_for_update, AppUpdateInfo, DEFAULT_UPDATE_ENDPOINT};
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::plugin_sandbox::PluginSandbox;
+use crate::services::shutdown::{ShutdownRegistry, ShutdownReport, ShutdownStage, SHUTDOWN_DEADLINE};
+use crate::services::task_supervisor::TaskSupervisor;
+use crate::services::window_layouts::save_session_layout;
+use crate::services::windows::MAIN_WINDOW;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// How long background tasks get to wind down at shutdown. Part of
+/// `SHUTDOWN_DEADLINE`, leaving the rest to the hooks after them.
+const TASK_STOP_DEADLINE: Duration = Duration::from_secs(2);
+
+/// Register the app's shutdown hooks; called once from setup
+pub(crate) fn register_shutdown_hooks(handle: &AppHandle) {
+    let registry = handle.state::<ShutdownRegistry>();
+
+    // Tasks stuck mid-run are reported after TASK_STOP_DEADLINE and left
+    // to exit with the process
+    let app = handle.clone();
+    registry.register(ShutdownStage::StopWatchers, "background_tasks", move || {
+        match app.state::<TaskSupervisor>().stop_all(TASK_STOP_DEADLINE).as_slice() {
+            [] => Ok(()),
+            unfinished => Err(format!("Still running: {}", unfinished.join(", "))),
+        }
+    });
+
+    let app = handle.clone();
+    registry.register(ShutdownStage::StopIndexers, "file_indexer", move || {
+        stop_file_indexer(app.state::<SearchState>(), app.state::<TaskSupervisor>())
+    });
+
+    // Waits out in-flight database writes such as a browser cache refresh,
+    // and keeps new ones from starting for the rest of the shutdown
+    let app = handle.clone();
+    registry.register(ShutdownStage::StopIndexers, "db_writers", move || {
+        app.state::<MaintenanceGuard>().close()
+    });
+
+    let app = handle.clone();
+    registry.register(ShutdownStage::FlushWrites, "plugin_rollups", move || {
+        crate::cmds::plugin_performance::flush_rollups(&app)
+    });
+
+    let app = handle.clone();
+    registry.register(ShutdownStage::SaveState, "plugin_sandbox", move || {
+        app.state::<PluginSandbox>().flush_state()
+    });
+
+    let app = handle.clone();
+    registry.register(ShutdownStage::SaveState, "window_state", move || {
+        let window = app.get_webview_window(MAIN_WINDOW).ok_or("Main window not found")?;
+        save_session_layout(&app, &window)
+    });
+}
+
+/// Run the shutdown hooks, skipping saved state unless `save_state`
+async fn shut_down(handle: &AppHandle, save_state: bool) -> Result<ShutdownReport, String> {
+    let app = handle.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        app.state::<ShutdownRegistry>()
+            .run(SHUTDOWN_DEADLINE, |stage| save_state || stage != ShutdownStage::SaveState)
+    })
+    .await
+    .map_err(|e| format!("Shutdown failed: {}", e))?;
+
+    for (hook, e) in &report.failed {
+        eprintln!("[Shutdown] {} failed: {}", hook, e);
+    }
+    if report.timed_out {
+        eprintln!("[Shutdown] Deadline passed after {:?}, forcing exit", report.completed);
+    }
+    Ok(report)
+}
+
+/// Quit after an ordered shutdown. `save_state: false` skips saving state
+/// for the next launch. Exits immediately if a shutdown is already running.
+#[tauri::command]
+pub async fn quit_app(handle: AppHandle, save_state: bool) -> Result<(), String> {
+    if handle.state::<ShutdownRegistry>().is_started() {
+        std::process::exit(0);
+    }
+
+    let report = shut_down(&handle, save_state).await?;
+    if report.timed_out {
+        std::process::exit(0);
+    }
+    handle.exit(0);
+    Ok(())
+}
+
+/// Relaunch after an ordered shutdown, for settings that need a restart
+#[tauri::command]
+pub async fn restart_app(handle: AppHandle) -> Result<(), String> {
+    if !handle.state::<ShutdownRegistry>().is_started() {
+        shut_down(&handle, true).await?;
+    }
+    handle.restart()
+}
+
+/// Compare the running version with the latest published release
+#[tauri::command]
+pub async fn check_app_update(handle: AppHandle) -> Result<AppUpdateInfo, String> {
+    let endpoint = crate::cmds::settings::load_settings(&handle)?
+        .update_endpoint
+        .unwrap_or_else(|| DEFAULT_UPDATE_ENDPOINT.to_string());
+    check_for_update(&endpoint, &handle.package_info().version.to_string()).await
+}
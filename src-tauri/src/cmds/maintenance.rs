@@ -0,0 +1,143 @@
+/**
+ * Database Maintenance Commands
+ * Integrity check, vacuum and rebuild for the file index and browser cache
+ */
+
+use crate::cmds::search::SearchState;
+use crate::db::browser::open_browser_db;
+use crate::db::files::open_files_db;
+use crate::db::{db_file_size, get_browser_db_path, get_files_db_path};
+use crate::services::browser_reader::{BrowserReader, BrowserReaderConfig};
+use crate::services::db_maintenance::{
+    integrity_check, rebuild, vacuum, MaintenanceGuard, MaintenanceMode, MaintenanceReport,
+    MaintenanceTarget,
+};
+use crate::services::search::cache::invalidate_search_cache;
+use crate::types::events::{self, MaintenanceProgress};
+use crate::utils::run_blocking;
+use rusqlite::{Connection, Result as SqliteResult};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager};
+
+fn db_path(handle: &AppHandle, target: MaintenanceTarget) -> Result<PathBuf, String> {
+    match target {
+        MaintenanceTarget::Files => get_files_db_path(handle),
+        MaintenanceTarget::Browser => get_browser_db_path(handle),
+    }
+}
+
+fn open_target(target: MaintenanceTarget, path: &Path) -> SqliteResult<Connection> {
+    match target {
+        MaintenanceTarget::Files => open_files_db(path),
+        MaintenanceTarget::Browser => open_browser_db(path),
+    }
+}
+
+fn emit_progress(handle: &AppHandle, target: MaintenanceTarget, stage: &str, progress: u8) {
+    let _ = events::emit(handle, MaintenanceProgress {
+        target,
+        stage: stage.to_string(),
+        progress,
+    });
+}
+
+/// Refill a rebuilt database: the running indexer rescans, the browser cache is re-read
+fn refill(handle: &AppHandle, target: MaintenanceTarget) -> Result<(), String> {
+    match target {
+        MaintenanceTarget::Files => {
+            let state = handle.state::<SearchState>();
+            let indexer = state.file_indexer.lock().map_err(|e| format!("Lock error: {}", e))?;
+            if let Some(indexer) = indexer.as_ref() {
+                indexer.reset_indexed()?;
+            }
+            Ok(())
+        }
+        MaintenanceTarget::Browser => {
+            let settings = handle.state::<SearchState>().settings()?;
+            BrowserReader::new(BrowserReaderConfig::from_settings(&settings))
+                .update_cache(handle)
+                .map(|_| ())
+        }
+    }
+}
+
+/// Run one maintenance mode against one database while holding the
+/// exclusive maintenance guard
+pub(crate) fn run_maintenance(
+    handle: &AppHandle,
+    target: MaintenanceTarget,
+    mode: MaintenanceMode,
+) -> Result<MaintenanceReport, String> {
+    let start = std::time::Instant::now();
+    let path = db_path(handle, target)?;
+    let size_before = db_file_size(&path);
+
+    let guard = handle.state::<MaintenanceGuard>();
+    let issues = {
+        let _exclusive = guard.exclusive()?;
+
+        match mode {
+            MaintenanceMode::IntegrityCheck => match open_target(target, &path) {
+                Ok(conn) => integrity_check(&conn).unwrap_or_else(|e| vec![e.to_string()]),
+                // A database that can't even be opened is an integrity problem, not a command error
+                Err(e) => vec![e.to_string()],
+            },
+            MaintenanceMode::Vacuum => {
+                let conn = open_target(target, &path).map_err(|e| format!("DB error: {}", e))?;
+                vacuum(&conn).map_err(|e| format!("Vacuum failed: {}", e))?;
+                vec![]
+            }
+            MaintenanceMode::Rebuild => {
+                emit_progress(handle, target, "removing", 0);
+                rebuild(&path, |p| open_target(target, p))?;
+                emit_progress(handle, target, "recreated", 40);
+                vec![]
+            }
+        }
+    };
+
+    if mode == MaintenanceMode::Rebuild {
+        invalidate_search_cache(handle);
+        emit_progress(handle, target, "refilling", 60);
+        if let Err(e) = refill(handle, target) {
+            eprintln!("[DbMaintenance] Failed to refill {:?} database: {}", target, e);
+        }
+        emit_progress(handle, target, "complete", 100);
+    }
+
+    Ok(MaintenanceReport {
+        target,
+        mode,
+        issues,
+        size_before,
+        size_after: db_file_size(&path),
+        duration_ms: start.elapsed().as_millis() as u64,
+    })
+}
+
+/// Run database maintenance: `integrity_check`, `vacuum` or `rebuild`
+#[tauri::command]
+pub async fn run_db_maintenance(
+    handle: AppHandle,
+    target: MaintenanceTarget,
+    mode: MaintenanceMode,
+) -> Result<MaintenanceReport, String> {
+    run_blocking(move || run_maintenance(&handle, target, mode)).await
+}
+
+/// Check both databases after an unclean shutdown, emitting
+/// `db-maintenance:issues` for any that report problems
+pub(crate) fn check_after_unclean_shutdown(handle: AppHandle) {
+    std::thread::spawn(move || {
+        for target in [MaintenanceTarget::Files, MaintenanceTarget::Browser] {
+            match run_maintenance(&handle, target, MaintenanceMode::IntegrityCheck) {
+                Ok(report) if !report.issues.is_empty() => {
+                    eprintln!("[DbMaintenance] {:?} database has {} issue(s)", target, report.issues.len());
+                    let _ = events::emit(&handle, report);
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[DbMaintenance] Startup integrity check failed: {}", e),
+            }
+        }
+    });
+}
@@ -1,9 +1,18 @@
 //! NPM-based Marketplace Commands
 //! Tauri commands for npm-based plugin marketplace operations
 
-use crate::services::marketplace_service::MarketplaceService;
+use crate::cmds::plugins::{
+    detect_trigger_conflicts, offer_archived_settings, plugin_suppressed_triggers, quarantine_incompatible,
+    request_plugin_consents, settle_trigger_conflicts, wait_for_state_revision,
+};
+use crate::services::app_paths;
+use crate::services::marketplace_service::{package_links, read_package_links, MarketplaceError, MarketplaceService};
+use crate::services::plugin_install_meta::read_install_meta;
+use crate::services::plugin_protection::{ensure_uninstallable, is_protected};
+use crate::services::plugin_sandbox::ConsentSource;
 use crate::models::plugin::*;
-use tauri::{AppHandle, Manager};
+use crate::utils::run_blocking;
+use tauri::{AppHandle, Runtime};
 use std::sync::Mutex;
 
 // Lazy static marketplace service
@@ -22,7 +31,7 @@ pub fn marketplace_list(
     page: u32,
     page_size: u32,
     handle: AppHandle,
-) -> Result<MarketplacePluginPage, String> {
+) -> Result<MarketplacePluginPage, MarketplaceError> {
     println!("[Marketplace] Listing plugins - category: {:?}, page: {}", category, page);
 
     let service = get_marketplace_service()
@@ -41,7 +50,7 @@ pub fn marketplace_search(
     page: u32,
     page_size: u32,
     handle: AppHandle,
-) -> Result<MarketplacePluginPage, String> {
+) -> Result<MarketplacePluginPage, MarketplaceError> {
     println!("[Marketplace] Searching plugins - query: {}, category: {:?}", query, category);
 
     let service = get_marketplace_service()
@@ -52,6 +61,17 @@ pub fn marketplace_search(
     service.search_plugins(&query, category_ref, page, page_size, &handle)
 }
 
+/// Category and tag counts for a marketplace search; an empty query
+/// covers every plugin
+#[tauri::command]
+pub fn marketplace_get_facets(query: String) -> Result<MarketplaceFacets, MarketplaceError> {
+    let service = get_marketplace_service()
+        .lock()
+        .map_err(|e| format!("Failed to acquire lock: {}", e))?;
+
+    service.get_facets(&query)
+}
+
 /// Install a plugin from npm
 /// 安装后更新 package.json
 ///
@@ -60,7 +80,7 @@ pub fn marketplace_search(
 pub fn marketplace_install(
     package_name: String,
     handle: AppHandle,
-) -> Result<Plugin, String> {
+) -> Result<Plugin, MarketplaceError> {
     println!("[Marketplace] Installing plugin: {}", package_name);
 
     let service = get_marketplace_service()
@@ -68,13 +88,16 @@ pub fn marketplace_install(
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
 
     // 1. 从 npm 下载并安装
-    let plugin = service.install_plugin(&package_name, &handle)?;
+    let mut plugin = service.install_plugin(&package_name, &handle)?;
+    // Triggers colliding with installed plugins start out suppressed
+    let conflicts = detect_trigger_conflicts(&handle, Some(&plugin.id), &plugin.triggers)?;
+    settle_trigger_conflicts(&handle, &conflicts)?;
+    plugin.suppressed_triggers = plugin_suppressed_triggers(&handle, &plugin.id, &plugin.triggers)?;
+    request_plugin_consents(&handle, &plugin.id, &plugin.version, &plugin.permissions, ConsentSource::Install)?;
+    offer_archived_settings(&handle, &plugin.id);
 
     // 2. 更新 package.json
-    let plugins_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))?
+    let plugins_dir = app_paths::data_dir(&handle)?
         .join("plugins");
 
     let package_json_path = plugins_dir.join("package.json");
@@ -131,26 +154,30 @@ pub fn marketplace_install(
 /// 卸载后从 package.json 移除
 ///
 /// @param package_name - npm package name to uninstall
+/// @param purge_data - false keeps the plugin's data directory, e.g. for a reinstall
 #[tauri::command]
 pub fn marketplace_uninstall(
     package_name: String,
     handle: AppHandle,
+    purge_data: Option<bool>,
 ) -> Result<(), String> {
     println!("[Marketplace] Uninstalling plugin: {}", package_name);
 
+    let plugins_dir = app_paths::data_dir(&handle)?
+        .join("plugins");
+    ensure_uninstallable(&package_name, &plugins_dir.join("node_modules").join(&package_name))
+        .map_err(|e| e.to_string())?;
+
     let service = get_marketplace_service()
         .lock()
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
 
     // 1. 从文件系统卸载
     service.uninstall_plugin(&package_name, &handle)?;
+    crate::cmds::plugins::invalidate_cached_results(&handle, &package_name);
+    crate::cmds::plugins::forget_plugin(&handle, &package_name)?;
 
     // 2. 从 package.json 移除
-    let plugins_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))?
-        .join("plugins");
 
     let package_json_path = plugins_dir.join("package.json");
 
@@ -178,7 +205,7 @@ pub fn marketplace_uninstall(
 
     println!("[Marketplace] ✅ Plugin {} uninstalled and removed from package.json", package_name);
 
-    Ok(())
+    crate::cmds::plugins::purge_plugin_data(&handle, &package_name, purge_data)
 }
 
 /// Update a plugin using npm
@@ -188,14 +215,18 @@ pub fn marketplace_uninstall(
 pub fn marketplace_update(
     package_name: String,
     handle: AppHandle,
-) -> Result<Plugin, String> {
+) -> Result<Plugin, MarketplaceError> {
     println!("[Marketplace] Updating plugin: {}", package_name);
 
     let service = get_marketplace_service()
         .lock()
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
 
-    service.update_plugin(&package_name, &handle)
+    let plugin = service.update_plugin(&package_name, &handle)?;
+    crate::cmds::plugins::invalidate_cached_results(&handle, &plugin.id);
+    // Permissions the new version adds are asked for again
+    request_plugin_consents(&handle, &plugin.id, &plugin.version, &plugin.permissions, ConsentSource::Update)?;
+    Ok(plugin)
 }
 
 /// Check for plugin updates
@@ -203,14 +234,25 @@ pub fn marketplace_update(
 #[tauri::command]
 pub fn marketplace_check_updates(
     handle: AppHandle,
-) -> Result<Vec<PluginUpdateInfo>, String> {
+) -> Result<Vec<PluginUpdateInfo>, MarketplaceError> {
     println!("[Marketplace] Checking for plugin updates");
 
     let service = get_marketplace_service()
         .lock()
         .map_err(|e| format!("Failed to acquire lock: {}", e))?;
 
-    service.check_updates(&handle)
+    let updates = service.check_updates(&handle)?;
+
+    if !updates.is_empty() {
+        crate::cmds::notifications::notify(
+            &handle,
+            "Plugin updates available",
+            Some(&format!("{} plugin update(s) available", updates.len())),
+            crate::services::notifications::APP_SOURCE,
+        );
+    }
+
+    Ok(updates)
 }
 
 /// Get plugin details from npm registry
@@ -218,7 +260,7 @@ pub fn marketplace_check_updates(
 pub fn marketplace_get_plugin(
     package_name: String,
     handle: AppHandle,
-) -> Result<MarketplacePlugin, String> {
+) -> Result<MarketplacePlugin, MarketplaceError> {
     println!("[Marketplace] Getting plugin details: {}", package_name);
 
     let service = get_marketplace_service()
@@ -231,21 +273,34 @@ pub fn marketplace_get_plugin(
     result.plugins
         .into_iter()
         .find(|p| p.id == package_name)
-        .ok_or_else(|| format!("Plugin not found: {}", package_name))
+        .ok_or_else(|| format!("Plugin not found: {}", package_name).into())
 }
 
-/// Get installed npm plugins from package.json
-/// 读取 package.json，性能极佳（< 1ms）
+/// Get installed npm plugins from package.json. `min_revision`, a
+/// `state_revision` from `plugin_enable` or `plugin_disable`, waits briefly
+/// for that change to land, as `plugin_list` does.
 #[tauri::command]
-pub fn get_installed_plugins(handle: AppHandle) -> Result<Vec<Plugin>, String> {
+pub async fn get_installed_plugins<R: Runtime>(
+    handle: AppHandle<R>,
+    min_revision: Option<u64>,
+) -> Result<Vec<Plugin>, String> {
+    run_blocking(move || {
+        if let Some(revision) = min_revision {
+            wait_for_state_revision(&handle, revision);
+        }
+        read_installed_plugins(handle)
+    })
+    .await
+}
+
+/// Installed npm plugins from package.json
+/// 读取 package.json，性能极佳（< 1ms）
+pub(crate) fn read_installed_plugins<R: Runtime>(handle: AppHandle<R>) -> Result<Vec<Plugin>, String> {
     let start_total = std::time::Instant::now();
     println!("[Marketplace] ===== get_installed_plugins command called =====");
 
     // 1. 获取插件目录
-    let plugins_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))?
+    let plugins_dir = app_paths::data_dir(&handle)?
         .join("plugins");
 
     // 2. 确保 plugins 目录存在
@@ -342,15 +397,30 @@ pub fn get_installed_plugins(handle: AppHandle) -> Result<Vec<Plugin>, String> {
         // 从 plugin.json 构造 Plugin 对象
         // 读取插件启用状态
         let plugin_id = plugin_data["name"].as_str().unwrap_or(package_name);
-        let enabled = crate::cmds::plugins::get_plugin_enabled_state(&handle, &plugin_id.to_string())
+        let mut enabled = crate::cmds::plugins::get_plugin_enabled_state(&handle, &plugin_id.to_string())
             .unwrap_or(true); // 默认启用
+        let api_compatibility = crate::services::plugin_api::check_api_version(plugin_data["api_version"].as_str());
+
+        // Plugins written for an API this host can't run stay off
+        if enabled && !api_compatibility.is_loadable() {
+            quarantine_incompatible(&handle, plugin_id);
+            enabled = false;
+        }
 
         let entry_point = plugin_data["main"].as_str().unwrap_or("index.js");
+        let version = plugin_data["version"].as_str().unwrap_or("0.0.0");
+        let install = read_install_meta(&plugin_path, PluginSource::Marketplace, version)?;
+        let links = if is_package_json {
+            package_links(&plugin_data)
+        } else {
+            PluginLinks::from_package_json(&plugin_data).or(read_package_links(&plugin_path))
+        }
+        .web_only();
 
-        let plugin = Plugin {
+        let mut plugin = Plugin {
             id: plugin_id.to_string(),
             name: plugin_data["name"].as_str().unwrap_or(package_name).to_string(),
-            version: plugin_data["version"].as_str().unwrap_or("0.0.0").to_string(),
+            version: version.to_string(),
             description: plugin_data["description"].as_str().unwrap_or("").to_string(),
             author: plugin_data["author"].as_str().map(|s| s.to_string()),
             enabled, // 从 plugin-state.json 读取
@@ -365,6 +435,8 @@ pub fn get_installed_plugins(handle: AppHandle) -> Result<Vec<Plugin>, String> {
                     keyword: v.as_str().unwrap_or("").to_string(),
                     description: "".to_string(),
                     hotkey: None,
+                    args: Vec::new(),
+                    cache_ttl_ms: None,
                 }).collect())
                 .unwrap_or_default(),
             settings: plugin_data["settings"]
@@ -373,6 +445,11 @@ pub fn get_installed_plugins(handle: AppHandle) -> Result<Vec<Plugin>, String> {
                     (k.clone(), v.clone())
                 }).collect())
                 .unwrap_or_default(),
+            api_version: plugin_data["api_version"].as_str().map(String::from),
+            api_compatibility,
+            protected: is_protected(package_name, plugin_data["protected"].as_bool().unwrap_or(false)),
+            suppressed_triggers: Vec::new(),
+            links,
             health: PluginHealth {
                 status: PluginHealthStatus::Healthy,
                 message: None,
@@ -385,14 +462,12 @@ pub fn get_installed_plugins(handle: AppHandle) -> Result<Vec<Plugin>, String> {
                 last_execution_time: None,
                 average_execution_time: None,
             },
-            installed_at: std::fs::metadata(&plugin_path)
-                .and_then(|m| m.created())
-                .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as i64)
-                .unwrap_or(0),
+            installed_at: install.installed_at,
             // 拼接完整的入口文件路径（目录 + entry_point）
             install_path: plugin_path.join(&entry_point).to_string_lossy().to_string(),
-            source: PluginSource::Marketplace,
+            source: install.source,
         };
+        plugin.suppressed_triggers = plugin_suppressed_triggers(&handle, plugin_id, &plugin.triggers)?;
 
         println!("[Marketplace] Added plugin: {} (source: {:?}, enabled: {})", plugin.id, plugin.source, plugin.enabled);
         plugins.push(plugin);
@@ -1,13 +1,36 @@
 pub mod abbreviation;
+pub mod analytics;
 pub mod app;
+pub mod backup;
+pub mod bookmarks;
 pub mod clipboard;
+pub mod connectivity;
+pub mod crash;
+pub mod data_access;
+pub mod data_location;
+pub mod data_reset;
 pub mod debug;
+pub mod drop;
+pub mod file_index;
+pub mod maintenance;
 pub mod marketplace;
+pub mod notifications;
+pub mod onboarding;
+pub mod openers;
 pub mod performance;
+pub mod plugin_host;
+pub mod plugin_performance;
 pub mod plugins;
+pub mod privacy;
+pub mod processes;
+pub mod retention;
 pub mod search;
 pub mod search_test;
+pub mod selection;
 pub mod settings;
 pub mod shell;
+pub mod startup;
+pub mod tasks;
 pub mod files;
+pub mod lifecycle;
 pub mod window;
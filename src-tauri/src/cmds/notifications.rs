@@ -0,0 +1,70 @@
+/**
+ * Notification Commands
+ * Send notifications, query history and mute individual plugins
+ */
+
+use crate::cmds::plugins::installed_plugin_ids;
+use crate::cmds::search::SearchState;
+use crate::services::notifications::{check_source, NotificationCenter, NotificationRecord, APP_SOURCE};
+use chrono::Timelike;
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_notification::NotificationExt;
+
+/// Show a notification unless do-not-disturb or a plugin mute suppresses it.
+/// Either way it's recorded in history.
+pub(crate) fn notify<R: Runtime>(handle: &AppHandle<R>, title: &str, body: Option<&str>, source: &str) -> Option<NotificationRecord> {
+    let center = handle.try_state::<NotificationCenter>()?;
+
+    let dnd = handle
+        .try_state::<SearchState>()
+        .and_then(|state| state.settings().ok())
+        .map(|settings| settings.do_not_disturb)
+        .unwrap_or_default();
+    let now = chrono::Local::now();
+    let reason = center.suppress_reason(source, &dnd, now.hour() * 60 + now.minute());
+
+    if reason.is_none() {
+        let mut builder = handle.notification().builder().title(title);
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+        if let Err(e) = builder.show() {
+            eprintln!("[Notifications] Failed to show notification: {}", e);
+        }
+    }
+
+    Some(center.record(title, body, source, reason))
+}
+
+/// Send a notification; `source` defaults to the app itself, and otherwise
+/// must be an installed plugin
+#[tauri::command]
+pub fn send_notification(
+    handle: AppHandle,
+    title: String,
+    body: Option<String>,
+    source: Option<String>,
+) -> Result<NotificationRecord, String> {
+    let source = source.unwrap_or_else(|| APP_SOURCE.to_string());
+    if source != APP_SOURCE {
+        check_source(&source, &installed_plugin_ids(&handle)?)?;
+    }
+    notify(&handle, &title, body.as_deref(), &source)
+        .ok_or_else(|| "Notification service is not available".to_string())
+}
+
+/// Get recent notifications, newest first, including suppressed ones
+#[tauri::command]
+pub fn get_notification_history(center: State<NotificationCenter>) -> Vec<NotificationRecord> {
+    center.history()
+}
+
+/// Mute or unmute a plugin's notifications
+#[tauri::command]
+pub fn set_plugin_notifications_muted(
+    plugin_id: String,
+    muted: bool,
+    center: State<NotificationCenter>,
+) -> Result<(), String> {
+    center.set_muted(&plugin_id, muted)
+}
@@ -0,0 +1,115 @@
+/**
+ * Onboarding Commands
+ * First-run steps the UI walks through and resumes, with each step's
+ * choices applied through the regular settings commands
+ */
+
+use crate::cmds::abbreviation::{has_user_abbreviations, import_abbreviation_config, ImportOptions};
+use crate::cmds::data_access::get_data_access_status;
+use crate::cmds::search::SearchState;
+use crate::cmds::settings::{load_settings, reregister_hotkey, set_hotkey, set_setting};
+use crate::services::config_service::abbreviation_config_path;
+use crate::services::data_access::AccessState;
+use crate::services::onboarding::{onboarding_store, satisfied_steps, OnboardingStep, OnboardingView, SetupFacts};
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::{AppHandle, State};
+
+#[derive(Deserialize)]
+struct HotkeyPayload {
+    hotkey: String,
+}
+
+#[derive(Deserialize)]
+struct IndexFoldersPayload {
+    paths: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct BrowsersPayload {
+    enabled: bool,
+}
+
+#[derive(Deserialize)]
+struct AbbreviationsPayload {
+    config_json: String,
+    #[serde(default)]
+    options: Option<ImportOptions>,
+}
+
+fn parse_payload<T: serde::de::DeserializeOwned>(step: OnboardingStep, payload: Value) -> Result<T, String> {
+    serde_json::from_value(payload).map_err(|e| format!("Invalid payload for onboarding step {:?}: {}", step, e))
+}
+
+/// Onboarding progress, with steps that are already set up marked
+/// completed. The UI resumes at `current`.
+#[tauri::command]
+pub fn get_onboarding_state(handle: AppHandle, search: State<SearchState>) -> Result<OnboardingView, String> {
+    let settings = load_settings(&handle)?;
+    let facts = SetupFacts {
+        disk_access_granted: get_data_access_status(search)?
+            .iter()
+            .all(|status| status.state != AccessState::Denied),
+        has_user_abbreviations: has_user_abbreviations(&abbreviation_config_path(&handle)?),
+    };
+    let satisfied = satisfied_steps(&settings, facts);
+
+    onboarding_store(&handle)?.update(|state| {
+        state.detect(&satisfied, chrono::Utc::now().timestamp());
+        Ok::<_, String>(state.view())
+    })
+}
+
+/// Complete a step, first applying what the user chose in it: `{ hotkey }`,
+/// `{ paths }` for index folders, `{ enabled }` for browsers, or
+/// `{ config_json, options }` to import abbreviations. Without a payload
+/// the step is completed as is. Steps can be completed again.
+#[tauri::command]
+pub async fn complete_onboarding_step(
+    handle: AppHandle,
+    step: OnboardingStep,
+    payload: Option<Value>,
+) -> Result<OnboardingView, String> {
+    if let Some(payload) = payload {
+        match step {
+            OnboardingStep::Hotkey => {
+                let HotkeyPayload { hotkey } = parse_payload(step, payload)?;
+                // Saved only once it registered, so a hotkey another app holds
+                // isn't kept for the next launch
+                reregister_hotkey(handle.clone(), hotkey.clone())?;
+                set_hotkey(handle.clone(), hotkey)?;
+            }
+            OnboardingStep::IndexFolders => {
+                let IndexFoldersPayload { paths } = parse_payload(step, payload)?;
+                set_setting(handle.clone(), "file_index_paths".to_string(), paths.into())
+                    .map_err(|e| e.to_string())?;
+            }
+            OnboardingStep::Browsers => {
+                let BrowsersPayload { enabled } = parse_payload(step, payload)?;
+                set_setting(handle.clone(), "enable_browser_search".to_string(), enabled.into())
+                    .map_err(|e| e.to_string())?;
+            }
+            OnboardingStep::Abbreviations => {
+                let AbbreviationsPayload { config_json, options } = parse_payload(step, payload)?;
+                import_abbreviation_config(config_json, options, handle.clone()).await?;
+            }
+            // Granted in System Settings, nothing to apply
+            OnboardingStep::DiskAccess => {}
+        }
+    }
+
+    onboarding_store(&handle)?.update(|state| {
+        state.complete(step, chrono::Utc::now().timestamp());
+        Ok::<_, String>(state.view())
+    })
+}
+
+/// Skip the rest of onboarding. Steps added by later versions still show
+/// once.
+#[tauri::command]
+pub fn skip_onboarding(handle: AppHandle) -> Result<OnboardingView, String> {
+    onboarding_store(&handle)?.update(|state| {
+        state.skip_remaining(chrono::Utc::now().timestamp());
+        Ok::<_, String>(state.view())
+    })
+}
@@ -0,0 +1,139 @@
+/**
+ * Opener Commands
+ * Choose which app opens browser results, file types and URL schemes, and
+ * open results with it
+ */
+
+use crate::cmds::app::AppState;
+use crate::cmds::settings::{load_settings, modify_settings};
+use crate::db::browser::{init_browser_db, record_launcher_open};
+use crate::models::app::ApplicationEntry;
+use crate::models::preferences::OpenerTarget;
+use crate::services::analytics::{record_usage, UsageKind};
+use crate::services::openers::{self, Launch, OpenOutcome, OpenRequest, OpenerContext};
+use crate::services::path_display::reveal_target;
+use crate::services::privacy::PrivacyMode;
+use crate::services::search::cache::invalidate_search_cache;
+use crate::utils::run_blocking;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_opener::OpenerExt;
+
+/// Installed apps when `target` is an app, scanning on first use. A
+/// command doesn't need them, so it doesn't wait for a scan.
+fn installed_apps(handle: &AppHandle, target: &OpenerTarget) -> Result<Vec<ApplicationEntry>, String> {
+    if !matches!(target, OpenerTarget::App { .. }) {
+        return Ok(Vec::new());
+    }
+    let state = handle.state::<AppState>();
+    let mut monitor = state.app_monitor.lock().map_err(|e| format!("Lock error: {}", e))?;
+    Ok(monitor.cached_apps())
+}
+
+/// Configured openers by context
+#[tauri::command]
+pub fn get_openers(handle: AppHandle) -> Result<BTreeMap<String, OpenerTarget>, String> {
+    Ok(load_settings(&handle)?.openers)
+}
+
+/// Open results of `context` with `target`, replacing its opener. The app
+/// must be installed, or the command's program found, when it's saved.
+#[tauri::command]
+pub async fn set_opener(handle: AppHandle, context: String, target: OpenerTarget) -> Result<(), String> {
+    let context = OpenerContext::parse(&context)?;
+    run_blocking(move || {
+        let path_var = std::env::var_os("PATH");
+        openers::validate_target(&target, &installed_apps(&handle, &target)?, path_var.as_deref())?;
+
+        modify_settings(&handle, |settings| {
+            settings.openers.insert(context.to_string(), target);
+            Ok::<_, String>(())
+        })
+    })
+    .await
+}
+
+/// Open results of `context` with the system default again
+#[tauri::command]
+pub fn remove_opener(handle: AppHandle, context: String) -> Result<(), String> {
+    let context = OpenerContext::parse(&context)?;
+    modify_settings(&handle, |settings| {
+        settings.openers.remove(&context.to_string());
+        Ok::<_, String>(())
+    })
+}
+
+/// Open a result with its configured opener, or the system default when
+/// there's none or it can't be used. Runs off the main thread, since the
+/// first use of an app opener scans the installed apps.
+#[tauri::command]
+pub async fn open_result(handle: AppHandle, request: OpenRequest) -> Result<OpenOutcome, String> {
+    run_blocking(move || open_with_opener(&handle, &request)).await
+}
+
+fn open_with_opener(handle: &AppHandle, request: &OpenRequest) -> Result<OpenOutcome, String> {
+    let settings = load_settings(handle)?;
+    let subject = request.subject();
+    record_usage(handle, UsageKind::Launch, subject);
+
+    let mut outcome = OpenOutcome { opener: None, fallback_reason: None };
+    let mut with = None;
+    if let Some((context, target)) = openers::resolve(&settings.openers, request) {
+        let path_var = std::env::var_os("PATH");
+        match openers::launch_for(target, subject, &installed_apps(handle, target)?, path_var.as_deref()) {
+            Ok(Launch::Command(program, args)) => {
+                std::process::Command::new(&program)
+                    .args(&args)
+                    .spawn()
+                    .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+                outcome.opener = Some(context.to_string());
+                return Ok(outcome);
+            }
+            Ok(Launch::With(app)) => {
+                outcome.opener = Some(context.to_string());
+                with = Some(app);
+            }
+            Err(reason) => {
+                eprintln!("[Openers] Falling back to the system default for {}: {}", context, reason);
+                outcome.fallback_reason = Some(reason);
+            }
+        }
+    }
+
+    let opened = match request {
+        OpenRequest::File { path } => handle.opener().open_path(path, with.as_deref()),
+        OpenRequest::BrowserResult { url } | OpenRequest::Url { url } => handle.opener().open_url(url, with.as_deref()),
+    };
+    opened.map_err(|e| format!("Failed to open {}: {}", subject, e))?;
+    if let OpenRequest::BrowserResult { url } = request {
+        record_browser_open(handle, url);
+    }
+    Ok(outcome)
+}
+
+/// Show a file in its folder in the system file manager. A symlinked
+/// folder opens where it links to.
+#[tauri::command]
+pub fn reveal_in_folder(handle: AppHandle, path: String) -> Result<(), String> {
+    handle
+        .opener()
+        .reveal_item_in_dir(reveal_target(Path::new(&path)))
+        .map_err(|e| format!("Failed to reveal {}: {}", path, e))
+}
+
+/// Count an opened browser result towards its ranking. Skipped while
+/// privacy mode is on; failures are only logged.
+fn record_browser_open(handle: &AppHandle, url: &str) {
+    if handle.try_state::<PrivacyMode>().is_some_and(|privacy| privacy.is_active()) {
+        return;
+    }
+    let result = init_browser_db(handle)
+        .and_then(|conn| record_launcher_open(&conn, url, chrono::Utc::now().timestamp()));
+    match result {
+        // Cached results were ranked without this open
+        Ok(counted) if counted > 0 => invalidate_search_cache(handle),
+        Ok(_) => {}
+        Err(e) => eprintln!("[Openers] Failed to record opening {}: {}", url, e),
+    }
+}
@@ -0,0 +1,185 @@
+/**
+ * Plugin Host Commands
+ * Permission-checked bridge from frontend-run plugins to native capabilities
+ */
+
+use crate::cmds::performance::PerformanceState;
+use crate::cmds::plugin_performance::record_native_operation;
+use crate::cmds::notifications::notify;
+use crate::cmds::search::SearchState;
+use crate::cmds::startup::ensure_stage;
+use crate::models::preferences::AppSettings;
+use crate::services::notifications::APP_SOURCE;
+use crate::services::performance::PerformanceEvent;
+use crate::services::plugin_host::{
+    authorize, execute, execute_in_storage, HostCall, HostCallError, HostCallOutput, FILE_IO_TIMEOUT,
+};
+use crate::services::plugin_performance::MemoryProbe;
+use crate::services::plugin_rate_limiter::PluginRateLimiter;
+use crate::services::plugin_sandbox::PluginSandbox;
+use crate::services::plugin_storage::PluginStorage;
+use crate::services::shell_exec;
+use crate::services::startup::StartupStage;
+use crate::types::events::{self, PluginQuarantined};
+use std::path::Path;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Apply the plugin's rate limit. Repeated violations count against the
+/// sandbox's crash counter, which disables the plugin once exhausted.
+fn enforce_rate_limit(handle: &AppHandle, plugin_id: &str, capability: &str) -> Result<(), HostCallError> {
+    let limit = handle
+        .state::<SearchState>()
+        .settings()
+        .map(|s| s.plugin_rate_limits)
+        .unwrap_or_default()
+        .for_capability(capability);
+
+    let limiter = handle.state::<PluginRateLimiter>();
+    let Err(limited) = limiter.check(plugin_id, capability, limit, std::time::Instant::now()) else {
+        return Ok(());
+    };
+
+    if limited.escalate {
+        let sandbox = handle.state::<PluginSandbox>();
+        if let Ok(true) = sandbox.handle_plugin_crash(plugin_id) {
+            limiter.reset(plugin_id);
+            notify(
+                handle,
+                "Plugin disabled",
+                Some(&format!("{} was disabled for repeatedly exceeding its rate limits", plugin_id)),
+                APP_SOURCE,
+            );
+            let _ = events::emit(handle, PluginQuarantined {
+                plugin_id: plugin_id.to_string(),
+                reason: "Repeatedly exceeded host call rate limits".to_string(),
+            });
+        }
+    }
+
+    Err(HostCallError::RateLimited {
+        retry_after_ms: limited.retry_after.as_millis() as u64,
+    })
+}
+
+/// Per-plugin storage quota from settings, in bytes
+pub fn plugin_storage_quota<R: Runtime>(handle: &AppHandle<R>) -> u64 {
+    let quota_mb = handle
+        .try_state::<SearchState>()
+        .and_then(|state| state.settings().ok())
+        .map(|s| s.plugin_storage_quota_mb)
+        .unwrap_or_else(|| AppSettings::default().plugin_storage_quota_mb);
+    quota_mb as u64 * 1024 * 1024
+}
+
+/// Call a host capability on behalf of a plugin.
+///
+/// Fails with `PermissionDenied { permission }` when the plugin hasn't been
+/// granted the capability, so the UI can ask the user for consent, with
+/// `ConsentPending { permission }` while a dangerous permission awaits an
+/// answer, and with `RateLimited { retry_after_ms }` when it calls too often.
+#[tauri::command]
+pub async fn plugin_host_call(
+    handle: AppHandle,
+    plugin_id: String,
+    capability: String,
+    payload: Option<serde_json::Value>,
+) -> Result<HostCallOutput, HostCallError> {
+    let call = HostCall::parse(&capability, payload.unwrap_or_default())?;
+    let shell_allowlist = handle
+        .state::<SearchState>()
+        .settings()
+        .map(|s| s.shell_allowlist)
+        .unwrap_or_default();
+    // Grants are restored in the background at startup
+    ensure_stage(&handle, StartupStage::Sandbox)?;
+    let sandbox = handle.state::<PluginSandbox>();
+    authorize(&sandbox, &shell_allowlist, &plugin_id, &call).inspect_err(|e| {
+        if matches!(e, HostCallError::PermissionDenied { .. } | HostCallError::CommandNotAllowed { .. }) {
+            sandbox.record_violation(&plugin_id);
+        }
+    })?;
+    enforce_rate_limit(&handle, &plugin_id, call.capability())?;
+
+    let start = std::time::Instant::now();
+    let memory = MemoryProbe::start();
+    let result = match &call {
+        HostCall::Notification { title, body } => notify(&handle, title, body.as_deref(), &plugin_id)
+            .map(|record| HostCallOutput::Notification { suppressed: record.suppressed })
+            .ok_or_else(|| HostCallError::Failed {
+                message: "Notification service is not available".to_string(),
+            }),
+        HostCall::Shell { command, args, cwd, timeout_ms } => {
+            let timeout = timeout_ms
+                .map(std::time::Duration::from_millis)
+                .unwrap_or(shell_exec::DEFAULT_TIMEOUT)
+                .min(shell_exec::MAX_TIMEOUT);
+            match shell_exec::run(&handle, &plugin_id, command, args, cwd.as_deref().map(Path::new), Some(timeout)).await {
+                Ok(output) if output.timed_out => Err(HostCallError::TimedOut {
+                    timeout_ms: timeout.as_millis() as u64,
+                }),
+                Ok(output) => Ok(HostCallOutput::Shell {
+                    stdout: output.stdout,
+                    stderr: output.stderr,
+                    exit_code: output.exit_code,
+                    truncated: output.truncated,
+                }),
+                Err(message) => Err(HostCallError::Failed { message }),
+            }
+        }
+        // File writes land in the plugin's own data directory
+        HostCall::Storage(_) | HostCall::WriteFile { .. } => {
+            let quota = plugin_storage_quota(&handle);
+            let (handle, plugin_id, call) = (handle.clone(), plugin_id.clone(), call.clone());
+            let task = tauri::async_runtime::spawn_blocking(move || {
+                let storage = handle.try_state::<PluginStorage>().ok_or_else(|| HostCallError::Failed {
+                    message: "Plugin storage is not available".to_string(),
+                })?;
+                execute_in_storage(&storage, &plugin_id, &call, quota)
+            });
+            match tokio::time::timeout(FILE_IO_TIMEOUT, task).await {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => Err(HostCallError::Failed { message: e.to_string() }),
+                Err(_) => Err(HostCallError::TimedOut {
+                    timeout_ms: FILE_IO_TIMEOUT.as_millis() as u64,
+                }),
+            }
+        }
+        // Network and clipboard calls block, so they run off the async runtime
+        _ => {
+            let timeout = call.timeout();
+            let call = call.clone();
+            let task = tauri::async_runtime::spawn_blocking(move || execute(&call));
+            let joined = match timeout {
+                Some(timeout) => tokio::time::timeout(timeout, task).await.map_err(|_| HostCallError::TimedOut {
+                    timeout_ms: timeout.as_millis() as u64,
+                }),
+                None => Ok(task.await),
+            };
+            match joined {
+                Ok(Ok(result)) => result,
+                Ok(Err(e)) => Err(HostCallError::Failed { message: e.to_string() }),
+                Err(timed_out) => Err(timed_out),
+            }
+        }
+    };
+
+    let duration_ms = start.elapsed().as_millis() as u64;
+    record_native_operation(
+        &handle,
+        &plugin_id,
+        format!("host:{}", call.capability()),
+        duration_ms,
+        result.is_ok(),
+        memory.finish(),
+    );
+    if let Ok(monitor) = handle.state::<PerformanceState>().monitor.lock() {
+        monitor.record_event(PerformanceEvent::PluginHostCall {
+            plugin_id,
+            capability: call.capability().to_string(),
+            duration_ms,
+            success: result.is_ok(),
+        });
+    }
+
+    result
+}
@@ -0,0 +1,143 @@
+/**
+ * Plugin Performance Commands
+ * Live plugin metrics plus the persisted daily history (rollups)
+ */
+
+use crate::db::analytics::init_analytics_db;
+use crate::db::plugin_performance::{merge_rollups, prune_rollups_before, rollup_history};
+use crate::t;
+use crate::services::plugin_performance::{
+    rollup_date, PerformanceMetric, PerformanceSummary, PluginPerformance, PluginPerformanceMonitor,
+};
+use crate::services::task_supervisor::{TaskSchedule, TaskSupervisor};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+/// Metrics kept in memory
+pub const MAX_BUFFERED_METRICS: usize = 1000;
+/// Operations slower than this are tracked per plugin
+pub const SLOW_OPERATION_MS: u64 = 500;
+/// Days of history returned for a plugin
+const HISTORY_DAYS: i64 = 30;
+/// Rollups older than this are deleted
+const ROLLUP_RETENTION_DAYS: i64 = 90;
+/// Background task writing buffered rollups to disk
+const ROLLUP_FLUSH_TASK: &str = "plugin_rollup_flush";
+/// How often buffered rollups are written to disk
+const FLUSH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+fn days_ago(days: i64) -> String {
+    rollup_date((chrono::Utc::now() - chrono::Duration::days(days)).timestamp_millis())
+}
+
+/// Merge buffered rollups into the analytics database. Rollups that can't
+/// be written are dropped rather than retried.
+pub(crate) fn flush_rollups(handle: &AppHandle) -> Result<(), String> {
+    let rollups = handle.state::<PluginPerformanceMonitor>().take_rollups();
+    if rollups.is_empty() {
+        return Ok(());
+    }
+    let mut conn = init_analytics_db(handle).map_err(|e| format!("Failed to open analytics db: {}", e))?;
+    merge_rollups(&mut conn, rollups).map_err(|e| format!("Failed to save plugin rollups: {}", e))
+}
+
+/// Flush rollups every few minutes and prune those past retention
+pub(crate) fn start_rollup_flusher(handle: &AppHandle) -> Result<(), String> {
+    let app = handle.clone();
+    let supervisor = handle.state::<TaskSupervisor>();
+    supervisor.register(ROLLUP_FLUSH_TASK, TaskSchedule::every(FLUSH_INTERVAL), move |_| {
+        // Prune even when the flush fails
+        let flushed = flush_rollups(&app);
+        init_analytics_db(&app)
+            .and_then(|conn| prune_rollups_before(&conn, &days_ago(ROLLUP_RETENTION_DAYS)))
+            .map_err(|e| format!("Failed to prune rollups: {}", e))?;
+        flushed
+    })?;
+    supervisor.start(ROLLUP_FLUSH_TASK)
+}
+
+/// Record a plugin operation that ran in the frontend
+#[tauri::command]
+pub fn record_plugin_execution(
+    monitor: State<PluginPerformanceMonitor>,
+    plugin_id: String,
+    operation: String,
+    duration_ms: u64,
+    success: bool,
+) {
+    monitor
+        .start_operation(operation, plugin_id)
+        .complete_with_duration(duration_ms, success);
+}
+
+/// Live stats and the last 30 days of history for a plugin
+#[tauri::command]
+pub fn get_plugin_performance(handle: AppHandle, plugin_id: String) -> Result<PluginPerformance, String> {
+    flush_rollups(&handle)?;
+    let conn = init_analytics_db(&handle).map_err(|e| format!("Failed to open analytics db: {}", e))?;
+    let history = rollup_history(&conn, &plugin_id, &days_ago(HISTORY_DAYS - 1))
+        .map_err(|e| format!("Failed to read plugin rollups: {}", e))?;
+
+    Ok(PluginPerformance {
+        stats: handle.state::<PluginPerformanceMonitor>().get_plugin_stats(&plugin_id),
+        plugin_id,
+        history,
+    })
+}
+
+/// Totals across all plugins for the buffered metrics
+#[tauri::command]
+pub fn get_performance_summary(monitor: State<PluginPerformanceMonitor>) -> PerformanceSummary {
+    monitor.get_summary()
+}
+
+/// Buffered operations slower than `threshold_ms` (default 500 ms)
+#[tauri::command]
+pub fn get_slow_operations(
+    monitor: State<PluginPerformanceMonitor>,
+    threshold_ms: Option<u64>,
+) -> Vec<PerformanceMetric> {
+    monitor.get_operations_slower_than(threshold_ms.unwrap_or(SLOW_OPERATION_MS))
+}
+
+/// Record memory measured by the plugin's Worker sandbox, in bytes
+#[tauri::command]
+pub fn report_plugin_memory(monitor: State<PluginPerformanceMonitor>, plugin_id: String, bytes: u64) {
+    monitor.record_memory(&plugin_id, bytes);
+}
+
+/// Record a native operation done for a plugin, e.g. a host call keyed by
+/// capability or an install
+pub(crate) fn record_native_operation(
+    handle: &AppHandle,
+    plugin_id: &str,
+    operation: String,
+    duration_ms: u64,
+    success: bool,
+    memory_usage_mb: Option<f64>,
+) {
+    if let Some(monitor) = handle.try_state::<PluginPerformanceMonitor>() {
+        monitor
+            .start_operation(operation, plugin_id.to_string())
+            .with_memory_usage(memory_usage_mb)
+            .complete_with_duration(duration_ms, success);
+    }
+}
+
+/// Health warning when a plugin's peak memory is over the configured threshold
+pub(crate) fn memory_warning(handle: &AppHandle, plugin_id: &str) -> Option<String> {
+    let stats = handle
+        .try_state::<PluginPerformanceMonitor>()?
+        .get_plugin_stats(plugin_id)?;
+    let threshold_mb = crate::cmds::settings::load_settings(handle)
+        .ok()?
+        .plugin_memory_warning_mb;
+    if !stats.exceeds_memory(threshold_mb as f64) {
+        return None;
+    }
+    Some(t!(
+        "plugins.memory_warning",
+        peak_mb = format!("{:.0}", stats.peak_memory_mb.unwrap_or_default()),
+        threshold_mb = threshold_mb
+    ))
+}
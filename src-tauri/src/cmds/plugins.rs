@@ -2,25 +2,72 @@
 //! Tauri commands for plugin management
 #![allow(unused_variables)]
 
+use crate::cmds::plugin_performance::{memory_warning, record_native_operation};
+use crate::cmds::startup::ensure_stage;
 use crate::models::plugin::*;
+use crate::services::app_paths;
+use crate::services::app_watcher::{run_debounced, Debounce};
+use crate::services::marketplace_service::read_package_links;
+use crate::services::install_jobs::{job_id_for, max_age_from_hours, sweep_temp_dir, InstallJobManager, DEFAULT_TEMP_MAX_AGE};
+use crate::services::plugin_api::check_api_version;
+use crate::services::plugin_archive::{
+    latest_archive, read_orphan_sweep, record_orphan_sweep, remove_orphans, write_archive, OrphanSweepReport,
+    PluginArchive,
+};
+use crate::services::plugin_install_meta::{read_install_meta, record_update, recorded_install};
+use crate::services::plugin_performance::MemoryProbe;
+use crate::services::plugin_protection::{ensure_uninstallable, installed_name, is_protected};
+use crate::services::plugin_registry::{PluginRegistry, RegistryGuard};
+use crate::services::plugin_result_cache::{cache_ttl, CachedPluginResult, PluginResultCache};
+use crate::services::plugin_sandbox::{
+    plugin_permissions_path, ConsentSource, PendingConsent, PermissionConsent, PluginSandbox,
+};
+use crate::services::plugin_storage::{PluginStorage, StorageUsage};
+use crate::services::startup::StartupStage;
+use crate::services::task_supervisor::{TaskSchedule, TaskSupervisor};
+use crate::services::plugin_validator::{PluginValidationResult, PluginValidator, SecurityReport};
+use crate::services::package_transfer::{download_to_file, TransferredPackage, UploadManager};
 use crate::services::plugin_installer::{PluginInstaller, PackageValidation as InstallerValidation, ExtractionResult as InstallerResult};
-use std::collections::HashMap;
+use crate::services::trigger_conflicts::{find_conflicts, TriggerClaim, TriggerConflict, TriggerOwners};
+use crate::services::trigger_completions::{
+    static_completions, CompletionBridge, CompletionRequest, TriggerCompletions, DYNAMIC_COMPLETION_TIMEOUT,
+};
+use crate::t;
+use crate::types::events::{self, PluginArchiveFound, PluginQuarantined, PluginReloaded};
+use crate::utils::{managed_store, run_blocking, StateStore};
+use notify::{RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use tauri::{AppHandle, Manager};
+use std::sync::mpsc::channel;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+const PLUGIN_HEALTH_TASK: &str = "plugin-health";
+
+/// How often the health task re-checks every listed plugin
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Rescan once the plugins dir has been quiet for this long, or at least
+/// this often while an install keeps writing to it
+const WATCH_QUIET_PERIOD: Duration = Duration::from_secs(1);
+const WATCH_MAX_DELAY: Duration = Duration::from_secs(10);
 
 /// Get plugins directory
-fn get_plugins_dir(handle: &AppHandle) -> Result<PathBuf, String> {
-    handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))
+fn get_plugins_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    app_paths::data_dir(handle)
         .map(|dir| dir.join("plugins"))
 }
 
+/// Installer temp directory for uploaded packages and extractions
+fn get_installer_temp_dir(handle: &AppHandle) -> Result<PathBuf, String> {
+    app_paths::temp_dir(handle)
+}
+
 /// Ensure plugins directory exists
-fn ensure_plugins_dir(handle: &AppHandle) -> Result<PathBuf, String> {
+fn ensure_plugins_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
     let dir = get_plugins_dir(handle)?;
     fs::create_dir_all(&dir)
         .map_err(|e| format!("Failed to create plugins dir: {}", e))?;
@@ -46,84 +93,242 @@ fn find_plugin_path(plugins_dir: &PathBuf, plugin_id: &str) -> Result<PathBuf, S
     if plugin_path.exists() {
         Ok(plugin_path)
     } else {
-        Err(format!("插件不存在: {}", plugin_id))
+        Err(t!("plugins.not_found", plugin_id = plugin_id))
+    }
+}
+
+/// Folder of an installed plugin: a local plugin folder, or else its npm
+/// package
+fn installed_plugin_dir(plugins_dir: &PathBuf, plugin_id: &str) -> Result<PathBuf, String> {
+    let local = plugins_dir.join(plugin_id);
+    if local.join("plugin.json").exists() {
+        return Ok(local);
     }
+    find_plugin_path(plugins_dir, plugin_id)
 }
 
-/// List all installed plugins
+/// Longest a listing waits for the registry to reach its `min_revision`
+const STATE_REVISION_WAIT: Duration = Duration::from_millis(500);
+
+/// List all installed plugins from the registry. `force_refresh` rescans
+/// the plugins dir first, which touches each plugin folder, so it runs on
+/// the blocking pool. `min_revision`, a `state_revision` from an install or
+/// enable/disable, waits briefly for that change to land; past the wait the
+/// listing answers with what the registry has.
 #[tauri::command]
-pub fn plugin_list(handle: AppHandle) -> Result<Vec<Plugin>, String> {
-    let plugins_dir = ensure_plugins_dir(&handle)?;
+pub async fn plugin_list<R: Runtime>(
+    handle: AppHandle<R>,
+    force_refresh: Option<bool>,
+    min_revision: Option<u64>,
+) -> Result<Vec<Plugin>, String> {
+    run_blocking(move || {
+        if let Some(revision) = min_revision {
+            wait_for_state_revision(&handle, revision);
+        }
+        list_plugins(&handle, force_refresh.unwrap_or(false))
+    })
+    .await
+}
+
+/// Wait up to STATE_REVISION_WAIT for the registry, and the state file
+/// written with it, to reach `revision`
+pub(crate) fn wait_for_state_revision<R: Runtime>(handle: &AppHandle<R>, revision: u64) {
+    plugin_registry(handle).wait_for_revision(revision, STATE_REVISION_WAIT);
+}
+
+/// The plugin registry, created on first use
+fn plugin_registry<R: Runtime>(handle: &AppHandle<R>) -> State<'_, PluginRegistry> {
+    if let Some(registry) = handle.try_state::<PluginRegistry>() {
+        return registry;
+    }
+    handle.manage(PluginRegistry::new());
+    handle.state::<PluginRegistry>()
+}
+
+/// Installed plugins, scanning the plugins dir when the registry hasn't
+/// been loaded yet or `force_refresh` is set
+pub fn list_plugins<R: Runtime>(handle: &AppHandle<R>, force_refresh: bool) -> Result<Vec<Plugin>, String> {
+    with_registry(handle, |plugins| {
+        if force_refresh || !plugins.is_loaded() {
+            let scanned = scan_plugins(handle, plugins)?;
+            plugins.load(scanned);
+        }
+        Ok(plugins.snapshot().unwrap_or_default())
+    })
+}
+
+/// Rescan the plugins dir into the registry, unless it was never loaded
+pub(crate) fn refresh_plugin_registry<R: Runtime>(handle: &AppHandle<R>) -> Result<(), String> {
+    with_registry(handle, |plugins| {
+        if plugins.is_loaded() {
+            let scanned = scan_plugins(handle, plugins)?;
+            plugins.load(scanned);
+        }
+        Ok(())
+    })
+}
+
+/// Lock the registry for `change`. Plugins it quarantined are announced to
+/// the sandbox and the UI after the lock is released, so nothing else is
+/// waited on while it's held.
+fn with_registry<R: Runtime, T>(
+    handle: &AppHandle<R>,
+    change: impl FnOnce(&mut RegistryGuard) -> Result<T, String>,
+) -> Result<T, String> {
+    let registry = plugin_registry(handle);
+    let mut plugins = registry.lock();
+    let result = change(&mut plugins);
+    let quarantined = plugins.take_quarantined();
+    drop(plugins);
+    for plugin_id in quarantined {
+        announce_quarantine(handle, &plugin_id);
+    }
+    result
+}
+
+/// Re-read one plugin into the locked registry, dropping it when its folder
+/// or manifest is gone
+fn refresh_plugin_entry<R: Runtime>(
+    handle: &AppHandle<R>,
+    plugins: &mut RegistryGuard,
+    plugin_id: &str,
+) -> Result<(), String> {
+    let path = get_plugins_dir(handle)?.join(plugin_id);
+    let state = load_plugin_state(handle)?;
+    let usage_stats = load_plugin_usage_stats(handle)?;
+    let owners = trigger_owners_store(handle)?.read()?;
+    match read_plugin_entry(handle, &path, &state, &usage_stats, &owners, plugins)? {
+        Some(plugin) => plugins.upsert(plugin),
+        None => plugins.remove(plugin_id),
+    }
+    Ok(())
+}
+
+/// Read every plugin folder: manifest, install metadata, state and health
+fn scan_plugins<R: Runtime>(handle: &AppHandle<R>, registry: &mut RegistryGuard) -> Result<Vec<Plugin>, String> {
+    let plugins_dir = ensure_plugins_dir(handle)?;
     let mut plugins = Vec::new();
 
     // Load plugin state (T046)
-    let state = load_plugin_state(&handle)?;
-    let usage_stats = load_plugin_usage_stats(&handle)?;
+    let state = load_plugin_state(handle)?;
+    let usage_stats = load_plugin_usage_stats(handle)?;
+    let owners = trigger_owners_store(handle)?.read()?;
 
     let entries = fs::read_dir(&plugins_dir)
         .map_err(|e| format!("Failed to read plugins directory: {}", e))?;
 
     for entry in entries.flatten() {
-        let path = entry.path();
-        if path.is_dir() {
-            let manifest_path = path.join("plugin.json");
-            if let Ok(manifest) = read_plugin_manifest(&manifest_path) {
-                let plugin_id = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                // Load enabled state from persisted state (T046)
-                let enabled = state.get(&plugin_id).copied().unwrap_or(true);
-
-                // Get installation time
-                let installed_at = get_plugin_installation_time(&path)?;
-
-                // Get usage stats
-                let stats = usage_stats.get(&plugin_id).cloned().unwrap_or(PluginUsageStats {
-                    last_used: None,
-                    usage_count: 0,
-                    last_execution_time: None,
-                    average_execution_time: None,
-                });
-
-                // Get plugin health
-                let health = get_plugin_health_for(&plugin_id, &path)?;
-
-                plugins.push(Plugin {
-                    id: plugin_id.clone(),
-                    name: manifest.name,
-                    version: manifest.version,
-                    description: manifest.description,
-                    author: manifest.author,
-                    enabled,
-                    permissions: manifest.permissions,
-                    entry_point: manifest.entry,
-                    triggers: manifest.triggers,
-                    settings: Default::default(),
-                    health,
-                    usage_stats: stats,
-                    installed_at,
-                    install_path: path.to_string_lossy().to_string(),
-                    source: crate::models::plugin::PluginSource::Local,
-                });
-            }
-        }
+        plugins.extend(read_plugin_entry(handle, &entry.path(), &state, &usage_stats, &owners, registry)?);
     }
 
     Ok(plugins)
 }
 
-/// Get plugin installation time
-fn get_plugin_installation_time(path: &PathBuf) -> Result<i64, String> {
-    use std::time::SystemTime;
-    let metadata = fs::metadata(path)
-        .map_err(|e| format!("Failed to get plugin metadata: {}", e))?;
-    let modified = metadata.modified()
-        .map_err(|e| format!("Failed to get modification time: {}", e))?;
-    let duration = modified.duration_since(SystemTime::UNIX_EPOCH)
-        .map_err(|e| format!("Failed to convert timestamp: {}", e))?;
-    Ok(duration.as_millis() as i64)
+/// The plugin in folder `path`, None when it isn't a plugin folder. An
+/// incompatible plugin is disabled and noted in `registry` for announcing.
+fn read_plugin_entry<R: Runtime>(
+    handle: &AppHandle<R>,
+    path: &PathBuf,
+    state: &HashMap<String, bool>,
+    usage_stats: &HashMap<String, PluginUsageStats>,
+    owners: &TriggerOwners,
+    registry: &mut RegistryGuard,
+) -> Result<Option<Plugin>, String> {
+    if !path.is_dir() {
+        return Ok(None);
+    }
+    let manifest_path = path.join("plugin.json");
+    let Ok(manifest) = read_plugin_manifest(&manifest_path) else {
+        return Ok(None);
+    };
+    let plugin_id = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    // Load enabled state from persisted state (T046)
+    let mut enabled = state.get(&plugin_id).copied().unwrap_or(true);
+
+    // Plugins written for an API this host can't run stay off
+    if enabled && !check_api_version(manifest.api_version.as_deref()).is_loadable() {
+        disable_incompatible(handle, &plugin_id);
+        registry.note_quarantined(&plugin_id);
+        enabled = false;
+    }
+
+    let install = read_install_meta(path, PluginSource::Local, &manifest.version)?;
+
+    // Get usage stats
+    let stats = usage_stats.get(&plugin_id).cloned().unwrap_or(PluginUsageStats {
+        last_used: None,
+        usage_count: 0,
+        last_execution_time: None,
+        average_execution_time: None,
+    });
+
+    // Last known health; the health task keeps it current afterwards
+    let health = get_plugin_health_for(&plugin_id, path)?;
+    let suppressed_triggers = owners.suppressed_triggers(&plugin_id, &manifest.triggers);
+
+    Ok(Some(Plugin {
+        id: plugin_id.clone(),
+        name: manifest.name,
+        version: manifest.version,
+        description: manifest.description,
+        author: manifest.author,
+        enabled,
+        permissions: manifest.permissions,
+        entry_point: manifest.entry,
+        triggers: manifest.triggers,
+        settings: Default::default(),
+        api_compatibility: check_api_version(manifest.api_version.as_deref()),
+        api_version: manifest.api_version,
+        protected: is_protected(&installed_name(&plugin_id, path), manifest.protected),
+        suppressed_triggers,
+        links: plugin_links(manifest.links, path),
+        health,
+        usage_stats: stats,
+        installed_at: install.installed_at,
+        install_path: path.to_string_lossy().to_string(),
+        source: install.source,
+    }))
+}
+
+/// Disable a plugin whose API version this host can't run and tell the UI
+pub(crate) fn quarantine_incompatible<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) {
+    disable_incompatible(handle, plugin_id);
+    announce_quarantine(handle, plugin_id);
+}
+
+/// Save an incompatible plugin as disabled. Called while listing, with the
+/// registry locked, so it writes the state file directly.
+fn disable_incompatible<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) {
+    if let Err(e) = write_plugin_enabled_state(handle, plugin_id, false) {
+        eprintln!("[Plugins] Failed to disable incompatible plugin {}: {}", plugin_id, e);
+    }
+}
+
+/// Disable a quarantined plugin in the sandbox and tell the UI. Call
+/// without the registry locked.
+fn announce_quarantine<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) {
+    // Before the sandbox is restored it has no context for the plugin yet
+    if ensure_stage(handle, StartupStage::Sandbox).is_ok() {
+        if let Some(sandbox) = handle.try_state::<PluginSandbox>() {
+            let _ = sandbox.set_plugin_enabled(plugin_id, false);
+        }
+    }
+    let _ = events::emit(handle, PluginQuarantined {
+        plugin_id: plugin_id.to_string(),
+        reason: "incompatible".to_string(),
+    });
+}
+
+/// Refuse to enable a plugin whose API version this host can't run
+fn ensure_api_compatible(manifest: &PluginManifest) -> Result<(), String> {
+    match PluginValidator::new().api_version_error(manifest.api_version.as_deref()) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
 }
 
 /// Get plugin health for a plugin
@@ -139,26 +344,66 @@ fn get_plugin_health_for(plugin_id: &str, plugin_path: &PathBuf) -> Result<Plugi
         PluginHealthStatus::Error
     };
 
-    Ok(PluginHealth {
+    Ok(with_api_compatibility(&manifest, PluginHealth {
         status,
         message: None,
         last_checked: chrono::Utc::now().timestamp_millis(),
         errors: vec![],
-    })
+    }))
 }
 
-/// Read plugin manifest from file
-fn read_plugin_manifest(path: &PathBuf) -> Result<PluginManifest, String> {
+/// Mark plugins this host runs in compatibility mode as Warning, and ones
+/// it can't run at all as Error
+fn with_api_compatibility(manifest: &PluginManifest, mut health: PluginHealth) -> PluginHealth {
+    let (mut errors, mut warnings) = (Vec::new(), Vec::new());
+    PluginValidator::new().validate_api_version(manifest.api_version.as_deref(), &mut errors, &mut warnings);
+
+    if let Some(error) = errors.into_iter().next() {
+        health.status = PluginHealthStatus::Error;
+        health.message = Some(error.message.clone());
+        health.errors.push(PluginErrorEntry {
+            code: error.code,
+            message: error.message,
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            context: None,
+        });
+    } else if let Some(warning) = warnings.into_iter().next() {
+        if health.status == PluginHealthStatus::Healthy {
+            health.status = PluginHealthStatus::Warning;
+            health.message = Some(warning.message);
+        }
+    }
+    health
+}
+
+/// Read plugin manifest from file as written, without normalizing
+/// permissions
+fn read_raw_plugin_manifest(path: &PathBuf) -> Result<PluginManifest, String> {
     let content = fs::read_to_string(path)
         .map_err(|e| format!("Failed to read manifest: {}", e))?;
     serde_json::from_str(&content)
         .map_err(|e| format!("Failed to parse manifest: {}", e))
 }
 
+/// Links shown for the plugin in `plugin_dir`: the manifest's `links`, with
+/// those it leaves out taken from an npm package.json beside it. Homepages
+/// and repositories that aren't http(s) URLs are dropped; validation warns
+/// about them.
+fn plugin_links(links: PluginLinks, plugin_dir: &Path) -> PluginLinks {
+    links.or(read_package_links(plugin_dir)).web_only()
+}
+
+/// Read plugin manifest from file, with permissions in canonical form
+fn read_plugin_manifest(path: &PathBuf) -> Result<PluginManifest, String> {
+    let mut manifest = read_raw_plugin_manifest(path)?;
+    manifest.normalize_permissions();
+    Ok(manifest)
+}
+
 /// Validate plugin manifest (T096)
 #[tauri::command]
-pub fn validate_plugin_manifest(
-    handle: AppHandle,
+pub fn validate_plugin_manifest<R: Runtime>(
+    handle: AppHandle<R>,
     plugin_id: String,
 ) -> Result<PluginValidationResult, String> {
     let plugins_dir = get_plugins_dir(&handle)?;
@@ -168,154 +413,77 @@ pub fn validate_plugin_manifest(
     if !manifest_path.exists() {
         return Ok(PluginValidationResult {
             is_valid: false,
-            errors: vec![format!("插件清单文件不存在: {:?}", manifest_path)],
+            errors: vec![t!("plugins.manifest_missing", path = manifest_path.display())],
             warnings: vec![],
         });
     }
 
-    // Try to parse manifest
-    let manifest = match read_plugin_manifest(&manifest_path) {
+    // Try to parse manifest, keeping legacy permission names so they can be flagged
+    let mut manifest = match read_raw_plugin_manifest(&manifest_path) {
         Ok(m) => m,
         Err(e) => {
             return Ok(PluginValidationResult {
                 is_valid: false,
-                errors: vec![format!("解析失败: {}", e)],
+                errors: vec![t!("plugins.manifest_parse_failed", error = e)],
                 warnings: vec![],
             });
         }
     };
 
-    let mut errors = Vec::new();
-    let mut warnings = Vec::new();
-
-    // Validate plugin_id from directory
-    if plugin_id.is_empty() {
-        errors.push("插件 ID 不能为空".to_string());
-    }
-
-    // Validate ID format (should be alphanumeric with hyphens/underscores)
-    if !plugin_id.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
-        errors.push("插件 ID 只能包含字母、数字、连字符和下划线".to_string());
-    }
-
-    // Validate required fields from manifest
-    if manifest.name.is_empty() {
-        errors.push("插件名称不能为空".to_string());
-    }
-
-    if manifest.version.is_empty() {
-        errors.push("插件版本不能为空".to_string());
-    }
-
-    // Validate version format (semver-like)
-    if !manifest.version.chars().all(|c| c.is_ascii_digit() || c == '.') {
-        warnings.push("版本号格式建议使用语义化版本 (如 1.0.0)".to_string());
-    }
-
-    // Validate triggers
-    if manifest.triggers.is_empty() {
-        warnings.push("插件没有定义任何触发器，用户将无法通过搜索调用".to_string());
-    }
-
-    for trigger in &manifest.triggers {
-        if !trigger.keyword.ends_with(':') {
-            warnings.push(format!("触发器 '{}' 建议以冒号结尾", trigger.keyword));
-        }
-    }
-
-    // Validate entry point
-    if manifest.entry.is_empty() {
-        errors.push("插件入口点不能为空".to_string());
-    } else {
-        let entry_path = plugins_dir.join(&plugin_id).join(&manifest.entry);
-        if !entry_path.exists() {
-            errors.push(format!("入口点文件不存在: {:?}", entry_path));
-        }
-    }
-
-    // Validate permissions
-    let valid_permissions = [
-        "read_clipboard", "write_clipboard",
-        "read_file", "write_file",
-        "network", "shell", "notification",
-    ];
-
-    for perm in &manifest.permissions {
-        if !valid_permissions.contains(&perm.as_str()) {
-            warnings.push(format!("未知权限: '{}'", perm));
-        }
-    }
-
-    // Check for dangerous permission combinations
-    if manifest.permissions.contains(&"shell".to_string()) {
-        warnings.push("shell 权限具有安全风险，请谨慎使用".to_string());
-    }
-
-    let is_valid = errors.is_empty();
+    // Links from package.json are checked too, as they're what gets shown
+    let plugin_dir = plugins_dir.join(&plugin_id);
+    manifest.links = manifest.links.or(read_package_links(&plugin_dir));
+    Ok(PluginValidator::new().validate_installed(&manifest, &plugin_id, &plugin_dir).into())
+}
 
-    Ok(PluginValidationResult {
-        is_valid,
-        errors,
-        warnings,
-    })
+/// Get an installed plugin's security score, risks and permission breakdown
+#[tauri::command]
+pub fn get_plugin_security_report(handle: AppHandle, plugin_id: String) -> Result<SecurityReport, String> {
+    let manifest_path = get_plugins_dir(&handle)?.join(&plugin_id).join("plugin.json");
+    let manifest = read_plugin_manifest(&manifest_path)?;
+    Ok(PluginValidator::new().security_report(&manifest))
 }
 
-/// Plugin validation result
-#[derive(Debug, Clone, serde::Serialize)]
-pub struct PluginValidationResult {
-    pub is_valid: bool,
-    pub errors: Vec<String>,
-    pub warnings: Vec<String>,
+/// Plugin state file in an app data dir (T046)
+pub fn plugin_state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("plugin-state.json")
 }
 
 /// Get plugin state file path (T046)
-fn get_plugin_state_path(handle: &AppHandle) -> Result<PathBuf, String> {
-    handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))
-        .map(|dir| dir.join("plugin-state.json"))
+fn get_plugin_state_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    app_paths::data_dir(handle).map(|dir| plugin_state_path(&dir))
 }
 
-/// Load plugin state (T046)
-fn load_plugin_state(handle: &AppHandle) -> Result<std::collections::HashMap<String, bool>, String> {
-    let state_path = get_plugin_state_path(handle)?;
-    if !state_path.exists() {
-        return Ok(std::collections::HashMap::new());
-    }
-
-    let content = fs::read_to_string(&state_path)
-        .map_err(|e| format!("Failed to read plugin state: {}", e))?;
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse plugin state: {}", e))
+/// Plugin enabled state, keyed by plugin id (T046)
+fn plugin_state_store<R: Runtime>(handle: &AppHandle<R>) -> Result<State<'_, StateStore<HashMap<String, bool>>>, String> {
+    managed_store(handle, || Ok(StateStore::new(get_plugin_state_path(handle)?, "plugin state")))
 }
 
-/// Save plugin state (T046)
-fn save_plugin_state(handle: &AppHandle, state: &std::collections::HashMap<String, bool>) -> Result<(), String> {
-    let state_path = get_plugin_state_path(handle)?;
-    let json = serde_json::to_string_pretty(state)
-        .map_err(|e| format!("Failed to serialize plugin state: {}", e))?;
-
-    fs::write(&state_path, json)
-        .map_err(|e| format!("Failed to write plugin state: {}", e))
+/// Load plugin state (T046)
+fn load_plugin_state<R: Runtime>(handle: &AppHandle<R>) -> Result<HashMap<String, bool>, String> {
+    plugin_state_store(handle)?.read()
 }
 
-/// Save plugin enabled state
-fn save_plugin_enabled_state(handle: &AppHandle, plugin_id: &str, enabled: bool) -> Result<(), String> {
-    // Load existing state
-    let state = load_plugin_state(handle)?;
-    let mut new_state = state.clone();
-
-    // Update the plugin's enabled state
-    new_state.insert(plugin_id.to_string(), enabled);
+/// Save plugin enabled state, updating the registry in the same critical
+/// section. Returns the registry revision of the change.
+fn save_plugin_enabled_state<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str, enabled: bool) -> Result<u64, String> {
+    let registry = plugin_registry(handle);
+    let mut plugins = registry.lock();
+    write_plugin_enabled_state(handle, plugin_id, enabled)?;
+    plugins.set_enabled(plugin_id, enabled);
+    Ok(plugins.revision())
+}
 
-    // Save the updated state
-    save_plugin_state(handle, &new_state)
+/// Write plugin enabled state without touching the registry
+fn write_plugin_enabled_state<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str, enabled: bool) -> Result<(), String> {
+    plugin_state_store(handle)?.update(|state| {
+        state.insert(plugin_id.to_string(), enabled);
+        Ok(())
+    })
 }
 
 /// Get plugin enabled state
-pub fn get_plugin_enabled_state(handle: &AppHandle, plugin_id: &str) -> Result<bool, String> {
+pub fn get_plugin_enabled_state<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) -> Result<bool, String> {
     let state = load_plugin_state(handle)?;
 
     // If plugin is not in state, it's enabled by default
@@ -323,24 +491,94 @@ pub fn get_plugin_enabled_state(handle: &AppHandle, plugin_id: &str) -> Result<b
 }
 
 /// Remove plugin state (US4)
-fn remove_plugin_state(handle: &AppHandle, plugin_id: &str) -> Result<(), String> {
-    // Load existing state
-    let state = load_plugin_state(handle)?;
-    let mut new_state = state.clone();
+fn remove_plugin_state<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) -> Result<(), String> {
+    plugin_state_store(handle)?.update(|state| {
+        state.remove(plugin_id);
+        Ok(())
+    })
+}
+
+/// Get trigger owners file path
+fn get_trigger_owners_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    app_paths::data_dir(handle)
+        .map(|dir| dir.join("trigger-owners.json"))
+}
+
+/// Which plugin owns each contested trigger keyword
+fn trigger_owners_store<R: Runtime>(handle: &AppHandle<R>) -> Result<State<'_, StateStore<TriggerOwners>>, String> {
+    managed_store(handle, || Ok(StateStore::new(get_trigger_owners_path(handle)?, "trigger owners")))
+}
 
-    // Remove the plugin's state
-    new_state.remove(plugin_id);
+/// `plugin_id`'s triggers that lost their keyword to another plugin
+pub(crate) fn plugin_suppressed_triggers<R: Runtime>(
+    handle: &AppHandle<R>,
+    plugin_id: &str,
+    triggers: &[PluginTrigger],
+) -> Result<Vec<String>, String> {
+    Ok(trigger_owners_store(handle)?.read()?.suppressed_triggers(plugin_id, triggers))
+}
+
+/// Keywords installed plugins answer to, local and npm ones alike: their
+/// triggers that aren't suppressed, and the enabled abbreviations set up
+/// for them
+fn trigger_claims<R: Runtime>(handle: &AppHandle<R>) -> Result<Vec<TriggerClaim>, String> {
+    let mut plugins = list_plugins(handle, false)?;
+    plugins.extend(crate::cmds::marketplace::read_installed_plugins(handle.clone())?);
+    let mut claims: Vec<TriggerClaim> = plugins
+        .iter()
+        .flat_map(|plugin| {
+            plugin
+                .triggers
+                .iter()
+                .filter(|trigger| !plugin.suppressed_triggers.contains(&trigger.keyword))
+                .map(|trigger| TriggerClaim::trigger(&plugin.id, &trigger.keyword))
+        })
+        .collect();
+    for (plugin_id, abbreviations) in get_plugin_abbreviations(handle.clone())? {
+        claims.extend(
+            abbreviations
+                .iter()
+                .filter(|abbreviation| abbreviation.enabled)
+                .map(|abbreviation| TriggerClaim::abbreviation(&plugin_id, &abbreviation.keyword)),
+        );
+    }
+    Ok(claims)
+}
 
-    // Save the updated state
-    save_plugin_state(handle, &new_state)
+/// `triggers` that collide with what installed plugins answer to.
+/// `plugin_id`'s own claims are left out, so an update doesn't collide with
+/// itself. Reads the registry, so call it without holding the lock.
+pub(crate) fn detect_trigger_conflicts<R: Runtime>(
+    handle: &AppHandle<R>,
+    plugin_id: Option<&str>,
+    triggers: &[PluginTrigger],
+) -> Result<Vec<TriggerConflict>, String> {
+    Ok(find_conflicts(plugin_id.unwrap_or_default(), triggers, &trigger_claims(handle)?))
+}
+
+/// Leave each keyword an installing plugin collides on with the plugin that
+/// had it, so the newcomer's trigger stays suppressed until the user picks.
+/// Call once the plugin's files are in place and before its registry entry
+/// is read.
+pub(crate) fn settle_trigger_conflicts<R: Runtime>(
+    handle: &AppHandle<R>,
+    conflicts: &[TriggerConflict],
+) -> Result<(), String> {
+    if conflicts.is_empty() {
+        return Ok(());
+    }
+    trigger_owners_store(handle)?.update(|owners| {
+        owners.keep_existing(conflicts);
+        Ok(())
+    })
 }
 
 /// Install a plugin (T043)
 #[tauri::command]
-pub fn install_plugin(
-    handle: AppHandle,
+pub fn install_plugin<R: Runtime>(
+    handle: AppHandle<R>,
     plugin_path: String,
-) -> Result<Plugin, String> {
+) -> Result<PluginChange, String> {
     // For now, plugin_path is expected to be a directory path
     let source_dir = PathBuf::from(&plugin_path);
 
@@ -366,19 +604,32 @@ pub fn install_plugin(
     // Copy plugin to plugins directory
     let plugins_dir = ensure_plugins_dir(&handle)?;
     let target_dir = plugins_dir.join(&plugin_id);
+    let conflicts = detect_trigger_conflicts(&handle, Some(&plugin_id), &manifest.triggers)?;
+
+    let (install, state_revision) = with_registry(&handle, |plugins| {
+        // Remove existing if present; a reinstall keeps its install time
+        let previous = recorded_install(&target_dir);
+        if target_dir.exists() {
+            fs::remove_dir_all(&target_dir)
+                .map_err(|e| format!("Failed to remove existing plugin: {}", e))?;
+        }
 
-    // Remove existing if present
-    if target_dir.exists() {
-        fs::remove_dir_all(&target_dir)
-            .map_err(|e| format!("Failed to remove existing plugin: {}", e))?;
-    }
-
-    // Copy plugin files
-    copy_dir_recursive(&source_dir, &target_dir)?;
-
-    let installed_at = chrono::Utc::now().timestamp_millis();
-
-    Ok(Plugin {
+        // Copy plugin files
+        copy_dir_recursive(&source_dir, &target_dir)?;
+        let install = record_update(&target_dir, previous, PluginSource::Local, &manifest.version)?;
+        settle_trigger_conflicts(&handle, &conflicts)?;
+        refresh_plugin_entry(&handle, plugins, &plugin_id)?;
+        Ok((install, plugins.revision()))
+    })?;
+    invalidate_cached_results(&handle, &plugin_id);
+    request_plugin_consents(&handle, &plugin_id, &manifest.version, &manifest.permissions, ConsentSource::Install)?;
+    offer_archived_settings(&handle, &plugin_id);
+
+    let installed_at = install.installed_at;
+    let protected = is_protected(&installed_name(&plugin_id, &target_dir), manifest.protected);
+    let suppressed_triggers = plugin_suppressed_triggers(&handle, &plugin_id, &manifest.triggers)?;
+
+    let plugin = Plugin {
         id: plugin_id,
         name: manifest.name,
         version: manifest.version,
@@ -389,6 +640,11 @@ pub fn install_plugin(
         entry_point: manifest.entry,
         triggers: manifest.triggers,
         settings: Default::default(),
+        api_compatibility: check_api_version(manifest.api_version.as_deref()),
+        api_version: manifest.api_version,
+        protected,
+        suppressed_triggers,
+        links: plugin_links(manifest.links, &target_dir),
         health: PluginHealth {
             status: PluginHealthStatus::Healthy,
             message: None,
@@ -403,8 +659,9 @@ pub fn install_plugin(
         },
         installed_at,
         install_path: target_dir.to_string_lossy().to_string(),
-        source: crate::models::plugin::PluginSource::Local,
-    })
+        source: install.source,
+    };
+    Ok(PluginChange { plugin, state_revision })
 }
 
 /// Copy directory recursively
@@ -430,43 +687,273 @@ fn copy_dir_recursive(source: &PathBuf, target: &PathBuf) -> Result<(), String>
     Ok(())
 }
 
-/// Uninstall a plugin
+/// Uninstall a plugin. With `preserve_settings` its settings and consent
+/// records are archived first, for `restore_archived_settings` after a
+/// reinstall.
 #[tauri::command]
-pub fn uninstall_plugin(
-    handle: AppHandle,
+pub fn uninstall_plugin<R: Runtime>(
+    handle: AppHandle<R>,
     plugin_id: String,
+    purge_data: Option<bool>,
+    preserve_settings: Option<bool>,
 ) -> Result<(), String> {
     let plugins_dir = get_plugins_dir(&handle)?;
     let plugin_path = plugins_dir.join(&plugin_id);
+    ensure_uninstallable(&plugin_id, &plugin_path).map_err(|e| e.to_string())?;
+    if preserve_settings.unwrap_or(false) {
+        archive_plugin_settings(&handle, &plugin_id, &plugin_path)?;
+    }
 
     if plugin_path.exists() {
         fs::remove_dir_all(&plugin_path)
             .map_err(|e| format!("Failed to remove plugin: {}", e))?;
     }
+    invalidate_cached_results(&handle, &plugin_id);
+    forget_plugin(&handle, &plugin_id)?;
+
+    purge_plugin_data(&handle, &plugin_id, purge_data)
+}
 
+/// Archive the settings and consent records of the plugin installed at
+/// `plugin_path`, under the version its manifest gives
+fn archive_plugin_settings<R: Runtime>(
+    handle: &AppHandle<R>,
+    plugin_id: &str,
+    plugin_path: &Path,
+) -> Result<(), String> {
+    let data_dir = app_paths::data_dir(handle)?;
+    let version = read_plugin_manifest(&plugin_path.join("plugin.json"))
+        .map(|manifest| manifest.version)
+        .unwrap_or_else(|_| "unknown".to_string());
+
+    ensure_stage(handle, StartupStage::Sandbox)?;
+    let consents = handle
+        .try_state::<PluginSandbox>()
+        .map(|sandbox| sandbox.get_consents(plugin_id))
+        .unwrap_or_default();
+    let archive = PluginArchive {
+        plugin_id: plugin_id.to_string(),
+        version,
+        archived_at: chrono::Utc::now().timestamp_millis(),
+        settings: plugin_settings_store(handle)?.read()?.remove(plugin_id).unwrap_or_default(),
+        consents,
+    };
+    write_archive(&data_dir, &archive)?;
     Ok(())
 }
 
-/// Enable a plugin (T044)
+/// Remove what's kept about an uninstalled plugin outside its folder:
+/// settings, enabled state, usage stats, abbreviations and permissions,
+/// and drop it from the registry
+pub(crate) fn forget_plugin<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) -> Result<(), String> {
+    with_registry(handle, |plugins| {
+        plugins.remove(plugin_id);
+        plugin_settings_store(handle)?.update(|settings| {
+            settings.remove(plugin_id);
+            Ok::<_, String>(())
+        })?;
+        remove_plugin_state(handle, plugin_id)?;
+        plugin_usage_stats_store(handle)?.update(|stats| {
+            stats.remove(plugin_id);
+            Ok::<_, String>(())
+        })?;
+
+        plugin_abbreviations_store(handle)?.update(|abbreviations| {
+            abbreviations.remove(plugin_id);
+            Ok::<_, String>(())
+        })?;
+
+        // Triggers the plugin had won fire again for the plugins they collided with
+        let released = trigger_owners_store(handle)?.update(|owners| Ok::<_, String>(owners.release(plugin_id)))?;
+        if released && plugins.is_loaded() {
+            let scanned = scan_plugins(handle, plugins)?;
+            plugins.load(scanned);
+        }
+        Ok(())
+    })?;
+
+    // Until the sandbox is restored there's nothing to unregister; the
+    // startup orphan sweep drops the plugin's restored grants instead
+    if ensure_stage(handle, StartupStage::Sandbox).is_err() {
+        return Ok(());
+    }
+    if let Some(sandbox) = handle.try_state::<PluginSandbox>() {
+        if sandbox.unregister_plugin(plugin_id).is_ok() {
+            save_plugin_sandbox(handle, &sandbox)?;
+        }
+    }
+    Ok(())
+}
+
+/// Bring back the settings and consent records archived when the plugin
+/// was uninstalled. Archived settings replace ones set since; consents are
+/// restored only for permissions the installed version requests and that
+/// weren't answered again at install. The archive is removed once restored.
 #[tauri::command]
-pub fn enable_plugin(
-    handle: AppHandle,
+pub fn restore_archived_settings<R: Runtime>(handle: AppHandle<R>, plugin_id: String) -> Result<PluginArchive, String> {
+    let data_dir = app_paths::data_dir(&handle)?;
+    if !installed_plugin_ids(&handle)?.contains(&plugin_id) {
+        return Err(t!("plugins.not_found", plugin_id = plugin_id));
+    }
+    let (path, archive) = latest_archive(&data_dir, &plugin_id)
+        .ok_or_else(|| format!("No archived settings for plugin {}", plugin_id))?;
+
+    plugin_settings_store(&handle)?.update(|settings| {
+        settings.entry(plugin_id.clone()).or_default().extend(archive.settings.clone());
+        Ok::<_, String>(())
+    })?;
+    let requested: Vec<PluginPermission> = installed_plugin_manifest(&handle, &plugin_id)
+        .map(|manifest| manifest.permissions.iter().filter_map(|p| p.parse().ok()).collect())
+        .unwrap_or_default();
+    ensure_stage(&handle, StartupStage::Sandbox)?;
+    if let Some(sandbox) = handle.try_state::<PluginSandbox>() {
+        if !sandbox.restore_consents(&plugin_id, archive.consents.clone(), &requested).is_empty() {
+            save_plugin_sandbox(&handle, &sandbox)?;
+        }
+    }
+    invalidate_cached_results(&handle, &plugin_id);
+
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove plugin archive: {}", e))?;
+    Ok(archive)
+}
+
+/// Tell the UI a freshly installed plugin has archived settings it can
+/// restore
+pub(crate) fn offer_archived_settings<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) {
+    let Ok(data_dir) = app_paths::data_dir(handle) else {
+        return;
+    };
+    if let Some((_, archive)) = latest_archive(&data_dir, plugin_id) {
+        let _ = events::emit(handle, PluginArchiveFound {
+            plugin_id: archive.plugin_id,
+            version: archive.version,
+            archived_at: archive.archived_at,
+        });
+    }
+}
+
+/// Ids of the installed plugins: folders in the plugins dir and npm
+/// packages, under both their short and package names. A folder counts
+/// even when its manifest can't be read, so its data isn't swept. Fails
+/// when the plugins dir can't be listed, rather than finding no plugins.
+pub(crate) fn installed_plugin_ids<R: Runtime>(handle: &AppHandle<R>) -> Result<HashSet<String>, String> {
+    let plugins_dir = get_plugins_dir(handle)?;
+    let dir_names = |dir: &Path| -> Result<Vec<String>, String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+            if entry.path().is_dir() {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        Ok(names)
+    };
+
+    let mut ids: HashSet<String> =
+        dir_names(&plugins_dir)?.into_iter().filter(|name| name != "node_modules").collect();
+    // No npm plugins installed yet
+    let npm_dir = plugins_dir.join("node_modules").join("@etools-plugin");
+    let npm_names = if npm_dir.exists() { dir_names(&npm_dir)? } else { Vec::new() };
+    for name in npm_names {
+        ids.insert(format!("@etools-plugin/{}", name));
+        ids.insert(name);
+    }
+    Ok(ids)
+}
+
+/// Remove settings, state, usage stats, abbreviations and permissions left
+/// behind by plugins uninstalled before uninstalling cleaned them up. Runs
+/// once; returns what it removed, or None when it had already run. When the
+/// installed plugins can't be listed nothing is removed, and the sweep runs
+/// again next time.
+pub(crate) fn sweep_orphaned_plugin_data<R: Runtime>(
+    handle: &AppHandle<R>,
+) -> Result<Option<OrphanSweepReport>, String> {
+    let data_dir = app_paths::data_dir(handle)?;
+    if read_orphan_sweep(&data_dir).is_some() {
+        return Ok(None);
+    }
+
+    let installed = installed_plugin_ids(handle)?;
+    let mut report = OrphanSweepReport {
+        swept_at: chrono::Utc::now().timestamp_millis(),
+        ..Default::default()
+    };
+    report.settings =
+        plugin_settings_store(handle)?.update(|settings| Ok::<_, String>(remove_orphans(settings, &installed)))?;
+    report.state = plugin_state_store(handle)?.update(|state| Ok::<_, String>(remove_orphans(state, &installed)))?;
+    report.usage_stats =
+        plugin_usage_stats_store(handle)?.update(|stats| Ok::<_, String>(remove_orphans(stats, &installed)))?;
+
+    report.abbreviations = plugin_abbreviations_store(handle)?
+        .update(|abbreviations| Ok::<_, String>(remove_orphans(abbreviations, &installed)))?;
+
+    ensure_stage(handle, StartupStage::Sandbox)?;
+    if let Some(sandbox) = handle.try_state::<PluginSandbox>() {
+        let mut registered = sandbox.get_registered_plugins();
+        registered.retain(|id| !installed.contains(id));
+        registered.sort();
+        for id in &registered {
+            let _ = sandbox.unregister_plugin(id);
+        }
+        if !registered.is_empty() {
+            save_plugin_sandbox(handle, &sandbox)?;
+        }
+        report.permissions = registered;
+    }
+
+    record_orphan_sweep(&data_dir, &report)?;
+    Ok(Some(report))
+}
+
+/// Delete an uninstalled plugin's data directory unless the caller asked
+/// to keep it (`purge_data: false`), e.g. for a reinstall
+pub(crate) fn purge_plugin_data<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str, purge_data: Option<bool>) -> Result<(), String> {
+    if !purge_data.unwrap_or(true) {
+        return Ok(());
+    }
+    if let Some(storage) = handle.try_state::<PluginStorage>() {
+        storage
+            .purge(plugin_id)
+            .map_err(|e| format!("Failed to delete plugin data: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Bytes a plugin stores in its data directory, and its quota
+#[tauri::command]
+pub fn get_plugin_storage_usage<R: Runtime>(handle: AppHandle<R>, plugin_id: String) -> Result<StorageUsage, String> {
+    let storage = handle
+        .try_state::<PluginStorage>()
+        .ok_or("Plugin storage is not available")?;
+    storage
+        .usage(&plugin_id, crate::cmds::plugin_host::plugin_storage_quota(&handle))
+        .map_err(|e| e.to_string())
+}
+
+/// Enable a plugin (T044), returning the registry revision of the change
+#[tauri::command]
+pub fn enable_plugin<R: Runtime>(
+    handle: AppHandle<R>,
     plugin_id: String,
-) -> Result<(), String> {
-    let mut state = load_plugin_state(&handle)?;
-    state.insert(plugin_id, true);
-    save_plugin_state(&handle, &state)
+) -> Result<u64, String> {
+    let manifest_path = get_plugins_dir(&handle)?.join(&plugin_id).join("plugin.json");
+    if manifest_path.exists() {
+        ensure_api_compatible(&read_plugin_manifest(&manifest_path)?)?;
+    }
+
+    save_plugin_enabled_state(&handle, &plugin_id, true)
 }
 
-/// Disable a plugin (T044)
+/// Disable a plugin (T044), returning the registry revision of the change
 #[tauri::command]
-pub fn disable_plugin(
-    handle: AppHandle,
+pub fn disable_plugin<R: Runtime>(
+    handle: AppHandle<R>,
     plugin_id: String,
-) -> Result<(), String> {
-    let mut state = load_plugin_state(&handle)?;
-    state.insert(plugin_id, false);
-    save_plugin_state(&handle, &state)
+) -> Result<u64, String> {
+    invalidate_cached_results(&handle, &plugin_id);
+    save_plugin_enabled_state(&handle, &plugin_id, false)
 }
 
 /// Get plugin manifest
@@ -480,12 +967,146 @@ pub fn get_plugin_manifest(
     read_plugin_manifest(&manifest_path)
 }
 
+/// Manifest of an installed plugin, with permissions normalized
+pub(crate) fn installed_plugin_manifest<R: Runtime>(
+    handle: &AppHandle<R>,
+    plugin_id: &str,
+) -> Result<PluginManifest, String> {
+    read_plugin_manifest(&installed_plugin_dir(&get_plugins_dir(handle)?, plugin_id)?.join("plugin.json"))
+}
+
+/// Hints for the argument being typed after a plugin trigger. Suggestions
+/// from the manifest come back right away; for `dynamic` arguments an
+/// enabled plugin is asked too, for at most DYNAMIC_COMPLETION_TIMEOUT.
+#[tauri::command]
+pub async fn get_trigger_completions<R: Runtime>(
+    handle: AppHandle<R>,
+    plugin_id: String,
+    trigger: String,
+    partial_args: Vec<String>,
+) -> Result<TriggerCompletions, String> {
+    run_blocking(move || {
+        let manifest_path = get_plugins_dir(&handle)?.join(&plugin_id).join("plugin.json");
+        let plugin_trigger = read_plugin_manifest(&manifest_path)?
+            .triggers
+            .into_iter()
+            .find(|t| t.keyword == trigger)
+            .ok_or_else(|| format!("Plugin {} has no trigger {}", plugin_id, trigger))?;
+
+        let mut completions = static_completions(&plugin_trigger, &partial_args);
+        let Some(arg) = completions.arg.as_ref().filter(|arg| arg.dynamic) else {
+            return Ok(completions);
+        };
+        if !load_plugin_state(&handle)?.get(&plugin_id).copied().unwrap_or(true) {
+            return Ok(completions);
+        }
+
+        let request = |request_id| {
+            events::emit(&handle, CompletionRequest {
+                request_id,
+                plugin_id: plugin_id.clone(),
+                trigger: trigger.clone(),
+                arg: arg.name.clone(),
+                partial_args: partial_args.clone(),
+            })
+            .map_err(|e| e.to_string())
+        };
+        let dynamic = handle.state::<CompletionBridge>().request(request, DYNAMIC_COMPLETION_TIMEOUT);
+        completions.merge(dynamic);
+        Ok(completions)
+    })
+    .await
+}
+
+/// The plugin's answer to a `plugin:completion_request`. Returns false when
+/// it came too late to be used.
+#[tauri::command]
+pub fn resolve_trigger_completions(
+    request_id: u64,
+    suggestions: Vec<String>,
+    bridge: State<CompletionBridge>,
+) -> bool {
+    bridge.resolve(request_id, suggestions)
+}
+
+/// A trigger's earlier payload for this input while its TTL lasts. None
+/// means the plugin has to run.
+#[tauri::command]
+pub fn get_cached_plugin_result(
+    plugin_id: String,
+    trigger: String,
+    input: String,
+    cache: State<PluginResultCache>,
+) -> Option<CachedPluginResult> {
+    cache.get(&plugin_id, &trigger, &input)
+}
+
+/// Offer a trigger run's payload for reuse. Only enabled plugins' triggers
+/// that declare `cache_ttl_ms`, on plugins without clipboard or file
+/// permissions, are kept; returns whether this one was.
+#[tauri::command]
+pub fn cache_plugin_result<R: Runtime>(
+    handle: AppHandle<R>,
+    plugin_id: String,
+    trigger: String,
+    input: String,
+    payload: serde_json::Value,
+) -> Result<bool, String> {
+    let Some(cache) = handle.try_state::<PluginResultCache>() else {
+        return Ok(false);
+    };
+    if !load_plugin_state(&handle)?.get(&plugin_id).copied().unwrap_or(true) {
+        return Ok(false);
+    }
+
+    // Offered on every keystroke, so plugin.json is only parsed again once
+    // it was modified
+    let manifest_path = find_plugin_path(&get_plugins_dir(&handle)?, &plugin_id)?.join("plugin.json");
+    let modified = fs::metadata(&manifest_path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+    let manifest = match cache.manifest(&plugin_id, modified) {
+        Some(manifest) => manifest,
+        None => {
+            let manifest = Arc::new(read_plugin_manifest(&manifest_path)?);
+            cache.remember_manifest(&plugin_id, modified, Arc::clone(&manifest));
+            manifest
+        }
+    };
+    let Some(ttl) = cache_ttl(&manifest, &trigger) else {
+        return Ok(false);
+    };
+    cache.insert(&plugin_id, &trigger, &input, payload, ttl);
+    Ok(true)
+}
+
+/// Drop a plugin's cached trigger results. Returns how many were dropped.
+#[tauri::command]
+pub fn invalidate_plugin_cache<R: Runtime>(handle: AppHandle<R>, plugin_id: String) -> usize {
+    invalidate_cached_results(&handle, &plugin_id)
+}
+
+/// Called whenever a plugin's code or enabled state changes, since its
+/// cached results may no longer be what it would return
+pub(crate) fn invalidate_cached_results<R: Runtime>(handle: &AppHandle<R>, plugin_id: &str) -> usize {
+    handle
+        .try_state::<PluginResultCache>()
+        .map_or(0, |cache| cache.invalidate_plugin(plugin_id))
+}
+
 /// Reload a plugin
 #[tauri::command]
-pub fn reload_plugin(
-    handle: AppHandle,
+pub fn reload_plugin<R: Runtime>(
+    handle: AppHandle<R>,
     plugin_id: String,
 ) -> Result<Plugin, String> {
+    let invalidated_results = invalidate_cached_results(&handle, &plugin_id);
+    with_registry(&handle, |plugins| refresh_plugin_entry(&handle, plugins, &plugin_id))?;
+    let _ = events::emit(&handle, PluginReloaded {
+        plugin_id: plugin_id.clone(),
+        invalidated_results,
+    });
+
     // TODO: Implement plugin reload logic
     let now = chrono::Utc::now().timestamp_millis();
     Ok(Plugin {
@@ -497,6 +1118,11 @@ pub fn reload_plugin(
         enabled: true,
         permissions: vec![],
         entry_point: "index.ts".to_string(),
+        api_version: None,
+        api_compatibility: Default::default(),
+        protected: false,
+        suppressed_triggers: Vec::new(),
+        links: Default::default(),
         triggers: vec![],
         settings: Default::default(),
         health: PluginHealth {
@@ -523,9 +1149,15 @@ pub fn grant_plugin_permission(
     handle: AppHandle,
     plugin_id: String,
     permission: String,
+    sandbox: State<PluginSandbox>,
 ) -> Result<(), String> {
-    // TODO: Implement permission granting
-    Ok(())
+    // Changes made before the saved grants are restored would be lost
+    ensure_stage(&handle, StartupStage::Sandbox)?;
+    let permission: PluginPermission = permission.parse()?;
+
+    let version = installed_version(&handle, &plugin_id);
+    sandbox.set_consent(&plugin_id, permission, true, version, ConsentSource::Manual);
+    save_plugin_sandbox(&handle, &sandbox)
 }
 
 /// Revoke plugin permission
@@ -534,61 +1166,121 @@ pub fn revoke_plugin_permission(
     handle: AppHandle,
     plugin_id: String,
     permission: String,
+    sandbox: State<PluginSandbox>,
 ) -> Result<(), String> {
-    // TODO: Implement permission revocation
-    Ok(())
+    ensure_stage(&handle, StartupStage::Sandbox)?;
+    let permission: PluginPermission = permission.parse()?;
+
+    let version = installed_version(&handle, &plugin_id);
+    sandbox.set_consent(&plugin_id, permission, false, version, ConsentSource::Manual);
+    save_plugin_sandbox(&handle, &sandbox)
 }
 
-/// Get plugin permissions and settings
+/// Set the programs a plugin may run through the Shell host capability
+#[tauri::command]
+pub fn set_plugin_shell_allowlist(
+    handle: AppHandle,
+    plugin_id: String,
+    commands: Vec<String>,
+    sandbox: State<PluginSandbox>,
+) -> Result<(), String> {
+    ensure_stage(&handle, StartupStage::Sandbox)?;
+    sandbox.ensure_registered(&plugin_id);
+    sandbox.set_shell_allowlist(&plugin_id, commands)?;
+    save_plugin_sandbox(&handle, &sandbox)
+}
+
+/// Persist granted permissions and shell allowlists
+fn save_plugin_sandbox<R: Runtime>(handle: &AppHandle<R>, sandbox: &PluginSandbox) -> Result<(), String> {
+    let data_dir = app_paths::data_dir(handle)?;
+    sandbox.save(&plugin_permissions_path(&data_dir))
+}
+
+/// Version of an installed plugin, None when its manifest can't be read
+fn installed_version(handle: &AppHandle, plugin_id: &str) -> Option<String> {
+    installed_plugin_manifest(handle, plugin_id).ok().map(|manifest| manifest.version)
+}
+
+/// Queue consent prompts for the permissions a plugin version requests
+/// that were never answered. Unknown permission names are skipped; the
+/// validator reports those.
+pub(crate) fn request_plugin_consents<R: Runtime>(
+    handle: &AppHandle<R>,
+    plugin_id: &str,
+    version: &str,
+    permissions: &[String],
+    source: ConsentSource,
+) -> Result<(), String> {
+    let requested: Vec<PluginPermission> = permissions.iter().filter_map(|p| p.parse().ok()).collect();
+    ensure_stage(handle, StartupStage::Sandbox)?;
+    let sandbox = handle.state::<PluginSandbox>();
+    if sandbox.request_consents(plugin_id, version, &requested, source).is_empty() {
+        return Ok(());
+    }
+    save_plugin_sandbox(handle, &sandbox)
+}
+
+/// Get plugin permissions, consent history and settings
 #[tauri::command]
 pub fn get_plugin_permissions(
     handle: AppHandle,
     plugin_id: String,
+    sandbox: State<PluginSandbox>,
 ) -> Result<PluginPermissionsResponse, String> {
     // Get plugin manifest to check required permissions
     let plugins_dir = get_plugins_dir(&handle)?;
     let manifest_path = plugins_dir.join(&plugin_id).join("plugin.json");
     let manifest = read_plugin_manifest(&manifest_path)?;
 
-    // TODO: Load granted permissions from state
-    // For now, return all permissions from manifest
+    ensure_stage(&handle, StartupStage::Sandbox)?;
     Ok(PluginPermissionsResponse {
         permissions: manifest.permissions,
+        consents: sandbox.get_consents(&plugin_id),
         settings: Default::default(),
     })
 }
 
-/// Get plugin settings file path (T045)
-fn get_plugin_settings_path(handle: &AppHandle) -> Result<PathBuf, String> {
-    handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))
-        .map(|dir| dir.join("plugin-settings.json"))
-}
-
-/// Load plugin settings (T045)
-fn load_plugin_settings(handle: &AppHandle) -> Result<std::collections::HashMap<String, std::collections::HashMap<String, serde_json::Value>>, String> {
-    let settings_path = get_plugin_settings_path(handle)?;
-    if !settings_path.exists() {
-        return Ok(std::collections::HashMap::new());
+/// Permissions plugins request that the user hasn't answered yet
+#[tauri::command]
+pub fn get_pending_consents(handle: AppHandle, sandbox: State<PluginSandbox>) -> Vec<PendingConsent> {
+    if ensure_stage(&handle, StartupStage::Sandbox).is_err() {
+        return Vec::new();
     }
+    sandbox.get_pending_consents()
+}
 
-    let content = fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read plugin settings: {}", e))?;
+/// Answer a pending consent, granting the permission when `allow`
+#[tauri::command]
+pub fn respond_to_consent(
+    handle: AppHandle,
+    plugin_id: String,
+    permission: String,
+    allow: bool,
+    sandbox: State<PluginSandbox>,
+) -> Result<PermissionConsent, String> {
+    ensure_stage(&handle, StartupStage::Sandbox)?;
+    let permission: PluginPermission = permission.parse()?;
+
+    let consent = sandbox.respond_to_consent(&plugin_id, &permission, allow)?;
+    save_plugin_sandbox(&handle, &sandbox)?;
+    Ok(consent)
+}
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse plugin settings: {}", e))
+/// Plugin settings file in an app data dir (T045)
+pub fn plugin_settings_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("plugin-settings.json")
 }
 
-/// Save plugin settings (T045)
-fn save_plugin_settings(handle: &AppHandle, settings: &std::collections::HashMap<String, std::collections::HashMap<String, serde_json::Value>>) -> Result<(), String> {
-    let settings_path = get_plugin_settings_path(handle)?;
-    let json = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize plugin settings: {}", e))?;
+/// Get plugin settings file path (T045)
+fn get_plugin_settings_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    app_paths::data_dir(handle).map(|dir| plugin_settings_path(&dir))
+}
 
-    fs::write(&settings_path, json)
-        .map_err(|e| format!("Failed to write plugin settings: {}", e))
+/// Plugin settings, keyed by plugin id then setting key (T045)
+fn plugin_settings_store<R: Runtime>(
+    handle: &AppHandle<R>,
+) -> Result<State<'_, StateStore<HashMap<String, HashMap<String, serde_json::Value>>>>, String> {
+    managed_store(handle, || Ok(StateStore::new(get_plugin_settings_path(handle)?, "plugin settings")))
 }
 
 /// Set plugin setting (T045)
@@ -599,10 +1291,12 @@ pub fn set_plugin_setting(
     key: String,
     value: serde_json::Value,
 ) -> Result<(), String> {
-    let mut all_settings = load_plugin_settings(&handle)?;
-    let plugin_settings = all_settings.entry(plugin_id).or_insert_with(std::collections::HashMap::new);
-    plugin_settings.insert(key, value);
-    save_plugin_settings(&handle, &all_settings)
+    // Settings can change what a trigger returns
+    invalidate_cached_results(&handle, &plugin_id);
+    plugin_settings_store(&handle)?.update(|all_settings| {
+        all_settings.entry(plugin_id).or_default().insert(key, value);
+        Ok(())
+    })
 }
 
 /// Get plugin setting (T045)
@@ -612,7 +1306,7 @@ pub fn get_plugin_setting(
     plugin_id: String,
     key: String,
 ) -> Result<serde_json::Value, String> {
-    let all_settings = load_plugin_settings(&handle)?;
+    let all_settings = plugin_settings_store(&handle)?.read()?;
     if let Some(plugin_settings) = all_settings.get(&plugin_id) {
         if let Some(value) = plugin_settings.get(&key) {
             return Ok(value.clone());
@@ -630,37 +1324,21 @@ pub fn get_plugin_setting(
 // ============================================================================
 
 /// Get plugin usage stats file path
-fn get_plugin_usage_stats_path(handle: &AppHandle) -> Result<PathBuf, String> {
-    handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))
+fn get_plugin_usage_stats_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    app_paths::data_dir(handle)
         .map(|dir| dir.join("plugin-usage-stats.json"))
 }
 
-/// Load plugin usage stats
-fn load_plugin_usage_stats(handle: &AppHandle) -> Result<HashMap<String, PluginUsageStats>, String> {
-    let stats_path = get_plugin_usage_stats_path(handle)?;
-    if !stats_path.exists() {
-        return Ok(HashMap::new());
-    }
-
-    let content = fs::read_to_string(&stats_path)
-        .map_err(|e| format!("Failed to read plugin usage stats: {}", e))?;
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse plugin usage stats: {}", e))
+/// Plugin usage stats, keyed by plugin id
+fn plugin_usage_stats_store<R: Runtime>(
+    handle: &AppHandle<R>,
+) -> Result<State<'_, StateStore<HashMap<String, PluginUsageStats>>>, String> {
+    managed_store(handle, || Ok(StateStore::new(get_plugin_usage_stats_path(handle)?, "plugin usage stats")))
 }
 
-/// Save plugin usage stats
-#[allow(dead_code)]
-fn save_plugin_usage_stats(handle: &AppHandle, stats: &HashMap<String, PluginUsageStats>) -> Result<(), String> {
-    let stats_path = get_plugin_usage_stats_path(handle)?;
-    let json = serde_json::to_string_pretty(stats)
-        .map_err(|e| format!("Failed to serialize plugin usage stats: {}", e))?;
-
-    fs::write(&stats_path, json)
-        .map_err(|e| format!("Failed to write plugin usage stats: {}", e))
+/// Load plugin usage stats
+fn load_plugin_usage_stats<R: Runtime>(handle: &AppHandle<R>) -> Result<HashMap<String, PluginUsageStats>, String> {
+    plugin_usage_stats_store(handle)?.read()
 }
 
 /// Get plugin usage stats
@@ -690,23 +1368,42 @@ pub fn get_plugin_health(
 ) -> Result<PluginHealth, String> {
     let plugins_dir = get_plugins_dir(&handle)?;
     let plugin_path = plugins_dir.join(&plugin_id);
-    get_plugin_health_for(&plugin_id, &plugin_path)
+    get_plugin_health_for(&plugin_id, &plugin_path).map(|health| with_memory_warning(&handle, &plugin_id, health))
 }
 
-/// Check plugin health
+/// Downgrade a healthy plugin to Warning when its memory use is too high
+fn with_memory_warning(handle: &AppHandle, plugin_id: &str, mut health: PluginHealth) -> PluginHealth {
+    if health.status == PluginHealthStatus::Healthy {
+        if let Some(message) = memory_warning(handle, plugin_id) {
+            health.status = PluginHealthStatus::Warning;
+            health.message = Some(message);
+        }
+    }
+    health
+}
+
+/// Check plugin health. Reads the plugin folder and may save new consent
+/// requests, so it runs on the blocking pool.
 #[tauri::command]
-pub fn check_plugin_health(
+pub async fn check_plugin_health(
     handle: AppHandle,
     plugin_id: String,
 ) -> Result<PluginHealth, String> {
+    run_blocking(move || check_plugin_health_now(handle, plugin_id)).await
+}
+
+fn check_plugin_health_now(handle: AppHandle, plugin_id: String) -> Result<PluginHealth, String> {
     // Trigger active health check
     let plugins_dir = get_plugins_dir(&handle)?;
-    let plugin_path = plugins_dir.join(&plugin_id);
+    let plugin_path = installed_plugin_dir(&plugins_dir, &plugin_id)?;
 
     let manifest_path = plugin_path.join("plugin.json");
     let manifest = read_plugin_manifest(&manifest_path)?;
     let entry_path = plugin_path.join(&manifest.entry);
 
+    // Catches permissions added by an update made outside the app
+    request_plugin_consents(&handle, &plugin_id, &manifest.version, &manifest.permissions, ConsentSource::Update)?;
+
     // Check if entry point exists and is readable
     let mut errors = vec![];
     let status = if entry_path.exists() {
@@ -740,12 +1437,76 @@ pub fn check_plugin_health(
         Some("Plugin has errors".to_string())
     };
 
-    Ok(PluginHealth {
+    let health = with_api_compatibility(&manifest, PluginHealth {
         status,
         message,
         last_checked: chrono::Utc::now().timestamp_millis(),
         errors,
+    });
+    let health = with_memory_warning(&handle, &plugin_id, health);
+    plugin_registry(&handle).lock().set_health(&plugin_id, health.clone());
+    Ok(health)
+}
+
+/// Re-check every listed plugin's health, reading plugin folders outside
+/// the registry lock. Plugins whose manifest is gone are left to the
+/// plugins dir watcher.
+fn refresh_plugin_health<R: Runtime>(handle: &AppHandle<R>) {
+    let registry = plugin_registry(handle);
+    let listed = registry.lock().snapshot().unwrap_or_default();
+    let checked: Vec<(String, PluginHealth)> = listed
+        .into_iter()
+        .filter_map(|plugin| {
+            let health = get_plugin_health_for(&plugin.id, &PathBuf::from(&plugin.install_path)).ok()?;
+            Some((plugin.id, health))
+        })
+        .collect();
+
+    let mut plugins = registry.lock();
+    for (plugin_id, health) in checked {
+        plugins.set_health(&plugin_id, health);
+    }
+}
+
+/// Keep listed plugins' health current in the background
+pub(crate) fn start_plugin_health_task(handle: &AppHandle) -> Result<(), String> {
+    let app = handle.clone();
+    let supervisor = handle.state::<TaskSupervisor>();
+    supervisor.register(PLUGIN_HEALTH_TASK, TaskSchedule::every(HEALTH_CHECK_INTERVAL), move |_| {
+        refresh_plugin_health(&app);
+        Ok(())
+    })?;
+    supervisor.start(PLUGIN_HEALTH_TASK)
+}
+
+/// Rescan the registry when plugin folders are added or removed outside
+/// the app. The permission and state files next to them don't count.
+pub(crate) fn start_plugins_dir_watcher(handle: &AppHandle) -> Result<(), String> {
+    let plugins_dir = ensure_plugins_dir(handle)?;
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |res: Result<notify::Event, _>| {
+        if let Ok(event) = res {
+            if event.paths.iter().any(|path| !path.extension().is_some_and(|extension| extension == "json")) {
+                let _ = tx.send(event);
+            }
+        }
     })
+    .map_err(|e| format!("Failed to create plugins dir watcher: {}", e))?;
+    watcher
+        .watch(&plugins_dir, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to watch plugins dir: {}", e))?;
+
+    let handle = handle.clone();
+    std::thread::spawn(move || {
+        // The watcher stops sending when dropped
+        let _watcher = watcher;
+        run_debounced(rx, Debounce::new(WATCH_QUIET_PERIOD, WATCH_MAX_DELAY), || {
+            if let Err(e) = refresh_plugin_registry(&handle) {
+                eprintln!("[Plugins] Failed to rescan plugins: {}", e);
+            }
+        });
+    });
+    Ok(())
 }
 
 // ============================================================================
@@ -763,7 +1524,7 @@ pub fn bulk_enable_plugins(
 
     for plugin_id in &plugin_ids {
         let result = match enable_plugin(handle.clone(), plugin_id.clone()) {
-            Ok(()) => crate::models::plugin::BulkOperationResult {
+            Ok(_) => crate::models::plugin::BulkOperationResult {
                 plugin_id: plugin_id.clone(),
                 success: true,
                 error: None,
@@ -806,7 +1567,7 @@ pub fn bulk_disable_plugins(
 
     for plugin_id in &plugin_ids {
         let result = match disable_plugin(handle.clone(), plugin_id.clone()) {
-            Ok(()) => crate::models::plugin::BulkOperationResult {
+            Ok(_) => crate::models::plugin::BulkOperationResult {
                 plugin_id: plugin_id.clone(),
                 success: true,
                 error: None,
@@ -838,17 +1599,21 @@ pub fn bulk_disable_plugins(
     })
 }
 
-/// Bulk uninstall plugins
+/// Bulk uninstall plugins. Removing plugin trees runs on the blocking pool.
 #[tauri::command]
-pub fn bulk_uninstall_plugins(
+pub async fn bulk_uninstall_plugins(
     handle: AppHandle,
     plugin_ids: Vec<String>,
 ) -> Result<BulkOperation, String> {
+    run_blocking(move || uninstall_all(handle, plugin_ids)).await
+}
+
+fn uninstall_all(handle: AppHandle, plugin_ids: Vec<String>) -> Result<BulkOperation, String> {
     let started_at = chrono::Utc::now().timestamp_millis();
     let mut results = vec![];
 
     for plugin_id in &plugin_ids {
-        let result = match uninstall_plugin(handle.clone(), plugin_id.clone()) {
+        let result = match uninstall_plugin(handle.clone(), plugin_id.clone(), None, None) {
             Ok(()) => crate::models::plugin::BulkOperationResult {
                 plugin_id: plugin_id.clone(),
                 success: true,
@@ -889,24 +1654,35 @@ pub struct CancelInstallResponse {
     pub cleanup_required: bool,
 }
 
+/// Add the triggers of a validated package that collide with installed
+/// plugins. `plugin_id` is the id it will install as, when it replaces one.
+fn with_trigger_conflicts(
+    handle: &AppHandle,
+    plugin_id: Option<&str>,
+    mut validation: InstallerValidation,
+) -> Result<InstallerValidation, String> {
+    if let Some(manifest) = &validation.manifest {
+        validation.trigger_conflicts = detect_trigger_conflicts(handle, plugin_id, &manifest.triggers)?;
+    }
+    Ok(validation)
+}
+
 /// Validate plugin package from file (US1-T004)
 #[tauri::command]
 pub async fn plugin_validate_package(
     handle: AppHandle,
     file_path: String,
     _source: String,
+    plugin_id: Option<String>,
 ) -> Result<InstallerValidation, String> {
-    let temp_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get temp dir: {}", e))?
-        .join("temp");
+    let temp_dir = get_installer_temp_dir(&handle)?;
     
     let installer = PluginInstaller::new(temp_dir, get_plugins_dir(&handle)?);
-    installer
+    let validation = installer
         .validate_package(&file_path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+    with_trigger_conflicts(&handle, plugin_id.as_deref(), validation)
 }
 
 /// Extract plugin package (US1-T005)
@@ -915,52 +1691,95 @@ pub async fn plugin_extract_package(
     handle: AppHandle,
     file_path: String,
 ) -> Result<InstallerResult, String> {
-    let temp_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get temp dir: {}", e))?
-        .join("temp");
+    let temp_dir = get_installer_temp_dir(&handle)?;
     
     let installer = PluginInstaller::new(temp_dir, get_plugins_dir(&handle)?);
-    installer
+    let result = installer
         .extract_package(&file_path)
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string())?;
+
+    begin_install_job(&handle, &result);
+    Ok(result)
 }
 
-/// Install plugin from extracted directory (US1-T006)
+/// Install plugin from extracted directory (US1-T006). `permissions` are
+/// the ones the user accepted in the install dialog; any other permission
+/// the manifest requests awaits consent.
 #[tauri::command]
 pub async fn plugin_install(
     handle: AppHandle,
     extracted_path: String,
     plugin_id: String,
-    _permissions: Vec<String>,
+    permissions: Vec<String>,
     auto_enable: Option<bool>,
-) -> Result<Plugin, String> {
+    allow_low_security_score: Option<bool>,
+) -> Result<PluginChange, String> {
     let plugins_dir = get_plugins_dir(&handle)?;
-    let temp_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get temp dir: {}", e))?
-        .join("temp");
+    let min_security_score = crate::cmds::settings::load_settings(&handle)?.min_plugin_security_score;
+    let temp_dir = get_installer_temp_dir(&handle)?;
 
     let installer = PluginInstaller::new(temp_dir, plugins_dir.clone());
 
     // Install plugin
-    installer
-        .install_plugin(&extracted_path, &plugin_id)
-        .await
-        .map_err(|e| e.to_string())?;
-
-    // Set enabled state
-    let enabled = auto_enable.unwrap_or(false);
-    save_plugin_enabled_state(&handle, &plugin_id, enabled)
-        .map_err(|e| format!("Failed to save plugin state: {}", e))?;
+    let start = std::time::Instant::now();
+    let memory = MemoryProbe::start();
+    let installed = installer
+        .install_plugin(
+            &extracted_path,
+            &plugin_id,
+            min_security_score,
+            allow_low_security_score.unwrap_or(false),
+        )
+        .await;
+    record_native_operation(
+        &handle,
+        &plugin_id,
+        "install".to_string(),
+        start.elapsed().as_millis() as u64,
+        installed.is_ok(),
+        memory.finish(),
+    );
+    installed.map_err(|e| e.to_string())?;
+    // A reinstall replaces the code behind any cached results
+    invalidate_cached_results(&handle, &plugin_id);
+
+    // Extraction folders would otherwise pile up in the temp dir
+    if let Some(job_id) = job_id_for(Path::new(&extracted_path)) {
+        handle.state::<InstallJobManager>().finish(&job_id);
+    }
+    if let Err(e) = installer.cleanup_extraction(&extracted_path) {
+        eprintln!("[Plugins] Failed to remove extraction folder {}: {}", extracted_path, e);
+    }
 
-    // Load and return installed plugin
+    // Load the installed manifest; triggers colliding with installed
+    // plugins start out suppressed
     let manifest_path = plugins_dir.join(&plugin_id).join("plugin.json");
     let manifest = read_plugin_manifest(&manifest_path)
         .map_err(|e| format!("Failed to read installed manifest: {}", e))?;
+    let conflicts = detect_trigger_conflicts(&handle, Some(&plugin_id), &manifest.triggers)?;
+    settle_trigger_conflicts(&handle, &conflicts)?;
+
+    // Set enabled state and list the plugin together
+    let enabled = auto_enable.unwrap_or(false);
+    let state_revision = with_registry(&handle, |plugins| {
+        write_plugin_enabled_state(&handle, &plugin_id, enabled)
+            .map_err(|e| format!("Failed to save plugin state: {}", e))?;
+        refresh_plugin_entry(&handle, plugins, &plugin_id)?;
+        Ok(plugins.revision())
+    })?;
+
+    ensure_stage(&handle, StartupStage::Sandbox)?;
+    let sandbox = handle.state::<PluginSandbox>();
+    let accepted: Vec<PluginPermission> = permissions.iter().filter_map(|p| p.parse().ok()).collect();
+    for permission in manifest.permissions.iter().filter_map(|p| p.parse::<PluginPermission>().ok()) {
+        if accepted.contains(&permission) {
+            sandbox.set_consent(&plugin_id, permission, true, Some(manifest.version.clone()), ConsentSource::Install);
+        }
+    }
+    save_plugin_sandbox(&handle, &sandbox)?;
+    request_plugin_consents(&handle, &plugin_id, &manifest.version, &manifest.permissions, ConsentSource::Install)?;
+    offer_archived_settings(&handle, &plugin_id);
 
     let health = get_plugin_health_for(&plugin_id, &plugins_dir.join(&plugin_id))?;
     let stats = PluginUsageStats {
@@ -970,12 +1789,11 @@ pub async fn plugin_install(
         average_execution_time: None,
     };
 
-    let installed_at = get_plugin_installation_time(&plugins_dir.join(&plugin_id))
-        .map_err(|e| format!("Failed to get installation time: {}", e))?;
-
     let plugin_path = plugins_dir.join(&plugin_id);
+    let install = read_install_meta(&plugin_path, PluginSource::Local, &manifest.version)?;
+    let suppressed_triggers = plugin_suppressed_triggers(&handle, &plugin_id, &manifest.triggers)?;
 
-    Ok(Plugin {
+    let plugin = Plugin {
         id: plugin_id.clone(),
         name: manifest.name,
         version: manifest.version,
@@ -986,12 +1804,18 @@ pub async fn plugin_install(
         entry_point: manifest.entry,
         triggers: manifest.triggers,
         settings: HashMap::new(),
+        api_compatibility: check_api_version(manifest.api_version.as_deref()),
+        api_version: manifest.api_version,
+        protected: is_protected(&installed_name(&plugin_id, &plugin_path), manifest.protected),
+        suppressed_triggers,
+        links: plugin_links(manifest.links, &plugin_path),
         health,
         usage_stats: stats,
-        installed_at,
+        installed_at: install.installed_at,
         install_path: plugin_path.to_string_lossy().to_string(),
-        source: crate::models::plugin::PluginSource::Local,
-    })
+        source: install.source,
+    };
+    Ok(PluginChange { plugin, state_revision })
 }
 
 /// Get plugin installation status (US1-T007)
@@ -1010,15 +1834,24 @@ pub async fn plugin_get_install_status(
     })
 }
 
-/// Cancel installation (US1-T008)
+/// Cancel installation (US1-T008). `install_id` is the extraction folder
+/// name; its files are removed unless `cleanup` is false.
 #[tauri::command]
 pub async fn plugin_cancel_install(
-    _handle: AppHandle,
-    _install_id: String,
+    handle: AppHandle,
+    install_id: String,
     cleanup: Option<bool>,
 ) -> Result<CancelInstallResponse, String> {
-    // For now, just return success
-    // In real implementation, you'd clean up temp files and cancel operations
+    handle.state::<InstallJobManager>().finish(&install_id);
+
+    if cleanup.unwrap_or(true) {
+        let temp_dir = get_installer_temp_dir(&handle)?;
+        let extraction = temp_dir.join(&install_id);
+        PluginInstaller::new(temp_dir, get_plugins_dir(&handle)?)
+            .cleanup_extraction(extraction.to_string_lossy().as_ref())
+            .map_err(|e| format!("Failed to remove extraction folder: {}", e))?;
+    }
+
     Ok(CancelInstallResponse {
         success: true,
         message: "Installation cancelled".to_string(),
@@ -1026,6 +1859,25 @@ pub async fn plugin_cancel_install(
     })
 }
 
+/// Remove installer temp files older than `older_than_hours` (default 24),
+/// skipping extractions of installs still in progress. Returns bytes reclaimed.
+#[tauri::command]
+pub async fn cleanup_temp_files(
+    handle: AppHandle,
+    older_than_hours: Option<u64>,
+    jobs: State<'_, InstallJobManager>,
+) -> Result<u64, String> {
+    let older_than = older_than_hours.map(max_age_from_hours).unwrap_or(DEFAULT_TEMP_MAX_AGE);
+    sweep_temp_dir(&get_installer_temp_dir(&handle)?, older_than, &jobs.active_ids())
+}
+
+/// Track an extraction until it is installed or cancelled
+fn begin_install_job(handle: &AppHandle, extraction: &InstallerResult) {
+    if let Some(job_id) = job_id_for(Path::new(&extraction.path)) {
+        handle.state::<InstallJobManager>().begin(&job_id);
+    }
+}
+
 // ============================================================================
 // Buffer-based Plugin Installation (for drag-and-drop from web)
 // ============================================================================
@@ -1037,12 +1889,9 @@ pub async fn plugin_validate_package_from_buffer(
     buffer: Vec<u8>,
     file_name: String,
     _source: String,
+    plugin_id: Option<String>,
 ) -> Result<InstallerValidation, String> {
-    let temp_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get temp dir: {}", e))?
-        .join("temp");
+    let temp_dir = get_installer_temp_dir(&handle)?;
     
     fs::create_dir_all(&temp_dir)
         .map_err(|e| format!("Failed to create temp dir: {}", e))?;
@@ -1053,10 +1902,13 @@ pub async fn plugin_validate_package_from_buffer(
         .map_err(|e| format!("Failed to write buffer to file: {}", e))?;
     
     let installer = PluginInstaller::new(temp_dir, get_plugins_dir(&handle)?);
-    installer
+    let result = installer
         .validate_package(temp_file.to_string_lossy().as_ref())
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string());
+
+    let _ = fs::remove_file(&temp_file);
+    with_trigger_conflicts(&handle, plugin_id.as_deref(), result?)
 }
 
 /// Extract plugin package from buffer (US1-T005)
@@ -1066,11 +1918,7 @@ pub async fn plugin_extract_package_from_buffer(
     buffer: Vec<u8>,
     file_name: String,
 ) -> Result<InstallerResult, String> {
-    let temp_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get temp dir: {}", e))?
-        .join("temp");
+    let temp_dir = get_installer_temp_dir(&handle)?;
     
     fs::create_dir_all(&temp_dir)
         .map_err(|e| format!("Failed to create temp dir: {}", e))?;
@@ -1081,10 +1929,91 @@ pub async fn plugin_extract_package_from_buffer(
         .map_err(|e| format!("Failed to write buffer to file: {}", e))?;
 
     let installer = PluginInstaller::new(temp_dir, get_plugins_dir(&handle)?);
-    installer
+    let result = installer
         .extract_package(temp_file.to_string_lossy().as_ref())
         .await
-        .map_err(|e| e.to_string())
+        .map_err(|e| e.to_string());
+
+    let _ = fs::remove_file(&temp_file);
+    let result = result?;
+    begin_install_job(&handle, &result);
+    Ok(result)
+}
+
+// ============================================================================
+// Streamed Package Transfer (marketplace downloads and chunked uploads)
+// ============================================================================
+
+/// Package size limit from settings, in bytes
+fn max_package_bytes(handle: &AppHandle) -> Result<u64, String> {
+    Ok(crate::cmds::settings::load_settings(handle)?.max_plugin_package_mb as u64 * 1024 * 1024)
+}
+
+/// Start a chunked upload of a dropped package, returning the upload id
+#[tauri::command]
+pub fn plugin_begin_upload(
+    handle: AppHandle,
+    file_name: String,
+    total_size: Option<u64>,
+    uploads: State<UploadManager>,
+) -> Result<String, String> {
+    uploads.begin(
+        &get_installer_temp_dir(&handle)?,
+        &file_name,
+        total_size,
+        max_package_bytes(&handle)?,
+    )
+}
+
+/// Append a base64-encoded chunk, returning the bytes received so far
+#[tauri::command]
+pub fn plugin_append_upload_chunk(
+    upload_id: String,
+    chunk: String,
+    uploads: State<UploadManager>,
+) -> Result<u64, String> {
+    use base64::prelude::*;
+    let bytes = BASE64_STANDARD
+        .decode(chunk)
+        .map_err(|e| format!("Invalid chunk encoding: {}", e))?;
+    uploads.append(&upload_id, &bytes)
+}
+
+/// Finish an upload. The returned path can be passed to
+/// `plugin_validate_package` and `plugin_extract_package`.
+#[tauri::command]
+pub fn plugin_finish_upload(
+    upload_id: String,
+    uploads: State<UploadManager>,
+) -> Result<TransferredPackage, String> {
+    uploads.finish(&upload_id)
+}
+
+/// Abandon an upload and remove what was received
+#[tauri::command]
+pub fn plugin_cancel_upload(upload_id: String, uploads: State<UploadManager>) {
+    uploads.cancel(&upload_id);
+}
+
+/// Download a package into the installer temp dir, emitting
+/// `plugin-download:progress` as chunks arrive
+#[tauri::command]
+pub async fn plugin_download_package(
+    handle: AppHandle,
+    url: String,
+    file_name: Option<String>,
+) -> Result<TransferredPackage, String> {
+    // The extension is kept so the installer can tell the format
+    let name = file_name
+        .or_else(|| url.split(['?', '#']).next()?.rsplit('/').next().map(String::from))
+        .filter(|name| !name.is_empty() && !name.contains(['/', '\\']))
+        .unwrap_or_else(|| "plugin.zip".to_string());
+    let dest = get_installer_temp_dir(&handle)?.join(format!("{}-{}", uuid::Uuid::new_v4(), name));
+
+    download_to_file(&url, dest, max_package_bytes(&handle)?, |progress| {
+        let _ = events::emit(&handle, progress);
+    })
+    .await
 }
 
 // ============================================================================
@@ -1093,19 +2022,21 @@ pub async fn plugin_extract_package_from_buffer(
 
 /// Enable a plugin
 #[tauri::command]
-pub async fn plugin_enable(handle: AppHandle, plugin_id: String) -> Result<Plugin, String> {
+pub async fn plugin_enable(handle: AppHandle, plugin_id: String) -> Result<PluginChange, String> {
     let plugins_dir = ensure_plugins_dir(&handle)?;
 
     // Find plugin path (tries direct and npm-style locations)
     let actual_path = find_plugin_path(&plugins_dir, &plugin_id)?;
 
-    // Update enabled state
-    save_plugin_enabled_state(&handle, &plugin_id, true)?;
-
-    // Load and return updated plugin
     let manifest_path = actual_path.join("plugin.json");
     let manifest = read_plugin_manifest(&manifest_path)
         .map_err(|e| format!("Failed to read manifest: {}", e))?;
+    ensure_api_compatible(&manifest)?;
+
+    // Update enabled state
+    let state_revision = save_plugin_enabled_state(&handle, &plugin_id, true)?;
+
+    // Return the updated plugin
 
     let health = get_plugin_health_for(&plugin_id, &actual_path)?;
     let stats = load_plugin_usage_stats(&handle)?
@@ -1113,10 +2044,10 @@ pub async fn plugin_enable(handle: AppHandle, plugin_id: String) -> Result<Plugi
         .cloned()
         .unwrap_or_default();
 
-    let installed_at = get_plugin_installation_time(&actual_path)
-        .map_err(|e| format!("Failed to get installation time: {}", e))?;
+    let install = read_install_meta(&actual_path, PluginSource::Marketplace, &manifest.version)?;
+    let suppressed_triggers = plugin_suppressed_triggers(&handle, &plugin_id, &manifest.triggers)?;
 
-    Ok(Plugin {
+    let plugin = Plugin {
         id: plugin_id.clone(),
         name: manifest.name,
         version: manifest.version,
@@ -1127,24 +2058,31 @@ pub async fn plugin_enable(handle: AppHandle, plugin_id: String) -> Result<Plugi
         entry_point: manifest.entry,
         triggers: manifest.triggers,
         settings: HashMap::new(),
+        api_compatibility: check_api_version(manifest.api_version.as_deref()),
+        api_version: manifest.api_version,
+        protected: is_protected(&installed_name(&plugin_id, &actual_path), manifest.protected),
+        suppressed_triggers,
+        links: plugin_links(manifest.links, &actual_path),
         health,
         usage_stats: stats,
         install_path: actual_path.to_string_lossy().to_string(),
-        source: crate::models::plugin::PluginSource::Local,
-        installed_at,
-    })
+        source: install.source,
+        installed_at: install.installed_at,
+    };
+    Ok(PluginChange { plugin, state_revision })
 }
 
 /// Disable a plugin
 #[tauri::command]
-pub async fn plugin_disable(handle: AppHandle, plugin_id: String) -> Result<Plugin, String> {
+pub async fn plugin_disable(handle: AppHandle, plugin_id: String) -> Result<PluginChange, String> {
     let plugins_dir = ensure_plugins_dir(&handle)?;
 
     // Find plugin path (tries direct and npm-style locations)
     let actual_path = find_plugin_path(&plugins_dir, &plugin_id)?;
 
     // Update enabled state
-    save_plugin_enabled_state(&handle, &plugin_id, false)?;
+    invalidate_cached_results(&handle, &plugin_id);
+    let state_revision = save_plugin_enabled_state(&handle, &plugin_id, false)?;
 
     // Load and return updated plugin
     let manifest_path = actual_path.join("plugin.json");
@@ -1157,10 +2095,10 @@ pub async fn plugin_disable(handle: AppHandle, plugin_id: String) -> Result<Plug
         .cloned()
         .unwrap_or_default();
 
-    let installed_at = get_plugin_installation_time(&actual_path)
-        .map_err(|e| format!("Failed to get installation time: {}", e))?;
+    let install = read_install_meta(&actual_path, PluginSource::Marketplace, &manifest.version)?;
+    let suppressed_triggers = plugin_suppressed_triggers(&handle, &plugin_id, &manifest.triggers)?;
 
-    Ok(Plugin {
+    let plugin = Plugin {
         id: plugin_id.clone(),
         name: manifest.name,
         version: manifest.version,
@@ -1171,29 +2109,70 @@ pub async fn plugin_disable(handle: AppHandle, plugin_id: String) -> Result<Plug
         entry_point: manifest.entry,
         triggers: manifest.triggers,
         settings: HashMap::new(),
+        api_compatibility: check_api_version(manifest.api_version.as_deref()),
+        api_version: manifest.api_version,
+        protected: is_protected(&installed_name(&plugin_id, &actual_path), manifest.protected),
+        suppressed_triggers,
+        links: plugin_links(manifest.links, &actual_path),
         health,
         usage_stats: stats,
         install_path: actual_path.to_string_lossy().to_string(),
-        source: crate::models::plugin::PluginSource::Local,
-        installed_at,
-    })
+        source: install.source,
+        installed_at: install.installed_at,
+    };
+    Ok(PluginChange { plugin, state_revision })
+}
+
+/// Give `keyword` to `winner_plugin_id`: its colliding trigger fires, and
+/// other plugins' triggers colliding with the keyword are suppressed. The
+/// choice is kept across restarts and later installs.
+#[tauri::command]
+pub fn resolve_trigger_conflict<R: Runtime>(
+    handle: AppHandle<R>,
+    keyword: String,
+    winner_plugin_id: String,
+) -> Result<(), String> {
+    let plugins = list_plugins(&handle, false)?;
+    let winner = plugins
+        .iter()
+        .find(|plugin| plugin.id == winner_plugin_id)
+        .ok_or_else(|| t!("plugins.not_found", plugin_id = winner_plugin_id))?;
+    let contested = TriggerClaim::trigger(&winner.id, &keyword);
+    if !winner
+        .triggers
+        .iter()
+        .any(|trigger| contested.overlaps(&TriggerClaim::trigger(&winner.id, &trigger.keyword)))
+    {
+        return Err(format!("Plugin {} has no trigger matching {}", winner_plugin_id, keyword));
+    }
+
+    trigger_owners_store(&handle)?.update(|owners| {
+        owners.resolve(&keyword, &winner_plugin_id);
+        Ok::<_, String>(())
+    })?;
+    refresh_plugin_registry(&handle)
 }
 
 // ============================================================================
 // Uninstall Command (US4)
 // ============================================================================
 
-/// Uninstall a plugin
+/// Uninstall a plugin, archiving its settings first with `preserve_settings`
 #[tauri::command]
-pub async fn plugin_uninstall(handle: AppHandle, plugin_id: String) -> Result<(), String> {
-    // TODO: Check if it's a core plugin that should not be uninstalled
-    let core_plugins = vec!["core", "system"];
-    if core_plugins.contains(&plugin_id.as_str()) {
-        return Err(format!("不能卸载核心插件: {}", plugin_id));
-    }
-
+pub async fn plugin_uninstall(
+    handle: AppHandle,
+    plugin_id: String,
+    purge_data: Option<bool>,
+    preserve_settings: Option<bool>,
+) -> Result<(), String> {
     // Use npm uninstall (matches new installation approach)
     let plugins_dir = ensure_plugins_dir(&handle)?;
+    if let Ok(plugin_path) = find_plugin_path(&plugins_dir, &plugin_id) {
+        ensure_uninstallable(&plugin_id, &plugin_path).map_err(|e| e.to_string())?;
+        if preserve_settings.unwrap_or(false) {
+            archive_plugin_settings(&handle, &plugin_id, &plugin_path)?;
+        }
+    }
 
     println!("[plugin_uninstall] Running: npm uninstall {}", plugin_id);
     let output = Command::new("npm")
@@ -1209,10 +2188,10 @@ pub async fn plugin_uninstall(handle: AppHandle, plugin_id: String) -> Result<()
 
     println!("[plugin_uninstall] npm uninstall successful");
 
-    // Remove plugin state
-    remove_plugin_state(&handle, &plugin_id)?;
+    invalidate_cached_results(&handle, &plugin_id);
+    forget_plugin(&handle, &plugin_id)?;
 
-    Ok(())
+    purge_plugin_data(&handle, &plugin_id, purge_data)
 }
 
 // ============================================================================
@@ -1227,52 +2206,34 @@ pub struct PluginAbbreviation {
 }
 
 /// Get plugin abbreviations configuration file path
-fn get_abbreviations_config_path(handle: &AppHandle) -> Result<PathBuf, String> {
-    let data_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))?;
+fn get_abbreviations_config_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    let data_dir = app_paths::data_dir(handle)?;
 
     Ok(data_dir.join("plugin_abbreviations.json"))
 }
 
+/// Plugin abbreviations, keyed by plugin id
+fn plugin_abbreviations_store<R: Runtime>(
+    handle: &AppHandle<R>,
+) -> Result<State<'_, StateStore<HashMap<String, Vec<PluginAbbreviation>>>>, String> {
+    managed_store(handle, || Ok(StateStore::new(get_abbreviations_config_path(handle)?, "abbreviations config")))
+}
+
 /// Get all plugin abbreviations
 #[tauri::command]
-pub fn get_plugin_abbreviations(handle: AppHandle) -> Result<HashMap<String, Vec<PluginAbbreviation>>, String> {
-    let config_path = get_abbreviations_config_path(&handle)?;
-
-    if !config_path.exists() {
-        return Ok(HashMap::new());
-    }
-
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read abbreviations config: {}", e))?;
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse abbreviations config: {}", e))
+pub fn get_plugin_abbreviations<R: Runtime>(
+    handle: AppHandle<R>,
+) -> Result<HashMap<String, Vec<PluginAbbreviation>>, String> {
+    plugin_abbreviations_store(&handle)?.read()
 }
 
 /// Save plugin abbreviations
 #[tauri::command]
-pub fn save_plugin_abbreviations(
-    handle: AppHandle,
+pub fn save_plugin_abbreviations<R: Runtime>(
+    handle: AppHandle<R>,
     config: HashMap<String, Vec<PluginAbbreviation>>,
 ) -> Result<(), String> {
-    let config_path = get_abbreviations_config_path(&handle)?;
-
-    // Ensure parent directory exists
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-    }
-
-    let content = serde_json::to_string_pretty(&config)
-        .map_err(|e| format!("Failed to serialize abbreviations config: {}", e))?;
-
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write abbreviations config: {}", e))?;
-
-    Ok(())
+    plugin_abbreviations_store(&handle)?.replace(config)
 }
 
 /// Set abbreviation for a plugin
@@ -1282,13 +2243,10 @@ pub fn set_plugin_abbreviation(
     plugin_id: String,
     abbreviation: PluginAbbreviation,
 ) -> Result<(), String> {
-    let mut config = get_plugin_abbreviations(handle.clone())?;
-
-    config.entry(plugin_id.clone())
-        .or_insert_with(Vec::new)
-        .push(abbreviation);
-
-    save_plugin_abbreviations(handle, config)
+    plugin_abbreviations_store(&handle)?.update(|config| {
+        config.entry(plugin_id).or_default().push(abbreviation);
+        Ok(())
+    })
 }
 
 /// Remove abbreviation from a plugin
@@ -1298,12 +2256,484 @@ pub fn remove_plugin_abbreviation(
     plugin_id: String,
     keyword: String,
 ) -> Result<(), String> {
-    let mut config = get_plugin_abbreviations(handle.clone())?;
+    plugin_abbreviations_store(&handle)?.update(|config| {
+        if let Some(abbreviations) = config.get_mut(&plugin_id) {
+            abbreviations.retain(|abbr| abbr.keyword != keyword);
+        }
+        Ok(())
+    })
+}
+
 
-    if let Some(abbreviations) = config.get_mut(&plugin_id) {
-        abbreviations.retain(|abbr| abbr.keyword != keyword);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestApp;
+    use serde_json::json;
+    use tauri::async_runtime::block_on;
+
+    fn manifest(name: &str, permissions: &[&str]) -> serde_json::Value {
+        json!({
+            "name": name,
+            "version": "1.0.0",
+            "description": format!("{} plugin", name),
+            "author": null,
+            "permissions": permissions,
+            "entry": "index.js",
+            "triggers": ["hello"],
+            "api_version": crate::services::plugin_api::HOST_API_VERSION,
+        })
     }
 
-    save_plugin_abbreviations(handle, config)
-}
+    fn by_id(plugins: Vec<Plugin>) -> HashMap<String, Plugin> {
+        plugins.into_iter().map(|p| (p.id.clone(), p)).collect()
+    }
+
+    #[test]
+    fn test_plugin_list_empty() {
+        let app = TestApp::new();
+        assert!(block_on(plugin_list(app.handle().clone(), None, None)).unwrap().is_empty());
+        // The plugins dir is created inside the test app
+        assert!(app.data_dir().join("plugins").is_dir());
+    }
+
+    #[test]
+    fn test_plugin_list_reads_seeded_plugins() {
+        let app = TestApp::new();
+        app.seed_plugin("hello", manifest("Hello", &["clipboard:read"]));
+        let broken = app.seed_plugin("broken-entry", manifest("Broken Entry", &[]));
+        fs::remove_file(broken.join("index.js")).unwrap();
+        // Skipped: no manifest, unparseable manifest
+        fs::create_dir_all(app.data_dir().join("plugins").join("empty")).unwrap();
+        app.seed_plugin("garbage", json!("not a manifest"));
+
+        let plugins = by_id(block_on(plugin_list(app.handle().clone(), None, None)).unwrap());
+        assert_eq!(plugins.len(), 2);
+
+        let hello = &plugins["hello"];
+        assert_eq!(hello.name, "Hello");
+        assert!(hello.enabled);
+        // Legacy permission names come back canonical
+        assert_eq!(hello.permissions, vec!["read_clipboard"]);
+        assert_eq!(hello.triggers[0].keyword, "hello");
+        assert_eq!(hello.health.status, PluginHealthStatus::Healthy);
+        assert_eq!(hello.usage_stats.usage_count, 0);
+
+        assert_eq!(plugins["broken-entry"].health.status, PluginHealthStatus::Error);
+    }
+
+    #[test]
+    fn test_only_web_links_are_listed_and_the_rest_warn() {
+        let app = TestApp::new();
+        let mut hello = manifest("Hello", &[]);
+        hello["homepage"] = json!("javascript:alert(1)");
+        let dir = app.seed_plugin("hello", hello);
+        fs::write(dir.join("package.json"), json!({ "repository": "acme/hello", "license": "MIT" }).to_string())
+            .unwrap();
+
+        let plugins = by_id(block_on(plugin_list(app.handle().clone(), None, None)).unwrap());
+        let links = &plugins["hello"].links;
+        assert_eq!(links.homepage, None);
+        assert_eq!(links.repository.as_deref(), Some("https://github.com/acme/hello"));
+        assert_eq!(links.license.as_deref(), Some("MIT"));
+
+        let validation = validate_plugin_manifest(app.handle().clone(), "hello".to_string()).unwrap();
+        assert!(validation.warnings.iter().any(|warning| warning.contains("javascript:alert(1)")));
+    }
+
+    #[test]
+    fn test_plugin_list_reflects_enabled_state() {
+        let app = TestApp::new();
+        app.seed_plugin("hello", manifest("Hello", &[]));
+        app.seed_plugin("other", manifest("Other", &[]));
+
+        disable_plugin(app.handle().clone(), "hello".to_string()).unwrap();
+        let plugins = by_id(block_on(plugin_list(app.handle().clone(), None, None)).unwrap());
+        assert!(!plugins["hello"].enabled);
+        assert!(plugins["other"].enabled);
+
+        enable_plugin(app.handle().clone(), "hello".to_string()).unwrap();
+        assert!(by_id(block_on(plugin_list(app.handle().clone(), None, None)).unwrap())["hello"].enabled);
+    }
+
+    #[test]
+    fn test_concurrent_toggles_keep_every_plugin() {
+        let app = TestApp::new();
+        let ids: Vec<String> = (0..10).map(|i| format!("plugin-{}", i)).collect();
+        for id in &ids {
+            app.seed_plugin(id, manifest(id, &[]));
+        }
+
+        let threads: Vec<_> = (0..100)
+            .map(|i| {
+                let handle = app.handle().clone();
+                let id = ids[i % 10].clone();
+                std::thread::spawn(move || {
+                    if (i / 10) % 2 == 0 {
+                        enable_plugin(handle, id)
+                    } else {
+                        disable_plugin(handle, id)
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap().unwrap();
+        }
+
+        let on_disk: HashMap<String, bool> =
+            serde_json::from_str(&fs::read_to_string(app.data_dir().join("plugin-state.json")).unwrap()).unwrap();
+        assert_eq!(on_disk.len(), ids.len());
+        assert!(ids.iter().all(|id| on_disk.contains_key(id)));
+    }
+
+    #[test]
+    fn test_plugin_list_reports_api_compatibility() {
+        let app = TestApp::new();
+        let with_api = |name: &str, api_version: serde_json::Value| {
+            let mut manifest = manifest(name, &[]);
+            manifest["api_version"] = api_version;
+            manifest
+        };
+        app.seed_plugin("current", manifest("Current", &[]));
+        app.seed_plugin("legacy", with_api("Legacy", json!("1.0.0")));
+        app.seed_plugin("undeclared", with_api("Undeclared", serde_json::Value::Null));
+        app.seed_plugin("ancient", with_api("Ancient", json!("0.9.0")));
+
+        let plugins = by_id(block_on(plugin_list(app.handle().clone(), None, None)).unwrap());
+        assert_eq!(plugins["current"].api_compatibility, ApiCompatibility::Compatible);
+        assert_eq!(plugins["current"].health.status, PluginHealthStatus::Healthy);
+
+        for id in ["legacy", "undeclared"] {
+            assert_eq!(plugins[id].api_compatibility, ApiCompatibility::Legacy);
+            assert_eq!(plugins[id].health.status, PluginHealthStatus::Warning);
+            assert!(plugins[id].enabled);
+        }
+        assert_eq!(plugins["undeclared"].api_version, None);
+
+        // Below the supported range: quarantined, and it stays off
+        let ancient = &plugins["ancient"];
+        assert_eq!(ancient.api_version.as_deref(), Some("0.9.0"));
+        assert_eq!(ancient.api_compatibility, ApiCompatibility::Incompatible);
+        assert_eq!(ancient.health.status, PluginHealthStatus::Error);
+        assert_eq!(ancient.health.errors[0].code, "API_VERSION_UNSUPPORTED");
+        assert!(!ancient.enabled);
+        assert_eq!(get_plugin_enabled_state(app.handle(), "ancient"), Ok(false));
+        assert!(enable_plugin(app.handle().clone(), "ancient".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_plugin_results_cached_until_reload() {
+        let app = TestApp::new();
+        app.manage(PluginResultCache::new(16));
+        let cached_manifest = |name: &str, permissions: &[&str]| {
+            let mut manifest = manifest(name, permissions);
+            manifest["triggers"] = json!([{ "keyword": "b64:", "cache_ttl_ms": 60_000 }, "hello"]);
+            manifest
+        };
+        app.seed_plugin("base64", cached_manifest("Base64", &["notification"]));
+        app.seed_plugin("paste", cached_manifest("Paste", &["clipboard:read"]));
+        let handle = || app.handle().clone();
+        let store = |id: &str, trigger: &str| {
+            cache_plugin_result(handle(), id.to_string(), trigger.to_string(), "b64: hi".to_string(), json!(["aGk="]))
+                .unwrap()
+        };
+        let cache = app.handle().state::<PluginResultCache>();
+
+        assert!(store("base64", "b64:"));
+        assert_eq!(
+            cache.get("base64", "b64:", "b64: hi"),
+            Some(CachedPluginResult {
+                payload: json!(["aGk="]),
+                cached: true,
+            })
+        );
+        // No TTL declared, or results may depend on the clipboard
+        assert!(!store("base64", "hello"));
+        assert!(!store("paste", "b64:"));
+        assert_eq!(cache.len(), 1);
+
+        reload_plugin(handle(), "base64".to_string()).unwrap();
+        assert!(cache.is_empty());
+
+        // Disabled plugins neither keep nor gain entries
+        assert!(store("base64", "b64:"));
+        disable_plugin(handle(), "base64".to_string()).unwrap();
+        assert!(cache.is_empty());
+        assert!(!store("base64", "b64:"));
+    }
+
+    #[test]
+    fn test_listing_reads_the_registry() {
+        let app = TestApp::new();
+        app.seed_plugin("hello", manifest("Hello", &[]));
+        assert_eq!(list_plugins(app.handle(), false).unwrap().len(), 1);
+
+        // Folders added behind the registry's back wait for a rescan
+        app.seed_plugin("other", manifest("Other", &[]));
+        assert_eq!(list_plugins(app.handle(), false).unwrap().len(), 1);
+        assert_eq!(list_plugins(app.handle(), true).unwrap().len(), 2);
+
+        fs::remove_dir_all(app.data_dir().join("plugins").join("other")).unwrap();
+        refresh_plugin_registry(app.handle()).unwrap();
+        let ids: Vec<_> = list_plugins(app.handle(), false).unwrap().into_iter().map(|p| p.id).collect();
+        assert_eq!(ids, vec!["hello"]);
+    }
+
+    #[test]
+    fn test_registry_follows_each_mutation() {
+        let app = TestApp::new();
+        app.manage(PluginSandbox::new());
+        let handle = || app.handle().clone();
+        let listed = || by_id(list_plugins(app.handle(), false).unwrap());
+        app.seed_plugin("hello", manifest("Hello", &[]));
+        // The installed copy is validated before it's replaced
+        let mut notes = manifest("Notes", &[]);
+        notes["author"] = json!("tester");
+        let notes_dir = app.seed_plugin("notes", notes.clone());
+        fs::write(notes_dir.join("index.js"), "export default {};").unwrap();
+        assert_eq!(listed().len(), 2);
+
+        disable_plugin(handle(), "hello".to_string()).unwrap();
+        assert!(!listed()["hello"].enabled);
+        enable_plugin(handle(), "hello".to_string()).unwrap();
+        assert!(listed()["hello"].enabled);
+
+        // Reinstalling from a folder of the same name replaces the entry
+        let source = tempfile::tempdir().unwrap();
+        let source_dir = source.path().join("notes");
+        let mut update = notes.clone();
+        update["version"] = json!("2.0.0");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(source_dir.join("plugin.json"), update.to_string()).unwrap();
+        fs::write(source_dir.join("index.js"), "export default {};").unwrap();
+        install_plugin(handle(), source_dir.to_string_lossy().into_owned()).unwrap();
+        assert_eq!(listed()["notes"].version, "2.0.0");
+
+        // A reload picks up changes made on disk
+        fs::remove_file(app.data_dir().join("plugins").join("notes").join("index.js")).unwrap();
+        reload_plugin(handle(), "notes".to_string()).unwrap();
+        assert_eq!(listed()["notes"].health.status, PluginHealthStatus::Error);
+
+        uninstall_plugin(handle(), "notes".to_string(), None, None).unwrap();
+        assert_eq!(listed().into_keys().collect::<Vec<_>>(), vec!["hello"]);
+    }
+
+    #[test]
+    fn test_listing_given_a_revision_sees_the_install_it_raced() {
+        let app = TestApp::new();
+        app.manage(PluginSandbox::new());
+        let handle = || app.handle().clone();
+        // The installed copy is validated before it's replaced
+        app.seed_plugin("notes", manifest("Notes", &[]));
+        list_plugins(app.handle(), false).unwrap();
+        let listing = |min_revision: u64| {
+            let handle = handle();
+            std::thread::spawn(move || by_id(block_on(plugin_list(handle, None, Some(min_revision))).unwrap()))
+        };
+
+        let source = tempfile::tempdir().unwrap();
+        let source_dir = source.path().join("notes");
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(source_dir.join("index.js"), "export default {};").unwrap();
+
+        let mut enabled = true;
+        for round in 0..20 {
+            let version = format!("1.0.{}", round);
+            let mut update = manifest("Notes", &[]);
+            update["version"] = json!(version);
+            fs::write(source_dir.join("plugin.json"), update.to_string()).unwrap();
+
+            // Asked for before the install lands, answered once it has
+            let raced = listing(plugin_registry(app.handle()).lock().revision() + 1);
+            let installed = install_plugin(handle(), source_dir.to_string_lossy().into_owned()).unwrap();
+            let listed = raced.join().unwrap();
+            assert_eq!(listed["notes"].version, version);
+            // A reinstall leaves the enabled state alone
+            assert_eq!(listed["notes"].enabled, enabled);
+
+            enabled = round % 2 == 1;
+            let raced = listing(installed.state_revision + 1);
+            let revision = if enabled {
+                enable_plugin(handle(), "notes".to_string())
+            } else {
+                disable_plugin(handle(), "notes".to_string())
+            }
+            .unwrap();
+            assert!(revision > installed.state_revision);
+            assert_eq!(raced.join().unwrap()["notes"].enabled, enabled);
+        }
 
+        // A revision that never comes only delays the listing
+        let start = std::time::Instant::now();
+        let listed = by_id(block_on(plugin_list(handle(), None, Some(u64::MAX))).unwrap());
+        assert!(start.elapsed() >= STATE_REVISION_WAIT);
+        assert!(listed["notes"].enabled);
+    }
+
+    #[test]
+    fn test_colliding_trigger_installs_suppressed_until_resolved() {
+        let app = TestApp::new();
+        app.manage(PluginSandbox::new());
+        let handle = || app.handle().clone();
+        let listed = || by_id(list_plugins(app.handle(), false).unwrap());
+        let mut palette = manifest("Palette", &[]);
+        palette["triggers"] = json!(["color:"]);
+        app.seed_plugin("palette", palette);
+        app.seed_plugin("picker", manifest("Picker", &[]));
+
+        // An update of picker that also answers "color:"
+        let source = tempfile::tempdir().unwrap();
+        let source_dir = source.path().join("picker");
+        let mut update = manifest("Picker", &[]);
+        update["triggers"] = json!(["color:", "pick:"]);
+        fs::create_dir_all(&source_dir).unwrap();
+        fs::write(source_dir.join("plugin.json"), update.to_string()).unwrap();
+        fs::write(source_dir.join("index.js"), "").unwrap();
+        let incoming = read_plugin_manifest(&source_dir.join("plugin.json")).unwrap();
+        let conflicts = detect_trigger_conflicts(app.handle(), Some("picker"), &incoming.triggers).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].plugin_id, "palette");
+
+        // The installed plugin keeps the keyword
+        let installed = install_plugin(handle(), source_dir.to_string_lossy().into_owned()).unwrap();
+        assert_eq!(installed.plugin.suppressed_triggers, vec!["color:"]);
+        assert_eq!(listed()["picker"].suppressed_triggers, vec!["color:"]);
+        assert!(listed()["palette"].suppressed_triggers.is_empty());
+
+        assert!(resolve_trigger_conflict(handle(), "color:".to_string(), "palette-less".to_string()).is_err());
+        assert!(resolve_trigger_conflict(handle(), "calc".to_string(), "picker".to_string()).is_err());
+        resolve_trigger_conflict(handle(), "color:".to_string(), "picker".to_string()).unwrap();
+        assert!(listed()["picker"].suppressed_triggers.is_empty());
+        assert_eq!(listed()["palette"].suppressed_triggers, vec!["color:"]);
+        let saved = fs::read_to_string(app.data_dir().join("trigger-owners.json")).unwrap();
+        assert_eq!(serde_json::from_str::<serde_json::Value>(&saved).unwrap(), json!({ "color:": "picker" }));
+
+        // Uninstalling the winner gives the keyword back
+        uninstall_plugin(handle(), "picker".to_string(), None, None).unwrap();
+        assert!(listed()["palette"].suppressed_triggers.is_empty());
+    }
+
+    #[test]
+    fn test_npm_plugin_triggers_count_in_conflicts() {
+        let app = TestApp::new();
+        let npm_dir = app.data_dir().join("plugins/node_modules/@etools-plugin/palette");
+        fs::create_dir_all(&npm_dir).unwrap();
+        let mut palette = manifest("palette", &[]);
+        palette["triggers"] = json!(["color:"]);
+        fs::write(npm_dir.join("plugin.json"), palette.to_string()).unwrap();
+        fs::write(
+            app.data_dir().join("plugins/package.json"),
+            json!({ "dependencies": { "@etools-plugin/palette": "latest" } }).to_string(),
+        )
+        .unwrap();
+
+        let mut incoming = manifest("Picker", &[]);
+        incoming["triggers"] = json!(["color:"]);
+        let incoming: PluginManifest = serde_json::from_value(incoming).unwrap();
+        let conflicts = detect_trigger_conflicts(app.handle(), Some("picker"), &incoming.triggers).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].plugin_id, "palette");
+    }
+
+    #[test]
+    fn test_uninstall_archives_and_restores_settings() {
+        let app = TestApp::new();
+        app.manage(PluginSandbox::new());
+        app.seed_plugin("notes", manifest("Notes", &["clipboard:read"]));
+        let handle = || app.handle().clone();
+        plugin_settings_store(app.handle())
+            .unwrap()
+            .update(|settings| {
+                settings.insert("notes".to_string(), HashMap::from([("folder".to_string(), json!("~/notes"))]));
+                Ok::<_, String>(())
+            })
+            .unwrap();
+        let sandbox = app.handle().state::<PluginSandbox>();
+        let consent = sandbox.set_consent(
+            "notes",
+            PluginPermission::ReadClipboard,
+            true,
+            Some("1.0.0".to_string()),
+            ConsentSource::Install,
+        );
+        disable_plugin(handle(), "notes".to_string()).unwrap();
+
+        uninstall_plugin(handle(), "notes".to_string(), None, Some(true)).unwrap();
+        let archive_file = app.data_dir().join("plugins-archive").join("notes-1.0.0.json");
+        assert!(archive_file.is_file());
+        // Nothing is left behind outside the archive
+        assert!(plugin_settings_store(app.handle()).unwrap().read().unwrap().is_empty());
+        assert!(load_plugin_state(app.handle()).unwrap().is_empty());
+        assert!(sandbox.get_consents("notes").is_empty());
+
+        app.seed_plugin("notes", manifest("Notes", &["clipboard:read"]));
+        let restored = restore_archived_settings(handle(), "notes".to_string()).unwrap();
+        assert_eq!(restored.version, "1.0.0");
+        let settings = plugin_settings_store(app.handle()).unwrap().read().unwrap();
+        assert_eq!(settings["notes"], HashMap::from([("folder".to_string(), json!("~/notes"))]));
+        assert_eq!(sandbox.get_consents("notes"), vec![consent]);
+        assert_eq!(sandbox.check_permission("notes", PluginPermission::ReadClipboard), Ok(true));
+        // Restored once
+        assert!(!archive_file.exists());
+        assert!(restore_archived_settings(handle(), "notes".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_uninstall_without_preserving_leaves_no_archive() {
+        let app = TestApp::new();
+        app.seed_plugin("notes", manifest("Notes", &[]));
+        uninstall_plugin(app.handle().clone(), "notes".to_string(), None, None).unwrap();
+        assert!(!app.data_dir().join("plugins-archive").exists());
+    }
+
+    #[test]
+    fn test_orphan_sweep_runs_once() {
+        let app = TestApp::new();
+        app.manage(PluginSandbox::new());
+        app.seed_plugin("kept", manifest("Kept", &[]));
+        let write = |name: &str, value: serde_json::Value| {
+            fs::write(app.data_dir().join(name), value.to_string()).unwrap();
+        };
+        write("plugin-settings.json", json!({ "kept": { "a": 1 }, "gone": { "a": 2 } }));
+        write("plugin-state.json", json!({ "kept": false, "gone": true }));
+        write("plugin_abbreviations.json", json!({ "gone": [{ "keyword": "g", "enabled": true }] }));
+        let sandbox = app.handle().state::<PluginSandbox>();
+        sandbox.ensure_registered("kept");
+        sandbox.ensure_registered("gone");
+
+        let report = sweep_orphaned_plugin_data(app.handle()).unwrap().unwrap();
+        assert_eq!(report.settings, vec!["gone"]);
+        assert_eq!(report.state, vec!["gone"]);
+        assert!(report.usage_stats.is_empty());
+        assert_eq!(report.abbreviations, vec!["gone"]);
+        assert_eq!(report.permissions, vec!["gone"]);
+
+        let settings = plugin_settings_store(app.handle()).unwrap().read().unwrap();
+        assert_eq!(settings.into_keys().collect::<Vec<_>>(), vec!["kept"]);
+        assert_eq!(get_plugin_enabled_state(app.handle(), "kept"), Ok(false));
+        assert!(get_plugin_abbreviations(app.handle().clone()).unwrap().is_empty());
+        assert_eq!(sandbox.get_registered_plugins(), vec!["kept"]);
+
+        // Entries orphaned later are uninstall's to remove, not the sweep's
+        write("plugin-state.json", json!({ "gone": true }));
+        assert_eq!(sweep_orphaned_plugin_data(app.handle()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_orphan_sweep_skipped_when_plugins_cant_be_listed() {
+        let app = TestApp::new();
+        fs::write(app.data_dir().join("plugin-settings.json"), json!({ "kept": { "a": 1 } }).to_string()).unwrap();
+        // Stands in for a plugins dir that can't be read
+        fs::write(app.data_dir().join("plugins"), "").unwrap();
+
+        assert!(sweep_orphaned_plugin_data(app.handle()).is_err());
+        let settings = plugin_settings_store(app.handle()).unwrap().read().unwrap();
+        assert!(settings.contains_key("kept"));
+
+        // Not recorded as done, so it runs once the folder is back
+        fs::remove_file(app.data_dir().join("plugins")).unwrap();
+        app.seed_plugin("kept", manifest("Kept", &[]));
+        assert!(sweep_orphaned_plugin_data(app.handle()).unwrap().is_some());
+    }
+}
@@ -0,0 +1,67 @@
+/**
+ * Privacy Commands
+ * Toggle privacy mode, which pauses capture and hides sensitive results
+ */
+
+use crate::services::privacy::{privacy_state_path, PrivacyMode, PrivacyStatus};
+use crate::services::search::cache::invalidate_search_cache;
+use crate::types::events;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+/// Emit the current status and drop cached results that depend on it
+fn notify_changed(handle: &AppHandle, status: &PrivacyStatus) {
+    invalidate_search_cache(handle);
+    let _ = events::emit(handle, status);
+}
+
+/// Persist or clear the saved privacy state
+fn save_privacy_state(handle: &AppHandle, privacy: &PrivacyMode) -> Result<(), String> {
+    let config_dir = handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?;
+    std::fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config dir: {}", e))?;
+
+    privacy.save(&privacy_state_path(&config_dir))
+}
+
+/// Turn privacy mode on or off.
+///
+/// `expires_in_minutes` turns it back off automatically; `persistent` keeps it
+/// on across restarts (it otherwise always starts off).
+#[tauri::command]
+pub fn set_privacy_mode(
+    handle: AppHandle,
+    enabled: bool,
+    expires_in_minutes: Option<u64>,
+    persistent: Option<bool>,
+    privacy: State<PrivacyMode>,
+) -> Result<PrivacyStatus, String> {
+    let expires_in = expires_in_minutes.map(|m| Duration::from_secs(m * 60));
+    let (status, generation) = privacy.set(enabled, expires_in, persistent.unwrap_or(false));
+
+    save_privacy_state(&handle, &privacy)?;
+    notify_changed(&handle, &status);
+
+    if let (true, Some(expires_in)) = (enabled, expires_in) {
+        let privacy = privacy.inner().clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(expires_in);
+            // A later change supersedes this timer
+            if privacy.expire(generation) {
+                let _ = save_privacy_state(&handle, &privacy);
+                notify_changed(&handle, &privacy.status());
+            }
+        });
+    }
+
+    Ok(status)
+}
+
+/// Get the current privacy mode status
+#[tauri::command]
+pub fn get_privacy_mode(privacy: State<PrivacyMode>) -> PrivacyStatus {
+    privacy.status()
+}
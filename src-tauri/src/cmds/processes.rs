@@ -0,0 +1,35 @@
+/**
+ * Process Commands
+ * Tauri commands for listing and ending running processes
+ */
+
+use crate::services::process_manager::{self, ProcessAction, ProcessCache, ProcessInfo};
+use crate::utils::run_blocking;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager};
+
+fn process_cache(handle: &AppHandle) -> Arc<ProcessCache> {
+    Arc::clone(handle.state::<Arc<ProcessCache>>().inner())
+}
+
+/// Running processes matching `query`, from a snapshot at most a couple of
+/// seconds old
+#[tauri::command]
+pub async fn search_processes(handle: AppHandle, query: String) -> Result<Vec<ProcessInfo>, String> {
+    let cache = process_cache(&handle);
+    run_blocking(move || Ok(process_manager::search_processes(&cache.snapshot(), &query))).await
+}
+
+/// Quit or force kill a process. `name` must still match the pid, and force
+/// kill is refused unless `confirmed`.
+#[tauri::command]
+pub async fn end_process(
+    handle: AppHandle,
+    pid: u32,
+    name: String,
+    action: ProcessAction,
+    confirmed: bool,
+) -> Result<(), String> {
+    let cache = process_cache(&handle);
+    run_blocking(move || process_manager::end_process(&cache, pid, &name, action, confirmed)).await
+}
@@ -0,0 +1,82 @@
+/**
+ * Retention Commands
+ * Storage usage per store, and pruning stores to their budgets, daily in
+ * the background or on demand
+ */
+
+use crate::cmds::settings::load_settings;
+use crate::db::get_data_dir;
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::retention::{
+    prune_all, retention_state_path, storage_report, RetentionState, StorePrune, StoreReport,
+};
+use crate::services::search::cache::invalidate_search_cache;
+use crate::services::task_supervisor::{TaskSchedule, TaskSupervisor};
+use crate::utils::{managed_store, run_blocking, StateStore};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+/// Background task pruning stores once a day
+const RETENTION_TASK: &str = "retention";
+/// How often the background task checks whether pruning is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+fn retention_state(handle: &AppHandle) -> Result<State<'_, StateStore<RetentionState>>, String> {
+    managed_store(handle, || {
+        let path = retention_state_path(&get_data_dir(handle)?);
+        Ok(StateStore::new(path, "retention state"))
+    })
+}
+
+/// Prune every store to the budgets in settings. A dry run deletes nothing
+/// and isn't recorded as a prune.
+fn prune(handle: &AppHandle, dry_run: bool) -> Result<Vec<StorePrune>, String> {
+    let settings = load_settings(handle)?;
+    let data_dir = get_data_dir(handle)?;
+    let now = chrono::Utc::now().timestamp();
+    let maintenance = handle.state::<MaintenanceGuard>();
+    let prunes = prune_all(&data_dir, &settings.retention, &maintenance, dry_run, now)?;
+
+    if !dry_run {
+        retention_state(handle)?.update(|state| {
+            for prune in &prunes {
+                state.last_pruned.insert(prune.store, now);
+            }
+            Ok::<_, String>(())
+        })?;
+        if prunes.iter().any(|p| p.deleted_rows > 0) {
+            invalidate_search_cache(handle);
+        }
+    }
+    Ok(prunes)
+}
+
+/// Check hourly and prune once a day has passed since the last run
+pub(crate) fn start_retention_task(handle: &AppHandle) -> Result<(), String> {
+    let app = handle.clone();
+    let supervisor = handle.state::<TaskSupervisor>();
+    supervisor.register(RETENTION_TASK, TaskSchedule::every(CHECK_INTERVAL), move |_| {
+        let state = retention_state(&app)?.read()?;
+        if state.is_due(chrono::Utc::now().timestamp()) {
+            prune(&app, false)?;
+        }
+        Ok(())
+    })?;
+    supervisor.start(RETENTION_TASK)
+}
+
+/// Size, row count and last prune time of every store, and how large the
+/// current settings let it grow
+#[tauri::command]
+pub fn get_storage_report(handle: AppHandle) -> Result<Vec<StoreReport>, String> {
+    let state = retention_state(&handle)?.read()?;
+    let settings = load_settings(&handle)?;
+    Ok(storage_report(&get_data_dir(&handle)?, &state, &settings))
+}
+
+/// Prune stores to their budgets now. With `dry_run`, report what would
+/// be deleted without deleting it.
+#[tauri::command]
+pub async fn prune_storage(handle: AppHandle, dry_run: bool) -> Result<Vec<StorePrune>, String> {
+    run_blocking(move || prune(&handle, dry_run)).await
+}
@@ -4,17 +4,109 @@
  */
 
 use crate::models::app::ApplicationEntry;
+use crate::models::preferences::AppSettings;
+use crate::models::pagination::{Page, PageCursor};
+use crate::cmds::clipboard::recent_clipboard_items;
+use crate::cmds::notifications::notify;
+use crate::db::analytics::{init_analytics_db, subject_usage};
+use crate::services::analytics::subject_salt;
+use crate::services::notifications::APP_SOURCE;
 use crate::services::app_monitor::AppMonitor;
+use crate::services::app_sources::AppSources;
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::privacy::PrivacyMode;
+use crate::services::query_history::{clear_queries, record_query, recent_queries, QueryHistoryEntry, QUERY_HISTORY_CAP};
 use crate::services::file_indexer::{FileIndexer, IndexerConfig};
-use crate::services::browser_reader::{BrowserReader, BrowserReaderConfig};
+use crate::services::browser_match::{match_context, MatchContext};
+use crate::services::path_display::abbreviate_path;
+use crate::services::browser_reader::{BrowserCacheUpdate, BrowserReader, BrowserReaderConfig};
+use crate::services::search::cache::{CacheKey, CacheStats};
+use crate::services::search::emoji_provider::{find_emoji, EmojiUsage};
+use crate::services::search::exclusion::{app_matches_pattern, filter_excluded};
+use crate::services::search::filters::QueryFilters;
+use crate::services::search::suggestions::{compose_suggestions, SuggestionSources, FRECENCY_WINDOW_DAYS};
+use crate::services::search::streaming::FIRST_PAGE_SIZE;
+use crate::services::search::{
+    MergedResults, PageError, ParsedQuery, ProviderRegistry, ResultsPage, RetainedResults, SearchCache,
+    SearchCancellation, SearchDebounce, SearchPerformance, SearchProfile, SearchTimings,
+};
+use crate::services::search::profile::SearchPerformanceReport;
+use crate::services::search_diagnostics::{diagnose, SearchDiagnosis};
+use crate::services::task_supervisor::{TaskSchedule, TaskSupervisor};
+use crate::types::events::{self, SearchComplete, SearchPartial};
+use crate::utils::{run_blocking, StateStore};
 use serde::{Deserialize, Serialize};
-use std::sync::Mutex;
-use tauri::{AppHandle, State};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+pub use crate::models::search::SearchResultItem;
+use crate::models::search::BuiltinIcon;
+
+/// Background task rescanning the indexed paths while the indexer runs
+pub(crate) const FILE_SCAN_TASK: &str = "file_index_scan";
+/// Background task applying file system events to the index
+pub(crate) const FILE_WATCH_TASK: &str = "file_index_watch";
 
 /// Global search state (T024)
 pub struct SearchState {
-    pub app_monitor: Mutex<AppMonitor>,
+    pub app_monitor: Arc<Mutex<AppMonitor>>,
     pub file_indexer: Mutex<Option<FileIndexer>>,
+    pub providers: Arc<ProviderRegistry>,
+    pub cancellation: Arc<SearchCancellation>,
+    /// Holds back streaming searches while the user types
+    pub debounce: Arc<SearchDebounce>,
+    /// Full results of the latest streaming searches, for paging
+    pub retained: Arc<RetainedResults>,
+    /// Timings of recent searches
+    pub performance: Arc<SearchPerformance>,
+    pub cache: SearchCache,
+    /// Settings snapshot shared with providers, kept in sync by save_settings
+    pub settings: Arc<RwLock<AppSettings>>,
+}
+
+impl SearchState {
+    /// Current settings snapshot
+    pub fn settings(&self) -> Result<AppSettings, String> {
+        self.settings
+            .read()
+            .map(|s| s.clone())
+            .map_err(|e| format!("Lock error: {}", e))
+    }
+}
+
+/// Push saved settings into SearchState and the app monitors, and drop
+/// cached results that may depend on the old values
+pub(crate) fn refresh_search_settings<R: Runtime>(handle: &AppHandle<R>, settings: &AppSettings) {
+    let sources = AppSources::from_settings(settings);
+    if let Some(state) = handle.try_state::<crate::cmds::app::AppState>() {
+        if let Ok(mut monitor) = state.app_monitor.lock() {
+            monitor.set_sources(sources);
+        }
+    }
+
+    if let Some(state) = handle.try_state::<SearchState>() {
+        if let Ok(mut current) = state.settings.write() {
+            *current = settings.clone();
+        }
+        state.cache.invalidate();
+
+        // Turning a source on or off changes the app list, so rescan it
+        let sources_changed = state
+            .app_monitor
+            .lock()
+            .map(|mut monitor| monitor.set_sources(sources))
+            .unwrap_or(false);
+        if sources_changed {
+            let handle = handle.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = crate::services::app_watcher::refresh_and_notify(&handle) {
+                    eprintln!("[Search] Failed to rescan apps: {}", e);
+                }
+            });
+        }
+    }
 }
 
 /// Unified search query
@@ -22,22 +114,14 @@ pub struct SearchState {
 pub struct SearchQuery {
     pub query: String,
     pub limit: Option<usize>,
-    #[allow(dead_code)]
+    /// Restrict the search to these provider names (e.g. "app", "file")
     pub sources: Option<Vec<String>>,
-}
-
-/// Search result item
-#[derive(Debug, Serialize, Clone)]
-pub struct SearchResultItem {
-    pub id: String,
-    pub title: String,
-    pub subtitle: String,
-    pub icon: Option<String>,
-    #[serde(rename = "type")]
-    pub result_type: String,
-    pub score: f64,
-    pub path: String,
-    pub frequency: u32,
+    /// Attach each result's score breakdown, for tuning scoring weights
+    #[serde(default)]
+    pub explain: bool,
+    /// Attach where the search's time went
+    #[serde(default)]
+    pub include_timings: bool,
 }
 
 /// Search response
@@ -46,165 +130,322 @@ pub struct SearchResponse {
     pub results: Vec<SearchResultItem>,
     pub total: usize,
     pub query_time: u64,
+    /// Result count per result type
+    pub groups: HashMap<String, usize>,
+    /// Result types with more results available via `search_more`
+    pub has_more_per_group: HashMap<String, bool>,
+    /// Results dropped as duplicates of another provider's result
+    pub duplicates_folded: usize,
+    /// Where the time went, if the query asked with `include_timings`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<SearchTimings>,
+    /// The query's exclusions and kind:/ext: filters, for results the
+    /// frontend adds itself
+    pub filters: QueryFilters,
 }
 
-/// Perform unified search
+impl SearchResponse {
+    fn from_merged(merged: MergedResults, start: std::time::Instant) -> Self {
+        Self {
+            total: merged.results.len(),
+            results: merged.results,
+            query_time: start.elapsed().as_millis() as u64,
+            groups: merged.groups,
+            has_more_per_group: merged.has_more_per_group,
+            duplicates_folded: merged.duplicates_folded,
+            timings: None,
+            filters: QueryFilters::default(),
+        }
+    }
+}
+
+/// Perform unified search across all registered providers
 #[tauri::command]
 pub fn unified_search(
     query: SearchQuery,
     state: State<SearchState>,
 ) -> Result<SearchResponse, String> {
     let start = std::time::Instant::now();
+    let profile = SearchProfile::start();
+
+    let settings = state.settings()?;
+    let parsed = ParsedQuery::parse(&query.query, &settings.query_prefixes)
+        .with_scoring(settings.scoring_weights, query.explain);
+    let limit = query.limit.unwrap_or(settings.max_results);
+
+    let merged = state.providers.search_cached(
+        &state.cache,
+        &parsed,
+        &settings,
+        query.sources.as_deref(),
+        limit,
+        Some(&profile),
+    );
+
+    let timings = state.performance.finish(&profile, settings.search_budget_ms);
+    let mut response = SearchResponse::from_merged(merged, start);
+    response.timings = query.include_timings.then_some(timings);
+    response.filters = parsed.filters;
+    Ok(response)
+}
 
-    let mut monitor = state.app_monitor.lock().map_err(|e| e.to_string())?;
+/// Explain why `expected_path` does or doesn't show up for `query`, as a
+/// report to paste into a bug report. Paths are hashed when usage is
+/// anonymized. Nothing is recorded as usage.
+#[tauri::command]
+pub async fn diagnose_search(
+    handle: AppHandle,
+    query: String,
+    expected_path: String,
+) -> Result<SearchDiagnosis, String> {
+    let state = handle.state::<SearchState>();
+    let settings = state.settings()?;
+    let providers = Arc::clone(&state.providers);
+    // The running indexer's roots, else the ones it would start with
+    let index = state
+        .file_indexer
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .as_ref()
+        .map(|indexer| indexer.config())
+        .unwrap_or_else(|| IndexerConfig {
+            paths: settings.file_index_paths.iter().map(|p| p.into()).collect(),
+            ..Default::default()
+        });
+    let files_db = crate::db::get_files_db_path(&handle)?;
+    let salt = subject_salt(&handle)?;
+
+    run_blocking(move || {
+        let parsed = ParsedQuery::parse(&query, &settings.query_prefixes)
+            .with_scoring(settings.scoring_weights, false);
+        // Don't create the index just to look into it
+        let files = if files_db.exists() {
+            Some(crate::db::files::open_files_db(&files_db).map_err(|e| format!("DB error: {}", e))?)
+        } else {
+            None
+        };
+        diagnose(
+            &providers,
+            &settings,
+            &parsed,
+            settings.max_results,
+            &index,
+            files.as_ref(),
+            std::path::Path::new(&expected_path),
+            salt.as_deref(),
+        )
+    })
+    .await
+}
 
-    // Get all apps
-    let apps = monitor.scan_apps();
+/// Recent queries whose results were acted on, newest first, for up-arrow recall
+#[tauri::command]
+pub fn get_query_history<R: Runtime>(handle: AppHandle<R>, limit: Option<usize>) -> Result<Vec<QueryHistoryEntry>, String> {
+    recent_queries(&handle, limit.unwrap_or(QUERY_HISTORY_CAP))
+}
 
-    // Filter by query if provided
-    let filtered: Vec<&ApplicationEntry> = if query.query.trim().is_empty() {
-        apps.iter().collect()
-    } else {
-        apps
-            .iter()
-            .filter(|app| {
-                let query_lower = query.query.to_lowercase();
-                let name_matches = app.name.to_lowercase().contains(&query_lower);
-
-                // Extract app bundle name from path for better matching
-                // e.g., "/Applications/Visual Studio Code.app/..." -> "visual studio code"
-                let app_name_from_path = app.executable_path
-                    .split('/')
-                    .find(|segment| segment.ends_with(".app"))
-                    .map(|s| s.trim_end_matches(".app").to_lowercase())
-                    .unwrap_or_default();
-
-                let path_app_name_matches = !app_name_from_path.is_empty()
-                    && app_name_from_path.contains(&query_lower);
-
-                // Check alternate names (e.g., .app filename like "Visual Studio Code")
-                let alternate_matches = app.alternate_names.as_ref().map_or(false, |names| {
-                    names.iter().any(|n| n.to_lowercase().contains(&query_lower))
-                });
+/// Remember a query once one of its results is acted on, so up-arrow
+/// recall only offers searches that went somewhere
+#[tauri::command]
+pub fn remember_query<R: Runtime>(handle: AppHandle<R>, query: String) {
+    record_query(&handle, &query);
+}
 
-                // NEW: Initialism/abbreviation matching (only for queries >= 2 chars)
-                // Allows searching "vsc" for "Visual Studio Code"
-                let initialism_matches = {
-                    // Only use initialism matching for queries of 2+ characters
-                    // to avoid over-matching single characters
-                    if query_lower.chars().all(|c| c.is_ascii_lowercase()) && query_lower.len() >= 2 {
-                        // Get initials from app name (split by spaces/special chars)
-                        let initials: String = app.name
-                            .split(|c: char| !c.is_alphanumeric())
-                            .filter(|s| !s.is_empty())
-                            .map(|word| word.chars().next().unwrap_or(' '))
-                            .collect::<String>()
-                            .to_lowercase();
-
-                        // Also get initials from path app name
-                        let path_initials: String = app_name_from_path
-                            .split(|c: char| !c.is_alphanumeric())
-                            .filter(|s| !s.is_empty())
-                            .map(|word| word.chars().next().unwrap_or(' '))
-                            .collect::<String>();
-
-                        // Only use starts_with to avoid over-matching
-                        initials.starts_with(&query_lower) || path_initials.starts_with(&query_lower)
-                    } else {
-                        false
-                    }
-                };
-
-                name_matches || path_app_name_matches || alternate_matches || initialism_matches
-            })
-            .collect()
-    };
+/// Forget all remembered queries
+#[tauri::command]
+pub fn clear_query_history<R: Runtime>(handle: AppHandle<R>) -> Result<(), String> {
+    clear_queries(&handle)
+}
 
-    // Limit results
-    let limit = query.limit.unwrap_or(50);
-    let limited: Vec<&ApplicationEntry> = filtered.into_iter().take(limit).collect();
-
-    // Convert to search results
-    let results: Vec<SearchResultItem> = limited
-        .iter()
-        .map(|app| {
-            // Calculate simple relevance score
-            let query_lower = query.query.to_lowercase();
-            let name_lower = app.name.to_lowercase();
-            let exact_match = if name_lower == query_lower { 1.0 } else { 0.0 };
-            let starts_with = if name_lower.starts_with(&query_lower) {
-                0.8
-            } else {
-                0.0
-            };
-            let contains = if name_lower.contains(&query_lower) {
-                0.5
-            } else {
-                0.0
-            };
+/// Count a use of an emoji picked from search so it ranks higher next time.
+/// Not counted while privacy mode is on.
+#[tauri::command]
+pub fn record_emoji_usage<R: Runtime>(
+    handle: AppHandle<R>,
+    usage: State<'_, Arc<StateStore<EmojiUsage>>>,
+    emoji: String,
+) -> Result<(), String> {
+    if handle.try_state::<PrivacyMode>().is_some_and(|privacy| privacy.is_active()) {
+        return Ok(());
+    }
 
-            // Check alternate names for scoring
-            let alternate_score = app.alternate_names.as_ref().map_or(0.0, |names| {
-                names.iter().fold(0.0_f64, |acc, n| {
-                    let n_lower = n.to_lowercase();
-                    let score = if n_lower == query_lower {
-                        0.9  // Slightly less than exact name match
-                    } else if n_lower.starts_with(&query_lower) {
-                        0.7
-                    } else if n_lower.contains(&query_lower) {
-                        0.4
-                    } else {
-                        0.0
-                    };
-                    acc.max(score)
-                })
-            });
+    let record = find_emoji(&emoji).ok_or_else(|| format!("Unknown emoji: {}", emoji))?;
+    usage.update(|usage| {
+        usage.record(record);
+        Ok::<_, String>(())
+    })
+}
 
-            // NEW: Score initialism matches (only for queries >= 2 chars)
-            let initialism_score = {
-                if query_lower.chars().all(|c| c.is_ascii_lowercase()) && query_lower.len() >= 2 {
-                    let initials: String = app.name
-                        .split(|c: char| !c.is_alphanumeric())
-                        .filter(|s| !s.is_empty())
-                        .map(|word| word.chars().next().unwrap_or(' '))
-                        .collect::<String>()
-                        .to_lowercase();
-
-                    if initials == query_lower {
-                        0.85  // Very high score for exact initialism match (e.g., "vsc" for "Visual Studio Code")
-                    } else if initials.starts_with(&query_lower) {
-                        0.65  // Good score for partial initialism match
-                    } else {
-                        0.0
-                    }
-                } else {
-                    0.0
+/// Names of the builtin icons results may ask for with `icon_kind`
+/// "builtin". The frontend maps each to its own icon set.
+#[tauri::command]
+pub fn get_builtin_icons() -> Vec<&'static str> {
+    BuiltinIcon::ALL.iter().map(|icon| icon.name()).collect()
+}
+
+/// Start a streaming search and return immediately.
+///
+/// Emits `search:partial` as each provider finishes and `search:complete`
+/// once all have. Both carry only the first FIRST_PAGE_SIZE results; the
+/// full ranked list is kept for `get_results_page`. Starting a newer
+/// `search_id` cancels this one, and a cancelled search emits nothing further.
+/// With `include_timings`, `search:complete` says where the time went up to
+/// sending it.
+///
+/// The first query after a pause of search_debounce_ms runs at once. One
+/// typed sooner waits out that pause and is dropped if a newer one arrives
+/// meanwhile, so a burst of keystrokes runs its first and last query only.
+/// A query cached from a recent search is answered at once either way.
+#[tauri::command]
+pub fn unified_search_streaming(
+    handle: AppHandle,
+    query: SearchQuery,
+    search_id: u64,
+    state: State<SearchState>,
+) -> Result<(), String> {
+    let settings = state.settings()?;
+
+    let Some(cancel) = state.cancellation.begin(search_id) else {
+        // A newer search is already running
+        return Ok(());
+    };
+    state.retained.begin(search_id);
+    let delay = std::time::Duration::from_millis(settings.search_debounce_ms);
+    let leading = state.debounce.arrive(search_id, delay);
+
+    let providers = state.providers.clone();
+    let cancellation = state.cancellation.clone();
+    let debounce = state.debounce.clone();
+    let retained = state.retained.clone();
+    let performance = state.performance.clone();
+
+    std::thread::spawn(move || {
+        // Returns early when the debounce drops the query; either way the
+        // search is finished after it
+        let run = || {
+            let parsed = ParsedQuery::parse(&query.query, &settings.query_prefixes)
+                .with_scoring(settings.scoring_weights, query.explain);
+            let limit = query.limit.unwrap_or(settings.max_results);
+            let key = CacheKey::new(&parsed, query.sources.as_deref(), limit);
+            let search_state = handle.state::<SearchState>();
+            let cached = search_state.cache.get(&key);
+            if cached.is_none() && !leading && !debounce.settle(search_id, delay) {
+                // A newer query arrived while this one waited
+                retained.abandon(search_id);
+                return;
+            }
+
+            let start = std::time::Instant::now();
+            let profile = SearchProfile::start();
+            let merged = match cached {
+                Some(merged) => {
+                    profile.cache_hit();
+                    Some(merged)
                 }
+                None => profile
+                    .stage("providers", || {
+                        providers.search_streaming(
+                            &parsed,
+                            &settings,
+                            query.sources.as_deref(),
+                            limit,
+                            &cancel,
+                            Some(&profile),
+                            |source, mut results| {
+                                let total = results.len();
+                                results.truncate(FIRST_PAGE_SIZE);
+                                profile.stage("emit", || {
+                                    let _ = events::emit(&handle, SearchPartial {
+                                        search_id,
+                                        source: source.to_string(),
+                                        total,
+                                        results,
+                                    });
+                                });
+                            },
+                        )
+                    })
+                    .map(|(batches, merged)| {
+                        search_state.cache.insert(key, batches, merged.clone());
+                        merged
+                    }),
             };
 
-            let frequency_boost = (app.usage_count as f64).log10() / 10.0;
-
-            SearchResultItem {
-                id: app.id.clone(),
-                title: app.name.clone(),
-                subtitle: app.executable_path.clone(),
-                icon: app.icon.clone(), // Return icon as-is (None or cached path)
-                result_type: "app".to_string(),
-                score: exact_match + starts_with + contains + alternate_score + initialism_score + frequency_boost,
-                path: app.app_path.clone().unwrap_or_else(|| app.executable_path.clone()),
-                frequency: app.usage_count,
+            if let Some(merged) = merged {
+                let total = merged.results.len();
+                let results = merged.results.iter().take(FIRST_PAGE_SIZE).cloned().collect();
+                profile.stage("retain", || retained.store(search_id, merged.results));
+                let timings = query.include_timings.then(|| profile.timings());
+                profile.stage("emit", || {
+                    let _ = events::emit(&handle, SearchComplete {
+                        search_id,
+                        total,
+                        results,
+                        query_time: start.elapsed().as_millis() as u64,
+                        groups: merged.groups,
+                        has_more_per_group: merged.has_more_per_group,
+                        duplicates_folded: merged.duplicates_folded,
+                        filters: parsed.filters.clone(),
+                        timings,
+                    });
+                });
+                performance.finish(&profile, settings.search_budget_ms);
+            } else {
+                retained.abandon(search_id);
             }
-        })
-        .collect();
+        };
+        run();
 
-    let total = results.len();
-    let query_time = start.elapsed().as_millis() as u64;
+        cancellation.finish(search_id);
+    });
 
-    Ok(SearchResponse {
-        results,
-        total,
-        query_time,
-    })
+    Ok(())
+}
+
+/// Results of a streaming search past the first page, as the user scrolls
+/// or arrows down. Fails with `Pending` while the search is still running,
+/// and with `Expired` once it was cancelled or newer searches pushed its
+/// results out; search again then.
+#[tauri::command]
+pub fn get_results_page(
+    search_id: u64,
+    offset: usize,
+    limit: usize,
+    state: State<SearchState>,
+) -> Result<ResultsPage, PageError> {
+    state.retained.page(search_id, offset, limit)
+}
+
+/// Latency percentiles of recent searches, overall and per provider and
+/// stage, for the diagnostics page
+#[tauri::command]
+pub fn get_search_performance(state: State<SearchState>) -> SearchPerformanceReport {
+    SearchPerformanceReport {
+        debounce: state.debounce.stats(),
+        ..state.performance.report()
+    }
+}
+
+/// Page through one result group, e.g. "show all files"
+#[tauri::command]
+pub fn search_more(
+    query: String,
+    source: String,
+    offset: usize,
+    state: State<SearchState>,
+) -> Result<SearchResponse, String> {
+    let start = std::time::Instant::now();
+
+    let settings = state.settings()?;
+    let parsed = ParsedQuery::parse(&query, &settings.query_prefixes).with_scoring(settings.scoring_weights, false);
+
+    let merged = state
+        .providers
+        .search_source(&parsed, &settings, &source, offset, settings.max_results)?;
+
+    let mut response = SearchResponse::from_merged(merged, start);
+    response.filters = parsed.filters;
+    Ok(response)
 }
 
 /// Get search statistics
@@ -213,22 +454,153 @@ pub struct SearchStats {
     pub total_apps: usize,
     pub total_files: usize,
     pub total_browser_items: usize,
+    /// When the file index was last written, None if it's empty
     pub index_last_updated: Option<String>,
+    pub cache: CacheStats,
+    /// On-disk size of files_index.db in bytes
+    pub files_db_size: u64,
+    /// On-disk size of browser_cache.db in bytes
+    pub browser_db_size: u64,
+    pub file_index: Option<crate::db::files::FileIndexStats>,
+    pub browser_cache: Option<crate::db::browser::BrowserCacheStats>,
 }
 
 #[tauri::command]
-pub fn get_search_stats(state: State<SearchState>) -> Result<SearchStats, String> {
-    let mut monitor = state.app_monitor.lock().map_err(|e| e.to_string())?;
-    let apps = monitor.scan_apps();
+pub fn get_search_stats<R: Runtime>(handle: AppHandle<R>, state: State<SearchState>) -> Result<SearchStats, String> {
+    let excluded = state.settings()?.excluded_apps;
+    let total_apps = {
+        let mut monitor = state.app_monitor.lock().map_err(|e| e.to_string())?;
+        filter_excluded(monitor.scan_apps(), &excluded).len()
+    };
+
+    let files_db_path = crate::db::get_files_db_path(&handle)?;
+    let browser_db_path = crate::db::get_browser_db_path(&handle)?;
+
+    let file_index = crate::db::files::open_files_db(&files_db_path)
+        .and_then(|conn| crate::db::files::get_index_stats(&conn))
+        .map_err(|e| eprintln!("[Search] Failed to read file index stats: {}", e))
+        .ok();
+    let browser_cache = crate::db::browser::open_browser_db(&browser_db_path)
+        .and_then(|conn| crate::db::browser::get_cache_stats(&conn))
+        .map_err(|e| eprintln!("[Search] Failed to read browser cache stats: {}", e))
+        .ok();
+
+    let index_last_updated = file_index
+        .as_ref()
+        .and_then(|stats| stats.last_indexed)
+        .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+        .map(|dt| dt.to_rfc3339());
 
     Ok(SearchStats {
-        total_apps: apps.len(),
-        total_files: 0,
-        total_browser_items: 0,
-        index_last_updated: Some(chrono::Utc::now().to_rfc3339()),
+        total_apps,
+        total_files: file_index.as_ref().map_or(0, |s| s.total_files),
+        total_browser_items: browser_cache.as_ref().map_or(0, |s| s.by_type.values().sum()),
+        index_last_updated,
+        cache: state.cache.stats(),
+        files_db_size: crate::db::db_file_size(&files_db_path),
+        browser_db_size: crate::db::db_file_size(&browser_db_path),
+        file_index,
+        browser_cache,
     })
 }
 
+/// List the currently known apps an exclusion pattern would hide
+#[tauri::command]
+pub fn preview_exclusion(
+    pattern: String,
+    state: State<SearchState>,
+) -> Result<Vec<ApplicationEntry>, String> {
+    let mut monitor = state.app_monitor.lock().map_err(|e| e.to_string())?;
+
+    Ok(monitor
+        .cached_apps()
+        .into_iter()
+        .filter(|app| app_matches_pattern(app, &pattern))
+        .collect())
+}
+
+/// Suggestions returned when `get_default_suggestions` gets no limit
+const DEFAULT_SUGGESTION_LIMIT: usize = 10;
+
+/// Suggestions for an empty query, grouped by result type. Reads only the app
+/// cache, usage aggregates, the file index, the browser cache and the newest
+/// clipboard files, so it never triggers a scan; an unavailable source just leaves its section out.
+#[tauri::command]
+pub fn get_default_suggestions(
+    handle: AppHandle,
+    limit: Option<usize>,
+    state: State<SearchState>,
+    privacy: State<PrivacyMode>,
+) -> Result<Vec<SearchResultItem>, String> {
+    let settings = state.settings()?;
+    let limit = limit.unwrap_or(DEFAULT_SUGGESTION_LIMIT).min(settings.max_results);
+    let privacy = privacy.is_active();
+    let now = chrono::Utc::now().timestamp();
+
+    let apps = state.app_monitor.lock().map_err(|e| e.to_string())?.loaded_apps();
+
+    let usage = |kind: &str, since: i64, limit: usize| {
+        init_analytics_db(&handle)
+            .and_then(|conn| subject_usage(&conn, kind, since, limit))
+            .unwrap_or_else(|e| {
+                eprintln!("[Search] Failed to read {} usage: {}", kind, e);
+                Vec::new()
+            })
+    };
+    // Launches can't be ranked without seeing every launched app
+    let app_usage = usage("launch", now - FRECENCY_WINDOW_DAYS * 24 * 3600, apps.len().max(limit));
+    let plugin_usage = if privacy { Vec::new() } else { usage("plugin", 0, limit) };
+
+    let clipboard = if settings.enable_clipboard && !privacy {
+        recent_clipboard_items(&handle, limit).unwrap_or_else(|e| {
+            eprintln!("[Search] Failed to read clipboard history: {}", e);
+            Vec::new()
+        })
+    } else {
+        Vec::new()
+    };
+
+    let documents = if settings.enable_file_search && !privacy {
+        crate::db::files::init_files_db(&handle)
+            .and_then(|conn| crate::db::files::recent_files(&conn, limit))
+            .unwrap_or_else(|e| {
+                eprintln!("[Search] Failed to read recent files: {}", e);
+                Vec::new()
+            })
+    } else {
+        Vec::new()
+    };
+
+    let browser = if settings.enable_browser_search && !privacy {
+        crate::db::browser::init_browser_db(&handle)
+            .and_then(|conn| crate::db::browser::most_opened_from_launcher(&conn, limit))
+            .unwrap_or_else(|e| {
+                eprintln!("[Search] Failed to read opened browser pages: {}", e);
+                Vec::new()
+            })
+    } else {
+        Vec::new()
+    };
+
+    let sources = SuggestionSources {
+        apps,
+        app_usage,
+        salt: subject_salt(&handle)?,
+        clipboard,
+        documents,
+        browser,
+        plugin_usage,
+    };
+    Ok(compose_suggestions(
+        &sources,
+        &settings.suggestion_weights,
+        &settings.excluded_apps,
+        privacy,
+        limit,
+        now,
+    ))
+}
+
 /// File search result
 #[derive(Debug, Serialize)]
 pub struct FileSearchResult {
@@ -238,6 +610,14 @@ pub struct FileSearchResult {
     pub extension: Option<String>,
     pub size: u64,
     pub indexed: i64,
+    pub tags: Vec<String>,
+    /// A cloud file that isn't downloaded; opening it starts the download
+    pub is_placeholder: bool,
+    /// Folder holding the file, as indexed; pass it as `path_prefix` to
+    /// search within it
+    pub parent_dir: String,
+    /// `parent_dir` shortened for display, e.g. "~/Projects/…/src"
+    pub parent_display: String,
 }
 
 /// Browser search result
@@ -250,16 +630,26 @@ pub struct BrowserSearchResult {
     #[serde(rename = "entry_type")]
     pub entry_type: String,
     pub favicon: Option<String>,
+    /// Dominant favicon color as "#rrggbb"
+    pub accent_color: Option<String>,
     #[serde(rename = "last_visited")]
     pub last_visited: i64,
+    /// Entry types merged into this result, e.g. ["bookmark", "history"]
+    pub sources: Vec<String>,
+    pub description: Option<String>,
+    /// Host and the part the query matched, to tell apart pages sharing
+    /// a title
+    pub match_context: MatchContext,
 }
 
-/// Search files (T140, T022) - queries file index
+/// Search files (T140, T022) - queries file index, only files below
+/// `path_prefix` when given
 #[tauri::command]
 pub fn search_files(
     handle: AppHandle,
     query: String,
     limit: usize,
+    path_prefix: Option<String>,
 ) -> Result<Vec<FileSearchResult>, String> {
     use crate::services::file_indexer::FileIndexer;
     use crate::services::file_indexer::IndexerConfig;
@@ -267,71 +657,98 @@ pub fn search_files(
     let config = IndexerConfig::default();
     let indexer = FileIndexer::new(config);
 
-    let files = indexer.search(&handle, &query, limit)?;
+    let files = indexer.search(&handle, &query, limit, path_prefix.as_deref())?;
+    let home = handle.path().home_dir().ok();
 
     // Convert to FileSearchResult
     let results: Vec<FileSearchResult> = files
         .into_iter()
-        .map(|f| FileSearchResult {
-            id: f.id.unwrap_or(0).to_string(),
-            filename: f.filename,
-            path: f.path,
-            extension: f.extension,
-            size: f.size as u64,
-            indexed: f.indexed,
+        .map(|f| {
+            let parent = Path::new(&f.path).parent().unwrap_or(Path::new(""));
+            FileSearchResult {
+                id: f.id.unwrap_or(0).to_string(),
+                parent_dir: parent.to_string_lossy().to_string(),
+                parent_display: abbreviate_path(parent, home.as_deref()),
+                filename: f.filename,
+                path: f.path,
+                extension: f.extension,
+                size: f.size as u64,
+                indexed: f.indexed,
+                tags: f.tags,
+                is_placeholder: f.is_placeholder,
+            }
         })
         .collect();
 
     Ok(results)
 }
 
-/// Search browser data (T150, T032) - queries cached browser data
+/// Search browser data (T150, T032) - queries cached browser data. Pass a
+/// page's `next_cursor` back as `cursor` for the page after it.
 #[tauri::command]
 pub fn search_browser_data(
     handle: AppHandle,
     query: String,
     limit: usize,
-) -> Result<Vec<BrowserSearchResult>, String> {
+    cursor: Option<String>,
+) -> Result<Page<BrowserSearchResult>, String> {
     let config = BrowserReaderConfig::default();
     let reader = BrowserReader::new(config);
 
-    let entries = reader.search(&handle, &query, limit)?;
+    let after = PageCursor::parse(cursor.as_deref())?;
+    // Privacy mode hides history but leaves bookmarks
+    let include_history = !handle.try_state::<PrivacyMode>().is_some_and(|privacy| privacy.is_active());
+    let page = reader.search(&handle, &query, after.as_ref(), limit, include_history)?;
 
     // Convert to BrowserSearchResult
-    let results: Vec<BrowserSearchResult> = entries
-        .into_iter()
-        .map(|e| BrowserSearchResult {
-            id: e.id.unwrap_or(0).to_string(),
-            title: e.title,
-            url: e.url,
-            browser: e.browser,
-            entry_type: e.entry_type,
-            favicon: e.favicon,
-            last_visited: e.last_visited.unwrap_or(0),
-        })
-        .collect();
+    let results = page.map(|m| BrowserSearchResult {
+        match_context: match_context(&query, &m.entry),
+        id: m.entry.id.unwrap_or(0).to_string(),
+        title: m.entry.title,
+        url: m.entry.url,
+        browser: m.entry.browser,
+        entry_type: m.entry.entry_type,
+        favicon: m.entry.favicon,
+        accent_color: m.entry.accent_color,
+        last_visited: m.entry.last_visited.unwrap_or(0),
+        sources: m.sources,
+        description: m.entry.description,
+    });
 
     Ok(results)
 }
 
-/// Update browser cache (T149, T030) - refreshes bookmarks and history from browsers
+/// Update browser cache (T149, T030) - refreshes bookmarks and history from
+/// browsers, reporting entries and time taken per browser
 #[tauri::command]
-pub async fn update_browser_cache(handle: AppHandle) -> Result<usize, String> {
-    let config = BrowserReaderConfig::default();
-    let reader = BrowserReader::new(config);
+pub async fn update_browser_cache(handle: AppHandle) -> Result<BrowserCacheUpdate, String> {
+    let settings = handle.state::<SearchState>().settings()?;
+    let reader = BrowserReader::new(BrowserReaderConfig::from_settings(&settings));
 
-    reader.update_cache(&handle)
+    // Copying and walking history databases can take seconds
+    run_blocking(move || reader.update_cache(&handle)).await
 }
 
-/// Index files (T138)
+/// Index files (T138). The scan runs on the blocking pool, so searches and
+/// other commands keep answering while a large tree is indexed.
 #[tauri::command]
-pub async fn index_files(
-    handle: AppHandle,
+pub async fn index_files<R: Runtime>(
+    handle: AppHandle<R>,
     paths: Vec<String>,
 ) -> Result<usize, String> {
-    let config = IndexerConfig::default();
-    let indexer = FileIndexer::new(config);
-    indexer.index_paths(&handle, &paths)
+    let settings = handle.state::<SearchState>().settings()?;
+    let config = IndexerConfig {
+        read_tags: settings.index_file_tags,
+        ..Default::default()
+    };
+    let indexer = FileIndexer::new(config)
+        .with_privacy(handle.state::<PrivacyMode>().inner().clone())
+        .with_maintenance(handle.state::<MaintenanceGuard>().inner().clone());
+    let scan_handle = handle.clone();
+    let count = run_blocking(move || indexer.index_paths(&scan_handle, &paths)).await?;
+
+    notify(&handle, "File indexing complete", Some(&format!("Indexed {} files", count)), APP_SOURCE);
+    Ok(count)
 }
 
 /// File index stats for API response (T139, T023)
@@ -366,24 +783,41 @@ pub fn get_file_index_stats(handle: AppHandle) -> Result<FileIndexStats, String>
 pub fn start_file_indexer(
     handle: AppHandle,
     state: State<SearchState>,
+    supervisor: State<TaskSupervisor>,
     paths: Option<Vec<String>>,
 ) -> Result<(), String> {
     let mut indexer_guard = state.file_indexer.lock()
         .map_err(|e| format!("Lock error: {}", e))?;
 
-    // Create config with provided paths or defaults
-    let config = if let Some(paths) = paths {
-        IndexerConfig {
-            paths: paths.into_iter().map(|p| p.into()).collect(),
-            ..Default::default()
-        }
-    } else {
-        IndexerConfig::default()
+    // Create config with provided paths or the saved index paths
+    let settings = state.settings()?;
+    let config = IndexerConfig {
+        paths: paths
+            .unwrap_or(settings.file_index_paths)
+            .into_iter()
+            .map(|p| p.into())
+            .collect(),
+        read_tags: settings.index_file_tags,
+        ..Default::default()
     };
 
-    let indexer = FileIndexer::new(config);
+    let indexer = FileIndexer::new(config)
+        .with_privacy(handle.state::<PrivacyMode>().inner().clone())
+        .with_maintenance(handle.state::<MaintenanceGuard>().inner().clone());
     indexer.start(&handle)?;
 
+    // Replaces the tasks of an indexer started earlier
+    let scanner = indexer.clone();
+    supervisor.register(FILE_SCAN_TASK, TaskSchedule::every(indexer.scan_interval()), move |ctx| {
+        scanner.scan_once(&|| ctx.should_stop())
+    })?;
+    let watcher = indexer.clone();
+    supervisor.register(FILE_WATCH_TASK, TaskSchedule::LongRunning, move |ctx| {
+        watcher.watch_events(&|| ctx.should_stop())
+    })?;
+    supervisor.start(FILE_SCAN_TASK)?;
+    supervisor.start(FILE_WATCH_TASK)?;
+
     *indexer_guard = Some(indexer);
 
     Ok(())
@@ -391,11 +825,13 @@ pub fn start_file_indexer(
 
 /// Stop file indexer (T024)
 #[tauri::command]
-pub fn stop_file_indexer(state: State<SearchState>) -> Result<(), String> {
+pub fn stop_file_indexer(state: State<SearchState>, supervisor: State<TaskSupervisor>) -> Result<(), String> {
     let mut indexer_guard = state.file_indexer.lock()
         .map_err(|e| format!("Lock error: {}", e))?;
 
     if let Some(indexer) = indexer_guard.as_ref() {
+        supervisor.stop(FILE_SCAN_TASK)?;
+        supervisor.stop(FILE_WATCH_TASK)?;
         indexer.stop()?;
         *indexer_guard = None;
     }
@@ -1,335 +1,214 @@
 /**
  * Search Commands Unit Tests
- * Tests for unified search, app search, and search statistics
+ * Command-level tests for search statistics and settings refresh, run
+ * against a mock app with its own data dir
  */
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::cmds::search::{get_search_stats, index_files, unified_search, SearchQuery, SearchState};
+    use crate::cmds::settings::{set_scoring_weights, set_setting};
+    use crate::db::files::{upsert_file, FileEntry};
+    use crate::models::preferences::{AppSettings, ScoringWeights};
     use crate::services::app_monitor::AppMonitor;
-    use crate::models::app::ApplicationEntry;
-    use std::sync::Mutex;
-    use tauri::State;
-
-    fn create_mock_search_state() -> SearchState {
-        SearchState {
-            app_monitor: Mutex::new(AppMonitor::new()),
+    use crate::services::db_maintenance::MaintenanceGuard;
+    use crate::services::privacy::PrivacyMode;
+    use crate::services::search::{
+        FileProvider, ProviderRegistry, RetainedResults, SearchCache, SearchCancellation, SearchDebounce,
+        SearchPerformance,
+    };
+    use crate::test_support::TestApp;
+    use std::path::Path;
+    use std::sync::{Arc, Mutex, RwLock};
+    use std::time::{Duration, Instant};
+    use tauri::async_runtime::block_on;
+    use tauri::Manager;
+
+    /// Slowest a search may answer while a large index scan runs
+    const SEARCH_BUDGET: Duration = Duration::from_millis(500);
+
+    /// Manage a SearchState whose app list comes from `apps_dir`
+    fn manage_search_state(app: &TestApp, apps_dir: &Path) {
+        app.manage(SearchState {
+            app_monitor: Arc::new(Mutex::new(AppMonitor::with_dirs(vec![apps_dir.to_path_buf()]))),
+            file_indexer: Mutex::new(None),
+            providers: Arc::new(ProviderRegistry::new()),
+            cancellation: Arc::new(SearchCancellation::new()),
+            debounce: Arc::new(SearchDebounce::new()),
+            retained: Arc::new(RetainedResults::new()),
+            performance: Arc::new(SearchPerformance::new()),
+            cache: SearchCache::new(),
+            settings: Arc::new(RwLock::new(AppSettings::default())),
+        });
+    }
+
+    fn file(path: &str, indexed: i64) -> FileEntry {
+        FileEntry {
+            id: None,
+            path: path.to_string(),
+            filename: Path::new(path).file_name().unwrap().to_string_lossy().to_string(),
+            extension: Path::new(path).extension().map(|e| e.to_string_lossy().to_string()),
+            size: 100,
+            modified: indexed,
+            hidden: false,
+            indexed,
+            tags: Vec::new(),
+            is_placeholder: false,
         }
     }
 
-    fn create_mock_apps() -> Vec<ApplicationEntry> {
-        vec![
-            ApplicationEntry {
-                id: "1".to_string(),
-                name: "Visual Studio Code".to_string(),
-                executable_path: "/usr/bin/code".to_string(),
-                icon: Some("📝".to_string()),
-                usage_count: 45,
-            },
-            ApplicationEntry {
-                id: "2".to_string(),
-                name: "Chrome".to_string(),
-                executable_path: "/usr/bin/google-chrome".to_string(),
-                icon: Some("🌐".to_string()),
-                usage_count: 120,
-            },
-            ApplicationEntry {
-                id: "3".to_string(),
-                name: "Finder".to_string(),
-                executable_path: "/usr/bin/finder".to_string(),
-                icon: Some("📁".to_string()),
-                usage_count: 89,
-            },
-            ApplicationEntry {
-                id: "4".to_string(),
-                name: "Terminal".to_string(),
-                executable_path: "/usr/bin/terminal".to_string(),
-                icon: Some("⌨️".to_string()),
-                usage_count: 67,
-            },
-        ]
-    }
-
     #[test]
     fn test_search_query_deserialization() {
         let json = r#"{"query":"test","limit":10,"sources":["app","file"]}"#;
-        
         let query: SearchQuery = serde_json::from_str(json).unwrap();
-        
+
         assert_eq!(query.query, "test");
         assert_eq!(query.limit, Some(10));
         assert_eq!(query.sources, Some(vec!["app".to_string(), "file".to_string()]));
-    }
 
-    #[test]
-    fn test_search_query_minimal() {
-        let json = r#"{"query":"","limit":null,"sources":null}"#;
-        
-        let query: SearchQuery = serde_json::from_str(json).unwrap();
-        
-        assert_eq!(query.query, "");
-        assert_eq!(query.limit, None);
-        assert_eq!(query.sources, None);
+        let minimal: SearchQuery = serde_json::from_str(r#"{"query":"","limit":null,"sources":null}"#).unwrap();
+        assert_eq!(minimal.limit, None);
+        assert_eq!(minimal.sources, None);
     }
 
     #[test]
-    fn test_search_result_item_serialization() {
-        let item = SearchResultItem {
-            id: "test-id".to_string(),
-            title: "Test App".to_string(),
-            subtitle: "/usr/bin/test".to_string(),
-            icon: Some("🧪".to_string()),
-            result_type: "app".to_string(),
-            score: 0.95,
-            path: "/usr/bin/test".to_string(),
-            frequency: 50,
-        };
-        
-        let json = serde_json::to_string(&item).unwrap();
-        
-        assert!(json.contains("test-id"));
-        assert!(json.contains("Test App"));
-        assert!(json.contains("0.95"));
-    }
+    fn test_search_stats_on_empty_app() {
+        let app = TestApp::new();
+        let apps_dir = tempfile::tempdir().unwrap();
+        manage_search_state(&app, apps_dir.path());
 
-    #[test]
-    fn test_search_response_serialization() {
-        let response = SearchResponse {
-            results: vec![
-                SearchResultItem {
-                    id: "1".to_string(),
-                    title: "App 1".to_string(),
-                    subtitle: "/path/to/app1".to_string(),
-                    icon: Some("📱".to_string()),
-                    result_type: "app".to_string(),
-                    score: 1.0,
-                    path: "/path/to/app1".to_string(),
-                    frequency: 100,
-                },
-            ],
-            total: 1,
-            query_time: 15,
-        };
-        
-        let json = serde_json::to_string(&response).unwrap();
-        
-        assert!(json.contains("\"total\":1"));
-        assert!(json.contains("\"query_time\":15"));
+        let stats = get_search_stats(app.handle().clone(), app.handle().state()).unwrap();
+        assert_eq!(stats.total_apps, 0);
+        assert_eq!(stats.total_files, 0);
+        assert_eq!(stats.total_browser_items, 0);
+        assert_eq!(stats.index_last_updated, None);
     }
 
     #[test]
-    fn test_search_stats_serialization() {
-        let stats = SearchStats {
-            total_apps: 42,
-            total_files: 1000,
-            total_browser_items: 500,
-            index_last_updated: Some("2024-01-01T00:00:00Z".to_string()),
-        };
-        
-        let json = serde_json::to_string(&stats).unwrap();
-        
-        assert!(json.contains("\"total_apps\":42"));
-        assert!(json.contains("\"total_files\":1000"));
-        assert!(json.contains("\"total_browser_items\":500"));
-    }
+    fn test_search_stats_count_seeded_file_index() {
+        let app = TestApp::new();
+        let apps_dir = tempfile::tempdir().unwrap();
+        manage_search_state(&app, apps_dir.path());
 
-    #[test]
-    fn test_file_search_result_serialization() {
-        let result = FileSearchResult {
-            id: "file-1".to_string(),
-            filename: "document.pdf".to_string(),
-            path: "/Users/test/document.pdf".to_string(),
-            extension: Some("pdf".to_string()),
-            size: 1024000,
-            indexed: 1704067200,
-        };
-        
-        let json = serde_json::to_string(&result).unwrap();
-        let deserialized: FileSearchResult = serde_json::from_str(&json).unwrap();
-        
-        assert_eq!(deserialized.filename, "document.pdf");
-        assert_eq!(deserialized.extension, Some("pdf".to_string()));
-        assert_eq!(deserialized.size, 1024000);
-    }
+        let conn = app.files_db();
+        upsert_file(&conn, &file("/home/user/notes.md", 1_704_067_200)).unwrap();
+        upsert_file(&conn, &file("/home/user/report.pdf", 1_704_153_600)).unwrap();
+        drop(conn);
 
-    #[test]
-    fn test_browser_search_result_serialization() {
-        let result = BrowserSearchResult {
-            id: "bookmark-1".to_string(),
-            title: "GitHub".to_string(),
-            url: "https://github.com".to_string(),
-            browser: "Chrome".to_string(),
-            entry_type: "bookmark".to_string(),
-            favicon: Some("📦".to_string()),
-            last_visited: 1704067200,
-        };
-        
-        let json = serde_json::to_string(&result).unwrap();
-        let deserialized: BrowserSearchResult = serde_json::from_str(&json).unwrap();
-        
-        assert_eq!(deserialized.title, "GitHub");
-        assert_eq!(deserialized.entry_type, "bookmark");
-        assert_eq!(deserialized.browser, "Chrome");
+        let stats = get_search_stats(app.handle().clone(), app.handle().state()).unwrap();
+        assert_eq!(stats.total_files, 2);
+        assert_eq!(stats.index_last_updated.as_deref(), Some("2024-01-02T00:00:00+00:00"));
+        assert!(stats.files_db_size > 0);
     }
 
+    #[cfg(target_os = "linux")]
     #[test]
-    fn test_scoring_exact_match() {
-        // Test exact match scoring
-        let app = ApplicationEntry {
-            id: "1".to_string(),
-            name: "Code".to_string(),
-            executable_path: "/usr/bin/code".to_string(),
-            icon: Some("📝".to_string()),
-            usage_count: 10,
-        };
-        
-        let query_lower = "code";
-        let name_lower = app.name.to_lowercase();
-        
-        let exact_match = if name_lower == query_lower { 1.0 } else { 0.0 };
-        let starts_with = if name_lower.starts_with(&query_lower) { 0.8 } else { 0.0 };
-        let contains = if name_lower.contains(&query_lower) { 0.5 } else { 0.0 };
-        let frequency_boost = (app.usage_count as f64).log10() / 10.0;
-        
-        let score = exact_match + starts_with + contains + frequency_boost;
-        
-        assert_eq!(exact_match, 1.0);
-        assert!(score > 1.0); // Should have frequency boost too
-    }
+    fn test_search_stats_apply_saved_exclusions() {
+        let app = TestApp::new();
+        let apps_dir = tempfile::tempdir().unwrap();
+        for name in ["Editor", "Chess"] {
+            std::fs::write(
+                apps_dir.path().join(format!("{}.desktop", name.to_lowercase())),
+                format!("[Desktop Entry]\nName={}\nExec={}\n", name, name.to_lowercase()),
+            )
+            .unwrap();
+        }
+        manage_search_state(&app, apps_dir.path());
 
-    #[test]
-    fn test_scoring_starts_with() {
-        let app = ApplicationEntry {
-            id: "1".to_string(),
-            name: "Code".to_string(),
-            executable_path: "/usr/bin/code".to_string(),
-            icon: Some("📝".to_string()),
-            usage_count: 5,
-        };
-        
-        let query_lower = "co";
-        let name_lower = app.name.to_lowercase();
-        
-        let exact_match = if name_lower == query_lower { 1.0 } else { 0.0 };
-        let starts_with = if name_lower.starts_with(&query_lower) { 0.8 } else { 0.0 };
-        let contains = if name_lower.contains(&query_lower) { 0.5 } else { 0.0 };
-        
-        assert_eq!(exact_match, 0.0);
-        assert_eq!(starts_with, 0.8);
-        assert_eq!(contains, 0.5); // Also contains
-    }
+        let stats = get_search_stats(app.handle().clone(), app.handle().state()).unwrap();
+        assert_eq!(stats.total_apps, 2);
 
-    #[test]
-    fn test_scoring_contains() {
-        let app = ApplicationEntry {
-            id: "1".to_string(),
-            name: "Visual Studio Code".to_string(),
-            executable_path: "/usr/bin/code".to_string(),
-            icon: Some("📝".to_string()),
-            usage_count: 5,
-        };
-        
-        let query_lower = "studio";
-        let name_lower = app.name.to_lowercase();
-        
-        let exact_match = if name_lower == query_lower { 1.0 } else { 0.0 };
-        let starts_with = if name_lower.starts_with(&query_lower) { 0.8 } else { 0.0 };
-        let contains = if name_lower.contains(&query_lower) { 0.5 } else { 0.0 };
-        
-        assert_eq!(exact_match, 0.0);
-        assert_eq!(starts_with, 0.0);
-        assert_eq!(contains, 0.5);
+        set_setting(app.handle().clone(), "excluded_apps".to_string(), serde_json::json!(["Chess"])).unwrap();
+        let stats = get_search_stats(app.handle().clone(), app.handle().state()).unwrap();
+        assert_eq!(stats.total_apps, 1);
     }
 
     #[test]
-    fn test_scoring_frequency_boost() {
-        let low_freq_app = ApplicationEntry {
-            id: "1".to_string(),
-            name: "App".to_string(),
-            executable_path: "/usr/bin/app".to_string(),
-            icon: None,
-            usage_count: 1,
-        };
-        
-        let high_freq_app = ApplicationEntry {
-            id: "2".to_string(),
-            name: "App".to_string(),
-            executable_path: "/usr/bin/app2".to_string(),
-            icon: None,
-            usage_count: 100,
-        };
-        
-        let low_boost = (low_freq_app.usage_count as f64).log10() / 10.0;
-        let high_boost = (high_freq_app.usage_count as f64).log10() / 10.0;
-        
-        assert!(high_boost > low_boost);
-        assert!(high_boost > 0.1); // 100 uses should give noticeable boost
-    }
+    fn test_saved_settings_refresh_search_state() {
+        let app = TestApp::new();
+        let apps_dir = tempfile::tempdir().unwrap();
+        manage_search_state(&app, apps_dir.path());
+        let state = app.handle().state::<SearchState>();
+        let invalidations = state.cache.stats().invalidations;
 
-    #[test]
-    fn test_limit_results() {
-        let apps = create_mock_apps();
-        
-        let limit = 2;
-        let limited: Vec<&ApplicationEntry> = apps.iter().take(limit).collect();
-        
-        assert_eq!(limited.len(), 2);
-        assert_eq!(limited[0].id, "1");
-        assert_eq!(limited[1].id, "2");
-    }
+        set_setting(app.handle().clone(), "max_results".to_string(), serde_json::json!(12)).unwrap();
 
-    #[test]
-    fn test_filter_by_query() {
-        let apps = create_mock_apps();
-        let query_lower = "code";
-        
-        let filtered: Vec<&ApplicationEntry> = apps
-            .iter()
-            .filter(|app| {
-                let name_matches = app.name.to_lowercase().contains(&query_lower);
-                let path_matches = app.executable_path.to_lowercase().contains(&query_lower);
-                name_matches || path_matches
-            })
-            .collect();
-        
-        assert_eq!(filtered.len(), 1);
-        assert_eq!(filtered[0].name, "Visual Studio Code");
+        assert_eq!(state.settings().unwrap().max_results, 12);
+        assert_eq!(state.cache.stats().invalidations, invalidations + 1);
     }
 
     #[test]
-    fn test_filter_empty_query() {
-        let apps = create_mock_apps();
-        let query_lower = "";
-        
-        let filtered: Vec<&ApplicationEntry> = apps
-            .iter()
-            .filter(|app| {
-                let name_matches = app.name.to_lowercase().contains(&query_lower);
-                let path_matches = app.executable_path.to_lowercase().contains(&query_lower);
-                name_matches || path_matches
-            })
-            .collect();
-        
-        // Empty query matches all
-        assert_eq!(filtered.len(), apps.len());
+    fn test_scoring_weight_changes_invalidate_cache() {
+        let app = TestApp::new();
+        let apps_dir = tempfile::tempdir().unwrap();
+        manage_search_state(&app, apps_dir.path());
+        let state = app.handle().state::<SearchState>();
+        let invalidations = state.cache.stats().invalidations;
+
+        let weights = ScoringWeights {
+            exact: 1.5,
+            ..Default::default()
+        };
+        set_scoring_weights(app.handle().clone(), weights).unwrap();
+
+        assert_eq!(state.settings().unwrap().scoring_weights, weights);
+        assert_eq!(state.cache.stats().invalidations, invalidations + 1);
     }
 
     #[test]
-    fn test_case_insensitive_search() {
-        let app = ApplicationEntry {
-            id: "1".to_string(),
-            name: "Visual Studio Code".to_string(),
-            executable_path: "/usr/bin/code".to_string(),
-            icon: None,
-            usage_count: 0,
-        };
-        
-        let queries = vec!["code", "CODE", "Code", "cOdE"];
-        
-        for query in queries {
-            let query_lower = query.to_lowercase();
-            let name_matches = app.name.to_lowercase().contains(&query_lower);
-            assert!(name_matches, "Query '{}' should match", query);
+    fn test_search_stays_responsive_during_large_index() {
+        let app = TestApp::new();
+        let tree = tempfile::tempdir().unwrap();
+        for dir in 0..40 {
+            let dir_path = tree.path().join(format!("dir{}", dir));
+            std::fs::create_dir(&dir_path).unwrap();
+            for file in 0..50 {
+                std::fs::write(dir_path.join(format!("report{}.txt", file)), "x").unwrap();
+            }
+        }
+
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(FileProvider::new(app.data_dir().join("files_index.db"))));
+        let apps_dir = tempfile::tempdir().unwrap();
+        app.manage(SearchState {
+            app_monitor: Arc::new(Mutex::new(AppMonitor::with_dirs(vec![apps_dir.path().to_path_buf()]))),
+            file_indexer: Mutex::new(None),
+            providers: Arc::new(registry),
+            cancellation: Arc::new(SearchCancellation::new()),
+            debounce: Arc::new(SearchDebounce::new()),
+            retained: Arc::new(RetainedResults::new()),
+            performance: Arc::new(SearchPerformance::new()),
+            cache: SearchCache::new(),
+            settings: Arc::new(RwLock::new(AppSettings {
+                enable_file_search: true,
+                ..Default::default()
+            })),
+        })
+        .manage(PrivacyMode::new())
+        .manage(MaintenanceGuard::new());
+
+        let handle = app.handle().clone();
+        let tree_path = tree.path().to_string_lossy().to_string();
+        let indexing = std::thread::spawn(move || block_on(index_files(handle, vec![tree_path])));
+
+        let mut slowest = Duration::ZERO;
+        let mut searches = 0;
+        while !indexing.is_finished() || searches == 0 {
+            // A new query each time so the result cache can't answer
+            let query = SearchQuery {
+                query: format!("report{}", searches % 50),
+                limit: Some(10),
+                sources: None,
+                explain: false,
+                include_timings: false,
+            };
+            let start = Instant::now();
+            unified_search(query, app.handle().state()).unwrap();
+            slowest = slowest.max(start.elapsed());
+            searches += 1;
         }
+
+        assert_eq!(indexing.join().unwrap(), Ok(2000));
+        assert!(slowest < SEARCH_BUDGET, "search took {:?} during indexing", slowest);
     }
 }
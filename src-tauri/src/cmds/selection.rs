@@ -0,0 +1,100 @@
+/**
+ * Selection Capture Commands
+ * Open the launcher pre-filled with the text selected in the frontmost app
+ */
+
+use crate::services::clipboard_watcher::ClipboardSuppression;
+use crate::services::selection_capture::{capture_selection, SystemClipboard, SystemKeystroke};
+use crate::services::windows::{ensure_window, MAIN_WINDOW};
+use crate::types::events::{self, SearchPrefill, WindowShown};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// The selection hotkey currently registered, if any
+static SELECTION_SHORTCUT: Mutex<Option<Shortcut>> = Mutex::new(None);
+
+/// Read the selection, falling back to None wherever capture can't work
+fn read_selection<R: Runtime>(handle: &AppHandle<R>) -> Option<String> {
+    let suppression = handle
+        .try_state::<ClipboardSuppression>()
+        .map(|state| state.inner().clone())
+        .unwrap_or_default();
+    let result = SystemClipboard::new()
+        .and_then(|mut clipboard| capture_selection(&SystemKeystroke, &mut clipboard, &suppression));
+
+    result.unwrap_or_else(|e| {
+        eprintln!("[Selection] {}", e);
+        None
+    })
+}
+
+/// Copy the selection from the frontmost app, then show the launcher and
+/// emit `search:prefill` with the captured text. Without a selection, or
+/// where capture isn't supported, the launcher is just shown.
+#[tauri::command]
+pub async fn capture_selection_and_show<R: Runtime>(handle: AppHandle<R>) -> Result<Option<String>, String> {
+    let window = ensure_window(&handle, MAIN_WINDOW)?;
+
+    // With the launcher in front there is no other app's selection to read
+    let captured = if window.is_visible().unwrap_or(false) {
+        None
+    } else {
+        let capture_handle = handle.clone();
+        tauri::async_runtime::spawn_blocking(move || read_selection(&capture_handle))
+            .await
+            .map_err(|e| format!("Selection capture failed: {}", e))?
+    };
+
+    window.show().map_err(|e| e.to_string())?;
+    window.set_focus().map_err(|e| e.to_string())?;
+    let _ = events::emit_to(&handle, MAIN_WINDOW, WindowShown);
+    crate::cmds::window::main_window_shown(&handle);
+    if let Some(text) = &captured {
+        let _ = events::emit_to(&handle, MAIN_WINDOW, SearchPrefill(text.clone()));
+    }
+
+    Ok(captured)
+}
+
+/// Register the selection hotkey, replacing the previous one. `None`
+/// removes it. Re-registers if something unregistered it meanwhile.
+pub(crate) fn register_selection_hotkey<R: Runtime>(handle: &AppHandle<R>, hotkey: Option<&str>) -> Result<(), String> {
+    let shortcut = hotkey.map(crate::parse_hotkey).transpose()?;
+    let mut current = SELECTION_SHORTCUT.lock().map_err(|e| format!("Lock error: {}", e))?;
+    // Nothing to register or remove; also keeps apps without the global
+    // shortcut plugin, such as test apps, working
+    if current.is_none() && shortcut.is_none() {
+        return Ok(());
+    }
+    let global_shortcut = handle.global_shortcut();
+
+    if let Some(previous) = *current {
+        if Some(previous) == shortcut && global_shortcut.is_registered(previous) {
+            return Ok(());
+        }
+        if global_shortcut.is_registered(previous) {
+            let _ = global_shortcut.unregister(previous);
+        }
+        *current = None;
+    }
+
+    let Some(shortcut) = shortcut else {
+        return Ok(());
+    };
+    global_shortcut
+        .on_shortcut(shortcut, |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                let handle = app.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = capture_selection_and_show(handle).await {
+                        eprintln!("[Selection] {}", e);
+                    }
+                });
+            }
+        })
+        .map_err(|e| format!("Failed to register selection hotkey: {}", e))?;
+    *current = Some(shortcut);
+
+    Ok(())
+}
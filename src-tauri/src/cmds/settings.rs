@@ -3,14 +3,27 @@
  * Handle application settings and preferences
  */
 
-use crate::models::preferences::AppSettings;
+use crate::models::preferences::{
+    migrate_settings, AppSettings, OnShowBehavior, OpenerTarget, ScoringWeights, SETTINGS_SCHEMA_VERSION,
+};
+use serde::Serialize;
 use serde_json;
+use std::fmt;
 use std::fs;
-use std::path::PathBuf;
-use tauri::{AppHandle, Emitter, Manager};
+use crate::services::app_paths::{self, DataLocations};
+use crate::services::browser_reader::{BrowserReader, BrowserReaderConfig};
+use crate::services::crash_reporter::CrashReporter;
+use crate::services::notifications::parse_time_of_day;
+use crate::services::plugin_result_cache::PluginResultCache;
+use crate::services::search::suggestions::SUGGESTION_SECTIONS;
+use crate::services::windows::MAIN_WINDOW;
+use crate::types::events::{self, WindowShown};
+use crate::utils::{managed_store, StateStore};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime, State};
 
 /// Settings storage path
-fn get_settings_path(handle: &AppHandle) -> Result<PathBuf, String> {
+fn get_settings_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
     let app_dir = handle
         .path()
         .app_config_dir()
@@ -28,41 +41,473 @@ fn get_settings_path(handle: &AppHandle) -> Result<PathBuf, String> {
     Ok(settings_path)
 }
 
-/// Load settings from file
-fn load_settings(handle: &AppHandle) -> Result<AppSettings, String> {
-    let settings_path = get_settings_path(handle)?;
+/// Parse settings.json, migrating older schemas
+fn parse_settings(content: &str) -> Result<AppSettings, String> {
+    let value: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| format!("Failed to parse settings: {}", e))?;
+    migrate_settings(value)
+}
 
-    if !settings_path.exists() {
-        // Return default settings if file doesn't exist
-        return Ok(AppSettings::default());
-    }
+fn settings_store<R: Runtime>(handle: &AppHandle<R>) -> Result<State<'_, StateStore<AppSettings>>, String> {
+    managed_store(handle, || Ok(StateStore::with_parser(get_settings_path(handle)?, "settings", parse_settings)))
+}
 
-    let content = fs::read_to_string(&settings_path)
-        .map_err(|e| format!("Failed to read settings file: {}", e))?;
+/// Load settings from file, defaults when there is none yet
+pub(crate) fn load_settings<R: Runtime>(handle: &AppHandle<R>) -> Result<AppSettings, String> {
+    settings_store(handle)?.read()
+}
 
-    serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse settings: {}", e))
+/// Settings from the frontend may not carry the schema version
+fn stamp_schema_version(settings: &mut AppSettings) {
+    settings.schema_version = SETTINGS_SCHEMA_VERSION;
 }
 
-/// Save settings to file
-fn save_settings(handle: &AppHandle, settings: &AppSettings) -> Result<(), String> {
-    let settings_path = get_settings_path(handle)?;
+/// Save settings to file. The data dir stays as saved, since only
+/// `migrate_data_location` may move it, along with the data.
+pub(crate) fn save_settings<R: Runtime>(handle: &AppHandle<R>, settings: &AppSettings) -> Result<(), String> {
+    let mut settings = settings.clone();
+    stamp_schema_version(&mut settings);
+    let settings = settings_store(handle)?.update(|saved| {
+        settings.data_dir = saved.data_dir.take();
+        *saved = settings;
+        Ok::<_, String>(saved.clone())
+    })?;
 
-    println!("[Settings] Saving settings to: {:?}", settings_path);
+    println!("[Settings] Settings saved successfully");
 
-    let content = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    apply_settings(handle, &settings);
+    Ok(())
+}
 
-    fs::write(&settings_path, content)
-        .map_err(|e| format!("Failed to write settings file: {}", e))?;
+/// Change settings in place and save them. Concurrent changes are applied
+/// one after another, so none is lost; when `change` fails nothing is saved.
+pub(crate) fn modify_settings<R: Runtime, U, E: From<String>>(
+    handle: &AppHandle<R>,
+    change: impl FnOnce(&mut AppSettings) -> Result<U, E>,
+) -> Result<U, E> {
+    let (out, settings) = settings_store(handle)?.update(|settings| {
+        let out = change(settings)?;
+        stamp_schema_version(settings);
+        Ok::<_, E>((out, settings.clone()))
+    })?;
 
     println!("[Settings] Settings saved successfully");
-    Ok(())
+
+    apply_settings(handle, &settings);
+    Ok(out)
+}
+
+/// Push settings into the running services that cache them
+pub(crate) fn apply_settings<R: Runtime>(handle: &AppHandle<R>, settings: &AppSettings) {
+    // Enabled sources, prefixes, caps and exclusions all affect search results
+    crate::cmds::search::refresh_search_settings(handle, settings);
+
+    if let Some(reporter) = handle.try_state::<CrashReporter>() {
+        reporter.set_enabled(settings.crash_reports);
+    }
+    if let Some(cache) = handle.try_state::<PluginResultCache>() {
+        cache.set_capacity(settings.plugin_cache_entries);
+    }
+    if let Some(locations) = handle.try_state::<DataLocations>() {
+        locations.set_temp_dir(settings.temp_dir.as_ref().map(PathBuf::from));
+    }
+
+    crate::services::i18n::set_locale_tag(&settings.language);
+    crate::services::http::set_manual_proxy(settings.http_proxy.clone());
+    crate::services::connectivity::monitor().set_forced(settings.offline_mode);
+
+    if let Err(e) = crate::cmds::selection::register_selection_hotkey(handle, settings.selection_hotkey.as_deref()) {
+        eprintln!("[Settings] {}", e);
+    }
+}
+
+/// Supported UI languages
+pub const SUPPORTED_LANGUAGES: &[&str] = &["en", "en-US", "zh-CN"];
+
+/// Allowed window opacity range
+pub const WINDOW_OPACITY_RANGE: (f32, f32) = (0.3, 1.0);
+
+/// Allowed max_results range
+pub const MAX_RESULTS_RANGE: (usize, usize) = (1, 200);
+
+/// Allowed search debounce range in milliseconds
+pub const SEARCH_DEBOUNCE_RANGE: (u64, u64) = (50, 1000);
+
+/// Allowed plugin package size limit range in MB
+pub const PLUGIN_PACKAGE_MB_RANGE: (u32, u32) = (1, 2048);
+
+/// Allowed plugin memory warning threshold range in MB
+pub const PLUGIN_MEMORY_WARNING_MB_RANGE: (u32, u32) = (16, 16384);
+
+/// Allowed per-plugin storage quota range in MB
+pub const PLUGIN_STORAGE_QUOTA_MB_RANGE: (u32, u32) = (1, 4096);
+
+/// Allowed range of cached plugin trigger results
+pub const PLUGIN_CACHE_ENTRIES_RANGE: (usize, usize) = (1, 10_000);
+
+/// Allowed browser database copy limit range in MB
+pub const BROWSER_DB_COPY_MB_RANGE: (u64, u64) = (16, 8192);
+
+/// Allowed range of history rows read per browser
+pub const BROWSER_HISTORY_LIMIT_RANGE: (usize, usize) = (100, 100_000);
+
+/// Allowed range of the minimum visits for a history page to be read
+pub const BROWSER_MIN_VISITS_RANGE: (u32, u32) = (1, 1000);
+
+/// Allowed range of seconds hidden before showing the launcher starts fresh
+pub const CLEAR_AFTER_SECONDS_RANGE: (u32, u32) = (1, 3600);
+
+/// Allowed post-prune vacuum threshold range in MB
+pub const VACUUM_THRESHOLD_MB_RANGE: (u64, u64) = (1, 1024);
+
+/// Largest weight of one suggestion section
+pub const MAX_SUGGESTION_WEIGHT: u32 = 100;
+
+/// Allowed range of each search scoring weight
+pub const SCORING_WEIGHT_RANGE: (f64, f64) = (0.0, 2.0);
+
+/// A single invalid settings field
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: &str, message: impl Into<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Settings command error
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SettingsError {
+    /// One or more fields failed validation
+    Validation { errors: Vec<FieldError> },
+    /// Reading or writing settings failed
+    Storage { message: String },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::Validation { errors } => {
+                let fields: Vec<String> = errors
+                    .iter()
+                    .map(|e| format!("{}: {}", e.field, e.message))
+                    .collect();
+                write!(f, "Invalid settings: {}", fields.join("; "))
+            }
+            SettingsError::Storage { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for SettingsError {
+    fn from(message: String) -> Self {
+        SettingsError::Storage { message }
+    }
+}
+
+/// Validate settings field ranges and cross-field rules.
+///
+/// `browsers_detected` is whether any supported browser is installed, which
+/// `enable_browser_search` depends on.
+pub fn validate_settings_fields(settings: &AppSettings, browsers_detected: bool) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    let (min, max) = WINDOW_OPACITY_RANGE;
+    if !(min..=max).contains(&settings.window_opacity) {
+        errors.push(FieldError::new(
+            "window_opacity",
+            format!("must be between {} and {}", min, max),
+        ));
+    }
+
+    let (min, max) = MAX_RESULTS_RANGE;
+    if !(min..=max).contains(&settings.max_results) {
+        errors.push(FieldError::new(
+            "max_results",
+            format!("must be between {} and {}", min, max),
+        ));
+    }
+
+    let (min, max) = SEARCH_DEBOUNCE_RANGE;
+    if !(min..=max).contains(&settings.search_debounce_ms) {
+        errors.push(FieldError::new(
+            "search_debounce_ms",
+            format!("must be between {} and {} ms", min, max),
+        ));
+    }
+
+    if !SUPPORTED_LANGUAGES.contains(&settings.language.as_str()) {
+        errors.push(FieldError::new(
+            "language",
+            format!("must be one of {}", SUPPORTED_LANGUAGES.join(", ")),
+        ));
+    }
+
+    let (min, max) = MAX_RESULTS_RANGE;
+    if settings.source_limits.values().any(|cap| !(min..=max).contains(cap)) {
+        errors.push(FieldError::new(
+            "source_limits",
+            format!("each limit must be between {} and {}", min, max),
+        ));
+    }
+
+    let bad_weight = settings.suggestion_weights.iter().any(|(section, weight)| {
+        !SUGGESTION_SECTIONS.contains(&section.as_str()) || *weight > MAX_SUGGESTION_WEIGHT
+    });
+    if bad_weight {
+        errors.push(FieldError::new(
+            "suggestion_weights",
+            format!(
+                "sections must be one of {} with weights up to {}",
+                SUGGESTION_SECTIONS.join(", "),
+                MAX_SUGGESTION_WEIGHT
+            ),
+        ));
+    }
+
+    let (min, max) = SCORING_WEIGHT_RANGE;
+    let bad_scoring: Vec<&str> = settings
+        .scoring_weights
+        .entries()
+        .into_iter()
+        .filter(|(_, weight)| !(min..=max).contains(weight))
+        .map(|(name, _)| name)
+        .collect();
+    if !bad_scoring.is_empty() {
+        errors.push(FieldError::new(
+            "scoring_weights",
+            format!("{} must be between {} and {}", bad_scoring.join(", "), min, max),
+        ));
+    }
+
+    let bad_prefix = settings.query_prefixes.iter().any(|(prefix, source)| {
+        prefix.is_empty() || prefix.chars().any(char::is_whitespace) || prefix.starts_with('\\') || source.is_empty()
+    });
+    if bad_prefix {
+        errors.push(FieldError::new(
+            "query_prefixes",
+            "prefixes must be non-empty, contain no whitespace and not start with a backslash",
+        ));
+    }
+
+    let limits = &settings.plugin_rate_limits;
+    let bad_limit = std::iter::once(&limits.default)
+        .chain(limits.overrides.values())
+        .any(|limit| limit.per_second.is_nan() || limit.per_second <= 0.0 || limit.burst == 0);
+    if bad_limit {
+        errors.push(FieldError::new(
+            "plugin_rate_limits",
+            "per_second must be positive and burst at least 1",
+        ));
+    }
+
+    if settings.shell_allowlist.iter().any(|program| program.trim().is_empty()) {
+        errors.push(FieldError::new(
+            "shell_allowlist",
+            "programs must be non-empty",
+        ));
+    }
+
+    if settings.ssh_terminal_template.trim().is_empty() {
+        errors.push(FieldError::new(
+            "ssh_terminal_template",
+            "must name a terminal program",
+        ));
+    }
+
+    if settings.cli_terminal_template.trim().is_empty() {
+        errors.push(FieldError::new(
+            "cli_terminal_template",
+            "must name a terminal program",
+        ));
+    }
+
+    if let Err(e) = regex::Regex::new(&settings.shell_history_exclude_pattern) {
+        errors.push(FieldError::new(
+            "shell_history_exclude_pattern",
+            format!("must be a valid regular expression: {}", e),
+        ));
+    }
+
+    if settings.min_plugin_security_score > 100 {
+        errors.push(FieldError::new(
+            "min_plugin_security_score",
+            "must be between 0 and 100",
+        ));
+    }
+
+    let (min, max) = PLUGIN_PACKAGE_MB_RANGE;
+    if !(min..=max).contains(&settings.max_plugin_package_mb) {
+        errors.push(FieldError::new(
+            "max_plugin_package_mb",
+            format!("must be between {} and {} MB", min, max),
+        ));
+    }
+
+    let (min, max) = PLUGIN_MEMORY_WARNING_MB_RANGE;
+    if !(min..=max).contains(&settings.plugin_memory_warning_mb) {
+        errors.push(FieldError::new(
+            "plugin_memory_warning_mb",
+            format!("must be between {} and {} MB", min, max),
+        ));
+    }
+
+    let (min, max) = PLUGIN_STORAGE_QUOTA_MB_RANGE;
+    if !(min..=max).contains(&settings.plugin_storage_quota_mb) {
+        errors.push(FieldError::new(
+            "plugin_storage_quota_mb",
+            format!("must be between {} and {} MB", min, max),
+        ));
+    }
+
+    let (min, max) = PLUGIN_CACHE_ENTRIES_RANGE;
+    if !(min..=max).contains(&settings.plugin_cache_entries) {
+        errors.push(FieldError::new(
+            "plugin_cache_entries",
+            format!("must be between {} and {}", min, max),
+        ));
+    }
+
+    let (min, max) = BROWSER_DB_COPY_MB_RANGE;
+    if !(min..=max).contains(&settings.max_browser_db_copy_mb) {
+        errors.push(FieldError::new(
+            "max_browser_db_copy_mb",
+            format!("must be between {} and {} MB", min, max),
+        ));
+    }
+
+    let (min, max) = BROWSER_HISTORY_LIMIT_RANGE;
+    if !(min..=max).contains(&settings.browser_history_limit) {
+        errors.push(FieldError::new(
+            "browser_history_limit",
+            format!("must be between {} and {}", min, max),
+        ));
+    }
+
+    let (min, max) = BROWSER_MIN_VISITS_RANGE;
+    if !(min..=max).contains(&settings.browser_min_visits) {
+        errors.push(FieldError::new(
+            "browser_min_visits",
+            format!("must be between {} and {}", min, max),
+        ));
+    }
+
+    if let OnShowBehavior::ClearAfterSeconds(seconds) = settings.on_show_behavior {
+        let (min, max) = CLEAR_AFTER_SECONDS_RANGE;
+        if !(min..=max).contains(&seconds) {
+            errors.push(FieldError::new(
+                "on_show_behavior",
+                format!("seconds must be between {} and {}", min, max),
+            ));
+        }
+    }
+
+    let retention = &settings.retention;
+    let zero_limit = [retention.files, retention.browser, retention.analytics, retention.logs]
+        .iter()
+        .any(|budget| {
+            budget.max_rows == Some(0) || budget.max_mb == Some(0) || budget.max_age_days == Some(0)
+        });
+    let (min, max) = VACUUM_THRESHOLD_MB_RANGE;
+    if zero_limit {
+        errors.push(FieldError::new("retention", "limits must be at least 1"));
+    } else if !(min..=max).contains(&retention.vacuum_threshold_mb) {
+        errors.push(FieldError::new(
+            "retention",
+            format!("vacuum threshold must be between {} and {} MB", min, max),
+        ));
+    }
+
+    if let Some(hotkey) = &settings.selection_hotkey {
+        if !validate_hotkey(hotkey) || crate::parse_hotkey(hotkey).is_err() {
+            errors.push(FieldError::new("selection_hotkey", "must be a valid hotkey"));
+        } else if normalize_hotkey(hotkey) == normalize_hotkey(&settings.global_hotkey) {
+            errors.push(FieldError::new("selection_hotkey", "must differ from the global hotkey"));
+        }
+    }
+
+    for (context, target) in &settings.openers {
+        if let Err(e) = crate::services::openers::OpenerContext::parse(context) {
+            errors.push(FieldError::new("openers", e));
+        } else if matches!(target, OpenerTarget::Command { template } if template.trim().is_empty()) {
+            errors.push(FieldError::new("openers", format!("{} has an empty command", context)));
+        }
+    }
+
+    if let Some(proxy) = &settings.http_proxy {
+        let scheme_ok = ["http://", "https://", "socks5://"].iter().any(|s| proxy.starts_with(s));
+        if !scheme_ok || reqwest::Proxy::all(proxy.as_str()).is_err() {
+            errors.push(FieldError::new(
+                "http_proxy",
+                "must be an http://, https:// or socks5:// URL",
+            ));
+        }
+    }
+
+    if let Some(endpoint) = &settings.update_endpoint {
+        if !endpoint.starts_with("https://") || reqwest::Url::parse(endpoint).is_err() {
+            errors.push(FieldError::new("update_endpoint", "must be an https:// URL"));
+        }
+    }
+
+    if let Some(schedule) = &settings.do_not_disturb.schedule {
+        if parse_time_of_day(&schedule.start).is_none() || parse_time_of_day(&schedule.end).is_none() {
+            errors.push(FieldError::new(
+                "do_not_disturb",
+                "schedule times must be HH:MM",
+            ));
+        }
+    }
+
+    if settings.enable_browser_search && !browsers_detected {
+        errors.push(FieldError::new(
+            "enable_browser_search",
+            "no supported browser was detected",
+        ));
+    }
+
+    errors
+}
+
+/// Check a changed temp_dir against this machine's folders. The data dir
+/// isn't checked, since saving settings never changes it.
+fn validate_locations<R: Runtime>(
+    handle: &AppHandle<R>,
+    settings: &AppSettings,
+    saved: &AppSettings,
+) -> Vec<FieldError> {
+    let Some(temp_dir) = settings.temp_dir.as_deref().filter(|dir| saved.temp_dir.as_deref() != Some(*dir)) else {
+        return Vec::new();
+    };
+    app_paths::data_dir(handle)
+        .and_then(|data_dir| app_paths::validate_location(Path::new(temp_dir), &data_dir))
+        .err()
+        .map(|e| FieldError::new("temp_dir", e))
+        .into_iter()
+        .collect()
+}
+
+/// Validate settings against this machine's installed browsers and
+/// folders. `saved` is what's saved now, which unchanged folders keep.
+fn validate<R: Runtime>(handle: &AppHandle<R>, settings: &AppSettings, saved: &AppSettings) -> Vec<FieldError> {
+    let browsers_detected = settings.enable_browser_search
+        && !BrowserReader::new(BrowserReaderConfig::default()).detect_browsers().is_empty();
+    let mut errors = validate_settings_fields(settings, browsers_detected);
+    errors.extend(validate_locations(handle, settings, saved));
+    errors
 }
 
 /// Get all application settings (T025)
 #[tauri::command]
-pub fn get_settings(handle: AppHandle) -> Result<AppSettings, String> {
+pub fn get_settings<R: Runtime>(handle: AppHandle<R>) -> Result<AppSettings, String> {
     load_settings(&handle)
 }
 
@@ -83,23 +528,58 @@ macro_rules! impl_get_setting_match {
 /// Get a single setting value by key (T025)
 /// Simplified using macro to reduce code duplication
 #[tauri::command]
-pub fn get_setting(handle: AppHandle, key: String) -> Result<serde_json::Value, String> {
+pub fn get_setting<R: Runtime>(handle: AppHandle<R>, key: String) -> Result<serde_json::Value, String> {
     let settings = load_settings(&handle)?;
     impl_get_setting_match!(settings, key, {
         startup_behavior,
         language,
+        selection_hotkey,
+        on_show_behavior,
         theme,
         window_opacity,
         show_menubar_icon,
+        emoji_skin_tone,
         enable_clipboard,
         enable_file_search,
         enable_browser_search,
+        enable_pinyin_search,
         anonymize_usage,
         crash_reports,
         search_debounce_ms,
+        search_budget_ms,
         max_results,
         excluded_apps,
+        index_settings_panes,
+        index_store_apps,
         file_index_paths,
+        index_file_tags,
+        enable_shell_commands,
+        query_prefixes,
+        source_limits,
+        suggestion_weights,
+        scoring_weights,
+        plugin_rate_limits,
+        do_not_disturb,
+        shell_allowlist,
+        ssh_terminal_template,
+        cli_terminal_template,
+        cli_excluded_dirs,
+        shell_history_files,
+        shell_history_exclude_pattern,
+        min_plugin_security_score,
+        max_plugin_package_mb,
+        plugin_memory_warning_mb,
+        plugin_storage_quota_mb,
+        plugin_cache_entries,
+        max_browser_db_copy_mb,
+        browser_history_limit,
+        browser_min_visits,
+        http_proxy,
+        update_endpoint,
+        offline_mode,
+        retention,
+        data_dir,
+        temp_dir,
     })
 }
 
@@ -110,11 +590,14 @@ macro_rules! impl_set_setting_match {
         match $key.as_str() {
             $(
                 stringify!($field) => {
-                    $settings.$field = serde_json::from_value($value)
-                        .map_err(|e| format!("Invalid {}: {}", stringify!($field), e))?;
+                    $settings.$field = serde_json::from_value($value).map_err(|e| {
+                        SettingsError::Validation {
+                            errors: vec![FieldError::new(stringify!($field), e.to_string())],
+                        }
+                    })?;
                 }
             )*
-            _ => return Err(format!("Unknown setting key: {}", $key)),
+            _ => return Err(format!("Unknown setting key: {}", $key).into()),
         }
     };
 }
@@ -122,46 +605,136 @@ macro_rules! impl_set_setting_match {
 /// Set a single setting value by key (T026)
 /// Simplified using macro to reduce code duplication
 #[tauri::command]
-pub fn set_setting(handle: AppHandle, key: String, value: serde_json::Value) -> Result<(), String> {
-    let mut settings = load_settings(&handle)?;
-
-    impl_set_setting_match!(settings, key, value, {
-        startup_behavior,
-        language,
-        theme,
-        window_opacity,
-        show_menubar_icon,
-        enable_clipboard,
-        enable_file_search,
-        enable_browser_search,
-        anonymize_usage,
-        crash_reports,
-        search_debounce_ms,
-        max_results,
-        excluded_apps,
-        file_index_paths,
-    });
+pub fn set_setting<R: Runtime>(handle: AppHandle<R>, key: String, value: serde_json::Value) -> Result<(), SettingsError> {
+    modify_settings(&handle, |settings| {
+        let saved = settings.clone();
+        impl_set_setting_match!(settings, key, value, {
+            startup_behavior,
+            language,
+            selection_hotkey,
+            on_show_behavior,
+            theme,
+            window_opacity,
+            show_menubar_icon,
+            emoji_skin_tone,
+            enable_clipboard,
+            enable_file_search,
+            enable_browser_search,
+            enable_pinyin_search,
+            anonymize_usage,
+            crash_reports,
+            search_debounce_ms,
+            search_budget_ms,
+            max_results,
+            excluded_apps,
+            index_settings_panes,
+            index_store_apps,
+            file_index_paths,
+            index_file_tags,
+            enable_shell_commands,
+            query_prefixes,
+            source_limits,
+            suggestion_weights,
+            scoring_weights,
+            plugin_rate_limits,
+            do_not_disturb,
+            shell_allowlist,
+            ssh_terminal_template,
+            cli_terminal_template,
+            cli_excluded_dirs,
+            shell_history_files,
+            shell_history_exclude_pattern,
+            min_plugin_security_score,
+            max_plugin_package_mb,
+            plugin_memory_warning_mb,
+            plugin_storage_quota_mb,
+            plugin_cache_entries,
+            max_browser_db_copy_mb,
+            browser_history_limit,
+            browser_min_visits,
+            http_proxy,
+            update_endpoint,
+            offline_mode,
+            retention,
+            temp_dir,
+        });
 
-    save_settings(&handle, &settings)
+        // Only the changed field can fail, so a stale invalid value elsewhere
+        // doesn't block unrelated changes
+        let errors: Vec<FieldError> = validate(&handle, settings, &saved)
+            .into_iter()
+            .filter(|e| e.field == key)
+            .collect();
+        if !errors.is_empty() {
+            return Err(SettingsError::Validation { errors });
+        }
+        Ok(())
+    })
 }
 
 /// Update all application settings (T027)
 #[tauri::command]
-pub fn update_settings(handle: AppHandle, settings: AppSettings) -> Result<(), String> {
-    save_settings(&handle, &settings)
+pub fn update_settings<R: Runtime>(handle: AppHandle<R>, settings: AppSettings) -> Result<(), SettingsError> {
+    let errors = validate(&handle, &settings, &load_settings(&handle)?);
+    if !errors.is_empty() {
+        return Err(SettingsError::Validation { errors });
+    }
+
+    Ok(save_settings(&handle, &settings)?)
+}
+
+/// Dry-run validation so the UI can show field errors before saving
+#[tauri::command]
+pub fn validate_settings<R: Runtime>(handle: AppHandle<R>, settings: AppSettings) -> Vec<FieldError> {
+    validate(&handle, &settings, &load_settings(&handle).unwrap_or_default())
 }
 
 /// Reset settings to defaults
 #[tauri::command]
-pub fn reset_settings(handle: AppHandle) -> Result<AppSettings, String> {
+pub fn reset_settings<R: Runtime>(handle: AppHandle<R>) -> Result<AppSettings, String> {
     let defaults = AppSettings::default();
     save_settings(&handle, &defaults)?;
     Ok(defaults)
 }
 
+/// Get the search scoring weights
+#[tauri::command]
+pub fn get_scoring_weights<R: Runtime>(handle: AppHandle<R>) -> Result<ScoringWeights, String> {
+    Ok(load_settings(&handle)?.scoring_weights)
+}
+
+/// Save search scoring weights, each 0.0–2.0. Saving drops cached search
+/// results, so the next search ranks with the new weights.
+#[tauri::command]
+pub fn set_scoring_weights<R: Runtime>(
+    handle: AppHandle<R>,
+    weights: ScoringWeights,
+) -> Result<ScoringWeights, SettingsError> {
+    modify_settings(&handle, |settings| {
+        settings.scoring_weights = weights;
+
+        // Installed browsers don't affect this field
+        let errors: Vec<FieldError> = validate_settings_fields(settings, true)
+            .into_iter()
+            .filter(|e| e.field == "scoring_weights")
+            .collect();
+        if !errors.is_empty() {
+            return Err(SettingsError::Validation { errors });
+        }
+        Ok(())
+    })?;
+    Ok(weights)
+}
+
+/// Restore the default search scoring weights
+#[tauri::command]
+pub fn reset_scoring_weights<R: Runtime>(handle: AppHandle<R>) -> Result<ScoringWeights, SettingsError> {
+    set_scoring_weights(handle, ScoringWeights::default())
+}
+
 /// Initialize preferences on first run (T029)
 #[tauri::command]
-pub fn init_preferences(handle: AppHandle) -> Result<AppSettings, String> {
+pub fn init_preferences<R: Runtime>(handle: AppHandle<R>) -> Result<AppSettings, String> {
     let settings_path = get_settings_path(&handle)?;
 
     if !settings_path.exists() {
@@ -189,9 +762,10 @@ pub fn set_hotkey(handle: AppHandle, hotkey: String) -> Result<(), String> {
     }
 
     // Load current settings, update hotkey, and save
-    let mut settings = load_settings(&handle)?;
-    settings.global_hotkey = hotkey.clone();
-    save_settings(&handle, &settings)?;
+    modify_settings(&handle, |settings| {
+        settings.global_hotkey = hotkey.clone();
+        Ok::<_, String>(())
+    })?;
 
     println!("Hotkey updated to: {}", hotkey);
     println!("Note: Restart the application for the new hotkey to take effect");
@@ -229,7 +803,7 @@ pub fn reregister_hotkey(handle: AppHandle, hotkey: String) -> Result<(), String
     let shortcut = crate::parse_hotkey(&hotkey)?;
 
     // Get the main window
-    let window = handle.get_webview_window("main")
+    let window = handle.get_webview_window(MAIN_WINDOW)
         .ok_or("Main window not found")?;
 
     use std::sync::atomic::{AtomicBool, Ordering};
@@ -251,6 +825,7 @@ pub fn reregister_hotkey(handle: AppHandle, hotkey: String) -> Result<(), String
 
         if is_visible {
             let _ = window_clone.hide();
+            crate::cmds::window::main_window_hidden(&handle_clone);
             println!("[GlobalShortcut] Window hidden");
         } else {
             // 显示窗口前先定位到鼠标所在屏幕的中心偏上位置
@@ -363,7 +938,8 @@ pub fn reregister_hotkey(handle: AppHandle, hotkey: String) -> Result<(), String
             println!("[GlobalShortcut] Window shown and focused");
 
             // 发送事件到前端，通知窗口已显示并聚焦
-            let _ = handle_clone.emit_to("main", "window-shown", ());
+            let _ = events::emit_to(&handle_clone, MAIN_WINDOW, WindowShown);
+            crate::cmds::window::main_window_shown(&handle_clone);
         }
 
         // Reset the flag after a short delay
@@ -374,10 +950,12 @@ pub fn reregister_hotkey(handle: AppHandle, hotkey: String) -> Result<(), String
         });
     }).map_err(|e| format!("Failed to register global shortcut: {}", e))?;
 
-    // Save to settings
-    let mut settings = load_settings(&handle)?;
-    settings.global_hotkey = hotkey.clone();
-    save_settings(&handle, &settings)?;
+    // Save to settings; applying them also restores the selection hotkey
+    // that unregister_all dropped
+    modify_settings(&handle, |settings| {
+        settings.global_hotkey = hotkey.clone();
+        Ok::<_, String>(())
+    })?;
 
     println!("Hotkey reregistered successfully: {}", hotkey);
 
@@ -431,7 +1009,7 @@ pub fn check_hotkey_conflicts(hotkey: String) -> Result<Vec<String>, String> {
 
 /// Get settings file path for debugging
 #[tauri::command]
-pub fn get_settings_file_path(handle: AppHandle) -> Result<String, String> {
+pub fn get_settings_file_path<R: Runtime>(handle: AppHandle<R>) -> Result<String, String> {
     let path = get_settings_path(&handle)?;
     Ok(path.to_string_lossy().to_string())
 }
@@ -461,3 +1039,216 @@ fn normalize_hotkey(hotkey: &str) -> String {
         .replace("Option", "Alt")
         .to_lowercase()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestApp;
+    use serde_json::json;
+
+    fn fields(settings: &AppSettings, browsers_detected: bool) -> Vec<String> {
+        validate_settings_fields(settings, browsers_detected)
+            .into_iter()
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_defaults_are_valid() {
+        assert!(fields(&AppSettings::default(), false).is_empty());
+    }
+
+    #[test]
+    fn test_window_opacity_bounds() {
+        let mut settings = AppSettings::default();
+        for (value, valid) in [(0.29, false), (0.3, true), (1.0, true), (1.01, false), (37.0, false)] {
+            settings.window_opacity = value;
+            assert_eq!(fields(&settings, false).is_empty(), valid, "opacity {}", value);
+        }
+    }
+
+    #[test]
+    fn test_max_results_bounds() {
+        let mut settings = AppSettings::default();
+        for (value, valid) in [(0, false), (1, true), (200, true), (201, false)] {
+            settings.max_results = value;
+            assert_eq!(fields(&settings, false).is_empty(), valid, "max_results {}", value);
+        }
+    }
+
+    #[test]
+    fn test_search_debounce_bounds() {
+        let mut settings = AppSettings::default();
+        for (value, valid) in [(10, false), (49, false), (50, true), (1000, true), (1001, false)] {
+            settings.search_debounce_ms = value;
+            assert_eq!(fields(&settings, false).is_empty(), valid, "debounce {}", value);
+        }
+    }
+
+    #[test]
+    fn test_clear_after_seconds_bounds() {
+        let mut settings = AppSettings::default();
+        for (value, valid) in [(0, false), (1, true), (3600, true), (3601, false)] {
+            settings.on_show_behavior = OnShowBehavior::ClearAfterSeconds(value);
+            assert_eq!(fields(&settings, false).is_empty(), valid, "clear after {}", value);
+        }
+    }
+
+    #[test]
+    fn test_language_must_be_supported() {
+        let mut settings = AppSettings::default();
+        settings.language = "zh-CN".to_string();
+        assert!(fields(&settings, false).is_empty());
+        settings.language = "klingon".to_string();
+        assert_eq!(fields(&settings, false), vec!["language"]);
+    }
+
+    #[test]
+    fn test_suggestion_weights_must_name_known_sections() {
+        let mut settings = AppSettings::default();
+        settings.suggestion_weights.insert("clipboard".to_string(), 0);
+        assert!(fields(&settings, false).is_empty());
+        settings.suggestion_weights.insert("app".to_string(), MAX_SUGGESTION_WEIGHT + 1);
+        assert_eq!(fields(&settings, false), vec!["suggestion_weights"]);
+        settings.suggestion_weights.insert("app".to_string(), 4);
+        settings.suggestion_weights.insert("weather".to_string(), 1);
+        assert_eq!(fields(&settings, false), vec!["suggestion_weights"]);
+    }
+
+    #[test]
+    fn test_scoring_weight_bounds() {
+        let mut settings = AppSettings::default();
+        for (value, valid) in [(-0.1, false), (0.0, true), (2.0, true), (2.01, false), (f64::NAN, false)] {
+            settings.scoring_weights.contains = value;
+            assert_eq!(fields(&settings, false).is_empty(), valid, "contains {}", value);
+        }
+
+        settings.scoring_weights.exact = 3.0;
+        let errors = validate_settings_fields(&settings, false);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "exact, contains must be between 0 and 2");
+    }
+
+    #[test]
+    fn test_theme_must_be_known() {
+        let result: Result<crate::models::preferences::Theme, _> =
+            serde_json::from_value(serde_json::json!("neon"));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_browser_search_requires_detected_browser() {
+        let mut settings = AppSettings::default();
+        settings.enable_browser_search = true;
+        assert_eq!(fields(&settings, false), vec!["enable_browser_search"]);
+        assert!(fields(&settings, true).is_empty());
+    }
+
+    #[test]
+    fn test_reports_every_invalid_field() {
+        let mut settings = AppSettings::default();
+        settings.window_opacity = 37.0;
+        settings.max_results = 0;
+        settings.search_debounce_ms = 10;
+        assert_eq!(
+            fields(&settings, false),
+            vec!["window_opacity", "max_results", "search_debounce_ms"]
+        );
+    }
+
+    #[test]
+    fn test_set_setting_round_trips() {
+        let app = TestApp::new();
+        let handle = app.handle().clone();
+
+        assert_eq!(get_setting(handle.clone(), "max_results".to_string()).unwrap(), json!(50));
+
+        set_setting(handle.clone(), "max_results".to_string(), json!(25)).unwrap();
+        set_setting(handle.clone(), "excluded_apps".to_string(), json!(["Chess"])).unwrap();
+        assert_eq!(get_setting(handle.clone(), "max_results".to_string()).unwrap(), json!(25));
+        assert_eq!(get_setting(handle.clone(), "excluded_apps".to_string()).unwrap(), json!(["Chess"]));
+
+        // Written to the app's own config dir
+        let saved: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(app.config_dir().join("settings.json")).unwrap()).unwrap();
+        assert_eq!(saved["max_results"], json!(25));
+        assert_eq!(saved["schema_version"], json!(SETTINGS_SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_set_setting_rejects_invalid_values() {
+        let app = TestApp::new();
+        let handle = app.handle().clone();
+        set_setting(handle.clone(), "max_results".to_string(), json!(25)).unwrap();
+
+        let out_of_range = set_setting(handle.clone(), "max_results".to_string(), json!(0));
+        assert!(matches!(out_of_range, Err(SettingsError::Validation { ref errors }) if errors[0].field == "max_results"));
+        let wrong_type = set_setting(handle.clone(), "max_results".to_string(), json!("many"));
+        assert!(matches!(wrong_type, Err(SettingsError::Validation { .. })));
+        assert!(set_setting(handle.clone(), "no_such_key".to_string(), json!(1)).is_err());
+        assert!(get_setting(handle.clone(), "no_such_key".to_string()).is_err());
+
+        // Nothing was saved by the failed calls
+        assert_eq!(get_setting(handle, "max_results".to_string()).unwrap(), json!(25));
+    }
+
+    #[test]
+    fn test_temp_dir_must_be_a_writable_folder_outside_the_data_dir() {
+        let app = TestApp::new();
+        let handle = app.handle().clone();
+        let elsewhere = tempfile::tempdir().unwrap();
+        let set = |dir: &Path| set_setting(handle.clone(), "temp_dir".to_string(), json!(dir));
+
+        let missing = set(&elsewhere.path().join("missing"));
+        assert!(matches!(missing, Err(SettingsError::Validation { ref errors }) if errors[0].field == "temp_dir"));
+        fs::create_dir(app.data_dir().join("nested")).unwrap();
+        assert!(set(&app.data_dir().join("nested")).is_err());
+        set(elsewhere.path()).unwrap();
+        assert_eq!(get_setting(handle.clone(), "temp_dir".to_string()).unwrap(), json!(elsewhere.path()));
+
+        // Saving all settings leaves the data dir alone; only a migration moves it
+        let mut settings = get_settings(handle.clone()).unwrap();
+        settings.data_dir = Some(elsewhere.path().to_string_lossy().into_owned());
+        update_settings(handle.clone(), settings).unwrap();
+        assert_eq!(get_settings(handle).unwrap().data_dir, None);
+    }
+
+    #[test]
+    fn test_seeded_settings_are_loaded_and_reset() {
+        let app = TestApp::new();
+        let mut seeded = AppSettings::default();
+        seeded.search_debounce_ms = 300;
+        app.seed_settings(&seeded);
+
+        let handle = app.handle().clone();
+        assert_eq!(get_settings(handle.clone()).unwrap().search_debounce_ms, 300);
+
+        reset_settings(handle.clone()).unwrap();
+        assert_eq!(
+            get_settings(handle).unwrap().search_debounce_ms,
+            AppSettings::default().search_debounce_ms
+        );
+    }
+
+    #[test]
+    fn test_scoring_weights_commands() {
+        let app = TestApp::new();
+        let handle = app.handle().clone();
+        assert_eq!(get_scoring_weights(handle.clone()).unwrap(), ScoringWeights::default());
+
+        let tuned = ScoringWeights {
+            contains: 1.2,
+            frequency: 0.0,
+            ..Default::default()
+        };
+        assert_eq!(set_scoring_weights(handle.clone(), tuned).unwrap(), tuned);
+        assert_eq!(get_scoring_weights(handle.clone()).unwrap(), tuned);
+
+        let rejected = set_scoring_weights(handle.clone(), ScoringWeights { exact: 2.5, ..tuned });
+        assert!(matches!(rejected, Err(SettingsError::Validation { ref errors }) if errors[0].field == "scoring_weights"));
+        assert_eq!(get_scoring_weights(handle.clone()).unwrap(), tuned);
+
+        reset_scoring_weights(handle.clone()).unwrap();
+        assert_eq!(get_scoring_weights(handle).unwrap(), ScoringWeights::default());
+    }
+}
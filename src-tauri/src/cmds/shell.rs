@@ -3,7 +3,15 @@
  * Tauri commands for shell integration and URL opening
  */
 
-use tauri::AppHandle;
+use crate::models::preferences::default_cli_terminal_template;
+use crate::services::app_paths;
+use crate::services::path_tools::{self, PathToolCache};
+use crate::services::shell_exec::{self, ShellAuditEntry, ShellAuditLog, ShellOutput};
+use crate::services::shell_history::{self, ShellHistoryCache};
+use crate::services::ssh_hosts;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tauri::{AppHandle, Manager, State};
 
 /// Open a URL in the default browser
 #[tauri::command]
@@ -22,3 +30,120 @@ pub fn get_default_browser() -> Result<String, String> {
     // This is platform-specific
     Ok("default".to_string())
 }
+
+/// Run a "> cmd" query in the user's shell (requires enable_shell_commands).
+/// It goes through the same path as plugin shell calls: killed after the
+/// default timeout, output capped, and recorded in the audit log under
+/// "shell-command".
+#[tauri::command]
+pub async fn run_shell_command(handle: AppHandle, command: String) -> Result<ShellOutput, String> {
+    let settings = crate::cmds::settings::load_settings(&handle)?;
+    if !settings.enable_shell_commands {
+        return Err("Shell commands are disabled in settings".to_string());
+    }
+
+    if command.trim().is_empty() {
+        return Err("Command is empty".to_string());
+    }
+
+    #[cfg(target_os = "windows")]
+    let (shell, args) = ("cmd", vec!["/C".to_string(), command]);
+    #[cfg(not(target_os = "windows"))]
+    let (shell, args) = ("sh", vec!["-c".to_string(), command]);
+
+    shell_exec::run(&handle, "shell-command", shell, &args, None, None).await
+}
+
+/// Get plugin shell executions, newest first, optionally for one plugin
+#[tauri::command]
+pub fn get_shell_audit_log(plugin_id: Option<String>, audit: State<ShellAuditLog>) -> Vec<ShellAuditEntry> {
+    audit.entries(plugin_id.as_deref())
+}
+
+/// Open a terminal with an SSH session to `host`, a host from the user's
+/// ssh config or known_hosts. The terminal program from the
+/// ssh_terminal_template setting must be on the shell allowlist.
+#[tauri::command]
+pub async fn open_ssh_session(handle: AppHandle, host: String) -> Result<(), String> {
+    if !ssh_hosts::is_valid_host(&host) {
+        return Err(format!("Invalid SSH host: {}", host));
+    }
+
+    let settings = crate::cmds::settings::load_settings(&handle)?;
+    let ssh_dir = handle
+        .path()
+        .home_dir()
+        .map_err(|e| format!("Failed to find home directory: {}", e))?
+        .join(".ssh");
+
+    crate::utils::run_blocking(move || {
+        if !ssh_hosts::load_hosts(&ssh_dir).iter().any(|known| known.alias == host) {
+            return Err(format!("Unknown SSH host: {}", host));
+        }
+
+        let (program, args) = ssh_hosts::terminal_command(&settings.ssh_terminal_template, &host)?;
+        if !settings.shell_allowlist.iter().any(|allowed| allowed == &program) {
+            return Err(format!(
+                "{} is not on the shell allowlist; add it in settings to open SSH sessions",
+                program
+            ));
+        }
+
+        shell_exec::launch(&handle, "ssh", &program, &args)
+    })
+    .await
+}
+
+/// Open a terminal running the command-line tool at `path`, one found by
+/// the PATH scan. The built-in terminal launcher always may run; a custom
+/// cli_terminal_template must start a program on the shell allowlist.
+#[tauri::command]
+pub async fn run_cli_tool(handle: AppHandle, path: String) -> Result<(), String> {
+    let tools = Arc::clone(handle.state::<Arc<PathToolCache>>().inner());
+    let Some(tool) = tools.find(&PathBuf::from(&path)) else {
+        return Err(format!("Unknown command-line tool: {}", path));
+    };
+
+    let settings = crate::cmds::settings::load_settings(&handle)?;
+    let (program, args) = path_tools::terminal_command(&settings.cli_terminal_template, &tool.path)?;
+    let builtin = settings.cli_terminal_template == default_cli_terminal_template();
+    if !builtin && !settings.shell_allowlist.iter().any(|allowed| allowed == &program) {
+        return Err(format!(
+            "{} is not on the shell allowlist; add it in settings to run command-line tools",
+            program
+        ));
+    }
+
+    crate::utils::run_blocking(move || shell_exec::launch(&handle, "cli", &program, &args)).await
+}
+
+/// Open a terminal running `command`, one from the user's shell history.
+/// History can hold destructive commands, so this needs `confirmed`, set
+/// once the user has seen the command and agreed to run it. It always runs
+/// in the built-in terminal, never one from settings or the shell allowlist.
+#[tauri::command]
+pub async fn run_history_command(handle: AppHandle, command: String, confirmed: bool) -> Result<(), String> {
+    if !confirmed {
+        return Err("Running a command from shell history needs confirmation".to_string());
+    }
+
+    let settings = crate::cmds::settings::load_settings(&handle)?;
+    let history = Arc::clone(handle.state::<Arc<ShellHistoryCache>>().inner());
+    let home = handle.path().home_dir().ok();
+    let cache_dir = app_paths::cache_dir(&handle)?;
+
+    crate::utils::run_blocking(move || {
+        let files = shell_history::history_paths(&settings.shell_history_files, home.as_deref());
+        let entries = history.entries(&files, &settings.shell_history_exclude_pattern);
+        if !entries.iter().any(|entry| entry.command == command) {
+            return Err("The command isn't in shell history".to_string());
+        }
+
+        std::fs::create_dir_all(&cache_dir).map_err(|e| format!("Failed to create cache dir: {}", e))?;
+        let script = cache_dir.join("history-command.command");
+        let (program, args) = shell_history::launcher_command(&command, &script)?;
+
+        shell_exec::launch(&handle, "shell-history", &program, &args)
+    })
+    .await
+}
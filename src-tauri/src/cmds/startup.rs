@@ -0,0 +1,356 @@
+/**
+ * Startup Commands
+ * The boot sequence outside the window and hotkey: the core that setup
+ * runs before the app is interactive, and the deferred stages a background
+ * thread runs once it is. Both are timed for `get_startup_report`.
+ */
+
+use crate::cmds::app::AppState;
+use crate::cmds::performance::PerformanceState;
+use crate::cmds::search::SearchState;
+use crate::models::clipboard::ClipboardSettings;
+use crate::models::preferences::AppSettings;
+use crate::services::app_paths;
+use crate::services::plugin_sandbox::{plugin_permissions_path, sandbox_state_path, PluginSandbox};
+use crate::services::startup::{StartupReport, StartupStage, StartupTimings};
+use crate::{cmds, db, services, utils};
+use std::sync::{Arc, Mutex, RwLock};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// Per-stage boot durations and the time until the app was interactive
+#[tauri::command]
+pub fn get_startup_report(timings: State<StartupTimings>) -> StartupReport {
+    timings.report()
+}
+
+/// Load settings and register the state commands rely on. Nothing here
+/// scans, opens databases or lists plugins; that's left to
+/// `run_deferred_stages`. Returns the loaded settings.
+pub fn init_core<R: Runtime>(handle: &AppHandle<R>, timings: &StartupTimings) -> AppSettings {
+    // Backend messages follow the UI language; HTTP uses the configured
+    // proxy and stays off the network in offline mode
+    let settings = timings.time(StartupStage::Settings, || {
+        let settings = cmds::settings::load_settings(handle).unwrap_or_default();
+        services::i18n::init(&settings.language);
+        services::http::set_manual_proxy(settings.http_proxy.clone());
+        services::connectivity::monitor().set_forced(settings.offline_mode);
+        settings
+    });
+    timings.time(StartupStage::Services, || manage_services(handle, &settings));
+    settings
+}
+
+fn manage_services<R: Runtime>(handle: &AppHandle<R>, settings: &AppSettings) {
+    let data_dir = app_paths::data_dir(handle).ok();
+    handle.manage(services::db_maintenance::MaintenanceGuard::new());
+
+    // App monitors start empty and scan on first use, with the settings
+    // panes and Store apps the settings allow
+    let app_sources = services::app_sources::AppSources::from_settings(settings);
+    let new_app_monitor = || {
+        let mut monitor = services::app_monitor::AppMonitor::new();
+        monitor.set_sources(app_sources);
+        monitor
+    };
+    handle.manage(AppState {
+        app_monitor: Mutex::new(new_app_monitor()),
+    });
+
+    // Privacy mode starts off unless a persistent state was saved
+    let privacy = services::privacy::PrivacyMode::new();
+    if let Ok(config_dir) = handle.path().app_config_dir() {
+        privacy.restore(&services::privacy::privacy_state_path(&config_dir));
+    }
+    handle.manage(privacy.clone());
+
+    let search_monitor = Arc::new(Mutex::new(new_app_monitor()));
+    let search_settings = Arc::new(RwLock::new(settings.clone()));
+    let mut providers = services::search::ProviderRegistry::new();
+    providers.register(Box::new(services::search::AppProvider::new(
+        search_monitor.clone(),
+        search_settings.clone(),
+    )));
+    providers.register(Box::new(services::search::CalculatorProvider));
+    providers.register(Box::new(services::search::DateTimeProvider));
+    if let Some(data_dir) = &data_dir {
+        let emoji_usage = Arc::new(utils::StateStore::new(data_dir.join("emoji_usage.json"), "emoji usage"));
+        handle.manage(emoji_usage.clone());
+        providers.register(Box::new(services::search::EmojiProvider::new(
+            emoji_usage,
+            search_settings.clone(),
+        )));
+    }
+    providers.register(Box::new(services::search::CommandProvider));
+    let process_cache = Arc::new(services::process_manager::ProcessCache::new(Box::new(
+        services::process_manager::SystemProcesses::new(),
+    )));
+    handle.manage(process_cache.clone());
+    providers.register(Box::new(services::search::ProcessProvider::new(process_cache)));
+    let ssh_dir = handle.path().home_dir().ok().map(|home| home.join(".ssh"));
+    providers.register(Box::new(services::search::SshProvider::new(ssh_dir)));
+    // The first search starts the PATH scan in the background
+    let path_tools = Arc::new(services::path_tools::PathToolCache::new());
+    handle.manage(path_tools.clone());
+    providers.register(Box::new(services::search::CliProvider::new(
+        path_tools,
+        search_settings.clone(),
+    )));
+    // History files are parsed on the first "!" query
+    let shell_history = Arc::new(services::shell_history::ShellHistoryCache::new());
+    handle.manage(shell_history.clone());
+    providers.register(Box::new(services::search::ShellHistoryProvider::new(
+        shell_history,
+        handle.path().home_dir().ok(),
+        search_settings.clone(),
+    )));
+    if let Ok(path) = services::config_service::abbreviation_config_path(handle) {
+        providers.register(Box::new(services::search::AbbreviationProvider::new(path)));
+    }
+    // Providers open their databases per query, so nothing is opened here
+    if let Ok(path) = db::get_files_db_path(handle) {
+        providers.register(Box::new(services::search::FileProvider::new(path)));
+    }
+    if let Ok(path) = db::get_browser_db_path(handle) {
+        providers.register(Box::new(services::search::BrowserProvider::new(path, privacy.clone())));
+    }
+
+    handle.manage(SearchState {
+        app_monitor: search_monitor,
+        file_indexer: Mutex::new(None),
+        providers: Arc::new(providers),
+        cancellation: Arc::new(services::search::SearchCancellation::new()),
+        debounce: Arc::new(services::search::SearchDebounce::new()),
+        retained: Arc::new(services::search::RetainedResults::new()),
+        performance: Arc::new(services::search::SearchPerformance::new()),
+        cache: services::search::SearchCache::new(),
+        settings: search_settings,
+    });
+
+    // Grants and crash state are restored by the sandbox stage, the
+    // registry is loaded by the plugins stage
+    handle.manage(PluginSandbox::new());
+    handle.manage(services::plugin_registry::PluginRegistry::new());
+    if let Some(data_dir) = &data_dir {
+        handle.manage(services::plugin_storage::PluginStorage::new(
+            services::plugin_storage::plugin_data_root(data_dir),
+        ));
+    }
+    handle.manage(services::plugin_rate_limiter::PluginRateLimiter::new());
+    handle.manage(services::install_jobs::InstallJobManager::new());
+    handle.manage(services::package_transfer::UploadManager::new());
+    handle.manage(services::trigger_completions::CompletionBridge::new());
+    handle.manage(services::drop_actions::DroppedPaths::default());
+    handle.manage(services::plugin_result_cache::PluginResultCache::new(
+        settings.plugin_cache_entries,
+    ));
+    handle.manage(services::notifications::NotificationCenter::new(data_dir.as_deref()));
+    handle.manage(services::shell_exec::ShellAuditLog::new(data_dir.as_deref()));
+
+    // Clipboard history skips the app's own writes, such as the selection
+    // hotkey's temporary copy, and pauses in privacy mode
+    let suppression = services::clipboard_watcher::ClipboardSuppression::new();
+    handle.manage(suppression.clone());
+    if let Some(data_dir) = &data_dir {
+        handle.manage(
+            services::clipboard_watcher::ClipboardWatcher::new(data_dir.join("clipboard"), ClipboardSettings::default())
+                .with_privacy(privacy.clone())
+                .with_suppression(suppression),
+        );
+    }
+    handle.manage(services::launcher_visibility::LauncherVisibility::new());
+    handle.manage(PerformanceState {
+        monitor: Arc::new(Mutex::new(services::performance::PerformanceMonitor::new())),
+    });
+    handle.manage(services::plugin_performance::PluginPerformanceMonitor::new(
+        cmds::plugin_performance::MAX_BUFFERED_METRICS,
+        cmds::plugin_performance::SLOW_OPERATION_MS,
+    ));
+
+    // Ordered shutdown for quit_app / restart_app
+    handle.manage(services::shutdown::ShutdownRegistry::new());
+    handle.manage(services::task_supervisor::TaskSupervisor::new());
+}
+
+/// Mark the app interactive and run the deferred stages on a background
+/// thread. Called once the event loop is running.
+pub(crate) fn start_deferred(handle: &AppHandle) {
+    handle.state::<StartupTimings>().mark_interactive();
+
+    let handle = handle.clone();
+    std::thread::spawn(move || {
+        let timings = handle.state::<StartupTimings>();
+        run_deferred_stages(&handle, &timings);
+        timings.time(StartupStage::Schedulers, || start_schedulers(&handle));
+        timings.mark_deferred_done();
+
+        let report = timings.report();
+        println!(
+            "[Startup] Interactive after {:.0} ms, fully loaded after {:.0} ms",
+            report.total_to_interactive_ms.unwrap_or_default(),
+            report.total_deferred_ms.unwrap_or_default(),
+        );
+    });
+}
+
+/// Load what the core left out, in dependency order: the sandbox before
+/// plugins, since listing plugins can quarantine one through it
+pub fn run_deferred_stages<R: Runtime>(handle: &AppHandle<R>, timings: &StartupTimings) {
+    timings.time(StartupStage::Sandbox, || {
+        if let (Some(sandbox), Ok(data_dir)) = (handle.try_state::<PluginSandbox>(), app_paths::data_dir(handle)) {
+            sandbox.restore(&plugin_permissions_path(&data_dir));
+            sandbox.restore_state(&sandbox_state_path(&data_dir));
+        }
+    });
+
+    timings.time(StartupStage::Plugins, || {
+        if let Err(e) = cmds::plugins::list_plugins(handle, false) {
+            eprintln!("[Startup] Failed to list plugins: {}", e);
+        }
+        match cmds::plugins::sweep_orphaned_plugin_data(handle) {
+            Ok(Some(report)) if !report.is_empty() => println!("[Startup] Removed orphaned plugin data: {:?}", report),
+            Ok(_) => {}
+            Err(e) => eprintln!("[Startup] Failed to sweep orphaned plugin data: {}", e),
+        }
+    });
+
+    // Creates or migrates the schemas before the first query needs them
+    timings.time(StartupStage::Indexes, || {
+        if let Err(e) = db::files::init_files_db(handle) {
+            eprintln!("[Startup] Failed to open files database: {}", e);
+        }
+        let browser_db = db::get_browser_db_path(handle)
+            .and_then(|path| db::browser::open_browser_db(&path).map_err(|e| e.to_string()));
+        if let Err(e) = browser_db {
+            eprintln!("[Startup] Failed to open browser database: {}", e);
+        }
+    });
+
+    timings.time(StartupStage::Apps, || {
+        let monitor = handle.state::<SearchState>().app_monitor.clone();
+        let apps = monitor.lock().map(|mut monitor| monitor.scan_apps().len());
+        match apps {
+            Ok(count) => println!("[Startup] Found {} applications", count),
+            Err(e) => eprintln!("[Startup] Failed to scan applications: {}", e),
+        }
+    });
+}
+
+fn start_schedulers(handle: &AppHandle) {
+    services::connectivity::start_connectivity_monitor(handle.clone());
+
+    if let Some(watcher) = handle.try_state::<services::clipboard_watcher::ClipboardWatcher>() {
+        if let Err(e) = watcher.start() {
+            eprintln!("[Startup] Failed to start clipboard watcher: {}", e);
+        }
+    }
+
+    // Keep the search app list current as apps are installed and removed
+    let search_monitor = handle.state::<SearchState>().app_monitor.clone();
+    if let Err(e) = services::app_watcher::start_app_watcher(handle.clone(), search_monitor) {
+        eprintln!("[Startup] Failed to start app watcher: {}", e);
+    }
+
+    let tasks = [
+        cmds::plugin_performance::start_rollup_flusher(handle),
+        cmds::retention::start_retention_task(handle),
+        cmds::plugins::start_plugin_health_task(handle),
+        cmds::plugins::start_plugins_dir_watcher(handle),
+    ];
+    for e in tasks.into_iter().filter_map(Result::err) {
+        eprintln!("[Startup] Failed to start background task: {}", e);
+    }
+
+    // Stale extractions and uploads from earlier sessions
+    if let Ok(temp_dir) = app_paths::temp_dir(handle) {
+        let max_age = services::install_jobs::DEFAULT_TEMP_MAX_AGE;
+        std::thread::spawn(move || {
+            match services::install_jobs::sweep_temp_dir(&temp_dir, max_age, &Default::default()) {
+                Ok(0) => {}
+                Ok(bytes) => println!("[Startup] Reclaimed {} bytes of installer temp files", bytes),
+                Err(e) => eprintln!("[Startup] Temp dir sweep failed: {}", e),
+            }
+        });
+    }
+}
+
+/// Fail with a "not ready" error until `stage` has run, for commands that
+/// would otherwise see its state half-loaded. Commands don't wait for it,
+/// since a sync command blocking here would freeze the main thread. Always
+/// ready when startup isn't being tracked, as in tests.
+pub(crate) fn ensure_stage<R: Runtime>(handle: &AppHandle<R>, stage: StartupStage) -> Result<(), String> {
+    match handle.try_state::<StartupTimings>() {
+        Some(timings) if !timings.has_run(stage) => Err("Still starting up; try again in a moment".to_string()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::TestApp;
+    use serde_json::json;
+
+    #[test]
+    fn test_core_then_deferred_stages() {
+        let app = TestApp::new();
+        app.seed_plugin(
+            "hello",
+            json!({
+                "name": "Hello",
+                "version": "1.0.0",
+                "description": "Says hello",
+                "author": null,
+                "permissions": [],
+                "entry": "index.js",
+                "triggers": ["hello"],
+                "api_version": crate::services::plugin_api::HOST_API_VERSION,
+            }),
+        );
+        let timings = StartupTimings::new();
+
+        init_core(app.handle(), &timings);
+        timings.mark_interactive();
+        // Commands can find their state, but nothing was opened or listed
+        assert!(app.handle().try_state::<SearchState>().is_some());
+        assert!(app.handle().try_state::<PluginSandbox>().is_some());
+        assert!(!app.data_dir().join("files_index.db").exists());
+
+        run_deferred_stages(app.handle(), &timings);
+        assert!(app.data_dir().join("files_index.db").exists());
+        assert!(app.data_dir().join("browser_cache.db").exists());
+
+        let report = timings.report();
+        let stages: Vec<_> = report.stages.iter().map(|s| s.stage).collect();
+        assert_eq!(
+            stages,
+            vec![
+                StartupStage::Settings,
+                StartupStage::Services,
+                StartupStage::Sandbox,
+                StartupStage::Plugins,
+                StartupStage::Indexes,
+                StartupStage::Apps,
+            ]
+        );
+        let interactive = report.total_to_interactive_ms.unwrap();
+        assert!(report
+            .stages
+            .iter()
+            .filter(|s| s.deferred)
+            .all(|s| s.started_ms >= interactive));
+    }
+
+    #[test]
+    fn test_commands_fail_fast_until_stage_has_run() {
+        let app = TestApp::new();
+        // Untracked startup, as in tests, never holds commands back
+        assert!(ensure_stage(app.handle(), StartupStage::Sandbox).is_ok());
+
+        app.handle().manage(StartupTimings::new());
+        let timings = app.handle().state::<StartupTimings>();
+        assert!(ensure_stage(app.handle(), StartupStage::Sandbox).is_err());
+
+        timings.time(StartupStage::Sandbox, || {});
+        assert!(ensure_stage(app.handle(), StartupStage::Sandbox).is_ok());
+    }
+}
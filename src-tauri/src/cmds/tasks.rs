@@ -0,0 +1,27 @@
+/**
+ * Background Task Commands
+ * The background task table for the diagnostics page, and running a task
+ * on demand
+ */
+
+use crate::services::task_supervisor::{TaskStatus, TaskSupervisor};
+use crate::utils::run_blocking;
+use tauri::{AppHandle, Manager, State};
+
+/// State, last run, last error and run times of every background task
+#[tauri::command]
+pub fn get_background_tasks(supervisor: State<TaskSupervisor>) -> Vec<TaskStatus> {
+    supervisor.snapshot()
+}
+
+/// Run an interval task now, even if it's paused, and return its updated
+/// row. Fails with the task's error if the run fails.
+#[tauri::command]
+pub async fn run_task_now(handle: AppHandle, name: String) -> Result<TaskStatus, String> {
+    run_blocking(move || {
+        let supervisor = handle.state::<TaskSupervisor>();
+        supervisor.run_now(&name)?;
+        supervisor.status(&name)
+    })
+    .await
+}
@@ -1,7 +1,12 @@
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager, Runtime};
 use tokio::time::Duration;
-use crate::models::{ViewConfig, CalculatedWindowLayout, ScreenInfo};
+use crate::models::{ViewConfig, CalculatedWindowLayout, ScreenInfo, WindowLayout};
 use crate::services::{detect_screen_info, calculate_window_layout};
+use crate::services::launcher_visibility::LauncherVisibility;
+use crate::services::windows::MAIN_WINDOW;
+use crate::services::window_layouts::{self, layout_store, snapshot_layout};
+use crate::cmds::settings::{load_settings, modify_settings};
+use crate::types::events::{self, SearchReset};
 
 /// Get current screen information
 #[tauri::command]
@@ -22,8 +27,8 @@ pub async fn resize_window_smart(
     let config = ViewConfig::from_id(&view_id)?;
 
     // Get current window
-    let window = app.get_webview_window("main")
-        .ok_or("Window 'main' not found")?;
+    let window = app.get_webview_window(MAIN_WINDOW)
+        .ok_or("Main window not found")?;
 
     // Get current size
     let current_size = window.outer_size()
@@ -41,7 +46,7 @@ pub async fn resize_window_smart(
     }
 
     // Emit resize_start event
-    let _ = app.emit("window:resize_start", &screen_info);
+    let _ = events::emit(&app, &screen_info);
 
     // Animate window resize
     let frames = 12; // 60fps * 200ms = 12 frames
@@ -77,7 +82,99 @@ pub async fn resize_window_smart(
         .map_err(|e| format!("Failed to set window position: {}", e))?;
 
     // Emit resize_complete event
-    let _ = app.emit("window:resize_complete", &target_layout);
+    let _ = events::emit(&app, &target_layout);
 
     Ok(target_layout)
 }
+
+/// Close a window by label. Closing one that isn't open is not an error.
+/// The launcher can only be hidden, since closing it would end the app.
+#[tauri::command]
+pub fn close_window(app: AppHandle, label: String) -> Result<(), String> {
+    if label == MAIN_WINDOW {
+        return Err("The main window can't be closed; hide it instead".to_string());
+    }
+
+    match app.get_webview_window(&label) {
+        Some(window) => window.close().map_err(|e| format!("Failed to close window '{}': {}", label, e)),
+        None => Ok(()),
+    }
+}
+
+/// Save the main window's current size, position and always-on-top as a
+/// named layout, replacing one with the same name. `results_height` is the
+/// frontend's results panel height; `follow_cursor` keeps only the size,
+/// so the launcher still opens on the monitor under the cursor.
+#[tauri::command]
+pub fn save_current_layout(
+    app: AppHandle,
+    name: String,
+    results_height: Option<u32>,
+    follow_cursor: Option<bool>,
+) -> Result<WindowLayout, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Layout name can't be empty".to_string());
+    }
+
+    let window = app.get_webview_window(MAIN_WINDOW)
+        .ok_or("Main window not found")?;
+    let layout = snapshot_layout(&window, name, results_height, follow_cursor.unwrap_or(false))?;
+
+    layout_store(&app)?.update(|layouts| {
+        layouts.save(layout.clone());
+        Ok::<_, String>(())
+    })?;
+    Ok(layout)
+}
+
+/// Resize and place the main window by a saved layout and keep it active.
+/// A layout saved on a monitor that's gone lands at the same relative spot
+/// on the primary monitor.
+#[tauri::command]
+pub fn apply_layout(app: AppHandle, name: String) -> Result<WindowLayout, String> {
+    let window = app.get_webview_window(MAIN_WINDOW)
+        .ok_or("Main window not found")?;
+    window_layouts::apply_layout(&app, &window, &name)
+}
+
+/// Saved layouts by name
+#[tauri::command]
+pub fn list_layouts(app: AppHandle) -> Result<Vec<WindowLayout>, String> {
+    Ok(layout_store(&app)?.read()?.list())
+}
+
+/// Delete a saved layout. It's no longer applied at startup either.
+#[tauri::command]
+pub fn delete_layout(app: AppHandle, name: String) -> Result<(), String> {
+    layout_store(&app)?.update(|layouts| layouts.remove(&name).map(|_| ()))?;
+
+    if load_settings(&app)?.default_window_layout.as_deref() == Some(name.as_str()) {
+        modify_settings(&app, |settings| {
+            settings.default_window_layout = None;
+            Ok::<_, String>(())
+        })?;
+    }
+    Ok(())
+}
+
+/// Record that the main window was hidden, for `main_window_shown`
+pub(crate) fn main_window_hidden<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(visibility) = app.try_state::<LauncherVisibility>() {
+        visibility.hidden();
+    }
+}
+
+/// The main window was shown; reset its search if `on_show_behavior` says
+/// the previous one shouldn't be kept
+pub(crate) fn main_window_shown<R: Runtime>(app: &AppHandle<R>) {
+    let Some(visibility) = app.try_state::<LauncherVisibility>() else {
+        return;
+    };
+    let behavior = load_settings(app).map(|settings| settings.on_show_behavior).unwrap_or_default();
+    if visibility.shown(behavior) {
+        if let Err(e) = events::emit_to(app, MAIN_WINDOW, SearchReset) {
+            eprintln!("[Window] Failed to emit search reset: {}", e);
+        }
+    }
+}
@@ -0,0 +1,222 @@
+//! Synthetic Data
+//! Deterministic apps, file index rows, browser history and file trees in
+//! bulk, for the benchmarks in `benches/` and tests that need volume. The
+//! same count always produces the same data, so benchmark runs compare.
+
+use crate::db::browser::BrowserEntry;
+use crate::db::files::{upsert_file, FileEntry};
+use crate::models::app::{AppSource, ApplicationEntry};
+use rusqlite::{Connection, Result as SqliteResult};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+const WORDS: &[&str] = &[
+    "alpha", "budget", "calendar", "code", "design", "docs", "editor", "finance", "git", "graph",
+    "image", "invoice", "journal", "keynote", "launch", "mail", "map", "music", "notes", "photo",
+    "plan", "player", "project", "report", "review", "sketch", "studio", "sync", "terminal", "todo",
+    "video", "viewer", "weather", "writer", "报告", "项目",
+];
+
+const EXTENSIONS: &[&str] = &["md", "pdf", "txt", "rs", "png", "jpg", "xlsx", "docx", "json", "mp4"];
+
+const BROWSERS: &[&str] = &["chrome", "firefox", "safari", "edge"];
+
+/// Files per generated directory
+const FILES_PER_DIR: usize = 100;
+
+/// Small xorshift generator; no dependency and stable across platforms
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform-ish in `0..n`
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    pub fn pick<'a>(&mut self, items: &[&'a str]) -> &'a str {
+        items[self.below(items.len())]
+    }
+
+    /// Title-cased name of one to three words
+    fn name(&mut self) -> String {
+        let words = 1 + self.below(3);
+        (0..words)
+            .map(|_| {
+                let word = self.pick(WORDS);
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|c| c.to_uppercase().chain(chars).collect::<String>())
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// `count` applications with word-based names, some with alternate names
+pub fn apps(count: usize) -> Vec<ApplicationEntry> {
+    let mut rng = Rng::new(0xA995);
+    (0..count)
+        .map(|i| {
+            let name = format!("{} {}", rng.name(), i);
+            let bundle = format!("/Applications/{}.app", name);
+            ApplicationEntry {
+                id: format!("app-{}", i),
+                executable_path: format!("{}/Contents/MacOS/{}", bundle, i),
+                app_path: Some(bundle),
+                icon: None,
+                usage_count: rng.below(200) as u32,
+                last_launched: None,
+                platform: "macos".to_string(),
+                alternate_names: (i % 4 == 0).then(|| vec![rng.name()]),
+                bundle_id: Some(format!("com.example.app{}", i)),
+                source: AppSource::Installed,
+                name,
+            }
+        })
+        .collect()
+}
+
+/// `count` file index rows spread over nested project folders
+pub fn file_entries(count: usize) -> Vec<FileEntry> {
+    let mut rng = Rng::new(0xF11E);
+    (0..count)
+        .map(|i| {
+            let filename = format!("{}-{}.{}", rng.pick(WORDS), i, rng.pick(EXTENSIONS));
+            let dir = format!("/home/user/{}/{}", rng.pick(WORDS), rng.pick(WORDS));
+            let modified = 1_700_000_000 + rng.below(30_000_000) as i64;
+            FileEntry {
+                id: None,
+                path: format!("{}/{}", dir, filename),
+                extension: filename.rsplit_once('.').map(|(_, ext)| ext.to_string()),
+                hidden: false,
+                size: rng.below(10_000_000) as i64,
+                modified,
+                indexed: modified,
+                tags: if i % 10 == 0 { vec![rng.pick(WORDS).to_string()] } else { Vec::new() },
+                is_placeholder: false,
+                filename,
+            }
+        })
+        .collect()
+}
+
+/// Insert `count` generated rows into a files database in one transaction
+pub fn seed_files_db(conn: &Connection, count: usize) -> SqliteResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    for entry in file_entries(count) {
+        upsert_file(&tx, &entry)?;
+    }
+    tx.commit()
+}
+
+/// `count` history and bookmark rows. About a fifth repeat an earlier page
+/// with a different scheme or browser, as real multi-browser caches do.
+pub fn browser_entries(count: usize) -> Vec<BrowserEntry> {
+    let mut rng = Rng::new(0xB0B0);
+    (0..count)
+        .map(|i| {
+            let page = if i > 0 && i % 5 == 0 { rng.below(i) } else { i };
+            let scheme = if rng.below(4) == 0 { "http" } else { "https" };
+            let bookmark = rng.below(20) == 0;
+            BrowserEntry {
+                id: None,
+                url: format!("{}://{}.example.com/{}/{}", scheme, WORDS[page % WORDS.len()], page, rng.pick(WORDS)),
+                title: format!("{} {}", rng.name(), page),
+                favicon: None,
+                accent_color: None,
+                browser: rng.pick(BROWSERS).to_string(),
+                entry_type: if bookmark { "bookmark" } else { "history" }.to_string(),
+                visit_count: rng.below(500) as i32,
+                last_visited: Some(1_700_000_000_000 + rng.below(1_000_000_000) as i64),
+                folder: bookmark.then(|| "Bookmarks Bar".to_string()),
+                description: None,
+                host: None,
+                path: None,
+                launcher_open_count: 0,
+                last_opened_from_launcher: None,
+                cached: 0,
+            }
+        })
+        .collect()
+}
+
+/// Insert `count` generated rows into a browser cache database in one
+/// transaction
+pub fn seed_browser_db(conn: &Connection, count: usize) -> SqliteResult<()> {
+    let tx = conn.unchecked_transaction()?;
+    {
+        let mut insert = tx.prepare(
+            "INSERT INTO browser_data (url, title, favicon, browser, type, visitCount, lastVisited, folder, cached)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        )?;
+        for e in browser_entries(count) {
+            insert.execute(rusqlite::params![
+                e.url, e.title, e.favicon, e.browser, e.entry_type, e.visit_count, e.last_visited, e.folder, e.cached
+            ])?;
+        }
+    }
+    tx.commit()
+}
+
+/// Create `count` small files under `root`, 100 per folder, two folder
+/// levels deep, plus a `node_modules` folder the indexer should skip.
+/// Returns the number of files the indexer should find.
+pub fn file_tree(root: &Path, count: usize) -> io::Result<usize> {
+    let mut rng = Rng::new(0x7EE);
+    for i in 0..count {
+        let group = i / FILES_PER_DIR;
+        let dir = root.join(format!("group-{}", group / 10)).join(format!("folder-{}", group));
+        if i % FILES_PER_DIR == 0 {
+            fs::create_dir_all(&dir)?;
+        }
+        fs::write(dir.join(format!("{}-{}.{}", rng.pick(WORDS), i, rng.pick(EXTENSIONS))), b"x")?;
+    }
+
+    let skipped = root.join("node_modules").join("dep");
+    fs::create_dir_all(&skipped)?;
+    fs::write(skipped.join("index.js"), b"x")?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generators_are_deterministic() {
+        let names = |apps: Vec<ApplicationEntry>| apps.into_iter().map(|a| a.name).collect::<Vec<_>>();
+        assert_eq!(names(apps(50)), names(apps(50)));
+        assert_eq!(apps(5000).len(), 5000);
+
+        let paths = |files: Vec<FileEntry>| files.into_iter().map(|f| f.path).collect::<Vec<_>>();
+        assert_eq!(paths(file_entries(50)), paths(file_entries(50)));
+    }
+
+    #[test]
+    fn test_seeded_databases_hold_every_row() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let files = crate::db::files::open_files_db(&dir.path().join("files.db")).unwrap();
+        seed_files_db(&files, 1000).unwrap();
+        assert_eq!(crate::db::files::get_index_stats(&files).unwrap().total_files, 1000);
+
+        let browser = crate::db::browser::open_browser_db(&dir.path().join("browser.db")).unwrap();
+        seed_browser_db(&browser, 1000).unwrap();
+        let rows: i64 = browser.query_row("SELECT COUNT(*) FROM browser_data", [], |r| r.get(0)).unwrap();
+        assert_eq!(rows, 1000);
+    }
+}
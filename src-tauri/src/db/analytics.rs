@@ -0,0 +1,274 @@
+//! Usage Analytics Database Module
+//! Local-only event store behind the usage dashboard. Nothing here is sent
+//! anywhere; the data never leaves this SQLite file.
+
+use rusqlite::{params, Connection, Result as SqliteResult};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::get_analytics_db_path;
+use super::plugin_performance::create_rollup_table;
+use tauri::{AppHandle, Runtime};
+
+/// Number of subjects reported in top-N lists
+const TOP_SUBJECTS: usize = 10;
+
+/// Initialize the analytics database with schema
+pub fn init_analytics_db<R: Runtime>(handle: &AppHandle<R>) -> SqliteResult<Connection> {
+    let db_path = get_analytics_db_path(handle)
+        .map_err(|e| rusqlite::Error::InvalidPath(PathBuf::from(e)))?;
+
+    open_analytics_db(&db_path)
+}
+
+/// Open an analytics database at an explicit path, creating the schema if needed
+pub fn open_analytics_db(db_path: &Path) -> SqliteResult<Connection> {
+    let conn = Connection::open(db_path)?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS events (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            kind TEXT NOT NULL,
+            subject_hash TEXT NOT NULL,
+            timestamp INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_events_kind_time ON events(kind, timestamp)",
+        [],
+    )?;
+
+    // Per-install values such as the salt anonymized subjects are hashed with
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    create_rollup_table(&conn)?;
+
+    Ok(conn)
+}
+
+/// This install's subject salt, created on first use. Purging events
+/// keeps it, so hashes stay comparable across purges.
+pub fn install_salt(conn: &Connection) -> SqliteResult<String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO meta (key, value) VALUES ('subject_salt', ?1)",
+        [uuid::Uuid::new_v4().simple().to_string()],
+    )?;
+    conn.query_row("SELECT value FROM meta WHERE key = 'subject_salt'", [], |row| row.get(0))
+}
+
+/// Insert one event; `subject` is stored as given (already hashed if required)
+pub fn insert_event(conn: &Connection, kind: &str, subject: &str, timestamp: i64) -> SqliteResult<()> {
+    conn.execute(
+        "INSERT INTO events (kind, subject_hash, timestamp) VALUES (?1, ?2, ?3)",
+        params![kind, subject, timestamp],
+    )?;
+    Ok(())
+}
+
+/// Delete events older than `before` (unix seconds) and reclaim the space.
+/// Returns the number of deleted rows.
+pub fn purge_events_before(conn: &Connection, before: i64) -> SqliteResult<usize> {
+    let deleted = conn.execute("DELETE FROM events WHERE timestamp < ?1", [before])?;
+    conn.execute("VACUUM", [])?;
+    Ok(deleted)
+}
+
+/// Aggregate events at or after `since` (unix seconds)
+pub fn summarize_events(conn: &Connection, since: i64) -> SqliteResult<UsageSummary> {
+    let counts_by_kind = conn
+        .prepare("SELECT kind, COUNT(*) FROM events WHERE timestamp >= ?1 GROUP BY kind")?
+        .query_map([since], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))?
+        .collect::<SqliteResult<HashMap<_, _>>>()?;
+
+    let top_subjects = |kind: &str| -> SqliteResult<Vec<SubjectCount>> {
+        conn.prepare(
+            "SELECT subject_hash, COUNT(*) AS n FROM events
+             WHERE kind = ?1 AND timestamp >= ?2
+             GROUP BY subject_hash
+             ORDER BY n DESC, subject_hash ASC
+             LIMIT ?3",
+        )?
+        .query_map(params![kind, since, TOP_SUBJECTS as i64], |row| {
+            Ok(SubjectCount {
+                subject: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
+            })
+        })?
+        .collect()
+    };
+
+    let searches_per_day = conn
+        .prepare(
+            "SELECT date(timestamp, 'unixepoch') AS day, COUNT(*) FROM events
+             WHERE kind = 'search' AND timestamp >= ?1
+             GROUP BY day
+             ORDER BY day ASC",
+        )?
+        .query_map([since], |row| {
+            Ok(DailyCount {
+                date: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
+            })
+        })?
+        .collect::<SqliteResult<Vec<_>>>()?;
+
+    Ok(UsageSummary {
+        total_events: counts_by_kind.values().sum(),
+        counts_by_kind,
+        top_apps: top_subjects("launch")?,
+        top_plugins: top_subjects("plugin")?,
+        searches_per_day,
+    })
+}
+
+/// Per-subject count and last use for one event kind since `since`, most
+/// recently used first
+pub fn subject_usage(conn: &Connection, kind: &str, since: i64, limit: usize) -> SqliteResult<Vec<SubjectUsage>> {
+    conn.prepare(
+        "SELECT subject_hash, COUNT(*) AS n, MAX(timestamp) AS last
+         FROM events
+         WHERE kind = ?1 AND timestamp >= ?2
+         GROUP BY subject_hash
+         ORDER BY last DESC, subject_hash ASC
+         LIMIT ?3",
+    )?
+    .query_map(params![kind, since, limit as i64], |row| {
+        Ok(SubjectUsage {
+            subject: row.get(0)?,
+            count: row.get::<_, i64>(1)? as usize,
+            last_used: row.get(2)?,
+        })
+    })?
+    .collect()
+}
+
+/// Aggregated usage for a period
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageSummary {
+    pub total_events: usize,
+    pub counts_by_kind: HashMap<String, usize>,
+    pub top_apps: Vec<SubjectCount>,
+    pub top_plugins: Vec<SubjectCount>,
+    pub searches_per_day: Vec<DailyCount>,
+}
+
+/// Event count for one subject (hashed when anonymize_usage is on)
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SubjectCount {
+    pub subject: String,
+    pub count: usize,
+}
+
+/// Use count and latest use (unix seconds) of one subject
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SubjectUsage {
+    pub subject: String,
+    pub count: usize,
+    pub last_used: i64,
+}
+
+/// Event count for one UTC day ("YYYY-MM-DD")
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DailyCount {
+    pub date: String,
+    pub count: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 3600;
+    // 2024-01-01T00:00:00Z
+    const JAN_1: i64 = 1_704_067_200;
+
+    fn seeded() -> (tempfile::TempDir, Connection) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_analytics_db(&dir.path().join("usage_analytics.db")).unwrap();
+
+        for (kind, subject, ts) in [
+            ("search", "q1", JAN_1 + 10),
+            ("search", "q2", JAN_1 + 20),
+            ("search", "q3", JAN_1 + DAY + 5),
+            ("launch", "Terminal", JAN_1 + 30),
+            ("launch", "Terminal", JAN_1 + DAY),
+            ("launch", "Safari", JAN_1 + DAY),
+            ("plugin", "calc", JAN_1 + 40),
+            ("launch", "Old", JAN_1 - DAY),
+        ] {
+            insert_event(&conn, kind, subject, ts).unwrap();
+        }
+
+        (dir, conn)
+    }
+
+    #[test]
+    fn test_install_salt_is_kept() {
+        let (dir, conn) = seeded();
+        let salt = install_salt(&conn).unwrap();
+        assert_eq!(salt.len(), 32);
+
+        purge_events_before(&conn, i64::MAX).unwrap();
+        drop(conn);
+        let conn = open_analytics_db(&dir.path().join("usage_analytics.db")).unwrap();
+        assert_eq!(install_salt(&conn).unwrap(), salt);
+    }
+
+    #[test]
+    fn test_summary_math() {
+        let (_dir, conn) = seeded();
+        let summary = summarize_events(&conn, JAN_1).unwrap();
+
+        assert_eq!(summary.total_events, 7);
+        assert_eq!(summary.counts_by_kind["search"], 3);
+        assert_eq!(summary.counts_by_kind["launch"], 3);
+        assert_eq!(summary.counts_by_kind["plugin"], 1);
+        assert_eq!(
+            summary.top_apps,
+            vec![
+                SubjectCount { subject: "Terminal".to_string(), count: 2 },
+                SubjectCount { subject: "Safari".to_string(), count: 1 },
+            ]
+        );
+        assert_eq!(summary.top_plugins[0].subject, "calc");
+        assert_eq!(
+            summary.searches_per_day,
+            vec![
+                DailyCount { date: "2024-01-01".to_string(), count: 2 },
+                DailyCount { date: "2024-01-02".to_string(), count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_subject_usage_most_recent_first() {
+        let (_dir, conn) = seeded();
+
+        assert_eq!(
+            subject_usage(&conn, "launch", JAN_1, 10).unwrap(),
+            vec![
+                SubjectUsage { subject: "Safari".to_string(), count: 1, last_used: JAN_1 + DAY },
+                SubjectUsage { subject: "Terminal".to_string(), count: 2, last_used: JAN_1 + DAY },
+            ]
+        );
+        assert_eq!(subject_usage(&conn, "launch", 0, 1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_purge_before() {
+        let (_dir, conn) = seeded();
+
+        assert_eq!(purge_events_before(&conn, JAN_1 + DAY).unwrap(), 5);
+        let summary = summarize_events(&conn, 0).unwrap();
+        assert_eq!(summary.total_events, 3);
+    }
+}
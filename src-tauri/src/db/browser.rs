@@ -2,12 +2,21 @@
 //! Handles SQLite database for browser bookmarks and history
 #![allow(dead_code)]
 
-use rusqlite::{Connection, Result as SqliteResult};
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Transaction};
 use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
 
 use super::get_browser_db_path;
+use super::migrations::{add_column_if_missing, migrate, Schema};
+use crate::models::pagination::{Page, PageCursor, MAX_PAGE_SIZE};
+use crate::services::favicon_color::favicon_accent_color;
+use crate::utils::{normalize_url, url_parts};
 use tauri::AppHandle;
 
+/// Rows fetched per requested result, since duplicates collapse after the query
+const DEDUP_OVERFETCH: usize = 4;
+
 /// Browser data entry
 #[derive(Debug, Clone, Serialize)]
 pub struct BrowserEntry {
@@ -15,11 +24,25 @@ pub struct BrowserEntry {
     pub url: String,
     pub title: String,
     pub favicon: Option<String>,
+    /// Dominant color of the favicon as "#rrggbb", found when it's cached
+    pub accent_color: Option<String>,
     pub browser: String,
     pub entry_type: String, // "bookmark" or "history"
     pub visit_count: i32,
     pub last_visited: Option<i64>,
     pub folder: Option<String>,
+    /// What the page is about, beyond its title: Firefox's description, or
+    /// for a Chrome bookmark the title the page had when visited
+    pub description: Option<String>,
+    /// Host and decoded path as the cache stores them (see `url_parts`);
+    /// None until the entry is cached, or for a URL without a host
+    pub host: Option<String>,
+    pub path: Option<String>,
+    /// Times the page was opened from the launcher, a stronger sign of what
+    /// the user wants than a browser visit. Cache refreshes leave it alone.
+    pub launcher_open_count: i32,
+    /// When the page was last opened from the launcher (unix seconds)
+    pub last_opened_from_launcher: Option<i64>,
     pub cached: i64,
 }
 
@@ -28,9 +51,24 @@ pub fn init_browser_db(handle: &AppHandle) -> SqliteResult<Connection> {
     let db_path = get_browser_db_path(handle)
         .map_err(|e| rusqlite::Error::InvalidPath(std::path::PathBuf::from(e)))?;
 
-    let conn = Connection::open(&db_path)?;
+    open_browser_db(&db_path)
+}
+
+/// Schema of the browser cache database
+pub const BROWSER_SCHEMA: Schema = Schema {
+    name: "browser_cache.db",
+    migrations: &[browser_baseline, add_url_parts, add_launcher_opens],
+};
 
-    // Create browser_data table
+/// Open a browser cache database at an explicit path, creating or migrating the schema
+pub fn open_browser_db(db_path: &Path) -> SqliteResult<Connection> {
+    let mut conn = Connection::open(db_path)?;
+    migrate(&mut conn, &BROWSER_SCHEMA)?;
+    Ok(conn)
+}
+
+/// Version 1: the schema as it was before versioning
+fn browser_baseline(conn: &Transaction) -> SqliteResult<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS browser_data (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -42,11 +80,15 @@ pub fn init_browser_db(handle: &AppHandle) -> SqliteResult<Connection> {
             visitCount INTEGER DEFAULT 0,
             lastVisited INTEGER,
             folder TEXT,
-            cached INTEGER NOT NULL
+            cached INTEGER NOT NULL,
+            accentColor TEXT
         )",
         [],
     )?;
 
+    // Databases created before favicon colors were stored lack the column
+    add_column_if_missing(conn, "browser_data", "accentColor TEXT")?;
+
     // Create indexes for faster queries
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_url ON browser_data(url)",
@@ -60,91 +102,370 @@ pub fn init_browser_db(handle: &AppHandle) -> SqliteResult<Connection> {
         "CREATE INDEX IF NOT EXISTS idx_browser_type ON browser_data(browser, type)",
         [],
     )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_folder ON browser_data(folder)",
+        [],
+    )?;
 
-    Ok(conn)
+    Ok(())
 }
 
-/// Insert or update a browser entry
+/// Version 2: host and path stored apart from the URL, and descriptions
+fn add_url_parts(conn: &Transaction) -> SqliteResult<()> {
+    conn.execute("ALTER TABLE browser_data ADD COLUMN host TEXT", [])?;
+    conn.execute("ALTER TABLE browser_data ADD COLUMN path TEXT", [])?;
+    conn.execute("ALTER TABLE browser_data ADD COLUMN description TEXT", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_host ON browser_data(host)", [])?;
+
+    let rows: Vec<(i64, String)> = conn
+        .prepare("SELECT id, url FROM browser_data")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<SqliteResult<_>>()?;
+    let mut update = conn.prepare("UPDATE browser_data SET host = ?2, path = ?3 WHERE id = ?1")?;
+    for (id, url) in rows {
+        if let Some(parts) = url_parts(&url) {
+            update.execute(rusqlite::params![id, parts.host, parts.path])?;
+        }
+    }
+    Ok(())
+}
+
+/// Version 3: opens from the launcher, which the browser never records
+fn add_launcher_opens(conn: &Transaction) -> SqliteResult<()> {
+    conn.execute(
+        "ALTER TABLE browser_data ADD COLUMN launcher_open_count INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+    conn.execute("ALTER TABLE browser_data ADD COLUMN last_opened_from_launcher INTEGER", [])?;
+    Ok(())
+}
+
+/// Insert or update a browser entry, matched on its URL, browser, type and
+/// folder so a page's bookmark and history rows stay separate, as do
+/// bookmarks of one page filed in several folders. Launcher opens are the
+/// app's own and survive the update.
 pub fn upsert_browser_entry(conn: &Connection, entry: &BrowserEntry) -> SqliteResult<i64> {
     let favicon_ref: Option<&String> = entry.favicon.as_ref();
     let folder_ref: Option<&String> = entry.folder.as_ref();
+    let accent_color = entry
+        .accent_color
+        .clone()
+        .or_else(|| entry.favicon.as_deref().and_then(favicon_accent_color));
+    let parts = url_parts(&entry.url);
+    let host = parts.as_ref().map(|parts| &parts.host);
+    let path = parts.as_ref().map(|parts| &parts.path);
+    let params = [
+        &entry.url as &dyn rusqlite::ToSql,
+        &entry.title as &dyn rusqlite::ToSql,
+        &favicon_ref as &dyn rusqlite::ToSql,
+        &entry.browser as &dyn rusqlite::ToSql,
+        &entry.entry_type as &dyn rusqlite::ToSql,
+        &entry.visit_count as &dyn rusqlite::ToSql,
+        &entry.last_visited as &dyn rusqlite::ToSql,
+        &folder_ref as &dyn rusqlite::ToSql,
+        &entry.cached as &dyn rusqlite::ToSql,
+        &accent_color as &dyn rusqlite::ToSql,
+        &host as &dyn rusqlite::ToSql,
+        &path as &dyn rusqlite::ToSql,
+        &entry.description as &dyn rusqlite::ToSql,
+    ];
+
+    // There's no unique key to upsert on, so update first and insert if
+    // nothing matched
+    let existing: Option<i64> = conn
+        .query_row(
+            "UPDATE browser_data SET
+                title = ?2,
+                favicon = ?3,
+                visitCount = ?6,
+                lastVisited = ?7,
+                cached = ?9,
+                accentColor = ?10,
+                host = ?11,
+                path = ?12,
+                description = ?13
+             WHERE url = ?1 AND browser = ?4 AND type = ?5 AND folder IS ?8
+             RETURNING id",
+            params,
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
 
     conn.execute(
-        "INSERT INTO browser_data (url, title, favicon, browser, type, visitCount, lastVisited, folder, cached)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
-         ON CONFLICT(url, browser) DO UPDATE SET
-            title = ?2,
-            favicon = ?3,
-            type = ?5,
-            visitCount = ?6,
-            lastVisited = ?7,
-            folder = ?8,
-            cached = ?9",
-        [
-            &entry.url as &dyn rusqlite::ToSql,
-            &entry.title as &dyn rusqlite::ToSql,
-            &favicon_ref as &dyn rusqlite::ToSql,
-            &entry.browser as &dyn rusqlite::ToSql,
-            &entry.entry_type as &dyn rusqlite::ToSql,
-            &entry.visit_count as &dyn rusqlite::ToSql,
-            &entry.last_visited as &dyn rusqlite::ToSql,
-            &folder_ref as &dyn rusqlite::ToSql,
-            &entry.cached as &dyn rusqlite::ToSql,
-        ],
+        "INSERT INTO browser_data
+            (url, title, favicon, browser, type, visitCount, lastVisited, folder, cached, accentColor,
+             host, path, description)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
+        params,
     )?;
 
     Ok(conn.last_insert_rowid())
 }
 
-/// Search browser data by title or URL
+/// A search hit: all entries sharing a normalized URL, merged into one
+#[derive(Debug, Clone, Serialize)]
+pub struct BrowserMatch {
+    /// The bookmark if there is one (its title and folder), else the most
+    /// visited history entry; visits are merged across all entries
+    pub entry: BrowserEntry,
+    /// Entry types that were merged, e.g. ["bookmark", "history"]
+    pub sources: Vec<String>,
+}
+
+/// Merge entries that point at the same page (see `normalize_url`). Input
+/// order decides which history entry represents a group; output is ordered
+/// by merged visit count, then last visit.
+pub fn dedup_entries(entries: Vec<BrowserEntry>) -> Vec<BrowserMatch> {
+    let mut groups: Vec<BrowserMatch> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for entry in entries {
+        let key = normalize_url(&entry.url);
+        let Some(&i) = index.get(&key) else {
+            index.insert(key, groups.len());
+            groups.push(BrowserMatch {
+                sources: vec![entry.entry_type.clone()],
+                entry,
+            });
+            continue;
+        };
+
+        let group = &mut groups[i];
+        if !group.sources.contains(&entry.entry_type) {
+            group.sources.push(entry.entry_type.clone());
+            group.sources.sort();
+        }
+
+        let visit_count = group.entry.visit_count.saturating_add(entry.visit_count);
+        let last_visited = group.entry.last_visited.max(entry.last_visited);
+        let description = group.entry.description.take().or_else(|| entry.description.clone());
+        // Opens are counted on every entry of a page, so they aren't summed
+        let launcher_open_count = group.entry.launcher_open_count.max(entry.launcher_open_count);
+        let last_opened = group.entry.last_opened_from_launcher.max(entry.last_opened_from_launcher);
+        if entry.entry_type == "bookmark" && group.entry.entry_type != "bookmark" {
+            group.entry = entry;
+        }
+        group.entry.visit_count = visit_count;
+        group.entry.last_visited = last_visited;
+        group.entry.description = group.entry.description.take().or(description);
+        group.entry.launcher_open_count = launcher_open_count;
+        group.entry.last_opened_from_launcher = last_opened;
+    }
+
+    groups.sort_by(|a, b| {
+        b.entry
+            .visit_count
+            .cmp(&a.entry.visit_count)
+            .then(b.entry.last_visited.cmp(&a.entry.last_visited))
+    });
+    groups
+}
+
+/// Search browser data by title, URL or description, one result per page.
+/// Without `include_history` (privacy mode) history rows are left out
+/// before merging, so their visits don't count towards a bookmark either.
 pub fn search_browser_data(
     conn: &Connection,
     query: &str,
     limit: usize,
+    include_history: bool,
+) -> SqliteResult<Vec<BrowserMatch>> {
+    let limit = limit.min(MAX_PAGE_SIZE);
+    let entries = search_browser_entries(conn, query, Some(limit * DEDUP_OVERFETCH), include_history)?;
+    let mut matches = dedup_entries(entries);
+    matches.truncate(limit);
+    Ok(matches)
+}
+
+/// Cursor for keyset paging of browser matches: most visited first, then
+/// most recently visited, ties by id
+pub fn browser_cursor(m: &BrowserMatch) -> PageCursor {
+    PageCursor::new(
+        vec![m.entry.visit_count as i64, m.entry.last_visited.unwrap_or(0)],
+        m.entry.id.unwrap_or(0).to_string(),
+    )
+}
+
+/// One page of search results, one per page visited, starting after
+/// `after`. Duplicates merge across all matches rather than within a page,
+/// so a page visited from several entries is never split over two pages;
+/// that means every match is read and the page is cut from the merged list.
+/// `include_history` is as for `search_browser_data`.
+pub fn search_browser_page(
+    conn: &Connection,
+    query: &str,
+    after: Option<&PageCursor>,
+    limit: usize,
+    include_history: bool,
+) -> SqliteResult<Page<BrowserMatch>> {
+    let mut matches = dedup_entries(search_browser_entries(conn, query, None, include_history)?);
+    matches.sort_by_cached_key(|m| std::cmp::Reverse(browser_cursor(m)));
+    Ok(Page::from_sorted(matches, after, limit.min(MAX_PAGE_SIZE), browser_cursor))
+}
+
+/// Raw rows matching a title, URL or description query, duplicates
+/// included. No limit reads every match.
+fn search_browser_entries(
+    conn: &Connection,
+    query: &str,
+    limit: Option<usize>,
+    include_history: bool,
 ) -> SqliteResult<Vec<BrowserEntry>> {
     let pattern = format!("%{}%", query);
-    let limit_i64 = limit as i64;
+    // SQLite reads a negative LIMIT as none
+    let limit_i64 = limit.map_or(-1, |limit| i64::try_from(limit).unwrap_or(i64::MAX));
 
     conn.prepare(
-        "SELECT id, url, title, favicon, browser, type, visitCount, lastVisited, folder, cached
+        "SELECT id, url, title, favicon, browser, type, visitCount, lastVisited, folder, cached, accentColor,
+                description, launcher_open_count, last_opened_from_launcher, host, path
          FROM browser_data
-         WHERE title LIKE ?1 OR url LIKE ?1
-         ORDER BY visitCount DESC, lastVisited DESC
+         WHERE (title LIKE ?1 OR url LIKE ?1 OR path LIKE ?1 OR description LIKE ?1)
+           AND (?3 OR type != 'history')
+         ORDER BY launcher_open_count DESC, visitCount DESC, lastVisited DESC, id DESC
          LIMIT ?2"
     )?
-    .query_map([&pattern as &dyn rusqlite::ToSql, &limit_i64 as &dyn rusqlite::ToSql], |row| {
-        Ok(BrowserEntry {
-            id: Some(row.get(0)?),
-            url: row.get(1)?,
-            title: row.get(2)?,
-            favicon: row.get(3)?,
-            browser: row.get(4)?,
-            entry_type: row.get(5)?,
-            visit_count: row.get(6)?,
-            last_visited: row.get(7)?,
-            folder: row.get(8)?,
-            cached: row.get(9)?,
-        })
-    })?
+    .query_map(
+        [&pattern as &dyn rusqlite::ToSql, &limit_i64 as &dyn rusqlite::ToSql, &include_history as &dyn rusqlite::ToSql],
+        entry_from_row,
+    )?
+    .collect()
+}
+
+/// A full `browser_data` row, selected in column order
+fn entry_from_row(row: &rusqlite::Row) -> SqliteResult<BrowserEntry> {
+    Ok(BrowserEntry {
+        id: Some(row.get(0)?),
+        url: row.get(1)?,
+        title: row.get(2)?,
+        favicon: row.get(3)?,
+        accent_color: row.get(10)?,
+        browser: row.get(4)?,
+        entry_type: row.get(5)?,
+        visit_count: row.get(6)?,
+        last_visited: row.get(7)?,
+        folder: row.get(8)?,
+        description: row.get(11)?,
+        host: row.get(14)?,
+        path: row.get(15)?,
+        launcher_open_count: row.get(12)?,
+        last_opened_from_launcher: row.get(13)?,
+        cached: row.get(9)?,
+    })
+}
+
+/// Count an open from the launcher of the page at `url`, on every entry of
+/// the page (see `normalize_url`) since search merges them. Returns the
+/// entries counted, none for a page the cache doesn't have.
+pub fn record_launcher_open(conn: &Connection, url: &str, now: i64) -> SqliteResult<usize> {
+    let key = normalize_url(url);
+    let host = url_parts(url).map(|parts| parts.host);
+
+    // Narrowed down by host, then compared normalized
+    let candidates: Vec<(i64, String)> = conn
+        .prepare("SELECT id, url FROM browser_data WHERE host IS ?1 OR url = ?2")?
+        .query_map(rusqlite::params![host, url], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<SqliteResult<_>>()?;
+    let mut update = conn.prepare(
+        "UPDATE browser_data
+         SET launcher_open_count = launcher_open_count + 1, last_opened_from_launcher = ?2
+         WHERE id = ?1",
+    )?;
+    let mut counted = 0;
+    for (id, _) in candidates.iter().filter(|(_, candidate)| normalize_url(candidate) == key) {
+        counted += update.execute(rusqlite::params![id, now])?;
+    }
+    Ok(counted)
+}
+
+/// Drop entries last cached before `cached_before`. Entries opened from the
+/// launcher stay, since their counts exist nowhere else. Returns the
+/// entries dropped.
+pub fn expire_browser_entries(conn: &Connection, cached_before: i64) -> SqliteResult<usize> {
+    conn.execute(
+        "DELETE FROM browser_data WHERE cached < ?1 AND launcher_open_count = 0",
+        [cached_before],
+    )
+}
+
+/// Pages opened from the launcher, one per page, most opened first
+pub fn most_opened_from_launcher(conn: &Connection, limit: usize) -> SqliteResult<Vec<BrowserEntry>> {
+    let entries = conn
+        .prepare(
+            "SELECT id, url, title, favicon, browser, type, visitCount, lastVisited, folder, cached, accentColor,
+                    description, launcher_open_count, last_opened_from_launcher, host, path
+             FROM browser_data
+             WHERE launcher_open_count > 0",
+        )?
+        .query_map([], entry_from_row)?
+        .collect::<SqliteResult<Vec<_>>>()?;
+
+    let mut pages: Vec<BrowserEntry> = dedup_entries(entries).into_iter().map(|m| m.entry).collect();
+    pages.sort_by(|a, b| {
+        b.launcher_open_count
+            .cmp(&a.launcher_open_count)
+            .then(b.last_opened_from_launcher.cmp(&a.last_opened_from_launcher))
+    });
+    pages.truncate(limit);
+    Ok(pages)
+}
+
+/// Bookmark count per folder path, for `build_folder_tree`
+pub fn bookmark_folder_counts(conn: &Connection) -> SqliteResult<Vec<(String, usize)>> {
+    conn.prepare(
+        "SELECT folder, COUNT(*) FROM browser_data
+         WHERE type = 'bookmark' AND folder IS NOT NULL
+         GROUP BY folder"
+    )?
+    .query_map([], |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as usize)))?
+    .collect()
+}
+
+/// Bookmarks in `folder`, and in its subfolders when `recursive`, ordered
+/// by folder then title
+pub fn bookmarks_in_folder(conn: &Connection, folder: &str, recursive: bool) -> SqliteResult<Vec<BrowserEntry>> {
+    // Compared by prefix rather than LIKE, so `%` and `_` in names match
+    // only themselves
+    conn.prepare(
+        "SELECT id, url, title, favicon, browser, type, visitCount, lastVisited, folder, cached, accentColor,
+                description, launcher_open_count, last_opened_from_launcher, host, path
+         FROM browser_data
+         WHERE type = 'bookmark'
+           AND (folder = ?1 OR (?2 AND substr(folder, 1, length(?1) + 1) = ?1 || '/'))
+         ORDER BY folder, title COLLATE NOCASE, id"
+    )?
+    .query_map([&folder as &dyn rusqlite::ToSql, &recursive as &dyn rusqlite::ToSql], entry_from_row)?
     .collect()
 }
 
 /// Get browser cache statistics
 pub fn get_cache_stats(conn: &Connection) -> SqliteResult<BrowserCacheStats> {
-    let bookmarks: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM browser_data WHERE type = 'bookmark'",
-        [],
-        |row| row.get(0),
-    )?;
-    let history: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM browser_data WHERE type = 'history'",
+    let count_by = |column: &str| -> SqliteResult<HashMap<String, usize>> {
+        conn.prepare(&format!(
+            "SELECT {column}, COUNT(*) FROM browser_data GROUP BY {column}"
+        ))?
+        .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize)))?
+        .collect()
+    };
+
+    let by_type = count_by("type")?;
+    let by_browser = count_by("browser")?;
+
+    let (oldest_cached, newest_cached): (Option<i64>, Option<i64>) = conn.query_row(
+        "SELECT MIN(cached), MAX(cached) FROM browser_data",
         [],
-        |row| row.get(0),
+        |row| Ok((row.get(0)?, row.get(1)?)),
     )?;
 
     Ok(BrowserCacheStats {
-        bookmarks: bookmarks as usize,
-        history: history as usize,
+        bookmarks: by_type.get("bookmark").copied().unwrap_or(0),
+        history: by_type.get("history").copied().unwrap_or(0),
+        by_type,
+        by_browser,
+        oldest_cached,
+        newest_cached,
     })
 }
 
@@ -153,4 +474,283 @@ pub fn get_cache_stats(conn: &Connection) -> SqliteResult<BrowserCacheStats> {
 pub struct BrowserCacheStats {
     pub bookmarks: usize,
     pub history: usize,
+    /// Entry count per entry type
+    pub by_type: HashMap<String, usize>,
+    /// Entry count per browser
+    pub by_browser: HashMap<String, usize>,
+    /// Oldest and newest `cached` timestamps (unix seconds)
+    pub oldest_cached: Option<i64>,
+    pub newest_cached: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(url: &str, browser: &str, entry_type: &str, cached: i64) -> BrowserEntry {
+        BrowserEntry {
+            id: None,
+            url: url.to_string(),
+            title: url.to_string(),
+            favicon: None,
+            accent_color: None,
+            browser: browser.to_string(),
+            entry_type: entry_type.to_string(),
+            visit_count: 0,
+            last_visited: None,
+            folder: None,
+            description: None,
+            host: None,
+            path: None,
+            launcher_open_count: 0,
+            last_opened_from_launcher: None,
+            cached,
+        }
+    }
+
+    fn visited(url: &str, entry_type: &str, title: &str, visit_count: i32, last_visited: i64) -> BrowserEntry {
+        BrowserEntry {
+            title: title.to_string(),
+            visit_count,
+            last_visited: Some(last_visited),
+            ..entry(url, "chrome", entry_type, 0)
+        }
+    }
+
+    #[test]
+    fn test_dedup_prefers_bookmark_and_merges_visits() {
+        let matches = dedup_entries(vec![
+            visited("https://docs.rs/serde?utm_source=feed", "history", "serde - Docs.rs", 30, 200),
+            visited("https://docs.rs/serde/", "history", "serde", 12, 500),
+            BrowserEntry {
+                folder: Some("Rust".to_string()),
+                ..visited("https://docs.rs/serde", "bookmark", "Serde docs", 0, 100)
+            },
+            visited("https://docs.rs/tokio", "history", "tokio", 3, 50),
+        ]);
+
+        assert_eq!(matches.len(), 2);
+        let serde = &matches[0];
+        assert_eq!(serde.entry.title, "Serde docs");
+        assert_eq!(serde.entry.folder.as_deref(), Some("Rust"));
+        assert_eq!(serde.entry.url, "https://docs.rs/serde");
+        assert_eq!(serde.entry.visit_count, 42);
+        assert_eq!(serde.entry.last_visited, Some(500));
+        assert_eq!(serde.sources, vec!["bookmark", "history"]);
+        assert_eq!(matches[1].sources, vec!["history"]);
+    }
+
+    #[test]
+    fn test_search_returns_one_row_per_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_browser_db(&dir.path().join("browser_cache.db")).unwrap();
+        for (url, entry_type, visits) in [
+            ("https://example.com/guide#intro", "history", 5),
+            ("https://example.com/guide", "bookmark", 0),
+            ("https://example.com/guide?page=2", "history", 2),
+        ] {
+            conn.execute(
+                "INSERT INTO browser_data (url, title, browser, type, visitCount, cached) VALUES (?1, 'Guide', 'chrome', ?2, ?3, 0)",
+                rusqlite::params![url, entry_type, visits],
+            )
+            .unwrap();
+        }
+
+        let matches = search_browser_data(&conn, "guide", 10, true).unwrap();
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].entry.entry_type, "bookmark");
+        assert_eq!(matches[0].entry.visit_count, 5);
+        assert_eq!(matches[1].entry.url, "https://example.com/guide?page=2");
+
+        assert_eq!(search_browser_data(&conn, "guide", 1, true).unwrap().len(), 1);
+
+        // Privacy mode: the bookmark alone, without the history's visits
+        let matches = search_browser_data(&conn, "guide", 10, false).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entry.visit_count, 0);
+        assert_eq!(matches[0].sources, vec!["bookmark"]);
+    }
+
+    #[test]
+    fn test_paging_while_history_is_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("browser_cache.db");
+        let conn = open_browser_db(&db_path).unwrap();
+        let insert = |conn: &Connection, url: String, visits: i64, last_visited: i64| {
+            conn.execute(
+                "INSERT INTO browser_data (url, title, browser, type, visitCount, lastVisited, cached) VALUES (?1, 'Page', 'chrome', 'history', ?2, ?3, 0)",
+                rusqlite::params![url, visits, last_visited],
+            )
+            .unwrap();
+        };
+        // Repeated visit counts and visit times, so ties are split by id
+        for i in 0..500 {
+            insert(&conn, format!("https://seed.test/{}", i), i % 7, i % 3);
+        }
+
+        let writer = {
+            let db_path = db_path.clone();
+            std::thread::spawn(move || {
+                let conn = open_browser_db(&db_path).unwrap();
+                conn.busy_timeout(std::time::Duration::from_secs(5)).unwrap();
+                for i in 0..200 {
+                    insert(&conn, format!("https://new.test/{}", i), 100 + i, 0);
+                }
+            })
+        };
+
+        conn.busy_timeout(std::time::Duration::from_secs(5)).unwrap();
+        let mut seen = Vec::new();
+        let mut cursor: Option<PageCursor> = None;
+        loop {
+            let page = search_browser_page(&conn, "test", cursor.as_ref(), 37, true).unwrap();
+            seen.extend(page.items.into_iter().map(|m| m.entry.url));
+            let Some(next) = page.next_cursor else { break };
+            cursor = Some(PageCursor::decode(&next).unwrap());
+        }
+        writer.join().unwrap();
+
+        let unique: std::collections::HashSet<&String> = seen.iter().collect();
+        assert_eq!(unique.len(), seen.len(), "a page was repeated");
+        for i in 0..500 {
+            assert!(unique.contains(&format!("https://seed.test/{}", i)), "seed {} was skipped", i);
+        }
+    }
+
+    #[test]
+    fn test_upsert_updates_the_matching_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_browser_db(&dir.path().join("browser_cache.db")).unwrap();
+        let bookmark = BrowserEntry {
+            folder: Some("Bookmarks Bar".to_string()),
+            ..entry("https://a.test", "chrome", "bookmark", 10)
+        };
+        let id = upsert_browser_entry(&conn, &bookmark).unwrap();
+        upsert_browser_entry(&conn, &entry("https://a.test", "chrome", "history", 10)).unwrap();
+
+        let renamed = BrowserEntry {
+            title: "Renamed".to_string(),
+            ..bookmark.clone()
+        };
+        assert_eq!(upsert_browser_entry(&conn, &renamed).unwrap(), id);
+
+        // The same page filed in a second folder is a bookmark of its own
+        let filed_again = BrowserEntry {
+            folder: Some("Bookmarks Bar/Morning".to_string()),
+            ..bookmark
+        };
+        assert_ne!(upsert_browser_entry(&conn, &filed_again).unwrap(), id);
+
+        let rows: i64 = conn.query_row("SELECT COUNT(*) FROM browser_data", [], |r| r.get(0)).unwrap();
+        assert_eq!(rows, 3);
+        let title: String = conn
+            .query_row("SELECT title FROM browser_data WHERE id = ?1", [id], |r| r.get(0))
+            .unwrap();
+        assert_eq!(title, "Renamed");
+    }
+
+    #[test]
+    fn test_search_reads_stored_host_and_decoded_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_browser_db(&dir.path().join("browser_cache.db")).unwrap();
+        let tokyo = entry("https://ja.wikipedia.org/wiki/%E6%9D%B1%E4%BA%AC", "chrome", "history", 0);
+        upsert_browser_entry(&conn, &tokyo).unwrap();
+
+        // Found by the decoded path, which the URL only has percent-encoded
+        let matches = search_browser_data(&conn, "東京", 10, true).unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].entry.host.as_deref(), Some("ja.wikipedia.org"));
+        assert_eq!(matches[0].entry.path.as_deref(), Some("/wiki/東京"));
+    }
+
+    #[test]
+    fn test_launcher_opens_survive_refreshes() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_browser_db(&dir.path().join("browser_cache.db")).unwrap();
+        let history = visited("https://docs.rs/serde/", "history", "serde", 12, 500);
+        upsert_browser_entry(&conn, &history).unwrap();
+        upsert_browser_entry(&conn, &visited("https://docs.rs/serde", "bookmark", "Serde docs", 0, 100)).unwrap();
+        upsert_browser_entry(&conn, &visited("https://docs.rs/tokio", "history", "tokio", 40, 600)).unwrap();
+
+        // Counted on the bookmark and the history entry of the page
+        assert_eq!(record_launcher_open(&conn, "https://docs.rs/serde?utm_source=launcher", 1_000).unwrap(), 2);
+        assert_eq!(record_launcher_open(&conn, "https://docs.rs/serde", 2_000).unwrap(), 2);
+        assert_eq!(record_launcher_open(&conn, "https://unknown.test/", 2_000).unwrap(), 0);
+
+        // A refresh brings new visits and titles but keeps the opens
+        let refreshed = BrowserEntry {
+            title: "serde - Rust".to_string(),
+            visit_count: 15,
+            ..history
+        };
+        upsert_browser_entry(&conn, &refreshed).unwrap();
+        let (count, last_opened, visits): (i32, Option<i64>, i32) = conn
+            .query_row(
+                "SELECT launcher_open_count, last_opened_from_launcher, visitCount FROM browser_data
+                 WHERE url = 'https://docs.rs/serde/'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((count, last_opened, visits), (2, Some(2_000), 15));
+
+        // One page, not summed over its entries, and the merged search row agrees
+        let opened = most_opened_from_launcher(&conn, 10).unwrap();
+        assert_eq!(opened.len(), 1);
+        assert_eq!((opened[0].title.as_str(), opened[0].launcher_open_count), ("Serde docs", 2));
+        let found = search_browser_data(&conn, "serde", 10, true).unwrap();
+        assert_eq!(found[0].entry.launcher_open_count, 2);
+    }
+
+    #[test]
+    fn test_expiry_keeps_launcher_opens() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_browser_db(&dir.path().join("browser_cache.db")).unwrap();
+        upsert_browser_entry(&conn, &entry("https://docs.rs/serde", "chrome", "history", 100)).unwrap();
+        upsert_browser_entry(&conn, &entry("https://docs.rs/tokio", "chrome", "history", 100)).unwrap();
+        upsert_browser_entry(&conn, &entry("https://crates.io", "chrome", "history", 900)).unwrap();
+        record_launcher_open(&conn, "https://docs.rs/serde", 150).unwrap();
+
+        assert_eq!(expire_browser_entries(&conn, 500).unwrap(), 1);
+        let urls: Vec<String> = conn
+            .prepare("SELECT url FROM browser_data ORDER BY url")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<SqliteResult<_>>()
+            .unwrap();
+        assert_eq!(urls, vec!["https://crates.io", "https://docs.rs/serde"]);
+        assert_eq!(most_opened_from_launcher(&conn, 10).unwrap()[0].launcher_open_count, 1);
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_browser_db(&dir.path().join("browser_cache.db")).unwrap();
+
+        let empty = get_cache_stats(&conn).unwrap();
+        assert_eq!(empty.bookmarks + empty.history, 0);
+        assert_eq!(empty.oldest_cached, None);
+
+        for e in [
+            entry("https://a.test", "chrome", "bookmark", 50),
+            entry("https://b.test", "chrome", "history", 10),
+            entry("https://c.test", "firefox", "history", 90),
+        ] {
+            conn.execute(
+                "INSERT INTO browser_data (url, title, browser, type, cached) VALUES (?1, ?2, ?3, ?4, ?5)",
+                rusqlite::params![e.url, e.title, e.browser, e.entry_type, e.cached],
+            )
+            .unwrap();
+        }
+
+        let stats = get_cache_stats(&conn).unwrap();
+        assert_eq!(stats.bookmarks, 1);
+        assert_eq!(stats.history, 2);
+        assert_eq!(stats.by_browser["chrome"], 2);
+        assert_eq!(stats.by_browser["firefox"], 1);
+        assert_eq!(stats.by_type["history"], 2);
+        assert_eq!(stats.oldest_cached, Some(10));
+        assert_eq!(stats.newest_cached, Some(90));
+    }
 }
@@ -0,0 +1,500 @@
+//! Clipboard History Database Module
+//! SQLite index of clipboard history for searching and paging it, kept in
+//! line with the item files the history is stored in
+
+use regex::Regex;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection, Result as SqliteResult, Row, Transaction};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::db::escape_like;
+use crate::db::migrations::{migrate, Schema};
+use crate::models::clipboard::{ClipboardContentType, ClipboardItem};
+use crate::models::pagination::{Page, PageCursor, MAX_PAGE_SIZE};
+
+/// How long a reader waits for the watcher to finish an insert
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Newest rows a regex search looks through. Regexes run in memory, so
+/// older items aren't searched by pattern.
+pub const MAX_REGEX_SCAN: usize = 5_000;
+
+/// Schema of the clipboard history database
+pub const CLIPBOARD_SCHEMA: Schema = Schema {
+    name: "clipboard/history.db",
+    migrations: &[clipboard_baseline, add_source_and_pins],
+};
+
+/// Open a clipboard history database, creating or migrating the schema
+pub fn open_clipboard_db(db_path: &Path) -> SqliteResult<Connection> {
+    let mut conn = Connection::open(db_path)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    migrate(&mut conn, &CLIPBOARD_SCHEMA)?;
+    Ok(conn)
+}
+
+/// Version 1: the schema as it was before versioning
+fn clipboard_baseline(conn: &Transaction) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS clipboard_history (
+            id TEXT PRIMARY KEY,
+            content_type TEXT NOT NULL,
+            text TEXT,
+            timestamp INTEGER NOT NULL,
+            is_sensitive BOOLEAN DEFAULT 0
+        )",
+        [],
+    )?;
+
+    // Pages are read newest first
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_timestamp ON clipboard_history(timestamp DESC, id DESC)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Version 2: the app an item was copied from, and pins
+fn add_source_and_pins(conn: &Transaction) -> SqliteResult<()> {
+    conn.execute("ALTER TABLE clipboard_history ADD COLUMN app_source TEXT", [])?;
+    conn.execute("ALTER TABLE clipboard_history ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT 0", [])?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_clipboard_type ON clipboard_history(content_type, timestamp DESC)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// Insert a clipboard item, replacing an item with the same id. A replaced
+/// item keeps its pin.
+pub fn insert_clipboard_item(conn: &Connection, item: &ClipboardItem) -> SqliteResult<()> {
+    conn.execute(
+        "INSERT INTO clipboard_history (id, content_type, text, timestamp, is_sensitive, app_source)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+         ON CONFLICT(id) DO UPDATE SET
+            content_type = excluded.content_type,
+            text = excluded.text,
+            timestamp = excluded.timestamp,
+            is_sensitive = excluded.is_sensitive,
+            app_source = excluded.app_source",
+        params![
+            item.id,
+            format!("{:?}", item.content_type),
+            item.text,
+            item.timestamp,
+            item.is_sensitive,
+            item.app_source,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Make the table hold exactly `items`, the history as its item files
+/// have it: new items are added, changed ones updated and deleted ones
+/// dropped. Items still there keep their pins.
+pub fn sync_clipboard_items(conn: &mut Connection, items: &[ClipboardItem]) -> SqliteResult<()> {
+    let tx = conn.transaction()?;
+    tx.execute("CREATE TEMP TABLE IF NOT EXISTS synced_ids (id TEXT PRIMARY KEY)", [])?;
+    tx.execute("DELETE FROM synced_ids", [])?;
+    {
+        let mut synced = tx.prepare("INSERT OR IGNORE INTO synced_ids (id) VALUES (?1)")?;
+        for item in items {
+            synced.execute([&item.id])?;
+            insert_clipboard_item(&tx, item)?;
+        }
+    }
+    tx.execute("DELETE FROM clipboard_history WHERE id NOT IN (SELECT id FROM synced_ids)", [])?;
+    tx.commit()
+}
+
+/// Pin or unpin an item. Returns whether the item exists.
+pub fn set_clipboard_pinned(conn: &Connection, id: &str, pinned: bool) -> SqliteResult<bool> {
+    let changed = conn.execute(
+        "UPDATE clipboard_history SET pinned = ?2 WHERE id = ?1",
+        params![id, pinned],
+    )?;
+    Ok(changed > 0)
+}
+
+/// Cursor for keyset paging of clipboard items: newest first, ties by id
+pub fn clipboard_cursor(item: &ClipboardItem) -> PageCursor {
+    PageCursor::new(vec![item.timestamp], item.id.clone())
+}
+
+/// What a clipboard search matches. Every condition set must hold.
+#[derive(Debug, Default)]
+pub struct ClipboardFilter {
+    /// Substring of the item's text, ignoring ASCII case. Empty matches
+    /// every item, including images, which have no text.
+    pub text: String,
+    /// Matched against the text in place of `text`. SQLite has no regex
+    /// support here, so it runs on the rows the other conditions select.
+    pub pattern: Option<Regex>,
+    /// Every type listed; two different types match nothing
+    pub content_types: Vec<ClipboardContentType>,
+    /// Substrings of the app the item was copied from
+    pub app_sources: Vec<String>,
+    /// Copied at or after (unix seconds)
+    pub since: Option<i64>,
+    /// Copied before (unix seconds)
+    pub until: Option<i64>,
+    pub sensitive_only: bool,
+    pub pinned_only: bool,
+    /// Leave out sensitive items, as privacy mode does
+    pub hide_sensitive: bool,
+}
+
+impl ClipboardFilter {
+    /// The conditions as a SQL expression and its parameters
+    fn predicates(&self) -> (String, Vec<Value>) {
+        let mut clauses = vec!["1 = 1".to_string()];
+        let mut values = Vec::new();
+        let mut push = |clause: &str, value: Value| {
+            values.push(value);
+            clauses.push(clause.replace('?', &format!("?{}", values.len())));
+        };
+
+        if self.pattern.is_none() && !self.text.is_empty() {
+            push("text LIKE ? ESCAPE '\\'", Value::Text(format!("%{}%", escape_like(&self.text))));
+        }
+        for content_type in &self.content_types {
+            push("content_type = ?", Value::Text(format!("{:?}", content_type)));
+        }
+        for app in &self.app_sources {
+            push("app_source LIKE ? ESCAPE '\\'", Value::Text(format!("%{}%", escape_like(app))));
+        }
+        if let Some(since) = self.since {
+            push("timestamp >= ?", Value::Integer(since));
+        }
+        if let Some(until) = self.until {
+            push("timestamp < ?", Value::Integer(until));
+        }
+        if self.sensitive_only {
+            clauses.push("is_sensitive = 1".to_string());
+        }
+        if self.hide_sensitive {
+            clauses.push("(is_sensitive = 0 OR is_sensitive IS NULL)".to_string());
+        }
+        if self.pinned_only {
+            clauses.push("pinned = 1".to_string());
+        }
+
+        (clauses.join(" AND "), values)
+    }
+}
+
+/// A clipboard item found by a search
+#[derive(Debug, Clone)]
+pub struct ClipboardRow {
+    pub item: ClipboardItem,
+    pub pinned: bool,
+}
+
+const ROW_COLUMNS: &str = "id, content_type, text, timestamp, is_sensitive, app_source, pinned";
+
+fn read_row(row: &Row) -> SqliteResult<ClipboardRow> {
+    let content_type: String = row.get(1)?;
+    Ok(ClipboardRow {
+        item: ClipboardItem {
+            id: row.get(0)?,
+            content_type: match content_type.as_str() {
+                "Image" => ClipboardContentType::Image,
+                "Html" => ClipboardContentType::Html,
+                "File" => ClipboardContentType::File,
+                _ => ClipboardContentType::Text,
+            },
+            text: row.get(2)?,
+            image_path: None,
+            thumbnail_path: None,
+            hash: String::new(),
+            perceptual_hash: None,
+            timestamp: row.get(3)?,
+            is_sensitive: row.get::<_, Option<bool>>(4)?.unwrap_or(false),
+            app_source: row.get(5)?,
+        },
+        pinned: row.get(6)?,
+    })
+}
+
+fn row_cursor(row: &ClipboardRow) -> PageCursor {
+    clipboard_cursor(&row.item)
+}
+
+/// One page of items matching `filter`, newest first, starting after
+/// `after`. Items copied while the UI pages land ahead of the cursor, so
+/// they don't shift later pages.
+pub fn search_clipboard_page(
+    conn: &Connection,
+    filter: &ClipboardFilter,
+    after: Option<&PageCursor>,
+    limit: usize,
+) -> SqliteResult<Page<ClipboardRow>> {
+    let limit = limit.min(MAX_PAGE_SIZE);
+    let (predicates, mut values) = filter.predicates();
+
+    if let Some(pattern) = &filter.pattern {
+        // The total needs every match anyway, so page in memory, over the
+        // newest `MAX_REGEX_SCAN` rows
+        values.push(Value::Integer(MAX_REGEX_SCAN as i64));
+        let rows: Vec<ClipboardRow> = conn
+            .prepare(&format!(
+                "SELECT {} FROM clipboard_history WHERE {} ORDER BY timestamp DESC, id DESC LIMIT ?{}",
+                ROW_COLUMNS,
+                predicates,
+                values.len()
+            ))?
+            .query_map(params_from_iter(values), read_row)?
+            .filter(|row| {
+                row.as_ref()
+                    .map_or(true, |row| row.item.text.as_deref().is_some_and(|text| pattern.is_match(text)))
+            })
+            .collect::<SqliteResult<_>>()?;
+        return Ok(Page::from_sorted(rows, after, limit, row_cursor));
+    }
+
+    let total: i64 = conn.query_row(
+        &format!("SELECT COUNT(*) FROM clipboard_history WHERE {}", predicates),
+        params_from_iter(values.iter()),
+        |row| row.get(0),
+    )?;
+
+    let mut sql = format!("SELECT {} FROM clipboard_history WHERE {}", ROW_COLUMNS, predicates);
+    if let Some(cursor) = after {
+        values.push(Value::Integer(cursor.key.first().copied().unwrap_or(i64::MAX)));
+        values.push(Value::Text(cursor.id.clone()));
+        sql.push_str(&format!(" AND (timestamp, id) < (?{}, ?{})", values.len() - 1, values.len()));
+    }
+    // One row past the limit says whether there is a next page
+    values.push(Value::Integer(limit as i64 + 1));
+    sql.push_str(&format!(" ORDER BY timestamp DESC, id DESC LIMIT ?{}", values.len()));
+
+    let mut rows: Vec<ClipboardRow> = conn
+        .prepare(&sql)?
+        .query_map(params_from_iter(values), read_row)?
+        .collect::<SqliteResult<_>>()?;
+
+    let has_more = rows.len() > limit;
+    rows.truncate(limit);
+    Ok(Page::new(rows, has_more, total as usize, row_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::thread;
+
+    fn item(id: &str, text: &str, timestamp: i64, is_sensitive: bool) -> ClipboardItem {
+        ClipboardItem {
+            id: id.to_string(),
+            content_type: ClipboardContentType::Text,
+            text: Some(text.to_string()),
+            image_path: None,
+            thumbnail_path: None,
+            hash: String::new(),
+            perceptual_hash: None,
+            timestamp,
+            is_sensitive,
+            app_source: None,
+        }
+    }
+
+    fn text(query: &str) -> ClipboardFilter {
+        ClipboardFilter { text: query.to_string(), ..Default::default() }
+    }
+
+    fn ids(page: &Page<ClipboardRow>) -> Vec<&str> {
+        page.items.iter().map(|r| r.item.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_search_filters_and_hides_sensitive() {
+        let conn = open_clipboard_db(Path::new(":memory:")).unwrap();
+        insert_clipboard_item(&conn, &item("a", "hello world", 1, false)).unwrap();
+        insert_clipboard_item(&conn, &item("b", "hello secret", 2, true)).unwrap();
+        insert_clipboard_item(&conn, &item("c", "goodbye", 3, false)).unwrap();
+
+        let page = search_clipboard_page(&conn, &text("hello"), None, 10).unwrap();
+        let ids: Vec<&str> = page.items.iter().map(|r| r.item.id.as_str()).collect();
+        assert_eq!(ids, vec!["b", "a"]);
+        assert_eq!(page.total, 2);
+        assert_eq!(page.next_cursor, None);
+
+        let hidden = ClipboardFilter { hide_sensitive: true, ..text("hello") };
+        let page = search_clipboard_page(&conn, &hidden, None, 10).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.total, 1);
+    }
+
+    #[test]
+    fn test_paging_while_items_are_copied() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("history.db");
+        let conn = open_clipboard_db(&db_path).unwrap();
+        // Seeded in pairs sharing a timestamp, so ties are split by id
+        for i in 0..500 {
+            insert_clipboard_item(&conn, &item(&format!("seed-{:03}", i), "copied text", i / 2, false)).unwrap();
+        }
+
+        let writer = {
+            let db_path = db_path.clone();
+            thread::spawn(move || {
+                let conn = open_clipboard_db(&db_path).unwrap();
+                for i in 0..200 {
+                    insert_clipboard_item(&conn, &item(&format!("new-{:03}", i), "copied text", 1000 + i, false))
+                        .unwrap();
+                }
+            })
+        };
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<PageCursor> = None;
+        loop {
+            let page = search_clipboard_page(&conn, &text("copied"), cursor.as_ref(), 37).unwrap();
+            seen.extend(page.items.into_iter().map(|r| r.item.id));
+            let Some(next) = page.next_cursor else { break };
+            cursor = Some(PageCursor::decode(&next).unwrap());
+        }
+        writer.join().unwrap();
+
+        let unique: HashSet<&String> = seen.iter().collect();
+        assert_eq!(unique.len(), seen.len(), "an item was repeated across pages");
+        for i in 0..500 {
+            assert!(unique.contains(&format!("seed-{:03}", i)), "seed-{:03} was skipped", i);
+        }
+    }
+
+    #[test]
+    fn test_page_size_is_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_clipboard_db(&dir.path().join("history.db")).unwrap();
+        for i in 0..(MAX_PAGE_SIZE as i64 + 5) {
+            insert_clipboard_item(&conn, &item(&format!("item-{:04}", i), "copied text", i, false)).unwrap();
+        }
+
+        let page = search_clipboard_page(&conn, &text("copied"), None, usize::MAX).unwrap();
+        assert_eq!(page.items.len(), MAX_PAGE_SIZE);
+        assert!(page.next_cursor.is_some());
+    }
+
+    #[test]
+    fn test_filters_combine() {
+        let conn = open_clipboard_db(Path::new(":memory:")).unwrap();
+        let from_app = |id: &str, text: &str, timestamp: i64, app: &str| ClipboardItem {
+            app_source: Some(app.to_string()),
+            ..item(id, text, timestamp, false)
+        };
+        insert_clipboard_item(&conn, &from_app("old", "deploy 100%", 10, "Terminal")).unwrap();
+        insert_clipboard_item(&conn, &from_app("new", "deploy_log", 20, "Terminal")).unwrap();
+        insert_clipboard_item(&conn, &from_app("web", "deploy docs", 20, "Firefox")).unwrap();
+        insert_clipboard_item(&conn, &ClipboardItem {
+            content_type: ClipboardContentType::Image,
+            text: None,
+            ..from_app("shot", "", 30, "Screenshot")
+        })
+        .unwrap();
+        set_clipboard_pinned(&conn, "old", true).unwrap();
+
+        // Images have no text, but still match on their app and date
+        let images = ClipboardFilter {
+            content_types: vec![ClipboardContentType::Image],
+            app_sources: vec!["screen".to_string()],
+            since: Some(25),
+            ..Default::default()
+        };
+        assert_eq!(ids(&search_clipboard_page(&conn, &images, None, 10).unwrap()), vec!["shot"]);
+
+        let terminal = ClipboardFilter { app_sources: vec!["term".to_string()], until: Some(20), ..text("deploy") };
+        assert_eq!(ids(&search_clipboard_page(&conn, &terminal, None, 10).unwrap()), vec!["old"]);
+
+        // LIKE wildcards are taken literally
+        assert_eq!(ids(&search_clipboard_page(&conn, &text("y 100%"), None, 10).unwrap()), vec!["old"]);
+        assert_eq!(ids(&search_clipboard_page(&conn, &text("_"), None, 10).unwrap()), vec!["new"]);
+
+        // Pins survive the item being copied again
+        insert_clipboard_item(&conn, &from_app("old", "deploy 100%", 40, "Terminal")).unwrap();
+        let pinned = ClipboardFilter { pinned_only: true, ..Default::default() };
+        assert_eq!(ids(&search_clipboard_page(&conn, &pinned, None, 10).unwrap()), vec!["old"]);
+
+        let conflicting = ClipboardFilter {
+            content_types: vec![ClipboardContentType::Text, ClipboardContentType::Image],
+            ..Default::default()
+        };
+        assert_eq!(search_clipboard_page(&conn, &conflicting, None, 10).unwrap().total, 0);
+
+        // Regex runs after the SQL conditions, and still pages
+        let pattern = ClipboardFilter {
+            pattern: Some(Regex::new("^deploy[ _]").unwrap()),
+            app_sources: vec!["Terminal".to_string()],
+            ..Default::default()
+        };
+        let first = search_clipboard_page(&conn, &pattern, None, 1).unwrap();
+        assert_eq!((ids(&first), first.total), (vec!["old"], 2));
+        let after = PageCursor::decode(first.next_cursor.as_deref().unwrap()).unwrap();
+        let second = search_clipboard_page(&conn, &pattern, Some(&after), 1).unwrap();
+        assert_eq!(ids(&second), vec!["new"]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    #[test]
+    fn test_sync_follows_the_item_files() {
+        let mut conn = open_clipboard_db(Path::new(":memory:")).unwrap();
+        sync_clipboard_items(&mut conn, &[item("a", "first", 1, false), item("b", "second", 2, false)]).unwrap();
+        set_clipboard_pinned(&conn, "a", true).unwrap();
+
+        // "b" was deleted and "c" copied since
+        sync_clipboard_items(&mut conn, &[item("a", "first", 1, false), item("c", "third", 3, false)]).unwrap();
+        let page = search_clipboard_page(&conn, &ClipboardFilter::default(), None, 10).unwrap();
+        assert_eq!(ids(&page), vec!["c", "a"]);
+        assert!(page.items[1].pinned);
+
+        sync_clipboard_items(&mut conn, &[]).unwrap();
+        assert_eq!(search_clipboard_page(&conn, &ClipboardFilter::default(), None, 10).unwrap().total, 0);
+    }
+
+    #[test]
+    fn test_regex_search_scans_only_the_newest_rows() {
+        let conn = open_clipboard_db(Path::new(":memory:")).unwrap();
+        insert_clipboard_item(&conn, &item("oldest", "needle", 0, false)).unwrap();
+        for i in 0..MAX_REGEX_SCAN {
+            insert_clipboard_item(&conn, &item(&format!("hay-{}", i), "hay", 1 + i as i64, false)).unwrap();
+        }
+        let pattern = ClipboardFilter { pattern: Some(Regex::new("need+le").unwrap()), ..Default::default() };
+        assert_eq!(search_clipboard_page(&conn, &pattern, None, 10).unwrap().total, 0);
+        // Plain text search still reaches it
+        assert_eq!(ids(&search_clipboard_page(&conn, &text("needle"), None, 10).unwrap()), vec!["oldest"]);
+    }
+
+    #[test]
+    fn test_upgrades_unversioned_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("history.db");
+        let legacy = Connection::open(&db_path).unwrap();
+        legacy
+            .execute(
+                "CREATE TABLE clipboard_history (
+                    id TEXT PRIMARY KEY,
+                    content_type TEXT NOT NULL,
+                    text TEXT,
+                    timestamp INTEGER NOT NULL,
+                    is_sensitive BOOLEAN DEFAULT 0
+                )",
+                [],
+            )
+            .unwrap();
+        legacy
+            .execute(
+                "INSERT INTO clipboard_history (id, content_type, text, timestamp) VALUES ('a', 'Text', 'kept', 1)",
+                [],
+            )
+            .unwrap();
+        drop(legacy);
+
+        let conn = open_clipboard_db(&db_path).unwrap();
+        let page = search_clipboard_page(&conn, &text("kept"), None, 10).unwrap();
+        assert_eq!(ids(&page), vec!["a"]);
+        assert!(!page.items[0].pinned);
+    }
+}
@@ -2,11 +2,14 @@
 //! Handles SQLite database for local file indexing
 #![allow(dead_code)]
 
-use rusqlite::{Connection, Result as SqliteResult};
-use std::path::PathBuf;
+use rusqlite::{Connection, OptionalExtension, Result as SqliteResult, Transaction};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
 
-use super::get_files_db_path;
-use tauri::AppHandle;
+use super::{escape_like, get_files_db_path};
+use super::migrations::{add_column_if_missing, migrate, Schema};
+use crate::services::search::terms::query_terms;
+use tauri::{AppHandle, Runtime};
 
 /// File index entry
 #[derive(Debug, Clone)]
@@ -19,16 +22,35 @@ pub struct FileEntry {
     pub modified: i64,
     pub hidden: bool,
     pub indexed: i64,
+    /// Finder tags and download source hosts, empty when none or not indexed
+    pub tags: Vec<String>,
+    /// A cloud file whose content hasn't been downloaded
+    pub is_placeholder: bool,
 }
 
 /// Initialize the files database with schema
-pub fn init_files_db(handle: &AppHandle) -> SqliteResult<Connection> {
+pub fn init_files_db<R: Runtime>(handle: &AppHandle<R>) -> SqliteResult<Connection> {
     let db_path = get_files_db_path(handle)
         .map_err(|e| rusqlite::Error::InvalidPath(PathBuf::from(e)))?;
 
-    let conn = Connection::open(&db_path)?;
+    open_files_db(&db_path)
+}
+
+/// Schema of the files database
+pub const FILES_SCHEMA: Schema = Schema {
+    name: "files_index.db",
+    migrations: &[files_baseline, add_kind_column],
+};
+
+/// Open a files database at an explicit path, creating or migrating the schema
+pub fn open_files_db(db_path: &Path) -> SqliteResult<Connection> {
+    let mut conn = Connection::open(db_path)?;
+    migrate(&mut conn, &FILES_SCHEMA)?;
+    Ok(conn)
+}
 
-    // Create files table
+/// Version 1: the schema as it was before versioning
+fn files_baseline(conn: &Transaction) -> SqliteResult<()> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS files (
             id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -38,11 +60,18 @@ pub fn init_files_db(handle: &AppHandle) -> SqliteResult<Connection> {
             size INTEGER NOT NULL,
             modified INTEGER NOT NULL,
             hidden BOOLEAN DEFAULT 0,
-            indexed INTEGER NOT NULL
+            indexed INTEGER NOT NULL,
+            tags TEXT,
+            is_placeholder BOOLEAN DEFAULT 0
         )",
         [],
     )?;
 
+    // Databases created before tags and placeholders were indexed lack the columns
+    for column in ["tags TEXT", "is_placeholder BOOLEAN DEFAULT 0"] {
+        add_column_if_missing(conn, "files", column)?;
+    }
+
     // Create indexes for faster queries
     conn.execute(
         "CREATE INDEX IF NOT EXISTS idx_filename ON files(filename)",
@@ -56,25 +85,87 @@ pub fn init_files_db(handle: &AppHandle) -> SqliteResult<Connection> {
         "CREATE INDEX IF NOT EXISTS idx_path ON files(path)",
         [],
     )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_modified ON files(modified)",
+        [],
+    )?;
 
-    Ok(conn)
+    Ok(())
+}
+
+/// Version 2: the kind of each file, derived from its extension
+fn add_kind_column(conn: &Transaction) -> SqliteResult<()> {
+    conn.execute("ALTER TABLE files ADD COLUMN kind TEXT NOT NULL DEFAULT 'other'", [])?;
+    conn.execute("CREATE INDEX IF NOT EXISTS idx_kind ON files(kind)", [])?;
+
+    // Every row in one statement, with file_kind's table as a CASE
+    let cases: String = FILE_KINDS
+        .iter()
+        .map(|(kind, extensions)| format!(" WHEN lower(extension) IN ('{}') THEN '{}'", extensions.join("', '"), kind))
+        .collect();
+    conn.execute(
+        &format!("UPDATE files SET kind = CASE{} ELSE 'other' END WHERE extension IS NOT NULL", cases),
+        [],
+    )?;
+    Ok(())
+}
+
+/// Lowercase extensions of each file kind; any other file is "other"
+const FILE_KINDS: &[(&str, &[&str])] = &[
+    ("image", &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "ico", "tiff", "heic"]),
+    ("video", &["mp4", "mov", "mkv", "avi", "webm", "m4v"]),
+    ("audio", &["mp3", "wav", "flac", "aac", "ogg", "m4a"]),
+    (
+        "document",
+        &["pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "md", "rtf", "pages", "numbers", "key", "csv"],
+    ),
+    ("archive", &["zip", "tar", "gz", "tgz", "bz2", "xz", "7z", "rar", "dmg"]),
+    (
+        "code",
+        &[
+            "rs", "js", "ts", "tsx", "jsx", "py", "go", "java", "c", "h", "cpp", "swift", "rb", "sh", "json", "toml",
+            "yaml", "yml", "html", "css",
+        ],
+    ),
+];
+
+/// Kind of a file by extension: image, video, audio, document, archive,
+/// code or other
+pub fn file_kind(extension: Option<&str>) -> &'static str {
+    let Some(extension) = extension else {
+        return "other";
+    };
+    let extension = extension.to_ascii_lowercase();
+    FILE_KINDS
+        .iter()
+        .find(|(_, extensions)| extensions.contains(&extension.as_str()))
+        .map_or("other", |(kind, _)| kind)
 }
 
 /// Insert or update a file entry
 pub fn upsert_file(conn: &Connection, entry: &FileEntry) -> SqliteResult<i64> {
     let hidden_val: i64 = if entry.hidden { 1 } else { 0 };
+    let placeholder_val: i64 = if entry.is_placeholder { 1 } else { 0 };
     let extension_ref: Option<&String> = entry.extension.as_ref();
+    let tags_json: Option<String> = if entry.tags.is_empty() {
+        None
+    } else {
+        serde_json::to_string(&entry.tags).ok()
+    };
 
     conn.execute(
-        "INSERT INTO files (path, filename, extension, size, modified, hidden, indexed)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+        "INSERT INTO files (path, filename, extension, size, modified, hidden, indexed, tags, is_placeholder, kind)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
          ON CONFLICT(path) DO UPDATE SET
             filename = ?2,
             extension = ?3,
             size = ?4,
             modified = ?5,
             hidden = ?6,
-            indexed = ?7",
+            indexed = ?7,
+            tags = ?8,
+            is_placeholder = ?9,
+            kind = ?10",
         [
             &entry.path as &dyn rusqlite::ToSql,
             &entry.filename as &dyn rusqlite::ToSql,
@@ -83,40 +174,142 @@ pub fn upsert_file(conn: &Connection, entry: &FileEntry) -> SqliteResult<i64> {
             &entry.modified as &dyn rusqlite::ToSql,
             &hidden_val as &dyn rusqlite::ToSql,
             &entry.indexed as &dyn rusqlite::ToSql,
+            &tags_json as &dyn rusqlite::ToSql,
+            &placeholder_val as &dyn rusqlite::ToSql,
+            &file_kind(entry.extension.as_deref()) as &dyn rusqlite::ToSql,
         ],
     )?;
 
     Ok(conn.last_insert_rowid())
 }
 
-/// Search files by name
+/// Record whether the indexed file at `path` is a cloud placeholder
+pub fn set_placeholder(conn: &Connection, path: &str, is_placeholder: bool) -> SqliteResult<usize> {
+    conn.execute(
+        "UPDATE files SET is_placeholder = ?2 WHERE path = ?1",
+        rusqlite::params![path, is_placeholder],
+    )
+}
+
+/// Split `tag:<name>` filters out of a search query. Returns the remaining
+/// text and the requested tags; a bare `tag:` is kept as text.
+pub fn parse_tag_filters(query: &str) -> (String, Vec<String>) {
+    let mut text = Vec::new();
+    let mut tags = Vec::new();
+
+    for token in query.split_whitespace() {
+        match token.get(..4) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("tag:") && token.len() > 4 => {
+                tags.push(token[4..].to_string());
+            }
+            _ => text.push(token),
+        }
+    }
+
+    (text.join(" "), tags)
+}
+
+const FILE_COLUMNS: &str = "id, path, filename, extension, size, modified, hidden, indexed, tags, is_placeholder";
+
+fn row_to_entry(row: &rusqlite::Row) -> SqliteResult<FileEntry> {
+    let tags: Option<String> = row.get(8)?;
+    Ok(FileEntry {
+        id: Some(row.get(0)?),
+        path: row.get(1)?,
+        filename: row.get(2)?,
+        extension: row.get(3)?,
+        size: row.get(4)?,
+        modified: row.get(5)?,
+        hidden: row.get(6)?,
+        indexed: row.get(7)?,
+        tags: tags
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default(),
+        is_placeholder: row.get(9)?,
+    })
+}
+
+/// Search files by name. Every term of the query must occur in the path,
+/// in any order, and at least one in the filename; files with more terms in
+/// the filename come first. `tag:<name>` terms in the query only keep files
+/// carrying that tag (case-insensitive).
 pub fn search_files(
     conn: &Connection,
     query: &str,
     limit: usize,
 ) -> SqliteResult<Vec<FileEntry>> {
-    let pattern = format!("%{}%", query);
+    search_files_in(conn, query, limit, None)
+}
+
+/// `search_files`, keeping only files below `folder` when given
+pub fn search_files_in(
+    conn: &Connection,
+    query: &str,
+    limit: usize,
+    folder: Option<&str>,
+) -> SqliteResult<Vec<FileEntry>> {
+    let (text, tags) = parse_tag_filters(query);
+    let folder_pattern = folder.map(|folder| {
+        let folder = folder.trim_end_matches(['/', '\\']);
+        format!("{}{}%", escape_like(folder), std::path::MAIN_SEPARATOR)
+    });
+    let patterns: Vec<String> = query_terms(&text).iter().map(|term| format!("%{}%", term)).collect();
     let limit_i64 = limit as i64;
 
-    conn.prepare(
-        "SELECT id, path, filename, extension, size, modified, hidden, indexed
-         FROM files
-         WHERE filename LIKE ?1
-         ORDER BY filename ASC
-         LIMIT ?2"
-    )?
-    .query_map([&pattern as &dyn rusqlite::ToSql, &limit_i64 as &dyn rusqlite::ToSql], |row| {
-        Ok(FileEntry {
-            id: Some(row.get(0)?),
-            path: row.get(1)?,
-            filename: row.get(2)?,
-            extension: row.get(3)?,
-            size: row.get(4)?,
-            modified: row.get(5)?,
-            hidden: row.get(6)?,
-            indexed: row.get(7)?,
-        })
-    })?
+    // ?1 is the limit, then one parameter per term, then the tags
+    let term_params: Vec<usize> = (2..patterns.len() + 2).collect();
+    let in_filename: Vec<String> = term_params.iter().map(|i| format!("(filename LIKE ?{})", i)).collect();
+
+    let mut sql = format!("SELECT {} FROM files WHERE 1 = 1", FILE_COLUMNS);
+    // The path includes the filename
+    for i in &term_params {
+        sql.push_str(&format!(" AND path LIKE ?{}", i));
+    }
+    if !in_filename.is_empty() {
+        sql.push_str(&format!(" AND ({})", in_filename.join(" OR ")));
+    }
+    for i in 0..tags.len() {
+        sql.push_str(&format!(
+            " AND EXISTS (SELECT 1 FROM json_each(files.tags) WHERE lower(value) = lower(?{}))",
+            i + patterns.len() + 2
+        ));
+    }
+    if folder_pattern.is_some() {
+        sql.push_str(&format!(" AND path LIKE ?{} ESCAPE '\\'", tags.len() + patterns.len() + 2));
+    }
+    if !in_filename.is_empty() {
+        sql.push_str(&format!(" ORDER BY {} DESC,", in_filename.join(" + ")));
+    } else {
+        sql.push_str(" ORDER BY");
+    }
+    sql.push_str(" filename ASC LIMIT ?1");
+
+    let mut params: Vec<&dyn rusqlite::ToSql> = vec![&limit_i64];
+    params.extend(patterns.iter().map(|p| p as &dyn rusqlite::ToSql));
+    params.extend(tags.iter().map(|t| t as &dyn rusqlite::ToSql));
+    params.extend(folder_pattern.iter().map(|p| p as &dyn rusqlite::ToSql));
+
+    conn.prepare(&sql)?
+        .query_map(params.as_slice(), row_to_entry)?
+        .collect()
+}
+
+/// The index entry for a path, if it's indexed
+pub fn file_by_path(conn: &Connection, path: &str) -> SqliteResult<Option<FileEntry>> {
+    conn.query_row(&format!("SELECT {} FROM files WHERE path = ?1", FILE_COLUMNS), [path], row_to_entry)
+        .optional()
+}
+
+/// Most recently modified visible files
+pub fn recent_files(conn: &Connection, limit: usize) -> SqliteResult<Vec<FileEntry>> {
+    conn.prepare(&format!(
+        "SELECT {} FROM files
+         WHERE hidden = 0
+         ORDER BY modified DESC
+         LIMIT ?1",
+        FILE_COLUMNS
+    ))?
+    .query_map([limit as i64], row_to_entry)?
     .collect()
 }
 
@@ -126,20 +319,394 @@ pub fn delete_file(conn: &Connection, path: &str) -> SqliteResult<()> {
     Ok(())
 }
 
+/// Number of extensions reported in `FileIndexStats::top_extensions`
+const TOP_EXTENSIONS: usize = 10;
+
 /// Get file index statistics
 pub fn get_index_stats(conn: &Connection) -> SqliteResult<FileIndexStats> {
     let total_files: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
-    let total_size: i64 = conn.query_row("SELECT SUM(size) FROM files", [], |row| row.get(0)).unwrap_or(0);
+    let total_size: i64 = conn.query_row("SELECT COALESCE(SUM(size), 0) FROM files", [], |row| row.get(0))?;
+    let last_indexed: Option<i64> = conn.query_row("SELECT MAX(indexed) FROM files", [], |row| row.get(0))?;
+
+    let top_extensions = conn
+        .prepare(
+            "SELECT LOWER(extension) AS ext, COUNT(*) AS n
+             FROM files
+             WHERE extension IS NOT NULL AND extension != ''
+             GROUP BY ext
+             ORDER BY n DESC, ext ASC
+             LIMIT ?1",
+        )?
+        .query_map([TOP_EXTENSIONS as i64], |row| {
+            Ok(ExtensionCount {
+                extension: row.get(0)?,
+                count: row.get::<_, i64>(1)? as usize,
+            })
+        })?
+        .collect::<SqliteResult<Vec<_>>>()?;
 
     Ok(FileIndexStats {
         total_files: total_files as usize,
         total_size,
+        last_indexed,
+        top_extensions,
     })
 }
 
 /// File index statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct FileIndexStats {
     pub total_files: usize,
     pub total_size: i64,
+    /// Newest `indexed` timestamp (unix seconds)
+    pub last_indexed: Option<i64>,
+    /// Most common extensions, lowercased, most frequent first
+    pub top_extensions: Vec<ExtensionCount>,
+}
+
+/// Number of indexed files with an extension
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExtensionCount {
+    pub extension: String,
+    pub count: usize,
+}
+
+/// Matches the file `?1` and everything below it, where `?2` is `?1` plus a
+/// trailing separator. A prefix comparison rather than LIKE, so `%` and `_`
+/// in folder names aren't wildcards.
+const UNDER_ROOT: &str = "(path = ?1 OR substr(path, 1, length(?2)) = ?2)";
+
+fn root_prefix(root: &str) -> String {
+    if root.ends_with(std::path::MAIN_SEPARATOR) {
+        root.to_string()
+    } else {
+        format!("{}{}", root, std::path::MAIN_SEPARATOR)
+    }
+}
+
+/// Delete every file indexed under `root`. Returns the number of rows removed.
+pub fn delete_files_under(conn: &Connection, root: &str) -> SqliteResult<usize> {
+    conn.execute(
+        &format!("DELETE FROM files WHERE {}", UNDER_ROOT),
+        [root, &root_prefix(root)],
+    )
+}
+
+/// Statistics for the files indexed under one root
+pub fn path_index_stats(conn: &Connection, root: &str) -> SqliteResult<PathIndexStats> {
+    conn.query_row(
+        &format!(
+            "SELECT COUNT(*), COALESCE(SUM(size), 0), MAX(indexed) FROM files WHERE {}",
+            UNDER_ROOT
+        ),
+        [root, &root_prefix(root)],
+        |row| {
+            Ok(PathIndexStats {
+                file_count: row.get::<_, i64>(0)? as usize,
+                total_size: row.get(1)?,
+                last_indexed: row.get(2)?,
+            })
+        },
+    )
+}
+
+/// Index statistics for one indexed root
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PathIndexStats {
+    pub file_count: usize,
+    /// Combined size of the indexed files in bytes
+    pub total_size: i64,
+    /// Newest `indexed` timestamp (unix seconds)
+    pub last_indexed: Option<i64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::migrations::schema_version;
+
+    fn entry(filename: &str, extension: Option<&str>, size: i64, indexed: i64) -> FileEntry {
+        FileEntry {
+            id: None,
+            path: format!("/tmp/{}", filename),
+            filename: filename.to_string(),
+            extension: extension.map(|e| e.to_string()),
+            size,
+            modified: 0,
+            hidden: false,
+            indexed,
+            tags: Vec::new(),
+            is_placeholder: false,
+        }
+    }
+
+    #[test]
+    fn test_index_stats() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_files_db(&dir.path().join("files_index.db")).unwrap();
+
+        let empty = get_index_stats(&conn).unwrap();
+        assert_eq!(empty.total_files, 0);
+        assert_eq!(empty.total_size, 0);
+        assert_eq!(empty.last_indexed, None);
+        assert!(empty.top_extensions.is_empty());
+
+        for e in [
+            entry("a.md", Some("md"), 10, 100),
+            entry("b.MD", Some("MD"), 20, 300),
+            entry("c.rs", Some("rs"), 30, 200),
+            entry("Makefile", None, 40, 150),
+        ] {
+            upsert_file(&conn, &e).unwrap();
+        }
+
+        let stats = get_index_stats(&conn).unwrap();
+        assert_eq!(stats.total_files, 4);
+        assert_eq!(stats.total_size, 100);
+        assert_eq!(stats.last_indexed, Some(300));
+        assert_eq!(
+            stats.top_extensions,
+            vec![
+                ExtensionCount { extension: "md".to_string(), count: 2 },
+                ExtensionCount { extension: "rs".to_string(), count: 1 },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_extensions_capped() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_files_db(&dir.path().join("files_index.db")).unwrap();
+
+        for i in 0..15 {
+            let ext = format!("e{:02}", i);
+            upsert_file(&conn, &entry(&format!("f.{}", ext), Some(&ext), 1, 0)).unwrap();
+        }
+
+        let stats = get_index_stats(&conn).unwrap();
+        assert_eq!(stats.top_extensions.len(), TOP_EXTENSIONS);
+    }
+
+    #[test]
+    fn test_parse_tag_filters() {
+        assert_eq!(parse_tag_filters("report tag:Blue"), ("report".to_string(), vec!["Blue".to_string()]));
+        assert_eq!(
+            parse_tag_filters("TAG:red  q3 tag:work"),
+            ("q3".to_string(), vec!["red".to_string(), "work".to_string()])
+        );
+        assert_eq!(parse_tag_filters("tag: notes"), ("tag: notes".to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_search_filters_by_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_files_db(&dir.path().join("files_index.db")).unwrap();
+
+        let mut blue = entry("report-blue.pdf", Some("pdf"), 1, 0);
+        blue.tags = vec!["Blue".to_string(), "Work".to_string()];
+        let mut red = entry("report-red.pdf", Some("pdf"), 1, 0);
+        red.tags = vec!["Red".to_string()];
+        for e in [blue, red, entry("report-plain.pdf", Some("pdf"), 1, 0)] {
+            upsert_file(&conn, &e).unwrap();
+        }
+
+        let names = |query: &str| -> Vec<String> {
+            search_files(&conn, query, 10).unwrap().into_iter().map(|f| f.filename).collect()
+        };
+        assert_eq!(names("report").len(), 3);
+        assert_eq!(names("report tag:blue"), vec!["report-blue.pdf"]);
+        assert_eq!(names("tag:RED"), vec!["report-red.pdf"]);
+        assert_eq!(names("tag:blue tag:work"), vec!["report-blue.pdf"]);
+        assert!(names("tag:blue tag:red").is_empty());
+
+        let found = search_files(&conn, "tag:blue", 10).unwrap();
+        assert_eq!(found[0].tags, vec!["Blue", "Work"]);
+        let plain = search_files(&conn, "plain", 10).unwrap();
+        assert!(plain[0].tags.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_search_within_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_files_db(&dir.path().join("files_index.db")).unwrap();
+        for path in ["/work/src/main.rs", "/work/src_old/main.rs", "/work/src/nested/main.rs", "/home/main.rs"] {
+            let mut e = entry("main.rs", Some("rs"), 1, 0);
+            e.path = path.to_string();
+            upsert_file(&conn, &e).unwrap();
+        }
+
+        let paths = |folder: &str| -> Vec<String> {
+            let mut paths: Vec<String> = search_files_in(&conn, "main", 10, Some(folder))
+                .unwrap()
+                .into_iter()
+                .map(|f| f.path)
+                .collect();
+            paths.sort();
+            paths
+        };
+        // Sibling folders sharing the name's start aren't inside it
+        assert_eq!(paths("/work/src"), vec!["/work/src/main.rs", "/work/src/nested/main.rs"]);
+        assert_eq!(paths("/work/src/"), paths("/work/src"));
+        assert_eq!(paths("/work/src_old"), vec!["/work/src_old/main.rs"]);
+        assert!(paths("/work/s%").is_empty());
+    }
+
+    #[test]
+    fn test_search_requires_every_term_in_any_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_files_db(&dir.path().join("files_index.db")).unwrap();
+        let mut notes = entry("q3 notes.txt", Some("txt"), 1, 0);
+        notes.path = "/work/budget/q3 notes.txt".to_string();
+        for e in [
+            entry("2024年项目报告.docx", Some("docx"), 1, 0),
+            entry("项目计划.xlsx", Some("xlsx"), 1, 0),
+            entry("Budget-Q3-2024.xlsx", Some("xlsx"), 1, 0),
+            entry("budget-q4.xlsx", Some("xlsx"), 1, 0),
+            notes,
+        ] {
+            upsert_file(&conn, &e).unwrap();
+        }
+
+        let names = |query: &str| -> Vec<String> {
+            search_files(&conn, query, 10).unwrap().into_iter().map(|f| f.filename).collect()
+        };
+        assert_eq!(names("项目 报告 2024"), vec!["2024年项目报告.docx"]);
+        assert_eq!(names("报告 项目"), vec!["2024年项目报告.docx"]);
+        assert_eq!(names("项目"), vec!["2024年项目报告.docx", "项目计划.xlsx"]);
+        assert_eq!(names("xlsx q3 budget"), vec!["Budget-Q3-2024.xlsx"]);
+        assert_eq!(names("项目xlsx"), vec!["项目计划.xlsx"]);
+        // A term found only in the folder counts, but ranks below the filename
+        assert_eq!(names("budget q3"), vec!["Budget-Q3-2024.xlsx", "q3 notes.txt"]);
+        // Every term must match somewhere
+        assert!(names("budget q5").is_empty());
+    }
+
+    #[test]
+    fn test_adds_new_columns_to_existing_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("files_index.db");
+        Connection::open(&path)
+            .unwrap()
+            .execute(
+                "CREATE TABLE files (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    path TEXT UNIQUE NOT NULL,
+                    filename TEXT NOT NULL,
+                    extension TEXT,
+                    size INTEGER NOT NULL,
+                    modified INTEGER NOT NULL,
+                    hidden BOOLEAN DEFAULT 0,
+                    indexed INTEGER NOT NULL
+                )",
+                [],
+            )
+            .unwrap();
+
+        let conn = open_files_db(&path).unwrap();
+        let mut tagged = entry("a.md", Some("md"), 1, 0);
+        tagged.tags = vec!["Blue".to_string()];
+        upsert_file(&conn, &tagged).unwrap();
+        assert_eq!(search_files(&conn, "tag:blue", 10).unwrap().len(), 1);
+
+        let mut cloud = entry("b.md", Some("md"), 1, 0);
+        cloud.is_placeholder = true;
+        upsert_file(&conn, &cloud).unwrap();
+        assert!(file_by_path(&conn, "/tmp/b.md").unwrap().unwrap().is_placeholder);
+        set_placeholder(&conn, "/tmp/b.md", false).unwrap();
+        assert!(!file_by_path(&conn, "/tmp/b.md").unwrap().unwrap().is_placeholder);
+        assert!(!file_by_path(&conn, "/tmp/a.md").unwrap().unwrap().is_placeholder);
+
+        // Reopening must not fail on the existing column
+        drop(conn);
+        open_files_db(&path).unwrap();
+    }
+
+    #[test]
+    fn test_migrates_unversioned_db_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("files_index.db");
+        let legacy = Connection::open(&path).unwrap();
+        legacy
+            .execute(
+                "CREATE TABLE files (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    path TEXT UNIQUE NOT NULL,
+                    filename TEXT NOT NULL,
+                    extension TEXT,
+                    size INTEGER NOT NULL,
+                    modified INTEGER NOT NULL,
+                    hidden BOOLEAN DEFAULT 0,
+                    indexed INTEGER NOT NULL,
+                    tags TEXT
+                )",
+                [],
+            )
+            .unwrap();
+        legacy
+            .execute(
+                "INSERT INTO files (path, filename, extension, size, modified, indexed)
+                 VALUES ('/tmp/a.PNG', 'a.PNG', 'PNG', 1, 0, 0), ('/tmp/b', 'b', NULL, 1, 0, 0),
+                        ('/tmp/e.Rs', 'e.Rs', 'Rs', 1, 0, 0), ('/tmp/f.xyz', 'f.xyz', 'xyz', 1, 0, 0)",
+                [],
+            )
+            .unwrap();
+        assert_eq!(schema_version(&legacy).unwrap(), 0);
+        drop(legacy);
+
+        let kinds = |conn: &Connection| -> Vec<String> {
+            let mut stmt = conn.prepare("SELECT kind FROM files ORDER BY path").unwrap();
+            stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect()
+        };
+        let conn = open_files_db(&path).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), FILES_SCHEMA.latest_version());
+        assert_eq!(kinds(&conn), vec!["image", "other", "code", "other"]);
+        assert!(!file_by_path(&conn, "/tmp/a.PNG").unwrap().unwrap().is_placeholder);
+        drop(conn);
+
+        // Nothing runs again on reopen
+        let conn = open_files_db(&path).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), FILES_SCHEMA.latest_version());
+        assert_eq!(kinds(&conn), vec!["image", "other", "code", "other"]);
+        upsert_file(&conn, &entry("c.rs", Some("rs"), 1, 0)).unwrap();
+        assert_eq!(kinds(&conn).len(), 5);
+    }
+
+    #[test]
+    fn test_delete_and_stats_under_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_files_db(&dir.path().join("files_index.db")).unwrap();
+
+        let at = |path: &str, size: i64, indexed: i64| FileEntry {
+            path: path.to_string(),
+            ..entry("f", None, size, indexed)
+        };
+        for e in [
+            at("/home/me/docs/a.md", 10, 100),
+            at("/home/me/docs/sub/b.md", 20, 300),
+            at("/home/me/docs2/c.md", 40, 500),
+            at("/home/me/100%_done/d.md", 80, 700),
+            at("/home/me/100x_done/e.md", 160, 900),
+        ] {
+            upsert_file(&conn, &e).unwrap();
+        }
+
+        assert_eq!(
+            path_index_stats(&conn, "/home/me/docs").unwrap(),
+            PathIndexStats { file_count: 2, total_size: 30, last_indexed: Some(300) }
+        );
+        // Wildcard characters in the root match literally
+        assert_eq!(path_index_stats(&conn, "/home/me/100%_done").unwrap().file_count, 1);
+
+        // A sibling sharing the name prefix is untouched
+        assert_eq!(delete_files_under(&conn, "/home/me/docs/").unwrap(), 2);
+        assert_eq!(path_index_stats(&conn, "/home/me/docs").unwrap().file_count, 0);
+        assert_eq!(path_index_stats(&conn, "/home/me/docs2").unwrap().file_count, 1);
+        assert_eq!(get_index_stats(&conn).unwrap().total_files, 3);
+
+        assert_eq!(
+            path_index_stats(&conn, "/nowhere").unwrap(),
+            PathIndexStats { file_count: 0, total_size: 0, last_indexed: None }
+        );
+    }
 }
@@ -0,0 +1,148 @@
+//! Schema Migrations
+//! A database's schema version is kept in SQLite's `user_version`. Each
+//! migration takes the schema from the version before it to the next, and
+//! opening a database runs the ones it hasn't had yet, in order, inside one
+//! transaction: a failed migration leaves the database as it was. Databases
+//! from a newer version of the app are refused rather than written to.
+//!
+//! Migrations are append-only. Once released, a migration must not change;
+//! a fix goes in a new one.
+
+use rusqlite::{ffi, Connection, Error, Result as SqliteResult, Transaction};
+
+/// Brings the schema one version forward
+pub type Migration = fn(&Transaction) -> SqliteResult<()>;
+
+/// The migrations of one database, oldest first. Version N is the schema
+/// after the first N migrations ran; 0 is an empty or unversioned database.
+pub struct Schema {
+    /// File name, for errors
+    pub name: &'static str,
+    pub migrations: &'static [Migration],
+}
+
+impl Schema {
+    /// Version this build creates and migrates to
+    pub fn latest_version(&self) -> u32 {
+        self.migrations.len() as u32
+    }
+}
+
+/// Schema version recorded in `conn`
+pub fn schema_version(conn: &Connection) -> SqliteResult<u32> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+/// Fails if `conn` holds a schema newer than this build knows
+pub fn check_version(conn: &Connection, schema: &Schema) -> SqliteResult<u32> {
+    let version = schema_version(conn)?;
+    if version > schema.latest_version() {
+        return Err(Error::SqliteFailure(
+            ffi::Error::new(ffi::SQLITE_CANTOPEN),
+            Some(format!(
+                "{} has schema version {}, but this version of the app only knows up to {}; \
+                 update the app or reset its data",
+                schema.name,
+                version,
+                schema.latest_version()
+            )),
+        ));
+    }
+    Ok(version)
+}
+
+/// Run the migrations `conn` hasn't had yet and record the new version.
+/// Returns the version the database is at.
+pub fn migrate(conn: &mut Connection, schema: &Schema) -> SqliteResult<u32> {
+    let version = check_version(conn, schema)?;
+    let latest = schema.latest_version();
+    if version == latest {
+        return Ok(version);
+    }
+
+    let tx = conn.transaction()?;
+    for migration in &schema.migrations[version as usize..] {
+        migration(&tx)?;
+    }
+    tx.pragma_update(None, "user_version", latest)?;
+    tx.commit()?;
+
+    println!("[Migrations] {} migrated from version {} to {}", schema.name, version, latest);
+    Ok(latest)
+}
+
+/// Whether `table` has `column`
+pub fn has_column(conn: &Connection, table: &str, column: &str) -> SqliteResult<bool> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let mut names = stmt.query_map([], |row| row.get::<_, String>(1))?;
+    names.try_fold(false, |found, name| Ok(found || name? == column))
+}
+
+/// Add `column` ("name TYPE ...") to `table` unless it's there. For
+/// baselines, which adopt databases created before versioning, possibly by
+/// a build that already had the column.
+pub fn add_column_if_missing(conn: &Connection, table: &str, column: &str) -> SqliteResult<()> {
+    let name = column.split_whitespace().next().unwrap_or(column);
+    if !has_column(conn, table, name)? {
+        conn.execute(&format!("ALTER TABLE {} ADD COLUMN {}", table, column), [])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_notes(tx: &Transaction) -> SqliteResult<()> {
+        tx.execute("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT NOT NULL)", [])?;
+        Ok(())
+    }
+
+    fn add_pinned(tx: &Transaction) -> SqliteResult<()> {
+        tx.execute("ALTER TABLE notes ADD COLUMN pinned BOOLEAN DEFAULT 0", [])?;
+        Ok(())
+    }
+
+    fn fail(tx: &Transaction) -> SqliteResult<()> {
+        tx.execute("ALTER TABLE missing ADD COLUMN x TEXT", [])?;
+        Ok(())
+    }
+
+    const NOTES: Schema = Schema { name: "notes.db", migrations: &[create_notes, add_pinned] };
+
+    #[test]
+    fn test_migrations_run_once_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.db");
+
+        let mut conn = Connection::open(&path).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        assert_eq!(migrate(&mut conn, &NOTES).unwrap(), 2);
+        assert!(has_column(&conn, "notes", "pinned").unwrap());
+        drop(conn);
+
+        // Reopening runs nothing; the migrations would fail if they ran again
+        let mut conn = Connection::open(&path).unwrap();
+        assert_eq!(migrate(&mut conn, &NOTES).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let broken = Schema { name: "notes.db", migrations: &[create_notes, fail] };
+
+        assert!(migrate(&mut conn, &broken).is_err());
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        assert!(!has_column(&conn, "notes", "id").unwrap());
+    }
+
+    #[test]
+    fn test_newer_schema_is_refused() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", 3).unwrap();
+
+        let error = migrate(&mut conn, &NOTES).unwrap_err().to_string();
+        assert!(error.contains("notes.db has schema version 3"), "{}", error);
+        assert_eq!(schema_version(&conn).unwrap(), 3);
+    }
+}
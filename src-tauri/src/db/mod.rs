@@ -3,23 +3,24 @@
  * Handles SQLite databases for file index and browser cache
  */
 
+pub mod analytics;
+pub mod clipboard;
 pub mod files;
 pub mod browser;
+pub mod migrations;
+pub mod plugin_performance;
 pub mod plugin_schema;
 
-use std::path::PathBuf;
-use tauri::{AppHandle, Manager};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime};
 
 /// Get the application data directory
-pub fn get_data_dir(handle: &AppHandle) -> Result<PathBuf, String> {
-    handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))
+pub fn get_data_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    crate::services::app_paths::data_dir(handle)
 }
 
 /// Ensure the data directory exists
-pub fn ensure_data_dir(handle: &AppHandle) -> Result<PathBuf, String> {
+pub fn ensure_data_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
     let data_dir = get_data_dir(handle)?;
     std::fs::create_dir_all(&data_dir)
         .map_err(|e| format!("Failed to create data dir: {}", e))?;
@@ -27,13 +28,30 @@ pub fn ensure_data_dir(handle: &AppHandle) -> Result<PathBuf, String> {
 }
 
 /// Get the file index database path
-pub fn get_files_db_path(handle: &AppHandle) -> Result<PathBuf, String> {
+pub fn get_files_db_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
     let data_dir = ensure_data_dir(handle)?;
     Ok(data_dir.join("files_index.db"))
 }
 
 /// Get the browser cache database path
-pub fn get_browser_db_path(handle: &AppHandle) -> Result<PathBuf, String> {
+pub fn get_browser_db_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
     let data_dir = ensure_data_dir(handle)?;
     Ok(data_dir.join("browser_cache.db"))
 }
+
+/// Get the usage analytics database path
+pub fn get_analytics_db_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    let data_dir = ensure_data_dir(handle)?;
+    Ok(data_dir.join("usage_analytics.db"))
+}
+
+/// `text` for a LIKE pattern escaped with `\`, so `%`, `_` and `\` in it
+/// are taken literally
+pub(crate) fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// Size of a database file on disk in bytes, 0 if it doesn't exist yet
+pub fn db_file_size(db_path: &Path) -> u64 {
+    std::fs::metadata(db_path).map(|m| m.len()).unwrap_or(0)
+}
@@ -0,0 +1,137 @@
+//! Plugin Performance Rollups
+//! Daily per-plugin operation counts, latency and failures, stored in the
+//! analytics database so the plugin detail page can chart a month of history
+//! beyond what the in-memory metrics buffer holds.
+
+use crate::services::plugin_performance::DailyRollup;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+
+/// Create the rollup table; called when the analytics database is opened
+pub fn create_rollup_table(conn: &Connection) -> SqliteResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS plugin_perf_rollups (
+            plugin_id TEXT NOT NULL,
+            date TEXT NOT NULL,
+            op_count INTEGER NOT NULL,
+            avg_ms REAL NOT NULL,
+            p95_ms INTEGER NOT NULL,
+            failures INTEGER NOT NULL,
+            samples TEXT NOT NULL DEFAULT '[]',
+            PRIMARY KEY (plugin_id, date)
+        )",
+        [],
+    )?;
+    Ok(())
+}
+
+fn row_to_rollup(row: &rusqlite::Row) -> SqliteResult<DailyRollup> {
+    let mut rollup = DailyRollup::new(&row.get::<_, String>(0)?, &row.get::<_, String>(1)?);
+    rollup.op_count = row.get::<_, i64>(2)? as u64;
+    rollup.avg_ms = row.get(3)?;
+    rollup.p95_ms = row.get::<_, i64>(4)? as u64;
+    rollup.failures = row.get::<_, i64>(5)? as u64;
+    rollup.samples = serde_json::from_str(&row.get::<_, String>(6)?).unwrap_or_default();
+    Ok(rollup)
+}
+
+fn load_rollup(conn: &Connection, plugin_id: &str, date: &str) -> SqliteResult<Option<DailyRollup>> {
+    conn.query_row(
+        "SELECT plugin_id, date, op_count, avg_ms, p95_ms, failures, samples
+         FROM plugin_perf_rollups WHERE plugin_id = ?1 AND date = ?2",
+        params![plugin_id, date],
+        row_to_rollup,
+    )
+    .optional()
+}
+
+/// Merge freshly drained rollups into the stored ones
+pub fn merge_rollups(conn: &mut Connection, rollups: Vec<DailyRollup>) -> SqliteResult<()> {
+    let tx = conn.transaction()?;
+    for rollup in rollups {
+        let merged = match load_rollup(&tx, &rollup.plugin_id, &rollup.date)? {
+            Some(mut stored) => {
+                stored.merge(rollup);
+                stored
+            }
+            None => rollup,
+        };
+        tx.execute(
+            "INSERT OR REPLACE INTO plugin_perf_rollups
+             (plugin_id, date, op_count, avg_ms, p95_ms, failures, samples)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                merged.plugin_id,
+                merged.date,
+                merged.op_count as i64,
+                merged.avg_ms,
+                merged.p95_ms as i64,
+                merged.failures as i64,
+                serde_json::to_string(&merged.samples).unwrap_or_else(|_| "[]".to_string()),
+            ],
+        )?;
+    }
+    tx.commit()
+}
+
+/// Rollups for a plugin on or after `since` ("YYYY-MM-DD"), oldest first
+pub fn rollup_history(conn: &Connection, plugin_id: &str, since: &str) -> SqliteResult<Vec<DailyRollup>> {
+    conn.prepare(
+        "SELECT plugin_id, date, op_count, avg_ms, p95_ms, failures, samples
+         FROM plugin_perf_rollups
+         WHERE plugin_id = ?1 AND date >= ?2
+         ORDER BY date ASC",
+    )?
+    .query_map(params![plugin_id, since], row_to_rollup)?
+    .collect()
+}
+
+/// Delete rollups dated before `before` ("YYYY-MM-DD"). Returns the number
+/// of deleted rows.
+pub fn prune_rollups_before(conn: &Connection, before: &str) -> SqliteResult<usize> {
+    conn.execute("DELETE FROM plugin_perf_rollups WHERE date < ?1", [before])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::analytics::open_analytics_db;
+
+    fn rollup(plugin_id: &str, date: &str, durations: &[u64]) -> DailyRollup {
+        let mut rollup = DailyRollup::new(plugin_id, date);
+        for &ms in durations {
+            rollup.record(ms, true);
+        }
+        rollup
+    }
+
+    #[test]
+    fn test_flushes_merge_into_one_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = open_analytics_db(&dir.path().join("usage_analytics.db")).unwrap();
+
+        merge_rollups(&mut conn, vec![rollup("calc", "2024-01-01", &[10, 20])]).unwrap();
+        merge_rollups(&mut conn, vec![rollup("calc", "2024-01-01", &[30]), rollup("calc", "2024-01-02", &[5])]).unwrap();
+
+        let history = rollup_history(&conn, "calc", "2024-01-01").unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].op_count, 3);
+        assert!((history[0].avg_ms - 20.0).abs() < 1e-9);
+        assert_eq!(history[0].p95_ms, 30);
+        assert_eq!(history[1].date, "2024-01-02");
+        assert!(rollup_history(&conn, "other", "2024-01-01").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_prune_before() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut conn = open_analytics_db(&dir.path().join("usage_analytics.db")).unwrap();
+        merge_rollups(
+            &mut conn,
+            vec![rollup("calc", "2023-09-01", &[1]), rollup("calc", "2024-01-01", &[1])],
+        )
+        .unwrap();
+
+        assert_eq!(prune_rollups_before(&conn, "2023-10-03").unwrap(), 1);
+        assert_eq!(rollup_history(&conn, "calc", "").unwrap().len(), 1);
+    }
+}
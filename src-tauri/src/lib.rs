@@ -4,45 +4,85 @@ mod cmds;
 mod models;
 mod services;
 mod types;
+mod utils;
+#[cfg(test)]
+mod test_support;
+#[cfg(any(test, feature = "datagen"))]
+#[doc(hidden)]
+pub mod datagen;
+
+/// Hot paths exercised by the benchmarks in `benches/`, callable without an
+/// AppHandle, plus the boot stages `tests/cold_start_benchmark.rs` times on
+/// a mock app. Not a stable API.
+#[doc(hidden)]
+pub mod bench {
+    #[cfg(feature = "datagen")]
+    pub use crate::datagen;
+    pub use crate::db::browser::{open_browser_db, search_browser_data};
+    pub use crate::db::files::{open_files_db, search_files};
+    pub use crate::services::file_indexer::{FileIndexer, IndexerConfig};
+    pub use crate::services::search::app_provider::search_apps;
+    pub use crate::services::search::ParsedQuery;
+    pub use crate::cmds::plugins::list_plugins;
+    pub use crate::cmds::startup::{init_core, run_deferred_stages};
+    pub use crate::services::startup::{StartupReport, StartupStage, StartupTimings};
+}
 
-use tauri::{Emitter, Manager};
+use tauri::Manager;
+use types::events::{self, WindowShown};
 use tauri_plugin_global_shortcut::{Code, GlobalShortcutExt, Modifiers, Shortcut};
-use cmds::app::{AppState, get_installed_apps, launch_app, track_app_usage, get_app_icon, get_app_icon_nsworkspace, get_recently_used};
-use cmds::search::{SearchState, unified_search, get_search_stats, search_files, search_browser_data, update_browser_cache, index_files, get_file_index_stats, start_file_indexer, stop_file_indexer};
-use cmds::clipboard::{get_clipboard_history, get_clipboard_item, paste_clipboard_item, delete_clipboard_item, clear_clipboard_history, get_clipboard_settings, set_clipboard_settings, search_clipboard, write_clipboard_text};
+use cmds::app::{get_installed_apps, rescan_apps, launch_app, track_app_usage, get_app_icon, get_app_icon_nsworkspace, get_recently_used};
+use cmds::file_index::{list_index_paths, add_index_path, remove_index_path, reindex_path, get_path_breadcrumbs};
+use cmds::search::{unified_search, unified_search_streaming, get_results_page, get_search_performance, diagnose_search, get_query_history, remember_query, clear_query_history, record_emoji_usage, get_builtin_icons, search_more, preview_exclusion, get_default_suggestions, get_search_stats, search_files, search_browser_data, update_browser_cache, index_files, get_file_index_stats, start_file_indexer, stop_file_indexer};
+use cmds::bookmarks::{get_bookmark_folders, get_bookmarks_in_folder, open_bookmark_folder};
+use cmds::clipboard::{get_clipboard_history, get_clipboard_item, paste_clipboard_item, delete_clipboard_item, clear_clipboard_history, get_clipboard_settings, set_clipboard_settings, search_clipboard, set_clipboard_item_pinned, write_clipboard_text};
 use cmds::plugins::{
     install_plugin, uninstall_plugin, enable_plugin, disable_plugin,
-    get_plugin_manifest, reload_plugin, grant_plugin_permission, revoke_plugin_permission,
-    get_plugin_permissions, set_plugin_setting, get_plugin_setting, validate_plugin_manifest,
+    get_plugin_manifest, get_trigger_completions, resolve_trigger_completions, get_cached_plugin_result, cache_plugin_result, invalidate_plugin_cache, reload_plugin, grant_plugin_permission, revoke_plugin_permission,
+    get_plugin_permissions, get_pending_consents, respond_to_consent, set_plugin_shell_allowlist, get_plugin_security_report, set_plugin_setting, get_plugin_setting, validate_plugin_manifest,
     // New commands
-    get_plugin_health, check_plugin_health, get_plugin_usage_stats,
+    get_plugin_health, check_plugin_health, get_plugin_usage_stats, get_plugin_storage_usage,
     bulk_enable_plugins, bulk_disable_plugins, bulk_uninstall_plugins,
     plugin_validate_package, plugin_extract_package, plugin_install, plugin_get_install_status,
-    plugin_cancel_install,
+    plugin_cancel_install, cleanup_temp_files,
     plugin_validate_package_from_buffer, plugin_extract_package_from_buffer,
+    plugin_begin_upload, plugin_append_upload_chunk, plugin_finish_upload, plugin_cancel_upload,
+    plugin_download_package,
     // Enable/Disable/Uninstall commands (US3/US4)
-    plugin_enable, plugin_disable, plugin_uninstall,
+    plugin_enable, plugin_disable, resolve_trigger_conflict, plugin_uninstall, restore_archived_settings,
     // Plugin abbreviation commands
     get_plugin_abbreviations, save_plugin_abbreviations,
     set_plugin_abbreviation, remove_plugin_abbreviation,
 };
-use cmds::shell::{open_url, get_default_browser};
-use cmds::files::{read_file, write_file};
-use cmds::marketplace::{marketplace_list, marketplace_search, marketplace_install, marketplace_uninstall, marketplace_update, marketplace_check_updates, marketplace_get_plugin, get_installed_plugins};
-use cmds::settings::{get_settings, get_setting, set_setting, update_settings, reset_settings, init_preferences, get_hotkey, set_hotkey, unregister_all_hotkeys, reregister_hotkey, check_hotkey_conflicts, get_settings_file_path};
-use cmds::window::{get_screen_info, resize_window_smart};
-use cmds::performance::{PerformanceState, get_performance_metrics, check_performance_requirements, record_performance_event, get_average_search_time};
-use cmds::abbreviation::{get_abbreviation_config, save_abbreviation_config, add_abbreviation, update_abbreviation, delete_abbreviation, export_abbreviation_config, import_abbreviation_config};
+use cmds::privacy::{set_privacy_mode, get_privacy_mode};
+use cmds::processes::{search_processes, end_process};
+use cmds::analytics::{get_usage_summary, purge_usage_data, record_usage_event};
+use cmds::shell::{open_url, get_default_browser, run_shell_command, get_shell_audit_log, open_ssh_session, run_cli_tool, run_history_command};
+use cmds::files::{read_file, write_file, get_file_preview, download_and_open_file};
+use cmds::marketplace::{marketplace_list, marketplace_search, marketplace_get_facets, marketplace_install, marketplace_uninstall, marketplace_update, marketplace_check_updates, marketplace_get_plugin, get_installed_plugins};
+use cmds::settings::{get_settings, get_setting, set_setting, update_settings, validate_settings, reset_settings, init_preferences, get_hotkey, set_hotkey, unregister_all_hotkeys, reregister_hotkey, check_hotkey_conflicts, get_settings_file_path, get_scoring_weights, set_scoring_weights, reset_scoring_weights};
+use cmds::window::{get_screen_info, resize_window_smart, close_window, save_current_layout, apply_layout, list_layouts, delete_layout};
+use cmds::selection::capture_selection_and_show;
+use cmds::performance::{get_performance_metrics, check_performance_requirements, record_performance_event, get_average_search_time};
+use cmds::plugin_performance::{record_plugin_execution, report_plugin_memory, get_plugin_performance, get_performance_summary, get_slow_operations};
+use cmds::abbreviation::{get_abbreviation_config, save_abbreviation_config, add_abbreviation, update_abbreviation, delete_abbreviation, export_abbreviation_config, import_abbreviation_config, set_category_enabled, delete_category, search_abbreviations, record_abbreviation_use};
 use cmds::debug::{write_debug_log, clear_debug_log, read_debug_log};
-
-/// Get the default global hotkey for the current platform.
-/// Simplifies duplicate default hotkey logic throughout the codebase.
-fn default_hotkey() -> String {
-    #[cfg(target_os = "macos")]
-    return "Cmd+Shift+K".to_string();
-    #[cfg(not(target_os = "macos"))]
-    return "Ctrl+Shift+K".to_string();
-}
+use cmds::crash::{list_crash_reports, get_crash_report, delete_crash_report};
+use cmds::maintenance::run_db_maintenance;
+use cmds::backup::{create_backup, restore_backup};
+use cmds::data_reset::reset_app_data;
+use cmds::data_location::migrate_data_location;
+use cmds::retention::{get_storage_report, prune_storage};
+use cmds::data_access::{get_data_access_status, open_privacy_settings};
+use cmds::onboarding::{get_onboarding_state, complete_onboarding_step, skip_onboarding};
+use cmds::lifecycle::{quit_app, restart_app, check_app_update};
+use cmds::connectivity::{get_connectivity_status, set_offline_mode};
+use cmds::openers::{get_openers, set_opener, remove_opener, open_result, reveal_in_folder};
+use cmds::drop::{handle_dropped_files, run_drop_action};
+use cmds::plugin_host::plugin_host_call;
+use cmds::notifications::{send_notification, get_notification_history, set_plugin_notifications_muted};
+use cmds::startup::get_startup_report;
+use cmds::tasks::{get_background_tasks, run_task_now};
 
 /// Parse hotkey string (e.g., "Cmd+Space", "Ctrl+Shift+A") into a Shortcut
 pub fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
@@ -192,9 +232,11 @@ fn parse_key_code(key: &str) -> Result<Code, String> {
 fn toggle_window(window: tauri::Window) -> Result<(), String> {
     if window.is_visible().map_err(|e| e.to_string())? {
         window.hide().map_err(|e| e.to_string())?;
+        cmds::window::main_window_hidden(window.app_handle());
     } else {
         window.show().map_err(|e| e.to_string())?;
         window.set_focus().map_err(|e| e.to_string())?;
+        cmds::window::main_window_shown(window.app_handle());
     }
     Ok(())
 }
@@ -235,6 +277,7 @@ fn hide_plugin_popup(_app: tauri::AppHandle) -> Result<(), String> {
 #[tauri::command]
 fn hide_window(window: tauri::Window) -> Result<(), String> {
     window.hide().map_err(|e| e.to_string())?;
+    cmds::window::main_window_hidden(window.app_handle());
     Ok(())
 }
 
@@ -265,6 +308,7 @@ fn show_window(window: tauri::Window) -> Result<(), String> {
 
     window.show().map_err(|e| e.to_string())?;
     window.set_focus().map_err(|e| e.to_string())?;
+    cmds::window::main_window_shown(window.app_handle());
 
     println!("[show_window] Window shown and focused at ({}, {})", x, y);
 
@@ -273,26 +317,49 @@ fn show_window(window: tauri::Window) -> Result<(), String> {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    tauri::Builder::default()
+    // Nothing is recorded until setup has configured the reporter
+    let crash_reporter = services::crash_reporter::CrashReporter::new();
+    crash_reporter.install_panic_hook();
+    let setup_reporter = crash_reporter.clone();
+
+    // Boot stages are timed from here
+    let startup_timings = services::startup::StartupTimings::new();
+
+    let result = tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         .plugin(tauri_plugin_shell::init())
-        .setup(|app| {
-            // Initialize app monitor state
-            app.manage(AppState {
-                app_monitor: std::sync::Mutex::new(services::app_monitor::AppMonitor::new()),
-            });
-
-            // Initialize search state
-            app.manage(SearchState {
-                app_monitor: std::sync::Mutex::new(services::app_monitor::AppMonitor::new()),
-                file_indexer: std::sync::Mutex::new(None),
-            });
-
-            // Initialize performance monitor state
-            app.manage(PerformanceState {
-                monitor: std::sync::Arc::new(std::sync::Mutex::new(services::performance::PerformanceMonitor::new())),
-            });
+        .plugin(tauri_plugin_notification::init())
+        .setup(move |app| {
+            app.manage(startup_timings);
+            let timings = app.state::<services::startup::StartupTimings>();
+
+            // Every data path resolves through the data and temp roots
+            let saved_settings = cmds::settings::load_settings(app.handle()).unwrap_or_default();
+            app.manage(services::app_paths::DataLocations::from_settings(&saved_settings));
+
+            // Crash reports only when the user opted in
+            if let Ok(data_dir) = services::app_paths::data_dir(app.handle()) {
+                setup_reporter.configure(&data_dir, Some(data_dir.join("debug.log")));
+            }
+
+            // Settings and the state commands rely on. Plugins, databases,
+            // app scans and watchers load once the app is interactive.
+            let startup_settings = cmds::startup::init_core(app.handle(), &timings);
+            setup_reporter.set_enabled(startup_settings.crash_reports);
+            app.manage(setup_reporter.clone());
+
+            // Databases are checked if the last session didn't remove its sentinel
+            if let Ok(data_dir) = services::app_paths::data_dir(app.handle()) {
+                if services::db_maintenance::begin_session(&data_dir) {
+                    println!("[Setup] Previous session did not shut down cleanly, checking databases");
+                    cmds::maintenance::check_after_unclean_shutdown(app.handle().clone());
+                }
+            }
+
+            cmds::lifecycle::register_shutdown_hooks(app.handle());
+
+            let window_span = timings.span(services::startup::StartupStage::Window);
 
             // Clear old window state to ensure window centers properly
             if let Ok(config_dir) = app.path().app_config_dir() {
@@ -305,26 +372,18 @@ pub fn run() {
             }
 
             // Get the main window
-            let window = app.get_webview_window("main").unwrap();
+            let window = app.get_webview_window(services::windows::MAIN_WINDOW).unwrap();
+            services::window_layouts::apply_startup_layout(
+                app.handle(),
+                &window,
+                startup_settings.default_window_layout.as_deref(),
+            );
+            drop(window_span);
 
-            // Load hotkey from settings or use default
-            let settings_path = app.path().app_config_dir()
-                .map_err(|e| format!("Failed to get config dir: {}", e))?;
-            let settings_file = settings_path.join("settings.json");
+            let _hotkey_span = timings.span(services::startup::StartupStage::Hotkey);
 
-            // Load hotkey from settings or use default (simplified with helper function)
-            let hotkey_str = if settings_file.exists() {
-                use std::fs;
-                fs::read_to_string(&settings_file)
-                    .ok()
-                    .and_then(|content| {
-                        serde_json::from_str::<crate::models::preferences::AppSettings>(&content).ok()
-                    })
-                    .map(|settings| settings.global_hotkey)
-                    .unwrap_or_else(|| default_hotkey())
-            } else {
-                default_hotkey()
-            };
+            // Load hotkey from settings (the default when there are none)
+            let hotkey_str = startup_settings.global_hotkey.clone();
 
             println!("[GlobalShortcut] Registering hotkey: {}", hotkey_str);
 
@@ -349,6 +408,7 @@ pub fn run() {
 
                 if is_visible {
                     let _ = window_clone.hide();
+                    cmds::window::main_window_hidden(window_clone.app_handle());
                     println!("[GlobalShortcut] Window hidden");
                 } else {
                     // 显示窗口前先定位到鼠标所在屏幕的中心偏上位置
@@ -356,7 +416,9 @@ pub fn run() {
                     let window_height = 600u32;
 
                     // 获取鼠标位置
-                    if let Ok(cursor_pos) = window_clone.cursor_position() {
+                    if services::window_layouts::position_for_active_layout(&window_clone) {
+                        println!("[GlobalShortcut] Positioned by the active window layout");
+                    } else if let Ok(cursor_pos) = window_clone.cursor_position() {
                         let cursor_x = cursor_pos.x as i32;
                         let cursor_y = cursor_pos.y as i32;
 
@@ -467,7 +529,8 @@ pub fn run() {
                     println!("[GlobalShortcut] Window shown and focused");
 
                     // 发送事件到前端，通知窗口已显示并聚焦
-                    let _ = window_clone.emit("window-shown", ());
+                    let _ = events::emit(&window_clone, WindowShown);
+                    cmds::window::main_window_shown(window_clone.app_handle());
                 }
 
                 // Reset the flag after a short delay
@@ -478,6 +541,14 @@ pub fn run() {
                 });
             }).map_err(|e| format!("Failed to register global shortcut: {}", e))?;
 
+            // Optional hotkey that opens the launcher with the selected text
+            if let Err(e) = cmds::selection::register_selection_hotkey(
+                app.handle(),
+                startup_settings.selection_hotkey.as_deref(),
+            ) {
+                eprintln!("[GlobalShortcut] {}", e);
+            }
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -491,8 +562,15 @@ pub fn run() {
             hide_plugin_popup,
             get_screen_info,
             resize_window_smart,
+            close_window,
+            save_current_layout,
+            apply_layout,
+            list_layouts,
+            delete_layout,
+            capture_selection_and_show,
             // App commands
             get_installed_apps,
+            rescan_apps,
             launch_app,
             track_app_usage,
             get_app_icon,
@@ -500,14 +578,34 @@ pub fn run() {
             get_recently_used,
             // Search commands
             unified_search,
+            unified_search_streaming,
+            get_results_page,
+            get_search_performance,
+            diagnose_search,
+            get_query_history,
+            remember_query,
+            clear_query_history,
+            record_emoji_usage,
+            get_builtin_icons,
+            search_more,
+            preview_exclusion,
+            get_default_suggestions,
             get_search_stats,
             search_files,
             search_browser_data,
             update_browser_cache,
+            get_bookmark_folders,
+            get_bookmarks_in_folder,
+            open_bookmark_folder,
             index_files,
             get_file_index_stats,
             start_file_indexer,
             stop_file_indexer,
+            list_index_paths,
+            add_index_path,
+            remove_index_path,
+            reindex_path,
+            get_path_breadcrumbs,
             // Clipboard commands
             get_clipboard_history,
             get_clipboard_item,
@@ -517,6 +615,7 @@ pub fn run() {
             get_clipboard_settings,
             set_clipboard_settings,
             search_clipboard,
+            set_clipboard_item_pinned,
             write_clipboard_text,
             // Plugin commands
             // ✅ 安全加固：移除 plugin_list，只允许从市场安装插件
@@ -526,10 +625,20 @@ pub fn run() {
             enable_plugin,
             disable_plugin,
             get_plugin_manifest,
+            get_trigger_completions,
+            resolve_trigger_completions,
+            get_cached_plugin_result,
+            cache_plugin_result,
+            invalidate_plugin_cache,
             reload_plugin,
             grant_plugin_permission,
             revoke_plugin_permission,
             get_plugin_permissions,
+            get_pending_consents,
+            respond_to_consent,
+            set_plugin_shell_allowlist,
+            get_plugin_security_report,
+            plugin_host_call,
             set_plugin_setting,
             get_plugin_setting,
             validate_plugin_manifest,
@@ -537,6 +646,7 @@ pub fn run() {
             get_plugin_health,
             check_plugin_health,
             get_plugin_usage_stats,
+            get_plugin_storage_usage,
             bulk_enable_plugins,
             bulk_disable_plugins,
             bulk_uninstall_plugins,
@@ -546,12 +656,20 @@ pub fn run() {
             plugin_install,
             plugin_get_install_status,
             plugin_cancel_install,
+            cleanup_temp_files,
             plugin_validate_package_from_buffer,
             plugin_extract_package_from_buffer,
+            plugin_begin_upload,
+            plugin_append_upload_chunk,
+            plugin_finish_upload,
+            plugin_cancel_upload,
+            plugin_download_package,
             // Enable/Disable/Uninstall commands (US3/US4)
             plugin_enable,
             plugin_disable,
+            resolve_trigger_conflict,
             plugin_uninstall,
+            restore_archived_settings,
             // Plugin abbreviation commands
             get_plugin_abbreviations,
             save_plugin_abbreviations,
@@ -562,15 +680,82 @@ pub fn run() {
             check_performance_requirements,
             record_performance_event,
             get_average_search_time,
+            // Plugin performance commands
+            record_plugin_execution,
+            report_plugin_memory,
+            get_plugin_performance,
+            get_performance_summary,
+            get_slow_operations,
+            // Privacy commands
+            set_privacy_mode,
+            get_privacy_mode,
+            // Process commands
+            search_processes,
+            end_process,
+            // Usage analytics commands
+            get_usage_summary,
+            purge_usage_data,
+            record_usage_event,
+            // Crash report commands
+            list_crash_reports,
+            get_crash_report,
+            delete_crash_report,
+            // Database maintenance commands
+            run_db_maintenance,
+            // Notification commands
+            send_notification,
+            get_notification_history,
+            set_plugin_notifications_muted,
+            // Backup commands
+            create_backup,
+            restore_backup,
+            // Data reset commands
+            reset_app_data,
+            migrate_data_location,
+            get_storage_report,
+            prune_storage,
+            get_data_access_status,
+            open_privacy_settings,
+            // Onboarding commands
+            get_onboarding_state,
+            complete_onboarding_step,
+            skip_onboarding,
+            // App lifecycle commands
+            quit_app,
+            restart_app,
+            check_app_update,
+            get_startup_report,
+            // Background task commands
+            get_background_tasks,
+            run_task_now,
+            // Connectivity commands
+            get_connectivity_status,
+            set_offline_mode,
+            // Opener commands
+            get_openers,
+            set_opener,
+            remove_opener,
+            open_result,
+            reveal_in_folder,
+            handle_dropped_files,
+            run_drop_action,
             // Shell commands
             open_url,
             get_default_browser,
+            run_shell_command,
+            get_shell_audit_log,
+            open_ssh_session,
+            run_cli_tool,
+            run_history_command,
             // File system commands
             read_file,
             write_file,
+            get_file_preview,
+            download_and_open_file,
             // Marketplace commands
             marketplace_list,
             marketplace_search,
+            marketplace_get_facets,
             marketplace_install,
             marketplace_uninstall,
             marketplace_update,
@@ -582,6 +767,7 @@ pub fn run() {
             get_setting,
             set_setting,
             update_settings,
+            validate_settings,
             reset_settings,
             init_preferences,
             get_hotkey,
@@ -590,6 +776,9 @@ pub fn run() {
             reregister_hotkey,
             check_hotkey_conflicts,
             get_settings_file_path,
+            get_scoring_weights,
+            set_scoring_weights,
+            reset_scoring_weights,
             // Debug commands
             write_debug_log,
             clear_debug_log,
@@ -601,7 +790,36 @@ pub fn run() {
             delete_abbreviation,
             export_abbreviation_config,
             import_abbreviation_config,
+            set_category_enabled,
+            delete_category,
+            search_abbreviations,
+            record_abbreviation_use,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!());
+
+    match result {
+        Ok(app) => app.run(|handle, event| match event {
+            // The window and hotkey are up; load the rest in the background
+            tauri::RunEvent::Ready => cmds::startup::start_deferred(handle),
+            tauri::RunEvent::WindowEvent {
+                label,
+                event: tauri::WindowEvent::DragDrop(tauri::DragDropEvent::Drop { paths, .. }),
+                ..
+            } if label == services::windows::MAIN_WINDOW => cmds::drop::files_dropped(handle, paths),
+            tauri::RunEvent::Exit => {
+                if let Err(e) = cmds::plugin_performance::flush_rollups(handle) {
+                    eprintln!("[PluginPerformance] {}", e);
+                }
+                if let Ok(data_dir) = services::app_paths::data_dir(handle) {
+                    services::db_maintenance::end_session(&data_dir);
+                }
+            }
+            _ => {}
+        }),
+        Err(e) => {
+            crash_reporter.record("tauri_error", e.to_string(), None);
+            eprintln!("error while running tauri application: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
@@ -21,6 +21,25 @@ pub struct ApplicationEntry {
     /// Alternate names for search (e.g., .app filename, aliases)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub alternate_names: Option<Vec<String>>,
+    /// Platform bundle identifier (CFBundleIdentifier on macOS, desktop file id on Linux)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    /// Where the entry was discovered, which decides how it's launched
+    #[serde(default)]
+    pub source: AppSource,
+}
+
+/// Kind of launchable entry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AppSource {
+    /// An app found in the scanned application directories
+    #[default]
+    Installed,
+    /// A macOS System Settings pane
+    SettingsPane,
+    /// A Windows Store (UWP/AppX) app
+    StoreApp,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
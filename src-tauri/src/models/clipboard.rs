@@ -12,7 +12,13 @@ pub struct ClipboardItem {
     pub content_type: ClipboardContentType,
     pub text: Option<String>,
     pub image_path: Option<PathBuf>,
+    /// Small JPEG preview of an image item, for list rendering
+    #[serde(default)]
+    pub thumbnail_path: Option<PathBuf>,
     pub hash: String,
+    /// Hash of how an image looks, so re-encoded copies are recognized
+    #[serde(default)]
+    pub perceptual_hash: Option<String>,
     pub timestamp: i64,
     pub is_sensitive: bool,
     pub app_source: Option<String>,
@@ -32,6 +38,20 @@ pub struct ClipboardSettings {
     pub retention_days: i64,
     pub sensitive_expiry_minutes: i64,
     pub enabled: bool,
+    /// Images bigger than this are stored downscaled
+    #[serde(default = "default_max_image_bytes")]
+    pub max_image_bytes: u64,
+    /// Longest side of image thumbnails, in pixels
+    #[serde(default = "default_thumbnail_size")]
+    pub thumbnail_size: u32,
+}
+
+fn default_max_image_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_thumbnail_size() -> u32 {
+    256
 }
 
 impl Default for ClipboardSettings {
@@ -41,6 +61,8 @@ impl Default for ClipboardSettings {
             retention_days: 30,
             sensitive_expiry_minutes: 2,
             enabled: true,
+            max_image_bytes: default_max_image_bytes(),
+            thumbnail_size: default_thumbnail_size(),
         }
     }
 }
@@ -1,11 +1,13 @@
 pub mod app;
 pub mod clipboard;
+pub mod pagination;
 pub mod plugin;
 pub mod preferences;
 pub mod screen_info;
+pub mod search;
 pub mod view_config;
 pub mod window_layout;
 
 pub use screen_info::ScreenInfo;
 pub use view_config::ViewConfig;
-pub use window_layout::CalculatedWindowLayout;
+pub use window_layout::{CalculatedWindowLayout, LayoutPosition, MonitorArea, WindowLayout};
@@ -0,0 +1,135 @@
+/**
+ * Pagination Model
+ * Keyset pages for lists that keep growing while the UI scrolls through them
+ */
+
+use base64::prelude::*;
+use serde::Serialize;
+
+/// Most items a page holds, whatever limit a caller asks for
+pub const MAX_PAGE_SIZE: usize = 1_000;
+
+/// Position of the last item of a page: its sort key, most significant value
+/// first, and its id as the tiebreak. Lists are sorted descending, and the
+/// next page holds only items that sort strictly after the cursor, so items
+/// inserted ahead of it (new clipboard entries at the head) never shift
+/// later pages: nothing is skipped or repeated. An item whose sort key
+/// changes between pages may still move across the cursor.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PageCursor {
+    pub key: Vec<i64>,
+    pub id: String,
+}
+
+impl PageCursor {
+    pub fn new(key: Vec<i64>, id: impl Into<String>) -> Self {
+        Self { key, id: id.into() }
+    }
+
+    /// Opaque token handed to the frontend as `next_cursor`
+    pub fn encode(&self) -> String {
+        let key: Vec<String> = self.key.iter().map(|k| k.to_string()).collect();
+        BASE64_URL_SAFE_NO_PAD.encode(format!("{}:{}", key.join(","), self.id))
+    }
+
+    pub fn decode(token: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid page cursor: {}", token);
+        let bytes = BASE64_URL_SAFE_NO_PAD.decode(token).map_err(|_| invalid())?;
+        let raw = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let (key, id) = raw.split_once(':').ok_or_else(invalid)?;
+        let key = key
+            .split(',')
+            .map(|k| k.parse().map_err(|_| invalid()))
+            .collect::<Result<Vec<i64>, String>>()?;
+        Ok(Self::new(key, id))
+    }
+
+    /// Decode an optional token as passed to commands
+    pub fn parse(token: Option<&str>) -> Result<Option<Self>, String> {
+        token.map(Self::decode).transpose()
+    }
+}
+
+/// One page of a list
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// Pass back to get the next page; None on the last page
+    pub next_cursor: Option<String>,
+    /// Items in the whole list when this page was read
+    pub total: usize,
+}
+
+impl<T> Page<T> {
+    /// Page through `items`, which must be sorted descending by `cursor_of`
+    pub fn from_sorted(
+        items: Vec<T>,
+        after: Option<&PageCursor>,
+        limit: usize,
+        cursor_of: impl Fn(&T) -> PageCursor,
+    ) -> Self {
+        let total = items.len();
+        let mut items: Vec<T> = items
+            .into_iter()
+            .filter(|item| after.is_none_or(|after| cursor_of(item) < *after))
+            .collect();
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+        Self::new(items, has_more, total, cursor_of)
+    }
+
+    /// A page whose items were already cut at `limit`; `has_more` says
+    /// whether anything follows them
+    pub fn new(items: Vec<T>, has_more: bool, total: usize, cursor_of: impl Fn(&T) -> PageCursor) -> Self {
+        let next_cursor = if has_more {
+            items.last().map(|item| cursor_of(item).encode())
+        } else {
+            None
+        };
+        Self {
+            items,
+            next_cursor,
+            total,
+        }
+    }
+
+    /// The same page with its items converted
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+            total: self.total,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cursor_of(item: &(i64, &str)) -> PageCursor {
+        PageCursor::new(vec![item.0], item.1)
+    }
+
+    #[test]
+    fn test_cursor_round_trips() {
+        let cursor = PageCursor::new(vec![12, -3], "item:with:colons");
+        assert_eq!(PageCursor::decode(&cursor.encode()), Ok(cursor));
+        assert!(PageCursor::decode("not a cursor").is_err());
+        assert_eq!(PageCursor::parse(None), Ok(None));
+    }
+
+    #[test]
+    fn test_pages_split_ties_by_id() {
+        let items = vec![(3, "c"), (2, "b"), (2, "a"), (1, "z")];
+
+        let first = Page::from_sorted(items.clone(), None, 2, cursor_of);
+        assert_eq!(first.items, vec![(3, "c"), (2, "b")]);
+        assert_eq!(first.total, 4);
+
+        let after = PageCursor::decode(first.next_cursor.as_deref().unwrap()).unwrap();
+        let second = Page::from_sorted(items, Some(&after), 2, cursor_of);
+        assert_eq!(second.items, vec![(2, "a"), (1, "z")]);
+        assert_eq!(second.next_cursor, None);
+    }
+}
@@ -9,6 +9,30 @@ use std::collections::HashMap;
 // Plugin Health
 // ============================================================================
 
+/// How a plugin's declared API version relates to this host's API; see
+/// services::plugin_api
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApiCompatibility {
+    /// Written for this host API
+    #[default]
+    Compatible,
+    /// Written for an older API this host still supports; loads with a
+    /// warning in health
+    Legacy,
+    /// Needs a newer host; refused at install
+    RequiresNewerHost,
+    /// Below the supported range, or not a valid version; quarantined
+    Incompatible,
+}
+
+impl ApiCompatibility {
+    /// Whether the plugin may be loaded at all
+    pub fn is_loadable(self) -> bool {
+        matches!(self, ApiCompatibility::Compatible | ApiCompatibility::Legacy)
+    }
+}
+
 /// Plugin health status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -66,6 +90,22 @@ pub struct Plugin {
     pub entry_point: String,
     pub triggers: Vec<PluginTrigger>,
     pub settings: HashMap<String, serde_json::Value>,
+    /// Host API version from the manifest; None when it doesn't declare one
+    #[serde(default)]
+    pub api_version: Option<String>,
+    #[serde(default)]
+    pub api_compatibility: ApiCompatibility,
+    /// Ships with the app: can be disabled, not uninstalled
+    #[serde(default)]
+    pub protected: bool,
+    /// Triggers that don't fire because another plugin owns a keyword they
+    /// collide with; see `resolve_trigger_conflict`
+    #[serde(default)]
+    pub suppressed_triggers: Vec<String>,
+    /// Homepage, repository and license from the manifest or package.json,
+    /// with only http(s) homepage and repository URLs
+    #[serde(flatten)]
+    pub links: PluginLinks,
 
     // === Installation fields ===
     pub health: PluginHealth,
@@ -75,8 +115,18 @@ pub struct Plugin {
     pub source: PluginSource, // Installation source
 }
 
+/// A plugin as an install or enable/disable left it, with the registry
+/// revision of that change to hand to `plugin_list` or
+/// `get_installed_plugins` as `min_revision`
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginChange {
+    #[serde(flatten)]
+    pub plugin: Plugin,
+    pub state_revision: u64,
+}
+
 /// Plugin installation source
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PluginSource {
     Marketplace,
@@ -147,6 +197,41 @@ pub struct PluginTrigger {
     pub keyword: String,
     pub description: String,
     pub hotkey: Option<String>,
+    /// Arguments typed after the keyword, in order
+    pub args: Vec<TriggerArg>,
+    /// How long results may be reused for the same input, for triggers that
+    /// are pure functions of it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_ms: Option<u64>,
+}
+
+/// Argument of a trigger, e.g. the target language of "translate:".
+/// Fields stay loosely typed so a malformed schema reaches the validator,
+/// which reports it per field, instead of failing the whole manifest.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TriggerArg {
+    #[serde(default)]
+    pub name: String,
+    /// One of TRIGGER_ARG_TYPES
+    #[serde(rename = "type", default = "default_trigger_arg_type")]
+    pub arg_type: String,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub description: String,
+    /// Suggested values, offered as completions
+    #[serde(rename = "enum", default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<serde_json::Value>,
+    /// Ask the plugin for further suggestions as the user types
+    #[serde(default)]
+    pub dynamic: bool,
+}
+
+/// Types a trigger argument can have
+pub const TRIGGER_ARG_TYPES: &[&str] = &["string", "number", "boolean"];
+
+fn default_trigger_arg_type() -> String {
+    "string".to_string()
 }
 
 // ============================================================================
@@ -160,6 +245,10 @@ pub struct PluginUpdateInfo {
     pub current_version: String,    // Currently installed version
     pub latest_version: String,     // Latest version from npm
     pub has_update: bool,           // Whether an update is available
+    /// Latest version remembered from an earlier check, because the app
+    /// is offline
+    #[serde(default)]
+    pub stale: bool,
 }
 
 // Custom deserialization to support both string and object formats
@@ -188,6 +277,8 @@ impl<'de> Deserialize<'de> for PluginTrigger {
                     keyword: value.to_string(),
                     description: String::new(),
                     hotkey: None,
+                    args: Vec::new(),
+                    cache_ttl_ms: None,
                 })
             }
 
@@ -198,6 +289,8 @@ impl<'de> Deserialize<'de> for PluginTrigger {
                 let mut keyword = None;
                 let mut description = None;
                 let mut hotkey = None;
+                let mut args = None;
+                let mut cache_ttl_ms = None;
 
                 while let Some(key) = map.next_key::<String>()? {
                     match key.as_str() {
@@ -210,6 +303,12 @@ impl<'de> Deserialize<'de> for PluginTrigger {
                         "hotkey" => {
                             hotkey = Some(map.next_value()?);
                         }
+                        "args" => {
+                            args = Some(map.next_value()?);
+                        }
+                        "cache_ttl_ms" => {
+                            cache_ttl_ms = map.next_value()?;
+                        }
                         _ => {
                             map.next_value::<serde::de::IgnoredAny>()?;
                         }
@@ -222,6 +321,8 @@ impl<'de> Deserialize<'de> for PluginTrigger {
                     keyword,
                     description: description.unwrap_or_default(),
                     hotkey,
+                    args: args.unwrap_or_default(),
+                    cache_ttl_ms,
                 })
             }
         }
@@ -239,10 +340,245 @@ pub struct PluginManifest {
     pub permissions: Vec<String>,
     pub entry: String,
     pub triggers: Vec<PluginTrigger>,
+    /// Host API version the plugin is written for, e.g. "1.1.0". Required
+    /// for new plugins; older manifests without it are treated as 1.0.
+    #[serde(default)]
+    pub api_version: Option<String>,
+    /// Can be disabled but not uninstalled. Only honored for first-party
+    /// plugins on the bundled allowlist.
+    #[serde(default)]
+    pub protected: bool,
+    /// Files the plugin takes when they're dropped on the launcher
+    #[serde(default)]
+    pub accepts_drops: Option<DropCapability>,
+    /// Where the project lives and its license; optional, and invalid
+    /// values only warn
+    #[serde(flatten)]
+    pub links: PluginLinks,
+}
+
+/// The `accepts_drops` capability of a manifest. Dropped paths go to one
+/// of the plugin's triggers as its input; reading them needs `fs:read`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DropCapability {
+    /// Extensions the plugin takes, without the dot; any file when empty.
+    /// Folders are never offered to plugins.
+    #[serde(default)]
+    pub extensions: Vec<String>,
+    /// Trigger the paths are passed to, the manifest's first when None
+    #[serde(default)]
+    pub trigger: Option<String>,
+    /// Label of the action, e.g. "Compress images"; the plugin's name when None
+    #[serde(default)]
+    pub title: Option<String>,
+}
+
+/// Project links and license of a plugin, shown on its detail page. Values
+/// that aren't text are dropped rather than failing the manifest; text that
+/// isn't a valid URL or license is kept for the validator to warn about,
+/// and `web_only` drops such URLs before they're shown.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PluginLinks {
+    /// Project website, meant to be an http(s) URL
+    #[serde(default, deserialize_with = "lenient_text")]
+    pub homepage: Option<String>,
+    /// Source repository, meant to be an http(s) URL
+    #[serde(default, deserialize_with = "lenient_text")]
+    pub repository: Option<String>,
+    /// SPDX license expression such as "MIT OR Apache-2.0", or a short name
+    #[serde(default, deserialize_with = "lenient_text")]
+    pub license: Option<String>,
+}
+
+/// Text of a package.json style field: a string, or the `url` or `type` of
+/// an object such as `{ "type": "git", "url": ".." }`. None when blank.
+fn link_text(value: &serde_json::Value) -> Option<String> {
+    let text = value.get("url").or_else(|| value.get("type")).unwrap_or(value);
+    text.as_str().map(str::trim).filter(|s| !s.is_empty()).map(String::from)
 }
 
+fn lenient_text<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(value.as_ref().and_then(link_text))
+}
+
+impl PluginLinks {
+    /// Links from an npm package.json, or its `etools` block. `repository`
+    /// may be an object with a `url` or a shorthand such as "owner/repo"
+    /// or "gitlab:owner/repo", and `license` the legacy `{ "type": .. }`.
+    pub fn from_package_json(package: &serde_json::Value) -> Self {
+        let field = |name: &str| package.get(name).and_then(link_text);
+        Self {
+            homepage: field("homepage"),
+            repository: field("repository").map(|repository| repository_url(&repository)),
+            license: field("license"),
+        }
+    }
+
+    /// These links, with fields they lack taken from `fallback`
+    pub fn or(self, fallback: PluginLinks) -> PluginLinks {
+        PluginLinks {
+            homepage: self.homepage.or(fallback.homepage),
+            repository: self.repository.or(fallback.repository),
+            license: self.license.or(fallback.license),
+        }
+    }
+
+    /// These links without a homepage or repository that isn't an http(s)
+    /// URL, so only web pages are offered to open
+    pub fn web_only(self) -> PluginLinks {
+        PluginLinks {
+            homepage: self.homepage.filter(|url| is_web_url(url)),
+            repository: self.repository.filter(|url| is_web_url(url)),
+            license: self.license,
+        }
+    }
+}
+
+/// Whether `url` is an http(s) URL with a host
+pub fn is_web_url(url: &str) -> bool {
+    url::Url::parse(url.trim())
+        .is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host_str().is_some_and(|h| !h.is_empty()))
+}
+
+/// Browsable URL of an npm `repository` value. Git URLs and hosted
+/// shorthands become https; anything else is kept for validation to flag.
+fn repository_url(repository: &str) -> String {
+    const HOSTS: &[(&str, &str)] =
+        &[("github:", "github.com"), ("gitlab:", "gitlab.com"), ("bitbucket:", "bitbucket.org")];
+
+    let url = repository.strip_prefix("git+").unwrap_or(repository);
+    let url = if let Some((prefix, host)) = HOSTS.iter().find(|(prefix, _)| url.starts_with(prefix)) {
+        format!("https://{}/{}", host, &url[prefix.len()..])
+    } else if let Some(path) = url.strip_prefix("git@") {
+        // scp-like "git@github.com:owner/repo.git"
+        format!("https://{}", path.replacen(':', "/", 1))
+    } else if let Some(path) = url.strip_prefix("git://") {
+        format!("https://{}", path)
+    } else if !url.contains(':') && url.matches('/').count() == 1 {
+        // npm reads a bare "owner/repo" as GitHub
+        format!("https://github.com/{}", url)
+    } else {
+        url.to_string()
+    };
+    url.strip_suffix(".git").map(String::from).unwrap_or(url)
+}
+
+impl PluginManifest {
+    /// Rewrite legacy permission names to their canonical form. Unknown
+    /// names are kept so validators can report them.
+    pub fn normalize_permissions(&mut self) {
+        for name in &mut self.permissions {
+            if let Ok(permission) = name.parse::<PluginPermission>() {
+                *name = permission.as_str().to_string();
+            }
+        }
+    }
+
+    /// Parsed permissions; fails on the first unknown name rather than dropping it
+    pub fn parsed_permissions(&self) -> Result<Vec<PluginPermission>, String> {
+        self.permissions.iter().map(|name| name.parse()).collect()
+    }
+
+    pub fn has_permission(&self, permission: &PluginPermission) -> bool {
+        self.permissions
+            .iter()
+            .any(|name| name.parse::<PluginPermission>().as_ref() == Ok(permission))
+    }
+
+    /// Trigger dropped files are passed to: the one `accepts_drops` names,
+    /// else the first. None when the plugin doesn't take drops.
+    pub fn drop_trigger(&self) -> Option<&str> {
+        let drops = self.accepts_drops.as_ref()?;
+        drops
+            .trigger
+            .as_deref()
+            .or_else(|| self.triggers.first().map(|trigger| trigger.keyword.as_str()))
+    }
+}
+
+/// Permissions a plugin can request and be granted. Serializes to the
+/// canonical snake_case name; the aliases are older spellings still
+/// accepted in manifests ("clipboard:read" from the validator, "read:clipboard"
+/// from the plugin SDK) and in plugin_permissions.json (PascalCase).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginPermission {
+    #[serde(alias = "clipboard:read", alias = "read:clipboard", alias = "ReadClipboard")]
+    ReadClipboard,
+    #[serde(alias = "clipboard:write", alias = "write:clipboard", alias = "WriteClipboard")]
+    WriteClipboard,
+    #[serde(alias = "fs:read", alias = "read:files", alias = "ReadFile")]
+    ReadFile,
+    #[serde(alias = "fs:write", alias = "write:files", alias = "WriteFile")]
+    WriteFile,
+    #[serde(alias = "network:request", alias = "Network")]
+    Network,
+    #[serde(alias = "shell:execute", alias = "Shell")]
+    Shell,
+    #[serde(alias = "show:notification", alias = "Notification")]
+    Notification,
+    #[serde(alias = "plugin:manage", alias = "ManagePlugins")]
+    ManagePlugins,
+}
+
+impl PluginPermission {
+    pub const ALL: [PluginPermission; 8] = [
+        PluginPermission::ReadClipboard,
+        PluginPermission::WriteClipboard,
+        PluginPermission::ReadFile,
+        PluginPermission::WriteFile,
+        PluginPermission::Network,
+        PluginPermission::Shell,
+        PluginPermission::Notification,
+        PluginPermission::ManagePlugins,
+    ];
+
+    /// Canonical name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginPermission::ReadClipboard => "read_clipboard",
+            PluginPermission::WriteClipboard => "write_clipboard",
+            PluginPermission::ReadFile => "read_file",
+            PluginPermission::WriteFile => "write_file",
+            PluginPermission::Network => "network",
+            PluginPermission::Shell => "shell",
+            PluginPermission::Notification => "notification",
+            PluginPermission::ManagePlugins => "manage_plugins",
+        }
+    }
+
+    /// Whether `name` is a recognized but deprecated spelling
+    pub fn is_legacy_name(name: &str) -> bool {
+        name.parse::<PluginPermission>()
+            .is_ok_and(|permission| permission.as_str() != name)
+    }
+
+    /// Whether the permission reaches beyond the app (files, network, processes)
+    pub fn is_dangerous(&self) -> bool {
+        matches!(
+            self,
+            PluginPermission::WriteFile | PluginPermission::Network | PluginPermission::Shell
+        )
+    }
+}
+
+impl std::str::FromStr for PluginPermission {
+    type Err = String;
+
+    /// Parse a canonical or legacy permission name
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        serde_json::from_value(serde_json::Value::String(name.to_string()))
+            .map_err(|_| format!("Unknown permission: {}", name))
+    }
+}
+
+/// A permission with its grant state, for display
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PluginPermission {
+pub struct PluginPermissionInfo {
     pub name: String,
     pub description: String,
     pub granted: bool,
@@ -251,6 +587,8 @@ pub struct PluginPermission {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PluginPermissionsResponse {
     pub permissions: Vec<String>,
+    /// Latest answer per permission, including revoked ones
+    pub consents: Vec<crate::services::plugin_sandbox::PermissionConsent>,
     pub settings: std::collections::HashMap<String, serde_json::Value>,
 }
 
@@ -303,7 +641,7 @@ pub struct BulkOperationResult {
 // ============================================================================
 
 /// Plugin category
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PluginCategory {
     Productivity,
@@ -312,6 +650,38 @@ pub enum PluginCategory {
     Search,
     Media,
     Integration,
+    /// A category this version doesn't know
+    #[serde(other)]
+    Other,
+}
+
+impl PluginCategory {
+    pub const ALL: [PluginCategory; 7] = [
+        PluginCategory::Productivity,
+        PluginCategory::Developer,
+        PluginCategory::Utilities,
+        PluginCategory::Search,
+        PluginCategory::Media,
+        PluginCategory::Integration,
+        PluginCategory::Other,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginCategory::Productivity => "productivity",
+            PluginCategory::Developer => "developer",
+            PluginCategory::Utilities => "utilities",
+            PluginCategory::Search => "search",
+            PluginCategory::Media => "media",
+            PluginCategory::Integration => "integration",
+            PluginCategory::Other => "other",
+        }
+    }
+
+    /// Category with this name, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|c| c.as_str().eq_ignore_ascii_case(name))
+    }
 }
 
 /// Marketplace plugin
@@ -356,4 +726,193 @@ pub struct MarketplacePluginPage {
     pub page: u32,
     pub page_size: u32,
     pub has_more: bool,
+    /// Served from cache because the registry couldn't be reached
+    #[serde(default)]
+    pub stale: bool,
+}
+
+/// A facet value and how many plugins have it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub count: u32,
+}
+
+/// Category and tag counts for a marketplace query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarketplaceFacets {
+    /// Plugins the counts were computed over
+    pub total: u32,
+    /// Categories present, most plugins first
+    pub categories: Vec<FacetCount>,
+    /// Most common tags, most plugins first
+    pub tags: Vec<FacetCount>,
+    /// Computed from cached results because the registry couldn't be
+    /// reached
+    #[serde(default)]
+    pub stale: bool,
+    /// Computed over only some of the matches: too many to page through,
+    /// or offline with later pages not cached
+    #[serde(default)]
+    pub partial: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALIASES: &[(&str, PluginPermission)] = &[
+        ("clipboard:read", PluginPermission::ReadClipboard),
+        ("read:clipboard", PluginPermission::ReadClipboard),
+        ("clipboard:write", PluginPermission::WriteClipboard),
+        ("write:clipboard", PluginPermission::WriteClipboard),
+        ("fs:read", PluginPermission::ReadFile),
+        ("read:files", PluginPermission::ReadFile),
+        ("fs:write", PluginPermission::WriteFile),
+        ("write:files", PluginPermission::WriteFile),
+        ("network:request", PluginPermission::Network),
+        ("shell:execute", PluginPermission::Shell),
+        ("show:notification", PluginPermission::Notification),
+        ("plugin:manage", PluginPermission::ManagePlugins),
+    ];
+
+    fn manifest(permissions: &[&str]) -> PluginManifest {
+        PluginManifest {
+            name: "Legacy".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Uses old permission names".to_string(),
+            author: Some("tester".to_string()),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            entry: "index.js".to_string(),
+            triggers: vec![],
+            api_version: None,
+            protected: false,
+            accepts_drops: None,
+            links: PluginLinks::default(),
+        }
+    }
+
+    #[test]
+    fn test_links_from_package_json() {
+        let links = PluginLinks::from_package_json(&serde_json::json!({
+            "homepage": "https://example.com/hello",
+            "repository": { "type": "git", "url": "git+https://github.com/acme/hello.git" },
+            "license": "MIT",
+        }));
+        assert_eq!(links.homepage.as_deref(), Some("https://example.com/hello"));
+        assert_eq!(links.repository.as_deref(), Some("https://github.com/acme/hello"));
+        assert_eq!(links.license.as_deref(), Some("MIT"));
+
+        let repository = |value: serde_json::Value| {
+            PluginLinks::from_package_json(&serde_json::json!({ "repository": value })).repository
+        };
+        for (value, expected) in [
+            ("acme/hello", "https://github.com/acme/hello"),
+            ("gitlab:acme/hello", "https://gitlab.com/acme/hello"),
+            ("git@github.com:acme/hello.git", "https://github.com/acme/hello"),
+            ("git://github.com/acme/hello.git", "https://github.com/acme/hello"),
+            ("https://codeberg.org/acme/hello", "https://codeberg.org/acme/hello"),
+        ] {
+            assert_eq!(repository(serde_json::json!(value)).as_deref(), Some(expected), "{}", value);
+        }
+        assert_eq!(repository(serde_json::json!("  ")), None);
+
+        // Legacy license object, and no links at all
+        let legacy = PluginLinks::from_package_json(&serde_json::json!({ "license": { "type": "ISC" } }));
+        assert_eq!(legacy.license.as_deref(), Some("ISC"));
+        assert_eq!(PluginLinks::from_package_json(&serde_json::json!({})), PluginLinks::default());
+    }
+
+    #[test]
+    fn test_manifest_links_fall_back_to_package_json() {
+        let manifest: PluginManifest = serde_json::from_value(serde_json::json!({
+            "name": "Hello", "version": "1.0.0", "description": "Greets", "author": "acme",
+            "permissions": [], "entry": "index.js", "triggers": [],
+            "homepage": "https://hello.example",
+        }))
+        .unwrap();
+        let package = serde_json::json!({
+            "homepage": "https://www.npmjs.com/package/hello",
+            "repository": "acme/hello",
+            "license": "Apache-2.0",
+        });
+
+        let links = manifest.links.or(PluginLinks::from_package_json(&package));
+        assert_eq!(links.homepage.as_deref(), Some("https://hello.example"));
+        assert_eq!(links.repository.as_deref(), Some("https://github.com/acme/hello"));
+        assert_eq!(links.license.as_deref(), Some("Apache-2.0"));
+
+        // A misshapen link doesn't fail the manifest
+        let manifest: PluginManifest = serde_json::from_value(serde_json::json!({
+            "name": "Hello", "version": "1.0.0", "description": "Greets", "author": "acme",
+            "permissions": [], "entry": "index.js", "triggers": [],
+            "homepage": 42, "repository": { "type": "git", "url": "https://github.com/acme/hello" },
+        }))
+        .unwrap();
+        assert_eq!(manifest.links.homepage, None);
+        assert_eq!(manifest.links.repository.as_deref(), Some("https://github.com/acme/hello"));
+    }
+
+    #[test]
+    fn test_only_web_links_are_shown() {
+        let links = PluginLinks {
+            homepage: Some("javascript:alert(1)".to_string()),
+            repository: Some("file:///etc/passwd".to_string()),
+            license: Some("MIT".to_string()),
+        }
+        .web_only();
+        assert_eq!(links, PluginLinks { license: Some("MIT".to_string()), ..Default::default() });
+
+        let links = PluginLinks {
+            homepage: Some("https://hello.example".to_string()),
+            repository: Some("http://git.example/acme/hello".to_string()),
+            license: None,
+        };
+        assert_eq!(links.clone().web_only(), links);
+    }
+
+    #[test]
+    fn test_canonical_names_round_trip() {
+        for permission in PluginPermission::ALL {
+            let json = serde_json::to_value(&permission).unwrap();
+            assert_eq!(json, serde_json::json!(permission.as_str()));
+            assert_eq!(serde_json::from_value::<PluginPermission>(json).unwrap(), permission);
+            assert_eq!(permission.as_str().parse::<PluginPermission>().unwrap(), permission);
+            assert!(!PluginPermission::is_legacy_name(permission.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_legacy_aliases_round_trip_to_canonical() {
+        for (alias, expected) in ALIASES {
+            let parsed: PluginPermission = alias.parse().unwrap();
+            assert_eq!(&parsed, expected, "{}", alias);
+            assert!(PluginPermission::is_legacy_name(alias));
+            // Serializing writes the canonical name back
+            let json = serde_json::to_value(&parsed).unwrap();
+            assert_eq!(serde_json::from_value::<PluginPermission>(json).unwrap(), parsed);
+        }
+        assert!("clipboard:steal".parse::<PluginPermission>().is_err());
+    }
+
+    #[test]
+    fn test_normalize_keeps_unknown_names() {
+        let mut manifest = manifest(&["clipboard:read", "shell", "made:up"]);
+        manifest.normalize_permissions();
+
+        assert_eq!(manifest.permissions, vec!["read_clipboard", "shell", "made:up"]);
+        assert!(manifest.parsed_permissions().is_err());
+        assert!(manifest.has_permission(&PluginPermission::ReadClipboard));
+    }
+
+    #[test]
+    fn test_unknown_category_deserializes_as_other() {
+        let category: PluginCategory = serde_json::from_value(serde_json::json!("developer")).unwrap();
+        assert_eq!(category, PluginCategory::Developer);
+        let category: PluginCategory = serde_json::from_value(serde_json::json!("finance")).unwrap();
+        assert_eq!(category, PluginCategory::Other);
+        assert_eq!(serde_json::to_value(PluginCategory::Other).unwrap(), serde_json::json!("other"));
+        assert_eq!(PluginCategory::from_name("Media"), Some(PluginCategory::Media));
+        assert_eq!(PluginCategory::from_name("finance"), None);
+    }
 }
@@ -4,6 +4,7 @@
  */
 
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserPreference {
@@ -33,6 +34,10 @@ pub enum PreferenceCategory {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
+    /// Settings file schema version; files written before versioning read as 0
+    #[serde(default)]
+    pub schema_version: u32,
+
     // General
     #[serde(default)]
     pub startup_behavior: StartupBehavior,
@@ -40,6 +45,12 @@ pub struct AppSettings {
     pub language: String,
     #[serde(default = "default_global_hotkey")]
     pub global_hotkey: String,
+    /// Opens the launcher pre-filled with the selected text; unset disables it
+    #[serde(default)]
+    pub selection_hotkey: Option<String>,
+    /// Whether showing the launcher keeps the search it was hidden with
+    #[serde(default)]
+    pub on_show_behavior: OnShowBehavior,
 
     // Appearance
     #[serde(default)]
@@ -48,6 +59,9 @@ pub struct AppSettings {
     pub window_opacity: f32,
     #[serde(default = "default_show_menubar_icon")]
     pub show_menubar_icon: bool,
+    /// Skin tone for emoji that have skin tone variants
+    #[serde(default)]
+    pub emoji_skin_tone: SkinTone,
 
     // Features
     #[serde(default = "default_enable_clipboard")]
@@ -56,29 +70,424 @@ pub struct AppSettings {
     pub enable_file_search: bool,
     #[serde(default)]
     pub enable_browser_search: bool,
+    /// Match Chinese app names by pinyin; implied by a Chinese language
+    #[serde(default)]
+    pub enable_pinyin_search: bool,
+    /// Allow "> cmd" queries to run shell commands
+    #[serde(default)]
+    pub enable_shell_commands: bool,
 
     // Privacy
     #[serde(default = "default_anonymize_usage")]
     pub anonymize_usage: bool,
     #[serde(default)]
     pub crash_reports: bool,
+    /// Suppress notifications (they're still recorded in history)
+    #[serde(default)]
+    pub do_not_disturb: DoNotDisturb,
 
     // Advanced
     #[serde(default = "default_search_debounce_ms")]
     pub search_debounce_ms: u64,
+    /// Searches slower than this are logged with their slowest stage
+    #[serde(default = "default_search_budget_ms")]
+    pub search_budget_ms: u64,
     #[serde(default = "default_max_results")]
     pub max_results: usize,
     #[serde(default)]
     pub excluded_apps: Vec<String>,
+    /// List macOS System Settings panes alongside applications
+    #[serde(default = "default_index_settings_panes")]
+    pub index_settings_panes: bool,
+    /// List Windows Store apps alongside applications
+    #[serde(default = "default_index_store_apps")]
+    pub index_store_apps: bool,
     #[serde(default)]
     pub file_index_paths: Vec<String>,
+    /// Read Finder tags and download sources while indexing so `tag:` queries
+    /// can match them
+    #[serde(default)]
+    pub index_file_tags: bool,
+    /// Leading query tokens that scope a search to one provider, e.g. "f" -> "file"
+    #[serde(default = "default_query_prefixes")]
+    pub query_prefixes: HashMap<String, String>,
+    /// Maximum results per source before the global limit, e.g. "file" -> 10
+    #[serde(default = "default_source_limits")]
+    pub source_limits: HashMap<String, usize>,
+    /// Share of empty-query suggestion slots per section, e.g. "app" -> 4;
+    /// a missing or zero weight hides the section
+    #[serde(default = "default_suggestion_weights")]
+    pub suggestion_weights: HashMap<String, u32>,
+    /// How much each kind of match and past usage counts toward a result's score
+    #[serde(default)]
+    pub scoring_weights: ScoringWeights,
+    /// Rate limits for plugin host calls
+    #[serde(default)]
+    pub plugin_rate_limits: PluginRateLimits,
+    /// Programs every plugin with the shell permission may run, in
+    /// addition to each plugin's own allowlist
+    #[serde(default)]
+    pub shell_allowlist: Vec<String>,
+    /// Terminal command that opens an SSH session. "%h" is replaced by the
+    /// host; without it an ssh:// URL is appended ("open -a iTerm").
+    #[serde(default = "default_ssh_terminal_template")]
+    pub ssh_terminal_template: String,
+    /// Terminal command that runs a command-line tool from search. "%c" is
+    /// replaced by the tool's path; without it the path is appended.
+    #[serde(default = "default_cli_terminal_template")]
+    pub cli_terminal_template: String,
+    /// PATH directories left out of CLI tool search, as glob patterns
+    /// ("*/libexec*")
+    #[serde(default = "default_cli_excluded_dirs")]
+    pub cli_excluded_dirs: Vec<String>,
+    /// History files searched with "!", "~/" meaning the home directory.
+    /// The format (zsh, bash or fish) follows from the file name.
+    #[serde(default = "default_shell_history_files")]
+    pub shell_history_files: Vec<String>,
+    /// Regex hiding history commands that hold secrets, on top of the
+    /// clipboard's sensitive-content rules. Empty hides only those.
+    #[serde(default = "default_shell_history_exclude_pattern")]
+    pub shell_history_exclude_pattern: String,
+    /// Plugins scoring below this (0-100) need an explicit override to install
+    #[serde(default = "default_min_plugin_security_score")]
+    pub min_plugin_security_score: u8,
+    /// Largest plugin package accepted from uploads and downloads, in MB
+    #[serde(default = "default_max_plugin_package_mb")]
+    pub max_plugin_package_mb: u32,
+    /// Plugins whose peak memory exceeds this are flagged unhealthy, in MB
+    #[serde(default = "default_plugin_memory_warning_mb")]
+    pub plugin_memory_warning_mb: u32,
+    /// Space each plugin may use in its data directory, in MB
+    #[serde(default = "default_plugin_storage_quota_mb")]
+    pub plugin_storage_quota_mb: u32,
+    /// Plugin trigger results kept for reuse, across all plugins
+    #[serde(default = "default_plugin_cache_entries")]
+    pub plugin_cache_entries: usize,
+    /// Locked browser databases larger than this are skipped instead of
+    /// copied when refreshing the browser cache, in MB
+    #[serde(default = "default_max_browser_db_copy_mb")]
+    pub max_browser_db_copy_mb: u64,
+    /// History rows read per browser: this many of the most recently
+    /// visited pages plus this many of the most visited ones. Each refresh
+    /// holds them in memory and the browser cache keeps them on disk.
+    #[serde(default = "default_browser_history_limit")]
+    pub browser_history_limit: usize,
+    /// History pages visited fewer times than this are left out
+    #[serde(default = "default_browser_min_visits")]
+    pub browser_min_visits: u32,
+    /// App or command that opens results, by context: "browser_result",
+    /// "file_extension:<ext>" or "url_scheme:<scheme>". Results with no
+    /// opener use the system default.
+    #[serde(default)]
+    pub openers: BTreeMap<String, OpenerTarget>,
+    /// Proxy for all HTTP requests (http://, https:// or socks5://);
+    /// unset uses the system proxy
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    /// Latest-release JSON checked by `check_app_update`; unset uses the
+    /// project's GitHub releases
+    #[serde(default)]
+    pub update_endpoint: Option<String>,
+    /// Skip all network requests; network features use cached data
+    #[serde(default)]
+    pub offline_mode: bool,
+    /// Size, row and age limits for the app's stores, enforced daily
+    #[serde(default)]
+    pub retention: RetentionSettings,
+    /// Saved window layout applied at startup; unset keeps the default size
+    #[serde(default)]
+    pub default_window_layout: Option<String>,
+    /// Root of databases, plugins and state files; unset uses the
+    /// platform's data dir. Only `migrate_data_location` changes it, moving
+    /// the data along.
+    #[serde(default)]
+    pub data_dir: Option<String>,
+    /// Root for installer temp files; unset keeps them in the data dir
+    #[serde(default)]
+    pub temp_dir: Option<String>,
+}
+
+/// Limits for one store; unset limits don't apply
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StoreBudget {
+    pub max_rows: Option<u64>,
+    pub max_mb: Option<u64>,
+    /// Rows older than this are pruned regardless of size
+    pub max_age_days: Option<u32>,
+}
+
+/// Budgets per store. Logs only have a size limit.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetentionSettings {
+    pub files: StoreBudget,
+    pub browser: StoreBudget,
+    pub analytics: StoreBudget,
+    pub logs: StoreBudget,
+    /// Vacuum a database after pruning once this much space is free, in MB
+    pub vacuum_threshold_mb: u64,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            files: StoreBudget {
+                max_rows: Some(250_000),
+                ..StoreBudget::default()
+            },
+            browser: StoreBudget {
+                max_rows: Some(50_000),
+                max_mb: Some(100),
+                max_age_days: Some(90),
+            },
+            analytics: StoreBudget {
+                max_mb: Some(50),
+                max_age_days: Some(365),
+                ..StoreBudget::default()
+            },
+            logs: StoreBudget {
+                max_mb: Some(10),
+                ..StoreBudget::default()
+            },
+            vacuum_threshold_mb: 8,
+        }
+    }
+}
+
+/// Do-not-disturb: always on when `enabled`, otherwise during `schedule`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct DoNotDisturb {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub schedule: Option<QuietHours>,
+}
+
+/// Daily quiet hours in local time, "HH:MM"; may wrap past midnight (22:00–08:00)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+/// What opens a result for an opener context
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OpenerTarget {
+    /// An installed application, by its id from the app scan
+    App { id: String },
+    /// A program and its arguments; "%u" is replaced by the URL or path,
+    /// which is appended when there's no "%u"
+    Command { template: String },
+}
+
+/// Token bucket limit: sustained calls per second plus a burst allowance
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct RateLimit {
+    pub per_second: f64,
+    pub burst: u32,
+}
+
+/// Rate limits for plugin host calls, per plugin and capability
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PluginRateLimits {
+    #[serde(default = "default_plugin_rate_limit")]
+    pub default: RateLimit,
+    /// Per-capability limits, e.g. "notification" -> 1/s
+    #[serde(default = "default_plugin_rate_limit_overrides")]
+    pub overrides: HashMap<String, RateLimit>,
+}
+
+/// Score components providers add up when ranking a result. Each weight
+/// is 0.0–2.0; the defaults are the values search shipped with.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ScoringWeights {
+    /// The name equals the query (default 1.0)
+    pub exact: f64,
+    /// The name starts with the query (default 0.8)
+    pub starts_with: f64,
+    /// The name contains the query (default 0.5)
+    pub contains: f64,
+    /// The query is the name's initials, "vsc" for "Visual Studio Code"
+    /// (default 0.85); a prefix of the initials scores three quarters of this
+    pub initialism: f64,
+    /// Multiplier of log10 of the launch or visit count (default 0.1)
+    pub frequency: f64,
+}
+
+impl ScoringWeights {
+    /// Weights by name, in declaration order
+    pub fn entries(&self) -> [(&'static str, f64); 5] {
+        [
+            ("exact", self.exact),
+            ("starts_with", self.starts_with),
+            ("contains", self.contains),
+            ("initialism", self.initialism),
+            ("frequency", self.frequency),
+        ]
+    }
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            exact: 1.0,
+            starts_with: 0.8,
+            contains: 0.5,
+            initialism: 0.85,
+            frequency: 0.1,
+        }
+    }
+}
+
+impl PluginRateLimits {
+    /// Limit that applies to a capability
+    pub fn for_capability(&self, capability: &str) -> RateLimit {
+        self.overrides.get(capability).copied().unwrap_or(self.default)
+    }
+}
+
+impl Default for PluginRateLimits {
+    fn default() -> Self {
+        Self {
+            default: default_plugin_rate_limit(),
+            overrides: default_plugin_rate_limit_overrides(),
+        }
+    }
+}
+
+/// Settings migration steps; entry `n` upgrades a version `n` file to `n + 1`
+const SETTINGS_MIGRATIONS: &[fn(serde_json::Value) -> serde_json::Value] = &[
+    // 0 -> 1: unversioned files have the current shape; serde defaults fill the rest
+    |value| value,
+    // 1 -> 2: suggestions gained a browser section; saved weights would hide it
+    |mut value| {
+        if let Some(weights) = value.get_mut("suggestion_weights").and_then(|w| w.as_object_mut()) {
+            weights.entry("browser").or_insert_with(|| DEFAULT_BROWSER_SUGGESTION_WEIGHT.into());
+        }
+        value
+    },
+];
+
+/// Suggestion slots for pages opened from the launcher
+const DEFAULT_BROWSER_SUGGESTION_WEIGHT: u32 = 2;
+
+/// Current settings schema version
+pub const SETTINGS_SCHEMA_VERSION: u32 = SETTINGS_MIGRATIONS.len() as u32;
+
+/// Schema version of a raw settings value
+pub fn settings_schema_version(value: &serde_json::Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0) as u32
+}
+
+/// Run the migration chain on a raw settings value and parse the result
+pub fn migrate_settings(mut value: serde_json::Value) -> Result<AppSettings, String> {
+    let from = settings_schema_version(&value);
+    if from > SETTINGS_SCHEMA_VERSION {
+        return Err(format!(
+            "Settings schema version {} is newer than supported version {}",
+            from, SETTINGS_SCHEMA_VERSION
+        ));
+    }
+
+    for step in &SETTINGS_MIGRATIONS[from as usize..] {
+        value = step(value);
+    }
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema_version".to_string(), SETTINGS_SCHEMA_VERSION.into());
+    }
+
+    serde_json::from_value(value).map_err(|e| format!("Failed to parse settings: {}", e))
 }
 
 // Default functions for serde
+fn default_plugin_rate_limit() -> RateLimit {
+    RateLimit { per_second: 10.0, burst: 10 }
+}
+
+fn default_plugin_rate_limit_overrides() -> HashMap<String, RateLimit> {
+    [("notification".to_string(), RateLimit { per_second: 1.0, burst: 1 })]
+        .into_iter()
+        .collect()
+}
+
+fn default_min_plugin_security_score() -> u8 {
+    40
+}
+
+fn default_max_plugin_package_mb() -> u32 {
+    256
+}
+
+fn default_plugin_memory_warning_mb() -> u32 {
+    200
+}
+
+fn default_plugin_storage_quota_mb() -> u32 {
+    50
+}
+
+fn default_plugin_cache_entries() -> usize {
+    crate::services::plugin_result_cache::DEFAULT_CAPACITY
+}
+
+fn default_max_browser_db_copy_mb() -> u64 {
+    512
+}
+
+fn default_browser_history_limit() -> usize {
+    crate::services::browser_reader::DEFAULT_HISTORY_LIMIT
+}
+
+fn default_browser_min_visits() -> u32 {
+    crate::services::browser_reader::DEFAULT_MIN_VISITS
+}
+
 fn default_language() -> String {
     "en".to_string()
 }
 
+fn default_ssh_terminal_template() -> String {
+    #[cfg(target_os = "macos")]
+    return "open -a Terminal".to_string();
+    #[cfg(target_os = "windows")]
+    return "wt ssh %h".to_string();
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    return "x-terminal-emulator -e ssh %h".to_string();
+}
+
+/// The built-in terminal launcher for CLI tools, trusted without being on
+/// the shell allowlist
+pub fn default_cli_terminal_template() -> String {
+    #[cfg(target_os = "macos")]
+    return "open -a Terminal".to_string();
+    #[cfg(target_os = "windows")]
+    return "wt %c".to_string();
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    return "x-terminal-emulator -e %c".to_string();
+}
+
+fn default_cli_excluded_dirs() -> Vec<String> {
+    vec!["*/libexec*".to_string()]
+}
+
+fn default_shell_history_files() -> Vec<String> {
+    ["~/.zsh_history", "~/.bash_history", "~/.local/share/fish/fish_history"]
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Passwords in URLs ("https://me:pw@host") and sshpass invocations
+fn default_shell_history_exclude_pattern() -> String {
+    r"://[^/\s:@]+:[^@\s]+@|\bsshpass\b".to_string()
+}
+
 fn default_global_hotkey() -> String {
     #[cfg(target_os = "macos")]
     return "Cmd+Shift+K".to_string();
@@ -102,14 +511,53 @@ fn default_anonymize_usage() -> bool {
     true
 }
 
+fn default_index_settings_panes() -> bool {
+    true
+}
+
+fn default_index_store_apps() -> bool {
+    true
+}
+
 fn default_search_debounce_ms() -> u64 {
     150
 }
 
+fn default_search_budget_ms() -> u64 {
+    200
+}
+
 fn default_max_results() -> usize {
     50
 }
 
+fn default_query_prefixes() -> HashMap<String, String> {
+    [("f", "file"), ("b", "browser"), ("c", "calculator"), (">", "command")]
+        .into_iter()
+        .map(|(prefix, source)| (prefix.to_string(), source.to_string()))
+        .collect()
+}
+
+fn default_source_limits() -> HashMap<String, usize> {
+    [("app", 8), ("file", 10), ("browser", 6), ("plugin", 6)]
+        .into_iter()
+        .map(|(source, cap)| (source.to_string(), cap))
+        .collect()
+}
+
+fn default_suggestion_weights() -> HashMap<String, u32> {
+    [
+        ("app", 4),
+        ("clipboard", 2),
+        ("file", 2),
+        ("browser", DEFAULT_BROWSER_SUGGESTION_WEIGHT),
+        ("plugin", 2),
+    ]
+        .into_iter()
+        .map(|(section, weight)| (section.to_string(), weight))
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub enum StartupBehavior {
     AutoStart,
@@ -136,24 +584,132 @@ impl Default for Theme {
     }
 }
 
+/// What showing the launcher does with the search it was hidden with
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnShowBehavior {
+    /// Keep the previous query and results
+    #[default]
+    Keep,
+    /// Always start with an empty search
+    Clear,
+    /// Start fresh once the launcher was hidden for this many seconds
+    ClearAfterSeconds(u32),
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SkinTone {
+    #[default]
+    Neutral,
+    Light,
+    MediumLight,
+    Medium,
+    MediumDark,
+    Dark,
+}
+
+impl SkinTone {
+    /// The Fitzpatrick modifier that follows the base emoji, None for the
+    /// yellow default
+    pub fn modifier(&self) -> Option<char> {
+        match self {
+            SkinTone::Neutral => None,
+            SkinTone::Light => Some('\u{1F3FB}'),
+            SkinTone::MediumLight => Some('\u{1F3FC}'),
+            SkinTone::Medium => Some('\u{1F3FD}'),
+            SkinTone::MediumDark => Some('\u{1F3FE}'),
+            SkinTone::Dark => Some('\u{1F3FF}'),
+        }
+    }
+}
+
 impl Default for AppSettings {
     fn default() -> Self {
         Self {
+            schema_version: SETTINGS_SCHEMA_VERSION,
             startup_behavior: StartupBehavior::OnDemand,
             language: default_language(),
             global_hotkey: default_global_hotkey(),
+            selection_hotkey: None,
+            on_show_behavior: OnShowBehavior::Keep,
             theme: Theme::System,
             window_opacity: default_window_opacity(),
             show_menubar_icon: default_show_menubar_icon(),
+            emoji_skin_tone: SkinTone::Neutral,
             enable_clipboard: default_enable_clipboard(),
             enable_file_search: false,
             enable_browser_search: false,
+            enable_pinyin_search: false,
+            enable_shell_commands: false,
             anonymize_usage: default_anonymize_usage(),
             crash_reports: false,
+            do_not_disturb: DoNotDisturb::default(),
             search_debounce_ms: default_search_debounce_ms(),
+            search_budget_ms: default_search_budget_ms(),
             max_results: default_max_results(),
             excluded_apps: vec![],
+            index_settings_panes: default_index_settings_panes(),
+            index_store_apps: default_index_store_apps(),
             file_index_paths: vec![],
+            index_file_tags: false,
+            query_prefixes: default_query_prefixes(),
+            source_limits: default_source_limits(),
+            suggestion_weights: default_suggestion_weights(),
+            scoring_weights: ScoringWeights::default(),
+            plugin_rate_limits: PluginRateLimits::default(),
+            shell_allowlist: Vec::new(),
+            ssh_terminal_template: default_ssh_terminal_template(),
+            cli_terminal_template: default_cli_terminal_template(),
+            cli_excluded_dirs: default_cli_excluded_dirs(),
+            shell_history_files: default_shell_history_files(),
+            shell_history_exclude_pattern: default_shell_history_exclude_pattern(),
+            min_plugin_security_score: default_min_plugin_security_score(),
+            max_plugin_package_mb: default_max_plugin_package_mb(),
+            plugin_memory_warning_mb: default_plugin_memory_warning_mb(),
+            plugin_storage_quota_mb: default_plugin_storage_quota_mb(),
+            plugin_cache_entries: default_plugin_cache_entries(),
+            max_browser_db_copy_mb: default_max_browser_db_copy_mb(),
+            browser_history_limit: default_browser_history_limit(),
+            browser_min_visits: default_browser_min_visits(),
+            openers: BTreeMap::new(),
+            http_proxy: None,
+            update_endpoint: None,
+            offline_mode: false,
+            retention: RetentionSettings::default(),
+            default_window_layout: None,
+            data_dir: None,
+            temp_dir: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrates_unversioned_settings() {
+        let settings = migrate_settings(serde_json::json!({ "language": "zh-CN" })).unwrap();
+        assert_eq!(settings.schema_version, SETTINGS_SCHEMA_VERSION);
+        assert_eq!(settings.language, "zh-CN");
+        assert_eq!(settings.max_results, default_max_results());
+    }
+
+    #[test]
+    fn test_adds_browser_suggestion_weight() {
+        let value = serde_json::json!({ "schema_version": 1, "suggestion_weights": { "app": 6, "file": 0 } });
+        let weights = migrate_settings(value).unwrap().suggestion_weights;
+        assert_eq!(weights.get("browser"), Some(&DEFAULT_BROWSER_SUGGESTION_WEIGHT));
+        assert_eq!((weights.get("app"), weights.get("file")), (Some(&6), Some(&0)));
+
+        // A weight the user already set is kept
+        let value = serde_json::json!({ "schema_version": 1, "suggestion_weights": { "browser": 0 } });
+        assert_eq!(migrate_settings(value).unwrap().suggestion_weights.get("browser"), Some(&0));
+    }
+
+    #[test]
+    fn test_rejects_newer_schema() {
+        let value = serde_json::json!({ "schema_version": SETTINGS_SCHEMA_VERSION + 1 });
+        assert!(migrate_settings(value).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+/**
+ * Search Result Model
+ * Shared result type produced by every search provider
+ */
+
+use crate::services::browser_match::MatchContext;
+use serde::Serialize;
+
+/// Search result item
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchResultItem {
+    pub id: String,
+    pub title: String,
+    pub subtitle: String,
+    pub icon: Option<String>,
+    /// What `icon` holds, so the frontend knows how to draw it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon_kind: Option<IconKind>,
+    /// Theme-neutral "#rrggbb" the frontend may tint the row with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent_color: Option<String>,
+    #[serde(rename = "type")]
+    pub result_type: String,
+    pub score: f64,
+    pub path: String,
+    pub frequency: u32,
+    /// How the score was reached, only for searches that ask to `explain`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub explain: Option<ScoreBreakdown>,
+    /// Where a browser result matched, to tell apart pages sharing a title
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_context: Option<MatchContext>,
+}
+
+/// How a result's icon is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IconKind {
+    /// An emoji or symbol glyph
+    Emoji,
+    /// A file path or data URL to an image
+    Path,
+    /// The name of an icon in the frontend's own set, see `BuiltinIcon`
+    Builtin,
+}
+
+/// Icons the frontend draws from its own set, so they match the theme
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BuiltinIcon {
+    Calculator,
+    Clock,
+    Conversion,
+    Process,
+    Server,
+    System,
+    Terminal,
+}
+
+impl BuiltinIcon {
+    pub const ALL: &'static [BuiltinIcon] = &[
+        BuiltinIcon::Calculator,
+        BuiltinIcon::Clock,
+        BuiltinIcon::Conversion,
+        BuiltinIcon::Process,
+        BuiltinIcon::Server,
+        BuiltinIcon::System,
+        BuiltinIcon::Terminal,
+    ];
+
+    /// The name sent in `SearchResultItem::icon`
+    pub fn name(self) -> &'static str {
+        match self {
+            BuiltinIcon::Calculator => "calculator",
+            BuiltinIcon::Clock => "clock",
+            BuiltinIcon::Conversion => "conversion",
+            BuiltinIcon::Process => "process",
+            BuiltinIcon::Server => "server",
+            BuiltinIcon::System => "system",
+            BuiltinIcon::Terminal => "terminal",
+        }
+    }
+}
+
+/// Components of a result's score, for tuning scoring weights
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ScoreBreakdown {
+    pub exact: f64,
+    pub starts_with: f64,
+    pub contains: f64,
+    pub initialism: f64,
+    pub frequency: f64,
+    /// Parts the weights don't cover: alternate names, the bookmark boost,
+    /// URL-only matches, fixed-score results
+    pub other: f64,
+    /// Provider score, the sum of the components above
+    pub raw: f64,
+    /// Raw score divided by the best raw score in the provider's batch
+    pub normalized: f64,
+    /// Provider weight the normalized score was multiplied by
+    pub provider_weight: f64,
+}
+
+impl ScoreBreakdown {
+    /// A score the weights don't shape, e.g. a calculator answer
+    pub fn fixed(score: f64) -> Self {
+        Self {
+            other: score,
+            ..Default::default()
+        }
+    }
+
+    /// Sum of the components
+    pub fn total(&self) -> f64 {
+        self.exact + self.starts_with + self.contains + self.initialism + self.frequency + self.other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> SearchResultItem {
+        SearchResultItem {
+            id: "calculator".to_string(),
+            title: "42".to_string(),
+            subtitle: "6 * 7".to_string(),
+            icon: None,
+            icon_kind: None,
+            accent_color: None,
+            result_type: "calculator".to_string(),
+            score: 1.0,
+            path: "42".to_string(),
+            frequency: 0,
+            explain: None,
+            match_context: None,
+        }
+    }
+
+    #[test]
+    fn test_icon_fields_serialize() {
+        let json = serde_json::to_value(SearchResultItem {
+            icon: Some(BuiltinIcon::Calculator.name().to_string()),
+            icon_kind: Some(IconKind::Builtin),
+            accent_color: Some("#1a73e8".to_string()),
+            ..item()
+        })
+        .unwrap();
+        assert_eq!(json["icon"], "calculator");
+        assert_eq!(json["icon_kind"], "builtin");
+        assert_eq!(json["accent_color"], "#1a73e8");
+        assert_eq!(json["type"], "calculator");
+
+        // Unset, they're left out rather than sent as null
+        let json = serde_json::to_value(item()).unwrap();
+        assert!(json.get("icon_kind").is_none());
+        assert!(json.get("accent_color").is_none());
+        assert_eq!(json["icon"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_builtin_icon_names_match_serde() {
+        for icon in BuiltinIcon::ALL {
+            assert_eq!(serde_json::to_value(icon).unwrap(), icon.name());
+        }
+        assert_eq!(serde_json::to_value(IconKind::Emoji).unwrap(), "emoji");
+        assert_eq!(serde_json::to_value(IconKind::Path).unwrap(), "path");
+    }
+}
@@ -1,6 +1,88 @@
 use serde::{Deserialize, Serialize};
 use super::screen_info::ScreenInfo;
 
+/// Where a saved layout puts the main window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum LayoutPosition {
+    /// Centered on the monitor under the cursor each time it's shown
+    FollowCursor,
+    /// A fixed spot, as fractions of the monitor's size so it survives
+    /// resolution changes. `monitor` is the monitor's name, when it has one.
+    Fixed {
+        monitor: Option<String>,
+        rel_x: f64,
+        rel_y: f64,
+    },
+}
+
+/// A named arrangement of the launcher, e.g. "compact" for quick searches
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WindowLayout {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub position: LayoutPosition,
+    /// Height of the results panel, applied by the frontend
+    pub results_height: u32,
+    pub always_on_top: bool,
+}
+
+/// A connected monitor's bounds in physical pixels
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorArea {
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl MonitorArea {
+    /// Where a window's top-left corner sits, as fractions of this monitor
+    pub fn relative(&self, x: i32, y: i32) -> (f64, f64) {
+        (
+            (x - self.x) as f64 / self.width.max(1) as f64,
+            (y - self.y) as f64 / self.height.max(1) as f64,
+        )
+    }
+
+    /// Top-left corner at the given fractions, moved so a window of the
+    /// given size stays on this monitor
+    pub fn place(&self, rel_x: f64, rel_y: f64, width: u32, height: u32) -> (i32, i32) {
+        let max_x = self.width.saturating_sub(width) as f64;
+        let max_y = self.height.saturating_sub(height) as f64;
+        (
+            self.x + (rel_x * self.width as f64).clamp(0.0, max_x).round() as i32,
+            self.y + (rel_y * self.height as f64).clamp(0.0, max_y).round() as i32,
+        )
+    }
+
+    /// Top-left corner centering a window of the given size on this monitor
+    pub fn center(&self, width: u32, height: u32) -> (i32, i32) {
+        (
+            self.x + (self.width as i32 - width as i32) / 2,
+            self.y + (self.height as i32 - height as i32) / 2,
+        )
+    }
+}
+
+impl WindowLayout {
+    /// Top-left corner for a fixed layout: its spot on the monitor it was
+    /// saved on, or the same relative spot on `primary` when that monitor
+    /// is no longer connected. None for layouts that follow the cursor.
+    pub fn fixed_position(&self, monitors: &[MonitorArea], primary: &MonitorArea) -> Option<(i32, i32)> {
+        let LayoutPosition::Fixed { monitor, rel_x, rel_y } = &self.position else {
+            return None;
+        };
+        let target = monitor
+            .as_ref()
+            .and_then(|name| monitors.iter().find(|m| m.name.as_ref() == Some(name)))
+            .unwrap_or(primary);
+        Some(target.place(*rel_x, *rel_y, self.width, self.height))
+    }
+}
+
 /// Result of window size calculation with final dimensions and position
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalculatedWindowLayout {
@@ -60,3 +142,53 @@ impl CalculatedWindowLayout {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(name: &str, x: i32, y: i32, width: u32, height: u32) -> MonitorArea {
+        MonitorArea { name: Some(name.to_string()), x, y, width, height }
+    }
+
+    fn fixed(monitor: &str, rel_x: f64, rel_y: f64) -> WindowLayout {
+        WindowLayout {
+            name: "expanded".to_string(),
+            width: 800,
+            height: 600,
+            position: LayoutPosition::Fixed { monitor: Some(monitor.to_string()), rel_x, rel_y },
+            results_height: 480,
+            always_on_top: true,
+        }
+    }
+
+    #[test]
+    fn test_saved_monitor_keeps_position() {
+        let primary = monitor("Built-in", 0, 0, 1440, 900);
+        let external = monitor("DELL U2720Q", 1440, -200, 2560, 1440);
+
+        let (rel_x, rel_y) = external.relative(2000, 100);
+        let layout = fixed("DELL U2720Q", rel_x, rel_y);
+        assert_eq!(layout.fixed_position(&[primary.clone(), external], &primary), Some((2000, 100)));
+    }
+
+    #[test]
+    fn test_missing_monitor_falls_back_to_primary() {
+        let primary = monitor("Built-in", 0, 0, 1440, 900);
+        let connected = vec![primary.clone()];
+        // A quarter across and a third down the disconnected monitor
+        let layout = fixed("DELL U2720Q", 0.25, 1.0 / 3.0);
+        assert_eq!(layout.fixed_position(&connected, &primary), Some((360, 300)));
+
+        // Kept on screen when the spot would push the window off the edge
+        let layout = fixed("DELL U2720Q", 0.9, 0.9);
+        assert_eq!(layout.fixed_position(&connected, &primary), Some((640, 300)));
+
+        // Primary monitors need not start at the origin
+        let offset = monitor("Built-in", -1440, 0, 1440, 900);
+        assert_eq!(layout.fixed_position(&[], &offset), Some((-800, 300)));
+
+        let follow = WindowLayout { position: LayoutPosition::FollowCursor, ..layout };
+        assert_eq!(follow.fixed_position(&[], &primary), None);
+    }
+}
@@ -0,0 +1,132 @@
+//! Usage Analytics
+//! Records local usage events (searches, launches, plugin runs, clipboard
+//! pastes) when the user acts on a result, not as they type. Subjects are
+//! hashed with SHA-256 and a per-install salt when `anonymize_usage` is on,
+//! so short subjects such as app names can't be looked up in a table of
+//! precomputed hashes. Nothing is ever uploaded; aggregation happens
+//! against the local database only.
+
+use crate::cmds::search::SearchState;
+use crate::db::analytics::{init_analytics_db, insert_event, install_salt};
+use crate::services::privacy::PrivacyMode;
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Kind of recorded usage event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UsageKind {
+    Search,
+    Launch,
+    PluginExecution,
+    Clipboard,
+}
+
+impl UsageKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UsageKind::Search => "search",
+            UsageKind::Launch => "launch",
+            UsageKind::PluginExecution => "plugin",
+            UsageKind::Clipboard => "clipboard",
+        }
+    }
+
+    pub fn parse(kind: &str) -> Option<Self> {
+        match kind {
+            "search" => Some(UsageKind::Search),
+            "launch" => Some(UsageKind::Launch),
+            "plugin" => Some(UsageKind::PluginExecution),
+            "clipboard" => Some(UsageKind::Clipboard),
+            _ => None,
+        }
+    }
+}
+
+/// Value stored for an event subject: a salted hash when anonymizing
+/// (`salt` given), else as-is
+pub fn subject_value(subject: &str, salt: Option<&str>) -> String {
+    let Some(salt) = salt else {
+        return subject.to_string();
+    };
+
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(subject.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Whether usage subjects are hashed. Defaults to anonymizing if settings
+/// can't be read.
+fn anonymizes<R: Runtime>(handle: &AppHandle<R>) -> bool {
+    handle
+        .try_state::<SearchState>()
+        .and_then(|state| state.settings().ok())
+        .map_or(true, |settings| settings.anonymize_usage)
+}
+
+/// The salt to hash subjects with, None when usage isn't anonymized
+pub fn subject_salt<R: Runtime>(handle: &AppHandle<R>) -> Result<Option<String>, String> {
+    if !anonymizes(handle) {
+        return Ok(None);
+    }
+
+    init_analytics_db(handle)
+        .and_then(|conn| install_salt(&conn))
+        .map(Some)
+        .map_err(|e| format!("Failed to read usage salt: {}", e))
+}
+
+/// Record a usage event. Skipped while privacy mode is on; failures are
+/// logged and never surface to the caller.
+pub fn record_usage<R: Runtime>(handle: &AppHandle<R>, kind: UsageKind, subject: &str) {
+    if handle.try_state::<PrivacyMode>().is_some_and(|privacy| privacy.is_active()) {
+        return;
+    }
+
+    let anonymize = anonymizes(handle);
+    let result = init_analytics_db(handle)
+        .and_then(|conn| {
+            let salt = if anonymize { Some(install_salt(&conn)?) } else { None };
+            insert_event(
+                &conn,
+                kind.as_str(),
+                &subject_value(subject, salt.as_deref()),
+                chrono::Utc::now().timestamp(),
+            )
+        });
+
+    if let Err(e) = result {
+        eprintln!("[Analytics] Failed to record {} event: {}", kind.as_str(), e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_is_hashed_only_when_anonymizing() {
+        assert_eq!(subject_value("Terminal", None), "Terminal");
+
+        let hashed = subject_value("Terminal", Some("salt"));
+        assert_ne!(hashed, "Terminal");
+        assert_eq!(hashed.len(), 64);
+        assert_eq!(hashed, subject_value("Terminal", Some("salt")));
+        assert_ne!(hashed, subject_value("Safari", Some("salt")));
+        // Another install hashes the same subject differently
+        assert_ne!(hashed, subject_value("Terminal", Some("pepper")));
+    }
+
+    #[test]
+    fn test_kind_round_trip() {
+        for kind in [
+            UsageKind::Search,
+            UsageKind::Launch,
+            UsageKind::PluginExecution,
+            UsageKind::Clipboard,
+        ] {
+            assert_eq!(UsageKind::parse(kind.as_str()), Some(kind));
+        }
+        assert_eq!(UsageKind::parse("unknown"), None);
+    }
+}
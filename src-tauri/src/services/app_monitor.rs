@@ -3,25 +3,151 @@
  * Discovers installed applications on macOS, Windows, and Linux
  */
 
-use crate::models::app::ApplicationEntry;
+use crate::models::app::{AppSource, ApplicationEntry};
+use crate::services::app_sources::AppSources;
+use crate::services::search::pinyin::{collation_key, pinyin_names, PinyinIndex};
 use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 
-/// Application cache
+/// Application cache. Discovery only runs on the first read and on
+/// `rescan`; the app watcher calls the latter when app directories change.
 pub struct AppMonitor {
     cache: HashMap<String, ApplicationEntry>,
+    /// Directories searched for applications
+    dirs: Vec<PathBuf>,
+    /// Settings panes and Store apps, found outside `dirs`
+    sources: AppSources,
+    scanned: bool,
+    /// Pinyin of the Chinese app names, worked out on first use after a scan
+    pinyin: Option<Arc<PinyinIndex>>,
+}
+
+/// Platform directories that hold installed applications
+pub fn default_app_dirs() -> Vec<PathBuf> {
+    #[cfg(target_os = "macos")]
+    {
+        vec![
+            PathBuf::from("/Applications"),
+            PathBuf::from(std::env::var("HOME").unwrap_or_default()).join("Applications"),
+        ]
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        vec![
+            PathBuf::from(std::env::var("PROGRAMDATA").unwrap_or_default())
+                .join("Microsoft\\Windows\\Start Menu\\Programs"),
+            PathBuf::from(std::env::var("APPDATA").unwrap_or_default())
+                .join("Microsoft\\Windows\\Start Menu\\Programs"),
+        ]
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        vec![
+            PathBuf::from("/usr/share/applications"),
+            PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".local/share/applications"),
+        ]
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        Vec::new()
+    }
 }
 
 impl AppMonitor {
     pub fn new() -> Self {
+        let mut monitor = Self::with_dirs(default_app_dirs());
+        monitor.sources = AppSources::default();
+        monitor
+    }
+
+    /// Create a monitor that discovers applications in `dirs` only
+    pub fn with_dirs(dirs: Vec<PathBuf>) -> Self {
         Self {
             cache: HashMap::new(),
+            dirs,
+            sources: AppSources {
+                settings_panes: false,
+                store_apps: false,
+            },
+            scanned: false,
+            pinyin: None,
+        }
+    }
+
+    /// Create a monitor pre-populated with a known set of applications
+    pub fn from_apps(apps: Vec<ApplicationEntry>) -> Self {
+        Self {
+            cache: apps.into_iter().map(|app| (app.id.clone(), app)).collect(),
+            dirs: default_app_dirs(),
+            sources: AppSources::default(),
+            scanned: true,
+            pinyin: None,
+        }
+    }
+
+    /// Directories searched for applications
+    pub fn dirs(&self) -> &[PathBuf] {
+        &self.dirs
+    }
+
+    /// Choose the additional sources; takes effect on the next rescan.
+    /// Returns whether they changed.
+    pub fn set_sources(&mut self, sources: AppSources) -> bool {
+        let changed = self.sources != sources;
+        self.sources = sources;
+        changed
+    }
+
+    /// Cached applications in no particular order; never scans
+    pub fn loaded_apps(&self) -> Vec<ApplicationEntry> {
+        self.cache.values().cloned().collect()
+    }
+
+    /// Get cached applications sorted by name, scanning on first use
+    pub fn cached_apps(&mut self) -> Vec<ApplicationEntry> {
+        if !self.scanned {
+            self.rescan();
         }
+
+        let mut apps: Vec<ApplicationEntry> = self.cache.values().cloned().collect();
+        apps.sort_by_cached_key(|app| collation_key(&app.name));
+        apps
     }
 
-    /// Scan for installed applications
+    /// Pinyin of the cached apps' Chinese names by app id, for pinyin
+    /// search. Worked out once per scan, and only when asked for.
+    pub fn pinyin_names(&mut self) -> Arc<PinyinIndex> {
+        if !self.scanned {
+            self.rescan();
+        }
+
+        let cache = &self.cache;
+        self.pinyin
+            .get_or_insert_with(|| {
+                Arc::new(
+                    cache
+                        .values()
+                        .filter_map(|app| Some((app.id.clone(), pinyin_names(&app.name)?)))
+                        .collect(),
+                )
+            })
+            .clone()
+    }
+
+    /// Installed applications. A cheap cached read; only the first call
+    /// scans, use `rescan` to pick up changes.
     pub fn scan_apps(&mut self) -> Vec<ApplicationEntry> {
+        self.cached_apps()
+    }
+
+    /// Rediscover installed applications, replacing the cache so
+    /// uninstalled apps disappear
+    pub fn rescan(&mut self) -> Vec<ApplicationEntry> {
         let mut apps = Vec::new();
 
         // Platform-specific discovery
@@ -40,24 +166,40 @@ impl AppMonitor {
             apps.extend(self.scan_linux_apps());
         }
 
-        // Update cache
-        for app in &apps {
-            self.cache.insert(app.id.clone(), app.clone());
-        }
+        apps.extend(self.sources.scan());
+
+        self.cache = apps.iter().map(|app| (app.id.clone(), app.clone())).collect();
+        self.scanned = true;
+        self.pinyin = None;
 
         apps
     }
 
+    /// `rescan` for a monitor shared with the search path. Discovery runs
+    /// on a copy outside the lock, so searches keep reading the old cache
+    /// until the new one is swapped in.
+    pub fn rescan_shared(monitor: &Mutex<AppMonitor>) -> Result<Vec<ApplicationEntry>, String> {
+        let (dirs, sources) = {
+            let shared = monitor.lock().map_err(|e| format!("Lock error: {}", e))?;
+            (shared.dirs.clone(), shared.sources)
+        };
+        let mut scanner = AppMonitor::with_dirs(dirs);
+        scanner.sources = sources;
+        let apps = scanner.rescan();
+
+        let mut shared = monitor.lock().map_err(|e| format!("Lock error: {}", e))?;
+        shared.cache = scanner.cache;
+        shared.scanned = true;
+        shared.pinyin = None;
+        Ok(apps)
+    }
+
     #[cfg(target_os = "macos")]
     fn scan_macos_apps(&self) -> Vec<ApplicationEntry> {
         let mut apps = Vec::new();
-        let search_paths = vec![
-            PathBuf::from("/Applications"),
-            PathBuf::from(std::env::var("HOME").unwrap_or_default()).join("Applications"),
-        ];
 
-        for base_dir in search_paths {
-            if let Ok(entries) = fs::read_dir(&base_dir) {
+        for base_dir in &self.dirs {
+            if let Ok(entries) = fs::read_dir(base_dir) {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if path.extension().and_then(|s| s.to_str()) == Some("app") {
@@ -79,14 +221,16 @@ impl AppMonitor {
         let info_plist_path = contents_path.join("Info.plist");
 
         // Parse Info.plist for display name
-        let display_name = self.read_plist_value(&info_plist_path, "CFBundleName")
+        let display_name = read_plist_value(&info_plist_path, "CFBundleName")
             .unwrap_or_else(|| name.clone());
 
         // Find executable
-        let executable_path = self.read_plist_value(&info_plist_path, "CFBundleExecutable")
+        let executable_path = read_plist_value(&info_plist_path, "CFBundleExecutable")
             .map(|exe| contents_path.join("MacOS").join(exe))
             .unwrap_or_else(|| app_path.to_path_buf());
 
+        let bundle_id = read_plist_value(&info_plist_path, "CFBundleIdentifier");
+
         // Don't extract icon during scan to avoid blocking
         // Icon will be loaded on-demand via NSWorkspace API
         let icon = None;
@@ -108,39 +252,19 @@ impl AppMonitor {
             last_launched: None,
             platform: "macos".to_string(),
             alternate_names,
+            bundle_id,
+            source: AppSource::Installed,
         })
     }
 
-    #[cfg(target_os = "macos")]
-    fn read_plist_value(&self, plist_path: &Path, key: &str) -> Option<String> {
-        // Simple plist parsing (for production, use a proper plist library)
-        if let Ok(content) = fs::read_to_string(plist_path) {
-            // Look for <key>{key}</key>\s*<string>(.*?)</string>
-            let pattern = format!("<key>{}</key>\\s*<string>(.*?)</string>", regex::escape(key));
-            if let Ok(re) = regex::Regex::new(&pattern) {
-                if let Some(caps) = re.captures(&content) {
-                    return caps.get(1).map(|m| m.as_str().to_string());
-                }
-            }
-        }
-        None
-    }
-
 
     #[cfg(target_os = "windows")]
     fn scan_windows_apps(&self) -> Vec<ApplicationEntry> {
         let mut apps = Vec::new();
 
         // Scan start menu
-        let start_menu_paths = vec![
-            PathBuf::from(std::env::var("PROGRAMDATA").unwrap_or_default())
-                .join("Microsoft\\Windows\\Start Menu\\Programs"),
-            PathBuf::from(std::env::var("APPDATA").unwrap_or_default())
-                .join("Microsoft\\Windows\\Start Menu\\Programs"),
-        ];
-
-        for base_dir in start_menu_paths {
-            if let Ok(entries) = fs::read_dir(&base_dir) {
+        for base_dir in &self.dirs {
+            if let Ok(entries) = fs::read_dir(base_dir) {
                 for entry in entries.flatten() {
                     if let Ok(file_type) = entry.file_type() {
                         if file_type.is_dir() {
@@ -184,13 +308,9 @@ impl AppMonitor {
     #[cfg(target_os = "linux")]
     fn scan_linux_apps(&self) -> Vec<ApplicationEntry> {
         let mut apps = Vec::new();
-        let data_dirs = vec![
-            PathBuf::from("/usr/share/applications"),
-            PathBuf::from(std::env::var("HOME").unwrap_or_default()).join(".local/share/applications"),
-        ];
 
-        for base_dir in data_dirs {
-            if let Ok(entries) = fs::read_dir(&base_dir) {
+        for base_dir in &self.dirs {
+            if let Ok(entries) = fs::read_dir(base_dir) {
                 for entry in entries.flatten() {
                     let path = entry.path();
                     if path.extension().and_then(|s| s.to_str()) == Some("desktop") {
@@ -224,10 +344,17 @@ impl AppMonitor {
                     id: hash_string(&e),
                     name: n,
                     executable_path: e,
+                    app_path: Some(desktop_path.to_string_lossy().to_string()),
                     icon: None,
                     usage_count: 0,
                     last_launched: None,
                     platform: "linux".to_string(),
+                    alternate_names: None,
+                    bundle_id: desktop_path
+                        .file_stem()
+                        .and_then(|s| s.to_str())
+                        .map(|s| s.to_string()),
+                    source: AppSource::Installed,
                 });
             }
         }
@@ -261,8 +388,24 @@ impl Default for AppMonitor {
     }
 }
 
+/// Read a string value from an Info.plist
+#[cfg(target_os = "macos")]
+pub(crate) fn read_plist_value(plist_path: &Path, key: &str) -> Option<String> {
+    // Simple plist parsing (for production, use a proper plist library)
+    if let Ok(content) = fs::read_to_string(plist_path) {
+        // Look for <key>{key}</key>\s*<string>(.*?)</string>
+        let pattern = format!("<key>{}</key>\\s*<string>(.*?)</string>", regex::escape(key));
+        if let Ok(re) = regex::Regex::new(&pattern) {
+            if let Some(caps) = re.captures(&content) {
+                return caps.get(1).map(|m| m.as_str().to_string());
+            }
+        }
+    }
+    None
+}
+
 /// Simple hash function for strings
-fn hash_string(s: &str) -> String {
+pub(crate) fn hash_string(s: &str) -> String {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -0,0 +1,587 @@
+//! App Paths
+//! Where the app keeps its data and temporary files. Every data path goes
+//! through `data_dir` and every temp path through `temp_dir`, so the
+//! data_dir and temp_dir settings move all of them at once.
+//! `migrate_data_dir` moves existing data to a new data root: it copies
+//! everything, checks the copies, switches over and only then removes the
+//! originals, undoing the copies if any step before the switch fails.
+//! Config files never move: on macOS and Windows the config dir is the
+//! default data dir, and settings.json is where the data_dir setting itself
+//! is read from.
+
+use crate::models::preferences::AppSettings;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use serde::Serialize;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Installer temp files under the data dir, when no temp root is set
+const DEFAULT_TEMP_DIR: &str = "temp";
+
+/// Entries of the data dir a migration leaves behind: temp files, and the
+/// staging directory of an interrupted restore
+const NOT_MIGRATED: &[&str] = &[DEFAULT_TEMP_DIR, ".restore-staging"];
+
+/// Files kept in the config dir, which stay put when it is also the data dir
+const CONFIG_FILES: &[&str] = &[
+    "settings.json",
+    "settings.json.bak",
+    "abbreviations.json",
+    "privacy_mode.json",
+    "window_state.json",
+];
+
+/// SQLite journal files, covered by the backup API copying their database
+const DB_SIDE_FILES: &[&str] = &["-journal", "-wal", "-shm"];
+
+const SQLITE_HEADER: &[u8] = b"SQLite format 3\0";
+
+/// The data and temp roots in effect, managed from startup
+#[derive(Debug, Default)]
+pub struct DataLocations {
+    data_dir: RwLock<Option<PathBuf>>,
+    temp_dir: RwLock<Option<PathBuf>>,
+}
+
+impl DataLocations {
+    /// Roots from the settings. A root that has gone missing, e.g. on an
+    /// unplugged drive, is ignored so the app still starts.
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        let existing = |root: &Option<String>| {
+            let path = PathBuf::from(root.as_deref()?);
+            if path.is_dir() {
+                Some(path)
+            } else {
+                eprintln!("[AppPaths] {:?} is missing, using the default location", path);
+                None
+            }
+        };
+        Self {
+            data_dir: RwLock::new(existing(&settings.data_dir)),
+            temp_dir: RwLock::new(existing(&settings.temp_dir)),
+        }
+    }
+
+    pub fn data_dir(&self) -> Option<PathBuf> {
+        self.data_dir.read().ok()?.clone()
+    }
+
+    pub fn temp_dir(&self) -> Option<PathBuf> {
+        self.temp_dir.read().ok()?.clone()
+    }
+
+    pub fn set_data_dir(&self, root: Option<PathBuf>) {
+        if let Ok(mut data_dir) = self.data_dir.write() {
+            *data_dir = root;
+        }
+    }
+
+    pub fn set_temp_dir(&self, root: Option<PathBuf>) {
+        if let Ok(mut temp_dir) = self.temp_dir.write() {
+            *temp_dir = root;
+        }
+    }
+}
+
+/// The platform's data dir, where data lives unless the data_dir setting
+/// moved it
+pub fn default_data_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get data dir: {}", e))
+}
+
+/// Root of databases, plugins and state files
+pub fn data_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    match handle.try_state::<DataLocations>().and_then(|locations| locations.data_dir()) {
+        Some(root) => Ok(root),
+        None => default_data_dir(handle),
+    }
+}
+
+/// Installer uploads and extractions. A temp root set in the settings may
+/// be shared with other programs, so the app keeps to its own folder there.
+pub fn temp_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    match handle.try_state::<DataLocations>().and_then(|locations| locations.temp_dir()) {
+        Some(root) => Ok(root.join(&handle.config().identifier)),
+        None => Ok(data_dir(handle)?.join(DEFAULT_TEMP_DIR)),
+    }
+}
+
+/// Caches that can be rebuilt, such as the shell history index
+pub fn cache_dir<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    handle
+        .path()
+        .app_cache_dir()
+        .map_err(|e| format!("Failed to get cache dir: {}", e))
+}
+
+/// Check a new data or temp root: it must be an existing, writable folder
+/// outside the current data dir
+pub fn validate_location(path: &Path, current_data_dir: &Path) -> Result<(), String> {
+    if !path.is_absolute() {
+        return Err("must be an absolute path".to_string());
+    }
+    if !path.exists() {
+        return Err("folder does not exist".to_string());
+    }
+    if !path.is_dir() {
+        return Err("is not a folder".to_string());
+    }
+
+    let probe = path.join(format!(".write-test-{}", std::process::id()));
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map_err(|_| "folder is not writable".to_string())?;
+    let _ = fs::remove_file(&probe);
+
+    // Both resolved, so a symlink or relative segment can't hide nesting
+    let resolved = path.canonicalize().map_err(|e| format!("Failed to resolve {:?}: {}", path, e))?;
+    let current = current_data_dir.canonicalize().unwrap_or_else(|_| current_data_dir.to_path_buf());
+    if resolved.starts_with(&current) {
+        return Err("must be outside the current data folder".to_string());
+    }
+    Ok(())
+}
+
+/// Check a new data root: a valid location, as for `validate_location`,
+/// that is empty. An ancestor of the current data dir, such as the home
+/// folder, is never empty and so is refused too.
+pub fn validate_data_location(path: &Path, current_data_dir: &Path) -> Result<(), String> {
+    validate_location(path, current_data_dir)?;
+    let mut entries = fs::read_dir(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    if entries.next().is_some() {
+        return Err("folder must be empty".to_string());
+    }
+    Ok(())
+}
+
+/// The config files in `config_dir`, which a migration leaves in place
+pub fn config_files(config_dir: &Path) -> Vec<PathBuf> {
+    CONFIG_FILES.iter().map(|name| config_dir.join(name)).collect()
+}
+
+/// Migration step, for progress events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationStage {
+    Copying,
+    Verifying,
+    Switching,
+    RemovingOld,
+    RollingBack,
+    Done,
+}
+
+/// Migration progress, the `data-location:progress` event payload
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationProgress {
+    pub stage: MigrationStage,
+    /// Files done in this stage, of `total`
+    pub current: usize,
+    pub total: usize,
+    /// File being worked on, relative to the data dir
+    pub path: Option<String>,
+}
+
+/// Result of a finished migration
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationReport {
+    pub from: String,
+    pub to: String,
+    pub files: usize,
+    pub bytes: u64,
+    /// Originals that couldn't be removed after switching; the data is safe
+    /// in the new location, these are only left-over copies
+    pub left_behind: Vec<String>,
+}
+
+fn is_db_side_file(path: &Path) -> bool {
+    let name = path.to_string_lossy();
+    DB_SIDE_FILES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+fn is_sqlite(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .is_ok_and(|_| header == SQLITE_HEADER)
+}
+
+/// `kept` as paths relative to `from`, for those inside it. Both sides are
+/// resolved, so a config dir reached another way still matches.
+fn relative_to(from: &Path, kept: &[PathBuf]) -> Vec<PathBuf> {
+    let resolve = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let from = resolve(from);
+    kept.iter()
+        .filter_map(|path| {
+            let parent = resolve(path.parent()?);
+            let relative = parent.strip_prefix(&from).ok()?.join(path.file_name()?);
+            Some(relative)
+        })
+        .collect()
+}
+
+/// Files to migrate, relative to `from`, leaving out `kept`
+fn collect_files(from: &Path, relative: &Path, kept: &[PathBuf], out: &mut Vec<PathBuf>) -> Result<(), String> {
+    let dir = from.join(relative);
+    let entries = fs::read_dir(&dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+    for entry in entries.flatten() {
+        let path = relative.join(entry.file_name());
+        if relative.as_os_str().is_empty() && NOT_MIGRATED.iter().any(|skip| entry.file_name() == *skip) {
+            continue;
+        }
+        if entry.path().is_dir() {
+            collect_files(from, &path, kept, out)?;
+        } else if !is_db_side_file(&path) && !kept.contains(&path) {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Copy one file, through the backup API for databases so a live one
+/// copies consistently with its journal. Returns the bytes copied.
+fn copy_file(source: &Path, dest: &Path) -> Result<u64, String> {
+    if is_sqlite(source) {
+        Connection::open_with_flags(source, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|conn| conn.backup(DatabaseName::Main, dest, None))
+            .map_err(|e| format!("Failed to copy database {:?}: {}", source, e))?;
+        return fs::metadata(dest)
+            .map(|m| m.len())
+            .map_err(|e| format!("Failed to read {:?}: {}", dest, e));
+    }
+    fs::copy(source, dest).map_err(|e| format!("Failed to copy {:?}: {}", source, e))
+}
+
+/// Check a copy against its original: databases must pass an integrity
+/// check, other files must match byte for byte
+fn verify_file(source: &Path, dest: &Path) -> Result<(), String> {
+    if is_sqlite(source) {
+        let problems = Connection::open_with_flags(dest, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .and_then(|conn| crate::services::db_maintenance::integrity_check(&conn))
+            .map_err(|e| format!("Failed to check {:?}: {}", dest, e))?;
+        return match problems.first() {
+            None => Ok(()),
+            Some(problem) => Err(format!("Copy of {:?} is damaged: {}", source, problem)),
+        };
+    }
+    let read = |path: &Path| fs::read(path).map_err(|e| format!("Failed to read {:?}: {}", path, e));
+    if read(source)? != read(dest)? {
+        return Err(format!("Copy of {:?} differs from the original", source));
+    }
+    Ok(())
+}
+
+/// What a migration created, removed again on rollback
+#[derive(Default)]
+struct Created {
+    files: Vec<PathBuf>,
+    dirs: Vec<PathBuf>,
+}
+
+impl Created {
+    fn create_dirs(&mut self, dir: &Path) -> Result<(), String> {
+        let mut missing: Vec<&Path> = dir.ancestors().take_while(|d| !d.exists()).collect();
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create {:?}: {}", dir, e))?;
+        missing.reverse();
+        self.dirs.extend(missing.into_iter().map(Path::to_path_buf));
+        Ok(())
+    }
+
+    fn roll_back(&self) {
+        for file in self.files.iter().filter(|file| file.exists()) {
+            if let Err(e) = fs::remove_file(file) {
+                eprintln!("[AppPaths] Rollback couldn't remove {:?}: {}", file, e);
+            }
+        }
+        // Deepest first, so each is empty by the time it's removed
+        for dir in self.dirs.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Move the data in `from` to `to`, except the `kept` files, such as
+/// `config_files` when the config dir is inside `from`. `switch` makes the
+/// app use `to`; the originals are removed only once it succeeds. When a
+/// step before that fails, the copies are removed and `from` stays in use
+/// untouched.
+pub fn migrate_data_dir(
+    from: &Path,
+    to: &Path,
+    kept: &[PathBuf],
+    on_progress: impl FnMut(MigrationProgress),
+    switch: impl FnOnce() -> Result<(), String>,
+) -> Result<MigrationReport, String> {
+    migrate_with(from, to, kept, copy_file, on_progress, switch)
+}
+
+fn migrate_with(
+    from: &Path,
+    to: &Path,
+    kept: &[PathBuf],
+    mut copy: impl FnMut(&Path, &Path) -> Result<u64, String>,
+    mut on_progress: impl FnMut(MigrationProgress),
+    switch: impl FnOnce() -> Result<(), String>,
+) -> Result<MigrationReport, String> {
+    let mut files = Vec::new();
+    collect_files(from, Path::new(""), &relative_to(from, kept), &mut files)?;
+    if let Some(existing) = files.iter().find(|file| to.join(file).exists()) {
+        return Err(format!("{:?} already exists in the new location", existing));
+    }
+
+    let total = files.len();
+    let mut progress = |stage, current, path: Option<&PathBuf>| {
+        on_progress(MigrationProgress {
+            stage,
+            current,
+            total,
+            path: path.map(|p| p.to_string_lossy().to_string()),
+        })
+    };
+
+    let mut created = Created::default();
+    let mut bytes = 0;
+    let result = (|| {
+        for (i, file) in files.iter().enumerate() {
+            progress(MigrationStage::Copying, i, Some(file));
+            let dest = to.join(file);
+            if let Some(parent) = dest.parent() {
+                created.create_dirs(parent)?;
+            }
+            // Tracked before copying, so a half-written file is rolled back too
+            created.files.push(dest.clone());
+            bytes += copy(&from.join(file), &dest)?;
+        }
+        for (i, file) in files.iter().enumerate() {
+            progress(MigrationStage::Verifying, i, Some(file));
+            verify_file(&from.join(file), &to.join(file))?;
+        }
+        progress(MigrationStage::Switching, 0, None);
+        switch()
+    })();
+    if let Err(e) = result {
+        progress(MigrationStage::RollingBack, 0, None);
+        created.roll_back();
+        return Err(e);
+    }
+
+    let mut left_behind = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        progress(MigrationStage::RemovingOld, i, Some(file));
+        let source = from.join(file);
+        let side_files = DB_SIDE_FILES
+            .iter()
+            .map(|suffix| PathBuf::from(format!("{}{}", source.to_string_lossy(), suffix)))
+            .filter(|side| side.exists());
+        for path in std::iter::once(source.clone()).chain(side_files) {
+            if let Err(e) = fs::remove_file(&path) {
+                eprintln!("[AppPaths] Couldn't remove {:?}: {}", path, e);
+                left_behind.push(path.to_string_lossy().to_string());
+            }
+        }
+    }
+    progress(MigrationStage::Done, total, None);
+
+    Ok(MigrationReport {
+        from: from.to_string_lossy().to_string(),
+        to: to.to_string_lossy().to_string(),
+        files: total,
+        bytes,
+        left_behind,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A data dir with a database, state files and temp files
+    fn seed_data_dir() -> TempDir {
+        let dir = TempDir::new().unwrap();
+        let conn = crate::db::analytics::open_analytics_db(&dir.path().join("usage_analytics.db")).unwrap();
+        conn.execute_batch("CREATE TABLE extra (value TEXT); INSERT INTO extra VALUES ('kept');")
+            .unwrap();
+        fs::create_dir_all(dir.path().join("plugins/demo")).unwrap();
+        fs::write(dir.path().join("plugins/plugin_state.json"), "{}").unwrap();
+        fs::write(dir.path().join("plugins/demo/health.json"), "{\"ok\":true}").unwrap();
+        fs::write(dir.path().join("query_history.json"), "[]").unwrap();
+        fs::create_dir_all(dir.path().join("temp")).unwrap();
+        fs::write(dir.path().join("temp/upload.zip"), "partial").unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_validate_location() {
+        let data = TempDir::new().unwrap();
+        let other = TempDir::new().unwrap();
+        assert!(validate_location(other.path(), data.path()).is_ok());
+        assert!(!other.path().read_dir().unwrap().any(|_| true), "probe file left behind");
+
+        assert!(validate_location(Path::new("relative/dir"), data.path()).is_err());
+        assert!(validate_location(&other.path().join("missing"), data.path()).is_err());
+        let file = other.path().join("file.txt");
+        fs::write(&file, "").unwrap();
+        assert!(validate_location(&file, data.path()).is_err());
+
+        // Inside the data dir, directly or through a `..` detour
+        let nested = data.path().join("nested");
+        fs::create_dir(&nested).unwrap();
+        assert!(validate_location(&nested, data.path()).is_err());
+        assert!(validate_location(data.path(), data.path()).is_err());
+        assert!(validate_location(&nested.join("..").join("nested"), data.path()).is_err());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let read_only = other.path().join("read-only");
+            fs::create_dir(&read_only).unwrap();
+            fs::set_permissions(&read_only, fs::Permissions::from_mode(0o555)).unwrap();
+            // Root can write anywhere, so only check when the probe fails
+            if fs::write(read_only.join("probe"), "").is_err() {
+                assert_eq!(
+                    validate_location(&read_only, data.path()).unwrap_err(),
+                    "folder is not writable"
+                );
+            }
+            fs::set_permissions(&read_only, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_migrates_and_removes_originals() {
+        let from = seed_data_dir();
+        let to = TempDir::new().unwrap();
+        let mut stages = Vec::new();
+        let mut switched = false;
+
+        let report = migrate_data_dir(
+            from.path(),
+            to.path(),
+            &[],
+            |progress| stages.push(progress.stage),
+            || {
+                switched = true;
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert!(switched);
+        assert_eq!(report.files, 4);
+        assert!(report.left_behind.is_empty());
+        let conn = Connection::open(to.path().join("usage_analytics.db")).unwrap();
+        let value: String = conn.query_row("SELECT value FROM extra", [], |row| row.get(0)).unwrap();
+        assert_eq!(value, "kept");
+        assert_eq!(fs::read_to_string(to.path().join("plugins/demo/health.json")).unwrap(), "{\"ok\":true}");
+
+        // Temp files stay behind, the rest moved
+        assert!(!to.path().join("temp").exists());
+        assert!(from.path().join("temp/upload.zip").exists());
+        assert!(!from.path().join("usage_analytics.db").exists());
+        assert!(!from.path().join("plugins/plugin_state.json").exists());
+
+        assert_eq!(stages.first(), Some(&MigrationStage::Copying));
+        assert!(stages.contains(&MigrationStage::Verifying));
+        assert_eq!(stages.last(), Some(&MigrationStage::Done));
+    }
+
+    #[test]
+    fn test_failure_mid_copy_rolls_back() {
+        let from = seed_data_dir();
+        let to = TempDir::new().unwrap();
+        fs::write(to.path().join("unrelated.txt"), "mine").unwrap();
+        let mut copied = 0;
+        let mut stages = Vec::new();
+
+        let result = migrate_with(
+            from.path(),
+            to.path(),
+            &[],
+            |source, dest| {
+                copied += 1;
+                if copied == 3 {
+                    // Fails after writing part of the file, like a full disk
+                    fs::write(dest, "part").unwrap();
+                    return Err("No space left on device".to_string());
+                }
+                copy_file(source, dest)
+            },
+            |progress| stages.push(progress.stage),
+            || panic!("switched after a failed copy"),
+        );
+
+        assert_eq!(result.unwrap_err(), "No space left on device");
+        assert_eq!(stages.last(), Some(&MigrationStage::RollingBack));
+
+        // The new location is as it was and the originals are untouched
+        let left: Vec<_> = to.path().read_dir().unwrap().map(|e| e.unwrap().file_name()).collect();
+        assert_eq!(left, vec!["unrelated.txt"]);
+        assert!(from.path().join("usage_analytics.db").exists());
+        assert_eq!(fs::read_to_string(from.path().join("plugins/plugin_state.json")).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_failed_switch_rolls_back_and_conflicts_are_refused() {
+        let from = seed_data_dir();
+        let to = TempDir::new().unwrap();
+
+        let result = migrate_data_dir(from.path(), to.path(), &[], |_| {}, || Err("settings not saved".to_string()));
+        assert!(result.is_err());
+        assert_eq!(to.path().read_dir().unwrap().count(), 0);
+        assert!(from.path().join("query_history.json").exists());
+
+        // An existing file is never overwritten
+        fs::write(to.path().join("query_history.json"), "[\"theirs\"]").unwrap();
+        let result = migrate_data_dir(from.path(), to.path(), &[], |_| {}, || Ok(()));
+        assert!(result.unwrap_err().contains("already exists"));
+        assert_eq!(fs::read_to_string(to.path().join("query_history.json")).unwrap(), "[\"theirs\"]");
+    }
+
+    #[test]
+    fn test_config_files_stay_when_config_dir_is_the_data_dir() {
+        // As on macOS and Windows, where both are the same folder
+        let from = seed_data_dir();
+        fs::write(from.path().join("settings.json"), r#"{ "language": "en" }"#).unwrap();
+        fs::write(from.path().join("abbreviations.json"), "[]").unwrap();
+        let to = TempDir::new().unwrap();
+
+        let report = migrate_data_dir(from.path(), to.path(), &config_files(from.path()), |_| {}, || {
+            // The switch writes data_dir into the settings it leaves in place
+            fs::write(from.path().join("settings.json"), r#"{ "data_dir": "moved" }"#).unwrap();
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(report.files, 4);
+        assert_eq!(
+            fs::read_to_string(from.path().join("settings.json")).unwrap(),
+            r#"{ "data_dir": "moved" }"#
+        );
+        assert!(from.path().join("abbreviations.json").exists());
+        assert!(!to.path().join("settings.json").exists());
+        assert!(to.path().join("query_history.json").exists());
+        assert!(!from.path().join("query_history.json").exists());
+    }
+
+    #[test]
+    fn test_data_location_must_be_empty() {
+        let data = TempDir::new().unwrap();
+        let parent = data.path().parent().unwrap();
+        let empty = TempDir::new().unwrap();
+        assert!(validate_data_location(empty.path(), data.path()).is_ok());
+
+        fs::write(empty.path().join("notes.txt"), "").unwrap();
+        assert_eq!(validate_data_location(empty.path(), data.path()).unwrap_err(), "folder must be empty");
+        // An ancestor of the data dir, like the home folder, holds it
+        assert!(validate_data_location(parent, data.path()).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Additional Application Sources
+//! Launchable things that aren't app bundles in the scanned directories:
+//! System Settings panes on macOS and Store (UWP/AppX) apps on Windows.
+//! Each source can be turned off in settings, and a source that fails
+//! (e.g. PowerShell unavailable) contributes nothing instead of an error.
+
+use crate::models::app::{AppSource, ApplicationEntry};
+use crate::models::preferences::AppSettings;
+use std::path::Path;
+
+/// URL scheme that opens a System Settings pane by id
+pub const SETTINGS_PANE_URL_PREFIX: &str = "x-apple.systempreferences:";
+
+/// Shell folder that launches a Store app by its AppUserModelID
+pub const APPS_FOLDER_PREFIX: &str = "shell:AppsFolder\\";
+
+/// System Settings panes from macOS 13 on, which are app extensions rather
+/// than .prefPane bundles, so they can't be discovered on disk
+pub const KNOWN_SETTINGS_PANES: &[(&str, &str)] = &[
+    ("Accessibility", "com.apple.Accessibility-Settings.extension"),
+    ("Appearance", "com.apple.Appearance-Settings.extension"),
+    ("Battery", "com.apple.Battery-Settings.extension"),
+    ("Bluetooth", "com.apple.BluetoothSettings"),
+    ("Date & Time", "com.apple.Date-Time-Settings.extension"),
+    ("Desktop & Dock", "com.apple.Desktop-Settings.extension"),
+    ("Displays", "com.apple.Displays-Settings.extension"),
+    ("Focus", "com.apple.Focus-Settings.extension"),
+    ("General", "com.apple.systempreferences.GeneralSettings"),
+    ("Keyboard", "com.apple.Keyboard-Settings.extension"),
+    ("Lock Screen", "com.apple.Lock-Screen-Settings.extension"),
+    ("Mouse", "com.apple.Mouse-Settings.extension"),
+    ("Network", "com.apple.Network-Settings.extension"),
+    ("Notifications", "com.apple.Notifications-Settings.extension"),
+    ("Printers & Scanners", "com.apple.Print-Scan-Settings.extension"),
+    ("Privacy & Security", "com.apple.settings.PrivacySecurity.extension"),
+    ("Screen Time", "com.apple.Screen-Time-Settings.extension"),
+    ("Sharing", "com.apple.Sharing-Settings.extension"),
+    ("Software Update", "com.apple.Software-Update-Settings.extension"),
+    ("Sound", "com.apple.Sound-Settings.extension"),
+    ("Trackpad", "com.apple.Trackpad-Settings.extension"),
+    ("Users & Groups", "com.apple.Users-Groups-Settings.extension"),
+    ("Wi-Fi", "com.apple.wifi-settings-extension"),
+];
+
+/// Which additional sources a scan includes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AppSources {
+    pub settings_panes: bool,
+    pub store_apps: bool,
+}
+
+impl Default for AppSources {
+    fn default() -> Self {
+        Self {
+            settings_panes: true,
+            store_apps: true,
+        }
+    }
+}
+
+impl AppSources {
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            settings_panes: settings.index_settings_panes,
+            store_apps: settings.index_store_apps,
+        }
+    }
+
+    /// Entries from the enabled source available on this platform, if any
+    pub fn scan(&self) -> Vec<ApplicationEntry> {
+        #[cfg(target_os = "macos")]
+        if self.settings_panes {
+            return scan_settings_panes();
+        }
+
+        #[cfg(target_os = "windows")]
+        if self.store_apps {
+            return scan_store_apps();
+        }
+
+        Vec::new()
+    }
+}
+
+/// Program and arguments that launch `path`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl LaunchCommand {
+    fn new(program: &str, args: &[&str]) -> Self {
+        Self {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+}
+
+impl AppSource {
+    /// Source of a launch path whose entry isn't at hand, e.g. from an
+    /// older frontend that only sends the path
+    pub fn infer(path: &str) -> Self {
+        if path.starts_with(SETTINGS_PANE_URL_PREFIX) || path.ends_with(".prefPane") {
+            AppSource::SettingsPane
+        } else if path.starts_with(APPS_FOLDER_PREFIX) {
+            AppSource::StoreApp
+        } else {
+            AppSource::Installed
+        }
+    }
+
+    /// How to launch `path`, an entry's `executable_path` or `app_path`
+    pub fn launch_command(self, path: &str) -> LaunchCommand {
+        match self {
+            // `open` handles both the pane URL and a .prefPane bundle
+            AppSource::SettingsPane => LaunchCommand::new("open", &[path]),
+            // Store apps have no executable to run; the shell resolves the AUMID
+            AppSource::StoreApp => LaunchCommand::new("explorer.exe", &[path]),
+            AppSource::Installed => installed_launch_command(path),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn installed_launch_command(path: &str) -> LaunchCommand {
+    LaunchCommand::new("open", &[path])
+}
+
+#[cfg(target_os = "windows")]
+fn installed_launch_command(path: &str) -> LaunchCommand {
+    LaunchCommand::new("cmd", &["/C", "start", "", path])
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn installed_launch_command(path: &str) -> LaunchCommand {
+    LaunchCommand::new("xdg-open", &[path])
+}
+
+/// Entry for a System Settings pane. `bundle` is the .prefPane for panes
+/// found on disk, used for its icon.
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub fn settings_pane_entry(name: &str, pane_id: &str, bundle: Option<&Path>) -> ApplicationEntry {
+    let url = format!("{}{}", SETTINGS_PANE_URL_PREFIX, pane_id);
+    ApplicationEntry {
+        id: super::app_monitor::hash_string(&url),
+        name: name.to_string(),
+        executable_path: url,
+        app_path: bundle.map(|b| b.to_string_lossy().to_string()),
+        icon: None,
+        usage_count: 0,
+        last_launched: None,
+        platform: "macos".to_string(),
+        // "bluetooth settings" finds the Bluetooth pane
+        alternate_names: Some(vec![format!("{} Settings", name)]),
+        bundle_id: Some(pane_id.to_string()),
+        source: AppSource::SettingsPane,
+    }
+}
+
+/// Entry for a Store app from its display name and AppUserModelID
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn store_app_entry(name: &str, aumid: &str) -> ApplicationEntry {
+    let launch_path = format!("{}{}", APPS_FOLDER_PREFIX, aumid);
+    ApplicationEntry {
+        id: super::app_monitor::hash_string(&launch_path),
+        name: name.to_string(),
+        executable_path: launch_path,
+        app_path: None,
+        icon: None,
+        usage_count: 0,
+        last_launched: None,
+        platform: "windows".to_string(),
+        alternate_names: None,
+        bundle_id: Some(aumid.to_string()),
+        source: AppSource::StoreApp,
+    }
+}
+
+/// Store apps from `Get-StartApps | ConvertTo-Json` output. Packaged apps
+/// are the ones whose AppID is an AUMID (`PackageFamilyName!AppId`); the
+/// rest are shortcuts the directory scan already finds. PowerShell emits a
+/// bare object instead of an array when there's a single app.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn parse_start_apps(json: &str) -> Vec<ApplicationEntry> {
+    let value: serde_json::Value = match serde_json::from_str(json.trim()) {
+        Ok(value) => value,
+        Err(_) => return Vec::new(),
+    };
+    let rows = match value {
+        serde_json::Value::Array(rows) => rows,
+        row @ serde_json::Value::Object(_) => vec![row],
+        _ => return Vec::new(),
+    };
+
+    rows.iter()
+        .filter_map(|row| {
+            let name = row.get("Name")?.as_str()?.trim();
+            let aumid = row.get("AppID")?.as_str()?.trim();
+            (!name.is_empty() && aumid.contains('!')).then(|| store_app_entry(name, aumid))
+        })
+        .collect()
+}
+
+/// Settings panes: the known pane ids plus .prefPane bundles, which is all
+/// there is before macOS 13 and where third-party panes still live
+#[cfg(target_os = "macos")]
+fn scan_settings_panes() -> Vec<ApplicationEntry> {
+    let home = std::path::PathBuf::from(std::env::var("HOME").unwrap_or_default());
+    let pane_dirs = [
+        std::path::PathBuf::from("/System/Library/PreferencePanes"),
+        std::path::PathBuf::from("/Library/PreferencePanes"),
+        home.join("Library/PreferencePanes"),
+    ];
+
+    let mut panes: Vec<ApplicationEntry> = KNOWN_SETTINGS_PANES
+        .iter()
+        .map(|(name, pane_id)| settings_pane_entry(name, pane_id, None))
+        .collect();
+
+    for dir in &pane_dirs {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            continue;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("prefPane") {
+                continue;
+            }
+            let Some(stem) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let plist = path.join("Contents/Info.plist");
+            let name = super::app_monitor::read_plist_value(&plist, "NSPrefPaneIconLabel")
+                .or_else(|| super::app_monitor::read_plist_value(&plist, "CFBundleName"))
+                .unwrap_or_else(|| stem.to_string());
+            // A known pane of the same name is the one that opens on 13+
+            if panes.iter().any(|p| p.name.eq_ignore_ascii_case(&name)) {
+                continue;
+            }
+            let pane_id = super::app_monitor::read_plist_value(&plist, "CFBundleIdentifier")
+                .unwrap_or_else(|| stem.to_string());
+            panes.push(settings_pane_entry(&name, &pane_id, Some(&path)));
+        }
+    }
+
+    panes
+}
+
+#[cfg(target_os = "windows")]
+fn scan_store_apps() -> Vec<ApplicationEntry> {
+    let output = std::process::Command::new("powershell")
+        .args(["-NoProfile", "-NonInteractive", "-Command", "Get-StartApps | ConvertTo-Json -Compress"])
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => parse_start_apps(&String::from_utf8_lossy(&output.stdout)),
+        Ok(output) => {
+            eprintln!("[AppSources] Get-StartApps failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+            Vec::new()
+        }
+        Err(e) => {
+            eprintln!("[AppSources] PowerShell unavailable: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_settings_pane_entry_opens_pane_url() {
+        let pane = settings_pane_entry("Bluetooth", "com.apple.BluetoothSettings", None);
+        assert_eq!(pane.executable_path, "x-apple.systempreferences:com.apple.BluetoothSettings");
+        assert_eq!(pane.source, AppSource::SettingsPane);
+        assert_eq!(pane.alternate_names, Some(vec!["Bluetooth Settings".to_string()]));
+        assert_eq!(pane.app_path, None);
+
+        let bundle = Path::new("/Library/PreferencePanes/Flash.prefPane");
+        let legacy = settings_pane_entry("Flash", "com.example.flash", Some(bundle));
+        assert_eq!(legacy.app_path.as_deref(), Some("/Library/PreferencePanes/Flash.prefPane"));
+        assert_ne!(legacy.id, pane.id);
+    }
+
+    #[test]
+    fn test_parse_start_apps_keeps_packaged_apps() {
+        let json = r#"[
+            {"Name": "Calculator", "AppID": "Microsoft.WindowsCalculator_8wekyb3d8bbwe!App"},
+            {"Name": "Notepad++", "AppID": "C:\\Program Files\\Notepad++\\notepad++.exe"},
+            {"Name": "", "AppID": "Broken_1234!App"}
+        ]"#;
+        let apps = parse_start_apps(json);
+        assert_eq!(apps.len(), 1);
+        assert_eq!(apps[0].name, "Calculator");
+        assert_eq!(apps[0].executable_path, "shell:AppsFolder\\Microsoft.WindowsCalculator_8wekyb3d8bbwe!App");
+        assert_eq!(apps[0].bundle_id.as_deref(), Some("Microsoft.WindowsCalculator_8wekyb3d8bbwe!App"));
+        assert_eq!(apps[0].source, AppSource::StoreApp);
+
+        // A single app comes back as a bare object
+        let single = r#"{"Name": "Photos", "AppID": "Microsoft.Windows.Photos_8wekyb3d8bbwe!App"}"#;
+        assert_eq!(parse_start_apps(single).len(), 1);
+    }
+
+    #[test]
+    fn test_parse_start_apps_degrades_to_empty() {
+        assert!(parse_start_apps("").is_empty());
+        assert!(parse_start_apps("'Get-StartApps' is not recognized").is_empty());
+        assert!(parse_start_apps("42").is_empty());
+    }
+
+    #[test]
+    fn test_launch_command_per_source() {
+        assert_eq!(
+            AppSource::SettingsPane.launch_command("x-apple.systempreferences:com.apple.BluetoothSettings"),
+            LaunchCommand::new("open", &["x-apple.systempreferences:com.apple.BluetoothSettings"])
+        );
+        assert_eq!(
+            AppSource::StoreApp.launch_command("shell:AppsFolder\\Microsoft.WindowsCalculator_8wekyb3d8bbwe!App"),
+            LaunchCommand::new("explorer.exe", &["shell:AppsFolder\\Microsoft.WindowsCalculator_8wekyb3d8bbwe!App"])
+        );
+
+        let installed = AppSource::Installed.launch_command("/Applications/Safari.app");
+        #[cfg(target_os = "macos")]
+        assert_eq!(installed, LaunchCommand::new("open", &["/Applications/Safari.app"]));
+        #[cfg(target_os = "windows")]
+        assert_eq!(installed, LaunchCommand::new("cmd", &["/C", "start", "", "/Applications/Safari.app"]));
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        assert_eq!(installed, LaunchCommand::new("xdg-open", &["/Applications/Safari.app"]));
+    }
+
+    #[test]
+    fn test_infer_source_from_launch_path() {
+        assert_eq!(AppSource::infer("x-apple.systempreferences:com.apple.BluetoothSettings"), AppSource::SettingsPane);
+        assert_eq!(AppSource::infer("/System/Library/PreferencePanes/Displays.prefPane"), AppSource::SettingsPane);
+        assert_eq!(AppSource::infer("shell:AppsFolder\\Microsoft.WindowsCalculator_8wekyb3d8bbwe!App"), AppSource::StoreApp);
+        assert_eq!(AppSource::infer("/Applications/Safari.app"), AppSource::Installed);
+    }
+
+    #[test]
+    fn test_disabled_sources_contribute_nothing() {
+        let none = AppSources {
+            settings_panes: false,
+            store_apps: false,
+        };
+        assert!(none.scan().is_empty());
+
+        let settings = AppSettings {
+            index_settings_panes: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            AppSources::from_settings(&settings),
+            AppSources {
+                settings_panes: false,
+                store_apps: true,
+            }
+        );
+    }
+}
@@ -0,0 +1,113 @@
+//! App Update Check
+//! Compares the running version with the latest release published at the
+//! configured endpoint (GitHub releases JSON). Only reports; nothing is
+//! downloaded or installed.
+
+use crate::services::http;
+use serde::{Deserialize, Serialize};
+
+/// Latest-release endpoint used unless settings override it
+pub const DEFAULT_UPDATE_ENDPOINT: &str = "https://api.github.com/repos/Chee-0806/etools/releases/latest";
+
+/// The fields of a GitHub release this check uses
+#[derive(Debug, Clone, Deserialize)]
+struct Release {
+    tag_name: String,
+    html_url: String,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// `check_app_update` result
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AppUpdateInfo {
+    pub current: String,
+    pub latest: String,
+    /// Release page
+    pub url: String,
+    pub notes: Option<String>,
+    pub update_available: bool,
+}
+
+/// Numeric components of a version like "v1.2.3" or "1.2.3-beta.1";
+/// pre-release suffixes are ignored
+fn version_parts(version: &str) -> Vec<u64> {
+    version
+        .trim()
+        .trim_start_matches(['v', 'V'])
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Whether `latest` is a higher version than `current`
+pub fn is_newer(latest: &str, current: &str) -> bool {
+    let (mut latest, mut current) = (version_parts(latest), version_parts(current));
+    let len = latest.len().max(current.len());
+    latest.resize(len, 0);
+    current.resize(len, 0);
+    latest > current
+}
+
+fn update_info(release: Release, current: &str) -> AppUpdateInfo {
+    let latest = release.tag_name.trim_start_matches(['v', 'V']).to_string();
+    AppUpdateInfo {
+        update_available: is_newer(&latest, current),
+        current: current.to_string(),
+        latest,
+        url: release.html_url,
+        notes: release.body.filter(|notes| !notes.trim().is_empty()),
+    }
+}
+
+/// Fetch the latest release from `endpoint` and compare it with `current`
+pub async fn check_for_update(endpoint: &str, current: &str) -> Result<AppUpdateInfo, String> {
+    let response = http::get(endpoint)
+        .await
+        .map_err(|e| format!("Update check failed: {}", e))?;
+    let release: Release = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid release response: {}", e))?;
+    Ok(update_info(release, current))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_version_comparison() {
+        assert!(is_newer("v0.2.0", "0.1.9"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(is_newer("0.1.1", "0.1"));
+        assert!(!is_newer("0.1.0", "0.1.0"));
+        assert!(!is_newer("v0.1.0-beta.2", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+    }
+
+    #[test]
+    fn test_update_info_from_release_json() {
+        let release: Release = serde_json::from_value(serde_json::json!({
+            "tag_name": "v0.3.0",
+            "html_url": "https://github.com/Chee-0806/etools/releases/tag/v0.3.0",
+            "body": "Faster search",
+            "assets": [],
+        }))
+        .unwrap();
+
+        assert_eq!(
+            update_info(release, "0.1.0"),
+            AppUpdateInfo {
+                current: "0.1.0".to_string(),
+                latest: "0.3.0".to_string(),
+                url: "https://github.com/Chee-0806/etools/releases/tag/v0.3.0".to_string(),
+                notes: Some("Faster search".to_string()),
+                update_available: true,
+            }
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Application Watcher
+//! Watches the application directories and rescans the app list when apps
+//! are installed, updated or removed. Installs and updates touch hundreds of
+//! files, so events are debounced into one rescan per burst.
+
+use crate::services::app_monitor::AppMonitor;
+use crate::services::search::SearchCache;
+use crate::types::events::{self, AppsChanged};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Rescan once events have stopped for this long
+const QUIET_PERIOD: Duration = Duration::from_secs(2);
+
+/// Rescan at least this often during a continuous burst
+const MAX_DELAY: Duration = Duration::from_secs(10);
+
+/// Start menu folders nest, the macOS and Linux app directories don't
+const WATCH_MODE: RecursiveMode = if cfg!(target_os = "windows") {
+    RecursiveMode::Recursive
+} else {
+    RecursiveMode::NonRecursive
+};
+
+/// Collapses a burst of events into one action, fired after a quiet period
+/// or once the burst has lasted `max_delay`
+#[derive(Debug)]
+pub struct Debounce {
+    quiet: Duration,
+    max_delay: Duration,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl Debounce {
+    pub fn new(quiet: Duration, max_delay: Duration) -> Self {
+        Self {
+            quiet,
+            max_delay,
+            first: None,
+            last: None,
+        }
+    }
+
+    pub fn event(&mut self, now: Instant) {
+        self.first.get_or_insert(now);
+        self.last = Some(now);
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.first.is_some()
+    }
+
+    /// When the pending burst fires, if there is one
+    pub fn deadline(&self) -> Option<Instant> {
+        match (self.first, self.last) {
+            (Some(first), Some(last)) => Some((last + self.quiet).min(first + self.max_delay)),
+            _ => None,
+        }
+    }
+
+    /// Whether the pending burst is due; clears it if so
+    pub fn take_due(&mut self, now: Instant) -> bool {
+        match self.deadline() {
+            Some(deadline) if now >= deadline => {
+                self.first = None;
+                self.last = None;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Only events that can add, remove or change an app count
+fn is_relevant(event: &Event) -> bool {
+    !matches!(event.kind, EventKind::Access(_))
+}
+
+/// Feed watcher events through `debounce`, calling `on_change` once per
+/// burst. Returns when the sender disconnects, flushing a pending burst.
+pub fn run_debounced(rx: Receiver<Event>, mut debounce: Debounce, mut on_change: impl FnMut()) {
+    loop {
+        let received = match debounce.deadline() {
+            Some(deadline) => rx.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+        };
+
+        match received {
+            Ok(event) if is_relevant(&event) => debounce.event(Instant::now()),
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => {
+                if debounce.is_pending() {
+                    on_change();
+                }
+                return;
+            }
+        }
+
+        if debounce.take_due(Instant::now()) {
+            on_change();
+        }
+    }
+}
+
+/// Rescan `monitor` and drop cached search results. Returns the number of
+/// applications found.
+pub fn refresh_apps(monitor: &Mutex<AppMonitor>, cache: &SearchCache) -> Result<usize, String> {
+    let total = AppMonitor::rescan_shared(monitor)?.len();
+    cache.invalidate();
+    Ok(total)
+}
+
+/// Rescan the search app list and tell the frontend about it
+pub fn refresh_and_notify<R: Runtime>(handle: &AppHandle<R>) -> Result<usize, String> {
+    let state = handle.state::<crate::cmds::search::SearchState>();
+    let total = refresh_apps(&state.app_monitor, &state.cache)?;
+    let _ = events::emit(handle, AppsChanged { total });
+    Ok(total)
+}
+
+/// Watch the application directories of `monitor` for the app's lifetime
+pub fn start_app_watcher(handle: AppHandle, monitor: Arc<Mutex<AppMonitor>>) -> Result<(), String> {
+    let dirs = monitor
+        .lock()
+        .map_err(|e| format!("Lock error: {}", e))?
+        .dirs()
+        .to_vec();
+
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |res: Result<Event, _>| {
+        if let Ok(event) = res {
+            let _ = tx.send(event);
+        }
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    for dir in dirs.iter().filter(|d| d.is_dir()) {
+        if let Err(e) = watcher.watch(dir, WATCH_MODE) {
+            eprintln!("[AppWatcher] Failed to watch {}: {}", dir.display(), e);
+        }
+    }
+
+    thread::spawn(move || {
+        // The watcher stops sending when dropped
+        let _watcher = watcher;
+        run_debounced(rx, Debounce::new(QUIET_PERIOD, MAX_DELAY), || {
+            match refresh_and_notify(&handle) {
+                Ok(total) => println!("[AppWatcher] Applications changed, {} installed", total),
+                Err(e) => eprintln!("[AppWatcher] Rescan failed: {}", e),
+            }
+        });
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{AccessKind, CreateKind, ModifyKind};
+    use std::path::PathBuf;
+
+    fn event(kind: EventKind) -> Event {
+        Event::new(kind).add_path(PathBuf::from("/Applications/Example.app"))
+    }
+
+    #[test]
+    fn test_debounce_waits_for_quiet_period() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut debounce = Debounce::new(ms(100), ms(1000));
+        assert!(!debounce.take_due(start));
+
+        debounce.event(start);
+        debounce.event(start + ms(50));
+        assert!(!debounce.take_due(start + ms(120)));
+        assert!(debounce.take_due(start + ms(150)));
+        // Cleared once fired
+        assert!(!debounce.is_pending());
+        assert!(!debounce.take_due(start + ms(500)));
+    }
+
+    #[test]
+    fn test_debounce_fires_during_long_burst() {
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let mut debounce = Debounce::new(ms(100), ms(300));
+
+        for i in 0..10 {
+            debounce.event(start + ms(i * 50));
+        }
+        assert_eq!(debounce.deadline(), Some(start + ms(300)));
+        assert!(debounce.take_due(start + ms(300)));
+    }
+
+    #[test]
+    fn test_burst_of_events_refreshes_once() {
+        let (tx, rx) = channel();
+        for _ in 0..200 {
+            tx.send(event(EventKind::Modify(ModifyKind::Any))).unwrap();
+        }
+        drop(tx);
+
+        let mut calls = 0;
+        run_debounced(rx, Debounce::new(Duration::from_millis(50), Duration::from_secs(5)), || calls += 1);
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_separate_bursts_refresh_separately() {
+        let (tx, rx) = channel();
+        let sender = thread::spawn(move || {
+            tx.send(event(EventKind::Create(CreateKind::Folder))).unwrap();
+            thread::sleep(Duration::from_millis(300));
+            tx.send(event(EventKind::Create(CreateKind::Folder))).unwrap();
+        });
+
+        let mut calls = 0;
+        run_debounced(rx, Debounce::new(Duration::from_millis(50), Duration::from_secs(5)), || calls += 1);
+        sender.join().unwrap();
+        assert_eq!(calls, 2);
+    }
+
+    #[test]
+    fn test_access_events_are_ignored() {
+        let (tx, rx) = channel();
+        tx.send(event(EventKind::Access(AccessKind::Any))).unwrap();
+        drop(tx);
+
+        let mut calls = 0;
+        run_debounced(rx, Debounce::new(Duration::from_millis(10), Duration::from_secs(1)), || calls += 1);
+        assert_eq!(calls, 0);
+    }
+
+    #[test]
+    fn test_refresh_invalidates_search_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let monitor = Mutex::new(AppMonitor::with_dirs(vec![dir.path().to_path_buf()]));
+        let cache = SearchCache::new();
+
+        let before = cache.stats().invalidations;
+        refresh_apps(&monitor, &cache).unwrap();
+        assert_eq!(cache.stats().invalidations, before + 1);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_refresh_picks_up_installs_and_removals() {
+        let dir = tempfile::tempdir().unwrap();
+        let monitor = Mutex::new(AppMonitor::with_dirs(vec![dir.path().to_path_buf()]));
+        let cache = SearchCache::new();
+        assert!(monitor.lock().unwrap().scan_apps().is_empty());
+
+        let desktop = dir.path().join("editor.desktop");
+        std::fs::write(&desktop, "[Desktop Entry]\nName=Editor\nExec=editor\n").unwrap();
+        // Reads stay cached until a rescan
+        assert!(monitor.lock().unwrap().scan_apps().is_empty());
+        assert_eq!(refresh_apps(&monitor, &cache).unwrap(), 1);
+        assert_eq!(monitor.lock().unwrap().scan_apps()[0].name, "Editor");
+
+        std::fs::remove_file(&desktop).unwrap();
+        assert_eq!(refresh_apps(&monitor, &cache).unwrap(), 0);
+        assert!(monitor.lock().unwrap().scan_apps().is_empty());
+    }
+}
@@ -0,0 +1,599 @@
+//! Backup and Restore
+//! Packs settings, abbreviations, plugin state, clipboard history and the
+//! SQLite databases into a single zip with a manifest, and restores selected
+//! components through a staging directory so a failed restore leaves the
+//! current data untouched.
+
+use crate::cmds::plugins::{plugin_settings_path, plugin_state_path};
+use crate::db::browser::BROWSER_SCHEMA;
+use crate::db::files::FILES_SCHEMA;
+use crate::db::migrations::{schema_version, Schema};
+use crate::models::preferences::{migrate_settings, settings_schema_version, SETTINGS_SCHEMA_VERSION};
+use crate::services::plugin_sandbox::plugin_permissions_path;
+use rusqlite::{Connection, DatabaseName};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use zip::write::FileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Backup archive format version
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Staging directory (under the data dir) used while restoring
+const STAGING_DIR: &str = ".restore-staging";
+
+#[derive(Debug, Clone, Copy)]
+enum Root {
+    Config,
+    Data,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ComponentKind {
+    File,
+    Dir,
+    Database,
+}
+
+/// A piece of app state that can be backed up on its own
+#[derive(Debug)]
+struct Component {
+    name: &'static str,
+    root: Root,
+    /// Location of the component below its root
+    path: fn(&Path) -> PathBuf,
+    kind: ComponentKind,
+}
+
+const COMPONENTS: &[Component] = &[
+    Component { name: "settings", root: Root::Config, path: |dir| dir.join("settings.json"), kind: ComponentKind::File },
+    Component { name: "abbreviations", root: Root::Config, path: |dir| dir.join("abbreviations.json"), kind: ComponentKind::File },
+    Component { name: "plugin_state", root: Root::Data, path: plugin_state_path, kind: ComponentKind::File },
+    Component { name: "plugin_settings", root: Root::Data, path: plugin_settings_path, kind: ComponentKind::File },
+    Component { name: "plugin_permissions", root: Root::Data, path: plugin_permissions_path, kind: ComponentKind::File },
+    Component { name: "clipboard", root: Root::Data, path: |dir| dir.join("clipboard"), kind: ComponentKind::Dir },
+    Component { name: "usage", root: Root::Data, path: |dir| dir.join("usage_analytics.db"), kind: ComponentKind::Database },
+    Component { name: "browser_cache", root: Root::Data, path: |dir| dir.join("browser_cache.db"), kind: ComponentKind::Database },
+    Component { name: "file_index", root: Root::Data, path: |dir| dir.join("files_index.db"), kind: ComponentKind::Database },
+];
+
+/// Where the app keeps its state
+#[derive(Debug, Clone)]
+pub struct BackupPaths {
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+}
+
+impl BackupPaths {
+    fn resolve(&self, component: &Component) -> PathBuf {
+        match component.root {
+            Root::Config => (component.path)(&self.config_dir),
+            Root::Data => (component.path)(&self.data_dir),
+        }
+    }
+}
+
+/// What to include when creating or restoring a backup
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BackupOptions {
+    /// Clipboard history can hold sensitive data, so it's opt-in
+    pub include_clipboard: bool,
+    /// The file index is large and can be rebuilt instead
+    pub include_file_index: bool,
+    /// Restrict to these component names; all others are skipped
+    pub components: Option<Vec<String>>,
+}
+
+impl BackupOptions {
+    fn includes(&self, name: &str) -> bool {
+        let allowed = match name {
+            "clipboard" => self.include_clipboard,
+            "file_index" => self.include_file_index,
+            _ => true,
+        };
+
+        allowed
+            && self
+                .components
+                .as_ref()
+                .map_or(true, |names| names.iter().any(|n| n == name))
+    }
+}
+
+/// Backup manifest, stored as manifest.json in the archive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    pub app_version: String,
+    /// Unix timestamp (ms)
+    pub created_at: i64,
+    pub settings_schema_version: u32,
+    pub db_schema_versions: HashMap<String, u32>,
+    pub components: Vec<String>,
+}
+
+/// Result of a restore
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreReport {
+    pub restored: Vec<String>,
+    /// Settings schema version the restored settings were migrated from, if any
+    pub settings_migrated_from: Option<u32>,
+}
+
+fn component(name: &str) -> Option<&'static Component> {
+    COMPONENTS.iter().find(|c| c.name == name)
+}
+
+/// Newest schema version this build can open for a database component.
+/// Databases without migrations are always at version 0.
+fn latest_db_schema_version(name: &str) -> u32 {
+    let schema: Option<&Schema> = match name {
+        "browser_cache" => Some(&BROWSER_SCHEMA),
+        "file_index" => Some(&FILES_SCHEMA),
+        _ => None,
+    };
+    schema.map_or(0, Schema::latest_version)
+}
+
+fn file_name(path: &Path) -> Result<String, String> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.to_string())
+        .ok_or_else(|| format!("Invalid path: {:?}", path))
+}
+
+/// All files below a directory, relative to it
+fn collect_files(dir: &Path, prefix: &Path, out: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read {:?}: {}", dir, e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let relative = prefix.join(entry.file_name());
+        if path.is_dir() {
+            collect_files(&path, &relative, out)?;
+        } else {
+            out.push(relative);
+        }
+    }
+    Ok(())
+}
+
+fn add_file<W: Write + std::io::Seek>(zip: &mut ZipWriter<W>, name: &str, source: &Path) -> Result<(), String> {
+    let bytes = fs::read(source).map_err(|e| format!("Failed to read {:?}: {}", source, e))?;
+    zip.start_file(name, FileOptions::default())
+        .map_err(|e| format!("Failed to add {} to backup: {}", name, e))?;
+    zip.write_all(&bytes)
+        .map_err(|e| format!("Failed to write {} to backup: {}", name, e))
+}
+
+/// Create a backup archive at `target`. The archive is written next to the
+/// target and renamed into place once complete.
+pub fn create_backup(paths: &BackupPaths, target: &Path, options: &BackupOptions) -> Result<BackupManifest, String> {
+    let snapshot_dir = tempfile::tempdir().map_err(|e| format!("Failed to create temp dir: {}", e))?;
+    let partial = PathBuf::from(format!("{}.partial", target.to_string_lossy()));
+    let file = fs::File::create(&partial).map_err(|e| format!("Failed to create backup file: {}", e))?;
+    let mut zip = ZipWriter::new(file);
+
+    let mut manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+        settings_schema_version: SETTINGS_SCHEMA_VERSION,
+        db_schema_versions: HashMap::new(),
+        components: vec![],
+    };
+
+    let result = (|| -> Result<(), String> {
+        for component in COMPONENTS.iter().filter(|c| options.includes(c.name)) {
+            let source = paths.resolve(component);
+            if !source.exists() {
+                continue;
+            }
+
+            match component.kind {
+                ComponentKind::File => {
+                    if component.name == "settings" {
+                        let value: serde_json::Value = fs::read_to_string(&source)
+                            .ok()
+                            .and_then(|content| serde_json::from_str(&content).ok())
+                            .unwrap_or_default();
+                        manifest.settings_schema_version = settings_schema_version(&value);
+                    }
+                    add_file(&mut zip, &format!("{}/{}", component.name, file_name(&source)?), &source)?;
+                }
+                ComponentKind::Dir => {
+                    let mut files = vec![];
+                    collect_files(&source, Path::new(""), &mut files)?;
+                    for relative in files {
+                        let name = format!("{}/{}", component.name, relative.to_string_lossy().replace('\\', "/"));
+                        add_file(&mut zip, &name, &source.join(&relative))?;
+                    }
+                }
+                ComponentKind::Database => {
+                    // The backup API copies a consistent snapshot even while the DB is in use
+                    let snapshot = snapshot_dir.path().join(file_name(&source)?);
+                    Connection::open(&source)
+                        .and_then(|conn| conn.backup(DatabaseName::Main, &snapshot, None))
+                        .map_err(|e| format!("Failed to snapshot {}: {}", component.name, e))?;
+                    let version = Connection::open(&snapshot)
+                        .and_then(|conn| schema_version(&conn))
+                        .map_err(|e| format!("Failed to read {} schema version: {}", component.name, e))?;
+                    add_file(&mut zip, &format!("{}/{}", component.name, file_name(&source)?), &snapshot)?;
+                    manifest.db_schema_versions.insert(component.name.to_string(), version);
+                }
+            }
+
+            manifest.components.push(component.name.to_string());
+        }
+
+        let content = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        zip.start_file(MANIFEST_NAME, FileOptions::default())
+            .and_then(|_| zip.write_all(content.as_bytes()).map_err(Into::into))
+            .and_then(|_| zip.finish().map(|_| ()))
+            .map_err(|e| format!("Failed to write backup: {}", e))
+    })();
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+
+    fs::rename(&partial, target).map_err(|e| format!("Failed to move backup into place: {}", e))?;
+    Ok(manifest)
+}
+
+/// Read and validate the manifest of a backup archive
+pub fn read_manifest(source: &Path) -> Result<BackupManifest, String> {
+    let file = fs::File::open(source).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Invalid backup archive: {}", e))?;
+    read_manifest_from(&mut archive)
+}
+
+fn read_manifest_from<R: Read + std::io::Seek>(archive: &mut ZipArchive<R>) -> Result<BackupManifest, String> {
+    let mut content = String::new();
+    archive
+        .by_name(MANIFEST_NAME)
+        .map_err(|_| "Backup has no manifest".to_string())?
+        .read_to_string(&mut content)
+        .map_err(|e| format!("Failed to read manifest: {}", e))?;
+
+    let manifest: BackupManifest =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid manifest: {}", e))?;
+
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        return Err(format!(
+            "Backup format version {} is newer than supported version {}",
+            manifest.format_version, BACKUP_FORMAT_VERSION
+        ));
+    }
+    if manifest.settings_schema_version > SETTINGS_SCHEMA_VERSION {
+        return Err(format!(
+            "Backup settings schema version {} is newer than supported version {}",
+            manifest.settings_schema_version, SETTINGS_SCHEMA_VERSION
+        ));
+    }
+    for (name, version) in &manifest.db_schema_versions {
+        let latest = latest_db_schema_version(name);
+        if *version > latest {
+            return Err(format!(
+                "Backup {} database schema version {} is newer than supported version {}",
+                name, version, latest
+            ));
+        }
+    }
+
+    Ok(manifest)
+}
+
+/// Path a component is staged at: the component's directory for Dir
+/// components, the single file inside it otherwise
+fn staged_path(paths: &BackupPaths, staging: &Path, component: &Component) -> PathBuf {
+    let dir = staging.join(component.name);
+    match component.kind {
+        ComponentKind::Dir => dir,
+        _ => dir.join(paths.resolve(component).file_name().unwrap_or_default()),
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.bak", path.to_string_lossy()))
+}
+
+/// Move every staged component into place. Existing data is moved aside
+/// first and put back if any step fails.
+fn swap_in(paths: &BackupPaths, staging: &Path, selected: &[&Component]) -> Result<(), String> {
+    let mut swapped: Vec<(PathBuf, bool)> = vec![];
+
+    let result = (|| -> Result<(), String> {
+        for component in selected {
+            let target = paths.resolve(component);
+            let had_existing = target.exists();
+
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+            }
+            if had_existing {
+                fs::rename(&target, backup_path(&target))
+                    .map_err(|e| format!("Failed to move aside {:?}: {}", target, e))?;
+            }
+            swapped.push((target.clone(), had_existing));
+
+            if component.kind == ComponentKind::Database {
+                for suffix in ["-journal", "-wal", "-shm"] {
+                    let _ = fs::remove_file(format!("{}{}", target.to_string_lossy(), suffix));
+                }
+            }
+
+            fs::rename(staged_path(paths, staging, component), &target)
+                .map_err(|e| format!("Failed to restore {}: {}", component.name, e))?;
+        }
+        Ok(())
+    })();
+
+    for (target, had_existing) in swapped.into_iter().rev() {
+        let aside = backup_path(&target);
+        if result.is_ok() {
+            let _ = if aside.is_dir() { fs::remove_dir_all(&aside) } else { fs::remove_file(&aside) };
+            continue;
+        }
+
+        let _ = if target.is_dir() { fs::remove_dir_all(&target) } else { fs::remove_file(&target) };
+        if had_existing {
+            let _ = fs::rename(&aside, &target);
+        }
+    }
+
+    result
+}
+
+/// Restore the selected components of a backup. Callers are responsible for
+/// pausing anything that writes to the restored files.
+pub fn restore_backup(paths: &BackupPaths, source: &Path, options: &BackupOptions) -> Result<RestoreReport, String> {
+    let file = fs::File::open(source).map_err(|e| format!("Failed to open backup: {}", e))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| format!("Invalid backup archive: {}", e))?;
+    let manifest = read_manifest_from(&mut archive)?;
+
+    let selected: Vec<&Component> = manifest
+        .components
+        .iter()
+        .filter(|name| options.includes(name))
+        .filter_map(|name| component(name))
+        .collect();
+
+    let staging = paths.data_dir.join(STAGING_DIR);
+    if staging.exists() {
+        fs::remove_dir_all(&staging).map_err(|e| format!("Failed to clear staging dir: {}", e))?;
+    }
+
+    let result = (|| -> Result<RestoreReport, String> {
+        for component in &selected {
+            fs::create_dir_all(staging.join(component.name))
+                .map_err(|e| format!("Failed to create staging dir: {}", e))?;
+        }
+
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i).map_err(|e| format!("Failed to read backup: {}", e))?;
+            // enclosed_name rejects entries that would escape the staging dir
+            let Some(relative) = entry.enclosed_name().map(|p| p.to_path_buf()) else {
+                continue;
+            };
+            let Some(top) = relative.components().next().and_then(|c| c.as_os_str().to_str()) else {
+                continue;
+            };
+            if !selected.iter().any(|c| c.name == top) || entry.is_dir() {
+                continue;
+            }
+
+            let dest = staging.join(&relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("Failed to create staging dir: {}", e))?;
+            }
+            let mut out = fs::File::create(&dest).map_err(|e| format!("Failed to stage {:?}: {}", relative, e))?;
+            std::io::copy(&mut entry, &mut out).map_err(|e| format!("Failed to stage {:?}: {}", relative, e))?;
+        }
+
+        let mut settings_migrated_from = None;
+        if let Some(settings) = selected.iter().find(|c| c.name == "settings") {
+            let path = staged_path(paths, &staging, settings);
+            let value: serde_json::Value = fs::read_to_string(&path)
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .ok_or("Backup contains unreadable settings")?;
+
+            let from = settings_schema_version(&value);
+            let migrated = migrate_settings(value)?;
+            if from < SETTINGS_SCHEMA_VERSION {
+                settings_migrated_from = Some(from);
+            }
+
+            let content = serde_json::to_string_pretty(&migrated)
+                .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+            fs::write(&path, content).map_err(|e| format!("Failed to stage settings: {}", e))?;
+        }
+
+        swap_in(paths, &staging, &selected)?;
+
+        Ok(RestoreReport {
+            restored: selected.iter().map(|c| c.name.to_string()).collect(),
+            settings_migrated_from,
+        })
+    })();
+
+    let _ = fs::remove_dir_all(&staging);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::analytics::{insert_event, open_analytics_db, summarize_events};
+    use crate::db::browser::open_browser_db;
+    use crate::db::files::open_files_db;
+
+    fn app_dirs(root: &Path) -> BackupPaths {
+        let paths = BackupPaths {
+            config_dir: root.join("config"),
+            data_dir: root.join("data"),
+        };
+        fs::create_dir_all(&paths.config_dir).unwrap();
+        fs::create_dir_all(&paths.data_dir).unwrap();
+        paths
+    }
+
+    fn seed(paths: &BackupPaths) {
+        // An unversioned settings file, as written before schema versions existed
+        fs::write(paths.config_dir.join("settings.json"), r#"{ "language": "zh-CN", "max_results": 20 }"#).unwrap();
+        fs::write(paths.config_dir.join("abbreviations.json"), r#"{ "categories": [] }"#).unwrap();
+        fs::create_dir_all(paths.data_dir.join("clipboard")).unwrap();
+        fs::write(paths.data_dir.join("clipboard/item-1"), "secret").unwrap();
+
+        let conn = open_analytics_db(&paths.data_dir.join("usage_analytics.db")).unwrap();
+        insert_event(&conn, "launch", "Terminal", 1_700_000_000).unwrap();
+    }
+
+    #[test]
+    fn test_round_trip_migrates_older_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = app_dirs(&dir.path().join("old-machine"));
+        seed(&source);
+
+        let archive = dir.path().join("etools-backup.zip");
+        let manifest = create_backup(&source, &archive, &BackupOptions::default()).unwrap();
+        assert_eq!(manifest.settings_schema_version, 0);
+        assert_eq!(manifest.components, vec!["settings", "abbreviations", "usage"]);
+        assert_eq!(read_manifest(&archive).unwrap().db_schema_versions["usage"], 0);
+
+        let target = app_dirs(&dir.path().join("new-machine"));
+        fs::write(target.config_dir.join("settings.json"), r#"{ "language": "en" }"#).unwrap();
+
+        let report = restore_backup(&target, &archive, &BackupOptions::default()).unwrap();
+        assert_eq!(report.restored, vec!["settings", "abbreviations", "usage"]);
+        assert_eq!(report.settings_migrated_from, Some(0));
+
+        let value: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(target.config_dir.join("settings.json")).unwrap()).unwrap();
+        assert_eq!(value["schema_version"], SETTINGS_SCHEMA_VERSION);
+        assert_eq!(value["language"], "zh-CN");
+        assert_eq!(value["max_results"], 20);
+
+        let conn = open_analytics_db(&target.data_dir.join("usage_analytics.db")).unwrap();
+        assert_eq!(summarize_events(&conn, 0).unwrap().total_events, 1);
+
+        // Clipboard history is opt-in
+        assert!(!target.data_dir.join("clipboard").exists());
+        assert!(!target.data_dir.join(STAGING_DIR).exists());
+        assert!(!target.config_dir.join("settings.json.bak").exists());
+    }
+
+    #[test]
+    fn test_selected_components_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = app_dirs(&dir.path().join("source"));
+        seed(&source);
+
+        let archive = dir.path().join("backup.zip");
+        let options = BackupOptions { include_clipboard: true, ..Default::default() };
+        create_backup(&source, &archive, &options).unwrap();
+
+        let target = app_dirs(&dir.path().join("target"));
+        let restore_options = BackupOptions {
+            include_clipboard: true,
+            components: Some(vec!["clipboard".to_string()]),
+            ..Default::default()
+        };
+        let report = restore_backup(&target, &archive, &restore_options).unwrap();
+
+        assert_eq!(report.restored, vec!["clipboard"]);
+        assert_eq!(fs::read_to_string(target.data_dir.join("clipboard/item-1")).unwrap(), "secret");
+        assert!(!target.config_dir.join("settings.json").exists());
+    }
+
+    #[test]
+    fn test_round_trip_restores_plugin_state_and_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = app_dirs(&dir.path().join("source"));
+        fs::write(plugin_state_path(&source.data_dir), r#"{ "demo": false }"#).unwrap();
+        fs::write(plugin_settings_path(&source.data_dir), r#"{ "demo": { "interval": 5 } }"#).unwrap();
+
+        let archive = dir.path().join("backup.zip");
+        let manifest = create_backup(&source, &archive, &BackupOptions::default()).unwrap();
+        assert_eq!(manifest.components, vec!["plugin_state", "plugin_settings"]);
+
+        let target = app_dirs(&dir.path().join("target"));
+        fs::write(plugin_state_path(&target.data_dir), r#"{ "demo": true }"#).unwrap();
+
+        let report = restore_backup(&target, &archive, &BackupOptions::default()).unwrap();
+        assert_eq!(report.restored, vec!["plugin_state", "plugin_settings"]);
+        assert_eq!(fs::read_to_string(plugin_state_path(&target.data_dir)).unwrap(), r#"{ "demo": false }"#);
+        assert_eq!(
+            fs::read_to_string(plugin_settings_path(&target.data_dir)).unwrap(),
+            r#"{ "demo": { "interval": 5 } }"#
+        );
+    }
+
+    #[test]
+    fn test_manifest_records_each_database_schema_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = app_dirs(&dir.path().join("source"));
+        open_browser_db(&source.data_dir.join("browser_cache.db")).unwrap();
+        open_files_db(&source.data_dir.join("files_index.db")).unwrap();
+
+        let archive = dir.path().join("backup.zip");
+        let options = BackupOptions { include_file_index: true, ..Default::default() };
+        create_backup(&source, &archive, &options).unwrap();
+
+        let versions = read_manifest(&archive).unwrap().db_schema_versions;
+        assert_eq!(versions["browser_cache"], BROWSER_SCHEMA.latest_version());
+        assert_eq!(versions["file_index"], FILES_SCHEMA.latest_version());
+    }
+
+    #[test]
+    fn test_rejects_newer_database_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("future-db.zip");
+
+        let mut zip = ZipWriter::new(fs::File::create(&archive).unwrap());
+        zip.start_file(MANIFEST_NAME, FileOptions::default()).unwrap();
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION,
+            app_version: "99.0.0".to_string(),
+            created_at: 0,
+            settings_schema_version: SETTINGS_SCHEMA_VERSION,
+            db_schema_versions: HashMap::from([("browser_cache".to_string(), BROWSER_SCHEMA.latest_version() + 1)]),
+            components: vec!["browser_cache".to_string()],
+        };
+        zip.write_all(serde_json::to_string(&manifest).unwrap().as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let error = read_manifest(&archive).unwrap_err();
+        assert!(error.contains("browser_cache database schema version"), "{}", error);
+    }
+
+    #[test]
+    fn test_rejects_newer_backup() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("future.zip");
+
+        let mut zip = ZipWriter::new(fs::File::create(&archive).unwrap());
+        zip.start_file(MANIFEST_NAME, FileOptions::default()).unwrap();
+        let manifest = BackupManifest {
+            format_version: BACKUP_FORMAT_VERSION + 1,
+            app_version: "99.0.0".to_string(),
+            created_at: 0,
+            settings_schema_version: SETTINGS_SCHEMA_VERSION,
+            db_schema_versions: HashMap::new(),
+            components: vec![],
+        };
+        zip.write_all(serde_json::to_string(&manifest).unwrap().as_bytes()).unwrap();
+        zip.finish().unwrap();
+
+        let target = app_dirs(&dir.path().join("target"));
+        assert!(restore_backup(&target, &archive, &BackupOptions::default()).is_err());
+    }
+}
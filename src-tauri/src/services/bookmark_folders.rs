@@ -0,0 +1,216 @@
+//! Bookmark Folders
+//! Folder paths for cached bookmarks and the tree built from them. Chrome's
+//! nested JSON folders and Firefox's moz_bookmarks parents both map to the
+//! same "Bookmarks Bar/Morning/News" form, with the browsers' top-level
+//! folders renamed to shared names so the same folder lines up across them.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Separates folder names in a path
+pub const FOLDER_SEPARATOR: char = '/';
+
+/// More tabs than this are only opened when confirmed
+pub const MAX_TABS_WITHOUT_CONFIRMATION: usize = 10;
+
+const BOOKMARKS_BAR: &str = "Bookmarks Bar";
+const BOOKMARKS_MENU: &str = "Bookmarks Menu";
+const OTHER_BOOKMARKS: &str = "Other Bookmarks";
+const MOBILE_BOOKMARKS: &str = "Mobile Bookmarks";
+
+/// Top-level folder for a key under Chrome's `roots`
+pub fn chrome_root_name(key: &str) -> Option<&'static str> {
+    match key {
+        "bookmark_bar" => Some(BOOKMARKS_BAR),
+        "other" => Some(OTHER_BOOKMARKS),
+        "synced" => Some(MOBILE_BOOKMARKS),
+        _ => None,
+    }
+}
+
+/// Top-level folder for a Firefox root folder's guid
+pub fn firefox_root_name(guid: &str) -> Option<&'static str> {
+    match guid {
+        "toolbar_____" => Some(BOOKMARKS_BAR),
+        "menu________" => Some(BOOKMARKS_MENU),
+        "unfiled_____" => Some(OTHER_BOOKMARKS),
+        "mobile______" => Some(MOBILE_BOOKMARKS),
+        _ => None,
+    }
+}
+
+/// `parent` with `name` appended. A separator inside a name is swapped for
+/// a look-alike so it doesn't split the folder in two.
+pub fn join_folder(parent: &str, name: &str) -> String {
+    let name = name.replace(FOLDER_SEPARATOR, "\u{2215}");
+    if parent.is_empty() {
+        name
+    } else {
+        format!("{}{}{}", parent, FOLDER_SEPARATOR, name)
+    }
+}
+
+/// A folder with its bookmark counts and subfolders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BookmarkFolder {
+    pub name: String,
+    pub path: String,
+    /// Bookmarks directly in this folder
+    pub count: usize,
+    /// Bookmarks in this folder and all its subfolders
+    pub total: usize,
+    /// Sorted by name
+    pub children: Vec<BookmarkFolder>,
+}
+
+#[derive(Default)]
+struct FolderNode {
+    count: usize,
+    children: BTreeMap<String, FolderNode>,
+}
+
+impl FolderNode {
+    fn into_folder(self, name: String, parent: &str) -> BookmarkFolder {
+        let path = if parent.is_empty() {
+            name.clone()
+        } else {
+            format!("{}{}{}", parent, FOLDER_SEPARATOR, name)
+        };
+        let children: Vec<_> = self
+            .children
+            .into_iter()
+            .map(|(child, node)| node.into_folder(child, &path))
+            .collect();
+        BookmarkFolder {
+            total: self.count + children.iter().map(|c| c.total).sum::<usize>(),
+            count: self.count,
+            children,
+            name,
+            path,
+        }
+    }
+}
+
+/// The folder tree for per-folder bookmark counts. Folders that only hold
+/// subfolders get a count of 0.
+pub fn build_folder_tree(counts: &[(String, usize)]) -> Vec<BookmarkFolder> {
+    let mut root = FolderNode::default();
+    for (path, count) in counts {
+        let node = path
+            .split(FOLDER_SEPARATOR)
+            .filter(|name| !name.is_empty())
+            .fold(&mut root, |node, name| {
+                node.children.entry(name.to_string()).or_default()
+            });
+        node.count += count;
+    }
+    root.children
+        .into_iter()
+        .map(|(name, node)| node.into_folder(name, ""))
+        .collect()
+}
+
+/// Refuse to open more than `MAX_TABS_WITHOUT_CONFIRMATION` tabs unless
+/// `confirmed`
+pub fn check_tab_count(count: usize, confirmed: bool) -> Result<(), String> {
+    if count > MAX_TABS_WITHOUT_CONFIRMATION && !confirmed {
+        return Err(format!("Opening {} tabs needs confirmation", count));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::browser::{
+        bookmark_folder_counts, bookmarks_in_folder, open_browser_db, upsert_browser_entry, BrowserEntry,
+    };
+    use crate::services::browser_reader::{chrome_bookmark_entries, firefox_bookmark_entries};
+    use rusqlite::Connection;
+
+    const CHROME_BOOKMARKS: &str = include_str!("../../tests/fixtures/bookmarks/chrome_bookmarks.json");
+    const FIREFOX_PLACES: &str = include_str!("../../tests/fixtures/bookmarks/firefox_places.sql");
+
+    fn cached_tree(entries: &[BrowserEntry]) -> (tempfile::TempDir, Connection, Vec<BookmarkFolder>) {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_browser_db(&dir.path().join("browser_cache.db")).unwrap();
+        for entry in entries {
+            upsert_browser_entry(&conn, entry).unwrap();
+        }
+        let tree = build_folder_tree(&bookmark_folder_counts(&conn).unwrap());
+        (dir, conn, tree)
+    }
+
+    fn summary(folders: &[BookmarkFolder]) -> Vec<(String, usize, usize)> {
+        let mut out = Vec::new();
+        for folder in folders {
+            out.push((folder.path.clone(), folder.count, folder.total));
+            out.extend(summary(&folder.children));
+        }
+        out
+    }
+
+    #[test]
+    fn test_tree_from_chrome_bookmarks() {
+        let json: serde_json::Value = serde_json::from_str(CHROME_BOOKMARKS).unwrap();
+        let (_dir, conn, tree) = cached_tree(&chrome_bookmark_entries(&json, "chrome"));
+
+        assert_eq!(
+            summary(&tree),
+            vec![
+                ("Bookmarks Bar".to_string(), 1, 5),
+                ("Bookmarks Bar/Morning".to_string(), 2, 4),
+                ("Bookmarks Bar/Morning/News".to_string(), 2, 2),
+                ("Other Bookmarks".to_string(), 0, 1),
+                ("Other Bookmarks/CI \u{2215} CD".to_string(), 1, 1),
+            ]
+        );
+
+        let morning = bookmarks_in_folder(&conn, "Bookmarks Bar/Morning", false).unwrap();
+        let titles: Vec<_> = morning.iter().map(|e| e.title.as_str()).collect();
+        assert_eq!(titles, vec!["Calendar", "Mail"]);
+        assert_eq!(
+            bookmarks_in_folder(&conn, "Bookmarks Bar/Morning", true).unwrap().len(),
+            4
+        );
+        // A prefix of a name isn't a parent
+        assert!(bookmarks_in_folder(&conn, "Bookmarks Bar/Morn", true)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_tree_from_firefox_places() {
+        let places = Connection::open_in_memory().unwrap();
+        places.execute_batch(FIREFOX_PLACES).unwrap();
+        let entries = firefox_bookmark_entries(&places).unwrap();
+        // Tag entries are references to bookmarks, not bookmarks
+        assert_eq!(entries.len(), 5);
+        let (_dir, conn, tree) = cached_tree(&entries);
+
+        assert_eq!(
+            summary(&tree),
+            vec![
+                ("Bookmarks Bar".to_string(), 1, 4),
+                ("Bookmarks Bar/Morning".to_string(), 1, 3),
+                ("Bookmarks Bar/Morning/News".to_string(), 2, 2),
+                ("Bookmarks Menu".to_string(), 1, 1),
+            ]
+        );
+
+        let news = bookmarks_in_folder(&conn, "Bookmarks Bar/Morning/News", false).unwrap();
+        let urls: Vec<_> = news.iter().map(|e| e.url.as_str()).collect();
+        assert_eq!(urls, vec!["https://news.ycombinator.com/", "https://lobste.rs/"]);
+        assert!(news.iter().all(|e| e.browser == "firefox"));
+    }
+
+    #[test]
+    fn test_opening_many_tabs_needs_confirmation() {
+        assert!(check_tab_count(MAX_TABS_WITHOUT_CONFIRMATION, false).is_ok());
+        assert_eq!(
+            check_tab_count(11, false).unwrap_err(),
+            "Opening 11 tabs needs confirmation"
+        );
+        assert!(check_tab_count(11, true).is_ok());
+    }
+}
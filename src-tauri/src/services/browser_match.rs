@@ -0,0 +1,213 @@
+//! Browser Match Context
+//! Where a browser search matched a page, so pages sharing a title (issue
+//! pages, say) can be told apart by their host and the matched part.
+//! Ranges are UTF-16 offsets into the matched text, as JavaScript indexes
+//! strings, and the frontend decides how to highlight them.
+
+use crate::db::browser::BrowserEntry;
+use crate::utils::url_parts;
+use serde::Serialize;
+
+/// Part of a page a query matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchField {
+    Title,
+    Host,
+    Path,
+    Description,
+}
+
+/// A matched span, start inclusive, end exclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TextRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// What a browser result shows about its match
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MatchContext {
+    /// Shown prominently; empty for URLs without a host
+    pub host: String,
+    /// Decoded path, or the whole URL when it has no host
+    pub path: String,
+    /// First of title, host, path and description the query matched; the
+    /// title when it matched none of them, e.g. only the query string
+    pub field: MatchField,
+    /// Text of `field`
+    pub text: String,
+    /// Matches in `text`, in order
+    pub ranges: Vec<TextRange>,
+}
+
+/// Match context of a page for `query`, matched case-insensitively like
+/// the cache's LIKE search. Uses the host and path the cache stored for the
+/// page, splitting its URL only for an entry that wasn't read from the cache.
+pub fn match_context(query: &str, entry: &BrowserEntry) -> MatchContext {
+    let (host, path) = match (&entry.host, &entry.path) {
+        (Some(host), Some(path)) => (host.clone(), path.clone()),
+        _ => match url_parts(&entry.url) {
+            Some(parts) => (parts.host, parts.path),
+            None => (String::new(), entry.url.clone()),
+        },
+    };
+    let title = entry.title.as_str();
+    let query = query.trim();
+
+    let candidates = [
+        (MatchField::Title, title),
+        (MatchField::Host, host.as_str()),
+        (MatchField::Path, path.as_str()),
+        (MatchField::Description, entry.description.as_deref().unwrap_or_default()),
+    ];
+    let (field, text, ranges) = candidates
+        .iter()
+        .find_map(|(field, text)| {
+            let ranges = find_ranges(text, query);
+            (!ranges.is_empty()).then(|| (*field, text.to_string(), ranges))
+        })
+        .unwrap_or_else(|| (MatchField::Title, title.to_string(), Vec::new()));
+
+    MatchContext {
+        host,
+        path,
+        field,
+        text,
+        ranges,
+    }
+}
+
+/// Non-overlapping case-insensitive occurrences of `query` in `text`
+pub fn find_ranges(text: &str, query: &str) -> Vec<TextRange> {
+    let needle: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+
+    // Lowercased chars of `text`, each with the UTF-16 offset of the char
+    // it came from
+    let mut folded: Vec<(char, usize)> = Vec::with_capacity(text.len());
+    let mut offset = 0;
+    for c in text.chars() {
+        folded.extend(c.to_lowercase().map(|lower| (lower, offset)));
+        offset += c.len_utf16();
+    }
+
+    let mut ranges = Vec::new();
+    let mut i = 0;
+    while i + needle.len() <= folded.len() {
+        let window = &folded[i..i + needle.len()];
+        if window.iter().map(|(c, _)| *c).eq(needle.iter().copied()) {
+            let end = folded.get(i + needle.len()).map_or(offset, |(_, at)| *at);
+            ranges.push(TextRange { start: window[0].1, end });
+            i += needle.len();
+        } else {
+            i += 1;
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A page as the search reads it, with no stored host or path
+    fn page(title: &str, url: &str, description: Option<&str>) -> BrowserEntry {
+        BrowserEntry {
+            id: None,
+            url: url.to_string(),
+            title: title.to_string(),
+            favicon: None,
+            accent_color: None,
+            browser: "chrome".to_string(),
+            entry_type: "history".to_string(),
+            visit_count: 0,
+            last_visited: None,
+            folder: None,
+            description: description.map(str::to_string),
+            host: None,
+            path: None,
+            launcher_open_count: 0,
+            last_opened_from_launcher: None,
+            cached: 0,
+        }
+    }
+
+    fn context_of(query: &str, title: &str, url: &str, description: Option<&str>) -> MatchContext {
+        match_context(query, &page(title, url, description))
+    }
+
+    fn spans(context: &MatchContext) -> Vec<(usize, usize)> {
+        context.ranges.iter().map(|range| (range.start, range.end)).collect()
+    }
+
+    /// The highlighted text, sliced the way the frontend does
+    fn highlighted(context: &MatchContext) -> Vec<String> {
+        let units: Vec<u16> = context.text.encode_utf16().collect();
+        context
+            .ranges
+            .iter()
+            .map(|range| String::from_utf16(&units[range.start..range.end]).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_title_ranges() {
+        let context = context_of(
+            "issue",
+            "Issue #42 · tauri-apps/tauri issues",
+            "https://github.com/tauri-apps/tauri/issues/42",
+            None,
+        );
+        assert_eq!(context.field, MatchField::Title);
+        assert_eq!(context.host, "github.com");
+        assert_eq!(spans(&context), vec![(0, 5), (29, 34)]);
+
+        // UTF-16 offsets: the rocket is two units, CJK one each
+        let context = context_of("launch", "🚀 Launch", "https://example.com/", None);
+        assert_eq!(spans(&context), vec![(3, 9)]);
+        let context = context_of("タワー", "東京タワー - 観光", "https://example.jp/", None);
+        assert_eq!(spans(&context), vec![(2, 5)]);
+        assert_eq!(highlighted(&context), vec!["タワー"]);
+        let context = context_of("ÉTÉ", "Un été à Paris", "https://example.fr/", None);
+        assert_eq!(highlighted(&context), vec!["été"]);
+    }
+
+    #[test]
+    fn test_host_and_path_ranges() {
+        let context = context_of("rust-lang", "Install", "https://www.rust-lang.org/tools/install", None);
+        assert_eq!(context.field, MatchField::Host);
+        assert_eq!(context.text, "rust-lang.org");
+        assert_eq!(spans(&context), vec![(0, 9)]);
+        assert_eq!(context.path, "/tools/install");
+
+        // Matched in the decoded path, not its percent-encoding
+        let context = context_of(
+            "東京",
+            "Wikipedia",
+            "https://ja.wikipedia.org/wiki/%E6%9D%B1%E4%BA%AC",
+            None,
+        );
+        assert_eq!(context.field, MatchField::Path);
+        assert_eq!(context.text, "/wiki/東京");
+        assert_eq!(spans(&context), vec![(6, 8)]);
+
+        let context = context_of("42", "Issue", "https://github.com/tauri-apps/tauri/issues/42", None);
+        assert_eq!((context.field, spans(&context)), (MatchField::Path, vec![(25, 27)]));
+    }
+
+    #[test]
+    fn test_description_and_unmatched() {
+        let context = context_of("borrow", "Chapter 4", "https://doc.rust-lang.org/book/ch04", Some("Borrowing"));
+        assert_eq!(context.field, MatchField::Description);
+        assert_eq!(spans(&context), vec![(0, 6)]);
+
+        // Matched only in the query string
+        let context = context_of("utm", "Home", "https://example.com/?utm_source=x", None);
+        assert_eq!((context.field, context.text.as_str()), (MatchField::Title, "Home"));
+        assert!(context.ranges.is_empty());
+        assert!(find_ranges("anything", "  ".trim()).is_empty());
+    }
+}
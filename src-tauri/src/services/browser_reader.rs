@@ -2,14 +2,36 @@
 //! Reads bookmarks and history from browser databases with cache expiry and lock handling
 #![allow(dead_code)]
 
-use crate::db::browser::{BrowserEntry, init_browser_db, upsert_browser_entry, search_browser_data, get_cache_stats};
-use rusqlite::Connection;
+use crate::db::browser::{
+    expire_browser_entries, get_cache_stats, init_browser_db, search_browser_page, upsert_browser_entry, BrowserEntry,
+    BrowserMatch,
+};
+use crate::models::pagination::{Page, PageCursor};
+use crate::models::preferences::AppSettings;
+use crate::services::bookmark_folders::{chrome_root_name, firefox_root_name, join_folder};
+use crate::services::data_access::{is_denied, SKIPPED_PERMISSION};
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::favicon_color::favicon_data_url;
+use crate::services::search::cache::invalidate_search_cache;
+use rusqlite::{Connection, ErrorCode, OpenFlags};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
-use tauri::AppHandle;
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
 use tempfile::NamedTempFile;
 
+/// Buffer size for streaming a locked database to a temp copy
+const COPY_BUFFER_BYTES: usize = 1024 * 1024;
+
+/// History rows read per browser by default, for each of recency and visits
+pub const DEFAULT_HISTORY_LIMIT: usize = 1000;
+
+/// Fewest visits a history page needs to be read by default
+pub const DEFAULT_MIN_VISITS: u32 = 1;
+
 /// Browser type enumeration
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum BrowserType {
@@ -24,6 +46,25 @@ pub enum BrowserType {
 pub struct BrowserReaderConfig {
     pub cache_expiry_minutes: i64,
     pub enabled_browsers: Vec<BrowserType>,
+    /// Locked databases larger than this are skipped instead of copied, in MB
+    pub max_copy_mb: u64,
+    /// Most recently visited history pages read per browser; as many of the
+    /// most visited pages are merged in
+    pub history_limit: usize,
+    /// History pages visited fewer times are skipped
+    pub min_visits: u32,
+}
+
+impl BrowserReaderConfig {
+    /// Default config with the user's copy and history limits
+    pub fn from_settings(settings: &AppSettings) -> Self {
+        Self {
+            max_copy_mb: settings.max_browser_db_copy_mb,
+            history_limit: settings.browser_history_limit,
+            min_visits: settings.browser_min_visits,
+            ..Self::default()
+        }
+    }
 }
 
 impl Default for BrowserReaderConfig {
@@ -36,8 +77,347 @@ impl Default for BrowserReaderConfig {
                 BrowserType::Safari,
                 BrowserType::Edge,
             ],
+            max_copy_mb: 512,
+            history_limit: DEFAULT_HISTORY_LIMIT,
+            min_visits: DEFAULT_MIN_VISITS,
+        }
+    }
+}
+
+/// Per-browser outcome of a cache update
+#[derive(Debug, Clone, Serialize)]
+pub struct BrowserUpdateTiming {
+    pub browser: String,
+    pub entries: usize,
+    pub duration_ms: u64,
+    /// Why the browser's data couldn't be read
+    pub error: Option<String>,
+    /// Set when the browser wasn't read at all, e.g. "permission" when the
+    /// OS denied access to its profile
+    pub skipped: Option<String>,
+}
+
+/// `update_cache` result
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BrowserCacheUpdate {
+    /// Entries written across all browsers
+    pub total: usize,
+    pub browsers: Vec<BrowserUpdateTiming>,
+}
+
+/// What to do when a browser database can't be opened in place
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CopyFallback {
+    /// The browser holds a lock; stream a copy and read that
+    Copy,
+    /// Locked, but over the copy limit
+    TooLarge,
+    /// Not a locking problem, so a copy wouldn't help
+    Fail,
+}
+
+/// Pick the fallback for a failed in-place open of a `size`-byte database.
+/// A page read while the browser was writing it looks corrupt, so corrupt
+/// and not-a-database errors are retried on a copy too.
+fn copy_fallback(error: &rusqlite::Error, size: u64, max_copy_bytes: u64) -> CopyFallback {
+    let locked = matches!(
+        error.sqlite_error_code(),
+        Some(
+            ErrorCode::DatabaseBusy
+                | ErrorCode::DatabaseLocked
+                | ErrorCode::CannotOpen
+                | ErrorCode::SystemIoFailure
+                | ErrorCode::DatabaseCorrupt
+                | ErrorCode::NotADatabase
+        )
+    );
+    if !locked {
+        CopyFallback::Fail
+    } else if size > max_copy_bytes {
+        CopyFallback::TooLarge
+    } else {
+        CopyFallback::Copy
+    }
+}
+
+/// SQLite URI for `path`; `%`, `?` and `#` would otherwise end or escape the path
+fn sqlite_file_uri(path: &Path) -> String {
+    #[cfg(windows)]
+    let path = path.to_string_lossy().replace('\\', "/");
+    #[cfg(not(windows))]
+    let path = path.to_string_lossy();
+    let mut uri = String::from("file:");
+    // Windows drive paths need the empty authority, "file:///C:/..."
+    if !path.starts_with('/') {
+        uri.push_str("///");
+    }
+    for c in path.chars() {
+        match c {
+            '%' => uri.push_str("%25"),
+            '?' => uri.push_str("%3f"),
+            '#' => uri.push_str("%23"),
+            c => uri.push(c),
+        }
+    }
+    uri
+}
+
+/// Open a database read-only without copying it. SQLite's locking still
+/// applies, so a browser writing the file makes this fail with a busy or
+/// locked error instead of handing back half-written pages; the OS denying
+/// shared reads (Windows) fails it too. The probe query surfaces either now
+/// rather than mid-read.
+fn open_in_place(path: &Path) -> rusqlite::Result<Connection> {
+    let uri = format!("{}?mode=ro", sqlite_file_uri(path));
+    let conn = Connection::open_with_flags(uri, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI)?;
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?;
+    Ok(conn)
+}
+
+/// Favicon bitmaps wider than this aren't cached; results show icons small
+const MAX_FAVICON_WIDTH: i64 = 32;
+
+/// Page URL and image of each favicon bitmap in a Chromium `Favicons`
+/// database, narrowest first
+const CHROME_FAVICONS_QUERY: &str = "SELECT m.page_url, b.image_data
+     FROM icon_mapping m JOIN favicon_bitmaps b ON b.icon_id = m.icon_id
+     WHERE b.width <= ?1
+     ORDER BY b.width";
+
+/// The same from a Firefox `favicons.sqlite`
+const FIREFOX_FAVICONS_QUERY: &str = "SELECT p.page_url, i.data
+     FROM moz_pages_w_icons p
+     JOIN moz_icons_to_pages ip ON ip.page_id = p.id
+     JOIN moz_icons i ON i.id = ip.icon_id
+     WHERE i.width <= ?1
+     ORDER BY i.width";
+
+/// Favicons of the pages in `entries` as data URLs, read with one of the
+/// favicon queries. Rows come narrowest first, so each page ends up with
+/// its widest bitmap up to `MAX_FAVICON_WIDTH`.
+fn read_favicons(conn: &Connection, query: &str, entries: &[BrowserEntry]) -> Result<HashMap<String, String>, String> {
+    let wanted: HashSet<&str> = entries.iter().map(|entry| entry.url.as_str()).collect();
+    let mut stmt = conn
+        .prepare(query)
+        .map_err(|e| format!("Failed to prepare favicon query: {}", e))?;
+    let rows = stmt
+        .query_map([MAX_FAVICON_WIDTH], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<Vec<u8>>>(1)?))
+        })
+        .map_err(|e| format!("Failed to query favicons: {}", e))?;
+
+    let mut favicons = HashMap::new();
+    for (url, image) in rows.flatten() {
+        if !wanted.contains(url.as_str()) {
+            continue;
+        }
+        if let Some(data_url) = image.as_deref().and_then(favicon_data_url) {
+            favicons.insert(url, data_url);
+        }
+    }
+    Ok(favicons)
+}
+
+/// Give each entry the favicon found for its URL
+fn attach_favicons(entries: &mut [BrowserEntry], favicons: &HashMap<String, String>) {
+    for entry in entries {
+        if entry.favicon.is_none() {
+            entry.favicon = favicons.get(&entry.url).cloned();
+        }
+    }
+}
+
+/// A browser database opened for reading, with the temp copy backing it if
+/// the original was locked
+struct BrowserDb {
+    conn: Connection,
+    _copy: Option<NamedTempFile>,
+}
+
+/// Profile directory Chrome last used, from the `Local State` JSON
+fn last_used_profile(local_state: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(local_state).ok()?;
+    json.get("profile")?
+        .get("last_used")?
+        .as_str()
+        .filter(|profile| !profile.is_empty())
+        .map(str::to_string)
+}
+
+/// Bookmarks in a Chrome (or Edge) `Bookmarks` file, each with its folder
+/// path
+pub(crate) fn chrome_bookmark_entries(json: &serde_json::Value, browser: &str) -> Vec<BrowserEntry> {
+    let mut entries = Vec::new();
+    let Some(roots) = json.get("roots").and_then(|r| r.as_object()) else {
+        return entries;
+    };
+    for (key, root) in roots {
+        let name = chrome_root_name(key)
+            .map(str::to_string)
+            .or_else(|| root.get("name").and_then(|n| n.as_str()).map(str::to_string))
+            .unwrap_or_else(|| key.clone());
+        if let Some(children) = root.get("children") {
+            extract_chrome_bookmark_children(children, &name, browser, &mut entries);
+        }
+    }
+    entries
+}
+
+/// Recursively extract Chrome bookmark children under `folder`
+fn extract_chrome_bookmark_children(
+    children: &serde_json::Value,
+    folder: &str,
+    browser: &str,
+    entries: &mut Vec<BrowserEntry>,
+) {
+    let Some(arr) = children.as_array() else {
+        return;
+    };
+    for child in arr {
+        let name = child.get("name").and_then(|n| n.as_str()).unwrap_or("Untitled");
+
+        // If this is a folder, recurse
+        if child.get("type").and_then(|t| t.as_str()) == Some("folder") {
+            if let Some(kids) = child.get("children") {
+                extract_chrome_bookmark_children(kids, &join_folder(folder, name), browser, entries);
+            }
+        }
+        // If this is a bookmark, add it
+        else if let Some(url) = child.get("url").and_then(|u| u.as_str()) {
+            entries.push(BrowserEntry {
+                id: None,
+                url: url.to_string(),
+                title: name.to_string(),
+                favicon: None,
+                accent_color: None,
+                browser: browser.to_string(),
+                entry_type: "bookmark".to_string(),
+                visit_count: 0,
+                last_visited: None,
+                folder: Some(folder.to_string()),
+                description: None,
+                host: None,
+                path: None,
+                launcher_open_count: 0,
+                last_opened_from_launcher: None,
+                cached: chrono::Utc::now().timestamp(),
+            });
+        }
+    }
+}
+
+/// Chrome keeps no page descriptions, so a bookmark renamed by the user is
+/// described by the title its page had in history (`urls.title`)
+fn describe_chrome_bookmarks(bookmarks: &mut [BrowserEntry], history: &[BrowserEntry]) {
+    let titles: HashMap<&str, &str> = history.iter().map(|entry| (entry.url.as_str(), entry.title.as_str())).collect();
+    for bookmark in bookmarks.iter_mut().filter(|entry| entry.entry_type == "bookmark") {
+        if let Some(title) = titles.get(bookmark.url.as_str()).filter(|title| **title != bookmark.title) {
+            bookmark.description = Some(title.to_string());
+        }
+    }
+}
+
+/// Firefox folders by id: parent id, title and guid
+type FirefoxFolders = HashMap<i64, (i64, Option<String>, String)>;
+
+/// Folder path of the Firefox folder `id`, walking parents up to the root.
+/// None for tags, whose entries point at bookmarks rather than being ones.
+fn firefox_folder_path(folders: &FirefoxFolders, id: i64) -> Option<String> {
+    let mut names = Vec::new();
+    let mut current = id;
+    // Bounded, in case a damaged database has a parent cycle
+    for _ in 0..folders.len() + 1 {
+        let (parent, title, guid) = folders.get(&current)?;
+        match guid.as_str() {
+            "root________" => break,
+            "tags________" => return None,
+            _ => {}
         }
+        let name = firefox_root_name(guid)
+            .map(str::to_string)
+            .or_else(|| title.clone())
+            .unwrap_or_else(|| "Untitled".to_string());
+        names.push(name);
+        current = *parent;
     }
+    Some(names.iter().rev().fold(String::new(), |path, name| join_folder(&path, name)))
+}
+
+/// Bookmarks in a Firefox places database, each with its folder path (T144)
+pub(crate) fn firefox_bookmark_entries(conn: &Connection) -> Result<Vec<BrowserEntry>, String> {
+    let folders: FirefoxFolders = conn
+        .prepare("SELECT id, parent, title, guid FROM moz_bookmarks WHERE type = 2")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?))))?
+                .collect()
+        })
+        .map_err(|e| format!("Failed to query bookmark folders: {}", e))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT b.title, p.url, b.dateAdded, b.parent, p.description FROM moz_bookmarks b
+         JOIN moz_places p ON b.fk = p.id
+         WHERE b.type = 1 AND p.url IS NOT NULL
+         ORDER BY b.dateAdded DESC LIMIT 1000"
+    ).map_err(|e| format!("Failed to prepare bookmarks statement: {}", e))?;
+
+    let bookmarks = stmt.query_map([], |row| {
+        Ok((
+            row.get::<_, Option<String>>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, i64>(2)?,
+            row.get::<_, i64>(3)?,
+            row.get::<_, Option<String>>(4)?,
+        ))
+    }).map_err(|e| format!("Failed to query bookmarks: {}", e))?;
+
+    let mut entries = Vec::new();
+    for (title, url, date_added, parent, description) in bookmarks.flatten() {
+        // Tag folders hold references to bookmarks filed elsewhere
+        let Some(folder) = firefox_folder_path(&folders, parent) else {
+            continue;
+        };
+
+        // Convert Firefox timestamp (microseconds since 1970-01-01) to Unix timestamp
+        let unix_timestamp = date_added / 1_000_000;
+
+        entries.push(BrowserEntry {
+            id: None,
+            url,
+            title: title.unwrap_or_else(|| "Untitled".to_string()),
+            favicon: None,
+            accent_color: None,
+            browser: "firefox".to_string(),
+            entry_type: "bookmark".to_string(),
+            visit_count: 0,
+            last_visited: Some(unix_timestamp),
+            folder: Some(folder),
+            description: description.filter(|d| !d.trim().is_empty()),
+            host: None,
+            path: None,
+            launcher_open_count: 0,
+            last_opened_from_launcher: None,
+            cached: chrono::Utc::now().timestamp(),
+        });
+    }
+
+    Ok(entries)
+}
+
+/// SQL for a browser's history: the most recently visited pages merged
+/// with the most visited ones, so pages visited often but not lately stay
+/// searchable. `?1` is the fewest visits a page needs and `?2` the rows
+/// taken by each order. Selects url, title, visit count, last visit and
+/// `description_column`, "NULL" for browsers that keep no description.
+fn history_query(table: &str, last_visit_column: &str, description_column: &str) -> String {
+    let select = format!(
+        "SELECT url, title, visit_count, {} AS last_visit, {} AS description FROM {} WHERE visit_count >= ?1",
+        last_visit_column, description_column, table
+    );
+    format!(
+        "SELECT * FROM ({select} ORDER BY last_visit DESC LIMIT ?2)
+         UNION
+         SELECT * FROM ({select} ORDER BY visit_count DESC, last_visit DESC LIMIT ?2)"
+    )
 }
 
 /// Browser data reader service
@@ -51,14 +431,41 @@ impl BrowserReader {
         Self { config }
     }
 
-    /// Update browser cache with expiry (T148)
-    pub fn update_cache(&self, handle: &AppHandle) -> Result<usize, String> {
-        let mut count = 0;
+    /// Update browser cache with expiry (T148), timing each browser
+    pub fn update_cache(&self, handle: &AppHandle) -> Result<BrowserCacheUpdate, String> {
+        let maintenance = handle.try_state::<MaintenanceGuard>();
+        let _shared = match maintenance.as_ref() {
+            Some(guard) => Some(
+                guard
+                    .try_shared()
+                    .ok_or("Browser cache update is paused during database maintenance")?,
+            ),
+            None => None,
+        };
+
+        let mut update = BrowserCacheUpdate::default();
 
         // Expire old cache entries (T148)
         self.expire_cache(handle)?;
 
         for browser_type in &self.config.enabled_browsers {
+            let start = Instant::now();
+            let mut timing = BrowserUpdateTiming {
+                browser: format!("{:?}", browser_type).to_lowercase(),
+                entries: 0,
+                duration_ms: 0,
+                error: None,
+                skipped: None,
+            };
+
+            // Without Full Disk Access the reads below would fail anyway;
+            // report that instead of an empty result
+            if self.data_location(browser_type).is_ok_and(|path| is_denied(&path)) {
+                timing.skipped = Some(SKIPPED_PERMISSION.to_string());
+                update.browsers.push(timing);
+                continue;
+            }
+
             match self.read_browser_data(browser_type) {
                 Ok(entries) => {
                     let conn = init_browser_db(handle)
@@ -66,29 +473,48 @@ impl BrowserReader {
 
                     for entry in entries {
                         let _ = upsert_browser_entry(&conn, &entry);
-                        count += 1;
+                        timing.entries += 1;
                     }
                 }
                 Err(e) => {
                     eprintln!("Failed to read {:?} data: {}", browser_type, e);
+                    timing.error = Some(e);
                 }
             }
+
+            timing.duration_ms = start.elapsed().as_millis() as u64;
+            update.total += timing.entries;
+            update.browsers.push(timing);
         }
 
-        Ok(count)
+        invalidate_search_cache(handle);
+
+        Ok(update)
+    }
+
+    /// Enabled browsers whose data directory exists on this machine
+    pub fn detect_browsers(&self) -> Vec<BrowserType> {
+        self.config
+            .enabled_browsers
+            .iter()
+            .filter(|browser| {
+                self.get_browser_data_dir(browser)
+                    .map(|dir| dir.exists())
+                    .unwrap_or(false)
+            })
+            .cloned()
+            .collect()
     }
 
-    /// Expire old cache entries (T148)
+    /// Expire old cache entries (T148), keeping launcher open counts
     fn expire_cache(&self, handle: &AppHandle) -> Result<(), String> {
         let conn = init_browser_db(handle)
             .map_err(|e| format!("DB error: {}", e))?;
 
         let expiry_time = chrono::Utc::now().timestamp() - (self.config.cache_expiry_minutes * 60);
 
-        conn.execute(
-            "DELETE FROM browser_data WHERE cached < ?1",
-            [expiry_time],
-        ).map_err(|e| format!("Failed to expire cache: {}", e))?;
+        expire_browser_entries(&conn, expiry_time)
+            .map_err(|e| format!("Failed to expire cache: {}", e))?;
 
         Ok(())
     }
@@ -105,6 +531,17 @@ impl BrowserReader {
         }
     }
 
+    /// The file or directory a browser's data is read from first, which is
+    /// what Full Disk Access decides on
+    pub fn data_location(&self, browser_type: &BrowserType) -> Result<PathBuf, String> {
+        let data_dir = self.get_browser_data_dir(browser_type)?;
+        Ok(match browser_type {
+            BrowserType::Chrome | BrowserType::Edge => self.chrome_profile_dir(&data_dir),
+            BrowserType::Firefox => data_dir.join("Profiles"),
+            BrowserType::Safari => data_dir.join("History.db"),
+        })
+    }
+
     /// Get browser data directory
     fn get_browser_data_dir(&self, browser_type: &BrowserType) -> Result<PathBuf, String> {
         let home = std::env::var("HOME").map_err(|_| "Failed to get HOME directory")?;
@@ -153,48 +590,75 @@ impl BrowserReader {
         }
     }
 
+    /// The profile Chrome last used, falling back to "Default"
+    fn chrome_profile_dir(&self, data_dir: &Path) -> PathBuf {
+        fs::read_to_string(data_dir.join("Local State"))
+            .ok()
+            .and_then(|local_state| last_used_profile(&local_state))
+            .map(|profile| data_dir.join(profile))
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(|| data_dir.join("Default"))
+    }
+
     /// Read Chrome data (bookmarks and history) (T143, T147)
     fn read_chrome_data(&self, data_dir: &PathBuf) -> Result<Vec<BrowserEntry>, String> {
         let mut entries = Vec::new();
+        let profile_dir = self.chrome_profile_dir(data_dir);
 
         // Read bookmarks
-        let bookmarks_path = data_dir.join("Default/Bookmarks");
+        let bookmarks_path = profile_dir.join("Bookmarks");
         if bookmarks_path.exists() {
             if let Ok(content) = std::fs::read_to_string(&bookmarks_path) {
                 if let Ok(bookmarks_json) = serde_json::from_str::<serde_json::Value>(&content) {
-                    self.extract_chrome_bookmarks(&bookmarks_json, &mut entries);
+                    entries.extend(chrome_bookmark_entries(&bookmarks_json, "chrome"));
                 }
             }
         }
 
         // Read history from SQLite with lock handling (T143, T147)
-        let history_path = data_dir.join("Default/History");
+        let history_path = profile_dir.join("History");
         if history_path.exists() {
-            if let Ok(history_entries) = self.read_chrome_history(&history_path) {
-                entries.extend(history_entries);
+            match self.read_chrome_history(&history_path) {
+                Ok(history_entries) => {
+                    describe_chrome_bookmarks(&mut entries, &history_entries);
+                    entries.extend(history_entries);
+                }
+                Err(e) => eprintln!("[BrowserReader] {}", e),
             }
         }
 
+        self.add_favicons(&profile_dir.join("Favicons"), CHROME_FAVICONS_QUERY, &mut entries);
         Ok(entries)
     }
 
+    /// Fill in favicons from a browser's favicon database. They're optional,
+    /// so a missing or unreadable database only leaves them out.
+    fn add_favicons(&self, path: &Path, query: &str, entries: &mut [BrowserEntry]) {
+        if !path.exists() || entries.is_empty() {
+            return;
+        }
+        match self
+            .open_browser_db(path)
+            .and_then(|db| read_favicons(&db.conn, query, entries))
+        {
+            Ok(favicons) => attach_favicons(entries, &favicons),
+            Err(e) => eprintln!("[BrowserReader] Skipped favicons: {}", e),
+        }
+    }
+
     /// Read Chrome history with database lock handling (T147)
     fn read_chrome_history(&self, history_path: &PathBuf) -> Result<Vec<BrowserEntry>, String> {
-        // Copy to temp file to avoid database locks (T147)
-        let temp_file = self.copy_to_temp(history_path)?;
-
-        // Open the copied database
-        let conn = Connection::open(temp_file.path())
-            .map_err(|e| format!("Failed to open history database: {}", e))?;
+        // Read in place, or from a copy if Chrome holds a lock (T147)
+        let db = self.open_browser_db(history_path)?;
+        let conn = &db.conn;
 
         let mut entries = Vec::new();
 
         // Query URLs and visit counts
-        let mut stmt = conn.prepare(
-            "SELECT url, title, visit_count, last_visit_time FROM urls ORDER BY last_visit_time DESC LIMIT 1000"
-        ).map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let mut stmt = conn.prepare(&history_query("urls", "last_visit_time", "NULL"))
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
 
-        let urls = stmt.query_map([], |row| {
+        let urls = stmt.query_map(self.history_params(), |row| {
             Ok((
                 row.get::<_, String>(0)?,
                 row.get::<_, Option<String>>(1)?,
@@ -213,11 +677,17 @@ impl BrowserReader {
                     url,
                     title: title.unwrap_or_else(|| "Untitled".to_string()),
                     favicon: None,
+                    accent_color: None,
                     browser: "chrome".to_string(),
                     entry_type: "history".to_string(),
                     visit_count: visit_count as i32,
                     last_visited: Some(unix_timestamp),
                     folder: None,
+                    description: None,
+                    host: None,
+                    path: None,
+                    launcher_open_count: 0,
+                    last_opened_from_launcher: None,
                     cached: chrono::Utc::now().timestamp(),
                 });
             }
@@ -226,65 +696,55 @@ impl BrowserReader {
         Ok(entries)
     }
 
-    /// Copy database to temp file to avoid locks (T147)
-    fn copy_to_temp(&self, path: &PathBuf) -> Result<NamedTempFile, String> {
-        let content = fs::read(path)
+    /// Parameters of `history_query`
+    fn history_params(&self) -> [i64; 2] {
+        [self.config.min_visits as i64, self.config.history_limit as i64]
+    }
+
+    /// Open a browser database read-only, in place when possible and from a
+    /// streamed temp copy when the browser's lock prevents that (T147)
+    fn open_browser_db(&self, path: &Path) -> Result<BrowserDb, String> {
+        let error = match open_in_place(path) {
+            Ok(conn) => return Ok(BrowserDb { conn, _copy: None }),
+            Err(e) => e,
+        };
+
+        let size = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        match copy_fallback(&error, size, self.config.max_copy_mb * 1024 * 1024) {
+            CopyFallback::Copy => {
+                let copy = self.copy_to_temp(path)?;
+                let conn = Connection::open_with_flags(copy.path(), OpenFlags::SQLITE_OPEN_READ_ONLY)
+                    .map_err(|e| format!("Failed to open copy of {}: {}", path.display(), e))?;
+                Ok(BrowserDb { conn, _copy: Some(copy) })
+            }
+            CopyFallback::TooLarge => Err(format!(
+                "Skipped {}: locked and {} MB, over the {} MB copy limit",
+                path.display(),
+                size / (1024 * 1024),
+                self.config.max_copy_mb
+            )),
+            CopyFallback::Fail => Err(format!("Failed to open {}: {}", path.display(), error)),
+        }
+    }
+
+    /// Stream a database to a temp file without holding it in memory (T147)
+    fn copy_to_temp(&self, path: &Path) -> Result<NamedTempFile, String> {
+        let source = fs::File::open(path)
             .map_err(|e| format!("Failed to read database: {}", e))?;
 
-        let mut temp_file = NamedTempFile::new()
+        let temp_file = NamedTempFile::new()
             .map_err(|e| format!("Failed to create temp file: {}", e))?;
 
-        temp_file.write_all(&content)
+        let mut reader = BufReader::with_capacity(COPY_BUFFER_BYTES, source);
+        let mut writer = BufWriter::with_capacity(COPY_BUFFER_BYTES, temp_file.as_file());
+        io::copy(&mut reader, &mut writer)
+            .and_then(|_| io::Write::flush(&mut writer))
             .map_err(|e| format!("Failed to write temp file: {}", e))?;
+        drop(writer);
 
         Ok(temp_file)
     }
 
-    /// Extract bookmarks from Chrome bookmarks JSON
-    fn extract_chrome_bookmarks(&self, json: &serde_json::Value, entries: &mut Vec<BrowserEntry>) {
-        if let Some(roots) = json.get("roots") {
-            for (_key, root) in roots.as_object().unwrap_or(&serde_json::Map::new()) {
-                if let Some(children) = root.get("children") {
-                    self.extract_chrome_bookmark_children(children, entries);
-                }
-            }
-        }
-    }
-
-    /// Recursively extract Chrome bookmark children
-    fn extract_chrome_bookmark_children(&self, children: &serde_json::Value, entries: &mut Vec<BrowserEntry>) {
-        if let Some(arr) = children.as_array() {
-            for child in arr {
-                // If this is a folder, recurse
-                if child.get("type").and_then(|t| t.as_str()) == Some("folder") {
-                    if let Some(kids) = child.get("children") {
-                        self.extract_chrome_bookmark_children(kids, entries);
-                    }
-                }
-                // If this is a bookmark, add it
-                else if let Some(url) = child.get("url").and_then(|u| u.as_str()) {
-                    let name = child.get("name")
-                        .and_then(|n| n.as_str())
-                        .unwrap_or("Untitled")
-                        .to_string();
-
-                    entries.push(BrowserEntry {
-                        id: None,
-                        url: url.to_string(),
-                        title: name,
-                        favicon: None,
-                        browser: "chrome".to_string(),
-                        entry_type: "bookmark".to_string(),
-                        visit_count: 0,
-                        last_visited: None,
-                        folder: None,
-                        cached: chrono::Utc::now().timestamp(),
-                    });
-                }
-            }
-        }
-    }
-
     /// Read Firefox data from places.sqlite (T144)
     fn read_firefox_data(&self, data_dir: &PathBuf) -> Result<Vec<BrowserEntry>, String> {
         let mut entries = Vec::new();
@@ -313,64 +773,28 @@ impl BrowserReader {
             return Ok(entries);
         }
 
-        let temp_file = self.copy_to_temp(&places_path)?;
-        let conn = Connection::open(temp_file.path())
-            .map_err(|e| format!("Failed to open places database: {}", e))?;
+        let db = self.open_browser_db(&places_path)?;
+        let conn = &db.conn;
 
         // Read bookmarks
-        let mut stmt = conn.prepare(
-            "SELECT b.title, p.url, b.dateAdded FROM moz_bookmarks b
-             JOIN moz_places p ON b.fk = p.id
-             WHERE b.type = 1 AND p.url IS NOT NULL
-             ORDER BY b.dateAdded DESC LIMIT 1000"
-        ).map_err(|e| format!("Failed to prepare bookmarks statement: {}", e))?;
-
-        let bookmarks = stmt.query_map([], |row| {
-            Ok((
-                row.get::<_, Option<String>>(0)?,
-                row.get::<_, String>(1)?,
-                row.get::<_, i64>(2)?,
-            ))
-        }).map_err(|e| format!("Failed to query bookmarks: {}", e))?;
-
-        for bookmark_result in bookmarks {
-            if let Ok((title, url, date_added)) = bookmark_result {
-                // Convert Firefox timestamp (microseconds since 1970-01-01) to Unix timestamp
-                let unix_timestamp = date_added / 1_000_000;
-
-                entries.push(BrowserEntry {
-                    id: None,
-                    url,
-                    title: title.unwrap_or_else(|| "Untitled".to_string()),
-                    favicon: None,
-                    browser: "firefox".to_string(),
-                    entry_type: "bookmark".to_string(),
-                    visit_count: 0,
-                    last_visited: Some(unix_timestamp),
-                    folder: None,
-                    cached: chrono::Utc::now().timestamp(),
-                });
-            }
-        }
+        entries.extend(firefox_bookmark_entries(conn)?);
 
         // Read history
-        let mut stmt = conn.prepare(
-            "SELECT url, title, visit_count, last_visit_date FROM moz_places
-             WHERE visit_count > 0
-             ORDER BY last_visit_date DESC LIMIT 1000"
-        ).map_err(|e| format!("Failed to prepare history statement: {}", e))?;
+        let mut stmt = conn.prepare(&history_query("moz_places", "last_visit_date", "description"))
+            .map_err(|e| format!("Failed to prepare history statement: {}", e))?;
 
-        let history = stmt.query_map([], |row| {
+        let history = stmt.query_map(self.history_params(), |row| {
             Ok((
                 row.get::<_, String>(0)?,
                 row.get::<_, Option<String>>(1)?,
                 row.get::<_, i64>(2)?,
                 row.get::<_, i64>(3)?,
+                row.get::<_, Option<String>>(4)?,
             ))
         }).map_err(|e| format!("Failed to query history: {}", e))?;
 
         for history_result in history {
-            if let Ok((url, title, visit_count, last_visit_date)) = history_result {
+            if let Ok((url, title, visit_count, last_visit_date, description)) = history_result {
                 // Convert Firefox timestamp (microseconds since 1970-01-01) to Unix timestamp
                 let unix_timestamp = last_visit_date / 1_000_000;
 
@@ -379,16 +803,23 @@ impl BrowserReader {
                     url,
                     title: title.unwrap_or_else(|| "Untitled".to_string()),
                     favicon: None,
+                    accent_color: None,
                     browser: "firefox".to_string(),
                     entry_type: "history".to_string(),
                     visit_count: visit_count as i32,
                     last_visited: Some(unix_timestamp),
                     folder: None,
+                    description: description.filter(|d| !d.trim().is_empty()),
+                    host: None,
+                    path: None,
+                    launcher_open_count: 0,
+                    last_opened_from_launcher: None,
                     cached: chrono::Utc::now().timestamp(),
                 });
             }
         }
 
+        self.add_favicons(&profile_dir.join("favicons.sqlite"), FIREFOX_FAVICONS_QUERY, &mut entries);
         Ok(entries)
     }
 
@@ -402,18 +833,15 @@ impl BrowserReader {
             return Ok(entries);
         }
 
-        // Copy to temp file to avoid locks (T147)
-        let temp_file = self.copy_to_temp(&history_path)?;
-        let conn = Connection::open(temp_file.path())
-            .map_err(|e| format!("Failed to open Safari history: {}", e))?;
+        // Read in place, or from a copy if Safari holds a lock (T147)
+        let db = self.open_browser_db(&history_path)?;
+        let conn = &db.conn;
 
         // Read history items
-        let mut stmt = conn.prepare(
-            "SELECT url, title, visit_count, last_visit_time FROM history_items
-             ORDER BY last_visit_time DESC LIMIT 1000"
-        ).map_err(|e| format!("Failed to prepare Safari history statement: {}", e))?;
+        let mut stmt = conn.prepare(&history_query("history_items", "last_visit_time", "NULL"))
+            .map_err(|e| format!("Failed to prepare Safari history statement: {}", e))?;
 
-        let history = stmt.query_map([], |row| {
+        let history = stmt.query_map(self.history_params(), |row| {
             Ok((
                 row.get::<_, String>(0)?,
                 row.get::<_, Option<String>>(1)?,
@@ -432,11 +860,17 @@ impl BrowserReader {
                     url,
                     title: title.unwrap_or_else(|| "Untitled".to_string()),
                     favicon: None,
+                    accent_color: None,
                     browser: "safari".to_string(),
                     entry_type: "history".to_string(),
                     visit_count: visit_count as i32,
                     last_visited: Some(unix_timestamp),
                     folder: None,
+                    description: None,
+                    host: None,
+                    path: None,
+                    launcher_open_count: 0,
+                    last_opened_from_launcher: None,
                     cached: chrono::Utc::now().timestamp(),
                 });
             }
@@ -455,16 +889,19 @@ impl BrowserReader {
         self.read_chrome_data(data_dir)
     }
 
-    /// Search cached browser data
+    /// Search cached browser data, leaving history out unless
+    /// `include_history`
     pub fn search(
         &self,
         handle: &AppHandle,
         query: &str,
+        after: Option<&PageCursor>,
         limit: usize,
-    ) -> Result<Vec<BrowserEntry>, String> {
+        include_history: bool,
+    ) -> Result<Page<BrowserMatch>, String> {
         let conn = init_browser_db(handle)
             .map_err(|e| format!("DB error: {}", e))?;
-        search_browser_data(&conn, query, limit)
+        search_browser_page(&conn, query, after, limit, include_history)
             .map_err(|e| format!("Search error: {}", e))
     }
 
@@ -476,3 +913,157 @@ impl BrowserReader {
             .map_err(|e| format!("Stats error: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rusqlite::ffi;
+
+    const MB: u64 = 1024 * 1024;
+
+    fn sqlite_error(code: i32) -> rusqlite::Error {
+        rusqlite::Error::SqliteFailure(ffi::Error::new(code), None)
+    }
+
+    #[test]
+    fn test_lock_errors_fall_back_to_copy() {
+        for code in [
+            ffi::SQLITE_BUSY,
+            ffi::SQLITE_LOCKED,
+            ffi::SQLITE_CANTOPEN,
+            ffi::SQLITE_IOERR,
+            ffi::SQLITE_CORRUPT,
+            ffi::SQLITE_NOTADB,
+        ] {
+            assert_eq!(copy_fallback(&sqlite_error(code), 10 * MB, 512 * MB), CopyFallback::Copy, "code {}", code);
+        }
+    }
+
+    #[test]
+    fn test_locked_database_over_limit_is_skipped() {
+        let busy = sqlite_error(ffi::SQLITE_BUSY);
+        assert_eq!(copy_fallback(&busy, 512 * MB, 512 * MB), CopyFallback::Copy);
+        assert_eq!(copy_fallback(&busy, 1400 * MB, 512 * MB), CopyFallback::TooLarge);
+    }
+
+    #[test]
+    fn test_other_errors_are_not_copied() {
+        assert_eq!(copy_fallback(&sqlite_error(ffi::SQLITE_READONLY), MB, 512 * MB), CopyFallback::Fail);
+        assert_eq!(copy_fallback(&rusqlite::Error::InvalidQuery, MB, 512 * MB), CopyFallback::Fail);
+    }
+
+    #[test]
+    fn test_opens_in_place_and_by_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        // Characters that are special in SQLite URIs
+        let path = dir.path().join("Application Support #1 ?100%.db");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch("CREATE TABLE urls (url TEXT); INSERT INTO urls VALUES ('https://example.com');")
+            .unwrap();
+        drop(conn);
+
+        let in_place = open_in_place(&path).unwrap();
+        let url: String = in_place.query_row("SELECT url FROM urls", [], |row| row.get(0)).unwrap();
+        assert_eq!(url, "https://example.com");
+
+        let reader = BrowserReader::new(BrowserReaderConfig::default());
+        let copy = reader.copy_to_temp(&path).unwrap();
+        assert_eq!(fs::read(copy.path()).unwrap(), fs::read(&path).unwrap());
+    }
+
+    #[test]
+    fn test_history_merges_recent_and_most_visited() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("History");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (url TEXT, title TEXT, visit_count INTEGER, last_visit_time INTEGER)",
+        )
+        .unwrap();
+        let insert = |url: &str, visits: i64, last_visit: i64| {
+            conn.execute(
+                "INSERT INTO urls VALUES (?1, ?1, ?2, ?3)",
+                rusqlite::params![url, visits, last_visit],
+            )
+            .unwrap();
+        };
+        for day in 0..10 {
+            insert(&format!("https://recent.example/{}", day), 1, 13_400_000_000_000_000 + day);
+        }
+        insert("https://old.example/often", 250, 13_000_000_000_000_000);
+        insert("https://old.example/once", 1, 13_000_000_000_000_001);
+        insert("https://never.example", 0, 13_500_000_000_000_000);
+        drop(conn);
+
+        let reader = BrowserReader::new(BrowserReaderConfig {
+            history_limit: 5,
+            ..BrowserReaderConfig::default()
+        });
+        let urls: Vec<String> = reader.read_chrome_history(&path).unwrap().into_iter().map(|e| e.url).collect();
+
+        assert!(urls.contains(&"https://recent.example/9".to_string()));
+        assert!(urls.contains(&"https://old.example/often".to_string()));
+        assert!(!urls.contains(&"https://old.example/once".to_string()));
+        assert!(!urls.contains(&"https://never.example".to_string()));
+        // The five most recent plus the most visited, which overlap apart from "often"
+        assert_eq!(urls.len(), 6);
+
+        let strict = BrowserReader::new(BrowserReaderConfig {
+            history_limit: 5,
+            min_visits: 2,
+            ..BrowserReaderConfig::default()
+        });
+        let urls: Vec<String> = strict.read_chrome_history(&path).unwrap().into_iter().map(|e| e.url).collect();
+        assert_eq!(urls, vec!["https://old.example/often"]);
+    }
+
+    #[test]
+    fn test_favicons_take_the_widest_small_bitmap() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = Connection::open(dir.path().join("Favicons")).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE icon_mapping (page_url TEXT, icon_id INTEGER);
+             CREATE TABLE favicon_bitmaps (icon_id INTEGER, width INTEGER, image_data BLOB);",
+        )
+        .unwrap();
+        let bitmap = |page: &str, icon: i64, width: i64, image: &[u8]| {
+            conn.execute("INSERT INTO icon_mapping VALUES (?1, ?2)", rusqlite::params![page, icon]).unwrap();
+            conn.execute(
+                "INSERT INTO favicon_bitmaps VALUES (?1, ?2, ?3)",
+                rusqlite::params![icon, width, image],
+            )
+            .unwrap();
+        };
+        let fixture = |name: &str| fs::read(Path::new("tests/fixtures/favicons").join(name)).unwrap();
+        bitmap("https://example.com/", 1, 16, &fixture("red_on_white.png"));
+        bitmap("https://example.com/", 2, 32, &fixture("solid_blue.png"));
+        bitmap("https://example.com/", 3, 192, &fixture("green_and_orange.png"));
+        bitmap("https://unrelated.example/", 4, 16, &fixture("solid_blue.png"));
+
+        let json = serde_json::json!({
+            "roots": {"bookmark_bar": {"children": [{"type": "url", "name": "Example", "url": "https://example.com/"}]}}
+        });
+        let mut entries = chrome_bookmark_entries(&json, "chrome");
+        let favicons = read_favicons(&conn, CHROME_FAVICONS_QUERY, &entries).unwrap();
+        assert_eq!(favicons.len(), 1);
+        attach_favicons(&mut entries, &favicons);
+
+        let favicon = entries[0].favicon.as_deref().unwrap();
+        assert!(favicon.starts_with("data:image/png;base64,"));
+        assert_eq!(
+            crate::services::favicon_color::favicon_accent_color(favicon).as_deref(),
+            Some("#1a73e8")
+        );
+    }
+
+    #[test]
+    fn test_last_used_profile() {
+        assert_eq!(
+            last_used_profile(r#"{"profile": {"last_used": "Profile 2", "info_cache": {}}}"#),
+            Some("Profile 2".to_string())
+        );
+        assert_eq!(last_used_profile(r#"{"profile": {"last_used": ""}}"#), None);
+        assert_eq!(last_used_profile(r#"{"browser": {}}"#), None);
+        assert_eq!(last_used_profile("not json"), None);
+    }
+}
@@ -0,0 +1,222 @@
+//! Clipboard Images
+//! Storage policy for copied images: originals over the size limit are kept
+//! downscaled, every image gets a small JPEG thumbnail for the history list,
+//! and a perceptual hash recognizes the same picture re-encoded. Files live
+//! in an `images` directory beside the item files, named after the item id.
+
+use crate::models::clipboard::{ClipboardItem, ClipboardSettings};
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use std::collections::HashSet;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// Directory under the clipboard storage dir holding image files
+const IMAGES_DIR: &str = "images";
+
+/// Quality thumbnails are encoded with
+const THUMBNAIL_QUALITY: u8 = 80;
+
+/// Each retry shrinks a downscaled image to this share of its sides
+const DOWNSCALE_STEP: f64 = 0.75;
+
+/// Perceptual hashes this many bits apart or fewer are the same picture
+const PERCEPTUAL_MATCH_BITS: u32 = 4;
+
+/// Files written for one image item
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoredImage {
+    pub image_path: PathBuf,
+    pub thumbnail_path: PathBuf,
+    pub perceptual_hash: String,
+    /// The original was over the size limit and was stored smaller
+    pub downscaled: bool,
+}
+
+/// Directory image files are kept in
+pub fn images_dir(storage_dir: &Path) -> PathBuf {
+    storage_dir.join(IMAGES_DIR)
+}
+
+/// Decode an encoded image (PNG or JPEG)
+pub fn decode_image(bytes: &[u8]) -> Result<DynamicImage, String> {
+    image::load_from_memory(bytes).map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+/// Difference hash: one bit per neighbouring pixel pair of a 9x8 greyscale
+/// copy, set when brightness falls left to right. Survives recompression
+/// and rescaling, unlike a hash of the bytes.
+pub fn perceptual_hash(image: &DynamicImage) -> String {
+    let small = image.resize_exact(9, 8, FilterType::Triangle).to_luma8();
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            hash <<= 1;
+            if small.get_pixel(x, y).0[0] > small.get_pixel(x + 1, y).0[0] {
+                hash |= 1;
+            }
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+/// Whether two perceptual hashes are close enough to be the same picture
+pub fn is_perceptual_match(a: &str, b: &str) -> bool {
+    match (u64::from_str_radix(a, 16), u64::from_str_radix(b, 16)) {
+        (Ok(a), Ok(b)) => (a ^ b).count_ones() <= PERCEPTUAL_MATCH_BITS,
+        _ => false,
+    }
+}
+
+/// Write an item's image and its thumbnail. Images within the size limit
+/// are stored as copied; larger ones are re-encoded as PNG, shrinking until
+/// they fit.
+pub fn store_image(
+    storage_dir: &Path,
+    id: &str,
+    bytes: &[u8],
+    image: &DynamicImage,
+    settings: &ClipboardSettings,
+) -> Result<StoredImage, String> {
+    let dir = images_dir(storage_dir);
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create images dir: {}", e))?;
+
+    let (image_path, downscaled) = if bytes.len() as u64 <= settings.max_image_bytes {
+        let extension = image::guess_format(bytes)
+            .ok()
+            .and_then(|format| format.extensions_str().first().copied())
+            .unwrap_or("png");
+        let path = dir.join(format!("{}.{}", id, extension));
+        fs::write(&path, bytes).map_err(|e| format!("Failed to write image: {}", e))?;
+        (path, false)
+    } else {
+        let path = dir.join(format!("{}.png", id));
+        fs::write(&path, downscale_to_fit(image, settings.max_image_bytes)?)
+            .map_err(|e| format!("Failed to write image: {}", e))?;
+        (path, true)
+    };
+
+    let thumbnail_path = dir.join(format!("{}_thumb.jpg", id));
+    write_thumbnail(image, settings.thumbnail_size, &thumbnail_path)?;
+
+    Ok(StoredImage {
+        image_path,
+        thumbnail_path,
+        perceptual_hash: perceptual_hash(image),
+        downscaled,
+    })
+}
+
+/// PNG of the image at the largest size that fits in `max_bytes`
+fn downscale_to_fit(image: &DynamicImage, max_bytes: u64) -> Result<Vec<u8>, String> {
+    let mut scale = 1.0;
+    loop {
+        let width = ((image.width() as f64 * scale) as u32).max(1);
+        let height = ((image.height() as f64 * scale) as u32).max(1);
+        let resized = if scale < 1.0 {
+            image.resize(width, height, FilterType::Triangle)
+        } else {
+            image.clone()
+        };
+
+        let mut encoded = Vec::new();
+        resized
+            .write_to(&mut Cursor::new(&mut encoded), ImageFormat::Png)
+            .map_err(|e| format!("Failed to encode image: {}", e))?;
+        if encoded.len() as u64 <= max_bytes || (width == 1 && height == 1) {
+            return Ok(encoded);
+        }
+        scale *= DOWNSCALE_STEP;
+    }
+}
+
+/// JPEG no larger than `size` pixels a side; smaller images aren't enlarged
+fn write_thumbnail(image: &DynamicImage, size: u32, path: &Path) -> Result<(), String> {
+    let thumbnail = if image.width() > size || image.height() > size {
+        image.thumbnail(size, size)
+    } else {
+        image.clone()
+    };
+
+    let mut encoded = Vec::new();
+    // JPEG has no alpha channel
+    DynamicImage::ImageRgb8(thumbnail.to_rgb8())
+        .write_with_encoder(JpegEncoder::new_with_quality(&mut encoded, THUMBNAIL_QUALITY))
+        .map_err(|e| format!("Failed to encode thumbnail: {}", e))?;
+    fs::write(path, encoded).map_err(|e| format!("Failed to write thumbnail: {}", e))
+}
+
+/// Delete an item's image files, if it has any
+pub fn remove_item_images(item: &ClipboardItem) {
+    for path in [&item.image_path, &item.thumbnail_path].into_iter().flatten() {
+        if path.exists() {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Delete image files no item refers to, e.g. left behind by items that
+/// expired while the app was closed. Returns how many were removed.
+pub fn remove_orphaned_images(storage_dir: &Path, items: &[ClipboardItem]) -> usize {
+    let Ok(entries) = fs::read_dir(images_dir(storage_dir)) else {
+        return 0;
+    };
+    let referenced: HashSet<&PathBuf> = items
+        .iter()
+        .flat_map(|item| [&item.image_path, &item.thumbnail_path])
+        .flatten()
+        .collect();
+
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && !referenced.contains(path))
+        .filter(|path| fs::remove_file(path).is_ok())
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture;
+
+    #[test]
+    fn test_large_images_are_stored_downscaled() {
+        let dir = tempfile::tempdir().unwrap();
+        let bytes = fixture!("clipboard/screenshot.png");
+        let image = decode_image(bytes).unwrap();
+
+        let within = store_image(dir.path(), "a", bytes, &image, &ClipboardSettings::default()).unwrap();
+        assert!(!within.downscaled);
+        assert_eq!(fs::read(&within.image_path).unwrap(), bytes);
+
+        let settings = ClipboardSettings {
+            max_image_bytes: bytes.len() as u64 / 4,
+            ..ClipboardSettings::default()
+        };
+        let stored = store_image(dir.path(), "b", bytes, &image, &settings).unwrap();
+        assert!(stored.downscaled);
+        assert!(fs::metadata(&stored.image_path).unwrap().len() <= settings.max_image_bytes);
+        let shrunk = decode_image(&fs::read(&stored.image_path).unwrap()).unwrap();
+        assert!(shrunk.width() < image.width() && shrunk.height() < image.height());
+
+        // Thumbnails are JPEGs bounded by the configured size
+        let thumbnail_bytes = fs::read(&stored.thumbnail_path).unwrap();
+        assert_eq!(image::guess_format(&thumbnail_bytes).unwrap(), ImageFormat::Jpeg);
+        let thumbnail = decode_image(&thumbnail_bytes).unwrap();
+        assert_eq!(thumbnail.width().max(thumbnail.height()), settings.thumbnail_size);
+    }
+
+    #[test]
+    fn test_perceptual_hash_matches_recompressed_copies() {
+        let png = perceptual_hash(&decode_image(fixture!("clipboard/screenshot.png")).unwrap());
+        let jpeg = perceptual_hash(&decode_image(fixture!("clipboard/screenshot.jpg")).unwrap());
+        let other = perceptual_hash(&decode_image(fixture!("clipboard/other.png")).unwrap());
+
+        assert!(is_perceptual_match(&png, &jpeg));
+        assert!(!is_perceptual_match(&png, &other));
+        assert!(!is_perceptual_match(&png, "not a hash"));
+    }
+}
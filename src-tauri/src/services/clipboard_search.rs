@@ -0,0 +1,394 @@
+//! Clipboard Search
+//! Turns a clipboard search into conditions on the history table. Besides
+//! its text, a query can hold filters, all of which must hold:
+//!
+//! - `type:text`, `type:image`, `type:html`, `type:file`
+//! - `app:<name>`, part of the name of the app the item was copied from
+//! - `from:<day>` and `to:<day>`, both inclusive, where a day is `today`,
+//!   `yesterday`, `YYYY-MM-DD`, or `<n>d` / `<n>w` for that many days or
+//!   weeks ago
+//! - `is:sensitive`, `is:pinned`
+//!
+//! A filter word whose value isn't understood, such as `to:do`, is searched
+//! as text. The same filters can be given as options, where a value that
+//! isn't understood is an error. Each result carries the ranges its text
+//! matched, UTF-16 offsets like browser match context.
+
+use chrono::{Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use regex::RegexBuilder;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::db::clipboard::{search_clipboard_page, ClipboardFilter, ClipboardRow};
+use crate::models::clipboard::{ClipboardContentType, ClipboardItem};
+use crate::models::pagination::{Page, PageCursor};
+use crate::services::browser_match::{find_ranges, TextRange};
+
+/// Longest compiled regex, so a pathological pattern can't stall a search
+const MAX_REGEX_SIZE: usize = 1 << 20;
+
+/// Filters of `search_clipboard`, on top of those written in the query
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ClipboardSearchOptions {
+    /// Treat the query text as a regular expression
+    pub regex: bool,
+    pub content_type: Option<String>,
+    pub app_source: Option<String>,
+    /// First day, in the same phrases as `from:`
+    pub from: Option<String>,
+    /// Last day, in the same phrases as `to:`
+    pub to: Option<String>,
+    pub sensitive_only: bool,
+    pub pinned_only: bool,
+}
+
+/// Clipboard search error
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ClipboardSearchError {
+    /// A filter or the regex couldn't be understood
+    Validation { field: String, message: String },
+    Failed { message: String },
+}
+
+impl ClipboardSearchError {
+    fn validation(field: &str, message: impl Into<String>) -> Self {
+        ClipboardSearchError::Validation {
+            field: field.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ClipboardSearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClipboardSearchError::Validation { field, message } => write!(f, "Invalid {}: {}", field, message),
+            ClipboardSearchError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for ClipboardSearchError {
+    fn from(message: String) -> Self {
+        ClipboardSearchError::Failed { message }
+    }
+}
+
+/// A clipboard item a search found
+#[derive(Debug, Clone, Serialize)]
+pub struct ClipboardMatch {
+    #[serde(flatten)]
+    pub item: ClipboardItem,
+    pub pinned: bool,
+    /// Where the query matched the item's text
+    pub highlights: Vec<TextRange>,
+}
+
+/// One page of clipboard items matching `query` and `options`, newest first
+pub fn search_clipboard(
+    conn: &Connection,
+    query: &str,
+    options: &ClipboardSearchOptions,
+    after: Option<&PageCursor>,
+    limit: usize,
+    hide_sensitive: bool,
+) -> Result<Page<ClipboardMatch>, ClipboardSearchError> {
+    let mut filter = parse_filter(query, options, Local::now().date_naive(), &Local)?;
+    filter.hide_sensitive = hide_sensitive;
+
+    let page = search_clipboard_page(conn, &filter, after, limit)
+        .map_err(|e| format!("Failed to query clipboard: {}", e))?;
+    Ok(page.map(|row| to_match(row, &filter)))
+}
+
+fn to_match(row: ClipboardRow, filter: &ClipboardFilter) -> ClipboardMatch {
+    let highlights = row
+        .item
+        .text
+        .as_deref()
+        .map(|text| highlights(text, filter))
+        .unwrap_or_default();
+    ClipboardMatch {
+        item: row.item,
+        pinned: row.pinned,
+        highlights,
+    }
+}
+
+/// The conditions `query` and `options` ask for. Days are those of `tz`,
+/// with `today` as the day relative phrases count from.
+pub fn parse_filter<Tz: TimeZone>(
+    query: &str,
+    options: &ClipboardSearchOptions,
+    today: NaiveDate,
+    tz: &Tz,
+) -> Result<ClipboardFilter, ClipboardSearchError> {
+    let mut filter = ClipboardFilter {
+        sensitive_only: options.sensitive_only,
+        pinned_only: options.pinned_only,
+        ..Default::default()
+    };
+    let given = [
+        ("type", &options.content_type),
+        ("app", &options.app_source),
+        ("from", &options.from),
+        ("to", &options.to),
+    ];
+    for (key, value) in given {
+        if let Some(value) = value.as_deref() {
+            apply_filter(&mut filter, key, value.trim(), today, tz)?;
+        }
+    }
+
+    // Words that aren't filters, such as URLs, or whose value isn't
+    // understood, are text
+    let mut words = Vec::new();
+    for word in query.split_whitespace() {
+        let applied = filter_token(word)
+            .is_some_and(|(key, value)| apply_filter(&mut filter, key, value, today, tz).is_ok());
+        if !applied {
+            words.push(word);
+        }
+    }
+
+    filter.text = words.join(" ");
+    if options.regex && !filter.text.is_empty() {
+        let pattern = RegexBuilder::new(&filter.text)
+            .case_insensitive(true)
+            .size_limit(MAX_REGEX_SIZE)
+            .build()
+            .map_err(|e| ClipboardSearchError::validation("query", e.to_string()))?;
+        filter.pattern = Some(pattern);
+    }
+    Ok(filter)
+}
+
+/// Narrow `filter` by one `key:value` filter, leaving it as it was when
+/// the value isn't understood
+fn apply_filter<Tz: TimeZone>(
+    filter: &mut ClipboardFilter,
+    key: &str,
+    value: &str,
+    today: NaiveDate,
+    tz: &Tz,
+) -> Result<(), ClipboardSearchError> {
+    match key {
+        "type" => filter.content_types.push(parse_content_type(value)?),
+        "app" => filter.app_sources.push(value.to_string()),
+        "from" => {
+            let day = parse_day(value, today).ok_or_else(|| unknown_day("from", value))?;
+            filter.since = filter.since.max(Some(day_start(day, tz)));
+        }
+        "to" => {
+            let day = parse_day(value, today).ok_or_else(|| unknown_day("to", value))?;
+            let end = day_start(day + Duration::days(1), tz);
+            filter.until = Some(filter.until.map_or(end, |until| until.min(end)));
+        }
+        _ => match value.to_ascii_lowercase().as_str() {
+            "sensitive" => filter.sensitive_only = true,
+            "pinned" => filter.pinned_only = true,
+            _ => {
+                return Err(ClipboardSearchError::validation(
+                    "is",
+                    format!("expected sensitive or pinned, got \"{}\"", value),
+                ))
+            }
+        },
+    }
+    Ok(())
+}
+
+/// `key:value` for a known filter key
+fn filter_token(word: &str) -> Option<(&'static str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let key = ["type", "app", "from", "to", "is"]
+        .into_iter()
+        .find(|known| known.eq_ignore_ascii_case(key))?;
+    (!value.is_empty()).then_some((key, value))
+}
+
+fn parse_content_type(value: &str) -> Result<ClipboardContentType, ClipboardSearchError> {
+    match value.to_ascii_lowercase().as_str() {
+        "text" => Ok(ClipboardContentType::Text),
+        "image" => Ok(ClipboardContentType::Image),
+        "html" => Ok(ClipboardContentType::Html),
+        "file" => Ok(ClipboardContentType::File),
+        _ => Err(ClipboardSearchError::validation(
+            "type",
+            format!("expected text, image, html or file, got \"{}\"", value),
+        )),
+    }
+}
+
+fn unknown_day(field: &str, value: &str) -> ClipboardSearchError {
+    ClipboardSearchError::validation(
+        field,
+        format!("expected today, yesterday, YYYY-MM-DD, <n>d or <n>w, got \"{}\"", value),
+    )
+}
+
+/// The day a date phrase names, counting back from `today`
+pub fn parse_day(phrase: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let phrase = phrase.trim().to_ascii_lowercase();
+    match phrase.as_str() {
+        "today" => return Some(today),
+        "yesterday" => return today.pred_opt(),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(&phrase, "%Y-%m-%d") {
+        return Some(date);
+    }
+
+    let (count, days_per_unit) = match phrase.split_at(phrase.len().checked_sub(1)?) {
+        (count, "d") => (count, 1),
+        (count, "w") => (count, 7),
+        _ => return None,
+    };
+    let count: i64 = count.parse().ok().filter(|count| *count >= 0)?;
+    today.checked_sub_signed(Duration::try_days(count.checked_mul(days_per_unit)?)?)
+}
+
+/// Unix seconds at which `day` starts in `tz`. A day starting inside a
+/// daylight saving gap starts at its first valid moment.
+fn day_start<Tz: TimeZone>(day: NaiveDate, tz: &Tz) -> i64 {
+    let midnight = day.and_time(NaiveTime::MIN);
+    (0..=2)
+        .find_map(|hours| tz.from_local_datetime(&(midnight + Duration::hours(hours))).earliest())
+        .map_or_else(|| midnight.and_utc().timestamp(), |start| start.timestamp())
+}
+
+/// Where `filter`'s text or pattern matches `text`
+pub fn highlights(text: &str, filter: &ClipboardFilter) -> Vec<TextRange> {
+    let Some(pattern) = &filter.pattern else {
+        return find_ranges(text, &filter.text);
+    };
+    let utf16_offset = |byte: usize| text[..byte].encode_utf16().count();
+    pattern
+        .find_iter(text)
+        .filter(|m| !m.is_empty())
+        .map(|m| TextRange {
+            start: utf16_offset(m.start()),
+            end: utf16_offset(m.end()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn day(text: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d").unwrap()
+    }
+
+    fn parse(query: &str, options: &ClipboardSearchOptions) -> Result<ClipboardFilter, ClipboardSearchError> {
+        parse_filter(query, options, day("2024-03-10"), &Utc)
+    }
+
+    #[test]
+    fn test_date_phrases() {
+        let today = day("2024-03-10");
+        assert_eq!(parse_day("today", today), Some(today));
+        assert_eq!(parse_day("Yesterday", today), Some(day("2024-03-09")));
+        assert_eq!(parse_day("2023-12-31", today), Some(day("2023-12-31")));
+        assert_eq!(parse_day("3d", today), Some(day("2024-03-07")));
+        assert_eq!(parse_day("2w", today), Some(day("2024-02-25")));
+        assert_eq!(parse_day("0d", today), Some(today));
+        for phrase in ["", "d", "-1d", "3m", "last week", "2024-02-30", "99999999999999999w"] {
+            assert_eq!(parse_day(phrase, today), None, "{:?}", phrase);
+        }
+
+        // `to:` includes the whole day
+        let filter = parse("from:yesterday to:today", &Default::default()).unwrap();
+        assert_eq!(filter.since, Some(day_start(day("2024-03-09"), &Utc)));
+        assert_eq!(filter.until, Some(day_start(day("2024-03-11"), &Utc)));
+
+        let someday = ClipboardSearchOptions { from: Some("someday".to_string()), ..Default::default() };
+        let error = parse("", &someday).unwrap_err();
+        assert!(matches!(error, ClipboardSearchError::Validation { ref field, .. } if field == "from"));
+    }
+
+    #[test]
+    fn test_regex_errors_are_validation_errors() {
+        let regex = ClipboardSearchOptions { regex: true, ..Default::default() };
+        let error = parse("foo(bar", &regex).unwrap_err();
+        assert!(matches!(error, ClipboardSearchError::Validation { ref field, .. } if field == "query"));
+        let json = serde_json::to_value(&error).unwrap();
+        assert_eq!(json["kind"], "validation");
+
+        // Without the option the same text is a plain substring
+        let filter = parse("foo(bar", &Default::default()).unwrap();
+        assert!(filter.pattern.is_none());
+        assert_eq!(filter.text, "foo(bar");
+
+        let filter = parse(r"\d{3}-\d{4}", &regex).unwrap();
+        let ranges = highlights("call 555-0100 or 555-0199", &filter);
+        assert_eq!(ranges, vec![TextRange { start: 5, end: 13 }, TextRange { start: 17, end: 25 }]);
+        // Offsets count UTF-16 units
+        let ranges = highlights("电话 555-0100", &filter);
+        assert_eq!(ranges, vec![TextRange { start: 3, end: 11 }]);
+    }
+
+    #[test]
+    fn test_query_filters_combine_with_options() {
+        let options = ClipboardSearchOptions {
+            content_type: Some("text".to_string()),
+            from: Some("2024-03-01".to_string()),
+            ..Default::default()
+        };
+        let filter = parse("deploy app:Term from:3d TYPE:image is:pinned http://host log", &options).unwrap();
+
+        assert_eq!(filter.text, "deploy http://host log");
+        assert_eq!(filter.app_sources, vec!["Term"]);
+        assert_eq!(format!("{:?}", filter.content_types), "[Text, Image]");
+        // The later of the two starts
+        assert_eq!(filter.since, Some(day_start(day("2024-03-07"), &Utc)));
+        assert!(filter.pinned_only && !filter.sensitive_only);
+
+        let video = ClipboardSearchOptions { content_type: Some("video".to_string()), ..Default::default() };
+        assert!(matches!(
+            parse("", &video),
+            Err(ClipboardSearchError::Validation { ref field, .. }) if field == "type"
+        ));
+
+        // Filter words that aren't understood are searched as text
+        let filter = parse("to:do type:video is:starred app:Notes", &Default::default()).unwrap();
+        assert_eq!(filter.text, "to:do type:video is:starred");
+        assert_eq!(filter.app_sources, vec!["Notes"]);
+        assert!(filter.until.is_none() && filter.content_types.is_empty() && !filter.pinned_only);
+    }
+
+    #[test]
+    fn test_search_highlights_matches() {
+        let conn = crate::db::clipboard::open_clipboard_db(std::path::Path::new(":memory:")).unwrap();
+        let item = |id: &str, text: Option<&str>, content_type, app: &str| ClipboardItem {
+            id: id.to_string(),
+            content_type,
+            text: text.map(str::to_string),
+            image_path: None,
+            thumbnail_path: None,
+            hash: String::new(),
+            perceptual_hash: None,
+            timestamp: Local::now().timestamp(),
+            is_sensitive: false,
+            app_source: Some(app.to_string()),
+        };
+        let insert = |item| crate::db::clipboard::insert_clipboard_item(&conn, &item).unwrap();
+        insert(item("note", Some("Deploy and deploy"), ClipboardContentType::Text, "Notes"));
+        insert(item("shot", None, ClipboardContentType::Image, "Screenshot"));
+
+        let page = search_clipboard(&conn, "deploy", &Default::default(), None, 10, false).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].highlights, vec![TextRange { start: 0, end: 6 }, TextRange { start: 11, end: 17 }]);
+
+        let page = search_clipboard(&conn, "type:image app:screen from:today", &Default::default(), None, 10, false)
+            .unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].item.id, "shot");
+        assert!(page.items[0].highlights.is_empty());
+    }
+}
@@ -4,13 +4,19 @@
 #![allow(unused_variables)]
 
 use crate::models::clipboard::*;
+use crate::services::clipboard_images::{self, decode_image, is_perceptual_match, perceptual_hash};
+use crate::services::privacy::PrivacyMode;
 use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::Hasher;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// How often the watcher polls the clipboard
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 /// Detect sensitive content (T075)
 pub fn detect_sensitive_content(text: &str) -> bool {
@@ -61,17 +67,84 @@ pub fn detect_sensitive_content(text: &str) -> bool {
 
 /// Calculate content hash for deduplication (T072)
 pub fn calculate_content_hash(content: &str) -> String {
+    calculate_bytes_hash(content.as_bytes())
+}
+
+/// Hash of raw content bytes, e.g. an encoded image
+pub fn calculate_bytes_hash(bytes: &[u8]) -> String {
     let mut hasher = DefaultHasher::new();
-    hasher.write(content.as_bytes());
+    hasher.write(bytes);
     format!("{:x}", hasher.finish())
 }
 
-/// Clipboard watcher service
+/// Whether an item holds the same content as another, byte for byte or,
+/// for images, by how they look
+fn is_duplicate(existing: &ClipboardItem, item: &ClipboardItem) -> bool {
+    if existing.hash == item.hash {
+        return true;
+    }
+    match (&existing.perceptual_hash, &item.perceptual_hash) {
+        (Some(a), Some(b)) => is_perceptual_match(a, b),
+        _ => false,
+    }
+}
+
+/// Lets the app write to the clipboard without the change entering
+/// history, e.g. the temporary copy made to capture the selected text.
+/// Cloned handles share state.
+#[derive(Clone, Default)]
+pub struct ClipboardSuppression {
+    active: Arc<AtomicUsize>,
+    /// Changes made just before the last token dropped may be polled late
+    linger_until: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ClipboardSuppression {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Suppress capture until the returned token is dropped, plus one poll
+    /// interval
+    pub fn suppress(&self) -> SuppressionToken {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        SuppressionToken { suppression: self.clone() }
+    }
+
+    pub fn is_suppressed(&self) -> bool {
+        if self.active.load(Ordering::SeqCst) > 0 {
+            return true;
+        }
+        self.linger_until
+            .lock()
+            .map(|until| until.is_some_and(|until| Instant::now() < until))
+            .unwrap_or(false)
+    }
+}
+
+/// Held while the app makes clipboard changes that aren't the user's
+pub struct SuppressionToken {
+    suppression: ClipboardSuppression,
+}
+
+impl Drop for SuppressionToken {
+    fn drop(&mut self) {
+        if let Ok(mut until) = self.suppression.linger_until.lock() {
+            *until = Some(Instant::now() + POLL_INTERVAL);
+        }
+        self.suppression.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Clipboard watcher service. Clones share history and running state.
+#[derive(Clone)]
 pub struct ClipboardWatcher {
     is_running: Arc<Mutex<bool>>,
     items: Arc<Mutex<Vec<ClipboardItem>>>,
     storage_dir: PathBuf,
     settings: ClipboardSettings,
+    privacy: PrivacyMode,
+    suppression: ClipboardSuppression,
 }
 
 impl ClipboardWatcher {
@@ -82,9 +155,24 @@ impl ClipboardWatcher {
             items: Arc::new(Mutex::new(Vec::new())),
             storage_dir,
             settings,
+            privacy: PrivacyMode::new(),
+            suppression: ClipboardSuppression::new(),
         }
     }
 
+    /// Share the app's suppression state so its own clipboard writes are
+    /// skipped
+    pub fn with_suppression(mut self, suppression: ClipboardSuppression) -> Self {
+        self.suppression = suppression;
+        self
+    }
+
+    /// Share the app's privacy mode so capture pauses while it's on
+    pub fn with_privacy(mut self, privacy: PrivacyMode) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
     /// Start watching clipboard
     pub fn start(&self) -> Result<(), String> {
         let mut running = self.is_running.lock().map_err(|e| format!("Lock error: {}", e))?;
@@ -98,16 +186,25 @@ impl ClipboardWatcher {
         self.load_history()?;
 
         // Spawn monitoring thread
-        let is_running = Arc::clone(&self.is_running);
-        let items = Arc::clone(&self.items);
-        let storage_dir = self.storage_dir.clone();
-        let settings = self.settings.clone();
-
+        let watcher = self.clone();
         thread::spawn(move || {
-            while *is_running.lock().unwrap() {
-                // TODO: Implement actual clipboard monitoring
-                // This would use a clipboard crate to read system clipboard
-                thread::sleep(Duration::from_millis(500));
+            // Without a clipboard, e.g. no display, there's nothing to poll
+            let mut clipboard = arboard::Clipboard::new().ok();
+            // What's on the clipboard at start isn't a new copy
+            let mut last_text = clipboard.as_mut().and_then(|clipboard| clipboard.get_text().ok());
+
+            while watcher.is_running.lock().map(|running| *running).unwrap_or(false) {
+                // Don't even poll the clipboard while privacy mode is on,
+                // or while the app is using it
+                let paused = !watcher.settings.enabled || watcher.privacy.is_active() || watcher.suppression.is_suppressed();
+                if !paused {
+                    if let Some(text) = clipboard.as_mut().and_then(|clipboard| clipboard.get_text().ok()) {
+                        if let Err(e) = watcher.capture_text(text, &mut last_text) {
+                            eprintln!("[Clipboard] {}", e);
+                        }
+                    }
+                }
+                thread::sleep(POLL_INTERVAL);
             }
         });
 
@@ -121,8 +218,38 @@ impl ClipboardWatcher {
         Ok(())
     }
 
+    /// Add polled clipboard text, unless it's what the last poll saw or blank
+    fn capture_text(&self, text: String, last_seen: &mut Option<String>) -> Result<(), String> {
+        if last_seen.as_deref() == Some(text.as_str()) {
+            return Ok(());
+        }
+        *last_seen = Some(text.clone());
+        if text.trim().is_empty() {
+            return Ok(());
+        }
+
+        self.add_item(ClipboardItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            content_type: ClipboardContentType::Text,
+            text: Some(text),
+            image_path: None,
+            thumbnail_path: None,
+            hash: String::new(),
+            perceptual_hash: None,
+            timestamp: chrono::Utc::now().timestamp(),
+            is_sensitive: false,
+            app_source: None,
+        })
+    }
+
     /// Add a clipboard item with deduplication and sensitive detection (T072, T075, T076, T077)
     pub fn add_item(&self, item: ClipboardItem) -> Result<(), String> {
+        // Nothing is captured while privacy mode is on or the app itself
+        // is using the clipboard
+        if self.privacy.is_active() || self.suppression.is_suppressed() {
+            return Ok(());
+        }
+
         let mut modified_item = item.clone();
 
         // Calculate hash if not set (T072)
@@ -142,7 +269,7 @@ impl ClipboardWatcher {
         let mut items = self.items.lock().map_err(|e| format!("Lock error: {}", e))?;
 
         // Check for duplicates using hash (T072)
-        if items.iter().any(|i| i.hash == modified_item.hash) {
+        if items.iter().any(|i| is_duplicate(i, &modified_item)) {
             return Ok(());
         }
 
@@ -150,13 +277,14 @@ impl ClipboardWatcher {
         items.insert(0, modified_item.clone());
 
         // Apply FIFO eviction (T077)
+        let mut dropped = Vec::new();
         if items.len() > self.settings.max_items {
-            items.truncate(self.settings.max_items);
+            dropped = items.split_off(self.settings.max_items);
         }
 
         // Apply auto-expiration (T076)
         let now = chrono::Utc::now().timestamp();
-        items.retain(|i| {
+        let (kept, expired): (Vec<_>, Vec<_>) = items.drain(..).partition(|i| {
             let age = now - i.timestamp;
             if i.is_sensitive {
                 age < (self.settings.sensitive_expiry_minutes * 60)
@@ -164,6 +292,13 @@ impl ClipboardWatcher {
                 age < (self.settings.retention_days * 24 * 3600)
             }
         });
+        *items = kept;
+        dropped.extend(expired);
+
+        // Evicted and expired items leave no files behind
+        for item in &dropped {
+            let _ = self.remove_item_files(item);
+        }
 
         // Persist to disk (T074 - rotating JSON file storage)
         self.persist_item(&modified_item)?;
@@ -171,6 +306,50 @@ impl ClipboardWatcher {
         Ok(())
     }
 
+    /// Add a copied image (PNG or JPEG bytes). Its files are written under
+    /// the storage dir: the image, downscaled if over the size limit, and a
+    /// thumbnail. Returns None when nothing was added, e.g. for a re-copy of
+    /// an image already in history.
+    pub fn add_image(&self, bytes: &[u8], app_source: Option<String>) -> Result<Option<ClipboardItem>, String> {
+        if self.privacy.is_active() || self.suppression.is_suppressed() {
+            return Ok(None);
+        }
+
+        let image = decode_image(bytes)?;
+        let mut item = ClipboardItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            content_type: ClipboardContentType::Image,
+            text: None,
+            image_path: None,
+            thumbnail_path: None,
+            hash: calculate_bytes_hash(bytes),
+            perceptual_hash: Some(perceptual_hash(&image)),
+            timestamp: chrono::Utc::now().timestamp(),
+            is_sensitive: false,
+            app_source,
+        };
+
+        // Check before writing any files
+        {
+            let items = self.items.lock().map_err(|e| format!("Lock error: {}", e))?;
+            if items.iter().any(|i| is_duplicate(i, &item)) {
+                return Ok(None);
+            }
+        }
+
+        let stored = clipboard_images::store_image(&self.storage_dir, &item.id, bytes, &image, &self.settings)?;
+        item.image_path = Some(stored.image_path);
+        item.thumbnail_path = Some(stored.thumbnail_path);
+        self.add_item(item.clone())?;
+
+        // A duplicate may have been added while the files were written
+        if !self.get_items()?.iter().any(|i| i.id == item.id) {
+            clipboard_images::remove_item_images(&item);
+            return Ok(None);
+        }
+        Ok(Some(item))
+    }
+
     /// Get all clipboard items
     pub fn get_items(&self) -> Result<Vec<ClipboardItem>, String> {
         let items = self.items.lock().map_err(|e| format!("Lock error: {}", e))?;
@@ -180,15 +359,24 @@ impl ClipboardWatcher {
     /// Delete a clipboard item
     pub fn delete_item(&self, id: &str) -> Result<(), String> {
         let mut items = self.items.lock().map_err(|e| format!("Lock error: {}", e))?;
-        items.retain(|item| item.id != id);
+        match items.iter().position(|item| item.id == id) {
+            Some(index) => self.remove_item_files(&items.remove(index)),
+            None => self.remove_item_file(id),
+        }
+    }
+
+    /// Delete an item's file and any image files
+    fn remove_item_files(&self, item: &ClipboardItem) -> Result<(), String> {
+        clipboard_images::remove_item_images(item);
+        self.remove_item_file(&item.id)
+    }
 
-        // Delete from disk
+    fn remove_item_file(&self, id: &str) -> Result<(), String> {
         let item_path = self.storage_dir.join(id);
         if item_path.exists() {
             fs::remove_file(&item_path)
                 .map_err(|e| format!("Failed to delete item: {}", e))?;
         }
-
         Ok(())
     }
 
@@ -248,6 +436,9 @@ impl ClipboardWatcher {
         // Sort by timestamp
         items.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
 
+        // Images of items that expired or were deleted while stopped
+        clipboard_images::remove_orphaned_images(&self.storage_dir, &items);
+
         Ok(())
     }
 
@@ -296,3 +487,130 @@ impl ClipboardWatcher {
         self.settings.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture;
+
+    fn item(text: &str) -> ClipboardItem {
+        ClipboardItem {
+            id: uuid::Uuid::new_v4().to_string(),
+            content_type: ClipboardContentType::Text,
+            text: Some(text.to_string()),
+            image_path: None,
+            thumbnail_path: None,
+            hash: String::new(),
+            perceptual_hash: None,
+            timestamp: chrono::Utc::now().timestamp(),
+            is_sensitive: false,
+            app_source: None,
+        }
+    }
+
+    #[test]
+    fn test_privacy_mode_pauses_capture() {
+        let dir = tempfile::tempdir().unwrap();
+        let privacy = PrivacyMode::new();
+        let watcher = ClipboardWatcher::new(dir.path().to_path_buf(), ClipboardSettings::default())
+            .with_privacy(privacy.clone());
+
+        privacy.set(true, Some(Duration::from_millis(30)), false);
+        watcher.add_item(item("during screen share")).unwrap();
+        assert!(watcher.get_items().unwrap().is_empty());
+
+        // Expiry re-enables capture
+        thread::sleep(Duration::from_millis(60));
+        watcher.add_item(item("after")).unwrap();
+        assert_eq!(watcher.get_items().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_suppression_token_skips_app_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let suppression = ClipboardSuppression::new();
+        let watcher = ClipboardWatcher::new(dir.path().to_path_buf(), ClipboardSettings::default())
+            .with_suppression(suppression.clone());
+
+        {
+            let _outer = suppression.suppress();
+            let inner = suppression.suppress();
+            drop(inner);
+            // Still held by the outer token
+            watcher.add_item(item("temporary copy")).unwrap();
+        }
+        // Lingers for one poll interval after the last token drops
+        assert!(suppression.is_suppressed());
+        watcher.add_item(item("late poll")).unwrap();
+        assert!(watcher.get_items().unwrap().is_empty());
+
+        thread::sleep(POLL_INTERVAL + Duration::from_millis(50));
+        assert!(!suppression.is_suppressed());
+        watcher.add_item(item("user copy")).unwrap();
+        assert_eq!(watcher.get_items().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_polled_text_is_captured_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = ClipboardWatcher::new(dir.path().to_path_buf(), ClipboardSettings::default());
+        let mut last_seen = Some("copied before launch".to_string());
+
+        watcher.capture_text("copied before launch".to_string(), &mut last_seen).unwrap();
+        watcher.capture_text("  ".to_string(), &mut last_seen).unwrap();
+        assert!(watcher.get_items().unwrap().is_empty());
+
+        watcher.capture_text("user copy".to_string(), &mut last_seen).unwrap();
+        watcher.capture_text("user copy".to_string(), &mut last_seen).unwrap();
+        let items = watcher.get_items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].text.as_deref(), Some("user copy"));
+    }
+
+    fn image_files(storage_dir: &std::path::Path) -> usize {
+        fs::read_dir(clipboard_images::images_dir(storage_dir)).map_or(0, |entries| entries.count())
+    }
+
+    #[test]
+    fn test_recompressed_image_is_not_duplicated() {
+        let dir = tempfile::tempdir().unwrap();
+        let watcher = ClipboardWatcher::new(dir.path().to_path_buf(), ClipboardSettings::default());
+
+        let added = watcher.add_image(fixture!("clipboard/screenshot.png"), None).unwrap().unwrap();
+        assert!(added.thumbnail_path.as_ref().unwrap().exists());
+        // Same bytes, then the same screenshot saved as JPEG
+        assert!(watcher.add_image(fixture!("clipboard/screenshot.png"), None).unwrap().is_none());
+        assert!(watcher.add_image(fixture!("clipboard/screenshot.jpg"), None).unwrap().is_none());
+        assert!(watcher.add_image(fixture!("clipboard/other.png"), None).unwrap().is_some());
+
+        assert_eq!(watcher.get_items().unwrap().len(), 2);
+        // Rejected copies leave no files behind
+        assert_eq!(image_files(dir.path()), 4);
+    }
+
+    #[test]
+    fn test_image_files_are_removed_with_their_items() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = ClipboardSettings { max_items: 2, ..ClipboardSettings::default() };
+        let watcher = ClipboardWatcher::new(dir.path().to_path_buf(), settings);
+
+        let screenshot = watcher.add_image(fixture!("clipboard/screenshot.png"), None).unwrap().unwrap();
+        let other = watcher.add_image(fixture!("clipboard/other.png"), None).unwrap().unwrap();
+        watcher.delete_item(&other.id).unwrap();
+        assert!(!other.image_path.unwrap().exists());
+        assert!(!other.thumbnail_path.unwrap().exists());
+
+        // Evicted once two newer items arrive
+        watcher.add_item(item("first")).unwrap();
+        watcher.add_item(item("second")).unwrap();
+        assert!(!screenshot.image_path.unwrap().exists());
+        assert_eq!(image_files(dir.path()), 0);
+
+        // Files no item refers to are removed on load
+        let images = clipboard_images::images_dir(dir.path());
+        fs::write(images.join("stale.png"), fixture!("clipboard/other.png")).unwrap();
+        watcher.load_history().unwrap();
+        assert_eq!(image_files(dir.path()), 0);
+        assert_eq!(watcher.get_items().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,134 @@
+//! Cloud Files
+//! iCloud Drive, OneDrive and other sync clients leave placeholders on disk:
+//! files listed with their name and size whose content is only downloaded
+//! when something reads it. The indexer flags them from metadata alone so a
+//! scan never starts a download, and the preview pane doesn't read them.
+
+use std::fmt;
+use std::fs::{self, File, Metadata};
+use std::io;
+use std::path::Path;
+
+/// `st_flags` bit APFS sets on files whose content isn't local
+#[cfg(target_os = "macos")]
+const SF_DATALESS: u32 = 0x4000_0000;
+
+/// Attributes Windows sets on cloud files that download when opened or read
+#[cfg(target_os = "windows")]
+const FILE_ATTRIBUTE_RECALL_ON_OPEN: u32 = 0x0004_0000;
+#[cfg(target_os = "windows")]
+const FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS: u32 = 0x0040_0000;
+
+/// Tells placeholders apart, so callers can be tested without a sync client
+pub trait PlaceholderProbe: Send + Sync + fmt::Debug {
+    /// Whether the file is a placeholder. Decided from `metadata` only;
+    /// implementations must not open the file.
+    fn is_placeholder(&self, path: &Path, metadata: &Metadata) -> bool;
+
+    /// Download a placeholder's content, blocking until it's local
+    fn materialize(&self, path: &Path) -> Result<(), String>;
+}
+
+/// Placeholder flags as the platform reports them
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemPlaceholders;
+
+impl PlaceholderProbe for SystemPlaceholders {
+    #[cfg(target_os = "macos")]
+    fn is_placeholder(&self, _path: &Path, metadata: &Metadata) -> bool {
+        use std::os::macos::fs::MetadataExt;
+        metadata.st_flags() & SF_DATALESS != 0
+    }
+
+    #[cfg(target_os = "windows")]
+    fn is_placeholder(&self, _path: &Path, metadata: &Metadata) -> bool {
+        use std::os::windows::fs::MetadataExt;
+        metadata.file_attributes() & (FILE_ATTRIBUTE_RECALL_ON_OPEN | FILE_ATTRIBUTE_RECALL_ON_DATA_ACCESS) != 0
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    fn is_placeholder(&self, _path: &Path, _metadata: &Metadata) -> bool {
+        false
+    }
+
+    fn materialize(&self, path: &Path) -> Result<(), String> {
+        // Both platforms download the content for a reader; reading it all
+        // waits until every part is local
+        let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        io::copy(&mut file, &mut io::sink())
+            .map(|_| ())
+            .map_err(|e| format!("Failed to download {}: {}", path.display(), e))
+    }
+}
+
+/// Download `path` if it's a placeholder. True when it was one.
+pub fn ensure_downloaded(probe: &dyn PlaceholderProbe, path: &Path) -> Result<bool, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if !probe.is_placeholder(path, &metadata) {
+        return Ok(false);
+    }
+    probe.materialize(path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+pub mod testing {
+    use super::*;
+    use std::collections::HashSet;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// Treats the listed paths as placeholders and records downloads
+    #[derive(Debug, Default)]
+    pub struct FakePlaceholders {
+        pub placeholders: Mutex<HashSet<PathBuf>>,
+        pub materialized: Mutex<Vec<PathBuf>>,
+    }
+
+    impl FakePlaceholders {
+        pub fn with(paths: &[&Path]) -> Self {
+            Self {
+                placeholders: Mutex::new(paths.iter().map(|path| path.to_path_buf()).collect()),
+                ..Self::default()
+            }
+        }
+    }
+
+    impl PlaceholderProbe for FakePlaceholders {
+        fn is_placeholder(&self, path: &Path, _metadata: &Metadata) -> bool {
+            self.placeholders.lock().unwrap().contains(path)
+        }
+
+        fn materialize(&self, path: &Path) -> Result<(), String> {
+            self.placeholders.lock().unwrap().remove(path);
+            self.materialized.lock().unwrap().push(path.to_path_buf());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::FakePlaceholders;
+    use super::*;
+
+    #[test]
+    fn test_only_placeholders_are_downloaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let local = dir.path().join("local.txt");
+        let cloud = dir.path().join("cloud.txt");
+        fs::write(&local, "local").unwrap();
+        fs::write(&cloud, "cloud").unwrap();
+        let placeholders = FakePlaceholders::with(&[&cloud]);
+
+        assert!(!ensure_downloaded(&placeholders, &local).unwrap());
+        assert!(ensure_downloaded(&placeholders, &cloud).unwrap());
+        assert!(!ensure_downloaded(&placeholders, &cloud).unwrap());
+        assert_eq!(*placeholders.materialized.lock().unwrap(), vec![cloud]);
+        assert!(ensure_downloaded(&placeholders, &dir.path().join("missing.txt")).is_err());
+
+        // Plain files are never placeholders here
+        let metadata = fs::metadata(&local).unwrap();
+        assert!(!SystemPlaceholders.is_placeholder(&local, &metadata));
+    }
+}
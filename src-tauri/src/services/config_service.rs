@@ -2,35 +2,34 @@
 //! Manages application configuration
 #![allow(dead_code)]
 
-use std::path::PathBuf;
-use tauri::Config;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, Runtime};
 
 use crate::cmds::abbreviation::AbbreviationConfig;
 
+/// Abbreviation config file name
+const ABBREVIATIONS_FILE: &str = "abbreviations.json";
+
+/// Marker left next to the legacy file once it has been migrated
+const MIGRATED_MARKER_SUFFIX: &str = ".migrated";
+
 pub struct ConfigService {
-    app_config: Config,
+    handle: AppHandle,
 }
 
 impl ConfigService {
-    pub fn new(app_config: Config) -> Self {
-        Self { app_config }
+    pub fn new(handle: AppHandle) -> Self {
+        Self { handle }
     }
 
     async fn get_config_path(&self) -> Result<PathBuf, String> {
-        let home = std::env::var("HOME")
-            .map_err(|_| "Failed to get HOME directory")?;
-        let app_data_dir = PathBuf::from(home).join(".config");
-
-        let config_dir = app_data_dir.join("kaka");
-        std::fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
-
-        Ok(config_dir.join("abbreviations.json"))
+        abbreviation_config_path(&self.handle)
     }
 
     pub async fn get_abbreviation_config(&self) -> Result<AbbreviationConfig, String> {
         let config_path = self.get_config_path().await?;
-        
+
         if !config_path.exists() {
             let default_config = AbbreviationConfig::default();
             self.save_abbreviation_config(&default_config).await?;
@@ -39,7 +38,7 @@ impl ConfigService {
 
         let content = std::fs::read_to_string(&config_path)
             .map_err(|e| format!("Failed to read config file: {}", e))?;
-        
+
         serde_json::from_str(&content)
             .map_err(|e| format!("Failed to parse config file: {}", e))
     }
@@ -55,4 +54,124 @@ impl ConfigService {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Get the abbreviation config path in the app config dir, migrating the
+/// legacy `~/.config/kaka/abbreviations.json` on first access
+pub fn abbreviation_config_path<R: Runtime>(handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    let config_dir = handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get config dir: {}", e))?;
+
+    fs::create_dir_all(&config_dir)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+
+    let config_path = config_dir.join(ABBREVIATIONS_FILE);
+
+    if let Some(legacy_path) = legacy_abbreviation_config_path() {
+        if let Err(e) = migrate_legacy_config(&legacy_path, &config_path) {
+            eprintln!("[ConfigService] Failed to migrate legacy abbreviation config: {}", e);
+        }
+    }
+
+    Ok(config_path)
+}
+
+/// Legacy location used before configs moved to the Tauri app dirs
+fn legacy_abbreviation_config_path() -> Option<PathBuf> {
+    let home = std::env::var("HOME").ok()?;
+    Some(PathBuf::from(home).join(".config").join("kaka").join(ABBREVIATIONS_FILE))
+}
+
+/// Copy a legacy config file to its new location.
+///
+/// Only runs when the legacy file exists, the target doesn't, and the legacy
+/// file hasn't been migrated already. Leaves a `.migrated` marker next to the
+/// legacy file. Returns whether a migration happened.
+pub fn migrate_legacy_config(legacy_path: &Path, target_path: &Path) -> Result<bool, String> {
+    let marker_path = migrated_marker_path(legacy_path);
+
+    if !legacy_path.exists() || target_path.exists() || marker_path.exists() {
+        return Ok(false);
+    }
+
+    if let Some(parent) = target_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    fs::copy(legacy_path, target_path)
+        .map_err(|e| format!("Failed to copy legacy config: {}", e))?;
+
+    fs::write(&marker_path, target_path.to_string_lossy().as_bytes())
+        .map_err(|e| format!("Failed to write migration marker: {}", e))?;
+
+    println!(
+        "[ConfigService] Migrated legacy config {:?} -> {:?}",
+        legacy_path, target_path
+    );
+
+    Ok(true)
+}
+
+fn migrated_marker_path(legacy_path: &Path) -> PathBuf {
+    let mut name = legacy_path
+        .file_name()
+        .map(|n| n.to_os_string())
+        .unwrap_or_default();
+    name.push(MIGRATED_MARKER_SUFFIX);
+    legacy_path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dirs() -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let root = tempfile::tempdir().unwrap();
+        let legacy = root.path().join("legacy").join(ABBREVIATIONS_FILE);
+        let target = root.path().join("config").join(ABBREVIATIONS_FILE);
+        (root, legacy, target)
+    }
+
+    #[test]
+    fn test_migrates_when_only_legacy_exists() {
+        let (_root, legacy, target) = temp_dirs();
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::write(&legacy, r#"{"legacy":true}"#).unwrap();
+
+        assert!(migrate_legacy_config(&legacy, &target).unwrap());
+        assert_eq!(fs::read_to_string(&target).unwrap(), r#"{"legacy":true}"#);
+        assert!(migrated_marker_path(&legacy).exists());
+        // Legacy file is left in place
+        assert!(legacy.exists());
+
+        // Second run is a no-op even if the target is removed again
+        fs::remove_file(&target).unwrap();
+        assert!(!migrate_legacy_config(&legacy, &target).unwrap());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_skips_when_both_exist() {
+        let (_root, legacy, target) = temp_dirs();
+        fs::create_dir_all(legacy.parent().unwrap()).unwrap();
+        fs::create_dir_all(target.parent().unwrap()).unwrap();
+        fs::write(&legacy, "legacy").unwrap();
+        fs::write(&target, "current").unwrap();
+
+        assert!(!migrate_legacy_config(&legacy, &target).unwrap());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "current");
+        assert!(!migrated_marker_path(&legacy).exists());
+    }
+
+    #[test]
+    fn test_skips_when_neither_exists() {
+        let (_root, legacy, target) = temp_dirs();
+
+        assert!(!migrate_legacy_config(&legacy, &target).unwrap());
+        assert!(!target.exists());
+        assert!(!migrated_marker_path(&legacy).exists());
+    }
+}
@@ -0,0 +1,278 @@
+//! Connectivity
+//! Application-wide online/offline state. The shared HTTP module reports
+//! request outcomes here and refuses to touch the network while offline; a
+//! background probe notices when the connection comes back. Users can also
+//! force offline mode.
+//!
+//! Metered connections aren't reported: no platform exposes them without
+//! per-OS network APIs this app doesn't link.
+
+use crate::types::events;
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+use tauri::AppHandle;
+
+/// Consecutive failed requests before the app counts as offline, so one
+/// flaky request doesn't switch every network feature off
+const OFFLINE_AFTER_FAILURES: u32 = 2;
+
+/// How often the connection is probed while online
+const PROBE_INTERVAL_ONLINE: Duration = Duration::from_secs(60);
+
+/// How often the connection is probed while offline
+const PROBE_INTERVAL_OFFLINE: Duration = Duration::from_secs(15);
+
+/// Probed for connectivity; the registry most network features use
+pub const PROBE_URL: &str = "https://registry.npmjs.org";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectivityState {
+    Online,
+    Offline,
+}
+
+/// Payload of `get_connectivity_status` and the `connectivity:changed` event
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConnectivityStatus {
+    pub state: ConnectivityState,
+    /// Offline because the user turned on offline mode
+    pub forced_offline: bool,
+    /// When the state last changed (Unix ms), 0 if it never has
+    pub changed_at: i64,
+}
+
+type Listener = Box<dyn Fn(&ConnectivityStatus) + Send + Sync>;
+
+struct Inner {
+    reachable: bool,
+    forced: bool,
+    failures: u32,
+    changed_at: i64,
+}
+
+impl Inner {
+    fn state(&self) -> ConnectivityState {
+        if self.reachable && !self.forced {
+            ConnectivityState::Online
+        } else {
+            ConnectivityState::Offline
+        }
+    }
+
+    fn status(&self) -> ConnectivityStatus {
+        ConnectivityStatus {
+            state: self.state(),
+            forced_offline: self.forced,
+            changed_at: self.changed_at,
+        }
+    }
+}
+
+/// Tracks whether the network can be used
+pub struct ConnectivityMonitor {
+    inner: Mutex<Inner>,
+    listener: Mutex<Option<Listener>>,
+}
+
+impl Default for ConnectivityMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConnectivityMonitor {
+    /// A monitor that assumes the network works until a request fails
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                reachable: true,
+                forced: false,
+                failures: 0,
+                changed_at: 0,
+            }),
+            listener: Mutex::new(None),
+        }
+    }
+
+    pub fn status(&self) -> ConnectivityStatus {
+        self.inner.lock().map(|inner| inner.status()).unwrap_or(ConnectivityStatus {
+            state: ConnectivityState::Online,
+            forced_offline: false,
+            changed_at: 0,
+        })
+    }
+
+    /// Whether network requests should be skipped
+    pub fn is_offline(&self) -> bool {
+        self.status().state == ConnectivityState::Offline
+    }
+
+    pub fn is_forced_offline(&self) -> bool {
+        self.status().forced_offline
+    }
+
+    /// Called with the new status whenever the state changes
+    pub fn set_listener(&self, listener: impl Fn(&ConnectivityStatus) + Send + Sync + 'static) {
+        if let Ok(mut current) = self.listener.lock() {
+            *current = Some(Box::new(listener));
+        }
+    }
+
+    /// A request reached its server, whatever the server answered
+    pub fn report_success(&self) {
+        self.update(|inner| {
+            inner.failures = 0;
+            inner.reachable = true;
+        });
+    }
+
+    /// A request couldn't connect or timed out
+    pub fn report_failure(&self) {
+        self.update(|inner| {
+            inner.failures = inner.failures.saturating_add(1);
+            if inner.failures >= OFFLINE_AFTER_FAILURES {
+                inner.reachable = false;
+            }
+        });
+    }
+
+    /// Result of a dedicated connectivity probe, trusted without the
+    /// failure threshold
+    pub fn report_probe(&self, reachable: bool) {
+        self.update(|inner| {
+            inner.failures = if reachable { 0 } else { OFFLINE_AFTER_FAILURES };
+            inner.reachable = reachable;
+        });
+    }
+
+    /// Turn the user's offline mode on or off
+    pub fn set_forced(&self, forced: bool) {
+        self.update(|inner| inner.forced = forced);
+    }
+
+    /// Apply `change`, then notify the listener if the state changed. That
+    /// happens outside the lock so the listener can use the monitor.
+    fn update(&self, change: impl FnOnce(&mut Inner)) {
+        let status = {
+            let Ok(mut inner) = self.inner.lock() else {
+                return;
+            };
+            let before = inner.status();
+            change(&mut inner);
+            let state = inner.state();
+            if state == before.state && inner.forced == before.forced_offline {
+                return;
+            }
+
+            if state != before.state {
+                inner.changed_at = chrono::Utc::now().timestamp_millis();
+            }
+            inner.status()
+        };
+
+        println!(
+            "[Connectivity] {:?}{}",
+            status.state,
+            if status.forced_offline { " (offline mode)" } else { "" }
+        );
+        if let Ok(listener) = self.listener.lock() {
+            if let Some(listener) = listener.as_ref() {
+                listener(&status);
+            }
+        }
+    }
+}
+
+/// The app-wide monitor used by the HTTP module
+pub fn monitor() -> &'static ConnectivityMonitor {
+    static MONITOR: OnceLock<ConnectivityMonitor> = OnceLock::new();
+    MONITOR.get_or_init(ConnectivityMonitor::new)
+}
+
+/// Probe `url` and report the result to `monitor`. Skipped in offline mode.
+pub fn probe(monitor: &ConnectivityMonitor, url: &str) {
+    if !monitor.is_forced_offline() {
+        monitor.report_probe(crate::services::http::probe(url));
+    }
+}
+
+/// Emit `connectivity:changed` on state changes and probe the connection
+/// in the background for the app's lifetime
+pub fn start_connectivity_monitor(handle: AppHandle) {
+    monitor().set_listener(move |status| {
+        let _ = events::emit(&handle, status);
+    });
+
+    thread::spawn(|| loop {
+        probe(monitor(), PROBE_URL);
+        thread::sleep(if monitor().is_offline() {
+            PROBE_INTERVAL_OFFLINE
+        } else {
+            PROBE_INTERVAL_ONLINE
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    fn counting_listener(monitor: &ConnectivityMonitor) -> Arc<AtomicU32> {
+        let changes = Arc::new(AtomicU32::new(0));
+        let counter = changes.clone();
+        monitor.set_listener(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        changes
+    }
+
+    #[test]
+    fn test_flapping_requests_stay_online() {
+        let monitor = ConnectivityMonitor::new();
+        let changes = counting_listener(&monitor);
+
+        for _ in 0..10 {
+            monitor.report_failure();
+            monitor.report_success();
+        }
+        assert!(!monitor.is_offline());
+        assert_eq!(changes.load(Ordering::SeqCst), 0);
+
+        monitor.report_failure();
+        monitor.report_failure();
+        assert!(monitor.is_offline());
+        assert_eq!(changes.load(Ordering::SeqCst), 1);
+
+        monitor.report_success();
+        assert!(!monitor.is_offline());
+        assert_eq!(changes.load(Ordering::SeqCst), 2);
+        assert!(monitor.status().changed_at > 0);
+    }
+
+    #[test]
+    fn test_forced_offline_overrides_detection() {
+        let monitor = ConnectivityMonitor::new();
+        let changes = counting_listener(&monitor);
+
+        monitor.set_forced(true);
+        assert!(monitor.is_offline());
+        assert!(monitor.status().forced_offline);
+
+        // Successes don't bring a forced-offline app back
+        monitor.report_probe(true);
+        assert!(monitor.is_offline());
+
+        monitor.set_forced(false);
+        assert!(!monitor.is_offline());
+        assert_eq!(changes.load(Ordering::SeqCst), 2);
+
+        // Probes are trusted without the failure threshold
+        monitor.report_probe(false);
+        assert!(monitor.is_offline());
+    }
+}
@@ -0,0 +1,318 @@
+//! Crash Reporter
+//! Writes panics and fatal Tauri errors to app-data/crashes/ when the
+//! `crash_reports` setting is on. Reports stay local; the user decides
+//! whether to share one.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// Crash report directory under the app data dir
+const CRASH_DIR: &str = "crashes";
+
+/// Number of reports kept on disk
+pub const MAX_CRASH_REPORTS: usize = 20;
+
+/// Number of debug log lines attached to a report
+const LOG_TAIL_LINES: usize = 100;
+
+/// Only this much of the end of the log is read, so a huge log can't stall the hook
+const LOG_TAIL_BYTES: u64 = 64 * 1024;
+
+/// A saved crash report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    /// Unix timestamp (ms)
+    pub timestamp: i64,
+    /// "panic" or "tauri_error"
+    pub kind: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: String,
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub log_tail: Vec<String>,
+}
+
+/// Crash report list entry
+#[derive(Debug, Clone, Serialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub timestamp: i64,
+    pub kind: String,
+    pub message: String,
+}
+
+struct CrashPaths {
+    crash_dir: PathBuf,
+    log_path: Option<PathBuf>,
+}
+
+/// Shared crash reporter. Created before the app starts and configured with
+/// its directories once the app handle is available.
+#[derive(Clone, Default)]
+pub struct CrashReporter {
+    enabled: Arc<AtomicBool>,
+    paths: Arc<OnceLock<CrashPaths>>,
+    sequence: Arc<AtomicU64>,
+}
+
+impl CrashReporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set where reports are written and which log file is tailed. Only the
+    /// first call has any effect.
+    pub fn configure(&self, data_dir: &Path, log_path: Option<PathBuf>) {
+        let _ = self.paths.set(CrashPaths {
+            crash_dir: crash_dir(data_dir),
+            log_path,
+        });
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::SeqCst)
+    }
+
+    /// Install a panic hook that records a report, then defers to the
+    /// previously installed hook
+    pub fn install_panic_hook(&self) {
+        let reporter = self.clone();
+        let previous = std::panic::take_hook();
+
+        std::panic::set_hook(Box::new(move |info| {
+            let message = if let Some(s) = info.payload().downcast_ref::<&str>() {
+                s.to_string()
+            } else if let Some(s) = info.payload().downcast_ref::<String>() {
+                s.clone()
+            } else {
+                "Box<dyn Any>".to_string()
+            };
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+
+            reporter.record("panic", message, location);
+            previous(info);
+        }));
+    }
+
+    /// Write a report if enabled and configured. Returns the report path.
+    ///
+    /// Runs inside the panic hook, so every step is fallible and errors are
+    /// swallowed rather than unwrapped.
+    pub fn record(&self, kind: &str, message: String, location: Option<String>) -> Option<PathBuf> {
+        if !self.is_enabled() {
+            return None;
+        }
+        let paths = self.paths.get()?;
+
+        let timestamp = chrono::Utc::now().timestamp_millis();
+        let id = format!("{}-{:06}", timestamp, self.sequence.fetch_add(1, Ordering::SeqCst));
+
+        let report = CrashReport {
+            id: id.clone(),
+            timestamp,
+            kind: kind.to_string(),
+            message,
+            location,
+            thread: std::thread::current().name().map(|n| n.to_string()),
+            backtrace: format!("{:?}", backtrace::Backtrace::new()),
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            log_tail: paths
+                .log_path
+                .as_deref()
+                .map(|p| tail_lines(p, LOG_TAIL_LINES))
+                .unwrap_or_default(),
+        };
+
+        fs::create_dir_all(&paths.crash_dir).ok()?;
+        let path = report_path(&paths.crash_dir, &id);
+        let content = serde_json::to_string_pretty(&report).ok()?;
+        fs::write(&path, content).ok()?;
+
+        prune_reports(&paths.crash_dir, MAX_CRASH_REPORTS);
+        Some(path)
+    }
+}
+
+/// Crash report directory for an app data dir
+pub fn crash_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join(CRASH_DIR)
+}
+
+fn report_path(crash_dir: &Path, id: &str) -> PathBuf {
+    crash_dir.join(format!("{}.json", id))
+}
+
+/// Report IDs are generated as "<timestamp>-<sequence>"; anything else could
+/// escape the crash directory
+fn validate_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid crash report id: {}", id));
+    }
+    Ok(())
+}
+
+/// Load all reports, newest first
+fn load_reports(crash_dir: &Path) -> Vec<CrashReport> {
+    let Ok(entries) = fs::read_dir(crash_dir) else {
+        return vec![];
+    };
+
+    let mut reports: Vec<CrashReport> = entries
+        .flatten()
+        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("json"))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|content| serde_json::from_str(&content).ok())
+        .collect();
+
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp).then_with(|| b.id.cmp(&a.id)));
+    reports
+}
+
+/// List reports, newest first
+pub fn list_reports(crash_dir: &Path) -> Vec<CrashReportSummary> {
+    load_reports(crash_dir)
+        .into_iter()
+        .map(|r| CrashReportSummary {
+            id: r.id,
+            timestamp: r.timestamp,
+            kind: r.kind,
+            message: r.message,
+        })
+        .collect()
+}
+
+/// Read a single report
+pub fn read_report(crash_dir: &Path, id: &str) -> Result<CrashReport, String> {
+    validate_id(id)?;
+    let content = fs::read_to_string(report_path(crash_dir, id))
+        .map_err(|e| format!("Failed to read crash report: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse crash report: {}", e))
+}
+
+/// Delete a single report
+pub fn delete_report(crash_dir: &Path, id: &str) -> Result<(), String> {
+    validate_id(id)?;
+    fs::remove_file(report_path(crash_dir, id))
+        .map_err(|e| format!("Failed to delete crash report: {}", e))
+}
+
+/// Delete all but the newest `keep` reports
+pub fn prune_reports(crash_dir: &Path, keep: usize) {
+    for report in load_reports(crash_dir).into_iter().skip(keep) {
+        let _ = fs::remove_file(report_path(crash_dir, &report.id));
+    }
+}
+
+/// Last `n` lines of a file, reading at most LOG_TAIL_BYTES from its end
+fn tail_lines(path: &Path, n: usize) -> Vec<String> {
+    let Ok(mut file) = fs::File::open(path) else {
+        return vec![];
+    };
+
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = len.saturating_sub(LOG_TAIL_BYTES);
+    if file.seek(SeekFrom::Start(start)).is_err() {
+        return vec![];
+    }
+
+    let mut bytes = Vec::new();
+    if file.read_to_end(&mut bytes).is_err() {
+        return vec![];
+    }
+    let content = String::from_utf8_lossy(&bytes);
+
+    let mut lines: Vec<&str> = content.lines().collect();
+    // The first line is likely cut off when reading from the middle
+    if start > 0 && !lines.is_empty() {
+        lines.remove(0);
+    }
+
+    let skip = lines.len().saturating_sub(n);
+    lines.into_iter().skip(skip).map(|l| l.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_panic_in_thread_writes_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("debug.log");
+        fs::write(&log_path, "first line\nlast line\n").unwrap();
+
+        let reporter = CrashReporter::new();
+        reporter.configure(dir.path(), Some(log_path));
+        reporter.set_enabled(true);
+        reporter.install_panic_hook();
+
+        let result = std::thread::Builder::new()
+            .name("crash-test".to_string())
+            .spawn(|| panic!("controlled crash for test"))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+
+        let reports = load_reports(&crash_dir(dir.path()));
+        let report = reports
+            .iter()
+            .find(|r| r.message == "controlled crash for test")
+            .expect("crash report written");
+        assert_eq!(report.kind, "panic");
+        assert_eq!(report.thread.as_deref(), Some("crash-test"));
+        assert_eq!(report.log_tail, vec!["first line", "last line"]);
+        assert!(read_report(&crash_dir(dir.path()), &report.id).is_ok());
+
+        // The hook stays installed for the rest of the test run
+        reporter.set_enabled(false);
+    }
+
+    #[test]
+    fn test_disabled_reporter_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let reporter = CrashReporter::new();
+        reporter.configure(dir.path(), None);
+
+        assert!(reporter.record("panic", "ignored".to_string(), None).is_none());
+        assert!(list_reports(&crash_dir(dir.path())).is_empty());
+    }
+
+    #[test]
+    fn test_prune_keeps_newest() {
+        let dir = tempfile::tempdir().unwrap();
+        let reporter = CrashReporter::new();
+        reporter.configure(dir.path(), None);
+        reporter.set_enabled(true);
+
+        for i in 0..MAX_CRASH_REPORTS + 3 {
+            reporter.record("panic", format!("crash {}", i), None).unwrap();
+        }
+
+        let reports = list_reports(&crash_dir(dir.path()));
+        assert_eq!(reports.len(), MAX_CRASH_REPORTS);
+        assert_eq!(reports[0].message, format!("crash {}", MAX_CRASH_REPORTS + 2));
+    }
+
+    #[test]
+    fn test_rejects_path_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_report(dir.path(), "../settings").is_err());
+        assert!(delete_report(dir.path(), "a/b").is_err());
+    }
+}
@@ -0,0 +1,151 @@
+//! Data Access
+//! Whether the app can read the places it gets data from: browser profiles
+//! and indexed folders. On macOS these can be behind Full Disk Access or
+//! Files and Folders, and reads fail with a permission error until the user
+//! grants it. Probing tells that apart from a location that doesn't exist,
+//! so the UI can explain empty results and link to the right settings pane.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Reason recorded for a source skipped because it couldn't be read
+pub const SKIPPED_PERMISSION: &str = "permission";
+
+/// Result of probing a location
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessState {
+    Granted,
+    /// Exists, but the OS refused the read
+    Denied,
+    Missing,
+    /// Failed for another reason, e.g. an IO error
+    Unreadable,
+}
+
+/// A System Settings privacy pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrivacyPane {
+    FullDiskAccess,
+    FilesAndFolders,
+}
+
+impl PrivacyPane {
+    /// Deep link that opens the pane in System Settings
+    pub fn settings_url(self) -> &'static str {
+        match self {
+            Self::FullDiskAccess => "x-apple.systempreferences:com.apple.preference.security?Privacy_AllFiles",
+            Self::FilesAndFolders => "x-apple.systempreferences:com.apple.preference.security?Privacy_FilesAndFolders",
+        }
+    }
+}
+
+/// Access to one data source
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DataSourceStatus {
+    /// e.g. "safari", "chrome" or "index_root"
+    pub source: String,
+    pub path: String,
+    pub state: AccessState,
+    /// The OS error when the probe failed
+    pub message: Option<String>,
+    /// Where to grant access, set when access was denied
+    pub pane: Option<PrivacyPane>,
+}
+
+impl DataSourceStatus {
+    /// Probe `path` for `source`, pointing at `pane` if access is denied
+    pub fn probe(source: &str, path: &Path, pane: PrivacyPane) -> Self {
+        let (state, message) = match probe(path) {
+            Ok(()) => (AccessState::Granted, None),
+            Err(e) => (classify(&e), Some(e.to_string())),
+        };
+        Self {
+            source: source.to_string(),
+            path: path.to_string_lossy().to_string(),
+            state,
+            message,
+            pane: (state == AccessState::Denied).then_some(pane),
+        }
+    }
+}
+
+/// Sort a failed read into denied, missing or otherwise unreadable.
+/// Protected locations on macOS fail with EPERM and ordinary permission
+/// bits with EACCES; both are `PermissionDenied`.
+pub fn classify(error: &io::Error) -> AccessState {
+    match error.kind() {
+        io::ErrorKind::PermissionDenied => AccessState::Denied,
+        io::ErrorKind::NotFound | io::ErrorKind::NotADirectory => AccessState::Missing,
+        _ => AccessState::Unreadable,
+    }
+}
+
+/// Cheap read of `path`: open a file or list a directory. Metadata alone
+/// isn't enough, as macOS allows it for protected locations.
+pub fn probe(path: &Path) -> io::Result<()> {
+    if fs::metadata(path)?.is_dir() {
+        fs::read_dir(path).map(drop)
+    } else {
+        fs::File::open(path).map(drop)
+    }
+}
+
+/// Whether `path` exists but can't be read for lack of permission
+pub fn is_denied(path: &Path) -> bool {
+    probe(path).is_err_and(|e| classify(&e) == AccessState::Denied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_io_errors() {
+        let kind = |kind: io::ErrorKind| classify(&io::Error::from(kind));
+        assert_eq!(kind(io::ErrorKind::PermissionDenied), AccessState::Denied);
+        assert_eq!(kind(io::ErrorKind::NotFound), AccessState::Missing);
+        assert_eq!(kind(io::ErrorKind::NotADirectory), AccessState::Missing);
+        assert_eq!(kind(io::ErrorKind::Interrupted), AccessState::Unreadable);
+        assert_eq!(kind(io::ErrorKind::InvalidData), AccessState::Unreadable);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_classify_os_errors() {
+        let os = |code: i32| classify(&io::Error::from_raw_os_error(code));
+        // EPERM, as macOS returns without Full Disk Access, and EACCES
+        assert_eq!(os(1), AccessState::Denied);
+        assert_eq!(os(13), AccessState::Denied);
+        // ENOENT, ENOTDIR
+        assert_eq!(os(2), AccessState::Missing);
+        assert_eq!(os(20), AccessState::Missing);
+        // EIO
+        assert_eq!(os(5), AccessState::Unreadable);
+    }
+
+    #[test]
+    fn test_probe_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = dir.path().join("History.db");
+        fs::write(&history, b"").unwrap();
+
+        let granted = DataSourceStatus::probe("safari", &history, PrivacyPane::FullDiskAccess);
+        assert_eq!(granted.state, AccessState::Granted);
+        assert_eq!((granted.message, granted.pane), (None, None));
+        assert_eq!(
+            DataSourceStatus::probe("chrome", dir.path(), PrivacyPane::FullDiskAccess).state,
+            AccessState::Granted
+        );
+
+        let missing = DataSourceStatus::probe("chrome", &dir.path().join("Default"), PrivacyPane::FullDiskAccess);
+        assert_eq!(missing.state, AccessState::Missing);
+        assert!(missing.message.is_some());
+        // Only a denial points at a settings pane
+        assert_eq!(missing.pane, None);
+        assert!(!is_denied(&dir.path().join("Default")));
+    }
+}
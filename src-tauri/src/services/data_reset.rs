@@ -0,0 +1,235 @@
+//! Data Reset
+//! Deletes selected parts of the app's on-disk state (search indexes,
+//! clipboard history, plugins, settings, caches) and recreates the empty
+//! structures the app expects, for a factory reset or to recover from a
+//! wedged state without hunting for the data directory.
+
+use crate::cmds::plugins::{plugin_settings_path, plugin_state_path};
+use crate::db::browser::open_browser_db;
+use crate::db::files::open_files_db;
+use crate::services::backup::BackupPaths;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Text the user has to type to confirm a reset
+pub const RESET_CONFIRM_TOKEN: &str = "RESET";
+
+/// Parts of the app state that can be reset on their own
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetComponent {
+    SearchIndexes,
+    ClipboardHistory,
+    Plugins,
+    Settings,
+    Caches,
+}
+
+impl ResetComponent {
+    pub const ALL: [ResetComponent; 5] = [
+        ResetComponent::SearchIndexes,
+        ResetComponent::ClipboardHistory,
+        ResetComponent::Plugins,
+        ResetComponent::Settings,
+        ResetComponent::Caches,
+    ];
+
+    /// Files and directories owned by this component
+    fn paths(self, paths: &BackupPaths) -> Vec<PathBuf> {
+        let data = &paths.data_dir;
+        match self {
+            ResetComponent::SearchIndexes => ["files_index.db", "browser_cache.db"]
+                .iter()
+                .flat_map(|db| sqlite_files(&data.join(db)))
+                .collect(),
+            ResetComponent::ClipboardHistory => vec![data.join("clipboard")],
+            ResetComponent::Plugins => vec![
+                data.join("plugins"),
+                data.join("plugins-data"),
+                plugin_state_path(data),
+                plugin_settings_path(data),
+            ],
+            ResetComponent::Settings => vec![
+                paths.config_dir.join("settings.json"),
+                paths.config_dir.join("settings.json.bak"),
+            ],
+            ResetComponent::Caches => vec![data.join("temp")],
+        }
+    }
+}
+
+/// A database file plus its WAL and shared-memory companions
+fn sqlite_files(db_path: &Path) -> Vec<PathBuf> {
+    let name = db_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+    vec![
+        db_path.to_path_buf(),
+        db_path.with_file_name(format!("{}-wal", name)),
+        db_path.with_file_name(format!("{}-shm", name)),
+    ]
+}
+
+/// What a reset removed, also the `app-data:reset` event payload
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ResetReport {
+    pub components: Vec<ResetComponent>,
+    /// Paths that existed and were deleted
+    pub removed: Vec<String>,
+}
+
+/// Check the typed confirmation before anything is deleted
+pub fn check_confirm_token(confirm_token: &str) -> Result<(), String> {
+    if confirm_token.trim() == RESET_CONFIRM_TOKEN {
+        Ok(())
+    } else {
+        Err(format!("Type {} to confirm the reset", RESET_CONFIRM_TOKEN))
+    }
+}
+
+fn remove_path(path: &Path) -> Result<bool, String> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        return Ok(false);
+    };
+    result
+        .map(|_| true)
+        .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))
+}
+
+/// Recreate the empty structure a component expects after deletion.
+/// Settings are rewritten by the caller so they also reach running services.
+fn recreate(component: ResetComponent, paths: &BackupPaths) -> Result<(), String> {
+    let data = &paths.data_dir;
+    match component {
+        ResetComponent::SearchIndexes => {
+            open_files_db(&data.join("files_index.db"))
+                .map_err(|e| format!("Failed to recreate file index: {}", e))?;
+            open_browser_db(&data.join("browser_cache.db"))
+                .map_err(|e| format!("Failed to recreate browser cache: {}", e))?;
+        }
+        ResetComponent::ClipboardHistory => fs::create_dir_all(data.join("clipboard"))
+            .map_err(|e| format!("Failed to recreate clipboard dir: {}", e))?,
+        ResetComponent::Plugins => fs::create_dir_all(data.join("plugins"))
+            .map_err(|e| format!("Failed to recreate plugins dir: {}", e))?,
+        ResetComponent::Settings => {}
+        ResetComponent::Caches => fs::create_dir_all(data.join("temp"))
+            .map_err(|e| format!("Failed to recreate temp dir: {}", e))?,
+    }
+    Ok(())
+}
+
+/// Delete the selected components and recreate their empty structures.
+/// Callers must pause the services that write to them first.
+pub fn reset_components(paths: &BackupPaths, components: &[ResetComponent]) -> Result<ResetReport, String> {
+    fs::create_dir_all(&paths.data_dir).map_err(|e| format!("Failed to create data dir: {}", e))?;
+
+    let mut report = ResetReport::default();
+    for component in ResetComponent::ALL {
+        if !components.contains(&component) {
+            continue;
+        }
+        for path in component.paths(paths) {
+            if remove_path(&path)? {
+                report.removed.push(path.to_string_lossy().to_string());
+            }
+        }
+        recreate(component, paths)?;
+        report.components.push(component);
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn populated() -> (tempfile::TempDir, BackupPaths) {
+        let root = tempfile::tempdir().unwrap();
+        let paths = BackupPaths {
+            config_dir: root.path().join("config"),
+            data_dir: root.path().join("data"),
+        };
+        fs::create_dir_all(&paths.config_dir).unwrap();
+        fs::write(paths.config_dir.join("settings.json"), "{}").unwrap();
+        fs::write(paths.config_dir.join("abbreviations.json"), "[]").unwrap();
+        for dir in ["clipboard", "plugins/demo", "temp/3f2a"] {
+            fs::create_dir_all(paths.data_dir.join(dir)).unwrap();
+        }
+        fs::write(paths.data_dir.join("clipboard/item.json"), "{}").unwrap();
+        fs::write(paths.data_dir.join("plugins/demo/plugin.json"), "{}").unwrap();
+        fs::write(paths.data_dir.join("temp/3f2a/plugin.json"), "{}").unwrap();
+        fs::write(plugin_state_path(&paths.data_dir), r#"{ "demo": true }"#).unwrap();
+        fs::write(plugin_settings_path(&paths.data_dir), r#"{ "demo": {} }"#).unwrap();
+        open_files_db(&paths.data_dir.join("files_index.db")).unwrap();
+        open_browser_db(&paths.data_dir.join("browser_cache.db")).unwrap();
+        fs::write(paths.data_dir.join("usage_analytics.db"), "").unwrap();
+        (root, paths)
+    }
+
+    #[test]
+    fn test_confirm_token_is_required() {
+        assert!(check_confirm_token("reset").is_err());
+        assert!(check_confirm_token("").is_err());
+        assert!(check_confirm_token(RESET_CONFIRM_TOKEN).is_ok());
+    }
+
+    #[test]
+    fn test_resets_only_selected_components() {
+        let (_root, paths) = populated();
+
+        let report =
+            reset_components(&paths, &[ResetComponent::ClipboardHistory, ResetComponent::Caches]).unwrap();
+
+        assert_eq!(report.components, vec![ResetComponent::ClipboardHistory, ResetComponent::Caches]);
+        assert_eq!(report.removed.len(), 2);
+        // Emptied but recreated
+        assert_eq!(fs::read_dir(paths.data_dir.join("clipboard")).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(paths.data_dir.join("temp")).unwrap().count(), 0);
+        // Untouched
+        assert!(paths.data_dir.join("plugins/demo/plugin.json").exists());
+        assert!(paths.config_dir.join("settings.json").exists());
+        assert!(paths.data_dir.join("usage_analytics.db").exists());
+    }
+
+    #[test]
+    fn test_search_index_reset_recreates_schema() {
+        let (_root, paths) = populated();
+        let db_path = paths.data_dir.join("files_index.db");
+        open_files_db(&db_path)
+            .unwrap()
+            .execute(
+                "INSERT INTO files (path, filename, extension, size, modified, hidden, indexed)
+                 VALUES ('/tmp/a.txt', 'a.txt', 'txt', 1, 0, 0, 0)",
+                [],
+            )
+            .unwrap();
+
+        reset_components(&paths, &[ResetComponent::SearchIndexes]).unwrap();
+
+        let count: i64 = open_files_db(&db_path)
+            .unwrap()
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 0);
+        assert!(paths.data_dir.join("clipboard/item.json").exists());
+    }
+
+    #[test]
+    fn test_settings_and_plugins_reset() {
+        let (_root, paths) = populated();
+
+        let report = reset_components(&paths, &[ResetComponent::Settings, ResetComponent::Plugins]).unwrap();
+
+        assert!(!paths.config_dir.join("settings.json").exists());
+        assert!(paths.config_dir.join("abbreviations.json").exists());
+        assert!(paths.data_dir.join("plugins").is_dir());
+        assert!(!paths.data_dir.join("plugins/demo").exists());
+        assert!(!plugin_state_path(&paths.data_dir).exists());
+        assert!(!plugin_settings_path(&paths.data_dir).exists());
+        assert_eq!(report.removed.len(), 4);
+    }
+}
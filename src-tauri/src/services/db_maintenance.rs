@@ -0,0 +1,224 @@
+//! Database Maintenance
+//! Integrity check, vacuum and rebuild for the file index and browser cache
+//! databases, plus the unclean-shutdown sentinel that triggers a check on
+//! the next start.
+
+use rusqlite::{Connection, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Sentinel file present while the app is running
+const SESSION_SENTINEL: &str = "session.lock";
+
+/// Database a maintenance run applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MaintenanceTarget {
+    Files,
+    Browser,
+}
+
+/// What a maintenance run does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenanceMode {
+    IntegrityCheck,
+    Vacuum,
+    Rebuild,
+}
+
+/// Result of a maintenance run
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceReport {
+    pub target: MaintenanceTarget,
+    pub mode: MaintenanceMode,
+    /// Integrity check problems; empty when the database is healthy
+    pub issues: Vec<String>,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub duration_ms: u64,
+}
+
+/// Coordinates maintenance with the indexer and browser reader.
+///
+/// Normal writers hold shared access while they write and skip their work
+/// when it's unavailable; maintenance takes exclusive access, waiting for
+/// in-flight writes to finish. Shutdown closes the guard, which keeps
+/// regular writes away until the process exits.
+#[derive(Clone, Default)]
+pub struct MaintenanceGuard {
+    lock: Arc<RwLock<()>>,
+    closed: Arc<AtomicBool>,
+}
+
+impl MaintenanceGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shared access for regular writes, None while maintenance is running
+    /// or once the guard is closed
+    pub fn try_shared(&self) -> Option<RwLockReadGuard<'_, ()>> {
+        if self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        self.lock.try_read().ok()
+    }
+
+    /// Exclusive access for a maintenance run
+    pub fn exclusive(&self) -> Result<RwLockWriteGuard<'_, ()>, String> {
+        self.lock.write().map_err(|e| format!("Lock error: {}", e))
+    }
+
+    /// Turn regular writes away for good, then wait out those in flight.
+    /// For shutdown, where nothing may start writing after this returns.
+    pub fn close(&self) -> Result<(), String> {
+        self.closed.store(true, Ordering::SeqCst);
+        self.exclusive().map(drop)
+    }
+}
+
+/// Run PRAGMA integrity_check and return the reported problems
+pub fn integrity_check(conn: &Connection) -> SqliteResult<Vec<String>> {
+    let rows = conn
+        .prepare("PRAGMA integrity_check")?
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<SqliteResult<Vec<_>>>()?;
+
+    Ok(rows.into_iter().filter(|r| r != "ok").collect())
+}
+
+/// Reclaim free pages
+pub fn vacuum(conn: &Connection) -> SqliteResult<()> {
+    conn.execute("VACUUM", [])?;
+    Ok(())
+}
+
+/// Delete the database (and its journal files) and re-create the schema with `open`.
+/// Deleting rather than dropping tables also recovers malformed databases.
+pub fn rebuild(
+    db_path: &Path,
+    open: impl Fn(&Path) -> SqliteResult<Connection>,
+) -> Result<Connection, String> {
+    for suffix in ["", "-journal", "-wal", "-shm"] {
+        let path = PathBuf::from(format!("{}{}", db_path.to_string_lossy(), suffix));
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+        }
+    }
+
+    open(db_path).map_err(|e| format!("Failed to re-create database: {}", e))
+}
+
+/// Sentinel file path for a data dir
+fn sentinel_path(data_dir: &Path) -> PathBuf {
+    data_dir.join(SESSION_SENTINEL)
+}
+
+/// Mark the session as started. Returns true if the previous session didn't
+/// remove its sentinel, i.e. didn't shut down cleanly.
+pub fn begin_session(data_dir: &Path) -> bool {
+    let path = sentinel_path(data_dir);
+    let unclean = path.exists();
+
+    if let Err(e) = fs::create_dir_all(data_dir).and_then(|_| fs::write(&path, std::process::id().to_string())) {
+        eprintln!("[DbMaintenance] Failed to write session sentinel: {}", e);
+    }
+
+    unclean
+}
+
+/// Mark the session as cleanly shut down
+pub fn end_session(data_dir: &Path) {
+    let _ = fs::remove_file(sentinel_path(data_dir));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::files::open_files_db;
+
+    #[test]
+    fn test_integrity_check_on_healthy_db() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_files_db(&dir.path().join("files_index.db")).unwrap();
+
+        assert!(integrity_check(&conn).unwrap().is_empty());
+        vacuum(&conn).unwrap();
+    }
+
+    #[test]
+    fn test_rebuild_recreates_empty_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("files_index.db");
+        let conn = open_files_db(&db_path).unwrap();
+        conn.execute(
+            "INSERT INTO files (path, filename, size, modified, indexed) VALUES ('/a.txt', 'a.txt', 1, 0, 0)",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        let conn = rebuild(&db_path, open_files_db).unwrap();
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0)).unwrap();
+        assert_eq!(count, 0);
+        assert!(integrity_check(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rebuild_recovers_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("browser_cache.db");
+        fs::write(&db_path, b"not a database").unwrap();
+
+        let conn = rebuild(&db_path, crate::db::browser::open_browser_db).unwrap();
+        assert!(integrity_check(&conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_guard_blocks_shared_access_during_maintenance() {
+        let guard = MaintenanceGuard::new();
+        assert!(guard.try_shared().is_some());
+
+        let exclusive = guard.exclusive().unwrap();
+        assert!(guard.try_shared().is_none());
+        drop(exclusive);
+        assert!(guard.try_shared().is_some());
+    }
+
+    #[test]
+    fn test_closed_guard_stays_closed() {
+        let guard = MaintenanceGuard::new();
+        let writer = guard.clone();
+        let written = Arc::new(AtomicBool::new(false));
+        let done = written.clone();
+        let in_flight = std::thread::spawn(move || {
+            let shared = writer.try_shared().unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            done.store(true, Ordering::SeqCst);
+            drop(shared);
+        });
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        guard.close().unwrap();
+        // close waited for the write in flight
+        assert!(written.load(Ordering::SeqCst));
+        in_flight.join().unwrap();
+        assert!(guard.try_shared().is_none());
+        assert!(guard.clone().try_shared().is_none());
+    }
+
+    #[test]
+    fn test_session_sentinel() {
+        let dir = tempfile::tempdir().unwrap();
+
+        assert!(!begin_session(dir.path()));
+        // No end_session: simulates a crash
+        assert!(begin_session(dir.path()));
+        end_session(dir.path());
+        assert!(!begin_session(dir.path()));
+    }
+}
@@ -0,0 +1,427 @@
+//! Drop Actions
+//! Actions offered for files dropped on the launcher window: the built-in
+//! ones (copy the paths, move them to a folder) and plugins whose manifest
+//! declares `accepts_drops` for every dropped file. A plugin action runs
+//! through the plugin's trigger with the paths as its input, so it needs
+//! the plugin to hold fs:read.
+
+use crate::models::plugin::{PluginManifest, PluginPermission};
+use crate::services::plugin_sandbox::PluginSandbox;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Extensions of launchable applications and shortcuts to them
+const APP_EXTENSIONS: &[&str] = &["app", "exe", "lnk", "desktop", "appimage"];
+
+/// Extensions treated as images
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "webp", "bmp", "svg", "ico", "tiff", "heic"];
+
+/// What a dropped path is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DroppedKind {
+    File,
+    Directory,
+    /// An application bundle, executable or shortcut
+    App,
+    Image,
+}
+
+/// A dropped path, classified
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedItem {
+    pub path: String,
+    pub name: String,
+    pub kind: DroppedKind,
+    /// Lowercase, without the dot
+    pub extension: Option<String>,
+}
+
+/// What running a drop action does
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DropActionKind {
+    /// Copy the paths to the clipboard, one per line
+    CopyPath,
+    /// Move the paths into a folder the user picks
+    MoveToFolder,
+    /// Pass the paths to a plugin trigger
+    Plugin { plugin_id: String, trigger: String },
+}
+
+/// An action offered for the dropped paths
+#[derive(Debug, Clone, Serialize)]
+pub struct DropAction {
+    /// `copy_path`, `move_to_folder` or `plugin:<id>`
+    pub id: String,
+    pub title: String,
+    #[serde(flatten)]
+    pub kind: DropActionKind,
+}
+
+/// Lowercase extension of `path`
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+}
+
+/// Classify a dropped path. Bundles like `Safari.app` are directories on
+/// disk but count as apps.
+pub fn classify(path: &Path) -> DroppedKind {
+    let ext = extension(path);
+    let is_app = ext.as_deref().is_some_and(|ext| APP_EXTENSIONS.contains(&ext));
+    if path.is_dir() {
+        return if is_app { DroppedKind::App } else { DroppedKind::Directory };
+    }
+    match ext.as_deref() {
+        _ if is_app => DroppedKind::App,
+        Some(ext) if IMAGE_EXTENSIONS.contains(&ext) => DroppedKind::Image,
+        _ => DroppedKind::File,
+    }
+}
+
+/// Classify `path` and describe it for display
+pub fn describe(path: &Path) -> DroppedItem {
+    DroppedItem {
+        path: path.to_string_lossy().to_string(),
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string()),
+        kind: classify(path),
+        extension: extension(path),
+    }
+}
+
+/// The actions every drop gets
+pub fn builtin_actions() -> Vec<DropAction> {
+    vec![
+        DropAction {
+            id: "copy_path".to_string(),
+            title: "Copy path".to_string(),
+            kind: DropActionKind::CopyPath,
+        },
+        DropAction {
+            id: "move_to_folder".to_string(),
+            title: "Move to folder…".to_string(),
+            kind: DropActionKind::MoveToFolder,
+        },
+    ]
+}
+
+/// The action a plugin offers for `items`, if it takes all of them. Folders
+/// are never offered, and a plugin without fs:read can't read what it'd get.
+pub fn plugin_action(
+    plugin_id: &str,
+    plugin_name: &str,
+    manifest: &PluginManifest,
+    items: &[DroppedItem],
+) -> Option<DropAction> {
+    let drops = manifest.accepts_drops.as_ref()?;
+    let trigger = manifest.drop_trigger()?;
+    if items.is_empty() || !manifest.has_permission(&PluginPermission::ReadFile) {
+        return None;
+    }
+
+    let accepts = |item: &DroppedItem| {
+        item.kind != DroppedKind::Directory
+            && (drops.extensions.is_empty()
+                || item.extension.as_deref().is_some_and(|ext| {
+                    drops.extensions.iter().any(|accepted| {
+                        accepted.trim_start_matches('.').eq_ignore_ascii_case(ext)
+                    })
+                }))
+    };
+    if !items.iter().all(accepts) {
+        return None;
+    }
+
+    Some(DropAction {
+        id: format!("plugin:{}", plugin_id),
+        title: drops.title.clone().unwrap_or_else(|| plugin_name.to_string()),
+        kind: DropActionKind::Plugin {
+            plugin_id: plugin_id.to_string(),
+            trigger: trigger.to_string(),
+        },
+    })
+}
+
+/// Fails unless the sandbox lets `plugin_id` read files. Checked again when
+/// an action runs, since consent may have been revoked after it was offered.
+pub fn check_read_permission(sandbox: &PluginSandbox, plugin_id: &str) -> Result<(), String> {
+    if sandbox.check_permission(plugin_id, PluginPermission::ReadFile)? {
+        Ok(())
+    } else {
+        Err(format!("Plugin {} may not read dropped files", plugin_id))
+    }
+}
+
+/// The paths of the latest drop. Actions only run on these, so a caller
+/// can't point "move to folder" or a plugin at files that were never
+/// dropped.
+#[derive(Default)]
+pub struct DroppedPaths(Mutex<HashSet<String>>);
+
+impl DroppedPaths {
+    /// Remember `items` as the latest drop, forgetting the one before
+    pub fn replace(&self, items: &[DroppedItem]) {
+        let mut paths = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        *paths = items.iter().map(|item| item.path.clone()).collect();
+    }
+
+    /// Fails unless every one of `paths` was in the latest drop
+    pub fn check(&self, paths: &[String]) -> Result<(), String> {
+        let dropped = self.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match paths.iter().find(|path| !dropped.contains(*path)) {
+            Some(path) => Err(format!("{} wasn't dropped", path)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Move `paths` into the folder `destination`, returning where they ended
+/// up. Nothing is moved if any of them would overwrite an existing entry,
+/// and when one fails the ones already moved are put back.
+pub fn move_to_folder(paths: &[String], destination: &Path) -> Result<Vec<String>, String> {
+    if !destination.is_dir() {
+        return Err(format!("Not a folder: {}", destination.display()));
+    }
+
+    let mut moves = Vec::with_capacity(paths.len());
+    for path in paths {
+        let source = Path::new(path);
+        let name = source.file_name().ok_or_else(|| format!("Can't move {}", path))?;
+        let target = destination.join(name);
+        if target.exists() {
+            return Err(format!("{} already exists", target.display()));
+        }
+        moves.push((source, target));
+    }
+
+    for (done, (source, target)) in moves.iter().enumerate() {
+        if let Err(e) = move_entry(source, target) {
+            let mut error = format!("Failed to move {}: {}", source.display(), e);
+            for (source, target) in moves[..done].iter().rev() {
+                if let Err(e) = move_entry(target, source) {
+                    error.push_str(&format!("; {} was left at {}: {}", source.display(), target.display(), e));
+                }
+            }
+            return Err(error);
+        }
+    }
+    Ok(moves.into_iter().map(|(_, target)| target.to_string_lossy().to_string()).collect())
+}
+
+/// Rename `source` to `target`, or copy it and remove the original when
+/// they're on different filesystems, where rename fails with EXDEV
+fn move_entry(source: &Path, target: &Path) -> io::Result<()> {
+    match fs::rename(source, target) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => move_by_copy(source, target),
+        result => result,
+    }
+}
+
+/// Copy `source` to `target`, then remove `source`. A copy that fails
+/// halfway is removed again so the original stays the only one.
+fn move_by_copy(source: &Path, target: &Path) -> io::Result<()> {
+    if let Err(e) = copy_entry(source, target) {
+        let _ = remove_entry(target);
+        return Err(e);
+    }
+    remove_entry(source)
+}
+
+/// Copy a file, folder or symlink, keeping symlinks as links
+fn copy_entry(source: &Path, target: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(source)?;
+    if metadata.is_dir() {
+        fs::create_dir(target)?;
+        for entry in fs::read_dir(source)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &target.join(entry.file_name()))?;
+        }
+        fs::set_permissions(target, metadata.permissions())
+    } else if metadata.file_type().is_symlink() {
+        copy_symlink(source, target)
+    } else {
+        fs::copy(source, target).map(drop)
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(source)?, target)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(source: &Path, target: &Path) -> io::Result<()> {
+    fs::copy(source, target).map(drop)
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Search query that runs `trigger` on `paths`, all of them at once. Paths
+/// with spaces or quotes are quoted.
+pub fn dispatch_query(trigger: &str, paths: &[String]) -> String {
+    let args: Vec<String> = paths
+        .iter()
+        .map(|path| {
+            if path.contains(char::is_whitespace) || path.contains('"') {
+                format!("\"{}\"", path.replace('"', "\\\""))
+            } else {
+                path.clone()
+            }
+        })
+        .collect();
+    format!("{} {}", trigger, args.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::plugin::{DropCapability, PluginTrigger};
+    use tempfile::TempDir;
+
+    fn manifest(extensions: &[&str], permissions: &[&str]) -> PluginManifest {
+        PluginManifest {
+            name: "Shrink".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Compresses images".to_string(),
+            author: Some("tester".to_string()),
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            entry: "index.js".to_string(),
+            triggers: vec![PluginTrigger {
+                keyword: "shrink:".to_string(),
+                description: String::new(),
+                hotkey: None,
+                args: vec![],
+                cache_ttl_ms: None,
+            }],
+            api_version: None,
+            protected: false,
+            accepts_drops: Some(DropCapability {
+                extensions: extensions.iter().map(|e| e.to_string()).collect(),
+                trigger: None,
+                title: Some("Compress".to_string()),
+            }),
+            links: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_classify_dropped_paths() {
+        let dir = TempDir::new().unwrap();
+        let bundle = dir.path().join("Safari.app");
+        let folder = dir.path().join("docs");
+        fs::create_dir(&bundle).unwrap();
+        fs::create_dir(&folder).unwrap();
+        for name in ["photo.PNG", "notes.txt", "setup.exe"] {
+            fs::write(dir.path().join(name), b"x").unwrap();
+        }
+
+        assert_eq!(classify(&bundle), DroppedKind::App);
+        assert_eq!(classify(&folder), DroppedKind::Directory);
+        assert_eq!(classify(&dir.path().join("photo.PNG")), DroppedKind::Image);
+        assert_eq!(classify(&dir.path().join("notes.txt")), DroppedKind::File);
+        assert_eq!(classify(&dir.path().join("setup.exe")), DroppedKind::App);
+
+        let item = describe(&dir.path().join("photo.PNG"));
+        assert_eq!(item.name, "photo.PNG");
+        assert_eq!(item.extension.as_deref(), Some("png"));
+    }
+
+    #[test]
+    fn test_plugin_actions_need_read_permission_and_matching_files() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.png"), b"x").unwrap();
+        fs::write(dir.path().join("b.txt"), b"x").unwrap();
+        let png = describe(&dir.path().join("a.png"));
+        let txt = describe(&dir.path().join("b.txt"));
+        let folder = describe(dir.path());
+
+        let granted = manifest(&["png"], &["fs:read"]);
+        let action = plugin_action("shrink", "Shrink", &granted, std::slice::from_ref(&png)).unwrap();
+        assert_eq!(action.title, "Compress");
+        assert_eq!(
+            action.kind,
+            DropActionKind::Plugin { plugin_id: "shrink".to_string(), trigger: "shrink:".to_string() }
+        );
+        assert!(plugin_action("shrink", "Shrink", &granted, &[png.clone(), txt.clone()]).is_none());
+        assert!(plugin_action("shrink", "Shrink", &manifest(&[], &["fs:read"]), &[folder]).is_none());
+        assert!(plugin_action("shrink", "Shrink", &manifest(&["png"], &[]), &[png]).is_none());
+
+        let sandbox = PluginSandbox::new();
+        sandbox.register_plugin("shrink".to_string(), vec![]).unwrap();
+        assert!(check_read_permission(&sandbox, "shrink").is_err());
+        sandbox.grant_permission("shrink", PluginPermission::ReadFile).unwrap();
+        assert!(check_read_permission(&sandbox, "shrink").is_ok());
+        assert!(check_read_permission(&sandbox, "missing").is_err());
+    }
+
+    #[test]
+    fn test_dispatch_query_batches_paths() {
+        let paths = vec!["/tmp/a.png".to_string(), "/tmp/my photo.png".to_string()];
+        assert_eq!(dispatch_query("shrink:", &paths), "shrink: /tmp/a.png \"/tmp/my photo.png\"");
+    }
+
+    #[test]
+    fn test_failed_move_puts_earlier_files_back() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("a.txt");
+        fs::write(&source, "a").unwrap();
+        let destination = dir.path().join("dest");
+        fs::create_dir(&destination).unwrap();
+
+        let paths = vec![
+            source.to_string_lossy().to_string(),
+            dir.path().join("gone.txt").to_string_lossy().to_string(),
+        ];
+        assert!(move_to_folder(&paths, &destination).is_err());
+        assert_eq!(fs::read_to_string(&source).unwrap(), "a");
+        assert!(!destination.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_move_by_copy_keeps_tree() {
+        let dir = TempDir::new().unwrap();
+        let source = dir.path().join("photos");
+        fs::create_dir_all(source.join("2024")).unwrap();
+        fs::write(source.join("2024/a.png"), "png").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("2024/a.png", source.join("latest.png")).unwrap();
+
+        let target = dir.path().join("moved");
+        move_by_copy(&source, &target).unwrap();
+        assert!(!source.exists());
+        assert_eq!(fs::read_to_string(target.join("2024/a.png")).unwrap(), "png");
+        #[cfg(unix)]
+        assert_eq!(fs::read_link(target.join("latest.png")).unwrap(), Path::new("2024/a.png"));
+
+        // A copy that fails leaves nothing behind
+        assert!(move_by_copy(&dir.path().join("missing"), &dir.path().join("copy")).is_err());
+        assert!(!dir.path().join("copy").exists());
+    }
+
+    #[test]
+    fn test_only_dropped_paths_pass() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.path().join("a.png");
+        fs::write(&file, "").unwrap();
+        let dropped = DroppedPaths::default();
+        dropped.replace(&[describe(&file)]);
+
+        assert!(dropped.check(&[file.to_string_lossy().to_string()]).is_ok());
+        assert!(dropped.check(&["/etc/passwd".to_string()]).is_err());
+    }
+}
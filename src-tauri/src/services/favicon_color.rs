@@ -0,0 +1,162 @@
+//! Favicon Color
+//! Finds the dominant color of a favicon so browser results can carry an
+//! accent color. Colors are plain "#rrggbb" values; the frontend adapts them
+//! to the current theme, so changing themes doesn't need new colors.
+//! Whites, greys and blacks are left out, since they're usually background
+//! or outline rather than the site's brand.
+
+use base64::Engine;
+use std::fs;
+use std::path::Path;
+
+/// Favicons bigger than this are not read
+const MAX_FAVICON_BYTES: u64 = 1024 * 1024;
+
+/// Images are shrunk to at most this many pixels a side before counting
+const SAMPLE_SIZE: u32 = 64;
+
+/// Pixels more transparent than this are ignored
+const MIN_ALPHA: u8 = 128;
+
+/// Pixels whose channels are all within this range of each other are neutral
+const MIN_CHROMA: u8 = 24;
+
+/// Low bits dropped from each channel when grouping similar colors
+const BUCKET_SHIFT: u8 = 4;
+
+/// Accent color for a cached favicon, given as a data URL or a file path.
+/// None for remote URLs, unreadable images and favicons with no color.
+pub fn favicon_accent_color(favicon: &str) -> Option<String> {
+    let bytes = if let Some(data_url) = favicon.strip_prefix("data:") {
+        let (_, data) = data_url.split_once(";base64,")?;
+        base64::engine::general_purpose::STANDARD.decode(data.trim()).ok()?
+    } else if favicon.contains("://") {
+        return None;
+    } else {
+        let path = Path::new(favicon);
+        if fs::metadata(path).ok()?.len() > MAX_FAVICON_BYTES {
+            return None;
+        }
+        fs::read(path).ok()?
+    };
+    dominant_color(&bytes).map(to_hex)
+}
+
+/// A favicon image as a data URL for the browser cache. Only PNG and ICO
+/// are kept, the formats `dominant_color` reads; None for anything else and
+/// for images over the size limit.
+pub fn favicon_data_url(image_bytes: &[u8]) -> Option<String> {
+    if image_bytes.len() as u64 > MAX_FAVICON_BYTES {
+        return None;
+    }
+    let mime = if image_bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        "image/png"
+    } else if image_bytes.starts_with(&[0, 0, 1, 0]) {
+        "image/x-icon"
+    } else {
+        return None;
+    };
+    Some(format!(
+        "data:{};base64,{}",
+        mime,
+        base64::engine::general_purpose::STANDARD.encode(image_bytes)
+    ))
+}
+
+/// The most common non-neutral color in an encoded image (PNG or ICO),
+/// averaged over similar shades
+pub fn dominant_color(image_bytes: &[u8]) -> Option<[u8; 3]> {
+    let mut image = image::load_from_memory(image_bytes).ok()?;
+    if image.width() > SAMPLE_SIZE || image.height() > SAMPLE_SIZE {
+        image = image.thumbnail(SAMPLE_SIZE, SAMPLE_SIZE);
+    }
+    let pixels = image.to_rgba8();
+
+    // Pixel count and channel sums per bucket
+    let mut buckets: Vec<(u32, [u32; 3])> = vec![(0, [0; 3]); 1 << (3 * (8 - BUCKET_SHIFT))];
+    for pixel in pixels.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < MIN_ALPHA || is_neutral([r, g, b]) {
+            continue;
+        }
+        let index = bucket_index([r, g, b]);
+        let (count, sums) = &mut buckets[index];
+        *count += 1;
+        sums[0] += r as u32;
+        sums[1] += g as u32;
+        sums[2] += b as u32;
+    }
+
+    // max_by_key keeps the last of equal counts; reversed, ties go to the
+    // lowest bucket
+    let (count, sums) = buckets
+        .iter()
+        .rev()
+        .filter(|(count, _)| *count > 0)
+        .max_by_key(|(count, _)| *count)?;
+    Some(sums.map(|sum| (sum / count) as u8))
+}
+
+fn is_neutral([r, g, b]: [u8; 3]) -> bool {
+    r.max(g).max(b) - r.min(g).min(b) < MIN_CHROMA
+}
+
+fn bucket_index([r, g, b]: [u8; 3]) -> usize {
+    let bits = 8 - BUCKET_SHIFT;
+    let (r, g, b) = (
+        (r >> BUCKET_SHIFT) as usize,
+        (g >> BUCKET_SHIFT) as usize,
+        (b >> BUCKET_SHIFT) as usize,
+    );
+    (r << (2 * bits)) | (g << bits) | b
+}
+
+pub fn to_hex([r, g, b]: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture;
+
+    #[test]
+    fn test_dominant_color_of_fixtures() {
+        assert_eq!(dominant_color(fixture!("favicons/solid_blue.png")), Some([0x1a, 0x73, 0xe8]));
+        // White covers more of the tile but is neutral
+        assert_eq!(dominant_color(fixture!("favicons/red_on_white.png")), Some([0xd9, 0x30, 0x25]));
+        // The larger area wins; fully transparent pixels don't count
+        assert_eq!(
+            dominant_color(fixture!("favicons/green_and_orange.png")),
+            Some([0x18, 0x80, 0x38])
+        );
+        assert_eq!(dominant_color(fixture!("favicons/monochrome.png")), None);
+        assert_eq!(dominant_color(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_accent_color_from_data_url_and_path() {
+        let data_url = format!(
+            "data:image/png;base64,{}",
+            base64::engine::general_purpose::STANDARD.encode(fixture!("favicons/solid_blue.png"))
+        );
+        assert_eq!(favicon_accent_color(&data_url).as_deref(), Some("#1a73e8"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("favicon.png");
+        fs::write(&path, fixture!("favicons/red_on_white.png")).unwrap();
+        assert_eq!(favicon_accent_color(path.to_str().unwrap()).as_deref(), Some("#d93025"));
+
+        // Browser favicon bitmaps are cached as data URLs
+        let cached = favicon_data_url(fixture!("favicons/solid_blue.png")).unwrap();
+        assert_eq!(favicon_accent_color(&cached).as_deref(), Some("#1a73e8"));
+        assert_eq!(favicon_data_url(b"<svg xmlns='http://www.w3.org/2000/svg'/>"), None);
+
+        // Remote and missing favicons aren't fetched
+        assert_eq!(favicon_accent_color("https://example.com/favicon.ico"), None);
+        assert_eq!(
+            favicon_accent_color(dir.path().join("missing.png").to_str().unwrap()),
+            None
+        );
+    }
+}
@@ -3,16 +3,23 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
-use crate::db::files::{FileEntry, init_files_db, upsert_file, search_files, get_index_stats};
-use notify::{Watcher, RecursiveMode, EventKind, Event};
+use crate::db::files::{FileEntry, init_files_db, upsert_file, search_files_in, get_index_stats};
+use crate::services::cloud_files::{PlaceholderProbe, SystemPlaceholders};
+use crate::services::data_access::{classify, AccessState, SKIPPED_PERMISSION};
+use crate::services::db_maintenance::MaintenanceGuard;
+use crate::services::file_tags::read_file_tags;
+use crate::services::privacy::PrivacyMode;
+use crate::services::search::cache::invalidate_search_cache;
+use crate::types::events::{self, IndexProgress};
+use rusqlite::Connection;
+use notify::{Watcher, RecursiveMode, EventKind, Event, RecommendedWatcher};
 use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex};
-use std::thread;
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::Duration;
-use std::sync::mpsc::channel;
-use tauri::Emitter;
+use std::sync::mpsc::{channel, Receiver};
+use tauri::Runtime;
 
 /// File indexer configuration
 #[derive(Debug, Clone)]
@@ -21,6 +28,10 @@ pub struct IndexerConfig {
     pub excluded_dirs: Vec<String>,
     pub max_files: usize,
     pub debounce_ms: u64,
+    /// Read user tags (Finder tags) for each indexed file
+    pub read_tags: bool,
+    /// Flags cloud files whose content isn't downloaded
+    pub placeholders: Arc<dyn PlaceholderProbe>,
 }
 
 impl Default for IndexerConfig {
@@ -37,36 +48,191 @@ impl Default for IndexerConfig {
             ],
             max_files: 100_000,
             debounce_ms: 5000,
+            read_tags: false,
+            placeholders: Arc::new(SystemPlaceholders),
         }
     }
 }
 
-/// File indexer service
+/// Why the indexer leaves a path out
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize)]
+#[serde(tag = "rule", rename_all = "snake_case")]
+pub enum ExclusionRule {
+    /// Not inside any indexed folder
+    OutsideIndexRoots,
+    /// `path` (the path itself or a folder above it) is named `name`,
+    /// which is in `excluded_dirs`
+    ExcludedDir { name: String, path: String },
+}
+
+impl IndexerConfig {
+    /// The `excluded_dirs` entry matching a file or folder name
+    pub fn excluded_name(&self, name: &str) -> Option<&str> {
+        self.excluded_dirs.iter().map(String::as_str).find(|excluded| *excluded == name)
+    }
+
+    /// The rule that keeps `path` out of the index, if any. Decided from the
+    /// path and config alone, the way a scan from the roots would: names
+    /// below the root are checked, the root's own name isn't. A path under a
+    /// root with its symlinks resolved counts as under the root.
+    pub fn exclusion_rule(&self, path: &Path) -> Option<ExclusionRule> {
+        let Some((mut current, relative)) = self.paths.iter().find_map(|root| under_root(root, path)) else {
+            return Some(ExclusionRule::OutsideIndexRoots);
+        };
+
+        for component in relative.components() {
+            current.push(component);
+            if let Some(name) = component.as_os_str().to_str().and_then(|name| self.excluded_name(name)) {
+                return Some(ExclusionRule::ExcludedDir {
+                    name: name.to_string(),
+                    path: current.to_string_lossy().to_string(),
+                });
+            }
+        }
+        None
+    }
+}
+
+/// `path` relative to `root`, and the form of `root` it was found under:
+/// as configured, or with symlinks resolved the way watchers report paths
+/// (/private/tmp/... for a /tmp root on macOS)
+fn under_root(root: &Path, path: &Path) -> Option<(PathBuf, PathBuf)> {
+    if let Ok(relative) = path.strip_prefix(root) {
+        return Some((root.to_path_buf(), relative.to_path_buf()));
+    }
+    let real = root.canonicalize().ok()?;
+    let relative = path.strip_prefix(&real).ok()?.to_path_buf();
+    Some((real, relative))
+}
+
+/// Check that `path` can be added as an indexed root. Paths equal to or
+/// inside an existing root are rejected, naming that root. Returns the
+/// existing roots inside `path`, which the new root makes redundant.
+pub fn check_new_index_path(roots: &[String], path: &Path) -> Result<Vec<String>, String> {
+    if let Some(parent) = roots.iter().find(|root| path.starts_with(root)) {
+        return Err(if Path::new(parent) == path {
+            format!("{} is already indexed", path.display())
+        } else {
+            format!("{} is already indexed as part of {}", path.display(), parent)
+        });
+    }
+
+    Ok(roots
+        .iter()
+        .filter(|root| Path::new(root).starts_with(path))
+        .cloned()
+        .collect())
+}
+
+/// Scans run at most this often, however short `debounce_ms` is
+const MIN_SCAN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// File indexer service. Clones share state, so the background scan task
+/// works on the indexer the search state holds.
+#[derive(Clone)]
 pub struct FileIndexer {
-    config: IndexerConfig,
+    /// Shared with the scan task so path changes apply to the next round
+    config: Arc<RwLock<IndexerConfig>>,
+    /// Kept alive while running; dropping it stops file system events
+    watcher: Arc<Mutex<Option<RecommendedWatcher>>>,
+    /// The watcher's events, waiting for `watch_events`
+    events: Arc<Mutex<Option<Receiver<Event>>>>,
     indexed_files: Arc<Mutex<HashSet<PathBuf>>>,
     is_running: Arc<Mutex<bool>>,
     app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
-}
-
-/// Index progress event (T141)
-#[derive(Debug, Clone, serde::Serialize)]
-pub struct IndexProgressEvent {
-    pub current: usize,
-    pub total: usize,
-    pub path: String,
-    pub stage: String,
+    privacy: PrivacyMode,
+    maintenance: MaintenanceGuard,
 }
 
 impl FileIndexer {
     /// Create a new file indexer
     pub fn new(config: IndexerConfig) -> Self {
         Self {
-            config,
+            config: Arc::new(RwLock::new(config)),
+            watcher: Arc::new(Mutex::new(None)),
+            events: Arc::new(Mutex::new(None)),
             indexed_files: Arc::new(Mutex::new(HashSet::new())),
             is_running: Arc::new(Mutex::new(false)),
             app_handle: Arc::new(Mutex::new(None)),
+            privacy: PrivacyMode::new(),
+            maintenance: MaintenanceGuard::new(),
+        }
+    }
+
+    /// Share the app's privacy mode so scans and watching pause while it's on
+    pub fn with_privacy(mut self, privacy: PrivacyMode) -> Self {
+        self.privacy = privacy;
+        self
+    }
+
+    /// Share the app's maintenance guard so no writes happen during database maintenance
+    pub fn with_maintenance(mut self, maintenance: MaintenanceGuard) -> Self {
+        self.maintenance = maintenance;
+        self
+    }
+
+    /// Forget which files were indexed so the next scan re-adds everything
+    /// (used after the index database is rebuilt)
+    pub fn reset_indexed(&self) -> Result<(), String> {
+        self.indexed_files
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .clear();
+        Ok(())
+    }
+
+    /// Snapshot of the current configuration
+    pub fn config(&self) -> IndexerConfig {
+        self.config.read().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Add an indexed root. The next scan round covers it and, if running,
+    /// it's watched from now on.
+    pub fn add_path(&self, path: &Path) -> Result<(), String> {
+        {
+            let mut config = self.config.write().map_err(|e| format!("Lock error: {}", e))?;
+            if config.paths.iter().any(|p| p == path) {
+                return Ok(());
+            }
+            config.paths.push(path.to_path_buf());
         }
+
+        if let Some(watcher) = self.watcher.lock().map_err(|e| format!("Lock error: {}", e))?.as_mut() {
+            watcher
+                .watch(path, RecursiveMode::Recursive)
+                .map_err(|e| format!("Failed to watch path: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Stop scanning and watching an indexed root. Its rows stay in the
+    /// database; callers delete them.
+    pub fn remove_path(&self, path: &Path) -> Result<(), String> {
+        self.config
+            .write()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .paths
+            .retain(|p| p != path);
+
+        if let Some(watcher) = self.watcher.lock().map_err(|e| format!("Lock error: {}", e))?.as_mut() {
+            // Fails if the directory was already deleted, which stops the watch anyway
+            let _ = watcher.unwatch(path);
+        }
+        self.forget_path(path)
+    }
+
+    /// Forget files under `path` so the next scan re-adds them
+    pub fn forget_path(&self, path: &Path) -> Result<(), String> {
+        self.indexed_files
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .retain(|file| !file.starts_with(path));
+        Ok(())
+    }
+
+    /// Whether indexing is currently paused by privacy mode
+    pub fn is_paused(&self) -> bool {
+        self.privacy.is_active()
     }
 
     /// Start indexing with file system watching (T133, T136)
@@ -88,40 +254,37 @@ impl FileIndexer {
         let _conn = init_files_db(app_handle)
             .map_err(|e| format!("Failed to init DB: {}", e))?;
 
-        // Spawn indexing thread
-        let is_running = Arc::clone(&self.is_running);
-        let indexed_files = Arc::clone(&self.indexed_files);
-        let config = self.config.clone();
-        let app_handle = app_handle.clone();
-        let app_handle_arc = Arc::clone(&self.app_handle);
-
-        thread::spawn(move || {
-            let mut last_scan = std::time::Instant::now();
-
-            while *is_running.lock().unwrap() {
-                // Check if it's time to scan again
-                if last_scan.elapsed() >= std::time::Duration::from_millis(config.debounce_ms) {
-                    if let Err(e) = Self::scan_directory_recursive(
-                        &config,
-                        &indexed_files,
-                        &app_handle,
-                    ) {
-                        eprintln!("Indexing error: {}", e);
-                    }
-                    last_scan = std::time::Instant::now();
-                }
-
-                thread::sleep(Duration::from_secs(10));
-            }
-        });
-
         // Setup file system watcher (T136)
         self.setup_file_watcher()?;
 
         Ok(())
     }
 
-    /// Setup file system watcher for real-time updates (T136)
+    /// How often the background task calls `scan_once`
+    pub fn scan_interval(&self) -> Duration {
+        let debounce = self.config.read().map(|config| config.debounce_ms).unwrap_or_default();
+        Duration::from_millis(debounce).max(MIN_SCAN_INTERVAL)
+    }
+
+    /// One rescan of the indexed paths. Skipped while stopped, while privacy
+    /// mode is on and while database maintenance is running; cut short once
+    /// `should_stop` returns true.
+    pub fn scan_once(&self, should_stop: &dyn Fn() -> bool) -> Result<(), String> {
+        if !*self.is_running.lock().map_err(|e| format!("Lock error: {}", e))? || self.privacy.is_active() {
+            return Ok(());
+        }
+        let Some(app_handle) = self.app_handle.lock().map_err(|e| format!("Lock error: {}", e))?.clone() else {
+            return Ok(());
+        };
+        let Some(_shared) = self.maintenance.try_shared() else {
+            return Ok(());
+        };
+        let config = self.config();
+        Self::scan_directory_recursive(&config, &self.indexed_files, &app_handle, should_stop)
+    }
+
+    /// Setup file system watcher for real-time updates (T136). Events queue
+    /// up until `watch_events` handles them.
     fn setup_file_watcher(&self) -> Result<(), String> {
         let (tx, rx) = channel();
 
@@ -132,105 +295,147 @@ impl FileIndexer {
             }
         }).map_err(|e| format!("Failed to create watcher: {}", e))?;
 
+        let config = self.config();
+
         // Watch all configured paths
-        for path in &self.config.paths {
+        for path in &config.paths {
             if path.exists() {
                 watcher.watch(path, RecursiveMode::Recursive)
                     .map_err(|e| format!("Failed to watch path: {}", e))?;
             }
         }
 
-        // Spawn watcher thread
-        let indexed_files = Arc::clone(&self.indexed_files);
-        let is_running = Arc::clone(&self.is_running);
-        let app_handle_arc = Arc::clone(&self.app_handle);
-        let excluded_dirs = self.config.excluded_dirs.clone();
-
-        thread::spawn(move || {
-            while *is_running.lock().unwrap() {
-                if let Ok(event) = rx.recv_timeout(Duration::from_secs(1)) {
-                    for path in event.paths {
-                        // Skip excluded directories
-                        if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                            if excluded_dirs.iter().any(|ex| ex == name) {
-                                continue;
-                            }
-                        }
+        *self.events.lock().map_err(|e| format!("Lock error: {}", e))? = Some(rx);
+        *self.watcher.lock().map_err(|e| format!("Lock error: {}", e))? = Some(watcher);
 
-                        // Handle different event kinds
-                        match event.kind {
-                            EventKind::Create(_) | EventKind::Modify(_) => {
-                                // Add or update file
-                                if path.is_file() {
-                                    if let Some(handle) = app_handle_arc.lock().unwrap().as_ref() {
-                                        if let Ok(conn) = init_files_db(handle) {
-                                            let metadata = fs::metadata(&path);
-                                            if let Ok(meta) = metadata {
-                                                let modified = meta.modified()
-                                                    .ok()
-                                                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                                                    .map(|d| d.as_secs() as i64)
-                                                    .unwrap_or(0);
-
-                                                let filename = path.file_name()
-                                                    .and_then(|n| n.to_str())
-                                                    .unwrap_or("unknown")
-                                                    .to_string();
-
-                                                let is_hidden = filename.starts_with('.');
-
-                                                let extension = path.extension()
-                                                    .and_then(|e| e.to_str())
-                                                    .map(|s| s.to_string());
-
-                                                let entry = FileEntry {
-                                                    id: None,
-                                                    path: path.to_string_lossy().to_string(),
-                                                    filename,
-                                                    extension,
-                                                    size: meta.len() as i64,
-                                                    modified,
-                                                    hidden: is_hidden,
-                                                    indexed: chrono::Utc::now().timestamp(),
-                                                };
-
-                                                let _ = upsert_file(&conn, &entry);
-
-                                                let mut files = indexed_files.lock().unwrap();
-                                                files.insert(path);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                            EventKind::Remove(_) => {
-                                // Remove from index
-                                if let Some(handle) = app_handle_arc.lock().unwrap().as_ref() {
-                                    if let Ok(conn) = init_files_db(handle) {
-                                        let path_str = path.to_string_lossy().to_string();
-                                        let _ = conn.execute(
-                                            "DELETE FROM files WHERE path = ?1",
-                                            [&path_str]
-                                        );
-                                    }
+        Ok(())
+    }
+
+    /// Apply file system events to the index until `should_stop` or the
+    /// indexer stops. Run by the background watch task; a later run picks up
+    /// the events queued in between.
+    pub fn watch_events(&self, should_stop: &dyn Fn() -> bool) -> Result<(), String> {
+        let Some(rx) = self.events.lock().map_err(|e| format!("Lock error: {}", e))?.take() else {
+            return Ok(());
+        };
+
+        while !should_stop() && *self.is_running.lock().map_err(|e| format!("Lock error: {}", e))? {
+            if let Ok(event) = rx.recv_timeout(Duration::from_secs(1)) {
+                self.apply_event(event);
+            }
+        }
+
+        *self.events.lock().map_err(|e| format!("Lock error: {}", e))? = Some(rx);
+        Ok(())
+    }
+
+    /// Add, update or remove the index rows for one file system event
+    fn apply_event(&self, event: Event) {
+        // Drop file events while privacy mode is on
+        if self.privacy.is_active() {
+            return;
+        }
+
+        // Drop them during database maintenance too; a rebuild rescans anyway
+        let Some(_shared) = self.maintenance.try_shared() else {
+            return;
+        };
+
+        let Some(handle) = self.app_handle.lock().ok().and_then(|handle| handle.clone()) else {
+            return;
+        };
+        // A poisoned config can't say what's excluded, so nothing is applied
+        let config = match self.config.read() {
+            Ok(config) => config.clone(),
+            Err(e) => {
+                eprintln!("[FileIndexer] Dropping file event, config lock poisoned: {}", e);
+                return;
+            }
+        };
+        let read_tags = config.read_tags;
+        let placeholders = Arc::clone(&config.placeholders);
+
+        for path in event.paths {
+            // Skip paths in excluded directories, as a scan would
+            if let Some(rule) = config.exclusion_rule(&path) {
+                if rule == ExclusionRule::OutsideIndexRoots {
+                    eprintln!("[FileIndexer] Ignoring event outside the indexed folders: {:?}", path);
+                }
+                continue;
+            }
+
+            // Handle different event kinds
+            match event.kind {
+                EventKind::Create(_) | EventKind::Modify(_) => {
+                    // Add or update file
+                    if path.is_file() {
+                        if let Ok(conn) = init_files_db(&handle) {
+                            let metadata = fs::metadata(&path);
+                            if let Ok(meta) = metadata {
+                                let modified = meta.modified()
+                                    .ok()
+                                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                                    .map(|d| d.as_secs() as i64)
+                                    .unwrap_or(0);
+
+                                let filename = path.file_name()
+                                    .and_then(|n| n.to_str())
+                                    .unwrap_or("unknown")
+                                    .to_string();
+
+                                let is_hidden = filename.starts_with('.');
+
+                                let extension = path.extension()
+                                    .and_then(|e| e.to_str())
+                                    .map(|s| s.to_string());
+
+                                let entry = FileEntry {
+                                    id: None,
+                                    path: path.to_string_lossy().to_string(),
+                                    filename,
+                                    extension,
+                                    size: meta.len() as i64,
+                                    modified,
+                                    hidden: is_hidden,
+                                    indexed: chrono::Utc::now().timestamp(),
+                                    tags: if read_tags { read_file_tags(&path) } else { Vec::new() },
+                                    is_placeholder: placeholders.is_placeholder(&path, &meta),
+                                };
+
+                                let _ = upsert_file(&conn, &entry);
+
+                                if let Ok(mut files) = self.indexed_files.lock() {
+                                    files.insert(path);
                                 }
-                                let mut files = indexed_files.lock().unwrap();
-                                files.remove(&path);
                             }
-                            _ => {}
                         }
                     }
                 }
+                EventKind::Remove(_) => {
+                    // Remove from index
+                    if let Ok(conn) = init_files_db(&handle) {
+                        let path_str = path.to_string_lossy().to_string();
+                        let _ = conn.execute(
+                            "DELETE FROM files WHERE path = ?1",
+                            [&path_str]
+                        );
+                    }
+                    if let Ok(mut files) = self.indexed_files.lock() {
+                        files.remove(&path);
+                    }
+                }
+                _ => {}
             }
-        });
+        }
 
-        Ok(())
+        // Flush: cached search results may now be stale
+        invalidate_search_cache(&handle);
     }
 
     /// Emit progress event (T141)
-    fn emit_progress(&self, event: IndexProgressEvent) {
+    fn emit_progress(&self, event: IndexProgress) {
         if let Some(handle) = self.app_handle.lock().unwrap().as_ref() {
-            let _ = handle.emit("index:progress", event);
+            let _ = events::emit(handle, event);
         }
     }
 
@@ -238,6 +443,10 @@ impl FileIndexer {
     pub fn stop(&self) -> Result<(), String> {
         let mut running = self.is_running.lock().map_err(|e| format!("Lock error: {}", e))?;
         *running = false;
+        drop(running);
+
+        self.watcher.lock().map_err(|e| format!("Lock error: {}", e))?.take();
+        self.events.lock().map_err(|e| format!("Lock error: {}", e))?.take();
         Ok(())
     }
 
@@ -246,101 +455,88 @@ impl FileIndexer {
         config: &IndexerConfig,
         indexed_files: &Arc<Mutex<HashSet<PathBuf>>>,
         app_handle: &tauri::AppHandle,
+        should_stop: &dyn Fn() -> bool,
     ) -> Result<(), String> {
+        let conn = init_files_db(app_handle)
+            .map_err(|e| format!("Failed to init DB: {}", e))?;
+        let on_progress = |progress: IndexProgress| {
+            let _ = events::emit(app_handle, progress);
+        };
+
         for base_path in &config.paths {
             if !base_path.exists() {
                 continue;
             }
 
-            Self::scan_dir(base_path, config, indexed_files, app_handle, 0, 0)?;
+            Self::scan_dir(base_path, config, indexed_files, &conn, &on_progress, should_stop, 0, 0)?;
         }
 
+        invalidate_search_cache(app_handle);
+
         Ok(())
     }
 
-    /// Scan a single directory with progress tracking (T141)
-    fn scan_dir(
+    /// Scan a single directory with progress tracking (T141). Files already
+    /// in `indexed_files` are skipped; new ones are written to `conn`.
+    /// `should_stop` is checked before each directory. Returns `current`
+    /// plus the number of files added.
+    #[allow(clippy::too_many_arguments)]
+    pub fn scan_dir(
         dir: &Path,
         config: &IndexerConfig,
-        indexed_files: &Arc<Mutex<HashSet<PathBuf>>>,
-        app_handle: &tauri::AppHandle,
+        indexed_files: &Mutex<HashSet<PathBuf>>,
+        conn: &Connection,
+        on_progress: &dyn Fn(IndexProgress),
+        should_stop: &dyn Fn() -> bool,
         current: usize,
         total: usize,
     ) -> Result<usize, String> {
-        let entries = fs::read_dir(dir)
-            .map_err(|e| format!("Failed to read directory: {}", e))?;
+        if should_stop() {
+            return Ok(current);
+        }
+
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            // Folders macOS protects are reported rather than failing the scan
+            Err(e) if classify(&e) == AccessState::Denied => {
+                on_progress(IndexProgress::skipped(dir, SKIPPED_PERMISSION));
+                return Ok(current);
+            }
+            Err(e) => return Err(format!("Failed to read directory: {}", e)),
+        };
 
         let mut count = current;
         let entries_vec: Vec<_> = entries.flatten().collect();
 
         // Emit progress event
         if total > 0 {
-            let progress = IndexProgressEvent {
+            on_progress(IndexProgress {
                 current: count,
                 total,
                 path: dir.to_string_lossy().to_string(),
                 stage: "scanning".to_string(),
-            };
-            let _ = app_handle.emit("index:progress", progress);
+                reason: None,
+            });
         }
 
         for entry in entries_vec {
             let path = entry.path();
 
             // Skip excluded directories
-            if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                if config.excluded_dirs.iter().any(|ex| ex == name) {
-                    continue;
-                }
+            if entry.file_name().to_str().and_then(|name| config.excluded_name(name)).is_some() {
+                continue;
             }
 
             if path.is_dir() {
                 // Recursively scan subdirectories
-                count = Self::scan_dir(&path, config, indexed_files, app_handle, count, total)?;
+                count = Self::scan_dir(&path, config, indexed_files, conn, on_progress, should_stop, count, total)?;
             } else if path.is_file() {
                 // Check if already indexed
                 let mut files = indexed_files.lock().map_err(|e| format!("Lock error: {}", e))?;
 
                 if !files.contains(&path) {
-                    // Get file metadata
-                    let metadata = fs::metadata(&path)
-                        .map_err(|e| format!("Failed to get metadata: {}", e))?;
-
-                    let modified = metadata.modified()
-                        .map_err(|e| format!("Failed to get modified time: {}", e))?
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .map_err(|e| format!("Time conversion error: {}", e))?
-                        .as_secs() as i64;
-
-                    // Get filename and extension
-                    let filename = path.file_name()
-                        .and_then(|n| n.to_str())
-                        .unwrap_or("unknown")
-                        .to_string();
-
-                    let extension = path.extension()
-                        .and_then(|e| e.to_str())
-                        .map(|s| s.to_string());
-
-                    // Check if file is hidden
-                    let hidden = filename.starts_with('.');
-
-                    // Create file entry
-                    let entry = FileEntry {
-                        id: None,
-                        path: path.to_string_lossy().to_string(),
-                        filename,
-                        extension,
-                        size: metadata.len() as i64,
-                        modified,
-                        hidden,
-                        indexed: chrono::Utc::now().timestamp(),
-                    };
-
-                    // Store in database
-                    if let Ok(conn) = init_files_db(app_handle) {
-                        let _ = upsert_file(&conn, &entry);
-                    }
+                    let entry = file_entry(&path, config)?;
+                    let _ = upsert_file(conn, &entry);
 
                     // Mark as indexed
                     files.insert(path);
@@ -352,16 +548,17 @@ impl FileIndexer {
         Ok(count)
     }
 
-    /// Search indexed files
+    /// Search indexed files, only those below `folder` when given
     pub fn search(
         &self,
         app_handle: &tauri::AppHandle,
         query: &str,
         limit: usize,
+        folder: Option<&str>,
     ) -> Result<Vec<FileEntry>, String> {
         let conn = init_files_db(app_handle)
             .map_err(|e| format!("DB error: {}", e))?;
-        search_files(&conn, query, limit)
+        search_files_in(&conn, query, limit, folder)
             .map_err(|e| format!("Search error: {}", e))
     }
 
@@ -375,11 +572,27 @@ impl FileIndexer {
 
     /// Update configuration
     pub fn update_config(&mut self, config: IndexerConfig) {
-        self.config = config;
+        if let Ok(mut current) = self.config.write() {
+            *current = config;
+        }
     }
 
     /// Index specific paths (T138)
-    pub fn index_paths(&self, app_handle: &tauri::AppHandle, paths: &[String]) -> Result<usize, String> {
+    pub fn index_paths<R: Runtime>(&self, app_handle: &tauri::AppHandle<R>, paths: &[String]) -> Result<usize, String> {
+        if self.is_paused() {
+            return Err("File indexing is paused while privacy mode is on".to_string());
+        }
+        let _shared = self
+            .maintenance
+            .try_shared()
+            .ok_or("File indexing is paused during database maintenance")?;
+
+        let config = self.config();
+        let conn = init_files_db(app_handle)
+            .map_err(|e| format!("Failed to init DB: {}", e))?;
+        let on_progress = |progress: IndexProgress| {
+            let _ = events::emit(app_handle, progress);
+        };
         let mut count = 0;
         let mut indexed_files = self.indexed_files.lock().map_err(|e| format!("Lock error: {}", e))?;
 
@@ -392,56 +605,220 @@ impl FileIndexer {
             // Create a temporary config for this path
             let temp_config = IndexerConfig {
                 paths: vec![path.clone()],
-                excluded_dirs: self.config.excluded_dirs.clone(),
-                max_files: self.config.max_files,
-                debounce_ms: self.config.debounce_ms,
+                excluded_dirs: config.excluded_dirs.clone(),
+                max_files: config.max_files,
+                debounce_ms: config.debounce_ms,
+                read_tags: config.read_tags,
+                placeholders: Arc::clone(&config.placeholders),
             };
 
             // Scan the path
             if path.is_dir() {
-                Self::scan_dir(&path, &temp_config, &Arc::new(Mutex::new(HashSet::new())), app_handle, 0, 0)?;
+                count += Self::scan_dir(&path, &temp_config, &Mutex::new(HashSet::new()), &conn, &on_progress, &|| false, 0, 0)?;
             } else if path.is_file() {
                 // Index single file
-                let metadata = fs::metadata(&path)
-                    .map_err(|e| format!("Failed to get metadata: {}", e))?;
-
-                let modified = metadata.modified()
-                    .map_err(|e| format!("Failed to get modified time: {}", e))?
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .map_err(|e| format!("Time conversion error: {}", e))?
-                    .as_secs() as i64;
-
-                let filename = path.file_name()
-                    .and_then(|n| n.to_str())
-                    .unwrap_or("unknown")
-                    .to_string();
-
-                let extension = path.extension()
-                    .and_then(|e| e.to_str())
-                    .map(|s| s.to_string());
-
-                let hidden = filename.starts_with('.');
-
-                let entry = FileEntry {
-                    id: None,
-                    path: path.to_string_lossy().to_string(),
-                    filename,
-                    extension,
-                    size: metadata.len() as i64,
-                    modified,
-                    hidden,
-                    indexed: chrono::Utc::now().timestamp(),
-                };
-
-                if let Ok(conn) = init_files_db(app_handle) {
-                    let _ = upsert_file(&conn, &entry);
-                }
+                let entry = file_entry(&path, &config)?;
+                let _ = upsert_file(&conn, &entry);
 
                 indexed_files.insert(path);
                 count += 1;
             }
         }
 
+        invalidate_search_cache(app_handle);
+
         Ok(count)
     }
 }
+
+/// Build the index row for a file from its metadata. Nothing here reads the
+/// content, so cloud placeholders aren't downloaded.
+fn file_entry(path: &Path, config: &IndexerConfig) -> Result<FileEntry, String> {
+    let metadata = fs::metadata(path)
+        .map_err(|e| format!("Failed to get metadata: {}", e))?;
+
+    let modified = metadata.modified()
+        .map_err(|e| format!("Failed to get modified time: {}", e))?
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_err(|e| format!("Time conversion error: {}", e))?
+        .as_secs() as i64;
+
+    // Get filename and extension
+    let filename = path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("unknown")
+        .to_string();
+
+    let extension = path.extension()
+        .and_then(|e| e.to_str())
+        .map(|s| s.to_string());
+
+    // Check if file is hidden
+    let hidden = filename.starts_with('.');
+
+    Ok(FileEntry {
+        id: None,
+        path: path.to_string_lossy().to_string(),
+        filename,
+        extension,
+        size: metadata.len() as i64,
+        modified,
+        hidden,
+        indexed: chrono::Utc::now().timestamp(),
+        tags: if config.read_tags { read_file_tags(path) } else { Vec::new() },
+        is_placeholder: config.placeholders.is_placeholder(path, &metadata),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use crate::db::files::search_files;
+
+    #[test]
+    fn test_privacy_mode_pauses_indexer() {
+        let privacy = PrivacyMode::new();
+        let indexer = FileIndexer::new(IndexerConfig::default()).with_privacy(privacy.clone());
+        assert!(!indexer.is_paused());
+
+        privacy.set(true, Some(Duration::from_millis(30)), false);
+        assert!(indexer.is_paused());
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(!indexer.is_paused());
+    }
+
+    #[test]
+    fn test_nested_index_paths_rejected() {
+        let roots = vec!["/home/me/docs".to_string(), "/home/me/code/".to_string()];
+
+        let err = check_new_index_path(&roots, Path::new("/home/me/docs/notes")).unwrap_err();
+        assert!(err.contains("already indexed as part of /home/me/docs"), "{}", err);
+        assert!(check_new_index_path(&roots, Path::new("/home/me/code/etools/src")).is_err());
+        assert_eq!(
+            check_new_index_path(&roots, Path::new("/home/me/docs")).unwrap_err(),
+            "/home/me/docs is already indexed"
+        );
+
+        // Only whole components nest
+        assert_eq!(check_new_index_path(&roots, Path::new("/home/me/docs2")).unwrap(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parent_index_path_absorbs_children() {
+        let roots = vec!["/home/me/docs".to_string(), "/srv/data".to_string()];
+        assert_eq!(
+            check_new_index_path(&roots, Path::new("/home/me")).unwrap(),
+            vec!["/home/me/docs".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_remove_path_updates_live_config() {
+        let indexer = FileIndexer::new(IndexerConfig::default());
+        indexer.add_path(Path::new("/home/me/docs")).unwrap();
+        indexer.add_path(Path::new("/home/me/docs")).unwrap();
+        indexer.add_path(Path::new("/srv/data")).unwrap();
+        assert_eq!(indexer.config().paths.len(), 2);
+
+        indexer.indexed_files.lock().unwrap().extend([
+            PathBuf::from("/home/me/docs/a.md"),
+            PathBuf::from("/srv/data/b.csv"),
+        ]);
+        indexer.remove_path(Path::new("/home/me/docs")).unwrap();
+
+        assert_eq!(indexer.config().paths, vec![PathBuf::from("/srv/data")]);
+        let remaining: Vec<PathBuf> = indexer.indexed_files.lock().unwrap().iter().cloned().collect();
+        assert_eq!(remaining, vec![PathBuf::from("/srv/data/b.csv")]);
+    }
+
+    #[test]
+    fn test_scan_dir_indexes_new_files_once() {
+        let root = tempfile::tempdir().unwrap();
+        let expected = crate::datagen::file_tree(root.path(), 250).unwrap();
+        let db_dir = tempfile::tempdir().unwrap();
+        let conn = crate::db::files::open_files_db(&db_dir.path().join("files.db")).unwrap();
+        let config = IndexerConfig::default();
+        let indexed = Mutex::new(HashSet::new());
+        let scan = || FileIndexer::scan_dir(root.path(), &config, &indexed, &conn, &|_| {}, &|| false, 0, 0).unwrap();
+
+        // node_modules is excluded
+        assert_eq!(scan(), expected);
+        assert_eq!(get_index_stats(&conn).unwrap().total_files, expected);
+        assert_eq!(scan(), 0);
+    }
+
+    #[test]
+    fn test_scan_dir_stops_when_asked() {
+        let root = tempfile::tempdir().unwrap();
+        crate::datagen::file_tree(root.path(), 50).unwrap();
+        let db_dir = tempfile::tempdir().unwrap();
+        let conn = crate::db::files::open_files_db(&db_dir.path().join("files.db")).unwrap();
+        let indexed = Mutex::new(HashSet::new());
+
+        let added = FileIndexer::scan_dir(root.path(), &IndexerConfig::default(), &indexed, &conn, &|_| {}, &|| true, 0, 0).unwrap();
+
+        assert_eq!(added, 0);
+        assert_eq!(get_index_stats(&conn).unwrap().total_files, 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exclusion_rule_through_symlinked_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("real");
+        fs::create_dir_all(real.join("node_modules")).unwrap();
+        let root = dir.path().join("linked");
+        std::os::unix::fs::symlink(&real, &root).unwrap();
+        let config = IndexerConfig {
+            paths: vec![root.clone()],
+            excluded_dirs: vec!["node_modules".to_string()],
+            ..Default::default()
+        };
+
+        // Watchers report the resolved path
+        let real = real.canonicalize().unwrap();
+        assert_eq!(config.exclusion_rule(&root.join("notes.md")), None);
+        assert_eq!(config.exclusion_rule(&real.join("notes.md")), None);
+        assert_eq!(
+            config.exclusion_rule(&real.join("node_modules").join("index.js")),
+            Some(ExclusionRule::ExcludedDir {
+                name: "node_modules".to_string(),
+                path: real.join("node_modules").to_string_lossy().to_string(),
+            })
+        );
+        assert_eq!(
+            config.exclusion_rule(&dir.path().join("elsewhere.md")),
+            Some(ExclusionRule::OutsideIndexRoots)
+        );
+    }
+
+    #[test]
+    fn test_scan_flags_placeholders_without_downloading() {
+        use crate::services::cloud_files::testing::FakePlaceholders;
+
+        let root = tempfile::tempdir().unwrap();
+        let cloud = root.path().join("cloud.pdf");
+        fs::write(root.path().join("local.pdf"), "local").unwrap();
+        fs::write(&cloud, "cloud").unwrap();
+        let placeholders = Arc::new(FakePlaceholders::with(&[&cloud]));
+        let config = IndexerConfig {
+            placeholders: placeholders.clone(),
+            ..Default::default()
+        };
+        let db_dir = tempfile::tempdir().unwrap();
+        let conn = crate::db::files::open_files_db(&db_dir.path().join("files.db")).unwrap();
+
+        FileIndexer::scan_dir(root.path(), &config, &Mutex::new(HashSet::new()), &conn, &|_| {}, &|| false, 0, 0).unwrap();
+
+        let mut found: Vec<(String, bool)> = search_files(&conn, "pdf", 10)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.filename, entry.is_placeholder))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![("cloud.pdf".to_string(), true), ("local.pdf".to_string(), false)]);
+        assert!(placeholders.materialized.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,540 @@
+//! File Preview
+//! Quick metadata for the results preview pane: size, modification time,
+//! mime type, image dimensions and the start of text files. Only file
+//! headers are read, and results are cached briefly by path and
+//! modification time since users arrow through result lists quickly. Cloud
+//! placeholders aren't read at all, since reading would download them.
+
+use crate::services::cloud_files::PlaceholderProbe;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Bytes of a text file included in the preview
+const TEXT_PREVIEW_BYTES: usize = 4096;
+
+/// Bytes read to find image dimensions; JPEG metadata can push the frame
+/// header well past the start of the file
+const IMAGE_HEADER_BYTES: u64 = 256 * 1024;
+
+/// Text files larger than this aren't previewed
+const MAX_TEXT_PREVIEW_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Symlinks followed before giving up
+const MAX_SYMLINK_HOPS: usize = 32;
+
+/// How long a cached preview is served
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Previews kept in the cache
+const CACHE_CAPACITY: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileKind {
+    File,
+    Directory,
+    Other,
+}
+
+/// Why a file has no text preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreviewUnavailable {
+    Binary,
+    TooLarge,
+    /// A cloud file whose content isn't downloaded
+    NotDownloaded,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ImageDimensions {
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Payload of `get_file_preview`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FilePreview {
+    /// Path after following symlinks
+    pub path: String,
+    pub kind: FileKind,
+    pub size: u64,
+    /// Unix seconds
+    pub modified: i64,
+    pub mime: Option<String>,
+    pub dimensions: Option<ImageDimensions>,
+    /// Start of the file, for text files
+    pub text: Option<String>,
+    /// The text wasn't valid UTF-8 and invalid bytes were replaced
+    pub lossy: bool,
+    /// The text is only the start of the file
+    pub truncated: bool,
+    pub unavailable: Option<PreviewUnavailable>,
+}
+
+/// Preview failure, serialized so the UI can tell these cases apart
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PreviewError {
+    NotFound { path: String },
+    PermissionDenied { path: String },
+    SymlinkLoop { path: String },
+    Failed { message: String },
+}
+
+impl fmt::Display for PreviewError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreviewError::NotFound { path } => write!(f, "File not found: {}", path),
+            PreviewError::PermissionDenied { path } => write!(f, "Permission denied: {}", path),
+            PreviewError::SymlinkLoop { path } => write!(f, "Symlink loop at {}", path),
+            PreviewError::Failed { message } => write!(f, "Preview failed: {}", message),
+        }
+    }
+}
+
+impl PreviewError {
+    fn io(e: io::Error, path: &Path) -> Self {
+        let path = path.display().to_string();
+        match e.kind() {
+            io::ErrorKind::NotFound => PreviewError::NotFound { path },
+            io::ErrorKind::PermissionDenied => PreviewError::PermissionDenied { path },
+            _ => PreviewError::Failed { message: format!("{}: {}", path, e) },
+        }
+    }
+}
+
+/// Recent previews keyed by resolved path and modification time, so an
+/// edited file is never served stale
+pub struct PreviewCache {
+    entries: Mutex<HashMap<(PathBuf, SystemTime), (Instant, FilePreview)>>,
+    ttl: Duration,
+}
+
+impl PreviewCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl,
+        }
+    }
+
+    pub fn get(&self, path: &Path, modified: SystemTime) -> Option<FilePreview> {
+        let entries = self.entries.lock().ok()?;
+        entries
+            .get(&(path.to_path_buf(), modified))
+            .filter(|(cached_at, _)| cached_at.elapsed() < self.ttl)
+            .map(|(_, preview)| preview.clone())
+    }
+
+    pub fn insert(&self, path: &Path, modified: SystemTime, preview: FilePreview) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        entries.retain(|_, (cached_at, _)| cached_at.elapsed() < self.ttl);
+        if entries.len() >= CACHE_CAPACITY {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (cached_at, _))| *cached_at)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = oldest {
+                entries.remove(&key);
+            }
+        }
+        entries.insert((path.to_path_buf(), modified), (Instant::now(), preview));
+    }
+}
+
+/// The app-wide preview cache
+pub fn cache() -> &'static PreviewCache {
+    static CACHE: OnceLock<PreviewCache> = OnceLock::new();
+    CACHE.get_or_init(|| PreviewCache::new(CACHE_TTL))
+}
+
+/// Preview `path`, from `cache` when the file hasn't changed
+pub fn preview_file(
+    path: &Path,
+    cache: &PreviewCache,
+    placeholders: &dyn PlaceholderProbe,
+) -> Result<FilePreview, PreviewError> {
+    let resolved = resolve_symlinks(path)?;
+    let metadata = fs::metadata(&resolved).map_err(|e| PreviewError::io(e, &resolved))?;
+    let modified = metadata.modified().unwrap_or(UNIX_EPOCH);
+
+    if let Some(preview) = cache.get(&resolved, modified) {
+        return Ok(preview);
+    }
+    let preview = build_preview(&resolved, &metadata, modified, placeholders)?;
+    // A download keeps the modification time, so this would outlive it
+    if preview.unavailable != Some(PreviewUnavailable::NotDownloaded) {
+        cache.insert(&resolved, modified, preview.clone());
+    }
+    Ok(preview)
+}
+
+/// Follow symlinks at `path` until a real file or directory, failing on
+/// loops instead of spinning
+pub fn resolve_symlinks(path: &Path) -> Result<PathBuf, PreviewError> {
+    let mut current = path.to_path_buf();
+    let mut seen = HashSet::new();
+
+    loop {
+        let metadata = fs::symlink_metadata(&current).map_err(|e| PreviewError::io(e, &current))?;
+        if !metadata.file_type().is_symlink() {
+            return Ok(current);
+        }
+        if !seen.insert(current.clone()) || seen.len() > MAX_SYMLINK_HOPS {
+            return Err(PreviewError::SymlinkLoop { path: path.display().to_string() });
+        }
+
+        let target = fs::read_link(&current).map_err(|e| PreviewError::io(e, &current))?;
+        // Relative targets are relative to the link's folder
+        current = match current.parent() {
+            Some(parent) => parent.join(target),
+            None => target,
+        };
+    }
+}
+
+fn build_preview(
+    path: &Path,
+    metadata: &Metadata,
+    modified: SystemTime,
+    placeholders: &dyn PlaceholderProbe,
+) -> Result<FilePreview, PreviewError> {
+    let mut preview = FilePreview {
+        path: path.to_string_lossy().to_string(),
+        kind: FileKind::Other,
+        size: metadata.len(),
+        modified: modified
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0),
+        mime: None,
+        dimensions: None,
+        text: None,
+        lossy: false,
+        truncated: false,
+        unavailable: None,
+    };
+
+    if metadata.is_dir() {
+        preview.kind = FileKind::Directory;
+        return Ok(preview);
+    }
+    if !metadata.is_file() {
+        return Ok(preview);
+    }
+    preview.kind = FileKind::File;
+    if placeholders.is_placeholder(path, metadata) {
+        preview.unavailable = Some(PreviewUnavailable::NotDownloaded);
+        return Ok(preview);
+    }
+
+    let read_error = |e| PreviewError::io(e, path);
+    let mut file = File::open(path).map_err(read_error)?;
+    let mut head = Vec::with_capacity(TEXT_PREVIEW_BYTES);
+    (&mut file)
+        .take(TEXT_PREVIEW_BYTES as u64)
+        .read_to_end(&mut head)
+        .map_err(read_error)?;
+
+    let detected = infer::get(&head);
+    let is_text_type = detected.is_some_and(|t| t.matcher_type() == infer::MatcherType::Text);
+
+    if let Some(kind) = detected.filter(|_| !is_text_type) {
+        preview.mime = Some(kind.mime_type().to_string());
+        preview.unavailable = Some(PreviewUnavailable::Binary);
+        if kind.matcher_type() == infer::MatcherType::Image {
+            file.take(IMAGE_HEADER_BYTES - head.len() as u64)
+                .read_to_end(&mut head)
+                .map_err(read_error)?;
+            preview.dimensions = image_dimensions(&head);
+        }
+        return Ok(preview);
+    }
+
+    let Some((text, lossy)) = sniff_text(&head) else {
+        preview.mime = Some("application/octet-stream".to_string());
+        preview.unavailable = Some(PreviewUnavailable::Binary);
+        return Ok(preview);
+    };
+
+    preview.mime = Some(match detected {
+        Some(kind) => kind.mime_type().to_string(),
+        None => text_mime(path).to_string(),
+    });
+    if preview.size > MAX_TEXT_PREVIEW_SIZE {
+        preview.unavailable = Some(PreviewUnavailable::TooLarge);
+        return Ok(preview);
+    }
+    preview.truncated = preview.size > head.len() as u64;
+    preview.text = Some(text);
+    preview.lossy = lossy;
+    Ok(preview)
+}
+
+/// The text in `head` and whether invalid UTF-8 had to be replaced, or
+/// None if it looks binary. `head` may end mid-character.
+pub fn sniff_text(head: &[u8]) -> Option<(String, bool)> {
+    if head.contains(&0) {
+        return None;
+    }
+    let control = head
+        .iter()
+        .filter(|&&b| b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b))
+        .count();
+    if control * 10 > head.len() {
+        return None;
+    }
+
+    match std::str::from_utf8(head) {
+        Ok(text) => Some((text.to_string(), false)),
+        // Cut off inside the last character, not invalid
+        Err(e) if e.error_len().is_none() => {
+            Some((String::from_utf8_lossy(&head[..e.valid_up_to()]).into_owned(), false))
+        }
+        Err(_) => Some((String::from_utf8_lossy(head).into_owned(), true)),
+    }
+}
+
+/// Mime type of a text file, from its extension
+fn text_mime(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "md" | "markdown" => "text/markdown",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "csv" => "text/csv",
+        "js" | "mjs" => "text/javascript",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        _ => "text/plain",
+    }
+}
+
+/// Width and height from a PNG, GIF, JPEG, BMP or WebP header
+pub fn image_dimensions(data: &[u8]) -> Option<ImageDimensions> {
+    let u16_be = |i: usize| Some(u16::from_be_bytes(data.get(i..i + 2)?.try_into().ok()?) as u32);
+    let u16_le = |i: usize| Some(u16::from_le_bytes(data.get(i..i + 2)?.try_into().ok()?) as u32);
+    let u24_le = |i: usize| Some(u32::from_le_bytes([*data.get(i)?, *data.get(i + 1)?, *data.get(i + 2)?, 0]));
+    let u32_be = |i: usize| Some(u32::from_be_bytes(data.get(i..i + 4)?.try_into().ok()?));
+    let i32_le = |i: usize| Some(i32::from_le_bytes(data.get(i..i + 4)?.try_into().ok()?));
+    let dims = |width: u32, height: u32| Some(ImageDimensions { width, height });
+
+    if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return dims(u32_be(16)?, u32_be(20)?);
+    }
+    if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        return dims(u16_le(6)?, u16_le(8)?);
+    }
+    if data.starts_with(b"BM") {
+        return dims(i32_le(18)?.unsigned_abs(), i32_le(22)?.unsigned_abs());
+    }
+    if data.starts_with(b"RIFF") && data.get(8..12) == Some(b"WEBP") {
+        return match data.get(12..16)? {
+            b"VP8 " => dims(u16_le(26)? & 0x3fff, u16_le(28)? & 0x3fff),
+            b"VP8L" => {
+                let b = data.get(21..25)?;
+                let (b0, b1, b2, b3) = (b[0] as u32, b[1] as u32, b[2] as u32, b[3] as u32);
+                dims(1 + (((b1 & 0x3f) << 8) | b0), 1 + (((b3 & 0xf) << 10) | (b2 << 2) | ((b1 & 0xc0) >> 6)))
+            }
+            b"VP8X" => dims(1 + u24_le(24)?, 1 + u24_le(27)?),
+            _ => None,
+        };
+    }
+    if data.starts_with(&[0xff, 0xd8]) {
+        // Walk the segments to the first start-of-frame marker
+        let mut i = 2;
+        while *data.get(i)? == 0xff {
+            let marker = *data.get(i + 1)?;
+            match marker {
+                0xff => i += 1,
+                0xc0..=0xcf if !matches!(marker, 0xc4 | 0xc8 | 0xcc) => {
+                    return dims(u16_be(i + 7)?, u16_be(i + 5)?);
+                }
+                0xd0..=0xd9 | 0x01 => i += 2,
+                _ => i += 2 + u16_be(i + 2)? as usize,
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::cloud_files::testing::FakePlaceholders;
+    use crate::services::cloud_files::SystemPlaceholders;
+
+    /// Minimal PNG header: signature plus an IHDR chunk
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        data.extend_from_slice(&width.to_be_bytes());
+        data.extend_from_slice(&height.to_be_bytes());
+        data.extend_from_slice(&[8, 6, 0, 0, 0]);
+        data
+    }
+
+    #[test]
+    fn test_sniff_text_and_binary() {
+        assert_eq!(sniff_text(b"hello\nworld\t!"), Some(("hello\nworld\t!".to_string(), false)));
+        assert_eq!(sniff_text("项目报告".as_bytes()), Some(("项目报告".to_string(), false)));
+        assert_eq!(sniff_text(b"ab\0cd"), None);
+        assert_eq!(sniff_text(&[0x01, 0x02, 0x03, b'a', 0x04]), None);
+
+        // A character cut off by the read limit isn't invalid
+        let cut = &"报告".as_bytes()[..4];
+        assert_eq!(sniff_text(cut), Some(("报".to_string(), false)));
+
+        // Latin-1 bytes are replaced and flagged
+        let (text, lossy) = sniff_text(b"caf\xe9 au lait").unwrap();
+        assert!(lossy);
+        assert!(text.starts_with("caf\u{fffd}"));
+    }
+
+    #[test]
+    fn test_image_dimensions_from_headers() {
+        assert_eq!(image_dimensions(&png_header(640, 480)), Some(ImageDimensions { width: 640, height: 480 }));
+
+        let gif = b"GIF89a\x20\x03\x58\x02\0\0\0";
+        assert_eq!(image_dimensions(gif), Some(ImageDimensions { width: 800, height: 600 }));
+
+        // SOI, an APP0 segment, then SOF0 with height 200 and width 300
+        let jpeg = [
+            0xff, 0xd8, 0xff, 0xe0, 0x00, 0x04, 0x00, 0x00, 0xff, 0xc0, 0x00, 0x11, 0x08, 0x00, 0xc8, 0x01, 0x2c,
+        ];
+        assert_eq!(image_dimensions(&jpeg), Some(ImageDimensions { width: 300, height: 200 }));
+
+        assert_eq!(image_dimensions(b"not an image"), None);
+        assert_eq!(image_dimensions(&png_header(640, 480)[..18]), None);
+    }
+
+    #[test]
+    fn test_previews_text_and_images() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = PreviewCache::new(CACHE_TTL);
+
+        let notes = dir.path().join("notes.md");
+        fs::write(&notes, "# Notes\n".repeat(1000)).unwrap();
+        let preview = preview_file(&notes, &cache, &SystemPlaceholders).unwrap();
+        assert_eq!(preview.kind, FileKind::File);
+        assert_eq!(preview.mime.as_deref(), Some("text/markdown"));
+        assert_eq!(preview.text.as_ref().unwrap().len(), TEXT_PREVIEW_BYTES);
+        assert!(preview.truncated);
+
+        let image = dir.path().join("photo.png");
+        fs::write(&image, png_header(32, 16)).unwrap();
+        let preview = preview_file(&image, &cache, &SystemPlaceholders).unwrap();
+        assert_eq!(preview.mime.as_deref(), Some("image/png"));
+        assert_eq!(preview.dimensions, Some(ImageDimensions { width: 32, height: 16 }));
+        assert_eq!(preview.unavailable, Some(PreviewUnavailable::Binary));
+        assert_eq!(preview.text, None);
+
+        assert_eq!(
+            preview_file(&dir.path().join("missing.txt"), &cache, &SystemPlaceholders).unwrap_err(),
+            PreviewError::NotFound { path: dir.path().join("missing.txt").display().to_string() }
+        );
+    }
+
+    #[test]
+    fn test_cache_keyed_by_path_and_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("todo.txt");
+        fs::write(&path, "first").unwrap();
+        let cache = PreviewCache::new(CACHE_TTL);
+
+        let first = preview_file(&path, &cache, &SystemPlaceholders).unwrap();
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        assert_eq!(cache.get(&path, modified), Some(first.clone()));
+
+        // Same mtime: served from the cache even though the content changed
+        fs::write(&path, "second").unwrap();
+        File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+        assert_eq!(preview_file(&path, &cache, &SystemPlaceholders).unwrap().text, first.text);
+
+        // New mtime: a new key
+        let later = modified + Duration::from_secs(5);
+        File::options().write(true).open(&path).unwrap().set_modified(later).unwrap();
+        assert_eq!(preview_file(&path, &cache, &SystemPlaceholders).unwrap().text.as_deref(), Some("second"));
+        assert_eq!(cache.get(&path, later).unwrap().text.as_deref(), Some("second"));
+
+        // Entries expire
+        let expiring = PreviewCache::new(Duration::ZERO);
+        expiring.insert(&path, later, first);
+        assert_eq!(expiring.get(&path, later), None);
+    }
+
+    #[test]
+    fn test_placeholders_are_not_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.txt");
+        fs::write(&path, "quarterly numbers").unwrap();
+        let placeholders = FakePlaceholders::with(&[&path]);
+        let cache = PreviewCache::new(CACHE_TTL);
+
+        let preview = preview_file(&path, &cache, &placeholders).unwrap();
+        assert_eq!(preview.kind, FileKind::File);
+        assert_eq!(preview.size, 17);
+        assert_eq!(preview.unavailable, Some(PreviewUnavailable::NotDownloaded));
+        assert_eq!(preview.text, None);
+
+        // Not cached, so the preview shows up once the file is downloaded
+        placeholders.materialize(&path).unwrap();
+        let preview = preview_file(&path, &cache, &placeholders).unwrap();
+        assert_eq!(preview.text.as_deref(), Some("quarterly numbers"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinks_resolved_and_loops_rejected() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("real.txt");
+        fs::write(&target, "real").unwrap();
+        symlink("real.txt", dir.path().join("link.txt")).unwrap();
+        symlink(dir.path().join("link.txt"), dir.path().join("link2.txt")).unwrap();
+        assert_eq!(resolve_symlinks(&dir.path().join("link2.txt")).unwrap(), target);
+
+        symlink("b", dir.path().join("a")).unwrap();
+        symlink("a", dir.path().join("b")).unwrap();
+        assert!(matches!(
+            preview_file(&dir.path().join("a"), &PreviewCache::new(CACHE_TTL), &SystemPlaceholders),
+            Err(PreviewError::SymlinkLoop { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_file_is_a_typed_error() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secret.txt");
+        fs::write(&path, "secret").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o000)).unwrap();
+        // Root reads anything; nothing to check then
+        if File::open(&path).is_ok() {
+            return;
+        }
+
+        assert!(matches!(
+            preview_file(&path, &PreviewCache::new(CACHE_TTL), &SystemPlaceholders),
+            Err(PreviewError::PermissionDenied { .. })
+        ));
+    }
+}
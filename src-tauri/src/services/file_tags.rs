@@ -0,0 +1,245 @@
+//! File Tags
+//! Reads file tags for the file index. On macOS these are Finder tags and
+//! the hosts a download came from ("Where from"), both stored as binary
+//! plists in extended attributes, so "tag:github.com" finds downloads from
+//! GitHub. Windows document properties (Title, Author) are out of scope:
+//! the property store needs a COM round trip per file, too slow for
+//! indexing. Other platforms report no tags.
+
+use std::path::Path;
+
+/// Extended attribute holding Finder tags
+#[cfg(target_os = "macos")]
+const USER_TAGS_XATTR: &str = "com.apple.metadata:_kMDItemUserTags";
+
+/// Extended attribute holding the URLs a download came from
+#[cfg(target_os = "macos")]
+const WHERE_FROMS_XATTR: &str = "com.apple.metadata:kMDItemWhereFroms";
+
+/// Longer arrays are treated as corrupt
+const MAX_ARRAY_LEN: usize = 4096;
+
+/// Tags of the file at `path`; empty when it has none or they can't be read
+pub fn read_file_tags(path: &Path) -> Vec<String> {
+    #[cfg(target_os = "macos")]
+    {
+        let read = |name: &str, decode: fn(&[u8]) -> Result<Vec<String>, String>| match xattr::get(path, name) {
+            Ok(Some(data)) => decode(&data).unwrap_or_else(|e| {
+                eprintln!("[FileTags] Bad {} on {}: {}", name, path.display(), e);
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        let mut tags = read(USER_TAGS_XATTR, decode_user_tags);
+        for host in read(WHERE_FROMS_XATTR, decode_where_froms) {
+            if !tags.contains(&host) {
+                tags.push(host);
+            }
+        }
+        tags
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = path;
+        Vec::new()
+    }
+}
+
+/// Decode `_kMDItemUserTags`: a binary plist array of "Name\n<color>"
+/// strings. The color index suffix is dropped.
+pub fn decode_user_tags(data: &[u8]) -> Result<Vec<String>, String> {
+    Ok(decode_string_array(data)?
+        .into_iter()
+        .map(|tag| match tag.rsplit_once('\n') {
+            Some((name, color)) if color.chars().all(|c| c.is_ascii_digit()) => name.to_string(),
+            _ => tag,
+        })
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty())
+        .collect())
+}
+
+/// Decode `kMDItemWhereFroms`: a binary plist array of the URLs a file was
+/// downloaded from, as their hosts. Entries without a host, like the
+/// message: URLs of mail attachments, are skipped.
+pub fn decode_where_froms(data: &[u8]) -> Result<Vec<String>, String> {
+    let mut hosts: Vec<String> = Vec::new();
+    for url in decode_string_array(data)? {
+        let host = url::Url::parse(&url).ok().and_then(|url| url.host_str().map(str::to_lowercase));
+        if let Some(host) = host.filter(|host| !hosts.contains(host)) {
+            hosts.push(host);
+        }
+    }
+    Ok(hosts)
+}
+
+/// Minimal reader for a `bplist00` whose top object is an array of strings,
+/// the only shape the tag attributes use
+fn decode_string_array(data: &[u8]) -> Result<Vec<String>, String> {
+    let plist = BinaryPlist::parse(data)?;
+    let (kind, info, body) = plist.object(plist.top_object)?;
+    if kind != 0xA {
+        return Err(format!("expected an array, found object type {:#x}", kind));
+    }
+
+    let (count, mut pos) = plist.length(info, body)?;
+    if count > MAX_ARRAY_LEN {
+        return Err(format!("array of {} items is too long", count));
+    }
+
+    let mut strings = Vec::with_capacity(count);
+    for _ in 0..count {
+        let object = plist.read_uint(pos, plist.ref_size)? as usize;
+        pos += plist.ref_size;
+        strings.push(plist.string(object)?);
+    }
+    Ok(strings)
+}
+
+struct BinaryPlist<'a> {
+    data: &'a [u8],
+    offsets: Vec<usize>,
+    ref_size: usize,
+    top_object: usize,
+}
+
+impl<'a> BinaryPlist<'a> {
+    const MAGIC: &'static [u8] = b"bplist00";
+    const TRAILER_LEN: usize = 32;
+
+    fn parse(data: &'a [u8]) -> Result<Self, String> {
+        if data.len() < Self::MAGIC.len() + Self::TRAILER_LEN || !data.starts_with(Self::MAGIC) {
+            return Err("not a binary plist".to_string());
+        }
+
+        let trailer = &data[data.len() - Self::TRAILER_LEN..];
+        let offset_size = trailer[6] as usize;
+        let ref_size = trailer[7] as usize;
+        let be_u64 = |bytes: &[u8]| bytes.iter().fold(0u64, |n, &b| (n << 8) | b as u64);
+        let num_objects = be_u64(&trailer[8..16]) as usize;
+        let top_object = be_u64(&trailer[16..24]) as usize;
+        let table_start = be_u64(&trailer[24..32]) as usize;
+
+        if !(1..=8).contains(&offset_size) || !(1..=8).contains(&ref_size) || top_object >= num_objects {
+            return Err("corrupt plist trailer".to_string());
+        }
+        let table_len = num_objects
+            .checked_mul(offset_size)
+            .filter(|len| table_start.saturating_add(*len) <= data.len() - Self::TRAILER_LEN)
+            .ok_or("offset table out of bounds")?;
+
+        let mut plist = Self { data, offsets: Vec::with_capacity(num_objects), ref_size, top_object };
+        for i in (table_start..table_start + table_len).step_by(offset_size) {
+            let offset = plist.read_uint(i, offset_size)? as usize;
+            plist.offsets.push(offset);
+        }
+        Ok(plist)
+    }
+
+    fn byte(&self, pos: usize) -> Result<u8, String> {
+        self.data.get(pos).copied().ok_or_else(|| "unexpected end of plist".to_string())
+    }
+
+    fn bytes(&self, pos: usize, len: usize) -> Result<&'a [u8], String> {
+        pos.checked_add(len)
+            .and_then(|end| self.data.get(pos..end))
+            .ok_or_else(|| "unexpected end of plist".to_string())
+    }
+
+    fn read_uint(&self, pos: usize, size: usize) -> Result<u64, String> {
+        Ok(self.bytes(pos, size)?.iter().fold(0u64, |n, &b| (n << 8) | b as u64))
+    }
+
+    /// Object type nibble, info nibble and the position just past the marker
+    fn object(&self, index: usize) -> Result<(u8, u8, usize), String> {
+        let offset = *self.offsets.get(index).ok_or("object reference out of range")?;
+        let marker = self.byte(offset)?;
+        Ok((marker >> 4, marker & 0x0F, offset + 1))
+    }
+
+    /// Element count of a string or array and where its contents start.
+    /// Counts of 15 or more follow the marker as an int object.
+    fn length(&self, info: u8, pos: usize) -> Result<(usize, usize), String> {
+        if info != 0x0F {
+            return Ok((info as usize, pos));
+        }
+        let marker = self.byte(pos)?;
+        if marker >> 4 != 0x1 || marker & 0x0F > 3 {
+            return Err("bad length marker".to_string());
+        }
+        let size = 1usize << (marker & 0x0F);
+        Ok((self.read_uint(pos + 1, size)? as usize, pos + 1 + size))
+    }
+
+    fn string(&self, index: usize) -> Result<String, String> {
+        let (kind, info, body) = self.object(index)?;
+        let (len, pos) = self.length(info, body)?;
+        match kind {
+            // ASCII
+            0x5 => Ok(String::from_utf8_lossy(self.bytes(pos, len)?).into_owned()),
+            // UTF-16BE, `len` code units
+            0x6 => {
+                let units: Vec<u16> = self
+                    .bytes(pos, len.checked_mul(2).ok_or("string too long")?)?
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                    .collect();
+                Ok(String::from_utf16_lossy(&units))
+            }
+            _ => Err(format!("expected a string, found object type {:#x}", kind)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fixture;
+
+    #[test]
+    fn test_decodes_tags_and_drops_color_suffix() {
+        assert_eq!(
+            decode_user_tags(fixture!("file_tags/user_tags.bplist")).unwrap(),
+            vec!["Blue", "Important", "Red"]
+        );
+    }
+
+    #[test]
+    fn test_decodes_utf16_tags() {
+        assert_eq!(
+            decode_user_tags(fixture!("file_tags/user_tags_unicode.bplist")).unwrap(),
+            vec!["Wichtig ✓", "工作"]
+        );
+    }
+
+    #[test]
+    fn test_decodes_empty_and_long_arrays() {
+        assert!(decode_user_tags(fixture!("file_tags/user_tags_empty.bplist")).unwrap().is_empty());
+
+        let many = decode_user_tags(fixture!("file_tags/user_tags_many.bplist")).unwrap();
+        assert_eq!(many.len(), 20);
+        assert_eq!(many[0], "project-00");
+        assert_eq!(many[19], "project-19");
+    }
+
+    #[test]
+    fn test_decodes_where_froms_as_hosts() {
+        assert_eq!(
+            decode_where_froms(fixture!("file_tags/where_froms.bplist")).unwrap(),
+            vec!["objects.githubusercontent.com", "github.com"]
+        );
+        assert!(decode_where_froms(fixture!("file_tags/not_an_array.bplist")).is_err());
+    }
+
+    #[test]
+    fn test_rejects_other_shapes_and_garbage() {
+        assert!(decode_user_tags(fixture!("file_tags/not_an_array.bplist")).is_err());
+        assert!(decode_user_tags(b"<?xml version=\"1.0\"?><plist/>").is_err());
+        assert!(decode_user_tags(b"").is_err());
+
+        // Truncated: the trailer no longer points at valid data
+        let data = fixture!("file_tags/user_tags.bplist");
+        assert!(decode_user_tags(&data[..data.len() - 5]).is_err());
+    }
+}
@@ -0,0 +1,487 @@
+//! HTTP Client
+//! Shared reqwest clients with connect/read timeouts, system or manual
+//! proxy and an app user-agent, a retry wrapper for idempotent GETs, and a
+//! cached connectivity check so network features can fail fast offline.
+//! GETs report to the app's connectivity state and aren't sent while it
+//! is offline.
+
+use crate::services::connectivity::{self, ConnectivityMonitor};
+use serde::Serialize;
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// User-agent sent with every request
+pub const USER_AGENT: &str = concat!("ETools/", env!("CARGO_PKG_VERSION"));
+
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Read timeout for async requests, overall timeout for blocking ones
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Attempts for idempotent GETs, including the first
+pub const RETRY_ATTEMPTS: u32 = 3;
+
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+
+/// Timeout of the connectivity probe
+const ONLINE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// How long a connectivity probe result is reused
+const ONLINE_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Proxy environment variables, in order of preference
+const PROXY_ENV_VARS: &[&str] = &[
+    "HTTPS_PROXY",
+    "https_proxy",
+    "HTTP_PROXY",
+    "http_proxy",
+    "ALL_PROXY",
+    "all_proxy",
+];
+
+/// HTTP failure, serialized so the UI can tell being offline apart
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HttpError {
+    /// The network can't be reached, or offline mode is on
+    Offline,
+    TimedOut,
+    /// The server answered with a non-success status
+    Status { status: u16 },
+    Failed { message: String },
+}
+
+impl fmt::Display for HttpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HttpError::Offline => write!(f, "No network connection"),
+            HttpError::TimedOut => write!(f, "Request timed out"),
+            HttpError::Status { status } => write!(f, "Server returned HTTP {}", status),
+            HttpError::Failed { message } => write!(f, "Request failed: {}", message),
+        }
+    }
+}
+
+impl From<reqwest::Error> for HttpError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            HttpError::TimedOut
+        } else if let Some(status) = e.status() {
+            HttpError::Status { status: status.as_u16() }
+        } else {
+            HttpError::Failed { message: e.to_string() }
+        }
+    }
+}
+
+impl From<HttpError> for String {
+    fn from(e: HttpError) -> Self {
+        e.to_string()
+    }
+}
+
+impl HttpError {
+    /// Transient failures worth another attempt
+    fn is_retryable(&self) -> bool {
+        match self {
+            HttpError::TimedOut | HttpError::Failed { .. } => true,
+            HttpError::Status { status } => *status == 429 || *status >= 500,
+            HttpError::Offline => false,
+        }
+    }
+}
+
+/// Proxy from the standard environment variables
+pub fn proxy_from_env(lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+    PROXY_ENV_VARS
+        .iter()
+        .filter_map(|name| lookup(name))
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
+fn manual_proxy() -> &'static RwLock<Option<String>> {
+    static MANUAL_PROXY: OnceLock<RwLock<Option<String>>> = OnceLock::new();
+    MANUAL_PROXY.get_or_init(|| RwLock::new(None))
+}
+
+/// Set the proxy from settings; None falls back to the environment and
+/// system proxy
+pub fn set_manual_proxy(proxy: Option<String>) {
+    let proxy = proxy.filter(|p| !p.trim().is_empty());
+    if let Ok(mut current) = manual_proxy().write() {
+        *current = proxy;
+    }
+}
+
+/// Manual proxy, else the proxy environment variables. None leaves
+/// reqwest on the platform's system proxy configuration.
+fn current_proxy() -> Option<String> {
+    manual_proxy()
+        .read()
+        .ok()
+        .and_then(|p| p.clone())
+        .or_else(|| proxy_from_env(|name| std::env::var(name).ok()))
+}
+
+fn proxy_config(proxy: &str) -> Result<reqwest::Proxy, HttpError> {
+    reqwest::Proxy::all(proxy)
+        .map(|p| p.no_proxy(reqwest::NoProxy::from_env()))
+        .map_err(|e| HttpError::Failed { message: format!("Invalid proxy {}: {}", proxy, e) })
+}
+
+fn build_blocking(proxy: Option<&str>, timeout: Duration) -> Result<reqwest::blocking::Client, HttpError> {
+    let mut builder = reqwest::blocking::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(timeout);
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy_config(proxy)?);
+    }
+    builder.build().map_err(HttpError::from)
+}
+
+fn build_async(proxy: Option<&str>) -> Result<reqwest::Client, HttpError> {
+    let mut builder = reqwest::Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(REQUEST_TIMEOUT);
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(proxy_config(proxy)?);
+    }
+    builder.build().map_err(HttpError::from)
+}
+
+/// A client built for a proxy setting, rebuilt when the setting changes
+type Cached<C> = Mutex<Option<(Option<String>, C)>>;
+
+fn cached<C: Clone>(
+    cache: &Cached<C>,
+    build: impl FnOnce(Option<&str>) -> Result<C, HttpError>,
+) -> Result<C, HttpError> {
+    let proxy = current_proxy();
+    let mut cache = cache.lock().map_err(|e| HttpError::Failed { message: format!("Lock error: {}", e) })?;
+    match cache.as_ref() {
+        Some((built_for, client)) if *built_for == proxy => Ok(client.clone()),
+        _ => {
+            let client = build(proxy.as_deref())?;
+            *cache = Some((proxy, client.clone()));
+            Ok(client)
+        }
+    }
+}
+
+/// Shared blocking client. Must not be used from async tasks.
+pub fn blocking_client() -> Result<reqwest::blocking::Client, HttpError> {
+    static CLIENT: Cached<reqwest::blocking::Client> = Mutex::new(None);
+    cached(&CLIENT, |proxy| build_blocking(proxy, REQUEST_TIMEOUT))
+}
+
+/// Shared async client
+pub fn client() -> Result<reqwest::Client, HttpError> {
+    static CLIENT: Cached<reqwest::Client> = Mutex::new(None);
+    cached(&CLIENT, build_async)
+}
+
+/// Exponential backoff with up to one base delay of jitter
+fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let jitter = RandomState::new().build_hasher().finish() % (base.as_millis() as u64 + 1);
+    base * 2u32.pow(attempt) + Duration::from_millis(jitter)
+}
+
+fn retry_blocking_with<T>(
+    attempts: u32,
+    base_delay: Duration,
+    mut op: impl FnMut() -> Result<T, HttpError>,
+) -> Result<T, HttpError> {
+    let mut attempt = 0;
+    loop {
+        match op() {
+            Err(e) if e.is_retryable() && attempt + 1 < attempts => {
+                std::thread::sleep(backoff_delay(base_delay, attempt));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Retry an idempotent blocking request on transient failures
+pub fn retry_blocking<T>(op: impl FnMut() -> Result<T, HttpError>) -> Result<T, HttpError> {
+    retry_blocking_with(RETRY_ATTEMPTS, RETRY_BASE_DELAY, op)
+}
+
+/// Retry an idempotent async request on transient failures
+pub async fn retry<T, F, Fut>(mut op: F) -> Result<T, HttpError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, HttpError>>,
+{
+    let mut attempt = 0;
+    loop {
+        match op().await {
+            Err(e) if e.is_retryable() && attempt + 1 < RETRY_ATTEMPTS => {
+                tokio::time::sleep(backoff_delay(RETRY_BASE_DELAY, attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+fn send_get_blocking(client: &reqwest::blocking::Client, url: &str) -> Result<reqwest::blocking::Response, HttpError> {
+    Ok(client.get(url).send()?.error_for_status()?)
+}
+
+/// Fail with `HttpError::Offline`, without a network attempt, while
+/// `monitor` is offline
+fn check_online(monitor: &ConnectivityMonitor) -> Result<(), HttpError> {
+    if monitor.is_offline() {
+        Err(HttpError::Offline)
+    } else {
+        Ok(())
+    }
+}
+
+/// Feed a request outcome to `monitor`. Any HTTP status means the server
+/// was reached.
+fn report<T>(monitor: &ConnectivityMonitor, result: &Result<T, HttpError>) {
+    match result {
+        Ok(_) | Err(HttpError::Status { .. }) => monitor.report_success(),
+        Err(HttpError::TimedOut | HttpError::Failed { .. }) => monitor.report_failure(),
+        Err(HttpError::Offline) => {}
+    }
+}
+
+fn get_blocking_with(
+    monitor: &ConnectivityMonitor,
+    client: &reqwest::blocking::Client,
+    url: &str,
+    attempts: u32,
+    base_delay: Duration,
+) -> Result<reqwest::blocking::Response, HttpError> {
+    check_online(monitor)?;
+    let result = retry_blocking_with(attempts, base_delay, || send_get_blocking(client, url));
+    report(monitor, &result);
+    result
+}
+
+/// GET with retries, failing on non-success statuses
+pub fn get_blocking(url: &str) -> Result<reqwest::blocking::Response, HttpError> {
+    get_blocking_via(connectivity::monitor(), url)
+}
+
+/// `get_blocking` gated by and reporting to `monitor` instead of the
+/// app-wide connectivity state
+pub fn get_blocking_via(monitor: &ConnectivityMonitor, url: &str) -> Result<reqwest::blocking::Response, HttpError> {
+    let client = blocking_client()?;
+    get_blocking_with(monitor, &client, url, RETRY_ATTEMPTS, RETRY_BASE_DELAY)
+}
+
+/// Async GET with retries, failing on non-success statuses. Only sending
+/// is retried; the body is read by the caller.
+pub async fn get(url: &str) -> Result<reqwest::Response, HttpError> {
+    let monitor = connectivity::monitor();
+    check_online(monitor)?;
+    let client = client()?;
+    let result = retry(|| async { Ok(client.get(url).send().await?.error_for_status()?) }).await;
+    report(monitor, &result);
+    result
+}
+
+/// Whether `url` answers a HEAD request right now; any HTTP response
+/// counts. Ignores the connectivity state. Blocking.
+pub fn probe(url: &str) -> bool {
+    build_blocking(current_proxy().as_deref(), ONLINE_CHECK_TIMEOUT)
+        .map(|client| client.head(url).send().is_ok())
+        .unwrap_or(false)
+}
+
+fn online_cache() -> &'static Mutex<HashMap<String, Instant>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Whether `url` can be reached. False without a network attempt while
+/// the app is offline; otherwise a successful probe is reused briefly and
+/// a failed one switches the app offline. Blocking.
+pub fn is_online(url: &str) -> bool {
+    let monitor = connectivity::monitor();
+    if monitor.is_offline() {
+        return false;
+    }
+    if let Some(checked_at) = online_cache().lock().ok().and_then(|c| c.get(url).copied()) {
+        if checked_at.elapsed() < ONLINE_CACHE_TTL {
+            return true;
+        }
+    }
+
+    let online = probe(url);
+    monitor.report_probe(online);
+    if let Ok(mut cache) = online_cache().lock() {
+        if online {
+            cache.insert(url.to_string(), Instant::now());
+        } else {
+            cache.remove(url);
+        }
+    }
+    online
+}
+
+/// Fail fast with `HttpError::Offline` when `url` can't be reached
+pub fn ensure_online(url: &str) -> Result<(), HttpError> {
+    if is_online(url) {
+        Ok(())
+    } else {
+        Err(HttpError::Offline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    /// Serve canned responses, one per connection, returning the base URL
+    fn mock_server(respond: impl Fn(u32) -> Option<String> + Send + 'static) -> (String, Arc<AtomicU32>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicU32::new(0));
+        let counter = hits.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut buf = [0; 1024];
+                let _ = stream.read(&mut buf);
+                let hit = counter.fetch_add(1, Ordering::SeqCst);
+                match respond(hit) {
+                    Some(response) => {
+                        let _ = stream.write_all(response.as_bytes());
+                    }
+                    // Hold the connection open without answering
+                    None => std::thread::sleep(Duration::from_secs(2)),
+                }
+            }
+        });
+        (url, hits)
+    }
+
+    fn response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        )
+    }
+
+    #[test]
+    fn test_hung_server_times_out() {
+        let (url, _) = mock_server(|_| None);
+        let client = build_blocking(None, Duration::from_millis(200)).unwrap();
+
+        assert_eq!(send_get_blocking(&client, &url).unwrap_err(), HttpError::TimedOut);
+    }
+
+    #[test]
+    fn test_retries_until_success() {
+        let (url, hits) = mock_server(|hit| {
+            Some(if hit == 0 { response("503 Service Unavailable", "") } else { response("200 OK", "ok") })
+        });
+        let client = build_blocking(None, REQUEST_TIMEOUT).unwrap();
+
+        let body = retry_blocking_with(3, Duration::from_millis(1), || send_get_blocking(&client, &url))
+            .unwrap()
+            .text()
+            .unwrap();
+
+        assert_eq!(body, "ok");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        let (url, hits) = mock_server(|_| Some(response("404 Not Found", "")));
+        let client = build_blocking(None, REQUEST_TIMEOUT).unwrap();
+
+        let result = retry_blocking_with(3, Duration::from_millis(1), || send_get_blocking(&client, &url));
+
+        assert_eq!(result.unwrap_err(), HttpError::Status { status: 404 });
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_proxy_env_parsing() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| vars.iter().find(|(k, _)| *k == name).map(|(_, v)| v.to_string())
+        };
+
+        assert_eq!(proxy_from_env(env(&[])), None);
+        assert_eq!(
+            proxy_from_env(env(&[("http_proxy", "http://proxy:8080")])),
+            Some("http://proxy:8080".to_string())
+        );
+        // HTTPS_PROXY wins, blank values are ignored
+        assert_eq!(
+            proxy_from_env(env(&[
+                ("HTTPS_PROXY", " "),
+                ("https_proxy", "http://secure:3128"),
+                ("HTTP_PROXY", "http://plain:8080"),
+            ])),
+            Some("http://secure:3128".to_string())
+        );
+        assert!(proxy_config("not a url").is_err());
+    }
+
+    #[test]
+    fn test_no_requests_while_offline() {
+        let (url, hits) = mock_server(|_| Some(response("200 OK", "ok")));
+        let client = build_blocking(None, REQUEST_TIMEOUT).unwrap();
+        let monitor = ConnectivityMonitor::new();
+        let get = |monitor: &ConnectivityMonitor| get_blocking_with(monitor, &client, &url, 1, Duration::from_millis(1));
+
+        monitor.set_forced(true);
+        assert_eq!(get(&monitor).unwrap_err(), HttpError::Offline);
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        monitor.set_forced(false);
+        assert!(get(&monitor).is_ok());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_connection_failures_switch_offline() {
+        // Nothing listens on a port freed right after binding
+        let closed = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}", listener.local_addr().unwrap())
+        };
+        let (url, hits) = mock_server(|_| Some(response("404 Not Found", "")));
+        let client = build_blocking(None, REQUEST_TIMEOUT).unwrap();
+        let monitor = ConnectivityMonitor::new();
+        let get = |url: &str| get_blocking_with(&monitor, &client, url, 1, Duration::from_millis(1));
+
+        assert!(matches!(get(&closed), Err(HttpError::Failed { .. })));
+        // An error status still proves the network works
+        assert_eq!(get(&url).unwrap_err(), HttpError::Status { status: 404 });
+        assert!(!monitor.is_offline());
+
+        assert!(get(&closed).is_err());
+        assert!(get(&closed).is_err());
+        assert!(monitor.is_offline());
+        assert_eq!(get(&url).unwrap_err(), HttpError::Offline);
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        monitor.report_probe(true);
+        assert!(get(&url).is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+}
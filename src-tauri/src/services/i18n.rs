@@ -0,0 +1,171 @@
+//! Localization Service
+//! Looks up user-visible backend messages by code in the bundled locale
+//! tables (en, zh-CN) and renders `{name}` placeholders. The active locale
+//! follows the `language` setting and can change at runtime.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::OnceLock;
+
+const EN_MESSAGES: &str = include_str!("../../locales/en.json");
+const ZH_CN_MESSAGES: &str = include_str!("../../locales/zh-CN.json");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    /// Map a language setting ("en", "zh-CN", "zh", ...) to a locale,
+    /// falling back to English for anything unsupported
+    pub fn from_tag(tag: &str) -> Self {
+        if tag.to_ascii_lowercase().starts_with("zh") {
+            Locale::ZhCn
+        } else {
+            Locale::En
+        }
+    }
+
+    fn index(self) -> u8 {
+        match self {
+            Locale::En => 0,
+            Locale::ZhCn => 1,
+        }
+    }
+}
+
+type Table = HashMap<String, String>;
+
+static CURRENT_LOCALE: AtomicU8 = AtomicU8::new(0);
+static TABLES: OnceLock<(Table, Table)> = OnceLock::new();
+
+fn tables() -> &'static (Table, Table) {
+    TABLES.get_or_init(|| {
+        let parse = |name: &str, raw: &str| -> Table {
+            serde_json::from_str(raw).unwrap_or_else(|e| {
+                eprintln!("Failed to parse {} locale table: {}", name, e);
+                Table::new()
+            })
+        };
+        (parse("en", EN_MESSAGES), parse("zh-CN", ZH_CN_MESSAGES))
+    })
+}
+
+/// Parse the bundled locale tables and select the initial locale
+pub fn init(language: &str) {
+    tables();
+    set_locale_tag(language);
+}
+
+pub fn set_locale(locale: Locale) {
+    CURRENT_LOCALE.store(locale.index(), Ordering::Relaxed);
+}
+
+pub fn set_locale_tag(language: &str) {
+    set_locale(Locale::from_tag(language));
+}
+
+pub fn current_locale() -> Locale {
+    match CURRENT_LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::ZhCn,
+        _ => Locale::En,
+    }
+}
+
+/// Render `code` in `locale`. Missing translations fall back to English,
+/// then to the code itself so a gap never hides the message entirely.
+pub fn translate(locale: Locale, code: &str, args: &[(&str, String)]) -> String {
+    let (en, zh_cn) = tables();
+    let table = match locale {
+        Locale::En => en,
+        Locale::ZhCn => zh_cn,
+    };
+    let template = table
+        .get(code)
+        .or_else(|| en.get(code))
+        .map(String::as_str)
+        .unwrap_or(code);
+
+    args.iter().fold(template.to_string(), |message, (name, value)| {
+        message.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+/// Render `code` in the current locale
+pub fn tr(code: &str, args: &[(&str, String)]) -> String {
+    translate(current_locale(), code, args)
+}
+
+/// Message arguments as carried on error types for frontend re-rendering
+pub fn args_map(args: &[(&str, String)]) -> HashMap<String, String> {
+    args.iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+/// Translate a message code in the current locale:
+/// `t!("plugins.not_found", plugin_id = id)`
+#[macro_export]
+macro_rules! t {
+    ($code:expr) => {
+        $crate::services::i18n::tr($code, &[])
+    };
+    ($code:expr, $($name:ident = $value:expr),+ $(,)?) => {
+        $crate::services::i18n::tr(
+            $code,
+            &[$((stringify!($name), $value.to_string())),+],
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_renders_sample_codes_in_both_locales() {
+        let samples: &[(&str, &[(&str, String)])] = &[
+            ("validator.id_required", &[]),
+            ("validator.unauthorized_permission", &[("permission", "telepathy".to_string())]),
+            ("installer.already_installed", &[("plugin_id", "demo".to_string())]),
+            ("plugins.not_found", &[("plugin_id", "demo".to_string())]),
+            ("permission.shell.name", &[]),
+        ];
+
+        for (code, args) in samples {
+            let en = translate(Locale::En, code, args);
+            let zh = translate(Locale::ZhCn, code, args);
+            assert_ne!(en, *code, "missing en translation for {}", code);
+            assert_ne!(zh, *code, "missing zh-CN translation for {}", code);
+            assert_ne!(en, zh, "{} is not localized", code);
+            assert!(!en.contains('{') && !zh.contains('{'), "unfilled placeholder in {}", code);
+            for (_, value) in args.iter() {
+                assert!(en.contains(value.as_str()) && zh.contains(value.as_str()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_locale_tables_have_the_same_codes() {
+        let (en, zh_cn) = tables();
+        let mut en_codes: Vec<_> = en.keys().collect();
+        let mut zh_codes: Vec<_> = zh_cn.keys().collect();
+        en_codes.sort();
+        zh_codes.sort();
+        assert_eq!(en_codes, zh_codes);
+    }
+
+    #[test]
+    fn test_unknown_codes_fall_back_to_the_code() {
+        assert_eq!(translate(Locale::ZhCn, "no.such.code", &[]), "no.such.code");
+    }
+
+    #[test]
+    fn test_locale_tags_map_to_supported_locales() {
+        assert_eq!(Locale::from_tag("zh-CN"), Locale::ZhCn);
+        assert_eq!(Locale::from_tag("zh"), Locale::ZhCn);
+        assert_eq!(Locale::from_tag("en-US"), Locale::En);
+        assert_eq!(Locale::from_tag("fr"), Locale::En);
+    }
+}
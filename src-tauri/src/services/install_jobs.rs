@@ -0,0 +1,208 @@
+//! Plugin Install Jobs
+//! Tracks extractions that belong to in-progress installs and garbage
+//! collects the installer temp dir, which otherwise keeps every uploaded
+//! buffer and extraction folder forever.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// Temp entries older than this are swept at startup
+pub const DEFAULT_TEMP_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A max age given in hours. Ages too large to represent are clamped, which
+/// keeps everything.
+pub fn max_age_from_hours(hours: u64) -> Duration {
+    Duration::from_secs(hours.saturating_mul(60 * 60))
+}
+
+/// Installs between extraction and install/cancel. A job id is the name of
+/// its extraction folder in the temp dir.
+#[derive(Default)]
+pub struct InstallJobManager {
+    active: Mutex<HashSet<String>>,
+}
+
+impl InstallJobManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn begin(&self, job_id: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.insert(job_id.to_string());
+        }
+    }
+
+    pub fn finish(&self, job_id: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(job_id);
+        }
+    }
+
+    pub fn active_ids(&self) -> HashSet<String> {
+        self.active.lock().map(|active| active.clone()).unwrap_or_default()
+    }
+}
+
+/// Job id for an extraction folder path
+pub fn job_id_for(extracted_path: &Path) -> Option<String> {
+    extracted_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+}
+
+/// Size of a temp entry without following symlinks
+fn entry_size(path: &Path) -> u64 {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !meta.is_dir() {
+        return meta.len();
+    }
+    fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|entry| entry_size(&entry.path())).sum())
+        .unwrap_or(0)
+}
+
+/// Remove a temp entry. Symlinks are unlinked, never followed.
+fn remove_entry(path: &Path, meta: &fs::Metadata) -> std::io::Result<()> {
+    if meta.file_type().is_symlink() {
+        fs::remove_file(path).or_else(|_| fs::remove_dir(path))
+    } else if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Remove top-level temp entries last modified more than `older_than` ago,
+/// skipping extraction folders of active jobs. Returns bytes reclaimed.
+pub fn sweep_temp_dir(temp_dir: &Path, older_than: Duration, active: &HashSet<String>) -> Result<u64, String> {
+    let entries = match fs::read_dir(temp_dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(format!("Failed to read temp dir: {}", e)),
+    };
+
+    let now = SystemTime::now();
+    let mut reclaimed = 0;
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if active.contains(&name) {
+            continue;
+        }
+
+        let path = entry.path();
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            continue;
+        };
+        let age = meta
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .unwrap_or_default();
+        if age < older_than {
+            continue;
+        }
+
+        let size = entry_size(&path);
+        match remove_entry(&path, &meta) {
+            Ok(()) => reclaimed += size,
+            Err(e) => eprintln!("[InstallJobs] Failed to remove {:?}: {}", path, e),
+        }
+    }
+
+    Ok(reclaimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn age(path: &Path, hours: u64) {
+        let mtime = SystemTime::now() - Duration::from_secs(hours * 60 * 60);
+        fs::File::open(path).unwrap().set_modified(mtime).unwrap();
+    }
+
+    #[test]
+    fn test_max_age_from_hours_clamps() {
+        assert_eq!(max_age_from_hours(24), DEFAULT_TEMP_MAX_AGE);
+        assert_eq!(max_age_from_hours(u64::MAX), Duration::from_secs(u64::MAX));
+
+        let dir = tempfile::tempdir().unwrap();
+        fake_extraction(dir.path(), "old", 48);
+        assert_eq!(sweep_temp_dir(dir.path(), max_age_from_hours(u64::MAX), &HashSet::new()).unwrap(), 0);
+        assert!(dir.path().join("old").exists());
+    }
+
+    fn fake_extraction(temp_dir: &Path, name: &str, hours_old: u64) {
+        let dir = temp_dir.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("plugin.json"), "0123456789").unwrap();
+        age(&dir, hours_old);
+    }
+
+    #[test]
+    fn test_sweep_removes_only_old_inactive_entries() {
+        let temp = tempfile::tempdir().unwrap();
+        fake_extraction(temp.path(), "old", 48);
+        fake_extraction(temp.path(), "old-active", 48);
+        fake_extraction(temp.path(), "fresh", 1);
+        fs::write(temp.path().join("upload.zip"), "12345").unwrap();
+        age(&temp.path().join("upload.zip"), 30);
+
+        let jobs = InstallJobManager::new();
+        jobs.begin("old-active");
+
+        let reclaimed = sweep_temp_dir(temp.path(), DEFAULT_TEMP_MAX_AGE, &jobs.active_ids()).unwrap();
+
+        assert_eq!(reclaimed, 15);
+        assert!(!temp.path().join("old").exists());
+        assert!(!temp.path().join("upload.zip").exists());
+        assert!(temp.path().join("old-active").exists());
+        assert!(temp.path().join("fresh").exists());
+    }
+
+    #[test]
+    fn test_finished_jobs_are_swept() {
+        let temp = tempfile::tempdir().unwrap();
+        fake_extraction(temp.path(), "job", 48);
+
+        let jobs = InstallJobManager::new();
+        jobs.begin("job");
+        jobs.finish("job");
+
+        sweep_temp_dir(temp.path(), DEFAULT_TEMP_MAX_AGE, &jobs.active_ids()).unwrap();
+        assert!(!temp.path().join("job").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sweep_does_not_follow_symlinks() {
+        let root = tempfile::tempdir().unwrap();
+        let temp_dir = root.path().join("temp");
+        let outside = root.path().join("outside");
+        fs::create_dir_all(&temp_dir).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        fs::write(outside.join("keep.txt"), "keep").unwrap();
+
+        // A stale extraction linking out of the temp dir
+        fake_extraction(&temp_dir, "stale", 48);
+        std::os::unix::fs::symlink(&outside, temp_dir.join("stale").join("escape")).unwrap();
+        age(&temp_dir.join("stale"), 48);
+
+        sweep_temp_dir(&temp_dir, DEFAULT_TEMP_MAX_AGE, &HashSet::new()).unwrap();
+
+        assert!(!temp_dir.join("stale").exists());
+        assert!(outside.join("keep.txt").exists());
+    }
+
+    #[test]
+    fn test_missing_temp_dir_reclaims_nothing() {
+        let root = tempfile::tempdir().unwrap();
+        assert_eq!(sweep_temp_dir(&root.path().join("temp"), Duration::ZERO, &HashSet::new()).unwrap(), 0);
+    }
+}
@@ -0,0 +1,85 @@
+//! Launcher Visibility
+//! Remembers when the launcher was hidden so showing it again can decide,
+//! per the `on_show_behavior` setting, whether to keep the previous search.
+//! The decision is made here rather than in the webview, whose timers stop
+//! while it's suspended. Wall-clock time is used for the same reason: a
+//! monotonic clock may not advance while the machine sleeps.
+
+use crate::models::preferences::OnShowBehavior;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// When the launcher was last hidden, managed as app state
+#[derive(Default)]
+pub struct LauncherVisibility {
+    hidden_at: Mutex<Option<SystemTime>>,
+}
+
+impl LauncherVisibility {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The launcher was hidden just now
+    pub fn hidden(&self) {
+        if let Ok(mut hidden_at) = self.hidden_at.lock() {
+            *hidden_at = Some(SystemTime::now());
+        }
+    }
+
+    /// The launcher was shown just now; whether its search should be reset
+    pub fn shown(&self, behavior: OnShowBehavior) -> bool {
+        let hidden_at = self.hidden_at.lock().ok().and_then(|mut hidden_at| hidden_at.take());
+        // A clock set backwards counts as no time passing
+        let hidden_for = hidden_at.map(|at| at.elapsed().unwrap_or_default());
+        should_reset(behavior, hidden_for)
+    }
+}
+
+/// Whether a launcher hidden for `hidden_for` starts with an empty search.
+/// `None` means it wasn't hidden since it was last shown, e.g. at startup.
+pub fn should_reset(behavior: OnShowBehavior, hidden_for: Option<Duration>) -> bool {
+    let Some(hidden_for) = hidden_for else {
+        return false;
+    };
+    match behavior {
+        OnShowBehavior::Keep => false,
+        OnShowBehavior::Clear => true,
+        OnShowBehavior::ClearAfterSeconds(seconds) => hidden_for >= Duration::from_secs(u64::from(seconds)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_and_clear() {
+        let hour = Some(Duration::from_secs(3600));
+        assert!(!should_reset(OnShowBehavior::Keep, hour));
+        assert!(!should_reset(OnShowBehavior::Keep, Some(Duration::ZERO)));
+        assert!(should_reset(OnShowBehavior::Clear, Some(Duration::ZERO)));
+        assert!(should_reset(OnShowBehavior::Clear, hour));
+        assert!(!should_reset(OnShowBehavior::Clear, None));
+    }
+
+    #[test]
+    fn test_clear_after_seconds() {
+        let behavior = OnShowBehavior::ClearAfterSeconds(30);
+        assert!(!should_reset(behavior, Some(Duration::from_secs(29))));
+        assert!(should_reset(behavior, Some(Duration::from_secs(30))));
+        assert!(should_reset(behavior, Some(Duration::from_secs(600))));
+        assert!(!should_reset(behavior, None));
+    }
+
+    #[test]
+    fn test_hide_time_is_used_once() {
+        let visibility = LauncherVisibility::new();
+        assert!(!visibility.shown(OnShowBehavior::Clear));
+
+        visibility.hidden();
+        assert!(visibility.shown(OnShowBehavior::Clear));
+        // Shown again without being hidden in between
+        assert!(!visibility.shown(OnShowBehavior::Clear));
+    }
+}
@@ -3,31 +3,123 @@
 //!
 //! This replaces the custom marketplace with npm-based plugin distribution.
 
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use crate::models::plugin::*;
+use crate::services::app_paths;
+use crate::services::connectivity::{self, ConnectivityMonitor};
+use crate::services::http::{self, HttpError};
+use crate::services::plugin_api::check_api_version;
+use crate::services::plugin_install_meta::{read_install_meta, record_install, record_update, recorded_install};
+use crate::services::plugin_protection::{check_protected_claim, is_protected};
+use crate::services::plugin_validator::PluginValidator;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
 use std::fs;
+use std::path::Path;
 use std::process::Command;
-use std::time::SystemTime;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
 use serde_json::Value;
 
+/// Marketplace failure; `Offline` lets the UI show a dedicated state
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MarketplaceError {
+    Offline,
+    Failed { message: String },
+}
+
+impl fmt::Display for MarketplaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MarketplaceError::Offline => write!(f, "{}", HttpError::Offline),
+            MarketplaceError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<HttpError> for MarketplaceError {
+    fn from(e: HttpError) -> Self {
+        match e {
+            HttpError::Offline => MarketplaceError::Offline,
+            e => MarketplaceError::Failed { message: e.to_string() },
+        }
+    }
+}
+
+impl From<String> for MarketplaceError {
+    fn from(message: String) -> Self {
+        MarketplaceError::Failed { message }
+    }
+}
+
+impl From<&str> for MarketplaceError {
+    fn from(message: &str) -> Self {
+        MarketplaceError::Failed { message: message.to_string() }
+    }
+}
+
+impl From<MarketplaceError> for String {
+    fn from(e: MarketplaceError) -> Self {
+        e.to_string()
+    }
+}
+
 /// Error type for marketplace operations
-pub type MarketplaceResult<T> = Result<T, String>;
+pub type MarketplaceResult<T> = Result<T, MarketplaceError>;
 
 /// NPM registry search API endpoint
 const NPM_SEARCH_API: &str = "https://registry.npmjs.org/-/v1/search";
-/// NPM registry API endpoint (reserved for future use)
-#[allow(dead_code)]
+/// NPM registry API endpoint, also probed for connectivity
 const NPM_REGISTRY_API: &str = "https://registry.npmjs.org";
 
+/// Keyword every marketplace plugin carries
+const PLUGIN_KEYWORD: &str = "etools-plugin";
+
+/// Keyword prefix naming a plugin's category, e.g. "etools-category:developer"
+const CATEGORY_KEYWORD_PREFIX: &str = "etools-category:";
+
+/// Matches fetched per request when computing facets, npm's largest page
+/// size
+const FACET_PAGE_SIZE: u32 = 250;
+
+/// Most matches facets are computed over; past it they're labeled partial
+const FACET_MAX_MATCHES: usize = 2_500;
+
+/// Number of tags returned in facets
+const TOP_TAGS: usize = 12;
+
+/// How long a registry search response is reused
+const SEARCH_CACHE_TTL: Duration = Duration::from_secs(300);
+
+/// Cached search responses kept before the cache is cleared
+const SEARCH_CACHE_CAPACITY: usize = 32;
+
 /// Marketplace service (npm-based)
 pub struct MarketplaceService {
-    // Add any required fields here (e.g., http client)
+    /// Registry search responses by URL, so paging back and computing
+    /// facets don't repeat requests
+    search_cache: Mutex<HashMap<String, (Instant, NpmSearchResponse)>>,
+    /// Latest versions from past update checks, reused while offline
+    latest_versions: Mutex<HashMap<String, String>>,
+    /// Gates registry requests; the app-wide state outside tests
+    connectivity: &'static ConnectivityMonitor,
 }
 
 impl MarketplaceService {
     /// Create a new marketplace service instance
     pub fn new() -> Self {
-        Self {}
+        Self::with_connectivity(connectivity::monitor())
+    }
+
+    /// A service whose registry requests follow `connectivity`
+    pub fn with_connectivity(connectivity: &'static ConnectivityMonitor) -> Self {
+        Self {
+            search_cache: Mutex::new(HashMap::new()),
+            latest_versions: Mutex::new(HashMap::new()),
+            connectivity,
+        }
     }
 
     /// List marketplace plugins from npm
@@ -39,18 +131,10 @@ impl MarketplaceService {
         _handle: &AppHandle,
     ) -> MarketplaceResult<MarketplacePluginPage> {
         // Search npm for etools-plugin packages
-        let search_query = "keywords:etools-plugin";
         let from = (page.saturating_sub(1) * page_size) as usize;
+        let url = search_url("", page_size, from);
 
-        let url = format!(
-            "{}?text={}&size={}&from={}",
-            NPM_SEARCH_API,
-            urlencoding::encode(search_query),
-            page_size,
-            from
-        );
-
-        let response = self.npm_search(&url)?;
+        let (response, stale) = self.npm_search(&url)?;
 
         let total = response.total as u32;
         let object_count = response.objects.len();
@@ -63,6 +147,7 @@ impl MarketplaceService {
             page,
             page_size,
             has_more,
+            stale,
         })
     }
 
@@ -76,18 +161,10 @@ impl MarketplaceService {
         _handle: &AppHandle,
     ) -> MarketplaceResult<MarketplacePluginPage> {
         // Search npm with query
-        let search_query = format!("{} keywords:etools-plugin", query);
         let from = (page.saturating_sub(1) * page_size) as usize;
+        let url = search_url(query, page_size, from);
 
-        let url = format!(
-            "{}?text={}&size={}&from={}",
-            NPM_SEARCH_API,
-            urlencoding::encode(&search_query),
-            page_size,
-            from
-        );
-
-        let response = self.npm_search(&url)?;
+        let (response, stale) = self.npm_search(&url)?;
 
         let total = response.total as u32;
         let object_count = response.objects.len();
@@ -100,18 +177,52 @@ impl MarketplaceService {
             page,
             page_size,
             has_more,
+            stale,
+        })
+    }
+
+    /// Category and tag counts over the plugins matching `query` (all
+    /// plugins when empty), as `search_plugins` would return them. Every
+    /// match is paged through up to FACET_MAX_MATCHES; counts over fewer
+    /// than all of them, past the cap or offline, are labeled partial.
+    pub fn get_facets(&self, query: &str) -> MarketplaceResult<MarketplaceFacets> {
+        let mut plugins = Vec::new();
+        let mut fetched = 0;
+        let mut total = 0;
+        let mut stale = false;
+        while fetched < FACET_MAX_MATCHES {
+            let (response, page_stale) = match self.npm_search(&search_url(query, FACET_PAGE_SIZE, fetched)) {
+                Ok(page) => page,
+                // Later pages may not be cached while offline
+                Err(_) if fetched > 0 => break,
+                Err(e) => return Err(e),
+            };
+            stale |= page_stale;
+            total = response.total;
+            if response.objects.is_empty() {
+                break;
+            }
+            fetched += response.objects.len();
+            plugins.extend(self.convert_npm_to_marketplace(response.objects, None));
+            if fetched >= total {
+                break;
+            }
+        }
+
+        Ok(MarketplaceFacets {
+            stale,
+            partial: fetched < total,
+            ..compute_facets(&plugins)
         })
     }
 
     /// Install plugin from npm
     pub fn install_plugin(&self, package_name: &str, handle: &AppHandle) -> MarketplaceResult<Plugin> {
         println!("[Marketplace] Installing plugin: {}", package_name);
+        http::ensure_online(NPM_REGISTRY_API)?;
 
         // 1. Get plugins directory
-        let plugins_base = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?
+        let plugins_base = app_paths::data_dir(handle)?
             .join("plugins");
 
         println!("[Marketplace] Plugins base directory: {:?}", plugins_base);
@@ -141,7 +252,7 @@ impl MarketplaceService {
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("npm install failed: {}", error));
+            return Err(format!("npm install failed: {}", error).into());
         }
 
         println!("[Marketplace] npm install successful");
@@ -163,7 +274,7 @@ impl MarketplaceService {
         println!("[Marketplace] Package.json exists: {}", package_path.exists());
 
         if !package_path.exists() {
-            return Err(format!("package.json not found at {:?}", package_path));
+            return Err(format!("package.json not found at {:?}", package_path).into());
         }
 
         println!("[Marketplace] Using package.json at: {:?}", package_path);
@@ -177,6 +288,24 @@ impl MarketplaceService {
         let etools_metadata = package_json.get("etools")
             .and_then(|v| v.as_object());
 
+        // Refuse packages written for a host API this build can't run
+        let api_version = etools_api_version(etools_metadata);
+        if let Some(error) = PluginValidator::new().api_version_error(api_version.as_deref()) {
+            if let Err(e) = self.uninstall_plugin(package_name, handle) {
+                eprintln!("[Marketplace] Failed to remove incompatible package {}: {}", package_name, e);
+            }
+            return Err(error.into());
+        }
+
+        // Only first-party packages may make themselves un-uninstallable
+        let claims_protected = etools_protected(etools_metadata);
+        if let Err(error) = check_protected_claim(package_name, claims_protected) {
+            if let Err(e) = self.uninstall_plugin(package_name, handle) {
+                eprintln!("[Marketplace] Failed to remove package {}: {}", package_name, e);
+            }
+            return Err(error.into());
+        }
+
         // Generate plugin_id from package name if not in etools metadata
         let plugin_id = if let Some(meta) = &etools_metadata {
             meta.get("id")
@@ -265,16 +394,9 @@ impl MarketplaceService {
             .and_then(|v| v.as_str())
             .map(String::from);
 
-        // TODO: Add homepage, repository, category to Plugin struct when needed
-        let _homepage = package_json.get("homepage")
-            .and_then(|v| v.as_str())
-            .or_else(|| etools_metadata.as_ref().and_then(|m| m.get("homepage").and_then(|v| v.as_str())))
-            .map(String::from);
-
-        let _repository = package_json.get("repository")
-            .and_then(|v| v.as_str())
-            .map(String::from);
+        let links = package_links(&package_json);
 
+        // TODO: Add category to Plugin struct when needed
         let _category_str = etools_metadata.as_ref()
             .and_then(|m| m.get("category"))
             .and_then(|v| v.as_str())
@@ -292,7 +414,11 @@ impl MarketplaceService {
             .map_err(|e| format!("Failed to get timestamp: {}", e))?
             .as_millis() as i64;
 
-        // 7. Return Plugin object
+        // 7. Record the install
+        let plugin_dir = package_path.parent().unwrap();
+        let install = record_install(plugin_dir, PluginSource::Marketplace, version)?;
+
+        // 8. Return Plugin object
         Ok(Plugin {
             id: plugin_id.to_string(),
             name: title.to_string(),
@@ -306,8 +432,15 @@ impl MarketplaceService {
                 keyword: t.clone(),
                 description: "".to_string(),
                 hotkey: None,
+                args: Vec::new(),
+                cache_ttl_ms: None,
             }).collect(),
             settings: Default::default(),
+            api_compatibility: check_api_version(api_version.as_deref()),
+            api_version,
+            protected: is_protected(package_name, claims_protected),
+            suppressed_triggers: Vec::new(),
+            links: links.web_only(),
             health: PluginHealth {
                 status: PluginHealthStatus::Healthy,
                 message: Some("Installed from npm".to_string()),
@@ -320,9 +453,9 @@ impl MarketplaceService {
                 last_execution_time: None,
                 average_execution_time: None,
             },
-            installed_at: now,
-            install_path: package_path.parent().unwrap().to_string_lossy().to_string(),
-            source: PluginSource::Marketplace,
+            installed_at: install.installed_at,
+            install_path: plugin_dir.to_string_lossy().to_string(),
+            source: install.source,
         })
     }
 
@@ -330,10 +463,7 @@ impl MarketplaceService {
     pub fn uninstall_plugin(&self, package_name: &str, handle: &AppHandle) -> MarketplaceResult<()> {
         println!("[Marketplace] Uninstalling plugin: {}", package_name);
 
-        let plugins_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?
+        let plugins_dir = app_paths::data_dir(handle)?
             .join("plugins");
 
         // Execute npm uninstall
@@ -345,7 +475,7 @@ impl MarketplaceService {
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("npm uninstall failed: {}", error));
+            return Err(format!("npm uninstall failed: {}", error).into());
         }
 
         println!("[Marketplace] npm uninstall successful");
@@ -355,12 +485,12 @@ impl MarketplaceService {
     /// Update plugin using npm
     pub fn update_plugin(&self, package_name: &str, handle: &AppHandle) -> MarketplaceResult<Plugin> {
         println!("[Marketplace] Updating plugin: {}", package_name);
+        http::ensure_online(NPM_REGISTRY_API)?;
 
-        let plugins_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?
+        let plugins_dir = app_paths::data_dir(handle)?
             .join("plugins");
+        // npm replaces the package folder, install metadata included
+        let previous = recorded_install(&plugins_dir.join("node_modules").join(package_name));
 
         // Execute npm update
         let output = Command::new("npm")
@@ -371,13 +501,22 @@ impl MarketplaceService {
 
         if !output.status.success() {
             let error = String::from_utf8_lossy(&output.stderr);
-            return Err(format!("npm update failed: {}", error));
+            return Err(format!("npm update failed: {}", error).into());
         }
 
         println!("[Marketplace] npm update successful");
 
-        // Re-read the updated package
-        self.install_plugin(package_name, handle)
+        // Re-read the updated package, keeping when it was first installed
+        let mut plugin = self.install_plugin(package_name, handle)?;
+        let install = record_update(
+            Path::new(&plugin.install_path),
+            previous,
+            PluginSource::Marketplace,
+            &plugin.version,
+        )?;
+        plugin.installed_at = install.installed_at;
+        plugin.source = install.source;
+        Ok(plugin)
     }
 
     /// Check for plugin updates
@@ -386,10 +525,7 @@ impl MarketplaceService {
         println!("[Marketplace] Checking for plugin updates...");
 
         // 1. Get installed plugins from package.json
-        let plugins_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?
+        let plugins_dir = app_paths::data_dir(handle)?
             .join("plugins");
 
         let package_json_path = plugins_dir.join("package.json");
@@ -414,6 +550,7 @@ impl MarketplaceService {
         println!("[Marketplace] Checking updates for {} plugins", dependencies.len());
 
         let mut update_info_list = Vec::new();
+        let mut offline = false;
 
         // 3. For each installed plugin, fetch latest version from npm
         for (package_name, _version_spec) in dependencies.iter() {
@@ -426,8 +563,8 @@ impl MarketplaceService {
             }
 
             // Fetch package metadata from npm registry
-            match self.get_latest_version_from_npm(package_name) {
-                Ok(latest_version) => {
+            match self.latest_version(package_name) {
+                Ok((latest_version, stale)) => {
                     // Get currently installed version from node_modules
                     let node_modules_dir = plugins_dir.join("node_modules").join(package_name);
                     let current_version = if node_modules_dir.exists() {
@@ -463,9 +600,11 @@ impl MarketplaceService {
                         current_version,
                         latest_version,
                         has_update,
+                        stale,
                     });
                 }
                 Err(e) => {
+                    offline |= e == MarketplaceError::Offline;
                     println!("[Marketplace] Failed to check updates for {}: {}", package_name, e);
                     // Continue checking other plugins even if one fails
                     continue;
@@ -473,6 +612,11 @@ impl MarketplaceService {
             }
         }
 
+        // Offline with nothing remembered: "no updates" would be misleading
+        if offline && update_info_list.is_empty() {
+            return Err(MarketplaceError::Offline);
+        }
+
         // Filter to only include plugins with updates
         let plugins_with_updates: Vec<PluginUpdateInfo> = update_info_list
             .into_iter()
@@ -484,25 +628,34 @@ impl MarketplaceService {
         Ok(plugins_with_updates)
     }
 
+    /// Latest version of a package, falling back to the last version seen
+    /// while offline. The flag is true for remembered versions.
+    fn latest_version(&self, package_name: &str) -> MarketplaceResult<(String, bool)> {
+        match self.get_latest_version_from_npm(package_name) {
+            Ok(version) => {
+                if let Ok(mut versions) = self.latest_versions.lock() {
+                    versions.insert(package_name.to_string(), version.clone());
+                }
+                Ok((version, false))
+            }
+            Err(MarketplaceError::Offline) => self
+                .latest_versions
+                .lock()
+                .ok()
+                .and_then(|versions| versions.get(package_name).cloned())
+                .map(|version| (version, true))
+                .ok_or(MarketplaceError::Offline),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Get the latest version of a package from npm registry
     fn get_latest_version_from_npm(&self, package_name: &str) -> MarketplaceResult<String> {
         let url = format!("{}/{}", NPM_REGISTRY_API, package_name);
 
         println!("[Marketplace] Fetching package info from: {}", url);
 
-        let client = reqwest::blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-        let response = client.get(&url)
-            .header("User-Agent", "ETools/1.0")
-            .send()
-            .map_err(|e| format!("Failed to fetch package info from npm: {}", e))?;
-
-        if !response.status().is_success() {
-            return Err(format!("npm API returned error for {}: {}", package_name, response.status()));
-        }
+        let response = http::get_blocking_via(self.connectivity, &url)?;
 
         let text = response.text()
             .map_err(|e| format!("Failed to read response: {}", e))?;
@@ -529,29 +682,41 @@ impl MarketplaceService {
     // Private helper methods
     // ========================================================================
 
-    /// Execute npm search API call
-    fn npm_search(&self, url: &str) -> MarketplaceResult<NpmSearchResponse> {
-        let client = reqwest::blocking::Client::builder()
-            .timeout(std::time::Duration::from_secs(10))
-            .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
-
-        let response = client.get(url)
-            .header("User-Agent", "ETools/1.0")
-            .send()
-            .map_err(|e| format!("Failed to fetch from npm: {}", e))?;
-
-        if !response.status().is_success() {
-            return Err(format!("npm API returned error: {}", response.status()));
+    /// Execute npm search API call, reusing a recent response for the same
+    /// URL. While offline an older response is returned instead, flagged
+    /// as stale.
+    fn npm_search(&self, url: &str) -> MarketplaceResult<(NpmSearchResponse, bool)> {
+        let cached = self.search_cache.lock().ok().and_then(|cache| cache.get(url).cloned());
+        if let Some((fetched, response)) = &cached {
+            if fetched.elapsed() < SEARCH_CACHE_TTL {
+                return Ok((response.clone(), false));
+            }
         }
 
+        let response = match http::get_blocking_via(self.connectivity, url) {
+            Ok(response) => response,
+            Err(HttpError::Offline) => {
+                return cached
+                    .map(|(_, response)| (response, true))
+                    .ok_or(MarketplaceError::Offline);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
         let text = response.text()
             .map_err(|e| format!("Failed to read response: {}", e))?;
 
         let search_response: NpmSearchResponse = serde_json::from_str(&text)
             .map_err(|e| format!("Failed to parse npm response: {}", e))?;
 
-        Ok(search_response)
+        if let Ok(mut cache) = self.search_cache.lock() {
+            if cache.len() >= SEARCH_CACHE_CAPACITY {
+                cache.clear();
+            }
+            cache.insert(url.to_string(), (Instant::now(), search_response.clone()));
+        }
+
+        Ok((search_response, false))
     }
 
     /// Convert npm search results to marketplace plugins
@@ -565,21 +730,12 @@ impl MarketplaceService {
             .filter_map(|obj| {
                 let package = obj.package;
 
+                let category = parse_category_from_keywords(&package.keywords);
+
                 // Filter by category if specified
                 if let Some(cat) = category_filter {
-                    if cat != "all" {
-                        // Try to get category from package keywords or etools metadata
-                        let package_cat = package.keywords.iter()
-                            .find(|k| {
-                                matches!(k.as_str(),
-                                    "productivity" | "developer" | "utilities" |
-                                    "search" | "media" | "integration"
-                                )
-                            });
-
-                        if package_cat.map(|k| k.as_str()) != Some(cat) {
-                            return None;
-                        }
+                    if cat != "all" && !category.as_str().eq_ignore_ascii_case(cat) {
+                        return None;
                     }
                 }
 
@@ -607,7 +763,7 @@ impl MarketplaceService {
                     download_count: 0, // npm search doesn't provide this
                     rating: 0.0,       // npm search doesn't provide this
                     rating_count: 0,
-                    category: Self::parse_category_from_keywords(&package.keywords),
+                    category,
                     installed: false,
                     installed_version: None,
                     update_available: false,
@@ -645,26 +801,7 @@ impl MarketplaceService {
     }
 
     fn parse_category(category_str: &str) -> PluginCategory {
-        match category_str.to_lowercase().as_str() {
-            "productivity" => PluginCategory::Productivity,
-            "developer" => PluginCategory::Developer,
-            "utilities" => PluginCategory::Utilities,
-            "search" => PluginCategory::Search,
-            "media" => PluginCategory::Media,
-            "integration" => PluginCategory::Integration,
-            _ => PluginCategory::Utilities,
-        }
-    }
-
-    fn parse_category_from_keywords(keywords: &[String]) -> PluginCategory {
-        for keyword in keywords {
-            let cat = Self::parse_category(keyword);
-            // Return first valid category that isn't Utilities (default)
-            if !matches!(cat, PluginCategory::Utilities) {
-                return cat;
-            }
-        }
-        PluginCategory::Utilities
+        PluginCategory::from_name(category_str).unwrap_or(PluginCategory::Utilities)
     }
 
     /// List installed npm plugins
@@ -673,13 +810,9 @@ impl MarketplaceService {
     pub fn list_installed_plugins(&self, handle: &AppHandle) -> MarketplaceResult<Vec<Plugin>> {
         println!("[Marketplace] list_installed_plugins called");
 
-        let app_data_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| {
-                println!("[Marketplace] Failed to get app data dir: {}", e);
-                format!("Failed to get data dir: {}", e)
-            })?;
+        let app_data_dir = app_paths::data_dir(handle).inspect_err(|e| {
+            println!("[Marketplace] Failed to get app data dir: {}", e);
+        })?;
 
         println!("[Marketplace] App data dir: {:?}", app_data_dir);
 
@@ -867,17 +1000,14 @@ impl MarketplaceService {
                     keyword: keyword.clone(),
                     description: format!("Trigger: {}", keyword),
                     hotkey: None,
+                    args: Vec::new(),
+                    cache_ttl_ms: None,
                 })
                 .collect();
 
-            // Get installation time
-            let installed_at = path
-                .metadata()
-                .and_then(|m| m.modified())
-                .ok()
-                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
-                .map(|d| d.as_millis() as i64)
-                .unwrap_or(0);
+            let api_version = etools_api_version(etools_metadata);
+
+            let install = read_install_meta(&path, PluginSource::Marketplace, &version)?;
 
             plugins.push(Plugin {
                 id: plugin_id.clone(),
@@ -890,6 +1020,11 @@ impl MarketplaceService {
                 entry_point: format!("@etools-plugin/{}", path.file_name().unwrap().to_string_lossy()),
                 triggers: plugin_triggers,
                 settings: Default::default(),
+                api_compatibility: check_api_version(api_version.as_deref()),
+                api_version,
+                protected: is_protected(package_name, etools_protected(etools_metadata)),
+                suppressed_triggers: Vec::new(),
+                links: package_links(&package_json).web_only(),
                 health: PluginHealth {
                     status: PluginHealthStatus::Healthy,
                     message: Some("Installed from npm".to_string()),
@@ -905,9 +1040,9 @@ impl MarketplaceService {
                     last_execution_time: None,
                     average_execution_time: None,
                 },
-                installed_at: installed_at,
+                installed_at: install.installed_at,
                 install_path: path.to_string_lossy().to_string(),
-                source: PluginSource::Marketplace,
+                source: install.source,
             });
         }
 
@@ -916,45 +1051,328 @@ impl MarketplaceService {
     }
 }
 
+/// `api_version` from a package's "etools" metadata
+fn etools_api_version(etools_metadata: Option<&serde_json::Map<String, Value>>) -> Option<String> {
+    etools_metadata?.get("api_version")?.as_str().map(String::from)
+}
+
+/// Homepage, repository and license from the `etools` block, with the ones
+/// it leaves out taken from package.json's own fields
+pub fn package_links(package_json: &Value) -> PluginLinks {
+    package_json
+        .get("etools")
+        .map(PluginLinks::from_package_json)
+        .unwrap_or_default()
+        .or(PluginLinks::from_package_json(package_json))
+}
+
+/// `package_links` of the package.json in `plugin_dir`; none when there's
+/// no readable one
+pub fn read_package_links(plugin_dir: &Path) -> PluginLinks {
+    fs::read_to_string(plugin_dir.join("package.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .map(|package_json| package_links(&package_json))
+        .unwrap_or_default()
+}
+
+/// Whether the `etools` block claims the plugin is protected
+fn etools_protected(etools_metadata: Option<&serde_json::Map<String, Value>>) -> bool {
+    etools_metadata
+        .and_then(|meta| meta.get("protected"))
+        .and_then(Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// npm search text for a marketplace query; empty lists every plugin
+fn search_url(query: &str, size: u32, from: usize) -> String {
+    let query = query.trim();
+    let text = if query.is_empty() {
+        format!("keywords:{}", PLUGIN_KEYWORD)
+    } else {
+        format!("{} keywords:{}", query, PLUGIN_KEYWORD)
+    };
+
+    format!(
+        "{}?text={}&size={}&from={}",
+        NPM_SEARCH_API,
+        urlencoding::encode(&text),
+        size,
+        from
+    )
+}
+
+/// Category of a package from its keywords. An `etools-category:<name>`
+/// keyword wins, unknown names mapping to Other; older packages list the
+/// bare category name. Anything else is Utilities.
+fn parse_category_from_keywords(keywords: &[String]) -> PluginCategory {
+    let declared = keywords.iter().find_map(|k| {
+        let k = k.to_lowercase();
+        k.strip_prefix(CATEGORY_KEYWORD_PREFIX).map(|name| name.trim().to_string())
+    });
+    if let Some(name) = declared {
+        return PluginCategory::from_name(&name).unwrap_or(PluginCategory::Other);
+    }
+
+    keywords
+        .iter()
+        .filter_map(|k| PluginCategory::from_name(k))
+        .find(|c| *c != PluginCategory::Other)
+        .unwrap_or(PluginCategory::Utilities)
+}
+
+/// Keywords shown as tags: everything but the marketplace marker and
+/// category keywords, which the category facet already covers
+fn is_tag_keyword(keyword: &str) -> bool {
+    !keyword.eq_ignore_ascii_case(PLUGIN_KEYWORD)
+        && !keyword.to_lowercase().starts_with(CATEGORY_KEYWORD_PREFIX)
+        && PluginCategory::from_name(keyword).is_none()
+}
+
+/// Most frequent first, ties by value
+fn sorted_counts(counts: HashMap<String, u32>) -> Vec<FacetCount> {
+    let mut counts: Vec<FacetCount> = counts
+        .into_iter()
+        .map(|(value, count)| FacetCount { value, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    counts
+}
+
+/// Category and tag counts over `plugins`
+pub fn compute_facets(plugins: &[MarketplacePlugin]) -> MarketplaceFacets {
+    let mut categories: HashMap<String, u32> = HashMap::new();
+    let mut tags: HashMap<String, u32> = HashMap::new();
+
+    for plugin in plugins {
+        *categories.entry(plugin.category.as_str().to_string()).or_default() += 1;
+
+        let mut seen: Vec<String> = plugin
+            .tags
+            .iter()
+            .filter(|t| is_tag_keyword(t))
+            .map(|t| t.trim().to_lowercase())
+            .filter(|t| !t.is_empty())
+            .collect();
+        seen.sort();
+        seen.dedup();
+        for tag in seen {
+            *tags.entry(tag).or_default() += 1;
+        }
+    }
+
+    let mut tags = sorted_counts(tags);
+    tags.truncate(TOP_TAGS);
+
+    MarketplaceFacets {
+        total: plugins.len() as u32,
+        categories: sorted_counts(categories),
+        tags,
+        stale: false,
+        partial: false,
+    }
+}
+
 // ============================================================================
 // NPM API Types
 // ============================================================================
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 struct NpmSearchResponse {
     objects: Vec<NpmSearchObject>,
     total: usize,
     time: String,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 struct NpmSearchObject {
     package: NpmPackage,
     score: NpmScore,
     searchScore: f64,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 struct NpmPackage {
     name: String,
     version: String,
+    #[serde(default)]
     description: String,
+    #[serde(default)]
     keywords: Vec<String>,
     // author can be various types
     #[serde(default)]
     author: serde_json::Value,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 struct NpmScore {
     #[serde(rename = "final")]
     final_score: f64,
     detail: NpmScoreDetail,
 }
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 struct NpmScoreDetail {
     quality: f64,
     popularity: f64,
     maintenance: f64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../tests/fixtures/marketplace/npm_search.json");
+
+    fn fixture_plugins(category: Option<&str>) -> Vec<MarketplacePlugin> {
+        let response: NpmSearchResponse = serde_json::from_str(FIXTURE).unwrap();
+        MarketplaceService::new().convert_npm_to_marketplace(response.objects, category)
+    }
+
+    fn counts(facets: &[FacetCount]) -> Vec<(&str, u32)> {
+        facets.iter().map(|f| (f.value.as_str(), f.count)).collect()
+    }
+
+    fn keywords(list: &[&str]) -> Vec<String> {
+        list.iter().map(|k| k.to_string()).collect()
+    }
+
+    #[test]
+    fn test_category_from_keywords() {
+        let category = |list: &[&str]| parse_category_from_keywords(&keywords(list));
+
+        assert_eq!(category(&["etools-plugin", "etools-category:developer"]), PluginCategory::Developer);
+        assert_eq!(category(&["etools-category:Media"]), PluginCategory::Media);
+        assert_eq!(category(&["etools-category:finance"]), PluginCategory::Other);
+        // The declared category wins over bare names
+        assert_eq!(category(&["search", "etools-category:media"]), PluginCategory::Media);
+        assert_eq!(category(&["etools-plugin", "productivity"]), PluginCategory::Productivity);
+        assert_eq!(category(&["etools-plugin", "other"]), PluginCategory::Utilities);
+        assert_eq!(category(&["etools-plugin"]), PluginCategory::Utilities);
+        assert_eq!(category(&[]), PluginCategory::Utilities);
+    }
+
+    #[test]
+    fn test_fixture_categories_and_filter() {
+        let plugins = fixture_plugins(None);
+        assert_eq!(plugins.len(), 6);
+        let by_id: HashMap<&str, PluginCategory> =
+            plugins.iter().map(|p| (p.id.as_str(), p.category)).collect();
+        assert_eq!(by_id["jsonformatter"], PluginCategory::Developer);
+        assert_eq!(by_id["stocks"], PluginCategory::Other);
+        assert_eq!(by_id["hello"], PluginCategory::Utilities);
+
+        let developer = fixture_plugins(Some("developer"));
+        assert_eq!(developer.len(), 2);
+        assert_eq!(fixture_plugins(Some("other")).len(), 1);
+        assert_eq!(fixture_plugins(Some("all")).len(), 6);
+    }
+
+    #[test]
+    fn test_facet_counts_match_filtering() {
+        let facets = compute_facets(&fixture_plugins(None));
+
+        assert_eq!(facets.total, 6);
+        assert_eq!(
+            counts(&facets.categories),
+            vec![("developer", 2), ("media", 1), ("other", 1), ("productivity", 1), ("utilities", 1)]
+        );
+        for facet in &facets.categories {
+            assert_eq!(fixture_plugins(Some(&facet.value)).len() as u32, facet.count, "{}", facet.value);
+        }
+
+        // Marker and category keywords are left out; tags count once per
+        // plugin, ignoring case
+        assert_eq!(
+            counts(&facets.tags),
+            vec![("json", 2), ("formatter", 1), ("git", 1), ("image", 1), ("stocks", 1), ("todo", 1)]
+        );
+    }
+
+    #[test]
+    fn test_search_url_scopes_to_plugins() {
+        assert!(search_url("", 20, 0).contains("text=keywords%3Aetools-plugin&size=20&from=0"));
+        assert!(search_url(" json ", 250, 0).contains("text=json%20keywords%3Aetools-plugin&size=250"));
+    }
+
+    #[test]
+    fn test_package_links_prefer_etools_block() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(read_package_links(dir.path()), PluginLinks::default());
+
+        let package_json = serde_json::json!({
+            "name": "@etools-plugin/hello",
+            "homepage": "https://www.npmjs.com/package/@etools-plugin/hello",
+            "repository": { "type": "git", "url": "git+https://github.com/acme/hello.git" },
+            "license": "MIT",
+            "etools": { "id": "hello", "homepage": "https://hello.example" },
+        });
+        fs::write(dir.path().join("package.json"), package_json.to_string()).unwrap();
+
+        let links = read_package_links(dir.path());
+        assert_eq!(links, package_links(&package_json));
+        assert_eq!(links.homepage.as_deref(), Some("https://hello.example"));
+        assert_eq!(links.repository.as_deref(), Some("https://github.com/acme/hello"));
+        assert_eq!(links.license.as_deref(), Some("MIT"));
+    }
+
+    #[test]
+    fn test_offline_serves_stale_cache() {
+        let connectivity: &'static ConnectivityMonitor = Box::leak(Box::new(ConnectivityMonitor::new()));
+        let service = MarketplaceService::with_connectivity(connectivity);
+        let cached_url = search_url("json", 20, 0);
+        let expired = Instant::now() - SEARCH_CACHE_TTL - Duration::from_secs(1);
+        service
+            .search_cache
+            .lock()
+            .unwrap()
+            .insert(cached_url.clone(), (expired, serde_json::from_str(FIXTURE).unwrap()));
+        service
+            .latest_versions
+            .lock()
+            .unwrap()
+            .insert("@etools-plugin/hello".to_string(), "1.2.0".to_string());
+
+        // Offline mode keeps these from reaching the registry
+        connectivity.set_forced(true);
+
+        let (response, stale) = service.npm_search(&cached_url).unwrap();
+        assert!(stale);
+        assert_eq!(response.objects.len(), 6);
+        assert_eq!(service.npm_search(&search_url("git", 20, 0)).unwrap_err(), MarketplaceError::Offline);
+        assert_eq!(service.latest_version("@etools-plugin/hello").unwrap(), ("1.2.0".to_string(), true));
+        assert_eq!(service.latest_version("@etools-plugin/stocks").unwrap_err(), MarketplaceError::Offline);
+    }
+
+    #[test]
+    fn test_facets_page_through_every_match() {
+        let connectivity: &'static ConnectivityMonitor = Box::leak(Box::new(ConnectivityMonitor::new()));
+        let service = MarketplaceService::with_connectivity(connectivity);
+        let mut first: NpmSearchResponse = serde_json::from_str(FIXTURE).unwrap();
+        let second = NpmSearchResponse {
+            objects: first.objects.split_off(4),
+            ..first.clone()
+        };
+        let fetched = Instant::now();
+        service
+            .search_cache
+            .lock()
+            .unwrap()
+            .insert(search_url("", FACET_PAGE_SIZE, 0), (fetched, first));
+        connectivity.set_forced(true);
+
+        // Only the first page is cached
+        let facets = service.get_facets("").unwrap();
+        assert_eq!(facets.total, 4);
+        assert!(facets.partial);
+
+        service
+            .search_cache
+            .lock()
+            .unwrap()
+            .insert(search_url("", FACET_PAGE_SIZE, 4), (fetched, second));
+        let facets = service.get_facets("").unwrap();
+        assert_eq!(facets, MarketplaceFacets { stale: false, ..compute_facets(&fixture_plugins(None)) });
+        assert!(!facets.partial);
+    }
+}
@@ -1,18 +1,74 @@
+pub mod analytics;
 pub mod app_monitor;
+pub mod app_paths;
+pub mod app_sources;
+pub mod app_update;
+pub mod app_watcher;
+pub mod backup;
+pub mod bookmark_folders;
+pub mod browser_match;
 pub mod browser_reader;
+pub mod clipboard_images;
+pub mod clipboard_search;
 pub mod clipboard_watcher;
+pub mod cloud_files;
 pub mod config_service;
+pub mod connectivity;
+pub mod crash_reporter;
+pub mod data_access;
+pub mod data_reset;
+pub mod db_maintenance;
+pub mod drop_actions;
+pub mod favicon_color;
 pub mod file_indexer;
+pub mod file_preview;
+pub mod file_tags;
+pub mod http;
+pub mod i18n;
+pub mod install_jobs;
+pub mod launcher_visibility;
 pub mod marketplace_service;
+pub mod notifications;
+pub mod onboarding;
+pub mod openers;
+pub mod package_transfer;
+pub mod path_display;
+pub mod path_tools;
 pub mod performance;
+pub mod plugin_api;
+pub mod plugin_archive;
 pub mod plugin_errors;
 pub mod plugin_installer;
+pub mod plugin_host;
+pub mod plugin_install_meta;
 pub mod plugin_performance;
+pub mod plugin_protection;
+pub mod plugin_rate_limiter;
+pub mod plugin_registry;
+pub mod plugin_result_cache;
 pub mod plugin_sandbox;
 pub mod plugin_service;
+pub mod plugin_storage;
 pub mod plugin_validator;
+pub mod privacy;
+pub mod process_manager;
+pub mod query_history;
+pub mod retention;
 pub mod screen_detector;
+pub mod search;
+pub mod search_diagnostics;
+pub mod selection_capture;
+pub mod shell_exec;
+pub mod shell_history;
+pub mod shutdown;
+pub mod ssh_hosts;
+pub mod startup;
+pub mod task_supervisor;
+pub mod trigger_completions;
+pub mod trigger_conflicts;
 pub mod window_calculator;
+pub mod window_layouts;
+pub mod windows;
 
 pub use screen_detector::detect_screen_info;
 pub use window_calculator::calculate_window_layout;
@@ -0,0 +1,265 @@
+//! Notification Service
+//! Sends system notifications for the app and for plugins, honoring the
+//! global do-not-disturb setting and per-plugin mutes, and keeps a short
+//! history including suppressed notifications.
+
+use crate::models::preferences::{DoNotDisturb, QuietHours};
+use serde::Serialize;
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Notifications kept in history
+pub const HISTORY_CAPACITY: usize = 50;
+
+/// Per-plugin mute file, next to plugin-settings.json
+const MUTED_PLUGINS_FILE: &str = "plugin-notification-mutes.json";
+
+/// Source shown for notifications the app sends itself
+pub const APP_SOURCE: &str = "app";
+
+/// Why a notification wasn't shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SuppressReason {
+    DoNotDisturb,
+    Muted,
+}
+
+/// A sent or suppressed notification
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationRecord {
+    pub id: u64,
+    pub title: String,
+    pub body: Option<String>,
+    /// "app" or the sending plugin's ID
+    pub source: String,
+    /// Unix timestamp (ms)
+    pub timestamp: i64,
+    pub suppressed: bool,
+    pub suppress_reason: Option<SuppressReason>,
+}
+
+/// Check that `source` is the app or one of the `installed` plugins, so
+/// notifications can't be sent in the name of a plugin that doesn't exist
+pub fn check_source(source: &str, installed: &HashSet<String>) -> Result<(), String> {
+    if source == APP_SOURCE || installed.contains(source) {
+        Ok(())
+    } else {
+        Err(format!("Unknown notification source: {}", source))
+    }
+}
+
+/// Parse "HH:MM" into minutes since midnight
+pub fn parse_time_of_day(value: &str) -> Option<u32> {
+    let (hours, minutes) = value.trim().split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Whether `minute_of_day` falls inside the quiet hours. Start is
+/// inclusive, end exclusive; equal start and end means no quiet hours.
+pub fn in_quiet_hours(schedule: &QuietHours, minute_of_day: u32) -> bool {
+    let (Some(start), Some(end)) = (parse_time_of_day(&schedule.start), parse_time_of_day(&schedule.end)) else {
+        return false;
+    };
+
+    if start <= end {
+        (start..end).contains(&minute_of_day)
+    } else {
+        // Wraps past midnight, e.g. 22:00–08:00
+        minute_of_day >= start || minute_of_day < end
+    }
+}
+
+/// Whether do-not-disturb is in effect at `minute_of_day` (local time)
+pub fn dnd_active(dnd: &DoNotDisturb, minute_of_day: u32) -> bool {
+    dnd.enabled
+        || dnd
+            .schedule
+            .as_ref()
+            .is_some_and(|schedule| in_quiet_hours(schedule, minute_of_day))
+}
+
+struct NotificationsInner {
+    history: VecDeque<NotificationRecord>,
+    muted: HashSet<String>,
+    next_id: u64,
+}
+
+/// Notification history and per-plugin mutes
+pub struct NotificationCenter {
+    inner: Mutex<NotificationsInner>,
+    muted_path: Option<PathBuf>,
+}
+
+impl NotificationCenter {
+    /// Create a center, loading per-plugin mutes from `data_dir` if given
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        let muted_path = data_dir.map(|dir| dir.join(MUTED_PLUGINS_FILE));
+        let muted = muted_path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str::<Vec<String>>(&content).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+
+        Self {
+            inner: Mutex::new(NotificationsInner {
+                history: VecDeque::new(),
+                muted,
+                next_id: 1,
+            }),
+            muted_path,
+        }
+    }
+
+    /// Decide whether a notification from `source` should be suppressed
+    pub fn suppress_reason(&self, source: &str, dnd: &DoNotDisturb, minute_of_day: u32) -> Option<SuppressReason> {
+        if source != APP_SOURCE && self.is_muted(source) {
+            return Some(SuppressReason::Muted);
+        }
+        dnd_active(dnd, minute_of_day).then_some(SuppressReason::DoNotDisturb)
+    }
+
+    /// Add a notification to history, dropping the oldest past HISTORY_CAPACITY
+    pub fn record(&self, title: &str, body: Option<&str>, source: &str, suppress_reason: Option<SuppressReason>) -> NotificationRecord {
+        let mut inner = self.inner.lock().unwrap();
+
+        let record = NotificationRecord {
+            id: inner.next_id,
+            title: title.to_string(),
+            body: body.map(|b| b.to_string()),
+            source: source.to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            suppressed: suppress_reason.is_some(),
+            suppress_reason,
+        };
+        inner.next_id += 1;
+
+        inner.history.push_back(record.clone());
+        while inner.history.len() > HISTORY_CAPACITY {
+            inner.history.pop_front();
+        }
+
+        record
+    }
+
+    /// History, newest first
+    pub fn history(&self) -> Vec<NotificationRecord> {
+        let inner = self.inner.lock().unwrap();
+        inner.history.iter().rev().cloned().collect()
+    }
+
+    pub fn is_muted(&self, plugin_id: &str) -> bool {
+        self.inner.lock().unwrap().muted.contains(plugin_id)
+    }
+
+    /// Mute or unmute a plugin's notifications and persist the change
+    pub fn set_muted(&self, plugin_id: &str, muted: bool) -> Result<(), String> {
+        let mut list: Vec<String> = {
+            let mut inner = self.inner.lock().unwrap();
+            if muted {
+                inner.muted.insert(plugin_id.to_string());
+            } else {
+                inner.muted.remove(plugin_id);
+            }
+            inner.muted.iter().cloned().collect()
+        };
+        list.sort();
+
+        let Some(path) = &self.muted_path else {
+            return Ok(());
+        };
+        let content = serde_json::to_string_pretty(&list)
+            .map_err(|e| format!("Failed to serialize notification mutes: {}", e))?;
+        fs::write(path, content).map_err(|e| format!("Failed to write notification mutes: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet(start: &str, end: &str) -> QuietHours {
+        QuietHours { start: start.to_string(), end: end.to_string() }
+    }
+
+    #[test]
+    fn test_overnight_schedule_boundaries() {
+        let schedule = quiet("22:00", "08:00");
+
+        assert!(!in_quiet_hours(&schedule, 21 * 60 + 59));
+        assert!(in_quiet_hours(&schedule, 22 * 60));
+        assert!(in_quiet_hours(&schedule, 0));
+        assert!(in_quiet_hours(&schedule, 7 * 60 + 59));
+        assert!(!in_quiet_hours(&schedule, 8 * 60));
+    }
+
+    #[test]
+    fn test_same_day_and_empty_schedules() {
+        let schedule = quiet("13:00", "14:30");
+        assert!(!in_quiet_hours(&schedule, 12 * 60 + 59));
+        assert!(in_quiet_hours(&schedule, 13 * 60));
+        assert!(!in_quiet_hours(&schedule, 14 * 60 + 30));
+
+        assert!(!in_quiet_hours(&quiet("09:00", "09:00"), 9 * 60));
+        assert!(!in_quiet_hours(&quiet("25:00", "08:00"), 0));
+    }
+
+    #[test]
+    fn test_dnd_suppression_is_recorded() {
+        let center = NotificationCenter::new(None);
+        let dnd = DoNotDisturb { enabled: false, schedule: Some(quiet("22:00", "08:00")) };
+
+        let reason = center.suppress_reason(APP_SOURCE, &dnd, 23 * 60);
+        assert_eq!(reason, Some(SuppressReason::DoNotDisturb));
+        center.record("Index complete", None, APP_SOURCE, reason);
+
+        assert_eq!(center.suppress_reason(APP_SOURCE, &dnd, 12 * 60), None);
+        let history = center.history();
+        assert_eq!(history.len(), 1);
+        assert!(history[0].suppressed);
+    }
+
+    #[test]
+    fn test_per_plugin_mute_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let dnd = DoNotDisturb::default();
+
+        let center = NotificationCenter::new(Some(dir.path()));
+        center.set_muted("noisy", true).unwrap();
+        assert_eq!(center.suppress_reason("noisy", &dnd, 0), Some(SuppressReason::Muted));
+        assert_eq!(center.suppress_reason("quiet", &dnd, 0), None);
+
+        let reloaded = NotificationCenter::new(Some(dir.path()));
+        assert!(reloaded.is_muted("noisy"));
+        reloaded.set_muted("noisy", false).unwrap();
+        assert_eq!(reloaded.suppress_reason("noisy", &dnd, 0), None);
+    }
+
+    #[test]
+    fn test_source_must_be_app_or_installed_plugin() {
+        let installed: HashSet<String> = ["weather".to_string()].into_iter().collect();
+        assert!(check_source(APP_SOURCE, &installed).is_ok());
+        assert!(check_source("weather", &installed).is_ok());
+        assert!(check_source("Weather", &installed).is_err());
+        assert!(check_source("", &installed).is_err());
+        assert!(check_source("weather", &HashSet::new()).is_err());
+    }
+
+    #[test]
+    fn test_history_is_capped() {
+        let center = NotificationCenter::new(None);
+        for i in 0..HISTORY_CAPACITY + 5 {
+            center.record(&format!("n{}", i), None, APP_SOURCE, None);
+        }
+
+        let history = center.history();
+        assert_eq!(history.len(), HISTORY_CAPACITY);
+        assert_eq!(history[0].title, format!("n{}", HISTORY_CAPACITY + 4));
+    }
+}
@@ -0,0 +1,312 @@
+//! Onboarding
+//! Progress through the first-run steps, kept in a state file so the UI can
+//! resume where the user left off after quitting midway. Steps already set
+//! up, such as a hotkey changed before onboarding existed, are detected and
+//! count as completed.
+//!
+//! Onboarding is versioned. A release that adds steps bumps
+//! `ONBOARDING_VERSION` and gives them that `introduced_in`; users who went
+//! through an earlier version are shown only those steps, once.
+
+use crate::models::preferences::AppSettings;
+use crate::services::app_paths;
+use crate::utils::{managed_store, StateStore};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, Runtime, State};
+
+/// Version of the onboarding steps this build has
+pub const ONBOARDING_VERSION: u32 = 1;
+
+/// One step of onboarding, in the order they're shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    Hotkey,
+    DiskAccess,
+    IndexFolders,
+    Browsers,
+    Abbreviations,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 5] = [
+        OnboardingStep::Hotkey,
+        OnboardingStep::DiskAccess,
+        OnboardingStep::IndexFolders,
+        OnboardingStep::Browsers,
+        OnboardingStep::Abbreviations,
+    ];
+
+    /// Onboarding version that added the step
+    pub fn introduced_in(self) -> u32 {
+        match self {
+            OnboardingStep::Hotkey
+            | OnboardingStep::DiskAccess
+            | OnboardingStep::IndexFolders
+            | OnboardingStep::Browsers
+            | OnboardingStep::Abbreviations => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    #[default]
+    Pending,
+    Completed,
+    Skipped,
+}
+
+/// What happened to a step
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StepRecord {
+    pub status: StepStatus,
+    /// Completed because it was found already set up, not by the user
+    #[serde(default)]
+    pub detected: bool,
+    /// When the status last changed (unix seconds)
+    pub updated_at: i64,
+}
+
+/// Contents of the onboarding state file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OnboardingState {
+    /// Latest onboarding version the user finished or skipped; None until
+    /// the first run is through
+    #[serde(default)]
+    pub finished_version: Option<u32>,
+    /// Steps that left pending, whichever version they were shown in
+    #[serde(default)]
+    pub steps: BTreeMap<OnboardingStep, StepRecord>,
+}
+
+impl OnboardingState {
+    pub fn status(&self, step: OnboardingStep) -> StepStatus {
+        self.steps.get(&step).map_or(StepStatus::Pending, |record| record.status)
+    }
+
+    /// Steps of this run: all of them on the first run, afterwards those
+    /// added since the version the user last finished
+    pub fn offered_steps(&self) -> Vec<OnboardingStep> {
+        OnboardingStep::ALL
+            .into_iter()
+            .filter(|step| self.finished_version.is_none_or(|version| step.introduced_in() > version))
+            .collect()
+    }
+
+    /// The step to show, None when there is nothing left to do
+    pub fn current_step(&self) -> Option<OnboardingStep> {
+        self.offered_steps()
+            .into_iter()
+            .find(|step| self.status(*step) == StepStatus::Pending)
+    }
+
+    /// Mark `satisfied` steps that are still pending as completed
+    pub fn detect(&mut self, satisfied: &[OnboardingStep], now: i64) {
+        for step in satisfied {
+            if self.status(*step) == StepStatus::Pending {
+                self.set(*step, StepStatus::Completed, true, now);
+            }
+        }
+        self.finish_if_done();
+    }
+
+    /// Mark a step completed. Completing it again is fine, e.g. when the
+    /// user goes back to change their hotkey.
+    pub fn complete(&mut self, step: OnboardingStep, now: i64) {
+        if self.steps.get(&step).is_none_or(|record| record.status != StepStatus::Completed || record.detected) {
+            self.set(step, StepStatus::Completed, false, now);
+        }
+        self.finish_if_done();
+    }
+
+    /// Skip the rest of this run's steps
+    pub fn skip_remaining(&mut self, now: i64) {
+        for step in self.offered_steps() {
+            if self.status(step) == StepStatus::Pending {
+                self.set(step, StepStatus::Skipped, false, now);
+            }
+        }
+        self.finish_if_done();
+    }
+
+    fn set(&mut self, step: OnboardingStep, status: StepStatus, detected: bool, now: i64) {
+        self.steps.insert(step, StepRecord { status, detected, updated_at: now });
+    }
+
+    fn finish_if_done(&mut self) {
+        if self.current_step().is_none() {
+            self.finished_version = Some(ONBOARDING_VERSION);
+        }
+    }
+
+    /// What the UI shows
+    pub fn view(&self) -> OnboardingView {
+        let current = self.current_step();
+        OnboardingView {
+            version: ONBOARDING_VERSION,
+            first_run: self.finished_version.is_none(),
+            finished: current.is_none(),
+            current,
+            steps: self
+                .offered_steps()
+                .into_iter()
+                .map(|step| StepView {
+                    step,
+                    status: self.status(step),
+                    detected: self.steps.get(&step).is_some_and(|record| record.detected),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A step as the UI shows it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StepView {
+    pub step: OnboardingStep,
+    pub status: StepStatus,
+    pub detected: bool,
+}
+
+/// Onboarding as the UI shows it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OnboardingView {
+    pub version: u32,
+    /// The whole flow, as opposed to steps added by an update
+    pub first_run: bool,
+    pub finished: bool,
+    /// The step to resume at
+    pub current: Option<OnboardingStep>,
+    pub steps: Vec<StepView>,
+}
+
+/// What is already set up, as far as onboarding steps go
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SetupFacts {
+    /// No browser profile or indexed folder was refused for lack of permission
+    pub disk_access_granted: bool,
+    /// The abbreviation config has entries the user added or imported, not
+    /// just the defaults seeded on first read
+    pub has_user_abbreviations: bool,
+}
+
+/// Steps whose setup is already done
+pub fn satisfied_steps(settings: &AppSettings, facts: SetupFacts) -> Vec<OnboardingStep> {
+    OnboardingStep::ALL
+        .into_iter()
+        .filter(|step| match step {
+            OnboardingStep::Hotkey => settings.global_hotkey != AppSettings::default().global_hotkey,
+            OnboardingStep::DiskAccess => facts.disk_access_granted,
+            OnboardingStep::IndexFolders => !settings.file_index_paths.is_empty(),
+            OnboardingStep::Browsers => settings.enable_browser_search,
+            OnboardingStep::Abbreviations => facts.has_user_abbreviations,
+        })
+        .collect()
+}
+
+pub fn onboarding_store<R: Runtime>(
+    handle: &AppHandle<R>,
+) -> Result<State<'_, StateStore<OnboardingState>>, String> {
+    managed_store(handle, || {
+        let path = app_paths::data_dir(handle)?.join("onboarding.json");
+        Ok(StateStore::new(path, "onboarding state"))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn store(dir: &Path) -> StateStore<OnboardingState> {
+        StateStore::new(dir.join("onboarding.json"), "onboarding state")
+    }
+
+    #[test]
+    fn test_resumes_after_quit() {
+        let dir = tempfile::tempdir().unwrap();
+        store(dir.path())
+            .update(|state| {
+                state.detect(&[], 1);
+                state.complete(OnboardingStep::Hotkey, 2);
+                state.complete(OnboardingStep::DiskAccess, 3);
+                Ok::<_, String>(())
+            })
+            .unwrap();
+
+        // A new store reads the file as a restarted app would
+        let state = store(dir.path()).read().unwrap();
+        let view = state.view();
+        assert!(view.first_run && !view.finished);
+        assert_eq!(view.current, Some(OnboardingStep::IndexFolders));
+        assert_eq!(view.steps.len(), OnboardingStep::ALL.len());
+
+        // Re-running a finished step keeps the place
+        let mut state = state;
+        state.complete(OnboardingStep::Hotkey, 4);
+        assert_eq!(state.current_step(), Some(OnboardingStep::IndexFolders));
+        assert_eq!(state.steps[&OnboardingStep::Hotkey].updated_at, 2);
+
+        state.skip_remaining(5);
+        let view = state.view();
+        assert!(view.finished && !view.first_run);
+        assert_eq!(state.finished_version, Some(ONBOARDING_VERSION));
+        assert_eq!(state.status(OnboardingStep::Browsers), StepStatus::Skipped);
+        assert_eq!(state.status(OnboardingStep::DiskAccess), StepStatus::Completed);
+    }
+
+    #[test]
+    fn test_already_satisfied_steps_are_complete() {
+        let settings = AppSettings {
+            global_hotkey: "Alt+Space".to_string(),
+            file_index_paths: vec!["/Users/me/Documents".to_string()],
+            ..AppSettings::default()
+        };
+        let facts = SetupFacts { disk_access_granted: true, has_user_abbreviations: false };
+        let satisfied = satisfied_steps(&settings, facts);
+        assert_eq!(
+            satisfied,
+            vec![OnboardingStep::Hotkey, OnboardingStep::DiskAccess, OnboardingStep::IndexFolders]
+        );
+        assert!(satisfied_steps(&AppSettings::default(), SetupFacts::default()).is_empty());
+
+        let mut state = OnboardingState::default();
+        state.detect(&satisfied, 1);
+        assert_eq!(state.current_step(), Some(OnboardingStep::Browsers));
+        assert!(state.view().steps[0].detected);
+
+        // A skipped step stays skipped even once its setup is done
+        state.skip_remaining(2);
+        state.detect(&[OnboardingStep::Browsers], 3);
+        assert_eq!(state.status(OnboardingStep::Browsers), StepStatus::Skipped);
+
+        // Everything already set up finishes onboarding on its own
+        let mut state = OnboardingState::default();
+        state.detect(&OnboardingStep::ALL, 1);
+        assert!(state.view().finished);
+    }
+
+    #[test]
+    fn test_steps_from_later_versions_show_once() {
+        // Finished before the current version, with a step it never saw
+        let mut state = OnboardingState {
+            finished_version: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(state.offered_steps().len(), OnboardingStep::ALL.len());
+        assert!(!state.view().first_run);
+
+        state.skip_remaining(1);
+        assert_eq!(state.finished_version, Some(ONBOARDING_VERSION));
+        assert!(state.offered_steps().is_empty());
+        assert!(state.view().finished);
+
+        // Old state files without the newer fields still parse
+        let json = r#"{"steps":{"hotkey":{"status":"completed","updated_at":1}}}"#;
+        let state: OnboardingState = serde_json::from_str(json).unwrap();
+        assert_eq!(state.current_step(), Some(OnboardingStep::DiskAccess));
+    }
+}
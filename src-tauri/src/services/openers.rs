@@ -0,0 +1,369 @@
+//! Openers
+//! Picks the app or command that opens a result, from the `openers`
+//! setting. A result is looked up under each of its contexts, most
+//! specific first: a file by its extension, then as a "file" URL; a
+//! browser result as such, then by its URL's scheme. A context with no
+//! opener, or whose app or program has gone away, falls back to the
+//! system default, and the fallback is reported to the caller.
+
+use crate::models::app::ApplicationEntry;
+use crate::models::preferences::OpenerTarget;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::ffi::OsStr;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Where a result is opened from, the key of an opener
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OpenerContext {
+    /// A bookmark or history page
+    BrowserResult,
+    /// A file with this extension, lowercase without the dot
+    FileExtension(String),
+    /// A URL with this scheme, lowercase
+    UrlScheme(String),
+}
+
+impl OpenerContext {
+    /// Parse a settings key such as "file_extension:md"
+    pub fn parse(key: &str) -> Result<Self, String> {
+        let context = match key.split_once(':') {
+            None if key == "browser_result" => Self::BrowserResult,
+            Some(("file_extension", extension)) => {
+                Self::FileExtension(extension.trim_start_matches('.').to_lowercase())
+            }
+            Some(("url_scheme", scheme)) => Self::UrlScheme(scheme.to_lowercase()),
+            _ => return Err(format!("Unknown opener context: {}", key)),
+        };
+        let valid = match &context {
+            Self::BrowserResult => true,
+            Self::FileExtension(extension) => !extension.is_empty() && !extension.contains(['/', '\\', '.']),
+            Self::UrlScheme(scheme) => is_valid_scheme(scheme),
+        };
+        if valid {
+            Ok(context)
+        } else {
+            Err(format!("Invalid opener context: {}", key))
+        }
+    }
+}
+
+impl fmt::Display for OpenerContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BrowserResult => write!(f, "browser_result"),
+            Self::FileExtension(extension) => write!(f, "file_extension:{}", extension),
+            Self::UrlScheme(scheme) => write!(f, "url_scheme:{}", scheme),
+        }
+    }
+}
+
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// A result to open
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum OpenRequest {
+    File { path: String },
+    BrowserResult { url: String },
+    Url { url: String },
+}
+
+impl OpenRequest {
+    /// The path or URL handed to whatever opens it
+    pub fn subject(&self) -> &str {
+        match self {
+            Self::File { path } => path,
+            Self::BrowserResult { url } | Self::Url { url } => url,
+        }
+    }
+
+    /// Contexts to look openers up under, most specific first
+    pub fn contexts(&self) -> Vec<OpenerContext> {
+        match self {
+            Self::File { path } => {
+                let extension = Path::new(path).extension().map(|e| e.to_string_lossy().to_lowercase());
+                extension
+                    .map(OpenerContext::FileExtension)
+                    .into_iter()
+                    .chain([OpenerContext::UrlScheme("file".to_string())])
+                    .collect()
+            }
+            Self::BrowserResult { url } => {
+                std::iter::once(OpenerContext::BrowserResult).chain(url_scheme(url)).collect()
+            }
+            Self::Url { url } => url_scheme(url).into_iter().collect(),
+        }
+    }
+}
+
+fn url_scheme(url: &str) -> Option<OpenerContext> {
+    let (scheme, _) = url.split_once(':')?;
+    is_valid_scheme(scheme).then(|| OpenerContext::UrlScheme(scheme.to_lowercase()))
+}
+
+/// The opener for `request`: the first of its contexts that has one
+pub fn resolve<'a>(
+    openers: &'a BTreeMap<String, OpenerTarget>,
+    request: &OpenRequest,
+) -> Option<(OpenerContext, &'a OpenerTarget)> {
+    request
+        .contexts()
+        .into_iter()
+        .find_map(|context| openers.get(&context.to_string()).map(|target| (context, target)))
+}
+
+/// How `target` is launched, once checked to still be there
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Launch {
+    /// Hand the subject to the system opener with this app
+    With(String),
+    /// Run this program with these arguments
+    Command(String, Vec<String>),
+}
+
+/// Find what runs `target` for `subject`. Errors say why it can't be used:
+/// the app isn't installed anymore or the program isn't found.
+pub fn launch_for(
+    target: &OpenerTarget,
+    subject: &str,
+    apps: &[ApplicationEntry],
+    path_var: Option<&OsStr>,
+) -> Result<Launch, String> {
+    match target {
+        OpenerTarget::App { id } => {
+            let app = apps
+                .iter()
+                .find(|app| &app.id == id)
+                .ok_or_else(|| format!("App {} is not installed", id))?;
+            Ok(Launch::With(app.app_path.clone().unwrap_or_else(|| app.executable_path.clone())))
+        }
+        OpenerTarget::Command { template } => {
+            let (program, args) = command_parts(template, subject)?;
+            if find_program(&program, path_var).is_none() {
+                return Err(format!("Program {} was not found", program));
+            }
+            Ok(Launch::Command(program, args))
+        }
+    }
+}
+
+/// Check a target for saving: the app must be installed, the program found
+pub fn validate_target(
+    target: &OpenerTarget,
+    apps: &[ApplicationEntry],
+    path_var: Option<&OsStr>,
+) -> Result<(), String> {
+    launch_for(target, "", apps, path_var).map(|_| ())
+}
+
+/// Split a command template into the program and its arguments
+fn command_parts(template: &str, subject: &str) -> Result<(String, Vec<String>), String> {
+    let mut parts = split_template(template)?;
+    if parts.is_empty() {
+        return Err("The opener command is empty".to_string());
+    }
+
+    if parts.iter().any(|part| part.contains("%u")) {
+        for part in &mut parts {
+            *part = part.replace("%u", subject);
+        }
+    } else {
+        parts.push(subject.to_string());
+    }
+
+    let program = parts.remove(0);
+    Ok((program, parts))
+}
+
+/// Split at whitespace outside single or double quotes, which are removed,
+/// so a path with spaces can be quoted. Backslashes are kept as written;
+/// they're path separators on Windows.
+fn split_template(template: &str) -> Result<Vec<String>, String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    // Set by quotes too, so "" is an empty argument
+    let mut started = false;
+    let mut quote = None;
+    for c in template.chars() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                started = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if started {
+                    parts.push(std::mem::take(&mut current));
+                    started = false;
+                }
+            }
+            (None, c) => {
+                current.push(c);
+                started = true;
+            }
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unclosed quote in opener command: {}", template));
+    }
+    if started {
+        parts.push(current);
+    }
+    Ok(parts)
+}
+
+/// `program` itself when it's a path, otherwise the first match in PATH
+fn find_program(program: &str, path_var: Option<&OsStr>) -> Option<PathBuf> {
+    let path = Path::new(program);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+
+    let extensions: Vec<String> = if cfg!(target_os = "windows") && path.extension().is_none() {
+        crate::services::path_tools::parse_pathext(std::env::var("PATHEXT").ok().as_deref())
+    } else {
+        vec![String::new()]
+    };
+    crate::services::path_tools::path_dirs(path_var?)
+        .into_iter()
+        .flat_map(|dir| extensions.iter().map(move |extension| dir.join(format!("{}{}", program, extension))))
+        .find(|candidate| candidate.is_file())
+}
+
+/// What `open_result` did
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct OpenOutcome {
+    /// Context of the opener used, None when the system default opened it
+    pub opener: Option<String>,
+    /// Why the configured opener wasn't used, when it fell back
+    pub fallback_reason: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::app::AppSource;
+
+    fn app(id: &str) -> ApplicationEntry {
+        ApplicationEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            executable_path: format!("/usr/bin/{}", id),
+            app_path: None,
+            icon: None,
+            usage_count: 0,
+            last_launched: None,
+            platform: "linux".to_string(),
+            alternate_names: None,
+            bundle_id: None,
+            source: AppSource::Installed,
+        }
+    }
+
+    fn openers(entries: &[(&str, &str)]) -> BTreeMap<String, OpenerTarget> {
+        entries
+            .iter()
+            .map(|(context, id)| (context.to_string(), OpenerTarget::App { id: id.to_string() }))
+            .collect()
+    }
+
+    fn resolved(openers: &BTreeMap<String, OpenerTarget>, request: &OpenRequest) -> Option<String> {
+        resolve(openers, request).map(|(context, _)| context.to_string())
+    }
+
+    #[test]
+    fn test_parse_contexts() {
+        assert_eq!(OpenerContext::parse("browser_result").unwrap(), OpenerContext::BrowserResult);
+        assert_eq!(
+            OpenerContext::parse("file_extension:.MD").unwrap(),
+            OpenerContext::FileExtension("md".to_string())
+        );
+        assert_eq!(
+            OpenerContext::parse("url_scheme:HTTPS").unwrap(),
+            OpenerContext::UrlScheme("https".to_string())
+        );
+        for invalid in ["browser", "file_extension:", "file_extension:a/b", "url_scheme:1http", "mime:text"] {
+            assert!(OpenerContext::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_specific_context_wins() {
+        let openers = openers(&[
+            ("file_extension:md", "code"),
+            ("url_scheme:file", "files"),
+            ("browser_result", "firefox"),
+            ("url_scheme:https", "chrome"),
+        ]);
+
+        let markdown = OpenRequest::File { path: "/notes/Todo.MD".to_string() };
+        assert_eq!(resolved(&openers, &markdown).as_deref(), Some("file_extension:md"));
+        let text = OpenRequest::File { path: "/notes/todo.txt".to_string() };
+        assert_eq!(resolved(&openers, &text).as_deref(), Some("url_scheme:file"));
+
+        let bookmark = OpenRequest::BrowserResult { url: "https://example.com".to_string() };
+        assert_eq!(resolved(&openers, &bookmark).as_deref(), Some("browser_result"));
+        let link = OpenRequest::Url { url: "https://example.com".to_string() };
+        assert_eq!(resolved(&openers, &link).as_deref(), Some("url_scheme:https"));
+
+        let mail = OpenRequest::Url { url: "mailto:a@example.com".to_string() };
+        assert_eq!(resolved(&openers, &mail), None);
+    }
+
+    #[test]
+    fn test_missing_app_or_program_is_reported() {
+        let apps = vec![app("code")];
+        let installed = OpenerTarget::App { id: "code".to_string() };
+        assert_eq!(
+            launch_for(&installed, "/a.md", &apps, None),
+            Ok(Launch::With("/usr/bin/code".to_string()))
+        );
+
+        let removed = OpenerTarget::App { id: "firefox".to_string() };
+        assert!(launch_for(&removed, "https://example.com", &apps, None).is_err());
+        assert!(validate_target(&removed, &apps, None).is_err());
+
+        let bin = tempfile::tempdir().unwrap();
+        std::fs::write(bin.path().join("edit"), "").unwrap();
+        let path_var = bin.path().as_os_str();
+        let command = OpenerTarget::Command { template: "edit --wait %u".to_string() };
+        assert_eq!(
+            launch_for(&command, "/a.md", &[], Some(path_var)),
+            Ok(Launch::Command("edit".to_string(), vec!["--wait".to_string(), "/a.md".to_string()]))
+        );
+        let missing = OpenerTarget::Command { template: "gone".to_string() };
+        assert!(launch_for(&missing, "/a.md", &[], Some(path_var)).is_err());
+    }
+
+    #[test]
+    fn test_command_parts_with_quotes() {
+        assert_eq!(
+            command_parts(r#""/Applications/My Editor/edit" --title 'two words' "" %u"#, "/My Notes/a.md"),
+            Ok((
+                "/Applications/My Editor/edit".to_string(),
+                vec![
+                    "--title".to_string(),
+                    "two words".to_string(),
+                    String::new(),
+                    "/My Notes/a.md".to_string()
+                ]
+            ))
+        );
+        assert_eq!(
+            command_parts(r"C:\Tools\edit.exe --flag=it's", "x"),
+            Err("Unclosed quote in opener command: C:\\Tools\\edit.exe --flag=it's".to_string())
+        );
+        assert_eq!(
+            command_parts(r#"C:\Tools\edit.exe "--name=%u""#, "a b"),
+            Ok(("C:\\Tools\\edit.exe".to_string(), vec!["--name=a b".to_string()]))
+        );
+        assert!(command_parts("   ", "x").is_err());
+    }
+}
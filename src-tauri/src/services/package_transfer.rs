@@ -0,0 +1,252 @@
+//! Plugin Package Transfer
+//! Streams plugin packages to the installer temp dir without holding them
+//! in memory: marketplace downloads are written chunk by chunk, and
+//! drag-and-drop uploads arrive as a sequence of chunks. Both enforce the
+//! package size limit and hash the content as it arrives.
+
+use crate::services::http;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// A package written to disk
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferredPackage {
+    pub path: String,
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the content
+    pub sha256: String,
+}
+
+/// Download progress, the `plugin-download:progress` event payload
+#[derive(Debug, Clone, Serialize)]
+pub struct DownloadProgress {
+    pub url: String,
+    pub received: u64,
+    pub total: Option<u64>,
+}
+
+fn too_large(max_bytes: u64) -> String {
+    format!("Package exceeds the {} MB size limit", max_bytes / (1024 * 1024))
+}
+
+/// Writes a package to disk chunk by chunk, hashing as it goes. The file
+/// is removed if the writer is dropped before `finish`.
+pub struct PackageWriter {
+    path: PathBuf,
+    file: Option<fs::File>,
+    hasher: Sha256,
+    size: u64,
+    max_bytes: u64,
+}
+
+impl PackageWriter {
+    pub fn create(path: PathBuf, max_bytes: u64) -> Result<Self, String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("Failed to create temp dir: {}", e))?;
+        }
+        let file = fs::File::create(&path).map_err(|e| format!("Failed to create package file: {}", e))?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            hasher: Sha256::new(),
+            size: 0,
+            max_bytes,
+        })
+    }
+
+    /// Append a chunk, returning the bytes written so far
+    pub fn append(&mut self, chunk: &[u8]) -> Result<u64, String> {
+        if self.size + chunk.len() as u64 > self.max_bytes {
+            return Err(too_large(self.max_bytes));
+        }
+        let file = self.file.as_mut().ok_or("Package writer is closed")?;
+        file.write_all(chunk).map_err(|e| format!("Failed to write package: {}", e))?;
+        self.hasher.update(chunk);
+        self.size += chunk.len() as u64;
+        Ok(self.size)
+    }
+
+    pub fn finish(mut self) -> Result<TransferredPackage, String> {
+        let mut file = self.file.take().ok_or("Package writer is closed")?;
+        file.flush().map_err(|e| format!("Failed to write package: {}", e))?;
+        Ok(TransferredPackage {
+            path: self.path.to_string_lossy().to_string(),
+            size: self.size,
+            sha256: format!("{:x}", std::mem::take(&mut self.hasher).finalize()),
+        })
+    }
+}
+
+impl Drop for PackageWriter {
+    fn drop(&mut self) {
+        // Still open means the transfer never finished
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Keep only the final path component of a client-supplied file name
+fn safe_file_name(file_name: &str) -> Result<String, String> {
+    Path::new(file_name)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| format!("Invalid file name: {}", file_name))
+}
+
+/// Chunked uploads in progress, keyed by upload id
+#[derive(Default)]
+pub struct UploadManager {
+    uploads: Mutex<HashMap<String, PackageWriter>>,
+}
+
+impl UploadManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start an upload into `temp_dir`. A declared size over the limit is
+    /// rejected before anything is written.
+    pub fn begin(
+        &self,
+        temp_dir: &Path,
+        file_name: &str,
+        declared_size: Option<u64>,
+        max_bytes: u64,
+    ) -> Result<String, String> {
+        if declared_size.is_some_and(|size| size > max_bytes) {
+            return Err(too_large(max_bytes));
+        }
+        let upload_id = Uuid::new_v4().to_string();
+        // The extension is kept so the installer can tell the format
+        let path = temp_dir.join(format!("{}-{}", upload_id, safe_file_name(file_name)?));
+        let writer = PackageWriter::create(path, max_bytes)?;
+        self.uploads
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .insert(upload_id.clone(), writer);
+        Ok(upload_id)
+    }
+
+    /// Append a chunk. Going over the size limit aborts the upload.
+    pub fn append(&self, upload_id: &str, chunk: &[u8]) -> Result<u64, String> {
+        let mut uploads = self.uploads.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let writer = uploads
+            .get_mut(upload_id)
+            .ok_or_else(|| format!("Unknown upload: {}", upload_id))?;
+        writer.append(chunk).inspect_err(|_| {
+            uploads.remove(upload_id);
+        })
+    }
+
+    pub fn finish(&self, upload_id: &str) -> Result<TransferredPackage, String> {
+        self.uploads
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .remove(upload_id)
+            .ok_or_else(|| format!("Unknown upload: {}", upload_id))?
+            .finish()
+    }
+
+    /// Drop an upload and its partial file
+    pub fn cancel(&self, upload_id: &str) {
+        if let Ok(mut uploads) = self.uploads.lock() {
+            uploads.remove(upload_id);
+        }
+    }
+}
+
+/// Download `url` to `dest` without buffering the body, reporting progress
+/// after each chunk
+pub async fn download_to_file(
+    url: &str,
+    dest: PathBuf,
+    max_bytes: u64,
+    mut on_progress: impl FnMut(DownloadProgress),
+) -> Result<TransferredPackage, String> {
+    let mut response = http::get(url)
+        .await
+        .map_err(|e| format!("Download failed: {}", e))?;
+
+    let total = response.content_length();
+    if total.is_some_and(|total| total > max_bytes) {
+        return Err(too_large(max_bytes));
+    }
+
+    let mut writer = PackageWriter::create(dest, max_bytes)?;
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Download failed: {}", e))?
+    {
+        let received = writer.append(&chunk)?;
+        on_progress(DownloadProgress {
+            url: url.to_string(),
+            received,
+            total,
+        });
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_chunks_are_reassembled_in_order() {
+        let temp = tempfile::tempdir().unwrap();
+        let uploads = UploadManager::new();
+        let content: Vec<u8> = (0..10_000u32).flat_map(|i| i.to_le_bytes()).collect();
+
+        let id = uploads.begin(temp.path(), "plugin.zip", Some(content.len() as u64), MB).unwrap();
+        for chunk in content.chunks(4096) {
+            uploads.append(&id, chunk).unwrap();
+        }
+        let package = uploads.finish(&id).unwrap();
+
+        assert!(package.path.ends_with("-plugin.zip"));
+        assert_eq!(package.size, content.len() as u64);
+        assert_eq!(fs::read(&package.path).unwrap(), content);
+        assert_eq!(package.sha256, format!("{:x}", Sha256::digest(&content)));
+        assert!(uploads.append(&id, b"late").is_err());
+    }
+
+    #[test]
+    fn test_size_limit_aborts_upload() {
+        let temp = tempfile::tempdir().unwrap();
+        let uploads = UploadManager::new();
+
+        assert!(uploads.begin(temp.path(), "big.zip", Some(2 * MB), MB).is_err());
+
+        let id = uploads.begin(temp.path(), "big.zip", None, MB).unwrap();
+        uploads.append(&id, &vec![0; MB as usize]).unwrap();
+        assert!(uploads.append(&id, b"x").is_err());
+
+        // The partial file is gone and the upload can't be finished
+        assert!(uploads.finish(&id).is_err());
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_cancel_removes_partial_file() {
+        let temp = tempfile::tempdir().unwrap();
+        let uploads = UploadManager::new();
+
+        let id = uploads.begin(temp.path(), "../../escape.zip", None, MB).unwrap();
+        uploads.append(&id, b"partial").unwrap();
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 1);
+
+        uploads.cancel(&id);
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,181 @@
+//! Path Display
+//! How file results show where they live: the parent folder shortened under
+//! the home folder ("~/Projects/…/src"), and breadcrumbs for each of its
+//! ancestors. Paths are shown as typed, so a file found through a symlinked
+//! folder stays under that folder; revealing it resolves the real folder.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Component, Path, PathBuf, MAIN_SEPARATOR_STR};
+
+/// Folders shown after the home folder or root before the middle ones are
+/// left out
+const MAX_DISPLAY_SEGMENTS: usize = 3;
+
+/// Stands in for the folders left out of a shortened path
+const ELLIPSIS: &str = "…";
+
+/// One ancestor of a path
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Breadcrumb {
+    /// Folder name, "~" for the home folder and the root itself for a root
+    pub name: String,
+    /// Full path, as typed
+    pub path: String,
+    /// Inside an indexed folder, so searching within it finds files
+    pub browsable: bool,
+}
+
+/// The home-relative or root start of `path`, and the names after it
+fn segments(path: &Path, home: Option<&Path>) -> (String, PathBuf, Vec<String>) {
+    if let Some((home, rest)) = home.and_then(|home| Some((home, path.strip_prefix(home).ok()?))) {
+        let names = rest.iter().map(|name| name.to_string_lossy().to_string()).collect();
+        return ("~".to_string(), home.to_path_buf(), names);
+    }
+
+    let mut head = String::new();
+    let mut start = PathBuf::new();
+    let mut names = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Prefix(prefix) => {
+                head.push_str(&prefix.as_os_str().to_string_lossy());
+                start.push(component);
+            }
+            Component::RootDir => {
+                head.push_str(MAIN_SEPARATOR_STR);
+                start.push(component);
+            }
+            other => names.push(other.as_os_str().to_string_lossy().to_string()),
+        }
+    }
+    (head, start, names)
+}
+
+/// `path` as shown in results: under the home folder as "~", with the
+/// middle folders of a long path left out, e.g. "~/Projects/…/src"
+pub fn abbreviate_path(path: &Path, home: Option<&Path>) -> String {
+    let (head, _, names) = segments(path, home);
+    let names = if names.len() > MAX_DISPLAY_SEGMENTS {
+        vec![names[0].clone(), ELLIPSIS.to_string(), names[names.len() - 1].clone()]
+    } else {
+        names
+    };
+    if names.is_empty() {
+        return head;
+    }
+    let joined = names.join(MAIN_SEPARATOR_STR);
+    if head.is_empty() || head.ends_with(MAIN_SEPARATOR_STR) {
+        format!("{}{}", head, joined)
+    } else {
+        format!("{}{}{}", head, MAIN_SEPARATOR_STR, joined)
+    }
+}
+
+/// Breadcrumbs from the home folder or root down to `path` itself. A
+/// crumb is browsable when it's inside one of `index_roots` as typed: the
+/// index stores paths as found under its roots, so a crumb that only
+/// resolves into a root through a symlink has no files to search within.
+pub fn breadcrumbs(path: &Path, home: Option<&Path>, index_roots: &[PathBuf]) -> Vec<Breadcrumb> {
+    let (head, start, names) = segments(path, home);
+    let is_indexed = |crumb: &Path| index_roots.iter().any(|root| crumb.starts_with(root));
+
+    let mut crumbs = Vec::new();
+    let mut current = start;
+    if !head.is_empty() {
+        crumbs.push(Breadcrumb {
+            name: head,
+            path: current.to_string_lossy().to_string(),
+            browsable: is_indexed(&current),
+        });
+    }
+    for name in names {
+        current.push(&name);
+        crumbs.push(Breadcrumb {
+            browsable: is_indexed(&current),
+            path: current.to_string_lossy().to_string(),
+            name,
+        });
+    }
+    crumbs
+}
+
+/// Where revealing `path` should point: the file itself inside its folder's
+/// real location, so a symlinked parent opens the folder it links to. Falls
+/// back to `path` when the folder can't be resolved.
+pub fn reveal_target(path: &Path) -> PathBuf {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return path.to_path_buf();
+    };
+    match fs::canonicalize(parent) {
+        Ok(real_parent) => real_parent.join(name),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    fn names(crumbs: &[Breadcrumb]) -> Vec<&str> {
+        crumbs.iter().map(|crumb| crumb.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_abbreviation() {
+        let home = Path::new("/Users/me");
+        let abbreviate = |path: &str| abbreviate_path(Path::new(path), Some(home));
+
+        assert_eq!(abbreviate("/Users/me/Projects/etools/src-tauri/src"), "~/Projects/…/src");
+        assert_eq!(abbreviate("/Users/me/Projects/etools"), "~/Projects/etools");
+        assert_eq!(abbreviate("/Users/me"), "~");
+        // Only whole folder names count as the home folder
+        assert_eq!(abbreviate("/Users/meg/notes"), "/Users/meg/notes");
+        assert_eq!(abbreviate("/var/lib/app/data/cache"), "/var/…/cache");
+        assert_eq!(abbreviate("/"), "/");
+        assert_eq!(abbreviate_path(Path::new("/Users/me/a/b/c/d"), None), "/Users/…/d");
+    }
+
+    #[test]
+    fn test_breadcrumbs() {
+        let home = Path::new("/Users/me");
+        let roots = vec![PathBuf::from("/Users/me/Projects")];
+
+        let crumbs = breadcrumbs(Path::new("/Users/me/Projects/etools/src"), Some(home), &roots);
+        assert_eq!(names(&crumbs), vec!["~", "Projects", "etools", "src"]);
+        assert_eq!(crumbs[0].path, "/Users/me");
+        assert_eq!(crumbs[2].path, "/Users/me/Projects/etools");
+        let browsable: Vec<bool> = crumbs.iter().map(|crumb| crumb.browsable).collect();
+        assert_eq!(browsable, vec![false, true, true, true]);
+
+        let crumbs = breadcrumbs(Path::new("/var/log"), Some(home), &roots);
+        assert_eq!(names(&crumbs), vec!["/", "var", "log"]);
+        assert_eq!(crumbs[1].path, "/var");
+        assert!(crumbs.iter().all(|crumb| !crumb.browsable));
+    }
+
+    #[test]
+    fn test_symlinked_parent_shows_as_typed_and_reveals_real_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(real.join("data/reports")).unwrap();
+        fs::write(real.join("data/reports/q1.pdf"), b"").unwrap();
+        std::os::unix::fs::symlink(real.join("data"), real.join("link")).unwrap();
+
+        let typed = real.join("link/reports/q1.pdf");
+        assert_eq!(reveal_target(&typed), real.join("data/reports/q1.pdf"));
+        // Nothing to resolve leaves the path alone
+        let missing = real.join("gone/q1.pdf");
+        assert_eq!(reveal_target(&missing), missing);
+
+        // Shown under the link, but not browsable: the index holds the files
+        // under the real folder, where a search within the link finds none
+        let crumbs = breadcrumbs(&real.join("link/reports"), Some(&real), &[real.join("data")]);
+        assert_eq!(names(&crumbs), vec!["~", "link", "reports"]);
+        assert_eq!(crumbs[2].path, real.join("link/reports").to_string_lossy());
+        assert!(crumbs.iter().all(|crumb| !crumb.browsable));
+        let crumbs = breadcrumbs(&real.join("link/reports"), Some(&real), &[real.join("link")]);
+        assert!(!crumbs[0].browsable && crumbs[1].browsable && crumbs[2].browsable);
+        assert_eq!(abbreviate_path(&typed, Some(&real)), "~/link/reports/q1.pdf");
+    }
+}
@@ -0,0 +1,440 @@
+//! PATH Tools
+//! Executables in the PATH directories, for launching command-line tools
+//! from search. A name found in several directories resolves to the first,
+//! as in a shell. Scans are cached: searches only read the cached list and
+//! ask for a background refresh, which rescans once the TTL runs out, PATH
+//! changes, a PATH directory is modified or the excluded patterns change.
+
+use crate::services::search::exclusion::glob_match;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs::{self, Metadata};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// A full rescan happens at least this often
+const TOOL_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Searches check PATH and directory mtimes at most this often
+const STALE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// PATHEXT when the variable isn't set
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathTool {
+    /// What's typed to run it: the file name, without the PATHEXT
+    /// extension on Windows
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// How executables are told apart from other files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutableRule {
+    /// Files with an execute permission bit
+    ModeBits,
+    /// Files with one of these extensions, lowercase with the dot, in
+    /// PATHEXT order
+    Extensions(Vec<String>),
+}
+
+impl ExecutableRule {
+    /// PATHEXT on Windows, permission bits elsewhere
+    pub fn for_platform() -> Self {
+        if cfg!(target_os = "windows") {
+            Self::Extensions(parse_pathext(env::var("PATHEXT").ok().as_deref()))
+        } else {
+            Self::ModeBits
+        }
+    }
+
+    /// The tool name and its rank among same-named files in a directory
+    /// (lower wins), None when the file isn't executable
+    fn tool_name(&self, file_name: &str, metadata: &Metadata) -> Option<(String, usize)> {
+        if !metadata.is_file() {
+            return None;
+        }
+        match self {
+            Self::ModeBits => is_executable(metadata).then(|| (file_name.to_string(), 0)),
+            Self::Extensions(extensions) => {
+                let (stem, extension) = file_name.rsplit_once('.')?;
+                let extension = format!(".{}", extension.to_lowercase());
+                let rank = extensions.iter().position(|known| *known == extension)?;
+                (!stem.is_empty()).then(|| (stem.to_string(), rank))
+            }
+        }
+    }
+
+    /// Windows file names are case-insensitive, so "Node" and "node" are one tool
+    fn key(&self, name: &str) -> String {
+        match self {
+            Self::ModeBits => name.to_string(),
+            Self::Extensions(_) => name.to_lowercase(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &Metadata) -> bool {
+    true
+}
+
+/// PATHEXT as lowercase extensions with the dot, e.g. [".com", ".exe"].
+/// Unset or empty falls back to the Windows default.
+pub fn parse_pathext(pathext: Option<&str>) -> Vec<String> {
+    let pathext = pathext
+        .filter(|value| !value.trim().is_empty())
+        .unwrap_or(DEFAULT_PATHEXT);
+    let mut extensions: Vec<String> = Vec::new();
+    for extension in pathext.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let extension = if extension.starts_with('.') {
+            extension.to_lowercase()
+        } else {
+            format!(".{}", extension.to_lowercase())
+        };
+        if !extensions.contains(&extension) {
+            extensions.push(extension);
+        }
+    }
+    extensions
+}
+
+/// Whether a PATH directory matches one of the excluded patterns
+pub fn is_excluded_dir(dir: &Path, excluded: &[String]) -> bool {
+    let dir = dir.to_string_lossy();
+    excluded
+        .iter()
+        .map(|pattern| pattern.trim())
+        .any(|pattern| !pattern.is_empty() && glob_match(pattern, &dir))
+}
+
+/// The directories of a PATH value, in order, each once
+pub fn path_dirs(path_var: &OsStr) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    env::split_paths(path_var)
+        .filter(|dir| !dir.as_os_str().is_empty() && seen.insert(dir.clone()))
+        .collect()
+}
+
+/// Executables in `dirs`, sorted by name. Earlier directories win a name.
+/// Missing and unreadable directories are skipped.
+pub fn scan_dirs(dirs: &[PathBuf], excluded: &[String], rule: &ExecutableRule) -> Vec<PathTool> {
+    let mut seen = HashSet::new();
+    let mut tools = Vec::new();
+
+    for dir in dirs.iter().filter(|dir| !is_excluded_dir(dir, excluded)) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut found: Vec<(String, usize, PathBuf)> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.path();
+                let file_name = entry.file_name().into_string().ok()?;
+                // Follows symlinks, which is how Homebrew and others install tools
+                let metadata = fs::metadata(&path).ok()?;
+                let (name, rank) = rule.tool_name(&file_name, &metadata)?;
+                Some((name, rank, path))
+            })
+            .collect();
+        found.sort_by_key(|(_, rank, _)| *rank);
+
+        for (name, _, path) in found {
+            if seen.insert(rule.key(&name)) {
+                tools.push(PathTool { name, path });
+            }
+        }
+    }
+
+    tools.sort_by(|a, b| a.name.cmp(&b.name));
+    tools
+}
+
+/// What a scan was made from; any difference means it's stale
+#[derive(Debug, Clone, PartialEq)]
+struct ScanInputs {
+    path_var: OsString,
+    excluded: Vec<String>,
+    dir_mtimes: Vec<Option<SystemTime>>,
+}
+
+impl ScanInputs {
+    fn read(path_var: &OsStr, excluded: &[String]) -> Self {
+        Self {
+            path_var: path_var.to_os_string(),
+            excluded: excluded.to_vec(),
+            dir_mtimes: path_dirs(path_var)
+                .iter()
+                .map(|dir| fs::metadata(dir).and_then(|m| m.modified()).ok())
+                .collect(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CachedScan {
+    tools: Arc<Vec<PathTool>>,
+    inputs: Option<ScanInputs>,
+    scanned_at: Option<Instant>,
+    checked_at: Option<Instant>,
+}
+
+/// The tools on PATH, rescanned in the background when stale
+pub struct PathToolCache {
+    rule: ExecutableRule,
+    ttl: Duration,
+    cached: Mutex<CachedScan>,
+    refreshing: AtomicBool,
+}
+
+impl PathToolCache {
+    pub fn new() -> Self {
+        Self::with_rule(ExecutableRule::for_platform(), TOOL_CACHE_TTL)
+    }
+
+    pub fn with_rule(rule: ExecutableRule, ttl: Duration) -> Self {
+        Self {
+            rule,
+            ttl,
+            cached: Mutex::new(CachedScan::default()),
+            refreshing: AtomicBool::new(false),
+        }
+    }
+
+    /// The tools from the last scan; empty before the first one finishes
+    pub fn tools(&self) -> Arc<Vec<PathTool>> {
+        self.cached
+            .lock()
+            .map(|cached| Arc::clone(&cached.tools))
+            .unwrap_or_default()
+    }
+
+    /// Rescan if the cached scan is stale. Returns whether it rescanned.
+    pub fn refresh_if_stale(&self, path_var: &OsStr, excluded: &[String]) -> bool {
+        let inputs = ScanInputs::read(path_var, excluded);
+        {
+            let Ok(mut cached) = self.cached.lock() else {
+                return false;
+            };
+            cached.checked_at = Some(Instant::now());
+            let fresh =
+                cached.scanned_at.is_some_and(|at| at.elapsed() < self.ttl) && cached.inputs.as_ref() == Some(&inputs);
+            if fresh {
+                return false;
+            }
+        }
+
+        // Scanned without the lock so searches keep reading the old list
+        let tools = scan_dirs(&path_dirs(path_var), excluded, &self.rule);
+        let Ok(mut cached) = self.cached.lock() else {
+            return false;
+        };
+        cached.tools = Arc::new(tools);
+        cached.inputs = Some(inputs);
+        cached.scanned_at = Some(Instant::now());
+        true
+    }
+
+    /// Check for a stale scan on a background thread, unless one is running
+    /// or the last check was moments ago. Never blocks on the file system.
+    pub fn refresh_in_background(self: &Arc<Self>, excluded: Vec<String>) {
+        let recently_checked = self
+            .cached
+            .lock()
+            .map(|cached| cached.checked_at.is_some_and(|at| at.elapsed() < STALE_CHECK_INTERVAL))
+            .unwrap_or(true);
+        if recently_checked || self.refreshing.swap(true, Ordering::AcqRel) {
+            return;
+        }
+
+        let cache = Arc::clone(self);
+        thread::spawn(move || {
+            let path_var = env::var_os("PATH").unwrap_or_default();
+            cache.refresh_if_stale(&path_var, &excluded);
+            cache.refreshing.store(false, Ordering::Release);
+        });
+    }
+
+    /// The cached tool at `path`, so only scanned executables can be run
+    pub fn find(&self, path: &Path) -> Option<PathTool> {
+        self.tools().iter().find(|tool| tool.path == path).cloned()
+    }
+}
+
+impl Default for PathToolCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The terminal command that runs `tool`: "%c" in the template is replaced
+/// by the tool's path, which is appended when there's no "%c" ("open -a
+/// Terminal" runs an executable it's given)
+pub fn terminal_command(template: &str, tool: &Path) -> Result<(String, Vec<String>), String> {
+    let tool = tool.to_string_lossy();
+    let mut parts: Vec<String> = template.split_whitespace().map(str::to_string).collect();
+    if parts.is_empty() {
+        return Err("The CLI terminal command is empty".to_string());
+    }
+
+    if parts.iter().any(|part| part.contains("%c")) {
+        for part in &mut parts {
+            *part = part.replace("%c", &tool);
+        }
+    } else {
+        parts.push(tool.into_owned());
+    }
+
+    let program = parts.remove(0);
+    Ok((program, parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        fs::write(&path, "").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        path
+    }
+
+    fn names(tools: &[PathTool]) -> Vec<&str> {
+        tools.iter().map(|tool| tool.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_pathext_picks_windows_executables() {
+        assert_eq!(parse_pathext(None), vec![".com", ".exe", ".bat", ".cmd"]);
+        assert_eq!(
+            parse_pathext(Some(".EXE;;cmd; .Ps1;.exe")),
+            vec![".exe", ".cmd", ".ps1"]
+        );
+        assert_eq!(parse_pathext(Some(" ")), parse_pathext(None));
+
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        touch(first.path(), "npm.cmd");
+        let npm_exe = touch(first.path(), "npm.exe");
+        touch(first.path(), "README.txt");
+        touch(first.path(), ".exe");
+        let node = touch(first.path(), "Node.EXE");
+        touch(second.path(), "node.exe");
+        let build = touch(second.path(), "build.bat");
+
+        let rule = ExecutableRule::Extensions(parse_pathext(None));
+        let tools = scan_dirs(&[first.path().to_path_buf(), second.path().to_path_buf()], &[], &rule);
+        assert_eq!(names(&tools), vec!["Node", "build", "npm"]);
+        // PATHEXT order within a directory, PATH order across them
+        assert_eq!(tools[2].path, npm_exe);
+        assert_eq!(tools[0].path, node);
+        assert_eq!(tools[1].path, build);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mode_bits_and_excluded_dirs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let bin = tempfile::tempdir().unwrap();
+        let libexec = bin.path().join("libexec");
+        fs::create_dir(&libexec).unwrap();
+        touch(bin.path(), "htop");
+        touch(&libexec, "git-helper");
+        let notes = touch(bin.path(), "notes.txt");
+        fs::set_permissions(&notes, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let dirs = path_dirs(&env::join_paths([bin.path(), &libexec, bin.path()]).unwrap());
+        assert_eq!(dirs.len(), 2);
+        assert_eq!(
+            names(&scan_dirs(&dirs, &[], &ExecutableRule::ModeBits)),
+            vec!["git-helper", "htop"]
+        );
+        let excluded = vec!["*/libexec*".to_string()];
+        assert_eq!(
+            names(&scan_dirs(&dirs, &excluded, &ExecutableRule::ModeBits)),
+            vec!["htop"]
+        );
+    }
+
+    #[test]
+    fn test_cache_rescans_when_path_or_dirs_change() {
+        let first = tempfile::tempdir().unwrap();
+        let second = tempfile::tempdir().unwrap();
+        touch(first.path(), "rg.exe");
+        let rule = ExecutableRule::Extensions(parse_pathext(None));
+        let cache = PathToolCache::with_rule(rule.clone(), Duration::from_secs(3600));
+        let path_var = env::join_paths([first.path()]).unwrap();
+
+        assert!(cache.tools().is_empty());
+        assert!(cache.refresh_if_stale(&path_var, &[]));
+        assert_eq!(names(&cache.tools()), vec!["rg"]);
+        assert!(!cache.refresh_if_stale(&path_var, &[]));
+
+        // A new PATH entry
+        touch(second.path(), "fd.exe");
+        let longer = env::join_paths([first.path(), second.path()]).unwrap();
+        assert!(cache.refresh_if_stale(&longer, &[]));
+        assert_eq!(names(&cache.tools()), vec!["fd", "rg"]);
+
+        // A directory modified since the scan; the mtime is moved on
+        // explicitly in case the file system's clock is coarse
+        #[cfg(unix)]
+        {
+            touch(first.path(), "jq.exe");
+            let modified = fs::metadata(first.path()).unwrap().modified().unwrap();
+            let dir = fs::File::open(first.path()).unwrap();
+            dir.set_modified(modified + Duration::from_secs(1)).unwrap();
+            assert!(cache.refresh_if_stale(&longer, &[]));
+            assert_eq!(names(&cache.tools()), vec!["fd", "jq", "rg"]);
+        }
+
+        // Different exclusions, and an expired TTL
+        assert!(cache.refresh_if_stale(&longer, &[format!("{}*", second.path().display())]));
+        assert!(cache.find(&second.path().join("fd.exe")).is_none());
+        let expiring = PathToolCache::with_rule(rule, Duration::ZERO);
+        assert!(expiring.refresh_if_stale(&longer, &[]));
+        assert!(expiring.refresh_if_stale(&longer, &[]));
+    }
+
+    #[test]
+    fn test_terminal_command_template() {
+        let htop = Path::new("/opt/homebrew/bin/htop");
+        assert_eq!(
+            terminal_command("open -a Terminal", htop).unwrap(),
+            (
+                "open".to_string(),
+                vec![
+                    "-a".to_string(),
+                    "Terminal".to_string(),
+                    "/opt/homebrew/bin/htop".to_string()
+                ]
+            )
+        );
+        assert_eq!(
+            terminal_command("x-terminal-emulator -e %c", htop).unwrap(),
+            (
+                "x-terminal-emulator".to_string(),
+                vec!["-e".to_string(), "/opt/homebrew/bin/htop".to_string()]
+            )
+        );
+        assert!(terminal_command("  ", htop).is_err());
+    }
+}
@@ -28,6 +28,7 @@ pub enum PerformanceEvent {
     SearchCompleted { duration_ms: u64, result_count: usize },
     AppLaunched { app_id: String, duration_ms: u64 },
     MemoryUsed { mb: f64 },
+    PluginHostCall { plugin_id: String, capability: String, duration_ms: u64, success: bool },
 }
 
 /// Performance monitor
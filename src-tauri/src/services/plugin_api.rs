@@ -0,0 +1,77 @@
+//! Plugin API Version
+//! The version of the host API (host calls, storage, views) that plugins are
+//! written against. Manifests declare the version they need in
+//! `api_version`; this module decides whether this build can run them.
+
+use crate::models::plugin::ApiCompatibility;
+use semver::{Version, VersionReq};
+
+/// Host API this build provides. Bump the minor when a capability is added;
+/// bump the major, and `SUPPORTED_API_RANGE`, when one is removed or
+/// changes shape.
+pub const HOST_API_VERSION: &str = "1.1.0";
+
+/// API versions this build can run
+pub const SUPPORTED_API_RANGE: &str = ">=1.0.0, <2.0.0";
+
+/// Assumed for manifests written before `api_version` existed
+pub const LEGACY_API_VERSION: &str = "1.0.0";
+
+pub fn host_api_version() -> Version {
+    Version::parse(HOST_API_VERSION).expect("HOST_API_VERSION is a valid version")
+}
+
+/// Compare a manifest's `api_version` (None for manifests that predate it)
+/// with this host. Patch versions never change the API, so only major and
+/// minor count.
+pub fn check_api_version(declared: Option<&str>) -> ApiCompatibility {
+    let Ok(declared) = Version::parse(declared.unwrap_or(LEGACY_API_VERSION).trim()) else {
+        return ApiCompatibility::Incompatible;
+    };
+    let host = host_api_version();
+    let supported = VersionReq::parse(SUPPORTED_API_RANGE).expect("SUPPORTED_API_RANGE is a valid range");
+
+    if (declared.major, declared.minor) > (host.major, host.minor) {
+        ApiCompatibility::RequiresNewerHost
+    } else if !supported.matches(&declared) {
+        ApiCompatibility::Incompatible
+    } else if (declared.major, declared.minor) < (host.major, host.minor) {
+        ApiCompatibility::Legacy
+    } else {
+        ApiCompatibility::Compatible
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_current_api_is_compatible() {
+        assert_eq!(check_api_version(Some(HOST_API_VERSION)), ApiCompatibility::Compatible);
+        // A newer patch adds nothing
+        assert_eq!(check_api_version(Some("1.1.9")), ApiCompatibility::Compatible);
+    }
+
+    #[test]
+    fn test_older_supported_api_is_legacy() {
+        assert_eq!(check_api_version(Some("1.0.0")), ApiCompatibility::Legacy);
+        // Manifests without api_version are treated as the first API
+        assert_eq!(check_api_version(None), ApiCompatibility::Legacy);
+        assert!(ApiCompatibility::Legacy.is_loadable());
+    }
+
+    #[test]
+    fn test_newer_api_requires_newer_host() {
+        assert_eq!(check_api_version(Some("1.2.0")), ApiCompatibility::RequiresNewerHost);
+        assert_eq!(check_api_version(Some("2.0.0")), ApiCompatibility::RequiresNewerHost);
+        assert!(!ApiCompatibility::RequiresNewerHost.is_loadable());
+    }
+
+    #[test]
+    fn test_api_below_minimum_is_incompatible() {
+        assert_eq!(check_api_version(Some("0.9.0")), ApiCompatibility::Incompatible);
+        assert_eq!(check_api_version(Some("one")), ApiCompatibility::Incompatible);
+        assert!(!ApiCompatibility::Incompatible.is_loadable());
+    }
+}
@@ -0,0 +1,152 @@
+//! Plugin Archive
+//! What's left of a plugin once it's uninstalled. Uninstalling removes the
+//! plugin's settings, enabled state, usage stats, abbreviations and
+//! permission grants; asked to, it first writes the settings and consent
+//! records to `plugins-archive/<id>-<version>.json` so a later install of
+//! the same plugin can bring them back. Installs from before uninstall
+//! cleaned up after itself left such entries behind, which a one-time
+//! sweep removes.
+
+use crate::services::plugin_sandbox::PermissionConsent;
+use crate::utils::atomic_write::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Records the orphan sweep ran, and what it removed
+const ORPHAN_SWEEP_FILE: &str = "plugin-orphan-sweep.json";
+
+/// Archived settings and consents of an uninstalled plugin
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PluginArchive {
+    pub plugin_id: String,
+    /// Version that was uninstalled
+    pub version: String,
+    /// Unix timestamp (ms)
+    pub archived_at: i64,
+    pub settings: HashMap<String, serde_json::Value>,
+    pub consents: Vec<PermissionConsent>,
+}
+
+/// Directory holding the archives of an app data dir
+pub fn archive_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("plugins-archive")
+}
+
+/// Archive file for a plugin version. Scoped package names keep their
+/// scope in the file name rather than a subdirectory.
+pub fn archive_path(data_dir: &Path, plugin_id: &str, version: &str) -> PathBuf {
+    let name = format!("{}-{}.json", plugin_id, version).replace(['/', '\\'], "_");
+    archive_dir(data_dir).join(name)
+}
+
+/// Write `archive`, replacing an earlier archive of the same version
+pub fn write_archive(data_dir: &Path, archive: &PluginArchive) -> Result<PathBuf, String> {
+    let path = archive_path(data_dir, &archive.plugin_id, &archive.version);
+    fs::create_dir_all(archive_dir(data_dir)).map_err(|e| format!("Failed to create plugin archive dir: {}", e))?;
+    let content =
+        serde_json::to_string_pretty(archive).map_err(|e| format!("Failed to serialize plugin archive: {}", e))?;
+    write_atomic(&path, content).map_err(|e| format!("Failed to write plugin archive: {}", e))?;
+    Ok(path)
+}
+
+/// The most recent archive of `plugin_id` and its file, if there's one.
+/// Files that can't be read are skipped.
+pub fn latest_archive(data_dir: &Path, plugin_id: &str) -> Option<(PathBuf, PluginArchive)> {
+    fs::read_dir(archive_dir(data_dir))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "json"))
+        .filter_map(|path| {
+            let archive: PluginArchive = serde_json::from_str(&fs::read_to_string(&path).ok()?).ok()?;
+            (archive.plugin_id == plugin_id).then_some((path, archive))
+        })
+        .max_by_key(|(_, archive)| archive.archived_at)
+}
+
+/// Plugin ids the orphan sweep removed entries for, per store
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrphanSweepReport {
+    /// Unix timestamp (ms)
+    pub swept_at: i64,
+    pub settings: Vec<String>,
+    pub state: Vec<String>,
+    pub usage_stats: Vec<String>,
+    pub abbreviations: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+impl OrphanSweepReport {
+    pub fn is_empty(&self) -> bool {
+        [&self.settings, &self.state, &self.usage_stats, &self.abbreviations, &self.permissions]
+            .iter()
+            .all(|ids| ids.is_empty())
+    }
+}
+
+/// Remove entries of plugins that aren't installed, returning their ids
+pub fn remove_orphans<V>(entries: &mut HashMap<String, V>, installed: &HashSet<String>) -> Vec<String> {
+    let mut removed: Vec<String> = entries.keys().filter(|id| !installed.contains(*id)).cloned().collect();
+    removed.sort();
+    for id in &removed {
+        entries.remove(id);
+    }
+    removed
+}
+
+/// The report of the sweep, None until it has run
+pub fn read_orphan_sweep(data_dir: &Path) -> Option<OrphanSweepReport> {
+    serde_json::from_str(&fs::read_to_string(data_dir.join(ORPHAN_SWEEP_FILE)).ok()?).ok()
+}
+
+/// Record that the sweep ran, so it doesn't run again
+pub fn record_orphan_sweep(data_dir: &Path, report: &OrphanSweepReport) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(report).map_err(|e| format!("Failed to serialize orphan sweep: {}", e))?;
+    write_atomic(&data_dir.join(ORPHAN_SWEEP_FILE), content)
+        .map_err(|e| format!("Failed to record orphan sweep: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn archive(plugin_id: &str, version: &str, archived_at: i64) -> PluginArchive {
+        PluginArchive {
+            plugin_id: plugin_id.to_string(),
+            version: version.to_string(),
+            archived_at,
+            settings: HashMap::from([("theme".to_string(), json!(version))]),
+            consents: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_latest_archive_of_the_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(latest_archive(dir.path(), "notes"), None);
+
+        write_archive(dir.path(), &archive("notes", "1.0.0", 10)).unwrap();
+        write_archive(dir.path(), &archive("notes", "1.2.0", 20)).unwrap();
+        // Shares the "notes-" prefix without being the same plugin
+        write_archive(dir.path(), &archive("notes-sync", "2.0.0", 30)).unwrap();
+        let scoped = write_archive(dir.path(), &archive("@etools-plugin/notes", "3.0.0", 40)).unwrap();
+        assert_eq!(scoped.parent().unwrap(), archive_dir(dir.path()));
+        fs::write(archive_dir(dir.path()).join("garbage.json"), "{").unwrap();
+
+        let (path, latest) = latest_archive(dir.path(), "notes").unwrap();
+        assert_eq!(path, archive_path(dir.path(), "notes", "1.2.0"));
+        assert_eq!(latest, archive("notes", "1.2.0", 20));
+    }
+
+    #[test]
+    fn test_remove_orphans() {
+        let installed = HashSet::from(["kept".to_string()]);
+        let mut entries = HashMap::from([("kept", 1), ("gone", 2), ("also-gone", 3)].map(|(id, n)| (id.to_string(), n)));
+        assert_eq!(remove_orphans(&mut entries, &installed), vec!["also-gone", "gone"]);
+        assert_eq!(entries.into_keys().collect::<Vec<_>>(), vec!["kept"]);
+    }
+}
@@ -2,6 +2,7 @@
 //! Comprehensive error types and user-friendly error messages for plugin operations
 #![allow(dead_code)]
 
+use crate::t;
 use std::fmt;
 
 /// Plugin error types with user-friendly messages
@@ -118,10 +119,7 @@ impl PluginError {
             }
 
             PluginError::CorePluginProtected { plugin_id } => {
-                format!(
-                    "无法修改核心插件: {} (核心插件受保护)",
-                    plugin_id
-                )
+                t!("plugins.core_uninstall_forbidden", plugin_id = plugin_id)
             }
 
             PluginError::AlreadyInstalled { plugin_id, version } => {
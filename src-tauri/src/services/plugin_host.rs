@@ -0,0 +1,528 @@
+//! Plugin Host API
+//! Native capabilities that frontend-run plugins can call through
+//! `plugin_host_call`. Every call is checked against the permissions granted
+//! in the PluginSandbox; Shell calls are additionally limited to allowlisted
+//! programs and run through the shell execution service. Dangerous
+//! permissions a plugin requests stay blocked until the user answers the
+//! consent prompt. Storage needs no
+//! grant: every enabled plugin gets its own quota-limited data directory.
+//! WriteFile writes there too, so the write_file permission never reaches
+//! files outside it.
+//! Calls that do I/O run with a timeout.
+
+use crate::services::http;
+use crate::services::plugin_sandbox::{PluginPermission, PluginSandbox};
+use crate::services::plugin_storage::{PluginStorage, StorageError};
+use crate::services::shell_exec::is_program_allowed;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::time::Duration;
+
+/// Timeout for file I/O capabilities
+pub const FILE_IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Timeout for network requests
+pub const NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A host call with its payload, parsed from `capability` + `payload`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "capability", content = "payload", rename_all = "snake_case")]
+pub enum HostCall {
+    ReadClipboard,
+    /// `path` is relative to the plugin's data directory
+    WriteFile {
+        path: String,
+        contents: String,
+    },
+    Notification {
+        title: String,
+        #[serde(default)]
+        body: Option<String>,
+    },
+    Network {
+        url: String,
+        #[serde(default)]
+        method: Option<String>,
+        #[serde(default)]
+        body: Option<String>,
+    },
+    Shell {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        cwd: Option<String>,
+        /// Capped at shell_exec::MAX_TIMEOUT
+        #[serde(default)]
+        timeout_ms: Option<u64>,
+    },
+    /// The plugin's own key-value store and data directory
+    Storage(StorageOp),
+}
+
+/// An operation on the calling plugin's storage. File paths are relative
+/// to its data directory.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum StorageOp {
+    Get { key: String },
+    Set { key: String, value: serde_json::Value },
+    Delete { key: String },
+    GetDataDir,
+    ReadFile { path: String },
+    WriteFile { path: String, contents: String },
+    DeleteFile { path: String },
+}
+
+impl HostCall {
+    /// Parse a capability name and its JSON payload
+    pub fn parse(capability: &str, payload: serde_json::Value) -> Result<Self, HostCallError> {
+        // Unit variants take no payload
+        let payload = if payload.is_null() { None } else { Some(payload) };
+        let mut value = serde_json::json!({ "capability": capability });
+        if let Some(payload) = payload {
+            value["payload"] = payload;
+        }
+
+        serde_json::from_value(value).map_err(|e| HostCallError::InvalidPayload {
+            message: e.to_string(),
+        })
+    }
+
+    pub fn capability(&self) -> &'static str {
+        match self {
+            HostCall::ReadClipboard => "read_clipboard",
+            HostCall::WriteFile { .. } => "write_file",
+            HostCall::Notification { .. } => "notification",
+            HostCall::Network { .. } => "network",
+            HostCall::Shell { .. } => "shell",
+            HostCall::Storage(_) => "storage",
+        }
+    }
+
+    /// Deadline for capabilities that do I/O; None runs the call without one
+    pub fn timeout(&self) -> Option<Duration> {
+        match self {
+            HostCall::WriteFile { .. } | HostCall::Storage(_) => Some(FILE_IO_TIMEOUT),
+            HostCall::Network { .. } => Some(NETWORK_TIMEOUT),
+            _ => None,
+        }
+    }
+
+    /// Permission the plugin must have been granted; None when any
+    /// enabled plugin may make the call
+    pub fn required_permission(&self) -> Option<PluginPermission> {
+        match self {
+            HostCall::ReadClipboard => Some(PluginPermission::ReadClipboard),
+            HostCall::WriteFile { .. } => Some(PluginPermission::WriteFile),
+            HostCall::Notification { .. } => Some(PluginPermission::Notification),
+            HostCall::Network { .. } => Some(PluginPermission::Network),
+            HostCall::Shell { .. } => Some(PluginPermission::Shell),
+            HostCall::Storage(_) => None,
+        }
+    }
+}
+
+/// Result of a successful host call
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "capability", rename_all = "snake_case")]
+pub enum HostCallOutput {
+    ReadClipboard { text: String },
+    WriteFile { bytes_written: usize },
+    /// `suppressed` when do-not-disturb or a mute kept it from showing
+    Notification { suppressed: bool },
+    Network { status: u16, body: String },
+    /// `truncated` when output went past shell_exec::MAX_OUTPUT_BYTES
+    Shell { stdout: String, stderr: String, exit_code: Option<i32>, truncated: bool },
+    /// The value read, whether a key or file existed, bytes written or the
+    /// data directory path, depending on the operation
+    Storage { result: serde_json::Value },
+}
+
+/// Host call failure
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HostCallError {
+    /// The plugin lacks `permission`; the UI can ask the user to grant it
+    PermissionDenied { permission: String },
+    /// `permission` awaits the user's consent (see get_pending_consents)
+    ConsentPending { permission: String },
+    /// Shell permission is granted but this program isn't allowlisted
+    CommandNotAllowed { command: String },
+    InvalidPayload { message: String },
+    /// Too many calls; try again after `retry_after_ms`
+    RateLimited { retry_after_ms: u64 },
+    TimedOut { timeout_ms: u64 },
+    /// A storage write needs `needed_bytes` more than the plugin's quota allows
+    QuotaExceeded { used_bytes: u64, quota_bytes: u64, needed_bytes: u64 },
+    Failed { message: String },
+}
+
+impl fmt::Display for HostCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HostCallError::PermissionDenied { permission } => write!(f, "Permission denied: {}", permission),
+            HostCallError::ConsentPending { permission } => write!(f, "Awaiting consent: {}", permission),
+            HostCallError::CommandNotAllowed { command } => write!(f, "Command not allowed: {}", command),
+            HostCallError::InvalidPayload { message } => write!(f, "Invalid payload: {}", message),
+            HostCallError::RateLimited { retry_after_ms } => write!(f, "Rate limited, retry after {} ms", retry_after_ms),
+            HostCallError::TimedOut { timeout_ms } => write!(f, "Timed out after {} ms", timeout_ms),
+            HostCallError::QuotaExceeded { used_bytes, quota_bytes, needed_bytes } => write!(
+                f,
+                "Storage quota exceeded: {} of {} bytes used, {} more needed",
+                used_bytes, quota_bytes, needed_bytes
+            ),
+            HostCallError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for HostCallError {
+    fn from(message: String) -> Self {
+        HostCallError::Failed { message }
+    }
+}
+
+impl From<StorageError> for HostCallError {
+    fn from(e: StorageError) -> Self {
+        match e {
+            StorageError::QuotaExceeded { used_bytes, quota_bytes, needed_bytes } => {
+                HostCallError::QuotaExceeded { used_bytes, quota_bytes, needed_bytes }
+            }
+            StorageError::InvalidPath { .. } => HostCallError::InvalidPayload { message: e.to_string() },
+            StorageError::Failed { message } => HostCallError::Failed { message },
+        }
+    }
+}
+
+/// Check that a plugin may make a call. Shell programs must be on
+/// `shell_allowlist` (from settings) or the plugin's own allowlist.
+pub fn authorize(
+    sandbox: &PluginSandbox,
+    shell_allowlist: &[String],
+    plugin_id: &str,
+    call: &HostCall,
+) -> Result<(), HostCallError> {
+    if let Some(permission) = call.required_permission() {
+        if sandbox.is_consent_pending(plugin_id, &permission) {
+            return Err(HostCallError::ConsentPending {
+                permission: permission.as_str().to_string(),
+            });
+        }
+    }
+
+    // Unknown and disabled plugins have no permissions
+    let allowed = match call.required_permission() {
+        Some(permission) => sandbox.check_permission(plugin_id, permission).unwrap_or(false),
+        None => sandbox.get_plugin_context(plugin_id).is_some_and(|c| c.is_enabled),
+    };
+    if !allowed {
+        let permission = call.required_permission().map_or(call.capability(), |p| p.as_str());
+        return Err(HostCallError::PermissionDenied {
+            permission: permission.to_string(),
+        });
+    }
+
+    if let HostCall::Shell { command, .. } = call {
+        if !is_program_allowed(shell_allowlist, sandbox, plugin_id, command) {
+            return Err(HostCallError::CommandNotAllowed { command: command.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+/// Run an authorized call. Notifications, shell commands and file writes
+/// need app services, so plugin_host_call handles those itself.
+pub fn execute(call: &HostCall) -> Result<HostCallOutput, HostCallError> {
+    match call {
+        HostCall::ReadClipboard => {
+            let text = arboard::Clipboard::new()
+                .and_then(|mut clipboard| clipboard.get_text())
+                .map_err(|e| format!("Failed to read clipboard: {}", e))?;
+            Ok(HostCallOutput::ReadClipboard { text })
+        }
+        HostCall::WriteFile { .. } => Err(HostCallError::Failed {
+            message: "File writes run through execute_in_storage".to_string(),
+        }),
+        HostCall::Notification { .. } => Err(HostCallError::Failed {
+            message: "Notifications are sent through the notification service".to_string(),
+        }),
+        HostCall::Network { url, method, body } => {
+            let client = http::blocking_client().map_err(|e| e.to_string())?;
+            let method = method.as_deref().unwrap_or("GET").to_uppercase();
+            let method = reqwest::Method::from_bytes(method.as_bytes())
+                .map_err(|e| HostCallError::InvalidPayload { message: e.to_string() })?;
+
+            // Not retried: the method may not be idempotent
+            let mut request = client.request(method, url).timeout(NETWORK_TIMEOUT);
+            if let Some(body) = body {
+                request = request.body(body.clone());
+            }
+            let response = request.send().map_err(|e| format!("Request failed: {}", e))?;
+            let status = response.status().as_u16();
+            let body = response.text().map_err(|e| format!("Failed to read response: {}", e))?;
+            Ok(HostCallOutput::Network { status, body })
+        }
+        HostCall::Shell { .. } => Err(HostCallError::Failed {
+            message: "Shell commands run through the shell execution service".to_string(),
+        }),
+        HostCall::Storage(_) => Err(HostCallError::Failed {
+            message: "Storage calls run through execute_in_storage".to_string(),
+        }),
+    }
+}
+
+/// Run a Storage or WriteFile call in `plugin_id`'s own storage. Paths
+/// that resolve outside the plugin's data directory are rejected.
+pub fn execute_in_storage(
+    storage: &PluginStorage,
+    plugin_id: &str,
+    call: &HostCall,
+    quota_bytes: u64,
+) -> Result<HostCallOutput, HostCallError> {
+    match call {
+        HostCall::Storage(op) => execute_storage(storage, plugin_id, op, quota_bytes),
+        HostCall::WriteFile { path, contents } => Ok(HostCallOutput::WriteFile {
+            bytes_written: storage.write_file(plugin_id, path, contents, quota_bytes)?,
+        }),
+        other => Err(HostCallError::Failed {
+            message: format!("{} doesn't run in plugin storage", other.capability()),
+        }),
+    }
+}
+
+/// Run a storage operation in `plugin_id`'s own storage
+pub fn execute_storage(
+    storage: &PluginStorage,
+    plugin_id: &str,
+    op: &StorageOp,
+    quota_bytes: u64,
+) -> Result<HostCallOutput, HostCallError> {
+    let result = match op {
+        StorageOp::Get { key } => storage.get(plugin_id, key)?.unwrap_or_default(),
+        StorageOp::Set { key, value } => {
+            storage.set(plugin_id, key, value.clone(), quota_bytes)?;
+            serde_json::Value::Null
+        }
+        StorageOp::Delete { key } => storage.delete(plugin_id, key)?.into(),
+        StorageOp::GetDataDir => storage.data_dir(plugin_id)?.to_string_lossy().into(),
+        StorageOp::ReadFile { path } => storage.read_file(plugin_id, path)?.into(),
+        StorageOp::WriteFile { path, contents } => storage.write_file(plugin_id, path, contents, quota_bytes)?.into(),
+        StorageOp::DeleteFile { path } => storage.delete_file(plugin_id, path)?.into(),
+    };
+    Ok(HostCallOutput::Storage { result })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox_with(permissions: Vec<PluginPermission>) -> PluginSandbox {
+        let sandbox = PluginSandbox::new();
+        sandbox.register_plugin("test".to_string(), permissions).unwrap();
+        sandbox
+    }
+
+    #[test]
+    fn test_allows_granted_capability() {
+        let sandbox = sandbox_with(vec![PluginPermission::Notification]);
+        let call = HostCall::parse("notification", serde_json::json!({ "title": "Done" })).unwrap();
+
+        assert_eq!(authorize(&sandbox, &[], "test", &call), Ok(()));
+    }
+
+    #[test]
+    fn test_denies_and_names_missing_permission() {
+        let sandbox = sandbox_with(vec![PluginPermission::Notification]);
+        let call = HostCall::parse("read_clipboard", serde_json::Value::Null).unwrap();
+
+        assert_eq!(
+            authorize(&sandbox, &[], "test", &call),
+            Err(HostCallError::PermissionDenied { permission: "read_clipboard".to_string() })
+        );
+        assert!(matches!(
+            authorize(&sandbox, &[], "unknown", &call),
+            Err(HostCallError::PermissionDenied { .. })
+        ));
+    }
+
+    #[test]
+    fn test_shell_requires_allowlisted_command() {
+        let sandbox = sandbox_with(vec![PluginPermission::Shell]);
+        sandbox.set_shell_allowlist("test", vec!["echo".to_string()]).unwrap();
+        let global = vec!["git".to_string()];
+
+        let allowed = HostCall::parse("shell", serde_json::json!({ "command": "echo", "args": ["hi"] })).unwrap();
+        let blocked = HostCall::parse("shell", serde_json::json!({ "command": "rm", "args": ["-rf", "/tmp/x"] })).unwrap();
+
+        let git = HostCall::parse("shell", serde_json::json!({ "command": "git", "args": ["status"] })).unwrap();
+
+        assert_eq!(authorize(&sandbox, &global, "test", &allowed), Ok(()));
+        assert_eq!(authorize(&sandbox, &global, "test", &git), Ok(()));
+        assert_eq!(
+            authorize(&sandbox, &global, "test", &blocked),
+            Err(HostCallError::CommandNotAllowed { command: "rm".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_update_adding_dangerous_permission_waits_for_consent() {
+        use crate::models::plugin::PluginManifest;
+        use crate::services::plugin_sandbox::{plugin_permissions_path, ConsentSource};
+
+        let dir = tempfile::tempdir().unwrap();
+        let plugin_dir = dir.path().join("plugins").join("exporter");
+        std::fs::create_dir_all(&plugin_dir).unwrap();
+        let install = |version: &str, permissions: &[&str]| {
+            let manifest = serde_json::json!({
+                "name": "Exporter", "version": version, "description": "d", "author": "a",
+                "permissions": permissions, "entry": "index.js", "triggers": []
+            });
+            std::fs::write(plugin_dir.join("plugin.json"), manifest.to_string()).unwrap();
+            let content = std::fs::read_to_string(plugin_dir.join("plugin.json")).unwrap();
+            serde_json::from_str::<PluginManifest>(&content).unwrap()
+        };
+        let requested = |manifest: &PluginManifest| manifest.parsed_permissions().unwrap();
+        let write = HostCall::parse("write_file", serde_json::json!({ "path": "/tmp/x", "contents": "" })).unwrap();
+        let notify = HostCall::parse("notification", serde_json::json!({ "title": "Done" })).unwrap();
+
+        // 1.0 is installed with its one permission accepted
+        let v1 = install("1.0.0", &["notification"]);
+        let sandbox = PluginSandbox::new();
+        for permission in requested(&v1) {
+            sandbox.set_consent("exporter", permission, true, Some(v1.version.clone()), ConsentSource::Install);
+        }
+        assert!(sandbox.request_consents("exporter", &v1.version, &requested(&v1), ConsentSource::Install).is_empty());
+
+        // 2.0 adds fs:write, which is asked for and blocked meanwhile
+        let v2 = install("2.0.0", &["notification", "fs:write"]);
+        let added = sandbox.request_consents("exporter", &v2.version, &requested(&v2), ConsentSource::Update);
+        assert_eq!(added, vec![PluginPermission::WriteFile]);
+        assert_eq!(
+            authorize(&sandbox, &[], "exporter", &write),
+            Err(HostCallError::ConsentPending { permission: "write_file".to_string() })
+        );
+        assert_eq!(authorize(&sandbox, &[], "exporter", &notify), Ok(()));
+
+        // The prompt survives a restart
+        let path = plugin_permissions_path(dir.path());
+        sandbox.save(&path).unwrap();
+        let restarted = PluginSandbox::new();
+        restarted.restore(&path);
+        let pending = restarted.get_pending_consents();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(
+            (pending[0].version.as_str(), pending[0].source, pending[0].dangerous),
+            ("2.0.0", ConsentSource::Update, true)
+        );
+
+        let consent = restarted.respond_to_consent("exporter", &PluginPermission::WriteFile, true).unwrap();
+        assert_eq!((consent.granted_version.as_deref(), consent.source), (Some("2.0.0"), ConsentSource::Update));
+        assert!(consent.granted_at.is_some());
+        assert_eq!(authorize(&restarted, &[], "exporter", &write), Ok(()));
+        assert!(restarted.get_pending_consents().is_empty());
+        let history: Vec<_> = restarted
+            .get_consents("exporter")
+            .into_iter()
+            .map(|c| (c.permission, c.source))
+            .collect();
+        assert_eq!(history, vec![
+            (PluginPermission::Notification, ConsentSource::Install),
+            (PluginPermission::WriteFile, ConsentSource::Update),
+        ]);
+
+        // Re-checking the same manifest doesn't ask again
+        assert!(restarted.request_consents("exporter", &v2.version, &requested(&v2), ConsentSource::Update).is_empty());
+    }
+
+    #[test]
+    fn test_io_capabilities_have_timeouts() {
+        let write = HostCall::parse("write_file", serde_json::json!({ "path": "/tmp/x", "contents": "" })).unwrap();
+        let fetch = HostCall::parse("network", serde_json::json!({ "url": "https://example.com" })).unwrap();
+        let notify = HostCall::parse("notification", serde_json::json!({ "title": "t" })).unwrap();
+
+        assert_eq!(write.timeout(), Some(FILE_IO_TIMEOUT));
+        assert_eq!(fetch.timeout(), Some(NETWORK_TIMEOUT));
+        assert_eq!(notify.timeout(), None);
+    }
+
+    #[test]
+    fn test_storage_needs_enabled_plugin_only() {
+        let sandbox = sandbox_with(vec![]);
+        let call = HostCall::parse("storage", serde_json::json!({ "op": "get", "key": "draft" })).unwrap();
+
+        assert_eq!(authorize(&sandbox, &[], "test", &call), Ok(()));
+        sandbox.set_plugin_enabled("test", false).unwrap();
+        assert_eq!(
+            authorize(&sandbox, &[], "test", &call),
+            Err(HostCallError::PermissionDenied { permission: "storage".to_string() })
+        );
+        assert!(authorize(&sandbox, &[], "unknown", &call).is_err());
+    }
+
+    #[test]
+    fn test_storage_calls_are_scoped_to_the_plugin() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = PluginStorage::new(dir.path().to_path_buf());
+        let call = |payload| match HostCall::parse("storage", payload).unwrap() {
+            HostCall::Storage(op) => op,
+            other => panic!("parsed as {:?}", other),
+        };
+
+        let write = call(serde_json::json!({ "op": "write_file", "path": "../escape.txt", "contents": "x" }));
+        assert!(matches!(
+            execute_storage(&storage, "test", &write, 1024),
+            Err(HostCallError::InvalidPayload { .. })
+        ));
+
+        let set = call(serde_json::json!({ "op": "set", "key": "k", "value": "x".repeat(2000) }));
+        assert!(matches!(
+            execute_storage(&storage, "test", &set, 1024),
+            Err(HostCallError::QuotaExceeded { quota_bytes: 1024, .. })
+        ));
+
+        let data_dir = call(serde_json::json!({ "op": "get_data_dir" }));
+        let HostCallOutput::Storage { result } = execute_storage(&storage, "test", &data_dir, 1024).unwrap() else {
+            panic!("not a storage result");
+        };
+        assert!(std::path::Path::new(result.as_str().unwrap()).is_dir());
+    }
+
+    #[test]
+    fn test_write_file_stays_in_the_plugin_data_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = PluginStorage::new(dir.path().join("plugins-data"));
+        let write = |path: &str| {
+            let call = HostCall::parse("write_file", serde_json::json!({ "path": path, "contents": "hi" })).unwrap();
+            execute_in_storage(&storage, "exporter", &call, 1024)
+        };
+
+        assert!(matches!(write("out/report.txt"), Ok(HostCallOutput::WriteFile { bytes_written: 2 })));
+        let data_dir = storage.data_dir("exporter").unwrap();
+        assert_eq!(std::fs::read_to_string(data_dir.join("out/report.txt")).unwrap(), "hi");
+
+        let outside = dir.path().join("outside.txt");
+        for path in [outside.to_str().unwrap(), "../../outside.txt", "out/../../outside.txt"] {
+            assert!(matches!(write(path), Err(HostCallError::InvalidPayload { .. })), "{}", path);
+        }
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path(), data_dir.join("link")).unwrap();
+            assert!(matches!(write("link/outside.txt"), Err(HostCallError::InvalidPayload { .. })));
+        }
+        assert!(!outside.exists());
+    }
+
+    #[test]
+    fn test_invalid_payload() {
+        assert!(matches!(
+            HostCall::parse("write_file", serde_json::json!({ "path": "/tmp/x" })),
+            Err(HostCallError::InvalidPayload { .. })
+        ));
+        assert!(matches!(
+            HostCall::parse("launch_missiles", serde_json::Value::Null),
+            Err(HostCallError::InvalidPayload { .. })
+        ));
+    }
+}
@@ -0,0 +1,162 @@
+//! Plugin Install Metadata
+//! Each install path records when, where from and which version a plugin
+//! was installed in `.install-meta.json` inside the plugin's folder. The
+//! folder's own timestamps can't stand in for it: settings and health checks
+//! write into the folder, which moves its modified time. Updates replace the
+//! folder, file included, so they read it first and carry the original
+//! install over, noting the update in `updated_at`.
+
+use crate::models::plugin::PluginSource;
+use crate::utils::atomic_write::write_atomic;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+pub const INSTALL_META_FILE: &str = ".install-meta.json";
+
+/// Contents of `.install-meta.json`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallMeta {
+    /// Unix timestamp (ms)
+    pub installed_at: i64,
+    pub source: PluginSource,
+    /// Version that was installed
+    pub version: String,
+    pub install_id: String,
+    /// Unix timestamp (ms) of the latest update, None until updated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+/// Record a fresh install of the plugin in `plugin_dir`
+pub fn record_install(plugin_dir: &Path, source: PluginSource, version: &str) -> Result<InstallMeta, String> {
+    let meta = InstallMeta {
+        installed_at: chrono::Utc::now().timestamp_millis(),
+        source,
+        version: version.to_string(),
+        install_id: uuid::Uuid::new_v4().to_string(),
+        updated_at: None,
+    };
+    write_meta(plugin_dir, &meta)?;
+    Ok(meta)
+}
+
+/// The metadata recorded in `plugin_dir`, if any. Read it before an update
+/// replaces the folder.
+pub fn recorded_install(plugin_dir: &Path) -> Option<InstallMeta> {
+    fs::read_to_string(plugin_dir.join(INSTALL_META_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+}
+
+/// Record that the plugin in `plugin_dir` was updated to `version`. The
+/// `previous` install's time, source and id are kept; without one this is
+/// recorded as a fresh install from `source`.
+pub fn record_update(
+    plugin_dir: &Path,
+    previous: Option<InstallMeta>,
+    source: PluginSource,
+    version: &str,
+) -> Result<InstallMeta, String> {
+    let Some(previous) = previous else {
+        return record_install(plugin_dir, source, version);
+    };
+    let meta = InstallMeta {
+        version: version.to_string(),
+        updated_at: Some(chrono::Utc::now().timestamp_millis()),
+        ..previous
+    };
+    write_meta(plugin_dir, &meta)?;
+    Ok(meta)
+}
+
+/// Read a plugin's install metadata. Plugins installed before it was
+/// recorded get it from the folder's creation time, with `source` and
+/// `version` as given, and have it written for next time.
+pub fn read_install_meta(plugin_dir: &Path, source: PluginSource, version: &str) -> Result<InstallMeta, String> {
+    if let Some(meta) = recorded_install(plugin_dir) {
+        return Ok(meta);
+    }
+
+    let metadata = fs::metadata(plugin_dir).map_err(|e| format!("Failed to get plugin metadata: {}", e))?;
+    let created = metadata
+        .created()
+        .or_else(|_| metadata.modified())
+        .map_err(|e| format!("Failed to get installation time: {}", e))?;
+    let meta = InstallMeta {
+        installed_at: created
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_err(|e| format!("Failed to convert timestamp: {}", e))?
+            .as_millis() as i64,
+        source,
+        version: version.to_string(),
+        install_id: uuid::Uuid::new_v4().to_string(),
+        updated_at: None,
+    };
+    if let Err(e) = write_meta(plugin_dir, &meta) {
+        eprintln!(
+            "[Plugins] Failed to migrate install metadata in {:?}: {}",
+            plugin_dir, e
+        );
+    }
+    Ok(meta)
+}
+
+fn write_meta(plugin_dir: &Path, meta: &InstallMeta) -> Result<(), String> {
+    let content =
+        serde_json::to_string_pretty(meta).map_err(|e| format!("Failed to serialize install metadata: {}", e))?;
+    write_atomic(&plugin_dir.join(INSTALL_META_FILE), content)
+        .map_err(|e| format!("Failed to write install metadata: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_existing_install_is_migrated_once() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let migrated = read_install_meta(dir.path(), PluginSource::Marketplace, "1.2.0").unwrap();
+        assert!(migrated.installed_at > 0);
+        assert_eq!(migrated.source, PluginSource::Marketplace);
+        assert!(dir.path().join(INSTALL_META_FILE).exists());
+
+        // The recorded meta wins over what the caller guesses
+        let reread = read_install_meta(dir.path(), PluginSource::Local, "9.9.9").unwrap();
+        assert_eq!(reread, migrated);
+    }
+
+    #[test]
+    fn test_writes_into_plugin_dir_keep_install_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let recorded = record_install(dir.path(), PluginSource::Local, "1.0.0").unwrap();
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        fs::write(dir.path().join("settings.json"), "{}").unwrap();
+        fs::write(dir.path().join("index.js"), "export default {}").unwrap();
+
+        let meta = read_install_meta(dir.path(), PluginSource::Marketplace, "2.0.0").unwrap();
+        assert_eq!(meta.installed_at, recorded.installed_at);
+        assert_eq!(meta, recorded);
+    }
+
+    #[test]
+    fn test_update_keeps_install_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let installed = record_install(dir.path(), PluginSource::Marketplace, "1.0.0").unwrap();
+
+        // The update replaces the folder, meta file included
+        let previous = recorded_install(dir.path());
+        fs::remove_file(dir.path().join(INSTALL_META_FILE)).unwrap();
+        let updated = record_update(dir.path(), previous, PluginSource::Local, "2.0.0").unwrap();
+
+        assert_eq!(updated.installed_at, installed.installed_at);
+        assert_eq!(updated.install_id, installed.install_id);
+        assert_eq!(updated.source, PluginSource::Marketplace);
+        assert_eq!(updated.version, "2.0.0");
+        assert!(updated.updated_at.is_some());
+        assert_eq!(read_install_meta(dir.path(), PluginSource::Local, "2.0.0").unwrap(), updated);
+    }
+}
@@ -13,7 +13,11 @@ use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
 use tempfile::TempDir;
 
-use crate::models::plugin::PluginManifest;
+use crate::models::plugin::{PluginManifest, PluginSource};
+use crate::services::plugin_install_meta::record_install;
+use crate::services::plugin_validator::{PluginValidationResult, PluginValidator, SecurityReport};
+use crate::services::trigger_conflicts::TriggerConflict;
+use crate::t;
 
 /// Plugin installation progress
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +34,11 @@ pub struct PackageValidation {
     pub manifest: Option<PluginManifest>,
     pub errors: Vec<String>,
     pub warnings: Vec<String>,
+    /// Security score and risks, once the manifest could be read
+    pub security_report: Option<SecurityReport>,
+    /// Triggers that collide with installed plugins; they install suppressed
+    #[serde(default)]
+    pub trigger_conflicts: Vec<TriggerConflict>,
 }
 
 /// Extraction result
@@ -70,12 +79,14 @@ impl PluginInstaller {
         
         // Check file extension
         if !self.is_supported_format(&path) {
-            errors.push("不支持的文件格式，请使用 .zip 或 .tar.gz 文件".to_string());
+            errors.push(t!("installer.unsupported_format_hint"));
             return Ok(PackageValidation {
                 is_valid: false,
                 manifest: None,
                 errors,
                 warnings,
+                security_report: None,
+                trigger_conflicts: Vec::new(),
             });
         }
 
@@ -83,26 +94,30 @@ impl PluginInstaller {
         let manifest = match self.extract_and_validate_manifest(&path).await {
             Ok(manifest) => manifest,
             Err(e) => {
-                errors.push(format!("插件清单验证失败: {}", e));
+                errors.push(t!("installer.manifest_invalid", error = e));
                 return Ok(PackageValidation {
                     is_valid: false,
                     manifest: None,
                     errors,
                     warnings,
+                    security_report: None,
+                    trigger_conflicts: Vec::new(),
                 });
             }
         };
 
         // Validate manifest fields
-        if let Some(manifest_errors) = self.validate_manifest_fields(&manifest, None) {
-            errors.extend(manifest_errors);
-        }
+        let validator = PluginValidator::new();
+        let result: PluginValidationResult = validator.validate_manifest(&manifest, None).into();
+        let security_report = validator.security_report(&manifest);
 
         Ok(PackageValidation {
-            is_valid: errors.is_empty(),
+            is_valid: result.is_valid,
             manifest: Some(manifest),
-            errors,
-            warnings,
+            errors: result.errors,
+            warnings: result.warnings,
+            security_report: Some(security_report),
+            trigger_conflicts: Vec::new(),
         })
     }
 
@@ -126,15 +141,15 @@ impl PluginInstaller {
                             if tar_path.exists() {
                                 self.extract_tar(&tar_path, &extract_dir).await?;
                             } else {
-                                return Err(anyhow!("Invalid .tar.gz format: missing .tar file"));
+                                return Err(anyhow!(t!("installer.invalid_tar_gz")));
                             }
                         }
                     }
                 } else {
-                    return Err(anyhow!("Invalid .tar.gz format"));
+                    return Err(anyhow!(t!("installer.invalid_tar_gz")));
                 }
             }
-            _ => return Err(anyhow!("不支持的文件格式")),
+            _ => return Err(anyhow!(t!("installer.unsupported_format"))),
         }
 
         // Load and validate manifest
@@ -150,32 +165,71 @@ impl PluginInstaller {
         })
     }
 
-    /// Install plugin from extracted directory
+    /// Install plugin from extracted directory. Plugins whose security
+    /// score is below `min_security_score` are refused unless
+    /// `allow_low_score` is set.
     pub async fn install_plugin(
         &self,
         extracted_path: &str,
         plugin_id: &str,
+        min_security_score: u8,
+        allow_low_score: bool,
     ) -> Result<()> {
         let extract_path = Path::new(extracted_path);
         let plugin_dir = self.plugins_dir.join(plugin_id);
         
         // Check if plugin already exists
         if plugin_dir.exists() {
-            return Err(anyhow!("插件已存在: {}", plugin_id));
+            return Err(anyhow!(t!("installer.already_installed", plugin_id = plugin_id)));
+        }
+
+        // Check the security score and host API before anything is copied
+        let manifest = self.load_manifest(extract_path).await?;
+        let score = PluginValidator::new().calculate_security_score(&manifest);
+        if score < min_security_score && !allow_low_score {
+            return Err(anyhow!(t!(
+                "installer.security_score_too_low",
+                score = score,
+                minimum = min_security_score
+            )));
+        }
+        if let Some(error) = PluginValidator::new().api_version_error(manifest.api_version.as_deref()) {
+            return Err(anyhow!(error));
+        }
+
+        // Validate the staged package, entry file included, so a broken
+        // package never reaches the plugins dir
+        let result: PluginValidationResult = PluginValidator::new()
+            .validate_installed(&manifest, plugin_id, extract_path)
+            .into();
+        if !result.is_valid {
+            return Err(anyhow!(t!("installer.validation_failed", errors = result.errors.join(", "))));
         }
 
         // Create plugin directory
         fs::create_dir_all(&plugin_dir)?;
         
-        // Move extracted files to plugin directory
-        self.move_directory(&extract_path, &plugin_dir).await?;
-
-        // Load and validate manifest
-        let manifest = self.load_manifest(&plugin_dir).await?;
-        if let Some(errors) = self.validate_manifest_fields(&manifest, Some(plugin_id)) {
-            return Err(anyhow!("插件验证失败: {}", errors.join(", ")));
+        // Move extracted files to plugin directory, removing a partial copy
+        if let Err(e) = self.move_directory(extract_path, &plugin_dir).await {
+            let _ = fs::remove_dir_all(&plugin_dir);
+            return Err(e);
         }
+        record_install(&plugin_dir, PluginSource::Local, &manifest.version).map_err(|e| anyhow!(e))?;
+
+        Ok(())
+    }
 
+    /// Remove an extraction folder once it's no longer needed. Only folders
+    /// inside this installer's temp dir are touched.
+    pub fn cleanup_extraction(&self, extracted_path: &str) -> Result<()> {
+        let path = Path::new(extracted_path);
+        let inside_temp = match (path.canonicalize(), self.temp_dir.canonicalize()) {
+            (Ok(path), Ok(temp_dir)) => path.starts_with(&temp_dir) && path != temp_dir,
+            _ => false,
+        };
+        if inside_temp {
+            fs::remove_dir_all(path)?;
+        }
         Ok(())
     }
 
@@ -192,13 +246,13 @@ impl PluginInstaller {
     /// Extract ZIP archive
     async fn extract_zip(&self, zip_path: &Path, extract_dir: &Path) -> Result<()> {
         let file = fs::File::open(zip_path)
-            .map_err(|e| anyhow!("无法打开ZIP文件: {}", e))?;
+            .map_err(|e| anyhow!(t!("installer.zip_open_failed", error = e)))?;
         let mut archive = ZipArchive::new(file)
-            .map_err(|e| anyhow!("无法读取ZIP存档: {}", e))?;
+            .map_err(|e| anyhow!(t!("installer.zip_read_failed", error = e)))?;
         
         for i in 0..archive.len() {
             let mut file = archive.by_index(i)
-                .map_err(|e| anyhow!("ZIP文件读取错误: {}", e))?;
+                .map_err(|e| anyhow!(t!("installer.zip_entry_failed", error = e)))?;
             let outpath = extract_dir.join(file.name());
             
             if file.name().ends_with('/') {
@@ -208,7 +262,7 @@ impl PluginInstaller {
                     fs::create_dir_all(parent)?;
                 }
                 let mut outfile = fs::File::create(&outpath)
-                    .map_err(|e| anyhow!("无法创建文件: {}", e))?;
+                    .map_err(|e| anyhow!(t!("installer.create_file_failed", error = e)))?;
                 std::io::copy(&mut file, &mut outfile)?;
             }
         }
@@ -219,12 +273,12 @@ impl PluginInstaller {
     /// Extract TAR archive
     async fn extract_tar(&self, tar_path: &Path, extract_dir: &Path) -> Result<()> {
         let file = fs::File::open(tar_path)
-            .map_err(|e| anyhow!("无法打开TAR文件: {}", e))?;
+            .map_err(|e| anyhow!(t!("installer.tar_open_failed", error = e)))?;
         let decoder = GzDecoder::new(file);
         let mut archive = Archive::new(decoder);
         
         archive.unpack(extract_dir)
-            .map_err(|e| anyhow!("TAR解压失败: {}", e))?;
+            .map_err(|e| anyhow!(t!("installer.tar_extract_failed", error = e)))?;
         
         Ok(())
     }
@@ -239,7 +293,7 @@ impl PluginInstaller {
         match package_path.extension().and_then(|s| s.to_str()) {
             Some("zip") => self.extract_zip_manifest(package_path, extract_dir).await?,
             Some("gz") => self.extract_tar_manifest(package_path, extract_dir).await?,
-            _ => return Err(anyhow!("不支持的文件格式")),
+            _ => return Err(anyhow!(t!("installer.unsupported_format"))),
         }
 
         // Load and validate manifest
@@ -277,10 +331,10 @@ impl PluginInstaller {
                 if let Some(file_stem) = parent.file_stem() {
                     parent.join(format!("{}.tar", file_stem.to_string_lossy()))
                 } else {
-                    return Err(anyhow!("无效的.tar.gz格式"));
+                    return Err(anyhow!(t!("installer.invalid_tar_gz")));
                 }
             } else {
-                return Err(anyhow!("无效的.tar.gz格式"));
+                return Err(anyhow!(t!("installer.invalid_tar_gz")));
             }
         } else {
             tar_path.to_path_buf()
@@ -317,8 +371,10 @@ impl PluginInstaller {
         let manifest_path = dir.join("plugin.json");
         if manifest_path.exists() {
             let content = fs::read_to_string(&manifest_path)?;
-            return serde_json::from_str(&content)
-                .map_err(|e| anyhow!("JSON解析失败: {}", e));
+            let mut manifest: PluginManifest = serde_json::from_str(&content)
+                .map_err(|e| anyhow!(t!("installer.manifest_json_invalid", error = e)))?;
+            manifest.normalize_permissions();
+            return Ok(manifest);
         }
         
         // Try plugin.toml
@@ -327,49 +383,13 @@ impl PluginInstaller {
             let content = fs::read_to_string(&manifest_path)?;
             // For now, assume TOML format will be similar to JSON structure
             // In real implementation, you'd use toml crate
-            return serde_json::from_str(&content)
-                .map_err(|e| anyhow!("配置文件解析失败: {}", e));
+            let mut manifest: PluginManifest = serde_json::from_str(&content)
+                .map_err(|e| anyhow!(t!("installer.manifest_config_invalid", error = e)))?;
+            manifest.normalize_permissions();
+            return Ok(manifest);
         }
         
-        Err(anyhow!("找不到插件清单文件"))
-    }
-
-    /// Validate manifest fields
-    fn validate_manifest_fields(&self, manifest: &PluginManifest, plugin_id: Option<&str>) -> Option<Vec<String>> {
-        let mut errors = Vec::new();
-
-        // Validate ID format if provided
-        if let Some(id) = plugin_id {
-            if id.is_empty() {
-                errors.push("插件ID不能为空".to_string());
-            } else if !id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-') {
-                errors.push("插件ID只能包含小写字母、数字和连字符".to_string());
-            }
-        }
-
-        // Validate version
-        if manifest.version.is_empty() {
-            errors.push("版本号不能为空".to_string());
-        }
-
-        // Validate required fields
-        if manifest.name.is_empty() {
-            errors.push("插件名称不能为空".to_string());
-        }
-
-        if manifest.description.is_empty() {
-            errors.push("插件描述不能为空".to_string());
-        }
-
-        if manifest.entry.is_empty() {
-            errors.push("入口文件路径不能为空".to_string());
-        }
-
-        if errors.is_empty() {
-            None
-        } else {
-            Some(errors)
-        }
+        Err(anyhow!(t!("installer.manifest_not_found")))
     }
 
     /// Collect all files in directory recursively
@@ -385,7 +405,7 @@ impl PluginInstaller {
             let entry = entry?;
             let path = entry.path();
             let relative_path = path.strip_prefix(dir)
-                .map_err(|_| anyhow!("路径计算错误"))?;
+                .map_err(|_| anyhow!(t!("installer.path_error")))?;
             let relative_str = relative_path.to_string_lossy();
             let full_relative = if base_path.is_empty() {
                 relative_str.to_string()
@@ -417,7 +437,7 @@ impl PluginInstaller {
             let file_name_os = entry.file_name();
             let file_name = file_name_os
                 .to_str()
-                .ok_or_else(|| anyhow!("无效的文件名"))?;
+                .ok_or_else(|| anyhow!(t!("installer.invalid_file_name")))?;
             let dst_path = dst.join(file_name);
 
             if src_path.is_dir() {
@@ -442,7 +462,7 @@ impl PluginInstaller {
             let file_name_os = entry.file_name();
             let file_name = file_name_os
                 .to_str()
-                .ok_or_else(|| anyhow!("无效的文件名"))?;
+                .ok_or_else(|| anyhow!(t!("installer.invalid_file_name")))?;
             let dst_path = dst.join(file_name);
 
             if src_path.is_dir() {
@@ -454,4 +474,125 @@ impl PluginInstaller {
         }
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tauri::async_runtime::block_on;
+
+    fn write_risky_plugin(dir: &Path) {
+        fs::create_dir_all(dir).unwrap();
+        let manifest = serde_json::json!({
+            "name": "Risky Tool",
+            "version": "1.0.0",
+            "description": "Runs commands and talks to the network",
+            "author": "tester",
+            "permissions": ["shell", "network", "write_file"],
+            "entry": "index.js",
+            "triggers": ["risky:"]
+        });
+        fs::write(dir.join("plugin.json"), manifest.to_string()).unwrap();
+        fs::write(dir.join("index.js"), "export default {}").unwrap();
+    }
+
+    fn install_error(root: &Path, extracted: &Path) -> String {
+        let installer = PluginInstaller::new(root.join("temp"), root.join("plugins"));
+        let result = block_on(installer.install_plugin(&extracted.to_string_lossy(), "risky-tool", 0, true));
+        assert!(!root.join("plugins").join("risky-tool").exists());
+        result.unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_low_score_install_requires_override() {
+        let root = tempfile::tempdir().unwrap();
+        let extracted = root.path().join("extracted");
+        write_risky_plugin(&extracted);
+        let extracted_path = extracted.to_string_lossy().to_string();
+        let installer = PluginInstaller::new(root.path().join("temp"), root.path().join("plugins"));
+
+        // Scores 65, below the minimum of 70
+        let blocked = block_on(installer.install_plugin(&extracted_path, "risky-tool", 70, false));
+        assert!(blocked.is_err());
+        assert!(!root.path().join("plugins").join("risky-tool").exists());
+        assert!(extracted.join("plugin.json").exists());
+
+        block_on(installer.install_plugin(&extracted_path, "risky-tool", 70, true)).unwrap();
+        assert!(root.path().join("plugins").join("risky-tool").join("plugin.json").exists());
+        assert!(root.path().join("plugins").join("risky-tool").join(".install-meta.json").exists());
+    }
+
+    #[test]
+    fn test_score_at_minimum_installs_without_override() {
+        let root = tempfile::tempdir().unwrap();
+        let extracted = root.path().join("extracted");
+        write_risky_plugin(&extracted);
+        let installer = PluginInstaller::new(root.path().join("temp"), root.path().join("plugins"));
+
+        block_on(installer.install_plugin(&extracted.to_string_lossy(), "risky-tool", 65, false)).unwrap();
+    }
+
+    #[test]
+    fn test_cleanup_only_removes_extractions_in_temp_dir() {
+        let root = tempfile::tempdir().unwrap();
+        let temp_dir = root.path().join("temp");
+        let extraction = temp_dir.join("3f2a");
+        let outside = root.path().join("extracted");
+        write_risky_plugin(&extraction);
+        write_risky_plugin(&outside);
+        let installer = PluginInstaller::new(temp_dir.clone(), root.path().join("plugins"));
+
+        installer.cleanup_extraction(&outside.to_string_lossy()).unwrap();
+        installer.cleanup_extraction(&temp_dir.to_string_lossy()).unwrap();
+        assert!(outside.exists());
+        assert!(temp_dir.exists());
+
+        installer.cleanup_extraction(&extraction.to_string_lossy()).unwrap();
+        assert!(!extraction.exists());
+    }
+
+    #[test]
+    fn test_package_missing_entry_is_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let extracted = root.path().join("extracted");
+        write_risky_plugin(&extracted);
+        fs::remove_file(extracted.join("index.js")).unwrap();
+
+        let error = install_error(root.path(), &extracted);
+        assert!(error.contains("index.js"), "{}", error);
+        assert!(extracted.join("plugin.json").exists());
+    }
+
+    #[test]
+    fn test_package_with_empty_entry_is_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let extracted = root.path().join("extracted");
+        write_risky_plugin(&extracted);
+        fs::write(extracted.join("index.js"), "").unwrap();
+
+        install_error(root.path(), &extracted);
+    }
+
+    #[test]
+    fn test_package_with_oversized_entry_is_rejected() {
+        let root = tempfile::tempdir().unwrap();
+        let extracted = root.path().join("extracted");
+        write_risky_plugin(&extracted);
+        let entry = fs::File::create(extracted.join("index.js")).unwrap();
+        entry.set_len(crate::services::plugin_validator::MAX_ENTRY_BYTES + 1).unwrap();
+
+        install_error(root.path(), &extracted);
+    }
+
+    #[test]
+    #[ignore = "needs support for manifests under a top-level folder"]
+    fn test_package_nested_under_top_level_folder_installs() {
+        let root = tempfile::tempdir().unwrap();
+        let extracted = root.path().join("extracted");
+        write_risky_plugin(&extracted.join("risky-tool"));
+        let installer = PluginInstaller::new(root.path().join("temp"), root.path().join("plugins"));
+
+        block_on(installer.install_plugin(&extracted.to_string_lossy(), "risky-tool", 0, true)).unwrap();
+        assert!(root.path().join("plugins").join("risky-tool").join("index.js").exists());
+    }
+}
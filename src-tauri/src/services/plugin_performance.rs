@@ -1,5 +1,7 @@
 //! Plugin Performance Monitoring Service
-//! Tracks performance metrics for plugin operations
+//! Tracks performance metrics for plugin operations. Recent metrics are
+//! kept in memory; daily per-plugin rollups are drained periodically into
+//! the analytics database for the plugin history chart.
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
@@ -7,6 +9,9 @@ use std::collections::HashMap;
 use std::time::{Duration, Instant};
 use serde::{Deserialize, Serialize};
 use std::sync::{Arc, Mutex};
+use sysinfo::{ProcessRefreshKind, ProcessesToUpdate, System};
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
 
 /// Performance metric for a single operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,12 +37,195 @@ pub struct PluginPerformanceStats {
     pub max_duration_ms: u64,
     pub last_operation: Option<i64>,
     pub slow_operations: Vec<PerformanceMetric>,
+    /// Highest memory measurement, None until one is recorded
+    pub peak_memory_mb: Option<f64>,
+    pub average_memory_mb: Option<f64>,
+    pub memory_samples: u64,
+}
+
+impl PluginPerformanceStats {
+    fn new(plugin_id: &str) -> Self {
+        Self {
+            plugin_id: plugin_id.to_string(),
+            total_operations: 0,
+            successful_operations: 0,
+            failed_operations: 0,
+            average_duration_ms: 0.0,
+            min_duration_ms: 0,
+            max_duration_ms: 0,
+            last_operation: None,
+            slow_operations: Vec::new(),
+            peak_memory_mb: None,
+            average_memory_mb: None,
+            memory_samples: 0,
+        }
+    }
+
+    fn record_memory(&mut self, memory_mb: f64) {
+        self.memory_samples += 1;
+        let average = self.average_memory_mb.unwrap_or(0.0);
+        self.average_memory_mb = Some(average + (memory_mb - average) / self.memory_samples as f64);
+        self.peak_memory_mb = Some(self.peak_memory_mb.map_or(memory_mb, |peak| peak.max(memory_mb)));
+    }
+
+    /// Whether peak memory went over `threshold_mb`
+    pub fn exceeds_memory(&self, threshold_mb: f64) -> bool {
+        self.peak_memory_mb.is_some_and(|peak| peak > threshold_mb)
+    }
+}
+
+/// Resident set size of this process in bytes, None where it can't be read
+pub fn process_rss_bytes() -> Option<u64> {
+    let pid = sysinfo::get_current_pid().ok()?;
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        ProcessesToUpdate::Some(&[pid]),
+        false,
+        ProcessRefreshKind::new().with_memory(),
+    );
+    system.process(pid).map(|process| process.memory()).filter(|&bytes| bytes > 0)
+}
+
+/// Measures how much the process RSS grows across a native operation
+pub struct MemoryProbe {
+    start_bytes: Option<u64>,
+}
+
+impl MemoryProbe {
+    pub fn start() -> Self {
+        Self { start_bytes: process_rss_bytes() }
+    }
+
+    /// Growth in MB (0 if RSS shrank), None if RSS isn't readable
+    pub fn finish(self) -> Option<f64> {
+        let start = self.start_bytes?;
+        let end = process_rss_bytes()?;
+        Some(end.saturating_sub(start) as f64 / BYTES_PER_MB)
+    }
+}
+
+/// Durations kept per plugin per day for the p95 estimate
+pub const ROLLUP_RESERVOIR_SIZE: usize = 256;
+
+/// One plugin's operations on one UTC day ("YYYY-MM-DD")
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyRollup {
+    pub plugin_id: String,
+    pub date: String,
+    pub op_count: u64,
+    pub avg_ms: f64,
+    pub p95_ms: u64,
+    pub failures: u64,
+    /// Uniform sample of at most ROLLUP_RESERVOIR_SIZE durations
+    #[serde(skip)]
+    pub samples: Vec<u64>,
+    #[serde(skip)]
+    rng: u64,
+}
+
+impl DailyRollup {
+    pub fn new(plugin_id: &str, date: &str) -> Self {
+        Self {
+            plugin_id: plugin_id.to_string(),
+            date: date.to_string(),
+            op_count: 0,
+            avg_ms: 0.0,
+            p95_ms: 0,
+            failures: 0,
+            samples: Vec::new(),
+            rng: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    /// Xorshift; the reservoir only needs a cheap, even spread
+    fn next_random(&mut self) -> u64 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    pub fn record(&mut self, duration_ms: u64, success: bool) {
+        self.op_count += 1;
+        self.avg_ms += (duration_ms as f64 - self.avg_ms) / self.op_count as f64;
+        if !success {
+            self.failures += 1;
+        }
+
+        // Reservoir sampling (Algorithm R)
+        if self.samples.len() < ROLLUP_RESERVOIR_SIZE {
+            self.samples.push(duration_ms);
+        } else {
+            let slot = (self.next_random() % self.op_count) as usize;
+            if slot < ROLLUP_RESERVOIR_SIZE {
+                self.samples[slot] = duration_ms;
+            }
+        }
+        self.p95_ms = percentile(&self.samples, 0.95);
+    }
+
+    /// Fold in another rollup for the same plugin and day. Each merged
+    /// sample is drawn from a side in proportion to its operation count.
+    pub fn merge(&mut self, other: DailyRollup) {
+        let total = self.op_count + other.op_count;
+        if total == 0 {
+            return;
+        }
+
+        let mut ours = std::mem::take(&mut self.samples);
+        let mut theirs = other.samples;
+        let mut merged = Vec::with_capacity(ROLLUP_RESERVOIR_SIZE);
+        while merged.len() < ROLLUP_RESERVOIR_SIZE && !(ours.is_empty() && theirs.is_empty()) {
+            let from_ours = theirs.is_empty()
+                || (!ours.is_empty() && self.next_random() % total < self.op_count);
+            let side = if from_ours { &mut ours } else { &mut theirs };
+            let index = (self.next_random() % side.len() as u64) as usize;
+            merged.push(side.swap_remove(index));
+        }
+
+        self.avg_ms = (self.avg_ms * self.op_count as f64 + other.avg_ms * other.op_count as f64) / total as f64;
+        self.op_count = total;
+        self.failures += other.failures;
+        self.samples = merged;
+        self.p95_ms = percentile(&self.samples, 0.95);
+    }
+}
+
+/// Nearest-rank percentile, 0 for no samples
+pub fn percentile(samples: &[u64], quantile: f64) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    let mut sorted = samples.to_vec();
+    sorted.sort_unstable();
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// UTC day of a unix timestamp in milliseconds
+pub fn rollup_date(timestamp_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .unwrap_or_default()
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// `get_plugin_performance` result: live stats plus daily history
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginPerformance {
+    pub plugin_id: String,
+    /// None when the plugin hasn't run since startup
+    pub stats: Option<PluginPerformanceStats>,
+    /// Oldest first
+    pub history: Vec<DailyRollup>,
 }
 
 /// Performance monitoring service
 pub struct PluginPerformanceMonitor {
     metrics: Arc<Mutex<Vec<PerformanceMetric>>>,
     stats: Arc<Mutex<HashMap<String, PluginPerformanceStats>>>,
+    /// Rollups not yet written to disk, keyed by (plugin id, date)
+    rollups: Arc<Mutex<HashMap<(String, String), DailyRollup>>>,
     max_metrics: usize,
     slow_threshold_ms: u64,
 }
@@ -48,6 +236,7 @@ impl PluginPerformanceMonitor {
         Self {
             metrics: Arc::new(Mutex::new(Vec::with_capacity(max_metrics))),
             stats: Arc::new(Mutex::new(HashMap::new())),
+            rollups: Arc::new(Mutex::new(HashMap::new())),
             max_metrics,
             slow_threshold_ms,
         }
@@ -61,11 +250,22 @@ impl PluginPerformanceMonitor {
             start: Instant::now(),
             monitor: self.clone(),
             metadata: HashMap::new(),
+            memory_usage_mb: None,
         }
     }
 
     /// Record a performance metric
     pub fn record_metric(&self, metric: PerformanceMetric) {
+        // Daily rollup
+        {
+            let date = rollup_date(metric.timestamp);
+            let mut rollups = self.rollups.lock().unwrap();
+            rollups
+                .entry((metric.plugin_id.clone(), date.clone()))
+                .or_insert_with(|| DailyRollup::new(&metric.plugin_id, &date))
+                .record(metric.duration_ms, metric.success);
+        }
+
         // Store metric
         let mut metrics = self.metrics.lock().unwrap();
         if metrics.len() >= self.max_metrics {
@@ -75,19 +275,9 @@ impl PluginPerformanceMonitor {
 
         // Update stats
         let mut stats = self.stats.lock().unwrap();
-        let plugin_stats = stats.entry(metric.plugin_id.clone()).or_insert_with(|| {
-            PluginPerformanceStats {
-                plugin_id: metric.plugin_id.clone(),
-                total_operations: 0,
-                successful_operations: 0,
-                failed_operations: 0,
-                average_duration_ms: 0.0,
-                min_duration_ms: metric.duration_ms,
-                max_duration_ms: metric.duration_ms,
-                last_operation: None,
-                slow_operations: Vec::new(),
-            }
-        });
+        let plugin_stats = stats
+            .entry(metric.plugin_id.clone())
+            .or_insert_with(|| PluginPerformanceStats::new(&metric.plugin_id));
 
         // Update statistics
         plugin_stats.total_operations += 1;
@@ -101,7 +291,7 @@ impl PluginPerformanceMonitor {
         let total_ms = plugin_stats.average_duration_ms * (plugin_stats.total_operations - 1) as f64;
         plugin_stats.average_duration_ms = (total_ms + metric.duration_ms as f64) / plugin_stats.total_operations as f64;
 
-        if metric.duration_ms < plugin_stats.min_duration_ms {
+        if plugin_stats.total_operations == 1 || metric.duration_ms < plugin_stats.min_duration_ms {
             plugin_stats.min_duration_ms = metric.duration_ms;
         }
         if metric.duration_ms > plugin_stats.max_duration_ms {
@@ -110,6 +300,10 @@ impl PluginPerformanceMonitor {
 
         plugin_stats.last_operation = Some(metric.timestamp);
 
+        if let Some(memory_mb) = metric.memory_usage_mb {
+            plugin_stats.record_memory(memory_mb);
+        }
+
         // Track slow operations
         if metric.duration_ms > self.slow_threshold_ms {
             plugin_stats.slow_operations.push(metric);
@@ -120,6 +314,16 @@ impl PluginPerformanceMonitor {
         }
     }
 
+    /// Record a memory measurement taken outside an operation, e.g. by the
+    /// frontend Worker sandbox
+    pub fn record_memory(&self, plugin_id: &str, bytes: u64) {
+        let mut stats = self.stats.lock().unwrap();
+        stats
+            .entry(plugin_id.to_string())
+            .or_insert_with(|| PluginPerformanceStats::new(plugin_id))
+            .record_memory(bytes as f64 / BYTES_PER_MB);
+    }
+
     /// Get all metrics for a plugin
     pub fn get_plugin_metrics(&self, plugin_id: &str) -> Vec<PerformanceMetric> {
         let metrics = self.metrics.lock().unwrap();
@@ -144,14 +348,25 @@ impl PluginPerformanceMonitor {
 
     /// Get slow operations across all plugins
     pub fn get_slow_operations(&self) -> Vec<PerformanceMetric> {
+        self.get_operations_slower_than(self.slow_threshold_ms)
+    }
+
+    /// Get buffered operations that took longer than `threshold_ms`
+    pub fn get_operations_slower_than(&self, threshold_ms: u64) -> Vec<PerformanceMetric> {
         let metrics = self.metrics.lock().unwrap();
         metrics
             .iter()
-            .filter(|m| m.duration_ms > self.slow_threshold_ms)
+            .filter(|m| m.duration_ms > threshold_ms)
             .cloned()
             .collect()
     }
 
+    /// Drain the rollups recorded since the last call, to be merged on disk
+    pub fn take_rollups(&self) -> Vec<DailyRollup> {
+        let mut rollups = self.rollups.lock().unwrap();
+        rollups.drain().map(|(_, rollup)| rollup).collect()
+    }
+
     /// Clear all metrics
     pub fn clear_metrics(&self) {
         let mut metrics = self.metrics.lock().unwrap();
@@ -192,6 +407,7 @@ impl Clone for PluginPerformanceMonitor {
         Self {
             metrics: Arc::clone(&self.metrics),
             stats: Arc::clone(&self.stats),
+            rollups: Arc::clone(&self.rollups),
             max_metrics: self.max_metrics,
             slow_threshold_ms: self.slow_threshold_ms,
         }
@@ -205,6 +421,7 @@ pub struct OperationTimer {
     start: Instant,
     monitor: PluginPerformanceMonitor,
     metadata: HashMap<String, String>,
+    memory_usage_mb: Option<f64>,
 }
 
 impl OperationTimer {
@@ -214,6 +431,12 @@ impl OperationTimer {
         self
     }
 
+    /// Attach a memory measurement, e.g. from a MemoryProbe
+    pub fn with_memory_usage(mut self, memory_usage_mb: Option<f64>) -> Self {
+        self.memory_usage_mb = memory_usage_mb;
+        self
+    }
+
     /// Complete the operation and record metric
     pub fn complete(self, success: bool) {
         let duration = self.start.elapsed();
@@ -226,7 +449,7 @@ impl OperationTimer {
                 .unwrap()
                 .as_millis() as i64,
             success,
-            memory_usage_mb: None, // TODO: Add memory tracking if needed
+            memory_usage_mb: self.memory_usage_mb,
             metadata: self.metadata,
         };
         self.monitor.record_metric(metric);
@@ -243,7 +466,7 @@ impl OperationTimer {
                 .unwrap()
                 .as_millis() as i64,
             success,
-            memory_usage_mb: None,
+            memory_usage_mb: self.memory_usage_mb,
             metadata: self.metadata,
         };
         self.monitor.record_metric(metric);
@@ -304,4 +527,138 @@ mod tests {
         assert_eq!(summary.successful_operations, 2);
         assert_eq!(summary.plugin_count, 2);
     }
+
+    #[test]
+    fn test_memory_stats_with_mixed_samples() {
+        let monitor = PluginPerformanceMonitor::new(100, 100);
+        for memory in [Some(40.0), None, Some(120.0), None] {
+            monitor
+                .start_operation("run".to_string(), "calc".to_string())
+                .with_memory_usage(memory)
+                .complete_with_duration(5, true);
+        }
+        monitor.record_memory("calc", 50 * 1024 * 1024);
+
+        let stats = monitor.get_plugin_stats("calc").unwrap();
+        assert_eq!(stats.total_operations, 4);
+        assert_eq!(stats.memory_samples, 3);
+        assert_eq!(stats.peak_memory_mb, Some(120.0));
+        assert!((stats.average_memory_mb.unwrap() - 70.0).abs() < 1e-9);
+        assert!(stats.exceeds_memory(100.0));
+        assert!(!stats.exceeds_memory(200.0));
+        assert_eq!(stats.min_duration_ms, 5);
+    }
+
+    #[test]
+    fn test_memory_stats_stay_empty_without_samples() {
+        let monitor = PluginPerformanceMonitor::new(100, 100);
+        monitor
+            .start_operation("run".to_string(), "calc".to_string())
+            .complete_with_duration(5, true);
+
+        let stats = monitor.get_plugin_stats("calc").unwrap();
+        assert_eq!(stats.peak_memory_mb, None);
+        assert_eq!(stats.average_memory_mb, None);
+        assert!(!stats.exceeds_memory(0.0));
+    }
+
+    #[test]
+    fn test_rollup_math() {
+        let mut rollup = DailyRollup::new("calc", "2024-01-01");
+        for ms in 1..=100 {
+            rollup.record(ms, ms % 10 != 0);
+        }
+
+        assert_eq!(rollup.op_count, 100);
+        assert_eq!(rollup.failures, 10);
+        assert!((rollup.avg_ms - 50.5).abs() < 1e-9);
+        assert_eq!(rollup.p95_ms, 95);
+    }
+
+    #[test]
+    fn test_rollup_reservoir_is_bounded() {
+        let mut rollup = DailyRollup::new("calc", "2024-01-01");
+        for _ in 0..10_000 {
+            rollup.record(10, true);
+        }
+        for _ in 0..1_000 {
+            rollup.record(1_000, true);
+        }
+
+        assert_eq!(rollup.samples.len(), ROLLUP_RESERVOIR_SIZE);
+        // ~9% of operations were slow, so p95 lands on them
+        assert_eq!(rollup.p95_ms, 1_000);
+        assert!((rollup.avg_ms - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_rollup_merge_weights_by_count() {
+        let mut earlier = DailyRollup::new("calc", "2024-01-01");
+        for _ in 0..900 {
+            earlier.record(10, true);
+        }
+        let mut later = DailyRollup::new("calc", "2024-01-01");
+        for _ in 0..100 {
+            later.record(50, false);
+        }
+
+        earlier.merge(later);
+
+        assert_eq!(earlier.op_count, 1_000);
+        assert_eq!(earlier.failures, 100);
+        assert!((earlier.avg_ms - 14.0).abs() < 1e-9);
+        assert_eq!(earlier.samples.len(), ROLLUP_RESERVOIR_SIZE);
+        let slow = earlier.samples.iter().filter(|&&ms| ms == 50).count();
+        assert!((10..=45).contains(&slow), "slow samples: {}", slow);
+    }
+
+    #[test]
+    fn test_metrics_feed_rollups_by_day() {
+        let monitor = PluginPerformanceMonitor::new(100, 100);
+        for (timestamp, duration_ms) in [(1_704_067_200_000, 10), (1_704_067_260_000, 30), (1_704_153_600_000, 5)] {
+            monitor.record_metric(PerformanceMetric {
+                operation: "run".to_string(),
+                plugin_id: "calc".to_string(),
+                duration_ms,
+                timestamp,
+                success: true,
+                memory_usage_mb: None,
+                metadata: HashMap::new(),
+            });
+        }
+
+        let mut rollups = monitor.take_rollups();
+        rollups.sort_by(|a, b| a.date.cmp(&b.date));
+        assert_eq!(rollups.len(), 2);
+        assert_eq!((rollups[0].date.as_str(), rollups[0].op_count), ("2024-01-01", 2));
+        assert_eq!((rollups[1].date.as_str(), rollups[1].op_count), ("2024-01-02", 1));
+        assert!(monitor.take_rollups().is_empty());
+    }
+
+    #[test]
+    fn test_performance_serialization() {
+        let mut rollup = DailyRollup::new("calc", "2024-01-01");
+        rollup.record(12, false);
+        let performance = PluginPerformance {
+            plugin_id: "calc".to_string(),
+            stats: None,
+            history: vec![rollup],
+        };
+
+        assert_eq!(
+            serde_json::to_value(&performance).unwrap(),
+            serde_json::json!({
+                "plugin_id": "calc",
+                "stats": null,
+                "history": [{
+                    "plugin_id": "calc",
+                    "date": "2024-01-01",
+                    "op_count": 1,
+                    "avg_ms": 12.0,
+                    "p95_ms": 12,
+                    "failures": 1,
+                }],
+            })
+        );
+    }
 }
@@ -0,0 +1,171 @@
+//! Plugin Protection
+//! First-party plugins can mark themselves protected (`"protected": true`
+//! in plugin.json, or in the `etools` block of an npm package.json), which
+//! lets users disable them but not uninstall them. Only packages on the
+//! allowlist bundled with the app may claim it, so a third-party package
+//! can't make itself impossible to remove.
+
+use crate::services::plugin_errors::PluginError;
+use crate::t;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+const ALLOWLIST_JSON: &str = include_str!("../../assets/protected_plugins.json");
+
+/// npm scope of first-party plugins; their plugin ids drop it
+const FIRST_PARTY_SCOPE: &str = "@etools-plugin/";
+
+#[derive(Deserialize)]
+struct Allowlist {
+    plugins: HashSet<String>,
+}
+
+fn allowlist() -> &'static HashSet<String> {
+    static ALLOWLIST: OnceLock<HashSet<String>> = OnceLock::new();
+    ALLOWLIST.get_or_init(|| {
+        serde_json::from_str::<Allowlist>(ALLOWLIST_JSON)
+            .expect("protected_plugins.json is valid")
+            .plugins
+    })
+}
+
+/// Whether a plugin may claim protection. Takes the name as installed,
+/// exactly: an unscoped package or local folder named like a first-party
+/// plugin isn't one.
+pub fn is_allowlisted(name: &str) -> bool {
+    allowlist().contains(name)
+}
+
+/// Name of the plugin installed in `plugin_dir` as the allowlist knows it:
+/// the scoped package name for a first-party npm package, whose plugin id
+/// drops the scope, and the id itself otherwise
+pub fn installed_name(plugin_id: &str, plugin_dir: &Path) -> String {
+    let scope = FIRST_PARTY_SCOPE.trim_end_matches('/');
+    let in_scope = plugin_dir.parent().and_then(Path::file_name).is_some_and(|parent| parent == scope);
+    match plugin_dir.file_name() {
+        Some(name) if in_scope => format!("{}{}", FIRST_PARTY_SCOPE, name.to_string_lossy()),
+        _ => plugin_id.to_string(),
+    }
+}
+
+/// Whether a plugin is protected: it claims to be and is allowed to. Takes
+/// the name as installed, see `installed_name`.
+pub fn is_protected(name: &str, claims_protected: bool) -> bool {
+    claims_protected && is_allowlisted(name)
+}
+
+/// Whether the plugin installed in `plugin_dir` claims protection, from
+/// plugin.json or else the `etools` block of package.json
+pub fn claims_protected(plugin_dir: &Path) -> bool {
+    let read = |name: &str| {
+        fs::read_to_string(plugin_dir.join(name))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+    };
+    let flag = |value: Option<&serde_json::Value>| value.and_then(|v| v.as_bool()).unwrap_or(false);
+
+    match read("plugin.json") {
+        Some(manifest) => flag(manifest.get("protected")),
+        None => flag(
+            read("package.json")
+                .as_ref()
+                .and_then(|package| package.get("etools")?.get("protected")),
+        ),
+    }
+}
+
+/// Refuse a package that claims protection without being on the allowlist
+pub fn check_protected_claim(plugin_id: &str, claims_protected: bool) -> Result<(), String> {
+    if claims_protected && !is_allowlisted(plugin_id) {
+        return Err(t!("validator.protected_not_allowed", plugin_id = plugin_id));
+    }
+    Ok(())
+}
+
+/// Refuse to uninstall the protected plugin installed in `plugin_dir`
+pub fn ensure_uninstallable(plugin_id: &str, plugin_dir: &Path) -> Result<(), PluginError> {
+    if is_protected(&installed_name(plugin_id, plugin_dir), claims_protected(plugin_dir)) {
+        return Err(PluginError::CorePluginProtected {
+            plugin_id: plugin_id.to_string(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn install(dir: &Path, name: &str, content: serde_json::Value) {
+        fs::create_dir_all(dir).unwrap();
+        fs::write(dir.join(name), content.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_protected_plugins_cannot_be_uninstalled() {
+        let dir = tempfile::tempdir().unwrap();
+        let devtools = dir.path().join("node_modules/@etools-plugin/devtools");
+        install(
+            &devtools,
+            "plugin.json",
+            json!({ "name": "Devtools", "protected": true }),
+        );
+        let hello = dir.path().join("node_modules/@etools-plugin/hello");
+        install(
+            &hello,
+            "package.json",
+            json!({ "name": "@etools-plugin/hello", "etools": { "id": "hello" } }),
+        );
+
+        match ensure_uninstallable("devtools", &devtools) {
+            Err(PluginError::CorePluginProtected { plugin_id }) => assert_eq!(plugin_id, "devtools"),
+            other => panic!("expected a protected plugin error, got {:?}", other),
+        }
+        assert!(ensure_uninstallable("@etools-plugin/devtools", &devtools).is_err());
+        // First-party, but doesn't claim protection
+        assert!(ensure_uninstallable("hello", &hello).is_ok());
+    }
+
+    #[test]
+    fn test_first_party_names_outside_the_scope_are_not_protected() {
+        let dir = tempfile::tempdir().unwrap();
+        let claim = json!({ "name": "devtools", "etools": { "protected": true } });
+        // An unscoped npm package and a local folder named like first-party plugins
+        let unscoped = dir.path().join("node_modules/devtools");
+        install(&unscoped, "package.json", claim.clone());
+        let local = dir.path().join("hello");
+        install(&local, "package.json", claim);
+
+        assert_eq!(installed_name("devtools", &unscoped), "devtools");
+        assert_eq!(installed_name("hello", &local), "hello");
+        assert!(check_protected_claim("devtools", true).is_err());
+        assert!(check_protected_claim("hello", true).is_err());
+        assert!(ensure_uninstallable("devtools", &unscoped).is_ok());
+        assert!(ensure_uninstallable("hello", &local).is_ok());
+    }
+
+    #[test]
+    fn test_third_party_protection_claims_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let spoof = dir.path().join("sticky");
+        install(
+            &spoof,
+            "package.json",
+            json!({ "name": "sticky", "etools": { "protected": true } }),
+        );
+
+        assert!(claims_protected(&spoof));
+        assert!(check_protected_claim("sticky", true).is_err());
+        assert!(check_protected_claim("@someone/devtools", true).is_err());
+        assert!(check_protected_claim("sticky", false).is_ok());
+        assert!(check_protected_claim("@etools-plugin/devtools", true).is_ok());
+
+        // A claim that slipped through still doesn't block uninstalling
+        assert!(!is_protected("sticky", true));
+        assert!(ensure_uninstallable("sticky", &spoof).is_ok());
+    }
+}
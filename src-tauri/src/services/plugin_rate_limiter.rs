@@ -0,0 +1,213 @@
+//! Plugin Host Call Rate Limiter
+//! Token buckets per plugin and capability, plus violation tracking that
+//! escalates repeat offenders to the sandbox's crash counter.
+
+use crate::models::preferences::RateLimit;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Violations inside this window count towards escalation
+pub const VIOLATION_WINDOW: Duration = Duration::from_secs(60);
+
+/// Violations within VIOLATION_WINDOW that count as one sandbox violation
+pub const VIOLATIONS_BEFORE_ESCALATION: usize = 5;
+
+/// Classic token bucket; starts full
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            last_refill: now,
+        }
+    }
+
+    /// Apply a changed limit without resetting accumulated tokens beyond the new burst
+    fn set_limit(&mut self, limit: RateLimit) {
+        self.limit = limit;
+        self.tokens = self.tokens.min(limit.burst as f64);
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.per_second).min(self.limit.burst as f64);
+        self.last_refill = now;
+    }
+
+    /// Take one token, or return how long until one is available
+    pub fn try_take(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+
+        // A zero rate (hand-edited settings) never refills
+        let missing = 1.0 - self.tokens;
+        Err(Duration::try_from_secs_f64(missing / self.limit.per_second).unwrap_or(Duration::MAX))
+    }
+}
+
+/// A rejected call
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+    /// The plugin hit VIOLATIONS_BEFORE_ESCALATION within VIOLATION_WINDOW;
+    /// the caller should record a sandbox violation
+    pub escalate: bool,
+}
+
+#[derive(Default)]
+struct LimiterInner {
+    buckets: HashMap<(String, String), TokenBucket>,
+    violations: HashMap<String, VecDeque<Instant>>,
+}
+
+/// Rate limiter shared by all plugin host calls
+#[derive(Default)]
+pub struct PluginRateLimiter {
+    inner: Mutex<LimiterInner>,
+}
+
+impl PluginRateLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Admit or reject one call by `plugin_id` to `capability`
+    pub fn check(&self, plugin_id: &str, capability: &str, limit: RateLimit, now: Instant) -> Result<(), RateLimited> {
+        let Ok(mut inner) = self.inner.lock() else {
+            // Fail open rather than wedging every plugin on a poisoned lock
+            return Ok(());
+        };
+
+        let bucket = inner
+            .buckets
+            .entry((plugin_id.to_string(), capability.to_string()))
+            .or_insert_with(|| TokenBucket::new(limit, now));
+        if bucket.limit != limit {
+            bucket.set_limit(limit);
+        }
+
+        let Err(retry_after) = bucket.try_take(now) else {
+            return Ok(());
+        };
+
+        let violations = inner.violations.entry(plugin_id.to_string()).or_default();
+        while violations
+            .front()
+            .is_some_and(|t| now.saturating_duration_since(*t) > VIOLATION_WINDOW)
+        {
+            violations.pop_front();
+        }
+        violations.push_back(now);
+
+        let escalate = violations.len() >= VIOLATIONS_BEFORE_ESCALATION;
+        if escalate {
+            violations.clear();
+        }
+
+        Err(RateLimited { retry_after, escalate })
+    }
+
+    /// Forget a plugin's buckets and violations (e.g. after it's re-enabled)
+    pub fn reset(&self, plugin_id: &str) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.buckets.retain(|(id, _), _| id != plugin_id);
+            inner.violations.remove(plugin_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIMIT: RateLimit = RateLimit { per_second: 10.0, burst: 3 };
+
+    #[test]
+    fn test_bucket_allows_burst_then_refills() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(LIMIT, start);
+
+        for _ in 0..3 {
+            assert!(bucket.try_take(start).is_ok());
+        }
+        let retry_after = bucket.try_take(start).unwrap_err();
+        assert_eq!(retry_after.as_millis(), 100);
+
+        // 10/s refills one token every 100ms
+        assert!(bucket.try_take(start + Duration::from_millis(100)).is_ok());
+        assert!(bucket.try_take(start + Duration::from_millis(150)).is_err());
+
+        // Refill caps at the burst size
+        let later = start + Duration::from_secs(10);
+        for _ in 0..3 {
+            assert!(bucket.try_take(later).is_ok());
+        }
+        assert!(bucket.try_take(later).is_err());
+    }
+
+    #[test]
+    fn test_retry_after_reflects_partial_token() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit { per_second: 1.0, burst: 1 }, start);
+
+        assert!(bucket.try_take(start).is_ok());
+        let retry_after = bucket.try_take(start + Duration::from_millis(250)).unwrap_err();
+        assert_eq!(retry_after.as_millis(), 750);
+    }
+
+    #[test]
+    fn test_limits_are_per_plugin_and_capability() {
+        let limiter = PluginRateLimiter::new();
+        let now = Instant::now();
+        let one = RateLimit { per_second: 1.0, burst: 1 };
+
+        assert!(limiter.check("a", "notification", one, now).is_ok());
+        assert!(limiter.check("a", "notification", one, now).is_err());
+        assert!(limiter.check("a", "read_clipboard", one, now).is_ok());
+        assert!(limiter.check("b", "notification", one, now).is_ok());
+    }
+
+    #[test]
+    fn test_repeated_violations_escalate() {
+        let limiter = PluginRateLimiter::new();
+        let start = Instant::now();
+        let one = RateLimit { per_second: 1.0, burst: 1 };
+        assert!(limiter.check("spammy", "notification", one, start).is_ok());
+
+        for i in 1..VIOLATIONS_BEFORE_ESCALATION {
+            let at = start + Duration::from_millis(i as u64);
+            assert!(!limiter.check("spammy", "notification", one, at).unwrap_err().escalate);
+        }
+        let at = start + Duration::from_millis(10);
+        assert!(limiter.check("spammy", "notification", one, at).unwrap_err().escalate);
+
+        // The window starts over after escalating
+        let at = start + Duration::from_millis(20);
+        assert!(!limiter.check("spammy", "notification", one, at).unwrap_err().escalate);
+    }
+
+    #[test]
+    fn test_old_violations_expire() {
+        let limiter = PluginRateLimiter::new();
+        let start = Instant::now();
+        let slow = RateLimit { per_second: 0.001, burst: 1 };
+        assert!(limiter.check("p", "shell", slow, start).is_ok());
+
+        for i in 1..VIOLATIONS_BEFORE_ESCALATION {
+            assert!(limiter.check("p", "shell", slow, start + Duration::from_millis(i as u64)).is_err());
+        }
+        let later = start + VIOLATION_WINDOW + Duration::from_secs(1);
+        assert!(!limiter.check("p", "shell", slow, later).unwrap_err().escalate);
+    }
+}
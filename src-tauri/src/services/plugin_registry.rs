@@ -0,0 +1,155 @@
+//! Plugin Registry
+//! The installed plugins as `plugin_list` returns them, kept in memory so a
+//! listing doesn't re-read every manifest, stat every folder and load the
+//! state files each time the plugins panel opens. It's built once, at
+//! startup or on first use, and kept current by the commands that install,
+//! uninstall, enable, disable and reload plugins, and by a watcher on the
+//! plugins dir for changes made outside the app. Those commands write their
+//! files while holding the registry's lock, so a listing never sees one
+//! without the other; anything that may wait on other state, like the
+//! sandbox, happens after it's released. Health is refreshed by a
+//! background task.
+//!
+//! Every change bumps the registry's revision. Commands hand the revision
+//! of their change back, and a listing given one waits until the registry
+//! has reached it, so a list that races an install can't answer from
+//! before it.
+
+use crate::models::plugin::{Plugin, PluginHealth};
+use std::collections::BTreeMap;
+use std::sync::{Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+#[derive(Default)]
+struct RegistryState {
+    plugins: Option<BTreeMap<String, Plugin>>,
+    /// Changes made so far, counting ones dropped before loading
+    revision: u64,
+}
+
+/// Installed plugins by id, managed as app state
+#[derive(Default)]
+pub struct PluginRegistry {
+    state: Mutex<RegistryState>,
+    /// Signalled when a lock that made changes is released
+    changed: Condvar,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lock the registry for a listing, or for a change made together with
+    /// the files behind it
+    pub fn lock(&self) -> RegistryGuard<'_> {
+        let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let revision = state.revision;
+        RegistryGuard {
+            state,
+            changed: &self.changed,
+            locked_at: revision,
+            quarantined: Vec::new(),
+        }
+    }
+
+    /// Wait up to `timeout` for the registry to reach `revision`. Returns
+    /// whether it did.
+    pub fn wait_for_revision(&self, revision: u64, timeout: Duration) -> bool {
+        let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let (state, _) = self
+            .changed
+            .wait_timeout_while(state, timeout, |state| state.revision < revision)
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.revision >= revision
+    }
+}
+
+/// The locked registry. Changes before it's loaded are dropped; loading
+/// reads them from the files.
+pub struct RegistryGuard<'a> {
+    state: MutexGuard<'a, RegistryState>,
+    changed: &'a Condvar,
+    /// Revision when the lock was taken
+    locked_at: u64,
+    /// Plugins disabled as incompatible under this lock, to announce once
+    /// it's released
+    quarantined: Vec<String>,
+}
+
+impl RegistryGuard<'_> {
+    pub fn is_loaded(&self) -> bool {
+        self.state.plugins.is_some()
+    }
+
+    /// Revision after the changes made so far, to hand back to the caller
+    /// that asked for them
+    pub fn revision(&self) -> u64 {
+        self.state.revision
+    }
+
+    /// Mutable contents, counting a change
+    fn plugins_mut(&mut self) -> Option<&mut BTreeMap<String, Plugin>> {
+        self.state.revision += 1;
+        self.state.plugins.as_mut()
+    }
+
+    /// Replace the contents with a fresh scan
+    pub fn load(&mut self, plugins: Vec<Plugin>) {
+        self.state.revision += 1;
+        self.state.plugins = Some(plugins.into_iter().map(|plugin| (plugin.id.clone(), plugin)).collect());
+    }
+
+    /// Every plugin, by id; None until loaded
+    pub fn snapshot(&self) -> Option<Vec<Plugin>> {
+        self.state.plugins.as_ref().map(|plugins| plugins.values().cloned().collect())
+    }
+
+    pub fn get(&self, plugin_id: &str) -> Option<&Plugin> {
+        self.state.plugins.as_ref()?.get(plugin_id)
+    }
+
+    /// Add a plugin or replace its entry
+    pub fn upsert(&mut self, plugin: Plugin) {
+        if let Some(plugins) = self.plugins_mut() {
+            plugins.insert(plugin.id.clone(), plugin);
+        }
+    }
+
+    pub fn remove(&mut self, plugin_id: &str) {
+        if let Some(plugins) = self.plugins_mut() {
+            plugins.remove(plugin_id);
+        }
+    }
+
+    pub fn set_enabled(&mut self, plugin_id: &str, enabled: bool) {
+        if let Some(plugin) = self.plugins_mut().and_then(|plugins| plugins.get_mut(plugin_id)) {
+            plugin.enabled = enabled;
+        }
+    }
+
+    pub fn set_health(&mut self, plugin_id: &str, health: PluginHealth) {
+        if let Some(plugin) = self.plugins_mut().and_then(|plugins| plugins.get_mut(plugin_id)) {
+            plugin.health = health;
+        }
+    }
+
+    /// Note a plugin disabled as incompatible while reading it in. Telling
+    /// the sandbox and the UI waits for the lock to be released.
+    pub fn note_quarantined(&mut self, plugin_id: &str) {
+        self.quarantined.push(plugin_id.to_string());
+    }
+
+    /// Plugins noted as quarantined so far
+    pub fn take_quarantined(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.quarantined)
+    }
+}
+
+impl Drop for RegistryGuard<'_> {
+    fn drop(&mut self) {
+        if self.state.revision != self.locked_at {
+            self.changed.notify_all();
+        }
+    }
+}
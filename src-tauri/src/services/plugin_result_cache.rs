@@ -0,0 +1,347 @@
+//! Plugin Result Cache
+//! Caches what a plugin trigger returned for an input, for triggers whose
+//! manifest declares a `cache_ttl_ms`. Pure triggers (color conversion,
+//! base64) then answer repeated keystrokes without a Worker round-trip.
+//! Plugins that may read or write the clipboard or files are never cached,
+//! since their results can depend on state outside the input. Bounded LRU;
+//! a plugin's entries are dropped when it's reloaded, updated or disabled.
+//! The manifests deciding what may be cached are kept too, so a keystroke
+//! doesn't re-read plugin.json unless it was modified since.
+
+use crate::models::plugin::{PluginManifest, PluginPermission};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Entries kept across all plugins when settings don't say otherwise
+pub const DEFAULT_CAPACITY: usize = 256;
+
+/// Permissions whose results depend on state outside the trigger input
+const UNCACHEABLE_PERMISSIONS: &[PluginPermission] = &[
+    PluginPermission::ReadClipboard,
+    PluginPermission::WriteClipboard,
+    PluginPermission::ReadFile,
+    PluginPermission::WriteFile,
+];
+
+/// Identifies a cached trigger run
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct CacheKey {
+    plugin_id: String,
+    trigger: String,
+    input: String,
+}
+
+impl CacheKey {
+    fn new(plugin_id: &str, trigger: &str, input: &str) -> Self {
+        Self {
+            plugin_id: plugin_id.to_string(),
+            trigger: trigger.to_string(),
+            input: input.to_string(),
+        }
+    }
+}
+
+struct CacheEntry {
+    payload: serde_json::Value,
+    expires: Instant,
+}
+
+/// A payload served from the cache
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CachedPluginResult {
+    pub payload: serde_json::Value,
+    /// Always true; tells the frontend the plugin didn't run
+    pub cached: bool,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// Least recently used first
+    order: VecDeque<CacheKey>,
+}
+
+impl CacheInner {
+    fn touch(&mut self, key: &CacheKey) {
+        self.order.retain(|k| k != key);
+        self.order.push_back(key.clone());
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        self.entries.remove(key);
+        self.order.retain(|k| k != key);
+    }
+
+    fn evict_to(&mut self, capacity: usize) {
+        while self.order.len() > capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+}
+
+/// How long `trigger`'s results may be cached, None when they may not be:
+/// the trigger declares no TTL, or the plugin has a clipboard or file
+/// permission
+pub fn cache_ttl(manifest: &PluginManifest, trigger: &str) -> Option<Duration> {
+    let ttl_ms = manifest
+        .triggers
+        .iter()
+        .find(|t| t.keyword == trigger)?
+        .cache_ttl_ms
+        .filter(|ttl| *ttl > 0)?;
+    if UNCACHEABLE_PERMISSIONS.iter().any(|p| manifest.has_permission(p)) {
+        return None;
+    }
+    Some(Duration::from_millis(ttl_ms))
+}
+
+/// A plugin's manifest as read when plugin.json had this modification time
+struct CachedManifest {
+    modified: SystemTime,
+    manifest: Arc<PluginManifest>,
+}
+
+/// LRU + per-entry TTL cache of plugin trigger results
+pub struct PluginResultCache {
+    capacity: Mutex<usize>,
+    inner: Mutex<CacheInner>,
+    manifests: Mutex<HashMap<String, CachedManifest>>,
+}
+
+impl Default for PluginResultCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPACITY)
+    }
+}
+
+impl PluginResultCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: Mutex::new(capacity),
+            inner: Mutex::new(CacheInner::default()),
+            manifests: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Change the entry limit, evicting the least recently used if over it
+    pub fn set_capacity(&self, capacity: usize) {
+        *self.capacity.lock().unwrap() = capacity;
+        self.inner.lock().unwrap().evict_to(capacity);
+    }
+
+    /// The cached payload for this run if it hasn't expired
+    pub fn get(&self, plugin_id: &str, trigger: &str, input: &str) -> Option<CachedPluginResult> {
+        self.get_at(plugin_id, trigger, input, Instant::now())
+    }
+
+    fn get_at(&self, plugin_id: &str, trigger: &str, input: &str, now: Instant) -> Option<CachedPluginResult> {
+        let key = CacheKey::new(plugin_id, trigger, input);
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.entries.get(&key)?.expires <= now {
+            inner.remove(&key);
+            return None;
+        }
+
+        inner.touch(&key);
+        inner.entries.get(&key).map(|entry| CachedPluginResult {
+            payload: entry.payload.clone(),
+            cached: true,
+        })
+    }
+
+    /// Remember a run's payload for `ttl`
+    pub fn insert(&self, plugin_id: &str, trigger: &str, input: &str, payload: serde_json::Value, ttl: Duration) {
+        self.insert_at(plugin_id, trigger, input, payload, ttl, Instant::now());
+    }
+
+    fn insert_at(
+        &self,
+        plugin_id: &str,
+        trigger: &str,
+        input: &str,
+        payload: serde_json::Value,
+        ttl: Duration,
+        now: Instant,
+    ) {
+        let capacity = *self.capacity.lock().unwrap();
+        let key = CacheKey::new(plugin_id, trigger, input);
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.entries.insert(
+            key.clone(),
+            CacheEntry {
+                payload,
+                expires: now + ttl,
+            },
+        );
+        inner.touch(&key);
+        inner.evict_to(capacity);
+    }
+
+    /// The manifest kept for a plugin, unless plugin.json was modified
+    /// since it was read
+    pub fn manifest(&self, plugin_id: &str, modified: SystemTime) -> Option<Arc<PluginManifest>> {
+        self.manifests
+            .lock()
+            .unwrap()
+            .get(plugin_id)
+            .filter(|cached| cached.modified == modified)
+            .map(|cached| Arc::clone(&cached.manifest))
+    }
+
+    /// Keep a plugin's manifest, read when plugin.json had `modified`
+    pub fn remember_manifest(&self, plugin_id: &str, modified: SystemTime, manifest: Arc<PluginManifest>) {
+        self.manifests
+            .lock()
+            .unwrap()
+            .insert(plugin_id.to_string(), CachedManifest { modified, manifest });
+    }
+
+    /// Drop every entry of a plugin, e.g. after it was reloaded or updated,
+    /// and its kept manifest. Returns how many entries were dropped.
+    pub fn invalidate_plugin(&self, plugin_id: &str) -> usize {
+        self.manifests.lock().unwrap().remove(plugin_id);
+        let mut inner = self.inner.lock().unwrap();
+        let before = inner.entries.len();
+        inner.entries.retain(|key, _| key.plugin_id != plugin_id);
+        inner.order.retain(|key| key.plugin_id != plugin_id);
+        before - inner.entries.len()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn manifest(permissions: &[&str], cache_ttl_ms: Option<u64>) -> PluginManifest {
+        serde_json::from_value(json!({
+            "name": "color",
+            "version": "1.0.0",
+            "description": "Color conversion",
+            "author": null,
+            "permissions": permissions,
+            "entry": "index.js",
+            "triggers": [
+                { "keyword": "color:", "description": "Convert", "cache_ttl_ms": cache_ttl_ms },
+                "rgb:"
+            ]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_ttl_expiry() {
+        let cache = PluginResultCache::new(8);
+        let start = Instant::now();
+        let ttl = Duration::from_millis(500);
+        cache.insert_at(
+            "color",
+            "color:",
+            "color: #fff",
+            json!([{ "title": "white" }]),
+            ttl,
+            start,
+        );
+
+        let hit = cache.get_at("color", "color:", "color: #fff", start + Duration::from_millis(499));
+        assert_eq!(
+            hit,
+            Some(CachedPluginResult {
+                payload: json!([{ "title": "white" }]),
+                cached: true,
+            })
+        );
+        // Keyed by input too
+        assert_eq!(cache.get_at("color", "color:", "color: #000", start), None);
+
+        assert_eq!(cache.get_at("color", "color:", "color: #fff", start + ttl), None);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_lru_bound() {
+        let cache = PluginResultCache::new(2);
+        let ttl = Duration::from_secs(60);
+        cache.insert("color", "color:", "a", json!(1), ttl);
+        cache.insert("color", "color:", "b", json!(2), ttl);
+        assert!(cache.get("color", "color:", "a").is_some());
+        cache.insert("color", "color:", "c", json!(3), ttl);
+
+        // "b" was the least recently used
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("color", "color:", "b").is_none());
+        assert!(cache.get("color", "color:", "a").is_some());
+
+        cache.set_capacity(1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get("color", "color:", "a").is_some());
+    }
+
+    #[test]
+    fn test_cache_ttl_bypassed_for_clipboard_and_file_permissions() {
+        assert_eq!(
+            cache_ttl(&manifest(&["notification"], Some(30_000)), "color:"),
+            Some(Duration::from_secs(30))
+        );
+        // Undeclared, zero, or for another trigger
+        assert_eq!(cache_ttl(&manifest(&[], None), "color:"), None);
+        assert_eq!(cache_ttl(&manifest(&[], Some(0)), "color:"), None);
+        assert_eq!(cache_ttl(&manifest(&[], Some(30_000)), "rgb:"), None);
+        assert_eq!(cache_ttl(&manifest(&[], Some(30_000)), "hex:"), None);
+
+        // Legacy names count as well
+        for permission in ["read_clipboard", "clipboard:write", "read:files", "write_file"] {
+            assert_eq!(
+                cache_ttl(&manifest(&[permission], Some(30_000)), "color:"),
+                None,
+                "{}",
+                permission
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalidate_plugin() {
+        let cache = PluginResultCache::new(8);
+        let ttl = Duration::from_secs(60);
+        cache.insert("color", "color:", "a", json!(1), ttl);
+        cache.insert("color", "rgb:", "b", json!(2), ttl);
+        cache.insert("base64", "b64:", "a", json!(3), ttl);
+
+        assert_eq!(cache.invalidate_plugin("color"), 2);
+        assert!(cache.get("color", "color:", "a").is_none());
+        assert!(cache.get("base64", "b64:", "a").is_some());
+        assert_eq!(cache.invalidate_plugin("color"), 0);
+    }
+
+    #[test]
+    fn test_manifest_kept_until_modified_or_invalidated() {
+        let cache = PluginResultCache::new(8);
+        let read_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        assert!(cache.manifest("color", read_at).is_none());
+
+        cache.remember_manifest("color", read_at, Arc::new(manifest(&[], Some(30_000))));
+        let kept = cache.manifest("color", read_at).unwrap();
+        assert_eq!(cache_ttl(&kept, "color:"), Some(Duration::from_secs(30)));
+
+        // plugin.json changed on disk
+        assert!(cache.manifest("color", read_at + Duration::from_secs(1)).is_none());
+
+        cache.invalidate_plugin("color");
+        assert!(cache.manifest("color", read_at).is_none());
+    }
+}
@@ -20,9 +20,12 @@
 //!
 //! This Rust module handles:
 //! - Plugin registration/unregistration
-//! - Permission grant/revoke operations
+//! - Permission grant/revoke operations, with a consent record per
+//!   permission and pending consents for permissions a plugin requests that
+//!   the user hasn't answered yet
 //! - Plugin enable/disable state
-//! - Crash count tracking (persisted to disk)
+//! - Crash count and violation tracking (persisted to disk, crash counts
+//!   expire after a week)
 //! - Permission validation (check_permission)
 //!
 //! The frontend PluginSandbox handles:
@@ -41,48 +44,65 @@
 #![allow(dead_code)]
 
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-/// Available plugin permissions
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
-pub enum PluginPermission {
-    ReadClipboard,
-    WriteClipboard,
-    ReadFile,
-    WriteFile,
-    Network,
-    Shell,
-    Notification,
+use crate::models::plugin::PluginManifest;
+pub use crate::models::plugin::PluginPermission;
+use crate::utils::write_atomic;
+
+/// Granted permissions file, under the plugins dir
+const PLUGIN_PERMISSIONS_FILE: &str = "plugin_permissions.json";
+
+/// Crash and violation state file, under the plugins dir
+const SANDBOX_STATE_FILE: &str = "plugin_sandbox_state.json";
+
+/// Crashes older than this no longer count towards auto-disabling
+pub const CRASH_EXPIRY: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Mutations within this window share one state write
+const STATE_SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How a permission decision came about
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentSource {
+    Install,
+    Update,
+    /// Granted or revoked from the plugin's settings
+    Manual,
 }
 
-impl PluginPermission {
-    /// Parse permission from string
-    pub fn from_str(s: &str) -> Option<Self> {
-        match s {
-            "read_clipboard" => Some(PluginPermission::ReadClipboard),
-            "write_clipboard" => Some(PluginPermission::WriteClipboard),
-            "read_file" => Some(PluginPermission::ReadFile),
-            "write_file" => Some(PluginPermission::WriteFile),
-            "network" => Some(PluginPermission::Network),
-            "shell" => Some(PluginPermission::Shell),
-            "notification" => Some(PluginPermission::Notification),
-            _ => None,
-        }
-    }
+/// The user's answer for one permission of a plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PermissionConsent {
+    pub permission: PluginPermission,
+    pub allowed: bool,
+    /// Unix timestamp (ms) of the answer; None for grants saved before
+    /// consent was recorded
+    pub granted_at: Option<i64>,
+    /// Plugin version the answer was given for, when known
+    pub granted_version: Option<String>,
+    pub source: ConsentSource,
+}
 
-    /// Convert to string
-    pub fn as_str(&self) -> &'static str {
-        match self {
-            PluginPermission::ReadClipboard => "read_clipboard",
-            PluginPermission::WriteClipboard => "write_clipboard",
-            PluginPermission::ReadFile => "read_file",
-            PluginPermission::WriteFile => "write_file",
-            PluginPermission::Network => "network",
-            PluginPermission::Shell => "shell",
-            PluginPermission::Notification => "notification",
-        }
-    }
+/// A permission a plugin requests that the user hasn't answered yet
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingConsent {
+    pub plugin_id: String,
+    pub permission: PluginPermission,
+    /// Plugin version that requested it
+    pub version: String,
+    pub source: ConsentSource,
+    /// Unix timestamp (ms)
+    pub requested_at: i64,
+    /// Dangerous permissions stay blocked until answered; others are
+    /// allowed meanwhile, as the manifest asks for them
+    pub dangerous: bool,
 }
 
 /// Plugin execution result
@@ -100,12 +120,112 @@ pub struct PluginExecutionContext {
     pub granted_permissions: HashSet<PluginPermission>,
     pub is_enabled: bool,
     pub crash_count: u32,
+    /// Unix timestamp (ms) of the most recent crash
+    pub last_crash_at: Option<i64>,
+    /// Denied host calls
+    pub violation_count: u32,
+    /// Programs the plugin may run through the Shell capability
+    pub shell_allowlist: HashSet<String>,
+    /// Latest answer per permission
+    pub consents: HashMap<PluginPermission, PermissionConsent>,
+    /// Requested permissions awaiting an answer
+    pub pending_consents: HashMap<PluginPermission, PendingConsent>,
+}
+
+impl PluginExecutionContext {
+    fn new(plugin_id: &str) -> Self {
+        Self {
+            plugin_id: plugin_id.to_string(),
+            granted_permissions: HashSet::new(),
+            is_enabled: true,
+            crash_count: 0,
+            last_crash_at: None,
+            violation_count: 0,
+            shell_allowlist: HashSet::new(),
+            consents: HashMap::new(),
+            pending_consents: HashMap::new(),
+        }
+    }
+
+    /// Record an answer, applying it to the granted set
+    fn record_consent(&mut self, consent: PermissionConsent) {
+        if consent.allowed {
+            self.granted_permissions.insert(consent.permission.clone());
+        } else {
+            self.granted_permissions.remove(&consent.permission);
+        }
+        self.pending_consents.remove(&consent.permission);
+        self.consents.insert(consent.permission.clone(), consent);
+    }
+
+    /// Forget a crash count whose last crash is older than CRASH_EXPIRY.
+    /// A plugin already disabled stays disabled.
+    fn expire_crashes(&mut self, now_ms: i64) {
+        let expired = self
+            .last_crash_at
+            .is_some_and(|at| now_ms.saturating_sub(at) > CRASH_EXPIRY.as_millis() as i64);
+        if expired {
+            self.crash_count = 0;
+            self.last_crash_at = None;
+        }
+    }
+}
+
+/// Per-plugin grants as persisted to disk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PersistedGrants {
+    granted_permissions: Vec<PluginPermission>,
+    shell_allowlist: Vec<String>,
+    #[serde(default)]
+    consents: Vec<PermissionConsent>,
+    #[serde(default)]
+    pending_consents: Vec<PendingConsent>,
+}
+
+/// Per-plugin crash and violation state as persisted to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PersistedState {
+    is_enabled: bool,
+    crash_count: u32,
+    last_crash_at: Option<i64>,
+    violation_count: u32,
+}
+
+type PluginContexts = Arc<Mutex<HashMap<String, PluginExecutionContext>>>;
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+/// Write the crash and violation state of every plugin
+fn write_state(plugins: &PluginContexts, path: &Path) -> Result<(), String> {
+    let saved: HashMap<String, PersistedState> = {
+        let plugins = plugins.lock().unwrap();
+        plugins
+            .iter()
+            .map(|(id, context)| {
+                (id.clone(), PersistedState {
+                    is_enabled: context.is_enabled,
+                    crash_count: context.crash_count,
+                    last_crash_at: context.last_crash_at,
+                    violation_count: context.violation_count,
+                })
+            })
+            .collect()
+    };
+
+    let content = serde_json::to_string_pretty(&saved)
+        .map_err(|e| format!("Failed to serialize sandbox state: {}", e))?;
+    write_atomic(path, content).map_err(|e| format!("Failed to write sandbox state: {}", e))
 }
 
 /// Plugin sandbox (T094)
 pub struct PluginSandbox {
-    plugins: Arc<Mutex<HashMap<String, PluginExecutionContext>>>,
+    plugins: PluginContexts,
     max_crashes: u32,
+    /// Where crash and violation state is saved after each change
+    state_path: Mutex<Option<PathBuf>>,
+    save_pending: Arc<AtomicBool>,
 }
 
 impl PluginSandbox {
@@ -114,6 +234,8 @@ impl PluginSandbox {
         Self {
             plugins: Arc::new(Mutex::new(HashMap::new())),
             max_crashes: 3,
+            state_path: Mutex::new(None),
+            save_pending: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -125,19 +247,15 @@ impl PluginSandbox {
             return Err(format!("Plugin {} already registered", plugin_id));
         }
 
-        let permission_set: HashSet<PluginPermission> = permissions.into_iter().collect();
-
-        plugins.insert(plugin_id.clone(), PluginExecutionContext {
-            plugin_id,
-            granted_permissions: permission_set,
-            is_enabled: true,
-            crash_count: 0,
-        });
+        let mut context = PluginExecutionContext::new(&plugin_id);
+        context.granted_permissions = permissions.into_iter().collect();
+        plugins.insert(plugin_id, context);
 
         Ok(())
     }
 
-    /// Check if plugin has permission (T097)
+    /// Check if plugin has permission (T097). A requested permission that
+    /// awaits consent isn't granted until the user answers.
     pub fn check_permission(&self, plugin_id: &str, permission: PluginPermission) -> Result<bool, String> {
         let plugins = self.plugins.lock().unwrap();
 
@@ -148,7 +266,148 @@ impl PluginSandbox {
             return Err(format!("Plugin {} is disabled", plugin_id));
         }
 
-        Ok(context.granted_permissions.contains(&permission))
+        Ok(context.granted_permissions.contains(&permission) && !context.pending_consents.contains_key(&permission))
+    }
+
+    /// Whether `permission` of a plugin awaits the user's answer
+    pub fn is_consent_pending(&self, plugin_id: &str, permission: &PluginPermission) -> bool {
+        let plugins = self.plugins.lock().unwrap();
+        plugins
+            .get(plugin_id)
+            .is_some_and(|context| context.pending_consents.contains_key(permission))
+    }
+
+    /// Record the user's answer for a permission, granting or revoking it
+    pub fn set_consent(
+        &self,
+        plugin_id: &str,
+        permission: PluginPermission,
+        allowed: bool,
+        version: Option<String>,
+        source: ConsentSource,
+    ) -> PermissionConsent {
+        let consent = PermissionConsent {
+            permission,
+            allowed,
+            granted_at: Some(now_ms()),
+            granted_version: version,
+            source,
+        };
+        let mut plugins = self.plugins.lock().unwrap();
+        plugins
+            .entry(plugin_id.to_string())
+            .or_insert_with(|| PluginExecutionContext::new(plugin_id))
+            .record_consent(consent.clone());
+        consent
+    }
+
+    /// Bring back answers recorded before the plugin was uninstalled,
+    /// granting or revoking each permission as answered then. Only
+    /// permissions the installed version still requests are restored, and
+    /// an answer given since, e.g. at reinstall, is kept. Returns the
+    /// restored permissions.
+    pub fn restore_consents(
+        &self,
+        plugin_id: &str,
+        consents: Vec<PermissionConsent>,
+        requested: &[PluginPermission],
+    ) -> Vec<PluginPermission> {
+        let mut plugins = self.plugins.lock().unwrap();
+        let context = plugins
+            .entry(plugin_id.to_string())
+            .or_insert_with(|| PluginExecutionContext::new(plugin_id));
+        let mut restored = Vec::new();
+        for consent in consents {
+            if !requested.contains(&consent.permission) || context.consents.contains_key(&consent.permission) {
+                continue;
+            }
+            restored.push(consent.permission.clone());
+            context.record_consent(consent);
+        }
+        restored
+    }
+
+    /// Queue consent for the permissions a plugin version requests that
+    /// have never been answered. Returns the newly pending permissions.
+    pub fn request_consents(
+        &self,
+        plugin_id: &str,
+        version: &str,
+        requested: &[PluginPermission],
+        source: ConsentSource,
+    ) -> Vec<PluginPermission> {
+        let mut plugins = self.plugins.lock().unwrap();
+        let context = plugins
+            .entry(plugin_id.to_string())
+            .or_insert_with(|| PluginExecutionContext::new(plugin_id));
+
+        let mut added = Vec::new();
+        for permission in requested {
+            if context.consents.contains_key(permission) || context.pending_consents.contains_key(permission) {
+                continue;
+            }
+            context.pending_consents.insert(permission.clone(), PendingConsent {
+                plugin_id: plugin_id.to_string(),
+                permission: permission.clone(),
+                version: version.to_string(),
+                source,
+                requested_at: now_ms(),
+                dangerous: permission.is_dangerous(),
+            });
+            added.push(permission.clone());
+        }
+        added
+    }
+
+    /// Answer a pending consent. The record keeps the version and source
+    /// the permission was requested with.
+    pub fn respond_to_consent(
+        &self,
+        plugin_id: &str,
+        permission: &PluginPermission,
+        allow: bool,
+    ) -> Result<PermissionConsent, String> {
+        let mut plugins = self.plugins.lock().unwrap();
+        let context = plugins
+            .get_mut(plugin_id)
+            .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+        let pending = context
+            .pending_consents
+            .get(permission)
+            .cloned()
+            .ok_or_else(|| format!("No pending consent for {} of {}", permission.as_str(), plugin_id))?;
+
+        let consent = PermissionConsent {
+            permission: pending.permission,
+            allowed: allow,
+            granted_at: Some(now_ms()),
+            granted_version: Some(pending.version),
+            source: pending.source,
+        };
+        context.record_consent(consent.clone());
+        Ok(consent)
+    }
+
+    /// Consent history of a plugin, in permission order
+    pub fn get_consents(&self, plugin_id: &str) -> Vec<PermissionConsent> {
+        let plugins = self.plugins.lock().unwrap();
+        let mut consents: Vec<_> = plugins
+            .get(plugin_id)
+            .map(|context| context.consents.values().cloned().collect())
+            .unwrap_or_default();
+        consents.sort_by_key(|c| c.permission.as_str());
+        consents
+    }
+
+    /// Every unanswered consent, by plugin then permission
+    pub fn get_pending_consents(&self) -> Vec<PendingConsent> {
+        let plugins = self.plugins.lock().unwrap();
+        let mut pending: Vec<_> = plugins
+            .values()
+            .flat_map(|context| context.pending_consents.values().cloned())
+            .collect();
+        pending.sort_by(|a, b| (&a.plugin_id, a.permission.as_str()).cmp(&(&b.plugin_id, b.permission.as_str())));
+        pending
     }
 
     /// Execute plugin code with permission checks (T094, T097)
@@ -206,33 +465,61 @@ impl PluginSandbox {
 
     /// Handle plugin crash (T098)
     pub fn handle_plugin_crash(&self, plugin_id: &str) -> Result<bool, String> {
-        let mut plugins = self.plugins.lock().unwrap();
+        self.handle_plugin_crash_at(plugin_id, now_ms())
+    }
 
-        let context = plugins.get_mut(plugin_id)
-            .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+    fn handle_plugin_crash_at(&self, plugin_id: &str, now_ms: i64) -> Result<bool, String> {
+        let disabled = {
+            let mut plugins = self.plugins.lock().unwrap();
 
-        context.crash_count += 1;
+            let context = plugins.get_mut(plugin_id)
+                .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
 
-        // Disable plugin if it crashed too many times
-        if context.crash_count >= self.max_crashes {
-            context.is_enabled = false;
-            return Ok(true); // Plugin was disabled
-        }
+            context.expire_crashes(now_ms);
+            context.crash_count += 1;
+            context.last_crash_at = Some(now_ms);
 
-        Ok(false) // Plugin remains enabled
+            // Disable plugin if it crashed too many times
+            if context.crash_count >= self.max_crashes {
+                context.is_enabled = false;
+            }
+            !context.is_enabled
+        };
+
+        self.schedule_save();
+        Ok(disabled)
     }
 
-    /// Reset crash count for a plugin
+    /// Reset crash count for a plugin, re-enabling it if crashes disabled it
     pub fn reset_crash_count(&self, plugin_id: &str) -> Result<(), String> {
-        let mut plugins = self.plugins.lock().unwrap();
+        {
+            let mut plugins = self.plugins.lock().unwrap();
 
-        let context = plugins.get_mut(plugin_id)
-            .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+            let context = plugins.get_mut(plugin_id)
+                .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+
+            context.crash_count = 0;
+            context.last_crash_at = None;
+            context.is_enabled = true;
+        }
 
-        context.crash_count = 0;
+        self.schedule_save();
         Ok(())
     }
 
+    /// Count a denied host call against a plugin
+    pub fn record_violation(&self, plugin_id: &str) {
+        {
+            let mut plugins = self.plugins.lock().unwrap();
+            let Some(context) = plugins.get_mut(plugin_id) else {
+                return;
+            };
+            context.violation_count += 1;
+        }
+
+        self.schedule_save();
+    }
+
     /// Get plugin execution context
     pub fn get_plugin_context(&self, plugin_id: &str) -> Option<PluginExecutionContext> {
         let plugins = self.plugins.lock().unwrap();
@@ -241,12 +528,16 @@ impl PluginSandbox {
 
     /// Enable/disable a plugin
     pub fn set_plugin_enabled(&self, plugin_id: &str, enabled: bool) -> Result<(), String> {
-        let mut plugins = self.plugins.lock().unwrap();
+        {
+            let mut plugins = self.plugins.lock().unwrap();
 
-        let context = plugins.get_mut(plugin_id)
-            .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+            let context = plugins.get_mut(plugin_id)
+                .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+
+            context.is_enabled = enabled;
+        }
 
-        context.is_enabled = enabled;
+        self.schedule_save();
         Ok(())
     }
 
@@ -272,6 +563,168 @@ impl PluginSandbox {
         Ok(())
     }
 
+    /// Register a plugin with the permissions its manifest requests. Legacy
+    /// permission names are accepted; unknown ones fail registration.
+    pub fn register_manifest(&self, plugin_id: String, manifest: &PluginManifest) -> Result<(), String> {
+        let permissions = manifest.parsed_permissions()?;
+        self.register_plugin(plugin_id, permissions)
+    }
+
+    /// Register a plugin with no permissions unless it's already registered
+    pub fn ensure_registered(&self, plugin_id: &str) {
+        let mut plugins = self.plugins.lock().unwrap();
+        plugins
+            .entry(plugin_id.to_string())
+            .or_insert_with(|| PluginExecutionContext::new(plugin_id));
+    }
+
+    /// Replace the set of programs a plugin may run
+    pub fn set_shell_allowlist(&self, plugin_id: &str, commands: Vec<String>) -> Result<(), String> {
+        let mut plugins = self.plugins.lock().unwrap();
+
+        let context = plugins.get_mut(plugin_id)
+            .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+
+        context.shell_allowlist = commands.into_iter().collect();
+        Ok(())
+    }
+
+    /// Whether a plugin may run `command` (matched on the program name, not its arguments)
+    pub fn is_shell_command_allowed(&self, plugin_id: &str, command: &str) -> bool {
+        let plugins = self.plugins.lock().unwrap();
+        plugins
+            .get(plugin_id)
+            .is_some_and(|context| context.shell_allowlist.contains(command))
+    }
+
+    /// Load persisted grants, registering every plugin found
+    pub fn restore(&self, path: &Path) {
+        let Some(saved) = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<HashMap<String, PersistedGrants>>(&content).ok())
+        else {
+            return;
+        };
+
+        let mut plugins = self.plugins.lock().unwrap();
+        for (plugin_id, grants) in saved {
+            let context = plugins
+                .entry(plugin_id.clone())
+                .or_insert_with(|| PluginExecutionContext::new(&plugin_id));
+            context.granted_permissions = grants.granted_permissions.into_iter().collect();
+            context.shell_allowlist = grants.shell_allowlist.into_iter().collect();
+            context.consents = grants.consents.into_iter().map(|c| (c.permission.clone(), c)).collect();
+            context.pending_consents = grants
+                .pending_consents
+                .into_iter()
+                .map(|p| (p.permission.clone(), p))
+                .collect();
+
+            // Grants saved before consent was recorded
+            for permission in &context.granted_permissions {
+                context.consents.entry(permission.clone()).or_insert_with(|| PermissionConsent {
+                    permission: permission.clone(),
+                    allowed: true,
+                    granted_at: None,
+                    granted_version: None,
+                    source: ConsentSource::Manual,
+                });
+            }
+        }
+    }
+
+    /// Load crash and violation state and save it to `path` after every
+    /// later change. Expired crash counts are dropped on load.
+    pub fn restore_state(&self, path: &Path) {
+        self.restore_state_at(path, now_ms());
+    }
+
+    fn restore_state_at(&self, path: &Path, now_ms: i64) {
+        let saved = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<HashMap<String, PersistedState>>(&content).ok())
+            .unwrap_or_default();
+
+        {
+            let mut plugins = self.plugins.lock().unwrap();
+            for (plugin_id, state) in saved {
+                let context = plugins
+                    .entry(plugin_id.clone())
+                    .or_insert_with(|| PluginExecutionContext::new(&plugin_id));
+                context.is_enabled = state.is_enabled;
+                context.crash_count = state.crash_count;
+                context.last_crash_at = state.last_crash_at;
+                context.violation_count = state.violation_count;
+                context.expire_crashes(now_ms);
+            }
+        }
+
+        *self.state_path.lock().unwrap() = Some(path.to_path_buf());
+    }
+
+    /// Write crash and violation state now, if a state file is set
+    pub fn flush_state(&self) -> Result<(), String> {
+        match self.state_path.lock().unwrap().clone() {
+            Some(path) => write_state(&self.plugins, &path),
+            None => Ok(()),
+        }
+    }
+
+    /// Save state shortly, folding a burst of changes into one write
+    fn schedule_save(&self) {
+        let Some(path) = self.state_path.lock().unwrap().clone() else {
+            return;
+        };
+        if self.save_pending.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        let plugins = self.plugins.clone();
+        let pending = self.save_pending.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(STATE_SAVE_DEBOUNCE);
+            pending.store(false, Ordering::SeqCst);
+            if let Err(e) = write_state(&plugins, &path) {
+                eprintln!("[PluginSandbox] {}", e);
+            }
+        });
+    }
+
+    /// Persist granted permissions and shell allowlists
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let saved: HashMap<String, PersistedGrants> = {
+            let plugins = self.plugins.lock().unwrap();
+            plugins
+                .iter()
+                .map(|(id, context)| {
+                    let mut granted_permissions: Vec<_> = context.granted_permissions.iter().cloned().collect();
+                    granted_permissions.sort_by_key(|p| p.as_str());
+                    let mut shell_allowlist: Vec<_> = context.shell_allowlist.iter().cloned().collect();
+                    shell_allowlist.sort();
+                    let consents: BTreeMap<_, _> =
+                        context.consents.values().map(|c| (c.permission.as_str(), c.clone())).collect();
+                    let pending_consents: BTreeMap<_, _> =
+                        context.pending_consents.values().map(|p| (p.permission.as_str(), p.clone())).collect();
+                    (id.clone(), PersistedGrants {
+                        granted_permissions,
+                        shell_allowlist,
+                        consents: consents.into_values().collect(),
+                        pending_consents: pending_consents.into_values().collect(),
+                    })
+                })
+                .collect()
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create plugins dir: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(&saved)
+            .map_err(|e| format!("Failed to serialize plugin permissions: {}", e))?;
+        fs::write(path, content)
+            .map_err(|e| format!("Failed to write plugin permissions: {}", e))
+    }
+
     /// Get all registered plugins
     pub fn get_registered_plugins(&self) -> Vec<String> {
         let plugins = self.plugins.lock().unwrap();
@@ -284,13 +737,205 @@ impl PluginSandbox {
 
         plugins.remove(plugin_id)
             .ok_or_else(|| format!("Plugin {} not found", plugin_id))?;
+        drop(plugins);
 
+        self.schedule_save();
         Ok(())
     }
 }
 
+/// Granted permissions file for an app data dir
+pub fn plugin_permissions_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("plugins").join(PLUGIN_PERMISSIONS_FILE)
+}
+
+/// Crash and violation state file for an app data dir
+pub fn sandbox_state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("plugins").join(SANDBOX_STATE_FILE)
+}
+
 impl Default for PluginSandbox {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_grants_survive_save_and_restore() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = plugin_permissions_path(dir.path());
+
+        let sandbox = PluginSandbox::new();
+        sandbox.ensure_registered("devtools");
+        sandbox.grant_permission("devtools", PluginPermission::Shell).unwrap();
+        sandbox.set_shell_allowlist("devtools", vec!["git".to_string()]).unwrap();
+        sandbox.save(&path).unwrap();
+
+        let restored = PluginSandbox::new();
+        restored.restore(&path);
+        assert!(restored.check_permission("devtools", PluginPermission::Shell).unwrap());
+        assert!(restored.is_shell_command_allowed("devtools", "git"));
+        assert!(!restored.is_shell_command_allowed("devtools", "rm"));
+    }
+
+    #[test]
+    fn test_grants_without_consent_records_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = plugin_permissions_path(dir.path());
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, r#"{"devtools": {"granted_permissions": ["Shell"], "shell_allowlist": []}}"#).unwrap();
+
+        let sandbox = PluginSandbox::new();
+        sandbox.restore(&path);
+        assert!(sandbox.check_permission("devtools", PluginPermission::Shell).unwrap());
+        let consents = sandbox.get_consents("devtools");
+        assert_eq!(consents.len(), 1);
+        assert_eq!(
+            (consents[0].allowed, consents[0].granted_at, consents[0].source),
+            (true, None, ConsentSource::Manual)
+        );
+
+        // Revoking keeps the record, so the permission isn't asked for again
+        sandbox.set_consent("devtools", PluginPermission::Shell, false, None, ConsentSource::Manual);
+        assert!(!sandbox.check_permission("devtools", PluginPermission::Shell).unwrap());
+        let shell = [PluginPermission::Shell];
+        assert!(sandbox.request_consents("devtools", "1.0.0", &shell, ConsentSource::Update).is_empty());
+        assert!(sandbox.respond_to_consent("devtools", &PluginPermission::Shell, true).is_err());
+    }
+
+    #[test]
+    fn test_crash_disabled_plugin_stays_disabled_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = sandbox_state_path(dir.path());
+
+        let sandbox = PluginSandbox::new();
+        sandbox.restore_state(&path);
+        sandbox.ensure_registered("flaky");
+        assert!(!sandbox.handle_plugin_crash("flaky").unwrap());
+        assert!(!sandbox.handle_plugin_crash("flaky").unwrap());
+        assert!(sandbox.handle_plugin_crash("flaky").unwrap());
+        sandbox.record_violation("flaky");
+        sandbox.flush_state().unwrap();
+
+        // Simulated restart
+        let restarted = PluginSandbox::new();
+        restarted.restore_state(&path);
+        let context = restarted.get_plugin_context("flaky").unwrap();
+        assert!(!context.is_enabled);
+        assert_eq!(context.crash_count, 3);
+        assert_eq!(context.violation_count, 1);
+        assert!(restarted.check_permission("flaky", PluginPermission::Network).is_err());
+
+        restarted.reset_crash_count("flaky").unwrap();
+        assert!(restarted.get_plugin_context("flaky").unwrap().is_enabled);
+    }
+
+    #[test]
+    fn test_crash_counts_expire_after_a_week() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = sandbox_state_path(dir.path());
+        let day = 24 * 60 * 60 * 1000;
+        let start = now_ms() - 10 * day;
+
+        let sandbox = PluginSandbox::new();
+        sandbox.restore_state(&path);
+        sandbox.ensure_registered("flaky");
+        sandbox.handle_plugin_crash_at("flaky", start).unwrap();
+        sandbox.handle_plugin_crash_at("flaky", start + day).unwrap();
+
+        // Eight days after the last crash, the next one starts a new count
+        assert!(!sandbox.handle_plugin_crash_at("flaky", start + 9 * day).unwrap());
+        assert_eq!(sandbox.get_plugin_context("flaky").unwrap().crash_count, 1);
+
+        // Counts that expired while the app was closed are dropped on load
+        sandbox.flush_state().unwrap();
+        let restarted = PluginSandbox::new();
+        restarted.restore_state_at(&path, start + 17 * day);
+        assert_eq!(restarted.get_plugin_context("flaky").unwrap().crash_count, 0);
+    }
+
+    #[test]
+    fn test_legacy_manifest_permissions_are_checked() {
+        let manifest: PluginManifest = serde_json::from_value(serde_json::json!({
+            "name": "Legacy",
+            "version": "1.0.0",
+            "description": "Old-style permissions",
+            "author": "tester",
+            "permissions": ["clipboard:read", "fs:write"],
+            "entry": "index.js",
+            "triggers": []
+        }))
+        .unwrap();
+
+        let sandbox = PluginSandbox::new();
+        sandbox.register_manifest("legacy".to_string(), &manifest).unwrap();
+
+        assert!(sandbox.check_permission("legacy", PluginPermission::ReadClipboard).unwrap());
+        assert!(sandbox.check_permission("legacy", PluginPermission::WriteFile).unwrap());
+        assert!(!sandbox.check_permission("legacy", PluginPermission::Shell).unwrap());
+    }
+
+    #[test]
+    fn test_unknown_manifest_permission_fails_registration() {
+        let mut manifest: PluginManifest = serde_json::from_value(serde_json::json!({
+            "name": "Odd", "version": "1.0.0", "description": "d", "author": "a",
+            "permissions": ["read_clipboard", "made:up"], "entry": "index.js", "triggers": []
+        }))
+        .unwrap();
+
+        let sandbox = PluginSandbox::new();
+        assert!(sandbox.register_manifest("odd".to_string(), &manifest).is_err());
+        manifest.permissions.pop();
+        assert!(sandbox.register_manifest("odd".to_string(), &manifest).is_ok());
+    }
+
+    #[test]
+    fn test_restored_consents_keep_fresh_answers_and_requested_permissions() {
+        let archived = |permission, allowed| PermissionConsent {
+            permission,
+            allowed,
+            granted_at: Some(1),
+            granted_version: Some("1.0.0".to_string()),
+            source: ConsentSource::Install,
+        };
+        let sandbox = PluginSandbox::new();
+        sandbox.ensure_registered("notes");
+        // Declined in the reinstall dialog
+        sandbox.set_consent("notes", PluginPermission::ReadClipboard, false, None, ConsentSource::Install);
+
+        let restored = sandbox.restore_consents(
+            "notes",
+            vec![
+                archived(PluginPermission::ReadClipboard, true),
+                archived(PluginPermission::Notification, true),
+                // No longer requested by the installed version
+                archived(PluginPermission::Shell, true),
+            ],
+            &[PluginPermission::ReadClipboard, PluginPermission::Notification],
+        );
+
+        assert_eq!(restored, vec![PluginPermission::Notification]);
+        assert!(!sandbox.check_permission("notes", PluginPermission::ReadClipboard).unwrap());
+        assert!(sandbox.check_permission("notes", PluginPermission::Notification).unwrap());
+        assert!(!sandbox.check_permission("notes", PluginPermission::Shell).unwrap());
+    }
+
+    #[test]
+    fn test_pending_consent_is_not_granted() {
+        let sandbox = PluginSandbox::new();
+        sandbox.ensure_registered("notes");
+        let requested = [PluginPermission::Notification, PluginPermission::ReadClipboard];
+        sandbox.request_consents("notes", "1.0.0", &requested, ConsentSource::Install);
+
+        // Neither dangerous, and still not granted before the answer
+        assert!(!sandbox.check_permission("notes", PluginPermission::Notification).unwrap());
+        sandbox.respond_to_consent("notes", &PluginPermission::Notification, true).unwrap();
+        sandbox.respond_to_consent("notes", &PluginPermission::ReadClipboard, false).unwrap();
+        assert!(sandbox.check_permission("notes", PluginPermission::Notification).unwrap());
+        assert!(!sandbox.check_permission("notes", PluginPermission::ReadClipboard).unwrap());
+    }
+}
@@ -9,8 +9,9 @@
 #![allow(dead_code)]
 #![allow(unused_variables)]
 
-use tauri::{AppHandle, Manager};
+use tauri::AppHandle;
 use crate::models::plugin::*;
+use crate::services::app_paths;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -102,10 +103,7 @@ impl PluginCache {
 
 /// Get plugins directory
 fn get_plugins_dir(handle: &AppHandle) -> PluginResult<PathBuf> {
-    let app_dir = handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get data dir: {}", e))?
+    let app_dir = app_paths::data_dir(handle)?
         .join("plugins");
 
     if !app_dir.exists() {
@@ -213,6 +211,11 @@ impl PluginService {
             entry_point: String::new(),
             triggers: Vec::new(),
             settings: HashMap::new(),
+            api_version: None,
+            api_compatibility: Default::default(),
+            protected: false,
+            suppressed_triggers: Vec::new(),
+            links: Default::default(),
             health: PluginHealth {
                 status: PluginHealthStatus::Unknown,
                 message: None,
@@ -243,10 +246,7 @@ impl PluginService {
 
         // Load from disk if cache miss
         if state.is_empty() {
-            let app_data_dir = handle
-                .path()
-                .app_data_dir()
-                .map_err(|e| format!("Failed to get data dir: {}", e))?;
+            let app_data_dir = app_paths::data_dir(handle)?;
             let state_file = app_data_dir.join("plugins/plugin_state.json");
 
             if state_file.exists() {
@@ -261,10 +261,7 @@ impl PluginService {
         state.insert(plugin_id.to_string(), true);
 
         // Save state to disk
-        let app_data_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?;
+        let app_data_dir = app_paths::data_dir(handle)?;
         let state_file = app_data_dir.join("plugins/plugin_state.json");
 
         fs::create_dir_all(state_file.parent().unwrap())
@@ -302,10 +299,7 @@ impl PluginService {
 
         // Load from disk if cache miss
         if state.is_empty() {
-            let app_data_dir = handle
-                .path()
-                .app_data_dir()
-                .map_err(|e| format!("Failed to get data dir: {}", e))?;
+            let app_data_dir = app_paths::data_dir(handle)?;
             let state_file = app_data_dir.join("plugins/plugin_state.json");
 
             if state_file.exists() {
@@ -320,10 +314,7 @@ impl PluginService {
         state.insert(plugin_id.to_string(), false);
 
         // Save state to disk
-        let app_data_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?;
+        let app_data_dir = app_paths::data_dir(handle)?;
         let state_file = app_data_dir.join("plugins/plugin_state.json");
 
         fs::create_dir_all(state_file.parent().unwrap())
@@ -352,10 +343,7 @@ impl PluginService {
     /// Get plugin health
     pub fn get_plugin_health(&self, plugin_id: &str, handle: &AppHandle) -> PluginResult<PluginHealth> {
         // Load health from file if exists
-        let app_data_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?;
+        let app_data_dir = app_paths::data_dir(handle)?;
         let health_file = app_data_dir.join(format!("plugins/{}/health.json", plugin_id));
 
         if health_file.exists() {
@@ -397,10 +385,7 @@ impl PluginService {
             .unwrap_or(false);
 
         // Check plugin directory exists
-        let app_data_dir = handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get data dir: {}", e))?;
+        let app_data_dir = app_paths::data_dir(handle)?;
         let plugin_dir = app_data_dir.join(format!("plugins/{}", plugin_id));
 
         if !plugin_dir.exists() {
@@ -0,0 +1,347 @@
+//! Plugin Storage
+//! A private data directory per plugin under plugins-data/, holding the
+//! plugin's key-value store and any files it writes there. Every write is
+//! checked against the plugin's quota, and paths are resolved so nothing
+//! can land outside the plugin's own directory.
+
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+/// Quota when settings don't say otherwise
+pub const DEFAULT_QUOTA_BYTES: u64 = 50 * 1024 * 1024;
+
+/// The key-value store inside each plugin directory. Reserved: file
+/// operations can't touch it.
+const STORE_FILE: &str = ".storage.json";
+
+/// Where plugin data directories live inside the app data directory
+pub fn plugin_data_root(data_dir: &Path) -> PathBuf {
+    data_dir.join("plugins-data")
+}
+
+/// Storage failure, serialized so plugins can tell a full quota apart
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum StorageError {
+    /// The write needs `needed_bytes` more than the quota has left
+    QuotaExceeded { used_bytes: u64, quota_bytes: u64, needed_bytes: u64 },
+    /// The plugin id or file path would leave the plugin's directory
+    InvalidPath { path: String },
+    Failed { message: String },
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::QuotaExceeded { used_bytes, quota_bytes, needed_bytes } => write!(
+                f,
+                "Storage quota exceeded: {} of {} bytes used, {} more needed",
+                used_bytes, quota_bytes, needed_bytes
+            ),
+            StorageError::InvalidPath { path } => write!(f, "Invalid storage path: {}", path),
+            StorageError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> Self {
+        StorageError::Failed { message: format!("Storage I/O failed: {}", e) }
+    }
+}
+
+/// Payload of `get_plugin_storage_usage`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorageUsage {
+    pub plugin_id: String,
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+}
+
+/// Per-plugin data directories under one root
+pub struct PluginStorage {
+    root: PathBuf,
+    /// Serializes writes so two calls can't both pass the quota check
+    writes: Mutex<()>,
+}
+
+impl PluginStorage {
+    /// Storage rooted at `root` (the app's plugins-data directory)
+    pub fn new(root: PathBuf) -> Self {
+        Self { root, writes: Mutex::new(()) }
+    }
+
+    /// The plugin's directory, whether or not it exists yet. Scoped npm
+    /// names ("@scope/name") are encoded into a single folder name.
+    pub fn plugin_dir(&self, plugin_id: &str) -> Result<PathBuf, StorageError> {
+        if plugin_id.is_empty() || plugin_id == "." || plugin_id == ".." {
+            return Err(StorageError::InvalidPath { path: plugin_id.to_string() });
+        }
+        Ok(self.root.join(urlencoding::encode(plugin_id).as_ref()))
+    }
+
+    /// The plugin's directory, created on first use
+    pub fn data_dir(&self, plugin_id: &str) -> Result<PathBuf, StorageError> {
+        let dir = self.plugin_dir(plugin_id)?;
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    pub fn get(&self, plugin_id: &str, key: &str) -> Result<Option<serde_json::Value>, StorageError> {
+        Ok(self.load_store(plugin_id)?.remove(key))
+    }
+
+    /// Store `value` under `key`, replacing any previous value
+    pub fn set(&self, plugin_id: &str, key: &str, value: serde_json::Value, quota_bytes: u64) -> Result<(), StorageError> {
+        let _write = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+        let mut store = self.load_store(plugin_id)?;
+        store.insert(key.to_string(), value);
+        self.save_store(plugin_id, &store, quota_bytes)
+    }
+
+    /// Remove `key`; returns whether it was set
+    pub fn delete(&self, plugin_id: &str, key: &str) -> Result<bool, StorageError> {
+        let _write = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+        let mut store = self.load_store(plugin_id)?;
+        if store.remove(key).is_none() {
+            return Ok(false);
+        }
+        // Shrinking never exceeds the quota
+        self.save_store(plugin_id, &store, u64::MAX)?;
+        Ok(true)
+    }
+
+    pub fn read_file(&self, plugin_id: &str, path: &str) -> Result<String, StorageError> {
+        let file = self.resolve(plugin_id, path)?;
+        Ok(fs::read_to_string(file)?)
+    }
+
+    /// Write a file inside the plugin's directory; returns bytes written
+    pub fn write_file(&self, plugin_id: &str, path: &str, contents: &str, quota_bytes: u64) -> Result<usize, StorageError> {
+        let _write = self.writes.lock().unwrap_or_else(|e| e.into_inner());
+        let file = self.resolve(plugin_id, path)?;
+        self.check_quota(plugin_id, &file, contents.len() as u64, quota_bytes)?;
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&file, contents)?;
+        Ok(contents.len())
+    }
+
+    /// Delete a file inside the plugin's directory; returns whether it existed
+    pub fn delete_file(&self, plugin_id: &str, path: &str) -> Result<bool, StorageError> {
+        let file = self.resolve(plugin_id, path)?;
+        match fs::remove_file(&file) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Bytes the plugin currently stores
+    pub fn usage(&self, plugin_id: &str, quota_bytes: u64) -> Result<StorageUsage, StorageError> {
+        Ok(StorageUsage {
+            plugin_id: plugin_id.to_string(),
+            used_bytes: dir_size(&self.plugin_dir(plugin_id)?),
+            quota_bytes,
+        })
+    }
+
+    /// Delete everything the plugin stored; returns whether anything existed
+    pub fn purge(&self, plugin_id: &str) -> Result<bool, StorageError> {
+        let dir = self.plugin_dir(plugin_id)?;
+        match fs::remove_dir_all(&dir) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Resolve a plugin-supplied relative path inside its directory.
+    /// Absolute paths, `..`, the key-value store and symlinks that lead
+    /// out of the directory are rejected.
+    fn resolve(&self, plugin_id: &str, path: &str) -> Result<PathBuf, StorageError> {
+        let invalid = || StorageError::InvalidPath { path: path.to_string() };
+        let dir = self.data_dir(plugin_id)?;
+
+        let mut relative = PathBuf::new();
+        for component in Path::new(path).components() {
+            match component {
+                Component::Normal(part) => relative.push(part),
+                Component::CurDir => {}
+                Component::ParentDir | Component::RootDir | Component::Prefix(_) => return Err(invalid()),
+            }
+        }
+        if relative.as_os_str().is_empty() || relative == Path::new(STORE_FILE) {
+            return Err(invalid());
+        }
+
+        // Follow any symlinks already in the directory and check where the
+        // deepest existing ancestor really is
+        let root = dir.canonicalize()?;
+        let target = dir.join(&relative);
+        let existing = target.ancestors().find(|p| p.exists()).unwrap_or(&dir);
+        if !existing.canonicalize()?.starts_with(&root) {
+            return Err(invalid());
+        }
+        if fs::symlink_metadata(&target).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Err(invalid());
+        }
+        Ok(target)
+    }
+
+    fn load_store(&self, plugin_id: &str) -> Result<serde_json::Map<String, serde_json::Value>, StorageError> {
+        let path = self.plugin_dir(plugin_id)?.join(STORE_FILE);
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).map_err(|e| StorageError::Failed {
+                message: format!("Corrupt plugin storage: {}", e),
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(serde_json::Map::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_store(
+        &self,
+        plugin_id: &str,
+        store: &serde_json::Map<String, serde_json::Value>,
+        quota_bytes: u64,
+    ) -> Result<(), StorageError> {
+        let path = self.data_dir(plugin_id)?.join(STORE_FILE);
+        let contents = serde_json::to_string(store).map_err(|e| StorageError::Failed { message: e.to_string() })?;
+        self.check_quota(plugin_id, &path, contents.len() as u64, quota_bytes)?;
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// Fail if replacing `file` with `new_len` bytes would exceed the quota
+    fn check_quota(&self, plugin_id: &str, file: &Path, new_len: u64, quota_bytes: u64) -> Result<(), StorageError> {
+        let used_bytes = dir_size(&self.plugin_dir(plugin_id)?);
+        let replaced = fs::metadata(file).map(|m| m.len()).unwrap_or(0);
+        let after = used_bytes.saturating_sub(replaced).saturating_add(new_len);
+        if after > quota_bytes {
+            return Err(StorageError::QuotaExceeded {
+                used_bytes,
+                quota_bytes,
+                needed_bytes: after - quota_bytes,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Total size of the files under `dir`, 0 if it doesn't exist. Symlinks
+/// count as themselves, not what they point to.
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn storage() -> (tempfile::TempDir, PluginStorage) {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = PluginStorage::new(dir.path().join("plugins-data"));
+        (dir, storage)
+    }
+
+    #[test]
+    fn test_key_value_round_trip() {
+        let (_dir, storage) = storage();
+
+        assert_eq!(storage.get("notes", "draft").unwrap(), None);
+        storage.set("notes", "draft", json!({ "text": "hi" }), DEFAULT_QUOTA_BYTES).unwrap();
+        assert_eq!(storage.get("notes", "draft").unwrap(), Some(json!({ "text": "hi" })));
+
+        // Plugins don't see each other's keys
+        assert_eq!(storage.get("@etools-plugin/other", "draft").unwrap(), None);
+
+        assert!(storage.delete("notes", "draft").unwrap());
+        assert!(!storage.delete("notes", "draft").unwrap());
+        assert_eq!(storage.get("notes", "draft").unwrap(), None);
+    }
+
+    #[test]
+    fn test_writes_over_quota_rejected() {
+        let (_dir, storage) = storage();
+        let quota = 1000;
+
+        storage.write_file("notes", "cache/a.txt", &"a".repeat(600), quota).unwrap();
+        let err = storage.write_file("notes", "cache/b.txt", &"b".repeat(600), quota).unwrap_err();
+        assert_eq!(err, StorageError::QuotaExceeded { used_bytes: 600, quota_bytes: 1000, needed_bytes: 200 });
+        assert!(!storage.plugin_dir("notes").unwrap().join("cache/b.txt").exists());
+
+        // Replacing a file only counts the difference
+        storage.write_file("notes", "cache/a.txt", &"a".repeat(900), quota).unwrap();
+        assert!(matches!(
+            storage.set("notes", "big", json!("x".repeat(200)), quota),
+            Err(StorageError::QuotaExceeded { .. })
+        ));
+
+        storage.delete_file("notes", "cache/a.txt").unwrap();
+        storage.set("notes", "big", json!("x".repeat(200)), quota).unwrap();
+        assert_eq!(storage.usage("notes", quota).unwrap().used_bytes, 210);
+    }
+
+    #[test]
+    fn test_paths_cannot_leave_plugin_dir() {
+        let (dir, storage) = storage();
+        fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+
+        for path in ["../../secret.txt", "/etc/passwd", "a/../../b", "", ".", ".storage.json"] {
+            assert!(
+                matches!(storage.write_file("notes", path, "x", DEFAULT_QUOTA_BYTES), Err(StorageError::InvalidPath { .. })),
+                "{} was accepted",
+                path
+            );
+        }
+        assert!(matches!(storage.read_file("notes", "../../secret.txt"), Err(StorageError::InvalidPath { .. })));
+        assert!(matches!(storage.data_dir(".."), Err(StorageError::InvalidPath { .. })));
+
+        // Ids with separators stay one folder below the root
+        let scoped = storage.data_dir("@etools-plugin/../../x").unwrap();
+        assert_eq!(scoped.parent().unwrap(), dir.path().join("plugins-data"));
+
+        #[cfg(unix)]
+        {
+            let plugin_dir = storage.data_dir("notes").unwrap();
+            std::os::unix::fs::symlink(dir.path(), plugin_dir.join("escape")).unwrap();
+            assert!(matches!(
+                storage.write_file("notes", "escape/secret.txt", "x", DEFAULT_QUOTA_BYTES),
+                Err(StorageError::InvalidPath { .. })
+            ));
+            assert_eq!(fs::read_to_string(dir.path().join("secret.txt")).unwrap(), "secret");
+        }
+    }
+
+    #[test]
+    fn test_purge_removes_everything() {
+        let (_dir, storage) = storage();
+        storage.set("notes", "k", json!(1), DEFAULT_QUOTA_BYTES).unwrap();
+        storage.write_file("notes", "files/a.txt", "a", DEFAULT_QUOTA_BYTES).unwrap();
+        storage.set("other", "k", json!(1), DEFAULT_QUOTA_BYTES).unwrap();
+
+        assert!(storage.purge("notes").unwrap());
+        assert!(!storage.plugin_dir("notes").unwrap().exists());
+        assert_eq!(storage.usage("notes", DEFAULT_QUOTA_BYTES).unwrap().used_bytes, 0);
+        assert!(!storage.purge("notes").unwrap());
+
+        assert_eq!(storage.get("other", "k").unwrap(), Some(json!(1)));
+    }
+}
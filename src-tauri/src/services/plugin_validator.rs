@@ -2,24 +2,122 @@
 //! Handles plugin manifest validation and security checks
 #![allow(dead_code)]
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
-use crate::models::plugin::PluginManifest;
+use crate::models::plugin::{
+    is_web_url, ApiCompatibility, PluginLinks, PluginManifest, PluginPermission, TriggerArg, TRIGGER_ARG_TYPES,
+};
+use crate::services::i18n;
+use crate::services::plugin_api::{check_api_version, HOST_API_VERSION, LEGACY_API_VERSION, SUPPORTED_API_RANGE};
+use crate::services::plugin_protection::is_allowlisted;
+use crate::t;
+
+/// Largest plugin entry file accepted, in bytes
+pub const MAX_ENTRY_BYTES: u64 = 20 * 1024 * 1024;
+
+/// Longest `license` accepted without a warning
+pub const MAX_LICENSE_CHARS: usize = 64;
 
 /// Validation error
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationError {
     pub code: String,
+    /// Rendered in the backend's current language
     pub message: String,
     pub field: Option<String>,
+    /// i18n code and arguments, for frontends that translate themselves
+    #[serde(default)]
+    pub message_code: String,
+    #[serde(default)]
+    pub message_args: HashMap<String, String>,
 }
 
 /// Validation warning
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationWarning {
     pub code: String,
+    /// Rendered in the backend's current language
     pub message: String,
     pub field: Option<String>,
+    /// i18n code and arguments, for frontends that translate themselves
+    #[serde(default)]
+    pub message_code: String,
+    #[serde(default)]
+    pub message_args: HashMap<String, String>,
+}
+
+impl ValidationError {
+    fn new(code: &str, field: &str, message_code: &str, args: &[(&str, String)]) -> Self {
+        Self {
+            code: code.to_string(),
+            message: i18n::tr(message_code, args),
+            field: Some(field.to_string()),
+            message_code: message_code.to_string(),
+            message_args: i18n::args_map(args),
+        }
+    }
+}
+
+impl ValidationWarning {
+    fn new(code: &str, field: &str, message_code: &str, args: &[(&str, String)]) -> Self {
+        Self {
+            code: code.to_string(),
+            message: i18n::tr(message_code, args),
+            field: Some(field.to_string()),
+            message_code: message_code.to_string(),
+            message_args: i18n::args_map(args),
+        }
+    }
+}
+
+/// Validation outcome as messages, for commands and the installer
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginValidationResult {
+    pub is_valid: bool,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl From<(Vec<ValidationError>, Vec<ValidationWarning>)> for PluginValidationResult {
+    fn from((errors, warnings): (Vec<ValidationError>, Vec<ValidationWarning>)) -> Self {
+        Self {
+            is_valid: errors.is_empty(),
+            errors: errors.into_iter().map(|e| e.message).collect(),
+            warnings: warnings.into_iter().map(|w| w.message).collect(),
+        }
+    }
+}
+
+/// How risky a requested permission is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// One requested permission's contribution to the security score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PermissionRisk {
+    pub permission: String,
+    pub risk: RiskLevel,
+    /// Points deducted from the security score
+    pub score_impact: u8,
+}
+
+/// Security review of a manifest, shown in the install and consent UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityReport {
+    /// 0-100, higher is safer
+    pub score: u8,
+    /// Where the plugin's source can be reviewed, if it says
+    pub repository: Option<String>,
+    pub errors: Vec<ValidationError>,
+    pub warnings: Vec<ValidationWarning>,
+    pub permission_risk_breakdown: Vec<PermissionRisk>,
 }
 
 /// Permission definition
@@ -31,33 +129,12 @@ pub struct PermissionDefinition {
     pub category: String,
 }
 
-pub struct PluginValidator {
-    allowed_permissions: HashSet<String>,
-}
+#[derive(Default)]
+pub struct PluginValidator;
 
 impl PluginValidator {
     pub fn new() -> Self {
-        let mut allowed_permissions = HashSet::new();
-
-        // Define allowed permissions
-        let permissions = vec![
-            "clipboard:read".to_string(),
-            "clipboard:write".to_string(),
-            "fs:read".to_string(),
-            "fs:write".to_string(),
-            "network".to_string(),
-            "shell".to_string(),
-            "notification".to_string(),
-            "plugin:manage".to_string(),
-        ];
-
-        for permission in permissions {
-            allowed_permissions.insert(permission);
-        }
-
-        Self {
-            allowed_permissions,
-        }
+        Self
     }
 
     /// Validate plugin manifest
@@ -75,10 +152,14 @@ impl PluginValidator {
         // Validate ID format if provided
         if let Some(id) = plugin_id {
             self.validate_plugin_id(id, &mut errors);
+            self.validate_protected(manifest.protected, id, &mut errors);
         }
 
         // Validate version format
-        self.validate_version(&manifest.version, &mut errors);
+        self.validate_version(&manifest.version, &mut errors, &mut warnings);
+
+        // Check the host API version the plugin needs
+        self.validate_api_version(manifest.api_version.as_deref(), &mut errors, &mut warnings);
 
         // Validate entry path
         self.validate_entry_path(&manifest.entry, &mut errors);
@@ -87,7 +168,15 @@ impl PluginValidator {
         self.validate_permissions(&manifest.permissions, &mut errors, &mut warnings);
 
         // Validate triggers
-        self.validate_triggers(&manifest.triggers, &mut errors);
+        self.validate_triggers(&manifest.triggers, &mut errors, &mut warnings);
+
+        // Validate the drop handler, if any
+        if manifest.accepts_drops.is_some() {
+            self.validate_drop_capability(manifest, &mut errors, &mut warnings);
+        }
+
+        // Links and license are optional, so problems there only warn
+        self.validate_links(&manifest.links, &mut warnings);
 
         // Check for potential security issues
         self.validate_security(&manifest, &mut warnings);
@@ -95,6 +184,52 @@ impl PluginValidator {
         (errors, warnings)
     }
 
+    /// Validate a plugin's manifest against its files in `plugin_dir`, an
+    /// installed plugin or a package staged for install. The entry must be
+    /// a non-empty file no larger than `MAX_ENTRY_BYTES`.
+    pub fn validate_installed(
+        &self,
+        manifest: &PluginManifest,
+        plugin_id: &str,
+        plugin_dir: &Path,
+    ) -> (Vec<ValidationError>, Vec<ValidationWarning>) {
+        let (mut errors, warnings) = self.validate_manifest(manifest, Some(plugin_id));
+
+        if !manifest.entry.trim().is_empty() {
+            self.validate_entry_file(&plugin_dir.join(&manifest.entry), &mut errors);
+        }
+
+        (errors, warnings)
+    }
+
+    /// Check the entry file a manifest points at
+    fn validate_entry_file(&self, entry_path: &Path, errors: &mut Vec<ValidationError>) {
+        let path = ("path", entry_path.display().to_string());
+        let size = match fs::metadata(entry_path) {
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => {
+                errors.push(ValidationError::new(
+                    "ENTRY_NOT_FOUND",
+                    "entry",
+                    "validator.entry_not_found",
+                    &[path],
+                ));
+                return;
+            }
+        };
+
+        if size == 0 {
+            errors.push(ValidationError::new("ENTRY_EMPTY", "entry", "validator.entry_empty", &[path]));
+        } else if size > MAX_ENTRY_BYTES {
+            errors.push(ValidationError::new(
+                "ENTRY_TOO_LARGE",
+                "entry",
+                "validator.entry_too_large",
+                &[path, ("max_mb", (MAX_ENTRY_BYTES / (1024 * 1024)).to_string())],
+            ));
+        }
+    }
+
     /// Validate required fields are present
     fn validate_required_fields(
         &self,
@@ -105,44 +240,49 @@ impl PluginValidator {
         // Validate plugin_id if provided
         if let Some(id) = plugin_id {
             if id.trim().is_empty() {
-                errors.push(ValidationError {
-                    code: "REQUIRED_FIELD_MISSING".to_string(),
-                    message: "插件ID是必填项".to_string(),
-                    field: Some("id".to_string()),
-                });
+                errors.push(ValidationError::new(
+                    "REQUIRED_FIELD_MISSING",
+                    "id",
+                    "validator.id_required",
+                    &[],
+                ));
             }
         }
 
         if manifest.name.trim().is_empty() {
-            errors.push(ValidationError {
-                code: "REQUIRED_FIELD_MISSING".to_string(),
-                message: "插件名称是必填项".to_string(),
-                field: Some("name".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "REQUIRED_FIELD_MISSING",
+                "name",
+                "validator.name_required",
+                &[],
+            ));
         }
 
         if manifest.description.trim().is_empty() {
-            errors.push(ValidationError {
-                code: "REQUIRED_FIELD_MISSING".to_string(),
-                message: "插件描述是必填项".to_string(),
-                field: Some("description".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "REQUIRED_FIELD_MISSING",
+                "description",
+                "validator.description_required",
+                &[],
+            ));
         }
 
         if manifest.author.as_ref().map(|a| a.trim()).unwrap_or_default().is_empty() {
-            errors.push(ValidationError {
-                code: "REQUIRED_FIELD_MISSING".to_string(),
-                message: "插件作者是必填项".to_string(),
-                field: Some("author".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "REQUIRED_FIELD_MISSING",
+                "author",
+                "validator.author_required",
+                &[],
+            ));
         }
 
         if manifest.entry.trim().is_empty() {
-            errors.push(ValidationError {
-                code: "REQUIRED_FIELD_MISSING".to_string(),
-                message: "入口文件路径是必填项".to_string(),
-                field: Some("entry".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "REQUIRED_FIELD_MISSING",
+                "entry",
+                "validator.entry_required",
+                &[],
+            ));
         }
     }
 
@@ -150,44 +290,110 @@ impl PluginValidator {
     fn validate_plugin_id(&self, id: &str, errors: &mut Vec<ValidationError>) {
         // ID format: lowercase alphanumeric with hyphens, 3-50 chars
         if !is_valid_plugin_id(id) {
-            errors.push(ValidationError {
-                code: "INVALID_ID_FORMAT".to_string(),
-                message: "插件ID格式无效：只能包含小写字母、数字和连字符，长度3-50字符".to_string(),
-                field: Some("id".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "INVALID_ID_FORMAT",
+                "id",
+                "validator.invalid_id_format",
+                &[],
+            ));
         }
 
         // Check for reserved words
         let reserved_words = vec!["kaka", "system", "core", "admin", "root"];
         if reserved_words.iter().any(|word| id.contains(word)) {
-            errors.push(ValidationError {
-                code: "RESERVED_ID".to_string(),
-                message: "插件ID包含保留字".to_string(),
-                field: Some("id".to_string()),
-            });
+            errors.push(ValidationError::new("RESERVED_ID", "id", "validator.reserved_id", &[]));
+        }
+    }
+
+    /// Only first-party plugins on the bundled allowlist may be protected,
+    /// matched by their exact installed name
+    fn validate_protected(&self, protected: bool, id: &str, errors: &mut Vec<ValidationError>) {
+        if protected && !is_allowlisted(id) {
+            errors.push(ValidationError::new(
+                "PROTECTED_NOT_ALLOWED",
+                "protected",
+                "validator.protected_not_allowed",
+                &[("plugin_id", id.to_string())],
+            ));
         }
     }
 
     /// Validate semantic version
-    fn validate_version(&self, version: &str, errors: &mut Vec<ValidationError>) {
+    fn validate_version(&self, version: &str, errors: &mut Vec<ValidationError>, warnings: &mut Vec<ValidationWarning>) {
         if !is_valid_semver(version) {
-            errors.push(ValidationError {
-                code: "INVALID_VERSION_FORMAT".to_string(),
-                message: "版本号格式无效：应符合语义化版本 (x.y.z)".to_string(),
-                field: Some("version".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "INVALID_VERSION_FORMAT",
+                "version",
+                "validator.invalid_version",
+                &[],
+            ));
+        } else if !version.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            // Pre-release suffixes are valid but discouraged
+            warnings.push(ValidationWarning::new(
+                "NON_NUMERIC_VERSION",
+                "version",
+                "validator.non_numeric_version",
+                &[],
+            ));
+        }
+    }
+
+    /// Check a manifest's `api_version` against this host. Plugins needing a
+    /// newer or no-longer-supported API are errors; older supported ones and
+    /// manifests without the field are warnings.
+    pub fn validate_api_version(
+        &self,
+        api_version: Option<&str>,
+        errors: &mut Vec<ValidationError>,
+        warnings: &mut Vec<ValidationWarning>,
+    ) {
+        let required = api_version.unwrap_or(LEGACY_API_VERSION).to_string();
+        match check_api_version(api_version) {
+            ApiCompatibility::Compatible => {}
+            ApiCompatibility::Legacy if api_version.is_none() => warnings.push(ValidationWarning::new(
+                "API_VERSION_MISSING",
+                "api_version",
+                "validator.api_version_missing",
+                &[("assumed", required)],
+            )),
+            ApiCompatibility::Legacy => warnings.push(ValidationWarning::new(
+                "API_VERSION_LEGACY",
+                "api_version",
+                "validator.api_version_legacy",
+                &[("required", required), ("current", HOST_API_VERSION.to_string())],
+            )),
+            ApiCompatibility::RequiresNewerHost => errors.push(ValidationError::new(
+                "API_VERSION_TOO_NEW",
+                "api_version",
+                "validator.api_version_too_new",
+                &[("required", required), ("current", HOST_API_VERSION.to_string())],
+            )),
+            ApiCompatibility::Incompatible => errors.push(ValidationError::new(
+                "API_VERSION_UNSUPPORTED",
+                "api_version",
+                "validator.api_version_unsupported",
+                &[("required", required), ("supported", SUPPORTED_API_RANGE.to_string())],
+            )),
         }
     }
 
+    /// Why this host can't run a plugin written for `api_version`, if it can't
+    pub fn api_version_error(&self, api_version: Option<&str>) -> Option<String> {
+        let mut errors = Vec::new();
+        self.validate_api_version(api_version, &mut errors, &mut Vec::new());
+        errors.into_iter().next().map(|e| e.message)
+    }
+
     /// Validate entry file path
     fn validate_entry_path(&self, entry: &str, errors: &mut Vec<ValidationError>) {
         // Check for path traversal attempts
         if entry.contains("..") || entry.starts_with('/') {
-            errors.push(ValidationError {
-                code: "INVALID_ENTRY_PATH".to_string(),
-                message: "入口文件路径包含非法字符".to_string(),
-                field: Some("entry".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "INVALID_ENTRY_PATH",
+                "entry",
+                "validator.invalid_entry_path",
+                &[],
+            ));
         }
 
         // Check for suspicious file extensions
@@ -196,11 +402,12 @@ impl PluginValidator {
             .iter()
             .any(|ext| entry.to_lowercase().ends_with(ext))
         {
-            errors.push(ValidationError {
-                code: "SUSPICIOUS_ENTRY".to_string(),
-                message: "入口文件使用了可疑的文件扩展名".to_string(),
-                field: Some("entry".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "SUSPICIOUS_ENTRY",
+                "entry",
+                "validator.suspicious_entry",
+                &[],
+            ));
         }
     }
 
@@ -211,23 +418,36 @@ impl PluginValidator {
         errors: &mut Vec<ValidationError>,
         warnings: &mut Vec<ValidationWarning>,
     ) {
-        for permission in permissions {
+        for name in permissions {
             // Check if permission is allowed
-            if !self.allowed_permissions.contains(permission) {
-                errors.push(ValidationError {
-                    code: "UNAUTHORIZED_PERMISSION".to_string(),
-                    message: format!("未授权的权限: {}", permission),
-                    field: Some("permissions".to_string()),
-                });
+            let Ok(permission) = name.parse::<PluginPermission>() else {
+                errors.push(ValidationError::new(
+                    "UNAUTHORIZED_PERMISSION",
+                    "permissions",
+                    "validator.unauthorized_permission",
+                    &[("permission", name.to_string())],
+                ));
+                continue;
+            };
+
+            // Legacy spellings still work but should be migrated
+            if permission.as_str() != name {
+                warnings.push(ValidationWarning::new(
+                    "DEPRECATED_PERMISSION",
+                    "permissions",
+                    "validator.deprecated_permission",
+                    &[("permission", name.to_string()), ("canonical", permission.as_str().to_string())],
+                ));
             }
 
             // Warn about dangerous permissions
-            if is_dangerous_permission(permission) {
-                warnings.push(ValidationWarning {
-                    code: "DANGEROUS_PERMISSION".to_string(),
-                    message: format!("权限具有潜在风险: {}", permission),
-                    field: Some("permissions".to_string()),
-                });
+            if permission.is_dangerous() {
+                warnings.push(ValidationWarning::new(
+                    "DANGEROUS_PERMISSION",
+                    "permissions",
+                    "validator.dangerous_permission",
+                    &[("permission", name.to_string())],
+                ));
             }
         }
     }
@@ -237,14 +457,25 @@ impl PluginValidator {
         &self,
         triggers: &[crate::models::plugin::PluginTrigger],
         errors: &mut Vec<ValidationError>,
+        warnings: &mut Vec<ValidationWarning>,
     ) {
-        for trigger in triggers {
+        if triggers.is_empty() {
+            warnings.push(ValidationWarning::new(
+                "NO_TRIGGERS",
+                "triggers",
+                "validator.no_triggers",
+                &[],
+            ));
+        }
+
+        for (index, trigger) in triggers.iter().enumerate() {
             if trigger.keyword.trim().is_empty() {
-                errors.push(ValidationError {
-                    code: "INVALID_TRIGGER".to_string(),
-                    message: "触发器关键字不能为空".to_string(),
-                    field: Some("triggers".to_string()),
-                });
+                errors.push(ValidationError::new(
+                    "INVALID_TRIGGER",
+                    "triggers",
+                    "validator.empty_trigger",
+                    &[],
+                ));
             }
 
             // Check for reserved trigger keywords
@@ -253,97 +484,185 @@ impl PluginValidator {
                 .iter()
                 .any(|reserved| trigger.keyword.to_lowercase().starts_with(reserved))
             {
-                errors.push(ValidationError {
-                    code: "RESERVED_TRIGGER".to_string(),
-                    message: format!("触发器关键字与保留字冲突: {}", trigger.keyword),
-                    field: Some("triggers".to_string()),
-                });
+                errors.push(ValidationError::new(
+                    "RESERVED_TRIGGER",
+                    "triggers",
+                    "validator.reserved_trigger",
+                    &[("keyword", trigger.keyword.to_string())],
+                ));
+            }
+
+            if !trigger.keyword.trim().is_empty() && !trigger.keyword.ends_with(':') {
+                warnings.push(ValidationWarning::new(
+                    "TRIGGER_MISSING_COLON",
+                    "triggers",
+                    "validator.trigger_missing_colon",
+                    &[("keyword", trigger.keyword.to_string())],
+                ));
+            }
+
+            self.validate_trigger_args(index, &trigger.args, errors);
+        }
+    }
+
+    /// Validate `accepts_drops`. The trigger it names must be one the plugin
+    /// declares, and without fs:read the plugin is never offered for drops.
+    fn validate_drop_capability(
+        &self,
+        manifest: &PluginManifest,
+        errors: &mut Vec<ValidationError>,
+        warnings: &mut Vec<ValidationWarning>,
+    ) {
+        let trigger = manifest.drop_trigger();
+        match trigger {
+            Some(keyword) if manifest.triggers.iter().any(|t| t.keyword == keyword) => {}
+            _ => errors.push(ValidationError::new(
+                "UNKNOWN_DROP_TRIGGER",
+                "accepts_drops.trigger",
+                "validator.unknown_drop_trigger",
+                &[("keyword", trigger.unwrap_or_default().to_string())],
+            )),
+        }
+
+        if !manifest.has_permission(&PluginPermission::ReadFile) {
+            warnings.push(ValidationWarning::new(
+                "DROP_WITHOUT_READ",
+                "accepts_drops",
+                "validator.drop_without_read",
+                &[],
+            ));
+        }
+    }
+
+    /// Validate a trigger's argument schema. Errors name the offending
+    /// field, e.g. `triggers[0].args[1].type`.
+    fn validate_trigger_args(&self, trigger_index: usize, args: &[TriggerArg], errors: &mut Vec<ValidationError>) {
+        let mut seen_optional = false;
+
+        for (index, arg) in args.iter().enumerate() {
+            let field = format!("triggers[{}].args[{}]", trigger_index, index);
+
+            if arg.name.trim().is_empty() {
+                errors.push(ValidationError::new(
+                    "INVALID_TRIGGER_ARG",
+                    &format!("{}.name", field),
+                    "validator.trigger_arg_name_missing",
+                    &[("field", field.clone())],
+                ));
+            } else if args[..index].iter().any(|earlier| earlier.name == arg.name) {
+                errors.push(ValidationError::new(
+                    "DUPLICATE_TRIGGER_ARG",
+                    &format!("{}.name", field),
+                    "validator.duplicate_trigger_arg",
+                    &[("name", arg.name.clone())],
+                ));
+            }
+
+            if !TRIGGER_ARG_TYPES.contains(&arg.arg_type.as_str()) {
+                errors.push(ValidationError::new(
+                    "INVALID_TRIGGER_ARG_TYPE",
+                    &format!("{}.type", field),
+                    "validator.invalid_trigger_arg_type",
+                    &[("arg_type", arg.arg_type.clone()), ("types", TRIGGER_ARG_TYPES.join(", "))],
+                ));
+            } else {
+                for (value_index, value) in arg.suggestions.iter().enumerate() {
+                    if !suggestion_matches_type(value, &arg.arg_type) {
+                        errors.push(ValidationError::new(
+                            "INVALID_TRIGGER_ARG_ENUM",
+                            &format!("{}.enum[{}]", field, value_index),
+                            "validator.invalid_trigger_arg_enum",
+                            &[("value", value.to_string()), ("arg_type", arg.arg_type.clone())],
+                        ));
+                    }
+                }
+            }
+
+            // Arguments are positional, so a required one can't follow an
+            // optional one the user may have left out
+            if arg.required && seen_optional {
+                errors.push(ValidationError::new(
+                    "REQUIRED_ARG_AFTER_OPTIONAL",
+                    &format!("{}.required", field),
+                    "validator.required_arg_after_optional",
+                    &[("name", arg.name.clone())],
+                ));
+            }
+            seen_optional |= !arg.required;
+        }
+    }
+
+    /// Homepage and repository must be http(s) URLs, and the license an
+    /// SPDX-style expression or short name
+    fn validate_links(&self, links: &PluginLinks, warnings: &mut Vec<ValidationWarning>) {
+        for (field, url) in [("homepage", &links.homepage), ("repository", &links.repository)] {
+            if let Some(url) = url.as_deref().filter(|url| !is_web_url(url)) {
+                warnings.push(ValidationWarning::new(
+                    "INVALID_LINK",
+                    field,
+                    "validator.invalid_link",
+                    &[("field", field.to_string()), ("url", url.to_string())],
+                ));
             }
         }
+
+        if let Some(license) = links.license.as_deref().filter(|license| !is_valid_license(license)) {
+            warnings.push(ValidationWarning::new(
+                "INVALID_LICENSE",
+                "license",
+                "validator.invalid_license",
+                &[("license", license.to_string()), ("max", MAX_LICENSE_CHARS.to_string())],
+            ));
+        }
     }
 
     /// Validate for security issues
     fn validate_security(&self, manifest: &PluginManifest, warnings: &mut Vec<ValidationWarning>) {
         // Warn if plugin has too many permissions
         if manifest.permissions.len() > 5 {
-            warnings.push(ValidationWarning {
-                code: "MANY_PERMISSIONS".to_string(),
-                message: "插件请求的权限数量较多，建议最小化权限".to_string(),
-                field: Some("permissions".to_string()),
-            });
+            warnings.push(ValidationWarning::new(
+                "MANY_PERMISSIONS",
+                "permissions",
+                "validator.many_permissions",
+                &[],
+            ));
         }
 
         // Warn about network access
-        if manifest.permissions.contains(&"network".to_string()) {
-            warnings.push(ValidationWarning {
-                code: "NETWORK_ACCESS".to_string(),
-                message: "插件请求网络访问权限，请确保来源可信".to_string(),
-                field: Some("permissions".to_string()),
-            });
+        if manifest.has_permission(&PluginPermission::Network) {
+            warnings.push(ValidationWarning::new(
+                "NETWORK_ACCESS",
+                "permissions",
+                "validator.network_access",
+                &[],
+            ));
         }
 
         // Warn about shell access
-        if manifest.permissions.contains(&"shell".to_string()) {
-            warnings.push(ValidationWarning {
-                code: "SHELL_ACCESS".to_string(),
-                message: "插件请求Shell执行权限，具有安全风险".to_string(),
-                field: Some("permissions".to_string()),
-            });
+        if manifest.has_permission(&PluginPermission::Shell) {
+            warnings.push(ValidationWarning::new(
+                "SHELL_ACCESS",
+                "permissions",
+                "validator.shell_access",
+                &[],
+            ));
         }
     }
 
     /// Get all allowed permissions
     pub fn get_allowed_permissions(&self) -> Vec<PermissionDefinition> {
-        vec![
-            PermissionDefinition {
-                id: "clipboard:read".to_string(),
-                name: "剪贴板读取".to_string(),
-                description: "读取系统剪贴板内容".to_string(),
-                category: "剪贴板".to_string(),
-            },
-            PermissionDefinition {
-                id: "clipboard:write".to_string(),
-                name: "剪贴板写入".to_string(),
-                description: "写入内容到系统剪贴板".to_string(),
-                category: "剪贴板".to_string(),
-            },
-            PermissionDefinition {
-                id: "fs:read".to_string(),
-                name: "文件读取".to_string(),
-                description: "读取用户文件系统".to_string(),
-                category: "文件系统".to_string(),
-            },
-            PermissionDefinition {
-                id: "fs:write".to_string(),
-                name: "文件写入".to_string(),
-                description: "写入文件到用户文件系统".to_string(),
-                category: "文件系统".to_string(),
-            },
-            PermissionDefinition {
-                id: "network".to_string(),
-                name: "网络访问".to_string(),
-                description: "访问网络资源".to_string(),
-                category: "网络".to_string(),
-            },
-            PermissionDefinition {
-                id: "shell".to_string(),
-                name: "Shell执行".to_string(),
-                description: "执行系统命令".to_string(),
-                category: "系统".to_string(),
-            },
-            PermissionDefinition {
-                id: "notification".to_string(),
-                name: "系统通知".to_string(),
-                description: "显示系统通知".to_string(),
-                category: "系统".to_string(),
-            },
-            PermissionDefinition {
-                id: "plugin:manage".to_string(),
-                name: "插件管理".to_string(),
-                description: "管理其他插件".to_string(),
-                category: "插件".to_string(),
-            },
-        ]
+        PluginPermission::ALL
+            .iter()
+            .map(|permission| {
+                let id = permission.as_str();
+                PermissionDefinition {
+                    id: id.to_string(),
+                    name: t!(&format!("permission.{}.name", id)),
+                    description: t!(&format!("permission.{}.description", id)),
+                    category: t!(&format!("permission.{}.category", id)),
+                }
+            })
+            .collect()
     }
 
     // ========================================================================
@@ -356,7 +675,7 @@ impl PluginValidator {
         let mut warnings = Vec::new();
 
         // Validate permission combinations for security risks
-        self.validate_permission_combinations(&manifest.permissions, &mut warnings);
+        self.validate_permission_combinations(manifest, &mut warnings);
 
         // Check for potentially dangerous keywords
         self.validate_dangerous_keywords(manifest, &mut errors);
@@ -368,29 +687,31 @@ impl PluginValidator {
     }
 
     /// Validate permission combinations for security risks
-    fn validate_permission_combinations(&self, permissions: &[String], warnings: &mut Vec<ValidationWarning>) {
+    fn validate_permission_combinations(&self, manifest: &PluginManifest, warnings: &mut Vec<ValidationWarning>) {
         // Check for dangerous permission combinations
-        let has_network = permissions.contains(&"network".to_string());
-        let has_shell = permissions.contains(&"shell".to_string());
-        let has_fs_write = permissions.contains(&"fs:write".to_string());
-        let has_plugin_manage = permissions.contains(&"plugin:manage".to_string());
+        let has_network = manifest.has_permission(&PluginPermission::Network);
+        let has_shell = manifest.has_permission(&PluginPermission::Shell);
+        let has_fs_write = manifest.has_permission(&PluginPermission::WriteFile);
+        let has_plugin_manage = manifest.has_permission(&PluginPermission::ManagePlugins);
 
         // Network + Shell is especially dangerous
         if has_network && has_shell {
-            warnings.push(ValidationWarning {
-                code: "DANGEROUS_PERMISSION_COMBO".to_string(),
-                message: "插件同时拥有网络访问和Shell执行权限,具有极高风险".to_string(),
-                field: Some("permissions".to_string()),
-            });
+            warnings.push(ValidationWarning::new(
+                "DANGEROUS_PERMISSION_COMBO",
+                "permissions",
+                "validator.network_shell_combo",
+                &[],
+            ));
         }
 
         // File write + Plugin manage can modify other plugins
         if has_fs_write && has_plugin_manage {
-            warnings.push(ValidationWarning {
-                code: "DANGEROUS_PERMISSION_COMBO".to_string(),
-                message: "插件可以修改系统文件和其他插件,具有极高风险".to_string(),
-                field: Some("permissions".to_string()),
-            });
+            warnings.push(ValidationWarning::new(
+                "DANGEROUS_PERMISSION_COMBO",
+                "permissions",
+                "validator.write_manage_combo",
+                &[],
+            ));
         }
 
         // Too many dangerous permissions
@@ -400,11 +721,12 @@ impl PluginValidator {
             .count();
 
         if dangerous_count >= 3 {
-            warnings.push(ValidationWarning {
-                code: "EXCESSIVE_DANGEROUS_PERMISSIONS".to_string(),
-                message: format!("插件拥有 {} 个高风险权限,建议仔细审查", dangerous_count),
-                field: Some("permissions".to_string()),
-            });
+            warnings.push(ValidationWarning::new(
+                "EXCESSIVE_DANGEROUS_PERMISSIONS",
+                "permissions",
+                "validator.excessive_dangerous_permissions",
+                &[("count", dangerous_count.to_string())],
+            ));
         }
     }
 
@@ -426,11 +748,12 @@ impl PluginValidator {
         };
 
         if check_field(&manifest.name) || check_field(&manifest.description) {
-            errors.push(ValidationError {
-                code: "DANGEROUS_KEYWORDS".to_string(),
-                message: "插件包含潜在危险的敏感关键词".to_string(),
-                field: Some("general".to_string()),
-            });
+            errors.push(ValidationError::new(
+                "DANGEROUS_KEYWORDS",
+                "general",
+                "validator.dangerous_keywords",
+                &[],
+            ));
         }
     }
 
@@ -439,11 +762,12 @@ impl PluginValidator {
         // Check if author field is suspicious
         if let Some(author) = &manifest.author {
             if author.is_empty() || author.len() < 2 {
-                warnings.push(ValidationWarning {
-                    code: "INVALID_AUTHOR".to_string(),
-                    message: "插件作者信息不完整或无效".to_string(),
-                    field: Some("author".to_string()),
-                });
+                warnings.push(ValidationWarning::new(
+                    "INVALID_AUTHOR",
+                    "author",
+                    "validator.invalid_author",
+                    &[],
+                ));
             }
         }
 
@@ -451,20 +775,33 @@ impl PluginValidator {
         if manifest.version.contains("malware") ||
            manifest.version.contains("hack") ||
            manifest.version.contains("crack") {
-            warnings.push(ValidationWarning {
-                code: "SUSPICIOUS_VERSION".to_string(),
-                message: "插件版本号包含可疑关键词".to_string(),
-                field: Some("version".to_string()),
-            });
+            warnings.push(ValidationWarning::new(
+                "SUSPICIOUS_VERSION",
+                "version",
+                "validator.suspicious_version",
+                &[],
+            ));
+        }
+
+        // Plugins aren't signed, so a repository is the only way to review
+        // the code being run
+        if !manifest.links.repository.as_deref().is_some_and(is_web_url) {
+            warnings.push(ValidationWarning::new(
+                "NO_REPOSITORY",
+                "repository",
+                "validator.no_repository",
+                &[],
+            ));
         }
 
         // Warn if plugin has no description
         if manifest.description.trim().is_empty() {
-            warnings.push(ValidationWarning {
-                code: "NO_DESCRIPTION".to_string(),
-                message: "插件缺少描述信息,无法确认其用途".to_string(),
-                field: Some("description".to_string()),
-            });
+            warnings.push(ValidationWarning::new(
+                "NO_DESCRIPTION",
+                "description",
+                "validator.no_description",
+                &[],
+            ));
         }
     }
 
@@ -472,36 +809,92 @@ impl PluginValidator {
     pub fn calculate_security_score(&self, manifest: &PluginManifest) -> u8 {
         let mut score = 100u8;
 
-        // Deduct points for permissions
+        // Deduct points for permissions; saturating so permission-heavy manifests bottom out at 0
         let permission_count = manifest.permissions.len();
         if permission_count > 5 {
-            score -= (permission_count - 5) as u8 * 5;
+            let extra = u8::try_from(permission_count - 5).unwrap_or(u8::MAX);
+            score = score.saturating_sub(extra.saturating_mul(5));
         }
 
         // Deduct for dangerous permissions
-        if manifest.permissions.contains(&"shell".to_string()) {
-            score -= 15;
-        }
-        if manifest.permissions.contains(&"network".to_string()) {
-            score -= 10;
-        }
-        if manifest.permissions.contains(&"fs:write".to_string()) {
-            score -= 10;
+        for risk in self.permission_risk_breakdown(manifest) {
+            score = score.saturating_sub(risk.score_impact);
         }
 
         // Deduct for missing metadata
         if manifest.author.as_ref().map_or(true, |a| a.trim().is_empty()) {
-            score -= 10;
+            score = score.saturating_sub(10);
         }
         if manifest.description.trim().is_empty() {
-            score -= 10;
+            score = score.saturating_sub(10);
+        }
+
+        score
+    }
+
+    /// Risk and score impact of each permission the manifest requests
+    pub fn permission_risk_breakdown(&self, manifest: &PluginManifest) -> Vec<PermissionRisk> {
+        PluginPermission::ALL
+            .iter()
+            .filter(|permission| manifest.has_permission(permission))
+            .map(|permission| {
+                let score_impact = permission_penalty(permission);
+                PermissionRisk {
+                    permission: permission.as_str().to_string(),
+                    risk: match score_impact {
+                        15.. => RiskLevel::High,
+                        10.. => RiskLevel::Medium,
+                        _ => RiskLevel::Low,
+                    },
+                    score_impact,
+                }
+            })
+            .collect()
+    }
+
+    /// Enhanced security validation, permission-combination checks and score
+    pub fn security_report(&self, manifest: &PluginManifest) -> SecurityReport {
+        let (errors, warnings) = self.validate_security_enhanced(manifest);
+        SecurityReport {
+            score: self.calculate_security_score(manifest),
+            repository: manifest.links.repository.clone().filter(|url| is_web_url(url)),
+            errors,
+            warnings,
+            permission_risk_breakdown: self.permission_risk_breakdown(manifest),
         }
+    }
+}
+
+/// Whether a suggested value fits the argument type
+fn suggestion_matches_type(value: &serde_json::Value, arg_type: &str) -> bool {
+    match arg_type {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        _ => false,
+    }
+}
 
-        // Ensure score doesn't go below 0
-        score.max(0)
+/// Score deducted for requesting a permission
+fn permission_penalty(permission: &PluginPermission) -> u8 {
+    match permission {
+        PluginPermission::Shell => 15,
+        PluginPermission::Network | PluginPermission::WriteFile => 10,
+        _ => 0,
     }
 }
 
+/// SPDX identifiers and expressions ("MIT", "(MIT OR Apache-2.0)",
+/// "LicenseRef-Custom") or a short name such as "SEE LICENSE IN LICENSE.md"
+fn is_valid_license(license: &str) -> bool {
+    let license = license.trim();
+    !license.is_empty()
+        && license.chars().count() <= MAX_LICENSE_CHARS
+        && license
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " -.+():_/".contains(c))
+}
+
 /// Helper function to validate plugin ID format
 fn is_valid_plugin_id(id: &str) -> bool {
     if id.len() < 3 || id.len() > 50 {
@@ -538,7 +931,309 @@ fn is_valid_semver(version: &str) -> bool {
     true
 }
 
-/// Helper function to check for dangerous permissions
-fn is_dangerous_permission(permission: &str) -> bool {
-    permission == "shell" || permission == "fs:write" || permission == "network"
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::plugin::{DropCapability, PluginTrigger};
+
+    fn manifest() -> PluginManifest {
+        PluginManifest {
+            name: "Hello World".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Greets the user".to_string(),
+            author: Some("tester".to_string()),
+            permissions: vec![],
+            entry: "index.js".to_string(),
+            triggers: vec![PluginTrigger {
+                keyword: "hello:".to_string(),
+                description: String::new(),
+                hotkey: None,
+                args: Vec::new(),
+                cache_ttl_ms: None,
+            }],
+            api_version: Some(crate::services::plugin_api::HOST_API_VERSION.to_string()),
+            protected: false,
+            accepts_drops: None,
+            links: PluginLinks::default(),
+        }
+    }
+
+    fn error_codes(manifest: &PluginManifest, plugin_id: Option<&str>) -> Vec<String> {
+        let (errors, _) = PluginValidator::new().validate_manifest(manifest, plugin_id);
+        errors.into_iter().map(|e| e.code).collect()
+    }
+
+    fn warning_codes(manifest: &PluginManifest) -> Vec<String> {
+        let (_, warnings) = PluginValidator::new().validate_manifest(manifest, Some("hello-world"));
+        warnings.into_iter().map(|w| w.code).collect()
+    }
+
+    #[test]
+    fn test_valid_installed_manifest_has_no_issues() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("index.js"), "export default {}").unwrap();
+
+        let result: PluginValidationResult = PluginValidator::new()
+            .validate_installed(&manifest(), "hello-world", dir.path())
+            .into();
+
+        assert!(result.is_valid, "{:?}", result.errors);
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+    }
+
+    // Errors the command-layer and installer validators used to report
+    #[test]
+    fn test_former_command_and_installer_errors_still_reported() {
+        assert!(error_codes(&manifest(), Some("")).contains(&"REQUIRED_FIELD_MISSING".to_string()));
+        assert!(error_codes(&manifest(), Some("Bad_ID")).contains(&"INVALID_ID_FORMAT".to_string()));
+        assert!(error_codes(&manifest(), Some("bad id!")).contains(&"INVALID_ID_FORMAT".to_string()));
+
+        let mut m = manifest();
+        m.name = String::new();
+        assert!(error_codes(&m, None).contains(&"REQUIRED_FIELD_MISSING".to_string()));
+
+        let mut m = manifest();
+        m.version = String::new();
+        assert!(error_codes(&m, None).contains(&"INVALID_VERSION_FORMAT".to_string()));
+
+        let mut m = manifest();
+        m.description = String::new();
+        assert!(error_codes(&m, None).contains(&"REQUIRED_FIELD_MISSING".to_string()));
+
+        let mut m = manifest();
+        m.entry = String::new();
+        assert!(error_codes(&m, None).contains(&"REQUIRED_FIELD_MISSING".to_string()));
+
+        let mut m = manifest();
+        m.permissions = vec!["made:up".to_string()];
+        assert!(error_codes(&m, None).contains(&"UNAUTHORIZED_PERMISSION".to_string()));
+
+        let dir = tempfile::tempdir().unwrap();
+        let (errors, _) = PluginValidator::new().validate_installed(&manifest(), "hello-world", dir.path());
+        assert!(errors.iter().any(|e| e.code == "ENTRY_NOT_FOUND"));
+    }
+
+    #[test]
+    fn test_protected_claims_need_the_allowlist() {
+        let protected = PluginManifest { protected: true, ..manifest() };
+        assert!(error_codes(&protected, Some("hello-world")).contains(&"PROTECTED_NOT_ALLOWED".to_string()));
+        // The allowlist names scoped npm packages; a local folder named like one isn't
+        assert!(error_codes(&protected, Some("devtools")).contains(&"PROTECTED_NOT_ALLOWED".to_string()));
+        assert!(!error_codes(&manifest(), Some("hello-world")).contains(&"PROTECTED_NOT_ALLOWED".to_string()));
+    }
+
+    // Warnings the command-layer validator used to report
+    #[test]
+    fn test_former_command_warnings_still_reported() {
+        let mut m = manifest();
+        m.version = "1.0.0-beta".to_string();
+        assert!(warning_codes(&m).contains(&"NON_NUMERIC_VERSION".to_string()));
+
+        let mut m = manifest();
+        m.triggers.clear();
+        assert!(warning_codes(&m).contains(&"NO_TRIGGERS".to_string()));
+
+        let mut m = manifest();
+        m.triggers[0].keyword = "hello".to_string();
+        assert!(warning_codes(&m).contains(&"TRIGGER_MISSING_COLON".to_string()));
+
+        let mut m = manifest();
+        m.permissions = vec!["shell".to_string()];
+        assert!(warning_codes(&m).contains(&"SHELL_ACCESS".to_string()));
+    }
+
+    #[test]
+    fn test_security_score_saturates_for_permission_heavy_manifests() {
+        let mut m = manifest();
+        m.author = None;
+        m.description = String::new();
+        m.permissions = vec!["shell".to_string(); 80];
+
+        assert_eq!(PluginValidator::new().calculate_security_score(&m), 0);
+    }
+
+    #[test]
+    fn test_security_report_breaks_down_permission_risk() {
+        let mut m = manifest();
+        m.permissions = vec!["shell".to_string(), "network".to_string(), "notification".to_string()];
+
+        let report = PluginValidator::new().security_report(&m);
+
+        assert_eq!(report.score, 75);
+        assert!(report.warnings.iter().any(|w| w.code == "DANGEROUS_PERMISSION_COMBO"));
+        let shell = report.permission_risk_breakdown.iter().find(|r| r.permission == "shell").unwrap();
+        assert_eq!((shell.risk, shell.score_impact), (RiskLevel::High, 15));
+        let notification = report.permission_risk_breakdown.iter().find(|r| r.permission == "notification").unwrap();
+        assert_eq!(notification.risk, RiskLevel::Low);
+    }
+
+    #[test]
+    fn test_link_fields_only_warn() {
+        let validator = PluginValidator::new();
+        let with_links = |homepage: &str, repository: &str, license: &str| PluginManifest {
+            links: PluginLinks {
+                homepage: Some(homepage.to_string()),
+                repository: Some(repository.to_string()),
+                license: Some(license.to_string()),
+            },
+            ..manifest()
+        };
+
+        let valid = with_links("https://hello.example/", "http://git.example/acme/hello", "(MIT OR Apache-2.0)");
+        let (errors, warnings) = validator.validate_manifest(&valid, Some("hello-world"));
+        assert!(errors.is_empty() && warnings.is_empty(), "{:?} {:?}", errors, warnings);
+
+        let invalid = with_links("javascript:alert(1)", "git@github.com:acme/hello.git", &"MIT ".repeat(20));
+        let (errors, warnings) = validator.validate_manifest(&invalid, Some("hello-world"));
+        assert!(errors.is_empty(), "{:?}", errors);
+        let fields: Vec<_> = warnings.iter().map(|w| (w.code.as_str(), w.field.as_deref().unwrap())).collect();
+        assert_eq!(
+            fields,
+            vec![("INVALID_LINK", "homepage"), ("INVALID_LINK", "repository"), ("INVALID_LICENSE", "license")]
+        );
+
+        for url in ["https://github.com/acme/hello", "HTTP://Example.com"] {
+            assert!(is_web_url(url), "{}", url);
+        }
+        for url in ["ftp://example.com/x", "https://", "/relative/path", "file:///etc/passwd", "example.com"] {
+            assert!(!is_web_url(url), "{}", url);
+        }
+        assert!(is_valid_license("LicenseRef-Acme-1.0"));
+        assert!(is_valid_license("SEE LICENSE IN LICENSE.md"));
+        assert!(!is_valid_license("MIT; rm -rf /"));
+    }
+
+    #[test]
+    fn test_security_report_flags_missing_repository() {
+        let validator = PluginValidator::new();
+        let report = validator.security_report(&manifest());
+        assert_eq!(report.repository, None);
+        assert!(report.warnings.iter().any(|w| w.code == "NO_REPOSITORY"));
+
+        let mut m = manifest();
+        m.links.repository = Some("https://github.com/acme/hello".to_string());
+        let report = validator.security_report(&m);
+        assert_eq!(report.repository.as_deref(), Some("https://github.com/acme/hello"));
+        assert!(!report.warnings.iter().any(|w| w.code == "NO_REPOSITORY"));
+    }
+
+    // Checks PluginValidator already had
+    #[test]
+    fn test_existing_validator_rules_unchanged() {
+        assert!(error_codes(&manifest(), Some("core-tools")).contains(&"RESERVED_ID".to_string()));
+
+        let mut m = manifest();
+        m.entry = "../escape.js".to_string();
+        assert!(error_codes(&m, None).contains(&"INVALID_ENTRY_PATH".to_string()));
+
+        let mut m = manifest();
+        m.author = None;
+        assert!(error_codes(&m, None).contains(&"REQUIRED_FIELD_MISSING".to_string()));
+
+        let mut m = manifest();
+        m.triggers[0].keyword = "help:me".to_string();
+        assert!(error_codes(&m, None).contains(&"RESERVED_TRIGGER".to_string()));
+    }
+
+    #[test]
+    fn test_trigger_arg_schemas_are_validated_per_field() {
+        let mut m = manifest();
+        m.triggers[0].args = serde_json::from_value(serde_json::json!([
+            { "name": "to", "required": true, "enum": ["en", "de"] },
+            { "name": "text", "type": "string" }
+        ]))
+        .unwrap();
+        assert!(error_codes(&m, Some("hello-world")).is_empty());
+
+        m.triggers[0].args = serde_json::from_value(serde_json::json!([
+            { "type": "string" },
+            { "name": "count", "type": "integer" },
+            { "name": "count", "type": "number", "enum": [1, "two"] },
+            { "name": "text", "required": true }
+        ]))
+        .unwrap();
+        let (errors, _) = PluginValidator::new().validate_manifest(&m, Some("hello-world"));
+        let fields: Vec<(&str, &str)> = errors
+            .iter()
+            .map(|e| (e.code.as_str(), e.field.as_deref().unwrap_or("")))
+            .collect();
+
+        assert_eq!(
+            fields,
+            vec![
+                ("INVALID_TRIGGER_ARG", "triggers[0].args[0].name"),
+                ("INVALID_TRIGGER_ARG_TYPE", "triggers[0].args[1].type"),
+                ("DUPLICATE_TRIGGER_ARG", "triggers[0].args[2].name"),
+                ("INVALID_TRIGGER_ARG_ENUM", "triggers[0].args[2].enum[1]"),
+                ("REQUIRED_ARG_AFTER_OPTIONAL", "triggers[0].args[3].required"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_api_version_outcomes() {
+        let validate = |api_version: Option<&str>| {
+            let manifest = PluginManifest { api_version: api_version.map(String::from), ..manifest() };
+            let (errors, warnings) = PluginValidator::new().validate_manifest(&manifest, Some("hello-world"));
+            let api_codes = |codes: Vec<String>| codes.into_iter().filter(|c| c.starts_with("API_")).collect::<Vec<_>>();
+            (
+                api_codes(errors.into_iter().map(|e| e.code).collect()),
+                api_codes(warnings.into_iter().map(|w| w.code).collect()),
+            )
+        };
+
+        assert_eq!(validate(Some(HOST_API_VERSION)), (vec![], vec![]));
+        assert_eq!(validate(Some("1.0.0")), (vec![], vec!["API_VERSION_LEGACY".to_string()]));
+        assert_eq!(validate(None), (vec![], vec!["API_VERSION_MISSING".to_string()]));
+        assert_eq!(validate(Some("1.9.0")), (vec!["API_VERSION_TOO_NEW".to_string()], vec![]));
+        assert_eq!(validate(Some("0.9.0")), (vec!["API_VERSION_UNSUPPORTED".to_string()], vec![]));
+        assert!(PluginValidator::new().api_version_error(Some("2.0.0")).is_some());
+    }
+
+    #[test]
+    fn test_legacy_permissions_validate_with_deprecation_warning() {
+        let manifest = PluginManifest {
+            name: "Clipboard Tools".to_string(),
+            version: "1.0.0".to_string(),
+            description: "Clipboard helpers".to_string(),
+            author: Some("tester".to_string()),
+            permissions: vec!["clipboard:read".to_string(), "notification".to_string()],
+            ..manifest()
+        };
+
+        let (errors, warnings) = PluginValidator::new().validate_manifest(&manifest, Some("clip-tools"));
+
+        assert!(errors.is_empty(), "{:?}", errors);
+        let deprecated: Vec<_> = warnings.iter().filter(|w| w.code == "DEPRECATED_PERMISSION").collect();
+        assert_eq!(deprecated.len(), 1);
+        assert!(deprecated[0].message.contains("read_clipboard"));
+    }
+
+    #[test]
+    fn test_drop_capability_is_validated() {
+        let validator = PluginValidator::new();
+        let codes = |manifest: &PluginManifest| {
+            let (errors, warnings) = validator.validate_manifest(manifest, Some("shrink"));
+            let errors: Vec<_> = errors.into_iter().map(|e| e.code).collect();
+            let warnings: Vec<_> = warnings.into_iter().map(|w| w.code).collect();
+            (errors, warnings)
+        };
+
+        let unknown_trigger = PluginManifest {
+            accepts_drops: Some(DropCapability { trigger: Some("nope:".to_string()), ..Default::default() }),
+            ..manifest()
+        };
+        let (errors, warnings) = codes(&unknown_trigger);
+        assert!(errors.contains(&"UNKNOWN_DROP_TRIGGER".to_string()));
+        assert!(warnings.contains(&"DROP_WITHOUT_READ".to_string()));
+
+        let readable = PluginManifest {
+            permissions: vec!["fs:read".to_string()],
+            accepts_drops: Some(DropCapability::default()),
+            ..manifest()
+        };
+        let (errors, warnings) = codes(&readable);
+        assert!(!errors.contains(&"UNKNOWN_DROP_TRIGGER".to_string()));
+        assert!(!warnings.contains(&"DROP_WITHOUT_READ".to_string()));
+    }
 }
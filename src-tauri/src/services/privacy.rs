@@ -0,0 +1,184 @@
+//! Privacy Mode
+//! One switch that pauses clipboard capture and file watching and hides
+//! sensitive rows from search, optionally expiring after a timeout
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Privacy mode state file, only written when the mode is persistent
+const PRIVACY_STATE_FILE: &str = "privacy_mode.json";
+
+/// Privacy mode status, also the `privacy-mode:changed` event payload
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrivacyStatus {
+    pub enabled: bool,
+    /// Unix timestamp (ms) when the mode turns itself off
+    pub expires_at: Option<i64>,
+    /// Whether the mode survives restarts
+    pub persistent: bool,
+}
+
+#[derive(Default)]
+struct PrivacyInner {
+    status: PrivacyStatus,
+    /// Bumped on every change so stale expiry timers can tell they're outdated
+    generation: u64,
+}
+
+/// Shared privacy mode flag, cheap to clone into watcher threads
+#[derive(Clone, Default)]
+pub struct PrivacyMode {
+    inner: Arc<Mutex<PrivacyInner>>,
+}
+
+impl PrivacyMode {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether privacy mode is on. A mode past its expiry counts as off,
+    /// though only `expire` turns it off.
+    pub fn is_active(&self) -> bool {
+        self.status().enabled
+    }
+
+    /// Turn privacy mode on or off. Returns the new status and its generation.
+    pub fn set(&self, enabled: bool, expires_in: Option<Duration>, persistent: bool) -> (PrivacyStatus, u64) {
+        let status = if enabled {
+            PrivacyStatus {
+                enabled: true,
+                expires_at: expires_in.map(|d| now_ms() + d.as_millis() as i64),
+                persistent,
+            }
+        } else {
+            PrivacyStatus::default()
+        };
+
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.status = status.clone();
+        inner.generation += 1;
+        (status, inner.generation)
+    }
+
+    /// Turn the mode off if it's still in the state identified by `generation`.
+    /// Used by expiry timers; returns whether anything changed.
+    pub fn expire(&self, generation: u64) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.generation != generation || !inner.status.enabled {
+            return false;
+        }
+        inner.status = PrivacyStatus::default();
+        inner.generation += 1;
+        true
+    }
+
+    /// The current status, off once past its expiry even if the expiry
+    /// timer hasn't run yet
+    pub fn status(&self) -> PrivacyStatus {
+        let Ok(inner) = self.inner.lock() else {
+            return PrivacyStatus::default();
+        };
+        match inner.status.expires_at {
+            Some(expires_at) if now_ms() >= expires_at => PrivacyStatus::default(),
+            _ => inner.status.clone(),
+        }
+    }
+
+    /// Restore a persisted status at startup. Non-persistent or expired
+    /// states are ignored, so privacy mode normally starts off.
+    pub fn restore(&self, path: &Path) {
+        let status = fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<PrivacyStatus>(&content).ok());
+
+        if let Some(status) = status {
+            let live = status.expires_at.is_none_or(|at| now_ms() < at);
+            if status.enabled && status.persistent && live {
+                let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+                inner.status = status;
+                inner.generation += 1;
+            }
+        }
+    }
+
+    /// Write the status if it's persistent, otherwise remove any saved state
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let status = self.status();
+
+        if status.enabled && status.persistent {
+            let content = serde_json::to_string_pretty(&status)
+                .map_err(|e| format!("Failed to serialize privacy mode: {}", e))?;
+            fs::write(path, content).map_err(|e| format!("Failed to write privacy mode: {}", e))
+        } else if path.exists() {
+            fs::remove_file(path).map_err(|e| format!("Failed to remove privacy mode: {}", e))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Location of the persisted privacy state inside the app config dir
+pub fn privacy_state_path(config_dir: &Path) -> PathBuf {
+    config_dir.join(PRIVACY_STATE_FILE)
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_turns_mode_off() {
+        let privacy = PrivacyMode::new();
+        let (_, generation) = privacy.set(true, Some(Duration::from_millis(30)), false);
+        assert!(privacy.is_active());
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!privacy.is_active());
+        assert_eq!(privacy.status(), PrivacyStatus::default());
+        // Reading the status changed nothing, so the expiry timer still
+        // gets to turn the mode off and announce it
+        assert!(privacy.expire(generation));
+        assert!(!privacy.expire(generation));
+    }
+
+    #[test]
+    fn test_stale_expiry_timer_is_ignored() {
+        let privacy = PrivacyMode::new();
+        let (_, first) = privacy.set(true, Some(Duration::from_secs(60)), false);
+        // Re-enabled with no timeout before the first timer fired
+        privacy.set(true, None, false);
+
+        assert!(!privacy.expire(first));
+        assert!(privacy.is_active());
+    }
+
+    #[test]
+    fn test_only_persistent_state_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = privacy_state_path(dir.path());
+
+        let privacy = PrivacyMode::new();
+        privacy.set(true, None, false);
+        privacy.save(&path).unwrap();
+        let restarted = PrivacyMode::new();
+        restarted.restore(&path);
+        assert!(!restarted.is_active());
+
+        privacy.set(true, None, true);
+        privacy.save(&path).unwrap();
+        let restarted = PrivacyMode::new();
+        restarted.restore(&path);
+        assert!(restarted.is_active());
+
+        privacy.set(false, None, false);
+        privacy.save(&path).unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,493 @@
+//! Process Manager
+//! Running processes for "kill chrome" searches, and ending them: quit asks
+//! politely (SIGTERM, or WM_CLOSE to the process's windows on Windows) and
+//! force kill doesn't (SIGKILL / TerminateProcess). System processes are
+//! listed but never ended. Snapshots are cached briefly so typing doesn't
+//! rescan the process table on every keystroke.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, ProcessRefreshKind, ProcessesToUpdate, System, UpdateKind};
+
+/// How long a process snapshot is reused
+pub const PROCESS_SNAPSHOT_TTL: Duration = Duration::from_secs(2);
+
+/// Processes below this pid are started by the system early in boot
+pub const PROTECTED_PID_THRESHOLD: u32 = 100;
+
+/// Processes whose death takes the session or the system down with them
+const PROTECTED_PROCESS_NAMES: &[&str] = &[
+    // macOS
+    "kernel_task",
+    "launchd",
+    "WindowServer",
+    "loginwindow",
+    "logd",
+    "opendirectoryd",
+    // Linux
+    "systemd",
+    "init",
+    "kthreadd",
+    "Xorg",
+    "gnome-shell",
+    "plasmashell",
+    // Windows
+    "System",
+    "Registry",
+    "smss.exe",
+    "csrss.exe",
+    "wininit.exe",
+    "winlogon.exe",
+    "services.exe",
+    "lsass.exe",
+    "svchost.exe",
+    "dwm.exe",
+    "explorer.exe",
+];
+
+/// A process as the platform reports it
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawProcess {
+    pub pid: u32,
+    pub name: String,
+    pub exe: Option<PathBuf>,
+    pub memory_bytes: u64,
+    pub cpu_percent: f32,
+}
+
+/// A process as search shows it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub memory_bytes: u64,
+    /// Since the previous snapshot; 0 in the first one
+    pub cpu_percent: f32,
+    /// App the executable belongs to, e.g. "Google Chrome" for its helpers
+    pub app_bundle: Option<String>,
+    /// False for system processes, which can't be ended from the launcher
+    pub killable: bool,
+}
+
+impl ProcessInfo {
+    pub fn from_raw(raw: RawProcess, own_pid: u32) -> Self {
+        Self {
+            killable: !is_protected(raw.pid, &raw.name, own_pid),
+            app_bundle: raw.exe.as_deref().and_then(owning_bundle),
+            pid: raw.pid,
+            name: raw.name,
+            memory_bytes: raw.memory_bytes,
+            cpu_percent: raw.cpu_percent,
+        }
+    }
+}
+
+/// Whether ending the process could take the system or the launcher down
+pub fn is_protected(pid: u32, name: &str, own_pid: u32) -> bool {
+    pid < PROTECTED_PID_THRESHOLD
+        || pid == own_pid
+        || PROTECTED_PROCESS_NAMES
+            .iter()
+            .any(|protected| protected.eq_ignore_ascii_case(name))
+}
+
+/// The outermost `.app` bundle containing `exe`, so helpers nested inside
+/// an app resolve to the app itself
+pub fn owning_bundle(exe: &Path) -> Option<String> {
+    exe.ancestors()
+        .filter_map(|dir| dir.file_name()?.to_str())
+        .filter_map(|name| {
+            let stem = name.len().checked_sub(4).filter(|&i| name.is_char_boundary(i))?;
+            name[stem..]
+                .eq_ignore_ascii_case(".app")
+                .then(|| name[..stem].to_string())
+        })
+        .last()
+}
+
+/// Where process lists come from, so matching can be tested with a fake
+pub trait ProcessSource: Send + Sync {
+    fn list(&self) -> Vec<RawProcess>;
+}
+
+/// The running system's processes. The `System` is kept between refreshes
+/// because CPU usage is measured from one refresh to the next.
+#[derive(Default)]
+pub struct SystemProcesses {
+    system: Mutex<System>,
+}
+
+impl SystemProcesses {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl ProcessSource for SystemProcesses {
+    fn list(&self) -> Vec<RawProcess> {
+        let Ok(mut system) = self.system.lock() else {
+            return Vec::new();
+        };
+        system.refresh_processes_specifics(
+            ProcessesToUpdate::All,
+            true,
+            ProcessRefreshKind::new()
+                .with_memory()
+                .with_cpu()
+                .with_exe(UpdateKind::OnlyIfNotSet),
+        );
+
+        system
+            .processes()
+            .values()
+            // Threads show up as processes on Linux
+            .filter(|process| process.thread_kind().is_none())
+            .map(|process| RawProcess {
+                pid: process.pid().as_u32(),
+                name: process.name().to_string_lossy().into_owned(),
+                exe: process.exe().map(Path::to_path_buf),
+                memory_bytes: process.memory(),
+                cpu_percent: process.cpu_usage(),
+            })
+            .collect()
+    }
+}
+
+/// Process snapshots reused for `ttl`
+pub struct ProcessCache {
+    source: Box<dyn ProcessSource>,
+    ttl: Duration,
+    own_pid: u32,
+    snapshot: Mutex<Option<(Instant, Arc<Vec<ProcessInfo>>)>>,
+}
+
+impl ProcessCache {
+    pub fn new(source: Box<dyn ProcessSource>) -> Self {
+        Self::with_ttl(source, PROCESS_SNAPSHOT_TTL)
+    }
+
+    pub fn with_ttl(source: Box<dyn ProcessSource>, ttl: Duration) -> Self {
+        Self {
+            source,
+            ttl,
+            own_pid: std::process::id(),
+            snapshot: Mutex::new(None),
+        }
+    }
+
+    /// The current snapshot, taken anew once the cached one is `ttl` old
+    pub fn snapshot(&self) -> Arc<Vec<ProcessInfo>> {
+        let Ok(mut cached) = self.snapshot.lock() else {
+            return Arc::new(Vec::new());
+        };
+        if let Some((taken, processes)) = cached.as_ref() {
+            if taken.elapsed() < self.ttl {
+                return Arc::clone(processes);
+            }
+        }
+
+        let processes: Arc<Vec<ProcessInfo>> = Arc::new(
+            self.source
+                .list()
+                .into_iter()
+                .map(|raw| ProcessInfo::from_raw(raw, self.own_pid))
+                .collect(),
+        );
+        *cached = Some((Instant::now(), Arc::clone(&processes)));
+        processes
+    }
+
+    /// Drop the snapshot, e.g. after ending a process
+    pub fn invalidate(&self) {
+        if let Ok(mut cached) = self.snapshot.lock() {
+            *cached = None;
+        }
+    }
+
+    pub fn own_pid(&self) -> u32 {
+        self.own_pid
+    }
+}
+
+/// Processes whose name, app or pid matches `query`, those starting with it
+/// first, then by memory use. An empty query lists everything by memory.
+pub fn search_processes(processes: &[ProcessInfo], query: &str) -> Vec<ProcessInfo> {
+    let query = query.trim().to_lowercase();
+    let mut matches: Vec<(bool, &ProcessInfo)> = processes
+        .iter()
+        .filter_map(|process| {
+            let name = process.name.to_lowercase();
+            let bundle = process.app_bundle.as_deref().map(str::to_lowercase).unwrap_or_default();
+            let starts = name.starts_with(&query) || bundle.starts_with(&query);
+            let matches =
+                starts || name.contains(&query) || bundle.contains(&query) || process.pid.to_string() == query;
+            matches.then_some((starts, process))
+        })
+        .collect();
+
+    matches.sort_by(|a, b| b.0.cmp(&a.0).then(b.1.memory_bytes.cmp(&a.1.memory_bytes)));
+    matches.into_iter().map(|(_, process)| process.clone()).collect()
+}
+
+/// How to end a process
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessAction {
+    /// Ask it to exit, letting it save its work
+    Quit,
+    /// End it immediately; needs the user's confirmation
+    ForceKill,
+}
+
+/// End process `pid`, which must still be named `name` so a reused pid
+/// can't end the wrong process. Force kill needs `confirmed`.
+pub fn end_process(
+    cache: &ProcessCache,
+    pid: u32,
+    name: &str,
+    action: ProcessAction,
+    confirmed: bool,
+) -> Result<(), String> {
+    if action == ProcessAction::ForceKill && !confirmed {
+        return Err(format!("Force killing {} needs confirmation", name));
+    }
+    if is_protected(pid, name, cache.own_pid()) {
+        return Err(format!("{} is a system process and can't be ended", name));
+    }
+
+    let result = signal_process(pid, name, action);
+    cache.invalidate();
+    result
+}
+
+fn signal_process(pid: u32, name: &str, action: ProcessAction) -> Result<(), String> {
+    let sys_pid = Pid::from_u32(pid);
+    let mut system = System::new();
+    system.refresh_processes_specifics(ProcessesToUpdate::Some(&[sys_pid]), true, ProcessRefreshKind::new());
+
+    let process = system
+        .process(sys_pid)
+        .ok_or_else(|| format!("{} (pid {}) is no longer running", name, pid))?;
+    if process.name().to_string_lossy() != name {
+        return Err(format!("Process {} is no longer {}", pid, name));
+    }
+
+    match action {
+        ProcessAction::ForceKill => process
+            .kill()
+            .then_some(())
+            .ok_or_else(|| format!("Failed to force kill {}", name)),
+        ProcessAction::Quit => quit_process(process, name),
+    }
+}
+
+#[cfg(unix)]
+fn quit_process(process: &sysinfo::Process, name: &str) -> Result<(), String> {
+    match process.kill_with(sysinfo::Signal::Term) {
+        Some(true) => Ok(()),
+        _ => Err(format!("Failed to quit {}", name)),
+    }
+}
+
+/// Windows has no SIGTERM; closing the process's windows is how apps are
+/// asked to exit
+#[cfg(windows)]
+fn quit_process(process: &sysinfo::Process, name: &str) -> Result<(), String> {
+    use windows_sys::Win32::Foundation::{BOOL, HWND, LPARAM};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        EnumWindows, GetWindowThreadProcessId, IsWindowVisible, PostMessageW, WM_CLOSE,
+    };
+
+    struct Search {
+        pid: u32,
+        closed: usize,
+    }
+
+    unsafe extern "system" fn close_if_owned(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        // SAFETY: lparam is the `Search` passed to EnumWindows below, alive for the call
+        let search = unsafe { &mut *(lparam as *mut Search) };
+        let mut owner = 0u32;
+        // SAFETY: hwnd comes from EnumWindows and owner is a valid out pointer
+        unsafe { GetWindowThreadProcessId(hwnd, &mut owner) };
+        // SAFETY: as above
+        if owner == search.pid
+            && unsafe { IsWindowVisible(hwnd) } != 0
+            && unsafe { PostMessageW(hwnd, WM_CLOSE, 0, 0) } != 0
+        {
+            search.closed += 1;
+        }
+        1
+    }
+
+    let mut search = Search {
+        pid: process.pid().as_u32(),
+        closed: 0,
+    };
+    // SAFETY: the callback only uses `search` while EnumWindows runs
+    unsafe { EnumWindows(Some(close_if_owned), &mut search as *mut Search as LPARAM) };
+
+    if search.closed > 0 {
+        Ok(())
+    } else {
+        Err(format!("{} has no windows to close; force kill it instead", name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const OWN_PID: u32 = 4242;
+
+    struct FakeProcesses {
+        processes: Vec<RawProcess>,
+        lists: AtomicUsize,
+    }
+
+    impl ProcessSource for FakeProcesses {
+        fn list(&self) -> Vec<RawProcess> {
+            self.lists.fetch_add(1, Ordering::SeqCst);
+            self.processes.clone()
+        }
+    }
+
+    fn raw(pid: u32, name: &str, exe: Option<&str>, memory_mb: u64) -> RawProcess {
+        RawProcess {
+            pid,
+            name: name.to_string(),
+            exe: exe.map(PathBuf::from),
+            memory_bytes: memory_mb * 1024 * 1024,
+            cpu_percent: 0.0,
+        }
+    }
+
+    fn fake() -> Vec<RawProcess> {
+        vec![
+            raw(1, "launchd", Some("/sbin/launchd"), 20),
+            raw(310, "WindowServer", None, 400),
+            raw(900, "Google Chrome", Some("/Applications/Google Chrome.app/Contents/MacOS/Google Chrome"), 500),
+            raw(
+                901,
+                "Google Chrome Helper (Renderer)",
+                Some("/Applications/Google Chrome.app/Contents/Frameworks/Google Chrome Framework.framework/Helpers/Google Chrome Helper (Renderer).app/Contents/MacOS/Google Chrome Helper (Renderer)"),
+                800,
+            ),
+            raw(1200, "chromedriver", Some("/usr/local/bin/chromedriver"), 30),
+            raw(1300, "Notes", Some("/System/Applications/Notes.app/Contents/MacOS/Notes"), 90),
+            raw(OWN_PID, "etools", None, 120),
+        ]
+    }
+
+    fn snapshot() -> Vec<ProcessInfo> {
+        fake()
+            .into_iter()
+            .map(|raw| ProcessInfo::from_raw(raw, OWN_PID))
+            .collect()
+    }
+
+    fn names(processes: &[ProcessInfo]) -> Vec<&str> {
+        processes.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_matching_ranks_prefix_then_memory() {
+        let processes = snapshot();
+
+        let chrome = search_processes(&processes, "chrome");
+        // Helpers match through their app; the prefix match on "chromedriver"
+        // outranks mere containment despite using less memory
+        assert_eq!(
+            names(&chrome),
+            vec!["chromedriver", "Google Chrome Helper (Renderer)", "Google Chrome"]
+        );
+
+        let google = search_processes(&processes, "Google");
+        assert_eq!(names(&google), vec!["Google Chrome Helper (Renderer)", "Google Chrome"]);
+
+        assert_eq!(names(&search_processes(&processes, "1300")), vec!["Notes"]);
+        assert!(search_processes(&processes, "firefox").is_empty());
+        assert_eq!(search_processes(&processes, "").len(), processes.len());
+    }
+
+    #[test]
+    fn test_helpers_resolve_to_the_outermost_app() {
+        let processes = snapshot();
+        let bundle = |pid: u32| processes.iter().find(|p| p.pid == pid).unwrap().app_bundle.clone();
+
+        assert_eq!(bundle(901).as_deref(), Some("Google Chrome"));
+        assert_eq!(bundle(1300).as_deref(), Some("Notes"));
+        assert_eq!(bundle(1200), None);
+    }
+
+    #[test]
+    fn test_system_processes_are_not_killable() {
+        let killable: Vec<(u32, bool)> = snapshot().iter().map(|p| (p.pid, p.killable)).collect();
+        assert_eq!(
+            killable,
+            vec![
+                (1, false),
+                (310, false),
+                (900, true),
+                (901, true),
+                (1200, true),
+                (1300, true),
+                (OWN_PID, false)
+            ]
+        );
+
+        assert!(is_protected(4, "System", OWN_PID));
+        assert!(is_protected(5000, "CSRSS.EXE", OWN_PID));
+        assert!(!is_protected(5000, "notepad.exe", OWN_PID));
+
+        let cache = ProcessCache::new(Box::new(FakeProcesses {
+            processes: fake(),
+            lists: AtomicUsize::new(0),
+        }));
+        let ended = end_process(&cache, 310, "WindowServer", ProcessAction::Quit, false);
+        assert_eq!(
+            ended.unwrap_err(),
+            "WindowServer is a system process and can't be ended"
+        );
+    }
+
+    #[test]
+    fn test_force_kill_needs_confirmation() {
+        let cache = ProcessCache::new(Box::new(FakeProcesses {
+            processes: fake(),
+            lists: AtomicUsize::new(0),
+        }));
+        let ended = end_process(&cache, 1200, "chromedriver", ProcessAction::ForceKill, false);
+        assert_eq!(ended.unwrap_err(), "Force killing chromedriver needs confirmation");
+    }
+
+    #[test]
+    fn test_snapshot_is_cached_until_ttl_or_invalidated() {
+        let source = Arc::new(FakeProcesses {
+            processes: fake(),
+            lists: AtomicUsize::new(0),
+        });
+        struct Shared(Arc<FakeProcesses>);
+        impl ProcessSource for Shared {
+            fn list(&self) -> Vec<RawProcess> {
+                self.0.list()
+            }
+        }
+
+        let cache = ProcessCache::with_ttl(Box::new(Shared(Arc::clone(&source))), Duration::from_secs(60));
+        assert_eq!(cache.snapshot().len(), 7);
+        cache.snapshot();
+        assert_eq!(source.lists.load(Ordering::SeqCst), 1);
+
+        cache.invalidate();
+        cache.snapshot();
+        assert_eq!(source.lists.load(Ordering::SeqCst), 2);
+
+        let expired = ProcessCache::with_ttl(Box::new(Shared(Arc::clone(&source))), Duration::ZERO);
+        expired.snapshot();
+        expired.snapshot();
+        assert_eq!(source.lists.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,156 @@
+//! Query History
+//! Recent search queries, newest first, for shell-style up-arrow recall and
+//! a "recent searches" section. Only queries whose results were acted on are
+//! kept, and nothing is kept while privacy mode or anonymized usage is on, or
+//! when the query looks like a secret by the clipboard's sensitive-content
+//! rules.
+
+use crate::cmds::search::SearchState;
+use crate::services::app_paths;
+use crate::services::clipboard_watcher::detect_sensitive_content;
+use crate::services::privacy::PrivacyMode;
+use crate::utils::{managed_store, StateStore};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// Queries remembered before the oldest are dropped
+pub const QUERY_HISTORY_CAP: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueryHistoryEntry {
+    pub query: String,
+    /// Unix timestamp of the latest search for this query
+    pub last_searched: i64,
+}
+
+/// Remembered queries, newest first, each at most once
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct QueryHistory {
+    pub entries: Vec<QueryHistoryEntry>,
+}
+
+impl QueryHistory {
+    /// Move `query` to the front, dropping the oldest entries beyond `cap`
+    pub fn record(&mut self, query: &str, now: i64, cap: usize) {
+        self.entries.retain(|entry| entry.query != query);
+        self.entries.insert(
+            0,
+            QueryHistoryEntry {
+                query: query.to_string(),
+                last_searched: now,
+            },
+        );
+        self.entries.truncate(cap);
+    }
+
+    /// The `limit` most recent queries
+    pub fn recent(&self, limit: usize) -> Vec<QueryHistoryEntry> {
+        self.entries.iter().take(limit).cloned().collect()
+    }
+}
+
+/// The query as it would be remembered, None if it must not be: blank, or
+/// looking like it holds a password or token
+pub fn rememberable(query: &str) -> Option<&str> {
+    let query = query.trim();
+    (!query.is_empty() && !detect_sensitive_content(query)).then_some(query)
+}
+
+fn history_store<R: Runtime>(handle: &AppHandle<R>) -> Result<State<'_, StateStore<QueryHistory>>, String> {
+    managed_store(handle, || {
+        let path = app_paths::data_dir(handle)?
+            .join("query_history.json");
+        Ok(StateStore::new(path, "query history"))
+    })
+}
+
+fn privacy_active<R: Runtime>(handle: &AppHandle<R>) -> bool {
+    handle.try_state::<PrivacyMode>().is_some_and(|privacy| privacy.is_active())
+}
+
+/// Remember a query whose result was acted on. Skipped while privacy mode or
+/// anonymized usage is on; failures are logged and never surface.
+pub fn record_query<R: Runtime>(handle: &AppHandle<R>, query: &str) {
+    if privacy_active(handle) {
+        return;
+    }
+
+    // Default to anonymizing if settings can't be read
+    let anonymize = handle
+        .try_state::<SearchState>()
+        .and_then(|state| state.settings().ok())
+        .is_none_or(|settings| settings.anonymize_usage);
+    if anonymize {
+        return;
+    }
+
+    let Some(query) = rememberable(query) else {
+        return;
+    };
+
+    let result = history_store(handle).and_then(|store| {
+        store.update(|history| {
+            history.record(query, chrono::Utc::now().timestamp(), QUERY_HISTORY_CAP);
+            Ok::<_, String>(())
+        })
+    });
+    if let Err(e) = result {
+        eprintln!("[QueryHistory] Failed to record query: {}", e);
+    }
+}
+
+/// The `limit` most recent queries; none while privacy mode is on
+pub fn recent_queries<R: Runtime>(handle: &AppHandle<R>, limit: usize) -> Result<Vec<QueryHistoryEntry>, String> {
+    if privacy_active(handle) {
+        return Ok(Vec::new());
+    }
+    Ok(history_store(handle)?.read()?.recent(limit))
+}
+
+/// Forget every remembered query
+pub fn clear_queries<R: Runtime>(handle: &AppHandle<R>) -> Result<(), String> {
+    history_store(handle)?.replace(QueryHistory::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queries(history: &QueryHistory) -> Vec<&str> {
+        history.entries.iter().map(|e| e.query.as_str()).collect()
+    }
+
+    #[test]
+    fn test_repeated_query_moves_to_front() {
+        let mut history = QueryHistory::default();
+        history.record("safari", 1, QUERY_HISTORY_CAP);
+        history.record("notes", 2, QUERY_HISTORY_CAP);
+        history.record("safari", 3, QUERY_HISTORY_CAP);
+        // Dedup is by exact string
+        history.record("Safari", 4, QUERY_HISTORY_CAP);
+
+        assert_eq!(queries(&history), vec!["Safari", "safari", "notes"]);
+        assert_eq!(history.entries[1].last_searched, 3);
+        assert_eq!(history.recent(2).len(), 2);
+    }
+
+    #[test]
+    fn test_history_is_capped_dropping_the_oldest() {
+        let mut history = QueryHistory::default();
+        for i in 0..QUERY_HISTORY_CAP + 20 {
+            history.record(&format!("query {}", i), i as i64, QUERY_HISTORY_CAP);
+        }
+
+        assert_eq!(history.entries.len(), QUERY_HISTORY_CAP);
+        assert_eq!(history.entries[0].query, format!("query {}", QUERY_HISTORY_CAP + 19));
+        assert_eq!(history.entries.last().unwrap().query, "query 20");
+    }
+
+    #[test]
+    fn test_sensitive_and_blank_queries_are_not_remembered() {
+        assert_eq!(rememberable("  visual studio "), Some("visual studio"));
+        assert_eq!(rememberable("   "), None);
+        assert_eq!(rememberable("my password is hunter2"), None);
+        assert_eq!(rememberable("access_token=abcdef1234567890"), None);
+    }
+}
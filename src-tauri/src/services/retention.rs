@@ -0,0 +1,498 @@
+//! Retention
+//! Keeps the app's stores within the budgets in settings: a row limit, a
+//! size limit and an age limit per store, each optional. Pruning deletes
+//! the oldest rows first, then vacuums once enough space is free to be
+//! worth rewriting the file. Logs are trimmed to their newest lines. Runs
+//! daily in the background, or on demand with a dry run that only reports
+//! what would be deleted.
+
+use crate::models::preferences::{AppSettings, RetentionSettings, StoreBudget};
+use crate::services::browser_reader::BrowserReaderConfig;
+use crate::services::db_maintenance::MaintenanceGuard;
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often stores are pruned
+pub const PRUNE_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+const MB: u64 = 1024 * 1024;
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+/// Rough size of a browser history row, cached on disk with its indexes or
+/// read into memory
+const HISTORY_ROW_BYTES: u64 = 512;
+
+/// A store with a retention budget
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreKind {
+    Files,
+    Browser,
+    Analytics,
+    Logs,
+}
+
+/// The table a database store prunes, oldest first by `time_column`
+/// (unix seconds)
+struct PrunedTable {
+    table: &'static str,
+    time_column: &'static str,
+}
+
+impl StoreKind {
+    pub const ALL: [StoreKind; 4] = [Self::Files, Self::Browser, Self::Analytics, Self::Logs];
+
+    /// File name in the app data directory
+    pub fn file_name(self) -> &'static str {
+        match self {
+            Self::Files => "files_index.db",
+            Self::Browser => "browser_cache.db",
+            Self::Analytics => "usage_analytics.db",
+            Self::Logs => "debug.log",
+        }
+    }
+
+    pub fn budget(self, settings: &RetentionSettings) -> StoreBudget {
+        match self {
+            Self::Files => settings.files,
+            Self::Browser => settings.browser,
+            Self::Analytics => settings.analytics,
+            Self::Logs => settings.logs,
+        }
+    }
+
+    fn pruned_table(self) -> Option<PrunedTable> {
+        let (table, time_column) = match self {
+            Self::Files => ("files", "indexed"),
+            Self::Browser => ("browser_data", "cached"),
+            Self::Analytics => ("events", "timestamp"),
+            Self::Logs => return None,
+        };
+        Some(PrunedTable { table, time_column })
+    }
+}
+
+/// When each store was last pruned, unix seconds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetentionState {
+    #[serde(default)]
+    pub last_pruned: BTreeMap<StoreKind, i64>,
+}
+
+impl RetentionState {
+    /// Whether any store hasn't been pruned within `PRUNE_INTERVAL`
+    pub fn is_due(&self, now: i64) -> bool {
+        let interval = PRUNE_INTERVAL.as_secs() as i64;
+        StoreKind::ALL
+            .iter()
+            .any(|store| self.last_pruned.get(store).is_none_or(|at| now - at >= interval))
+    }
+}
+
+/// Retention state file in the app data directory
+pub fn retention_state_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("retention_state.json")
+}
+
+/// One store's size, for the storage settings page
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StoreReport {
+    pub store: StoreKind,
+    pub path: String,
+    /// On disk, including free pages not yet vacuumed
+    pub size_bytes: u64,
+    /// None for logs, and for databases that don't exist yet
+    pub rows: Option<u64>,
+    pub last_pruned: Option<i64>,
+    /// What the current settings let the store grow to, for stores whose
+    /// size they bound
+    pub expected: Option<StoreEstimate>,
+}
+
+/// Growth of a store under the current settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct StoreEstimate {
+    pub rows: u64,
+    pub disk_bytes: u64,
+    /// Held in memory while the store is refreshed
+    pub memory_bytes: u64,
+}
+
+/// History rows the browser cache holds: each browser's most recent and
+/// most visited pages, read one browser at a time. Bookmarks come on top.
+fn browser_history_estimate(settings: &AppSettings) -> StoreEstimate {
+    let config = BrowserReaderConfig::from_settings(settings);
+    let per_browser = 2 * config.history_limit as u64;
+    let rows = per_browser * config.enabled_browsers.len() as u64;
+    StoreEstimate {
+        rows,
+        disk_bytes: rows * HISTORY_ROW_BYTES,
+        memory_bytes: per_browser * HISTORY_ROW_BYTES,
+    }
+}
+
+/// What pruning a store deleted, or would delete in a dry run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StorePrune {
+    pub store: StoreKind,
+    pub dry_run: bool,
+    /// Rows, or lines for logs
+    pub deleted_rows: u64,
+    /// Timestamps of the oldest and newest deleted rows
+    pub oldest_deleted: Option<i64>,
+    pub newest_deleted: Option<i64>,
+    /// How much the file shrank; always 0 in a dry run
+    pub reclaimed_bytes: u64,
+    pub vacuumed: bool,
+}
+
+impl StorePrune {
+    fn empty(store: StoreKind, dry_run: bool) -> Self {
+        Self {
+            store,
+            dry_run,
+            deleted_rows: 0,
+            oldest_deleted: None,
+            newest_deleted: None,
+            reclaimed_bytes: 0,
+            vacuumed: false,
+        }
+    }
+}
+
+/// Rows to delete, oldest first, to bring a store within `budget`.
+/// `used_bytes` leaves out free pages, and `expired` is how many rows are
+/// past the age limit. Rows are assumed to be of average size when
+/// converting a byte excess into rows.
+pub fn rows_over_budget(budget: &StoreBudget, rows: u64, used_bytes: u64, expired: u64) -> u64 {
+    let mut excess = expired;
+    if let Some(max_rows) = budget.max_rows {
+        excess = excess.max(rows.saturating_sub(max_rows));
+    }
+    if let Some(max_mb) = budget.max_mb {
+        let over = used_bytes.saturating_sub(max_mb * MB);
+        if over > 0 {
+            let needed = (over as u128 * rows as u128).div_ceil(used_bytes as u128);
+            excess = excess.max(needed as u64);
+        }
+    }
+    excess.min(rows)
+}
+
+/// Bytes in use by a database, not counting free pages
+fn used_bytes(conn: &Connection) -> SqliteResult<u64> {
+    let pragma = |name: &str| conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0));
+    Ok(((pragma("page_count")? - pragma("freelist_count")?) * pragma("page_size")?) as u64)
+}
+
+/// Bytes a VACUUM would give back
+fn free_bytes(conn: &Connection) -> SqliteResult<u64> {
+    let pragma = |name: &str| conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get::<_, i64>(0));
+    Ok((pragma("freelist_count")? * pragma("page_size")?) as u64)
+}
+
+/// How many rows the oldest `count` are, and their time range
+fn oldest_rows(conn: &Connection, table: &PrunedTable, count: u64) -> SqliteResult<(u64, Option<i64>, Option<i64>)> {
+    conn.query_row(
+        &format!(
+            "SELECT COUNT(*), MIN(t), MAX(t) FROM (SELECT {time} AS t FROM {table} ORDER BY {time}, rowid LIMIT ?1)",
+            time = table.time_column,
+            table = table.table,
+        ),
+        [count as i64],
+        |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?, row.get(2)?)),
+    )
+}
+
+/// Delete the oldest `count` rows, ties broken by insertion order
+fn delete_oldest(conn: &Connection, table: &PrunedTable, count: u64) -> SqliteResult<usize> {
+    conn.execute(
+        &format!(
+            "DELETE FROM {table} WHERE rowid IN (SELECT rowid FROM {table} ORDER BY {time}, rowid LIMIT ?1)",
+            time = table.time_column,
+            table = table.table,
+        ),
+        [count as i64],
+    )
+}
+
+/// Prune a database store to its budget
+fn prune_database(
+    path: &Path,
+    store: StoreKind,
+    table: &PrunedTable,
+    settings: &RetentionSettings,
+    dry_run: bool,
+    now: i64,
+) -> SqliteResult<StorePrune> {
+    let mut prune = StorePrune::empty(store, dry_run);
+    if !path.exists() {
+        return Ok(prune);
+    }
+    let conn = Connection::open(path)?;
+    let budget = store.budget(settings);
+
+    let rows: i64 = conn.query_row(&format!("SELECT COUNT(*) FROM {}", table.table), [], |row| row.get(0))?;
+    let expired: i64 = match budget.max_age_days {
+        Some(days) => conn.query_row(
+            &format!("SELECT COUNT(*) FROM {} WHERE {} < ?1", table.table, table.time_column),
+            params![now - days as i64 * SECONDS_PER_DAY],
+            |row| row.get(0),
+        )?,
+        None => 0,
+    };
+    let count = rows_over_budget(&budget, rows as u64, used_bytes(&conn)?, expired as u64);
+    if count == 0 {
+        return Ok(prune);
+    }
+
+    (prune.deleted_rows, prune.oldest_deleted, prune.newest_deleted) = oldest_rows(&conn, table, count)?;
+    if dry_run {
+        return Ok(prune);
+    }
+
+    let size_before = crate::db::db_file_size(path);
+    delete_oldest(&conn, table, count)?;
+    if free_bytes(&conn)? >= settings.vacuum_threshold_mb * MB {
+        conn.execute("VACUUM", [])?;
+        prune.vacuumed = true;
+    }
+    prune.reclaimed_bytes = size_before.saturating_sub(crate::db::db_file_size(path));
+    Ok(prune)
+}
+
+/// Trim a log to its newest lines within `budget.max_mb`
+fn prune_log(path: &Path, budget: &StoreBudget, dry_run: bool) -> Result<StorePrune, String> {
+    let mut prune = StorePrune::empty(StoreKind::Logs, dry_run);
+    let size = crate::db::db_file_size(path);
+    let Some(max_bytes) = budget.max_mb.map(|mb| mb * MB).filter(|max| size > *max) else {
+        return Ok(prune);
+    };
+
+    let mut content = Vec::new();
+    fs::File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut content))
+        .map_err(|e| format!("Failed to read log: {}", e))?;
+    // Keep whole lines only: cut after the first newline at or past the
+    // byte before the excess, so a cut on a line boundary stays there
+    let excess = content.len() - max_bytes as usize;
+    let cut = match content[excess - 1..].iter().position(|b| *b == b'\n') {
+        Some(newline) => excess + newline,
+        None => content.len(),
+    };
+    prune.deleted_rows = content[..cut].iter().filter(|b| **b == b'\n').count() as u64;
+    if dry_run {
+        return Ok(prune);
+    }
+
+    crate::utils::write_atomic(path, &content[cut..]).map_err(|e| format!("Failed to write log: {}", e))?;
+    prune.reclaimed_bytes = cut as u64;
+    Ok(prune)
+}
+
+/// Prune one store in `data_dir` to its budget
+pub fn prune_store(
+    data_dir: &Path,
+    store: StoreKind,
+    settings: &RetentionSettings,
+    dry_run: bool,
+    now: i64,
+) -> Result<StorePrune, String> {
+    let path = data_dir.join(store.file_name());
+    match store.pruned_table() {
+        Some(table) => prune_database(&path, store, &table, settings, dry_run, now)
+            .map_err(|e| format!("Failed to prune {}: {}", store.file_name(), e)),
+        None => prune_log(&path, &store.budget(settings), dry_run),
+    }
+}
+
+/// Prune every store. Holds shared maintenance access throughout, so this
+/// never overlaps a rebuild; fails rather than waits while one runs.
+pub fn prune_all(
+    data_dir: &Path,
+    settings: &RetentionSettings,
+    maintenance: &MaintenanceGuard,
+    dry_run: bool,
+    now: i64,
+) -> Result<Vec<StorePrune>, String> {
+    let _shared = maintenance
+        .try_shared()
+        .ok_or("Pruning is paused during database maintenance")?;
+    StoreKind::ALL
+        .iter()
+        .map(|store| prune_store(data_dir, *store, settings, dry_run, now))
+        .collect()
+}
+
+/// Size, rows and last prune of every store, and what `settings` let them
+/// grow to. Reads databases without creating them.
+pub fn storage_report(data_dir: &Path, state: &RetentionState, settings: &AppSettings) -> Vec<StoreReport> {
+    StoreKind::ALL
+        .iter()
+        .map(|store| {
+            let path = data_dir.join(store.file_name());
+            let rows = store.pruned_table().and_then(|table| {
+                Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                    .and_then(|conn| {
+                        conn.query_row(&format!("SELECT COUNT(*) FROM {}", table.table), [], |row| {
+                            row.get::<_, i64>(0)
+                        })
+                        .optional()
+                    })
+                    .ok()
+                    .flatten()
+                    .map(|rows| rows as u64)
+            });
+            StoreReport {
+                store: *store,
+                path: path.to_string_lossy().to_string(),
+                size_bytes: crate::db::db_file_size(&path),
+                rows,
+                last_pruned: state.last_pruned.get(store).copied(),
+                expected: (*store == StoreKind::Browser).then(|| browser_history_estimate(settings)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::analytics::{insert_event, open_analytics_db};
+
+    const NOW: i64 = 1_750_000_000;
+
+    fn budget(max_rows: Option<u64>, max_mb: Option<u64>) -> StoreBudget {
+        StoreBudget {
+            max_rows,
+            max_mb,
+            max_age_days: None,
+        }
+    }
+
+    #[test]
+    fn test_rows_over_budget() {
+        // Within every limit
+        assert_eq!(rows_over_budget(&budget(Some(1000), Some(10)), 1000, 10 * MB, 0), 0);
+        assert_eq!(rows_over_budget(&StoreBudget::default(), 1_000_000, 1000 * MB, 0), 0);
+
+        // Rows: the excess
+        assert_eq!(rows_over_budget(&budget(Some(800), None), 1000, 10 * MB, 0), 200);
+        // Bytes: the share of average-sized rows that covers the excess, 2 of 10 MB
+        assert_eq!(rows_over_budget(&budget(None, Some(8)), 1000, 10 * MB, 0), 200);
+        // Rounded up, so the store ends within budget
+        assert_eq!(rows_over_budget(&budget(None, Some(8)), 3, 10 * MB, 0), 1);
+
+        // The stricter limit wins, including the age limit
+        assert_eq!(rows_over_budget(&budget(Some(900), Some(5)), 1000, 10 * MB, 0), 500);
+        assert_eq!(rows_over_budget(&budget(Some(100), Some(5)), 1000, 10 * MB, 0), 900);
+        assert_eq!(rows_over_budget(&budget(Some(900), None), 1000, 10 * MB, 300), 300);
+
+        // Never more rows than there are
+        assert_eq!(rows_over_budget(&budget(None, Some(1)), 10, 10 * MB, 50), 10);
+        assert_eq!(rows_over_budget(&budget(None, Some(1)), 0, 0, 0), 0);
+    }
+
+    #[test]
+    fn test_prune_deletes_oldest_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_analytics_db(&dir.path().join("usage_analytics.db")).unwrap();
+        // Inserted out of order, with a tie at the cut
+        for (i, timestamp) in [500, 100, 400, 200, 300, 200].iter().enumerate() {
+            insert_event(&conn, "search", &format!("s{}", i), *timestamp).unwrap();
+        }
+        let settings = RetentionSettings {
+            analytics: budget(Some(4), None),
+            ..RetentionSettings::default()
+        };
+
+        let dry_run = prune_store(dir.path(), StoreKind::Analytics, &settings, true, NOW).unwrap();
+        assert_eq!(dry_run.deleted_rows, 2);
+        assert_eq!((dry_run.oldest_deleted, dry_run.newest_deleted), (Some(100), Some(200)));
+        let remaining = || -> Vec<(i64, String)> {
+            conn.prepare("SELECT timestamp, subject_hash FROM events ORDER BY timestamp, id")
+                .unwrap()
+                .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+                .unwrap()
+                .collect::<SqliteResult<_>>()
+                .unwrap()
+        };
+        assert_eq!(remaining().len(), 6);
+
+        let pruned = prune_store(dir.path(), StoreKind::Analytics, &settings, false, NOW).unwrap();
+        assert_eq!(pruned.deleted_rows, 2);
+        // Of the two at 200, the earlier insert went first
+        let expected = [(200, "s5"), (300, "s4"), (400, "s2"), (500, "s0")];
+        assert_eq!(remaining(), expected.map(|(t, s)| (t, s.to_string())).to_vec());
+    }
+
+    #[test]
+    fn test_age_limit_and_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let conn = open_analytics_db(&dir.path().join("usage_analytics.db")).unwrap();
+        for days in [400, 200, 10, 1] {
+            insert_event(&conn, "launch", "app", NOW - days * SECONDS_PER_DAY).unwrap();
+        }
+        let settings = RetentionSettings {
+            analytics: StoreBudget {
+                max_age_days: Some(365),
+                ..StoreBudget::default()
+            },
+            ..RetentionSettings::default()
+        };
+
+        let pruned = prune_store(dir.path(), StoreKind::Analytics, &settings, false, NOW).unwrap();
+        assert_eq!(pruned.deleted_rows, 1);
+        assert_eq!(pruned.oldest_deleted, Some(NOW - 400 * SECONDS_PER_DAY));
+
+        let state = RetentionState {
+            last_pruned: BTreeMap::from([(StoreKind::Analytics, NOW)]),
+        };
+        assert!(state.is_due(NOW));
+        let report = storage_report(dir.path(), &state, &AppSettings::default());
+        let analytics = report.iter().find(|r| r.store == StoreKind::Analytics).unwrap();
+        assert_eq!((analytics.rows, analytics.last_pruned), (Some(3), Some(NOW)));
+        assert_eq!(analytics.expected, None);
+        let browser = report.iter().find(|r| r.store == StoreKind::Browser).unwrap();
+        // 4 browsers, 1000 recent and 1000 most visited pages each
+        assert_eq!(browser.expected.map(|e| e.rows), Some(8000));
+        // Missing stores are reported, not created
+        let files = report.iter().find(|r| r.store == StoreKind::Files).unwrap();
+        assert_eq!((files.rows, files.size_bytes), (None, 0));
+        assert!(!dir.path().join("files_index.db").exists());
+    }
+
+    #[test]
+    fn test_log_keeps_newest_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("debug.log");
+        let line = format!("{}\n", "x".repeat(1023));
+        fs::write(&log, line.repeat(1536)).unwrap();
+        let settings = RetentionSettings {
+            logs: budget(None, Some(1)),
+            ..RetentionSettings::default()
+        };
+
+        let dry_run = prune_store(dir.path(), StoreKind::Logs, &settings, true, NOW).unwrap();
+        assert_eq!(dry_run.deleted_rows, 512);
+        assert_eq!(fs::metadata(&log).unwrap().len(), 1536 * 1024);
+
+        let pruned = prune_store(dir.path(), StoreKind::Logs, &settings, false, NOW).unwrap();
+        assert_eq!(pruned.reclaimed_bytes, 512 * 1024);
+        assert_eq!(fs::read_to_string(&log).unwrap(), line.repeat(1024));
+    }
+
+    #[test]
+    fn test_no_pruning_during_maintenance() {
+        let dir = tempfile::tempdir().unwrap();
+        let guard = MaintenanceGuard::new();
+        let _exclusive = guard.exclusive().unwrap();
+        let err = prune_all(dir.path(), &RetentionSettings::default(), &guard, false, NOW).unwrap_err();
+        assert_eq!(err, "Pruning is paused during database maintenance");
+    }
+}
@@ -1,10 +1,11 @@
-use tauri::{AppHandle, Emitter, Manager};
+use tauri::{AppHandle, Manager};
 use crate::models::ScreenInfo;
+use crate::services::windows::MAIN_WINDOW;
 
 /// Detects screen information using Tauri's window API
 pub async fn detect_screen_info(app: &AppHandle) -> Result<ScreenInfo, String> {
-    let window = app.get_webview_window("main")
-        .ok_or("Window 'main' not found")?;
+    let window = app.get_webview_window(MAIN_WINDOW)
+        .ok_or("Main window not found")?;
 
     let monitor = window.current_monitor()
         .map_err(|e| e.to_string())?
@@ -54,25 +55,25 @@ pub async fn get_screen_info_with_fallback(app: &AppHandle) -> ScreenInfo {
 /// Emit screen changed event when resolution or display configuration changes
 #[allow(dead_code)]
 pub fn emit_screen_changed(app: &AppHandle, old_info: Option<ScreenInfo>, new_info: &ScreenInfo) {
-    use crate::types::ScreenChangedPayload;
+    use crate::types::events::{self, ScreenChangeType, ScreenChanged};
 
     let change_type = if let Some(ref old) = old_info {
         if old.screen_width != new_info.screen_width || old.screen_height != new_info.screen_height {
-            "resolution"
+            ScreenChangeType::Resolution
         } else if old.scale_factor != new_info.scale_factor {
-            "scale_factor"
+            ScreenChangeType::ScaleFactor
         } else {
-            "display_connect"
+            ScreenChangeType::DisplayConnect
         }
     } else {
-        "display_connect"
+        ScreenChangeType::DisplayConnect
     };
 
-    let payload = ScreenChangedPayload {
+    let payload = ScreenChanged {
         old_screen_info: old_info,
         new_screen_info: new_info.clone(),
-        change_type: change_type.to_string(),
+        change_type,
     };
 
-    let _ = app.emit("screen:changed", &payload);
+    let _ = events::emit(app, payload);
 }
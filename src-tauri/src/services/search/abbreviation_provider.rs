@@ -0,0 +1,176 @@
+//! Abbreviation Search Provider
+//! Surfaces abbreviation expansions from the user's abbreviation config,
+//! in the config's sort order
+
+use super::{ParsedQuery, SearchProvider};
+use crate::cmds::abbreviation::{Abbreviation, AbbreviationConfig};
+use crate::models::search::{ScoreBreakdown, SearchResultItem};
+use std::path::PathBuf;
+
+/// Searches the abbreviation config file
+pub struct AbbreviationProvider {
+    config_path: PathBuf,
+}
+
+impl AbbreviationProvider {
+    pub fn new(config_path: PathBuf) -> Self {
+        Self { config_path }
+    }
+
+    /// Read the config, falling back to no abbreviations if it's missing or invalid
+    fn load_config(&self) -> Option<AbbreviationConfig> {
+        let content = std::fs::read_to_string(&self.config_path).ok()?;
+        match serde_json::from_str(&content) {
+            Ok(config) => Some(config),
+            Err(e) => {
+                eprintln!("[AbbreviationProvider] Failed to parse config: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl SearchProvider for AbbreviationProvider {
+    fn name(&self) -> &'static str {
+        "abbreviation"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let Some(config) = self.load_config() else {
+            return vec![];
+        };
+
+        let query_lower = query.text_lower();
+        config
+            .search(&query.text)
+            .into_iter()
+            .take(limit)
+            .map(|abbr| to_result(abbr, query, &query_lower))
+            .collect()
+    }
+}
+
+fn to_result(abbr: &Abbreviation, query: &ParsedQuery, query_lower: &str) -> SearchResultItem {
+    let abbr_lower = abbr.abbr.to_lowercase();
+    let mut breakdown = ScoreBreakdown::default();
+    if abbr_lower == query_lower {
+        breakdown.exact = query.weights.exact;
+    } else if abbr_lower.starts_with(query_lower) {
+        breakdown.starts_with = query.weights.starts_with;
+    } else {
+        // Matched the description or expansion
+        breakdown.other = 0.4;
+    }
+
+    SearchResultItem {
+        id: format!("abbr-{}", abbr.id),
+        title: abbr.description.clone().unwrap_or_else(|| abbr.abbr.clone()),
+        subtitle: format!("{} → {}", abbr.abbr, abbr.expansion),
+        icon: None,
+        icon_kind: None,
+        accent_color: None,
+        result_type: "abbreviation".to_string(),
+        score: breakdown.total(),
+        path: abbr.expansion.clone(),
+        frequency: abbr.use_count,
+        explain: query.explain.then_some(breakdown),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn abbr(id: &str, abbr: &str, expansion: &str, description: Option<&str>) -> Abbreviation {
+        Abbreviation {
+            id: id.to_string(),
+            abbr: abbr.to_string(),
+            expansion: expansion.to_string(),
+            description: description.map(str::to_string),
+            category: None,
+            enabled: true,
+            created_at: String::new(),
+            updated_at: String::new(),
+            use_count: 0,
+            last_used_at: None,
+        }
+    }
+
+    fn provider(dir: &std::path::Path, config: &AbbreviationConfig) -> AbbreviationProvider {
+        let config_path = dir.join("abbreviations.json");
+        std::fs::write(&config_path, serde_json::to_string(config).unwrap()).unwrap();
+        AbbreviationProvider::new(config_path)
+    }
+
+    fn config(abbreviations: Vec<Abbreviation>) -> AbbreviationConfig {
+        AbbreviationConfig {
+            abbreviations,
+            categories: Vec::new(),
+            ..AbbreviationConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_abbreviation_provider_scores_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = provider(
+            dir.path(),
+            &config(vec![
+                abbr("1", "gh", "https://github.com", Some("GitHub")),
+                abbr("2", "ghp", "https://github.com/pulls", None),
+                abbr("3", "hub", "https://hub.docker.com", Some("Docker gh mirror")),
+                abbr("4", "mdn", "https://developer.mozilla.org", None),
+            ]),
+        );
+
+        let results = provider.search(&ParsedQuery::new("GH"), 10);
+        let score = |id: &str| results.iter().find(|r| r.id == id).unwrap().score;
+        assert_eq!(results.len(), 3);
+        assert_eq!(score("abbr-1"), 1.0);
+        assert_eq!(score("abbr-2"), 0.8);
+        assert_eq!(score("abbr-3"), 0.4);
+
+        let gh = results.iter().find(|r| r.id == "abbr-1").unwrap();
+        assert_eq!(gh.title, "GitHub");
+        assert_eq!(gh.subtitle, "gh → https://github.com");
+        assert_eq!(gh.path, "https://github.com");
+        assert_eq!(gh.result_type, "abbreviation");
+        // Without a description the abbreviation is the title
+        assert_eq!(results.iter().find(|r| r.id == "abbr-2").unwrap().title, "ghp");
+
+        assert_eq!(provider.search(&ParsedQuery::new("gh"), 1).len(), 1);
+    }
+
+    #[test]
+    fn test_abbreviation_provider_respects_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut hidden = config(vec![abbr("1", "gh", "https://github.com", None)]);
+        hidden.show_in_search = false;
+        assert!(provider(dir.path(), &hidden).search(&ParsedQuery::new("gh"), 10).is_empty());
+
+        let mut disabled = config(vec![abbr("1", "gh", "https://github.com", None)]);
+        disabled.abbreviations[0].enabled = false;
+        assert!(provider(dir.path(), &disabled).search(&ParsedQuery::new("gh"), 10).is_empty());
+
+        let shown = provider(dir.path(), &config(vec![abbr("1", "gh", "https://github.com", None)]));
+        assert!(shown.search(&ParsedQuery::new(""), 10).is_empty());
+        assert_eq!(shown.search(&ParsedQuery::new("gh"), 10).len(), 1);
+    }
+
+    #[test]
+    fn test_abbreviation_provider_without_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = AbbreviationProvider::new(dir.path().join("abbreviations.json"));
+        assert!(missing.search(&ParsedQuery::new("gh"), 10).is_empty());
+
+        let invalid_path = dir.path().join("invalid.json");
+        std::fs::write(&invalid_path, "{ not json").unwrap();
+        let invalid = AbbreviationProvider::new(invalid_path);
+        assert!(invalid.search(&ParsedQuery::new("gh"), 10).is_empty());
+    }
+}
@@ -0,0 +1,467 @@
+//! Application Search Provider
+//! Matches installed applications by name, bundle name, alternate names and initialisms,
+//! and Chinese names by their pinyin when pinyin search is on
+
+use super::exclusion::filter_excluded;
+use super::pinyin::{collation_key, pinyin_search_enabled, PinyinIndex, PinyinNames};
+use super::terms::{all_terms_match, query_terms, terms_breakdown};
+use super::{ParsedQuery, SearchProvider};
+use crate::models::app::{AppSource, ApplicationEntry};
+use crate::models::preferences::{AppSettings, ScoringWeights};
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+use crate::services::app_monitor::AppMonitor;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Share of the initialism weight a partial initialism match ("vs" for
+/// "Visual Studio Code") scores, 0.65 at the default weight
+const INITIALISM_PREFIX_SHARE: f64 = 0.65 / 0.85;
+
+/// Searches the application list held by an AppMonitor, minus excluded apps
+pub struct AppProvider {
+    app_monitor: Arc<Mutex<AppMonitor>>,
+    settings: Arc<RwLock<AppSettings>>,
+}
+
+impl AppProvider {
+    pub fn new(app_monitor: Arc<Mutex<AppMonitor>>, settings: Arc<RwLock<AppSettings>>) -> Self {
+        Self { app_monitor, settings }
+    }
+}
+
+impl SearchProvider for AppProvider {
+    fn name(&self) -> &'static str {
+        "app"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        let (excluded, pinyin_enabled) = self
+            .settings
+            .read()
+            .map(|s| (s.excluded_apps.clone(), pinyin_search_enabled(&s)))
+            .unwrap_or_default();
+
+        let (apps, pinyin) = match self.app_monitor.lock() {
+            Ok(mut monitor) => {
+                let apps = monitor.cached_apps();
+                let pinyin = (pinyin_enabled && !query.is_empty()).then(|| monitor.pinyin_names());
+                (apps, pinyin)
+            }
+            Err(e) => {
+                eprintln!("[AppProvider] Lock error: {}", e);
+                return vec![];
+            }
+        };
+
+        search_apps(&filter_excluded(apps, &excluded), pinyin.as_deref(), query, limit)
+    }
+}
+
+/// Filter and score applications for a query. With `pinyin`, Chinese names
+/// also match by the spellings it holds for them.
+pub fn search_apps(
+    apps: &[ApplicationEntry],
+    pinyin: Option<&PinyinIndex>,
+    query: &ParsedQuery,
+    limit: usize,
+) -> Vec<SearchResultItem> {
+    let query_lower = query.text_lower();
+
+    let mut results: Vec<SearchResultItem> = apps
+        .iter()
+        .filter_map(|app| {
+            let app_pinyin = pinyin.and_then(|index| index.get(&app.id));
+            (query.is_empty() || app_matches(app, app_pinyin, &query_lower))
+                .then(|| to_result(app, app_pinyin, query, &query_lower))
+        })
+        .collect();
+
+    // Equal scores in collation order, so 备忘录 sorts near "Calendar". The
+    // score sort is stable, so it keeps that order among ties.
+    if !query.is_empty() {
+        results.sort_by_cached_key(|item| collation_key(&item.title));
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+    }
+    results.truncate(limit);
+    results
+}
+
+/// Extract app bundle name from path for better matching
+/// e.g., "/Applications/Visual Studio Code.app/..." -> "visual studio code"
+fn app_name_from_path(app: &ApplicationEntry) -> String {
+    app.executable_path
+        .split('/')
+        .find(|segment| segment.ends_with(".app"))
+        .map(|s| s.trim_end_matches(".app").to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Get initials from a name (split by spaces/special chars)
+fn initials(name: &str) -> String {
+    name.split(|c: char| !c.is_alphanumeric())
+        .filter(|s| !s.is_empty())
+        .map(|word| word.chars().next().unwrap_or(' '))
+        .collect::<String>()
+        .to_lowercase()
+}
+
+/// Initialism matching is only used for ASCII queries of 2+ characters
+/// to avoid over-matching single characters
+fn is_initialism_query(query_lower: &str) -> bool {
+    query_lower.chars().all(|c| c.is_ascii_lowercase()) && query_lower.len() >= 2
+}
+
+/// Whether the query spells a Chinese name in pinyin: part of the full
+/// pinyin, or the start of its initials
+fn pinyin_matches(pinyin: &PinyinNames, query_lower: &str) -> bool {
+    is_initialism_query(query_lower)
+        && (pinyin.full.iter().any(|full| full.contains(query_lower))
+            || pinyin.initials.iter().any(|initials| initials.starts_with(query_lower)))
+}
+
+fn app_matches(app: &ApplicationEntry, pinyin: Option<&PinyinNames>, query_lower: &str) -> bool {
+    // Several terms must each match the name, bundle name, an alternate name or a pinyin spelling
+    let terms = query_terms(query_lower);
+    if terms.len() > 1 {
+        let mut fields = vec![app.name.to_lowercase(), app_name_from_path(app)];
+        fields.extend(app.alternate_names.iter().flatten().map(|n| n.to_lowercase()));
+        fields.extend(pinyin.into_iter().flat_map(|pinyin| pinyin.full.iter().cloned()));
+        return all_terms_match(&terms, &fields);
+    }
+
+    let name_matches = app.name.to_lowercase().contains(query_lower);
+
+    let path_app_name = app_name_from_path(app);
+    let path_app_name_matches = !path_app_name.is_empty() && path_app_name.contains(query_lower);
+
+    // Check alternate names (e.g., .app filename like "Visual Studio Code")
+    let alternate_matches = app.alternate_names.as_ref().map_or(false, |names| {
+        names.iter().any(|n| n.to_lowercase().contains(query_lower))
+    });
+
+    // Allows searching "vsc" for "Visual Studio Code"; only starts_with to avoid over-matching
+    let initialism_matches = is_initialism_query(query_lower)
+        && (initials(&app.name).starts_with(query_lower) || initials(&path_app_name).starts_with(query_lower));
+
+    name_matches
+        || path_app_name_matches
+        || alternate_matches
+        || initialism_matches
+        || pinyin.is_some_and(|pinyin| pinyin_matches(pinyin, query_lower))
+}
+
+/// Score a pinyin spelling the way a Latin name is: the full pinyin for
+/// exact, prefix and substring matches, the initials as an initialism
+fn score_pinyin(pinyin: &PinyinNames, query_lower: &str, weights: &ScoringWeights) -> ScoreBreakdown {
+    if !is_initialism_query(query_lower) {
+        return ScoreBreakdown::default();
+    }
+
+    let full_score = |matches: fn(&str, &str) -> bool, weight: f64| {
+        if pinyin.full.iter().any(|full| matches(full, query_lower)) { weight } else { 0.0 }
+    };
+    let initialism = if pinyin.initials.iter().any(|initials| initials == query_lower) {
+        weights.initialism
+    } else if pinyin.initials.iter().any(|initials| initials.starts_with(query_lower)) {
+        weights.initialism * INITIALISM_PREFIX_SHARE
+    } else {
+        0.0
+    };
+
+    ScoreBreakdown {
+        exact: full_score(|full, query| full == query, weights.exact),
+        starts_with: full_score(|full, query| full.starts_with(query), weights.starts_with),
+        contains: full_score(|full, query| full.contains(query), weights.contains),
+        initialism,
+        ..Default::default()
+    }
+}
+
+/// Calculate relevance score components for an app
+fn score_app(
+    app: &ApplicationEntry,
+    pinyin: Option<&PinyinNames>,
+    query_lower: &str,
+    weights: &ScoringWeights,
+) -> ScoreBreakdown {
+    let name_lower = app.name.to_lowercase();
+    let exact = if name_lower == query_lower { weights.exact } else { 0.0 };
+    let starts_with = if name_lower.starts_with(query_lower) { weights.starts_with } else { 0.0 };
+    let mut contains = if name_lower.contains(query_lower) { weights.contains } else { 0.0 };
+
+    // Terms apart or out of order, "code visual"
+    let terms = query_terms(query_lower);
+    if contains == 0.0 && terms.len() > 1 && terms.iter().any(|term| name_lower.contains(term.as_str())) {
+        contains = terms_breakdown(&name_lower, &terms, weights).contains;
+    }
+
+    // Check alternate names for scoring
+    let alternate_score = app.alternate_names.as_ref().map_or(0.0, |names| {
+        names.iter().fold(0.0_f64, |acc, n| {
+            let n_lower = n.to_lowercase();
+            let score = if n_lower == query_lower {
+                0.9 // Slightly less than exact name match
+            } else if n_lower.starts_with(query_lower) {
+                0.7
+            } else if n_lower.contains(query_lower) {
+                0.4
+            } else {
+                0.0
+            };
+            acc.max(score)
+        })
+    });
+
+    let initialism = if is_initialism_query(query_lower) {
+        let app_initials = initials(&app.name);
+        if app_initials == query_lower {
+            weights.initialism // Exact initialism match (e.g., "vsc" for "Visual Studio Code")
+        } else if app_initials.starts_with(query_lower) {
+            weights.initialism * INITIALISM_PREFIX_SHARE
+        } else {
+            0.0
+        }
+    } else {
+        0.0
+    };
+
+    let frequency = if app.usage_count > 0 {
+        (app.usage_count as f64).log10() * weights.frequency
+    } else {
+        0.0
+    };
+
+    // A pinyin spelling counts like the name itself
+    let spelled = pinyin.map(|pinyin| score_pinyin(pinyin, query_lower, weights)).unwrap_or_default();
+
+    ScoreBreakdown {
+        exact: exact.max(spelled.exact),
+        starts_with: starts_with.max(spelled.starts_with),
+        contains: contains.max(spelled.contains),
+        initialism: initialism.max(spelled.initialism),
+        frequency,
+        other: alternate_score,
+        ..Default::default()
+    }
+}
+
+/// An app's icon and its kind: the cached icon path or data URL when there
+/// is one, the builtin system icon for settings panes with no bundle to
+/// load an icon from
+pub fn app_icon(app: &ApplicationEntry) -> (Option<String>, Option<IconKind>) {
+    match &app.icon {
+        Some(icon) => (Some(icon.clone()), Some(IconKind::Path)),
+        None if app.source == AppSource::SettingsPane && app.app_path.is_none() => {
+            (Some(BuiltinIcon::System.name().to_string()), Some(IconKind::Builtin))
+        }
+        None => (None, None),
+    }
+}
+
+fn to_result(
+    app: &ApplicationEntry,
+    pinyin: Option<&PinyinNames>,
+    query: &ParsedQuery,
+    query_lower: &str,
+) -> SearchResultItem {
+    let breakdown = score_app(app, pinyin, query_lower, &query.weights);
+    let (icon, icon_kind) = app_icon(app);
+
+    SearchResultItem {
+        id: app.id.clone(),
+        title: app.name.clone(),
+        subtitle: app.executable_path.clone(),
+        icon,
+        icon_kind,
+        accent_color: None,
+        result_type: "app".to_string(),
+        score: breakdown.total(),
+        path: app.app_path.clone().unwrap_or_else(|| app.executable_path.clone()),
+        frequency: app.usage_count,
+        explain: query.explain.then_some(breakdown),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::app::AppSource;
+
+    fn app(id: &str, name: &str, path: &str, usage_count: u32) -> ApplicationEntry {
+        ApplicationEntry {
+            id: id.to_string(),
+            name: name.to_string(),
+            executable_path: path.to_string(),
+            app_path: None,
+            icon: None,
+            usage_count,
+            last_launched: None,
+            platform: "macos".to_string(),
+            alternate_names: None,
+            bundle_id: None,
+            source: AppSource::Installed,
+        }
+    }
+
+    fn provider() -> AppProvider {
+        let apps = vec![
+            app("1", "Visual Studio Code", "/Applications/Visual Studio Code.app/Contents/MacOS/Electron", 45),
+            app("2", "Terminal", "/System/Applications/Utilities/Terminal.app/Contents/MacOS/Terminal", 67),
+            app("3", "Code Runner", "/Applications/Code Runner.app/Contents/MacOS/Code Runner", 0),
+        ];
+        AppProvider::new(
+            Arc::new(Mutex::new(AppMonitor::from_apps(apps))),
+            Arc::new(RwLock::new(AppSettings::default())),
+        )
+    }
+
+    #[test]
+    fn test_name_match_ranks_prefix_first() {
+        let results = provider().search(&ParsedQuery::new("code"), 10);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].id, "3");
+        assert_eq!(results[1].id, "1");
+    }
+
+    #[test]
+    fn test_initialism_match() {
+        let results = provider().search(&ParsedQuery::new("vsc"), 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Visual Studio Code");
+    }
+
+    #[test]
+    fn test_partial_initialism_score() {
+        let weights = ScoringWeights::default();
+        let vscode = app("1", "Visual Studio Code", "/Applications/Visual Studio Code.app", 0);
+        assert_eq!(score_app(&vscode, None, "vsc", &weights).initialism, 0.85);
+        assert!((score_app(&vscode, None, "vs", &weights).initialism - 0.65).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_empty_query_returns_all_up_to_limit() {
+        let results = provider().search(&ParsedQuery::new(""), 2);
+        assert_eq!(results.len(), 2);
+    }
+
+    #[test]
+    fn test_excluded_apps_are_skipped() {
+        let provider = provider();
+        provider.settings.write().unwrap().excluded_apps = vec!["/System/Applications/*".to_string()];
+
+        let results = provider.search(&ParsedQuery::new(""), 10);
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.title != "Terminal"));
+    }
+
+    #[test]
+    fn test_explain_breaks_down_score() {
+        let query = ParsedQuery::new("code").with_scoring(ScoringWeights::default(), true);
+        let results = provider().search(&query, 10);
+
+        // "Code Runner": prefix and substring of the name, never launched
+        let runner = results[0].explain.as_ref().unwrap();
+        assert_eq!(runner.starts_with, 0.8);
+        assert_eq!(runner.contains, 0.5);
+        assert_eq!(runner.exact, 0.0);
+        assert_eq!(runner.frequency, 0.0);
+        assert_eq!(runner.total(), results[0].score);
+
+        // "Visual Studio Code": substring only, plus launches
+        let vscode = results[1].explain.as_ref().unwrap();
+        assert_eq!(vscode.starts_with, 0.0);
+        assert!((vscode.frequency - 45f64.log10() * 0.1).abs() < 1e-9);
+
+        // Not asked for, not attached
+        assert!(provider().search(&ParsedQuery::new("code"), 10)[0].explain.is_none());
+    }
+
+    #[test]
+    fn test_weights_change_ranking() {
+        let weights = ScoringWeights {
+            starts_with: 0.0,
+            frequency: 2.0,
+            ..Default::default()
+        };
+        let results = provider().search(&ParsedQuery::new("code").with_scoring(weights, false), 10);
+        // Usage now outweighs the prefix match
+        assert_eq!(results[0].title, "Visual Studio Code");
+    }
+
+    #[test]
+    fn test_terms_match_in_any_order_and_script() {
+        let mut music = app("4", "网易云音乐", "/Applications/NeteaseMusic.app/Contents/MacOS/NeteaseMusic", 0);
+        music.alternate_names = Some(vec!["NetEase Cloud Music".to_string()]);
+        let apps = vec![
+            app("1", "Visual Studio Code", "/Applications/Visual Studio Code.app/Contents/MacOS/Electron", 45),
+            app("2", "微信 WeChat", "/Applications/WeChat.app/Contents/MacOS/WeChat", 0),
+            app("3", "Code Runner", "/Applications/Code Runner.app/Contents/MacOS/Code Runner", 0),
+            music,
+        ];
+        let titles = |query: &str| -> Vec<String> {
+            search_apps(&apps, None, &ParsedQuery::new(query), 10).into_iter().map(|r| r.title).collect()
+        };
+
+        assert_eq!(titles("code visual"), vec!["Visual Studio Code"]);
+        assert_eq!(titles("studio  code"), vec!["Visual Studio Code"]);
+        assert_eq!(titles("微信"), vec!["微信 WeChat"]);
+        assert_eq!(titles("wechat 微信"), vec!["微信 WeChat"]);
+        assert_eq!(titles("微信wechat"), vec!["微信 WeChat"]);
+        // Terms may come from the name and an alternate name
+        assert_eq!(titles("音乐 netease"), vec!["网易云音乐"]);
+        assert!(titles("code 微信").is_empty());
+    }
+
+    #[test]
+    fn test_chinese_names_match_by_pinyin() {
+        let apps = vec![
+            app("1", "微信", "/Applications/WeChat.app/Contents/MacOS/WeChat", 0),
+            app("2", "网易云音乐", "/Applications/NeteaseMusic.app/Contents/MacOS/NeteaseMusic", 0),
+            app("3", "招商银行", "/Applications/CMB.app/Contents/MacOS/CMB", 0),
+        ];
+        let provider = AppProvider::new(
+            Arc::new(Mutex::new(AppMonitor::from_apps(apps))),
+            Arc::new(RwLock::new(AppSettings { enable_pinyin_search: true, ..Default::default() })),
+        );
+        let titles = |query: &str| -> Vec<String> {
+            provider.search(&ParsedQuery::new(query), 10).into_iter().map(|r| r.title).collect()
+        };
+
+        assert_eq!(titles("weixin"), vec!["微信"]);
+        assert_eq!(titles("wx"), vec!["微信"]);
+        assert_eq!(titles("wyy"), vec!["网易云音乐"]);
+        assert_eq!(titles("zsyh"), vec!["招商银行"]);
+        // 行 reads both hang and xing
+        assert_eq!(titles("yinhang"), vec!["招商银行"]);
+
+        provider.settings.write().unwrap().enable_pinyin_search = false;
+        assert!(titles("weixin").is_empty());
+    }
+
+    #[test]
+    fn test_no_match() {
+        assert!(provider().search(&ParsedQuery::new("xyz"), 10).is_empty());
+    }
+
+    #[test]
+    fn test_app_icon_kind() {
+        let cached = ApplicationEntry {
+            icon: Some("/cache/icons/code.png".to_string()),
+            ..app("1", "Code", "/Applications/Code.app", 0)
+        };
+        assert_eq!(app_icon(&cached), (Some("/cache/icons/code.png".to_string()), Some(IconKind::Path)));
+
+        // Settings panes without a bundle get the builtin system icon
+        let pane = ApplicationEntry {
+            source: AppSource::SettingsPane,
+            ..app("2", "Bluetooth", "x-apple.systempreferences:com.apple.Bluetooth", 0)
+        };
+        assert_eq!(app_icon(&pane), (Some("system".to_string()), Some(IconKind::Builtin)));
+        let bundled = ApplicationEntry {
+            app_path: Some("/System/Library/PreferencePanes/Bluetooth.prefPane".to_string()),
+            ..pane
+        };
+        assert_eq!(app_icon(&bundled), (None, None));
+        assert_eq!(app_icon(&app("3", "Tool", "/usr/bin/tool", 0)), (None, None));
+    }
+}
@@ -0,0 +1,252 @@
+//! Browser Search Provider
+//! Queries cached bookmarks and history
+
+use super::{ParsedQuery, SearchProvider};
+use crate::db::browser::{open_browser_db, search_browser_data, BrowserEntry};
+use crate::models::preferences::{AppSettings, ScoringWeights};
+use crate::models::search::{IconKind, ScoreBreakdown, SearchResultItem};
+use crate::services::browser_match::match_context;
+use crate::services::privacy::PrivacyMode;
+use crate::services::search::suggestions::recency_weight;
+use std::path::PathBuf;
+
+/// Opening a result from the launcher says more about what the user wants
+/// from it than a browser visit, so opens outweigh visits
+const LAUNCHER_OPEN_WEIGHT: f64 = 4.0;
+
+/// Searches the SQLite browser cache
+pub struct BrowserProvider {
+    db_path: PathBuf,
+    privacy: PrivacyMode,
+}
+
+impl BrowserProvider {
+    pub fn new(db_path: PathBuf, privacy: PrivacyMode) -> Self {
+        Self { db_path, privacy }
+    }
+}
+
+impl SearchProvider for BrowserProvider {
+    fn name(&self) -> &'static str {
+        "browser"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        if query.is_empty() {
+            return vec![];
+        }
+
+        // Privacy mode hides history but leaves bookmarks
+        let include_history = !self.privacy.is_active();
+        let entries = open_browser_db(&self.db_path)
+            .and_then(|conn| search_browser_data(&conn, &query.text, limit, include_history));
+
+        match entries {
+            Ok(entries) => {
+                let query_lower = query.text_lower();
+                let now = chrono::Utc::now().timestamp();
+                // A merged page is represented by its bookmark when it has one
+                entries
+                    .iter()
+                    .map(|m| to_result(&m.entry, query, &query_lower, now))
+                    .collect()
+            }
+            Err(e) => {
+                eprintln!("[BrowserProvider] Search error: {}", e);
+                vec![]
+            }
+        }
+    }
+
+    fn enabled(&self, settings: &AppSettings) -> bool {
+        settings.enable_browser_search
+    }
+
+    fn weight(&self) -> f64 {
+        0.7
+    }
+}
+
+fn score_entry(entry: &BrowserEntry, query_lower: &str, weights: &ScoringWeights, now: i64) -> ScoreBreakdown {
+    let title_lower = entry.title.to_lowercase();
+    let mut breakdown = ScoreBreakdown::default();
+
+    if title_lower == query_lower {
+        breakdown.exact = weights.exact;
+    } else if title_lower.starts_with(query_lower) {
+        breakdown.starts_with = weights.starts_with;
+    } else if title_lower.contains(query_lower) {
+        breakdown.contains = weights.contains;
+    } else {
+        breakdown.other = 0.3; // URL match only
+    }
+
+    // Bookmarks are deliberate, so they get a small edge over history
+    if entry.entry_type == "bookmark" {
+        breakdown.other += 0.2;
+    }
+    if entry.visit_count > 0 {
+        breakdown.frequency = (entry.visit_count as f64).log10() * weights.frequency;
+    }
+    if entry.launcher_open_count > 0 {
+        // Even long-unopened results keep an edge over the same number of visits
+        let age = now - entry.last_opened_from_launcher.unwrap_or(0);
+        breakdown.frequency += (1.0 + entry.launcher_open_count as f64).log10()
+            * LAUNCHER_OPEN_WEIGHT
+            * recency_weight(age)
+            * weights.frequency;
+    }
+
+    breakdown
+}
+
+fn to_result(entry: &BrowserEntry, query: &ParsedQuery, query_lower: &str, now: i64) -> SearchResultItem {
+    let breakdown = score_entry(entry, query_lower, &query.weights, now);
+
+    SearchResultItem {
+        id: format!("browser-{}", entry.id.unwrap_or(0)),
+        title: entry.title.clone(),
+        subtitle: entry.url.clone(),
+        icon: entry.favicon.clone(),
+        icon_kind: entry.favicon.as_ref().map(|_| IconKind::Path),
+        accent_color: entry.accent_color.clone(),
+        result_type: "browser".to_string(),
+        score: breakdown.total(),
+        path: entry.url.clone(),
+        frequency: entry.visit_count.max(0) as u32,
+        explain: query.explain.then_some(breakdown),
+        match_context: Some(match_context(&query.text, entry)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::browser_match::MatchField;
+
+    #[test]
+    fn test_privacy_mode_hides_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("browser_cache.db");
+        let conn = open_browser_db(&db_path).unwrap();
+        for (url, entry_type) in [("https://docs.test/a", "bookmark"), ("https://docs.test/b", "history")] {
+            conn.execute(
+                "INSERT INTO browser_data (url, title, browser, type, cached) VALUES (?1, 'docs', 'chrome', ?2, 0)",
+                [url, entry_type],
+            )
+            .unwrap();
+        }
+
+        let privacy = PrivacyMode::new();
+        let provider = BrowserProvider::new(db_path, privacy.clone());
+        assert_eq!(provider.search(&ParsedQuery::new("docs"), 10).len(), 2);
+
+        privacy.set(true, None, false);
+        let results = provider.search(&ParsedQuery::new("docs"), 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "https://docs.test/a");
+    }
+
+    #[test]
+    fn test_browser_provider_scores_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("browser_cache.db");
+        let conn = open_browser_db(&db_path).unwrap();
+        for (url, title, entry_type) in [
+            ("https://rust.test/", "Rust", "bookmark"),
+            ("https://rust.test/blog", "Rust Blog", "history"),
+            ("https://rust.test/book", "The Rust Book", "history"),
+            ("https://docs.test/rust/std", "Standard library", "history"),
+            ("https://docs.test/python", "Python", "history"),
+        ] {
+            conn.execute(
+                "INSERT INTO browser_data (url, title, browser, type, cached) VALUES (?1, ?2, 'chrome', ?3, 0)",
+                [url, title, entry_type],
+            )
+            .unwrap();
+        }
+
+        let provider = BrowserProvider::new(db_path, PrivacyMode::new());
+        let results = provider.search(&ParsedQuery::new("rust"), 10);
+        let score = |url: &str| results.iter().find(|r| r.path == url).unwrap().score;
+        assert_eq!(results.len(), 4);
+        // Exact title, with the bookmark's edge
+        assert!((score("https://rust.test/") - 1.2).abs() < 1e-9);
+        assert_eq!(score("https://rust.test/blog"), 0.8);
+        assert_eq!(score("https://rust.test/book"), 0.5);
+        // Matched by URL only
+        assert_eq!(score("https://docs.test/rust/std"), 0.3);
+        // Which tells it apart from the rest
+        let context = results.iter().find(|r| r.path == "https://docs.test/rust/std").unwrap();
+        let context = context.match_context.as_ref().unwrap();
+        assert_eq!((context.host.as_str(), context.field), ("docs.test", MatchField::Path));
+
+        assert!(provider.search(&ParsedQuery::new(""), 10).is_empty());
+        assert_eq!(provider.search(&ParsedQuery::new("rust"), 2).len(), 2);
+    }
+
+    #[test]
+    fn test_favicon_icon_and_accent_color() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("browser_cache.db");
+        let conn = open_browser_db(&db_path).unwrap();
+        conn.execute(
+            "INSERT INTO browser_data (url, title, favicon, browser, type, cached, accentColor)
+             VALUES ('https://docs.test/a', 'docs', '/cache/docs.png', 'chrome', 'bookmark', 0, '#1a73e8'),
+                    ('https://docs.test/b', 'docs blog', NULL, 'chrome', 'bookmark', 0, NULL)",
+            [],
+        )
+        .unwrap();
+
+        let provider = BrowserProvider::new(db_path, PrivacyMode::new());
+        let results = provider.search(&ParsedQuery::new("docs"), 10);
+        let with_favicon = results.iter().find(|r| r.path == "https://docs.test/a").unwrap();
+        assert_eq!(with_favicon.icon.as_deref(), Some("/cache/docs.png"));
+        assert_eq!(with_favicon.icon_kind, Some(IconKind::Path));
+        assert_eq!(with_favicon.accent_color.as_deref(), Some("#1a73e8"));
+        let without = results.iter().find(|r| r.path == "https://docs.test/b").unwrap();
+        assert_eq!(without.icon_kind, None);
+        assert_eq!(without.accent_color, None);
+    }
+
+    #[test]
+    fn test_launcher_opens_outweigh_visits() {
+        const NOW: i64 = 1_704_067_200;
+        const DAY: i64 = 24 * 3600;
+        let weights = ScoringWeights::default();
+        let page = |url: &str| BrowserEntry {
+            id: None,
+            url: url.to_string(),
+            title: "Rust docs".to_string(),
+            favicon: None,
+            accent_color: None,
+            browser: "chrome".to_string(),
+            entry_type: "history".to_string(),
+            visit_count: 0,
+            last_visited: None,
+            folder: None,
+            description: None,
+            host: None,
+            path: None,
+            launcher_open_count: 0,
+            last_opened_from_launcher: None,
+            cached: 0,
+        };
+        let visited = BrowserEntry { visit_count: 5, ..page("https://doc.rust-lang.org/std") };
+        let opened = BrowserEntry {
+            launcher_open_count: 5,
+            last_opened_from_launcher: Some(NOW - 60 * DAY),
+            ..page("https://doc.rust-lang.org/book")
+        };
+
+        let visited_score = score_entry(&visited, "rust", &weights, NOW);
+        let opened_score = score_entry(&opened, "rust", &weights, NOW);
+        assert!(opened_score.total() > visited_score.total());
+        let expected = 6f64.log10() * LAUNCHER_OPEN_WEIGHT * recency_weight(60 * DAY) * weights.frequency;
+        assert!((opened_score.frequency - expected).abs() < 1e-9);
+
+        // Recent opens count in full
+        let recent = BrowserEntry { last_opened_from_launcher: Some(NOW - DAY), ..opened };
+        assert!(score_entry(&recent, "rust", &weights, NOW).frequency > opened_score.frequency);
+    }
+}
@@ -0,0 +1,401 @@
+//! Search Result Cache
+//! Small LRU cache of ranked results so incremental typing doesn't re-query
+//! every provider on each keystroke
+
+use super::filters::QueryFilters;
+use super::profile::{stage, SearchProfile};
+use super::{merge_batches, source_caps, MergedResults, ParsedQuery, ProviderBatch, ProviderRegistry};
+use crate::models::preferences::AppSettings;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Maximum number of cached queries
+pub const CACHE_CAPACITY: usize = 64;
+
+/// How long a cached query stays valid
+pub const CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// Identifies a cached search
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    scope: Option<String>,
+    text: String,
+    /// Batches are stored filtered, so only a query with the same filters
+    /// can refine them
+    filters: QueryFilters,
+    sources: Option<Vec<String>>,
+    limit: usize,
+    /// Explained results carry breakdowns the plain ones don't
+    explain: bool,
+}
+
+impl CacheKey {
+    pub fn new(query: &ParsedQuery, sources: Option<&[String]>, limit: usize) -> Self {
+        let sources = sources.map(|s| {
+            let mut sorted = s.to_vec();
+            sorted.sort();
+            sorted
+        });
+
+        Self {
+            scope: query.scope.clone(),
+            text: query.text_lower(),
+            filters: query.filters.clone(),
+            sources,
+            limit,
+            explain: query.explain,
+        }
+    }
+
+    /// Whether this key is a longer query typed after `prefix`
+    fn extends(&self, prefix: &CacheKey) -> bool {
+        !prefix.text.is_empty()
+            && self.text.len() > prefix.text.len()
+            && self.text.starts_with(&prefix.text)
+            && self.scope == prefix.scope
+            && self.filters == prefix.filters
+            && self.sources == prefix.sources
+            && self.limit == prefix.limit
+            && self.explain == prefix.explain
+    }
+}
+
+/// Cache hit metrics reported by get_search_stats
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub prefix_hits: u64,
+    pub misses: u64,
+    pub invalidations: u64,
+    pub entries: usize,
+}
+
+struct CacheEntry {
+    batches: Vec<ProviderBatch>,
+    merged: MergedResults,
+    inserted: Instant,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// Least recently used first
+    order: VecDeque<CacheKey>,
+    stats: CacheStats,
+}
+
+impl CacheInner {
+    fn touch(&mut self, key: &CacheKey) {
+        self.order.retain(|k| k != key);
+        self.order.push_back(key.clone());
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        self.entries.remove(key);
+        self.order.retain(|k| k != key);
+    }
+}
+
+/// LRU + TTL cache of search results
+pub struct SearchCache {
+    capacity: usize,
+    ttl: Duration,
+    inner: Mutex<CacheInner>,
+}
+
+impl Default for SearchCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SearchCache {
+    pub fn new() -> Self {
+        Self::with_config(CACHE_CAPACITY, CACHE_TTL)
+    }
+
+    pub fn with_config(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            inner: Mutex::new(CacheInner::default()),
+        }
+    }
+
+    /// Cached results for exactly this key
+    pub fn get(&self, key: &CacheKey) -> Option<MergedResults> {
+        let mut inner = self.inner.lock().ok()?;
+
+        let expired = inner.entries.get(key)?.inserted.elapsed() > self.ttl;
+        if expired {
+            inner.remove(key);
+            return None;
+        }
+
+        inner.touch(key);
+        inner.stats.hits += 1;
+        inner.entries.get(key).map(|e| e.merged.clone())
+    }
+
+    /// Raw batches of the longest live cached query that `key` extends
+    pub fn find_prefix(&self, key: &CacheKey) -> Option<Vec<ProviderBatch>> {
+        let mut inner = self.inner.lock().ok()?;
+
+        let found = inner
+            .entries
+            .iter()
+            .filter(|(k, e)| key.extends(k) && e.inserted.elapsed() <= self.ttl)
+            .max_by_key(|(k, _)| k.text.len())
+            .map(|(_, e)| e.batches.clone());
+
+        match found {
+            Some(_) => inner.stats.prefix_hits += 1,
+            None => inner.stats.misses += 1,
+        }
+        found
+    }
+
+    pub fn insert(&self, key: CacheKey, batches: Vec<ProviderBatch>, merged: MergedResults) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+
+        inner.entries.insert(key.clone(), CacheEntry {
+            batches,
+            merged,
+            inserted: Instant::now(),
+        });
+        inner.touch(&key);
+
+        while inner.order.len() > self.capacity {
+            if let Some(oldest) = inner.order.pop_front() {
+                inner.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Drop all cached results, e.g. after the underlying data changed
+    pub fn invalidate(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.entries.clear();
+            inner.order.clear();
+            inner.stats.invalidations += 1;
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        self.inner
+            .lock()
+            .map(|inner| CacheStats {
+                entries: inner.entries.len(),
+                ..inner.stats.clone()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl ProviderRegistry {
+    /// `search`, consulting the cache first and refining a cached prefix
+    /// query's results where providers support it. Timed into `profile`,
+    /// if given.
+    pub fn search_cached(
+        &self,
+        cache: &SearchCache,
+        query: &ParsedQuery,
+        settings: &AppSettings,
+        sources: Option<&[String]>,
+        limit: usize,
+        profile: Option<&SearchProfile>,
+    ) -> MergedResults {
+        let key = CacheKey::new(query, sources, limit);
+
+        if let Some(merged) = cache.get(&key) {
+            if let Some(profile) = profile {
+                profile.cache_hit();
+            }
+            return merged;
+        }
+
+        let previous = cache.find_prefix(&key);
+        let batches = stage(profile, "providers", || {
+            self.collect_batches(query, settings, sources, limit, previous.as_deref(), profile)
+        });
+        let merged = stage(profile, "merge", || merge_batches(&batches, &source_caps(query, settings), limit));
+
+        cache.insert(key, batches, merged.clone());
+        merged
+    }
+}
+
+/// Invalidate the search cache held in SearchState, if it's been set up
+pub fn invalidate_search_cache<R: Runtime>(handle: &AppHandle<R>) {
+    if let Some(state) = handle.try_state::<crate::cmds::search::SearchState>() {
+        state.cache.invalidate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{FileProvider, SearchProvider};
+    use super::*;
+    use crate::db::files::{open_files_db, upsert_file, FileEntry};
+    use crate::models::search::SearchResultItem;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Counts calls so tests can tell cache hits from provider queries
+    struct CountingProvider {
+        calls: Arc<AtomicUsize>,
+    }
+
+    impl SearchProvider for CountingProvider {
+        fn name(&self) -> &'static str {
+            "app"
+        }
+
+        fn search(&self, query: &ParsedQuery, _limit: usize) -> Vec<SearchResultItem> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            vec![SearchResultItem {
+                id: query.text.clone(),
+                title: query.text.clone(),
+                subtitle: String::new(),
+                icon: None,
+                icon_kind: None,
+                accent_color: None,
+                result_type: "app".to_string(),
+                score: 1.0,
+                path: String::new(),
+                frequency: 0,
+                explain: None,
+                match_context: None,
+            }]
+        }
+    }
+
+    fn counting_registry() -> (ProviderRegistry, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(CountingProvider { calls: calls.clone() }));
+        (registry, calls)
+    }
+
+    #[test]
+    fn test_cache_hit_and_ttl_expiry() {
+        let (registry, calls) = counting_registry();
+        let cache = SearchCache::with_config(8, Duration::from_millis(50));
+        let settings = AppSettings::default();
+        let query = ParsedQuery::new("term");
+
+        registry.search_cached(&cache, &query, &settings, None, 10, None);
+        registry.search_cached(&cache, &ParsedQuery::new("  TERM "), &settings, None, 10, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(cache.stats().hits, 1);
+
+        std::thread::sleep(Duration::from_millis(80));
+        registry.search_cached(&cache, &query, &settings, None, 10, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_cache_evicts_least_recently_used() {
+        let (registry, calls) = counting_registry();
+        let cache = SearchCache::with_config(2, CACHE_TTL);
+        let settings = AppSettings::default();
+
+        for text in ["a", "b", "a", "c"] {
+            registry.search_cached(&cache, &ParsedQuery::new(text), &settings, None, 10, None);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.stats().entries, 2);
+
+        // "a" was used more recently than "b", so "b" was evicted
+        registry.search_cached(&cache, &ParsedQuery::new("a"), &settings, None, 10, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        registry.search_cached(&cache, &ParsedQuery::new("b"), &settings, None, 10, None);
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+
+    fn seed_files(db_path: &std::path::Path, names: &[&str]) {
+        let conn = open_files_db(db_path).unwrap();
+        for name in names {
+            upsert_file(&conn, &FileEntry {
+                id: None,
+                path: format!("/tmp/{}", name),
+                filename: name.to_string(),
+                extension: None,
+                size: 1,
+                modified: 0,
+                hidden: false,
+                indexed: 0,
+                tags: Vec::new(),
+                is_placeholder: false,
+            })
+            .unwrap();
+        }
+    }
+
+    fn file_registry(db_path: &std::path::Path) -> (ProviderRegistry, AppSettings) {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(FileProvider::new(db_path.to_path_buf())));
+        let mut settings = AppSettings::default();
+        settings.enable_file_search = true;
+        (registry, settings)
+    }
+
+    #[test]
+    fn test_invalidation_on_index_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("files_index.db");
+        seed_files(&db_path, &["report.pdf"]);
+        let (registry, settings) = file_registry(&db_path);
+        let cache = SearchCache::new();
+        let query = ParsedQuery::new("report");
+
+        assert_eq!(registry.search_cached(&cache, &query, &settings, None, 10, None).results.len(), 1);
+
+        seed_files(&db_path, &["report-2.pdf"]);
+        // Still served from cache until the index change invalidates it
+        assert_eq!(registry.search_cached(&cache, &query, &settings, None, 10, None).results.len(), 1);
+
+        cache.invalidate();
+        assert_eq!(registry.search_cached(&cache, &query, &settings, None, 10, None).results.len(), 2);
+        assert_eq!(cache.stats().invalidations, 1);
+    }
+
+    #[test]
+    fn test_prefix_filtering_matches_fresh_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("files_index.db");
+        seed_files(&db_path, &["terminal.txt", "term-notes.md", "test.rs", "Termite.conf"]);
+        let (registry, settings) = file_registry(&db_path);
+        let cache = SearchCache::new();
+
+        registry.search_cached(&cache, &ParsedQuery::new("te"), &settings, None, 10, None);
+
+        // Add a row the refined search can't see, proving it didn't re-query
+        seed_files(&db_path, &["terms.txt"]);
+        let refined = registry.search_cached(&cache, &ParsedQuery::new("term"), &settings, None, 10, None);
+        assert_eq!(cache.stats().prefix_hits, 1);
+
+        let mut refined_titles: Vec<String> = refined.results.iter().map(|r| r.title.clone()).collect();
+        refined_titles.sort();
+        assert_eq!(refined_titles, vec!["Termite.conf", "term-notes.md", "terminal.txt"]);
+
+        // Refined scores match what a fresh search of the same rows produces
+        let fresh_dir = tempfile::tempdir().unwrap();
+        let fresh_db = fresh_dir.path().join("files_index.db");
+        seed_files(&fresh_db, &["terminal.txt", "term-notes.md", "test.rs", "Termite.conf"]);
+        let (fresh_registry, _) = file_registry(&fresh_db);
+        let fresh = fresh_registry.search(&ParsedQuery::new("term"), &settings, None, 10);
+
+        let scores = |m: &MergedResults| {
+            let mut s: Vec<(String, u64)> = m.results.iter().map(|r| (r.title.clone(), r.score.to_bits())).collect();
+            s.sort();
+            s
+        };
+        assert_eq!(scores(&refined), scores(&fresh));
+    }
+}
@@ -0,0 +1,208 @@
+//! Calculator Search Provider
+//! Evaluates simple arithmetic typed into the search box
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+
+/// Evaluates arithmetic expressions such as `(1 + 2) * 3`
+pub struct CalculatorProvider;
+
+impl SearchProvider for CalculatorProvider {
+    fn name(&self) -> &'static str {
+        "calculator"
+    }
+
+    fn evaluates_whole_query(&self) -> bool {
+        true
+    }
+
+    fn search(&self, query: &ParsedQuery, _limit: usize) -> Vec<SearchResultItem> {
+        // The calculator prefix forces evaluation even for bare numbers
+        if !query.is_scoped_to(self.name()) && !looks_like_expression(&query.input) {
+            return vec![];
+        }
+
+        match evaluate(&query.input) {
+            Some(value) if value.is_finite() => {
+                let formatted = format_number(value);
+                vec![SearchResultItem {
+                    id: "calculator".to_string(),
+                    title: formatted.clone(),
+                    subtitle: query.input.clone(),
+                    icon: Some(BuiltinIcon::Calculator.name().to_string()),
+                    icon_kind: Some(IconKind::Builtin),
+                    accent_color: None,
+                    result_type: "calculator".to_string(),
+                    score: 1.0,
+                    path: formatted,
+                    frequency: 0,
+                    explain: query.explain.then(|| ScoreBreakdown::fixed(1.0)),
+                    match_context: None,
+                }]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+/// Only treat the query as math when it has a digit and an operator,
+/// so plain numbers and words don't produce a calculator result
+fn looks_like_expression(text: &str) -> bool {
+    text.chars().any(|c| c.is_ascii_digit())
+        && text.chars().any(|c| matches!(c, '+' | '-' | '*' | '/' | '%' | '^'))
+        && text
+            .chars()
+            .all(|c| c.is_ascii_digit() || c.is_whitespace() || "+-*/%^().".contains(c))
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        let s = format!("{:.10}", value);
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// Evaluate an arithmetic expression, returning None on syntax errors
+pub fn evaluate(expr: &str) -> Option<f64> {
+    let tokens: Vec<char> = expr.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut parser = Parser { tokens, pos: 0 };
+    let value = parser.expr()?;
+    if parser.pos == parser.tokens.len() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+/// Recursive-descent parser:
+///   expr   := term (('+' | '-') term)*
+///   term   := unary (('*' | '/' | '%') unary)*
+///   unary  := '-' unary | power
+///   power  := atom ('^' unary)?
+///   atom   := number | '(' expr ')'
+struct Parser {
+    tokens: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn expr(&mut self) -> Option<f64> {
+        let mut value = self.term()?;
+        while let Some(op) = self.peek() {
+            match op {
+                '+' => {
+                    self.pos += 1;
+                    value += self.term()?;
+                }
+                '-' => {
+                    self.pos += 1;
+                    value -= self.term()?;
+                }
+                _ => break,
+            }
+        }
+        Some(value)
+    }
+
+    fn term(&mut self) -> Option<f64> {
+        let mut value = self.unary()?;
+        while let Some(op) = self.peek() {
+            match op {
+                '*' => {
+                    self.pos += 1;
+                    value *= self.unary()?;
+                }
+                '/' => {
+                    self.pos += 1;
+                    value /= self.unary()?;
+                }
+                '%' => {
+                    self.pos += 1;
+                    value %= self.unary()?;
+                }
+                _ => break,
+            }
+        }
+        Some(value)
+    }
+
+    fn unary(&mut self) -> Option<f64> {
+        if self.peek() == Some('-') {
+            self.pos += 1;
+            return Some(-self.unary()?);
+        }
+        self.power()
+    }
+
+    fn power(&mut self) -> Option<f64> {
+        let base = self.atom()?;
+        if self.peek() == Some('^') {
+            self.pos += 1;
+            let exponent = self.unary()?;
+            return Some(base.powf(exponent));
+        }
+        Some(base)
+    }
+
+    fn atom(&mut self) -> Option<f64> {
+        if self.peek() == Some('(') {
+            self.pos += 1;
+            let value = self.expr()?;
+            if self.peek() != Some(')') {
+                return None;
+            }
+            self.pos += 1;
+            return Some(value);
+        }
+
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if c.is_ascii_digit() || c == '.') {
+            self.pos += 1;
+        }
+        if start == self.pos {
+            return None;
+        }
+        self.tokens[start..self.pos].iter().collect::<String>().parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate() {
+        assert_eq!(evaluate("1 + 2 * 3"), Some(7.0));
+        assert_eq!(evaluate("(1 + 2) * 3"), Some(9.0));
+        assert_eq!(evaluate("-2 ^ 2"), Some(-4.0));
+        assert_eq!(evaluate("2 ^ 3 ^ 2"), Some(512.0));
+        assert_eq!(evaluate("10 % 4"), Some(2.0));
+        assert_eq!(evaluate("1.5 * 2"), Some(3.0));
+        assert_eq!(evaluate("(1 + 2"), None);
+        assert_eq!(evaluate("1 +"), None);
+    }
+
+    #[test]
+    fn test_calculator_provider() {
+        let provider = CalculatorProvider;
+        let results = provider.search(&ParsedQuery::new("1/3"), 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "0.3333333333");
+
+        assert!(provider.search(&ParsedQuery::new("42"), 10).is_empty());
+        assert!(provider.search(&ParsedQuery::new("vs-code"), 10).is_empty());
+        assert!(provider.search(&ParsedQuery::new("1/0"), 10).is_empty());
+
+        let mut prefixes = std::collections::HashMap::new();
+        prefixes.insert("c".to_string(), "calculator".to_string());
+        let results = provider.search(&ParsedQuery::parse("c 42", &prefixes), 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "42");
+    }
+}
@@ -0,0 +1,159 @@
+//! CLI Tool Search Provider
+//! Executables on PATH for queries like "htop" or "which node". A row's
+//! path is the executable; selecting it opens a terminal running the tool,
+//! through the CLI terminal template in settings. Only the cached scan is
+//! read, so a search never waits on the file system.
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::preferences::{AppSettings, ScoringWeights};
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+use crate::services::path_tools::{PathTool, PathToolCache};
+use std::sync::{Arc, RwLock};
+
+/// Query prefix that asks for tools by name
+const WHICH_PREFIX: &str = "which ";
+
+/// Unprefixed queries shorter than this don't list tools; every short
+/// word would otherwise match a few
+const MIN_UNPREFIXED_LEN: usize = 2;
+
+/// Searches the executables on PATH
+pub struct CliProvider {
+    tools: Arc<PathToolCache>,
+    settings: Arc<RwLock<AppSettings>>,
+}
+
+impl CliProvider {
+    pub fn new(tools: Arc<PathToolCache>, settings: Arc<RwLock<AppSettings>>) -> Self {
+        Self { tools, settings }
+    }
+}
+
+impl SearchProvider for CliProvider {
+    fn name(&self) -> &'static str {
+        "cli"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        let excluded = self
+            .settings
+            .read()
+            .map(|settings| settings.cli_excluded_dirs.clone())
+            .unwrap_or_default();
+        self.tools.refresh_in_background(excluded);
+
+        // Prefixed and scoped queries also match inside names
+        let (text, prefixed) = if query.is_scoped_to(self.name()) {
+            (query.text.as_str(), true)
+        } else if let Some(text) = strip_which_prefix(&query.text) {
+            (text, true)
+        } else {
+            (query.text.as_str(), false)
+        };
+        if text.is_empty() || (!prefixed && text.chars().count() < MIN_UNPREFIXED_LEN) {
+            return vec![];
+        }
+
+        let text_lower = text.to_lowercase();
+        let tools = self.tools.tools();
+        let mut matches: Vec<(&PathTool, ScoreBreakdown)> = tools
+            .iter()
+            .filter_map(|tool| Some((tool, score_tool(tool, &text_lower, prefixed, &query.weights)?)))
+            .collect();
+
+        // Stable, so equal scores stay in name order
+        matches.sort_by(|a, b| b.1.total().total_cmp(&a.1.total()));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(tool, breakdown)| to_result(tool, breakdown, query.explain))
+            .collect()
+    }
+
+    fn weight(&self) -> f64 {
+        0.7
+    }
+}
+
+/// The tool name after "which ", None without the prefix
+fn strip_which_prefix(text: &str) -> Option<&str> {
+    let head = text.get(..WHICH_PREFIX.len())?;
+    head.eq_ignore_ascii_case(WHICH_PREFIX)
+        .then(|| text[WHICH_PREFIX.len()..].trim())
+}
+
+/// Score components for a tool, None when it doesn't match. Without a
+/// prefix only exact names and name prefixes count.
+fn score_tool(tool: &PathTool, text_lower: &str, prefixed: bool, weights: &ScoringWeights) -> Option<ScoreBreakdown> {
+    let name = tool.name.to_lowercase();
+    let mut breakdown = ScoreBreakdown::default();
+    if name == text_lower {
+        breakdown.exact = weights.exact;
+    } else if name.starts_with(text_lower) {
+        breakdown.starts_with = weights.starts_with;
+    } else if prefixed && name.contains(text_lower) {
+        breakdown.contains = weights.contains;
+    } else {
+        return None;
+    }
+    Some(breakdown)
+}
+
+fn to_result(tool: &PathTool, breakdown: ScoreBreakdown, explain: bool) -> SearchResultItem {
+    let path = tool.path.to_string_lossy().to_string();
+    SearchResultItem {
+        id: format!("cli-{}", path),
+        title: tool.name.clone(),
+        subtitle: path.clone(),
+        icon: Some(BuiltinIcon::Terminal.name().to_string()),
+        icon_kind: Some(IconKind::Builtin),
+        accent_color: None,
+        result_type: "cli".to_string(),
+        score: breakdown.total(),
+        path,
+        frequency: 0,
+        explain: explain.then_some(breakdown),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::path_tools::{parse_pathext, ExecutableRule};
+    use std::fs;
+    use std::time::Duration;
+
+    fn provider(names: &[&str]) -> (tempfile::TempDir, CliProvider) {
+        let dir = tempfile::tempdir().unwrap();
+        for name in names {
+            fs::write(dir.path().join(name), "").unwrap();
+        }
+        let cache = PathToolCache::with_rule(ExecutableRule::Extensions(parse_pathext(None)), Duration::from_secs(60));
+        cache.refresh_if_stale(dir.path().as_os_str(), &[]);
+        let settings = Arc::new(RwLock::new(AppSettings::default()));
+        (dir, CliProvider::new(Arc::new(cache), settings))
+    }
+
+    fn titles(results: &[SearchResultItem]) -> Vec<&str> {
+        results.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_tools_match_by_name() {
+        let (dir, provider) = provider(&["node.exe", "nodemon.cmd", "pnode.exe", "htop.exe"]);
+
+        let results = provider.search(&ParsedQuery::new("node"), 10);
+        assert_eq!(titles(&results), vec!["node", "nodemon"]);
+        assert_eq!(results[0].result_type, "cli");
+        assert_eq!(results[0].path, dir.path().join("node.exe").to_string_lossy());
+
+        // "which" also matches inside names
+        assert_eq!(
+            titles(&provider.search(&ParsedQuery::new("which node"), 10)),
+            vec!["node", "nodemon", "pnode"]
+        );
+        assert!(provider.search(&ParsedQuery::new("h"), 10).is_empty());
+        assert!(provider.search(&ParsedQuery::new("which "), 10).is_empty());
+    }
+}
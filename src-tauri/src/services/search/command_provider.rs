@@ -0,0 +1,45 @@
+//! Shell Command Provider
+//! Offers to run the query as a shell command when scoped with the command prefix
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::preferences::AppSettings;
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+
+/// Turns "> ls -la" into a runnable command result
+pub struct CommandProvider;
+
+impl SearchProvider for CommandProvider {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn search(&self, query: &ParsedQuery, _limit: usize) -> Vec<SearchResultItem> {
+        // Never offer to run arbitrary text unless the user asked for it
+        if !query.is_scoped_to(self.name()) || query.input.is_empty() {
+            return vec![];
+        }
+
+        vec![SearchResultItem {
+            id: "command".to_string(),
+            title: query.input.clone(),
+            subtitle: "Run in shell".to_string(),
+            icon: Some(BuiltinIcon::Terminal.name().to_string()),
+            icon_kind: Some(IconKind::Builtin),
+            accent_color: None,
+            result_type: "command".to_string(),
+            score: 1.0,
+            path: query.input.clone(),
+            frequency: 0,
+            explain: query.explain.then(|| ScoreBreakdown::fixed(1.0)),
+            match_context: None,
+        }]
+    }
+
+    fn enabled(&self, settings: &AppSettings) -> bool {
+        settings.enable_shell_commands
+    }
+
+    fn evaluates_whole_query(&self) -> bool {
+        true
+    }
+}
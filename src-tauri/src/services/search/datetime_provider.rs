@@ -0,0 +1,605 @@
+//! Date and Time Search Provider
+//! Answers world-clock and date questions inline, like the calculator:
+//! "time in tokyo", "2024-03-01 + 45 days", "days until 2025-06-01",
+//! "unix 1717171717" and "unix 2024-06-01 12:00". A result's path is the
+//! value copied when it's selected.
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+use chrono::{DateTime, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
+
+/// Candidates shown for an ambiguous or partial city name
+const MAX_CITY_CANDIDATES: usize = 3;
+
+/// Cities with their time zones. The region tells same-named cities apart;
+/// the best-known one comes first.
+const CITY_TIMEZONES: &[(&str, &str, Tz)] = &[
+    ("Adelaide", "Australia", Tz::Australia__Adelaide),
+    ("Amsterdam", "Netherlands", Tz::Europe__Amsterdam),
+    ("Anchorage", "Alaska, US", Tz::America__Anchorage),
+    ("Athens", "Greece", Tz::Europe__Athens),
+    ("Auckland", "New Zealand", Tz::Pacific__Auckland),
+    ("Bangalore", "India", Tz::Asia__Kolkata),
+    ("Bangkok", "Thailand", Tz::Asia__Bangkok),
+    ("Beijing", "China", Tz::Asia__Shanghai),
+    ("Berlin", "Germany", Tz::Europe__Berlin),
+    ("Birmingham", "UK", Tz::Europe__London),
+    ("Birmingham", "Alabama, US", Tz::America__Chicago),
+    ("Bogota", "Colombia", Tz::America__Bogota),
+    ("Boston", "US", Tz::America__New_York),
+    ("Brisbane", "Australia", Tz::Australia__Brisbane),
+    ("Buenos Aires", "Argentina", Tz::America__Argentina__Buenos_Aires),
+    ("Cairo", "Egypt", Tz::Africa__Cairo),
+    ("Chicago", "US", Tz::America__Chicago),
+    ("Delhi", "India", Tz::Asia__Kolkata),
+    ("Denver", "US", Tz::America__Denver),
+    ("Dubai", "UAE", Tz::Asia__Dubai),
+    ("Dublin", "Ireland", Tz::Europe__Dublin),
+    ("Hong Kong", "China", Tz::Asia__Hong_Kong),
+    ("Honolulu", "Hawaii, US", Tz::Pacific__Honolulu),
+    ("Hyderabad", "India", Tz::Asia__Kolkata),
+    ("Hyderabad", "Pakistan", Tz::Asia__Karachi),
+    ("Istanbul", "Turkey", Tz::Europe__Istanbul),
+    ("Jakarta", "Indonesia", Tz::Asia__Jakarta),
+    ("Johannesburg", "South Africa", Tz::Africa__Johannesburg),
+    ("Karachi", "Pakistan", Tz::Asia__Karachi),
+    ("Kathmandu", "Nepal", Tz::Asia__Kathmandu),
+    ("Kingston", "Jamaica", Tz::America__Jamaica),
+    ("Kingston", "Ontario, Canada", Tz::America__Toronto),
+    ("Kyiv", "Ukraine", Tz::Europe__Kyiv),
+    ("Lagos", "Nigeria", Tz::Africa__Lagos),
+    ("Lima", "Peru", Tz::America__Lima),
+    ("Lisbon", "Portugal", Tz::Europe__Lisbon),
+    ("London", "UK", Tz::Europe__London),
+    ("London", "Ontario, Canada", Tz::America__Toronto),
+    ("Los Angeles", "US", Tz::America__Los_Angeles),
+    ("Madrid", "Spain", Tz::Europe__Madrid),
+    ("Manila", "Philippines", Tz::Asia__Manila),
+    ("Melbourne", "Australia", Tz::Australia__Melbourne),
+    ("Mexico City", "Mexico", Tz::America__Mexico_City),
+    ("Moscow", "Russia", Tz::Europe__Moscow),
+    ("Mumbai", "India", Tz::Asia__Kolkata),
+    ("Nairobi", "Kenya", Tz::Africa__Nairobi),
+    ("New York", "US", Tz::America__New_York),
+    ("Osaka", "Japan", Tz::Asia__Tokyo),
+    ("Paris", "France", Tz::Europe__Paris),
+    ("Perth", "Australia", Tz::Australia__Perth),
+    ("Perth", "Scotland, UK", Tz::Europe__London),
+    ("Phoenix", "US", Tz::America__Phoenix),
+    ("Portland", "Oregon, US", Tz::America__Los_Angeles),
+    ("Portland", "Maine, US", Tz::America__New_York),
+    ("Rome", "Italy", Tz::Europe__Rome),
+    ("San Francisco", "US", Tz::America__Los_Angeles),
+    ("San Jose", "California, US", Tz::America__Los_Angeles),
+    ("San Jose", "Costa Rica", Tz::America__Costa_Rica),
+    ("Santiago", "Chile", Tz::America__Santiago),
+    ("Sao Paulo", "Brazil", Tz::America__Sao_Paulo),
+    ("Seattle", "US", Tz::America__Los_Angeles),
+    ("Seoul", "South Korea", Tz::Asia__Seoul),
+    ("Shanghai", "China", Tz::Asia__Shanghai),
+    ("Shenzhen", "China", Tz::Asia__Shanghai),
+    ("Singapore", "Singapore", Tz::Asia__Singapore),
+    ("Stockholm", "Sweden", Tz::Europe__Stockholm),
+    ("Sydney", "Australia", Tz::Australia__Sydney),
+    ("Sydney", "Nova Scotia, Canada", Tz::America__Halifax),
+    ("Taipei", "Taiwan", Tz::Asia__Taipei),
+    ("Tehran", "Iran", Tz::Asia__Tehran),
+    ("Tokyo", "Japan", Tz::Asia__Tokyo),
+    ("Toronto", "Canada", Tz::America__Toronto),
+    ("Valencia", "Spain", Tz::Europe__Madrid),
+    ("Valencia", "Venezuela", Tz::America__Caracas),
+    ("Vancouver", "Canada", Tz::America__Vancouver),
+    ("Warsaw", "Poland", Tz::Europe__Warsaw),
+    ("Washington", "US", Tz::America__New_York),
+    ("Zurich", "Switzerland", Tz::Europe__Zurich),
+];
+
+/// Answers date and time questions such as `time in tokyo`
+pub struct DateTimeProvider;
+
+impl SearchProvider for DateTimeProvider {
+    fn name(&self) -> &'static str {
+        "datetime"
+    }
+
+    fn evaluates_whole_query(&self) -> bool {
+        true
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        answer(&query.input, Utc::now(), &Local)
+            .into_iter()
+            .take(limit)
+            .enumerate()
+            .map(|(rank, answer)| {
+                // Candidates for an ambiguous city keep their table order
+                let score = 1.0 - rank as f64 * 0.01;
+                SearchResultItem {
+                    id: format!("datetime-{}", rank),
+                    title: answer.title,
+                    subtitle: answer.subtitle,
+                    icon: Some(answer.icon.name().to_string()),
+                    icon_kind: Some(IconKind::Builtin),
+                    accent_color: None,
+                    result_type: "datetime".to_string(),
+                    score,
+                    path: answer.copy_value,
+                    frequency: 0,
+                    explain: query.explain.then(|| ScoreBreakdown::fixed(score)),
+                    match_context: None,
+                }
+            })
+            .collect()
+    }
+}
+
+/// One answer row
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub title: String,
+    pub subtitle: String,
+    /// Copied to the clipboard when the row is selected
+    pub copy_value: String,
+    /// Clock for times and dates, conversion for unix timestamps
+    pub icon: BuiltinIcon,
+}
+
+/// Answers for `text` at `now`, with `local` as the user's time zone.
+/// Empty when the text isn't a date or time question.
+pub fn answer<Z: TimeZone>(text: &str, now: DateTime<Utc>, local: &Z) -> Vec<Answer> {
+    let text = text.trim();
+    let lower = text.to_lowercase();
+    let today = now.with_timezone(local).date_naive();
+
+    if let Some(place) = strip_time_in(&lower) {
+        return world_clock(place, now, local);
+    }
+    if let Some(rest) = lower.strip_prefix("unix") {
+        return unix_conversion(rest.trim(), now, local).into_iter().collect();
+    }
+    if let Some(rest) = lower.strip_prefix("days until ") {
+        return days_between(today, rest, "until").into_iter().collect();
+    }
+    if let Some(rest) = lower.strip_prefix("days since ") {
+        return days_between(today, rest, "since").into_iter().collect();
+    }
+    date_arithmetic(&lower, today).into_iter().collect()
+}
+
+/// The place in "time in <place>", "what time is it in <place>" or
+/// "<place> time"
+fn strip_time_in(lower: &str) -> Option<&str> {
+    let place = lower
+        .strip_prefix("what time is it in ")
+        .or_else(|| lower.strip_prefix("time in "))
+        .or_else(|| lower.strip_suffix(" time"))?
+        .trim();
+    (!place.is_empty()).then_some(place)
+}
+
+/// Time zones `place` may mean: cities named exactly that, else cities
+/// whose name starts with it, else an IANA zone name like `Europe/Berlin`
+pub fn find_timezones(place: &str) -> Vec<(String, Tz)> {
+    let place = place.trim().to_lowercase();
+    let city_matches = |matches: &dyn Fn(&str) -> bool| -> Vec<(String, Tz)> {
+        CITY_TIMEZONES
+            .iter()
+            .filter(|(city, _, _)| matches(&city.to_lowercase()))
+            .take(MAX_CITY_CANDIDATES)
+            .map(|(city, region, tz)| (format!("{}, {}", city, region), *tz))
+            .collect()
+    };
+
+    let exact = city_matches(&|city| city == place);
+    if !exact.is_empty() {
+        return exact;
+    }
+    let prefix = city_matches(&|city| city.starts_with(&place));
+    if !prefix.is_empty() {
+        return prefix;
+    }
+
+    // IANA names are case-sensitive and use underscores for spaces
+    chrono_tz::TZ_VARIANTS
+        .iter()
+        .find(|tz| tz.name().eq_ignore_ascii_case(&place.replace(' ', "_")))
+        .map(|tz| vec![(tz.name().to_string(), *tz)])
+        .unwrap_or_default()
+}
+
+fn world_clock<Z: TimeZone>(place: &str, now: DateTime<Utc>, local: &Z) -> Vec<Answer> {
+    let local_offset = local.offset_from_utc_datetime(&now.naive_utc()).fix().local_minus_utc();
+
+    find_timezones(place)
+        .into_iter()
+        .map(|(label, tz)| {
+            let there = now.with_timezone(&tz);
+            let offset = there.offset().fix();
+            Answer {
+                title: format!("{} · {}", there.format("%H:%M"), there.format("%a %-d %b")),
+                subtitle: format!(
+                    "{} · UTC{} · {}",
+                    label,
+                    offset,
+                    describe_offset_difference(offset.local_minus_utc() - local_offset)
+                ),
+                copy_value: there.format("%Y-%m-%d %H:%M %Z").to_string(),
+                icon: BuiltinIcon::Clock,
+            }
+        })
+        .collect()
+}
+
+/// "3 hours ahead", "5 hours 30 minutes behind", "same time as here"
+pub fn describe_offset_difference(seconds: i32) -> String {
+    if seconds == 0 {
+        return "same time as here".to_string();
+    }
+    let minutes = seconds.unsigned_abs() / 60;
+    let (hours, minutes) = (minutes / 60, minutes % 60);
+    let plural = |n: u32, unit: &str| format!("{} {}{}", n, unit, if n == 1 { "" } else { "s" });
+
+    let amount = match (hours, minutes) {
+        (0, m) => plural(m, "minute"),
+        (h, 0) => plural(h, "hour"),
+        (h, m) => format!("{} {}", plural(h, "hour"), plural(m, "minute")),
+    };
+    let direction = if seconds > 0 { "ahead" } else { "behind" };
+    format!("{} {}", amount, direction)
+}
+
+/// A date as typed: `YYYY-MM-DD`, `today`, `tomorrow` or `yesterday`
+fn parse_date(text: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match text.trim() {
+        "today" | "now" => Some(today),
+        "tomorrow" => today.succ_opt(),
+        "yesterday" => today.pred_opt(),
+        date => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok(),
+    }
+}
+
+/// `<date> + <n> <unit>` or `<date> - <n> <unit>`, with days, weeks,
+/// months or years
+fn date_arithmetic(lower: &str, today: NaiveDate) -> Option<Answer> {
+    // The date itself contains '-', so try each operator position until
+    // both sides parse
+    lower
+        .char_indices()
+        .filter(|(_, c)| *c == '+' || *c == '-')
+        .find_map(|(index, op)| {
+            let base_text = lower[..index].trim();
+            let base = parse_date(base_text, today)?;
+            let (amount, unit) = parse_amount(&lower[index + 1..])?;
+            let result = shift_date(base, op == '+', amount, unit)?;
+
+            Some(Answer {
+                title: result.format("%Y-%m-%d").to_string(),
+                subtitle: format!(
+                    "{} · {} {} {} {}",
+                    result.format("%A"),
+                    base_text,
+                    op,
+                    amount,
+                    unit.label(amount)
+                ),
+                copy_value: result.format("%Y-%m-%d").to_string(),
+                icon: BuiltinIcon::Clock,
+            })
+        })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateUnit {
+    Day,
+    Week,
+    Month,
+    Year,
+}
+
+impl DateUnit {
+    fn parse(text: &str) -> Option<Self> {
+        match text {
+            "d" | "day" | "days" => Some(DateUnit::Day),
+            "w" | "week" | "weeks" => Some(DateUnit::Week),
+            "m" | "month" | "months" => Some(DateUnit::Month),
+            "y" | "year" | "years" => Some(DateUnit::Year),
+            _ => None,
+        }
+    }
+
+    fn label(self, amount: u32) -> &'static str {
+        match (self, amount == 1) {
+            (DateUnit::Day, true) => "day",
+            (DateUnit::Day, false) => "days",
+            (DateUnit::Week, true) => "week",
+            (DateUnit::Week, false) => "weeks",
+            (DateUnit::Month, true) => "month",
+            (DateUnit::Month, false) => "months",
+            (DateUnit::Year, true) => "year",
+            (DateUnit::Year, false) => "years",
+        }
+    }
+}
+
+/// "45 days", "45days", "3 w"
+fn parse_amount(text: &str) -> Option<(u32, DateUnit)> {
+    let text = text.trim();
+    let digits = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let amount = text[..digits].parse().ok()?;
+    let unit = DateUnit::parse(text[digits..].trim())?;
+    Some((amount, unit))
+}
+
+/// Months and years land on the same day of the month, or the month's last
+/// day when it's shorter (Jan 31 + 1 month = Feb 29 in a leap year)
+fn shift_date(base: NaiveDate, forward: bool, amount: u32, unit: DateUnit) -> Option<NaiveDate> {
+    let days = |n: i64| {
+        let delta = Duration::try_days(n)?;
+        if forward {
+            base.checked_add_signed(delta)
+        } else {
+            base.checked_sub_signed(delta)
+        }
+    };
+    let months = |n: u32| {
+        if forward {
+            base.checked_add_months(Months::new(n))
+        } else {
+            base.checked_sub_months(Months::new(n))
+        }
+    };
+
+    match unit {
+        DateUnit::Day => days(amount as i64),
+        DateUnit::Week => days(amount as i64 * 7),
+        DateUnit::Month => months(amount),
+        DateUnit::Year => months(amount.checked_mul(12)?),
+    }
+}
+
+/// "days until 2025-06-01" and "days since 2024-01-01"
+fn days_between(today: NaiveDate, date_text: &str, direction: &str) -> Option<Answer> {
+    let date = parse_date(date_text, today)?;
+    let days = if direction == "until" {
+        (date - today).num_days()
+    } else {
+        (today - date).num_days()
+    };
+    let plural = if days.abs() == 1 { "day" } else { "days" };
+    let title = match (direction, days < 0) {
+        ("until", true) => format!("{} {} ago", -days, plural),
+        ("since", true) => format!("in {} {}", -days, plural),
+        _ => format!("{} {}", days, plural),
+    };
+
+    Some(Answer {
+        title,
+        subtitle: format!("{} {}", direction, date.format("%A, %-d %B %Y")),
+        copy_value: days.to_string(),
+        icon: BuiltinIcon::Clock,
+    })
+}
+
+/// "unix 1717171717" (seconds, or milliseconds when 13 digits) to a date,
+/// and "unix 2024-06-01 12:00" (local time) or "unix now" to a timestamp
+fn unix_conversion<Z: TimeZone>(rest: &str, now: DateTime<Utc>, local: &Z) -> Option<Answer> {
+    if rest.is_empty() || rest == "now" {
+        let timestamp = now.timestamp();
+        return Some(Answer {
+            title: timestamp.to_string(),
+            subtitle: format!("unix time now · {}", now.format("%Y-%m-%d %H:%M:%S UTC")),
+            copy_value: timestamp.to_string(),
+            icon: BuiltinIcon::Conversion,
+        });
+    }
+
+    if rest.chars().all(|c| c.is_ascii_digit()) {
+        let value: i64 = rest.parse().ok()?;
+        let utc = if rest.len() >= 13 {
+            DateTime::from_timestamp_millis(value)?
+        } else {
+            DateTime::from_timestamp(value, 0)?
+        };
+        let here = utc.with_timezone(local);
+        return Some(Answer {
+            title: here.naive_local().format("%Y-%m-%d %H:%M:%S").to_string(),
+            subtitle: format!("local time · {}", utc.format("%Y-%m-%d %H:%M:%S UTC")),
+            copy_value: here.to_rfc3339(),
+            icon: BuiltinIcon::Conversion,
+        });
+    }
+
+    let local_time = parse_date_time(rest, now.with_timezone(local).date_naive())?;
+    // Times skipped by a DST change don't exist; repeated ones take the first
+    let here = local.from_local_datetime(&local_time).earliest()?;
+    let timestamp = here.timestamp();
+    Some(Answer {
+        title: timestamp.to_string(),
+        subtitle: format!(
+            "unix time of {} (UTC{})",
+            local_time.format("%Y-%m-%d %H:%M:%S"),
+            here.offset().fix()
+        ),
+        copy_value: timestamp.to_string(),
+        icon: BuiltinIcon::Conversion,
+    })
+}
+
+/// A date with an optional `HH:MM[:SS]` time, midnight when it's left out
+fn parse_date_time(text: &str, today: NaiveDate) -> Option<NaiveDateTime> {
+    let (date_text, time_text) = match text.trim().split_once(' ') {
+        Some((date, time)) => (date, Some(time.trim())),
+        None => (text.trim(), None),
+    };
+    let date = parse_date(date_text, today)?;
+    let time = match time_text {
+        None => NaiveTime::MIN,
+        Some(time) => NaiveTime::parse_from_str(time, "%H:%M:%S")
+            .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M"))
+            .ok()?,
+    };
+    Some(date.and_time(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    fn titles(answers: &[Answer]) -> Vec<&str> {
+        answers.iter().map(|a| a.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_time_in_city() {
+        let now = at("2024-06-01T12:00:00Z");
+        let answers = answer("time in Tokyo", now, &Tz::Europe__Berlin);
+
+        assert_eq!(titles(&answers), vec!["21:00 · Sat 1 Jun"]);
+        assert_eq!(answers[0].subtitle, "Tokyo, Japan · UTC+09:00 · 7 hours ahead");
+        assert_eq!(answers[0].copy_value, "2024-06-01 21:00 JST");
+
+        // Other phrasings and half-hour offsets
+        let kathmandu = answer("kathmandu time", now, &Tz::Europe__Berlin);
+        assert!(kathmandu[0].subtitle.ends_with("3 hours 45 minutes ahead"));
+        let here = answer("what time is it in berlin", now, &Tz::Europe__Berlin);
+        assert!(here[0].subtitle.ends_with("same time as here"));
+    }
+
+    #[test]
+    fn test_ambiguous_and_partial_cities_return_candidates() {
+        let portland = find_timezones("portland");
+        assert_eq!(
+            portland,
+            vec![
+                ("Portland, Oregon, US".to_string(), Tz::America__Los_Angeles),
+                ("Portland, Maine, US".to_string(), Tz::America__New_York),
+            ]
+        );
+
+        let partial: Vec<String> = find_timezones("san").into_iter().map(|(label, _)| label).collect();
+        assert_eq!(partial.len(), MAX_CITY_CANDIDATES);
+        assert!(partial[0].starts_with("San Francisco"));
+
+        assert_eq!(
+            find_timezones("europe/lisbon"),
+            vec![("Europe/Lisbon".to_string(), Tz::Europe__Lisbon)]
+        );
+        assert!(find_timezones("atlantis").is_empty());
+        assert!(answer("time in atlantis", Utc::now(), &Utc).is_empty());
+    }
+
+    #[test]
+    fn test_world_clock_across_dst_change() {
+        let local = Tz::Europe__London;
+        // New York springs forward at 07:00 UTC on 2024-03-10; London
+        // doesn't change until 2024-03-31
+        let before = answer("time in new york", at("2024-03-10T06:59:00Z"), &local);
+        assert_eq!(before[0].title, "01:59 · Sun 10 Mar");
+        assert!(before[0].subtitle.contains("UTC-05:00 · 5 hours behind"));
+
+        let after = answer("time in new york", at("2024-03-10T07:01:00Z"), &local);
+        assert_eq!(after[0].title, "03:01 · Sun 10 Mar");
+        assert!(after[0].subtitle.contains("UTC-04:00 · 4 hours behind"));
+        assert_eq!(after[0].copy_value, "2024-03-10 03:01 EDT");
+    }
+
+    #[test]
+    fn test_date_arithmetic() {
+        let now = at("2024-06-15T10:00:00Z");
+        let shift = |query: &str| answer(query, now, &Utc).first().map(|a| a.title.clone());
+
+        assert_eq!(shift("2024-03-01 + 45 days").as_deref(), Some("2024-04-15"));
+        assert_eq!(shift("2024-03-01 - 45 days").as_deref(), Some("2024-01-16"));
+        assert_eq!(shift("2024-03-01+2w").as_deref(), Some("2024-03-15"));
+        assert_eq!(shift("2024-01-31 + 1 month").as_deref(), Some("2024-02-29"));
+        assert_eq!(shift("2024-02-29 + 1 year").as_deref(), Some("2025-02-28"));
+        assert_eq!(shift("today + 1 week").as_deref(), Some("2024-06-22"));
+
+        let answers = answer("2024-03-01 + 45 days", now, &Utc);
+        assert_eq!(answers[0].subtitle, "Monday · 2024-03-01 + 45 days");
+
+        // Not date arithmetic
+        assert!(answer("2024-03-01", now, &Utc).is_empty());
+        assert!(answer("2024-03-01 + 45 parsecs", now, &Utc).is_empty());
+        assert!(answer("visual studio", now, &Utc).is_empty());
+    }
+
+    #[test]
+    fn test_days_until_and_since() {
+        let now = at("2025-03-01T23:30:00Z");
+        // Already March 2 in Tokyo, so the count is from there
+        let tokyo = answer("days until 2025-06-01", now, &Tz::Asia__Tokyo);
+        assert_eq!(tokyo[0].title, "91 days");
+        assert_eq!(tokyo[0].copy_value, "91");
+        assert_eq!(tokyo[0].subtitle, "until Sunday, 1 June 2025");
+
+        let utc = answer("days until 2025-06-01", now, &Utc);
+        assert_eq!(utc[0].title, "92 days");
+
+        assert_eq!(answer("days until 2025-02-28", now, &Utc)[0].title, "1 day ago");
+        assert_eq!(answer("days since 2025-01-01", now, &Utc)[0].title, "59 days");
+    }
+
+    #[test]
+    fn test_unix_conversion_both_directions() {
+        let now = at("2024-06-01T00:00:00Z");
+        let local = Tz::America__New_York;
+
+        let from_seconds = answer("unix 1717171717", now, &local);
+        assert_eq!(from_seconds[0].title, "2024-05-31 12:08:37");
+        assert_eq!(from_seconds[0].subtitle, "local time · 2024-05-31 16:08:37 UTC");
+        assert_eq!(from_seconds[0].copy_value, "2024-05-31T12:08:37-04:00");
+
+        let from_millis = answer("unix 1717171717000", now, &local);
+        assert_eq!(from_millis[0].title, "2024-05-31 12:08:37");
+
+        let to_unix = answer("unix 2024-05-31 12:08:37", now, &local);
+        assert_eq!(to_unix[0].title, "1717171717");
+        assert_eq!(answer("unix now", now, &local)[0].title, "1717200000");
+
+        // Conversions get their own icon; clock answers keep the clock
+        assert_eq!(to_unix[0].icon, BuiltinIcon::Conversion);
+        assert_eq!(answer("days since 2024-01-01", now, &local)[0].icon, BuiltinIcon::Clock);
+    }
+
+    #[test]
+    fn test_unix_conversion_at_dst_boundaries() {
+        let now = at("2024-06-01T00:00:00Z");
+        let local = Tz::America__New_York;
+
+        // 02:30 doesn't exist on the spring-forward day
+        assert!(answer("unix 2024-03-10 02:30", now, &local).is_empty());
+        assert_eq!(answer("unix 2024-03-10 03:30", now, &local)[0].title, "1710055800");
+
+        // 01:30 happens twice on the fall-back day; the first (EDT) is used
+        let repeated = answer("unix 2024-11-03 01:30", now, &local);
+        assert_eq!(repeated[0].title, "1730611800");
+        assert!(repeated[0].subtitle.ends_with("(UTC-04:00)"));
+    }
+
+    #[test]
+    fn test_offset_difference_wording() {
+        assert_eq!(describe_offset_difference(3600), "1 hour ahead");
+        assert_eq!(describe_offset_difference(-19800), "5 hours 30 minutes behind");
+        assert_eq!(describe_offset_difference(-1800), "30 minutes behind");
+    }
+
+    #[test]
+    fn test_provider_rows_copy_their_value() {
+        let results = DateTimeProvider.search(&ParsedQuery::new("2024-03-01 + 45 days"), 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].result_type, "datetime");
+        assert_eq!(results[0].path, "2024-04-15");
+
+        assert_eq!(
+            DateTimeProvider.search(&ParsedQuery::new("time in portland"), 1).len(),
+            1
+        );
+        assert!(DateTimeProvider.search(&ParsedQuery::new("notes"), 10).is_empty());
+    }
+}
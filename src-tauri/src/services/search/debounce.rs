@@ -0,0 +1,152 @@
+//! Search Debounce
+//! Holds back streaming searches while the user types. The first query
+//! after a pause of search_debounce_ms runs at once; during a burst each
+//! newer query restarts the timer, and only the last one runs once typing
+//! pauses, refining what the first one showed.
+
+use serde::Serialize;
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// How debouncing treated recent queries, for the performance report
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct DebounceStats {
+    /// The setting in effect for the latest query
+    pub debounce_ms: u64,
+    /// Queries run at once, being the first after a pause
+    pub leading: u64,
+    /// Queries run once typing paused
+    pub trailing: u64,
+    /// Queries dropped for a newer one typed before they ran
+    pub coalesced: u64,
+}
+
+#[derive(Default)]
+struct DebounceState {
+    /// Newest search id seen
+    latest: u64,
+    last_query: Option<Instant>,
+    stats: DebounceStats,
+}
+
+/// Debounce for streaming searches, held in SearchState
+#[derive(Default)]
+pub struct SearchDebounce {
+    state: Mutex<DebounceState>,
+    /// Signalled when a newer query arrives
+    newer: Condvar,
+}
+
+impl SearchDebounce {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Note the query of `search_id`. Returns whether it's the first after
+    /// a pause of `delay`, to run at once; otherwise it waits in `settle`.
+    pub fn arrive(&self, search_id: u64, delay: Duration) -> bool {
+        let Ok(mut state) = self.state.lock() else {
+            return true;
+        };
+        let now = Instant::now();
+        let leading = state.last_query.is_none_or(|last| now.duration_since(last) >= delay);
+        state.last_query = Some(now);
+        state.latest = state.latest.max(search_id);
+        state.stats.debounce_ms = delay.as_millis() as u64;
+        if leading {
+            state.stats.leading += 1;
+        }
+        self.newer.notify_all();
+        leading
+    }
+
+    /// Wait out `delay` after the query of `search_id`. Returns false as
+    /// soon as a newer query arrives, in which case this one shouldn't run.
+    pub fn settle(&self, search_id: u64, delay: Duration) -> bool {
+        let Ok(state) = self.state.lock() else {
+            return true;
+        };
+        let Ok((mut state, _)) = self
+            .newer
+            .wait_timeout_while(state, delay, |state| state.latest == search_id)
+        else {
+            return true;
+        };
+        if state.latest == search_id {
+            state.stats.trailing += 1;
+            true
+        } else {
+            state.stats.coalesced += 1;
+            false
+        }
+    }
+
+    pub fn stats(&self) -> DebounceStats {
+        self.state.lock().map(|state| state.stats.clone()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread::sleep;
+
+    const DELAY: Duration = Duration::from_millis(150);
+
+    /// Feed queries `ids` 20ms apart, the way the command does, and
+    /// collect the ones that run
+    fn burst(debounce: &Arc<SearchDebounce>, ids: std::ops::Range<u64>) -> Arc<Mutex<Vec<u64>>> {
+        let dispatched = Arc::new(Mutex::new(Vec::new()));
+        let mut waiting = Vec::new();
+        for search_id in ids {
+            if debounce.arrive(search_id, DELAY) {
+                dispatched.lock().unwrap().push(search_id);
+            } else {
+                let debounce = Arc::clone(debounce);
+                let dispatched = Arc::clone(&dispatched);
+                waiting.push(std::thread::spawn(move || {
+                    if debounce.settle(search_id, DELAY) {
+                        dispatched.lock().unwrap().push(search_id);
+                    }
+                }));
+            }
+            sleep(Duration::from_millis(20));
+        }
+        for thread in waiting {
+            thread.join().unwrap();
+        }
+        dispatched
+    }
+
+    #[test]
+    fn test_burst_runs_first_and_last_query() {
+        let debounce = Arc::new(SearchDebounce::new());
+
+        // Ten queries in 200ms: the first at once, the last once typing stops
+        let start = Instant::now();
+        assert_eq!(*burst(&debounce, 1..11).lock().unwrap(), vec![1, 10]);
+        assert!(start.elapsed() >= Duration::from_millis(180) + DELAY);
+        assert_eq!(
+            debounce.stats(),
+            DebounceStats { debounce_ms: 150, leading: 1, trailing: 1, coalesced: 8 }
+        );
+
+        // After a pause the next keystroke runs without waiting
+        sleep(DELAY);
+        let start = Instant::now();
+        assert!(debounce.arrive(11, DELAY));
+        assert!(start.elapsed() < Duration::from_millis(20));
+        assert_eq!(debounce.stats().leading, 2);
+    }
+
+    #[test]
+    fn test_slow_typing_runs_every_query() {
+        let debounce = Arc::new(SearchDebounce::new());
+        for search_id in 1..4 {
+            assert!(debounce.arrive(search_id, Duration::from_millis(10)));
+            sleep(Duration::from_millis(20));
+        }
+        assert_eq!(debounce.stats().leading, 3);
+    }
+}
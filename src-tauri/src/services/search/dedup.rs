@@ -0,0 +1,196 @@
+//! Result Deduplication
+//! Folds merged results that point at the same thing: an .app found both as
+//! an app and as a file, a bookmark and its history row, a file reached from
+//! two indexed roots through a symlink.
+
+use crate::models::search::SearchResultItem;
+use crate::utils::normalize_url;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// What a result points at. Apps and files share the path namespace so an
+/// app bundle and its file index row collide.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Identity {
+    Path(PathBuf),
+    Url(String),
+}
+
+/// Canonical paths resolved during one search, so each path hits the
+/// filesystem at most once however many merges the search runs
+#[derive(Debug, Default)]
+pub struct PathCache {
+    resolved: HashMap<String, PathBuf>,
+    /// Filesystem lookups made
+    lookups: usize,
+}
+
+impl PathCache {
+    /// `path` with symlinks resolved. Paths that can't be resolved (e.g.
+    /// deleted since they were indexed) are kept as given.
+    pub fn canonicalize(&mut self, path: &str) -> PathBuf {
+        if let Some(resolved) = self.resolved.get(path) {
+            return resolved.clone();
+        }
+        self.lookups += 1;
+        let resolved = std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+        self.resolved.insert(path.to_string(), resolved.clone());
+        resolved
+    }
+}
+
+/// Identity of a result, or None for results that are never duplicates
+/// (calculator, commands, abbreviations, plugins)
+fn identity(item: &SearchResultItem, paths: &mut PathCache) -> Option<Identity> {
+    if item.path.is_empty() {
+        return None;
+    }
+    match item.result_type.as_str() {
+        "app" | "file" => Some(Identity::Path(paths.canonicalize(&item.path))),
+        "browser" => Some(Identity::Url(normalize_url(&item.path))),
+        _ => None,
+    }
+}
+
+/// Keep the larger frequency and fill in a missing icon from the folded result
+fn absorb(survivor: &mut SearchResultItem, folded: SearchResultItem) {
+    survivor.frequency = survivor.frequency.max(folded.frequency);
+    if survivor.icon.is_none() {
+        survivor.icon = folded.icon;
+    }
+}
+
+/// Fold results with the same identity into the highest-scored one (the
+/// earliest on a tie), which takes over the others' useful metadata.
+/// Survivors keep their order. Returns how many results were folded away.
+pub fn dedup_results(results: &mut Vec<SearchResultItem>, paths: &mut PathCache) -> usize {
+    let before = results.len();
+    let mut kept: Vec<SearchResultItem> = Vec::with_capacity(before);
+    let mut seen: HashMap<Identity, usize> = HashMap::new();
+
+    for item in results.drain(..) {
+        let Some(id) = identity(&item, paths) else {
+            kept.push(item);
+            continue;
+        };
+        match seen.get(&id) {
+            Some(&index) if item.score > kept[index].score => {
+                let folded = std::mem::replace(&mut kept[index], item);
+                absorb(&mut kept[index], folded);
+            }
+            Some(&index) => absorb(&mut kept[index], item),
+            None => {
+                seen.insert(id, kept.len());
+                kept.push(item);
+            }
+        }
+    }
+
+    *results = kept;
+    before - results.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn item(id: &str, result_type: &str, path: &str, score: f64) -> SearchResultItem {
+        SearchResultItem {
+            id: id.to_string(),
+            title: id.to_string(),
+            subtitle: String::new(),
+            icon: None,
+            icon_kind: None,
+            accent_color: None,
+            result_type: result_type.to_string(),
+            score,
+            path: path.to_string(),
+            frequency: 0,
+            explain: None,
+            match_context: None,
+        }
+    }
+
+    fn ids(results: &[SearchResultItem]) -> Vec<&str> {
+        results.iter().map(|r| r.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_app_and_file_fold_into_higher_score() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("Notes.app");
+        fs::create_dir(&bundle).unwrap();
+        let bundle = bundle.to_string_lossy().to_string();
+
+        let mut app = item("app-notes", "app", &bundle, 0.9);
+        app.frequency = 12;
+        app.icon = Some("notes.png".to_string());
+        let file = item("file-1", "file", &bundle, 0.95);
+        let mut results = vec![app, file, item("calc", "calculator", "", 0.5)];
+
+        let folded = dedup_results(&mut results, &mut PathCache::default());
+
+        assert_eq!(folded, 1);
+        assert_eq!(ids(&results), vec!["file-1", "calc"]);
+        // The survivor keeps the app's usage count and icon
+        assert_eq!(results[0].frequency, 12);
+        assert_eq!(results[0].icon.as_deref(), Some("notes.png"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlinked_file_folds_once_per_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let real = dir.path().join("docs").join("report.md");
+        fs::create_dir_all(real.parent().unwrap()).unwrap();
+        fs::write(&real, "x").unwrap();
+        let link = dir.path().join("linked-docs");
+        std::os::unix::fs::symlink(real.parent().unwrap(), &link).unwrap();
+        let via_link = link.join("report.md").to_string_lossy().to_string();
+        let real = real.to_string_lossy().to_string();
+
+        let mut results = vec![
+            item("file-1", "file", &via_link, 0.7),
+            item("file-2", "file", &real, 0.6),
+            // The same row seen again in a later merge of the same search
+            item("file-1", "file", &via_link, 0.5),
+        ];
+        let mut paths = PathCache::default();
+
+        assert_eq!(dedup_results(&mut results, &mut paths), 2);
+        assert_eq!(ids(&results), vec!["file-1"]);
+        assert_eq!(paths.lookups, 2);
+
+        let mut again = vec![item("file-2", "file", &real, 0.6)];
+        dedup_results(&mut again, &mut paths);
+        assert_eq!(paths.lookups, 2);
+    }
+
+    #[test]
+    fn test_bookmark_and_history_fold_by_normalized_url() {
+        let mut bookmark = item("browser-1", "browser", "https://Example.com/docs/#intro", 0.8);
+        bookmark.icon = Some("favicon.ico".to_string());
+        let mut history = item("browser-2", "browser", "https://example.com/docs?utm_source=mail", 0.8);
+        history.frequency = 40;
+        let mut results = vec![bookmark, history, item("browser-3", "browser", "https://example.com/other", 0.4)];
+
+        assert_eq!(dedup_results(&mut results, &mut PathCache::default()), 1);
+        // Equal scores keep the first
+        assert_eq!(ids(&results), vec!["browser-1", "browser-3"]);
+        assert_eq!(results[0].frequency, 40);
+        assert_eq!(results[0].icon.as_deref(), Some("favicon.ico"));
+    }
+
+    #[test]
+    fn test_results_without_identity_are_kept() {
+        let mut results = vec![
+            item("calc", "calculator", "", 1.0),
+            item("calc-2", "calculator", "", 1.0),
+            item("abbr-1", "abbreviation", "https://github.com", 0.8),
+            item("browser-1", "browser", "https://github.com", 0.7),
+        ];
+        assert_eq!(dedup_results(&mut results, &mut PathCache::default()), 0);
+        assert_eq!(results.len(), 4);
+    }
+}
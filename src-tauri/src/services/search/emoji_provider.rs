@@ -0,0 +1,311 @@
+//! Emoji Search Provider
+//! Emoji and common symbols by CLDR name or keyword, for queries like
+//! ":shrug" or "emoji heart". A row's path is the glyph to copy, in the
+//! preferred skin tone when it has tones. Picking one counts a use, and
+//! often-used emoji rank first.
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::preferences::{AppSettings, ScoringWeights, SkinTone};
+use crate::models::search::{IconKind, ScoreBreakdown, SearchResultItem};
+use crate::utils::StateStore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+/// Query prefixes that ask for emoji: ":shrug", "emoji heart"
+const EMOJI_PREFIXES: &[&str] = &[":", "emoji "];
+
+/// Variation selector that asks for emoji rather than text presentation
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+/// One emoji or symbol from assets/emoji.tsv
+#[derive(Debug, PartialEq, Eq)]
+pub struct EmojiRecord {
+    pub glyph: &'static str,
+    pub name: &'static str,
+    pub group: &'static str,
+    pub keywords: &'static [&'static str],
+    /// Whether the emoji takes a skin tone modifier
+    pub skin_tones: bool,
+}
+
+/// The emoji table, generated by build.rs from assets/emoji.tsv
+pub static EMOJI: &[EmojiRecord] = include!(concat!(env!("OUT_DIR"), "/emoji_table.rs"));
+
+impl EmojiRecord {
+    /// The glyph in `tone`. Variants are never listed separately; they're
+    /// the base emoji followed by a modifier.
+    pub fn glyph_in(&self, tone: SkinTone) -> String {
+        let Some(modifier) = tone.modifier().filter(|_| self.skin_tones) else {
+            return self.glyph.to_string();
+        };
+
+        // The modifier implies emoji presentation, so the selector goes
+        let mut chars = self.glyph.chars();
+        let base = chars.next().unwrap_or_default();
+        let rest: String = chars.filter(|c| *c != EMOJI_PRESENTATION).collect();
+        format!("{}{}{}", base, modifier, rest)
+    }
+}
+
+/// The record for `glyph`, in any skin tone and with or without the
+/// presentation selector
+pub fn find_emoji(glyph: &str) -> Option<&'static EmojiRecord> {
+    let canonical = canonical_glyph(glyph);
+    EMOJI.iter().find(|record| canonical_glyph(record.glyph) == canonical)
+}
+
+fn canonical_glyph(glyph: &str) -> String {
+    glyph
+        .chars()
+        .filter(|c| *c != EMOJI_PRESENTATION && !('\u{1F3FB}'..='\u{1F3FF}').contains(c))
+        .collect()
+}
+
+/// How often each emoji was picked, keyed by its base glyph
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EmojiUsage {
+    pub counts: HashMap<String, u32>,
+}
+
+impl EmojiUsage {
+    /// Count a use of `record`, whatever skin tone it was picked in
+    pub fn record(&mut self, record: &EmojiRecord) {
+        *self.counts.entry(record.glyph.to_string()).or_insert(0) += 1;
+    }
+
+    pub fn count(&self, record: &EmojiRecord) -> u32 {
+        self.counts.get(record.glyph).copied().unwrap_or(0)
+    }
+}
+
+/// Searches the emoji table
+pub struct EmojiProvider {
+    usage: Arc<StateStore<EmojiUsage>>,
+    settings: Arc<RwLock<AppSettings>>,
+}
+
+impl EmojiProvider {
+    pub fn new(usage: Arc<StateStore<EmojiUsage>>, settings: Arc<RwLock<AppSettings>>) -> Self {
+        Self { usage, settings }
+    }
+}
+
+impl SearchProvider for EmojiProvider {
+    fn name(&self) -> &'static str {
+        "emoji"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        let text = if query.is_scoped_to(self.name()) {
+            query.text.as_str()
+        } else {
+            match strip_emoji_prefix(&query.text) {
+                Some(text) => text,
+                None => return vec![],
+            }
+        };
+
+        let usage = self.usage.read().unwrap_or_else(|e| {
+            eprintln!("[EmojiProvider] Failed to read usage: {}", e);
+            EmojiUsage::default()
+        });
+        let tone = self.settings.read().map(|s| s.emoji_skin_tone).unwrap_or_default();
+
+        search_emoji(text, &usage, &query.weights, limit)
+            .into_iter()
+            .map(|(record, breakdown)| to_result(record, breakdown, usage.count(record), tone, query.explain))
+            .collect()
+    }
+}
+
+/// The search text after an emoji prefix, None when there's no prefix
+fn strip_emoji_prefix(text: &str) -> Option<&str> {
+    if text.eq_ignore_ascii_case("emoji") {
+        return Some("");
+    }
+    EMOJI_PREFIXES.iter().find_map(|prefix| {
+        let head = text.get(..prefix.len())?;
+        head.eq_ignore_ascii_case(prefix).then(|| text[prefix.len()..].trim())
+    })
+}
+
+/// Matching records, best first. An empty `text` lists the most used.
+pub fn search_emoji(
+    text: &str,
+    usage: &EmojiUsage,
+    weights: &ScoringWeights,
+    limit: usize,
+) -> Vec<(&'static EmojiRecord, ScoreBreakdown)> {
+    let text_lower = text.trim().to_lowercase();
+    let mut matches: Vec<_> = EMOJI
+        .iter()
+        .filter_map(|record| {
+            let breakdown = score_emoji(record, &text_lower, usage.count(record), weights)?;
+            Some((record, breakdown))
+        })
+        .collect();
+
+    // Stable, so equal scores keep the table's order
+    matches.sort_by(|a, b| b.1.total().total_cmp(&a.1.total()));
+    matches.truncate(limit);
+    matches
+}
+
+/// Score components for a record, None when it doesn't match
+fn score_emoji(record: &EmojiRecord, text_lower: &str, uses: u32, weights: &ScoringWeights) -> Option<ScoreBreakdown> {
+    let name = record.name.to_lowercase();
+    let keywords: Vec<String> = record.keywords.iter().map(|k| k.to_lowercase()).collect();
+    let mut breakdown = ScoreBreakdown::default();
+
+    if text_lower.is_empty() {
+        // Everything matches; only usage orders it
+    } else if name == text_lower || keywords.iter().any(|k| k == text_lower) {
+        breakdown.exact = weights.exact;
+    } else if name.split([' ', '-']).any(|word| word.starts_with(text_lower))
+        || keywords.iter().any(|k| k.starts_with(text_lower))
+    {
+        breakdown.starts_with = weights.starts_with;
+    } else if name.contains(text_lower) {
+        breakdown.contains = weights.contains;
+    } else if is_subsequence(text_lower, &name) {
+        // Fuzzy: the letters appear in order, "thmup" for "thumbs up"
+        breakdown.other = weights.contains * 0.5;
+    } else {
+        return None;
+    }
+
+    // log10(1 + uses), so the first use already counts
+    breakdown.frequency = (uses as f64 + 1.0).log10() * weights.frequency;
+    Some(breakdown)
+}
+
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut haystack = haystack.chars();
+    needle
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .all(|c| haystack.any(|h| h == c))
+}
+
+fn to_result(
+    record: &EmojiRecord,
+    breakdown: ScoreBreakdown,
+    uses: u32,
+    tone: SkinTone,
+    explain: bool,
+) -> SearchResultItem {
+    let glyph = record.glyph_in(tone);
+    SearchResultItem {
+        id: format!("emoji-{}", record.glyph),
+        title: record.name.to_string(),
+        subtitle: record.group.to_string(),
+        icon: Some(glyph.clone()),
+        icon_kind: Some(IconKind::Emoji),
+        accent_color: None,
+        result_type: "emoji".to_string(),
+        score: breakdown.total(),
+        path: glyph,
+        frequency: uses,
+        explain: explain.then_some(breakdown),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider(dir: &std::path::Path) -> EmojiProvider {
+        EmojiProvider::new(
+            Arc::new(StateStore::new(dir.join("emoji_usage.json"), "emoji usage")),
+            Arc::new(RwLock::new(AppSettings::default())),
+        )
+    }
+
+    fn names(results: &[SearchResultItem]) -> Vec<&str> {
+        results.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_table_is_generated() {
+        assert!(EMOJI.len() > 200);
+        assert!(EMOJI.iter().all(|r| !r.glyph.is_empty() && !r.name.is_empty()));
+        let arrow = find_emoji("→").unwrap();
+        assert_eq!(arrow.name, "rightwards arrow");
+        assert_eq!(arrow.group, "Symbols");
+    }
+
+    #[test]
+    fn test_keyword_and_name_matching() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = provider(dir.path());
+
+        // "shrug" is a keyword of "person shrugging"
+        let shrug = provider.search(&ParsedQuery::new(":shrug"), 5);
+        assert_eq!(shrug[0].title, "person shrugging");
+        assert_eq!(shrug[0].path, "🤷");
+        assert_eq!(shrug[0].icon.as_deref(), Some("🤷"));
+        assert_eq!(shrug[0].result_type, "emoji");
+
+        let hearts = provider.search(&ParsedQuery::new("emoji heart"), 50);
+        assert!(names(&hearts).contains(&"red heart"));
+        assert!(names(&hearts).contains(&"broken heart"));
+
+        // Symbols by name, fuzzy matches last
+        assert_eq!(provider.search(&ParsedQuery::new(":em dash"), 1)[0].path, "—");
+        assert_eq!(provider.search(&ParsedQuery::new(":thmbup"), 1)[0].title, "thumbs up");
+
+        // No prefix, no emoji
+        assert!(provider.search(&ParsedQuery::new("heart"), 5).is_empty());
+        assert!(provider.search(&ParsedQuery::new(":zzzxq"), 5).is_empty());
+    }
+
+    #[test]
+    fn test_usage_ranks_emoji_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = provider(dir.path());
+        let query = ParsedQuery::new(":heart");
+
+        let before = provider.search(&query, 20);
+        assert_eq!(before[0].title, "red heart");
+        let purple = before.iter().position(|r| r.title == "purple heart").unwrap();
+        assert!(purple > 0);
+
+        provider
+            .usage
+            .update(|usage| {
+                usage.record(find_emoji("💜").unwrap());
+                Ok::<_, String>(())
+            })
+            .unwrap();
+
+        let after = provider.search(&query, 20);
+        assert_eq!(after[0].title, "purple heart");
+        assert!(after[0].score > before[purple].score);
+
+        // The most used lead an empty query
+        assert_eq!(provider.search(&ParsedQuery::new(":"), 3)[0].title, "purple heart");
+    }
+
+    #[test]
+    fn test_skin_tone_variants_share_the_base_emoji() {
+        let dir = tempfile::tempdir().unwrap();
+        let provider = provider(dir.path());
+        provider.settings.write().unwrap().emoji_skin_tone = SkinTone::Medium;
+
+        let thumbs = provider.search(&ParsedQuery::new(":thumbs up"), 1);
+        assert_eq!(thumbs[0].path, "👍🏽");
+        // Emoji without tones are left alone
+        assert_eq!(provider.search(&ParsedQuery::new(":rocket"), 1)[0].path, "🚀");
+
+        let victory = find_emoji("✌️").unwrap();
+        assert_eq!(victory.glyph_in(SkinTone::Dark), "✌🏿");
+        assert_eq!(victory.glyph_in(SkinTone::Neutral), "✌️");
+
+        // A use in any tone counts for the base emoji
+        assert_eq!(find_emoji("✌🏿"), Some(victory));
+        assert_eq!(find_emoji("👍🏻").map(|r| r.name), Some("thumbs up"));
+        assert_eq!(find_emoji("not an emoji"), None);
+    }
+}
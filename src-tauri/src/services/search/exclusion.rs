@@ -0,0 +1,119 @@
+//! App Exclusions
+//! Matches applications against the `excluded_apps` setting
+
+use crate::models::app::ApplicationEntry;
+
+/// Whether an app matches any exclusion pattern.
+///
+/// Patterns are compared against the bundle id, app path and executable path.
+/// A pattern without wildcards must match exactly; `*` matches any run of
+/// characters (including `/`) and `?` matches one character. Matching is
+/// case-insensitive.
+pub fn is_app_excluded(app: &ApplicationEntry, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| app_matches_pattern(app, pattern))
+}
+
+/// Whether an app matches a single exclusion pattern
+pub fn app_matches_pattern(app: &ApplicationEntry, pattern: &str) -> bool {
+    let pattern = pattern.trim();
+    if pattern.is_empty() {
+        return false;
+    }
+
+    [app.bundle_id.as_deref(), app.app_path.as_deref(), Some(app.executable_path.as_str())]
+        .into_iter()
+        .flatten()
+        .any(|candidate| glob_match(pattern, candidate))
+}
+
+/// Drop excluded apps from a list
+pub fn filter_excluded(apps: Vec<ApplicationEntry>, patterns: &[String]) -> Vec<ApplicationEntry> {
+    if patterns.is_empty() {
+        return apps;
+    }
+    apps.into_iter().filter(|app| !is_app_excluded(app, patterns)).collect()
+}
+
+/// Case-insensitive glob match supporting `*` and `?`
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text index it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = backtrack {
+            // Let the last `*` absorb one more character
+            p = star_p + 1;
+            t = star_t + 1;
+            backtrack = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::app::AppSource;
+
+    fn app(bundle_id: Option<&str>, app_path: &str) -> ApplicationEntry {
+        ApplicationEntry {
+            id: "1".to_string(),
+            name: "Test".to_string(),
+            executable_path: format!("{}/Contents/MacOS/Test", app_path),
+            app_path: Some(app_path.to_string()),
+            icon: None,
+            usage_count: 0,
+            last_launched: None,
+            platform: "macos".to_string(),
+            alternate_names: None,
+            bundle_id: bundle_id.map(|s| s.to_string()),
+            source: AppSource::Installed,
+        }
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("/System/Library/*", "/System/Library/CoreServices/Finder.app"));
+        assert!(glob_match("com.apple.*", "com.apple.Safari"));
+        assert!(glob_match("*Helper?.app", "/Applications/Foo Helper2.app"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+        assert!(!glob_match("/System/Library/*", "/Applications/Safari.app"));
+    }
+
+    #[test]
+    fn test_exact_bundle_id_exclusion() {
+        let safari = app(Some("com.apple.Safari"), "/Applications/Safari.app");
+        let preview = app(Some("com.apple.Preview"), "/Applications/Preview.app");
+        let patterns = vec!["com.apple.Safari".to_string()];
+
+        assert!(is_app_excluded(&safari, &patterns));
+        assert!(!is_app_excluded(&preview, &patterns));
+        // Exact patterns don't match substrings
+        assert!(!is_app_excluded(&safari, &["com.apple".to_string()]));
+    }
+
+    #[test]
+    fn test_glob_path_exclusion() {
+        let finder = app(Some("com.apple.finder"), "/System/Library/CoreServices/Finder.app");
+        let code = app(None, "/Applications/Visual Studio Code.app");
+        let patterns = vec!["/System/Library/*".to_string()];
+
+        let kept = filter_excluded(vec![finder, code], &patterns);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].app_path.as_deref(), Some("/Applications/Visual Studio Code.app"));
+    }
+}
@@ -0,0 +1,194 @@
+//! File Search Provider
+//! Queries the local file index database
+
+use super::terms::{query_terms, terms_breakdown};
+use super::{ParsedQuery, SearchProvider};
+use crate::db::files::{open_files_db, parse_tag_filters, search_files, FileEntry};
+use crate::models::preferences::{AppSettings, ScoringWeights};
+use crate::models::search::{ScoreBreakdown, SearchResultItem};
+use std::path::PathBuf;
+
+/// Searches the SQLite file index
+pub struct FileProvider {
+    db_path: PathBuf,
+}
+
+impl FileProvider {
+    pub fn new(db_path: PathBuf) -> Self {
+        Self { db_path }
+    }
+}
+
+impl SearchProvider for FileProvider {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        if query.is_empty() {
+            return vec![];
+        }
+
+        let entries = open_files_db(&self.db_path)
+            .and_then(|conn| search_files(&conn, &query.text, limit));
+
+        match entries {
+            Ok(entries) => {
+                // Tag filters aren't part of the name
+                let (query_lower, _) = parse_tag_filters(&query.text_lower());
+                entries.iter().map(|e| to_result(e, query, &query_lower)).collect()
+            }
+            Err(e) => {
+                eprintln!("[FileProvider] Search error: {}", e);
+                vec![]
+            }
+        }
+    }
+
+    fn enabled(&self, settings: &AppSettings) -> bool {
+        settings.enable_file_search
+    }
+
+    fn weight(&self) -> f64 {
+        0.8
+    }
+
+    fn refine(&self, previous: &[SearchResultItem], query: &ParsedQuery) -> Option<Vec<SearchResultItem>> {
+        // LIKE treats these as wildcards, which a substring filter can't mirror.
+        // Earlier results of one term miss files with only a later term in
+        // the filename.
+        if query.is_empty() || query.text.contains(['%', '_']) || query.terms().len() > 1 {
+            return None;
+        }
+
+        // SQLite LIKE only folds ASCII case
+        let needle = query.text.to_ascii_lowercase();
+        let query_lower = query.text_lower();
+
+        Some(
+            previous
+                .iter()
+                .filter(|item| item.title.to_ascii_lowercase().contains(&needle))
+                .map(|item| {
+                    let breakdown = score_filename(&item.title, &query_lower, &query.weights);
+                    SearchResultItem {
+                        score: breakdown.total(),
+                        explain: query.explain.then_some(breakdown),
+                        ..item.clone()
+                    }
+                })
+                .collect(),
+        )
+    }
+}
+
+fn score_filename(filename: &str, query_lower: &str, weights: &ScoringWeights) -> ScoreBreakdown {
+    let filename_lower = filename.to_lowercase();
+    let stem_lower = filename_lower
+        .rsplit_once('.')
+        .map(|(stem, _)| stem.to_string())
+        .unwrap_or_else(|| filename_lower.clone());
+
+    let terms = query_terms(query_lower);
+
+    let mut breakdown = ScoreBreakdown::default();
+    if filename_lower == query_lower || stem_lower == query_lower {
+        breakdown.exact = weights.exact;
+    } else if filename_lower.starts_with(query_lower) {
+        breakdown.starts_with = weights.starts_with;
+    } else if filename_lower.contains(query_lower) {
+        breakdown.contains = weights.contains;
+    } else if terms.len() > 1 {
+        // Matched term by term, or only in the folder
+        breakdown = terms_breakdown(&filename_lower, &terms, weights);
+    } else {
+        breakdown.other = 0.1;
+    }
+    breakdown
+}
+
+fn to_result(entry: &FileEntry, query: &ParsedQuery, query_lower: &str) -> SearchResultItem {
+    let breakdown = score_filename(&entry.filename, query_lower, &query.weights);
+
+    SearchResultItem {
+        id: format!("file-{}", entry.id.unwrap_or(0)),
+        title: entry.filename.clone(),
+        subtitle: entry.path.clone(),
+        icon: None,
+        icon_kind: None,
+        accent_color: None,
+        result_type: "file".to_string(),
+        score: breakdown.total(),
+        path: entry.path.clone(),
+        frequency: 0,
+        explain: query.explain.then_some(breakdown),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::files::upsert_file;
+
+    fn seed(db_path: &std::path::Path, names: &[&str]) {
+        let conn = open_files_db(db_path).unwrap();
+        for name in names {
+            upsert_file(&conn, &FileEntry {
+                id: None,
+                path: format!("/tmp/{}", name),
+                filename: name.to_string(),
+                extension: name.rsplit_once('.').map(|(_, ext)| ext.to_string()),
+                size: 1,
+                modified: 0,
+                hidden: false,
+                indexed: 0,
+                tags: Vec::new(),
+                is_placeholder: false,
+            }).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_file_provider_scores_matches() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("files_index.db");
+        seed(&db_path, &["readme.md", "my-readme-notes.txt", "main.rs"]);
+
+        let provider = FileProvider::new(db_path);
+        let mut results = provider.search(&ParsedQuery::new("readme"), 10);
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].title, "readme.md");
+        assert_eq!(results[0].score, 1.0);
+        assert_eq!(results[1].score, 0.5);
+    }
+
+    #[test]
+    fn test_file_provider_scores_terms_by_tightness() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("files_index.db");
+        seed(&db_path, &["budget q3.xlsx", "q3 final budget.xlsx", "budget-q3-2024.xlsx", "budget.xlsx"]);
+
+        let provider = FileProvider::new(db_path);
+        let mut results = provider.search(&ParsedQuery::new("budget q3"), 10);
+        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap());
+
+        let titles: Vec<&str> = results.iter().map(|r| r.title.as_str()).collect();
+        assert_eq!(titles, vec!["budget q3.xlsx", "budget-q3-2024.xlsx", "q3 final budget.xlsx"]);
+        // The query as typed is the stem, which outranks term-by-term matches
+        assert_eq!(results[0].score, 1.0);
+        assert!(results[1].score > results[2].score);
+        assert!(provider.refine(&results, &ParsedQuery::new("budget q3 x")).is_none());
+    }
+
+    #[test]
+    fn test_file_provider_gated_by_setting() {
+        let provider = FileProvider::new(PathBuf::from("/nonexistent/files_index.db"));
+        let mut settings = AppSettings::default();
+        assert!(!provider.enabled(&settings));
+        settings.enable_file_search = true;
+        assert!(provider.enabled(&settings));
+    }
+}
@@ -0,0 +1,278 @@
+//! Query Filters
+//! Exclusions and `kind:`/`ext:` filters typed into a query. "-draft" drops
+//! results whose name or path contains "draft", -"old version" does the
+//! same for a phrase, "ext:pdf" keeps only PDFs and "-kind:file" drops
+//! files. They're taken out of the text providers search for and applied
+//! to every provider's results before they're merged and scored. A dash
+//! that's quoted ("-5") or escaped (\-5) is text, as is a lone "-".
+
+use crate::models::search::SearchResultItem;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+
+const KIND_PREFIX: &str = "kind:";
+const EXT_PREFIX: &str = "ext:";
+
+/// One condition on a result. Serialized for the frontend, which applies
+/// the same conditions to the plugin results it adds.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Filter {
+    /// The result's name or path contains this, lowercase
+    Text(String),
+    /// The result's type, e.g. "file"
+    Kind(String),
+    /// The extension of the result's path, lowercase without the dot
+    Ext(String),
+}
+
+impl Filter {
+    pub fn matches(&self, item: &SearchResultItem) -> bool {
+        match self {
+            Self::Text(text) => {
+                item.title.to_lowercase().contains(text.as_str()) || item.path.to_lowercase().contains(text.as_str())
+            }
+            Self::Kind(kind) => item.result_type.eq_ignore_ascii_case(kind),
+            Self::Ext(ext) => Path::new(&item.path)
+                .extension()
+                .is_some_and(|extension| extension.to_string_lossy().eq_ignore_ascii_case(ext)),
+        }
+    }
+}
+
+/// Written back the way it's typed, e.g. "-ext:log"
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Text(text) if text.contains(char::is_whitespace) => write!(f, "\"{}\"", text),
+            Self::Text(text) => write!(f, "{}", text),
+            Self::Kind(kind) => write!(f, "{}{}", KIND_PREFIX, kind),
+            Self::Ext(ext) => write!(f, "{}{}", EXT_PREFIX, ext),
+        }
+    }
+}
+
+/// Filters of one query
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize)]
+pub struct QueryFilters {
+    /// `kind:` and `ext:` filters a result must all match
+    pub required: Vec<Filter>,
+    /// A result matching any of these is dropped
+    pub excluded: Vec<Filter>,
+}
+
+impl QueryFilters {
+    pub fn is_empty(&self) -> bool {
+        self.required.is_empty() && self.excluded.is_empty()
+    }
+
+    /// Why the query drops `item`, e.g. "-draft", or None if it's kept
+    pub fn rejection(&self, item: &SearchResultItem) -> Option<String> {
+        if let Some(filter) = self.excluded.iter().find(|filter| filter.matches(item)) {
+            return Some(format!("-{}", filter));
+        }
+        self.required.iter().find(|filter| !filter.matches(item)).map(|filter| filter.to_string())
+    }
+
+    /// Drop the results the query filters out
+    pub fn apply(&self, results: &mut Vec<SearchResultItem>) {
+        if !self.is_empty() {
+            results.retain(|item| self.rejection(item).is_none());
+        }
+    }
+}
+
+/// A whitespace-separated piece of the query
+struct Token {
+    text: String,
+    /// Written in quotes, so never a filter
+    quoted: bool,
+    /// The token started with a dash outside quotes
+    negated: bool,
+    /// The token started with an escaped dash
+    escaped: bool,
+}
+
+/// Split the query into tokens. Quotes group words and may follow a dash,
+/// a backslash before a leading dash makes it text.
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut chars = text.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(first) = chars.next() else {
+            break;
+        };
+
+        let mut token = Token { text: String::new(), quoted: false, negated: false, escaped: false };
+        let mut next = Some(first);
+        if first == '\\' && chars.peek() == Some(&'-') {
+            token.escaped = true;
+            token.text.extend(chars.next());
+            next = chars.next_if(|c| !c.is_whitespace());
+        } else if first == '-' && chars.peek().is_some_and(|c| !c.is_whitespace()) {
+            token.negated = true;
+            next = chars.next();
+        }
+
+        if next == Some('"') {
+            token.quoted = true;
+            for c in chars.by_ref() {
+                if c == '"' {
+                    break;
+                }
+                token.text.push(c);
+            }
+            // Anything glued to the closing quote belongs to the token
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.text.push(c);
+            }
+        } else if let Some(c) = next {
+            token.text.push(c);
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                token.text.push(c);
+            }
+        }
+        tokens.push(token);
+    }
+    tokens
+}
+
+/// `kind:<type>` or `ext:<extension>`, when `token` is one
+fn parse_filter(token: &str) -> Option<Filter> {
+    let value = |prefix: &str| {
+        token
+            .get(..prefix.len())
+            .filter(|start| start.eq_ignore_ascii_case(prefix))
+            .map(|_| token[prefix.len()..].to_lowercase())
+            .filter(|value| !value.is_empty())
+    };
+    value(KIND_PREFIX)
+        .map(Filter::Kind)
+        .or_else(|| value(EXT_PREFIX).map(|ext| Filter::Ext(ext.trim_start_matches('.').to_string())))
+}
+
+/// Split `text` into what providers search for and the query's filters.
+/// Text without filters, quotes or escapes comes back unchanged.
+pub fn parse_filters(text: &str) -> (String, QueryFilters) {
+    let tokens = tokenize(text);
+    let plain = tokens
+        .iter()
+        .all(|token| !token.quoted && !token.negated && !token.escaped && parse_filter(&token.text).is_none());
+    if plain {
+        return (text.to_string(), QueryFilters::default());
+    }
+
+    let mut words = Vec::new();
+    let mut filters = QueryFilters::default();
+    for token in tokens {
+        let filter = (!token.quoted).then(|| parse_filter(&token.text)).flatten();
+        match (token.negated, filter) {
+            (true, Some(filter)) => filters.excluded.push(filter),
+            (true, None) if !token.text.trim().is_empty() => {
+                filters.excluded.push(Filter::Text(token.text.trim().to_lowercase()))
+            }
+            (true, None) => {}
+            (false, Some(filter)) => filters.required.push(filter),
+            (false, None) => words.push(token.text),
+        }
+    }
+    (words.join(" "), filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> Filter {
+        Filter::Text(value.to_string())
+    }
+
+    fn item(title: &str, path: &str, result_type: &str) -> SearchResultItem {
+        SearchResultItem {
+            id: path.to_string(),
+            title: title.to_string(),
+            subtitle: String::new(),
+            icon: None,
+            icon_kind: None,
+            accent_color: None,
+            result_type: result_type.to_string(),
+            score: 1.0,
+            path: path.to_string(),
+            frequency: 0,
+            explain: None,
+            match_context: None,
+        }
+    }
+
+    #[test]
+    fn test_negated_terms_and_phrases() {
+        let (rest, filters) = parse_filters("report -Draft -2019");
+        assert_eq!(rest, "report");
+        assert_eq!(filters.excluded, vec![text("draft"), text("2019")]);
+
+        let (rest, filters) = parse_filters("notes -\"old version\" final");
+        assert_eq!(rest, "notes final");
+        assert_eq!(filters.excluded, vec![text("old version")]);
+        assert!(filters.required.is_empty());
+    }
+
+    #[test]
+    fn test_escaped_and_bare_dashes_are_text() {
+        let (rest, filters) = parse_filters("\"-old version\" \\-5 a - b");
+        assert_eq!(rest, "-old version -5 a - b");
+        assert!(filters.is_empty());
+
+        // Dashes inside words and plain text are left alone
+        assert_eq!(parse_filters("vs-code  1 - 2").0, "vs-code  1 - 2");
+        assert!(parse_filters("vs-code").1.is_empty());
+    }
+
+    #[test]
+    fn test_filters_combine_with_negation() {
+        let (rest, filters) = parse_filters("server ext:LOG -kind:browser -ext:.gz -\"kind:file\"");
+        assert_eq!(rest, "server");
+        assert_eq!(filters.required, vec![Filter::Ext("log".to_string())]);
+        assert_eq!(
+            filters.excluded,
+            vec![Filter::Kind("browser".to_string()), Filter::Ext("gz".to_string()), text("kind:file")]
+        );
+
+        // A filter with no value is text
+        assert_eq!(parse_filters("ext: kind:").0, "ext: kind:");
+    }
+
+    #[test]
+    fn test_rejection_names_the_filter() {
+        let (_, filters) = parse_filters("report -draft -ext:log kind:file");
+        let kept = item("report.pdf", "/docs/report.pdf", "file");
+        assert_eq!(filters.rejection(&kept), None);
+        assert_eq!(
+            filters.rejection(&item("report.pdf", "/drafts/report.pdf", "file")).as_deref(),
+            Some("-draft")
+        );
+        assert_eq!(
+            filters.rejection(&item("report.log", "/var/report.log", "file")).as_deref(),
+            Some("-ext:log")
+        );
+        assert_eq!(filters.rejection(&item("Report", "https://report.test", "browser")).as_deref(), Some("kind:file"));
+
+        let mut results = vec![kept, item("Draft report", "/a/report.pdf", "file")];
+        filters.apply(&mut results);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].path, "/docs/report.pdf");
+    }
+
+    #[test]
+    fn test_filters_serialize_for_the_frontend() {
+        let (_, filters) = parse_filters("report -draft ext:pdf");
+        assert_eq!(
+            serde_json::to_value(&filters).unwrap(),
+            serde_json::json!({
+                "required": [{ "type": "ext", "value": "pdf" }],
+                "excluded": [{ "type": "text", "value": "draft" }],
+            })
+        );
+    }
+}
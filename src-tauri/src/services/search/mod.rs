@@ -0,0 +1,826 @@
+//! Search Provider Framework
+//! Unifies apps, files, browser data, abbreviations, calculator, dates,
+//! emoji, processes, CLI tools and shell history behind a single
+//! `SearchProvider` trait, plus the merge/rank stage that combines them
+
+pub mod abbreviation_provider;
+pub mod app_provider;
+pub mod browser_provider;
+pub mod cache;
+pub mod calculator_provider;
+pub mod cli_provider;
+pub mod command_provider;
+pub mod datetime_provider;
+pub mod debounce;
+pub mod dedup;
+pub mod emoji_provider;
+pub mod exclusion;
+pub mod file_provider;
+pub mod filters;
+pub mod pinyin;
+pub mod process_provider;
+pub mod profile;
+pub mod shell_history_provider;
+pub mod ssh_provider;
+pub mod streaming;
+pub mod suggestions;
+pub mod terms;
+
+pub use abbreviation_provider::AbbreviationProvider;
+pub use app_provider::AppProvider;
+pub use browser_provider::BrowserProvider;
+pub use cache::SearchCache;
+pub use calculator_provider::CalculatorProvider;
+pub use cli_provider::CliProvider;
+pub use command_provider::CommandProvider;
+pub use datetime_provider::DateTimeProvider;
+pub use debounce::SearchDebounce;
+pub use emoji_provider::EmojiProvider;
+pub use file_provider::FileProvider;
+pub use process_provider::ProcessProvider;
+pub use profile::{SearchPerformance, SearchProfile, SearchTimings};
+pub use shell_history_provider::ShellHistoryProvider;
+pub use ssh_provider::SshProvider;
+pub use streaming::{CancellationToken, PageError, ResultsPage, RetainedResults, SearchCancellation};
+
+use crate::models::preferences::{AppSettings, ScoringWeights};
+use crate::models::search::SearchResultItem;
+use dedup::{dedup_results, PathCache};
+use filters::QueryFilters;
+use std::collections::HashMap;
+
+/// Escape character that disables prefix parsing, e.g. "\f stop"
+const PREFIX_ESCAPE: char = '\\';
+
+/// A query as seen by providers
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedQuery {
+    /// Query exactly as typed
+    pub raw: String,
+    /// Text providers should match against, with any scope prefix and the
+    /// query's filters removed
+    pub text: String,
+    /// Text after the scope prefix with its filters left in, for providers
+    /// that evaluate the query as a whole, like the calculator
+    pub input: String,
+    /// Exclusions and kind:/ext: filters applied to every provider's results
+    pub filters: QueryFilters,
+    /// Provider the query is scoped to, if it started with a known prefix
+    pub scope: Option<String>,
+    /// Weights providers score matches with
+    pub weights: ScoringWeights,
+    /// Attach a score breakdown to each result
+    pub explain: bool,
+}
+
+impl ParsedQuery {
+    /// Unscoped query
+    pub fn new(raw: &str) -> Self {
+        Self::with_input(raw, raw.trim(), None)
+    }
+
+    /// `raw` as the text `input`, scoped to `scope`, with filters split out
+    fn with_input(raw: &str, input: &str, scope: Option<String>) -> Self {
+        let (text, filters) = filters::parse_filters(input);
+        Self {
+            raw: raw.to_string(),
+            text,
+            input: input.to_string(),
+            filters,
+            scope,
+            weights: ScoringWeights::default(),
+            explain: false,
+        }
+    }
+
+    /// Parse a query, stripping a leading scope token found in `prefixes`.
+    ///
+    /// The token must be followed by whitespace ("f readme"), so "freadme" or a
+    /// bare "f" are ordinary queries. Tokens not in `prefixes` fall through to a
+    /// normal search. A leading backslash escapes a prefix: "\f stop" searches
+    /// for the literal text "f stop".
+    pub fn parse(raw: &str, prefixes: &HashMap<String, String>) -> Self {
+        let trimmed = raw.trim();
+
+        if let Some(escaped) = trimmed.strip_prefix(PREFIX_ESCAPE) {
+            if split_prefix(escaped, prefixes).is_some() {
+                return Self::with_input(raw, escaped, None);
+            }
+        }
+
+        match split_prefix(trimmed, prefixes) {
+            Some((source, rest)) => Self::with_input(raw, rest.trim(), Some(source.to_string())),
+            None => Self::new(raw),
+        }
+    }
+
+    /// Score with `weights`, explaining each result's score if `explain`
+    pub fn with_scoring(self, weights: ScoringWeights, explain: bool) -> Self {
+        Self { weights, explain, ..self }
+    }
+
+    /// Lowercased match text
+    pub fn text_lower(&self) -> String {
+        self.text.to_lowercase()
+    }
+
+    /// Lowercased terms that must all match, see `terms::query_terms`
+    pub fn terms(&self) -> Vec<String> {
+        terms::query_terms(&self.text_lower())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Whether the query is scoped to the given provider
+    pub fn is_scoped_to(&self, name: &str) -> bool {
+        self.scope.as_deref() == Some(name)
+    }
+}
+
+/// Split "token rest" when `token` is a configured prefix, returning the mapped source
+fn split_prefix<'a>(text: &'a str, prefixes: &'a HashMap<String, String>) -> Option<(&'a str, &'a str)> {
+    let (token, rest) = text.split_once(char::is_whitespace)?;
+    prefixes.get(token).map(|source| (source.as_str(), rest))
+}
+
+/// A source of search results
+pub trait SearchProvider: Send + Sync {
+    /// Provider name, also used as the `sources` filter value
+    fn name(&self) -> &'static str;
+
+    /// Return up to `limit` results for the query, with provider-local scores
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem>;
+
+    /// Whether the provider should run under the current settings
+    fn enabled(&self, _settings: &AppSettings) -> bool {
+        true
+    }
+
+    /// Relative weight applied after score normalization
+    fn weight(&self) -> f64 {
+        1.0
+    }
+
+    /// Whether the provider answers `ParsedQuery::input` as a whole, like
+    /// a calculation, so the query's filters don't apply to its results
+    fn evaluates_whole_query(&self) -> bool {
+        false
+    }
+
+    /// Narrow the complete results of an earlier query that `query` extends
+    /// (e.g. "te" -> "ter") without re-querying. Return None when the provider
+    /// can't do this exactly.
+    fn refine(&self, _previous: &[SearchResultItem], _query: &ParsedQuery) -> Option<Vec<SearchResultItem>> {
+        None
+    }
+}
+
+/// Raw results from one provider for one query
+#[derive(Debug, Clone)]
+pub struct ProviderBatch {
+    pub source: &'static str,
+    pub weight: f64,
+    pub results: Vec<SearchResultItem>,
+    /// The provider returned every match, not just the first `fetch` ones
+    pub complete: bool,
+}
+
+/// Registry of search providers held in SearchState
+#[derive(Default)]
+pub struct ProviderRegistry {
+    providers: Vec<Box<dyn SearchProvider>>,
+}
+
+impl ProviderRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a provider
+    pub fn register(&mut self, provider: Box<dyn SearchProvider>) {
+        self.providers.push(provider);
+    }
+
+    /// Get a provider by name
+    pub fn get(&self, name: &str) -> Option<&dyn SearchProvider> {
+        self.providers
+            .iter()
+            .find(|p| p.name() == name)
+            .map(|p| p.as_ref())
+    }
+
+    /// Names of all registered providers
+    pub fn names(&self) -> Vec<&'static str> {
+        self.providers.iter().map(|p| p.name()).collect()
+    }
+
+    /// Providers that are enabled and match the query scope and optional source filter
+    pub fn active<'a>(
+        &'a self,
+        query: &ParsedQuery,
+        settings: &AppSettings,
+        sources: Option<&[String]>,
+    ) -> Vec<&'a dyn SearchProvider> {
+        self.providers
+            .iter()
+            .map(|p| p.as_ref())
+            .filter(|p| p.enabled(settings))
+            .filter(|p| query.scope.as_deref().map_or(true, |scope| scope == p.name()))
+            .filter(|p| sources.map_or(true, |s| s.iter().any(|name| name == p.name())))
+            .collect()
+    }
+
+    /// Run all active providers and merge their results.
+    ///
+    /// Per-source caps from settings apply unless the query is scoped to a
+    /// single provider, in which case only the global limit applies.
+    pub fn search(
+        &self,
+        query: &ParsedQuery,
+        settings: &AppSettings,
+        sources: Option<&[String]>,
+        limit: usize,
+    ) -> MergedResults {
+        let batches = self.collect_batches(query, settings, sources, limit, None, None);
+        merge_batches(&batches, &source_caps(query, settings), limit)
+    }
+
+    /// Run active providers, reusing `previous` batches where a provider can
+    /// refine them instead of searching again. Each run is timed into
+    /// `profile`, if given.
+    pub fn collect_batches(
+        &self,
+        query: &ParsedQuery,
+        settings: &AppSettings,
+        sources: Option<&[String]>,
+        limit: usize,
+        previous: Option<&[ProviderBatch]>,
+        profile: Option<&SearchProfile>,
+    ) -> Vec<ProviderBatch> {
+        let caps = source_caps(query, settings);
+
+        self.active(query, settings, sources)
+            .into_iter()
+            .map(|provider| {
+                let fetch = fetch_limit(&caps, provider.name(), limit);
+                let start = std::time::Instant::now();
+
+                let refined = previous
+                    .and_then(|batches| {
+                        batches.iter().find(|b| b.source == provider.name() && b.complete)
+                    })
+                    .and_then(|batch| provider.refine(&batch.results, query));
+
+                let was_refined = refined.is_some();
+                let FilteredResults { results, returned, complete } = match refined {
+                    Some(mut results) => {
+                        results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+                        results.truncate(fetch);
+                        // Whether the provider ran out, before filters drop any
+                        let complete = results.len() < fetch;
+                        let returned = results.len();
+                        apply_filters(query, provider, &mut results);
+                        FilteredResults { results, returned, complete }
+                    }
+                    None => search_filtered(provider, query, fetch),
+                };
+                if let Some(profile) = profile {
+                    profile.record_provider(provider.name(), start.elapsed(), returned, was_refined);
+                }
+
+                ProviderBatch {
+                    source: provider.name(),
+                    weight: provider.weight(),
+                    complete,
+                    results,
+                }
+            })
+            .collect()
+    }
+
+    /// Page through a single provider's results, skipping `offset` items
+    pub fn search_source(
+        &self,
+        query: &ParsedQuery,
+        settings: &AppSettings,
+        source: &str,
+        offset: usize,
+        page_size: usize,
+    ) -> Result<MergedResults, String> {
+        let provider = self
+            .get(source)
+            .ok_or_else(|| format!("Unknown search source: {}", source))?;
+
+        if !provider.enabled(settings) {
+            return Ok(MergedResults::default());
+        }
+
+        let FilteredResults { results: mut batch, complete, .. } =
+            search_filtered(provider, query, offset + page_size + 1);
+        batch.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        // Past the overfetch cap there may be more that filters would keep
+        let has_more = batch.len() > offset + page_size || !complete;
+
+        let page: Vec<SearchResultItem> = batch.into_iter().skip(offset).take(page_size).collect();
+
+        let mut merged = MergedResults::default();
+        merged.groups.insert(source.to_string(), page.len());
+        merged.has_more_per_group.insert(source.to_string(), has_more);
+        merged.results = page;
+        Ok(merged)
+    }
+}
+
+/// Drop the results of `provider` that the query's filters exclude
+pub fn apply_filters(query: &ParsedQuery, provider: &dyn SearchProvider, results: &mut Vec<SearchResultItem>) {
+    if !provider.evaluates_whole_query() {
+        query.filters.apply(results);
+    }
+}
+
+/// Most a filtered search asks a provider for, as a multiple of the
+/// results it wants
+const MAX_FILTER_OVERFETCH: usize = 8;
+
+/// A provider's results with the query's filters applied
+pub struct FilteredResults {
+    pub results: Vec<SearchResultItem>,
+    /// Results the provider returned over every fetch, before filtering
+    pub returned: usize,
+    /// Whether the provider ran out of results
+    pub complete: bool,
+}
+
+/// Ask `provider` for `wanted` results that pass the query's filters. A
+/// filter can drop most of a batch, which would undercount what's left,
+/// so it asks again for twice as many until enough are kept, the provider
+/// runs out or `MAX_FILTER_OVERFETCH` is reached. Keeps the best `wanted`.
+pub fn search_filtered(provider: &dyn SearchProvider, query: &ParsedQuery, wanted: usize) -> FilteredResults {
+    let mut fetch = wanted;
+    let mut returned = 0;
+    loop {
+        let mut results = provider.search(query, fetch);
+        returned += results.len();
+        let complete = results.len() < fetch;
+        apply_filters(query, provider, &mut results);
+        if results.len() >= wanted || complete || fetch >= wanted.saturating_mul(MAX_FILTER_OVERFETCH) {
+            if results.len() > wanted {
+                results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+                results.truncate(wanted);
+            }
+            return FilteredResults { results, returned, complete };
+        }
+        fetch = fetch.saturating_mul(2);
+    }
+}
+
+/// Per-source caps for a query; scoped queries are only bound by the global limit
+pub fn source_caps(query: &ParsedQuery, settings: &AppSettings) -> HashMap<String, usize> {
+    if query.scope.is_some() {
+        HashMap::new()
+    } else {
+        settings.source_limits.clone()
+    }
+}
+
+/// How many results to ask a provider for: its cap (or the global limit) plus
+/// one extra so overflow can be detected
+pub fn fetch_limit(caps: &HashMap<String, usize>, source: &str, limit: usize) -> usize {
+    caps.get(source).map_or(limit, |cap| (*cap).min(limit)) + 1
+}
+
+/// Ranked results plus per-type group summaries
+#[derive(Debug, Clone, Default)]
+pub struct MergedResults {
+    /// Flat list, score-descending across all groups
+    pub results: Vec<SearchResultItem>,
+    /// Number of returned results per result_type
+    pub groups: HashMap<String, usize>,
+    /// Whether a result_type had results cut by its cap or the global limit
+    pub has_more_per_group: HashMap<String, bool>,
+    /// Results folded into another provider's result for the same app, file or page
+    pub duplicates_folded: usize,
+}
+
+/// Merge provider batches without consuming them
+pub fn merge_batches(batches: &[ProviderBatch], caps: &HashMap<String, usize>, limit: usize) -> MergedResults {
+    let batches = batches
+        .iter()
+        .map(|batch| (batch.weight, batch.results.clone()))
+        .collect();
+    merge_results(batches, caps, limit)
+}
+
+/// Merge per-provider batches into one ranked list.
+///
+/// Scores are normalized per batch (divided by the batch maximum) so that
+/// providers with different scoring scales are comparable, then multiplied by
+/// the provider weight. Each result_type is then sorted and cut to its cap in
+/// `caps` before the global `limit` is applied. Ties keep provider
+/// registration order.
+pub fn merge_results(
+    batches: Vec<(f64, Vec<SearchResultItem>)>,
+    caps: &HashMap<String, usize>,
+    limit: usize,
+) -> MergedResults {
+    merge_results_with(batches, caps, limit, &mut PathCache::default())
+}
+
+/// `merge_results`, resolving paths for deduplication through `paths` so a
+/// search that merges several times canonicalizes each path once
+pub fn merge_results_with(
+    batches: Vec<(f64, Vec<SearchResultItem>)>,
+    caps: &HashMap<String, usize>,
+    limit: usize,
+    paths: &mut PathCache,
+) -> MergedResults {
+    let mut weighted: Vec<SearchResultItem> = Vec::new();
+
+    for (weight, batch) in batches {
+        let max_score = batch.iter().map(|r| r.score).fold(0.0_f64, f64::max);
+
+        for mut item in batch {
+            let normalized = if max_score > 0.0 { item.score / max_score } else { 0.0 };
+            if let Some(breakdown) = item.explain.as_mut() {
+                breakdown.raw = item.score;
+                breakdown.normalized = normalized;
+                breakdown.provider_weight = weight;
+            }
+            item.score = normalized * weight;
+            weighted.push(item);
+        }
+    }
+
+    // Fold duplicates before caps so they don't use up a group's slots
+    let mut merged = MergedResults {
+        duplicates_folded: dedup_results(&mut weighted, paths),
+        ..Default::default()
+    };
+
+    let mut grouped: Vec<(String, Vec<SearchResultItem>)> = Vec::new();
+    for item in weighted {
+        match grouped.iter_mut().find(|(t, _)| *t == item.result_type) {
+            Some((_, group)) => group.push(item),
+            None => grouped.push((item.result_type.clone(), vec![item])),
+        }
+    }
+
+    for (result_type, mut group) in grouped {
+        group.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+        if let Some(cap) = caps.get(&result_type) {
+            if group.len() > *cap {
+                group.truncate(*cap);
+                merged.has_more_per_group.insert(result_type.clone(), true);
+            }
+        }
+        merged.results.extend(group);
+    }
+
+    merged.results.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
+    for dropped in merged.results.iter().skip(limit) {
+        merged.has_more_per_group.insert(dropped.result_type.clone(), true);
+    }
+    merged.results.truncate(limit);
+
+    for item in &merged.results {
+        *merged.groups.entry(item.result_type.clone()).or_insert(0) += 1;
+        merged.has_more_per_group.entry(item.result_type.clone()).or_insert(false);
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::search::ScoreBreakdown;
+
+    struct FakeProvider {
+        name: &'static str,
+        scores: Vec<f64>,
+        weight: f64,
+    }
+
+    impl SearchProvider for FakeProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn search(&self, _query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+            self.scores
+                .iter()
+                .enumerate()
+                .take(limit)
+                .map(|(i, score)| item(&format!("{}-{}", self.name, i), self.name, *score))
+                .collect()
+        }
+
+        fn enabled(&self, settings: &AppSettings) -> bool {
+            self.name != "file" || settings.enable_file_search
+        }
+
+        fn weight(&self) -> f64 {
+            self.weight
+        }
+    }
+
+    fn item(id: &str, result_type: &str, score: f64) -> SearchResultItem {
+        SearchResultItem {
+            id: id.to_string(),
+            title: id.to_string(),
+            subtitle: String::new(),
+            icon: None,
+            result_type: result_type.to_string(),
+            icon_kind: None,
+            accent_color: None,
+            score,
+            path: String::new(),
+            frequency: 0,
+            explain: None,
+            match_context: None,
+        }
+    }
+
+    fn registry() -> ProviderRegistry {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(FakeProvider { name: "app", scores: vec![3.0, 1.5], weight: 1.0 }));
+        registry.register(Box::new(FakeProvider { name: "file", scores: vec![0.5, 0.25], weight: 0.5 }));
+        registry
+    }
+
+    #[test]
+    fn test_merge_normalizes_scores() {
+        let merged = merge_results(
+            vec![
+                (1.0, vec![item("a", "app", 3.0), item("b", "app", 1.5)]),
+                (1.0, vec![item("f", "file", 0.5)]),
+            ],
+            &HashMap::new(),
+            10,
+        )
+        .results;
+
+        // Top of each batch normalizes to 1.0
+        assert_eq!(merged[0].score, 1.0);
+        assert_eq!(merged[1].score, 1.0);
+        assert_eq!(merged[2].id, "b");
+        assert_eq!(merged[2].score, 0.5);
+    }
+
+    #[test]
+    fn test_registry_respects_settings_gate() {
+        let registry = registry();
+        let mut settings = AppSettings::default();
+
+        settings.enable_file_search = false;
+        let results = registry.search(&ParsedQuery::new("x"), &settings, None, 10).results;
+        assert!(results.iter().all(|r| r.result_type == "app"));
+
+        settings.enable_file_search = true;
+        let results = registry.search(&ParsedQuery::new("x"), &settings, None, 10).results;
+        assert_eq!(results.len(), 4);
+        // Weighted file results rank below equally-normalized app results
+        assert_eq!(results[0].result_type, "app");
+        assert_eq!(results[1].result_type, "app");
+    }
+
+    #[test]
+    fn test_registry_source_filter() {
+        let registry = registry();
+        let mut settings = AppSettings::default();
+        settings.enable_file_search = true;
+
+        let sources = vec!["file".to_string()];
+        let results = registry.search(&ParsedQuery::new("x"), &settings, Some(&sources), 10).results;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.result_type == "file"));
+    }
+
+    #[test]
+    fn test_registry_drops_filtered_results() {
+        let registry = registry();
+        let mut settings = AppSettings::default();
+        settings.enable_file_search = true;
+
+        let query = ParsedQuery::new("x -APP-1 -kind:file");
+        assert_eq!(query.text, "x");
+        let results = registry.search(&query, &settings, None, 10).results;
+        let ids: Vec<&str> = results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["app-0"]);
+    }
+
+    #[test]
+    fn test_registry_scope_runs_only_scoped_provider() {
+        let registry = registry();
+        let mut settings = AppSettings::default();
+        settings.enable_file_search = true;
+
+        let query = ParsedQuery::parse("f x", &settings.query_prefixes);
+        let results = registry.search(&query, &settings, None, 10).results;
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.result_type == "file"));
+    }
+
+    #[test]
+    fn test_merge_enforces_caps_and_keeps_global_order() {
+        let files: Vec<SearchResultItem> = (0..20)
+            .map(|i| item(&format!("f{}", i), "file", 20.0 - i as f64))
+            .collect();
+        let apps = vec![item("a0", "app", 2.0), item("a1", "app", 1.0)];
+
+        let mut caps = HashMap::new();
+        caps.insert("file".to_string(), 10);
+        caps.insert("app".to_string(), 8);
+
+        let merged = merge_results(vec![(1.0, apps), (1.0, files)], &caps, 50);
+
+        assert_eq!(merged.groups["file"], 10);
+        assert_eq!(merged.groups["app"], 2);
+        assert!(merged.has_more_per_group["file"]);
+        assert!(!merged.has_more_per_group["app"]);
+        // The cap keeps the best-scoring files, not the first ones returned
+        assert!(merged.results.iter().any(|r| r.id == "f0"));
+        assert!(!merged.results.iter().any(|r| r.id == "f10"));
+        assert!(merged.results.windows(2).all(|w| w[0].score >= w[1].score));
+    }
+
+    #[test]
+    fn test_merge_global_limit_marks_overflow() {
+        let apps = vec![item("a0", "app", 3.0), item("a1", "app", 2.0), item("a2", "app", 1.0)];
+        let merged = merge_results(vec![(1.0, apps)], &HashMap::new(), 2);
+
+        assert_eq!(merged.results.len(), 2);
+        assert_eq!(merged.groups["app"], 2);
+        assert!(merged.has_more_per_group["app"]);
+    }
+
+    #[test]
+    fn test_merge_completes_score_breakdowns() {
+        let mut explained = item("a0", "app", 2.0);
+        explained.explain = Some(ScoreBreakdown {
+            exact: 1.0,
+            other: 1.0,
+            ..Default::default()
+        });
+        let merged = merge_results(vec![(0.5, vec![explained, item("a1", "app", 4.0)])], &HashMap::new(), 10);
+
+        let breakdown = merged.results[1].explain.as_ref().unwrap();
+        assert_eq!(breakdown.raw, 2.0);
+        assert_eq!(breakdown.normalized, 0.5);
+        assert_eq!(breakdown.provider_weight, 0.5);
+        assert_eq!(merged.results[1].score, 0.25);
+        assert!(merged.results[0].explain.is_none());
+    }
+
+    #[test]
+    fn test_merge_folds_duplicates_before_caps() {
+        let mut app = item("app-notes", "app", 2.0);
+        app.path = "/Applications/Notes.app".to_string();
+        let mut file = item("file-notes", "file", 3.0);
+        file.path = app.path.clone();
+        let files = vec![file, item("f1", "file", 2.0), item("f2", "file", 1.0)];
+
+        let mut caps = HashMap::new();
+        caps.insert("file".to_string(), 3);
+
+        // Weighted, the app outscores its file index row
+        let merged = merge_results(vec![(1.0, vec![app]), (0.5, files)], &caps, 50);
+
+        assert_eq!(merged.duplicates_folded, 1);
+        assert_eq!(merged.groups["app"], 1);
+        assert_eq!(merged.groups["file"], 2);
+        assert!(!merged.has_more_per_group["file"]);
+        assert_eq!(merged.results[0].id, "app-notes");
+    }
+
+    #[test]
+    fn test_search_source_pages() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(FakeProvider {
+            name: "app",
+            scores: vec![5.0, 4.0, 3.0, 2.0, 1.0],
+            weight: 1.0,
+        }));
+        let settings = AppSettings::default();
+        let query = ParsedQuery::new("x");
+
+        let page = registry.search_source(&query, &settings, "app", 2, 2).unwrap();
+        let ids: Vec<&str> = page.results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["app-2", "app-3"]);
+        assert!(page.has_more_per_group["app"]);
+
+        let last = registry.search_source(&query, &settings, "app", 4, 2).unwrap();
+        assert_eq!(last.results.len(), 1);
+        assert!(!last.has_more_per_group["app"]);
+
+        assert!(registry.search_source(&query, &settings, "nope", 0, 2).is_err());
+    }
+
+    #[test]
+    fn test_search_source_fetches_past_filtered_results() {
+        let mut registry = ProviderRegistry::new();
+        registry.register(Box::new(FakeProvider {
+            name: "app",
+            scores: (0..12).map(|i| 12.0 - i as f64).collect(),
+            weight: 1.0,
+        }));
+        let settings = AppSettings::default();
+        // Drops app-1, app-10 and app-11
+        let query = ParsedQuery::new("x -app-1");
+
+        let page = registry.search_source(&query, &settings, "app", 0, 8).unwrap();
+        assert_eq!(page.results.len(), 8);
+        assert!(page.results.iter().all(|r| !r.id.starts_with("app-1")));
+        assert!(page.has_more_per_group["app"]);
+
+        let last = registry.search_source(&query, &settings, "app", 8, 8).unwrap();
+        let ids: Vec<&str> = last.results.iter().map(|r| r.id.as_str()).collect();
+        assert_eq!(ids, vec!["app-9"]);
+        assert!(!last.has_more_per_group["app"]);
+    }
+
+    fn prefixes() -> HashMap<String, String> {
+        AppSettings::default().query_prefixes
+    }
+
+    #[test]
+    fn test_parse_default_prefixes() {
+        let cases = [
+            ("f readme", "file", "readme"),
+            ("b github", "browser", "github"),
+            ("c 2+2", "calculator", "2+2"),
+            ("> ls -la", "command", "ls -la"),
+            ("  f   spaced out  ", "file", "spaced out"),
+        ];
+
+        for (raw, scope, input) in cases {
+            let query = ParsedQuery::parse(raw, &prefixes());
+            assert_eq!(query.scope.as_deref(), Some(scope), "{}", raw);
+            assert_eq!(query.input, input, "{}", raw);
+            assert_eq!(query.raw, raw);
+        }
+    }
+
+    #[test]
+    fn test_parse_requires_explicit_prefix() {
+        for raw in ["freadme", "f", "x stop", "github", ">ls"] {
+            let query = ParsedQuery::parse(raw, &prefixes());
+            assert_eq!(query.scope, None, "{}", raw);
+            assert_eq!(query.text, raw);
+        }
+
+        // A prefix removed from the mapping is an ordinary query again
+        let mut custom = prefixes();
+        custom.remove("f");
+        let query = ParsedQuery::parse("f stop", &custom);
+        assert_eq!(query.scope, None);
+        assert_eq!(query.text, "f stop");
+    }
+
+    #[test]
+    fn test_parse_escape() {
+        let query = ParsedQuery::parse("\\f stop", &prefixes());
+        assert_eq!(query.scope, None);
+        assert_eq!(query.text, "f stop");
+
+        // Backslashes that don't precede a prefix are left alone
+        let query = ParsedQuery::parse("\\\\server\\share", &prefixes());
+        assert_eq!(query.scope, None);
+        assert_eq!(query.text, "\\\\server\\share");
+    }
+
+    #[test]
+    fn test_parse_unicode() {
+        let mut custom = prefixes();
+        custom.insert("文件".to_string(), "file".to_string());
+
+        let query = ParsedQuery::parse("文件 报告.pdf", &custom);
+        assert_eq!(query.scope.as_deref(), Some("file"));
+        assert_eq!(query.text, "报告.pdf");
+
+        // Full-width space separates the prefix too
+        let query = ParsedQuery::parse("f\u{3000}café", &custom);
+        assert_eq!(query.scope.as_deref(), Some("file"));
+        assert_eq!(query.text, "café");
+
+        let query = ParsedQuery::parse("café crème", &custom);
+        assert_eq!(query.scope, None);
+        assert_eq!(query.text, "café crème");
+    }
+
+    #[test]
+    fn test_parse_filters_after_prefix() {
+        let query = ParsedQuery::parse("f report -draft ext:pdf", &prefixes());
+        assert_eq!(query.scope.as_deref(), Some("file"));
+        assert_eq!(query.text, "report");
+        assert_eq!(query.filters.excluded, vec![filters::Filter::Text("draft".to_string())]);
+        assert_eq!(query.filters.required, vec![filters::Filter::Ext("pdf".to_string())]);
+
+        // Whole-query providers still see what was typed
+        let query = ParsedQuery::parse("> ls -la", &prefixes());
+        assert_eq!(query.input, "ls -la");
+        assert_eq!(CalculatorProvider.search(&ParsedQuery::new("10 -3"), 1)[0].title, "7");
+    }
+}
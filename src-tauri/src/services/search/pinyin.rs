@@ -0,0 +1,220 @@
+//! Pinyin Matching
+//! Chinese names matched by their pinyin, so "weixin" or "wx" finds 微信.
+//! A character with several readings (行 is xing or hang) matches with any
+//! of them. Names with more than MAX_VARIANTS combinations of readings keep
+//! the most common reading of each character plus each other reading on its
+//! own. Latin letters and digits in a name pass through as typed.
+
+use crate::models::preferences::AppSettings;
+use pinyin::{ToPinyin, ToPinyinMulti};
+use std::collections::HashMap;
+
+/// Most reading combinations kept per name
+const MAX_VARIANTS: usize = 32;
+
+/// Pinyin spellings of a name, lowercase and without tones or spaces
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PinyinNames {
+    /// Full pinyin, "weixin"
+    pub full: Vec<String>,
+    /// First letter of each syllable or word, "wx"
+    pub initials: Vec<String>,
+}
+
+/// Pinyin of app names by app id
+pub type PinyinIndex = HashMap<String, PinyinNames>;
+
+/// Whether app names are matched by pinyin: asked for, or implied by a
+/// Chinese interface language
+pub fn pinyin_search_enabled(settings: &AppSettings) -> bool {
+    settings.enable_pinyin_search || settings.language.starts_with("zh")
+}
+
+/// A run of a name: a Han character's readings or a Latin word
+enum Part {
+    Han(Vec<&'static str>),
+    Word(String),
+}
+
+fn parts(name: &str) -> Vec<Part> {
+    let mut parts = Vec::new();
+    let mut word = String::new();
+
+    for c in name.chars() {
+        if let Some(multi) = c.to_pinyin_multi() {
+            if !word.is_empty() {
+                parts.push(Part::Word(std::mem::take(&mut word)));
+            }
+            let mut readings: Vec<&'static str> = Vec::new();
+            for reading in multi.into_iter().map(|pinyin| pinyin.plain()) {
+                if !readings.contains(&reading) {
+                    readings.push(reading);
+                }
+            }
+            parts.push(Part::Han(readings));
+        } else if c.is_alphanumeric() {
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            parts.push(Part::Word(std::mem::take(&mut word)));
+        }
+    }
+    if !word.is_empty() {
+        parts.push(Part::Word(word));
+    }
+    parts
+}
+
+/// Which reading each part is spelled with, one list per spelling. Every
+/// reading of every character is in at least one of them.
+fn reading_choices(parts: &[Part]) -> Vec<Vec<usize>> {
+    let counts: Vec<usize> = parts
+        .iter()
+        .map(|part| match part {
+            Part::Han(readings) => readings.len(),
+            Part::Word(_) => 1,
+        })
+        .collect();
+
+    let combinations = counts.iter().try_fold(1usize, |total, &count| total.checked_mul(count));
+    if combinations.is_some_and(|total| total <= MAX_VARIANTS) {
+        let mut choices = vec![Vec::new()];
+        for &count in &counts {
+            choices = choices
+                .into_iter()
+                .flat_map(|choice: Vec<usize>| {
+                    (0..count).map(move |reading| {
+                        let mut choice = choice.clone();
+                        choice.push(reading);
+                        choice
+                    })
+                })
+                .collect();
+        }
+        return choices;
+    }
+
+    // Too many to list: the most common readings, then each other reading
+    // swapped in on its own
+    let mut choices = vec![vec![0; counts.len()]];
+    for (part, &count) in counts.iter().enumerate() {
+        for reading in 1..count {
+            let mut choice = vec![0; counts.len()];
+            choice[part] = reading;
+            choices.push(choice);
+        }
+    }
+    choices
+}
+
+/// Pinyin spellings of `name`, None when it has no Chinese characters
+pub fn pinyin_names(name: &str) -> Option<PinyinNames> {
+    let parts = parts(name);
+    if !parts.iter().any(|part| matches!(part, Part::Han(_))) {
+        return None;
+    }
+
+    let mut variants = Vec::new();
+    for choice in reading_choices(&parts) {
+        let mut full = String::new();
+        let mut initials = String::new();
+        for (part, &reading) in parts.iter().zip(&choice) {
+            let spelling = match part {
+                Part::Word(word) => word.as_str(),
+                Part::Han(readings) => readings[reading],
+            };
+            full.push_str(spelling);
+            initials.extend(spelling.chars().next());
+        }
+        variants.push((full, initials));
+    }
+
+    let mut names = PinyinNames::default();
+    for (full, initials) in variants {
+        if !names.full.contains(&full) {
+            names.full.push(full);
+        }
+        if !names.initials.contains(&initials) {
+            names.initials.push(initials);
+        }
+    }
+    Some(names)
+}
+
+/// Key ordering names the way a Chinese reader expects: Han characters by
+/// their most common reading, among Latin text, case-insensitively
+pub fn collation_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    for c in name.chars() {
+        match c.to_pinyin() {
+            Some(pinyin) => key.push_str(pinyin.plain()),
+            None => key.extend(c.to_lowercase()),
+        }
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_pinyin_and_initials() {
+        // The most common readings come first; 信 can also be read shen
+        let wechat = pinyin_names("微信").unwrap();
+        assert_eq!(wechat.full[0], "weixin");
+        assert_eq!(wechat.initials[0], "wx");
+
+        let music = pinyin_names("网易云音乐").unwrap();
+        assert!(music.full.contains(&"wangyiyunyinyue".to_string()));
+        assert!(music.initials.iter().any(|initials| initials.starts_with("wyy")));
+
+        // Latin words keep their letters, and their first letter as an initial
+        let qq = pinyin_names("QQ音乐").unwrap();
+        assert!(qq.full.contains(&"qqyinyue".to_string()));
+        assert!(qq.initials.contains(&"qyy".to_string()));
+
+        assert_eq!(pinyin_names("Visual Studio Code"), None);
+    }
+
+    #[test]
+    fn test_polyphones_match_any_reading() {
+        // 银行 is yinhang, though 行 alone is most often read xing
+        let bank = pinyin_names("招商银行").unwrap();
+        assert!(bank.full.contains(&"zhaoshangyinhang".to_string()));
+        assert!(bank.full.contains(&"zhaoshangyinxing".to_string()));
+        assert!(bank.initials.contains(&"zsyh".to_string()));
+
+        // 音乐: 乐 is both yue and le
+        let music = pinyin_names("音乐").unwrap();
+        assert!(music.full.contains(&"yinyue".to_string()));
+        assert!(music.full.contains(&"yinle".to_string()));
+    }
+
+    #[test]
+    fn test_every_reading_kept_when_combinations_are_capped() {
+        // 2^6 combinations of 长 (chang or zhang), more than are listed
+        let long = pinyin_names("长长长长长长").unwrap();
+        assert!(long.full.len() < 64);
+        for reading in ["chang", "zhang"] {
+            assert!(long.full.iter().any(|full| full.starts_with(reading)));
+            assert!(long.full.iter().any(|full| full.ends_with(reading)));
+        }
+    }
+
+    #[test]
+    fn test_collation_orders_by_pinyin() {
+        let mut names = vec!["微信", "Calendar", "备忘录", "access"];
+        names.sort_by_key(|name| collation_key(name));
+        assert_eq!(names, vec!["access", "备忘录", "Calendar", "微信"]);
+    }
+
+    #[test]
+    fn test_enabled_by_flag_or_chinese_language() {
+        let mut settings = AppSettings { language: "en".to_string(), ..Default::default() };
+        assert!(!pinyin_search_enabled(&settings));
+        settings.enable_pinyin_search = true;
+        assert!(pinyin_search_enabled(&settings));
+        settings = AppSettings { language: "zh-CN".to_string(), ..Default::default() };
+        assert!(pinyin_search_enabled(&settings));
+    }
+}
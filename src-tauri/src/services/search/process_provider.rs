@@ -0,0 +1,150 @@
+//! Process Search Provider
+//! Lists running processes for "kill chrome" or "quit chrome", with memory
+//! and CPU use. A row's path is the pid; selecting it quits the process.
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+use crate::services::process_manager::{search_processes, ProcessCache, ProcessInfo};
+use std::sync::Arc;
+
+/// Query prefixes that ask for processes
+const PROCESS_PREFIXES: &[&str] = &["kill ", "quit "];
+
+const BYTES_PER_MB: f64 = 1024.0 * 1024.0;
+
+/// Searches running processes
+pub struct ProcessProvider {
+    processes: Arc<ProcessCache>,
+}
+
+impl ProcessProvider {
+    pub fn new(processes: Arc<ProcessCache>) -> Self {
+        Self { processes }
+    }
+}
+
+impl SearchProvider for ProcessProvider {
+    fn name(&self) -> &'static str {
+        "process"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        let text = if query.is_scoped_to(self.name()) {
+            query.text.as_str()
+        } else {
+            match strip_process_prefix(&query.text) {
+                Some(text) => text,
+                None => return vec![],
+            }
+        };
+
+        search_processes(&self.processes.snapshot(), text)
+            .iter()
+            .take(limit)
+            .enumerate()
+            .map(|(rank, process)| {
+                // Already ranked; keep that order after normalization
+                let score = 1.0 - rank as f64 * 0.001;
+                to_result(process, score, query.explain)
+            })
+            .collect()
+    }
+}
+
+/// The process name after "kill " or "quit ", None without a prefix
+fn strip_process_prefix(text: &str) -> Option<&str> {
+    PROCESS_PREFIXES.iter().find_map(|prefix| {
+        let head = text.get(..prefix.len())?;
+        let rest = text[prefix.len()..].trim();
+        (head.eq_ignore_ascii_case(prefix) && !rest.is_empty()).then_some(rest)
+    })
+}
+
+/// "PID 901 · 812 MB · 3.5% CPU · Google Chrome"
+fn describe(process: &ProcessInfo) -> String {
+    let mut parts = vec![
+        format!("PID {}", process.pid),
+        format!("{:.0} MB", process.memory_bytes as f64 / BYTES_PER_MB),
+        format!("{:.1}% CPU", process.cpu_percent),
+    ];
+    if let Some(bundle) = &process.app_bundle {
+        parts.push(bundle.clone());
+    }
+    if !process.killable {
+        parts.push("system process".to_string());
+    }
+    parts.join(" · ")
+}
+
+fn to_result(process: &ProcessInfo, score: f64, explain: bool) -> SearchResultItem {
+    SearchResultItem {
+        id: format!("process-{}", process.pid),
+        title: process.name.clone(),
+        subtitle: describe(process),
+        icon: Some(BuiltinIcon::Process.name().to_string()),
+        icon_kind: Some(IconKind::Builtin),
+        accent_color: None,
+        result_type: "process".to_string(),
+        score,
+        path: process.pid.to_string(),
+        frequency: 0,
+        explain: explain.then(|| ScoreBreakdown::fixed(score)),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::process_manager::{ProcessSource, RawProcess};
+    use std::path::PathBuf;
+
+    struct FakeProcesses;
+
+    impl ProcessSource for FakeProcesses {
+        fn list(&self) -> Vec<RawProcess> {
+            vec![
+                RawProcess {
+                    pid: 1,
+                    name: "launchd".to_string(),
+                    exe: None,
+                    memory_bytes: 20 * 1024 * 1024,
+                    cpu_percent: 0.0,
+                },
+                RawProcess {
+                    pid: 900,
+                    name: "Google Chrome".to_string(),
+                    exe: Some(PathBuf::from(
+                        "/Applications/Google Chrome.app/Contents/MacOS/Google Chrome",
+                    )),
+                    memory_bytes: 512 * 1024 * 1024,
+                    cpu_percent: 3.46,
+                },
+            ]
+        }
+    }
+
+    fn provider() -> ProcessProvider {
+        ProcessProvider::new(Arc::new(ProcessCache::new(Box::new(FakeProcesses))))
+    }
+
+    #[test]
+    fn test_kill_prefix_lists_matching_processes() {
+        let results = provider().search(&ParsedQuery::new("kill chrome"), 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].title, "Google Chrome");
+        assert_eq!(results[0].subtitle, "PID 900 · 512 MB · 3.5% CPU · Google Chrome");
+        assert_eq!(results[0].path, "900");
+        assert_eq!(results[0].result_type, "process");
+
+        let system = provider().search(&ParsedQuery::new("Quit launchd"), 10);
+        assert!(system[0].subtitle.ends_with("system process"));
+    }
+
+    #[test]
+    fn test_no_prefix_no_processes() {
+        assert!(provider().search(&ParsedQuery::new("chrome"), 10).is_empty());
+        assert!(provider().search(&ParsedQuery::new("kill"), 10).is_empty());
+        assert!(provider().search(&ParsedQuery::new("killer app"), 10).is_empty());
+    }
+}
@@ -0,0 +1,368 @@
+//! Search Profiling
+//! Where a search's time goes: each provider's run, the merge, the emits,
+//! and the whole round trip. A `SearchProfile` is threaded through one
+//! search; its `SearchTimings` can go back with the response, and
+//! `SearchPerformance` keeps the last PERFORMANCE_WINDOW of them for the
+//! diagnostics page's percentiles.
+
+use super::debounce::DebounceStats;
+use crate::services::plugin_performance::percentile;
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Searches kept for the percentiles
+pub const PERFORMANCE_WINDOW: usize = 1000;
+
+/// Milliseconds, to the microsecond
+fn millis(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+/// One provider's run within a search
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderTiming {
+    pub source: String,
+    pub ms: f64,
+    /// Results the provider returned, before the query's filters. Counts
+    /// every fetch of a filtered search, not the rows it scanned to find them.
+    pub returned: usize,
+    /// The provider refined a cached search instead of searching again
+    pub refined: bool,
+}
+
+/// One stage of a search. Stages don't overlap, so they add up to about
+/// the total.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchStage {
+    pub name: String,
+    pub ms: f64,
+}
+
+/// Where one search's time went
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchTimings {
+    pub total_ms: f64,
+    /// The merged results came straight from the search cache
+    pub cache_hit: bool,
+    pub stages: Vec<SearchStage>,
+    /// In the order they finished. They run in parallel for a streaming
+    /// search, so their times can add up to more than the total.
+    pub providers: Vec<ProviderTiming>,
+}
+
+impl SearchTimings {
+    /// The stage or provider that took longest, and its time. The
+    /// "providers" stage is named by its slowest provider.
+    pub fn slowest_stage(&self) -> Option<(String, f64)> {
+        let stages = self
+            .stages
+            .iter()
+            .filter(|stage| stage.name != "providers" || self.providers.is_empty())
+            .map(|stage| (stage.name.clone(), stage.ms));
+        let providers = self
+            .providers
+            .iter()
+            .map(|provider| (format!("provider:{}", provider.source), provider.ms));
+        stages.chain(providers).max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    /// Warning for a search over `budget_ms`, naming where the time went
+    pub fn budget_warning(&self, budget_ms: u64) -> Option<String> {
+        if self.total_ms <= budget_ms as f64 {
+            return None;
+        }
+        let slowest = self
+            .slowest_stage()
+            .map(|(name, ms)| format!("; slowest stage: {} ({:.1} ms)", name, ms))
+            .unwrap_or_default();
+        Some(format!("Search took {:.1} ms, over the {} ms budget{}", self.total_ms, budget_ms, slowest))
+    }
+}
+
+#[derive(Debug, Default)]
+struct ProfileData {
+    cache_hit: bool,
+    stages: Vec<(&'static str, Duration)>,
+    providers: Vec<ProviderTiming>,
+}
+
+impl ProfileData {
+    fn stage_time(&self) -> Duration {
+        self.stages.iter().map(|(_, duration)| *duration).sum()
+    }
+}
+
+/// Timings of one search as it runs. Shared by the provider threads of a
+/// streaming search.
+#[derive(Debug)]
+pub struct SearchProfile {
+    /// Reads the time; tests pass a clock they advance by hand
+    clock: fn() -> Instant,
+    start: Instant,
+    data: Mutex<ProfileData>,
+}
+
+impl Default for SearchProfile {
+    fn default() -> Self {
+        Self::start()
+    }
+}
+
+impl SearchProfile {
+    /// Start timing a search now
+    pub fn start() -> Self {
+        Self::with_clock(Instant::now)
+    }
+
+    fn with_clock(clock: fn() -> Instant) -> Self {
+        Self {
+            clock,
+            start: clock(),
+            data: Mutex::new(ProfileData::default()),
+        }
+    }
+
+    /// Run `f` as stage `name`. Stages recorded while it runs count toward
+    /// themselves only, and time in a stage that runs more than once adds up.
+    pub fn stage<T>(&self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let nested_before = self.data.lock().map(|data| data.stage_time()).unwrap_or_default();
+        let start = (self.clock)();
+        let value = f();
+        let elapsed = (self.clock)().saturating_duration_since(start);
+
+        if let Ok(mut data) = self.data.lock() {
+            let nested = data.stage_time().saturating_sub(nested_before);
+            let own = elapsed.saturating_sub(nested);
+            match data.stages.iter_mut().find(|(stage, _)| *stage == name) {
+                Some((_, duration)) => *duration += own,
+                None => data.stages.push((name, own)),
+            }
+        }
+        value
+    }
+
+    /// A provider finished after `duration`, having returned `returned` results
+    pub fn record_provider(&self, source: &str, duration: Duration, returned: usize, refined: bool) {
+        if let Ok(mut data) = self.data.lock() {
+            data.providers.push(ProviderTiming {
+                source: source.to_string(),
+                ms: millis(duration),
+                returned,
+                refined,
+            });
+        }
+    }
+
+    /// The merged results came from the search cache
+    pub fn cache_hit(&self) {
+        if let Ok(mut data) = self.data.lock() {
+            data.cache_hit = true;
+        }
+    }
+
+    /// Timings so far, with the total up to now
+    pub fn timings(&self) -> SearchTimings {
+        let total_ms = millis((self.clock)().saturating_duration_since(self.start));
+        let Ok(data) = self.data.lock() else {
+            return SearchTimings { total_ms, ..Default::default() };
+        };
+        SearchTimings {
+            total_ms,
+            cache_hit: data.cache_hit,
+            stages: data
+                .stages
+                .iter()
+                .map(|(name, duration)| SearchStage { name: name.to_string(), ms: millis(*duration) })
+                .collect(),
+            providers: data.providers.clone(),
+        }
+    }
+}
+
+/// Run `f` as stage `name` of `profile`, or just run it without one
+pub fn stage<T>(profile: Option<&SearchProfile>, name: &'static str, f: impl FnOnce() -> T) -> T {
+    match profile {
+        Some(profile) => profile.stage(name, f),
+        None => f(),
+    }
+}
+
+/// p50 and p95 of some durations
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LatencyPercentiles {
+    pub count: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl LatencyPercentiles {
+    fn of(micros: &[u64]) -> Self {
+        Self {
+            count: micros.len(),
+            p50_ms: percentile(micros, 0.5) as f64 / 1000.0,
+            p95_ms: percentile(micros, 0.95) as f64 / 1000.0,
+        }
+    }
+}
+
+/// `get_search_performance` result, over the last PERFORMANCE_WINDOW searches
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SearchPerformanceReport {
+    pub searches: usize,
+    pub cache_hits: usize,
+    /// Searches over the budget in settings
+    pub over_budget: usize,
+    pub total: LatencyPercentiles,
+    pub providers: BTreeMap<String, LatencyPercentiles>,
+    pub stages: BTreeMap<String, LatencyPercentiles>,
+    /// How streaming searches were debounced; filled in by the command
+    pub debounce: DebounceStats,
+}
+
+/// Timings of recent searches
+#[derive(Debug, Default)]
+pub struct SearchPerformance {
+    recent: Mutex<VecDeque<(SearchTimings, bool)>>,
+}
+
+impl SearchPerformance {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Finish timing a search: warn if it went over `budget_ms` and keep
+    /// its timings. Returns them.
+    pub fn finish(&self, profile: &SearchProfile, budget_ms: u64) -> SearchTimings {
+        let timings = profile.timings();
+        let warning = timings.budget_warning(budget_ms);
+        if let Some(warning) = &warning {
+            eprintln!("[Search] {}", warning);
+        }
+        self.record(timings.clone(), warning.is_some());
+        timings
+    }
+
+    /// Keep a finished search's timings, dropping the oldest past the window
+    pub fn record(&self, timings: SearchTimings, over_budget: bool) {
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == PERFORMANCE_WINDOW {
+                recent.pop_front();
+            }
+            recent.push_back((timings, over_budget));
+        }
+    }
+
+    /// Percentiles over the kept searches
+    pub fn report(&self) -> SearchPerformanceReport {
+        let Ok(recent) = self.recent.lock() else {
+            return SearchPerformanceReport::default();
+        };
+
+        let micros = |ms: f64| (ms * 1000.0).round() as u64;
+        let mut totals = Vec::with_capacity(recent.len());
+        let mut providers: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        let mut stages: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        for (timings, _) in recent.iter() {
+            totals.push(micros(timings.total_ms));
+            for provider in &timings.providers {
+                providers.entry(provider.source.clone()).or_default().push(micros(provider.ms));
+            }
+            for stage in &timings.stages {
+                stages.entry(stage.name.clone()).or_default().push(micros(stage.ms));
+            }
+        }
+
+        let percentiles = |samples: BTreeMap<String, Vec<u64>>| {
+            samples
+                .into_iter()
+                .map(|(name, micros)| (name, LatencyPercentiles::of(&micros)))
+                .collect()
+        };
+        SearchPerformanceReport {
+            searches: recent.len(),
+            cache_hits: recent.iter().filter(|(timings, _)| timings.cache_hit).count(),
+            over_budget: recent.iter().filter(|(_, over)| *over).count(),
+            total: LatencyPercentiles::of(&totals),
+            providers: percentiles(providers),
+            stages: percentiles(stages),
+            debounce: DebounceStats::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::sync::OnceLock;
+
+    thread_local! {
+        static ELAPSED: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+    }
+
+    /// Time that only moves when the test calls `advance`
+    fn test_clock() -> Instant {
+        static EPOCH: OnceLock<Instant> = OnceLock::new();
+        *EPOCH.get_or_init(Instant::now) + ELAPSED.with(Cell::get)
+    }
+
+    fn advance(ms: u64) {
+        ELAPSED.with(|elapsed| elapsed.set(elapsed.get() + Duration::from_millis(ms)));
+    }
+
+    #[test]
+    fn test_stages_add_up_to_the_total() {
+        let profile = SearchProfile::with_clock(test_clock);
+        profile.stage("providers", || {
+            advance(15);
+            profile.record_provider("app", Duration::from_millis(15), 3, false);
+            // Nested, so not counted toward "providers" too
+            profile.stage("emit", || advance(12));
+        });
+        advance(1);
+        profile.stage("merge", || advance(5));
+        profile.stage("emit", || advance(5));
+
+        let timings = profile.timings();
+        let stages: Vec<(&str, f64)> = timings.stages.iter().map(|stage| (stage.name.as_str(), stage.ms)).collect();
+        assert_eq!(stages, vec![("emit", 17.0), ("providers", 15.0), ("merge", 5.0)]);
+        assert_eq!(timings.providers[0].returned, 3);
+
+        // Only the time between stages is unaccounted
+        let sum: f64 = timings.stages.iter().map(|stage| stage.ms).sum();
+        assert_eq!(timings.total_ms - sum, 1.0);
+
+        assert_eq!(timings.slowest_stage().unwrap().0, "emit");
+        assert!(timings.budget_warning(1_000).is_none());
+        assert!(timings.budget_warning(1).unwrap().contains("slowest stage: emit"));
+    }
+
+    #[test]
+    fn test_performance_percentiles_over_a_rolling_window() {
+        let performance = SearchPerformance::new();
+        let timings = |total_ms: f64, file_ms: f64| SearchTimings {
+            total_ms,
+            providers: vec![ProviderTiming { source: "file".to_string(), ms: file_ms, returned: 1, refined: false }],
+            ..Default::default()
+        };
+
+        // Fills the window; then 1..=100 ms, each ten times, push it all out
+        for _ in 0..PERFORMANCE_WINDOW {
+            performance.record(timings(500.0, 400.0), true);
+        }
+        for ms in 1..=100 {
+            for _ in 0..10 {
+                performance.record(timings(ms as f64, ms as f64 / 2.0), false);
+            }
+        }
+
+        let report = performance.report();
+        assert_eq!(report.searches, PERFORMANCE_WINDOW);
+        assert_eq!(report.over_budget, 0);
+        assert_eq!(report.total, LatencyPercentiles { count: 1000, p50_ms: 50.0, p95_ms: 95.0 });
+        assert_eq!(report.providers["file"].p50_ms, 25.0);
+        assert_eq!(report.providers["file"].p95_ms, 47.5);
+    }
+}
@@ -0,0 +1,191 @@
+//! Shell History Search Provider
+//! Commands from the user's shell history for queries like "!docker run".
+//! A row's path is the command; selecting it runs the command in a
+//! terminal once the user confirms, and the secondary action copies it.
+//! History files are only read once the prefix is typed, and parsed again
+//! only when they change.
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::preferences::{AppSettings, ScoringWeights};
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+use crate::services::shell_history::{history_paths, HistoryEntry, ShellHistoryCache};
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+/// Query prefix that asks for history, as in the shell's own "!" recall
+const HISTORY_PREFIX: char = '!';
+
+/// Searches the user's shell history
+pub struct ShellHistoryProvider {
+    history: Arc<ShellHistoryCache>,
+    /// None when the home directory is unknown
+    home: Option<PathBuf>,
+    settings: Arc<RwLock<AppSettings>>,
+}
+
+impl ShellHistoryProvider {
+    pub fn new(history: Arc<ShellHistoryCache>, home: Option<PathBuf>, settings: Arc<RwLock<AppSettings>>) -> Self {
+        Self { history, home, settings }
+    }
+}
+
+impl SearchProvider for ShellHistoryProvider {
+    fn name(&self) -> &'static str {
+        "shell-history"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        let text = if query.is_scoped_to(self.name()) {
+            query.text.as_str()
+        } else {
+            match query.text.strip_prefix(HISTORY_PREFIX) {
+                Some(text) => text.trim(),
+                None => return vec![],
+            }
+        };
+
+        let Ok(settings) = self.settings.read() else {
+            return vec![];
+        };
+        let files = history_paths(&settings.shell_history_files, self.home.as_deref());
+        let exclude = settings.shell_history_exclude_pattern.clone();
+        drop(settings);
+        if files.is_empty() {
+            return vec![];
+        }
+
+        let entries = self.history.entries(&files, &exclude);
+        let text_lower = text.to_lowercase();
+        let terms = super::terms::query_terms(&text_lower);
+        let mut matches: Vec<(&HistoryEntry, ScoreBreakdown)> = entries
+            .iter()
+            .filter_map(|entry| Some((entry, score_entry(entry, &text_lower, &terms, &query.weights)?)))
+            .collect();
+
+        // Stable, so equal scores stay newest first
+        matches.sort_by(|a, b| b.1.total().total_cmp(&a.1.total()));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(entry, breakdown)| to_result(entry, breakdown, query.explain))
+            .collect()
+    }
+}
+
+/// Score components for a command, None when it doesn't contain every
+/// term. An empty query lists the most recent commands.
+fn score_entry(
+    entry: &HistoryEntry,
+    text_lower: &str,
+    terms: &[String],
+    weights: &ScoringWeights,
+) -> Option<ScoreBreakdown> {
+    let command = entry.command.to_lowercase();
+    let mut breakdown = ScoreBreakdown::default();
+    if text_lower.is_empty() {
+        // Everything matches
+    } else if command == text_lower {
+        breakdown.exact = weights.exact;
+    } else if command.starts_with(text_lower) {
+        breakdown.starts_with = weights.starts_with;
+    } else if terms.iter().all(|term| command.contains(term.as_str())) {
+        breakdown.contains = weights.contains;
+    } else {
+        return None;
+    }
+    Some(breakdown)
+}
+
+fn to_result(entry: &HistoryEntry, breakdown: ScoreBreakdown, explain: bool) -> SearchResultItem {
+    // Multi-line commands show their first line, the whole runs
+    let first_line = entry.command.lines().next().unwrap_or_default();
+    let title = if entry.command.contains('\n') {
+        format!("{} …", first_line)
+    } else {
+        first_line.to_string()
+    };
+    let run_at = entry
+        .timestamp
+        .and_then(|at| chrono::DateTime::from_timestamp(at, 0))
+        .map(|at| at.with_timezone(&chrono::Local));
+    let subtitle = match run_at {
+        Some(at) => format!("{} history · {}", entry.shell.name(), at.format("%Y-%m-%d %H:%M")),
+        None => format!("{} history", entry.shell.name()),
+    };
+    SearchResultItem {
+        id: format!("shell-history-{}", entry.command),
+        title,
+        subtitle,
+        icon: Some(BuiltinIcon::Terminal.name().to_string()),
+        icon_kind: Some(IconKind::Builtin),
+        accent_color: None,
+        result_type: "shell-history".to_string(),
+        score: breakdown.total(),
+        path: entry.command.clone(),
+        frequency: 0,
+        explain: explain.then_some(breakdown),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn provider() -> (tempfile::TempDir, ShellHistoryProvider) {
+        let home = tempfile::tempdir().unwrap();
+        fs::write(
+            home.path().join(".zsh_history"),
+            ": 1700000000:0;docker run --rm -it alpine sh\n: 1700000100:0;git status\n: 1700000200:0;docker ps\n",
+        )
+        .unwrap();
+        fs::write(home.path().join(".bash_history"), "docker ps\nexport NPM_TOKEN=abc123\n").unwrap();
+        let settings = AppSettings {
+            shell_history_files: vec!["~/.zsh_history".to_string(), "~/.bash_history".to_string()],
+            ..Default::default()
+        };
+        let provider = ShellHistoryProvider::new(
+            Arc::new(ShellHistoryCache::new()),
+            Some(home.path().to_path_buf()),
+            Arc::new(RwLock::new(settings)),
+        );
+        (home, provider)
+    }
+
+    fn titles(results: &[SearchResultItem]) -> Vec<&str> {
+        results.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_history_prefix_lists_matching_commands() {
+        let (_home, provider) = provider();
+
+        let results = provider.search(&ParsedQuery::new("!docker"), 10);
+        // "docker ps" is in both files but listed once, at its latest time
+        assert_eq!(titles(&results), vec!["docker ps", "docker run --rm -it alpine sh"]);
+        assert_eq!(results[0].result_type, "shell-history");
+        assert_eq!(results[0].path, "docker ps");
+        // Shown in local time, so the day depends on the time zone
+        assert!(results[0].subtitle.starts_with("zsh history · 2023-11-1"));
+
+        let results = provider.search(&ParsedQuery::new("! alpine rm"), 10);
+        assert_eq!(titles(&results), vec!["docker run --rm -it alpine sh"]);
+        // The secret never shows up, even for a bare "!"
+        assert_eq!(provider.search(&ParsedQuery::new("!"), 10).len(), 3);
+        assert!(provider.search(&ParsedQuery::new("!npm"), 10).is_empty());
+    }
+
+    #[test]
+    fn test_no_prefix_no_history() {
+        let (_home, provider) = provider();
+        assert!(provider.search(&ParsedQuery::new("docker"), 10).is_empty());
+        assert!(ShellHistoryProvider::new(
+            Arc::new(ShellHistoryCache::new()),
+            None,
+            Arc::new(RwLock::new(AppSettings::default())),
+        )
+        .search(&ParsedQuery::new("!docker"), 10)
+        .is_empty());
+    }
+}
@@ -0,0 +1,173 @@
+//! SSH Host Search Provider
+//! Hosts from ~/.ssh/config and known_hosts for queries like "ssh prod".
+//! A row's path is the host to connect to; selecting it opens a terminal
+//! running ssh, through the terminal template in settings.
+
+use super::{ParsedQuery, SearchProvider};
+use crate::models::preferences::ScoringWeights;
+use crate::models::search::{BuiltinIcon, IconKind, ScoreBreakdown, SearchResultItem};
+use crate::services::ssh_hosts::{load_hosts, SshHost, SshHostSource};
+use std::path::PathBuf;
+
+/// Query prefix that asks for hosts
+const SSH_PREFIX: &str = "ssh ";
+
+/// Score added to hosts from the ssh config
+const CONFIG_HOST_BOOST: f64 = 0.05;
+
+/// Searches the user's SSH hosts
+pub struct SshProvider {
+    /// ~/.ssh, None when the home directory is unknown
+    ssh_dir: Option<PathBuf>,
+}
+
+impl SshProvider {
+    pub fn new(ssh_dir: Option<PathBuf>) -> Self {
+        Self { ssh_dir }
+    }
+}
+
+impl SearchProvider for SshProvider {
+    fn name(&self) -> &'static str {
+        "ssh"
+    }
+
+    fn search(&self, query: &ParsedQuery, limit: usize) -> Vec<SearchResultItem> {
+        let text = if query.is_scoped_to(self.name()) {
+            query.text.as_str()
+        } else {
+            match strip_ssh_prefix(&query.text) {
+                Some(text) => text,
+                None => return vec![],
+            }
+        };
+        let Some(ssh_dir) = &self.ssh_dir else {
+            return vec![];
+        };
+
+        // The files are small and only read once the prefix is typed
+        let text_lower = text.to_lowercase();
+        let mut matches: Vec<_> = load_hosts(ssh_dir)
+            .into_iter()
+            .filter_map(|host| {
+                let breakdown = score_host(&host, &text_lower, &query.weights)?;
+                Some((host, breakdown))
+            })
+            .collect();
+
+        // Stable, so config order is kept for equal scores
+        matches.sort_by(|a, b| b.1.total().total_cmp(&a.1.total()));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(host, breakdown)| to_result(&host, breakdown, query.explain))
+            .collect()
+    }
+}
+
+/// The host text after "ssh ", None without the prefix. A bare "ssh"
+/// lists every host.
+fn strip_ssh_prefix(text: &str) -> Option<&str> {
+    if text.eq_ignore_ascii_case("ssh") {
+        return Some("");
+    }
+    let head = text.get(..SSH_PREFIX.len())?;
+    head.eq_ignore_ascii_case(SSH_PREFIX)
+        .then(|| text[SSH_PREFIX.len()..].trim())
+}
+
+/// Score components for a host, None when it doesn't match. Config hosts
+/// edge out equally matching known hosts since they were named on purpose.
+fn score_host(host: &SshHost, text_lower: &str, weights: &ScoringWeights) -> Option<ScoreBreakdown> {
+    let alias = host.alias.to_lowercase();
+    let hostname = host.hostname.as_deref().unwrap_or_default().to_lowercase();
+    let mut breakdown = ScoreBreakdown::default();
+
+    if text_lower.is_empty() {
+        // Everything matches
+    } else if alias == text_lower {
+        breakdown.exact = weights.exact;
+    } else if alias.starts_with(text_lower) {
+        breakdown.starts_with = weights.starts_with;
+    } else if alias.contains(text_lower) || hostname.contains(text_lower) {
+        breakdown.contains = weights.contains;
+    } else {
+        return None;
+    }
+
+    if host.source == SshHostSource::Config {
+        breakdown.other = CONFIG_HOST_BOOST;
+    }
+    Some(breakdown)
+}
+
+fn to_result(host: &SshHost, breakdown: ScoreBreakdown, explain: bool) -> SearchResultItem {
+    let source = match host.source {
+        SshHostSource::Config => "ssh config",
+        SshHostSource::KnownHosts => "known_hosts",
+    };
+    SearchResultItem {
+        id: format!("ssh-{}", host.alias),
+        title: format!("ssh {}", host.alias),
+        subtitle: format!("{} · {}", host.destination(), source),
+        icon: Some(BuiltinIcon::Server.name().to_string()),
+        icon_kind: Some(IconKind::Builtin),
+        accent_color: None,
+        result_type: "ssh".to_string(),
+        score: breakdown.total(),
+        path: host.alias.clone(),
+        frequency: 0,
+        explain: explain.then_some(breakdown),
+        match_context: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn provider() -> (tempfile::TempDir, SshProvider) {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("config"),
+            "Host prod-db\n    HostName 10.0.0.5\n    User deploy\n\nHost staging-db\n    HostName db.staging.example.com\n",
+        )
+        .unwrap();
+        fs::write(dir.path().join("known_hosts"), "prod.example.com ssh-ed25519 AAAA\n").unwrap();
+        let provider = SshProvider::new(Some(dir.path().to_path_buf()));
+        (dir, provider)
+    }
+
+    fn titles(results: &[SearchResultItem]) -> Vec<&str> {
+        results.iter().map(|r| r.title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_ssh_prefix_lists_matching_hosts() {
+        let (_dir, provider) = provider();
+
+        let results = provider.search(&ParsedQuery::new("ssh prod"), 10);
+        assert_eq!(titles(&results), vec!["ssh prod-db", "ssh prod.example.com"]);
+        assert_eq!(results[0].subtitle, "deploy@10.0.0.5 · ssh config");
+        assert_eq!(results[0].path, "prod-db");
+        assert_eq!(results[0].result_type, "ssh");
+
+        // HostName matches too
+        assert_eq!(
+            titles(&provider.search(&ParsedQuery::new("ssh staging.example"), 10)),
+            vec!["ssh staging-db"]
+        );
+        assert_eq!(provider.search(&ParsedQuery::new("SSH"), 10).len(), 3);
+    }
+
+    #[test]
+    fn test_no_prefix_no_hosts() {
+        let (_dir, provider) = provider();
+        assert!(provider.search(&ParsedQuery::new("prod"), 10).is_empty());
+        assert!(provider.search(&ParsedQuery::new("sshd"), 10).is_empty());
+        assert!(SshProvider::new(None)
+            .search(&ParsedQuery::new("ssh prod"), 10)
+            .is_empty());
+    }
+}
@@ -0,0 +1,405 @@
+//! Streaming Search
+//! Runs providers concurrently and reports each batch as soon as it's ready,
+//! with cooperative cancellation when a newer search supersedes this one
+
+use super::dedup::PathCache;
+use super::profile::{stage, SearchProfile};
+use super::{
+    fetch_limit, merge_results_with, search_filtered, source_caps, FilteredResults, MergedResults, ParsedQuery,
+    ProviderBatch, ProviderRegistry,
+};
+use crate::models::preferences::AppSettings;
+use crate::models::search::SearchResultItem;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+
+/// Shared flag checked by in-flight provider work
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// Tracks the latest streaming search so older ones can be cancelled
+#[derive(Default)]
+pub struct SearchCancellation {
+    current: Mutex<Option<(u64, CancellationToken)>>,
+}
+
+impl SearchCancellation {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start tracking `search_id`, cancelling any older search.
+    ///
+    /// Returns None if a newer search is already running, in which case this
+    /// one is stale and shouldn't run at all.
+    pub fn begin(&self, search_id: u64) -> Option<CancellationToken> {
+        let mut current = self.current.lock().ok()?;
+
+        if let Some((id, token)) = current.as_ref() {
+            if *id > search_id {
+                return None;
+            }
+            token.cancel();
+        }
+
+        let token = CancellationToken::new();
+        *current = Some((search_id, token.clone()));
+        Some(token)
+    }
+
+    /// Stop tracking `search_id` once it has completed
+    pub fn finish(&self, search_id: u64) {
+        if let Ok(mut current) = self.current.lock() {
+            if current.as_ref().is_some_and(|(id, _)| *id == search_id) {
+                *current = None;
+            }
+        }
+    }
+}
+
+/// Rows sent with `search:partial` and `search:complete`; the rest are
+/// fetched with `get_results_page`
+pub const FIRST_PAGE_SIZE: usize = 20;
+/// Largest page `get_results_page` serves
+pub const MAX_PAGE_SIZE: usize = 100;
+/// Searches whose full results are kept for paging, newest first
+pub const RETAINED_SEARCHES: usize = 3;
+
+/// Rows of a retained search's results
+#[derive(Debug, Clone, Serialize)]
+pub struct ResultsPage {
+    pub search_id: u64,
+    pub offset: usize,
+    /// Results the search found in all
+    pub total: usize,
+    pub results: Vec<SearchResultItem>,
+}
+
+/// Paging failure, serialized so the UI can tell these cases apart
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PageError {
+    /// The search is still running; retry after `search:complete`
+    Pending { search_id: u64 },
+    /// The search's results were dropped for newer ones; search again
+    Expired { search_id: u64 },
+}
+
+impl fmt::Display for PageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PageError::Pending { search_id } => write!(f, "Search {} is still running", search_id),
+            PageError::Expired { search_id } => write!(f, "Results of search {} have expired", search_id),
+        }
+    }
+}
+
+/// Full ranked results of the latest streaming searches. Events carry only
+/// the first page, so scrolling past it reads from here. Searches still
+/// running hold no results yet.
+#[derive(Default)]
+pub struct RetainedResults {
+    searches: Mutex<VecDeque<(u64, Option<Vec<SearchResultItem>>)>>,
+}
+
+impl RetainedResults {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark `search_id` as running, so paging it reports Pending until
+    /// `store` or `abandon`
+    pub fn begin(&self, search_id: u64) {
+        self.insert(search_id, None);
+    }
+
+    /// Keep the results of `search_id`, dropping the oldest search past
+    /// RETAINED_SEARCHES
+    pub fn store(&self, search_id: u64, results: Vec<SearchResultItem>) {
+        self.insert(search_id, Some(results));
+    }
+
+    /// Forget `search_id` if it never stored results, e.g. when cancelled
+    pub fn abandon(&self, search_id: u64) {
+        if let Ok(mut searches) = self.searches.lock() {
+            searches.retain(|(id, results)| *id != search_id || results.is_some());
+        }
+    }
+
+    fn insert(&self, search_id: u64, results: Option<Vec<SearchResultItem>>) {
+        let Ok(mut searches) = self.searches.lock() else {
+            return;
+        };
+        searches.retain(|(id, _)| *id != search_id);
+        searches.push_front((search_id, results));
+        searches.truncate(RETAINED_SEARCHES);
+    }
+
+    /// Up to `limit` results of `search_id` from `offset`, at most MAX_PAGE_SIZE
+    pub fn page(&self, search_id: u64, offset: usize, limit: usize) -> Result<ResultsPage, PageError> {
+        let expired = PageError::Expired { search_id };
+        let searches = self.searches.lock().map_err(|_| expired.clone())?;
+        let (_, results) = searches.iter().find(|(id, _)| *id == search_id).ok_or(expired)?;
+        let results = results.as_ref().ok_or(PageError::Pending { search_id })?;
+
+        Ok(ResultsPage {
+            search_id,
+            offset,
+            total: results.len(),
+            results: results.iter().skip(offset).take(limit.min(MAX_PAGE_SIZE)).cloned().collect(),
+        })
+    }
+}
+
+impl ProviderRegistry {
+    /// Run active providers concurrently, calling `on_partial(source, results)`
+    /// in completion order. Each partial batch is normalized and capped the same
+    /// way as in `search`. Returns the providers' batches, for the cache, and
+    /// the final merge, or None if cancelled. Providers and the final merge
+    /// are timed into `profile`, if given.
+    #[allow(clippy::too_many_arguments)]
+    pub fn search_streaming<F>(
+        &self,
+        query: &ParsedQuery,
+        settings: &AppSettings,
+        sources: Option<&[String]>,
+        limit: usize,
+        cancel: &CancellationToken,
+        profile: Option<&SearchProfile>,
+        mut on_partial: F,
+    ) -> Option<(Vec<ProviderBatch>, MergedResults)>
+    where
+        F: FnMut(&str, Vec<SearchResultItem>),
+    {
+        let caps = &source_caps(query, settings);
+        let providers = self.active(query, settings, sources);
+
+        std::thread::scope(|scope| {
+            let (tx, rx) = mpsc::channel();
+
+            for provider in &providers {
+                let tx = tx.clone();
+                let fetch = fetch_limit(caps, provider.name(), limit);
+                scope.spawn(move || {
+                    if cancel.is_cancelled() {
+                        return;
+                    }
+                    let start = std::time::Instant::now();
+                    let FilteredResults { results, returned, complete } = search_filtered(*provider, query, fetch);
+                    if let Some(profile) = profile {
+                        profile.record_provider(provider.name(), start.elapsed(), returned, false);
+                    }
+                    if !cancel.is_cancelled() {
+                        let _ = tx.send(ProviderBatch {
+                            source: provider.name(),
+                            weight: provider.weight(),
+                            complete,
+                            results,
+                        });
+                    }
+                });
+            }
+            drop(tx);
+
+            let mut batches = Vec::new();
+            let mut paths = PathCache::default();
+            for batch in rx {
+                if cancel.is_cancelled() {
+                    return None;
+                }
+                let partial = merge_results_with(vec![(batch.weight, batch.results.clone())], caps, limit, &mut paths);
+                on_partial(batch.source, partial.results);
+                batches.push(batch);
+            }
+
+            if cancel.is_cancelled() {
+                return None;
+            }
+            let merged = stage(profile, "merge", || {
+                let weighted = batches.iter().map(|batch| (batch.weight, batch.results.clone())).collect();
+                merge_results_with(weighted, caps, limit, &mut paths)
+            });
+            Some((batches, merged))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::SearchProvider;
+    use super::*;
+    use std::time::Duration;
+
+    struct SlowProvider {
+        name: &'static str,
+        delay_ms: u64,
+    }
+
+    impl SearchProvider for SlowProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn search(&self, _query: &ParsedQuery, _limit: usize) -> Vec<SearchResultItem> {
+            std::thread::sleep(Duration::from_millis(self.delay_ms));
+            vec![SearchResultItem {
+                id: self.name.to_string(),
+                title: self.name.to_string(),
+                subtitle: String::new(),
+                icon: None,
+                icon_kind: None,
+                accent_color: None,
+                result_type: self.name.to_string(),
+                score: 1.0,
+                path: String::new(),
+                frequency: 0,
+                explain: None,
+                match_context: None,
+            }]
+        }
+    }
+
+    fn registry() -> ProviderRegistry {
+        let mut registry = ProviderRegistry::new();
+        // Registered slow-first so ordering can only come from completion time
+        registry.register(Box::new(SlowProvider { name: "slow", delay_ms: 150 }));
+        registry.register(Box::new(SlowProvider { name: "fast", delay_ms: 5 }));
+        registry
+    }
+
+    #[test]
+    fn test_partials_arrive_in_completion_order() {
+        let registry = registry();
+        let mut order = Vec::new();
+
+        let (_, merged) = registry
+            .search_streaming(
+                &ParsedQuery::new("x"),
+                &AppSettings::default(),
+                None,
+                10,
+                &CancellationToken::new(),
+                None,
+                |source, _| order.push(source.to_string()),
+            )
+            .unwrap();
+
+        assert_eq!(order, vec!["fast", "slow"]);
+        assert_eq!(merged.results.len(), 2);
+    }
+
+    #[test]
+    fn test_cancellation_suppresses_late_results() {
+        let registry = Arc::new(registry());
+        let cancellation = Arc::new(SearchCancellation::new());
+        let token = cancellation.begin(1).unwrap();
+
+        let partials = Arc::new(Mutex::new(Vec::new()));
+        let handle = {
+            let registry = registry.clone();
+            let partials = partials.clone();
+            std::thread::spawn(move || {
+                registry.search_streaming(
+                    &ParsedQuery::new("x"),
+                    &AppSettings::default(),
+                    None,
+                    10,
+                    &token,
+                    None,
+                    |source, _| partials.lock().unwrap().push(source.to_string()),
+                )
+            })
+        };
+
+        std::thread::sleep(Duration::from_millis(50));
+        // A newer search cancels the first one
+        assert!(cancellation.begin(2).is_some());
+
+        assert!(handle.join().unwrap().is_none());
+        assert_eq!(*partials.lock().unwrap(), vec!["fast"]);
+
+        // A search older than the current one is rejected outright
+        assert!(cancellation.begin(1).is_none());
+    }
+
+    fn item(id: usize) -> SearchResultItem {
+        SearchResultItem {
+            id: id.to_string(),
+            title: format!("Result {}", id),
+            subtitle: String::new(),
+            icon: None,
+            icon_kind: None,
+            accent_color: None,
+            result_type: "file".to_string(),
+            score: 1.0,
+            path: String::new(),
+            frequency: 0,
+            explain: None,
+            match_context: None,
+        }
+    }
+
+    #[test]
+    fn test_retained_results_are_paged() {
+        let retained = RetainedResults::new();
+        retained.store(7, (0..45).map(item).collect());
+
+        let page = retained.page(7, 20, 20).unwrap();
+        assert_eq!((page.offset, page.total), (20, 45));
+        assert_eq!(page.results.first().unwrap().id, "20");
+        assert_eq!(page.results.len(), 20);
+        assert_eq!(retained.page(7, 40, 20).unwrap().results.len(), 5);
+        assert!(retained.page(7, 60, 20).unwrap().results.is_empty());
+        assert_eq!(retained.page(7, 0, 500).unwrap().results.len(), 45.min(MAX_PAGE_SIZE));
+    }
+
+    #[test]
+    fn test_only_latest_searches_are_retained() {
+        let retained = RetainedResults::new();
+        for search_id in 1..=RETAINED_SEARCHES as u64 + 1 {
+            retained.store(search_id, vec![item(0)]);
+        }
+
+        assert_eq!(retained.page(1, 0, 10).unwrap_err(), PageError::Expired { search_id: 1 });
+        for search_id in 2..=RETAINED_SEARCHES as u64 + 1 {
+            assert_eq!(retained.page(search_id, 0, 10).unwrap().total, 1);
+        }
+        assert!(retained.page(99, 0, 10).is_err());
+    }
+
+    #[test]
+    fn test_running_search_is_pending() {
+        let retained = RetainedResults::new();
+        retained.begin(1);
+        assert_eq!(retained.page(1, 0, 10).unwrap_err(), PageError::Pending { search_id: 1 });
+
+        retained.store(1, vec![item(0)]);
+        assert_eq!(retained.page(1, 0, 10).unwrap().total, 1);
+        retained.abandon(1);
+        assert_eq!(retained.page(1, 0, 10).unwrap().total, 1);
+
+        retained.begin(2);
+        retained.abandon(2);
+        assert_eq!(retained.page(2, 0, 10).unwrap_err(), PageError::Expired { search_id: 2 });
+    }
+}
@@ -0,0 +1,484 @@
+//! Default Suggestions
+//! What the launcher shows before anything is typed: frequently used apps
+//! plus recent clipboard items, documents, web pages opened from the
+//! launcher and plugin triggers. The caller
+//! gathers sources from caches and aggregates; this module only ranks and
+//! composes them, so it does no I/O.
+
+use crate::db::analytics::SubjectUsage;
+use crate::db::browser::BrowserEntry;
+use crate::db::files::FileEntry;
+use crate::models::app::ApplicationEntry;
+use crate::models::clipboard::ClipboardItem;
+use crate::models::search::{IconKind, SearchResultItem};
+use crate::services::analytics::subject_value;
+use crate::services::search::app_provider::app_icon;
+use crate::services::search::exclusion::is_app_excluded;
+use std::collections::HashMap;
+
+/// Section result types, in display order
+pub const SUGGESTION_SECTIONS: &[&str] = &["app", "clipboard", "file", "browser", "plugin"];
+
+/// Clipboard titles are cut to this many characters
+pub const CLIPBOARD_TITLE_MAX_CHARS: usize = 60;
+
+/// Launches older than this don't count towards app frecency
+pub const FRECENCY_WINDOW_DAYS: i64 = 90;
+
+/// Cached data suggestions are built from
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionSources {
+    /// Installed apps from the app monitor cache
+    pub apps: Vec<ApplicationEntry>,
+    /// Launch events per launched path
+    pub app_usage: Vec<SubjectUsage>,
+    /// Salt usage subjects are hashed with, set when `anonymize_usage` is on
+    pub salt: Option<String>,
+    /// Recent clipboard items, newest first
+    pub clipboard: Vec<ClipboardItem>,
+    /// Recently modified indexed files, newest first
+    pub documents: Vec<FileEntry>,
+    /// Browser pages opened from the launcher, most opened first
+    pub browser: Vec<BrowserEntry>,
+    /// Plugin executions per trigger, most recent first
+    pub plugin_usage: Vec<SubjectUsage>,
+}
+
+/// How much a use counts given how long ago it was
+pub fn recency_weight(age_secs: i64) -> f64 {
+    const DAY: i64 = 24 * 3600;
+    match age_secs.max(0) / DAY {
+        0..=3 => 1.0,
+        4..=13 => 0.7,
+        14..=30 => 0.5,
+        _ => 0.3,
+    }
+}
+
+/// Use count scaled by how recently the subject was last used
+pub fn frecency(usage: &SubjectUsage, now: i64) -> f64 {
+    usage.count as f64 * recency_weight(now - usage.last_used)
+}
+
+/// Split `limit` slots between sections in proportion to their weights
+/// (D'Hondt), never giving a section more than it has. Slots a section
+/// can't fill go to the others.
+fn section_quotas(available: &[usize], weights: &[u32], limit: usize) -> Vec<usize> {
+    let mut taken = vec![0; available.len()];
+    for _ in 0..limit {
+        let mut next: Option<(usize, f64)> = None;
+        for (i, (&have, &weight)) in available.iter().zip(weights).enumerate() {
+            if weight == 0 || taken[i] >= have {
+                continue;
+            }
+            let quotient = weight as f64 / (taken[i] + 1) as f64;
+            // Strictly greater, so ties go to the earlier section
+            if next.is_none_or(|(_, best)| quotient > best) {
+                next = Some((i, quotient));
+            }
+        }
+        match next {
+            Some((i, _)) => taken[i] += 1,
+            None => break,
+        }
+    }
+    taken
+}
+
+/// Cut to the first line and at most `max_chars`, marking the cut with "…"
+fn truncate_title(text: &str, max_chars: usize) -> String {
+    let line = text.trim().lines().next().unwrap_or_default().trim_end();
+    let cut = line.chars().count() > max_chars || line.len() < text.trim().len();
+    if !cut {
+        return line.to_string();
+    }
+    let mut title: String = line.chars().take(max_chars.saturating_sub(1)).collect();
+    title.push('…');
+    title
+}
+
+fn suggestion(id: String, title: String, subtitle: String, result_type: &str, path: String, frequency: u32) -> SearchResultItem {
+    SearchResultItem {
+        id,
+        title,
+        subtitle,
+        icon: None,
+        icon_kind: None,
+        accent_color: None,
+        result_type: result_type.to_string(),
+        score: 0.0,
+        path,
+        frequency,
+        explain: None,
+        match_context: None,
+    }
+}
+
+/// Launched apps by frecency. Launches are recorded against the app or
+/// executable path, hashed the same way when usage is anonymized.
+fn app_suggestions(sources: &SuggestionSources, excluded_apps: &[String], now: i64) -> Vec<SearchResultItem> {
+    let usage: HashMap<&str, &SubjectUsage> = sources
+        .app_usage
+        .iter()
+        .map(|usage| (usage.subject.as_str(), usage))
+        .collect();
+
+    let mut ranked: Vec<(f64, &ApplicationEntry, usize)> = sources
+        .apps
+        .iter()
+        .filter(|app| !is_app_excluded(app, excluded_apps))
+        .filter_map(|app| {
+            let paths = [app.app_path.as_deref(), Some(app.executable_path.as_str())];
+            let (score, count) = paths
+                .into_iter()
+                .flatten()
+                .filter_map(|path| usage.get(subject_value(path, sources.salt.as_deref()).as_str()))
+                .fold((0.0, 0), |(score, count), usage| (score + frecency(usage, now), count + usage.count));
+            (count > 0).then_some((score, app, count))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.name.cmp(&b.1.name)));
+
+    ranked
+        .into_iter()
+        .map(|(score, app, count)| {
+            let (icon, icon_kind) = app_icon(app);
+            SearchResultItem {
+                icon,
+                icon_kind,
+                score,
+                ..suggestion(
+                    app.id.clone(),
+                    app.name.clone(),
+                    app.executable_path.clone(),
+                    "app",
+                    app.app_path.clone().unwrap_or_else(|| app.executable_path.clone()),
+                    count as u32,
+                )
+            }
+        })
+        .collect()
+}
+
+fn clipboard_suggestions(items: &[ClipboardItem]) -> Vec<SearchResultItem> {
+    items
+        .iter()
+        .filter(|item| !item.is_sensitive)
+        .filter_map(|item| {
+            let text = item.text.as_deref().filter(|text| !text.trim().is_empty())?;
+            Some(suggestion(
+                format!("clipboard-{}", item.id),
+                truncate_title(text, CLIPBOARD_TITLE_MAX_CHARS),
+                item.app_source.clone().unwrap_or_default(),
+                "clipboard",
+                item.id.clone(),
+                0,
+            ))
+        })
+        .collect()
+}
+
+fn document_suggestions(documents: &[FileEntry]) -> Vec<SearchResultItem> {
+    documents
+        .iter()
+        .map(|entry| {
+            suggestion(
+                format!("file-{}", entry.id.unwrap_or(0)),
+                entry.filename.clone(),
+                entry.path.clone(),
+                "file",
+                entry.path.clone(),
+                0,
+            )
+        })
+        .collect()
+}
+
+/// Pages opened from the launcher, ranked like app launches
+fn browser_suggestions(pages: &[BrowserEntry], now: i64) -> Vec<SearchResultItem> {
+    let mut ranked: Vec<(f64, &BrowserEntry)> = pages
+        .iter()
+        .filter(|page| page.launcher_open_count > 0)
+        .map(|page| {
+            let age = now - page.last_opened_from_launcher.unwrap_or(0);
+            (page.launcher_open_count as f64 * recency_weight(age), page)
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    ranked
+        .into_iter()
+        .map(|(_, page)| SearchResultItem {
+            icon: page.favicon.clone(),
+            icon_kind: page.favicon.as_ref().map(|_| IconKind::Path),
+            accent_color: page.accent_color.clone(),
+            ..suggestion(
+                format!("browser-{}", page.id.unwrap_or(0)),
+                page.title.clone(),
+                page.url.clone(),
+                "browser",
+                page.url.clone(),
+                page.launcher_open_count.max(0) as u32,
+            )
+        })
+        .collect()
+}
+
+/// Hashed triggers can't be shown, so anonymized usage yields none
+fn plugin_suggestions(sources: &SuggestionSources) -> Vec<SearchResultItem> {
+    if sources.salt.is_some() {
+        return Vec::new();
+    }
+    sources
+        .plugin_usage
+        .iter()
+        .map(|usage| {
+            suggestion(
+                format!("plugin-{}", usage.subject),
+                usage.subject.clone(),
+                String::new(),
+                "plugin",
+                usage.subject.clone(),
+                usage.count as u32,
+            )
+        })
+        .collect()
+}
+
+/// Compose up to `limit` suggestions, grouped by section in
+/// `SUGGESTION_SECTIONS` order. `weights` maps a section's result type to its
+/// share of the slots; missing or zero weights drop the section. In privacy
+/// mode only apps are suggested, since the other sections reveal activity.
+pub fn compose_suggestions(
+    sources: &SuggestionSources,
+    weights: &HashMap<String, u32>,
+    excluded_apps: &[String],
+    privacy: bool,
+    limit: usize,
+    now: i64,
+) -> Vec<SearchResultItem> {
+    let sections: Vec<Vec<SearchResultItem>> = SUGGESTION_SECTIONS
+        .iter()
+        .map(|&section| match section {
+            "app" => app_suggestions(sources, excluded_apps, now),
+            _ if privacy => Vec::new(),
+            "clipboard" => clipboard_suggestions(&sources.clipboard),
+            "file" => document_suggestions(&sources.documents),
+            "browser" => browser_suggestions(&sources.browser, now),
+            _ => plugin_suggestions(sources),
+        })
+        .collect();
+
+    let available: Vec<usize> = sections.iter().map(Vec::len).collect();
+    let section_weights: Vec<u32> = SUGGESTION_SECTIONS
+        .iter()
+        .map(|section| weights.get(*section).copied().unwrap_or(0))
+        .collect();
+    let quotas = section_quotas(&available, &section_weights, limit);
+
+    sections
+        .into_iter()
+        .zip(quotas)
+        .flat_map(|(items, quota)| {
+            items.into_iter().take(quota).enumerate().map(|(rank, item)| SearchResultItem {
+                // Within-section order for sections without a usage score
+                score: if item.result_type == "app" { item.score } else { 1.0 / (rank + 1) as f64 },
+                ..item
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::app::AppSource;
+    use crate::models::clipboard::ClipboardContentType;
+
+    const NOW: i64 = 1_704_067_200;
+    const DAY: i64 = 24 * 3600;
+
+    fn app(name: &str) -> ApplicationEntry {
+        ApplicationEntry {
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            executable_path: format!("/Applications/{name}.app/Contents/MacOS/{name}"),
+            app_path: Some(format!("/Applications/{name}.app")),
+            icon: None,
+            usage_count: 0,
+            last_launched: None,
+            platform: "macos".to_string(),
+            alternate_names: None,
+            bundle_id: Some(format!("com.example.{}", name.to_lowercase())),
+            source: AppSource::Installed,
+        }
+    }
+
+    fn usage(subject: &str, count: usize, last_used: i64) -> SubjectUsage {
+        SubjectUsage { subject: subject.to_string(), count, last_used }
+    }
+
+    fn clip(id: &str, text: &str, is_sensitive: bool) -> ClipboardItem {
+        ClipboardItem {
+            id: id.to_string(),
+            content_type: ClipboardContentType::Text,
+            text: Some(text.to_string()),
+            image_path: None,
+            thumbnail_path: None,
+            hash: id.to_string(),
+            perceptual_hash: None,
+            timestamp: NOW,
+            is_sensitive,
+            app_source: None,
+        }
+    }
+
+    fn document(id: i64, filename: &str) -> FileEntry {
+        FileEntry {
+            id: Some(id),
+            path: format!("/Users/me/Documents/{filename}"),
+            filename: filename.to_string(),
+            extension: None,
+            size: 1,
+            modified: NOW,
+            hidden: false,
+            indexed: NOW,
+            tags: Vec::new(),
+            is_placeholder: false,
+        }
+    }
+
+    fn sources() -> SuggestionSources {
+        SuggestionSources {
+            apps: vec![app("Safari"), app("Terminal"), app("Notes"), app("Mail")],
+            app_usage: vec![
+                usage("/Applications/Safari.app", 3, NOW - DAY),
+                // Many launches, but long ago
+                usage("/Applications/Notes.app", 8, NOW - 60 * DAY),
+                usage("/Applications/Terminal.app", 5, NOW),
+            ],
+            salt: None,
+            clipboard: vec![
+                clip("c1", "hello world", false),
+                clip("c2", "hunter2", true),
+                clip("c3", "second", false),
+                clip("c4", "third", false),
+            ],
+            documents: vec![document(1, "report.pdf"), document(2, "notes.md"), document(3, "todo.txt")],
+            browser: vec![],
+            plugin_usage: vec![usage("translate", 2, NOW), usage("calc", 9, NOW - DAY)],
+        }
+    }
+
+    fn weights(pairs: &[(&str, u32)]) -> HashMap<String, u32> {
+        pairs.iter().map(|(section, weight)| (section.to_string(), *weight)).collect()
+    }
+
+    fn default_weights() -> HashMap<String, u32> {
+        weights(&[("app", 4), ("clipboard", 2), ("file", 2), ("plugin", 2)])
+    }
+
+    fn ids_of(items: &[SearchResultItem], result_type: &str) -> Vec<String> {
+        items.iter().filter(|item| item.result_type == result_type).map(|item| item.id.clone()).collect()
+    }
+
+    #[test]
+    fn test_sections_follow_weights_and_order() {
+        let items = compose_suggestions(&sources(), &default_weights(), &[], false, 8, NOW);
+
+        let types: Vec<&str> = items.iter().map(|item| item.result_type.as_str()).collect();
+        assert_eq!(
+            types,
+            vec!["app", "app", "app", "clipboard", "clipboard", "file", "file", "plugin"]
+        );
+        // Frecency: recent launches beat a larger but stale count
+        assert_eq!(ids_of(&items, "app"), vec!["terminal", "safari", "notes"]);
+        // Sensitive items never appear
+        assert_eq!(ids_of(&items, "clipboard"), vec!["clipboard-c1", "clipboard-c3"]);
+        assert_eq!(ids_of(&items, "plugin"), vec!["plugin-translate"]);
+    }
+
+    #[test]
+    fn test_unfilled_slots_go_to_other_sections() {
+        let mut sources = sources();
+        sources.documents.clear();
+
+        let items = compose_suggestions(&sources, &default_weights(), &[], false, 8, NOW);
+        assert_eq!(items.len(), 8);
+        assert!(ids_of(&items, "file").is_empty());
+        assert_eq!(ids_of(&items, "clipboard").len(), 3);
+
+        let items = compose_suggestions(&sources, &weights(&[("app", 1), ("file", 5)]), &[], false, 10, NOW);
+        assert_eq!(ids_of(&items, "app").len(), 3);
+        assert_eq!(items.len(), 3);
+    }
+
+    #[test]
+    fn test_privacy_mode_only_suggests_apps() {
+        let items = compose_suggestions(&sources(), &default_weights(), &[], true, 10, NOW);
+
+        assert!(items.iter().all(|item| item.result_type == "app"));
+        assert_eq!(ids_of(&items, "app"), vec!["terminal", "safari", "notes"]);
+    }
+
+    #[test]
+    fn test_excluded_apps_are_skipped() {
+        let items = compose_suggestions(&sources(), &default_weights(), &["com.example.terminal".to_string()], false, 10, NOW);
+
+        assert_eq!(ids_of(&items, "app"), vec!["safari", "notes"]);
+    }
+
+    #[test]
+    fn test_anonymized_usage_matches_hashed_paths() {
+        let mut sources = sources();
+        sources.salt = Some("salt".to_string());
+        sources.app_usage = vec![usage(&subject_value("/Applications/Mail.app", Some("salt")), 1, NOW)];
+
+        let items = compose_suggestions(&sources, &default_weights(), &[], false, 10, NOW);
+        assert_eq!(ids_of(&items, "app"), vec!["mail"]);
+        assert!(ids_of(&items, "plugin").is_empty());
+    }
+
+    #[test]
+    fn test_pages_opened_from_the_launcher() {
+        let page = |id: i64, opens: i32, last_opened: i64| BrowserEntry {
+            id: Some(id),
+            url: format!("https://example.com/{id}"),
+            title: format!("Page {id}"),
+            favicon: None,
+            accent_color: None,
+            browser: "chrome".to_string(),
+            entry_type: "history".to_string(),
+            visit_count: 0,
+            last_visited: None,
+            folder: None,
+            description: None,
+            host: None,
+            path: None,
+            launcher_open_count: opens,
+            last_opened_from_launcher: Some(last_opened),
+            cached: 0,
+        };
+        let mut sources = sources();
+        // Opened often but long ago, opened today, and a stale single open
+        sources.browser = vec![page(1, 6, NOW - 60 * DAY), page(2, 3, NOW), page(3, 1, NOW - 60 * DAY)];
+        let mut weights = default_weights();
+        weights.insert("browser".to_string(), 2);
+
+        let items = compose_suggestions(&sources, &weights, &[], false, 12, NOW);
+        assert_eq!(ids_of(&items, "browser"), vec!["browser-2", "browser-1"]);
+        let page = items.iter().find(|item| item.id == "browser-2").unwrap();
+        assert_eq!((page.path.as_str(), page.frequency), ("https://example.com/2", 3));
+
+        let items = compose_suggestions(&sources, &weights, &[], true, 12, NOW);
+        assert!(ids_of(&items, "browser").is_empty());
+    }
+
+    #[test]
+    fn test_clipboard_titles_are_truncated() {
+        let long = "x".repeat(100);
+        assert_eq!(truncate_title(&long, 10), format!("{}…", "x".repeat(9)));
+        assert_eq!(truncate_title("first line\nsecond", 60), "first line…");
+        assert_eq!(truncate_title("  short  ", 60), "short");
+    }
+}
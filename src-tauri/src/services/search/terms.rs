@@ -0,0 +1,123 @@
+//! Query Terms
+//! Splits a query into terms that must all match, in any order. CJK text
+//! has no spaces between words, so terms are matched as plain substrings
+//! and a query switching between CJK and other scripts ("项目report") is
+//! split where the script changes.
+
+use crate::models::preferences::ScoringWeights;
+use crate::models::search::ScoreBreakdown;
+
+/// Score given to a match whose terms were only found outside the name,
+/// e.g. in a file's folder
+const OUTSIDE_NAME_SCORE: f64 = 0.1;
+
+/// Share of the contains weight kept by terms found out of query order
+const OUT_OF_ORDER_FACTOR: f64 = 0.9;
+
+/// Han ideographs, kana and hangul, which are written without spaces
+pub fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3040}'..='\u{30ff}'     // Hiragana, Katakana
+        | '\u{3400}'..='\u{4dbf}'   // CJK Extension A
+        | '\u{4e00}'..='\u{9fff}'   // CJK Unified Ideographs
+        | '\u{ac00}'..='\u{d7af}'   // Hangul Syllables
+        | '\u{f900}'..='\u{faff}'   // CJK Compatibility Ideographs
+        | '\u{20000}'..='\u{2ebef}' // CJK Extensions B-F
+    )
+}
+
+/// Distinct terms of a query, in order: split on whitespace (including the
+/// ideographic space) and where CJK meets other text. Case is kept, so pass
+/// lowercased text to match case-insensitively.
+pub fn query_terms(text: &str) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        let mut start = 0;
+        let mut previous: Option<bool> = None;
+        for (index, c) in word.char_indices() {
+            let cjk = is_cjk(c);
+            if previous.is_some_and(|previous| previous != cjk) {
+                push_term(&mut terms, &word[start..index]);
+                start = index;
+            }
+            previous = Some(cjk);
+        }
+        push_term(&mut terms, &word[start..]);
+    }
+    terms
+}
+
+fn push_term(terms: &mut Vec<String>, term: &str) {
+    if !term.is_empty() && !terms.iter().any(|t| t == term) {
+        terms.push(term.to_string());
+    }
+}
+
+/// Whether every term occurs in at least one of the fields
+pub fn all_terms_match<S: AsRef<str>>(terms: &[String], fields: &[S]) -> bool {
+    terms
+        .iter()
+        .all(|term| fields.iter().any(|field| field.as_ref().contains(term.as_str())))
+}
+
+/// Score for a name matched term by term: the contains weight, scaled by
+/// the share of terms found in the name and by how tightly they sit
+/// together. Terms next to each other in query order keep the full weight.
+pub fn terms_breakdown(name_lower: &str, terms: &[String], weights: &ScoringWeights) -> ScoreBreakdown {
+    let found: Vec<(usize, usize)> = terms
+        .iter()
+        .filter_map(|term| name_lower.find(term.as_str()).map(|start| (start, start + term.len())))
+        .collect();
+    if found.is_empty() || terms.is_empty() {
+        return ScoreBreakdown::fixed(OUTSIDE_NAME_SCORE);
+    }
+
+    let span_start = found.iter().map(|(start, _)| *start).min().unwrap_or(0);
+    let span_end = found.iter().map(|(_, end)| *end).max().unwrap_or(0);
+    let matched_len: usize = found.iter().map(|(start, end)| end - start).sum();
+    let tightness = (matched_len as f64 / (span_end - span_start).max(1) as f64).min(1.0);
+    let in_order = found.windows(2).all(|pair| pair[0].0 <= pair[1].0);
+
+    let mut contains = weights.contains * (found.len() as f64 / terms.len() as f64) * (0.5 + 0.5 * tightness);
+    if !in_order {
+        contains *= OUT_OF_ORDER_FACTOR;
+    }
+    ScoreBreakdown {
+        contains,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_terms_split_whitespace_and_scripts() {
+        assert_eq!(query_terms("budget  q3 xlsx"), vec!["budget", "q3", "xlsx"]);
+        assert_eq!(query_terms("项目　报告 2024"), vec!["项目", "报告", "2024"]);
+        assert_eq!(query_terms("项目report 2024年"), vec!["项目", "report", "2024", "年"]);
+        assert_eq!(query_terms("a b a"), vec!["a", "b"]);
+        assert!(query_terms("  ").is_empty());
+    }
+
+    #[test]
+    fn test_all_terms_match_across_fields() {
+        let terms = query_terms("音乐 netease");
+        assert!(all_terms_match(&terms, &["网易云音乐", "netease music"]));
+        assert!(!all_terms_match(&terms, &["网易云音乐"]));
+    }
+
+    #[test]
+    fn test_tight_in_order_matches_score_higher() {
+        let weights = ScoringWeights::default();
+        let terms = query_terms("q3 budget");
+        let score = |name: &str| terms_breakdown(name, &terms, &weights).total();
+
+        assert_eq!(score("q3 budget.xlsx"), weights.contains * (0.5 + 0.5 * 8.0 / 9.0));
+        assert!(score("q3 budget.xlsx") > score("q3 final budget.xlsx"));
+        assert!(score("q3 final budget.xlsx") > score("budget final q3.xlsx"));
+        assert!(score("budget final q3.xlsx") > score("budget.xlsx"));
+        assert_eq!(score("notes.txt"), OUTSIDE_NAME_SCORE);
+    }
+}
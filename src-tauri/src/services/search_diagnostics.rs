@@ -0,0 +1,466 @@
+//! Search Diagnostics
+//! Explains why a file does or doesn't show up for a query, for bug
+//! reports: whether it exists, is indexed or excluded (and by which rule),
+//! what each provider returned, the score it got or would have got, and
+//! where it ranked against the result limit. Paths are hashed in the report
+//! when usage is anonymized.
+
+use crate::db::files::{file_by_path, FileEntry};
+use crate::models::preferences::AppSettings;
+use crate::services::analytics::subject_value;
+use crate::services::file_indexer::{ExclusionRule, IndexerConfig};
+use crate::services::search::{merge_batches, source_caps, ParsedQuery, ProviderRegistry};
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+/// How many results to ask a provider for when looking for a file that
+/// didn't make its normal batch
+const DEEP_SEARCH_LIMIT: usize = 10_000;
+
+/// The index row for the expected file
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IndexedFile {
+    /// Unix timestamp (s) the row was written
+    pub indexed: i64,
+    /// Modification time (s) recorded in the row
+    pub modified: i64,
+    /// Modification time (s) on disk now, when the file exists
+    pub modified_on_disk: Option<i64>,
+    pub hidden: bool,
+}
+
+impl IndexedFile {
+    fn new(entry: &FileEntry, path: &Path) -> Self {
+        let modified_on_disk = fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|age| age.as_secs() as i64);
+        Self {
+            indexed: entry.indexed,
+            modified: entry.modified,
+            modified_on_disk,
+            hidden: entry.hidden,
+        }
+    }
+}
+
+/// What one provider did with the query
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProviderDiagnosis {
+    pub source: String,
+    /// Enabled in settings and matching the query's scope
+    pub active: bool,
+    /// Results in its batch for the merged search
+    pub result_count: usize,
+    /// 1-based position of the expected file in its batch
+    pub rank: Option<usize>,
+    /// Provider score the expected file got, whether or not it made the batch
+    pub score: Option<f64>,
+    /// Found only past the provider's result limit
+    pub beyond_limit: bool,
+    /// Matched, but dropped by this filter of the query, e.g. "-draft"
+    pub filtered_by: Option<String>,
+}
+
+/// Where the expected file landed among the merged results
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Placement {
+    /// 1-based rank, or the rank it would have had if it was cut
+    pub rank: Option<usize>,
+    /// Merged score, normalized and weighted like the shown results
+    pub score: Option<f64>,
+    /// Most results a search returns
+    pub cutoff: usize,
+    /// Score of the last shown result when the limit was reached
+    pub cutoff_score: Option<f64>,
+}
+
+/// The first reason the expected file isn't shown, or its rank if it is
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum Verdict {
+    Shown {
+        rank: usize,
+    },
+    MissingOnDisk,
+    Excluded {
+        rule: ExclusionRule,
+    },
+    /// Indexable but not in the index yet
+    NotIndexed,
+    /// File search is off, or the query is scoped to another source
+    ProviderInactive,
+    /// Indexed, but no provider matches its name against the query
+    NoMatch,
+    /// Matched, but an exclusion or kind:/ext: filter in the query drops it
+    FilteredOut {
+        filter: String,
+    },
+    /// Matched, but past the provider's result limit
+    BeyondProviderLimit {
+        score: f64,
+    },
+    /// Made a provider batch, but ranked below the merged cutoff, was cut by
+    /// its source's cap or was folded into a duplicate
+    BelowCutoff {
+        rank: Option<usize>,
+        cutoff: usize,
+    },
+}
+
+/// Report for "why doesn't this file show up"
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SearchDiagnosis {
+    pub query: String,
+    pub expected_path: String,
+    /// Paths are hashed
+    pub anonymized: bool,
+    pub exists_on_disk: bool,
+    pub indexed: Option<IndexedFile>,
+    pub exclusion: Option<ExclusionRule>,
+    pub providers: Vec<ProviderDiagnosis>,
+    pub placement: Placement,
+    pub verdict: Verdict,
+}
+
+impl SearchDiagnosis {
+    /// Hash every path in the report
+    fn anonymize(mut self, salt: &str) -> Self {
+        self.expected_path = subject_value(&self.expected_path, Some(salt));
+        let hash_rule = |rule: &mut ExclusionRule| {
+            if let ExclusionRule::ExcludedDir { path, .. } = rule {
+                *path = subject_value(path, Some(salt));
+            }
+        };
+        if let Some(rule) = self.exclusion.as_mut() {
+            hash_rule(rule);
+        }
+        if let Verdict::Excluded { rule } = &mut self.verdict {
+            hash_rule(rule);
+        }
+        self.anonymized = true;
+        self
+    }
+}
+
+/// Run `query` as a search with `limit` results would and explain what
+/// happened to `expected`. `files` is the file index, None when it doesn't
+/// exist yet. Paths in the report are hashed when `salt` is given.
+#[allow(clippy::too_many_arguments)]
+pub fn diagnose(
+    registry: &ProviderRegistry,
+    settings: &AppSettings,
+    query: &ParsedQuery,
+    limit: usize,
+    index: &IndexerConfig,
+    files: Option<&Connection>,
+    expected: &Path,
+    salt: Option<&str>,
+) -> Result<SearchDiagnosis, String> {
+    let expected_str = expected.to_string_lossy().to_string();
+    let exists_on_disk = expected.exists();
+    let entry = match files {
+        Some(conn) => file_by_path(conn, &expected_str).map_err(|e| format!("Failed to read file index: {}", e))?,
+        None => None,
+    };
+    let exclusion = index.exclusion_rule(expected);
+
+    let batches = registry.collect_batches(query, settings, None, limit, None, None);
+    let merged = merge_batches(&batches, &source_caps(query, settings), limit);
+    let active = registry.active(query, settings, None);
+
+    let mut providers = Vec::new();
+    let mut placement = Placement {
+        rank: None,
+        score: None,
+        cutoff: limit,
+        cutoff_score: if merged.results.len() >= limit {
+            merged.results.last().map(|r| r.score)
+        } else {
+            None
+        },
+    };
+    for name in registry.names() {
+        let batch = batches.iter().find(|batch| batch.source == name);
+        let mut diagnosis = ProviderDiagnosis {
+            source: name.to_string(),
+            active: active.iter().any(|provider| provider.name() == name),
+            result_count: batch.map_or(0, |batch| batch.results.len()),
+            rank: None,
+            score: None,
+            beyond_limit: false,
+            filtered_by: None,
+        };
+
+        if let Some(batch) = batch {
+            let mut ranked: Vec<_> = batch.results.iter().collect();
+            ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+            if let Some(index) = ranked.iter().position(|item| item.path == expected_str) {
+                diagnosis.rank = Some(index + 1);
+                diagnosis.score = Some(ranked[index].score);
+            } else if let Some(provider) = registry.get(name) {
+                // Look past the batch and the query's filters for the score
+                // it would have had
+                let found = provider
+                    .search(query, DEEP_SEARCH_LIMIT)
+                    .into_iter()
+                    .find(|item| item.path == expected_str);
+                if let Some(item) = &found {
+                    diagnosis.score = Some(item.score);
+                    if !provider.evaluates_whole_query() {
+                        diagnosis.filtered_by = query.filters.rejection(item);
+                    }
+                    diagnosis.beyond_limit = diagnosis.filtered_by.is_none();
+                }
+            }
+
+            // Weighted like merge_results, against the batch's best score
+            if let (Some(score), None) = (diagnosis.score, placement.score) {
+                let best = batch.results.iter().map(|r| r.score).fold(score, f64::max);
+                let weighted = if best > 0.0 { score / best * batch.weight } else { 0.0 };
+                placement.score = Some(weighted);
+                placement.rank = Some(1 + merged.results.iter().filter(|r| r.score > weighted).count());
+            }
+        }
+        providers.push(diagnosis);
+    }
+
+    let shown = merged.results.iter().position(|item| item.path == expected_str);
+    if let Some(index) = shown {
+        placement.rank = Some(index + 1);
+        placement.score = Some(merged.results[index].score);
+    }
+
+    let file_provider = providers.iter().find(|p| p.source == "file");
+    let verdict = if let Some(index) = shown {
+        Verdict::Shown { rank: index + 1 }
+    } else if !exists_on_disk {
+        Verdict::MissingOnDisk
+    } else if let (Some(rule), None) = (&exclusion, &entry) {
+        Verdict::Excluded { rule: rule.clone() }
+    } else if entry.is_none() {
+        Verdict::NotIndexed
+    } else if !file_provider.is_some_and(|p| p.active) {
+        Verdict::ProviderInactive
+    } else if let Some(filter) = providers.iter().find_map(|p| p.filtered_by.clone()) {
+        Verdict::FilteredOut { filter }
+    } else if providers.iter().any(|p| p.rank.is_some()) {
+        Verdict::BelowCutoff {
+            rank: placement.rank,
+            cutoff: limit,
+        }
+    } else if let Some(score) = providers.iter().find(|p| p.beyond_limit).and_then(|p| p.score) {
+        Verdict::BeyondProviderLimit { score }
+    } else {
+        Verdict::NoMatch
+    };
+
+    let report = SearchDiagnosis {
+        query: query.raw.clone(),
+        expected_path: expected_str,
+        anonymized: false,
+        exists_on_disk,
+        indexed: entry.as_ref().map(|entry| IndexedFile::new(entry, expected)),
+        exclusion,
+        providers,
+        placement,
+        verdict,
+    };
+    Ok(match salt {
+        Some(salt) => report.anonymize(salt),
+        None => report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::files::{open_files_db, upsert_file};
+    use crate::services::search::FileProvider;
+
+    struct Fixture {
+        dir: tempfile::TempDir,
+        registry: ProviderRegistry,
+        settings: AppSettings,
+        index: IndexerConfig,
+        files: Connection,
+    }
+
+    impl Fixture {
+        /// An index rooted at `docs`, holding `indexed` (relative to the root)
+        fn new(indexed: &[&str]) -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let root = dir.path().join("docs");
+            fs::create_dir_all(&root).unwrap();
+            let db_path = dir.path().join("files_index.db");
+            let files = open_files_db(&db_path).unwrap();
+            for name in indexed {
+                let path = root.join(name);
+                upsert_file(
+                    &files,
+                    &FileEntry {
+                        id: None,
+                        path: path.to_string_lossy().to_string(),
+                        filename: path.file_name().unwrap().to_string_lossy().to_string(),
+                        extension: None,
+                        size: 1,
+                        modified: 100,
+                        hidden: false,
+                        indexed: 200,
+                        tags: vec![],
+                        is_placeholder: false,
+                    },
+                )
+                .unwrap();
+            }
+
+            let mut registry = ProviderRegistry::new();
+            registry.register(Box::new(FileProvider::new(db_path)));
+            Self {
+                dir,
+                registry,
+                settings: AppSettings {
+                    enable_file_search: true,
+                    ..AppSettings::default()
+                },
+                index: IndexerConfig {
+                    paths: vec![root],
+                    ..Default::default()
+                },
+                files,
+            }
+        }
+
+        /// Create `name` (relative to the temp dir) on disk
+        fn touch(&self, name: &str) -> std::path::PathBuf {
+            let path = self.dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, b"x").unwrap();
+            path
+        }
+
+        fn diagnose(&self, query: &str, expected: &Path, salt: Option<&str>) -> SearchDiagnosis {
+            let query = ParsedQuery::new(query);
+            diagnose(
+                &self.registry,
+                &self.settings,
+                &query,
+                20,
+                &self.index,
+                Some(&self.files),
+                expected,
+                salt,
+            )
+            .unwrap()
+        }
+
+        fn verdict(&self, query: &str, expected: &Path) -> Verdict {
+            self.diagnose(query, expected, None).verdict
+        }
+    }
+
+    #[test]
+    fn test_shown_file_reports_rank_and_index_row() {
+        let fixture = Fixture::new(&["report.txt", "old-report.txt"]);
+        let report = fixture.touch("docs/report.txt");
+
+        let diagnosis = fixture.diagnose("report", &report, None);
+        assert_eq!(diagnosis.verdict, Verdict::Shown { rank: 1 });
+        assert_eq!(diagnosis.placement.rank, Some(1));
+        let indexed = diagnosis.indexed.unwrap();
+        assert_eq!((indexed.indexed, indexed.modified), (200, 100));
+        assert!(indexed.modified_on_disk.is_some());
+        let file = &diagnosis.providers[0];
+        assert_eq!(
+            (file.source.as_str(), file.result_count, file.rank),
+            ("file", 2, Some(1))
+        );
+    }
+
+    #[test]
+    fn test_unindexed_files_explain_why() {
+        let fixture = Fixture::new(&["notes.txt"]);
+
+        let gone = fixture.dir.path().join("docs/report.txt");
+        assert_eq!(fixture.verdict("report", &gone), Verdict::MissingOnDisk);
+
+        let vendored = fixture.touch("docs/app/node_modules/report/index.txt");
+        let node_modules = fixture.dir.path().join("docs/app/node_modules");
+        assert_eq!(
+            fixture.verdict("index", &vendored),
+            Verdict::Excluded {
+                rule: ExclusionRule::ExcludedDir {
+                    name: "node_modules".to_string(),
+                    path: node_modules.to_string_lossy().to_string(),
+                }
+            }
+        );
+
+        let elsewhere = fixture.touch("Downloads/report.txt");
+        assert_eq!(
+            fixture.verdict("report", &elsewhere),
+            Verdict::Excluded {
+                rule: ExclusionRule::OutsideIndexRoots
+            }
+        );
+
+        let fresh = fixture.touch("docs/report.txt");
+        assert_eq!(fixture.verdict("report", &fresh), Verdict::NotIndexed);
+    }
+
+    #[test]
+    fn test_indexed_files_explain_why() {
+        let mut fixture = Fixture::new(&["notes.txt", "report0.txt", "report1.txt", "report2.txt"]);
+        let notes = fixture.touch("docs/notes.txt");
+        assert_eq!(fixture.verdict("report", &notes), Verdict::NoMatch);
+
+        // A cap of 1 fetches 2 from the provider and keeps 1 after merging
+        fixture.settings.source_limits.insert("file".to_string(), 1);
+        let first = fixture.touch("docs/report0.txt");
+        let second = fixture.touch("docs/report1.txt");
+        let third = fixture.touch("docs/report2.txt");
+        assert_eq!(fixture.verdict("report", &first), Verdict::Shown { rank: 1 });
+        assert_eq!(
+            fixture.verdict("report", &second),
+            Verdict::BelowCutoff {
+                rank: Some(1),
+                cutoff: 20
+            }
+        );
+        let beyond = fixture.diagnose("report", &third, None);
+        assert!(matches!(beyond.verdict, Verdict::BeyondProviderLimit { score } if score > 0.0));
+        assert!(beyond.providers[0].beyond_limit);
+
+        let filtered = fixture.diagnose("report -REPORT0", &first, None);
+        assert_eq!(filtered.verdict, Verdict::FilteredOut { filter: "-report0".to_string() });
+        assert!(!filtered.providers[0].beyond_limit);
+
+        fixture.settings.enable_file_search = false;
+        assert_eq!(fixture.verdict("report", &first), Verdict::ProviderInactive);
+    }
+
+    #[test]
+    fn test_anonymized_report_hashes_paths() {
+        let fixture = Fixture::new(&[]);
+        let vendored = fixture.touch("docs/node_modules/lib.txt");
+
+        let diagnosis = fixture.diagnose("lib", &vendored, Some("salt"));
+        assert!(diagnosis.anonymized);
+        assert_eq!(
+            diagnosis.expected_path,
+            subject_value(&vendored.to_string_lossy(), Some("salt"))
+        );
+        let Verdict::Excluded {
+            rule: ExclusionRule::ExcludedDir { name, path },
+        } = diagnosis.verdict
+        else {
+            panic!("expected an excluded dir, got {:?}", diagnosis.verdict);
+        };
+        assert_eq!(name, "node_modules");
+        assert!(!path.contains("node_modules"));
+        assert_eq!(diagnosis.exclusion, Some(ExclusionRule::ExcludedDir { name, path }));
+    }
+}
@@ -0,0 +1,290 @@
+//! Selection Capture
+//! Reads the text selected in the frontmost app by synthesizing the copy
+//! shortcut, then puts the user's clipboard back. The temporary copy is made
+//! under a clipboard suppression token so it never enters history.
+
+use crate::services::clipboard_watcher::ClipboardSuppression;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for the frontmost app to answer the copy shortcut
+const COPY_TIMEOUT: Duration = Duration::from_millis(300);
+const COPY_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Sends the platform copy shortcut to the frontmost app
+pub trait CopyKeystroke {
+    fn send_copy(&self) -> Result<(), String>;
+}
+
+/// Clipboard contents saved before the temporary copy
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClipboardSnapshot {
+    Empty,
+    Text(String),
+    Image {
+        width: usize,
+        height: usize,
+        bytes: Vec<u8>,
+    },
+}
+
+/// The clipboard operations capture needs
+pub trait ClipboardAccess {
+    fn snapshot(&mut self) -> ClipboardSnapshot;
+    fn restore(&mut self, snapshot: ClipboardSnapshot) -> Result<(), String>;
+    fn clear(&mut self) -> Result<(), String>;
+    fn read_text(&mut self) -> Option<String>;
+}
+
+/// Copy the current selection and return it, None if nothing was selected.
+/// The clipboard is restored whether or not the copy captured anything.
+pub fn capture_selection(
+    keystroke: &dyn CopyKeystroke,
+    clipboard: &mut dyn ClipboardAccess,
+    suppression: &ClipboardSuppression,
+) -> Result<Option<String>, String> {
+    let _token = suppression.suppress();
+    let saved = clipboard.snapshot();
+
+    let captured = clipboard
+        .clear()
+        .and_then(|_| keystroke.send_copy())
+        .map(|_| wait_for_copy(clipboard));
+
+    let restored = clipboard.restore(saved);
+    let captured = captured?;
+    restored?;
+    Ok(captured)
+}
+
+/// Poll until the copied text shows up; the clipboard was cleared first, so
+/// anything there now came from the copy
+fn wait_for_copy(clipboard: &mut dyn ClipboardAccess) -> Option<String> {
+    let deadline = Instant::now() + COPY_TIMEOUT;
+    loop {
+        if let Some(text) = clipboard.read_text().filter(|text| !text.trim().is_empty()) {
+            return Some(text);
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        thread::sleep(COPY_POLL_INTERVAL);
+    }
+}
+
+/// System clipboard via arboard
+pub struct SystemClipboard {
+    clipboard: arboard::Clipboard,
+}
+
+impl SystemClipboard {
+    pub fn new() -> Result<Self, String> {
+        arboard::Clipboard::new()
+            .map(|clipboard| Self { clipboard })
+            .map_err(|e| format!("Failed to open clipboard: {}", e))
+    }
+}
+
+impl ClipboardAccess for SystemClipboard {
+    fn snapshot(&mut self) -> ClipboardSnapshot {
+        if let Ok(text) = self.clipboard.get_text() {
+            return ClipboardSnapshot::Text(text);
+        }
+        match self.clipboard.get_image() {
+            Ok(image) => ClipboardSnapshot::Image {
+                width: image.width,
+                height: image.height,
+                bytes: image.bytes.into_owned(),
+            },
+            Err(_) => ClipboardSnapshot::Empty,
+        }
+    }
+
+    fn restore(&mut self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+        let result = match snapshot {
+            ClipboardSnapshot::Empty => self.clipboard.clear(),
+            ClipboardSnapshot::Text(text) => self.clipboard.set_text(text),
+            ClipboardSnapshot::Image { width, height, bytes } => self.clipboard.set_image(arboard::ImageData {
+                width,
+                height,
+                bytes: bytes.into(),
+            }),
+        };
+        result.map_err(|e| format!("Failed to restore clipboard: {}", e))
+    }
+
+    fn clear(&mut self) -> Result<(), String> {
+        self.clipboard.clear().map_err(|e| format!("Failed to clear clipboard: {}", e))
+    }
+
+    fn read_text(&mut self) -> Option<String> {
+        self.clipboard.get_text().ok()
+    }
+}
+
+/// Cmd+C via CGEvent on macOS, Ctrl+C via SendInput on Windows
+pub struct SystemKeystroke;
+
+impl CopyKeystroke for SystemKeystroke {
+    #[cfg(target_os = "macos")]
+    fn send_copy(&self) -> Result<(), String> {
+        use core_graphics::event::{CGEvent, CGEventFlags, CGEventTapLocation, CGKeyCode};
+        use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+
+        const KEY_C: CGKeyCode = 8;
+
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+            .map_err(|_| "Failed to create keyboard event source".to_string())?;
+        for key_down in [true, false] {
+            let event = CGEvent::new_keyboard_event(source.clone(), KEY_C, key_down)
+                .map_err(|_| "Failed to create keyboard event".to_string())?;
+            // Replaces any modifiers still held from the hotkey
+            event.set_flags(CGEventFlags::CGEventFlagCommand);
+            event.post(CGEventTapLocation::HID);
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    fn send_copy(&self) -> Result<(), String> {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+            SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_KEYUP, VK_CONTROL,
+        };
+
+        const VK_C: u16 = 0x43;
+
+        let key = |vk: u16, flags: u32| INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    wScan: 0,
+                    dwFlags: flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+        let inputs = [
+            key(VK_CONTROL, 0),
+            key(VK_C, 0),
+            key(VK_C, KEYEVENTF_KEYUP),
+            key(VK_CONTROL, KEYEVENTF_KEYUP),
+        ];
+
+        // SAFETY: `inputs` is a valid array of INPUT for the given length
+        let sent = unsafe { SendInput(inputs.len() as u32, inputs.as_ptr(), std::mem::size_of::<INPUT>() as i32) };
+        if sent as usize == inputs.len() {
+            Ok(())
+        } else {
+            Err("SendInput was blocked".to_string())
+        }
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    fn send_copy(&self) -> Result<(), String> {
+        Err("Selection capture isn't supported on this platform".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+
+    /// Clipboard shared between the mocks, like the real one
+    struct FakeClipboard {
+        content: RefCell<ClipboardSnapshot>,
+    }
+
+    struct FakeAccess<'a>(&'a FakeClipboard);
+
+    impl ClipboardAccess for FakeAccess<'_> {
+        fn snapshot(&mut self) -> ClipboardSnapshot {
+            self.0.content.borrow().clone()
+        }
+        fn restore(&mut self, snapshot: ClipboardSnapshot) -> Result<(), String> {
+            *self.0.content.borrow_mut() = snapshot;
+            Ok(())
+        }
+        fn clear(&mut self) -> Result<(), String> {
+            *self.0.content.borrow_mut() = ClipboardSnapshot::Empty;
+            Ok(())
+        }
+        fn read_text(&mut self) -> Option<String> {
+            match &*self.0.content.borrow() {
+                ClipboardSnapshot::Text(text) => Some(text.clone()),
+                _ => None,
+            }
+        }
+    }
+
+    /// Copies `selection` if any, recording whether history was suppressed
+    struct FakeKeystroke<'a> {
+        clipboard: &'a FakeClipboard,
+        selection: Option<&'static str>,
+        suppression: ClipboardSuppression,
+        suppressed_during_copy: RefCell<Option<bool>>,
+        fails: bool,
+    }
+
+    impl CopyKeystroke for FakeKeystroke<'_> {
+        fn send_copy(&self) -> Result<(), String> {
+            *self.suppressed_during_copy.borrow_mut() = Some(self.suppression.is_suppressed());
+            if self.fails {
+                return Err("blocked".to_string());
+            }
+            if let Some(selection) = self.selection {
+                *self.clipboard.content.borrow_mut() = ClipboardSnapshot::Text(selection.to_string());
+            }
+            Ok(())
+        }
+    }
+
+    fn run(
+        previous: ClipboardSnapshot,
+        selection: Option<&'static str>,
+        fails: bool,
+    ) -> (Result<Option<String>, String>, ClipboardSnapshot, Option<bool>) {
+        let clipboard = FakeClipboard { content: RefCell::new(previous) };
+        let suppression = ClipboardSuppression::new();
+        let keystroke = FakeKeystroke {
+            clipboard: &clipboard,
+            selection,
+            suppression: suppression.clone(),
+            suppressed_during_copy: RefCell::new(None),
+            fails,
+        };
+
+        let result = capture_selection(&keystroke, &mut FakeAccess(&clipboard), &suppression);
+        let suppressed = *keystroke.suppressed_during_copy.borrow();
+        (result, clipboard.content.into_inner(), suppressed)
+    }
+
+    #[test]
+    fn test_captures_selection_and_restores_clipboard() {
+        let (result, clipboard, suppressed) =
+            run(ClipboardSnapshot::Text("user's clipboard".to_string()), Some("selected words"), false);
+
+        assert_eq!(result, Ok(Some("selected words".to_string())));
+        assert_eq!(clipboard, ClipboardSnapshot::Text("user's clipboard".to_string()));
+        assert_eq!(suppressed, Some(true));
+    }
+
+    #[test]
+    fn test_no_selection_still_restores_clipboard() {
+        let image = ClipboardSnapshot::Image { width: 1, height: 1, bytes: vec![0, 0, 0, 255] };
+        let (result, clipboard, _) = run(image.clone(), None, false);
+
+        assert_eq!(result, Ok(None));
+        assert_eq!(clipboard, image);
+    }
+
+    #[test]
+    fn test_failed_keystroke_restores_clipboard() {
+        let (result, clipboard, _) = run(ClipboardSnapshot::Text("keep me".to_string()), Some("x"), true);
+
+        assert!(result.is_err());
+        assert_eq!(clipboard, ClipboardSnapshot::Text("keep me".to_string()));
+    }
+}
@@ -0,0 +1,428 @@
+//! Shell Execution Service
+//! Runs allowlisted programs on behalf of plugins through tauri-plugin-shell.
+//! Programs are spawned directly with an argument vector, never through a
+//! shell, so arguments can't smuggle in other commands. The user's own
+//! "> cmd" queries are the exception: the command is theirs, so it goes to
+//! `sh -c`. Each run has a hard timeout, capped output, a scrubbed
+//! environment and an audit log entry.
+
+use crate::services::plugin_sandbox::PluginSandbox;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::Receiver;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::process::CommandEvent;
+use tauri_plugin_shell::ShellExt;
+
+/// Output kept per stream; anything past this is dropped and flagged
+pub const MAX_OUTPUT_BYTES: usize = 1024 * 1024;
+
+/// Timeout when the caller doesn't ask for one
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Longest timeout a caller may ask for
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Audit entries kept in memory and on disk
+pub const AUDIT_CAPACITY: usize = 500;
+
+/// Audit log file in the app data dir, one JSON entry per line
+const AUDIT_LOG_FILE: &str = "shell_audit.jsonl";
+
+/// Environment variable name segments that mark a secret
+const SECRET_ENV_MARKERS: &[&str] = &["TOKEN", "SECRET", "KEY", "APIKEY", "PASSWORD", "PASSWD", "CREDENTIAL", "CREDENTIALS", "AUTH"];
+
+/// Captured result of a run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShellOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// None when the process was killed or terminated by a signal
+    pub exit_code: Option<i32>,
+    /// Output went past MAX_OUTPUT_BYTES and was cut off
+    pub truncated: bool,
+    /// The process outlived its timeout and was killed
+    pub timed_out: bool,
+}
+
+/// One execution, successful or not
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellAuditEntry {
+    /// Plugin that asked for the run, or the launcher feature, such as
+    /// "ssh" or "shell-command" for "> cmd" queries
+    pub caller: String,
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: Option<String>,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    #[serde(default)]
+    pub timed_out: bool,
+    #[serde(default)]
+    pub truncated: bool,
+    /// Set when the process couldn't be started
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Unix timestamp (ms)
+    pub timestamp: i64,
+}
+
+/// Whether `program` is on the global allowlist or the plugin's own.
+/// Names must match exactly, so allowlisting "git" doesn't allow "/tmp/git".
+pub fn is_program_allowed(global: &[String], sandbox: &PluginSandbox, plugin_id: &str, program: &str) -> bool {
+    global.iter().any(|allowed| allowed == program) || sandbox.is_shell_command_allowed(plugin_id, program)
+}
+
+/// Whether an environment variable looks like it holds a credential
+pub fn is_secret_env_var(name: &str) -> bool {
+    let upper = name.to_uppercase();
+    upper
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|segment| SECRET_ENV_MARKERS.contains(&segment))
+}
+
+/// The current environment without credentials
+pub fn scrubbed_env() -> Vec<(String, String)> {
+    std::env::vars().filter(|(name, _)| !is_secret_env_var(name)).collect()
+}
+
+/// Windows runs .bat/.cmd files through cmd.exe, which re-parses the arguments
+fn is_batch_file(program: &str) -> bool {
+    let lower = program.to_lowercase();
+    lower.ends_with(".bat") || lower.ends_with(".cmd")
+}
+
+/// Append `chunk` to `buffer` up to MAX_OUTPUT_BYTES; returns false if anything was dropped
+fn append_capped(buffer: &mut Vec<u8>, chunk: &[u8]) -> bool {
+    let room = MAX_OUTPUT_BYTES.saturating_sub(buffer.len());
+    buffer.extend_from_slice(&chunk[..chunk.len().min(room)]);
+    chunk.len() <= room
+}
+
+/// Drain a spawned process's events until it exits or `timeout` passes, in
+/// which case `kill` is called and the output so far is returned.
+pub async fn collect_output(mut events: Receiver<CommandEvent>, timeout: Duration, kill: impl FnOnce()) -> ShellOutput {
+    let deadline = tokio::time::Instant::now() + timeout;
+    let mut stdout = Vec::new();
+    let mut stderr = Vec::new();
+    let mut output = ShellOutput::default();
+
+    loop {
+        let event = match tokio::time::timeout_at(deadline, events.recv()).await {
+            Ok(Some(event)) => event,
+            // Channel closed without a Terminated event
+            Ok(None) => break,
+            Err(_) => {
+                kill();
+                output.timed_out = true;
+                break;
+            }
+        };
+
+        match event {
+            CommandEvent::Stdout(chunk) => output.truncated |= !append_capped(&mut stdout, &chunk),
+            CommandEvent::Stderr(chunk) => output.truncated |= !append_capped(&mut stderr, &chunk),
+            CommandEvent::Error(message) => output.truncated |= !append_capped(&mut stderr, message.as_bytes()),
+            CommandEvent::Terminated(payload) => {
+                output.exit_code = payload.code;
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    output.stdout = String::from_utf8_lossy(&stdout).to_string();
+    output.stderr = String::from_utf8_lossy(&stderr).to_string();
+    output
+}
+
+/// Run `program` with `args` for `caller` and record it in the audit log.
+/// The caller must already have checked the allowlist.
+pub async fn run(
+    handle: &AppHandle,
+    caller: &str,
+    program: &str,
+    args: &[String],
+    cwd: Option<&Path>,
+    timeout: Option<Duration>,
+) -> Result<ShellOutput, String> {
+    let timeout = timeout.unwrap_or(DEFAULT_TIMEOUT).min(MAX_TIMEOUT);
+    let start = Instant::now();
+    let result = spawn_and_collect(handle, program, args, cwd, timeout).await;
+
+    if let Some(audit) = handle.try_state::<ShellAuditLog>() {
+        let output = result.as_ref().ok();
+        audit.record(ShellAuditEntry {
+            caller: caller.to_string(),
+            program: program.to_string(),
+            args: args.to_vec(),
+            cwd: cwd.map(|dir| dir.to_string_lossy().to_string()),
+            exit_code: output.and_then(|o| o.exit_code),
+            duration_ms: start.elapsed().as_millis() as u64,
+            timed_out: output.is_some_and(|o| o.timed_out),
+            truncated: output.is_some_and(|o| o.truncated),
+            error: result.as_ref().err().cloned(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+
+    result
+}
+
+/// Start `program` for `caller` without waiting for it, for launching apps
+/// like a terminal that outlive the request. The environment is kept so
+/// the program sees the user's agent sockets; stdio goes nowhere. The
+/// caller must already have checked the allowlist.
+pub fn launch(handle: &AppHandle, caller: &str, program: &str, args: &[String]) -> Result<(), String> {
+    let start = Instant::now();
+    let result = if cfg!(target_os = "windows") && is_batch_file(program) {
+        Err("Batch files can't be run safely with arguments".to_string())
+    } else {
+        std::process::Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            // Reap it when it exits so it doesn't linger as a zombie
+            .map(|mut child| drop(std::thread::spawn(move || child.wait())))
+            .map_err(|e| format!("Failed to run {}: {}", program, e))
+    };
+
+    if let Some(audit) = handle.try_state::<ShellAuditLog>() {
+        audit.record(ShellAuditEntry {
+            caller: caller.to_string(),
+            program: program.to_string(),
+            args: args.to_vec(),
+            cwd: None,
+            exit_code: None,
+            duration_ms: start.elapsed().as_millis() as u64,
+            timed_out: false,
+            truncated: false,
+            error: result.as_ref().err().cloned(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+        });
+    }
+
+    result
+}
+
+async fn spawn_and_collect(
+    handle: &AppHandle,
+    program: &str,
+    args: &[String],
+    cwd: Option<&Path>,
+    timeout: Duration,
+) -> Result<ShellOutput, String> {
+    if cfg!(target_os = "windows") && is_batch_file(program) {
+        return Err("Batch files can't be run safely with arguments".to_string());
+    }
+
+    let mut command = handle
+        .shell()
+        .command(program)
+        .args(args)
+        .env_clear()
+        .envs(scrubbed_env())
+        .set_raw_out(true);
+    if let Some(dir) = cwd {
+        command = command.current_dir(dir);
+    }
+
+    let (events, child) = command
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    Ok(collect_output(events, timeout, move || {
+        let _ = child.kill();
+    })
+    .await)
+}
+
+/// Recent shell executions, persisted to the app data dir
+pub struct ShellAuditLog {
+    entries: Mutex<VecDeque<ShellAuditEntry>>,
+    path: Option<PathBuf>,
+}
+
+impl ShellAuditLog {
+    /// Create a log, loading earlier entries from `data_dir` if given
+    pub fn new(data_dir: Option<&Path>) -> Self {
+        let path = data_dir.map(|dir| dir.join(AUDIT_LOG_FILE));
+        let mut entries: VecDeque<ShellAuditEntry> = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if entries.len() > AUDIT_CAPACITY {
+            entries.drain(..entries.len() - AUDIT_CAPACITY);
+            // Rewrite the file so it doesn't grow without bound
+            if let Some(path) = &path {
+                let content: String = entries
+                    .iter()
+                    .filter_map(|entry| serde_json::to_string(entry).ok())
+                    .map(|line| line + "\n")
+                    .collect();
+                let _ = fs::write(path, content);
+            }
+        }
+
+        Self {
+            entries: Mutex::new(entries),
+            path,
+        }
+    }
+
+    pub fn record(&self, entry: ShellAuditEntry) {
+        if let Some(path) = &self.path {
+            if let Ok(line) = serde_json::to_string(&entry) {
+                let appended = OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .and_then(|mut file| writeln!(file, "{}", line));
+                if let Err(e) = appended {
+                    eprintln!("[ShellExec] Failed to write audit log: {}", e);
+                }
+            }
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.push_back(entry);
+        while entries.len() > AUDIT_CAPACITY {
+            entries.pop_front();
+        }
+    }
+
+    /// Entries newest first, optionally only those from `caller`
+    pub fn entries(&self, caller: Option<&str>) -> Vec<ShellAuditEntry> {
+        let entries = self.entries.lock().unwrap();
+        entries
+            .iter()
+            .rev()
+            .filter(|entry| caller.is_none() || caller == Some(entry.caller.as_str()))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::plugin_sandbox::PluginPermission;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use tauri::async_runtime::{block_on, channel};
+    use tauri_plugin_shell::process::TerminatedPayload;
+
+    fn entry(caller: &str) -> ShellAuditEntry {
+        ShellAuditEntry {
+            caller: caller.to_string(),
+            program: "git".to_string(),
+            args: vec!["status".to_string()],
+            cwd: None,
+            exit_code: Some(0),
+            duration_ms: 12,
+            timed_out: false,
+            truncated: false,
+            error: None,
+            timestamp: 0,
+        }
+    }
+
+    #[test]
+    fn test_allowlist_denies_unlisted_programs() {
+        let sandbox = PluginSandbox::new();
+        sandbox.register_plugin("devtools".to_string(), vec![PluginPermission::Shell]).unwrap();
+        sandbox.set_shell_allowlist("devtools", vec!["git".to_string()]).unwrap();
+        let global = vec!["echo".to_string()];
+
+        assert!(is_program_allowed(&global, &sandbox, "devtools", "git"));
+        assert!(is_program_allowed(&global, &sandbox, "devtools", "echo"));
+        assert!(!is_program_allowed(&global, &sandbox, "devtools", "rm"));
+        assert!(!is_program_allowed(&global, &sandbox, "devtools", "/usr/bin/git"));
+        assert!(!is_program_allowed(&global, &sandbox, "other", "git"));
+    }
+
+    #[test]
+    fn test_timeout_kills_process() {
+        let (tx, rx) = channel(8);
+        let killed = Arc::new(AtomicBool::new(false));
+        let flag = killed.clone();
+
+        let output = block_on(async move {
+            tx.send(CommandEvent::Stdout(b"working".to_vec())).await.unwrap();
+            // `tx` stays alive, like a process that never exits
+            let output = collect_output(rx, Duration::from_millis(50), move || flag.store(true, Ordering::SeqCst)).await;
+            drop(tx);
+            output
+        });
+
+        assert!(killed.load(Ordering::SeqCst));
+        assert!(output.timed_out);
+        assert_eq!(output.exit_code, None);
+        assert_eq!(output.stdout, "working");
+    }
+
+    #[test]
+    fn test_output_truncated_at_cap() {
+        let (tx, rx) = channel(8);
+
+        let output = block_on(async move {
+            let sender = tauri::async_runtime::spawn(async move {
+                for _ in 0..3 {
+                    tx.send(CommandEvent::Stdout(vec![b'a'; MAX_OUTPUT_BYTES / 2 + 1])).await.unwrap();
+                }
+                tx.send(CommandEvent::Stderr(b"warning".to_vec())).await.unwrap();
+                tx.send(CommandEvent::Terminated(TerminatedPayload { code: Some(0), signal: None }))
+                    .await
+                    .unwrap();
+            });
+            let output = collect_output(rx, Duration::from_secs(5), || panic!("should not be killed")).await;
+            sender.await.unwrap();
+            output
+        });
+
+        assert!(output.truncated);
+        assert!(!output.timed_out);
+        assert_eq!(output.stdout.len(), MAX_OUTPUT_BYTES);
+        assert_eq!(output.stderr, "warning");
+        assert_eq!(output.exit_code, Some(0));
+    }
+
+    #[test]
+    fn test_secret_env_vars_are_scrubbed() {
+        assert!(is_secret_env_var("GITHUB_TOKEN"));
+        assert!(is_secret_env_var("AWS_SECRET_ACCESS_KEY"));
+        assert!(is_secret_env_var("openai_api_key"));
+        assert!(is_secret_env_var("DB_PASSWORD"));
+        assert!(!is_secret_env_var("PATH"));
+        assert!(!is_secret_env_var("HOME"));
+        assert!(!is_secret_env_var("KEYBOARD_LAYOUT"));
+    }
+
+    #[test]
+    fn test_audit_log_persists_and_filters() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = ShellAuditLog::new(Some(dir.path()));
+        log.record(entry("devtools"));
+        log.record(entry("other"));
+
+        let reloaded = ShellAuditLog::new(Some(dir.path()));
+        assert_eq!(reloaded.entries(None).len(), 2);
+        assert_eq!(reloaded.entries(None)[0].caller, "other");
+        assert_eq!(reloaded.entries(Some("devtools")).len(), 1);
+    }
+}
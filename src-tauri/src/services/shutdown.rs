@@ -0,0 +1,176 @@
+//! Shutdown Service
+//! Ordered shutdown for quit and restart: background workers are stopped
+//! before pending writes are flushed and state is saved. Hooks run on a
+//! worker thread under a deadline so a hung hook can't keep the app alive.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shutdown gives up on remaining hooks after this
+pub const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(5);
+
+/// Shutdown phases, run in declaration order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShutdownStage {
+    /// Clipboard watcher and similar event sources
+    StopWatchers,
+    /// File indexer and other background writers
+    StopIndexers,
+    /// Buffered data that would otherwise be lost
+    FlushWrites,
+    /// State restored on next launch; skipped by `quit_app(save_state: false)`
+    SaveState,
+}
+
+type Hook = Arc<dyn Fn() -> Result<(), String> + Send + Sync>;
+
+/// What a shutdown run did
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ShutdownReport {
+    /// Hooks that finished, in run order
+    pub completed: Vec<String>,
+    /// Hooks that failed, with their error
+    pub failed: Vec<(String, String)>,
+    /// The deadline passed before every hook finished
+    pub timed_out: bool,
+}
+
+/// Hooks run on quit and restart
+#[derive(Default)]
+pub struct ShutdownRegistry {
+    hooks: Mutex<Vec<(ShutdownStage, String, Hook)>>,
+    started: AtomicBool,
+}
+
+impl ShutdownRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a hook. Hooks in the same stage run in registration order.
+    pub fn register(
+        &self,
+        stage: ShutdownStage,
+        name: &str,
+        hook: impl Fn() -> Result<(), String> + Send + Sync + 'static,
+    ) {
+        if let Ok(mut hooks) = self.hooks.lock() {
+            hooks.push((stage, name.to_string(), Arc::new(hook)));
+        }
+    }
+
+    /// Whether a shutdown has already started
+    pub fn is_started(&self) -> bool {
+        self.started.load(Ordering::SeqCst)
+    }
+
+    /// Run the hooks of the included stages in order. A failing hook doesn't
+    /// stop the rest. Returns at the deadline even if hooks are still running.
+    pub fn run(&self, deadline: Duration, include: impl Fn(ShutdownStage) -> bool) -> ShutdownReport {
+        self.started.store(true, Ordering::SeqCst);
+
+        let mut hooks: Vec<(ShutdownStage, String, Hook)> = self
+            .hooks
+            .lock()
+            .map(|hooks| hooks.iter().filter(|(stage, _, _)| include(*stage)).cloned().collect())
+            .unwrap_or_default();
+        // Stable, so registration order holds within a stage
+        hooks.sort_by_key(|(stage, _, _)| *stage);
+
+        let expected = hooks.len();
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            for (_, name, hook) in hooks {
+                let result = hook();
+                if tx.send((name, result)).is_err() {
+                    return;
+                }
+            }
+        });
+
+        let mut report = ShutdownReport::default();
+        let end = Instant::now() + deadline;
+        for _ in 0..expected {
+            let remaining = end.saturating_duration_since(Instant::now());
+            match rx.recv_timeout(remaining) {
+                Ok((name, Ok(()))) => report.completed.push(name),
+                Ok((name, Err(e))) => report.failed.push((name, e)),
+                Err(_) => {
+                    report.timed_out = true;
+                    break;
+                }
+            }
+        }
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(registry: &ShutdownRegistry, log: &Arc<Mutex<Vec<String>>>, stage: ShutdownStage, name: &str) {
+        let log = log.clone();
+        let entry = name.to_string();
+        registry.register(stage, name, move || {
+            log.lock().unwrap().push(entry.clone());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_hooks_run_in_stage_order() {
+        let registry = ShutdownRegistry::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        recording(&registry, &log, ShutdownStage::SaveState, "window");
+        recording(&registry, &log, ShutdownStage::FlushWrites, "rollups");
+        recording(&registry, &log, ShutdownStage::StopIndexers, "file_indexer");
+        recording(&registry, &log, ShutdownStage::StopWatchers, "clipboard");
+        recording(&registry, &log, ShutdownStage::FlushWrites, "sandbox");
+        registry.register(ShutdownStage::StopIndexers, "browser", || Err("busy".to_string()));
+
+        let report = registry.run(SHUTDOWN_DEADLINE, |_| true);
+
+        let order = vec!["clipboard", "file_indexer", "rollups", "sandbox", "window"];
+        assert_eq!(*log.lock().unwrap(), order);
+        assert_eq!(report.completed, order);
+        assert_eq!(report.failed, vec![("browser".to_string(), "busy".to_string())]);
+        assert!(!report.timed_out);
+        assert!(registry.is_started());
+    }
+
+    #[test]
+    fn test_excluded_stages_are_skipped() {
+        let registry = ShutdownRegistry::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        recording(&registry, &log, ShutdownStage::StopWatchers, "clipboard");
+        recording(&registry, &log, ShutdownStage::SaveState, "window");
+
+        registry.run(SHUTDOWN_DEADLINE, |stage| stage != ShutdownStage::SaveState);
+
+        assert_eq!(*log.lock().unwrap(), vec!["clipboard"]);
+    }
+
+    #[test]
+    fn test_deadline_stops_waiting_on_hung_hook() {
+        let registry = ShutdownRegistry::new();
+        let log = Arc::new(Mutex::new(Vec::new()));
+        recording(&registry, &log, ShutdownStage::StopWatchers, "clipboard");
+        registry.register(ShutdownStage::StopIndexers, "hung", || {
+            std::thread::sleep(Duration::from_secs(5));
+            Ok(())
+        });
+        recording(&registry, &log, ShutdownStage::SaveState, "window");
+
+        let start = Instant::now();
+        let report = registry.run(Duration::from_millis(100), |_| true);
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(report.timed_out);
+        assert_eq!(report.completed, vec!["clipboard"]);
+    }
+}
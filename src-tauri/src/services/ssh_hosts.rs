@@ -0,0 +1,505 @@
+//! SSH Hosts
+//! Hosts to jump to, read from ~/.ssh/config and ~/.ssh/known_hosts.
+//! The config parser understands the subset of ssh_config that names hosts:
+//! Host stanzas (several aliases each, wildcard patterns skipped), HostName,
+//! User, Port, and Include one level deep. Hashed known_hosts entries can't
+//! be read back and are skipped.
+
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Includes nested deeper than this are ignored
+const MAX_INCLUDE_DEPTH: usize = 1;
+
+/// Where a host was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SshHostSource {
+    Config,
+    KnownHosts,
+}
+
+/// The port ssh connects to when neither the config nor the entry names one
+const DEFAULT_PORT: u16 = 22;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SshHost {
+    /// What to pass to ssh: a config alias or a known host name
+    pub alias: String,
+    pub hostname: Option<String>,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub source: SshHostSource,
+}
+
+impl SshHost {
+    fn new(alias: &str, source: SshHostSource) -> Self {
+        Self {
+            alias: alias.to_string(),
+            hostname: None,
+            user: None,
+            port: None,
+            source,
+        }
+    }
+
+    /// "deploy@10.0.0.5:2222", or the alias when the config adds nothing
+    pub fn destination(&self) -> String {
+        let mut destination = self.hostname.clone().unwrap_or_else(|| self.alias.clone());
+        if let Some(user) = &self.user {
+            destination = format!("{}@{}", user, destination);
+        }
+        if let Some(port) = self.port {
+            destination = format!("{}:{}", destination, port);
+        }
+        destination
+    }
+    /// Whether this config host reaches the same endpoint as `known`, a
+    /// known_hosts entry: same name, by alias or HostName, and same port.
+    /// "[example.com]:2222" is a different server from "example.com".
+    fn covers(&self, known: &SshHost) -> bool {
+        let same_name = self.alias == known.alias || self.hostname.as_deref() == Some(known.alias.as_str());
+        same_name && self.port.unwrap_or(DEFAULT_PORT) == known.port.unwrap_or(DEFAULT_PORT)
+    }
+}
+
+/// Hosts from the ssh config in `ssh_dir` followed by known hosts the
+/// config doesn't already name. Missing files just contribute nothing.
+pub fn load_hosts(ssh_dir: &Path) -> Vec<SshHost> {
+    let mut hosts = parse_config_file(&ssh_dir.join("config"), ssh_dir);
+
+    if let Ok(content) = fs::read_to_string(ssh_dir.join("known_hosts")) {
+        for host in parse_known_hosts(&content) {
+            if !hosts.iter().any(|h| h.covers(&host)) {
+                hosts.push(host);
+            }
+        }
+    }
+    hosts
+}
+
+/// Hosts named by the config file at `path`. Relative Include paths are
+/// resolved against `ssh_dir`, as ssh does for user config files.
+pub fn parse_config_file(path: &Path, ssh_dir: &Path) -> Vec<SshHost> {
+    let mut hosts = Vec::new();
+    parse_config_into(path, ssh_dir, 0, &mut hosts);
+    hosts
+}
+
+fn parse_config_into(path: &Path, ssh_dir: &Path, depth: usize, hosts: &mut Vec<SshHost>) {
+    let Ok(content) = fs::read_to_string(path) else {
+        return;
+    };
+
+    // Indices into `hosts` of the current stanza's aliases; empty outside
+    // a Host stanza or inside one with only patterns
+    let mut stanza: Vec<usize> = Vec::new();
+    for line in content.lines() {
+        let Some((keyword, args)) = split_directive(line) else {
+            continue;
+        };
+
+        match keyword.to_lowercase().as_str() {
+            "host" => {
+                stanza = args
+                    .iter()
+                    .filter(|alias| !is_pattern(alias))
+                    .map(|alias| host_index(hosts, alias))
+                    .collect();
+            }
+            // Match blocks apply conditionally; their settings aren't shown
+            "match" => stanza.clear(),
+            "include" if depth < MAX_INCLUDE_DEPTH => {
+                for pattern in &args {
+                    for included in expand_include(pattern, ssh_dir) {
+                        parse_config_into(&included, ssh_dir, depth + 1, hosts);
+                    }
+                }
+            }
+            "hostname" => set_first(hosts, &stanza, |host| &mut host.hostname, args.first().cloned()),
+            "user" => set_first(hosts, &stanza, |host| &mut host.user, args.first().cloned()),
+            "port" => {
+                let port = args.first().and_then(|port| port.parse().ok());
+                set_first(hosts, &stanza, |host| &mut host.port, port);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Keyword and arguments of a config line, None for blanks and comments.
+/// Keywords may be separated from arguments by whitespace or '=', and
+/// arguments may be double-quoted to contain spaces.
+fn split_directive(line: &str) -> Option<(String, Vec<String>)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let split_at = line.find(|c: char| c.is_whitespace() || c == '=').unwrap_or(line.len());
+    let keyword = line[..split_at].to_string();
+    let rest = line[split_at..].trim_start();
+    let rest = rest.strip_prefix('=').unwrap_or(rest);
+
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in rest.chars() {
+        match c {
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    Some((keyword, args))
+}
+
+/// Wildcards and negations match many hosts and can't be connected to
+fn is_pattern(alias: &str) -> bool {
+    alias.contains(['*', '?']) || alias.starts_with('!')
+}
+
+/// Index of the host named `alias`, added if it's new
+fn host_index(hosts: &mut Vec<SshHost>, alias: &str) -> usize {
+    hosts.iter().position(|host| host.alias == alias).unwrap_or_else(|| {
+        hosts.push(SshHost::new(alias, SshHostSource::Config));
+        hosts.len() - 1
+    })
+}
+
+/// Set a field on each host of the stanza that doesn't have it yet; like
+/// ssh, the first value found for a host wins
+fn set_first<T>(
+    hosts: &mut [SshHost],
+    stanza: &[usize],
+    field: impl Fn(&mut SshHost) -> &mut Option<T>,
+    value: Option<T>,
+) where
+    T: Clone,
+{
+    let Some(value) = value else {
+        return;
+    };
+    for &index in stanza {
+        let slot = field(&mut hosts[index]);
+        if slot.is_none() {
+            *slot = Some(value.clone());
+        }
+    }
+}
+
+/// Files an Include argument names: `~/` is the home directory, relative
+/// paths are under `ssh_dir`, and `*` or `?` in the file name match within
+/// its directory, in sorted order
+fn expand_include(pattern: &str, ssh_dir: &Path) -> Vec<PathBuf> {
+    let path = match pattern.strip_prefix("~/") {
+        Some(rest) => match ssh_dir.parent() {
+            Some(home) => home.join(rest),
+            None => return Vec::new(),
+        },
+        None => ssh_dir.join(pattern),
+    };
+
+    let Some(file_pattern) = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .filter(|name| is_pattern(name))
+    else {
+        return vec![path];
+    };
+    let Some(dir) = path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut matches: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| wildcard_match(file_pattern, name))
+        })
+        .map(|entry| entry.path())
+        .collect();
+    matches.sort();
+    matches
+}
+
+/// `*` matches any run of characters and `?` any one character
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Where the last '*' was and the text position it's matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Hosts in known_hosts, one per line, skipping hashed names, markers
+/// (@cert-authority, @revoked) and wildcard patterns
+pub fn parse_known_hosts(content: &str) -> Vec<SshHost> {
+    let mut hosts: Vec<SshHost> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('@') {
+            continue;
+        }
+        let Some(names) = line.split_whitespace().next() else {
+            continue;
+        };
+        if names.starts_with("|1|") {
+            continue;
+        }
+
+        // "name,10.0.0.5" lists a host by name and address; the name is enough
+        let Some(host) = names
+            .split(',')
+            .filter(|name| !is_pattern(name))
+            .find_map(parse_known_host)
+        else {
+            continue;
+        };
+        if !hosts.iter().any(|h| h.alias == host.alias && h.port == host.port) {
+            hosts.push(host);
+        }
+    }
+    hosts
+}
+
+/// "example.com" or "[example.com]:2222"
+fn parse_known_host(name: &str) -> Option<SshHost> {
+    let mut host = SshHost::new(name, SshHostSource::KnownHosts);
+    if let Some(bracketed) = name.strip_prefix('[') {
+        let (alias, port) = bracketed.split_once("]:")?;
+        host.alias = alias.to_string();
+        host.port = Some(port.parse().ok()?);
+    }
+    Some(host)
+}
+
+/// Whether `host` is safe to hand to a terminal command line: no spaces,
+/// separators or a leading '-' that ssh or the terminal would read as an
+/// option or a second command
+pub fn is_valid_host(host: &str) -> bool {
+    !host.is_empty()
+        && !host.starts_with('-')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '@' | ':' | '[' | ']'))
+}
+
+/// Program and arguments that open a terminal connected to `host`. `%h` in
+/// the template becomes the host; a template without it gets an
+/// `ssh://host` URL appended, which macOS terminals open as a session.
+pub fn terminal_command(template: &str, host: &str) -> Result<(String, Vec<String>), String> {
+    let mut parts: Vec<String> = template.split_whitespace().map(str::to_string).collect();
+    if parts.is_empty() {
+        return Err("The SSH terminal command is empty".to_string());
+    }
+
+    if parts.iter().any(|part| part.contains("%h")) {
+        for part in &mut parts {
+            *part = part.replace("%h", host);
+        }
+    } else {
+        parts.push(format!("ssh://{}", host));
+    }
+
+    let program = parts.remove(0);
+    Ok((program, parts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A ~/.ssh directory holding `files`
+    fn ssh_dir(files: &[(&str, &str)]) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, content) in files {
+            let path = dir.path().join(name);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        dir
+    }
+
+    fn aliases(hosts: &[SshHost]) -> Vec<&str> {
+        hosts.iter().map(|h| h.alias.as_str()).collect()
+    }
+
+    const CONFIG: &str = "\
+# Work servers
+Host prod-db prod-db.internal
+    HostName 10.0.0.5
+    User deploy
+    Port 2222
+
+  # Staging shares a bastion
+Host staging
+    Hostname=staging.example.com
+    user ci
+
+Host *.internal !secret bastion
+    User jump
+
+Host *
+    ServerAliveInterval 60
+    User nobody
+
+Host \"spaced alias\"
+    HostName spaced.example.com
+";
+
+    #[test]
+    fn test_config_hosts_with_comments_and_aliases() {
+        let dir = ssh_dir(&[("config", CONFIG)]);
+        let hosts = parse_config_file(&dir.path().join("config"), dir.path());
+
+        // Wildcard and negated patterns aren't hosts
+        assert_eq!(
+            aliases(&hosts),
+            vec!["prod-db", "prod-db.internal", "staging", "bastion", "spaced alias"]
+        );
+
+        let prod = &hosts[0];
+        assert_eq!(prod.hostname.as_deref(), Some("10.0.0.5"));
+        assert_eq!(prod.destination(), "deploy@10.0.0.5:2222");
+        // Every alias of a stanza gets its settings, and the first value
+        // wins over later stanzas that also match
+        assert_eq!(hosts[1].destination(), "deploy@10.0.0.5:2222");
+
+        // Keywords are case-insensitive and may use '='
+        assert_eq!(hosts[2].destination(), "ci@staging.example.com");
+        assert_eq!(hosts[3].destination(), "jump@bastion");
+        assert_eq!(hosts[4].hostname.as_deref(), Some("spaced.example.com"));
+    }
+
+    #[test]
+    fn test_include_one_level_deep() {
+        let dir = ssh_dir(&[
+            (
+                "config",
+                "Include config.d/*.conf\nInclude ~/.ssh/extra\nInclude missing\n\nHost main\n    HostName main.example.com\n",
+            ),
+            ("config.d/10-web.conf", "Host web1 web2\n    User www\n"),
+            ("config.d/20-db.conf", "Host db\n    HostName db.example.com\n    Include nested\n"),
+            ("config.d/notes.txt", "Host not-included\n"),
+            ("extra", "Host extra\n"),
+            ("nested", "Host too-deep\n"),
+        ]);
+        // "~/" is the parent of the ssh dir, so make it look like ~/.ssh
+        let home = tempfile::tempdir().unwrap();
+        let ssh = home.path().join(".ssh");
+        fs::rename(dir.path(), &ssh).unwrap();
+
+        let hosts = parse_config_file(&ssh.join("config"), &ssh);
+        assert_eq!(aliases(&hosts), vec!["web1", "web2", "db", "extra", "main"]);
+        assert_eq!(hosts[1].user.as_deref(), Some("www"));
+        assert_eq!(hosts[2].hostname.as_deref(), Some("db.example.com"));
+    }
+
+    #[test]
+    fn test_known_hosts_skip_hashed_entries() {
+        let known = "\
+prod-db.internal,10.0.0.5 ssh-ed25519 AAAAC3Nza
+|1|F1E1KeoE/eEWhi10WpGv4OdiO6Y=|3988QV0VE8wmZL7suNrYQLITLCg= ssh-rsa AAAAB3Nza
+[git.example.com]:2222 ssh-ed25519 AAAAC3Nza
+# comment
+@cert-authority *.example.com ssh-rsa AAAAB3Nza
+10.1.2.3 ecdsa-sha2-nistp256 AAAAE2Vj
+github.com ssh-ed25519 AAAAC3Nza
+github.com ssh-rsa AAAAB3Nza
+";
+        let hosts = parse_known_hosts(known);
+        assert_eq!(
+            aliases(&hosts),
+            vec!["prod-db.internal", "git.example.com", "10.1.2.3", "github.com"]
+        );
+        assert_eq!(hosts[1].port, Some(2222));
+        assert!(hosts.iter().all(|h| h.source == SshHostSource::KnownHosts));
+    }
+
+    #[test]
+    fn test_load_merges_config_and_known_hosts() {
+        let dir = ssh_dir(&[
+            ("config", CONFIG),
+            (
+                "known_hosts",
+                "[10.0.0.5]:2222 ssh-ed25519 AAAA\n10.0.0.5 ssh-ed25519 AAAA\nstaging ssh-ed25519 AAAA\n\
+                 [staging]:2200 ssh-ed25519 AAAA\nnew.example.com ssh-ed25519 AAAA\n",
+            ),
+        ]);
+        let hosts = load_hosts(dir.path());
+
+        // Known hosts the config already covers, by alias or HostName on the
+        // same port, aren't repeated
+        let known: Vec<(&str, Option<u16>)> = hosts
+            .iter()
+            .filter(|h| h.source == SshHostSource::KnownHosts)
+            .map(|h| (h.alias.as_str(), h.port))
+            .collect();
+        assert_eq!(known, vec![("10.0.0.5", None), ("staging", Some(2200)), ("new.example.com", None)]);
+
+        assert!(load_hosts(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.conf", "10-web.conf"));
+        assert!(wildcard_match("host?", "host1"));
+        assert!(wildcard_match("a*b*c", "aXXbYYc"));
+        assert!(!wildcard_match("*.conf", "notes.txt"));
+        assert!(!wildcard_match("host?", "host12"));
+    }
+
+    #[test]
+    fn test_terminal_command_from_template() {
+        assert_eq!(
+            terminal_command("wt ssh %h", "prod-db").unwrap(),
+            ("wt".to_string(), vec!["ssh".to_string(), "prod-db".to_string()])
+        );
+        assert_eq!(
+            terminal_command("open -a iTerm", "prod-db").unwrap(),
+            (
+                "open".to_string(),
+                vec!["-a".to_string(), "iTerm".to_string(), "ssh://prod-db".to_string()]
+            )
+        );
+        assert!(terminal_command("  ", "prod-db").is_err());
+
+        assert!(is_valid_host("deploy@10.0.0.5"));
+        assert!(is_valid_host("prod-db.internal"));
+        assert!(!is_valid_host("-oProxyCommand=calc"));
+        assert!(!is_valid_host("prod; calc"));
+        assert!(!is_valid_host("spaced alias"));
+    }
+}
@@ -0,0 +1,220 @@
+//! Startup Timings
+//! How long each boot stage took, measured from launch, so cold start
+//! regressions show up in `get_startup_report`. Setup only runs what the
+//! hotkey and first search need; plugins, databases and watchers load in
+//! deferred stages once the app is interactive. Code that needs a deferred
+//! stage's state checks `has_run` and reports "not ready" rather than
+//! blocking until it has.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// A timed part of startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupStage {
+    /// Reading and applying settings
+    Settings,
+    /// Registering the state commands rely on
+    Services,
+    /// Preparing the main window
+    Window,
+    /// Registering global hotkeys
+    Hotkey,
+    /// Restoring plugin permission grants and crash state
+    Sandbox,
+    /// Listing installed plugins and quarantining incompatible ones
+    Plugins,
+    /// Opening the file and browser databases
+    Indexes,
+    /// Scanning installed applications
+    Apps,
+    /// Starting watchers, monitors and periodic jobs
+    Schedulers,
+}
+
+impl StartupStage {
+    /// Whether the stage runs after the app is interactive
+    pub fn is_deferred(self) -> bool {
+        matches!(
+            self,
+            Self::Sandbox | Self::Plugins | Self::Indexes | Self::Apps | Self::Schedulers
+        )
+    }
+}
+
+/// One stage's run, in milliseconds since launch
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageTiming {
+    pub stage: StartupStage,
+    pub deferred: bool,
+    pub started_ms: f64,
+    pub duration_ms: f64,
+}
+
+/// Everything recorded so far
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StartupReport {
+    /// In the order they finished
+    pub stages: Vec<StageTiming>,
+    /// Launch until the hotkey works; None while still starting
+    pub total_to_interactive_ms: Option<f64>,
+    /// Launch until the last deferred stage finished
+    pub total_deferred_ms: Option<f64>,
+}
+
+#[derive(Default)]
+struct TimingsInner {
+    stages: Vec<StageTiming>,
+    interactive: Option<Duration>,
+    deferred_done: Option<Duration>,
+}
+
+/// Records stage timings relative to when it was created
+pub struct StartupTimings {
+    origin: Instant,
+    inner: Mutex<TimingsInner>,
+}
+
+impl Default for StartupTimings {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StartupTimings {
+    /// Start the clock; create this as early in launch as possible
+    pub fn new() -> Self {
+        Self {
+            origin: Instant::now(),
+            inner: Mutex::new(TimingsInner::default()),
+        }
+    }
+
+    /// Run `f` as `stage`, recording how long it took
+    pub fn time<T>(&self, stage: StartupStage, f: impl FnOnce() -> T) -> T {
+        let _span = self.span(stage);
+        f()
+    }
+
+    /// Time `stage` until the returned span is dropped, including on early
+    /// returns
+    pub fn span(&self, stage: StartupStage) -> StageSpan<'_> {
+        StageSpan {
+            timings: self,
+            stage,
+            started: Instant::now(),
+        }
+    }
+
+    fn record(&self, stage: StartupStage, started: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.stages.push(StageTiming {
+            stage,
+            deferred: stage.is_deferred(),
+            started_ms: to_ms(started.duration_since(self.origin)),
+            duration_ms: to_ms(started.elapsed()),
+        });
+    }
+
+    /// The app can take input; only the first call counts
+    pub fn mark_interactive(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.interactive.get_or_insert(self.origin.elapsed());
+    }
+
+    /// Every deferred stage has run, including any that were skipped
+    pub fn mark_deferred_done(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.deferred_done.get_or_insert(self.origin.elapsed());
+    }
+
+    /// Whether `stage` has run, or the deferred stages are done without it
+    pub fn has_run(&self, stage: StartupStage) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner.deferred_done.is_some() || inner.stages.iter().any(|s| s.stage == stage)
+    }
+
+    pub fn report(&self) -> StartupReport {
+        let inner = self.inner.lock().unwrap();
+        StartupReport {
+            stages: inner.stages.clone(),
+            total_to_interactive_ms: inner.interactive.map(to_ms),
+            total_deferred_ms: inner.deferred_done.map(to_ms),
+        }
+    }
+}
+
+/// A running stage, recorded when dropped
+pub struct StageSpan<'a> {
+    timings: &'a StartupTimings,
+    stage: StartupStage,
+    started: Instant,
+}
+
+impl Drop for StageSpan<'_> {
+    fn drop(&mut self) {
+        self.timings.record(self.stage, self.started);
+    }
+}
+
+fn to_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    #[test]
+    fn test_report_records_stages_in_order() {
+        let timings = StartupTimings::new();
+        assert_eq!(timings.time(StartupStage::Settings, || 42), 42);
+        let register = || -> Result<(), String> {
+            let _span = timings.span(StartupStage::Hotkey);
+            thread::sleep(Duration::from_millis(5));
+            Err("hotkey taken".to_string())
+        };
+        // Recorded even though it failed
+        assert!(register().is_err());
+        timings.mark_interactive();
+        timings.time(StartupStage::Plugins, || {});
+
+        let report = timings.report();
+        let stages: Vec<_> = report.stages.iter().map(|s| (s.stage, s.deferred)).collect();
+        assert_eq!(
+            stages,
+            vec![
+                (StartupStage::Settings, false),
+                (StartupStage::Hotkey, false),
+                (StartupStage::Plugins, true),
+            ]
+        );
+        assert!(report.stages[1].duration_ms >= 5.0);
+        assert!(report.stages[2].started_ms >= report.stages[1].started_ms + 5.0);
+
+        let interactive = report.total_to_interactive_ms.unwrap();
+        assert!(interactive >= 5.0);
+        assert_eq!(report.total_deferred_ms, None);
+
+        // Later marks don't move it
+        timings.mark_interactive();
+        assert_eq!(timings.report().total_to_interactive_ms, Some(interactive));
+    }
+
+    #[test]
+    fn test_has_run_deferred_stage() {
+        let timings = StartupTimings::new();
+        assert!(!timings.has_run(StartupStage::Sandbox));
+
+        timings.time(StartupStage::Sandbox, || {});
+        assert!(timings.has_run(StartupStage::Sandbox));
+        assert!(!timings.has_run(StartupStage::Apps));
+
+        // A stage that never runs counts once startup is done
+        timings.mark_deferred_done();
+        assert!(timings.has_run(StartupStage::Apps));
+    }
+}
@@ -0,0 +1,557 @@
+//! Task Supervisor
+//! Runs the app's background tasks on their own threads with one set of
+//! controls (start, stop, pause, run now) and keeps per-task stats (last
+//! run, last error, run durations) for the diagnostics page. Interval tasks
+//! run their job every period; long-running tasks run one job that loops
+//! until told to stop.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+type Job = Arc<dyn Fn(&TaskContext) -> Result<(), String> + Send + Sync>;
+
+/// Waits out one period of an interval task, returning false if the task
+/// was stopped meanwhile
+type Timer = Arc<dyn Fn(&TaskContext, Duration) -> bool + Send + Sync>;
+
+/// How a task runs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TaskSchedule {
+    /// The job runs once per period, the first time one period after start
+    Interval { every_ms: u64 },
+    /// The job runs once and loops until `TaskContext::should_stop`
+    LongRunning,
+}
+
+impl TaskSchedule {
+    pub fn every(period: Duration) -> Self {
+        Self::Interval {
+            every_ms: period.as_millis() as u64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Stopped,
+    Running,
+    /// Started, but scheduled runs are skipped until resumed
+    Paused,
+}
+
+/// A failed run
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskError {
+    pub message: String,
+    /// Unix time in milliseconds
+    pub at: i64,
+}
+
+/// One row of the background task table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub schedule: TaskSchedule,
+    pub state: TaskState,
+    /// A run is in progress
+    pub busy: bool,
+    /// When the last run started, unix time in milliseconds
+    pub last_run: Option<i64>,
+    /// The most recent failure, kept after later runs succeed
+    pub last_error: Option<TaskError>,
+    pub runs: u64,
+    pub failures: u64,
+    pub last_duration_ms: Option<u64>,
+    pub avg_duration_ms: Option<u64>,
+    pub max_duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Default)]
+struct TaskStats {
+    last_run: Option<i64>,
+    last_error: Option<TaskError>,
+    runs: u64,
+    failures: u64,
+    total_duration: Duration,
+    last_duration: Option<Duration>,
+    max_duration: Option<Duration>,
+}
+
+struct Control {
+    state: TaskState,
+    /// Bumped on every start and stop, so a thread left over from an
+    /// earlier start exits instead of running alongside the new one
+    generation: u64,
+}
+
+struct Task {
+    name: String,
+    schedule: TaskSchedule,
+    job: Job,
+    timer: Timer,
+    control: Mutex<Control>,
+    wake: Condvar,
+    stats: Mutex<TaskStats>,
+    /// Held for the length of a run, so a manual run never overlaps a
+    /// scheduled one
+    running: Mutex<()>,
+    thread: Mutex<Option<JoinHandle<()>>>,
+}
+
+/// Handed to a task's job, so it can tell when to stop or pause
+pub struct TaskContext {
+    task: Arc<Task>,
+    generation: u64,
+}
+
+impl TaskContext {
+    /// The task was stopped (or restarted) since this run began
+    pub fn should_stop(&self) -> bool {
+        let control = self.task.control.lock().unwrap_or_else(|e| e.into_inner());
+        control.state == TaskState::Stopped || control.generation != self.generation
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.task.control.lock().unwrap_or_else(|e| e.into_inner()).state == TaskState::Paused
+    }
+
+    /// Sleep for `duration`, waking early when the task is stopped.
+    /// Returns false if it was.
+    pub fn wait(&self, duration: Duration) -> bool {
+        let control = self.task.control.lock().unwrap_or_else(|e| e.into_inner());
+        let (control, _) = self
+            .task
+            .wake
+            .wait_timeout_while(control, duration, |control| {
+                control.state != TaskState::Stopped && control.generation == self.generation
+            })
+            .unwrap_or_else(|e| e.into_inner());
+        control.state != TaskState::Stopped && control.generation == self.generation
+    }
+}
+
+impl Task {
+    /// Run the job once, recording the outcome
+    fn run(self: &Arc<Self>, generation: u64) -> Result<(), String> {
+        let _running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        let started = Instant::now();
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let context = TaskContext {
+            task: Arc::clone(self),
+            generation,
+        };
+        let result = (self.job)(&context);
+
+        let duration = started.elapsed();
+        let mut stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        stats.last_run = Some(started_at);
+        stats.runs += 1;
+        stats.total_duration += duration;
+        stats.last_duration = Some(duration);
+        stats.max_duration = stats.max_duration.max(Some(duration));
+        if let Err(e) = &result {
+            stats.failures += 1;
+            stats.last_error = Some(TaskError {
+                message: e.clone(),
+                at: started_at,
+            });
+            eprintln!("[Tasks] {} failed: {}", self.name, e);
+        }
+        result
+    }
+
+    fn generation(&self) -> u64 {
+        self.control.lock().unwrap_or_else(|e| e.into_inner()).generation
+    }
+
+    fn run_loop(self: Arc<Self>, generation: u64) {
+        match self.schedule {
+            TaskSchedule::Interval { every_ms } => {
+                let context = TaskContext {
+                    task: Arc::clone(&self),
+                    generation,
+                };
+                while (self.timer)(&context, Duration::from_millis(every_ms)) {
+                    if !context.is_paused() {
+                        let _ = self.run(generation);
+                    }
+                }
+            }
+            TaskSchedule::LongRunning => {
+                let _ = self.run(generation);
+                // A job that returns on its own leaves the task stopped
+                let mut control = self.control.lock().unwrap_or_else(|e| e.into_inner());
+                if control.generation == generation {
+                    control.state = TaskState::Stopped;
+                }
+            }
+        }
+    }
+
+    fn status(&self) -> TaskStatus {
+        let state = self.control.lock().unwrap_or_else(|e| e.into_inner()).state;
+        let busy = self.running.try_lock().is_err();
+        let stats = self.stats.lock().unwrap_or_else(|e| e.into_inner());
+        let millis = |duration: Duration| duration.as_millis() as u64;
+        TaskStatus {
+            name: self.name.clone(),
+            schedule: self.schedule,
+            state,
+            busy,
+            last_run: stats.last_run,
+            last_error: stats.last_error.clone(),
+            runs: stats.runs,
+            failures: stats.failures,
+            last_duration_ms: stats.last_duration.map(millis),
+            avg_duration_ms: (stats.runs > 0).then(|| millis(stats.total_duration / stats.runs as u32)),
+            max_duration_ms: stats.max_duration.map(millis),
+        }
+    }
+}
+
+/// The app's background tasks, by name
+pub struct TaskSupervisor {
+    tasks: Mutex<BTreeMap<String, Arc<Task>>>,
+    /// Paces interval tasks; tests pass one they advance by hand
+    timer: Timer,
+}
+
+impl Default for TaskSupervisor {
+    fn default() -> Self {
+        Self::with_timer(Arc::new(|context, period| context.wait(period)))
+    }
+}
+
+impl TaskSupervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn with_timer(timer: Timer) -> Self {
+        Self {
+            tasks: Mutex::new(BTreeMap::new()),
+            timer,
+        }
+    }
+
+    fn task(&self, name: &str) -> Result<Arc<Task>, String> {
+        self.tasks
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown background task '{}'", name))
+    }
+
+    /// Add a stopped task. A task already registered under `name` is
+    /// stopped and replaced, and its stats are dropped.
+    pub fn register(
+        &self,
+        name: &str,
+        schedule: TaskSchedule,
+        job: impl Fn(&TaskContext) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Result<(), String> {
+        let task = Arc::new(Task {
+            name: name.to_string(),
+            schedule,
+            job: Arc::new(job),
+            timer: Arc::clone(&self.timer),
+            control: Mutex::new(Control {
+                state: TaskState::Stopped,
+                generation: 0,
+            }),
+            wake: Condvar::new(),
+            stats: Mutex::new(TaskStats::default()),
+            running: Mutex::new(()),
+            thread: Mutex::new(None),
+        });
+        let replaced = self
+            .tasks
+            .lock()
+            .map_err(|e| format!("Lock error: {}", e))?
+            .insert(name.to_string(), task);
+        if let Some(replaced) = replaced {
+            Self::signal_stop(&replaced);
+        }
+        Ok(())
+    }
+
+    /// Start a task's thread. Resumes a paused task; no-op if it's running.
+    pub fn start(&self, name: &str) -> Result<(), String> {
+        let task = self.task(name)?;
+        let generation = {
+            let mut control = task.control.lock().map_err(|e| format!("Lock error: {}", e))?;
+            match control.state {
+                TaskState::Running => return Ok(()),
+                TaskState::Paused => {
+                    control.state = TaskState::Running;
+                    return Ok(());
+                }
+                TaskState::Stopped => {}
+            }
+            control.state = TaskState::Running;
+            control.generation += 1;
+            control.generation
+        };
+
+        let runner = Arc::clone(&task);
+        let thread = std::thread::Builder::new()
+            .name(format!("task-{}", name))
+            .spawn(move || runner.run_loop(generation))
+            .map_err(|e| format!("Failed to start task '{}': {}", name, e))?;
+        *task.thread.lock().map_err(|e| format!("Lock error: {}", e))? = Some(thread);
+        Ok(())
+    }
+
+    /// Stop a task. A run in progress finishes (long-running jobs see
+    /// `should_stop`); this doesn't wait for it.
+    pub fn stop(&self, name: &str) -> Result<(), String> {
+        let task = self.task(name)?;
+        Self::signal_stop(&task);
+        Ok(())
+    }
+
+    fn signal_stop(task: &Task) {
+        let mut control = task.control.lock().unwrap_or_else(|e| e.into_inner());
+        if control.state != TaskState::Stopped {
+            control.state = TaskState::Stopped;
+            control.generation += 1;
+        }
+        task.wake.notify_all();
+    }
+
+    /// Skip scheduled runs until resumed. Long-running jobs check
+    /// `is_paused` themselves.
+    pub fn pause(&self, name: &str) -> Result<(), String> {
+        let task = self.task(name)?;
+        let mut control = task.control.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if control.state == TaskState::Stopped {
+            return Err(format!("Background task '{}' isn't running", name));
+        }
+        control.state = TaskState::Paused;
+        Ok(())
+    }
+
+    pub fn resume(&self, name: &str) -> Result<(), String> {
+        let task = self.task(name)?;
+        let mut control = task.control.lock().map_err(|e| format!("Lock error: {}", e))?;
+        if control.state == TaskState::Stopped {
+            return Err(format!("Background task '{}' isn't running", name));
+        }
+        control.state = TaskState::Running;
+        Ok(())
+    }
+
+    /// Run an interval task's job now on the calling thread, even if the
+    /// task is paused or stopped. Waits for a run already in progress.
+    pub fn run_now(&self, name: &str) -> Result<(), String> {
+        let task = self.task(name)?;
+        if task.schedule == TaskSchedule::LongRunning {
+            return Err(format!("Background task '{}' runs continuously", name));
+        }
+        task.run(task.generation())
+    }
+
+    /// Stop every task and wait up to `deadline` for their threads to
+    /// finish. Returns the tasks still running at the deadline.
+    pub fn stop_all(&self, deadline: Duration) -> Vec<String> {
+        let tasks: Vec<Arc<Task>> = self
+            .tasks
+            .lock()
+            .map(|tasks| tasks.values().cloned().collect())
+            .unwrap_or_default();
+        for task in &tasks {
+            Self::signal_stop(task);
+        }
+
+        let give_up = Instant::now() + deadline;
+        let mut unfinished = Vec::new();
+        for task in &tasks {
+            let Some(thread) = task.thread.lock().ok().and_then(|mut thread| thread.take()) else {
+                continue;
+            };
+            while !thread.is_finished() && Instant::now() < give_up {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            if thread.is_finished() {
+                let _ = thread.join();
+            } else {
+                unfinished.push(task.name.clone());
+            }
+        }
+        unfinished
+    }
+
+    /// Every task's state and stats, by name
+    pub fn snapshot(&self) -> Vec<TaskStatus> {
+        self.tasks
+            .lock()
+            .map(|tasks| tasks.values().map(|task| task.status()).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self, name: &str) -> Result<TaskStatus, String> {
+        Ok(self.task(name)?.status())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Periods {
+        /// Periods the test has let pass
+        elapsed: u64,
+        /// Times a task thread started waiting for one
+        waits: u64,
+    }
+
+    /// Interval periods that only pass when the test calls `advance`
+    #[derive(Default)]
+    struct ManualClock {
+        periods: Mutex<Periods>,
+        changed: Condvar,
+    }
+
+    impl ManualClock {
+        fn supervisor(self: &Arc<Self>) -> TaskSupervisor {
+            let clock = Arc::clone(self);
+            TaskSupervisor::with_timer(Arc::new(move |context, _| clock.wait(context)))
+        }
+
+        fn wait(&self, context: &TaskContext) -> bool {
+            let mut periods = self.periods.lock().unwrap();
+            periods.waits += 1;
+            self.changed.notify_all();
+            while periods.elapsed < periods.waits {
+                if context.should_stop() {
+                    return false;
+                }
+                // Stopping wakes the task's own condvar, not this one
+                periods = self.changed.wait_timeout(periods, Duration::from_millis(10)).unwrap().0;
+            }
+            !context.should_stop()
+        }
+
+        /// Let one period pass and wait until the task thread is back
+        /// waiting for the next, i.e. the scheduled run (or skip) is over
+        fn advance(&self) {
+            let mut periods = self.periods.lock().unwrap();
+            periods.elapsed += 1;
+            self.changed.notify_all();
+            let elapsed = periods.elapsed;
+            let _periods = self.changed.wait_while(periods, |periods| periods.waits <= elapsed).unwrap();
+        }
+    }
+
+    fn counting_task(supervisor: &TaskSupervisor, name: &str, every: Duration) -> Arc<AtomicUsize> {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        supervisor
+            .register(name, TaskSchedule::every(every), move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .unwrap();
+        count
+    }
+
+    #[test]
+    fn test_registered_tasks_start_stopped() {
+        let clock = Arc::new(ManualClock::default());
+        let supervisor = clock.supervisor();
+        let count = counting_task(&supervisor, "scan", Duration::from_millis(5));
+        supervisor
+            .register("watch", TaskSchedule::LongRunning, |context| {
+                while context.wait(Duration::from_millis(5)) {}
+                Ok(())
+            })
+            .unwrap();
+
+        let names: Vec<String> = supervisor.snapshot().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, vec!["scan", "watch"]);
+        let scan = supervisor.status("scan").unwrap();
+        assert_eq!(scan.state, TaskState::Stopped);
+        assert_eq!(scan.schedule, TaskSchedule::Interval { every_ms: 5 });
+        assert_eq!((scan.runs, scan.last_run), (0, None));
+
+        // Stopped tasks don't run, but a manual run does
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+        supervisor.run_now("scan").unwrap();
+        assert_eq!(supervisor.status("scan").unwrap().runs, 1);
+
+        assert!(supervisor.run_now("watch").is_err());
+        assert_eq!(
+            supervisor.start("missing").unwrap_err(),
+            "Unknown background task 'missing'"
+        );
+
+        supervisor.start("watch").unwrap();
+        assert_eq!(supervisor.status("watch").unwrap().state, TaskState::Running);
+        assert!(supervisor.stop_all(Duration::from_secs(1)).is_empty());
+        assert_eq!(supervisor.status("watch").unwrap().state, TaskState::Stopped);
+    }
+
+    #[test]
+    fn test_paused_tasks_skip_runs_until_resumed() {
+        let clock = Arc::new(ManualClock::default());
+        let supervisor = clock.supervisor();
+        let count = counting_task(&supervisor, "scan", Duration::from_millis(5));
+        assert!(supervisor.pause("scan").is_err());
+
+        supervisor.start("scan").unwrap();
+        clock.advance();
+        clock.advance();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+
+        supervisor.pause("scan").unwrap();
+        clock.advance();
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(supervisor.status("scan").unwrap().state, TaskState::Paused);
+
+        supervisor.resume("scan").unwrap();
+        clock.advance();
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        assert!(supervisor.stop_all(Duration::from_secs(1)).is_empty());
+        assert_eq!(supervisor.status("scan").unwrap().state, TaskState::Stopped);
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_failures_are_recorded() {
+        let supervisor = TaskSupervisor::new();
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&attempts);
+        supervisor
+            .register("refresh", TaskSchedule::every(Duration::from_secs(60)), move |_| {
+                // Fails the first time only
+                match counter.fetch_add(1, Ordering::SeqCst) {
+                    0 => Err("database is locked".to_string()),
+                    _ => Ok(()),
+                }
+            })
+            .unwrap();
+
+        assert_eq!(supervisor.run_now("refresh").unwrap_err(), "database is locked");
+        let failed = supervisor.status("refresh").unwrap();
+        assert_eq!((failed.runs, failed.failures), (1, 1));
+        let error = failed.last_error.unwrap();
+        assert_eq!(error.message, "database is locked");
+        assert_eq!(Some(error.at), failed.last_run);
+        assert!(failed.last_duration_ms.is_some());
+
+        // The error stays visible after a later run succeeds
+        supervisor.run_now("refresh").unwrap();
+        let recovered = supervisor.status("refresh").unwrap();
+        assert_eq!((recovered.runs, recovered.failures), (2, 1));
+        assert_eq!(recovered.last_error.unwrap().message, "database is locked");
+    }
+}
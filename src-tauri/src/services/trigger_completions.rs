@@ -0,0 +1,215 @@
+//! Trigger Completions
+//! Argument hints for plugin triggers such as "translate:". Suggestions the
+//! manifest lists are answered right away; arguments marked `dynamic` also
+//! ask the plugin, which runs in the frontend, and whatever it answers
+//! within the timeout is added.
+
+use crate::models::plugin::{PluginTrigger, TriggerArg};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// How long to wait for a plugin's dynamic suggestions
+pub const DYNAMIC_COMPLETION_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// Completions for the argument being typed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TriggerCompletions {
+    /// Argument being completed, None once the trigger has no more
+    pub arg: Option<TriggerArg>,
+    pub suggestions: Vec<String>,
+}
+
+impl TriggerCompletions {
+    /// Add the plugin's suggestions after the manifest's, skipping repeats
+    pub fn merge(&mut self, dynamic: Vec<String>) {
+        for suggestion in dynamic {
+            if !self.suggestions.contains(&suggestion) {
+                self.suggestions.push(suggestion);
+            }
+        }
+    }
+}
+
+/// Manifest suggestions for the last of `partial_args`, the one being
+/// typed, that start with what has been typed so far
+pub fn static_completions(trigger: &PluginTrigger, partial_args: &[String]) -> TriggerCompletions {
+    let index = partial_args.len().saturating_sub(1);
+    let typed = partial_args.last().map(|a| a.trim().to_lowercase()).unwrap_or_default();
+
+    let Some(arg) = trigger.args.get(index) else {
+        return TriggerCompletions {
+            arg: None,
+            suggestions: Vec::new(),
+        };
+    };
+
+    let suggestions = arg
+        .suggestions
+        .iter()
+        .map(|value| match value {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        })
+        .filter(|suggestion| suggestion.to_lowercase().starts_with(&typed))
+        .collect();
+
+    TriggerCompletions {
+        arg: Some(arg.clone()),
+        suggestions,
+    }
+}
+
+/// `plugin:completion_request` event payload. The frontend answers with
+/// `resolve_trigger_completions`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CompletionRequest {
+    pub request_id: u64,
+    pub plugin_id: String,
+    pub trigger: String,
+    pub arg: String,
+    pub partial_args: Vec<String>,
+}
+
+/// Completion requests waiting for the plugin's answer
+#[derive(Default)]
+pub struct CompletionBridge {
+    next_id: AtomicU64,
+    pending: Mutex<HashMap<u64, Sender<Vec<String>>>>,
+}
+
+impl CompletionBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send a request with `send`, which gets its id, and wait up to
+    /// `timeout` for the answer. Nothing is suggested when sending fails
+    /// or the plugin is too slow.
+    pub fn request(&self, send: impl FnOnce(u64) -> Result<(), String>, timeout: Duration) -> Vec<String> {
+        let request_id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = mpsc::channel();
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.insert(request_id, tx);
+        }
+
+        let answer = match send(request_id) {
+            Ok(()) => rx.recv_timeout(timeout).unwrap_or_default(),
+            Err(e) => {
+                eprintln!("[TriggerCompletions] Failed to ask plugin: {}", e);
+                Vec::new()
+            }
+        };
+
+        if let Ok(mut pending) = self.pending.lock() {
+            pending.remove(&request_id);
+        }
+        answer
+    }
+
+    /// Deliver the answer to a request. Returns false when nobody is
+    /// waiting for it anymore, e.g. it arrived after the timeout.
+    pub fn resolve(&self, request_id: u64, suggestions: Vec<String>) -> bool {
+        let sender = self.pending.lock().ok().and_then(|mut pending| pending.remove(&request_id));
+        sender.is_some_and(|tx| tx.send(suggestions).is_ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    fn translate_trigger() -> PluginTrigger {
+        serde_json::from_value(json!({
+            "keyword": "translate:",
+            "args": [
+                { "name": "to", "required": true, "enum": ["en", "de", "es", "eo"] },
+                { "name": "text", "required": true, "dynamic": true }
+            ]
+        }))
+        .unwrap()
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_static_completions_filter_by_typed_prefix() {
+        let trigger = translate_trigger();
+
+        let all = static_completions(&trigger, &[]);
+        assert_eq!(all.arg.as_ref().map(|a| a.name.as_str()), Some("to"));
+        assert_eq!(all.suggestions, args(&["en", "de", "es", "eo"]));
+
+        let e = static_completions(&trigger, &args(&["E"]));
+        assert_eq!(e.suggestions, args(&["en", "es", "eo"]));
+
+        // The second argument has no static suggestions but asks the plugin
+        let text = static_completions(&trigger, &args(&["de", "hel"]));
+        assert!(text.arg.as_ref().is_some_and(|a| a.dynamic));
+        assert!(text.suggestions.is_empty());
+
+        let past_end = static_completions(&trigger, &args(&["de", "hello", "extra"]));
+        assert_eq!(past_end.arg, None);
+    }
+
+    #[test]
+    fn test_non_string_suggestions_are_rendered() {
+        let trigger: PluginTrigger = serde_json::from_value(json!({
+            "keyword": "dice:",
+            "args": [{ "name": "sides", "type": "number", "enum": [6, 12, 20] }]
+        }))
+        .unwrap();
+
+        let completions = static_completions(&trigger, &args(&["1"]));
+        assert_eq!(completions.suggestions, args(&["12"]));
+    }
+
+    #[test]
+    fn test_merge_skips_repeats() {
+        let mut completions = static_completions(&translate_trigger(), &args(&["e"]));
+        completions.merge(args(&["eo", "el"]));
+        assert_eq!(completions.suggestions, args(&["en", "es", "eo", "el"]));
+    }
+
+    #[test]
+    fn test_bridge_delivers_answer_within_timeout() {
+        let bridge = Arc::new(CompletionBridge::new());
+
+        let answer = bridge.request(
+            |request_id| {
+                let bridge = Arc::clone(&bridge);
+                std::thread::spawn(move || bridge.resolve(request_id, args(&["hello world"])));
+                Ok(())
+            },
+            Duration::from_secs(5),
+        );
+
+        assert_eq!(answer, args(&["hello world"]));
+    }
+
+    #[test]
+    fn test_bridge_gives_up_on_slow_or_unreachable_plugins() {
+        let bridge = CompletionBridge::new();
+
+        let mut asked = None;
+        let answer = bridge.request(
+            |request_id| {
+                asked = Some(request_id);
+                Ok(())
+            },
+            Duration::from_millis(10),
+        );
+        assert!(answer.is_empty());
+        assert!(!bridge.resolve(asked.unwrap(), args(&["too late"])));
+
+        let answer = bridge.request(|_| Err("no window".to_string()), Duration::from_secs(5));
+        assert!(answer.is_empty());
+    }
+}
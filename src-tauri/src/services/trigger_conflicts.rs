@@ -0,0 +1,231 @@
+//! Trigger Conflicts
+//! Plugin triggers match any query that starts with them, and a plugin
+//! abbreviation matches a query equal to it or starting with it plus ':'.
+//! Two claims conflict when some query would match both, e.g. two plugins
+//! declaring "color:", or "c" shadowing "color:". When an install brings a
+//! conflict, the plugin already holding the keyword keeps it and the new
+//! plugin's trigger is suppressed until the user picks a winner; the choice
+//! is kept in trigger-owners.json.
+
+use crate::models::plugin::PluginTrigger;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// Where a claim on a keyword comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimSource {
+    /// A trigger in the plugin's manifest
+    Trigger,
+    /// An abbreviation the user set up for the plugin
+    Abbreviation,
+}
+
+/// A keyword a plugin answers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TriggerClaim {
+    pub plugin_id: String,
+    pub keyword: String,
+    pub source: ClaimSource,
+}
+
+impl TriggerClaim {
+    pub fn trigger(plugin_id: &str, keyword: &str) -> Self {
+        Self { plugin_id: plugin_id.to_string(), keyword: keyword.to_string(), source: ClaimSource::Trigger }
+    }
+
+    pub fn abbreviation(plugin_id: &str, keyword: &str) -> Self {
+        Self { plugin_id: plugin_id.to_string(), keyword: keyword.to_string(), source: ClaimSource::Abbreviation }
+    }
+
+    /// Queries the claim matches, as (text, whole query only)
+    fn patterns(&self) -> Vec<(String, bool)> {
+        let keyword = normalize_keyword(&self.keyword);
+        match self.source {
+            ClaimSource::Trigger => vec![(keyword, false)],
+            ClaimSource::Abbreviation => vec![(format!("{}:", keyword), false), (keyword, true)],
+        }
+    }
+
+    /// Whether some query matches both claims
+    pub fn overlaps(&self, other: &TriggerClaim) -> bool {
+        let matches_both = |(a, a_exact): &(String, bool), (b, b_exact): &(String, bool)| match (a_exact, b_exact) {
+            (true, true) => a == b,
+            (true, false) => a.starts_with(b.as_str()),
+            (false, true) => b.starts_with(a.as_str()),
+            (false, false) => a.starts_with(b.as_str()) || b.starts_with(a.as_str()),
+        };
+        let theirs = other.patterns();
+        self.patterns().iter().any(|ours| theirs.iter().any(|their| matches_both(ours, their)))
+    }
+}
+
+/// How two claims collide
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// The same keyword
+    Same,
+    /// One keyword starts with the other, so queries for the longer one
+    /// match both
+    Overlapping,
+}
+
+/// A trigger of an incoming plugin that collides with an installed one
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TriggerConflict {
+    /// The incoming plugin's trigger
+    pub keyword: String,
+    /// Plugin already answering to it
+    pub plugin_id: String,
+    /// Its colliding keyword
+    pub existing_keyword: String,
+    pub source: ClaimSource,
+    pub kind: ConflictKind,
+}
+
+/// Keywords match case-insensitively and ignore surrounding whitespace
+pub fn normalize_keyword(keyword: &str) -> String {
+    keyword.trim().to_lowercase()
+}
+
+/// Conflicts between `plugin_id`'s triggers and other plugins' claims
+pub fn find_conflicts(plugin_id: &str, triggers: &[PluginTrigger], claims: &[TriggerClaim]) -> Vec<TriggerConflict> {
+    let mut conflicts = Vec::new();
+    for trigger in triggers {
+        let incoming = TriggerClaim::trigger(plugin_id, &trigger.keyword);
+        for claim in claims.iter().filter(|claim| claim.plugin_id != plugin_id && incoming.overlaps(claim)) {
+            conflicts.push(TriggerConflict {
+                keyword: trigger.keyword.clone(),
+                plugin_id: claim.plugin_id.clone(),
+                existing_keyword: claim.keyword.clone(),
+                source: claim.source,
+                kind: if normalize_keyword(&trigger.keyword) == normalize_keyword(&claim.keyword) {
+                    ConflictKind::Same
+                } else {
+                    ConflictKind::Overlapping
+                },
+            });
+        }
+    }
+    conflicts
+}
+
+/// Which plugin owns each contested keyword, keyed by normalized keyword
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TriggerOwners(pub HashMap<String, String>);
+
+impl TriggerOwners {
+    /// Give `keyword` to `plugin_id`
+    pub fn resolve(&mut self, keyword: &str, plugin_id: &str) {
+        self.0.insert(normalize_keyword(keyword), plugin_id.to_string());
+    }
+
+    /// Keep each conflict's keyword with the plugin that already had it,
+    /// unless the user already chose. Returns whether anything changed.
+    pub fn keep_existing(&mut self, conflicts: &[TriggerConflict]) -> bool {
+        let mut changed = false;
+        for conflict in conflicts {
+            if let Entry::Vacant(entry) = self.0.entry(normalize_keyword(&conflict.keyword)) {
+                entry.insert(conflict.plugin_id.clone());
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Forget the keywords `plugin_id` won, e.g. when it's uninstalled.
+    /// Returns whether anything changed.
+    pub fn release(&mut self, plugin_id: &str) -> bool {
+        let before = self.0.len();
+        self.0.retain(|_, owner| owner != plugin_id);
+        self.0.len() != before
+    }
+
+    /// `plugin_id`'s triggers that collide with a keyword another plugin owns
+    pub fn suppressed_triggers(&self, plugin_id: &str, triggers: &[PluginTrigger]) -> Vec<String> {
+        triggers
+            .iter()
+            .filter(|trigger| {
+                let ours = TriggerClaim::trigger(plugin_id, &trigger.keyword);
+                self.0.iter().any(|(keyword, owner)| {
+                    owner != plugin_id && ours.overlaps(&TriggerClaim::trigger(owner, keyword))
+                })
+            })
+            .map(|trigger| trigger.keyword.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triggers(keywords: &[&str]) -> Vec<PluginTrigger> {
+        keywords
+            .iter()
+            .map(|keyword| PluginTrigger {
+                keyword: keyword.to_string(),
+                description: String::new(),
+                hotkey: None,
+                args: vec![],
+                cache_ttl_ms: None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_conflicts_with_keywords_and_patterns() {
+        let claims = vec![
+            TriggerClaim::trigger("palette", "Color:"),
+            TriggerClaim::trigger("translate", "tr"),
+            TriggerClaim::abbreviation("notes", "n"),
+            TriggerClaim::trigger("calc", "="),
+        ];
+
+        let conflicts = find_conflicts("picker", &triggers(&["color:", "n:", "nn", "pick:"]), &claims);
+        let found: Vec<_> = conflicts
+            .iter()
+            .map(|c| (c.keyword.as_str(), c.plugin_id.as_str(), c.source, c.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("color:", "palette", ClaimSource::Trigger, ConflictKind::Same),
+                // The abbreviation "n" answers "n: ..."; "nn" is a different word
+                ("n:", "notes", ClaimSource::Abbreviation, ConflictKind::Overlapping),
+            ]
+        );
+
+        // "translate:" queries start with "tr", so both would answer them
+        let shadowed = find_conflicts("deepl", &triggers(&["translate:"]), &claims);
+        assert_eq!(shadowed[0].plugin_id, "translate");
+        assert_eq!(shadowed[0].kind, ConflictKind::Overlapping);
+
+        // A plugin doesn't conflict with itself, e.g. on reinstall
+        assert!(find_conflicts("palette", &triggers(&["color:"]), &claims).is_empty());
+    }
+
+    #[test]
+    fn test_owners_suppress_the_losing_triggers() {
+        let claims = vec![TriggerClaim::trigger("palette", "color:")];
+        let conflicts = find_conflicts("picker", &triggers(&["color:", "pick:"]), &claims);
+
+        // The installed plugin keeps the keyword
+        let mut owners = TriggerOwners::default();
+        assert!(owners.keep_existing(&conflicts));
+        assert_eq!(owners.suppressed_triggers("picker", &triggers(&["color:", "pick:"])), vec!["color:"]);
+        assert!(owners.suppressed_triggers("palette", &triggers(&["color:"])).is_empty());
+
+        // Until the user gives it to the new one, which a later install doesn't undo
+        owners.resolve("COLOR:", "picker");
+        assert!(!owners.keep_existing(&conflicts));
+        assert!(owners.suppressed_triggers("picker", &triggers(&["color:"])).is_empty());
+        assert_eq!(owners.suppressed_triggers("palette", &triggers(&["color:"])), vec!["color:"]);
+
+        assert!(owners.release("picker"));
+        assert!(owners.suppressed_triggers("palette", &triggers(&["color:"])).is_empty());
+    }
+}
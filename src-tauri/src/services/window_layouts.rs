@@ -0,0 +1,330 @@
+//! Window Layouts
+//! Named launcher layouts (size, position, results panel height and
+//! always-on-top) the user saves and switches between by command. The
+//! layout last applied stays active: a fixed layout keeps the launcher at
+//! its spot when the hotkey shows it, instead of centering on the cursor.
+//! Settings name the layout applied at startup; without one, the size the
+//! launcher had when the app last quit is restored.
+
+use crate::services::app_paths;
+use crate::models::{LayoutPosition, MonitorArea, WindowLayout};
+use crate::types::events;
+use crate::utils::{managed_store, StateStore};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Monitor, Runtime, State, WebviewWindow};
+
+/// Held while a layout is applied, so size, position and always-on-top
+/// from two layouts never interleave
+static APPLYING: Mutex<()> = Mutex::new(());
+
+/// Saved layouts by name, and the one last applied
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayoutStore {
+    #[serde(default)]
+    pub layouts: BTreeMap<String, WindowLayout>,
+    #[serde(default)]
+    pub active: Option<String>,
+    /// The launcher when the app last quit, saved by the shutdown hook
+    #[serde(default)]
+    pub last_session: Option<WindowLayout>,
+}
+
+impl LayoutStore {
+    /// Add a layout, replacing one with the same name
+    pub fn save(&mut self, layout: WindowLayout) {
+        self.layouts.insert(layout.name.clone(), layout);
+    }
+
+    pub fn get(&self, name: &str) -> Result<&WindowLayout, String> {
+        self.layouts
+            .get(name)
+            .ok_or_else(|| format!("Unknown window layout '{}'", name))
+    }
+
+    /// Remove a layout; it stops being active if it was
+    pub fn remove(&mut self, name: &str) -> Result<WindowLayout, String> {
+        let layout = self
+            .layouts
+            .remove(name)
+            .ok_or_else(|| format!("Unknown window layout '{}'", name))?;
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+        }
+        Ok(layout)
+    }
+
+    /// All layouts, by name
+    pub fn list(&self) -> Vec<WindowLayout> {
+        self.layouts.values().cloned().collect()
+    }
+
+    /// The active layout, if it still exists
+    pub fn active_layout(&self) -> Option<&WindowLayout> {
+        self.active.as_deref().and_then(|name| self.layouts.get(name))
+    }
+}
+
+pub fn layout_store<R: Runtime>(handle: &AppHandle<R>) -> Result<State<'_, StateStore<LayoutStore>>, String> {
+    managed_store(handle, || {
+        let path = app_paths::data_dir(handle)?
+            .join("window_layouts.json");
+        Ok(StateStore::new(path, "window layouts"))
+    })
+}
+
+fn monitor_area(monitor: &Monitor) -> MonitorArea {
+    MonitorArea {
+        name: monitor.name().cloned(),
+        x: monitor.position().x,
+        y: monitor.position().y,
+        width: monitor.size().width,
+        height: monitor.size().height,
+    }
+}
+
+/// The window as it is now, under `name`. Follow-cursor layouts only keep
+/// the size; fixed ones also keep the spot on the current monitor.
+pub fn snapshot_layout<R: Runtime>(
+    window: &WebviewWindow<R>,
+    name: &str,
+    results_height: Option<u32>,
+    follow_cursor: bool,
+) -> Result<WindowLayout, String> {
+    let size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to get window size: {}", e))?;
+    let position = if follow_cursor {
+        LayoutPosition::FollowCursor
+    } else {
+        let outer = window
+            .outer_position()
+            .map_err(|e| format!("Failed to get window position: {}", e))?;
+        let monitor = window
+            .current_monitor()
+            .map_err(|e| format!("Failed to get monitor: {}", e))?
+            .ok_or("No monitor found")?;
+        let area = monitor_area(&monitor);
+        let (rel_x, rel_y) = area.relative(outer.x, outer.y);
+        LayoutPosition::Fixed {
+            monitor: area.name,
+            rel_x,
+            rel_y,
+        }
+    };
+
+    Ok(WindowLayout {
+        name: name.to_string(),
+        width: size.width,
+        height: size.height,
+        position,
+        results_height: results_height.unwrap_or(size.height),
+        always_on_top: window
+            .is_always_on_top()
+            .map_err(|e| format!("Failed to get always-on-top: {}", e))?,
+    })
+}
+
+/// Where a fixed layout puts the window on the monitors connected now
+fn fixed_position<R: Runtime>(window: &WebviewWindow<R>, layout: &WindowLayout) -> Option<(i32, i32)> {
+    let monitors: Vec<MonitorArea> = window.available_monitors().ok()?.iter().map(monitor_area).collect();
+    let primary = window
+        .primary_monitor()
+        .ok()
+        .flatten()
+        .map(|monitor| monitor_area(&monitor))
+        .or_else(|| monitors.first().cloned())?;
+    layout.fixed_position(&monitors, &primary)
+}
+
+/// Resize and place the window, set always-on-top and tell the frontend
+/// the results panel height, as one step
+pub fn apply_to_window<R: Runtime>(window: &WebviewWindow<R>, layout: &WindowLayout) -> Result<(), String> {
+    let _applying = APPLYING.lock().unwrap_or_else(|e| e.into_inner());
+
+    window
+        .set_size(tauri::Size::Physical(tauri::PhysicalSize {
+            width: layout.width,
+            height: layout.height,
+        }))
+        .map_err(|e| format!("Failed to set window size: {}", e))?;
+
+    let position = match fixed_position(window, layout) {
+        Some(position) => Some(position),
+        // Follow-cursor layouts stay centered on the current monitor
+        None => window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .map(|monitor| monitor_area(&monitor).center(layout.width, layout.height)),
+    };
+    if let Some((x, y)) = position {
+        window
+            .set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))
+            .map_err(|e| format!("Failed to set window position: {}", e))?;
+    }
+
+    window
+        .set_always_on_top(layout.always_on_top)
+        .map_err(|e| format!("Failed to set always-on-top: {}", e))?;
+
+    let _ = events::emit(window, layout);
+    Ok(())
+}
+
+/// Apply a saved layout to the window and make it the active one
+pub fn apply_layout<R: Runtime>(
+    handle: &AppHandle<R>,
+    window: &WebviewWindow<R>,
+    name: &str,
+) -> Result<WindowLayout, String> {
+    let store = layout_store(handle)?;
+    let layout = store.read()?.get(name)?.clone();
+    apply_to_window(window, &layout)?;
+    store.update(|layouts| {
+        layouts.active = Some(name.to_string());
+        Ok::<_, String>(())
+    })?;
+    Ok(layout)
+}
+
+/// Move the window to the active layout's fixed spot before it's shown.
+/// False when no fixed layout is active, so the caller positions it.
+pub fn position_for_active_layout<R: Runtime>(window: &WebviewWindow<R>) -> bool {
+    let Ok(store) = layout_store(window.app_handle()) else {
+        return false;
+    };
+    let Some(layout) = store.read().ok().and_then(|layouts| layouts.active_layout().cloned()) else {
+        return false;
+    };
+    let _applying = APPLYING.lock().unwrap_or_else(|e| e.into_inner());
+    match fixed_position(window, &layout) {
+        Some((x, y)) => window
+            .set_position(tauri::Position::Physical(tauri::PhysicalPosition { x, y }))
+            .is_ok(),
+        None => false,
+    }
+}
+
+/// Remember the window for the next launch. Only its size is kept, unless
+/// a fixed layout is active, so the launcher still opens where it would.
+pub fn save_session_layout<R: Runtime>(handle: &AppHandle<R>, window: &WebviewWindow<R>) -> Result<(), String> {
+    let store = layout_store(handle)?;
+    let active = store.read()?.active_layout().cloned();
+    let follow_cursor = !matches!(active.as_ref().map(|layout| &layout.position), Some(LayoutPosition::Fixed { .. }));
+    let results_height = active.map(|layout| layout.results_height);
+    let layout = snapshot_layout(window, "last session", results_height, follow_cursor)?;
+    store.update(|layouts| {
+        layouts.last_session = Some(layout);
+        Ok::<_, String>(())
+    })
+}
+
+/// Apply the layout settings name for startup, or else the window as the
+/// last session left it. Failures are logged.
+pub fn apply_startup_layout<R: Runtime>(handle: &AppHandle<R>, window: &WebviewWindow<R>, name: Option<&str>) {
+    let Some(name) = name else {
+        let last_session = layout_store(handle)
+            .and_then(|store| store.read())
+            .map(|layouts| layouts.last_session);
+        if let Ok(Some(layout)) = last_session {
+            if let Err(e) = apply_to_window(window, &layout) {
+                eprintln!("[WindowLayouts] Last session's window not restored: {}", e);
+            }
+        }
+        return;
+    };
+    match apply_layout(handle, window, name) {
+        Ok(_) => println!("[WindowLayouts] Applied startup layout '{}'", name),
+        Err(e) => eprintln!("[WindowLayouts] Startup layout not applied: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout(name: &str, position: LayoutPosition) -> WindowLayout {
+        WindowLayout {
+            name: name.to_string(),
+            width: 800,
+            height: 120,
+            position,
+            results_height: 0,
+            always_on_top: true,
+        }
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("window_layouts.json");
+        let store = StateStore::<LayoutStore>::new(path.clone(), "window layouts");
+
+        let expanded = WindowLayout {
+            height: 640,
+            results_height: 520,
+            always_on_top: false,
+            ..layout(
+                "expanded",
+                LayoutPosition::Fixed {
+                    monitor: Some("DELL U2720Q".to_string()),
+                    rel_x: 0.25,
+                    rel_y: 0.1,
+                },
+            )
+        };
+        store
+            .update(|layouts| {
+                layouts.save(layout("compact", LayoutPosition::FollowCursor));
+                layouts.save(expanded.clone());
+                layouts.active = Some("expanded".to_string());
+                Ok::<_, String>(())
+            })
+            .unwrap();
+
+        // A fresh store reads what was written
+        let reread = StateStore::<LayoutStore>::new(path, "window layouts").read().unwrap();
+        let names: Vec<String> = reread.list().into_iter().map(|l| l.name).collect();
+        assert_eq!(names, vec!["compact", "expanded"]);
+        assert_eq!(reread.active_layout(), Some(&expanded));
+
+        // Saving under an existing name replaces it; removing the active one clears it
+        let mut layouts = reread;
+        layouts.save(WindowLayout {
+            width: 640,
+            ..layout("compact", LayoutPosition::FollowCursor)
+        });
+        assert_eq!(layouts.get("compact").unwrap().width, 640);
+        layouts.remove("expanded").unwrap();
+        assert_eq!(layouts.active, None);
+        assert_eq!(
+            layouts.remove("expanded").unwrap_err(),
+            "Unknown window layout 'expanded'"
+        );
+        assert!(layouts.get("missing").is_err());
+    }
+
+    #[test]
+    fn test_last_session_kept_apart_from_layouts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("window_layouts.json");
+        // Written before the last session was saved
+        std::fs::write(&path, r#"{"layouts":{},"active":null}"#).unwrap();
+        let store = StateStore::<LayoutStore>::new(path.clone(), "window layouts");
+        assert_eq!(store.read().unwrap().last_session, None);
+
+        let session = layout("last session", LayoutPosition::FollowCursor);
+        store
+            .update(|layouts| {
+                layouts.last_session = Some(session.clone());
+                Ok::<_, String>(())
+            })
+            .unwrap();
+
+        let reread = StateStore::<LayoutStore>::new(path, "window layouts").read().unwrap();
+        assert_eq!(reread.last_session, Some(session));
+        assert!(reread.list().is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+//! Windows
+//! Window labels and on-demand window creation. Every window is described
+//! in tauri.conf.json; one marked `"create": false` isn't built at startup
+//! but the first time a feature asks for it through `ensure_window`. A
+//! window closed by the user is rebuilt the same way.
+
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow, WebviewWindowBuilder};
+
+/// The launcher window
+pub const MAIN_WINDOW: &str = "main";
+
+/// Held while checking for and creating a window, so concurrent callers
+/// asking for the same window get one window
+static CREATION: Mutex<()> = Mutex::new(());
+
+/// Looks up and builds windows. Implemented by AppHandle; tests use a fake.
+pub trait WindowHost {
+    type Window;
+
+    /// The open window with this label
+    fn find(&self, label: &str) -> Option<Self::Window>;
+
+    /// Build the window with this label from its configuration
+    fn create(&self, label: &str) -> Result<Self::Window, String>;
+}
+
+impl<R: Runtime> WindowHost for AppHandle<R> {
+    type Window = WebviewWindow<R>;
+
+    fn find(&self, label: &str) -> Option<Self::Window> {
+        self.get_webview_window(label)
+    }
+
+    fn create(&self, label: &str) -> Result<Self::Window, String> {
+        let config = self
+            .config()
+            .app
+            .windows
+            .iter()
+            .find(|w| w.label == label)
+            .ok_or_else(|| format!("Unknown window '{}'", label))?;
+
+        WebviewWindowBuilder::from_config(self, config)
+            .and_then(|builder| builder.build())
+            .map_err(|e| format!("Failed to create window '{}': {}", label, e))
+    }
+}
+
+/// The window with `label`, created from its configuration if it isn't open.
+/// Building a window waits on the main thread, so call this from async
+/// commands or background threads, not from the event loop.
+pub fn ensure_window<H: WindowHost>(host: &H, label: &str) -> Result<H::Window, String> {
+    let _creation = CREATION.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(window) = host.find(label) {
+        return Ok(window);
+    }
+
+    println!("[Windows] Creating window '{}'", label);
+    host.create(label)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+
+    /// Window registry that knows `known` labels and counts creations
+    #[derive(Default)]
+    struct FakeHost {
+        known: Vec<&'static str>,
+        open: Mutex<HashSet<String>>,
+        created: AtomicUsize,
+    }
+
+    impl WindowHost for FakeHost {
+        type Window = String;
+
+        fn find(&self, label: &str) -> Option<String> {
+            self.open.lock().unwrap().get(label).cloned()
+        }
+
+        fn create(&self, label: &str) -> Result<String, String> {
+            if !self.known.contains(&label) {
+                return Err(format!("Unknown window '{}'", label));
+            }
+            // Widen the race window between the check and the insert
+            thread::sleep(Duration::from_millis(20));
+            self.created.fetch_add(1, Ordering::SeqCst);
+            self.open.lock().unwrap().insert(label.to_string());
+            Ok(label.to_string())
+        }
+    }
+
+    #[test]
+    fn test_existing_window_is_reused() {
+        let host = FakeHost { known: vec![MAIN_WINDOW], ..Default::default() };
+        host.open.lock().unwrap().insert(MAIN_WINDOW.to_string());
+
+        assert_eq!(ensure_window(&host, MAIN_WINDOW).unwrap(), MAIN_WINDOW);
+        assert_eq!(host.created.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_concurrent_callers_create_once() {
+        let host = Arc::new(FakeHost { known: vec!["plugin-view"], ..Default::default() });
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let host = host.clone();
+                thread::spawn(move || ensure_window(host.as_ref(), "plugin-view").unwrap())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), "plugin-view");
+        }
+
+        assert_eq!(host.created.load(Ordering::SeqCst), 1);
+
+        // Closed windows are rebuilt on the next request
+        host.open.lock().unwrap().clear();
+        ensure_window(host.as_ref(), "plugin-view").unwrap();
+        assert_eq!(host.created.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_unknown_window_is_an_error() {
+        let host = FakeHost::default();
+        assert_eq!(ensure_window(&host, "results").unwrap_err(), "Unknown window 'results'");
+    }
+}
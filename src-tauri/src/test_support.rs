@@ -0,0 +1,118 @@
+//! Test Support
+//! A mock Tauri app for command-level tests. The app's bundle identifier is
+//! an absolute temp path, and the path resolver joins the identifier onto
+//! the platform directories, so app data, config, cache and log dirs all
+//! land in a per-test temp dir on every platform. Nothing is written to the
+//! developer's real app data, and tests don't see each other's files.
+
+use crate::models::preferences::AppSettings;
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, AppHandle, Manager};
+use tempfile::TempDir;
+
+/// Bytes of a file under tests/fixtures, e.g.
+/// `fixture!("favicons/solid_blue.png")`
+macro_rules! fixture {
+    ($path:literal) => {
+        include_bytes!(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/", $path))
+    };
+}
+pub(crate) use fixture;
+
+/// A mock app whose directories live in a temp dir removed on drop
+pub struct TestApp {
+    // Dropped before the directory it points into
+    app: App<MockRuntime>,
+    dir: TempDir,
+}
+
+impl TestApp {
+    pub fn new() -> Self {
+        let dir = tempfile::Builder::new()
+            .prefix("etools-test-")
+            .tempdir()
+            .expect("failed to create test app dir");
+
+        let mut context: tauri::Context<MockRuntime> = mock_context(noop_assets());
+        context.config_mut().identifier = dir.path().to_string_lossy().into_owned();
+        let app = mock_builder().build(context).expect("failed to build mock app");
+
+        let test_app = Self { app, dir };
+        for resolved in [test_app.data_dir(), test_app.config_dir()] {
+            assert_eq!(resolved, test_app.dir.path(), "app dirs must resolve into the temp dir");
+        }
+        test_app
+    }
+
+    pub fn handle(&self) -> &AppHandle<MockRuntime> {
+        self.app.handle()
+    }
+
+    /// Where the data dir resolves: databases, plugins, state files
+    pub fn data_dir(&self) -> PathBuf {
+        crate::services::app_paths::data_dir(self.handle()).expect("no app data dir")
+    }
+
+    /// Where `app_config_dir` resolves: settings.json
+    pub fn config_dir(&self) -> PathBuf {
+        self.handle().path().app_config_dir().expect("no app config dir")
+    }
+
+    /// Add managed state, as `setup` does for the real app
+    pub fn manage<T: Send + Sync + 'static>(&self, state: T) -> &Self {
+        self.app.manage(state);
+        self
+    }
+
+    /// Write `settings` as the saved settings file
+    pub fn seed_settings(&self, settings: &AppSettings) {
+        let json = serde_json::to_string_pretty(settings).expect("failed to serialize settings");
+        write(&self.config_dir().join("settings.json"), json.as_bytes());
+    }
+
+    /// Install a plugin folder with `manifest` as its plugin.json, plus an
+    /// empty entry file when the manifest names one. Returns the folder.
+    pub fn seed_plugin(&self, id: &str, manifest: serde_json::Value) -> PathBuf {
+        let dir = self.data_dir().join("plugins").join(id);
+        write(&dir.join("plugin.json"), manifest.to_string().as_bytes());
+        if let Some(entry) = manifest["entry"].as_str() {
+            write(&dir.join(entry), b"");
+        }
+        dir
+    }
+
+    /// Open the file index database, creating it if needed
+    pub fn files_db(&self) -> Connection {
+        crate::db::files::init_files_db(self.handle()).expect("failed to open files db")
+    }
+}
+
+fn write(path: &Path, contents: &[u8]) {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).expect("failed to create test dir");
+    }
+    fs::write(path, contents).expect("failed to write test file");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apps_are_isolated_and_cleaned_up() {
+        let first = TestApp::new();
+        let second = TestApp::new();
+        assert_ne!(first.data_dir(), second.data_dir());
+        assert!(first.data_dir().starts_with(std::env::temp_dir()));
+
+        first.seed_settings(&AppSettings::default());
+        assert!(!second.config_dir().join("settings.json").exists());
+
+        let dir = first.data_dir();
+        drop(first);
+        assert!(!dir.exists());
+    }
+}
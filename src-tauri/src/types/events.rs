@@ -0,0 +1,292 @@
+//! App Events
+//! Every event the backend sends to the frontend, with its payload type.
+//! Events go out through `emit` and `emit_to`, which only take payloads in
+//! the catalog below, so a name can't be misspelled at a call site and a
+//! payload can't be sent under the wrong name. build.rs reads the catalog
+//! and writes events.d.ts from it; the frontend imports the committed copy
+//! in src/types/generated, which the tests below keep up to date.
+
+use crate::models::search::SearchResultItem;
+use crate::models::{CalculatedWindowLayout, ScreenInfo, WindowLayout};
+use crate::services::app_paths::MigrationProgress;
+use crate::services::connectivity::ConnectivityStatus;
+use crate::services::data_reset::ResetReport;
+use crate::services::db_maintenance::{MaintenanceReport, MaintenanceTarget};
+use crate::services::drop_actions::{DropAction, DroppedItem};
+use crate::services::package_transfer::DownloadProgress;
+use crate::services::privacy::PrivacyStatus;
+use crate::services::search::filters::QueryFilters;
+use crate::services::search::SearchTimings;
+use crate::services::trigger_completions::CompletionRequest;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::{Emitter, EventTarget, Runtime};
+
+/// A payload that can be emitted, and the name it goes out under
+pub trait AppEvent: Serialize + Clone {
+    const NAME: &'static str;
+}
+
+impl<E: AppEvent> AppEvent for &E {
+    const NAME: &'static str = E::NAME;
+}
+
+/// Declares each event's name constant and ties its payload type to it
+macro_rules! app_events {
+    ($($(#[$meta:meta])* $constant:ident = $name:literal => $payload:ty;)*) => {
+        $(
+            $(#[$meta])*
+            pub const $constant: &str = $name;
+
+            impl AppEvent for $payload {
+                const NAME: &'static str = $constant;
+            }
+        )*
+
+        /// Every event name in the catalog
+        #[cfg(test)]
+        const ALL_EVENTS: &[&str] = &[$($constant),*];
+    };
+}
+
+app_events! {
+    /// Files indexed so far, and folders the indexer skipped
+    INDEX_PROGRESS = "index:progress" => IndexProgress;
+    /// One provider's results for a streaming search
+    SEARCH_PARTIAL = "search:partial" => SearchPartial;
+    /// A streaming search finished
+    SEARCH_COMPLETE = "search:complete" => SearchComplete;
+    /// Text to put in the search box, e.g. the selection it was opened with
+    SEARCH_PREFILL = "search:prefill" => SearchPrefill;
+    /// The installed applications were rescanned
+    APPS_CHANGED = "apps:changed" => AppsChanged;
+    /// The main window was shown and focused
+    WINDOW_SHOWN = "window-shown" => WindowShown;
+    /// The main window was shown and should start with an empty search
+    SEARCH_RESET = "search:reset" => SearchReset;
+    /// Files were dropped on the main window; these actions can take them
+    DROP_ACTIONS_AVAILABLE = "drop:actions-available" => DropActionsAvailable;
+    /// The main window starts resizing for the screen it's on
+    WINDOW_RESIZE_START = "window:resize_start" => ScreenInfo;
+    /// The main window finished resizing
+    WINDOW_RESIZE_COMPLETE = "window:resize_complete" => CalculatedWindowLayout;
+    /// A saved window layout was applied
+    WINDOW_LAYOUT_APPLIED = "window:layout_applied" => WindowLayout;
+    /// The screen's resolution, scale or display changed
+    SCREEN_CHANGED = "screen:changed" => ScreenChanged;
+    /// The app went online or offline
+    CONNECTIVITY_CHANGED = "connectivity:changed" => ConnectivityStatus;
+    /// Privacy mode was turned on or off
+    PRIVACY_MODE_CHANGED = "privacy-mode:changed" => PrivacyStatus;
+    /// A database maintenance run moved to its next stage
+    DB_MAINTENANCE_PROGRESS = "db-maintenance:progress" => MaintenanceProgress;
+    /// The integrity check after an unclean shutdown found problems
+    DB_MAINTENANCE_ISSUES = "db-maintenance:issues" => MaintenanceReport;
+    /// App data was reset
+    APP_DATA_RESET = "app-data:reset" => ResetReport;
+    /// Moving the data dir to a new folder reached its next step
+    DATA_LOCATION_PROGRESS = "data-location:progress" => MigrationProgress;
+    /// A plugin was disabled for misbehaving or being incompatible
+    PLUGIN_QUARANTINED = "plugin:quarantined" => PluginQuarantined;
+    /// A plugin was installed that has settings archived from an earlier
+    /// uninstall, which `restore_archived_settings` can bring back
+    PLUGIN_ARCHIVE_FOUND = "plugin:archive-found" => PluginArchiveFound;
+    /// A plugin was reloaded; its cached results were dropped
+    PLUGIN_RELOADED = "plugin:reloaded" => PluginReloaded;
+    /// A plugin trigger needs completions from the plugin's frontend code
+    PLUGIN_COMPLETION_REQUEST = "plugin:completion_request" => CompletionRequest;
+    /// Bytes received for a plugin package download
+    PLUGIN_DOWNLOAD_PROGRESS = "plugin-download:progress" => DownloadProgress;
+}
+
+/// Emit `event` to every window
+pub fn emit<R: Runtime, E: AppEvent>(emitter: &impl Emitter<R>, event: E) -> tauri::Result<()> {
+    emitter.emit(E::NAME, event)
+}
+
+/// Emit `event` to one window
+pub fn emit_to<R: Runtime, E: AppEvent>(
+    emitter: &impl Emitter<R>,
+    target: impl Into<EventTarget>,
+    event: E,
+) -> tauri::Result<()> {
+    emitter.emit_to(target, E::NAME, event)
+}
+
+/// `index:progress` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexProgress {
+    pub current: usize,
+    pub total: usize,
+    pub path: String,
+    pub stage: String,
+    /// Why `path` was skipped, for the "skipped" stage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl IndexProgress {
+    /// `path` wasn't scanned, e.g. for lack of permission
+    pub fn skipped(path: &Path, reason: &str) -> Self {
+        Self {
+            current: 0,
+            total: 0,
+            path: path.to_string_lossy().to_string(),
+            stage: "skipped".to_string(),
+            reason: Some(reason.to_string()),
+        }
+    }
+}
+
+/// `search:partial` payload, one per provider
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchPartial {
+    pub search_id: u64,
+    pub source: String,
+    /// Results the provider found, of which `results` are the first
+    pub total: usize,
+    pub results: Vec<SearchResultItem>,
+}
+
+/// `search:complete` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchComplete {
+    pub search_id: u64,
+    pub total: usize,
+    /// First page of the merged results; `get_results_page` serves the rest
+    pub results: Vec<SearchResultItem>,
+    pub query_time: u64,
+    pub groups: HashMap<String, usize>,
+    pub has_more_per_group: HashMap<String, bool>,
+    pub duplicates_folded: usize,
+    /// The query's exclusions and kind:/ext: filters, for results the
+    /// frontend adds itself
+    pub filters: QueryFilters,
+    /// Where the time went, if the search asked with `include_timings`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timings: Option<SearchTimings>,
+}
+
+/// `search:prefill` payload, the text itself
+#[derive(Debug, Clone, Serialize)]
+#[serde(transparent)]
+pub struct SearchPrefill(pub String);
+
+/// `apps:changed` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct AppsChanged {
+    pub total: usize,
+}
+
+/// `window-shown` payload, which is empty
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowShown;
+
+/// `search:reset` payload, which is empty
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchReset;
+
+/// `drop:actions-available` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct DropActionsAvailable {
+    pub items: Vec<DroppedItem>,
+    /// Built-in actions first, then plugins by name
+    pub actions: Vec<DropAction>,
+}
+
+/// What changed about the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScreenChangeType {
+    Resolution,
+    DisplayConnect,
+    ScaleFactor,
+}
+
+/// `screen:changed` payload
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenChanged {
+    pub old_screen_info: Option<ScreenInfo>,
+    pub new_screen_info: ScreenInfo,
+    pub change_type: ScreenChangeType,
+}
+
+/// `db-maintenance:progress` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceProgress {
+    pub target: MaintenanceTarget,
+    pub stage: String,
+    pub progress: u8,
+}
+
+/// `plugin:quarantined` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginQuarantined {
+    pub plugin_id: String,
+    pub reason: String,
+}
+
+/// `plugin:archive-found` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginArchiveFound {
+    pub plugin_id: String,
+    /// Version whose settings were archived
+    pub version: String,
+    /// Unix timestamp (ms)
+    pub archived_at: i64,
+}
+
+/// `plugin:reloaded` payload
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginReloaded {
+    pub plugin_id: String,
+    /// Cached results dropped for the plugin
+    pub invalidated_results: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    const GENERATED_DTS: &str = include_str!("../../../src/types/generated/events.d.ts");
+    const BUILT_DTS: &str = include_str!(concat!(env!("OUT_DIR"), "/events.d.ts"));
+
+    #[test]
+    fn test_catalog_matches_generated_definitions() {
+        let unique: HashSet<_> = ALL_EVENTS.iter().collect();
+        assert_eq!(unique.len(), ALL_EVENTS.len(), "event names must be unique");
+
+        for name in ALL_EVENTS {
+            assert!(
+                GENERATED_DTS.contains(&format!("'{}':", name)),
+                "{} is missing from events.d.ts, rebuild to regenerate it",
+                name
+            );
+        }
+        assert_eq!(
+            serde_json::to_value(SearchPrefill("hello".to_string())).unwrap(),
+            serde_json::json!("hello")
+        );
+        assert_eq!(serde_json::to_value(WindowShown).unwrap(), serde_json::Value::Null);
+        assert_eq!(serde_json::to_value(SearchReset).unwrap(), serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_committed_definitions_are_current() {
+        if GENERATED_DTS == BUILT_DTS {
+            return;
+        }
+        if std::env::var_os("UPDATE_EVENT_TYPES").is_some() {
+            let committed = concat!(env!("CARGO_MANIFEST_DIR"), "/../src/types/generated/events.d.ts");
+            std::fs::write(committed, BUILT_DTS).expect("Failed to update events.d.ts");
+            return;
+        }
+        panic!(
+            "src/types/generated/events.d.ts is out of date with the event catalog; \
+             run `UPDATE_EVENT_TYPES=1 cargo test test_committed_definitions_are_current` to update it"
+        );
+    }
+}
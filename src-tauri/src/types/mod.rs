@@ -1,3 +1 @@
-pub mod screen_events;
-
-pub use screen_events::ScreenChangedPayload;
+pub mod events;
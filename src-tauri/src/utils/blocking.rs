@@ -0,0 +1,17 @@
+//! Blocking Work in Async Commands
+//! Async commands share a small runtime pool; disk scans, large copies and
+//! database walks belong on the blocking pool so they can't stall other
+//! invokes.
+
+/// Run `work` on the blocking pool and wait for its result. If the caller
+/// is dropped mid-way the work still runs to completion, so it never
+/// leaves files or databases half-written.
+pub async fn run_blocking<T, F>(work: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(work)
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))?
+}
@@ -0,0 +1,9 @@
+pub mod atomic_write;
+pub mod blocking;
+pub mod state_store;
+pub mod url;
+
+pub use atomic_write::write_atomic;
+pub use blocking::run_blocking;
+pub use state_store::{managed_store, StateStore};
+pub use url::{normalize_url, url_parts, UrlParts};
@@ -0,0 +1,225 @@
+//! State Store
+//! A JSON state file together with its lock and an in-memory copy of its
+//! contents. Read-modify-write cycles hold the lock from read to write, so
+//! commands racing to toggle plugins or save settings take turns instead of
+//! overwriting each other's changes. Writes go through `write_atomic`.
+
+use super::write_atomic;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
+use tauri::{AppHandle, Manager, Runtime, State};
+
+/// Modification time and length of a file, None when it doesn't exist
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Turns file contents into state, wording its own errors
+type Parser<T> = fn(&str) -> Result<T, String>;
+
+/// The file's contents as of `stamp`
+struct Cached<T> {
+    value: T,
+    stamp: Stamp,
+}
+
+pub struct StateStore<T> {
+    path: PathBuf,
+    /// What the file holds, for error messages, e.g. "plugin state"
+    name: &'static str,
+    /// Custom parser, plain deserializing when None
+    parse: Option<Parser<T>>,
+    cached: Mutex<Option<Cached<T>>>,
+}
+
+impl<T> StateStore<T> {
+    pub fn new(path: PathBuf, name: &'static str) -> Self {
+        Self {
+            path,
+            name,
+            parse: None,
+            cached: Mutex::new(None),
+        }
+    }
+
+    /// A store whose file needs more than plain deserializing, e.g. a
+    /// schema migration
+    pub fn with_parser(path: PathBuf, name: &'static str, parse: Parser<T>) -> Self {
+        Self {
+            parse: Some(parse),
+            ..Self::new(path, name)
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl<T: Clone + Default + Serialize + DeserializeOwned> StateStore<T> {
+    fn lock(&self) -> Result<MutexGuard<'_, Option<Cached<T>>>, String> {
+        self.cached.lock().map_err(|e| format!("Failed to lock {}: {}", self.name, e))
+    }
+
+    /// Bring the cached copy up to date. The file is only re-read when it
+    /// changed since it was cached, e.g. by a backup restore or a data reset.
+    fn refresh<'a>(&self, cached: &'a mut Option<Cached<T>>) -> Result<&'a mut Cached<T>, String> {
+        let current = stamp(&self.path);
+        if !matches!(cached, Some(c) if c.stamp == current) {
+            let value = match current {
+                None => T::default(),
+                Some(_) => {
+                    let content = fs::read_to_string(&self.path)
+                        .map_err(|e| format!("Failed to read {}: {}", self.name, e))?;
+                    match self.parse {
+                        Some(parse) => parse(&content)?,
+                        None => serde_json::from_str(&content)
+                            .map_err(|e| format!("Failed to parse {}: {}", self.name, e))?,
+                    }
+                }
+            };
+            *cached = Some(Cached { value, stamp: current });
+        }
+        Ok(cached.as_mut().expect("cache was just filled"))
+    }
+
+    fn write(&self, cached: &mut Option<Cached<T>>, value: T) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(&value)
+            .map_err(|e| format!("Failed to serialize {}: {}", self.name, e))?;
+        write_atomic(&self.path, json).map_err(|e| format!("Failed to write {}: {}", self.name, e))?;
+        *cached = Some(Cached {
+            value,
+            stamp: stamp(&self.path),
+        });
+        Ok(())
+    }
+
+    /// Current contents, the default when the file doesn't exist yet
+    pub fn read(&self) -> Result<T, String> {
+        let mut cached = self.lock()?;
+        Ok(self.refresh(&mut cached)?.value.clone())
+    }
+
+    /// Apply `change` to the current contents and write the result. The
+    /// lock is held throughout, so concurrent updates never lose each
+    /// other's changes. When `change` fails nothing is written.
+    pub fn update<U, E: From<String>>(&self, change: impl FnOnce(&mut T) -> Result<U, E>) -> Result<U, E> {
+        let mut cached = self.lock()?;
+        let mut value = self.refresh(&mut cached)?.value.clone();
+        let out = change(&mut value)?;
+        self.write(&mut cached, value)?;
+        Ok(out)
+    }
+
+    /// Overwrite the contents with `value`
+    pub fn replace(&self, value: T) -> Result<(), String> {
+        let mut cached = self.lock()?;
+        self.write(&mut cached, value)
+    }
+}
+
+/// The app's store for state of type `T`, created with `open` on first use.
+/// Stores are managed state keyed by type, so each state file needs its
+/// own type.
+pub fn managed_store<'a, R: Runtime, T: Send + Sync + 'static>(
+    handle: &'a AppHandle<R>,
+    open: impl FnOnce() -> Result<StateStore<T>, String>,
+) -> Result<State<'a, StateStore<T>>, String> {
+    if let Some(store) = handle.try_state::<StateStore<T>>() {
+        return Ok(store);
+    }
+    // When two commands race to open the store, the second `manage` is a
+    // no-op and both end up with the first store
+    handle.manage(open()?);
+    Ok(handle.state::<StateStore<T>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    fn store(dir: &Path) -> StateStore<HashMap<String, u32>> {
+        StateStore::new(dir.join("state.json"), "test state")
+    }
+
+    #[test]
+    fn test_missing_file_reads_as_default() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(store(dir.path()).read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = Arc::new(store(dir.path()));
+
+        let threads: Vec<_> = (0..50)
+            .map(|i| {
+                let store = Arc::clone(&store);
+                std::thread::spawn(move || {
+                    store
+                        .update(|state| {
+                            *state.entry("count".to_string()).or_insert(0) += 1;
+                            state.insert(format!("thread-{}", i), i);
+                            Ok::<_, String>(())
+                        })
+                        .unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let on_disk: HashMap<String, u32> =
+            serde_json::from_str(&fs::read_to_string(store.path()).unwrap()).unwrap();
+        assert_eq!(on_disk["count"], 50);
+        assert_eq!(on_disk.len(), 51);
+        assert_eq!(store.read().unwrap(), on_disk);
+    }
+
+    #[test]
+    fn test_failed_update_writes_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.replace(HashMap::from([("a".to_string(), 1)])).unwrap();
+
+        let result = store.update(|state| {
+            state.insert("b".to_string(), 2);
+            Err::<(), _>("rejected".to_string())
+        });
+
+        assert_eq!(result, Err("rejected".to_string()));
+        assert_eq!(store.read().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_changes_behind_the_store_are_picked_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        store.replace(HashMap::from([("a".to_string(), 1)])).unwrap();
+
+        fs::write(store.path(), r#"{ "restored": 7, "from": 8, "backup": 9 }"#).unwrap();
+        assert_eq!(store.read().unwrap().get("restored"), Some(&7));
+
+        fs::remove_file(store.path()).unwrap();
+        assert!(store.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unparseable_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = store(dir.path());
+        fs::write(store.path(), "not json").unwrap();
+        assert!(store.read().unwrap_err().starts_with("Failed to parse test state"));
+    }
+}
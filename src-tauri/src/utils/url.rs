@@ -0,0 +1,205 @@
+//! URL Normalization
+//! Reduces a URL to the form two browser entries must share to count as the
+//! same page. Only differences that never change the page are dropped:
+//! case and IDN encoding of the host, default ports, trailing slashes,
+//! in-page fragments and tracking parameters. Also splits a URL into the
+//! host and path shown for browser results.
+
+use url::Url;
+
+/// Query parameters that only track where a click came from
+const TRACKING_PARAMS: &[&str] = &[
+    "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "yclid", "igshid", "mc_cid", "mc_eid", "_ga",
+];
+
+fn is_tracking_param(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    key.starts_with("utm_") || TRACKING_PARAMS.contains(&key.as_str())
+}
+
+/// Fragments used as client-side routes ("#/inbox", "#!/page") address
+/// different pages, unlike in-page anchors
+fn is_route_fragment(fragment: &str) -> bool {
+    fragment.starts_with('/') || fragment.starts_with('!')
+}
+
+/// Normalized form of `url` for deduplication. Unparseable input is returned
+/// trimmed but otherwise unchanged, so it only matches itself.
+pub fn normalize_url(url: &str) -> String {
+    let trimmed = url.trim();
+    let Ok(parsed) = Url::parse(trimmed) else {
+        return trimmed.to_string();
+    };
+    if parsed.cannot_be_a_base() {
+        // mailto:, data: and the like have no path structure to normalize
+        let mut opaque = parsed;
+        opaque.set_fragment(None);
+        return opaque.to_string();
+    }
+
+    // Url already lowercases scheme and host, punycode-encodes IDN hosts and
+    // drops default ports
+    let mut normalized = format!("{}://", parsed.scheme());
+    if !parsed.username().is_empty() {
+        normalized.push_str(parsed.username());
+        if let Some(password) = parsed.password() {
+            normalized.push(':');
+            normalized.push_str(password);
+        }
+        normalized.push('@');
+    }
+    normalized.push_str(parsed.host_str().unwrap_or_default().trim_end_matches('.'));
+    if let Some(port) = parsed.port() {
+        normalized.push_str(&format!(":{}", port));
+    }
+    normalized.push_str(parsed.path().trim_end_matches('/'));
+
+    // Kept parameters stay in their original order and encoding
+    let query: Vec<&str> = parsed
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter(|pair| {
+            let key = pair.split('=').next().unwrap_or_default();
+            let key = urlencoding::decode(key).map(|k| k.into_owned()).unwrap_or_else(|_| key.to_string());
+            !is_tracking_param(&key)
+        })
+        .collect();
+    if !query.is_empty() {
+        normalized.push('?');
+        normalized.push_str(&query.join("&"));
+    }
+
+    if let Some(fragment) = parsed.fragment().filter(|f| is_route_fragment(f)) {
+        normalized.push('#');
+        normalized.push_str(fragment);
+    }
+
+    normalized
+}
+
+/// Host and path of a URL, as shown with a browser result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlParts {
+    /// Lowercase, without a trailing dot or "www."
+    pub host: String,
+    /// Percent-decoded where it decodes to UTF-8
+    pub path: String,
+}
+
+/// Host and path of `url`, None when it doesn't parse or has no host
+pub fn url_parts(url: &str) -> Option<UrlParts> {
+    let parsed = Url::parse(url.trim()).ok()?;
+    let host = parsed.host_str().filter(|host| !host.is_empty())?.trim_end_matches('.');
+    let path = parsed.path();
+    Some(UrlParts {
+        host: host.strip_prefix("www.").unwrap_or(host).to_string(),
+        path: urlencoding::decode(path).map(|p| p.into_owned()).unwrap_or_else(|_| path.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn same(a: &str, b: &str) {
+        assert_eq!(normalize_url(a), normalize_url(b), "{} vs {}", a, b);
+    }
+
+    fn different(a: &str, b: &str) {
+        assert_ne!(normalize_url(a), normalize_url(b), "{} vs {}", a, b);
+    }
+
+    #[test]
+    fn test_trailing_slashes_and_root() {
+        same("https://example.com/", "https://example.com");
+        same("https://example.com/docs/", "https://example.com/docs");
+        same("https://example.com/docs//", "https://example.com/docs");
+        assert_eq!(normalize_url("https://example.com/"), "https://example.com");
+        different("https://example.com/docs", "https://example.com/doc");
+    }
+
+    #[test]
+    fn test_scheme_host_case_and_default_ports() {
+        same("HTTPS://Example.COM/Path", "https://example.com/Path");
+        same("https://example.com:443/a", "https://example.com/a");
+        same("http://example.com:80/a", "http://example.com/a");
+        same("https://example.com./a", "https://example.com/a");
+        // Path case can matter
+        different("https://example.com/Path", "https://example.com/path");
+        different("https://example.com:8443/a", "https://example.com/a");
+        different("http://example.com/a", "https://example.com/a");
+        different("https://www.example.com/a", "https://example.com/a");
+    }
+
+    #[test]
+    fn test_fragments() {
+        same("https://example.com/a#section-2", "https://example.com/a");
+        same("https://example.com/a/#top", "https://example.com/a");
+        different("https://mail.example.com/#/inbox", "https://mail.example.com/#/sent");
+        different("https://app.example.com/#!/settings", "https://app.example.com/");
+    }
+
+    #[test]
+    fn test_tracking_params_removed() {
+        same("https://example.com/a?utm_source=x&utm_medium=y", "https://example.com/a");
+        same("https://example.com/a?UTM_Campaign=spring", "https://example.com/a");
+        same("https://example.com/a?fbclid=abc", "https://example.com/a");
+        same("https://example.com/a?gclid=1&id=5&utm_term=z", "https://example.com/a?id=5");
+        same("https://example.com/a?utm%5Fsource=x", "https://example.com/a");
+        assert_eq!(
+            normalize_url("https://example.com/a?q=rust&utm_source=news&page=2"),
+            "https://example.com/a?q=rust&page=2"
+        );
+    }
+
+    #[test]
+    fn test_meaningful_params_kept() {
+        different("https://example.com/watch?v=abc", "https://example.com/watch?v=def");
+        different("https://example.com/watch?v=abc", "https://example.com/watch");
+        different("https://example.com/search?q=a%20b", "https://example.com/search?q=a+c");
+        // Only whole-name matches are tracking params
+        different("https://example.com/a?utm=1", "https://example.com/a");
+        different("https://example.com/a?source=x", "https://example.com/a");
+        different("https://example.com/a?fbclid_page=2", "https://example.com/a");
+    }
+
+    #[test]
+    fn test_unicode_and_punycode_hosts() {
+        same("https://bücher.example/katalog", "https://xn--bcher-kva.example/katalog");
+        same("https://BÜCHER.example/", "https://xn--bcher-kva.example");
+        same("https://例え.jp/", "https://xn--r8jz45g.jp");
+        assert_eq!(normalize_url("https://bücher.example/"), "https://xn--bcher-kva.example");
+        different("https://bücher.example/", "https://bucher.example/");
+        // Non-ASCII paths are compared in their percent-encoded form
+        same("https://example.com/café", "https://example.com/caf%C3%A9");
+    }
+
+    #[test]
+    fn test_userinfo_and_ip_hosts() {
+        different("https://alice@example.com/", "https://bob@example.com/");
+        same("http://127.0.0.1:80/", "http://127.0.0.1");
+        same("http://[::1]:8080/x/", "http://[::1]:8080/x");
+    }
+
+    #[test]
+    fn test_non_hierarchical_and_invalid() {
+        same("mailto:someone@example.com#x", "mailto:someone@example.com");
+        different("mailto:a@example.com", "mailto:b@example.com");
+        assert_eq!(normalize_url("  not a url  "), "not a url");
+        same("file:///Users/me/notes.html#intro", "file:///Users/me/notes.html");
+        different("chrome://settings/", "chrome://history/");
+    }
+
+    #[test]
+    fn test_url_parts() {
+        let parts = url_parts("https://www.GitHub.com./tauri-apps/tauri/issues/42?q=1#top").unwrap();
+        assert_eq!(parts.host, "github.com");
+        assert_eq!(parts.path, "/tauri-apps/tauri/issues/42");
+        assert_eq!(url_parts("https://ja.wikipedia.org/wiki/%E6%9D%B1%E4%BA%AC").unwrap().path, "/wiki/東京");
+        assert_eq!(url_parts("https://example.com/%FF").unwrap().path, "/%FF");
+        assert!(url_parts("mailto:someone@example.com").is_none());
+        assert!(url_parts("not a url").is_none());
+    }
+}
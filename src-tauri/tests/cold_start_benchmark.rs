@@ -0,0 +1,97 @@
+//! Cold Start Benchmark
+//! Boots against a data dir seeded like a long-time user's (plugins,
+//! granted permissions, a 100k-file index and 50k browser entries) and
+//! fails when launch to interactive exceeds COLD_START_BUDGET_MS. It seeds
+//! large databases, so it only builds with the `cold-start-benchmark`
+//! feature; see benches/README.md for how to run it and the budget.
+
+use etools_lib::bench::{
+    datagen, init_core, open_browser_db, open_files_db, run_deferred_stages, StartupStage, StartupTimings,
+};
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+
+/// Launch to interactive, in ms. The mock runtime has no window or global
+/// hotkey, so this covers the settings and services stages.
+const COLD_START_BUDGET_MS: f64 = 150.0;
+
+const PLUGINS: usize = 25;
+const FILES: usize = 100_000;
+const BROWSER_ENTRIES: usize = 50_000;
+
+fn seed_data_dir(dir: &Path) {
+    fs::write(
+        dir.join("settings.json"),
+        json!({ "language": "en", "max_results": 50, "enable_browser_search": true }).to_string(),
+    )
+    .unwrap();
+
+    let plugins_dir = dir.join("plugins");
+    let mut grants = serde_json::Map::new();
+    for i in 0..PLUGINS {
+        let id = format!("plugin-{}", i);
+        let plugin_dir = plugins_dir.join(&id);
+        fs::create_dir_all(&plugin_dir).unwrap();
+        let manifest = json!({
+            "name": format!("Plugin {}", i),
+            "version": "1.0.0",
+            "description": "Seeded for the cold start benchmark",
+            "author": null,
+            "permissions": ["network", "notification"],
+            "entry": "index.js",
+            "triggers": [format!("p{}:", i)],
+            "api_version": "1.1.0",
+        });
+        fs::write(plugin_dir.join("plugin.json"), manifest.to_string()).unwrap();
+        fs::write(plugin_dir.join("index.js"), "export default {};").unwrap();
+        grants.insert(id, json!({ "granted_permissions": ["network"], "shell_allowlist": [] }));
+    }
+    fs::write(
+        plugins_dir.join("plugin_permissions.json"),
+        serde_json::Value::Object(grants).to_string(),
+    )
+    .unwrap();
+
+    datagen::seed_files_db(&open_files_db(&dir.join("files_index.db")).unwrap(), FILES).unwrap();
+    datagen::seed_browser_db(
+        &open_browser_db(&dir.join("browser_cache.db")).unwrap(),
+        BROWSER_ENTRIES,
+    )
+    .unwrap();
+}
+
+#[test]
+fn cold_start_benchmark() {
+    // An absolute identifier puts every app dir inside the temp dir
+    let dir = tempfile::tempdir().unwrap();
+    seed_data_dir(dir.path());
+    let mut context: tauri::Context<MockRuntime> = mock_context(noop_assets());
+    context.config_mut().identifier = dir.path().to_string_lossy().into_owned();
+    let app = mock_builder().build(context).unwrap();
+
+    let timings = StartupTimings::new();
+    init_core(app.handle(), &timings);
+    timings.mark_interactive();
+    run_deferred_stages(app.handle(), &timings);
+
+    let report = timings.report();
+    for stage in &report.stages {
+        let when = if stage.deferred { "deferred" } else { "startup" };
+        println!("{:?} ({}): {:.1} ms", stage.stage, when, stage.duration_ms);
+    }
+    let interactive = report.total_to_interactive_ms.unwrap();
+    println!("Total to interactive: {:.1} ms", interactive);
+
+    assert!(
+        interactive <= COLD_START_BUDGET_MS,
+        "took {:.1} ms to become interactive, budget is {} ms",
+        interactive,
+        COLD_START_BUDGET_MS
+    );
+    // None of the seeded data is read before the app is interactive
+    let deferred: Vec<_> = report.stages.iter().filter(|s| s.deferred).collect();
+    assert!(deferred.iter().all(|s| s.started_ms >= interactive));
+    assert!(deferred.iter().any(|s| s.stage == StartupStage::Indexes));
+}
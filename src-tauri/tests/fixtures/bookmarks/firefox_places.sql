@@ -0,0 +1,45 @@
+-- The parts of a Firefox places.sqlite the bookmark reader uses
+CREATE TABLE moz_places (
+    id INTEGER PRIMARY KEY,
+    url LONGVARCHAR,
+    title LONGVARCHAR,
+    visit_count INTEGER DEFAULT 0,
+    last_visit_date INTEGER,
+    description TEXT
+);
+CREATE TABLE moz_bookmarks (
+    id INTEGER PRIMARY KEY,
+    type INTEGER,
+    fk INTEGER DEFAULT NULL,
+    parent INTEGER,
+    position INTEGER,
+    title LONGVARCHAR,
+    dateAdded INTEGER,
+    guid TEXT UNIQUE
+);
+
+INSERT INTO moz_places (id, url, title) VALUES
+    (1, 'https://mail.example.com/', 'Mail'),
+    (2, 'https://news.ycombinator.com/', 'Hacker News'),
+    (3, 'https://lobste.rs/', 'Lobsters'),
+    (4, 'https://docs.rs/', 'Docs.rs'),
+    (5, 'https://www.mozilla.org/', 'Mozilla');
+
+-- Root folders have fixed guids; newer profiles also give them short titles
+INSERT INTO moz_bookmarks (id, type, fk, parent, position, title, dateAdded, guid) VALUES
+    (1, 2, NULL, 0, 0, '', 1700000000000000, 'root________'),
+    (2, 2, NULL, 1, 0, 'menu', 1700000000000000, 'menu________'),
+    (3, 2, NULL, 1, 1, 'toolbar', 1700000000000000, 'toolbar_____'),
+    (4, 2, NULL, 1, 2, 'tags', 1700000000000000, 'tags________'),
+    (5, 2, NULL, 1, 3, 'unfiled', 1700000000000000, 'unfiled_____'),
+    (6, 2, NULL, 1, 4, 'mobile', 1700000000000000, 'mobile______'),
+    (10, 2, NULL, 3, 0, 'Morning', 1700000100000000, 'morningFoldr'),
+    (11, 2, NULL, 10, 1, 'News', 1700000100000000, 'newsFolder__'),
+    (12, 1, 1, 10, 0, 'Mail', 1700000200000000, 'mailBookmark'),
+    (13, 1, 2, 11, 0, 'Hacker News', 1700000300000000, 'hnBookmark__'),
+    (14, 1, 3, 11, 1, 'Lobsters', 1700000400000000, 'lobstersBkmk'),
+    (15, 1, 4, 3, 1, 'Docs.rs', 1700000500000000, 'docsBookmark'),
+    (16, 1, 5, 2, 0, 'Mozilla', 1700000600000000, 'mozillaBkmrk'),
+    -- Tagging docs.rs "rust" files a second reference under the tag folder
+    (20, 2, NULL, 4, 0, 'rust', 1700000700000000, 'rustTagFoldr'),
+    (21, 1, 4, 20, 0, NULL, 1700000700000000, 'rustTagEntry');
@@ -14,6 +14,7 @@ import { PluginStoreProvider } from '@/services/pluginStateStore';
 import NotificationSystem from '@/components/PluginManager/NotificationSystem';
 import { pluginLoader } from '@/services/pluginLoader';
 import { initSandboxDevTools } from '@/services/sandboxDevTools';
+import { checkBuiltinIcons } from '@/utils/iconMaps';
 import { ViewContainer } from '@/components/ViewContainer';
 
 // Styles
@@ -35,12 +36,17 @@ function App() {
     const initializeApp = async () => {
       try {
         await pluginLoader.loadInstalledPlugins();
+        await pluginLoader.listenForCompletionRequests();
         console.log('[App] Installed plugins loaded successfully');
       } catch (error) {
         console.error('[App] Failed to load installed plugins:', error);
       }
 
       initSandboxDevTools();
+
+      if (import.meta.env.DEV) {
+        checkBuiltinIcons().catch((error) => console.error('[App] Failed to check builtin icons:', error));
+      }
     };
 
     initializeApp();
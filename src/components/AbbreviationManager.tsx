@@ -2,7 +2,7 @@ import React, { useState, useEffect } from 'react';
 import { Button } from '@/components/ui/Button';
 import { Input } from '@/components/ui/Input';
 import { Badge } from '@/components/ui/Badge';
-import { abbreviationService, type Abbreviation, type AbbreviationCategory, type AbbreviationConfig } from '@/services/abbreviationService';
+import { abbreviationService, isAbbreviationConflict, type Abbreviation, type AbbreviationCategory, type AbbreviationConfig } from '@/services/abbreviationService';
 
 interface AbbreviationManagerProps {
   onClose?: () => void;
@@ -64,6 +64,13 @@ export function AbbreviationManager({ onClose }: AbbreviationManagerProps) {
       await loadConfig();
       resetForm();
     } catch (error) {
+      if (isAbbreviationConflict(error)) {
+        const existing = config?.abbreviations.find(item => item.id === error.conflicting_id);
+        if (existing && confirm(`缩写 "${error.abbr}" 已存在，是否编辑已有的缩写？`)) {
+          startEdit(existing);
+        }
+        return;
+      }
       console.error('Failed to save abbreviation:', error);
       alert('保存失败: ' + error);
     }
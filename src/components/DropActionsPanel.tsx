@@ -0,0 +1,111 @@
+/**
+ * Drop Actions Panel
+ * Actions for files dropped on the launcher: copy their paths, move them to
+ * a folder, or hand them to a plugin that takes them
+ */
+
+import { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import type { DropAction, DropActionsAvailable } from '@/types/generated/events';
+import './DropActionsPanel.css';
+
+type DropOutcome =
+  | { type: 'copied' }
+  | { type: 'moved'; paths: string[] }
+  | { type: 'dispatch'; query: string };
+
+interface DropActionsPanelProps {
+  drop: DropActionsAvailable;
+  /** Run a plugin trigger query with the dropped paths */
+  onDispatch: (query: string) => void;
+  onClose: () => void;
+}
+
+const BUILTIN_TITLES: Record<string, string> = {
+  copy_path: '复制路径',
+  move_to_folder: '移动到文件夹…',
+};
+
+const KIND_ICONS = { file: '📄', directory: '📁', app: '📱', image: '🖼️' } as const;
+
+export function DropActionsPanel({ drop, onDispatch, onClose }: DropActionsPanelProps) {
+  const [destination, setDestination] = useState<string | null>(null);
+  const [status, setStatus] = useState<string | null>(null);
+  const paths = drop.items.map((item) => item.path);
+
+  const run = async (action: DropAction, target?: string) => {
+    try {
+      const outcome = await invoke<DropOutcome>('run_drop_action', {
+        actionId: action.id,
+        paths,
+        destination: target ?? null,
+      });
+      if (outcome.type === 'dispatch') {
+        onDispatch(outcome.query);
+      } else if (outcome.type === 'moved') {
+        setStatus(`已移动 ${outcome.paths.length} 个文件`);
+        setDestination(null);
+      } else {
+        setStatus('路径已复制');
+      }
+    } catch (error) {
+      setStatus(String(error));
+    }
+  };
+
+  const choose = (action: DropAction) => {
+    if (action.type === 'move_to_folder') {
+      setDestination('');
+    } else {
+      run(action);
+    }
+  };
+
+  const moveAction = drop.actions.find((action) => action.type === 'move_to_folder');
+
+  return (
+    <div className="drop-actions" role="dialog" aria-label="拖放的文件">
+      <div className="drop-actions__header">
+        <span className="drop-actions__files">
+          {drop.items.length === 1
+            ? `${KIND_ICONS[drop.items[0].kind]} ${drop.items[0].name}`
+            : `${drop.items.length} 个文件`}
+        </span>
+        <button className="drop-actions__close" type="button" onClick={onClose} aria-label="关闭">
+          ✕
+        </button>
+      </div>
+
+      <div className="drop-actions__list">
+        {drop.actions.map((action) => (
+          <button key={action.id} className="drop-actions__item" type="button" onClick={() => choose(action)}>
+            {BUILTIN_TITLES[action.id] ?? action.title}
+          </button>
+        ))}
+      </div>
+
+      {destination !== null && moveAction && (
+        <form
+          className="drop-actions__destination"
+          onSubmit={(e) => {
+            e.preventDefault();
+            run(moveAction, destination);
+          }}
+        >
+          <input
+            value={destination}
+            onChange={(e) => setDestination(e.target.value)}
+            placeholder="目标文件夹路径"
+            autoFocus
+            spellCheck={false}
+          />
+          <button type="submit" disabled={!destination.trim()}>
+            移动
+          </button>
+        </form>
+      )}
+
+      {status && <div className="drop-actions__status">{status}</div>}
+    </div>
+  );
+}
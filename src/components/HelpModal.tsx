@@ -23,6 +23,7 @@ const SHORTCUTS: Shortcut[] = [
   { keys: ['Esc'], description: '关闭窗口/清空输入', category: '全局' },
   { keys: ['↑', '↓'], description: '在结果中导航', category: '搜索' },
   { keys: ['Enter'], description: '执行选中项', category: '搜索' },
+  { keys: ['Cmd', 'Enter'], description: '执行次要操作（如复制命令行工具路径）', category: '搜索' },
   { keys: ['Tab'], description: '切换搜索类型', category: '搜索' },
   { keys: ['Cmd', 'C'], description: '复制选中内容', category: '剪贴板' },
   { keys: ['Cmd', 'V'], description: '粘贴剪贴板历史', category: '剪贴板' },
@@ -230,7 +230,7 @@ const PluginList: React.FC<PluginListProps> = ({
           searchQuery={searchQuery}
           onToggleSelect={() => onToggleSelect?.(plugin.manifest.id)}
           onToggleEnable={() => onToggleEnable?.(plugin.manifest.id)}
-          onUninstall={() => onUninstall?.(plugin.manifest.id)}
+          onUninstall={plugin.protected ? undefined : () => onUninstall?.(plugin.manifest.id)}
           onUpdate={() => onUpdate?.(plugin.manifest.id)}
           onClick={() => onPluginClick?.(plugin)}
         />
@@ -37,6 +37,28 @@ export const PluginMetadata: React.FC<PluginMetadataProps> = ({ plugin }) => {
             </span>
           </div>
         )}
+        {plugin.manifest.homepage && (
+          <div className="metadata-item">
+            <span className="metadata-label">主页:</span>
+            <a className="metadata-value" href={plugin.manifest.homepage} target="_blank" rel="noopener noreferrer">
+              {plugin.manifest.homepage}
+            </a>
+          </div>
+        )}
+        {plugin.manifest.repository && (
+          <div className="metadata-item">
+            <span className="metadata-label">源码:</span>
+            <a className="metadata-value" href={plugin.manifest.repository} target="_blank" rel="noopener noreferrer">
+              {plugin.manifest.repository}
+            </a>
+          </div>
+        )}
+        {plugin.manifest.license && (
+          <div className="metadata-item">
+            <span className="metadata-label">许可证:</span>
+            <span className="metadata-value">{plugin.manifest.license}</span>
+          </div>
+        )}
         <div className="metadata-item">
           <span className="metadata-label">状态:</span>
           <span className={`metadata-value ${plugin.enabled ? 'enabled' : 'disabled'}`}>
@@ -10,9 +10,10 @@
  * - Fast icon loading using iconutil command
  */
 
-import { memo, useMemo, useState, useEffect, useRef } from "react";
+import { memo, useMemo, useState, useEffect, useRef, type CSSProperties } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { SearchResult } from "@/types/search";
+import { getBuiltinIcon } from "@/utils/iconMaps";
 import "@/styles/components/ResultList.css";
 
 interface ResultListProps {
@@ -83,7 +84,16 @@ const ResultItem = memo(({
     };
   }, [result.type, result.icon, result.path]);
 
-  const isEmojiIcon = iconUrl && /^[\p{Emoji}\p{Symbol}\p{Other_Symbol}]/u.test(iconUrl);
+  // Builtin icons are names the backend shares with iconMaps
+  const builtinIcon = result.iconKind === 'builtin' && result.icon ? getBuiltinIcon(result.icon) : null;
+
+  // Emoji results always use their glyph, which may be punctuation such as an em dash
+  const isEmojiIcon = iconUrl && (result.iconKind === 'emoji' || result.type === 'emoji' || /^[\p{Emoji}\p{Symbol}\p{Other_Symbol}]/u.test(iconUrl));
+
+  // Accent colors are theme-neutral; the stylesheet mixes them with the theme background
+  const accentStyle = result.accentColor
+    ? ({ '--result-accent': result.accentColor } as CSSProperties)
+    : undefined;
 
   // Memoize highlighted title to avoid recalculating on each render
   const highlightedTitle = useMemo(() => {
@@ -126,8 +136,15 @@ const ResultItem = memo(({
       tabIndex={isSelected ? 0 : -1}
     >
       {/* Icon */}
-      <div className="result-item__icon">
-        {iconUrl ? (
+      <div
+        className={`result-item__icon ${accentStyle ? 'result-item__icon--accent' : ''}`}
+        style={accentStyle}
+      >
+        {builtinIcon ? (
+          <div className="result-item__icon-placeholder" aria-hidden="true">
+            {builtinIcon}
+          </div>
+        ) : iconUrl ? (
           isEmojiIcon ? (
             <span className="result-item__icon-emoji" aria-hidden="true">
               {iconUrl}
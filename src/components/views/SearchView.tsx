@@ -5,10 +5,12 @@
 
 import { useState, useEffect, useRef, useCallback } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import { listen } from '@tauri-apps/api/event';
 import { useSearch } from '@/hooks/useSearch';
+import { listenAppEvent } from '@/services/appEvents';
 import { useViewManagerStore } from '@/stores/viewManagerStore';
 import { ResultList } from '../ResultList';
+import { DropActionsPanel } from '../DropActionsPanel';
+import type { DropActionsAvailable } from '@/types/generated/events';
 import type { SearchResult } from '@/types/search';
 import { logger, initLogger } from '@/lib/logger';
 
@@ -95,20 +97,22 @@ declare global {
 
 export function SearchView() {
   const [query, setQuery] = useState('');
+  const [droppedFiles, setDroppedFiles] = useState<DropActionsAvailable | null>(null);
   const [selectedIndex, setSelectedIndex] = useState(0);
   const inputRef = useRef<HTMLInputElement>(null);
-  const { results, search, isLoading } = useSearch();
+  const { results, moreResults, hasMore, loadMore, search, isLoading } = useSearch();
   const { navigateToView } = useViewManagerStore();
   const isUserTypingRef = useRef(false);
   const isHidingRef = useRef(false);
 
-  const limitedResults = results.slice(0, 10);
+  const limitedResults = [...results.slice(0, 10), ...moreResults];
 
   const [recentApps, setRecentApps] = useState<Array<{
     id: string;
     name: string;
     executable_path: string;
     icon?: string;
+    source?: 'installed' | 'settings_pane' | 'store_app';
   }>>([]);
 
   useEffect(() => {
@@ -116,7 +120,7 @@ export function SearchView() {
     logger.info('SearchView', 'Component mounted');
 
     invoke('get_recently_used', { limit: 10 })
-      .then((response: { apps: Array<{ id: string; name: string; executable_path: string; icon?: string }> }) => {
+      .then((response: { apps: typeof recentApps }) => {
         setRecentApps(response.apps);
       })
       .catch((error) => {
@@ -133,13 +137,23 @@ export function SearchView() {
 
     focusInput();
 
-    const unlistenPromise = listen('window-shown', () => {
+    const unlistenPromise = listenAppEvent('window-shown', () => {
       if (!isUserTypingRef.current) {
         focusInput();
       }
     });
+    // Sent instead of keeping the last search, per the on-show setting;
+    // an empty query also empties the results
+    const unlistenResetPromise = listenAppEvent('search:reset', () => {
+      isUserTypingRef.current = false;
+      setQuery('');
+    });
+    // Files dropped on the window, with the actions that can take them
+    const unlistenDropPromise = listenAppEvent('drop:actions-available', setDroppedFiles);
     return () => {
       unlistenPromise.then(fn => fn());
+      unlistenResetPromise.then(fn => fn());
+      unlistenDropPromise.then(fn => fn());
     };
   }, []);
 
@@ -171,6 +185,10 @@ export function SearchView() {
         e.preventDefault();
         const nextIndex = selectedIndex < limitedResults.length - 1 ? selectedIndex + 1 : selectedIndex;
         setSelectedIndex(nextIndex);
+        // Arrowing onto the last row fetches the next page of results
+        if (hasMore && nextIndex === limitedResults.length - 1) {
+          void loadMore();
+        }
         break;
       case 'ArrowUp':
         e.preventDefault();
@@ -180,7 +198,7 @@ export function SearchView() {
       case 'Enter':
         e.preventDefault();
         if (limitedResults[selectedIndex]) {
-          await handleSelect(limitedResults[selectedIndex]);
+          await handleSelect(limitedResults[selectedIndex], e.metaKey || e.ctrlKey);
         }
         break;
       case 'Escape':
@@ -194,14 +212,18 @@ export function SearchView() {
     }
   };
 
-  const handleSelect = async (result: SearchResult) => {
+  const handleSelect = async (result: SearchResult, secondary = false) => {
     if (!result.action) {
       logger.error('SearchView', 'No action defined for result:', result);
       return;
     }
 
     try {
-      await result.action();
+      if (secondary && result.secondaryAction) {
+        await result.secondaryAction();
+      } else {
+        await result.action();
+      }
 
       if (result.type === 'app') {
         try {
@@ -211,6 +233,17 @@ export function SearchView() {
         }
       }
 
+      // Searches count as usage, and are remembered for recall, once a
+      // result is acted on, not per keystroke
+      if (query.trim()) {
+        invoke('record_usage_event', { kind: 'search', subject: query }).catch((error) => {
+          console.warn('Failed to record search usage:', error);
+        });
+        invoke('remember_query', { query }).catch((error) => {
+          console.warn('Failed to remember query:', error);
+        });
+      }
+
       isHidingRef.current = true;
       await hideWindow();
       setQuery('');
@@ -350,6 +383,18 @@ export function SearchView() {
           </button>
         </div>
 
+        {droppedFiles && (
+          <DropActionsPanel
+            drop={droppedFiles}
+            onDispatch={(dispatchQuery) => {
+              setDroppedFiles(null);
+              setQuery(dispatchQuery);
+              inputRef.current?.focus();
+            }}
+            onClose={() => setDroppedFiles(null)}
+          />
+        )}
+
         {query && limitedResults.length > 0 && (
           <div className="search-results-section">
             <ResultList
@@ -360,7 +405,7 @@ export function SearchView() {
               query={query}
               id="search-results"
             />
-            {results.length > 10 && (
+            {results.length > 10 && moreResults.length === 0 && (
               <div className="search-results-footer">
                 显示前 10 个结果，共 {results.length} 个
               </div>
@@ -384,7 +429,7 @@ export function SearchView() {
                           title: app.name,
                           type: 'app',
                           action: async () => {
-                            await invoke('launch_app', { path: app.executable_path });
+                            await invoke('launch_app', { path: app.executable_path, source: app.source });
                             await invoke('track_app_usage', { appId: app.id });
                           },
                         } as any)}
@@ -5,6 +5,7 @@
 
 import { useState, useCallback, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import type { Page } from '@/types/search';
 
 export interface ClipboardItem {
   id: string;
@@ -35,10 +36,10 @@ export function useClipboard() {
   const loadHistory = useCallback(async () => {
     setIsLoading(true);
     try {
-      const history = await invoke<ClipboardItem[]>('get_clipboard_history', {
+      const history = await invoke<Page<ClipboardItem>>('get_clipboard_history', {
         limit: 50,
       });
-      setItems(history);
+      setItems(history.items);
     } catch (e) {
       console.error('Failed to load clipboard history:', e);
     } finally {
@@ -19,6 +19,10 @@ export interface FileSearchResult {
   extension: string | null;
   size: number;
   indexed: number;
+  tags: string[];
+  is_placeholder: boolean;  // Cloud file not downloaded; open with download_and_open_file
+  parent_dir: string;  // Pass as pathPrefix to search_files to search within the folder
+  parent_display: string;  // parent_dir shortened, e.g. "~/Projects/…/src"
 }
 
 export interface UseFileIndexReturn {
@@ -4,12 +4,15 @@
 
 import { useState, useCallback, useRef, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
-import type { SearchResult } from '@/types/search';
+import type { Page, QueryFilter, QueryFilters, SearchResult } from '@/types/search';
+import type { SearchComplete, SearchResultItem } from '@/types/generated/events';
+import { listenAppEvent } from '@/services/appEvents';
 import { getSearchService } from '@/services/searchService';
 import { getActionService } from '@/services/actionService';
 import { pluginLoader } from '@/services/pluginLoader';
 import { abbreviationService } from '@/services/abbreviationService';
 import { pluginAbbreviationService } from '@/services/pluginAbbreviationService';
+import i18n from '@/i18n';
 
 // ============================================================================
 // Types
@@ -17,6 +20,10 @@ import { pluginAbbreviationService } from '@/services/pluginAbbreviationService'
 
 interface SearchState {
   results: SearchResult[];
+  /** Rows appended past `results` by `loadMore` */
+  moreResults: SearchResult[];
+  /** Whether `loadMore` has rows left to add */
+  hasMore: boolean;
   query: string;
   selectedIndex: number;
   isLoading: boolean;
@@ -29,6 +36,16 @@ interface UseSearchOptions {
   maxResults?: number;
 }
 
+/** Where a streaming search's results continue past those shown */
+interface Paging {
+  searchId: number;
+  /** Next row of the backend's full results to fetch */
+  offset: number;
+  total: number;
+  /** First-page rows the re-ranking left out, shown before fetching more */
+  leftover: SearchResult[];
+}
+
 interface SearchContext {
   query: string;
   maxResults: number;
@@ -48,7 +65,7 @@ async function searchClipboard(
   maxResults: number
 ): Promise<SearchResult[]> {
   try {
-    const clipboardItems = await invoke<Array<{
+    const clipboardPage = await invoke<Page<{
       id: string;
       content: string;
       timestamp: number;
@@ -58,7 +75,7 @@ async function searchClipboard(
       limit: maxResults,
     });
 
-    return clipboardItems.map((item) => ({
+    return clipboardPage.items.map((item) => ({
       id: item.id,
       title: item.content.substring(0, 50) + (item.content.length > 50 ? '...' : ''),
       subtitle: new Date(item.timestamp).toLocaleString(),
@@ -79,42 +96,198 @@ async function searchClipboard(
   }
 }
 
+/** Rows fetched per `get_results_page` call as the list is extended */
+const RESULTS_PAGE_SIZE = 20;
+/** Wait before asking again for a page of a search that is still running */
+const PENDING_RETRY_MS = 100;
+const PENDING_RETRIES = 20;
+
+/** Id of the latest streaming search started here; ids only grow */
+let lastSearchId = 0;
+/** Rejects the streaming search in flight once a newer one starts */
+let supersedePending: (() => void) | null = null;
+
+function abortError(): Error {
+  const error = new Error('Search superseded');
+  error.name = 'AbortError';
+  return error;
+}
+
 /**
- * Search using backend unified search
+ * Map a backend result to a UI result with its action
  */
-async function searchUnified(
-  query: string,
-  maxResults: number
-): Promise<SearchResult[]> {
-  const response = await invoke<{
-    results: Array<{
-      id: string;
-      title: string;
-      subtitle: string;
-      icon?: string;
-      type: string;
-      score: number;
-      path: string;
-      frequency: number;
-    }>;
-    total: number;
-    query_time: number;
-  }>('unified_search', {
-    query: { query, limit: maxResults, sources: null },
-  });
-
-  return response.results.map(r => ({
+function toSearchResult(r: SearchResultItem): SearchResult {
+  return {
     id: r.id,
     title: r.title,
     subtitle: r.subtitle,
-    icon: r.icon,
+    icon: r.icon ?? undefined,
+    iconKind: r.icon_kind,
+    accentColor: r.accent_color,
     type: r.type as SearchResult['type'],
     score: r.score,
     path: r.path,
     action: async () => {
+      // Date and time answers carry the value to copy in `path`
+      if (r.type === 'datetime') {
+        await invoke('write_clipboard_text', { text: r.path });
+        return;
+      }
+      if (r.type === 'emoji') {
+        await invoke('write_clipboard_text', { text: r.path });
+        invoke('record_emoji_usage', { emoji: r.path }).catch(console.error);
+        return;
+      }
+      if (r.type === 'process') {
+        await endProcess(Number(r.path), r.title);
+        return;
+      }
+      if (r.type === 'ssh') {
+        await invoke('open_ssh_session', { host: r.path });
+        return;
+      }
+      if (r.type === 'cli') {
+        await invoke('run_cli_tool', { path: r.path });
+        return;
+      }
+      // History can hold destructive commands, so ask before running one
+      if (r.type === 'shell-history') {
+        if (confirm(i18n.t('search.shellHistory.runConfirm', { command: r.path }))) {
+          await invoke('run_history_command', { command: r.path, confirmed: true });
+        }
+        return;
+      }
+      // Opened by the configured opener, or the system default
+      if (r.type === 'file') {
+        await invoke('open_result', { request: { kind: 'file', path: r.path } });
+        return;
+      }
+      if (r.type === 'browser') {
+        await invoke('open_result', { request: { kind: 'browser_result', url: r.path } });
+        return;
+      }
       await invoke('launch_app', { path: r.path });
     },
-  }));
+    // Command-line tools and history: copy the full path or command instead of running
+    secondaryAction: r.type === 'cli' || r.type === 'shell-history'
+      ? async () => { await invoke('write_clipboard_text', { text: r.path }); }
+      : undefined,
+  };
+}
+
+/**
+ * Search using the backend's streaming search. Resolves with the first page
+ * of `search:complete`; the rest is fetched with `fetchResultsPage`. A search
+ * superseded by a newer one rejects with an AbortError, since the backend
+ * drops it without completing it.
+ */
+async function searchUnified(query: string): Promise<{
+  results: SearchResult[];
+  filters: QueryFilters;
+  searchId: number;
+  total: number;
+}> {
+  supersedePending?.();
+  const searchId = ++lastSearchId;
+
+  let settle: ((payload: SearchComplete) => void) | null = null;
+  const unlisten = await listenAppEvent('search:complete', (payload) => {
+    if (payload.search_id === searchId) {
+      settle?.(payload);
+    }
+  });
+  try {
+    if (searchId !== lastSearchId) {
+      throw abortError();
+    }
+    const complete = await new Promise<SearchComplete>((resolve, reject) => {
+      settle = resolve;
+      supersedePending = () => reject(abortError());
+      // Limit left to max_results, so there is more than the first page to fetch
+      invoke('unified_search_streaming', {
+        query: { query, limit: null, sources: null },
+        searchId,
+      }).catch(reject);
+    });
+    return {
+      results: complete.results.map(toSearchResult),
+      filters: complete.filters,
+      searchId,
+      total: complete.total,
+    };
+  } finally {
+    unlisten();
+    if (searchId === lastSearchId) {
+      supersedePending = null;
+    }
+  }
+}
+
+/**
+ * Results `offset` onwards of a streaming search, or null once the search
+ * has expired. Retries while the backend reports it still running.
+ */
+async function fetchResultsPage(searchId: number, offset: number): Promise<SearchResultItem[] | null> {
+  for (let attempt = 0; attempt <= PENDING_RETRIES; attempt++) {
+    try {
+      const page = await invoke<{ results: SearchResultItem[] }>('get_results_page', {
+        searchId,
+        offset,
+        limit: RESULTS_PAGE_SIZE,
+      });
+      return page.results;
+    } catch (error) {
+      if ((error as { kind?: string })?.kind !== 'pending') {
+        return null;
+      }
+      await new Promise((resolve) => setTimeout(resolve, PENDING_RETRY_MS));
+    }
+  }
+  return null;
+}
+
+/**
+ * Whether a result matches one of the query's filters, the same way the
+ * backend matches its own results
+ */
+function matchesFilter(result: SearchResult, filter: QueryFilter): boolean {
+  const path = result.path ?? '';
+  switch (filter.type) {
+    case 'text':
+      return result.title.toLowerCase().includes(filter.value) || path.toLowerCase().includes(filter.value);
+    case 'kind':
+      return result.type.toLowerCase() === filter.value.toLowerCase();
+    case 'ext': {
+      const name = path.split(/[\\/]/).pop() ?? '';
+      const dot = name.lastIndexOf('.');
+      return dot > 0 && name.slice(dot + 1).toLowerCase() === filter.value.toLowerCase();
+    }
+  }
+}
+
+/**
+ * Drop results the query's filters exclude. The backend filters its own
+ * results; this covers the ones added here, like plugins'.
+ */
+function applyQueryFilters(results: SearchResult[], filters: QueryFilters): SearchResult[] {
+  return results.filter(result =>
+    !filters.excluded.some(filter => matchesFilter(result, filter)) &&
+    filters.required.every(filter => matchesFilter(result, filter))
+  );
+}
+
+/**
+ * Quit a process, offering to force kill it if it doesn't quit
+ */
+async function endProcess(pid: number, name: string): Promise<void> {
+  try {
+    await invoke('end_process', { pid, name, action: 'quit', confirmed: false });
+  } catch (error) {
+    console.warn(`[useSearch] Failed to quit ${name}:`, error);
+    if (confirm(i18n.t('search.process.forceKillConfirm', { name }))) {
+      await invoke('end_process', { pid, name, action: 'force_kill', confirmed: true });
+    }
+  }
 }
 
 /**
@@ -133,6 +306,7 @@ async function searchFiles(
       extension: string | null;
       size: number;
       indexed: number;
+      tags: string[];
     }>>('search_files', {
       query,
       limit: maxResults,
@@ -161,7 +335,7 @@ async function searchBrowser(
   searchService: ReturnType<typeof getSearchService>
 ): Promise<SearchResult[]> {
   try {
-    const browserResults = await invoke<Array<{
+    const browserPage = await invoke<Page<{
       id: string;
       title: string;
       url: string;
@@ -169,12 +343,13 @@ async function searchBrowser(
       entry_type: string;
       favicon: string | null;
       last_visited: number;
+      sources: string[];
     }>>('search_browser_data', {
       query,
       limit: maxResults,
     });
 
-    return browserResults.map((item) =>
+    return browserPage.items.map((item) =>
       searchService.createBrowserResult(
         item.id,
         item.title,
@@ -211,6 +386,7 @@ async function searchAbbreviations(query: string): Promise<{
         score: 0.95,
         action: async () => {
           await invoke('open_url', { url: abbr.expansion });
+          void abbreviationService.recordUse(abbr.id);
         },
       };
     });
@@ -323,6 +499,7 @@ async function performSearch(ctx: SearchContext): Promise<{
   results: SearchResult[];
   clipboardMode: boolean;
   shouldReturn?: boolean;
+  paging?: Paging;
 }> {
   const { query, maxResults, searchService, actionService } = ctx;
 
@@ -341,7 +518,8 @@ async function performSearch(ctx: SearchContext): Promise<{
   }
 
   // Unified search from backend
-  let searchResults = await searchUnified(query, maxResults);
+  const unified = await searchUnified(query);
+  let searchResults = unified.results;
 
   // Run file and browser searches in parallel
   const [fileResults, browserResults] = await Promise.all([
@@ -357,6 +535,14 @@ async function performSearch(ctx: SearchContext): Promise<{
   });
   searchResults = rankedResults.results;
 
+  const shown = new Set(searchResults.map((result) => result.id));
+  const paging: Paging = {
+    searchId: unified.searchId,
+    offset: unified.results.length,
+    total: unified.total,
+    leftover: unified.results.filter((result) => !shown.has(result.id)),
+  };
+
   // Search abbreviations
   const abbrResult = await searchAbbreviations(query);
   if (abbrResult.shouldReturn) {
@@ -366,13 +552,17 @@ async function performSearch(ctx: SearchContext): Promise<{
 
   // Search plugin abbreviations
   const pluginAbbrResults = await searchPluginAbbreviations(query);
-  searchResults = [...pluginAbbrResults, ...searchResults];
+  searchResults = [...applyQueryFilters(pluginAbbrResults, unified.filters), ...searchResults];
 
   // Search plugins
   const pluginResults = await searchPlugins(query);
-  searchResults = [...searchResults, ...pluginResults];
+  searchResults = [...searchResults, ...applyQueryFilters(pluginResults, unified.filters)];
 
-  return { results: searchResults, clipboardMode: false };
+  return { results: searchResults, clipboardMode: false, paging };
+}
+
+function hasMorePages(paging: Paging | null): boolean {
+  return paging !== null && (paging.leftover.length > 0 || paging.offset < paging.total);
 }
 
 // ============================================================================
@@ -384,6 +574,8 @@ export function useSearch(options: UseSearchOptions = {}) {
 
   const [state, setState] = useState<SearchState>({
     results: [],
+    moreResults: [],
+    hasMore: false,
     query: '',
     selectedIndex: 0,
     isLoading: false,
@@ -395,6 +587,8 @@ export function useSearch(options: UseSearchOptions = {}) {
   const actionService = getActionService();
   const debounceRef = useRef<ReturnType<typeof setTimeout> | null>(null);
   const abortControllerRef = useRef<AbortController | null>(null);
+  const pagingRef = useRef<Paging | null>(null);
+  const loadingMoreRef = useRef(false);
 
   /**
    * Perform search with debouncing
@@ -409,9 +603,12 @@ export function useSearch(options: UseSearchOptions = {}) {
     setState((prev) => ({ ...prev, query }));
 
     if (!query.trim()) {
+      pagingRef.current = null;
       setState((prev) => ({
         ...prev,
         results: [],
+        moreResults: [],
+        hasMore: false,
         selectedIndex: 0,
         isLoading: false,
         clipboardMode: false,
@@ -437,10 +634,13 @@ export function useSearch(options: UseSearchOptions = {}) {
           actionService,
         });
 
+        pagingRef.current = result.paging ?? null;
         if (result.shouldReturn) {
           setState((prev) => ({
             ...prev,
             results: [],
+            moreResults: [],
+            hasMore: false,
             isLoading: false,
             selectedIndex: 0,
           }));
@@ -450,6 +650,8 @@ export function useSearch(options: UseSearchOptions = {}) {
         setState((prev) => ({
           ...prev,
           results: result.results,
+          moreResults: [],
+          hasMore: hasMorePages(pagingRef.current),
           clipboardMode: result.clipboardMode,
           selectedIndex: 0,
           isLoading: false,
@@ -466,6 +668,41 @@ export function useSearch(options: UseSearchOptions = {}) {
     }, debounceMs);
   }, [debounceMs, maxResults, searchService, actionService]);
 
+  /**
+   * Append the next rows of the current search to `moreResults`
+   */
+  const loadMore = useCallback(async () => {
+    const paging = pagingRef.current;
+    if (!paging || loadingMoreRef.current) {
+      return;
+    }
+    loadingMoreRef.current = true;
+    try {
+      let rows = paging.leftover.splice(0, RESULTS_PAGE_SIZE);
+      if (rows.length === 0 && paging.offset < paging.total) {
+        const page = await fetchResultsPage(paging.searchId, paging.offset);
+        if (page === null || page.length === 0) {
+          // Expired or exhausted; the user can search again
+          paging.offset = paging.total;
+        } else {
+          paging.offset += page.length;
+          rows = page.map(toSearchResult);
+        }
+      }
+      if (pagingRef.current !== paging) {
+        // A newer search replaced the results meanwhile
+        return;
+      }
+      setState((prev) => ({
+        ...prev,
+        moreResults: [...prev.moreResults, ...rows],
+        hasMore: hasMorePages(paging),
+      }));
+    } finally {
+      loadingMoreRef.current = false;
+    }
+  }, []);
+
   /**
    * Select the next result
    */
@@ -513,6 +750,13 @@ export function useSearch(options: UseSearchOptions = {}) {
       if (selected.type === 'app') {
         await invoke('track_app_usage', { appId: selected.id });
       }
+
+      // Searches count as usage, and are remembered for recall, once a
+      // result is acted on, not per keystroke
+      if (state.query.trim()) {
+        invoke('record_usage_event', { kind: 'search', subject: state.query }).catch(console.error);
+        invoke('remember_query', { query: state.query }).catch(console.error);
+      }
     } catch (error) {
       console.error('Failed to execute action:', error);
       setState((prev) => ({
@@ -532,8 +776,11 @@ export function useSearch(options: UseSearchOptions = {}) {
     if (abortControllerRef.current) {
       abortControllerRef.current.abort();
     }
+    pagingRef.current = null;
     setState({
       results: [],
+      moreResults: [],
+      hasMore: false,
       query: '',
       selectedIndex: 0,
       isLoading: false,
@@ -561,6 +808,7 @@ export function useSearch(options: UseSearchOptions = {}) {
     selectPrevious,
     selectIndex,
     executeSelected,
+    loadMore,
     clear,
   };
 }
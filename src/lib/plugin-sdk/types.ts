@@ -32,10 +32,28 @@ export interface PluginManifest {
   author: string;
   permissions: PluginPermission[];
   triggers: string[];
+  /** Argument schemas by trigger keyword, for triggers that take arguments */
+  triggerArgs?: Record<string, PluginTriggerArg[]>;
   settings?: PluginSetting[];
   icon?: string;
   homepage?: string;
   repository?: string;
+  /** SPDX expression such as "MIT", or a short license name */
+  license?: string;
+}
+
+/**
+ * Argument typed after a trigger keyword, e.g. the target language of "translate:"
+ */
+export interface PluginTriggerArg {
+  name: string;
+  type: 'string' | 'number' | 'boolean';
+  required: boolean;
+  description: string;
+  /** Suggested values, offered as completions */
+  enum?: (string | number | boolean)[];
+  /** The plugin's onComplete is asked for further suggestions */
+  dynamic: boolean;
 }
 
 /**
@@ -109,6 +127,8 @@ export interface PluginHooks {
 export interface Plugin extends PluginHooks {
   manifest: PluginManifest;
   onSearch(query: string): Promise<PluginSearchResultV2[]>;
+  /** Suggestions for a `dynamic` trigger argument as it's typed */
+  onComplete?(trigger: string, arg: string, partialArgs: string[]): Promise<string[]>;
   ui?: {
     component: React.ComponentType<any>;
   };
@@ -30,6 +30,17 @@ export interface AbbreviationConfig {
   caseSensitive: boolean;
 }
 
+/** Rejection of an add or edit whose abbr another abbreviation already uses */
+export interface AbbreviationConflict {
+  kind: 'conflict';
+  abbr: string;
+  conflicting_id: string;
+}
+
+export function isAbbreviationConflict(error: unknown): error is AbbreviationConflict {
+  return typeof error === 'object' && error !== null && (error as { kind?: unknown }).kind === 'conflict';
+}
+
 class AbbreviationService {
   private config: AbbreviationConfig | null = null;
   private listeners: Set<(config: AbbreviationConfig) => void> = new Set();
@@ -153,8 +164,9 @@ class AbbreviationService {
       updatedAt: new Date().toISOString(),
     };
 
-    this.config.abbreviations.push(newAbbr);
-    await this.saveConfig(this.config);
+    // Kept out of this.config until the backend accepts it; a duplicate abbr
+    // is rejected with an AbbreviationConflict
+    await this.saveConfig({ ...this.config, abbreviations: [...this.config.abbreviations, newAbbr] });
     
     return newAbbr;
   }
@@ -165,14 +177,25 @@ class AbbreviationService {
     const index = this.config.abbreviations.findIndex(item => item.id === id);
     if (index === -1) throw new Error('Abbreviation not found');
 
-    this.config.abbreviations[index] = {
+    const updated: Abbreviation = {
       ...this.config.abbreviations[index],
       ...updates,
       updatedAt: new Date().toISOString(),
     };
+    const abbreviations = [...this.config.abbreviations];
+    abbreviations[index] = updated;
 
-    await this.saveConfig(this.config);
-    return this.config.abbreviations[index];
+    await this.saveConfig({ ...this.config, abbreviations });
+    return updated;
+  }
+
+  /** Count a use so most-used and recent ordering stay current */
+  async recordUse(id: string): Promise<void> {
+    try {
+      await invoke('record_abbreviation_use', { id });
+    } catch (error) {
+      console.warn('[AbbreviationService] Failed to record use:', error);
+    }
   }
 
   async deleteAbbreviation(id: string): Promise<void> {
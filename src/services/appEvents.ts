@@ -0,0 +1,20 @@
+/**
+ * App Events Service
+ * Typed listeners for events the backend emits. Event names and payload
+ * types come from types/generated/events.d.ts, which is generated from the
+ * backend's event catalog and checked by its tests, so a listener can't drift
+ * from what is actually sent.
+ */
+
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { AppEventName, AppEvents } from '@/types/generated/events';
+
+/**
+ * Listen for a backend event, receiving just its payload
+ */
+export function listenAppEvent<K extends AppEventName>(
+  name: K,
+  handler: (payload: AppEvents[K]) => void
+): Promise<UnlistenFn> {
+  return listen<AppEvents[K]>(name, (event) => handler(event.payload));
+}
@@ -180,6 +180,7 @@ export const marketplaceDataService = {
       search: { categoryName: '搜索增强', categoryIcon: '🔍' },
       media: { categoryName: '媒体处理', categoryIcon: '🎬' },
       integration: { categoryName: '第三方集成', categoryIcon: '🔗' },
+      other: { categoryName: '其他', categoryIcon: '🧩' },
     };
 
     return categoryMetadata[category] || { categoryName: category, categoryIcon: '📦' };
@@ -25,8 +25,11 @@
 import type { Plugin, PluginManifest } from '@/types/plugin';
 import type { PluginPermission } from '@/lib/plugin-sdk/types';
 import type { PluginSearchResultV2, PluginV2 } from '@/lib/plugin-sdk/v2-types';
+import type { Page } from '@/types/search';
 import { invoke } from '@tauri-apps/api/core';
+import type { UnlistenFn } from '@tauri-apps/api/event';
 import { getPluginSandbox } from './pluginSandbox';
+import { listenAppEvent } from './appEvents';
 
 // ============================================================================
 // Constants
@@ -76,6 +79,7 @@ const UNKNOWN_MANIFEST: PluginManifest = {
 interface RawPluginModule {
   manifest?: PluginManifest;
   onSearch?: (query: string) => PluginSearchResultV2[] | Promise<PluginSearchResultV2[]>;
+  onComplete?: (trigger: string, arg: string, partialArgs: string[]) => Promise<string[]>;
   init?(): Promise<void>;
   onDestroy?(): Promise<void>;
 }
@@ -87,6 +91,7 @@ interface ModuleWithDefault {
   default?: RawPluginModule;
   manifest?: PluginManifest;
   onSearch?: RawPluginModule['onSearch'];
+  onComplete?: RawPluginModule['onComplete'];
   init?: RawPluginModule['init'];
   onDestroy?: RawPluginModule['onDestroy'];
 }
@@ -100,6 +105,14 @@ export interface PluginLoadResult {
   error?: string;
 }
 
+/**
+ * Trigger results served from the backend cache
+ */
+interface CachedPluginResult {
+  payload: PluginSearchResultV2[];
+  cached: boolean;
+}
+
 /**
  * Plugin info from backend
  */
@@ -108,6 +121,7 @@ interface BackendPluginInfo {
   install_path?: string;
   source: string;
   enabled: boolean;
+  suppressed_triggers?: string[];
 }
 
 // ============================================================================
@@ -134,6 +148,7 @@ function extractPluginFromModule(module: ModuleWithDefault): RawPluginModule {
   return {
     manifest: module.manifest,
     onSearch: module.onSearch,
+    onComplete: module.onComplete,
     init: module.init,
     onDestroy: module.onDestroy,
   };
@@ -242,7 +257,8 @@ export function createRestrictedAPI(
   return {
     invoke: apiInvoke,
     clipboard: {
-      getHistory: (limit?: number) => apiInvoke('get_clipboard_history', { limit }),
+      getHistory: async (limit?: number) =>
+        ((await apiInvoke('get_clipboard_history', { limit })) as Page<unknown>).items,
       paste: (id: string) => apiInvoke('paste_clipboard_item', { id }),
     },
     file: {
@@ -277,6 +293,8 @@ export function createRestrictedAPI(
 export class PluginLoader {
   private loadedPlugins = new Map<string, Plugin>();
   private pluginManifests = new Map<string, PluginManifest>();
+  /** Triggers lost to another plugin in a conflict, lowercased */
+  private suppressedTriggers = new Map<string, Set<string>>();
 
   /**
    * Load a plugin from a module
@@ -364,7 +382,12 @@ export class PluginLoader {
   private async loadSingleNpmPlugin(
     pluginInfo: BackendPluginInfo
   ): Promise<PluginLoadResult> {
-    const { id: packageName, install_path: installPath = '', enabled } = pluginInfo;
+    const {
+      id: packageName,
+      install_path: installPath = '',
+      enabled,
+      suppressed_triggers: suppressed = [],
+    } = pluginInfo;
 
     if (!packageName) {
       return createErrorResult('unknown', 'Plugin ID is missing');
@@ -375,6 +398,12 @@ export class PluginLoader {
 
     try {
       const result = await this.loadPlugin(installPath);
+      if (result.plugin) {
+        this.suppressedTriggers.set(
+          result.manifest.id,
+          new Set(suppressed.map((trigger) => trigger.trim().toLowerCase()))
+        );
+      }
 
       // Set plugin enabled state based on backend info
       if (result.plugin && !enabled) {
@@ -412,6 +441,7 @@ export class PluginLoader {
     // Remove from storage
     this.loadedPlugins.delete(pluginId);
     this.pluginManifests.delete(pluginId);
+    this.suppressedTriggers.delete(pluginId);
 
     console.log(`${logPrefix('Loader')} Unloaded: ${pluginId}`);
   }
@@ -467,10 +497,13 @@ export class PluginLoader {
     await pluginAbbreviationService.loadConfig();
 
     for (const [pluginId, plugin] of this.loadedPlugins.entries()) {
-      // Check trigger match
-      const matchesTrigger = plugin.manifest.triggers.some((trigger) =>
-        lowerQuery.startsWith(trigger.toLowerCase())
-      );
+      // Check trigger match, leaving out triggers another plugin kept
+      const suppressed = this.suppressedTriggers.get(pluginId);
+      const matchedTrigger = plugin.manifest.triggers.find((trigger) => {
+        const lowerTrigger = trigger.toLowerCase();
+        return !suppressed?.has(lowerTrigger.trim()) && lowerQuery.startsWith(lowerTrigger);
+      });
+      const matchesTrigger = matchedTrigger !== undefined;
 
       // Check abbreviation match
       const abbreviations = pluginAbbreviationService.getAbbreviations(pluginId);
@@ -494,10 +527,20 @@ export class PluginLoader {
         continue;
       }
 
+      // Reuse an earlier run when the trigger declares a cache TTL
+      const cached = matchedTrigger && (await this.getCachedResults(pluginId, matchedTrigger, query));
+      if (cached) {
+        results.push(...cached);
+        continue;
+      }
+
       // Execute search
       const searchResults = await this.executePluginSearch(pluginId, plugin, query);
       if (Array.isArray(searchResults)) {
         results.push(...searchResults);
+        if (matchedTrigger) {
+          void this.cacheResults(pluginId, matchedTrigger, query, searchResults);
+        }
       }
     }
 
@@ -505,6 +548,75 @@ export class PluginLoader {
     return results;
   }
 
+  /**
+   * Answer backend requests for dynamic trigger completions with the
+   * plugin's onComplete. The backend stops waiting after 200ms.
+   */
+  async listenForCompletionRequests(): Promise<UnlistenFn> {
+    return listenAppEvent('plugin:completion_request', async (payload) => {
+      const plugin = this.loadedPlugins.get(payload.plugin_id);
+      let suggestions: string[] = [];
+
+      if (typeof plugin?.onComplete === 'function') {
+        try {
+          suggestions = await plugin.onComplete(payload.trigger, payload.arg, payload.partial_args);
+        } catch (error) {
+          console.error(`${logPrefix('Loader')} Plugin ${payload.plugin_id} completion error:`, error);
+        }
+      }
+
+      await invoke('resolve_trigger_completions', {
+        requestId: payload.request_id,
+        suggestions,
+      });
+    });
+  }
+
+  /**
+   * Results the backend cached for this trigger and input, flagged with
+   * `metadata.cached`. Undefined when the plugin has to run.
+   */
+  private async getCachedResults(
+    pluginId: string,
+    trigger: string,
+    input: string
+  ): Promise<PluginSearchResultV2[] | undefined> {
+    try {
+      const hit = await invoke<CachedPluginResult | null>('get_cached_plugin_result', {
+        pluginId,
+        trigger,
+        input,
+      });
+      if (!hit || !Array.isArray(hit.payload)) {
+        return undefined;
+      }
+      return hit.payload.map((result) => ({
+        ...result,
+        metadata: { ...result.metadata, cached: hit.cached },
+      }));
+    } catch (error) {
+      console.warn(`${logPrefix('Loader')} Plugin ${pluginId} cache lookup failed:`, error);
+      return undefined;
+    }
+  }
+
+  /**
+   * Offer results to the backend cache, which keeps them only for triggers
+   * that declare `cache_ttl_ms` and don't touch the clipboard or files
+   */
+  private async cacheResults(
+    pluginId: string,
+    trigger: string,
+    input: string,
+    results: PluginSearchResultV2[]
+  ): Promise<void> {
+    try {
+      await invoke<boolean>('cache_plugin_result', { pluginId, trigger, input, payload: results });
+    } catch (error) {
+      console.warn(`${logPrefix('Loader')} Plugin ${pluginId} results not cached:`, error);
+    }
+  }
+
   /**
    * Execute plugin search with error handling (v2)
    */
@@ -21,6 +21,7 @@ import type {
   Plugin,
   PluginManifest,
   MarketplacePlugin,
+  MarketplaceFacets,
   PluginFilters,
   MarketplaceQueryOptions,
   BulkOperation,
@@ -28,6 +29,7 @@ import type {
   PluginUsageStats,
   PluginCategory,
   PluginPermission,
+  PluginTriggerArg,
   PluginUpdateInfo,
 } from '../types/plugin';
 
@@ -53,12 +55,19 @@ interface RawPluginFromBackend {
   health: RawPluginHealth;
   usage_stats: RawPluginUsageStats;
   installed_at: number;
+  protected?: boolean;
+  /** Triggers that lost their keyword to another plugin */
+  suppressed_triggers?: string[];
+  homepage?: string | null;
+  repository?: string | null;
+  license?: string | null;
 }
 
 interface RawPluginTrigger {
   keyword: string;
   description: string;
   hotkey: string | null;
+  args: PluginTriggerArg[];
 }
 
 interface RawPluginHealth {
@@ -111,8 +120,13 @@ function convertRawPluginToFrontend(raw: RawPluginFromBackend): Plugin {
     averageExecutionTime: raw.usage_stats.average_execution_time ?? undefined,
   };
 
-  // Convert triggers
-  const triggers = raw.triggers.map(t => t.keyword);
+  // Convert triggers, leaving out the ones another plugin owns
+  const suppressed = new Set(raw.suppressed_triggers ?? []);
+  const active = raw.triggers.filter(t => !suppressed.has(t.keyword));
+  const triggers = active.map(t => t.keyword);
+  const triggerArgs = Object.fromEntries(
+    active.filter(t => t.args.length > 0).map(t => [t.keyword, t.args])
+  );
 
   // Convert permissions
   const permissions = raw.permissions as PluginPermission[];
@@ -126,6 +140,10 @@ function convertRawPluginToFrontend(raw: RawPluginFromBackend): Plugin {
     author: raw.author ?? '',
     permissions,
     triggers,
+    triggerArgs,
+    homepage: raw.homepage ?? undefined,
+    repository: raw.repository ?? undefined,
+    license: raw.license ?? undefined,
   };
 
   return {
@@ -134,6 +152,7 @@ function convertRawPluginToFrontend(raw: RawPluginFromBackend): Plugin {
     health,
     usageStats,
     installedAt: raw.installed_at,
+    protected: raw.protected ?? false,
   };
 }
 
@@ -142,6 +161,12 @@ function convertRawPluginToFrontend(raw: RawPluginFromBackend): Plugin {
  * Provides methods to interact with backend plugin management
  */
 export class PluginManagerService {
+  /**
+   * Registry revision of the latest enable/disable, so a listing right
+   * after one waits for it instead of showing the old state
+   */
+  private stateRevision: number | null = null;
+
   /**
    * Get all installed plugins from marketplace
    * 直接调用后端，后端读取 package.json（< 1ms）
@@ -149,7 +174,9 @@ export class PluginManagerService {
   async getInstalledPlugins(): Promise<Plugin[]> {
     try {
       // 直接调用后端，后端会读取 plugins/package.json
-      const rawPlugins = await invoke<RawPluginFromBackend[]>('get_installed_plugins');
+      const rawPlugins = await invoke<RawPluginFromBackend[]>('get_installed_plugins', {
+        minRevision: this.stateRevision,
+      });
 
       console.log('[PluginManager] Retrieved', rawPlugins.length, 'plugins from package.json');
 
@@ -163,12 +190,17 @@ export class PluginManagerService {
     }
   }
 
+  private noteStateRevision(revision: number): void {
+    this.stateRevision = Math.max(this.stateRevision ?? 0, revision);
+  }
+
   /**
    * Enable a plugin
    */
   async enablePlugin(pluginId: string): Promise<void> {
     try {
-      await invoke('plugin_enable', { pluginId });
+      const change = await invoke<{ state_revision: number }>('plugin_enable', { pluginId });
+      this.noteStateRevision(change.state_revision);
 
       // Update sandbox state
       const { getPluginSandbox } = await import('./pluginSandbox');
@@ -186,7 +218,8 @@ export class PluginManagerService {
    */
   async disablePlugin(pluginId: string): Promise<void> {
     try {
-      await invoke('plugin_disable', { pluginId });
+      const change = await invoke<{ state_revision: number }>('plugin_disable', { pluginId });
+      this.noteStateRevision(change.state_revision);
 
       // Update sandbox state
       const { getPluginSandbox } = await import('./pluginSandbox');
@@ -234,11 +267,11 @@ export class PluginManagerService {
   }
 
   /**
-   * Uninstall a plugin
+   * Uninstall a plugin, archiving its settings first when preserveSettings is set
    */
-  async uninstallPlugin(pluginId: string): Promise<void> {
+  async uninstallPlugin(pluginId: string, preserveSettings = false): Promise<void> {
     try {
-      await invoke('plugin_uninstall', { pluginId });
+      await invoke('plugin_uninstall', { pluginId, preserveSettings });
     } catch (error) {
       console.error('Failed to uninstall plugin:', error);
       throw new Error(
@@ -247,6 +280,20 @@ export class PluginManagerService {
     }
   }
 
+  /**
+   * Restore settings archived when a plugin was uninstalled
+   */
+  async restoreArchivedSettings(pluginId: string): Promise<void> {
+    try {
+      await invoke('restore_archived_settings', { pluginId });
+    } catch (error) {
+      console.error('Failed to restore archived settings:', error);
+      throw new Error(
+        `Failed to restore settings: ${error instanceof Error ? error.message : 'Unknown error'}`
+      );
+    }
+  }
+
   /**
    * Bulk uninstall plugins
    */
@@ -436,6 +483,20 @@ export class MarketplaceService {
     }
   }
 
+  /**
+   * Category and tag counts for a marketplace query (empty for all plugins)
+   */
+  async getMarketplaceFacets(query = ''): Promise<MarketplaceFacets> {
+    try {
+      return await invoke<MarketplaceFacets>('marketplace_get_facets', { query });
+    } catch (error) {
+      console.error('Failed to get marketplace facets:', error);
+      throw new Error(
+        `Failed to load marketplace facets: ${error instanceof Error ? error.message : 'Unknown error'}`
+      );
+    }
+  }
+
   /**
    * Install plugin from marketplace
    */
@@ -1,7 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
-import { listen } from '@tauri-apps/api/event';
 import type { ScreenInfo, ScreenChangedPayload } from '@/types';
 import { errorHandler, ErrorCategory, withErrorHandling } from './errorHandler';
+import { listenAppEvent } from './appEvents';
 
 class ScreenServiceImpl {
   private cache: ScreenInfo | null = null;
@@ -61,10 +61,10 @@ class ScreenServiceImpl {
   ): Promise<() => void> {
     return withErrorHandling(
       async () => {
-        const unlisten = await listen<ScreenChangedPayload>('screen:changed', (event) => {
+        const unlisten = await listenAppEvent('screen:changed', (payload) => {
           // Invalidate cache when screen changes
           this.invalidateCache();
-          callback(event.payload);
+          callback(payload);
         });
         return unlisten;
       },
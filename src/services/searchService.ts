@@ -65,6 +65,11 @@ export class SearchService {
       action: 0.55,
       url: 0.5,
       color: 0.95,
+      datetime: 0.95,
+      emoji: 0.95,
+      process: 0.9,
+      ssh: 0.9,
+      cli: 0.85,
     };
     const typeBoost = TYPE_PRIORITY[type];
 
@@ -260,8 +265,8 @@ export class SearchService {
       type: 'file',
       score: 0,
       action: async () => {
-        // Open file with default application
-        await invoke('open_url', { url: `file://${path}` });
+        // Open file with its configured opener or the default application
+        await invoke('open_result', { request: { kind: 'file', path } });
       },
     };
   }
@@ -285,7 +290,7 @@ export class SearchService {
       type: entryType === 'bookmark' ? 'bookmark' : 'history',
       score: 0,
       action: async () => {
-        await invoke('open_url', { url });
+        await invoke('open_result', { request: { kind: 'browser_result', url } });
       },
     };
   }
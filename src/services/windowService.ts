@@ -1,7 +1,7 @@
 import { invoke } from '@tauri-apps/api/core';
-import { listen } from '@tauri-apps/api/event';
-import type { ViewType, CalculatedWindowLayout, ResizeStartPayload, ResizeCompletePayload } from '@/types';
+import type { ViewType, CalculatedWindowLayout, ScreenInfo } from '@/types';
 import { errorHandler, ErrorCategory, withErrorHandling } from './errorHandler';
+import { listenAppEvent } from './appEvents';
 
 class WindowServiceImpl {
   async resizeToView(viewId: ViewType): Promise<CalculatedWindowLayout> {
@@ -63,13 +63,11 @@ class WindowServiceImpl {
   }
 
   async onResizeStart(
-    callback: (payload: ResizeStartPayload) => void
+    callback: (screenInfo: ScreenInfo) => void
   ): Promise<() => void> {
     return withErrorHandling(
       async () => {
-        const unlisten = await listen<ResizeStartPayload>('window:resize_start', (event) => {
-          callback(event.payload);
-        });
+        const unlisten = await listenAppEvent('window:resize_start', callback);
         return unlisten;
       },
       ErrorCategory.WINDOW_RESIZE,
@@ -78,13 +76,11 @@ class WindowServiceImpl {
   }
 
   async onResizeComplete(
-    callback: (payload: ResizeCompletePayload) => void
+    callback: (layout: CalculatedWindowLayout) => void
   ): Promise<() => void> {
     return withErrorHandling(
       async () => {
-        const unlisten = await listen<ResizeCompletePayload>('window:resize_complete', (event) => {
-          callback(event.payload);
-        });
+        const unlisten = await listenAppEvent('window:resize_complete', callback);
         return unlisten;
       },
       ErrorCategory.WINDOW_RESIZE,
@@ -15,8 +15,10 @@ export interface ClipboardItem {
   id: string;                    // Unique identifier (UUID)
   content_type: ClipboardContentType;  // Type of clipboard content
   text?: string;                 // Text content (for Text/Html types)
-  image_path?: string;           // Path to stored image file
+  image_path?: string;           // Path to stored image file (only from get_clipboard_item)
+  thumbnail_path?: string;       // Path to the image's small JPEG preview
   hash: string;                  // Content hash for deduplication (SHA-256)
+  perceptual_hash?: string;      // Image hash that matches re-encoded copies
   timestamp: number;             // When the content was copied (Unix timestamp)
   is_sensitive: boolean;         // Whether content is sensitive (passwords, API keys)
   app_source?: string;           // Application that copied the content
@@ -30,4 +32,6 @@ export interface ClipboardSettings {
   retention_days: number;               // Days to keep non-sensitive items (default: 30)
   sensitive_retention_minutes: number;  // Minutes to keep sensitive items (default: 2)
   enable_image_support: boolean;        // Whether to support image clipboard items
+  max_image_bytes: number;              // Larger images are stored downscaled (default: 10 MB)
+  thumbnail_size: number;               // Longest thumbnail side in pixels (default: 256)
 }
@@ -0,0 +1,461 @@
+// Generated by src-tauri/build.rs from the event catalog in src-tauri/src/types/events.rs.
+// Don't edit; change the catalog or the payload types and rebuild the backend.
+
+/** Payload of each event the backend emits, by event name */
+export interface AppEvents {
+  /** Files indexed so far, and folders the indexer skipped */
+  'index:progress': IndexProgress;
+  /** One provider's results for a streaming search */
+  'search:partial': SearchPartial;
+  /** A streaming search finished */
+  'search:complete': SearchComplete;
+  /** Text to put in the search box, e.g. the selection it was opened with */
+  'search:prefill': SearchPrefill;
+  /** The installed applications were rescanned */
+  'apps:changed': AppsChanged;
+  /** The main window was shown and focused */
+  'window-shown': WindowShown;
+  /** The main window was shown and should start with an empty search */
+  'search:reset': SearchReset;
+  /** Files were dropped on the main window; these actions can take them */
+  'drop:actions-available': DropActionsAvailable;
+  /** The main window starts resizing for the screen it's on */
+  'window:resize_start': ScreenInfo;
+  /** The main window finished resizing */
+  'window:resize_complete': CalculatedWindowLayout;
+  /** A saved window layout was applied */
+  'window:layout_applied': WindowLayout;
+  /** The screen's resolution, scale or display changed */
+  'screen:changed': ScreenChanged;
+  /** The app went online or offline */
+  'connectivity:changed': ConnectivityStatus;
+  /** Privacy mode was turned on or off */
+  'privacy-mode:changed': PrivacyStatus;
+  /** A database maintenance run moved to its next stage */
+  'db-maintenance:progress': MaintenanceProgress;
+  /** The integrity check after an unclean shutdown found problems */
+  'db-maintenance:issues': MaintenanceReport;
+  /** App data was reset */
+  'app-data:reset': ResetReport;
+  /** Moving the data dir to a new folder reached its next step */
+  'data-location:progress': MigrationProgress;
+  /** A plugin was disabled for misbehaving or being incompatible */
+  'plugin:quarantined': PluginQuarantined;
+  /**
+   * A plugin was installed that has settings archived from an earlier
+   * uninstall, which `restore_archived_settings` can bring back
+   */
+  'plugin:archive-found': PluginArchiveFound;
+  /** A plugin was reloaded; its cached results were dropped */
+  'plugin:reloaded': PluginReloaded;
+  /** A plugin trigger needs completions from the plugin's frontend code */
+  'plugin:completion_request': CompletionRequest;
+  /** Bytes received for a plugin package download */
+  'plugin-download:progress': DownloadProgress;
+}
+
+export type AppEventName = keyof AppEvents;
+
+/** `index:progress` payload */
+export interface IndexProgress {
+  current: number;
+  total: number;
+  path: string;
+  stage: string;
+  /** Why `path` was skipped, for the "skipped" stage */
+  reason?: string;
+}
+
+/** `search:partial` payload, one per provider */
+export interface SearchPartial {
+  search_id: number;
+  source: string;
+  /** Results the provider found, of which `results` are the first */
+  total: number;
+  results: SearchResultItem[];
+}
+
+/** `search:complete` payload */
+export interface SearchComplete {
+  search_id: number;
+  total: number;
+  /** First page of the merged results; `get_results_page` serves the rest */
+  results: SearchResultItem[];
+  query_time: number;
+  groups: Record<string, number>;
+  has_more_per_group: Record<string, boolean>;
+  duplicates_folded: number;
+  /**
+   * The query's exclusions and kind:/ext: filters, for results the
+   * frontend adds itself
+   */
+  filters: QueryFilters;
+  /** Where the time went, if the search asked with `include_timings` */
+  timings?: SearchTimings;
+}
+
+/** `search:prefill` payload, the text itself */
+export type SearchPrefill = string;
+
+/** `apps:changed` payload */
+export interface AppsChanged {
+  total: number;
+}
+
+/** `window-shown` payload, which is empty */
+export type WindowShown = null;
+
+/** `search:reset` payload, which is empty */
+export type SearchReset = null;
+
+/** `drop:actions-available` payload */
+export interface DropActionsAvailable {
+  items: DroppedItem[];
+  /** Built-in actions first, then plugins by name */
+  actions: DropAction[];
+}
+
+/** Screen information detected from the OS */
+export interface ScreenInfo {
+  screenWidth: number;
+  screenHeight: number;
+  availableWidth: number;
+  availableHeight: number;
+  scaleFactor: number;
+}
+
+/** Result of window size calculation with final dimensions and position */
+export interface CalculatedWindowLayout {
+  width: number;
+  height: number;
+  x: number;
+  y: number;
+  animationRequired: boolean;
+}
+
+/** A named arrangement of the launcher, e.g. "compact" for quick searches */
+export interface WindowLayout {
+  name: string;
+  width: number;
+  height: number;
+  position: LayoutPosition;
+  /** Height of the results panel, applied by the frontend */
+  results_height: number;
+  always_on_top: boolean;
+}
+
+/** `screen:changed` payload */
+export interface ScreenChanged {
+  oldScreenInfo: ScreenInfo | null;
+  newScreenInfo: ScreenInfo;
+  changeType: ScreenChangeType;
+}
+
+/** Payload of `get_connectivity_status` and the `connectivity:changed` event */
+export interface ConnectivityStatus {
+  state: ConnectivityState;
+  /** Offline because the user turned on offline mode */
+  forced_offline: boolean;
+  /** When the state last changed (Unix ms), 0 if it never has */
+  changed_at: number;
+}
+
+/** Privacy mode status, also the `privacy-mode:changed` event payload */
+export interface PrivacyStatus {
+  enabled: boolean;
+  /** Unix timestamp (ms) when the mode turns itself off */
+  expires_at: number | null;
+  /** Whether the mode survives restarts */
+  persistent: boolean;
+}
+
+/** `db-maintenance:progress` payload */
+export interface MaintenanceProgress {
+  target: MaintenanceTarget;
+  stage: string;
+  progress: number;
+}
+
+/** Result of a maintenance run */
+export interface MaintenanceReport {
+  target: MaintenanceTarget;
+  mode: MaintenanceMode;
+  /** Integrity check problems; empty when the database is healthy */
+  issues: string[];
+  size_before: number;
+  size_after: number;
+  duration_ms: number;
+}
+
+/** What a reset removed, also the `app-data:reset` event payload */
+export interface ResetReport {
+  components: ResetComponent[];
+  /** Paths that existed and were deleted */
+  removed: string[];
+}
+
+/** Migration progress, the `data-location:progress` event payload */
+export interface MigrationProgress {
+  stage: MigrationStage;
+  /** Files done in this stage, of `total` */
+  current: number;
+  total: number;
+  /** File being worked on, relative to the data dir */
+  path: string | null;
+}
+
+/** `plugin:quarantined` payload */
+export interface PluginQuarantined {
+  plugin_id: string;
+  reason: string;
+}
+
+/** `plugin:archive-found` payload */
+export interface PluginArchiveFound {
+  plugin_id: string;
+  /** Version whose settings were archived */
+  version: string;
+  /** Unix timestamp (ms) */
+  archived_at: number;
+}
+
+/** `plugin:reloaded` payload */
+export interface PluginReloaded {
+  plugin_id: string;
+  /** Cached results dropped for the plugin */
+  invalidated_results: number;
+}
+
+/**
+ * `plugin:completion_request` event payload. The frontend answers with
+ * `resolve_trigger_completions`.
+ */
+export interface CompletionRequest {
+  request_id: number;
+  plugin_id: string;
+  trigger: string;
+  arg: string;
+  partial_args: string[];
+}
+
+/** Download progress, the `plugin-download:progress` event payload */
+export interface DownloadProgress {
+  url: string;
+  received: number;
+  total: number | null;
+}
+
+/** Search result item */
+export interface SearchResultItem {
+  id: string;
+  title: string;
+  subtitle: string;
+  icon: string | null;
+  /** What `icon` holds, so the frontend knows how to draw it */
+  icon_kind?: IconKind;
+  /** Theme-neutral "#rrggbb" the frontend may tint the row with */
+  accent_color?: string;
+  type: string;
+  score: number;
+  path: string;
+  frequency: number;
+  /** How the score was reached, only for searches that ask to `explain` */
+  explain?: ScoreBreakdown;
+  /** Where a browser result matched, to tell apart pages sharing a title */
+  match_context?: MatchContext;
+}
+
+/** Filters of one query */
+export interface QueryFilters {
+  /** `kind:` and `ext:` filters a result must all match */
+  required: Filter[];
+  /** A result matching any of these is dropped */
+  excluded: Filter[];
+}
+
+/** Where one search's time went */
+export interface SearchTimings {
+  total_ms: number;
+  /** The merged results came straight from the search cache */
+  cache_hit: boolean;
+  stages: SearchStage[];
+  /**
+   * In the order they finished. They run in parallel for a streaming
+   * search, so their times can add up to more than the total.
+   */
+  providers: ProviderTiming[];
+}
+
+/** A dropped path, classified */
+export interface DroppedItem {
+  path: string;
+  name: string;
+  kind: DroppedKind;
+  /** Lowercase, without the dot */
+  extension: string | null;
+}
+
+/** An action offered for the dropped paths */
+export type DropAction = DropActionKind & {
+  /** `copy_path`, `move_to_folder` or `plugin:<id>` */
+  id: string;
+  title: string;
+};
+
+/** Where a saved layout puts the main window */
+export type LayoutPosition =
+  /** Centered on the monitor under the cursor each time it's shown */
+  | { mode: 'follow_cursor' }
+  /**
+   * A fixed spot, as fractions of the monitor's size so it survives
+   * resolution changes. `monitor` is the monitor's name, when it has one.
+   */
+  | { mode: 'fixed'; monitor: string | null; rel_x: number; rel_y: number };
+
+/** What changed about the screen */
+export type ScreenChangeType =
+  | 'resolution'
+  | 'display_connect'
+  | 'scale_factor';
+
+export type ConnectivityState =
+  | 'online'
+  | 'offline';
+
+/** Database a maintenance run applies to */
+export type MaintenanceTarget =
+  | 'files'
+  | 'browser';
+
+/** What a maintenance run does */
+export type MaintenanceMode =
+  | 'integrity_check'
+  | 'vacuum'
+  | 'rebuild';
+
+/** Parts of the app state that can be reset on their own */
+export type ResetComponent =
+  | 'search_indexes'
+  | 'clipboard_history'
+  | 'plugins'
+  | 'settings'
+  | 'caches';
+
+/** Migration step, for progress events */
+export type MigrationStage =
+  | 'copying'
+  | 'verifying'
+  | 'switching'
+  | 'removing_old'
+  | 'rolling_back'
+  | 'done';
+
+/** How a result's icon is drawn */
+export type IconKind =
+  /** An emoji or symbol glyph */
+  | 'emoji'
+  /** A file path or data URL to an image */
+  | 'path'
+  /** The name of an icon in the frontend's own set, see `BuiltinIcon` */
+  | 'builtin';
+
+/** Components of a result's score, for tuning scoring weights */
+export interface ScoreBreakdown {
+  exact: number;
+  starts_with: number;
+  contains: number;
+  initialism: number;
+  frequency: number;
+  /**
+   * Parts the weights don't cover: alternate names, the bookmark boost,
+   * URL-only matches, fixed-score results
+   */
+  other: number;
+  /** Provider score, the sum of the components above */
+  raw: number;
+  /** Raw score divided by the best raw score in the provider's batch */
+  normalized: number;
+  /** Provider weight the normalized score was multiplied by */
+  provider_weight: number;
+}
+
+/** What a browser result shows about its match */
+export interface MatchContext {
+  /** Shown prominently; empty for URLs without a host */
+  host: string;
+  /** Decoded path, or the whole URL when it has no host */
+  path: string;
+  /**
+   * First of title, host, path and description the query matched; the
+   * title when it matched none of them, e.g. only the query string
+   */
+  field: MatchField;
+  /** Text of `field` */
+  text: string;
+  /** Matches in `text`, in order */
+  ranges: TextRange[];
+}
+
+/**
+ * One condition on a result. Serialized for the frontend, which applies
+ * the same conditions to the plugin results it adds.
+ */
+export type Filter =
+  /** The result's name or path contains this, lowercase */
+  | { type: 'text'; value: string }
+  /** The result's type, e.g. "file" */
+  | { type: 'kind'; value: string }
+  /** The extension of the result's path, lowercase without the dot */
+  | { type: 'ext'; value: string };
+
+/**
+ * One stage of a search. Stages don't overlap, so they add up to about
+ * the total.
+ */
+export interface SearchStage {
+  name: string;
+  ms: number;
+}
+
+/** One provider's run within a search */
+export interface ProviderTiming {
+  source: string;
+  ms: number;
+  /**
+   * Results the provider returned, before the query's filters. Counts
+   * every fetch of a filtered search, not the rows it scanned to find them.
+   */
+  returned: number;
+  /** The provider refined a cached search instead of searching again */
+  refined: boolean;
+}
+
+/** What a dropped path is */
+export type DroppedKind =
+  | 'file'
+  | 'directory'
+  /** An application bundle, executable or shortcut */
+  | 'app'
+  | 'image';
+
+/** What running a drop action does */
+export type DropActionKind =
+  /** Copy the paths to the clipboard, one per line */
+  | { type: 'copy_path' }
+  /** Move the paths into a folder the user picks */
+  | { type: 'move_to_folder' }
+  /** Pass the paths to a plugin trigger */
+  | { type: 'plugin'; plugin_id: string; trigger: string };
+
+/** Part of a page a query matched */
+export type MatchField =
+  | 'title'
+  | 'host'
+  | 'path'
+  | 'description';
+
+/** A matched span, start inclusive, end exclusive */
+export interface TextRange {
+  start: number;
+  end: number;
+}
@@ -11,6 +11,7 @@ export type {
   ViewConfig,
   CalculatedWindowLayout,
 } from './events';
+export type { AppEvents, AppEventName } from './generated/events';
 
 // Plugin types (export all from plugin.ts)
 export type {
@@ -25,6 +26,10 @@ export type {
 export type {
   SearchResult,
   SearchResultType,
+  FilePreview,
+  FilePreviewError,
+  Page,
+  QueryHistoryEntry,
 } from './search';
 
 // Clipboard types
@@ -14,6 +14,7 @@
 export type {
   PluginManifest,
   PluginPermission,
+  PluginTriggerArg,
   PluginContext,
   Plugin as SDKPlugin,
   PluginSDK
@@ -168,6 +169,7 @@ export interface Plugin extends Omit<SDKPlugin, 'manifest'> {
   health?: PluginHealth;
   usageStats?: PluginUsageStats;
   installedAt?: number; // timestamp (ms)
+  protected?: boolean; // Ships with the app: can be disabled, not uninstalled
   grantedPermissions?: Set<PluginPermission>;
   configValues?: Record<string, string | number | boolean>;
 
@@ -192,7 +194,20 @@ export type PluginCategory =
   | 'utilities'
   | 'search'
   | 'media'
-  | 'integration';
+  | 'integration'
+  | 'other';
+
+/**
+ * Category and tag counts for a marketplace query
+ */
+export interface MarketplaceFacets {
+  total: number;
+  categories: { value: string; count: number }[];
+  tags: { value: string; count: number }[];
+  stale?: boolean;
+  /** Counted over only some of the matches */
+  partial?: boolean;
+}
 
 /**
  * Marketplace plugin (静态 JSON 数据源)
@@ -294,6 +309,7 @@ export interface PluginUpdateInfo {
   currentVersion: string;    // Currently installed version
   latestVersion: string;     // Latest version from npm
   hasUpdate: boolean;        // Whether an update is available
+  stale?: boolean;           // Remembered from an earlier check while offline
 }
 
 // ============================================================================
@@ -430,6 +446,7 @@ export interface MarketplacePluginPage {
   page: number;
   pageSize: number;
   hasMore: boolean;
+  stale?: boolean;  // Served from cache while offline
 }
 
 /**
@@ -11,7 +11,28 @@ export type SearchResultType =
   | 'plugin'           // Plugin result
   | 'action'           // Quick action (calculator, etc.)
   | 'url'              // Direct URL
-  | 'color';           // Color conversion (T037-T042)
+  | 'color'            // Color conversion (T037-T042)
+  | 'datetime'         // World clock or date calculation
+  | 'emoji'            // Emoji or symbol, copied when picked
+  | 'process'          // Running process, quit when picked
+  | 'ssh'              // SSH host, opens a terminal session when picked
+  | 'cli'              // Command-line tool on PATH, run in a terminal when picked
+  | 'shell-history';   // Command from shell history, run in a terminal once confirmed
+
+/** How a result's icon is drawn: a glyph, an image path or data URL, or a builtin icon name */
+export type IconKind = 'emoji' | 'path' | 'builtin';
+
+/** One of a query's filters, as the backend parsed it */
+export type QueryFilter =
+  | { type: 'text'; value: string }   // Name or path contains this, lowercase
+  | { type: 'kind'; value: string }   // Result type, e.g. "file"
+  | { type: 'ext'; value: string };   // Path extension, lowercase without the dot
+
+/** Exclusions and kind:/ext: filters typed into a query */
+export interface QueryFilters {
+  required: QueryFilter[];   // A result must match all of these
+  excluded: QueryFilter[];   // A result matching any of these is dropped
+}
 
 export interface ColorData {
   hex: string;
@@ -24,11 +45,14 @@ export interface SearchResult {
   title: string;
   subtitle?: string;
   icon?: string;
+  iconKind?: IconKind;
+  accentColor?: string;   // Theme-neutral "#rrggbb" the row may be tinted with
   type: SearchResultType;
   score: number;          // Relevance score (0-1)
   source?: string;        // Source plugin/app ID
   path?: string;          // Path to app bundle or file (for icon loading)
   action: () => void | Promise<void>;
+  secondaryAction?: () => void | Promise<void>;  // Cmd/Ctrl+Enter
   metadata?: Record<string, unknown>; // Additional metadata for specific result types
   colorData?: ColorData;  // Color conversion data (T040)
 }
@@ -62,4 +86,42 @@ export const TYPE_PRIORITY: Record<SearchResultType, number> = {
   action: 0.55,
   url: 0.5,
   color: 0.95,  // Color conversions have high priority (T037-T042)
+  datetime: 0.95,
+  emoji: 0.95,
+  process: 0.9,
+  ssh: 0.9,
+  cli: 0.85,
 };
+
+// File preview metadata (get_file_preview)
+export interface FilePreview {
+  path: string;           // Path after following symlinks
+  kind: 'file' | 'directory' | 'other';
+  size: number;
+  modified: number;       // Unix seconds
+  mime: string | null;
+  dimensions: { width: number; height: number } | null;
+  text: string | null;    // Start of the file, for text files
+  lossy: boolean;         // Invalid UTF-8 was replaced
+  truncated: boolean;     // text is only the start of the file
+  unavailable: 'binary' | 'too_large' | 'not_downloaded' | null;  // not_downloaded: cloud placeholder
+}
+
+export type FilePreviewError =
+  | { kind: 'not_found'; path: string }
+  | { kind: 'permission_denied'; path: string }
+  | { kind: 'symlink_loop'; path: string }
+  | { kind: 'failed'; message: string };
+
+// One page of a keyset-paged list (clipboard history, browser search)
+export interface Page<T> {
+  items: T[];
+  next_cursor: string | null;  // Pass back as `cursor` for the next page
+  total: number;               // Items in the whole list when the page was read
+}
+
+// A remembered search query, from get_query_history (newest first)
+export interface QueryHistoryEntry {
+  query: string;
+  last_searched: number;  // Unix timestamp in seconds
+}
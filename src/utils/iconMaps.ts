@@ -2,6 +2,8 @@
  * Centralized icon mappings for the application
  */
 
+import { invoke } from '@tauri-apps/api/core';
+
 /**
  * File extension to icon mapping
  */
@@ -139,6 +141,20 @@ const RESULT_TYPE_ICON_MAP: Record<string, string> = {
   'color': '🎨',
 };
 
+/**
+ * Builtin icon name to icon mapping. Names come from the backend's
+ * get_builtin_icons, so every name it returns needs an entry here.
+ */
+const BUILTIN_ICON_MAP: Record<string, string> = {
+  'calculator': '🧮',
+  'clock': '🕐',
+  'conversion': '🔄',
+  'process': '⚙️',
+  'server': '🗄️',
+  'system': '⚙️',
+  'terminal': '🖥️',
+};
+
 /**
  * Get icon for a file extension
  * @param extension - File extension (with or without leading dot)
@@ -169,5 +185,25 @@ export function getResultTypeIcon(type: string): string {
   return RESULT_TYPE_ICON_MAP[type] || '•';
 }
 
+/**
+ * Get icon for a builtin icon name sent with a search result
+ * @param name - Builtin icon name
+ * @returns Emoji icon, or a bullet for unknown names
+ */
+export function getBuiltinIcon(name: string): string {
+  return BUILTIN_ICON_MAP[name] || '•';
+}
+
+/**
+ * Warn about builtin icons the backend can send but this map lacks
+ */
+export async function checkBuiltinIcons(): Promise<void> {
+  const names = await invoke<string[]>('get_builtin_icons');
+  const missing = names.filter((name) => !(name in BUILTIN_ICON_MAP));
+  if (missing.length > 0) {
+    console.warn('[iconMaps] No icon for builtin icons:', missing);
+  }
+}
+
 // Export maps for direct access if needed
-export { FILE_ICON_MAP, BROWSER_ICON_MAP, RESULT_TYPE_ICON_MAP };
+export { FILE_ICON_MAP, BROWSER_ICON_MAP, RESULT_TYPE_ICON_MAP, BUILTIN_ICON_MAP };